@@ -1,7 +1,11 @@
 use clap::Parser;
-use fyrox::event_loop::EventLoop;
+use fyrox::{core::memory::TrackingAllocator, event_loop::EventLoop};
 use fyroxed_base::{Editor, StartupData};
 
+// Track memory usage of every subsystem, it is shown in the statistics window.
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator::system();
+
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
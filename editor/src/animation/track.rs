@@ -15,6 +15,7 @@ use crate::{
             algebra::{UnitQuaternion, Vector2, Vector3, Vector4},
             color::Color,
             log::Log,
+            math::curve::CurveKeyKind,
             parking_lot::Mutex,
             pool::{ErasedHandle, Handle},
             reflect::{prelude::*, Reflect},
@@ -27,7 +28,7 @@ use crate::{
         generic_animation::{
             container::{TrackDataContainer, TrackValueKind},
             track::Track,
-            value::{ValueBinding, ValueType},
+            value::{TrackValue, ValueBinding, ValueType},
             Animation,
         },
         graph::{BaseSceneGraph, SceneGraph, SceneGraphNode},
@@ -142,104 +143,6 @@ impl TrackContextMenu {
     }
 }
 
-fn type_id_to_supported_type(property_type: TypeId) -> Option<(TrackValueKind, ValueType)> {
-    if property_type == TypeId::of::<f32>() {
-        Some((TrackValueKind::Real, ValueType::F32))
-    } else if property_type == TypeId::of::<f64>() {
-        Some((TrackValueKind::Real, ValueType::F64))
-    } else if property_type == TypeId::of::<u64>() {
-        Some((TrackValueKind::Real, ValueType::U64))
-    } else if property_type == TypeId::of::<i64>() {
-        Some((TrackValueKind::Real, ValueType::I64))
-    } else if property_type == TypeId::of::<u32>() {
-        Some((TrackValueKind::Real, ValueType::U32))
-    } else if property_type == TypeId::of::<i32>() {
-        Some((TrackValueKind::Real, ValueType::I32))
-    } else if property_type == TypeId::of::<u16>() {
-        Some((TrackValueKind::Real, ValueType::U16))
-    } else if property_type == TypeId::of::<i16>() {
-        Some((TrackValueKind::Real, ValueType::I16))
-    } else if property_type == TypeId::of::<u8>() {
-        Some((TrackValueKind::Real, ValueType::U8))
-    } else if property_type == TypeId::of::<i8>() {
-        Some((TrackValueKind::Real, ValueType::I8))
-    } else if property_type == TypeId::of::<bool>() {
-        Some((TrackValueKind::Real, ValueType::Bool))
-    } else if property_type == TypeId::of::<Vector2<f32>>() {
-        Some((TrackValueKind::Vector2, ValueType::Vector2F32))
-    } else if property_type == TypeId::of::<Vector2<f64>>() {
-        Some((TrackValueKind::Vector2, ValueType::Vector2F64))
-    } else if property_type == TypeId::of::<Vector2<u64>>() {
-        Some((TrackValueKind::Vector2, ValueType::Vector2U64))
-    } else if property_type == TypeId::of::<Vector2<i64>>() {
-        Some((TrackValueKind::Vector2, ValueType::Vector2I64))
-    } else if property_type == TypeId::of::<Vector2<u32>>() {
-        Some((TrackValueKind::Vector2, ValueType::Vector2U32))
-    } else if property_type == TypeId::of::<Vector2<i32>>() {
-        Some((TrackValueKind::Vector2, ValueType::Vector2I32))
-    } else if property_type == TypeId::of::<Vector2<u16>>() {
-        Some((TrackValueKind::Vector2, ValueType::Vector2U16))
-    } else if property_type == TypeId::of::<Vector2<i16>>() {
-        Some((TrackValueKind::Vector2, ValueType::Vector2I16))
-    } else if property_type == TypeId::of::<Vector2<u8>>() {
-        Some((TrackValueKind::Vector2, ValueType::Vector2U8))
-    } else if property_type == TypeId::of::<Vector2<i8>>() {
-        Some((TrackValueKind::Vector2, ValueType::Vector2I8))
-    } else if property_type == TypeId::of::<Vector2<bool>>() {
-        Some((TrackValueKind::Vector2, ValueType::Vector2Bool))
-    } else if property_type == TypeId::of::<Vector3<f32>>() {
-        Some((TrackValueKind::Vector3, ValueType::Vector3F32))
-    } else if property_type == TypeId::of::<Vector3<f64>>() {
-        Some((TrackValueKind::Vector3, ValueType::Vector3F64))
-    } else if property_type == TypeId::of::<Vector3<u64>>() {
-        Some((TrackValueKind::Vector3, ValueType::Vector3U64))
-    } else if property_type == TypeId::of::<Vector3<i64>>() {
-        Some((TrackValueKind::Vector3, ValueType::Vector3I64))
-    } else if property_type == TypeId::of::<Vector3<u32>>() {
-        Some((TrackValueKind::Vector3, ValueType::Vector3U32))
-    } else if property_type == TypeId::of::<Vector3<i32>>() {
-        Some((TrackValueKind::Vector3, ValueType::Vector3I32))
-    } else if property_type == TypeId::of::<Vector3<u16>>() {
-        Some((TrackValueKind::Vector3, ValueType::Vector3U16))
-    } else if property_type == TypeId::of::<Vector3<i16>>() {
-        Some((TrackValueKind::Vector3, ValueType::Vector3I16))
-    } else if property_type == TypeId::of::<Vector3<u8>>() {
-        Some((TrackValueKind::Vector3, ValueType::Vector3U8))
-    } else if property_type == TypeId::of::<Vector3<i8>>() {
-        Some((TrackValueKind::Vector3, ValueType::Vector3I8))
-    } else if property_type == TypeId::of::<Vector3<bool>>() {
-        Some((TrackValueKind::Vector3, ValueType::Vector3Bool))
-    } else if property_type == TypeId::of::<Vector4<f32>>() {
-        Some((TrackValueKind::Vector4, ValueType::Vector4F32))
-    } else if property_type == TypeId::of::<Vector4<f64>>() {
-        Some((TrackValueKind::Vector4, ValueType::Vector4F64))
-    } else if property_type == TypeId::of::<Vector4<u64>>() {
-        Some((TrackValueKind::Vector4, ValueType::Vector4U64))
-    } else if property_type == TypeId::of::<Vector4<i64>>() {
-        Some((TrackValueKind::Vector4, ValueType::Vector4I64))
-    } else if property_type == TypeId::of::<Vector4<u32>>() {
-        Some((TrackValueKind::Vector4, ValueType::Vector4U32))
-    } else if property_type == TypeId::of::<Vector4<i32>>() {
-        Some((TrackValueKind::Vector4, ValueType::Vector4I32))
-    } else if property_type == TypeId::of::<Vector4<u16>>() {
-        Some((TrackValueKind::Vector4, ValueType::Vector4U16))
-    } else if property_type == TypeId::of::<Vector4<i16>>() {
-        Some((TrackValueKind::Vector4, ValueType::Vector4I16))
-    } else if property_type == TypeId::of::<Vector4<u8>>() {
-        Some((TrackValueKind::Vector4, ValueType::Vector4U8))
-    } else if property_type == TypeId::of::<Vector4<i8>>() {
-        Some((TrackValueKind::Vector4, ValueType::Vector4I8))
-    } else if property_type == TypeId::of::<Vector4<bool>>() {
-        Some((TrackValueKind::Vector4, ValueType::Vector4Bool))
-    } else if property_type == TypeId::of::<UnitQuaternion<f32>>() {
-        Some((TrackValueKind::UnitQuaternion, ValueType::UnitQuaternionF32))
-    } else if property_type == TypeId::of::<UnitQuaternion<f64>>() {
-        Some((TrackValueKind::UnitQuaternion, ValueType::UnitQuaternionF64))
-    } else {
-        None
-    }
-}
-
 #[allow(clippy::enum_variant_names)] // GTFO
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TrackViewMessage {
@@ -911,11 +814,26 @@ impl TrackList {
                                 let mut property_type = TypeId::of::<u32>();
                                 property.as_any(&mut |any| property_type = any.type_id());
 
-                                let types = type_id_to_supported_type(property_type);
+                                if let Some(actual_value_type) =
+                                    ValueType::from_type_id(property_type)
+                                {
+                                    let mut data = TrackDataContainer::new(
+                                        actual_value_type.track_value_kind(),
+                                    );
+
+                                    // Start from the current value of the property, this way the
+                                    // track could be used as a tween right away.
+                                    if let Some(value) = TrackValue::from_reflect(property) {
+                                        let key_kind = if actual_value_type.is_discrete() {
+                                            CurveKeyKind::Constant
+                                        } else {
+                                            CurveKeyKind::Linear
+                                        };
+                                        data.add_value_key(0.0, &value, key_kind);
+                                    }
 
-                                if let Some((track_value_kind, actual_value_type)) = types {
                                     let mut track = Track::new(
-                                        TrackDataContainer::new(track_value_kind),
+                                        data,
                                         ValueBinding::Property {
                                             name: property_path.path.clone(),
                                             value_type: actual_value_type,
@@ -1120,7 +1038,11 @@ impl TrackList {
             Vector4<u32>, Vector4<i32>,
             Vector4<i16>, Vector4<u16>, Vector4<i8>, Vector4<u8>,
 
-            UnitQuaternion<f32>
+            Vector2<bool>, Vector3<bool>, Vector4<bool>,
+
+            UnitQuaternion<f32>, UnitQuaternion<f64>,
+
+            Color
         })))
         .with_property_descriptors(descriptors)
         .build(&mut ui.build_ctx());
@@ -1192,9 +1114,7 @@ impl TrackList {
                 let mut property_type = TypeId::of::<u32>();
                 property.as_any(&mut |any| property_type = any.type_id());
 
-                let types = type_id_to_supported_type(property_type);
-
-                if let Some((_, actual_value_type)) = types {
+                if let Some(actual_value_type) = ValueType::from_type_id(property_type) {
                     sender.do_command(SetTrackBindingCommand {
                         animation_player_handle: selection.animation_player,
                         animation_handle: selection.animation,
@@ -1362,8 +1282,18 @@ impl TrackList {
                             .iter()
                             .enumerate()
                             .map(|(i, curve)| {
+                                let is_color = matches!(
+                                    model_track.binding(),
+                                    ValueBinding::Property {
+                                        value_type: ValueType::Color,
+                                        ..
+                                    }
+                                );
                                 let curve_name = match model_track.data_container().value_kind() {
                                     TrackValueKind::Real => "Value",
+                                    TrackValueKind::Vector4 if is_color => {
+                                        ["R", "G", "B", "A"].get(i).unwrap_or(&"_")
+                                    }
                                     TrackValueKind::Vector2
                                     | TrackValueKind::Vector3
                                     | TrackValueKind::Vector4 => {
@@ -1542,7 +1472,7 @@ impl TrackList {
                                 let mut property_type = TypeId::of::<u32>();
                                 value.as_any(&mut |any| property_type = any.type_id());
 
-                                if let Some((_, type_)) = type_id_to_supported_type(property_type) {
+                                if let Some(type_) = ValueType::from_type_id(property_type) {
                                     if *value_type != type_ {
                                         validation_result = Err(format!(
                                             "Property type mismatch. Expected {:?}, got {:?}",
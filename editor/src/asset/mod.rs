@@ -28,6 +28,7 @@ use crate::{
             list_view::{ListViewBuilder, ListViewMessage},
             menu::{ContextMenuBuilder, MenuItemBuilder, MenuItemContent, MenuItemMessage},
             message::{MessageDirection, UiMessage},
+            messagebox::{MessageBoxBuilder, MessageBoxResult},
             popup::{Placement, PopupBuilder, PopupMessage},
             scroll_viewer::{ScrollViewerBuilder, ScrollViewerMessage},
             searchbar::{SearchBarBuilder, SearchBarMessage},
@@ -42,7 +43,8 @@ use crate::{
             UserInterface, VerticalAlignment, BRUSH_DARK,
         },
         material::Material,
-        resource::{model::Model, texture::Texture},
+        platform,
+        resource::{gradient::ColorGradientResourceState, model::Model, texture::Texture},
         scene::sound::SoundBuffer,
         walkdir,
     },
@@ -50,7 +52,7 @@ use crate::{
     message::MessageSender,
     preview::PreviewPanel,
     utils::window_content,
-    Message, Mode,
+    Message, MessageBoxButtons, MessageBoxMessage, Mode,
 };
 use fyrox::core::Uuid;
 use fyrox::scene::tilemap::tileset::TileSet;
@@ -59,7 +61,6 @@ use std::sync::mpsc::Sender;
 use std::{
     ffi::OsStr,
     path::{Path, PathBuf},
-    process::Command,
     sync::Arc,
 };
 
@@ -77,27 +78,26 @@ struct ContextMenu {
     delete: Handle<UiNode>,
     placement_target: Handle<UiNode>,
     dependencies: Handle<UiNode>,
+    delete_confirmation: Handle<UiNode>,
+    path_to_delete: Option<PathBuf>,
 }
 
-fn execute_command(command: &mut Command) {
-    match command.spawn() {
-        Ok(mut process) => Log::verify(process.wait()),
-        Err(err) => Log::err(format!(
+fn show_in_explorer<P: AsRef<Path>>(path: P) {
+    if let Err(err) = platform::reveal_path(path) {
+        Log::err(format!(
             "Failed to show asset item in explorer. Reason: {:?}",
             err
-        )),
+        ))
     }
 }
 
-fn show_in_explorer<P: AsRef<OsStr>>(path: P) {
-    execute_command(Command::new("explorer").arg("/select,").arg(path))
-}
-
 fn open_in_explorer<P: AsRef<Path>>(path: P) {
-    if let Ok(path) = path.as_ref().canonicalize() {
-        Log::verify(open::that(path))
-    } else {
-        Log::err(format!("Failed to canonicalize path {:?}", path.as_ref()))
+    if let Err(err) = platform::open_path(path.as_ref()) {
+        Log::err(format!(
+            "Failed to open {:?} in explorer. Reason: {:?}",
+            path.as_ref(),
+            err
+        ))
     }
 }
 
@@ -162,6 +162,14 @@ impl ContextMenu {
         .build(ctx);
         let menu = RcUiNodeHandle::new(menu, ctx.sender());
 
+        let delete_confirmation = MessageBoxBuilder::new(
+            WindowBuilder::new(WidgetBuilder::new().with_width(350.0))
+                .open(false)
+                .with_title(WindowTitle::text("Asset Is In Use")),
+        )
+        .with_buttons(MessageBoxButtons::YesNo)
+        .build(ctx);
+
         Self {
             menu,
             open,
@@ -171,6 +179,47 @@ impl ContextMenu {
             placement_target: Default::default(),
             copy_file_name,
             dependencies,
+            delete_confirmation,
+            path_to_delete: None,
+        }
+    }
+
+    fn delete(&mut self, path: &Path, engine: &mut Engine) {
+        // Check whether the asset is used by other (loaded) assets first and ask for confirmation
+        // if so, because deleting it will break the references.
+        let dependents = make_relative_path(path)
+            .map(|relative_path| {
+                engine
+                    .resource_manager
+                    .state()
+                    .find_dependents(&relative_path)
+            })
+            .unwrap_or_default();
+
+        if dependents.is_empty() {
+            Log::verify(std::fs::remove_file(path))
+        } else {
+            let list = dependents
+                .iter()
+                .map(|p| format!("- {}", p.display()))
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            engine
+                .user_interfaces
+                .first()
+                .send_message(MessageBoxMessage::open(
+                    self.delete_confirmation,
+                    MessageDirection::ToWidget,
+                    None,
+                    Some(format!(
+                    "{} is used by the following assets:\n{}\n\nDo you want to delete it anyway?",
+                    path.display(),
+                    list
+                )),
+                ));
+
+            self.path_to_delete = Some(path.to_path_buf());
         }
     }
 
@@ -192,11 +241,10 @@ impl ContextMenu {
                 .and_then(|n| n.cast::<AssetItem>())
             {
                 if message.destination() == self.delete {
-                    Log::verify(std::fs::remove_file(&item.path))
+                    let path = item.path.clone();
+                    self.delete(&path, engine);
                 } else if message.destination() == self.show_in_explorer {
-                    if let Ok(canonical_path) = item.path.canonicalize() {
-                        show_in_explorer(canonical_path)
-                    }
+                    show_in_explorer(&item.path)
                 } else if message.destination() == self.open {
                     if item
                         .path
@@ -220,6 +268,16 @@ impl ContextMenu {
                                 sender.send(Message::OpenTileSetEditor(tile_set));
                             }
                         }
+                    } else if item.path.extension().map_or(false, |ext| ext == "gradient") {
+                        if let Ok(path) = make_relative_path(&item.path) {
+                            if let Ok(gradient) = block_on(
+                                engine
+                                    .resource_manager
+                                    .request::<ColorGradientResourceState>(path),
+                            ) {
+                                sender.send(Message::OpenGradientEditor(gradient));
+                            }
+                        }
                     } else {
                         open_in_explorer(&item.path)
                     }
@@ -233,6 +291,14 @@ impl ContextMenu {
                     }
                 }
             }
+        } else if let Some(MessageBoxMessage::Close(result)) = message.data() {
+            if message.destination() == self.delete_confirmation {
+                if let Some(path) = self.path_to_delete.take() {
+                    if let MessageBoxResult::Yes = result {
+                        Log::verify(std::fs::remove_file(path))
+                    }
+                }
+            }
         }
     }
 }
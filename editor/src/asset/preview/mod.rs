@@ -23,7 +23,9 @@ use crate::{
         renderer::framework::gpu_texture::{GpuTextureKind, PixelKind},
         resource::{
             curve::CurveResourceState,
+            gradient::ColorGradientResourceState,
             model::{Model, ModelResourceExtension},
+            svg::SvgImage,
             texture::{
                 Texture, TextureKind, TexturePixelKind, TextureResource, TextureResourceExtension,
             },
@@ -60,6 +62,11 @@ impl AssetPreviewGeneratorsCollection {
         this.add(Material::type_uuid(), MaterialPreview);
         this.add(HrirSphereResourceData::type_uuid(), HrirPreview);
         this.add(CurveResourceState::type_uuid(), CurvePreview);
+        this.add(
+            ColorGradientResourceState::type_uuid(),
+            ColorGradientPreview,
+        );
+        this.add(SvgImage::type_uuid(), SvgImagePreview);
         this.add(Font::type_uuid(), FontPreview);
         this.add(UserInterface::type_uuid(), UserInterfacePreview);
         this.add(SurfaceData::type_uuid(), SurfaceDataPreview);
@@ -596,6 +603,103 @@ impl AssetPreviewGenerator for CurvePreview {
     }
 }
 
+pub struct ColorGradientPreview;
+
+impl AssetPreviewGenerator for ColorGradientPreview {
+    fn generate_scene(
+        &mut self,
+        _resource: &UntypedResource,
+        _resource_manager: &ResourceManager,
+        _scene: &mut Scene,
+    ) -> Handle<Node> {
+        Handle::NONE
+    }
+
+    fn generate_preview(
+        &mut self,
+        resource: &UntypedResource,
+        _engine: &mut Engine,
+    ) -> Option<AssetPreviewTexture> {
+        const SIZE: u32 = 128;
+
+        let gradient_resource = resource.try_cast::<ColorGradientResourceState>()?;
+        let mut state = gradient_resource.state();
+        let gradient = &state.data()?.gradient;
+
+        let row = (0..SIZE)
+            .flat_map(|x| {
+                let color = gradient.get_color(x as f32 / (SIZE - 1) as f32);
+                [color.r, color.g, color.b, color.a]
+            })
+            .collect::<Vec<_>>();
+        let bytes = row.repeat(SIZE as usize);
+
+        Some(AssetPreviewTexture {
+            texture: TextureResource::from_bytes(
+                TextureKind::Rectangle {
+                    width: SIZE,
+                    height: SIZE,
+                },
+                TexturePixelKind::RGBA8,
+                bytes,
+                ResourceKind::Embedded,
+            )?,
+            flip_y: false,
+        })
+    }
+
+    fn simple_icon(
+        &self,
+        _resource: &UntypedResource,
+        _resource_manager: &ResourceManager,
+    ) -> Option<UntypedResource> {
+        load_image(include_bytes!("../../../resources/curve.png"))
+    }
+}
+
+pub struct SvgImagePreview;
+
+impl SvgImagePreview {
+    fn rasterize(resource: &UntypedResource, size: u32) -> Option<TextureResource> {
+        let image_resource = resource.try_cast::<SvgImage>()?;
+        let mut state = image_resource.state();
+        let image = state.data()?;
+        let image_size = image.size();
+        let scale = size as f32 / image_size.x.max(image_size.y).max(f32::EPSILON);
+        Some(image.texture(scale))
+    }
+}
+
+impl AssetPreviewGenerator for SvgImagePreview {
+    fn generate_scene(
+        &mut self,
+        _resource: &UntypedResource,
+        _resource_manager: &ResourceManager,
+        _scene: &mut Scene,
+    ) -> Handle<Node> {
+        Handle::NONE
+    }
+
+    fn generate_preview(
+        &mut self,
+        resource: &UntypedResource,
+        _engine: &mut Engine,
+    ) -> Option<AssetPreviewTexture> {
+        Some(AssetPreviewTexture {
+            texture: Self::rasterize(resource, 128)?,
+            flip_y: false,
+        })
+    }
+
+    fn simple_icon(
+        &self,
+        resource: &UntypedResource,
+        _resource_manager: &ResourceManager,
+    ) -> Option<UntypedResource> {
+        Self::rasterize(resource, 64).map(|texture| texture.into_untyped())
+    }
+}
+
 pub struct FontPreview;
 
 fn render_ui_to_texture(
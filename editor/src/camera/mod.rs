@@ -2,10 +2,7 @@ use crate::{
     fyrox::{
         core::{
             algebra::{Matrix4, Point3, UnitQuaternion, Vector2, Vector3},
-            math::{
-                aabb::AxisAlignedBoundingBox, plane::Plane, ray::Ray, Matrix4Ext,
-                TriangleDefinition, Vector3Ext,
-            },
+            math::{aabb::AxisAlignedBoundingBox, plane::Plane, ray::Ray, Matrix4Ext},
             pool::Handle,
         },
         graph::{BaseSceneGraph, SceneGraph, SceneGraphNode},
@@ -14,17 +11,14 @@ use crate::{
             base::BaseBuilder,
             camera::{Camera, CameraBuilder, Exposure, FitParameters, Projection},
             graph::Graph,
-            mesh::{
-                buffer::{VertexAttributeUsage, VertexReadTrait},
-                surface::SurfaceData,
-                Mesh,
-            },
+            mesh::Mesh,
             node::Node,
             pivot::PivotBuilder,
             sound::listener::ListenerBuilder,
             transform::TransformBuilder,
             Scene,
         },
+        utils::placement,
     },
     settings::{
         camera::CameraSettings,
@@ -730,27 +724,10 @@ impl CameraController {
     }
 }
 
-fn read_vertex_position(data: &SurfaceData, i: u32) -> Option<Vector3<f32>> {
-    data.vertex_buffer
-        .get(i as usize)
-        .and_then(|v| v.read_3_f32(VertexAttributeUsage::Position).ok())
-}
-
 fn transform_vertex(vertex: Vector3<f32>, transform: &Matrix4<f32>) -> Vector3<f32> {
     transform.transform_point(&Point3::from(vertex)).coords
 }
 
-fn read_triangle(
-    data: &SurfaceData,
-    triangle: &TriangleDefinition,
-    transform: &Matrix4<f32>,
-) -> Option<[Vector3<f32>; 3]> {
-    let a = transform_vertex(read_vertex_position(data, triangle[0])?, transform);
-    let b = transform_vertex(read_vertex_position(data, triangle[1])?, transform);
-    let c = transform_vertex(read_vertex_position(data, triangle[2])?, transform);
-    Some([a, b, c])
-}
-
 fn has_hull(node: &Node) -> bool {
     node.query_component_ref::<Mesh>().is_some()
 }
@@ -760,41 +737,7 @@ fn precise_ray_test(
     ray: &Ray,
     ignore_back_faces: bool,
 ) -> Option<(f32, Vector3<f32>)> {
-    let mut closest_distance = f32::MAX;
-    let mut closest_point = None;
-
-    if let Some(mesh) = node.query_component_ref::<Mesh>() {
-        let transform = mesh.global_transform();
-
-        for surface in mesh.surfaces().iter() {
-            let data = surface.data();
-            let data = data.data_ref();
-
-            for triangle in data
-                .geometry_buffer
-                .iter()
-                .filter_map(|t| read_triangle(&data, t, &transform))
-            {
-                if ignore_back_faces {
-                    // If normal of the triangle is facing in the same direction as ray's direction,
-                    // then we skip such triangle.
-                    let normal = (triangle[1] - triangle[0]).cross(&(triangle[2] - triangle[0]));
-                    if normal.dot(&ray.dir) >= 0.0 {
-                        continue;
-                    }
-                }
-
-                if let Some(pt) = ray.triangle_intersection_point(&triangle) {
-                    let distance = ray.origin.sqr_distance(&pt);
-
-                    if distance < closest_distance {
-                        closest_distance = distance;
-                        closest_point = Some(pt);
-                    }
-                }
-            }
-        }
-    }
-
-    closest_point.map(|pt| (closest_distance, pt))
+    node.query_component_ref::<Mesh>()
+        .and_then(|mesh| placement::mesh_ray_test(mesh, ray, ignore_back_faces))
+        .map(|hit| (hit.sqr_distance, hit.position))
 }
@@ -1,11 +1,15 @@
 use crate::{
     fyrox::{
+        asset::manager::ResourceManager,
         core::{
             color::Color,
+            futures::executor::block_on,
             log::{Log, LogMessage, MessageKind},
+            make_relative_path,
             pool::Handle,
             reflect::prelude::*,
         },
+        fxhash::FxHashSet,
         graph::BaseSceneGraph,
         gui::{
             border::BorderBuilder,
@@ -30,6 +34,7 @@ use crate::{
             BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
             VerticalAlignment, BRUSH_DARKER, BRUSH_LIGHT,
         },
+        walkdir,
     },
     gui::make_dropdown_list_option,
     message::MessageSender,
@@ -59,6 +64,8 @@ struct ExportOptions {
     #[reflect(hidden)]
     target_platform: TargetPlatform,
     destination_folder: PathBuf,
+    /// If set, only assets that are used by scenes and user interfaces (directly or indirectly) are
+    /// exported. Assets that are loaded only from code will be skipped!
     include_used_assets: bool,
     assets_folders: Vec<PathBuf>,
     ignored_extensions: Vec<String>,
@@ -66,6 +73,8 @@ struct ExportOptions {
     build_targets: Vec<String>,
     #[reflect(hidden)]
     selected_build_target: usize,
+    #[reflect(hidden)]
+    unused_assets: FxHashSet<PathBuf>,
     run_after_build: bool,
     open_destination_folder: bool,
 }
@@ -80,6 +89,7 @@ impl Default for ExportOptions {
             ignored_extensions: vec!["log".to_string()],
             build_targets: vec!["default".to_string()],
             selected_build_target: 0,
+            unused_assets: Default::default(),
             run_after_build: false,
             open_destination_folder: true,
         }
@@ -151,6 +161,71 @@ where
     Ok(())
 }
 
+/// Collects paths of assets in the given folders that are not used by any scene or user interface
+/// (directly or indirectly). Scenes and user interfaces themselves are considered used.
+fn collect_unused_assets(
+    folders: &[PathBuf],
+    resource_manager: &ResourceManager,
+) -> FxHashSet<PathBuf> {
+    let mut assets = FxHashSet::default();
+    for folder in folders {
+        for entry in walkdir::WalkDir::new(folder).into_iter().flatten() {
+            let path = entry.path();
+            let is_resource = path.extension().is_some_and(|ext| {
+                resource_manager
+                    .state()
+                    .loaders
+                    .iter()
+                    .any(|loader| loader.supports_extension(&ext.to_string_lossy()))
+            });
+            if is_resource {
+                if let Ok(relative_path) = make_relative_path(path) {
+                    assets.insert(relative_path);
+                }
+            }
+        }
+    }
+
+    let roots = assets
+        .iter()
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == "rgs" || ext == "ui")
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+
+    // Load every asset reachable from the roots and scan its references.
+    let mut visited = FxHashSet::default();
+    let mut stack = roots.clone();
+    while let Some(path) = stack.pop() {
+        if !visited.insert(path.clone()) {
+            continue;
+        }
+
+        if let Ok(resource) = block_on(resource_manager.request_untyped(&path)) {
+            let mut state = resource_manager.state();
+            state.scan_references(&resource);
+            stack.extend(
+                state
+                    .reference_graph()
+                    .dependencies_of(&path)
+                    .map(|p| p.to_path_buf()),
+            );
+        }
+    }
+
+    let mut state = resource_manager.state();
+    state
+        .reference_graph()
+        .find_unreferenced(
+            assets.iter().map(|p| p.as_path()),
+            roots.iter().map(|p| p.as_path()),
+        )
+        .into_iter()
+        .collect()
+}
+
 fn read_metadata() -> Result<Metadata, String> {
     return match std::process::Command::new("cargo")
         .arg("metadata")
@@ -495,6 +570,27 @@ fn copy_binaries_wasm(package_dir_path: &Path, destination_folder: &Path) -> Res
     .map_err(|e| e.to_string())
 }
 
+fn is_asset_used(path: &Path, unused_assets: &FxHashSet<PathBuf>) -> bool {
+    if unused_assets.is_empty() || path.is_dir() {
+        return true;
+    }
+
+    match make_relative_path(path) {
+        Ok(relative_path) => {
+            if unused_assets.contains(&relative_path) {
+                Log::info(format!(
+                    "{} is skipped, because it is not used.",
+                    path.display()
+                ));
+                false
+            } else {
+                true
+            }
+        }
+        Err(_) => true,
+    }
+}
+
 fn export(export_options: ExportOptions, cancel_flag: Arc<AtomicBool>) -> Result<(), String> {
     Log::info("Building the game...");
 
@@ -533,7 +629,7 @@ fn export(export_options: ExportOptions, cancel_flag: Arc<AtomicBool>) -> Result
                 Log::verify(copy_dir(
                     &folder,
                     export_options.destination_folder.join(&folder),
-                    &|_| true,
+                    &|path| is_asset_used(path, &export_options.unused_assets),
                 ));
             }
         }
@@ -583,7 +679,7 @@ fn export(export_options: ExportOptions, cancel_flag: Arc<AtomicBool>) -> Result
                     Log::verify(copy_dir(
                         &folder,
                         temp_assets_storage.join(&folder),
-                        &|_| true,
+                        &|path| is_asset_used(path, &export_options.unused_assets),
                     ));
                 }
             } else {
@@ -964,10 +1060,17 @@ impl ExportWindow {
         &mut self,
         message: &UiMessage,
         ui: &mut UserInterface,
+        resource_manager: &ResourceManager,
         sender: &MessageSender,
     ) {
         if let Some(ButtonMessage::Click) = message.data() {
             if message.destination() == self.export {
+                self.export_options.unused_assets = if self.export_options.include_used_assets {
+                    collect_unused_assets(&self.export_options.assets_folders, resource_manager)
+                } else {
+                    Default::default()
+                };
+
                 let (tx, rx) = mpsc::channel();
                 Log::add_listener(tx);
                 self.log_message_receiver = Some(rx);
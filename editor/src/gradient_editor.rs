@@ -0,0 +1,192 @@
+use crate::fyrox::{
+    asset::{untyped::ResourceKind, ResourceData},
+    core::{color_gradient::ColorGradient, log::Log, pool::Handle},
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        color::gradient::{ColorGradientEditorBuilder, ColorGradientEditorMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+    },
+    resource::gradient::ColorGradientResource,
+};
+use crate::{send_sync_message, MSG_SYNC_FLAG};
+
+/// A window that allows to edit color gradient resources (`*.gradient` files). Changes are applied
+/// to the resource immediately, so every user of the resource (particle systems, etc.) is updated
+/// live. "OK" saves the resource back to its file, "Cancel" reverts all the changes.
+pub struct GradientEditorWindow {
+    pub window: Handle<UiNode>,
+    gradient_editor: Handle<UiNode>,
+    ok: Handle<UiNode>,
+    cancel: Handle<UiNode>,
+    gradient_resource: Option<ColorGradientResource>,
+    backup: ColorGradient,
+}
+
+impl GradientEditorWindow {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let gradient_editor;
+        let ok;
+        let cancel;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(400.0).with_height(150.0))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child({
+                            gradient_editor = ColorGradientEditorBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .build(ctx);
+                            gradient_editor
+                        })
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_horizontal_alignment(HorizontalAlignment::Right)
+                                    .with_child({
+                                        ok = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_margin(Thickness::uniform(1.0))
+                                                .with_width(100.0),
+                                        )
+                                        .with_text("OK")
+                                        .build(ctx);
+                                        ok
+                                    })
+                                    .with_child({
+                                        cancel = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_margin(Thickness::uniform(1.0))
+                                                .with_width(100.0),
+                                        )
+                                        .with_text("Cancel")
+                                        .build(ctx);
+                                        cancel
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        ),
+                )
+                .add_row(Row::stretch())
+                .add_row(Row::strict(25.0))
+                .add_column(Column::stretch())
+                .build(ctx),
+            )
+            .with_title(WindowTitle::text("Gradient Editor"))
+            .build(ctx);
+
+        Self {
+            window,
+            gradient_editor,
+            ok,
+            cancel,
+            gradient_resource: None,
+            backup: Default::default(),
+        }
+    }
+
+    pub fn open(&mut self, gradient_resource: ColorGradientResource, ui: &UserInterface) {
+        self.backup = gradient_resource.data_ref().gradient.clone();
+
+        send_sync_message(
+            ui,
+            ColorGradientEditorMessage::value(
+                self.gradient_editor,
+                MessageDirection::ToWidget,
+                self.backup.clone(),
+            ),
+        );
+
+        let title = match gradient_resource.kind() {
+            ResourceKind::Embedded => "Gradient Editor - Unnamed Gradient".to_string(),
+            ResourceKind::External(path) => format!("Gradient Editor - {}", path.display()),
+        };
+        ui.send_message(WindowMessage::title(
+            self.window,
+            MessageDirection::ToWidget,
+            WindowTitle::text(title),
+        ));
+
+        self.gradient_resource = Some(gradient_resource);
+
+        ui.send_message(WindowMessage::open(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+            true,
+        ));
+    }
+
+    fn close(&mut self, ui: &UserInterface) {
+        self.gradient_resource = None;
+        self.backup = Default::default();
+
+        ui.send_message(WindowMessage::close(
+            self.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    fn save(&self) {
+        if let Some(gradient_resource) = self.gradient_resource.as_ref() {
+            if let ResourceKind::External(path) = gradient_resource.kind() {
+                Log::verify(gradient_resource.data_ref().save(&path));
+            }
+        }
+    }
+
+    fn revert(&self) {
+        if let Some(gradient_resource) = self.gradient_resource.as_ref() {
+            gradient_resource.data_ref().gradient = self.backup.clone();
+        }
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage, ui: &UserInterface) {
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.ok {
+                self.save();
+                self.close(ui);
+            } else if message.destination() == self.cancel {
+                self.revert();
+                self.close(ui);
+            }
+        } else if let Some(WindowMessage::Close) = message.data() {
+            if message.destination() == self.window
+                && message.direction() == MessageDirection::FromWidget
+                && self.gradient_resource.is_some()
+            {
+                // Closing the window by its "X" button discards the changes.
+                self.revert();
+                self.gradient_resource = None;
+            }
+        } else if let Some(ColorGradientEditorMessage::Value(gradient)) = message.data() {
+            if message.destination() == self.gradient_editor
+                && message.direction() == MessageDirection::FromWidget
+                && message.flags != MSG_SYNC_FLAG
+            {
+                if let Some(gradient_resource) = self.gradient_resource.as_ref() {
+                    gradient_resource.data_ref().gradient = gradient.clone();
+                }
+
+                // The editor does not update itself, so sync it back.
+                send_sync_message(
+                    ui,
+                    ColorGradientEditorMessage::value(
+                        self.gradient_editor,
+                        MessageDirection::ToWidget,
+                        gradient.clone(),
+                    ),
+                );
+            }
+        }
+    }
+}
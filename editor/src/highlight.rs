@@ -258,7 +258,7 @@ impl SceneRenderPass for HighlightRenderPass {
 
                 let Some(render_pass) = ctx
                     .shader_cache
-                    .get(ctx.pipeline_state, material.shader())
+                    .get_for_material(ctx.pipeline_state, material)
                     .and_then(|shader_set| shader_set.render_passes.get(&render_pass_name))
                 else {
                     continue;
@@ -274,21 +274,24 @@ impl SceneRenderPass for HighlightRenderPass {
                     };
 
                     self.framebuffer.draw(
-                        geometry,
+                        geometry.buffer,
                         ctx.pipeline_state,
                         ctx.viewport,
                         &render_pass.program,
                         &render_pass.draw_params,
-                        instance.element_range,
+                        geometry.element_range(instance.element_range)?,
                         |mut program_binding| {
                             apply_material(MaterialContext {
                                 material,
+                                global_properties: ctx.global_properties,
                                 program_binding: &mut program_binding,
                                 texture_cache: ctx.texture_cache,
                                 world_matrix: &instance.world_transform,
+                                prev_world_matrix: &instance.prev_world_transform,
                                 view_projection_matrix: &view_projection,
                                 wvp_matrix: &(view_projection * instance.world_transform),
                                 bone_matrices: &instance.bone_matrices,
+                                prev_bone_matrices: &instance.prev_bone_matrices,
                                 use_skeletal_animation: bundle.is_skinned,
                                 camera_position: &ctx.camera.global_position(),
                                 camera_up_vector: &camera_up,
@@ -308,6 +311,8 @@ impl SceneRenderPass for HighlightRenderPass {
                                 light_data: None,
                                 ambient_light: Default::default(),
                                 scene_depth: Some(&ctx.depth_texture),
+                                light_clusters: None,
+                                weather: Default::default(),
                             });
                         },
                     )?;
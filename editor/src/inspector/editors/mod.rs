@@ -2,10 +2,12 @@ use crate::{
     fyrox::{
         asset::{manager::ResourceManager, untyped::UntypedResource, Resource},
         core::{
+            algebra::Vector3,
             futures::executor::block_on,
             parking_lot::Mutex,
             pool::{ErasedHandle, Handle},
         },
+        generic_animation::machine::{LayerBlendMode, TransitionInterruption},
         gui::{
             self,
             font::FontResource,
@@ -26,20 +28,37 @@ use crate::{
         renderer::framework::state::PolygonFillMode,
         resource::{
             curve::{CurveResource, CurveResourceState},
-            model::{MaterialSearchOptions, Model, ModelResource},
+            dialogue::{
+                ComparisonOperator, Dialogue, DialogueChoice, DialogueCondition, DialogueNode,
+                DialogueValue,
+            },
+            gradient::{ColorGradientResource, ColorGradientResourceState},
+            item::{ItemDefinition, ItemResource, ItemResourceState},
+            model::{
+                AnimationClipImportOptions, MaterialRemap, MaterialSearchOptions, Model,
+                ModelResource, ModelUpAxis, TangentGenerationMethod,
+            },
             texture::{
                 CompressionOptions, MipFilter, TextureMagnificationFilter,
                 TextureMinificationFilter, TextureResource, TextureWrapMode,
             },
+            timeline::{TimelineResource, TimelineResourceState},
         },
         scene::{
             self,
+            animation::facial::{
+                BlendShapeTarget, ChannelCurve, ChannelValue, FacialChannel, FacialLayer,
+                FacialLayerSource, VisemeKey,
+            },
             base::{
-                Base, LevelOfDetail, LodGroup, Mobility, Property, PropertyValue, ScriptRecord,
+                Base, LevelOfDetail, LifetimePolicy, LodGroup, Mobility, Property, PropertyValue,
+                ScriptRecord,
             },
             camera::{
-                ColorGradingLut, Exposure, OrthographicProjection, PerspectiveProjection,
-                Projection, SkyBox,
+                effects::{CameraEffect, FovKick, Kick, SmoothFollow, TraumaShake},
+                post_effect::PostEffect,
+                BloomSettings, CameraBackground, ColorGradingLut, Exposure, OrthographicProjection,
+                PerspectiveProjection, Projection, SkyBox,
             },
             collider::{
                 BallShape, BitMask, CapsuleShape, ColliderShape, ConeShape, ConvexPolyhedronShape,
@@ -48,6 +67,7 @@ use crate::{
             },
             dim2,
             graph::physics::CoefficientCombineRule,
+            inventory::{Inventory, ItemStack},
             joint::*,
             light::{
                 directional::{CsmOptions, FrustumSplitOptions},
@@ -57,30 +77,46 @@ use crate::{
                 surface::{BlendShape, Surface, SurfaceResource},
                 BatchingMode, RenderPath,
             },
+            navmesh::NavmeshObstacle,
             node::Node,
+            ocean::OceanWave,
             particle_system::{
                 emitter::{
                     base::BaseEmitter, cuboid::CuboidEmitter, cylinder::CylinderEmitter,
                     sphere::SphereEmitter, Emitter,
                 },
-                ParticleSystemRng,
+                ParticleSimulationMode, ParticleSystemRng,
             },
+            portal::PortalKind,
             ragdoll::Limb,
             rigidbody::RigidBodyType,
+            smoke::{SmokeSimulation, SmokeSimulationMode},
             sound::{
                 self,
                 filter::{
                     AllPassFilterEffect, BandPassFilterEffect, HighPassFilterEffect,
                     HighShelfFilterEffect, LowPassFilterEffect, LowShelfFilterEffect,
                 },
-                reverb::Reverb,
-                Attenuate, AudioBus, Biquad, DistanceModel, Effect, SoundBuffer,
-                SoundBufferResource, Status,
+                occlusion::SoundOcclusion,
+                reverb::{Reverb, ReverbPreset},
+                reverb_zone::{ReverbZoneMode, ReverbZoneShape},
+                Attenuate, AudioBus, AudioSend, Biquad, DistanceModel, Effect, SoundBuffer,
+                SoundBufferResource, SoundCone, Status,
+            },
+            sprite::BillboardMode,
+            terrain::{Chunk, Layer, TerrainStreaming},
+            tilemap::{chunk::Tiles, projection::TileMapProjection, tileset::TileSet, Tile},
+            timeline::{
+                track::{
+                    AnimationClip, AnimationTrack, AudioTrack, CameraCut, CameraCutsTrack,
+                    EventMarker, EventTrack, PropertyTrack, SoundCue, TimelineTrack, TrackKind,
+                },
+                Timeline,
             },
-            terrain::{Chunk, Layer},
-            tilemap::{tileset::TileSet, Tile},
             transform::Transform,
+            vector_shape::{VectorPath, VectorShapeKind},
         },
+        utils::navmesh::bake::{NavmeshBakeSettings, NavmeshObstacleShape},
     },
     inspector::editors::{
         animation::{
@@ -140,7 +176,7 @@ where
         },
         state::{StateAction, StateActionWrapper},
         transition::{AndNode, LogicNode, NotNode, OrNode, XorNode},
-        BlendAnimations, BlendAnimationsByIndex, BlendPose, IndexedBlendInput, Machine,
+        BlendAnimations, BlendAnimationsByIndex, BlendPose, IndexedBlendInput, Machine, Parameter,
         PlayAnimation, PoseNode, PoseWeight, State,
     };
 
@@ -160,6 +196,7 @@ where
     container.insert(InspectablePropertyEditorDefinition::<BlendPose<Handle<T>>>::new());
     container.insert(VecCollectionPropertyEditorDefinition::<BlendPose<Handle<T>>>::new());
     container.insert(EnumPropertyEditorDefinition::<PoseWeight>::new());
+    container.insert(EnumPropertyEditorDefinition::<Parameter>::new());
     container.insert(EnumPropertyEditorDefinition::<StateAction<Handle<T>>>::new());
     container.insert(InspectablePropertyEditorDefinition::<
         StateActionWrapper<Handle<T>>,
@@ -302,6 +339,54 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.insert(InheritablePropertyEditorDefinition::<Option<CurveResource>>::new());
     container.register_inheritable_vec_collection::<Option<CurveResource>>();
 
+    container.insert(ResourceFieldPropertyEditorDefinition::<
+        ColorGradientResourceState,
+    >::new(
+        Arc::new(Mutex::new(
+            |resource_manager: &ResourceManager, path: &Path| {
+                resource_manager
+                    .try_request::<ColorGradientResourceState>(path)
+                    .map(block_on)
+            },
+        )),
+        sender.clone(),
+    ));
+    container.insert(InheritablePropertyEditorDefinition::<
+        Option<ColorGradientResource>,
+    >::new());
+    container.register_inheritable_vec_collection::<Option<ColorGradientResource>>();
+
+    container.insert(
+        ResourceFieldPropertyEditorDefinition::<ItemResourceState>::new(
+            Arc::new(Mutex::new(
+                |resource_manager: &ResourceManager, path: &Path| {
+                    resource_manager
+                        .try_request::<ItemResourceState>(path)
+                        .map(block_on)
+                },
+            )),
+            sender.clone(),
+        ),
+    );
+    container.insert(InheritablePropertyEditorDefinition::<Option<ItemResource>>::new());
+    container.register_inheritable_vec_collection::<Option<ItemResource>>();
+
+    container.insert(
+        ResourceFieldPropertyEditorDefinition::<TimelineResourceState>::new(
+            Arc::new(Mutex::new(
+                |resource_manager: &ResourceManager, path: &Path| {
+                    resource_manager
+                        .try_request::<TimelineResourceState>(path)
+                        .map(block_on)
+                },
+            )),
+            sender.clone(),
+        ),
+    );
+    container.insert(InheritablePropertyEditorDefinition::<
+        Option<TimelineResource>,
+    >::new());
+
     container.insert(ResourceFieldPropertyEditorDefinition::<UserInterface>::new(
         Arc::new(Mutex::new(
             |resource_manager: &ResourceManager, path: &Path| {
@@ -342,6 +427,7 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_vec_collection::<Option<ShaderResource>>();
 
     container.register_inheritable_inspectable::<ColorGradingLut>();
+    container.register_inheritable_inspectable::<BloomSettings>();
     container.register_inheritable_inspectable::<InteractionGroups>();
 
     container.register_inheritable_enum::<JointParams, _>();
@@ -354,6 +440,8 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_inspectable::<RevoluteJoint>();
     container.register_inheritable_inspectable::<PrismaticJoint>();
     container.register_inheritable_inspectable::<dim2::joint::PrismaticJoint>();
+    container.register_inheritable_inspectable::<dim2::joint::WheelJoint>();
+    container.register_inheritable_inspectable::<dim2::joint::JointMotor>();
 
     container.register_inheritable_inspectable::<Base>();
     container.register_inheritable_inspectable::<BaseLight>();
@@ -374,17 +462,37 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
 
     container.register_inheritable_inspectable::<Biquad>();
     container.register_inheritable_inspectable::<AudioBus>();
+    container.register_inheritable_inspectable::<AudioSend>();
+    container.register_inheritable_vec_collection::<AudioSend>();
+    container.register_inheritable_inspectable::<ReverbPreset>();
+    container.register_inheritable_inspectable::<SoundOcclusion>();
+    container.register_inheritable_inspectable::<SoundCone>();
+    container.register_inheritable_enum::<ReverbZoneShape, _>();
+    container.register_inheritable_enum::<ReverbZoneMode, _>();
     container.register_inheritable_inspectable::<BaseEmitter>();
     container.register_inheritable_inspectable::<SphereEmitter>();
     container.register_inheritable_inspectable::<CylinderEmitter>();
     container.register_inheritable_inspectable::<CuboidEmitter>();
     container.register_inheritable_inspectable::<PerspectiveProjection>();
     container.register_inheritable_inspectable::<OrthographicProjection>();
+    container.register_inheritable_inspectable::<TraumaShake>();
+    container.register_inheritable_inspectable::<Kick>();
+    container.register_inheritable_inspectable::<FovKick>();
+    container.register_inheritable_inspectable::<SmoothFollow>();
     container.register_inheritable_inspectable::<Transform>();
     container.register_inheritable_inspectable::<CsmOptions>();
+    container.register_inheritable_inspectable::<SmokeSimulation>();
+
+    container.register_inheritable_inspectable::<OceanWave>();
+    container.register_inheritable_vec_collection::<OceanWave>();
+
+    container.register_inheritable_enum::<PortalKind, _>();
+
+    container.register_inheritable_vec_collection::<Vector3<f32>>();
 
     container.register_inheritable_inspectable::<Chunk>();
     container.register_inheritable_vec_collection::<Chunk>();
+    container.register_inheritable_inspectable::<TerrainStreaming>();
 
     container.register_inheritable_vec_collection::<BlendShape>();
     container.register_inheritable_inspectable::<BlendShape>();
@@ -402,16 +510,34 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_enum::<TextureMagnificationFilter, _>();
     container.register_inheritable_enum::<TextureMinificationFilter, _>();
     container.register_inheritable_enum::<Projection, _>();
+    container.register_inheritable_enum::<CameraEffect, _>();
+    container.register_inheritable_vec_collection::<CameraEffect>();
+    container.register_inheritable_inspectable::<PostEffect>();
+    container.register_inheritable_vec_collection::<PostEffect>();
     container.register_inheritable_enum::<ColliderShape, _>();
     container.register_inheritable_enum::<PropertyValue, _>();
     container.register_inheritable_enum::<Mobility, _>();
+    container.register_inheritable_enum::<LifetimePolicy, _>();
     container.register_inheritable_enum::<RigidBodyType, _>();
     container.register_inheritable_enum::<Exposure, _>();
+    container.register_inheritable_enum::<CameraBackground, _>();
+    container.register_inheritable_enum::<BillboardMode, _>();
+    container.register_inheritable_enum::<VectorShapeKind, _>();
+    container.register_inheritable_inspectable::<VectorPath>();
     container.register_inheritable_enum::<FrustumSplitOptions, _>();
     container.register_inheritable_enum::<MaterialSearchOptions, _>();
+    container.register_inheritable_inspectable::<AnimationClipImportOptions>();
+    container.register_inheritable_vec_collection::<AnimationClipImportOptions>();
+    container.register_inheritable_enum::<ModelUpAxis, _>();
+    container.register_inheritable_enum::<TangentGenerationMethod, _>();
+    container.register_inheritable_inspectable::<MaterialRemap>();
+    container.register_inheritable_vec_collection::<MaterialRemap>();
     container.register_inheritable_enum::<DistanceModel, _>();
+    container.register_inheritable_option::<DistanceModel>();
     container.register_inheritable_enum::<sound::Renderer, _>();
     container.register_inheritable_enum::<RenderPath, _>();
+    container.register_inheritable_enum::<SmokeSimulationMode, _>();
+    container.register_inheritable_enum::<ParticleSimulationMode, _>();
 
     container.insert(EnumPropertyEditorDefinition::<Vec<ScriptRecord>>::new_optional());
     container.insert(VecCollectionPropertyEditorDefinition::<ScriptRecord>::new());
@@ -419,6 +545,7 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.insert(ScriptPropertyEditorDefinition {});
 
     container.insert(BitFieldPropertyEditorDefinition::<BitMask>::new());
+    container.insert(InheritablePropertyEditorDefinition::<BitMask>::new());
 
     container.register_inheritable_inspectable::<BallShape>();
     container.register_inheritable_inspectable::<dim2::collider::BallShape>();
@@ -480,6 +607,9 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
 
     container.insert(EnumPropertyEditorDefinition::<MipFilter>::new());
 
+    container.insert(EnumPropertyEditorDefinition::<TransitionInterruption>::new());
+    container.insert(EnumPropertyEditorDefinition::<LayerBlendMode>::new());
+
     container.register_inheritable_inspectable::<Limb>();
     container.insert(VecCollectionPropertyEditorDefinition::<Limb>::new());
 
@@ -487,6 +617,60 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
 
     container.register_inheritable_inspectable::<Tile>();
     container.register_inheritable_vec_collection::<Tile>();
+    container.register_inheritable_inspectable::<Tiles>();
+    container.register_inheritable_enum::<TileMapProjection, _>();
+
+    container.register_inheritable_inspectable::<Timeline>();
+    container.register_inheritable_inspectable::<TimelineTrack>();
+    container.register_inheritable_vec_collection::<TimelineTrack>();
+    container.register_inheritable_enum::<TrackKind, _>();
+    container.register_inheritable_inspectable::<CameraCutsTrack>();
+    container.register_inheritable_inspectable::<CameraCut>();
+    container.register_inheritable_vec_collection::<CameraCut>();
+    container.register_inheritable_inspectable::<AnimationTrack>();
+    container.register_inheritable_inspectable::<AnimationClip>();
+    container.register_inheritable_vec_collection::<AnimationClip>();
+    container.register_inheritable_inspectable::<AudioTrack>();
+    container.register_inheritable_inspectable::<SoundCue>();
+    container.register_inheritable_vec_collection::<SoundCue>();
+    container.register_inheritable_inspectable::<PropertyTrack>();
+    container.register_inheritable_inspectable::<EventTrack>();
+    container.register_inheritable_inspectable::<EventMarker>();
+    container.register_inheritable_vec_collection::<EventMarker>();
+
+    container.register_inheritable_inspectable::<BlendShapeTarget>();
+    container.register_inheritable_vec_collection::<BlendShapeTarget>();
+    container.register_inheritable_inspectable::<FacialChannel>();
+    container.register_inheritable_vec_collection::<FacialChannel>();
+    container.register_inheritable_inspectable::<ChannelValue>();
+    container.register_inheritable_vec_collection::<ChannelValue>();
+    container.register_inheritable_inspectable::<VisemeKey>();
+    container.register_inheritable_vec_collection::<VisemeKey>();
+    container.register_inheritable_inspectable::<ChannelCurve>();
+    container.register_inheritable_vec_collection::<ChannelCurve>();
+    container.register_inheritable_inspectable::<FacialLayer>();
+    container.register_inheritable_vec_collection::<FacialLayer>();
+    container.register_inheritable_enum::<FacialLayerSource, _>();
+
+    container.register_inheritable_inspectable::<Dialogue>();
+    container.register_inheritable_inspectable::<DialogueNode>();
+    container.register_inheritable_vec_collection::<DialogueNode>();
+    container.register_inheritable_inspectable::<DialogueChoice>();
+    container.register_inheritable_vec_collection::<DialogueChoice>();
+    container.register_inheritable_inspectable::<DialogueCondition>();
+    container.register_inheritable_vec_collection::<DialogueCondition>();
+    container.register_inheritable_enum::<DialogueValue, _>();
+    container.register_inheritable_enum::<ComparisonOperator, _>();
+
+    container.register_inheritable_inspectable::<ItemDefinition>();
+    container.register_inheritable_inspectable::<Inventory>();
+    container.register_inheritable_inspectable::<ItemStack>();
+    container.register_inheritable_vec_collection::<ItemStack>();
+
+    container.register_inheritable_inspectable::<NavmeshBakeSettings>();
+    container.register_inheritable_inspectable::<NavmeshObstacle>();
+    container.register_inheritable_vec_collection::<NavmeshObstacle>();
+    container.register_inheritable_enum::<NavmeshObstacleShape, _>();
 
     container
 }
@@ -16,12 +16,14 @@ use crate::fyrox::{
         grid::{Column, GridBuilder, Row},
         message::{KeyCode, MessageDirection, UiMessage},
         stack_panel::StackPanelBuilder,
+        utils::make_simple_tooltip,
         widget::{WidgetBuilder, WidgetMessage},
         window::{WindowBuilder, WindowMessage, WindowTitle},
         BuildContext, Orientation, Thickness, UiNode, UserInterface,
     },
     gui::{HorizontalAlignment, VerticalAlignment},
     scene::{camera::Camera, navmesh::NavigationalMesh},
+    utils::navmesh::bake::{NavmeshBakeSettings, NavmeshBuilder},
 };
 use crate::scene::SelectionContainer;
 use crate::{
@@ -39,7 +41,7 @@ use crate::{
         commands::{
             navmesh::{
                 AddNavmeshEdgeCommand, ConnectNavmeshEdgesCommand, DeleteNavmeshVertexCommand,
-                MoveNavmeshVertexCommand,
+                MoveNavmeshVertexCommand, SetNavmeshCommand,
             },
             ChangeSelectionCommand,
         },
@@ -57,6 +59,7 @@ pub mod selection;
 pub struct NavmeshPanel {
     pub window: Handle<UiNode>,
     connect_edges: Handle<UiNode>,
+    bake: Handle<UiNode>,
     sender: MessageSender,
     scene_frame: Handle<UiNode>,
 }
@@ -74,20 +77,39 @@ fn fetch_selection(editor_selection: &Selection) -> Option<NavmeshSelection> {
 impl NavmeshPanel {
     pub fn new(scene_frame: Handle<UiNode>, ctx: &mut BuildContext, sender: MessageSender) -> Self {
         let connect_edges;
+        let bake;
         let window = WindowBuilder::new(WidgetBuilder::new().with_name("NavmeshPanel"))
             .open(false)
             .with_title(WindowTitle::text("Navmesh"))
             .with_content(
                 GridBuilder::new(
                     WidgetBuilder::new().with_child(
-                        StackPanelBuilder::new(WidgetBuilder::new().with_child({
-                            connect_edges = ButtonBuilder::new(
-                                WidgetBuilder::new().with_margin(Thickness::uniform(1.0)),
-                            )
-                            .with_text("Connect Edges")
-                            .build(ctx);
-                            connect_edges
-                        }))
+                        StackPanelBuilder::new(
+                            WidgetBuilder::new()
+                                .with_child({
+                                    connect_edges = ButtonBuilder::new(
+                                        WidgetBuilder::new().with_margin(Thickness::uniform(1.0)),
+                                    )
+                                    .with_text("Connect Edges")
+                                    .build(ctx);
+                                    connect_edges
+                                })
+                                .with_child({
+                                    bake = ButtonBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_margin(Thickness::uniform(1.0))
+                                            .with_tooltip(make_simple_tooltip(
+                                                ctx,
+                                                "Bake the navmesh from the scene geometry. \
+                                                Baking parameters could be changed in the \
+                                                editor settings.",
+                                            )),
+                                    )
+                                    .with_text("Bake")
+                                    .build(ctx);
+                                    bake
+                                }),
+                        )
                         .with_orientation(Orientation::Horizontal)
                         .build(ctx),
                     ),
@@ -102,11 +124,19 @@ impl NavmeshPanel {
             window,
             sender,
             connect_edges,
+            bake,
             scene_frame,
         }
     }
 
-    pub fn handle_message(&mut self, message: &UiMessage, editor_selection: &Selection) {
+    pub fn handle_message(
+        &mut self,
+        message: &UiMessage,
+        editor_selection: &Selection,
+        game_scene: &GameScene,
+        engine: &Engine,
+        settings: &Settings,
+    ) {
         scope_profile!();
 
         if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
@@ -129,6 +159,25 @@ impl NavmeshPanel {
                         [vertices[0], vertices[1]],
                     ));
                 }
+            } else if message.destination() == self.bake {
+                if let Some(selection) = fetch_selection(editor_selection) {
+                    let graph = &engine.scenes[game_scene.scene].graph;
+                    if graph
+                        .try_get_of_type::<NavigationalMesh>(selection.navmesh_node())
+                        .is_some()
+                    {
+                        // Editor objects (camera, gizmos, etc.) must not affect the navmesh.
+                        let navmesh = NavmeshBuilder::bake(
+                            graph,
+                            &NavmeshBakeSettings {
+                                sources: vec![game_scene.scene_content_root],
+                                ..settings.navmesh.bake.clone()
+                            },
+                        );
+                        self.sender
+                            .do_command(SetNavmeshCommand::new(selection.navmesh_node(), navmesh));
+                    }
+                }
             }
         }
     }
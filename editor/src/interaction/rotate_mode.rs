@@ -1,13 +1,13 @@
 use crate::command::{Command, CommandGroup};
 use crate::fyrox::{
     core::{
-        algebra::{UnitQuaternion, Vector2},
-        math::round_to_step,
+        algebra::{UnitQuaternion, Vector2, Vector3},
         pool::Handle,
         uuid::{uuid, Uuid},
         TypeUuidProvider,
     },
     gui::{BuildContext, UiNode},
+    utils::placement,
 };
 use crate::scene::SelectionContainer;
 use crate::{
@@ -199,22 +199,19 @@ impl InteractionMode for RotateInteractionMode {
                     let transform = graph[node].local_transform_mut();
                     let rotation = **transform.rotation();
                     let final_rotation = rotation * rotation_delta;
-                    let (mut roll, mut pitch, mut yaw) = final_rotation.euler_angles();
-                    if settings.rotate_mode_settings.angle_snapping {
-                        pitch = round_to_step(
-                            pitch,
-                            settings.rotate_mode_settings.x_snap_step.to_radians(),
-                        );
-                        yaw = round_to_step(
-                            yaw,
-                            settings.rotate_mode_settings.y_snap_step.to_radians(),
-                        );
-                        roll = round_to_step(
-                            roll,
-                            settings.rotate_mode_settings.z_snap_step.to_radians(),
-                        );
-                    }
-                    transform.set_rotation(UnitQuaternion::from_euler_angles(roll, pitch, yaw));
+                    let snap_settings = &settings.rotate_mode_settings;
+                    transform.set_rotation(if snap_settings.angle_snapping {
+                        placement::snap_angles(
+                            final_rotation,
+                            Vector3::new(
+                                snap_settings.x_snap_step.to_radians(),
+                                snap_settings.y_snap_step.to_radians(),
+                                snap_settings.z_snap_step.to_radians(),
+                            ),
+                        )
+                    } else {
+                        final_rotation
+                    });
                 }
             } else {
                 let picked = game_scene
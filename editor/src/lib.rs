@@ -19,6 +19,7 @@ pub mod command;
 pub mod configurator;
 pub mod curve_editor;
 pub mod export;
+pub mod gradient_editor;
 pub mod gui;
 pub mod highlight;
 pub mod inspector;
@@ -109,9 +110,10 @@ use crate::{
             TextureResource, TextureResourceExtension,
         },
         scene::{graph::GraphUpdateSwitches, mesh::Mesh, Scene, SceneLoader},
-        utils::{translate_cursor_icon, translate_event},
+        utils::translate_event,
         window::{Icon, WindowAttributes},
     },
+    gradient_editor::GradientEditorWindow,
     highlight::HighlightRenderPass,
     inspector::Inspector,
     interaction::{
@@ -504,6 +506,7 @@ pub struct Editor {
     pub material_editor: MaterialEditor,
     pub inspector: Inspector,
     pub curve_editor: CurveEditorWindow,
+    pub gradient_editor: GradientEditorWindow,
     pub audio_panel: AudioPanel,
     pub absm_editor: AbsmEditor,
     pub mode: Mode,
@@ -791,6 +794,8 @@ impl Editor {
 
         let curve_editor = CurveEditorWindow::new(ctx);
 
+        let gradient_editor = GradientEditorWindow::new(ctx);
+
         let save_scene_dialog = SaveSceneConfirmationDialog::new(ctx);
 
         let build_window = BuildWindow::new(ctx);
@@ -835,6 +840,7 @@ impl Editor {
             material_editor,
             inspector,
             curve_editor,
+            gradient_editor,
             audio_panel,
             save_scene_dialog,
             mode: Mode::Edit,
@@ -1189,6 +1195,8 @@ impl Editor {
             .handle_ui_message(message, engine, self.message_sender.clone());
         self.command_stack_viewer.handle_ui_message(message);
         self.curve_editor.handle_ui_message(message, engine);
+        self.gradient_editor
+            .handle_ui_message(message, engine.user_interfaces.first());
         self.path_fixer.handle_ui_message(
             message,
             engine.user_interfaces.first_mut(),
@@ -1206,6 +1214,7 @@ impl Editor {
             export_window.handle_ui_message(
                 message,
                 engine.user_interfaces.first_mut(),
+                &engine.resource_manager,
                 &self.message_sender,
             );
         }
@@ -1304,8 +1313,13 @@ impl Editor {
                 self.scene_settings
                     .handle_ui_message(message, &self.message_sender);
 
-                self.navmesh_panel
-                    .handle_message(message, &current_scene_entry.selection);
+                self.navmesh_panel.handle_message(
+                    message,
+                    &current_scene_entry.selection,
+                    game_scene,
+                    engine,
+                    &self.settings,
+                );
 
                 if let Some(interaction_mode) = current_scene_entry
                     .current_interaction_mode
@@ -2265,7 +2279,7 @@ impl Editor {
                     stats.update(game_scene.scene, &self.engine);
                 }
 
-                self.light_panel.update(game_scene, &mut self.engine);
+                self.light_panel.update(&mut self.engine);
                 self.animation_editor.update(
                     &entry.selection,
                     self.engine.user_interfaces.first(),
@@ -2429,6 +2443,9 @@ impl Editor {
                         );
                     }
                     Message::OpenMaterialEditor(material) => self.open_material_editor(material),
+                    Message::OpenGradientEditor(gradient) => self
+                        .gradient_editor
+                        .open(gradient, self.engine.user_interfaces.first()),
                     Message::OpenNodeRemovalDialog => {
                         if let Some(entry) = self.scenes.current_scene_entry_ref() {
                             // TODO
@@ -2976,6 +2993,15 @@ fn update(editor: &mut Editor, window_target: &EventLoopWindowTarget<()>) {
                         definitions.remove(&type_id);
                     }
 
+                    for definitions in editor
+                        .inspector
+                        .property_editors
+                        .named_definitions_mut()
+                        .values_mut()
+                    {
+                        definitions.retain(|_, entry| entry.source_type_id != plugin_type_id);
+                    }
+
                     need_reload_plugins = true;
                 }
             }
@@ -3012,9 +3038,6 @@ fn update(editor: &mut Editor, window_target: &EventLoopWindowTarget<()>) {
     }
 
     let window = &editor.engine.graphics_context.as_initialized_ref().window;
-    window.set_cursor_icon(translate_cursor_icon(
-        editor.engine.user_interfaces.first_mut().cursor(),
-    ));
     window.request_redraw();
 
     if !editor.is_in_preview_mode() {
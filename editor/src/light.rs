@@ -1,5 +1,6 @@
 use crate::fyrox::{
     core::{log::Log, pool::Handle, reflect::prelude::*, scope_profile},
+    engine::task::TaskHandle,
     gui::{
         button::{ButtonBuilder, ButtonMessage},
         formatted_text::WrapMode,
@@ -22,11 +23,7 @@ use crate::{
     inspector::editors::make_property_editors_container, message::MessageSender, scene::GameScene,
     Engine, MSG_SYNC_FLAG,
 };
-use std::{
-    path::PathBuf,
-    sync::mpsc::{Receiver, Sender},
-    sync::Arc,
-};
+use std::{path::PathBuf, sync::Arc};
 
 #[derive(Reflect, Debug)]
 struct LightmapperSettings {
@@ -190,8 +187,7 @@ pub struct LightPanel {
     generate: Handle<UiNode>,
     settings: LightmapperSettings,
     progress_window: Option<ProgressWindow>,
-    sender: Sender<Result<Lightmap, LightmapGenerationError>>,
-    receiver: Receiver<Result<Lightmap, LightmapGenerationError>>,
+    task: Option<TaskHandle>,
 }
 
 impl LightPanel {
@@ -257,16 +253,13 @@ impl LightPanel {
         )
         .build(ctx);
 
-        let (sender, receiver) = std::sync::mpsc::channel();
-
         Self {
             window,
             inspector,
             generate,
             settings,
             progress_window: None,
-            sender,
-            receiver,
+            task: None,
         }
     }
 
@@ -280,62 +273,64 @@ impl LightPanel {
 
         if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
             if message.destination() == self.generate {
-                let scene = &mut engine.scenes[game_scene.scene];
+                let scene_handle = game_scene.scene;
+                let editor_objects_root = game_scene.editor_objects_root;
+                let scene = &engine.scenes[scene_handle];
+                let texels_per_unit = self.settings.texels_per_unit;
+                let spacing = self.settings.spacing;
+                let path = self.settings.path.clone();
+                let resource_manager = engine.resource_manager.clone();
 
-                let progress_indicator = ProgressIndicator::new();
-                let cancellation_token = CancellationToken::new();
-
-                let progress_window = ProgressWindow::new(
-                    &mut engine.user_interfaces.first_mut().build_ctx(),
-                    progress_indicator.clone(),
-                    cancellation_token.clone(),
-                );
-                progress_window.open(engine.user_interfaces.first());
-                self.progress_window = Some(progress_window);
-
-                if let Ok(input_data) = LightmapInputData::from_scene(
-                    scene,
-                    |handle, _| handle != game_scene.editor_objects_root,
-                    cancellation_token.clone(),
-                    progress_indicator.clone(),
-                ) {
-                    let sender = self.sender.clone();
-                    let texels_per_unit = self.settings.texels_per_unit;
-                    let spacing = self.settings.spacing;
-                    let path = self.settings.path.clone();
-                    let resource_manager = engine.resource_manager.clone();
-
-                    if let Err(e) = std::thread::Builder::new()
-                        .name("LightmapGenerationThread".to_string())
-                        .spawn(move || {
-                            match Lightmap::new(
-                                input_data,
+                let task = engine.task_pool.spawn_engine_task(
+                    "Lightmap Generation",
+                    |cancellation_token, progress_indicator| {
+                        // Input data is gathered on the main thread, because it needs the scene.
+                        let input_data = LightmapInputData::from_scene(
+                            scene,
+                            |handle, _| handle != editor_objects_root,
+                            cancellation_token.clone(),
+                            progress_indicator.clone(),
+                        );
+                        async move {
+                            let lightmap = Lightmap::new(
+                                input_data?,
                                 texels_per_unit,
                                 spacing,
                                 cancellation_token,
                                 progress_indicator,
-                            ) {
-                                Ok(lightmap) => {
-                                    if lightmap.save_textures(path, resource_manager).is_err() {
-                                        sender
-                                            .send(Err(LightmapGenerationError::Cancelled))
-                                            .unwrap();
-                                    } else {
-                                        sender.send(Ok(lightmap)).unwrap();
-                                    }
-                                }
-                                Err(err) => {
-                                    sender.send(Err(err)).unwrap();
+                            )?;
+                            if lightmap.save_textures(path, resource_manager).is_err() {
+                                Err(LightmapGenerationError::Cancelled)
+                            } else {
+                                Ok(lightmap)
+                            }
+                        }
+                    },
+                    move |result, ctx| match result {
+                        Ok(lightmap) => {
+                            if let Some(scene) = ctx.scenes.try_get_mut(scene_handle) {
+                                if let Err(err) = scene.graph.set_lightmap(lightmap) {
+                                    Log::err(format!(
+                                        "Failed to set generated lightmap. Reason: {}",
+                                        err
+                                    ));
                                 }
                             }
-                        })
-                    {
-                        Log::err(format!(
-                            "Failed to create a new lightmap generation thread. Reason: {}",
-                            e
-                        ))
-                    }
-                }
+                        }
+                        Err(err) => {
+                            Log::err(format!("Failed to generated a lightmap. Reason: {}", err));
+                        }
+                    },
+                );
+
+                let progress_window = ProgressWindow::new(
+                    &mut engine.user_interfaces.first_mut().build_ctx(),
+                    task.progress_indicator().clone(),
+                    task.cancellation_token().clone(),
+                );
+                progress_window.open(engine.user_interfaces.first());
+                self.progress_window = Some(progress_window);
+                self.task = Some(task);
             }
 
             if let Some(progress_window) = self.progress_window.as_ref() {
@@ -358,23 +353,14 @@ impl LightPanel {
         }
     }
 
-    pub fn update(&mut self, game_scene: &GameScene, engine: &mut Engine) {
+    pub fn update(&mut self, engine: &mut Engine) {
         if let Some(progress_window) = self.progress_window.as_ref() {
             progress_window.show_progress(engine.user_interfaces.first());
         }
 
-        if let Ok(result) = self.receiver.try_recv() {
-            let scene = &mut engine.scenes[game_scene.scene];
-            match result {
-                Ok(lightmap) => {
-                    if let Err(err) = scene.graph.set_lightmap(lightmap) {
-                        Log::err(format!("Failed to set generated lightmap. Reason: {}", err));
-                    }
-                }
-                Err(err) => {
-                    Log::err(format!("Failed to generated a lightmap. Reason: {}", err));
-                }
-            }
+        // The result of the task is applied to the scene by the engine.
+        if self.task.as_ref().is_some_and(|task| task.is_finished()) {
+            self.task = None;
 
             if let Some(progress_window) = self.progress_window.take() {
                 progress_window.close(engine.user_interfaces.first());
@@ -2,9 +2,10 @@ use crate::fyrox::{
     core::pool::Handle,
     gui::{menu::MenuItemMessage, message::UiMessage, BuildContext, UiNode},
     scene::{
-        animation::{absm::prelude::*, prelude::*},
+        animation::{absm::prelude::*, facial::FacialAnimatorBuilder, prelude::*},
         base::BaseBuilder,
         node::Node,
+        timeline::TimelinePlayerBuilder,
     },
 };
 use crate::menu::create_menu_item;
@@ -13,12 +14,16 @@ pub struct AnimationMenu {
     pub menu: Handle<UiNode>,
     create_animation_player: Handle<UiNode>,
     create_absm: Handle<UiNode>,
+    create_timeline_player: Handle<UiNode>,
+    create_facial_animator: Handle<UiNode>,
 }
 
 impl AnimationMenu {
     pub fn new(ctx: &mut BuildContext) -> Self {
         let create_animation_player;
         let create_absm;
+        let create_timeline_player;
+        let create_facial_animator;
 
         let menu = create_menu_item(
             "Animation",
@@ -31,6 +36,14 @@ impl AnimationMenu {
                     create_absm = create_menu_item("Animation Blending State Machine", vec![], ctx);
                     create_absm
                 },
+                {
+                    create_timeline_player = create_menu_item("Timeline Player", vec![], ctx);
+                    create_timeline_player
+                },
+                {
+                    create_facial_animator = create_menu_item("Facial Animator", vec![], ctx);
+                    create_facial_animator
+                },
             ],
             ctx,
         );
@@ -39,6 +52,8 @@ impl AnimationMenu {
             menu,
             create_animation_player,
             create_absm,
+            create_timeline_player,
+            create_facial_animator,
         }
     }
 
@@ -63,6 +78,16 @@ impl AnimationMenu {
                 .with_machine(machine)
                 .build_node();
                 Some(node)
+            } else if message.destination() == self.create_timeline_player {
+                let node =
+                    TimelinePlayerBuilder::new(BaseBuilder::new().with_name("Timeline Player"))
+                        .build_node();
+                Some(node)
+            } else if message.destination() == self.create_facial_animator {
+                let node =
+                    FacialAnimatorBuilder::new(BaseBuilder::new().with_name("Facial Animator"))
+                        .build_node();
+                Some(node)
             } else {
                 None
             }
@@ -4,7 +4,7 @@ use crate::{
     fyrox::{
         asset::untyped::ResourceKind,
         core::{
-            algebra::{Matrix4, Vector3},
+            algebra::{Matrix4, Vector2, Vector3},
             math::TriangleDefinition,
             pool::Handle,
         },
@@ -15,6 +15,7 @@ use crate::{
         material::{Material, MaterialResource},
         resource::texture::PLACEHOLDER,
         scene::{
+            ao_volume::AoVolumeBuilder,
             base::BaseBuilder,
             camera::CameraBuilder,
             decal::DecalBuilder,
@@ -23,19 +24,27 @@ use crate::{
                 spot::SpotLightBuilder, BaseLightBuilder,
             },
             mesh::{
+                primitives::{UvMapping, WallOpening},
                 surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
                 MeshBuilder,
             },
             navmesh::NavigationalMeshBuilder,
             node::Node,
+            ocean::OceanBuilder,
             particle_system::{
                 emitter::{base::BaseEmitterBuilder, sphere::SphereEmitterBuilder},
                 ParticleSystemBuilder,
             },
             pivot::PivotBuilder,
-            sound::{listener::ListenerBuilder, SoundBuilder},
+            portal::PortalBuilder,
+            probe_volume::ProbeVolumeBuilder,
+            smoke::SmokeBuilder,
+            sound::{listener::ListenerBuilder, reverb_zone::ReverbZoneBuilder, SoundBuilder},
             sprite::SpriteBuilder,
+            stencil_mask::StencilMaskBuilder,
             terrain::{Layer, TerrainBuilder},
+            transform::TransformBuilder,
+            vector_shape::VectorShapeBuilder,
         },
         utils::navmesh::Navmesh,
     },
@@ -120,7 +129,19 @@ pub struct CreateEntityMenu {
     create_sphere: Handle<UiNode>,
     create_cylinder: Handle<UiNode>,
     create_quad: Handle<UiNode>,
+    create_stairs: Handle<UiNode>,
+    create_ramp: Handle<UiNode>,
+    create_arch: Handle<UiNode>,
+    create_pipe: Handle<UiNode>,
+    create_wall: Handle<UiNode>,
     create_decal: Handle<UiNode>,
+    create_smoke: Handle<UiNode>,
+    create_ocean: Handle<UiNode>,
+    create_portal: Handle<UiNode>,
+    create_probe_volume: Handle<UiNode>,
+    create_ao_volume: Handle<UiNode>,
+    create_stencil_mask: Handle<UiNode>,
+    create_vector_shape: Handle<UiNode>,
     create_point_light: Handle<UiNode>,
     create_spot_light: Handle<UiNode>,
     create_directional_light: Handle<UiNode>,
@@ -130,6 +151,7 @@ pub struct CreateEntityMenu {
     create_sprite: Handle<UiNode>,
     create_particle_system: Handle<UiNode>,
     create_listener: Handle<UiNode>,
+    create_reverb_zone: Handle<UiNode>,
     create_sound_source: Handle<UiNode>,
     physics_menu: PhysicsMenu,
     physics2d_menu: Physics2dMenu,
@@ -148,6 +170,17 @@ fn placeholder_material() -> MaterialResource {
     MaterialResource::new_ok(ResourceKind::Embedded, material)
 }
 
+fn blockout_mesh(name: &str, data: SurfaceData) -> Node {
+    MeshBuilder::new(BaseBuilder::new().with_name(name))
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            data,
+        ))
+        .with_material(placeholder_material())
+        .build()])
+        .build_node()
+}
+
 impl CreateEntityMenu {
     pub fn new(ctx: &mut BuildContext) -> (Self, Vec<Handle<UiNode>>) {
         let create_cube;
@@ -155,18 +188,31 @@ impl CreateEntityMenu {
         let create_sphere;
         let create_cylinder;
         let create_quad;
+        let create_stairs;
+        let create_ramp;
+        let create_arch;
+        let create_pipe;
+        let create_wall;
         let create_point_light;
         let create_spot_light;
         let create_directional_light;
         let create_camera;
         let create_sprite;
         let create_decal;
+        let create_smoke;
+        let create_ocean;
+        let create_portal;
+        let create_probe_volume;
+        let create_ao_volume;
+        let create_stencil_mask;
+        let create_vector_shape;
         let create_navmesh;
         let create_particle_system;
         let create_terrain;
         let create_pivot;
         let create_sound_source;
         let create_listener;
+        let create_reverb_zone;
         let physics_menu = PhysicsMenu::new(ctx);
         let physics2d_menu = Physics2dMenu::new(ctx);
         let dim2_menu = Dim2Menu::new(ctx);
@@ -207,6 +253,32 @@ impl CreateEntityMenu {
                             create_quad = create_menu_item("Quad", vec![], ctx);
                             create_quad
                         },
+                        create_menu_item(
+                            "Blockout",
+                            vec![
+                                {
+                                    create_stairs = create_menu_item("Stairs", vec![], ctx);
+                                    create_stairs
+                                },
+                                {
+                                    create_ramp = create_menu_item("Ramp", vec![], ctx);
+                                    create_ramp
+                                },
+                                {
+                                    create_arch = create_menu_item("Arch", vec![], ctx);
+                                    create_arch
+                                },
+                                {
+                                    create_pipe = create_menu_item("Pipe", vec![], ctx);
+                                    create_pipe
+                                },
+                                {
+                                    create_wall = create_menu_item("Wall", vec![], ctx);
+                                    create_wall
+                                },
+                            ],
+                            ctx,
+                        ),
                     ],
                     ctx,
                 );
@@ -224,6 +296,10 @@ impl CreateEntityMenu {
                             create_listener = create_menu_item("Listener", vec![], ctx);
                             create_listener
                         },
+                        {
+                            create_reverb_zone = create_menu_item("Reverb Zone", vec![], ctx);
+                            create_reverb_zone
+                        },
                     ],
                     ctx,
                 );
@@ -275,6 +351,34 @@ impl CreateEntityMenu {
                 create_decal = create_menu_item("Decal", vec![], ctx);
                 create_decal
             },
+            {
+                create_smoke = create_menu_item("Smoke", vec![], ctx);
+                create_smoke
+            },
+            {
+                create_ocean = create_menu_item("Ocean", vec![], ctx);
+                create_ocean
+            },
+            {
+                create_portal = create_menu_item("Portal", vec![], ctx);
+                create_portal
+            },
+            {
+                create_probe_volume = create_menu_item("Probe Volume", vec![], ctx);
+                create_probe_volume
+            },
+            {
+                create_ao_volume = create_menu_item("AO Volume", vec![], ctx);
+                create_ao_volume
+            },
+            {
+                create_stencil_mask = create_menu_item("Stencil Mask", vec![], ctx);
+                create_stencil_mask
+            },
+            {
+                create_vector_shape = create_menu_item("Vector Shape", vec![], ctx);
+                create_vector_shape
+            },
             {
                 create_navmesh = create_menu_item("Navmesh", vec![], ctx);
                 create_navmesh
@@ -288,6 +392,11 @@ impl CreateEntityMenu {
                 create_sphere,
                 create_cylinder,
                 create_quad,
+                create_stairs,
+                create_ramp,
+                create_arch,
+                create_pipe,
+                create_wall,
                 create_point_light,
                 create_spot_light,
                 create_directional_light,
@@ -298,8 +407,16 @@ impl CreateEntityMenu {
                 create_terrain,
                 create_sound_source,
                 create_listener,
+                create_reverb_zone,
                 create_navmesh,
                 create_decal,
+                create_smoke,
+                create_ocean,
+                create_portal,
+                create_probe_volume,
+                create_ao_volume,
+                create_stencil_mask,
+                create_vector_shape,
                 physics_menu,
                 physics2d_menu,
                 dim2_menu,
@@ -333,6 +450,13 @@ impl CreateEntityMenu {
             self.sound_menu,
             self.create_navmesh,
             self.create_decal,
+            self.create_smoke,
+            self.create_ocean,
+            self.create_portal,
+            self.create_probe_volume,
+            self.create_ao_volume,
+            self.create_stencil_mask,
+            self.create_vector_shape,
             self.physics_menu.menu,
             self.physics2d_menu.menu,
             self.dim2_menu.menu,
@@ -453,6 +577,69 @@ impl CreateEntityMenu {
                                 .build()])
                                 .build_node(),
                         )
+                    } else if message.destination() == self.create_stairs {
+                        Some(blockout_mesh(
+                            "Stairs",
+                            SurfaceData::make_stairs(
+                                1.0,
+                                1.0,
+                                2.0,
+                                6,
+                                UvMapping::default(),
+                                &Matrix4::identity(),
+                            ),
+                        ))
+                    } else if message.destination() == self.create_ramp {
+                        Some(blockout_mesh(
+                            "Ramp",
+                            SurfaceData::make_ramp(
+                                1.0,
+                                1.0,
+                                2.0,
+                                UvMapping::default(),
+                                &Matrix4::identity(),
+                            ),
+                        ))
+                    } else if message.destination() == self.create_arch {
+                        Some(blockout_mesh(
+                            "Arch",
+                            SurfaceData::make_arch(
+                                2.0,
+                                2.5,
+                                0.25,
+                                0.5,
+                                16,
+                                UvMapping::default(),
+                                &Matrix4::identity(),
+                            ),
+                        ))
+                    } else if message.destination() == self.create_pipe {
+                        Some(blockout_mesh(
+                            "Pipe",
+                            SurfaceData::make_pipe(
+                                16,
+                                0.5,
+                                0.4,
+                                2.0,
+                                UvMapping::default(),
+                                &Matrix4::identity(),
+                            ),
+                        ))
+                    } else if message.destination() == self.create_wall {
+                        Some(blockout_mesh(
+                            "Wall",
+                            SurfaceData::make_wall(
+                                4.0,
+                                3.0,
+                                0.2,
+                                &[WallOpening {
+                                    position: Vector2::new(1.5, 0.0),
+                                    size: Vector2::new(1.0, 2.0),
+                                }],
+                                UvMapping::default(),
+                                &Matrix4::identity(),
+                            ),
+                        ))
                     } else if message.destination() == self.create_camera {
                         Some(
                             CameraBuilder::new(BaseBuilder::new().with_name("Camera")).build_node(),
@@ -503,11 +690,60 @@ impl CreateEntityMenu {
                         )
                     } else if message.destination() == self.create_decal {
                         Some(DecalBuilder::new(BaseBuilder::new().with_name("Decal")).build_node())
+                    } else if message.destination() == self.create_smoke {
+                        Some(SmokeBuilder::new(BaseBuilder::new().with_name("Smoke")).build_node())
+                    } else if message.destination() == self.create_ocean {
+                        Some(OceanBuilder::new(BaseBuilder::new().with_name("Ocean")).build_node())
+                    } else if message.destination() == self.create_portal {
+                        Some(
+                            PortalBuilder::new(BaseBuilder::new().with_name("Portal")).build_node(),
+                        )
+                    } else if message.destination() == self.create_probe_volume {
+                        Some(
+                            ProbeVolumeBuilder::new(
+                                BaseBuilder::new()
+                                    .with_name("ProbeVolume")
+                                    .with_local_transform(
+                                        TransformBuilder::new()
+                                            .with_local_scale(Vector3::new(16.0, 8.0, 16.0))
+                                            .build(),
+                                    ),
+                            )
+                            .build_node(),
+                        )
+                    } else if message.destination() == self.create_ao_volume {
+                        Some(
+                            AoVolumeBuilder::new(
+                                BaseBuilder::new()
+                                    .with_name("AoVolume")
+                                    .with_local_transform(
+                                        TransformBuilder::new()
+                                            .with_local_scale(Vector3::new(16.0, 8.0, 16.0))
+                                            .build(),
+                                    ),
+                            )
+                            .build_node(),
+                        )
+                    } else if message.destination() == self.create_stencil_mask {
+                        Some(
+                            StencilMaskBuilder::new(BaseBuilder::new().with_name("StencilMask"))
+                                .build_node(),
+                        )
+                    } else if message.destination() == self.create_vector_shape {
+                        Some(
+                            VectorShapeBuilder::new(BaseBuilder::new().with_name("VectorShape"))
+                                .build_node(),
+                        )
                     } else if message.destination() == self.create_listener {
                         Some(
                             ListenerBuilder::new(BaseBuilder::new().with_name("Listener"))
                                 .build_node(),
                         )
+                    } else if message.destination() == self.create_reverb_zone {
+                        Some(
+                            ReverbZoneBuilder::new(BaseBuilder::new().with_name("ReverbZone"))
+                                .build_node(),
+                        )
                     } else {
                         None
                     }
@@ -3,7 +3,9 @@ use crate::{
         core::pool::Handle,
         gui::{menu::MenuItemMessage, message::UiMessage, BuildContext, UiNode},
         scene::{
-            base::BaseBuilder, dim2::rectangle::RectangleBuilder, node::Node,
+            base::BaseBuilder,
+            dim2::{rectangle::RectangleBuilder, skinned_sprite::SkinnedSpriteBuilder},
+            node::Node,
             tilemap::TileMapBuilder,
         },
     },
@@ -14,12 +16,14 @@ pub struct Dim2Menu {
     pub menu: Handle<UiNode>,
     create_sprite: Handle<UiNode>,
     create_tile_map: Handle<UiNode>,
+    create_skinned_sprite: Handle<UiNode>,
 }
 
 impl Dim2Menu {
     pub fn new(ctx: &mut BuildContext) -> Self {
         let create_sprite;
         let create_tile_map;
+        let create_skinned_sprite;
 
         let menu = create_menu_item(
             "2D",
@@ -32,6 +36,10 @@ impl Dim2Menu {
                     create_tile_map = create_menu_item("Tile Map", vec![], ctx);
                     create_tile_map
                 },
+                {
+                    create_skinned_sprite = create_menu_item("Skinned Sprite", vec![], ctx);
+                    create_skinned_sprite
+                },
             ],
             ctx,
         );
@@ -40,6 +48,7 @@ impl Dim2Menu {
             menu,
             create_sprite,
             create_tile_map,
+            create_skinned_sprite,
         }
     }
 
@@ -53,6 +62,11 @@ impl Dim2Menu {
                 let node =
                     TileMapBuilder::new(BaseBuilder::new().with_name("Tile Map")).build_node();
                 Some(node)
+            } else if message.destination() == self.create_skinned_sprite {
+                let node =
+                    SkinnedSpriteBuilder::new(BaseBuilder::new().with_name("Skinned Sprite"))
+                        .build_node();
+                Some(node)
             } else {
                 None
             }
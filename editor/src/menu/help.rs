@@ -1,6 +1,7 @@
 use crate::fyrox::{
     core::{log::Log, pool::Handle},
     gui::{menu::MenuItemMessage, message::UiMessage, BuildContext, UiNode},
+    platform,
 };
 use crate::menu::{create_menu_item, create_root_menu_item};
 
@@ -39,9 +40,9 @@ impl HelpMenu {
     pub fn handle_ui_message(&mut self, message: &UiMessage) {
         if let Some(MenuItemMessage::Click) = message.data::<MenuItemMessage>() {
             if message.destination() == self.open_book {
-                Log::verify(open::that("https://fyrox-book.github.io"));
+                Log::verify(platform::open_url("https://fyrox-book.github.io"));
             } else if message.destination() == self.open_api_reference {
-                Log::verify(open::that("https://docs.rs/fyrox/latest"));
+                Log::verify(platform::open_url("https://docs.rs/fyrox/latest"));
             }
         }
     }
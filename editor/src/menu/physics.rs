@@ -2,8 +2,8 @@ use crate::fyrox::{
     core::pool::Handle,
     gui::{menu::MenuItemMessage, message::UiMessage, BuildContext, UiNode},
     scene::{
-        base::BaseBuilder, collider::*, joint::*, node::Node, ragdoll::RagdollBuilder,
-        rigidbody::RigidBodyBuilder,
+        base::BaseBuilder, collider::*, joint::*, node::Node, ocean::buoyancy::BuoyancyBuilder,
+        ragdoll::RagdollBuilder, rigidbody::RigidBodyBuilder,
     },
 };
 use crate::menu::create_menu_item;
@@ -17,6 +17,7 @@ pub struct PhysicsMenu {
     create_fixed_joint: Handle<UiNode>,
     create_collider: Handle<UiNode>,
     create_ragdoll: Handle<UiNode>,
+    create_buoyancy: Handle<UiNode>,
 }
 
 impl PhysicsMenu {
@@ -28,6 +29,7 @@ impl PhysicsMenu {
         let create_prismatic_joint;
         let create_fixed_joint;
         let create_ragdoll;
+        let create_buoyancy;
         let menu = create_menu_item(
             "Physics",
             vec![
@@ -59,6 +61,10 @@ impl PhysicsMenu {
                     create_ragdoll = create_menu_item("Ragdoll", vec![], ctx);
                     create_ragdoll
                 },
+                {
+                    create_buoyancy = create_menu_item("Buoyancy", vec![], ctx);
+                    create_buoyancy
+                },
             ],
             ctx,
         );
@@ -72,6 +78,7 @@ impl PhysicsMenu {
             create_fixed_joint,
             create_collider,
             create_ragdoll,
+            create_buoyancy,
         }
     }
 
@@ -111,6 +118,8 @@ impl PhysicsMenu {
                 )
             } else if message.destination == self.create_ragdoll {
                 Some(RagdollBuilder::new(BaseBuilder::new().with_name("Ragdoll")).build_node())
+            } else if message.destination == self.create_buoyancy {
+                Some(BuoyancyBuilder::new(BaseBuilder::new().with_name("Buoyancy")).build_node())
             } else {
                 None
             }
@@ -15,6 +15,7 @@ pub struct Physics2dMenu {
     create_ball_joint: Handle<UiNode>,
     create_prismatic_joint: Handle<UiNode>,
     create_fixed_joint: Handle<UiNode>,
+    create_wheel_joint: Handle<UiNode>,
     create_collider: Handle<UiNode>,
 }
 
@@ -25,6 +26,7 @@ impl Physics2dMenu {
         let create_ball_joint;
         let create_prismatic_joint;
         let create_fixed_joint;
+        let create_wheel_joint;
         let menu = create_menu_item(
             "Physics 2D",
             vec![
@@ -48,6 +50,10 @@ impl Physics2dMenu {
                     create_fixed_joint = create_menu_item("Fixed Joint", vec![], ctx);
                     create_fixed_joint
                 },
+                {
+                    create_wheel_joint = create_menu_item("Wheel Joint", vec![], ctx);
+                    create_wheel_joint
+                },
             ],
             ctx,
         );
@@ -58,6 +64,7 @@ impl Physics2dMenu {
             create_ball_joint,
             create_prismatic_joint,
             create_fixed_joint,
+            create_wheel_joint,
             create_collider,
         }
     }
@@ -87,6 +94,12 @@ impl Physics2dMenu {
                         .with_params(JointParams::FixedJoint(Default::default()))
                         .build_node(),
                 )
+            } else if message.destination() == self.create_wheel_joint {
+                Some(
+                    JointBuilder::new(BaseBuilder::new().with_name("Wheel Joint 2D"))
+                        .with_params(JointParams::WheelJoint(Default::default()))
+                        .build_node(),
+                )
             } else if message.destination == self.create_collider {
                 Some(
                     ColliderBuilder::new(BaseBuilder::new().with_name("Collider 2D"))
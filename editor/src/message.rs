@@ -8,6 +8,7 @@ use crate::{
         },
         gui::UiNode,
         material::MaterialResource,
+        resource::gradient::ColorGradientResource,
         scene::{camera::Projection, mesh::surface::SurfaceResource, node::Node},
     },
     scene::Selection,
@@ -45,6 +46,7 @@ pub enum Message {
     OpenAbsmEditor,
     OpenMaterialEditor(MaterialResource),
     OpenTileSetEditor(TileSetResource),
+    OpenGradientEditor(ColorGradientResource),
     OpenNodeRemovalDialog,
     ShowInAssetBrowser(PathBuf),
     LocateObject {
@@ -211,6 +211,7 @@ impl GameScene {
                 // Update only editor's camera.
                 node_overrides: Some(Default::default()),
                 paused: false,
+                parallel_animation: false,
             },
             sender,
             camera_state: Default::default(),
@@ -4,7 +4,9 @@ use crate::fyrox::{
     gui::{
         inspector::{
             editors::{
-                enumeration::EnumPropertyEditorDefinition, PropertyEditorDefinitionContainer,
+                enumeration::EnumPropertyEditorDefinition,
+                inspectable::InspectablePropertyEditorDefinition,
+                PropertyEditorDefinitionContainer,
             },
             InspectorBuilder, InspectorContext, InspectorMessage, PropertyFilter,
         },
@@ -16,12 +18,14 @@ use crate::fyrox::{
     },
     resource::texture::TextureResource,
     scene::{
+        day_night::DayNightCycle,
         dim2,
         graph::{
             physics::{IntegrationParameters, PhysicsWorld},
-            Graph, NodePool,
+            Graph, NodePool, PhysicsMode,
         },
-        SceneRenderingOptions,
+        weather::{Precipitation, PrecipitationKind, Weather, Wind},
+        RenderTargetQuality, RenderTargetUpdateMode, SceneComposition, SceneRenderingOptions,
     },
     utils::lightmap::Lightmap,
 };
@@ -65,7 +69,16 @@ impl SceneSettingsWindow {
         container.register_inheritable_inspectable::<PhysicsWorld>();
         container.register_inheritable_inspectable::<dim2::physics::PhysicsWorld>();
         container.register_inheritable_inspectable::<SceneRenderingOptions>();
+        container.insert(EnumPropertyEditorDefinition::<PhysicsMode>::new());
+        container.insert(EnumPropertyEditorDefinition::<RenderTargetUpdateMode>::new());
+        container.insert(InspectablePropertyEditorDefinition::<RenderTargetQuality>::new());
         container.insert(EnumPropertyEditorDefinition::<Color>::new_optional());
+        container.insert(InspectablePropertyEditorDefinition::<Weather>::new());
+        container.insert(InspectablePropertyEditorDefinition::<Precipitation>::new());
+        container.insert(InspectablePropertyEditorDefinition::<Wind>::new());
+        container.insert(EnumPropertyEditorDefinition::<PrecipitationKind>::new());
+        container.insert(InspectablePropertyEditorDefinition::<DayNightCycle>::new());
+        container.insert(InspectablePropertyEditorDefinition::<SceneComposition>::new());
 
         Self {
             window,
@@ -2,6 +2,7 @@ use crate::{
     fyrox::{
         core::{color::Color, math::Rect, pool::Handle, uuid::Uuid},
         engine::Engine,
+        engine::GraphicsContext,
         fxhash::FxHashMap,
         graph::{BaseSceneGraph, SceneGraph, SceneGraphNode},
         gui::{
@@ -30,7 +31,7 @@ use crate::{
             BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
             VerticalAlignment, BRUSH_BRIGHT_BLUE, BRUSH_DARKEST,
         },
-        renderer::framework::state::PolygonFillMode,
+        renderer::{debug_view::DebugViewMode, framework::state::PolygonFillMode},
         resource::texture::TextureResource,
         scene::camera::Projection,
     },
@@ -63,6 +64,30 @@ pub enum GraphicsDebugSwitches {
     #[default]
     Shaded,
     Wireframe,
+    WireframeOverlay,
+    Overdraw,
+    LightingOnly,
+    Normals,
+    RoughnessMetallic,
+    MipLevel,
+    ShadowCascades,
+}
+
+impl GraphicsDebugSwitches {
+    fn debug_view_mode(&self) -> DebugViewMode {
+        match self {
+            GraphicsDebugSwitches::Shaded | GraphicsDebugSwitches::Wireframe => {
+                DebugViewMode::Shaded
+            }
+            GraphicsDebugSwitches::WireframeOverlay => DebugViewMode::Wireframe,
+            GraphicsDebugSwitches::Overdraw => DebugViewMode::Overdraw,
+            GraphicsDebugSwitches::LightingOnly => DebugViewMode::LightingOnly,
+            GraphicsDebugSwitches::Normals => DebugViewMode::Normals,
+            GraphicsDebugSwitches::RoughnessMetallic => DebugViewMode::RoughnessMetallic,
+            GraphicsDebugSwitches::MipLevel => DebugViewMode::MipLevel,
+            GraphicsDebugSwitches::ShadowCascades => DebugViewMode::ShadowCascades,
+        }
+    }
 }
 
 struct GridSnappingMenu {
@@ -691,15 +716,18 @@ impl SceneViewer {
                                     entry.controller.downcast_ref::<GameScene>()
                                 {
                                     let scene = &mut engine.scenes[game_scene.scene];
-                                    match variant {
-                                        GraphicsDebugSwitches::Shaded => {
-                                            scene.rendering_options.polygon_rasterization_mode =
-                                                PolygonFillMode::Fill;
-                                        }
-                                        GraphicsDebugSwitches::Wireframe => {
-                                            scene.rendering_options.polygon_rasterization_mode =
-                                                PolygonFillMode::Line;
-                                        }
+                                    scene.rendering_options.polygon_rasterization_mode =
+                                        if let GraphicsDebugSwitches::Wireframe = variant {
+                                            PolygonFillMode::Line
+                                        } else {
+                                            PolygonFillMode::Fill
+                                        };
+                                    if let GraphicsContext::Initialized(ref mut graphics_context) =
+                                        engine.graphics_context
+                                    {
+                                        graphics_context
+                                            .renderer
+                                            .set_debug_view(variant.debug_view_mode());
                                     }
                                 }
                             }
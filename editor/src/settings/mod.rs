@@ -21,7 +21,10 @@ use crate::{
             window::{WindowBuilder, WindowMessage, WindowTitle},
             HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
         },
-        renderer::{CsmSettings, QualitySettings, ShadowMapPrecision},
+        renderer::{
+            AmbientOcclusionMode, CsmSettings, QualitySettings, ShadowMapPrecision, SsrSettings,
+        },
+        scene::light::directional::FrustumSplitOptions,
     },
     inspector::editors::make_property_editors_container,
     message::MessageSender,
@@ -210,9 +213,12 @@ impl SettingsData {
         container.insert(InspectablePropertyEditorDefinition::<GraphicsSettings>::new());
         container.insert(InspectablePropertyEditorDefinition::<SelectionSettings>::new());
         container.insert(EnumPropertyEditorDefinition::<ShadowMapPrecision>::new());
+        container.insert(EnumPropertyEditorDefinition::<AmbientOcclusionMode>::new());
         container.insert(EnumPropertyEditorDefinition::<ScriptEditor>::new());
         container.insert(InspectablePropertyEditorDefinition::<DebuggingSettings>::new());
         container.insert(InspectablePropertyEditorDefinition::<CsmSettings>::new());
+        container.insert(InspectablePropertyEditorDefinition::<SsrSettings>::new());
+        container.insert(EnumPropertyEditorDefinition::<FrustumSplitOptions>::new_optional());
         container.insert(InspectablePropertyEditorDefinition::<QualitySettings>::new());
         container.insert(InspectablePropertyEditorDefinition::<CameraSettings>::new());
         container.insert(InspectablePropertyEditorDefinition::<
@@ -1,4 +1,7 @@
-use crate::fyrox::core::{algebra::Vector3, math, reflect::prelude::*};
+use crate::fyrox::{
+    core::{algebra::Vector3, reflect::prelude::*},
+    utils::placement,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, PartialEq, Debug, Clone, Reflect)]
@@ -23,10 +26,9 @@ impl Default for MoveInteractionModeSettings {
 impl MoveInteractionModeSettings {
     pub fn try_snap_vector_to_grid(&self, v: Vector3<f32>) -> Vector3<f32> {
         if self.grid_snapping {
-            Vector3::new(
-                math::round_to_step(v.x, self.x_snap_step),
-                math::round_to_step(v.y, self.y_snap_step),
-                math::round_to_step(v.z, self.z_snap_step),
+            placement::snap_to_grid(
+                v,
+                Vector3::new(self.x_snap_step, self.y_snap_step, self.z_snap_step),
             )
         } else {
             v
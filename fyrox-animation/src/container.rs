@@ -4,7 +4,7 @@
 use crate::{
     core::{
         algebra::{Vector2, Vector3, Vector4},
-        math::curve::Curve,
+        math::curve::{Curve, CurveKey, CurveKeyKind},
         math::{quat_from_euler, RotationOrder},
         reflect::prelude::*,
         visitor::prelude::*,
@@ -182,6 +182,38 @@ impl TrackDataContainer {
         }
     }
 
+    /// Adds a key with the given value to the curves of the container at the given location. Each component
+    /// of the value is added to its respective curve, rotations are converted to Euler angles (`XYZ` order).
+    /// Returns `false` if the value does not match the kind of the container.
+    pub fn add_value_key(
+        &mut self,
+        location: f32,
+        value: &TrackValue,
+        key_kind: CurveKeyKind,
+    ) -> bool {
+        let components = match (self.kind, value) {
+            (TrackValueKind::Real, TrackValue::Real(v)) => vec![*v],
+            (TrackValueKind::Vector2, TrackValue::Vector2(v)) => v.as_slice().to_vec(),
+            (TrackValueKind::Vector3, TrackValue::Vector3(v)) => v.as_slice().to_vec(),
+            (TrackValueKind::Vector4, TrackValue::Vector4(v)) => v.as_slice().to_vec(),
+            (TrackValueKind::UnitQuaternion, TrackValue::UnitQuaternion(v)) => {
+                let (x, y, z) = v.euler_angles();
+                vec![x, y, z]
+            }
+            _ => return false,
+        };
+
+        if self.curves.len() < components.len() {
+            return false;
+        }
+
+        for (curve, component) in self.curves.iter_mut().zip(components) {
+            curve.add_key(CurveKey::new(location, component, key_kind.clone()));
+        }
+
+        true
+    }
+
     /// Find a right-most key on one of the curves in the container and returns its position. This position
     /// can be treated as a maximum "length" of the container.
     pub fn time_length(&self) -> f32 {
@@ -26,6 +26,7 @@ use std::{
 pub use fyrox_core as core;
 use fyrox_core::pool::ErasedHandle;
 use fyrox_core::uuid::uuid;
+pub use fyrox_resource as resource;
 
 pub use pose::{AnimationPose, NodePose};
 pub use signal::{AnimationEvent, AnimationSignal};
@@ -4,12 +4,12 @@
 use crate::{
     core::pool::Handle,
     machine::{State, Transition},
-    EntityId,
+    Animation, EntityId,
 };
 use std::collections::VecDeque;
 
 /// Specific state machine event.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Event<T: EntityId> {
     /// Occurs when enter some state. See module docs for example.
     StateEnter(Handle<State<T>>),
@@ -28,11 +28,25 @@ pub enum Event<T: EntityId> {
 
     /// Occurs when active transition was changed.
     ActiveTransitionChanged(Handle<Transition<T>>),
+
+    /// Occurs when playback position of an animation of the active state (or of any state of the active
+    /// transition) passes an animation signal (see [`crate::AnimationSignal`]). It could be used to react
+    /// to footsteps, hit frames, etc. without polling animation time manually.
+    AnimationSignal {
+        /// A state that uses the animation.
+        state: Handle<State<T>>,
+        /// An animation that contains the signal.
+        animation: Handle<Animation<T>>,
+        /// Name of the signal.
+        name: String,
+        /// Time position (in seconds) of the signal on the animation timeline.
+        time: f32,
+    },
 }
 
 /// A simple event queue with fixed capacity. It is used to store a fixed amount of events and discard any
 /// events when the queue is full.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct FixedEventQueue<T: EntityId> {
     queue: VecDeque<Event<T>>,
     limit: u32,
@@ -9,12 +9,35 @@ use crate::{
         visitor::prelude::*,
     },
     machine::{
-        event::FixedEventQueue, node::AnimationEventCollectionStrategy, AnimationPoseSource, Event,
-        LayerMask, ParameterContainer, PoseNode, State, Transition,
+        event::FixedEventQueue, node::AnimationEventCollectionStrategy, state::StateActionRequest,
+        AnimationPoseSource, Event, LayerMask, ParameterContainer, PoseNode, State, Transition,
+        TransitionInterruption,
     },
     Animation, AnimationContainer, AnimationEvent, AnimationPose, EntityId,
 };
-use fyrox_core::{find_by_name_mut, find_by_name_ref, NameProvider};
+use fxhash::FxHashMap;
+use fyrox_core::{find_by_name_mut, find_by_name_ref, uuid_provider, NameProvider};
+use std::{collections::VecDeque, ops::Range};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines how a pose of a layer is combined with the poses of the layers below it.
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, Visit, Reflect, VariantNames, EnumString, AsRefStr,
+)]
+pub enum LayerBlendMode {
+    /// The pose of the layer replaces the poses of the layers below it. Layer weight is used to interpolate
+    /// between the pose below the layer and the pose of the layer. This is the default mode.
+    #[default]
+    Override,
+    /// The pose of the layer is added on top of the poses of the layers below it. The pose is converted to offsets
+    /// by subtracting the reference pose of the layer - the first frame of its animations (see
+    /// [`MachineLayer::reference_pose`]). For example, an animation of a slight lean of the torso, that starts from
+    /// the idle pose, adds only the lean to any pose below (walking, running, etc.). The offsets are scaled by the
+    /// layer weight. Nodes that are not animated by the layers below are not affected.
+    Additive,
+}
+
+uuid_provider!(LayerBlendMode = "c4b7d0f2-8a3e-4e5b-b1f9-3d6a2e0c7f48");
 
 /// Layer is a separate state graph. Layers mainly used to animate different parts of humanoid (but not only) characters. For
 /// example there could a layer for upper body and a layer for lower body. Upper body layer could contain animations for aiming,
@@ -22,6 +45,13 @@ use fyrox_core::{find_by_name_mut, find_by_name_ref, NameProvider};
 /// ability to have running character that could aim or melee attack, or crouching and aiming, and so on with any combination.
 /// Both layers use the same set of parameters, so a change in a parameter will affect all layers that use it.
 ///
+/// # Blending
+///
+/// Layers are evaluated one after another and the pose of each layer is combined with the poses of the previous layers
+/// using the layer weight (see [`Self::set_weight`]) and the blend mode (see [`LayerBlendMode`]). Use layer mask (see
+/// [`Self::set_mask`]) to exclude nodes from a layer, for example to make an upper body layer override only the upper
+/// body of the pose produced by a lower body layer.
+///
 /// # Example
 ///
 /// ```rust
@@ -67,6 +97,9 @@ pub struct MachineLayer<T: EntityId> {
 
     weight: f32,
 
+    #[visit(optional)]
+    blend_mode: LayerBlendMode,
+
     mask: LayerMask<T>,
 
     #[reflect(hidden)]
@@ -87,19 +120,70 @@ pub struct MachineLayer<T: EntityId> {
     #[reflect(hidden)]
     active_transition: Handle<Transition<T>>,
 
+    #[visit(skip)]
+    #[reflect(hidden)]
+    transition_source: Handle<State<T>>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    transition_source_pose: Option<AnimationPose<T>>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    cancel_requested: bool,
+
     #[visit(skip)]
     #[reflect(hidden)]
     final_pose: AnimationPose<T>,
 
+    #[visit(skip)]
+    #[reflect(hidden)]
+    reference_pose: AnimationPose<T>,
+
     #[visit(skip)]
     #[reflect(hidden)]
     events: FixedEventQueue<T>,
 
+    #[visit(skip)]
+    #[reflect(hidden)]
+    action_requests: VecDeque<StateActionRequest<T>>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    signal_time_positions: FxHashMap<Handle<Animation<T>>, f32>,
+
     #[visit(skip)]
     #[reflect(hidden)]
     debug: bool,
 }
 
+/// Checks whether the playback position of an animation passed the signal at the given time when it moved from
+/// `prev` to `current` position. Looping animations could wrap around the time slice.
+fn is_signal_passed(
+    prev: f32,
+    current: f32,
+    speed: f32,
+    looped: bool,
+    time_slice: &Range<f32>,
+    time: f32,
+) -> bool {
+    if speed >= 0.0 {
+        if current >= prev {
+            prev < time && time <= current
+        } else {
+            looped
+                && (prev < time && time <= time_slice.end
+                    || time_slice.start <= time && time <= current)
+        }
+    } else if current <= prev {
+        current <= time && time < prev
+    } else {
+        looped
+            && (time_slice.start <= time && time < prev
+                || current <= time && time <= time_slice.end)
+    }
+}
+
 impl<T: EntityId> NameProvider for MachineLayer<T> {
     fn name(&self) -> &str {
         &self.name
@@ -154,11 +238,18 @@ impl<T: EntityId> MachineLayer<T> {
             states: Default::default(),
             transitions: Default::default(),
             final_pose: Default::default(),
+            reference_pose: Default::default(),
             active_state: Default::default(),
             entry_state: Default::default(),
             active_transition: Default::default(),
+            transition_source: Default::default(),
+            transition_source_pose: None,
+            cancel_requested: false,
             weight: 1.0,
+            blend_mode: Default::default(),
             events: FixedEventQueue::new(2048),
+            action_requests: Default::default(),
+            signal_time_positions: Default::default(),
             debug: false,
             mask: Default::default(),
         }
@@ -256,6 +347,9 @@ impl<T: EntityId> MachineLayer<T> {
     ///         Event::ActiveTransitionChanged(transition_handle) => {
     ///             // Occurs when active transition has changed.
     ///         }
+    ///         Event::AnimationSignal { name, .. } => {
+    ///             // Occurs when an animation of an active state passes a signal.
+    ///         }
     ///     }
     /// }
     /// ```
@@ -264,6 +358,15 @@ impl<T: EntityId> MachineLayer<T> {
         self.events.pop()
     }
 
+    /// Takes the oldest external action (see [`super::state::StateAction::is_external`]), that was
+    /// requested by a state on enter or leave. If the layer is a part of a [`super::Machine`], the
+    /// machine takes the actions of the layer automatically, use [`super::Machine::pop_action`]
+    /// instead.
+    #[inline]
+    pub fn pop_action(&mut self) -> Option<StateActionRequest<T>> {
+        self.action_requests.pop_front()
+    }
+
     /// Resets layer state; deactivates all active transitions and sets active state to entry state.
     #[inline]
     pub fn reset(&mut self) {
@@ -271,6 +374,8 @@ impl<T: EntityId> MachineLayer<T> {
             transition.reset();
         }
 
+        self.active_transition = Handle::NONE;
+        self.clear_transition_overrides();
         self.active_state = self.entry_state;
     }
 
@@ -418,6 +523,29 @@ impl<T: EntityId> MachineLayer<T> {
         self.active_transition
     }
 
+    /// Returns a handle of the state, from which the active transition is blending. It could differ from the source
+    /// state of the transition, if the transition has replaced another one (see [`TransitionInterruption::Retarget`]).
+    /// Returns [`Handle::NONE`] if there's no active transition.
+    #[inline]
+    pub fn active_transition_source(&self) -> Handle<State<T>> {
+        if self.transition_source.is_some() {
+            self.transition_source
+        } else {
+            self.transitions
+                .try_borrow(self.active_transition)
+                .map(|t| t.source())
+                .unwrap_or_default()
+        }
+    }
+
+    /// Cancels the active transition, so it starts blending back to its source state regardless of its condition and
+    /// interruption policy. Returns `false` if there's no active transition.
+    #[inline]
+    pub fn cancel_active_transition(&mut self) -> bool {
+        self.cancel_requested = self.active_transition.is_some();
+        self.cancel_requested
+    }
+
     /// Tries to borrow a transition using its handle, panics if the handle is invalid.
     #[inline]
     pub fn transition(&self, handle: Handle<Transition<T>>) -> &Transition<T> {
@@ -515,6 +643,18 @@ impl<T: EntityId> MachineLayer<T> {
         self.weight
     }
 
+    /// Sets new blend mode of the layer. See docs of [`LayerBlendMode`] for more info.
+    #[inline]
+    pub fn set_blend_mode(&mut self, blend_mode: LayerBlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
+    /// Returns current blend mode of the layer.
+    #[inline]
+    pub fn blend_mode(&self) -> LayerBlendMode {
+        self.blend_mode
+    }
+
     /// Sets new layer mask. See docs of [`LayerMask`] for more info about layer masks.
     #[inline]
     pub fn set_mask(&mut self, mask: LayerMask<T>) -> LayerMask<T> {
@@ -564,6 +704,216 @@ impl<T: EntityId> MachineLayer<T> {
             .all(|a| a.has_ended())
     }
 
+    fn emit_signal_events(&mut self, animations: &AnimationContainer<T>) {
+        let states = if let Some(transition) = self.transitions.try_borrow(self.active_transition) {
+            [transition.source(), transition.dest()]
+        } else {
+            [self.active_state, Handle::NONE]
+        };
+
+        let used_animations = states
+            .into_iter()
+            .filter(|state| state.is_some())
+            .flat_map(|state| {
+                self.animations_of_state(state)
+                    .map(move |animation| (state, animation))
+            })
+            .collect::<Vec<_>>();
+
+        // Positions of the animations that are not used anymore must be discarded, otherwise a signal could be
+        // emitted when a state is entered again (because its animations could be rewound).
+        let prev_positions = std::mem::take(&mut self.signal_time_positions);
+
+        for (state, animation_handle) in used_animations {
+            let Some(animation) = animations.try_get(animation_handle) else {
+                continue;
+            };
+
+            if self.signal_time_positions.contains_key(&animation_handle) {
+                continue;
+            }
+
+            let current = animation.time_position();
+            self.signal_time_positions.insert(animation_handle, current);
+
+            let Some(&prev) = prev_positions.get(&animation_handle) else {
+                continue;
+            };
+
+            if prev == current {
+                continue;
+            }
+
+            let time_slice = animation.time_slice();
+            for signal in animation.signals().iter().filter(|s| s.enabled) {
+                if is_signal_passed(
+                    prev,
+                    current,
+                    animation.speed(),
+                    animation.is_loop(),
+                    &time_slice,
+                    signal.time,
+                ) {
+                    self.events.push(Event::AnimationSignal {
+                        state,
+                        animation: animation_handle,
+                        name: signal.name.clone(),
+                        time: signal.time,
+                    });
+                }
+            }
+        }
+    }
+
+    fn leave_state(&mut self, state: Handle<State<T>>, animations: &mut AnimationContainer<T>) {
+        if let Some(state_ref) = self.states.try_borrow(state) {
+            for action in state_ref.on_leave_actions.iter() {
+                if action.is_external() {
+                    self.action_requests.push_back(StateActionRequest {
+                        state,
+                        state_name: state_ref.name.clone(),
+                        action: action.0.clone(),
+                    });
+                } else {
+                    action.apply(animations);
+                }
+            }
+
+            if self.debug {
+                Log::writeln(
+                    MessageKind::Information,
+                    format!("Leaving state: {}", state_ref.name),
+                );
+            }
+        }
+
+        self.events.push(Event::StateLeave(state));
+    }
+
+    fn enter_state(&mut self, state: Handle<State<T>>, animations: &mut AnimationContainer<T>) {
+        if let Some(state_ref) = self.states.try_borrow(state) {
+            for action in state_ref.on_enter_actions.iter() {
+                if action.is_external() {
+                    self.action_requests.push_back(StateActionRequest {
+                        state,
+                        state_name: state_ref.name.clone(),
+                        action: action.0.clone(),
+                    });
+                } else {
+                    action.apply(animations);
+                }
+            }
+
+            if self.debug {
+                Log::writeln(
+                    MessageKind::Information,
+                    format!("Entering state: {}", state_ref.name),
+                );
+            }
+        }
+
+        self.events.push(Event::StateEnter(state));
+    }
+
+    fn clear_transition_overrides(&mut self) {
+        self.transition_source = Handle::NONE;
+        self.transition_source_pose = None;
+        self.cancel_requested = false;
+    }
+
+    fn blend_active_transition(&self, pose: &mut AnimationPose<T>) {
+        let transition = &self.transitions[self.active_transition];
+
+        if let Some(source_pose) = self.transition_source_pose.as_ref() {
+            pose.blend_with(source_pose, 1.0 - transition.blend_factor());
+        } else if let Some(source_pose) = self
+            .states
+            .try_borrow(self.active_transition_source())
+            .and_then(|s| s.pose(&self.nodes))
+        {
+            pose.blend_with(&source_pose, 1.0 - transition.blend_factor());
+        }
+
+        if let Some(dest_pose) = self
+            .states
+            .try_borrow(transition.dest())
+            .and_then(|s| s.pose(&self.nodes))
+        {
+            pose.blend_with(&dest_pose, transition.blend_factor());
+        }
+    }
+
+    fn interrupt_active_transition(
+        &mut self,
+        parameters: &ParameterContainer,
+        animations: &mut AnimationContainer<T>,
+    ) {
+        let active = self.active_transition;
+        let source = self.active_transition_source();
+        let transition = &self.transitions[active];
+        let interruption = transition.interruption();
+        let dest = transition.dest();
+        let reversed = transition.is_reversed();
+        let blend_factor = transition.blend_factor();
+
+        let should_reverse = self.cancel_requested
+            || (interruption == TransitionInterruption::CancelAndReverse
+                && !transition.condition.calculate_value(parameters, animations));
+
+        if should_reverse != reversed {
+            self.transitions[active].reversed = should_reverse;
+            if should_reverse {
+                self.leave_state(dest, animations);
+                self.enter_state(source, animations);
+            } else {
+                self.leave_state(source, animations);
+                self.enter_state(dest, animations);
+            }
+            return;
+        }
+
+        if reversed
+            || !matches!(
+                interruption,
+                TransitionInterruption::Retarget | TransitionInterruption::BlendFromCurrentPose
+            )
+        {
+            return;
+        }
+
+        let Some(new) = self.transitions.pair_iter().find_map(|(handle, t)| {
+            (handle != active
+                && t.source() == dest
+                && t.dest() != dest
+                && t.condition.calculate_value(parameters, animations))
+            .then_some(handle)
+        }) else {
+            return;
+        };
+
+        if interruption == TransitionInterruption::BlendFromCurrentPose {
+            let mut pose = AnimationPose::default();
+            self.blend_active_transition(&mut pose);
+            self.transition_source_pose = Some(pose);
+            self.transition_source = Handle::NONE;
+            self.transitions[new].reset();
+        } else {
+            self.transition_source = source;
+            self.transitions[new].reset();
+            self.transitions[new].set_progress(blend_factor);
+        }
+
+        self.transitions[active].reset();
+
+        let new_dest = self.transitions[new].dest();
+        self.leave_state(dest, animations);
+        self.enter_state(new_dest, animations);
+
+        self.active_transition = new;
+        self.events
+            .push(Event::ActiveTransitionChanged(self.active_transition));
+    }
+
     #[inline]
     pub(super) fn evaluate_pose(
         &mut self,
@@ -581,81 +931,59 @@ impl<T: EntityId> MachineLayer<T> {
 
             if self.active_transition.is_none() {
                 // Find transition.
-                for (handle, transition) in self.transitions.pair_iter_mut() {
-                    if transition.dest() == self.active_state
-                        || transition.source() != self.active_state
-                    {
-                        continue;
-                    }
-
-                    if transition.condition.calculate_value(parameters, animations) {
-                        if let Some(active_state) = self.states.try_borrow(self.active_state) {
-                            for action in active_state.on_leave_actions.iter() {
-                                action.apply(animations);
-                            }
-                        }
-
-                        self.events.push(Event::StateLeave(self.active_state));
-                        if self.debug {
-                            Log::writeln(
-                                MessageKind::Information,
-                                format!("Leaving state: {}", self.states[self.active_state].name),
-                            );
-                        }
-
-                        if let Some(source) = self.states.try_borrow(transition.dest()) {
-                            for action in source.on_enter_actions.iter() {
-                                action.apply(animations);
-                            }
-                        }
-
-                        self.events.push(Event::StateEnter(transition.dest()));
-                        if self.debug {
-                            Log::writeln(
-                                MessageKind::Information,
-                                format!("Entering state: {}", self.states[transition.dest()].name),
-                            );
-                        }
+                let found = self
+                    .transitions
+                    .pair_iter()
+                    .find_map(|(handle, transition)| {
+                        (transition.dest() != self.active_state
+                            && transition.source() == self.active_state
+                            && transition.condition.calculate_value(parameters, animations))
+                        .then_some((handle, transition.source(), transition.dest()))
+                    });
 
-                        self.active_state = Handle::NONE;
+                if let Some((handle, source, dest)) = found {
+                    self.leave_state(source, animations);
+                    self.enter_state(dest, animations);
 
-                        self.active_transition = handle;
-                        self.events
-                            .push(Event::ActiveTransitionChanged(self.active_transition));
+                    self.active_state = Handle::NONE;
 
-                        break;
-                    }
+                    self.active_transition = handle;
+                    self.events
+                        .push(Event::ActiveTransitionChanged(self.active_transition));
                 }
+            } else {
+                self.interrupt_active_transition(parameters, animations);
             }
 
             // Double check for active transition because we can have empty machine.
             if self.active_transition.is_some() {
-                let transition = &mut self.transitions[self.active_transition];
-
                 // Blend between source and dest states.
-                if let Some(source_pose) = self.states[transition.source()].pose(&self.nodes) {
-                    self.final_pose
-                        .blend_with(&source_pose, 1.0 - transition.blend_factor());
-                }
-                if let Some(dest_pose) = self.states[transition.dest()].pose(&self.nodes) {
-                    self.final_pose
-                        .blend_with(&dest_pose, transition.blend_factor());
-                }
+                let mut final_pose = std::mem::take(&mut self.final_pose);
+                self.blend_active_transition(&mut final_pose);
+                self.final_pose = final_pose;
+
+                let source = self.active_transition_source();
+                let transition = &mut self.transitions[self.active_transition];
 
                 transition.update(dt);
 
                 if transition.is_done() {
+                    let (prev, new) = if transition.is_reversed() {
+                        (transition.dest(), source)
+                    } else {
+                        (source, transition.dest())
+                    };
+
                     transition.reset();
 
                     self.active_transition = Handle::NONE;
                     self.events
                         .push(Event::ActiveTransitionChanged(self.active_transition));
 
-                    self.active_state = transition.dest();
-                    self.events.push(Event::ActiveStateChanged {
-                        prev: transition.source(),
-                        new: transition.dest(),
-                    });
+                    self.active_state = new;
+                    self.events.push(Event::ActiveStateChanged { prev, new });
+
+                    self.clear_transition_overrides();
 
                     if self.debug {
                         Log::writeln(
@@ -674,12 +1002,145 @@ impl<T: EntityId> MachineLayer<T> {
                     active_state_pose.clone_into(&mut self.final_pose);
                 }
             }
+
+            self.emit_signal_events(animations);
         }
 
         self.final_pose
             .poses_mut()
             .retain(|h, _| self.mask.should_animate(*h));
 
+        if self.blend_mode == LayerBlendMode::Additive {
+            self.update_reference_pose(animations);
+        }
+
         &self.final_pose
     }
+
+    fn update_reference_pose(&mut self, animations: &AnimationContainer<T>) {
+        self.reference_pose.reset();
+        for node in self.nodes.iter() {
+            if let PoseNode::PlayAnimation(play_animation) = node {
+                if let Some(animation) = animations.try_get(play_animation.animation) {
+                    let time = animation.time_slice().start;
+                    for track in animation.tracks().iter().filter(|t| t.is_enabled()) {
+                        if let Some(value) = track.fetch(time) {
+                            // If several animations animate the same property, the first one wins.
+                            self.reference_pose.add_to_node_pose(track.target(), value);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the reference pose of the layer. It is the first frame of the animations of the layer and it is used
+    /// to convert the pose of an additive layer to offsets (see [`LayerBlendMode::Additive`]). If several animations
+    /// of the layer animate the same property, the value from the first animation is used. The pose is updated only
+    /// for additive layers.
+    #[inline]
+    pub fn reference_pose(&self) -> &AnimationPose<T> {
+        &self.reference_pose
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::pool::{ErasedHandle, Handle},
+        machine::{
+            layer::is_signal_passed, MachineLayer, Parameter, ParameterContainer, State,
+            Transition, TransitionInterruption,
+        },
+        AnimationContainer,
+    };
+
+    fn set_rule(parameters: &mut ParameterContainer, name: &str, value: bool) {
+        *parameters.get_mut(name).unwrap() = Parameter::Rule(value);
+    }
+
+    #[test]
+    fn test_cancel_and_reverse() {
+        let mut layer = MachineLayer::<ErasedHandle>::new();
+        let a = layer.add_state(State::new("A", Handle::NONE));
+        let b = layer.add_state(State::new("B", Handle::NONE));
+        let mut transition = Transition::new("A->B", a, b, 1.0, "AtoB");
+        transition.set_interruption(TransitionInterruption::CancelAndReverse);
+        let a_to_b = layer.add_transition(transition);
+        layer.set_entry_state(a);
+
+        let mut animations = AnimationContainer::new();
+        let mut parameters = ParameterContainer::default();
+        parameters.add("AtoB", Parameter::Rule(true));
+
+        layer.evaluate_pose(&mut animations, &parameters, 0.5);
+        assert_eq!(layer.active_transition(), a_to_b);
+        assert_eq!(layer.transition(a_to_b).blend_factor(), 0.5);
+
+        // The rule flips mid-transition, the machine must go back to the source state.
+        set_rule(&mut parameters, "AtoB", false);
+        layer.evaluate_pose(&mut animations, &parameters, 0.25);
+        assert!(layer.transition(a_to_b).is_reversed());
+        assert_eq!(layer.transition(a_to_b).blend_factor(), 0.25);
+        layer.evaluate_pose(&mut animations, &parameters, 0.25);
+        assert_eq!(layer.active_transition(), Handle::NONE);
+        assert_eq!(layer.active_state(), a);
+    }
+
+    #[test]
+    fn test_retarget_and_cancel() {
+        let mut layer = MachineLayer::<ErasedHandle>::new();
+        let a = layer.add_state(State::new("A", Handle::NONE));
+        let b = layer.add_state(State::new("B", Handle::NONE));
+        let c = layer.add_state(State::new("C", Handle::NONE));
+        let mut transition = Transition::new("A->B", a, b, 1.0, "AtoB");
+        transition.set_interruption(TransitionInterruption::Retarget);
+        layer.add_transition(transition);
+        let b_to_c = layer.add_transition(Transition::new("B->C", b, c, 2.0, "BtoC"));
+        layer.set_entry_state(a);
+
+        let mut animations = AnimationContainer::new();
+        let mut parameters = ParameterContainer::default();
+        parameters.add("AtoB", Parameter::Rule(true));
+        parameters.add("BtoC", Parameter::Rule(false));
+
+        layer.evaluate_pose(&mut animations, &parameters, 0.5);
+        set_rule(&mut parameters, "BtoC", true);
+        layer.evaluate_pose(&mut animations, &parameters, 0.0);
+        assert_eq!(layer.active_transition(), b_to_c);
+        assert_eq!(layer.active_transition_source(), a);
+        assert_eq!(layer.transition(b_to_c).blend_factor(), 0.5);
+
+        // Non-interruptible transitions could still be cancelled manually.
+        assert!(layer.cancel_active_transition());
+        layer.evaluate_pose(&mut animations, &parameters, 0.5);
+        assert_eq!(layer.active_transition(), b_to_c);
+        layer.evaluate_pose(&mut animations, &parameters, 1.0);
+        assert_eq!(layer.active_transition(), Handle::NONE);
+        assert_eq!(layer.active_state(), a);
+    }
+
+    #[test]
+    fn test_is_signal_passed() {
+        let slice = 0.0..1.0;
+
+        // Forward playback.
+        assert!(is_signal_passed(0.2, 0.4, 1.0, false, &slice, 0.3));
+        assert!(is_signal_passed(0.2, 0.4, 1.0, false, &slice, 0.4));
+        assert!(!is_signal_passed(0.2, 0.4, 1.0, false, &slice, 0.2));
+        assert!(!is_signal_passed(0.2, 0.4, 1.0, false, &slice, 0.5));
+
+        // Forward playback with wrapping.
+        assert!(is_signal_passed(0.9, 0.1, 1.0, true, &slice, 0.95));
+        assert!(is_signal_passed(0.9, 0.1, 1.0, true, &slice, 0.05));
+        assert!(!is_signal_passed(0.9, 0.1, 1.0, true, &slice, 0.5));
+        // Rewind of non-looping animation must not emit signals.
+        assert!(!is_signal_passed(0.9, 0.1, 1.0, false, &slice, 0.05));
+
+        // Backward playback.
+        assert!(is_signal_passed(0.4, 0.2, -1.0, false, &slice, 0.3));
+        assert!(!is_signal_passed(0.4, 0.2, -1.0, false, &slice, 0.4));
+        assert!(is_signal_passed(0.1, 0.9, -1.0, true, &slice, 0.05));
+        assert!(is_signal_passed(0.1, 0.9, -1.0, true, &slice, 0.95));
+    }
 }
@@ -10,21 +10,23 @@ use crate::{
         reflect::prelude::*,
         visitor::{Visit, VisitResult, Visitor},
     },
+    machine::state::{StateAction, StateActionRequest},
     AnimationContainer, AnimationPose, EntityId,
 };
+use std::collections::VecDeque;
 
 pub use event::Event;
 use fyrox_core::{find_by_name_mut, find_by_name_ref};
-pub use layer::MachineLayer;
+pub use layer::{LayerBlendMode, MachineLayer};
 pub use mask::LayerMask;
 pub use node::{
     blend::{BlendAnimations, BlendAnimationsByIndex, BlendPose, IndexedBlendInput},
     play::PlayAnimation,
     AnimationPoseSource, PoseNode,
 };
-pub use parameter::{Parameter, ParameterContainer, PoseWeight};
+pub use parameter::{CurveParameter, Parameter, ParameterContainer, PoseWeight};
 pub use state::State;
-pub use transition::Transition;
+pub use transition::{Transition, TransitionInterruption};
 
 pub mod event;
 pub mod layer;
@@ -177,6 +179,10 @@ pub struct Machine<T: EntityId> {
     #[visit(skip)]
     #[reflect(hidden)]
     final_pose: AnimationPose<T>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    actions: VecDeque<StateActionRequest<T>>,
 }
 
 impl<T: EntityId> Machine<T> {
@@ -187,6 +193,7 @@ impl<T: EntityId> Machine<T> {
             parameters: Default::default(),
             layers: vec![MachineLayer::new()],
             final_pose: Default::default(),
+            actions: Default::default(),
         }
     }
 
@@ -265,6 +272,58 @@ impl<T: EntityId> Machine<T> {
         &mut self.layers
     }
 
+    /// Pops an event from the event queue of the first layer that has any events. Events of each layer are
+    /// returned in the order they happened, layers are processed in their order. Use [`MachineLayer::pop_event`]
+    /// if you need to know the layer from which an event came from. See [`Event`] docs for the list of possible
+    /// events.
+    ///
+    /// ```rust
+    /// use fyrox_animation::machine::{Event, Machine};
+    /// use fyrox_core::pool::ErasedHandle;
+    ///
+    /// fn play_footstep_sounds(machine: &mut Machine<ErasedHandle>) {
+    ///     while let Some(event) = machine.pop_event() {
+    ///         if let Event::AnimationSignal { name, .. } = event {
+    ///             if name == "Footstep" {
+    ///                 // Play a sound here.
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    #[inline]
+    pub fn pop_event(&mut self) -> Option<Event<T>> {
+        self.layers.iter_mut().find_map(|layer| layer.pop_event())
+    }
+
+    /// Pops an external action (see [`state::StateAction::is_external`]), that was requested by a state
+    /// of any layer on enter or leave. [`state::StateAction::SetParameter`] actions are executed by the
+    /// machine itself and never returned by this method, every other external action must be executed by
+    /// the user of the machine.
+    ///
+    /// ```rust
+    /// use fyrox_animation::machine::{state::StateAction, Machine};
+    /// use fyrox_core::pool::ErasedHandle;
+    ///
+    /// fn execute_actions(machine: &mut Machine<ErasedHandle>) {
+    ///     while let Some(request) = machine.pop_action() {
+    ///         match request.action {
+    ///             StateAction::PlaySound(_sound) => {
+    ///                 // Play the sound here.
+    ///             }
+    ///             StateAction::SendMessage(_message) => {
+    ///                 // Send the message here.
+    ///             }
+    ///             _ => (),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    #[inline]
+    pub fn pop_action(&mut self) -> Option<StateActionRequest<T>> {
+        self.actions.pop_front()
+    }
+
     /// Tries to find a layer by its name. Returns index of the layer and its reference.
     #[inline]
     pub fn find_layer_by_name_ref<S: AsRef<str>>(
@@ -300,11 +359,218 @@ impl<T: EntityId> Machine<T> {
 
         for layer in self.layers.iter_mut() {
             let weight = layer.weight();
-            let pose = layer.evaluate_pose(animations, &self.parameters, dt);
+            layer.evaluate_pose(animations, &self.parameters, dt);
+
+            match layer.blend_mode() {
+                LayerBlendMode::Override => self.final_pose.blend_with(layer.pose(), weight),
+                LayerBlendMode::Additive => {
+                    self.final_pose
+                        .add_difference(layer.pose(), layer.reference_pose(), weight)
+                }
+            }
 
-            self.final_pose.blend_with(pose, weight);
+            while let Some(request) = layer.pop_action() {
+                if let StateAction::SetParameter { name, value } = request.action {
+                    match self.parameters.get_mut(&name) {
+                        Some(parameter) => *parameter = value,
+                        None => self.parameters.add(&name, value),
+                    }
+                } else {
+                    self.actions.push_back(request);
+                }
+            }
         }
 
         &self.final_pose
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        container::{TrackDataContainer, TrackValueKind},
+        core::{
+            algebra::{UnitQuaternion, Vector3},
+            math::curve::{Curve, CurveKey, CurveKeyKind},
+            pool::{ErasedHandle, Handle},
+        },
+        machine::{
+            state::{StateAction, StateActionWrapper},
+            LayerBlendMode, Machine, MachineLayer, Parameter, PlayAnimation, PoseNode, State,
+            Transition,
+        },
+        track::Track,
+        value::{TrackValue, ValueBinding},
+        Animation, AnimationContainer,
+    };
+
+    // Creates an animation, that changes the given property of a node from `from` to `to` in one second.
+    fn make_animation(
+        node: ErasedHandle,
+        binding: ValueBinding,
+        from: Vector3<f32>,
+        to: Vector3<f32>,
+    ) -> Animation<ErasedHandle> {
+        let kind = if binding == ValueBinding::Rotation {
+            // Rotations are stored as Euler angles.
+            TrackValueKind::UnitQuaternion
+        } else {
+            TrackValueKind::Vector3
+        };
+        let mut container = TrackDataContainer::new(kind);
+        for (curve, (from, to)) in container
+            .curves_mut()
+            .iter_mut()
+            .zip(from.iter().zip(to.iter()))
+        {
+            let mut new_curve = Curve::default();
+            new_curve.add_key(CurveKey::new(0.0, *from, CurveKeyKind::Linear));
+            new_curve.add_key(CurveKey::new(1.0, *to, CurveKeyKind::Linear));
+            *curve = new_curve;
+        }
+
+        let mut track = Track::new(container, binding);
+        track.set_target(node);
+
+        let mut animation = Animation::default();
+        animation.add_track(track);
+        animation.set_time_slice(0.0..1.0);
+        animation.set_loop(false);
+        animation
+    }
+
+    fn add_layer(
+        machine: &mut Machine<ErasedHandle>,
+        animations: &mut AnimationContainer<ErasedHandle>,
+        animation: Animation<ErasedHandle>,
+        blend_mode: LayerBlendMode,
+    ) {
+        let animation = animations.add(animation);
+        let mut layer = MachineLayer::new();
+        layer.set_blend_mode(blend_mode);
+        let node = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(animation)));
+        let state = layer.add_state(State::new("State", node));
+        layer.set_entry_state(state);
+        machine.add_layer(layer);
+    }
+
+    fn eval_additive(
+        binding: ValueBinding,
+        base: Vector3<f32>,
+        from: Vector3<f32>,
+        to: Vector3<f32>,
+    ) -> TrackValue {
+        let node = ErasedHandle::new(1, 1);
+        // The default layer of the machine is empty and does not affect the pose.
+        let mut machine = Machine::<ErasedHandle>::new();
+        let mut animations = AnimationContainer::new();
+        add_layer(
+            &mut machine,
+            &mut animations,
+            make_animation(node, binding.clone(), base, base),
+            LayerBlendMode::Override,
+        );
+        add_layer(
+            &mut machine,
+            &mut animations,
+            make_animation(node, binding.clone(), from, to),
+            LayerBlendMode::Additive,
+        );
+
+        // The pose is sampled before the time position is advanced, so the second update samples
+        // the animations at the end of their time slices.
+        for _ in 0..2 {
+            for animation in animations.iter_mut() {
+                animation.tick(1.0);
+            }
+            machine.evaluate_pose(&mut animations, 1.0);
+        }
+
+        let values = &machine.pose().poses()[&node].values.values;
+        values
+            .iter()
+            .find(|v| v.binding == binding)
+            .unwrap()
+            .value
+            .clone()
+    }
+
+    #[test]
+    fn test_additive_position() {
+        let value = eval_additive(
+            ValueBinding::Position,
+            Vector3::new(10.0, 0.0, 0.0),
+            Vector3::new(1.0, 2.0, 3.0),
+            Vector3::new(2.0, 2.0, 5.0),
+        );
+        // Only the difference between the first and the last frames is added.
+        assert_eq!(value, TrackValue::Vector3(Vector3::new(11.0, 0.0, 2.0)));
+    }
+
+    #[test]
+    fn test_additive_scale() {
+        let value = eval_additive(
+            ValueBinding::Scale,
+            Vector3::new(2.0, 2.0, 2.0),
+            Vector3::new(1.0, 2.0, 4.0),
+            Vector3::new(2.0, 2.0, 2.0),
+        );
+        assert_eq!(value, TrackValue::Vector3(Vector3::new(4.0, 2.0, 1.0)));
+    }
+
+    #[test]
+    fn test_additive_rotation() {
+        let value = eval_additive(
+            ValueBinding::Rotation,
+            Vector3::new(0.0, 0.5, 0.0),
+            Vector3::new(0.3, 0.0, 0.0),
+            Vector3::new(0.5, 0.0, 0.0),
+        );
+        let TrackValue::UnitQuaternion(rotation) = value else {
+            panic!("Rotation expected");
+        };
+        let expected = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.5)
+            * UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 0.2);
+        assert!(rotation.angle_to(&expected) < 1.0e-4);
+    }
+
+    #[test]
+    fn test_state_actions() {
+        let mut machine = Machine::<ErasedHandle>::new();
+        machine.set_parameter("Go", Parameter::Rule(true));
+
+        let layer = &mut machine.layers_mut()[0];
+        let mut a = State::new("A", Handle::NONE);
+        a.on_leave_actions
+            .push(StateActionWrapper(StateAction::SetParameter {
+                name: "Left".to_string(),
+                value: Parameter::Rule(true),
+            }));
+        let a = layer.add_state(a);
+        let mut b = State::new("B", Handle::NONE);
+        b.on_enter_actions
+            .push(StateActionWrapper(StateAction::SendMessage(
+                "Entered".to_string(),
+            )));
+        let b = layer.add_state(b);
+        layer.add_transition(Transition::new("A->B", a, b, 1.0, "Go"));
+        layer.set_entry_state(a);
+
+        let mut animations = AnimationContainer::new();
+        machine.evaluate_pose(&mut animations, 0.5);
+
+        // Parameters are changed by the machine itself.
+        assert_eq!(
+            machine.parameters().get("Left"),
+            Some(&Parameter::Rule(true))
+        );
+        let request = machine.pop_action().unwrap();
+        assert_eq!(request.state, b);
+        assert_eq!(request.state_name, "B");
+        assert_eq!(
+            request.action,
+            StateAction::SendMessage("Entered".to_string())
+        );
+        assert!(machine.pop_action().is_none());
+    }
+}
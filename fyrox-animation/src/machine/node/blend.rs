@@ -129,13 +129,7 @@ impl<T: EntityId> AnimationPoseSource<T> for BlendAnimations<T> {
         for blend_pose in self.pose_sources.iter() {
             let weight = match blend_pose.weight {
                 PoseWeight::Constant(value) => value,
-                PoseWeight::Parameter(ref param_id) => {
-                    if let Some(Parameter::Weight(weight)) = params.get(param_id) {
-                        *weight
-                    } else {
-                        0.0
-                    }
-                }
+                PoseWeight::Parameter(ref param_id) => params.weight(param_id).unwrap_or(0.0),
             };
 
             if let Some(pose_source) = nodes
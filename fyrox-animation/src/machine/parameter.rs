@@ -1,6 +1,9 @@
 //! Parameter is a name variable of a fixed type. See [`Parameter`] docs for more info.
 
-use crate::core::{algebra::Vector2, reflect::prelude::*, visitor::prelude::*};
+use crate::{
+    core::{algebra::Vector2, reflect::prelude::*, visitor::prelude::*},
+    resource::curve::CurveResource,
+};
 use fxhash::FxHashMap;
 use fyrox_core::uuid_provider;
 use std::{
@@ -52,13 +55,7 @@ impl PoseWeight {
     pub fn value(&self, params: &ParameterContainer) -> Option<f32> {
         match self {
             PoseWeight::Constant(val) => Some(*val),
-            PoseWeight::Parameter(name) => params.get(name).and_then(|p| {
-                if let Parameter::Weight(weight) = p {
-                    Some(*weight)
-                } else {
-                    None
-                }
-            }),
+            PoseWeight::Parameter(name) => params.weight(name),
         }
     }
 }
@@ -81,6 +78,24 @@ pub struct ParameterDefinition {
 
 uuid_provider!(ParameterDefinition = "6cbba5c9-8daf-4f4c-a920-9716ed233d89");
 
+/// A named weight, that is calculated by sampling a curve at the value of another Weight parameter.
+/// It allows you to define response curves (for example, speed -> blend weight) visually, instead
+/// of remapping the values in code. Curve parameter could be used everywhere, where Weight parameter
+/// is expected.
+#[derive(Reflect, Visit, Default, Debug, Clone, PartialEq)]
+pub struct CurveParameter {
+    /// Name of the parameter.
+    pub name: String,
+
+    /// A curve that maps the value of the input parameter to the weight.
+    pub resource: Option<CurveResource>,
+
+    /// Name of a Weight parameter, that is used as an input for the curve.
+    pub input_param: String,
+}
+
+uuid_provider!(CurveParameter = "0b3c6a0e-4d0f-4c57-9b0b-2f7e1e5c8a41");
+
 #[derive(Default, Debug, Clone)]
 struct Wrapper {
     parameters: Vec<ParameterDefinition>,
@@ -123,11 +138,15 @@ pub struct ParameterContainer {
     #[reflect(hidden)]
     #[visit(skip)]
     lookup: RefCell<FxHashMap<String, usize>>,
+
+    /// Curve parameters are stored separately, because they cannot be copied.
+    #[visit(optional)]
+    curves: Vec<CurveParameter>,
 }
 
 impl PartialEq for ParameterContainer {
     fn eq(&self, other: &Self) -> bool {
-        self.parameters == other.parameters
+        self.parameters == other.parameters && self.curves == other.curves
     }
 }
 
@@ -136,6 +155,7 @@ impl Clone for ParameterContainer {
         Self {
             parameters: self.parameters.clone(),
             lookup: RefCell::new(self.lookup.borrow().clone()),
+            curves: self.curves.clone(),
         }
     }
 }
@@ -171,6 +191,46 @@ impl ParameterContainer {
             .and_then(|i| self.parameters.parameters.get(*i).map(|d| &d.value))
     }
 
+    /// Tries to fetch a value of a Weight or Curve parameter with the given name. Curve parameters
+    /// are sampled at the value of their input parameter, `None` is returned if the curve resource
+    /// is not loaded yet or the input parameter is not a Weight parameter.
+    pub fn weight(&self, name: &str) -> Option<f32> {
+        if let Some(parameter) = self.get(name) {
+            return if let Parameter::Weight(weight) = parameter {
+                Some(*weight)
+            } else {
+                None
+            };
+        }
+
+        let curve_parameter = self.curve(name)?;
+        let Some(Parameter::Weight(input)) = self.get(&curve_parameter.input_param) else {
+            return None;
+        };
+        let mut state = curve_parameter.resource.as_ref()?.state();
+        state.data().map(|data| data.curve.value_at(*input))
+    }
+
+    /// Adds a new curve parameter to the container.
+    pub fn add_curve(&mut self, curve_parameter: CurveParameter) {
+        self.curves.push(curve_parameter)
+    }
+
+    /// Tries to borrow a curve parameter by its name.
+    pub fn curve(&self, name: &str) -> Option<&CurveParameter> {
+        self.curves.iter().find(|c| c.name == name)
+    }
+
+    /// Returns a reference to the curve parameters of the container.
+    pub fn curves(&self) -> &[CurveParameter] {
+        &self.curves
+    }
+
+    /// Returns a mutable reference to the curve parameters of the container.
+    pub fn curves_mut(&mut self) -> &mut Vec<CurveParameter> {
+        &mut self.curves
+    }
+
     /// Tries to borrow a parameter by its name. The method has O(1) complexity.
     pub fn get_mut(&mut self, name: &str) -> Option<&mut Parameter> {
         self.update_index();
@@ -180,3 +240,53 @@ impl ParameterContainer {
             .and_then(|i| self.parameters.parameters.get_mut(*i).map(|d| &mut d.value))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::math::curve::{Curve, CurveKey, CurveKeyKind},
+        machine::{parameter::CurveParameter, Parameter, ParameterContainer, PoseWeight},
+        resource::{curve::CurveResourceState, untyped::ResourceKind, Resource},
+    };
+
+    #[test]
+    fn test_curve_parameter() {
+        let curve = Curve::from(vec![
+            CurveKey::new(0.0, 0.0, CurveKeyKind::Linear),
+            CurveKey::new(10.0, 1.0, CurveKeyKind::Linear),
+        ]);
+
+        let mut params = ParameterContainer::default();
+        params.add("Speed", Parameter::Weight(5.0));
+        params.add_curve(CurveParameter {
+            name: "Run".to_string(),
+            resource: Some(Resource::new_ok(
+                ResourceKind::Embedded,
+                CurveResourceState { curve },
+            )),
+            input_param: "Speed".to_string(),
+        });
+        params.add_curve(CurveParameter {
+            name: "Broken".to_string(),
+            resource: None,
+            input_param: "Speed".to_string(),
+        });
+
+        assert_eq!(params.weight("Speed"), Some(5.0));
+        assert_eq!(params.weight("Run"), Some(0.5));
+        assert_eq!(params.weight("Broken"), None);
+        assert_eq!(
+            PoseWeight::Parameter("Run".to_string()).value(&params),
+            Some(0.5)
+        );
+
+        *params.get_mut("Speed").unwrap() = Parameter::Weight(10.0);
+        assert_eq!(params.weight("Run"), Some(1.0));
+
+        // In-place modifications of the curve (hot reloading, editing) are visible immediately.
+        let resource = params.curve("Run").unwrap().resource.clone().unwrap();
+        resource.data_ref().curve =
+            Curve::from(vec![CurveKey::new(0.0, 2.0, CurveKeyKind::Constant)]);
+        assert_eq!(params.weight("Run"), Some(2.0));
+    }
+}
@@ -8,7 +8,7 @@ use crate::{
         reflect::prelude::*,
         visitor::prelude::*,
     },
-    machine::{AnimationPoseSource, ParameterContainer, PoseNode},
+    machine::{AnimationPoseSource, Parameter, ParameterContainer, PoseNode},
     Animation, AnimationContainer, AnimationPose, EntityId,
 };
 use fyrox_core::uuid::{uuid, Uuid};
@@ -65,6 +65,20 @@ pub enum StateAction<T: EntityId> {
     /// are suitable for every situation, in this case you can add randomization to make attacks less
     /// predictable.
     EnableRandomAnimation(Vec<Handle<Animation<T>>>),
+    /// Sets a new value of a parameter of the state machine. The value is set after the layer of the
+    /// state was evaluated, so it affects transitions and weights on the next update.
+    SetParameter {
+        /// Name of the parameter.
+        name: String,
+        /// New value of the parameter.
+        value: Parameter,
+    },
+    /// Starts playback of a sound entity. The state machine itself cannot play sounds, the action is
+    /// passed to the user of the machine. See [`super::Machine::pop_action`] docs for more info.
+    PlaySound(T),
+    /// Sends a message with the given name. The state machine itself cannot send messages, the action
+    /// is passed to the user of the machine. See [`super::Machine::pop_action`] docs for more info.
+    SendMessage(String),
 }
 
 impl<T: EntityId> TypeUuidProvider for StateAction<T> {
@@ -74,10 +88,25 @@ impl<T: EntityId> TypeUuidProvider for StateAction<T> {
 }
 
 impl<T: EntityId> StateAction<T> {
-    /// Applies the action to the given animation container.
+    /// Returns `true` if the action cannot be applied to animations and must be executed by the
+    /// state machine or by its user.
+    pub fn is_external(&self) -> bool {
+        matches!(
+            self,
+            StateAction::SetParameter { .. }
+                | StateAction::PlaySound(_)
+                | StateAction::SendMessage(_)
+        )
+    }
+
+    /// Applies the action to the given animation container. External actions (see
+    /// [`Self::is_external`]) are ignored.
     pub fn apply(&self, animations: &mut AnimationContainer<T>) {
         match self {
-            StateAction::None => {}
+            StateAction::None
+            | StateAction::SetParameter { .. }
+            | StateAction::PlaySound(_)
+            | StateAction::SendMessage(_) => {}
             StateAction::RewindAnimation(animation) => {
                 if let Some(animation) = animations.try_get_mut(*animation) {
                     animation.rewind();
@@ -104,6 +133,18 @@ impl<T: EntityId> StateAction<T> {
     }
 }
 
+/// An external action (see [`StateAction::is_external`]) of a state, that was requested when the
+/// state was entered or left.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateActionRequest<T: EntityId> {
+    /// A state, that requested the action.
+    pub state: Handle<State<T>>,
+    /// Name of the state, that requested the action.
+    pub state_name: String,
+    /// The action to execute.
+    pub action: StateAction<T>,
+}
+
 /// State is a final "container" for animation pose. It has backing pose node which provides a set of values.
 /// States can be connected with each other using _transitions_, states with transitions form a state graph.
 #[derive(Default, Debug, Visit, Clone, Reflect, PartialEq)]
@@ -78,6 +78,8 @@ macro_rules! define_two_args_node {
                         max_value: None,
                         step: None,
                         precision: None,
+                        units: "",
+                        editor: None,
                         doc: "",
                     },
                     FieldInfo {
@@ -93,7 +95,10 @@ macro_rules! define_two_args_node {
                         min_value: None,
                         max_value: None,
                         step: None,
-                        precision: None,doc: "",
+                        precision: None,
+                        units: "",
+                        editor: None,
+                        doc: "",
                     },
                 ])
             }
@@ -231,6 +236,8 @@ impl<T: EntityId> Reflect for NotNode<T> {
             max_value: None,
             step: None,
             precision: None,
+            units: "",
+            editor: None,
             doc: "",
         }])
     }
@@ -375,7 +382,48 @@ impl<T: EntityId> LogicNode<T> {
     }
 }
 
+/// Defines what happens when the condition of an active transition changes, or when a transition from its destination
+/// state becomes possible, while the transition is still blending.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum TransitionInterruption {
+    /// The transition cannot be interrupted, it always runs to completion and the machine lands in the destination
+    /// state.
+    #[default]
+    None,
+    /// If the condition of the transition becomes `false`, the transition starts blending back to its source state.
+    /// If the condition becomes `true` again, the transition continues blending towards its destination state.
+    CancelAndReverse,
+    /// If a transition from the destination state becomes possible, it replaces the active transition. The source
+    /// state stays the same and the progress of the blending is preserved, so only the destination pose changes.
+    Retarget,
+    /// If a transition from the destination state becomes possible, the current blended pose is captured and the
+    /// new transition blends from the captured pose to its destination state.
+    BlendFromCurrentPose,
+}
+
+impl TypeUuidProvider for TransitionInterruption {
+    fn type_uuid() -> Uuid {
+        uuid!("9c5e2b7d-4a18-4f03-b6e1-d08a3f7c5e92")
+    }
+}
+
 /// Transition is a connection between two states with a rule that defines possibility of actual transition with blending.
+///
+/// By default, transitions run to completion, even if their condition changes while blending. This behaviour could be
+/// changed per transition, see [`TransitionInterruption`] docs for more info.
 #[derive(Default, Debug, Clone, Reflect, PartialEq)]
 pub struct Transition<T: EntityId> {
     /// The name of the transition, it is used for debug output.
@@ -402,6 +450,14 @@ pub struct Transition<T: EntityId> {
 
     /// 0 - evaluates `src` pose, 1 - `dest`, 0..1 - blends `src` and `dest`
     pub(crate) blend_factor: f32,
+
+    #[reflect(
+        description = "Defines what happens when the transition is interrupted while blending."
+    )]
+    pub(crate) interruption: TransitionInterruption,
+
+    #[reflect(hidden)]
+    pub(crate) reversed: bool,
 }
 
 impl<T: EntityId> Visit for Transition<T> {
@@ -413,6 +469,7 @@ impl<T: EntityId> Visit for Transition<T> {
         self.source.visit("Source", &mut guard)?;
         self.dest.visit("Dest", &mut guard)?;
         self.blend_factor.visit("BlendFactor", &mut guard)?;
+        let _ = self.interruption.visit("Interruption", &mut guard);
 
         if guard.is_reading() {
             if self.condition.visit("Condition", &mut guard).is_err() {
@@ -463,9 +520,27 @@ impl<T: EntityId> Transition<T> {
             dest,
             blend_factor: 0.0,
             condition: LogicNode::Parameter(rule.to_owned()),
+            interruption: Default::default(),
+            reversed: false,
         }
     }
 
+    /// Sets new interruption policy of the transition. See [`TransitionInterruption`] docs for more info.
+    pub fn set_interruption(&mut self, interruption: TransitionInterruption) {
+        self.interruption = interruption;
+    }
+
+    /// Returns current interruption policy of the transition.
+    pub fn interruption(&self) -> TransitionInterruption {
+        self.interruption
+    }
+
+    /// Returns `true` if the transition is blending back to its source state.
+    #[inline]
+    pub fn is_reversed(&self) -> bool {
+        self.reversed
+    }
+
     /// Returns a reference to the name of the transition.
     #[inline]
     pub fn name(&self) -> &str {
@@ -500,10 +575,15 @@ impl<T: EntityId> Transition<T> {
         &self.condition
     }
 
-    /// Returns true if the transition from the source to the destination state was finished.
+    /// Returns true if the transition from the source to the destination state was finished (or if the transition has
+    /// returned back to the source state, when it is reversed).
     #[inline]
     pub fn is_done(&self) -> bool {
-        (self.transition_time - self.elapsed_time).abs() <= f32::EPSILON
+        if self.reversed {
+            self.elapsed_time <= f32::EPSILON
+        } else {
+            (self.transition_time - self.elapsed_time).abs() <= f32::EPSILON
+        }
     }
 
     /// Returns current blend factor. 0 - evaluates `source` pose, 1 - `destination`, 0..1 - blends `source` and `destination`.
@@ -515,12 +595,19 @@ impl<T: EntityId> Transition<T> {
     pub(super) fn reset(&mut self) {
         self.elapsed_time = 0.0;
         self.blend_factor = 0.0;
+        self.reversed = false;
+    }
+
+    pub(super) fn set_progress(&mut self, blend_factor: f32) {
+        self.elapsed_time = self.transition_time * blend_factor.clamp(0.0, 1.0);
+        self.blend_factor = blend_factor.clamp(0.0, 1.0);
     }
 
     pub(super) fn update(&mut self, dt: f32) {
-        self.elapsed_time += dt;
-        if self.elapsed_time > self.transition_time {
-            self.elapsed_time = self.transition_time;
+        if self.reversed {
+            self.elapsed_time = (self.elapsed_time - dt).max(0.0);
+        } else {
+            self.elapsed_time = (self.elapsed_time + dt).min(self.transition_time);
         }
         self.blend_factor = self.elapsed_time / self.transition_time;
     }
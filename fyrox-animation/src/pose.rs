@@ -21,6 +21,13 @@ impl<T: EntityId> NodePose<T> {
     pub fn blend_with(&mut self, other: &NodePose<T>, weight: f32) {
         self.values.blend_with(&other.values, weight)
     }
+
+    /// Adds the difference between the other pose and the reference pose on top of the current pose. See
+    /// [`super::value::BoundValue::add_difference`] docs for more info.
+    pub fn add_difference(&mut self, other: &NodePose<T>, reference: &NodePose<T>, weight: f32) {
+        self.values
+            .add_difference(&other.values, &reference.values, weight)
+    }
 }
 
 /// Animations pose is a set of node poses. See [`NodePose`] docs for more info.
@@ -67,6 +74,26 @@ impl<T: EntityId> AnimationPose<T> {
             .blend_with(&other.root_motion.clone().unwrap_or_default(), weight);
     }
 
+    /// Adds the difference between another animation pose and a reference pose on top of the current pose using a
+    /// weight coefficient. In other words, the other pose is converted to offsets (deltas) relative to the reference
+    /// pose and then the offsets are applied to the current pose. Node poses that are missing in the current pose or
+    /// in the reference pose are ignored, because there is nothing to apply the offsets to (or nothing to compute them
+    /// from). Root motion of the current pose stays unchanged.
+    pub fn add_difference(
+        &mut self,
+        other: &AnimationPose<T>,
+        reference: &AnimationPose<T>,
+        weight: f32,
+    ) {
+        for (handle, other_pose) in other.poses.iter() {
+            if let (Some(current_pose), Some(reference_pose)) =
+                (self.poses.get_mut(handle), reference.poses.get(handle))
+            {
+                current_pose.add_difference(other_pose, reference_pose, weight);
+            }
+        }
+    }
+
     fn add_node_pose(&mut self, local_pose: NodePose<T>) {
         self.poses.insert(local_pose.node, local_pose);
     }
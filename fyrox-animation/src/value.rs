@@ -1,15 +1,22 @@
 //! A module that contains everything related to numeric values of animation tracks. See [`TrackValue`] docs
 //! for more info.
 
-use crate::core::{
-    algebra::{Unit, UnitQuaternion, Vector2, Vector3, Vector4},
-    math::lerpf,
-    num_traits::AsPrimitive,
-    reflect::prelude::*,
-    visitor::prelude::*,
+use crate::{
+    container::TrackValueKind,
+    core::{
+        algebra::{Unit, UnitQuaternion, Vector2, Vector3, Vector4},
+        color::Color,
+        math::lerpf,
+        num_traits::AsPrimitive,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
 };
 use fyrox_core::log::Log;
-use std::fmt::{Debug, Display, Formatter};
+use std::{
+    any::{Any, TypeId},
+    fmt::{Debug, Display, Formatter},
+};
 
 /// An actual type of a property value.
 #[derive(Visit, Reflect, Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -110,6 +117,9 @@ pub enum ValueType {
     UnitQuaternionF32,
     /// `UnitQuaternion<f64>`
     UnitQuaternionF64,
+
+    /// `Color`, animated as a 4-dimensional vector with components in `[0; 1]` range.
+    Color,
 }
 
 impl Default for ValueType {
@@ -118,6 +128,108 @@ impl Default for ValueType {
     }
 }
 
+macro_rules! define_type_id_mapping {
+    ($type_id:ident, $($ty:ty => $variant:ident),*) => {
+        $(
+            if $type_id == TypeId::of::<$ty>() {
+                return Some(ValueType::$variant);
+            }
+        )*
+    };
+}
+
+impl ValueType {
+    /// Tries to find a value type for a property of a type with the given id. Returns [`None`] if
+    /// the type cannot be animated.
+    pub fn from_type_id(type_id: TypeId) -> Option<Self> {
+        define_type_id_mapping!(type_id,
+            bool => Bool, f32 => F32, f64 => F64, u64 => U64, i64 => I64, u32 => U32, i32 => I32,
+            u16 => U16, i16 => I16, u8 => U8, i8 => I8,
+            Vector2<bool> => Vector2Bool, Vector2<f32> => Vector2F32, Vector2<f64> => Vector2F64,
+            Vector2<u64> => Vector2U64, Vector2<i64> => Vector2I64, Vector2<u32> => Vector2U32,
+            Vector2<i32> => Vector2I32, Vector2<u16> => Vector2U16, Vector2<i16> => Vector2I16,
+            Vector2<u8> => Vector2U8, Vector2<i8> => Vector2I8,
+            Vector3<bool> => Vector3Bool, Vector3<f32> => Vector3F32, Vector3<f64> => Vector3F64,
+            Vector3<u64> => Vector3U64, Vector3<i64> => Vector3I64, Vector3<u32> => Vector3U32,
+            Vector3<i32> => Vector3I32, Vector3<u16> => Vector3U16, Vector3<i16> => Vector3I16,
+            Vector3<u8> => Vector3U8, Vector3<i8> => Vector3I8,
+            Vector4<bool> => Vector4Bool, Vector4<f32> => Vector4F32, Vector4<f64> => Vector4F64,
+            Vector4<u64> => Vector4U64, Vector4<i64> => Vector4I64, Vector4<u32> => Vector4U32,
+            Vector4<i32> => Vector4I32, Vector4<u16> => Vector4U16, Vector4<i16> => Vector4I16,
+            Vector4<u8> => Vector4U8, Vector4<i8> => Vector4I8,
+            UnitQuaternion<f32> => UnitQuaternionF32, UnitQuaternion<f64> => UnitQuaternionF64,
+            Color => Color
+        );
+        None
+    }
+
+    /// Returns a kind of track values, that is used to animate properties of this type.
+    pub fn track_value_kind(self) -> TrackValueKind {
+        match self {
+            ValueType::Bool
+            | ValueType::F32
+            | ValueType::F64
+            | ValueType::U64
+            | ValueType::I64
+            | ValueType::U32
+            | ValueType::I32
+            | ValueType::U16
+            | ValueType::I16
+            | ValueType::U8
+            | ValueType::I8 => TrackValueKind::Real,
+            ValueType::Vector2Bool
+            | ValueType::Vector2F32
+            | ValueType::Vector2F64
+            | ValueType::Vector2U64
+            | ValueType::Vector2I64
+            | ValueType::Vector2U32
+            | ValueType::Vector2I32
+            | ValueType::Vector2U16
+            | ValueType::Vector2I16
+            | ValueType::Vector2U8
+            | ValueType::Vector2I8 => TrackValueKind::Vector2,
+            ValueType::Vector3Bool
+            | ValueType::Vector3F32
+            | ValueType::Vector3F64
+            | ValueType::Vector3U64
+            | ValueType::Vector3I64
+            | ValueType::Vector3U32
+            | ValueType::Vector3I32
+            | ValueType::Vector3U16
+            | ValueType::Vector3I16
+            | ValueType::Vector3U8
+            | ValueType::Vector3I8 => TrackValueKind::Vector3,
+            ValueType::Vector4Bool
+            | ValueType::Vector4F32
+            | ValueType::Vector4F64
+            | ValueType::Vector4U64
+            | ValueType::Vector4I64
+            | ValueType::Vector4U32
+            | ValueType::Vector4I32
+            | ValueType::Vector4U16
+            | ValueType::Vector4I16
+            | ValueType::Vector4U8
+            | ValueType::Vector4I8
+            | ValueType::Color => TrackValueKind::Vector4,
+            ValueType::UnitQuaternionF32 | ValueType::UnitQuaternionF64 => {
+                TrackValueKind::UnitQuaternion
+            }
+        }
+    }
+
+    /// Returns `true` if the type has only a few distinct values (such as `bool`), that cannot be
+    /// interpolated. Such properties should be animated using constant (step) keys.
+    pub fn is_discrete(self) -> bool {
+        matches!(
+            self,
+            ValueType::Bool
+                | ValueType::Vector2Bool
+                | ValueType::Vector3Bool
+                | ValueType::Vector4Bool
+        )
+    }
+}
+
 /// A real value that can be produced by an animation track. Animations always operate on real numbers (`f32`) for any kind
 /// of machine numeric types (including `bool`). This is needed to be able to blend values; final blending result is then
 /// converted to an actual machine type of a target property.
@@ -153,14 +265,43 @@ impl TrackValue {
         }
     }
 
+    /// Adds the difference between the value and the reference value, scaled by the given weight,
+    /// to the current value. Rotations are combined by multiplication with the difference rotation
+    /// (`reference⁻¹ * value`), that is interpolated from the identity rotation using the weight.
+    /// Addition is possible only if the types are the same.
+    pub fn add_difference(&mut self, value: &Self, reference: &Self, weight: f32) {
+        match (self, value, reference) {
+            (Self::Real(a), Self::Real(b), Self::Real(r)) => *a += (*b - *r) * weight,
+            (Self::Vector2(a), Self::Vector2(b), Self::Vector2(r)) => *a += (b - r).scale(weight),
+            (Self::Vector3(a), Self::Vector3(b), Self::Vector3(r)) => *a += (b - r).scale(weight),
+            (Self::Vector4(a), Self::Vector4(b), Self::Vector4(r)) => *a += (b - r).scale(weight),
+            (Self::UnitQuaternion(a), Self::UnitQuaternion(b), Self::UnitQuaternion(r)) => {
+                *a *= nlerp(UnitQuaternion::identity(), &(r.inverse() * b), weight)
+            }
+            _ => (),
+        }
+    }
+
     /// Tries to perform a numeric type casting of the current value to some other and returns a boxed value, that can
     /// be used to set the value using reflection.
     pub fn numeric_type_cast(&self, value_type: ValueType) -> Option<Box<dyn Reflect>> {
+        // Integers are rounded to the nearest value, instead of truncation, this way interpolated
+        // values are evenly distributed between keys.
+        fn round_if_integer<T: 'static>(value: f32) -> f32 {
+            if TypeId::of::<T>() == TypeId::of::<f32>() || TypeId::of::<T>() == TypeId::of::<f64>()
+            {
+                value
+            } else {
+                value.round()
+            }
+        }
+
         fn convert_vec2<T>(vec2: &Vector2<f32>) -> Vector2<T>
         where
             f32: AsPrimitive<T>,
             T: Copy + 'static,
         {
+            let vec2 = vec2.map(round_if_integer::<T>);
             Vector2::new(vec2.x.as_(), vec2.y.as_())
         }
 
@@ -169,6 +310,7 @@ impl TrackValue {
             f32: AsPrimitive<T>,
             T: Copy + 'static,
         {
+            let vec3 = vec3.map(round_if_integer::<T>);
             Vector3::new(vec3.x.as_(), vec3.y.as_(), vec3.z.as_())
         }
 
@@ -177,6 +319,7 @@ impl TrackValue {
             f32: AsPrimitive<T>,
             T: Copy + 'static,
         {
+            let vec4 = vec4.map(round_if_integer::<T>);
             Vector4::new(vec4.x.as_(), vec4.y.as_(), vec4.z.as_(), vec4.w.as_())
         }
 
@@ -185,14 +328,14 @@ impl TrackValue {
                 ValueType::Bool => Some(Box::new(real.ne(&0.0))),
                 ValueType::F32 => Some(Box::new(*real)),
                 ValueType::F64 => Some(Box::new(*real as f64)),
-                ValueType::U64 => Some(Box::new(*real as u64)),
-                ValueType::I64 => Some(Box::new(*real as i64)),
-                ValueType::U32 => Some(Box::new(*real as u32)),
-                ValueType::I32 => Some(Box::new(*real as i32)),
-                ValueType::U16 => Some(Box::new(*real as u16)),
-                ValueType::I16 => Some(Box::new(*real as i16)),
-                ValueType::U8 => Some(Box::new(*real as u8)),
-                ValueType::I8 => Some(Box::new(*real as i8)),
+                ValueType::U64 => Some(Box::new(real.round() as u64)),
+                ValueType::I64 => Some(Box::new(real.round() as i64)),
+                ValueType::U32 => Some(Box::new(real.round() as u32)),
+                ValueType::I32 => Some(Box::new(real.round() as i32)),
+                ValueType::U16 => Some(Box::new(real.round() as u16)),
+                ValueType::I16 => Some(Box::new(real.round() as i16)),
+                ValueType::U8 => Some(Box::new(real.round() as u8)),
+                ValueType::I8 => Some(Box::new(real.round() as i8)),
                 _ => None,
             },
             TrackValue::Vector2(vec2) => match value_type {
@@ -246,6 +389,10 @@ impl TrackValue {
                 ValueType::Vector4I16 => Some(Box::new(convert_vec4::<i16>(vec4))),
                 ValueType::Vector4U8 => Some(Box::new(convert_vec4::<u8>(vec4))),
                 ValueType::Vector4I8 => Some(Box::new(convert_vec4::<i8>(vec4))),
+                ValueType::Color => {
+                    let rgba = vec4.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+                    Some(Box::new(Color::from_rgba(rgba.x, rgba.y, rgba.z, rgba.w)))
+                }
                 _ => None,
             },
             TrackValue::UnitQuaternion(quat) => match value_type {
@@ -255,6 +402,101 @@ impl TrackValue {
             },
         }
     }
+
+    /// Tries to convert the given property value to a track value. It is the inverse operation of
+    /// [`Self::numeric_type_cast`] and could be used to fetch the current value of an animated
+    /// property (for example to create an initial key of a new track).
+    pub fn from_reflect(value: &dyn Reflect) -> Option<Self> {
+        let mut result = None;
+        value.as_any(&mut |any| result = Self::from_any(any));
+        result
+    }
+
+    fn from_any(any: &dyn Any) -> Option<Self> {
+        fn real<T: AsPrimitive<f32>>(any: &dyn Any) -> Option<TrackValue> {
+            any.downcast_ref::<T>().map(|v| TrackValue::Real(v.as_()))
+        }
+
+        fn vec2<T: AsPrimitive<f32> + PartialEq + Debug>(any: &dyn Any) -> Option<TrackValue> {
+            any.downcast_ref::<Vector2<T>>()
+                .map(|v| TrackValue::Vector2(v.map(|c| c.as_())))
+        }
+
+        fn vec3<T: AsPrimitive<f32> + PartialEq + Debug>(any: &dyn Any) -> Option<TrackValue> {
+            any.downcast_ref::<Vector3<T>>()
+                .map(|v| TrackValue::Vector3(v.map(|c| c.as_())))
+        }
+
+        fn vec4<T: AsPrimitive<f32> + PartialEq + Debug>(any: &dyn Any) -> Option<TrackValue> {
+            any.downcast_ref::<Vector4<T>>()
+                .map(|v| TrackValue::Vector4(v.map(|c| c.as_())))
+        }
+
+        fn bool_to_real(value: bool) -> f32 {
+            if value {
+                1.0
+            } else {
+                0.0
+            }
+        }
+
+        if let Some(value) = any.downcast_ref::<bool>() {
+            return Some(TrackValue::Real(bool_to_real(*value)));
+        } else if let Some(value) = any.downcast_ref::<Vector2<bool>>() {
+            return Some(TrackValue::Vector2(value.map(bool_to_real)));
+        } else if let Some(value) = any.downcast_ref::<Vector3<bool>>() {
+            return Some(TrackValue::Vector3(value.map(bool_to_real)));
+        } else if let Some(value) = any.downcast_ref::<Vector4<bool>>() {
+            return Some(TrackValue::Vector4(value.map(bool_to_real)));
+        } else if let Some(value) = any.downcast_ref::<Color>() {
+            return Some(TrackValue::Vector4(value.as_frgba()));
+        } else if let Some(value) = any.downcast_ref::<UnitQuaternion<f32>>() {
+            return Some(TrackValue::UnitQuaternion(*value));
+        } else if let Some(value) = any.downcast_ref::<UnitQuaternion<f64>>() {
+            return Some(TrackValue::UnitQuaternion(value.cast::<f32>()));
+        }
+
+        real::<f32>(any)
+            .or_else(|| real::<f64>(any))
+            .or_else(|| real::<u64>(any))
+            .or_else(|| real::<i64>(any))
+            .or_else(|| real::<u32>(any))
+            .or_else(|| real::<i32>(any))
+            .or_else(|| real::<u16>(any))
+            .or_else(|| real::<i16>(any))
+            .or_else(|| real::<u8>(any))
+            .or_else(|| real::<i8>(any))
+            .or_else(|| vec2::<f32>(any))
+            .or_else(|| vec2::<f64>(any))
+            .or_else(|| vec2::<u64>(any))
+            .or_else(|| vec2::<i64>(any))
+            .or_else(|| vec2::<u32>(any))
+            .or_else(|| vec2::<i32>(any))
+            .or_else(|| vec2::<u16>(any))
+            .or_else(|| vec2::<i16>(any))
+            .or_else(|| vec2::<u8>(any))
+            .or_else(|| vec2::<i8>(any))
+            .or_else(|| vec3::<f32>(any))
+            .or_else(|| vec3::<f64>(any))
+            .or_else(|| vec3::<u64>(any))
+            .or_else(|| vec3::<i64>(any))
+            .or_else(|| vec3::<u32>(any))
+            .or_else(|| vec3::<i32>(any))
+            .or_else(|| vec3::<u16>(any))
+            .or_else(|| vec3::<i16>(any))
+            .or_else(|| vec3::<u8>(any))
+            .or_else(|| vec3::<i8>(any))
+            .or_else(|| vec4::<f32>(any))
+            .or_else(|| vec4::<f64>(any))
+            .or_else(|| vec4::<u64>(any))
+            .or_else(|| vec4::<i64>(any))
+            .or_else(|| vec4::<u32>(any))
+            .or_else(|| vec4::<i32>(any))
+            .or_else(|| vec4::<u16>(any))
+            .or_else(|| vec4::<i16>(any))
+            .or_else(|| vec4::<u8>(any))
+            .or_else(|| vec4::<i8>(any))
+    }
 }
 
 /// Value binding tells the animation system to which of the many properties to set track's value. It has special
@@ -306,6 +548,34 @@ impl BoundValue {
         self.value.blend_with(&other.value, weight);
     }
 
+    /// Adds the difference between the value and the reference value on top of the current value
+    /// using the given weight. See [`TrackValue::add_difference`] for more info. Scale is an
+    /// exception, the current value is multiplied by the ratio of the value and the reference
+    /// value, that is interpolated from the unit scale using the weight.
+    pub fn add_difference(&mut self, value: &Self, reference: &Self, weight: f32) {
+        assert_eq!(self.binding, value.binding);
+        assert_eq!(self.binding, reference.binding);
+        match (
+            &self.binding,
+            &mut self.value,
+            &value.value,
+            &reference.value,
+        ) {
+            (
+                ValueBinding::Scale,
+                TrackValue::Vector3(a),
+                TrackValue::Vector3(b),
+                TrackValue::Vector3(r),
+            ) => {
+                let ratio = b.zip_map(r, |b, r| if r.abs() > f32::EPSILON { b / r } else { 1.0 });
+                a.component_mul_assign(&Vector3::repeat(1.0).lerp(&ratio, weight));
+            }
+            _ => self
+                .value
+                .add_difference(&value.value, &reference.value, weight),
+        }
+    }
+
     /// Sets a property of the given object.
     pub fn apply_to_object(
         &self,
@@ -356,6 +626,19 @@ impl BoundValueCollection {
             }
         }
     }
+
+    /// Tries to add the difference between each value of the other collection and a respective (by binding) value of the
+    /// reference collection on top of a respective value in the current collection. Values without a reference are
+    /// ignored. See [`BoundValue::add_difference`] docs for more info.
+    pub fn add_difference(&mut self, other: &Self, reference: &Self, weight: f32) {
+        for value in self.values.iter_mut() {
+            let other_value = other.values.iter().find(|v| v.binding == value.binding);
+            let reference_value = reference.values.iter().find(|v| v.binding == value.binding);
+            if let (Some(other_value), Some(reference_value)) = (other_value, reference_value) {
+                value.add_difference(other_value, reference_value, weight);
+            }
+        }
+    }
 }
 
 /// Interpolates from `a` to `b` using nlerp, including an additional check to ensure
@@ -371,3 +654,41 @@ pub fn nlerp(mut a: UnitQuaternion<f32>, b: &UnitQuaternion<f32>, w: f32) -> Uni
 pub fn negate_unit_quaternion(a: &UnitQuaternion<f32>) -> UnitQuaternion<f32> {
     Unit::new_unchecked(-a.as_ref())
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector4, color::Color, reflect::Reflect},
+        value::{TrackValue, ValueType},
+    };
+    use std::any::TypeId;
+
+    #[test]
+    fn test_integer_rounding() {
+        let value = TrackValue::Real(2.6);
+        let casted = value.numeric_type_cast(ValueType::I32).unwrap();
+        assert_eq!(casted.downcast::<i32>().ok().map(|v| *v), Some(3));
+    }
+
+    #[test]
+    fn test_color_round_trip() {
+        let color = Color::from_rgba(10, 128, 200, 255);
+        assert_eq!(
+            ValueType::from_type_id(TypeId::of::<Color>()),
+            Some(ValueType::Color)
+        );
+
+        let value = TrackValue::from_reflect(&color as &dyn Reflect).unwrap();
+        assert_eq!(value, TrackValue::Vector4(color.as_frgba()));
+
+        let casted = value.numeric_type_cast(ValueType::Color).unwrap();
+        assert_eq!(casted.downcast::<Color>().ok().map(|v| *v), Some(color));
+
+        let value = TrackValue::Vector4(Vector4::new(2.0, -1.0, 0.5, 1.0));
+        let casted = value.numeric_type_cast(ValueType::Color).unwrap();
+        assert_eq!(
+            casted.downcast::<Color>().ok().map(|v| *v),
+            Some(Color::from_rgba(255, 0, 128, 255))
+        );
+    }
+}
@@ -107,6 +107,13 @@ fn quote_field_prop(
 
     let description = field.description.clone().unwrap_or_default();
 
+    let units = field.units.clone().unwrap_or_default();
+
+    let editor = match field.editor {
+        None => quote! { None },
+        Some(ref v) => quote! { Some(#v) },
+    };
+
     quote! {
         FieldInfo {
             owner_type_id: std::any::TypeId::of::<Self>(),
@@ -122,6 +129,8 @@ fn quote_field_prop(
             step: #step,
             precision: #precision,
             description: #description,
+            units: #units,
+            editor: #editor,
             type_name: std::any::type_name::<#ty>()
         }
     }
@@ -210,6 +210,18 @@ pub struct FieldArgs {
     /// Description of the property.
     #[darling(default)]
     pub description: Option<String>,
+
+    /// `#[reflect(units = "m/s")]`
+    ///
+    /// Units of measurement of the property.
+    #[darling(default)]
+    pub units: Option<String>,
+
+    /// `#[reflect(editor = "slider")]`
+    ///
+    /// A name of a custom property editor.
+    #[darling(default)]
+    pub editor: Option<String>,
 }
 
 impl FieldArgs {
@@ -323,6 +323,8 @@ fn default_prop() -> FieldInfo<'static, 'static> {
         max_value: None,
         step: None,
         precision: None,
+        units: "",
+        editor: None,
         description: "",
         type_name: "",
         doc: "",
@@ -380,7 +382,9 @@ fn inspect_attributes() {
             max_value = 1.1,
             step = 0.1,
             precision = 3,
-            description = "This is a property description."
+            description = "This is a property description.",
+            units = "m/s",
+            editor = "slider"
         )]
         y: f32,
     }
@@ -409,6 +413,8 @@ fn inspect_attributes() {
             step: Some(0.1),
             precision: Some(3),
             description: "This is a property description.",
+            units: "m/s",
+            editor: Some("slider"),
             type_name: std::any::type_name::<f32>(),
             doc: "",
         },
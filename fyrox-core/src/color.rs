@@ -289,6 +289,389 @@ impl From<Color> for Hsl {
     }
 }
 
+/// Converts a single sRGB channel in `[0; 1]` range to linear space using exact sRGB transfer
+/// function (unlike [`Color::srgb_to_linear`] which uses the `2.2` gamma approximation).
+#[inline]
+pub fn srgb_channel_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a single linear channel in `[0; 1]` range to sRGB space using exact sRGB transfer
+/// function.
+#[inline]
+pub fn linear_channel_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn color_from_srgb_f32(r: f32, g: f32, b: f32) -> Color {
+    Color::opaque(
+        (r.clamp(0.0, 1.0) * 255.0).round() as u8,
+        (g.clamp(0.0, 1.0) * 255.0).round() as u8,
+        (b.clamp(0.0, 1.0) * 255.0).round() as u8,
+    )
+}
+
+/// A color in OKLab perceptual color space. Equal distances in this space correspond to (roughly)
+/// equal perceived differences, which makes it a good choice for interpolation and palette
+/// generation: a gradient from blue to yellow in OKLab does not go through a muddy gray as it does
+/// in sRGB. Alpha is not stored.
+///
+/// # References
+///
+/// "A perceptual color space for image processing" by Björn Ottosson, 2020.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Visit, Reflect)]
+pub struct OkLab {
+    /// Perceived lightness, `[0; 1]` range.
+    pub l: f32,
+    /// Green-red axis, roughly `[-0.4; 0.4]` range.
+    pub a: f32,
+    /// Blue-yellow axis, roughly `[-0.4; 0.4]` range.
+    pub b: f32,
+}
+
+impl OkLab {
+    pub fn new(l: f32, a: f32, b: f32) -> Self {
+        Self { l, a, b }
+    }
+
+    /// Linearly interpolates two colors.
+    #[inline]
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            l: self.l + (other.l - self.l) * t,
+            a: self.a + (other.a - self.a) * t,
+            b: self.b + (other.b - self.b) * t,
+        }
+    }
+
+    /// Returns perceptual distance (delta E) between two colors.
+    #[inline]
+    pub fn distance(&self, other: &Self) -> f32 {
+        ((self.l - other.l).powi(2) + (self.a - other.a).powi(2) + (self.b - other.b).powi(2))
+            .sqrt()
+    }
+}
+
+impl From<Color> for OkLab {
+    fn from(color: Color) -> Self {
+        let f = color.as_frgb();
+        let r = srgb_channel_to_linear(f.x);
+        let g = srgb_channel_to_linear(f.y);
+        let b = srgb_channel_to_linear(f.z);
+
+        let l = (0.41222146 * r + 0.53633255 * g + 0.051445995 * b).cbrt();
+        let m = (0.2119035 * r + 0.6806995 * g + 0.10739696 * b).cbrt();
+        let s = (0.08830246 * r + 0.28171885 * g + 0.6299787 * b).cbrt();
+
+        Self {
+            l: 0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
+            a: 1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+            b: 0.025904037 * l + 0.78277177 * m - 0.80867577 * s,
+        }
+    }
+}
+
+impl From<OkLab> for Color {
+    fn from(lab: OkLab) -> Self {
+        let l = (lab.l + 0.39633778 * lab.a + 0.21580376 * lab.b).powi(3);
+        let m = (lab.l - 0.105561346 * lab.a - 0.06385417 * lab.b).powi(3);
+        let s = (lab.l - 0.08948418 * lab.a - 1.2914855 * lab.b).powi(3);
+
+        let r = 4.0767417 * l - 3.3077116 * m + 0.23096994 * s;
+        let g = -1.268438 * l + 2.6097574 * m - 0.34131938 * s;
+        let b = -0.0041960863 * l - 0.7034186 * m + 1.7076147 * s;
+
+        color_from_srgb_f32(
+            linear_channel_to_srgb(r),
+            linear_channel_to_srgb(g),
+            linear_channel_to_srgb(b),
+        )
+    }
+}
+
+/// A color in OKLCH color space - cylindrical form of [`OkLab`]. Rotating the hue while keeping
+/// lightness and chroma produces colors of the same perceived brightness, which is used by
+/// [`Color::harmony`].
+#[derive(Default, Debug, Copy, Clone, PartialEq, Visit, Reflect)]
+pub struct OkLch {
+    /// Perceived lightness, `[0; 1]` range.
+    pub l: f32,
+    /// Chroma (colorfulness), roughly `[0; 0.4]` range.
+    pub c: f32,
+    /// Hue in degrees, `[0; 360]` range.
+    pub h: f32,
+}
+
+impl OkLch {
+    pub fn new(l: f32, c: f32, h: f32) -> Self {
+        Self {
+            l,
+            c,
+            h: h.rem_euclid(360.0),
+        }
+    }
+
+    /// Returns a copy of the color with its hue rotated by the given amount of degrees.
+    #[inline]
+    pub fn rotate_hue(&self, degrees: f32) -> Self {
+        Self::new(self.l, self.c, self.h + degrees)
+    }
+}
+
+impl From<OkLab> for OkLch {
+    fn from(lab: OkLab) -> Self {
+        Self::new(
+            lab.l,
+            (lab.a * lab.a + lab.b * lab.b).sqrt(),
+            lab.b.atan2(lab.a).to_degrees(),
+        )
+    }
+}
+
+impl From<OkLch> for OkLab {
+    fn from(lch: OkLch) -> Self {
+        let (sin, cos) = lch.h.to_radians().sin_cos();
+        Self {
+            l: lch.l,
+            a: lch.c * cos,
+            b: lch.c * sin,
+        }
+    }
+}
+
+impl From<Color> for OkLch {
+    fn from(color: Color) -> Self {
+        OkLab::from(color).into()
+    }
+}
+
+impl From<OkLch> for Color {
+    fn from(lch: OkLch) -> Self {
+        OkLab::from(lch).into()
+    }
+}
+
+// HSLuv constants, see https://www.hsluv.org/math/
+const HSLUV_M: [[f32; 3]; 3] = [
+    [3.24097, -1.5373832, -0.49861076],
+    [-0.96924364, 1.8759675, 0.04155506],
+    [0.05563008, -0.20397696, 1.0569715],
+];
+const HSLUV_M_INV: [[f32; 3]; 3] = [
+    [0.4123908, 0.35758433, 0.1804808],
+    [0.212639, 0.71516865, 0.07219232],
+    [0.019330818, 0.11919478, 0.95053214],
+];
+const HSLUV_REF_U: f32 = 0.19783;
+const HSLUV_REF_V: f32 = 0.46832;
+const HSLUV_KAPPA: f32 = 903.2963;
+const HSLUV_EPSILON: f32 = 0.008856452;
+
+/// A color in HSLuv color space - human-friendly alternative to [`Hsl`], based on CIELUV. Unlike
+/// HSL, colors with the same lightness have the same perceived brightness, which makes it well
+/// suited for UI theming: changing hue of a theme does not change its contrast.
+///
+/// # References
+///
+/// <https://www.hsluv.org>
+#[derive(Default, Debug, Copy, Clone, PartialEq, Visit, Reflect)]
+pub struct Hsluv {
+    /// [0; 360] range
+    hue: f32,
+    /// [0; 100] range
+    saturation: f32,
+    /// [0; 100] range
+    lightness: f32,
+}
+
+impl Hsluv {
+    /// Hue: [0; 360] range
+    /// Saturation: [0; 100] range
+    /// Lightness: [0; 100] range
+    pub fn new(hue: f32, saturation: f32, lightness: f32) -> Self {
+        Self {
+            hue: hue.rem_euclid(360.0),
+            saturation: saturation.clamp(0.0, 100.0),
+            lightness: lightness.clamp(0.0, 100.0),
+        }
+    }
+
+    pub fn hue(&self) -> f32 {
+        self.hue
+    }
+
+    pub fn set_hue(&mut self, hue: f32) {
+        self.hue = hue.rem_euclid(360.0);
+    }
+
+    pub fn saturation(&self) -> f32 {
+        self.saturation
+    }
+
+    pub fn set_saturation(&mut self, saturation: f32) {
+        self.saturation = saturation.clamp(0.0, 100.0)
+    }
+
+    pub fn lightness(&self) -> f32 {
+        self.lightness
+    }
+
+    pub fn set_lightness(&mut self, lightness: f32) {
+        self.lightness = lightness.clamp(0.0, 100.0)
+    }
+
+    /// Returns the maximum chroma of a color with given lightness and hue that still fits in sRGB
+    /// gamut. The gamut in LCh space is bounded by six lines (two per RGB channel).
+    fn max_chroma(lightness: f32, hue: f32) -> f32 {
+        let sub1 = (lightness + 16.0).powi(3) / 1560896.0;
+        let sub2 = if sub1 > HSLUV_EPSILON {
+            sub1
+        } else {
+            lightness / HSLUV_KAPPA
+        };
+        let (sin, cos) = hue.to_radians().sin_cos();
+
+        let mut min = f32::MAX;
+        for [m1, m2, m3] in HSLUV_M {
+            for t in [0.0, 1.0] {
+                let top1 = (284517.0 * m1 - 94839.0 * m3) * sub2;
+                let top2 = (838422.0 * m3 + 769860.0 * m2 + 731718.0 * m1) * lightness * sub2
+                    - 769860.0 * t * lightness;
+                let bottom = (632260.0 * m3 - 126452.0 * m2) * sub2 + 126452.0 * t;
+                let slope = top1 / bottom;
+                let intercept = top2 / bottom;
+                let length = intercept / (sin - slope * cos);
+                if length >= 0.0 {
+                    min = min.min(length);
+                }
+            }
+        }
+        min
+    }
+}
+
+impl From<Hsluv> for Color {
+    fn from(v: Hsluv) -> Self {
+        // HSLuv -> LCh
+        let l = v.lightness;
+        let c = if !(0.00001..=99.99999).contains(&l) {
+            0.0
+        } else {
+            Hsluv::max_chroma(l, v.hue) / 100.0 * v.saturation
+        };
+
+        if l <= 0.0 {
+            return Color::BLACK;
+        }
+
+        // LCh -> Luv
+        let (sin, cos) = v.hue.to_radians().sin_cos();
+        let u = c * cos;
+        let vv = c * sin;
+
+        // Luv -> XYZ
+        let var_u = u / (13.0 * l) + HSLUV_REF_U;
+        let var_v = vv / (13.0 * l) + HSLUV_REF_V;
+        let y = if l <= 8.0 {
+            l / HSLUV_KAPPA
+        } else {
+            ((l + 16.0) / 116.0).powi(3)
+        };
+        let x = -(9.0 * y * var_u) / ((var_u - 4.0) * var_v - var_u * var_v);
+        let z = (9.0 * y - 15.0 * var_v * y - var_v * x) / (3.0 * var_v);
+
+        // XYZ -> sRGB
+        let [r, g, b] =
+            HSLUV_M.map(|[m1, m2, m3]| linear_channel_to_srgb(m1 * x + m2 * y + m3 * z));
+        color_from_srgb_f32(r, g, b)
+    }
+}
+
+impl From<Color> for Hsluv {
+    fn from(color: Color) -> Self {
+        // sRGB -> XYZ
+        let f = color.as_frgb();
+        let linear = [
+            srgb_channel_to_linear(f.x),
+            srgb_channel_to_linear(f.y),
+            srgb_channel_to_linear(f.z),
+        ];
+        let [x, y, z] =
+            HSLUV_M_INV.map(|[m1, m2, m3]| m1 * linear[0] + m2 * linear[1] + m3 * linear[2]);
+
+        // XYZ -> Luv
+        let l = if y <= HSLUV_EPSILON {
+            y * HSLUV_KAPPA
+        } else {
+            116.0 * y.cbrt() - 16.0
+        };
+        let divider = x + 15.0 * y + 3.0 * z;
+        let (u, v) = if l <= 0.0 || divider <= 0.0 {
+            (0.0, 0.0)
+        } else {
+            (
+                13.0 * l * (4.0 * x / divider - HSLUV_REF_U),
+                13.0 * l * (9.0 * y / divider - HSLUV_REF_V),
+            )
+        };
+
+        // Luv -> LCh
+        let c = (u * u + v * v).sqrt();
+        let h = if c < 0.00001 {
+            0.0
+        } else {
+            v.atan2(u).to_degrees().rem_euclid(360.0)
+        };
+
+        // LCh -> HSLuv
+        let s = if !(0.00001..=99.99999).contains(&l) {
+            0.0
+        } else {
+            (c / Hsluv::max_chroma(l, h) * 100.0).min(100.0)
+        };
+
+        Hsluv::new(h, s, l)
+    }
+}
+
+/// Color harmony is a set of colors that look pleasing together. Harmonies are built by rotating
+/// the hue of a base color in [`OkLch`] space, so all colors of a harmony have the same perceived
+/// lightness and chroma.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ColorHarmony {
+    /// Base color and the color on the opposite side of the color wheel.
+    Complementary,
+    /// Base color and two neighbour colors (±30 degrees).
+    Analogous,
+    /// Three colors evenly spaced on the color wheel.
+    Triadic,
+    /// Base color and two neighbours of its complementary color (180 ± 30 degrees).
+    SplitComplementary,
+    /// Four colors evenly spaced on the color wheel.
+    Tetradic,
+}
+
+impl ColorHarmony {
+    /// Returns hue offsets (in degrees) of the harmony, the first one is always zero.
+    pub fn hue_offsets(self) -> &'static [f32] {
+        match self {
+            ColorHarmony::Complementary => &[0.0, 180.0],
+            ColorHarmony::Analogous => &[0.0, -30.0, 30.0],
+            ColorHarmony::Triadic => &[0.0, 120.0, 240.0],
+            ColorHarmony::SplitComplementary => &[0.0, 150.0, 210.0],
+            ColorHarmony::Tetradic => &[0.0, 90.0, 180.0, 270.0],
+        }
+    }
+}
+
 impl Color {
     pub const WHITE: Self = Self::repeat(255);
     pub const BLACK: Self = Self::opaque(0, 0, 0);
@@ -679,6 +1062,32 @@ impl Color {
             a,
         }
     }
+    /// Interpolates two colors in [`OkLab`] space. Unlike [`Self::lerp`], the intermediate colors
+    /// have smooth perceived lightness and do not get "muddy". Alpha is interpolated linearly.
+    #[inline]
+    pub fn lerp_oklab(self, other: Self, t: f32) -> Self {
+        let a = OkLab::from(self);
+        let b = OkLab::from(other);
+        let alpha = self.a as f32 + (other.a as f32 - self.a as f32) * t;
+        Color::from(a.lerp(&b, t)).with_new_alpha(alpha.round().clamp(0.0, 255.0) as u8)
+    }
+
+    /// Generates a set of colors that form the given harmony with this color. The first color of
+    /// the set is always this color. Alpha of the color is preserved.
+    pub fn harmony(self, harmony: ColorHarmony) -> Vec<Color> {
+        let lch = OkLch::from(self);
+        harmony
+            .hue_offsets()
+            .iter()
+            .map(|offset| {
+                if *offset == 0.0 {
+                    self
+                } else {
+                    Color::from(lch.rotate_hue(*offset)).with_new_alpha(self.a)
+                }
+            })
+            .collect()
+    }
 }
 
 impl Add for Color {
@@ -722,7 +1131,7 @@ impl SubAssign for Color {
 #[cfg(test)]
 mod test {
     use crate::algebra::{Vector3, Vector4};
-    use crate::color::{Color, Hsl, Hsv};
+    use crate::color::{Color, ColorHarmony, Hsl, Hsluv, Hsv, OkLab, OkLch};
 
     #[test]
     fn test_hsl() {
@@ -1022,4 +1431,85 @@ mod test {
         color -= Color::opaque(155, 155, 155);
         assert_eq!(color, Color::from_rgba(100, 100, 100, 0));
     }
+
+    fn assert_color_near(a: Color, b: Color) {
+        let d = |x: u8, y: u8| (x as i32 - y as i32).abs();
+        assert!(
+            d(a.r, b.r) <= 1 && d(a.g, b.g) <= 1 && d(a.b, b.b) <= 1,
+            "{a:?} != {b:?}"
+        );
+    }
+
+    #[test]
+    fn test_oklab() {
+        let white = OkLab::from(Color::WHITE);
+        assert!((white.l - 1.0).abs() < 1.0e-3);
+        assert!(white.a.abs() < 1.0e-3 && white.b.abs() < 1.0e-3);
+
+        // Reference values from https://bottosson.github.io/posts/oklab/
+        let red = OkLab::from(Color::RED);
+        assert!((red.l - 0.628).abs() < 1.0e-3);
+        assert!((red.a - 0.2249).abs() < 1.0e-3);
+        assert!((red.b - 0.1258).abs() < 1.0e-3);
+
+        for color in [
+            Color::RED,
+            Color::GREEN,
+            Color::BLUE,
+            Color::BLACK,
+            Color::opaque(12, 200, 99),
+            Color::opaque(240, 128, 7),
+        ] {
+            assert_color_near(Color::from(OkLab::from(color)), color);
+            assert_color_near(Color::from(OkLch::from(color)), color);
+        }
+    }
+
+    #[test]
+    fn test_hsluv() {
+        let red = Hsluv::from(Color::RED);
+        assert!((red.hue() - 12.177).abs() < 0.05);
+        assert!((red.saturation() - 100.0).abs() < 0.05);
+        assert!((red.lightness() - 53.237).abs() < 0.05);
+
+        assert_eq!(Hsluv::from(Color::BLACK).lightness(), 0.0);
+        assert!((Hsluv::from(Color::WHITE).lightness() - 100.0).abs() < 0.01);
+
+        for color in [
+            Color::RED,
+            Color::GREEN,
+            Color::BLUE,
+            Color::WHITE,
+            Color::opaque(12, 200, 99),
+            Color::opaque(240, 128, 7),
+        ] {
+            assert_color_near(Color::from(Hsluv::from(color)), color);
+        }
+    }
+
+    #[test]
+    fn test_color_lerp_oklab() {
+        assert_eq!(Color::RED.lerp_oklab(Color::BLUE, 0.0), Color::RED);
+        assert_eq!(Color::RED.lerp_oklab(Color::BLUE, 1.0), Color::BLUE);
+        let mid = Color::TRANSPARENT.lerp_oklab(Color::WHITE, 0.5);
+        assert_eq!(mid.a, 128);
+        // Half of the perceived lightness is darker than the arithmetic mid-gray.
+        assert!((95..105).contains(&mid.r));
+    }
+
+    #[test]
+    fn test_color_harmony() {
+        let base = Color::opaque(150, 110, 100);
+        let complementary = base.harmony(ColorHarmony::Complementary);
+        assert_eq!(complementary.len(), 2);
+        assert_eq!(complementary[0], base);
+        let lch_a = OkLch::from(base);
+        let lch_b = OkLch::from(complementary[1]);
+        assert!((lch_a.l - lch_b.l).abs() < 0.05);
+        let hue_diff = (lch_a.h - lch_b.h).rem_euclid(360.0);
+        assert!((hue_diff - 180.0).abs() < 5.0);
+
+        assert_eq!(base.harmony(ColorHarmony::Tetradic).len(), 4);
+        assert_eq!(base.harmony(ColorHarmony::Triadic).len(), 3);
+    }
 }
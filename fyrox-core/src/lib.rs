@@ -36,13 +36,16 @@ pub mod color_gradient;
 pub mod io;
 pub mod log;
 pub mod math;
+pub mod memory;
 pub mod net;
+pub mod noise;
 pub mod numeric_range;
 pub mod pool;
 pub mod profiler;
 pub mod quadtree;
 pub mod rectpack;
 pub mod reflect;
+pub mod rng;
 pub mod sparse;
 pub mod sstorage;
 pub mod task;
@@ -9,7 +9,10 @@ use crate::math::curve::CurveKeyKind;
 use crate::Uuid;
 use crate::{
     algebra::Scalar,
-    math::{aabb::AxisAlignedBoundingBox, frustum::Frustum, plane::Plane},
+    math::{
+        aabb::AxisAlignedBoundingBox, capsule::Capsule, frustum::Frustum, obb::OrientedBoundingBox,
+        plane::Plane,
+    },
     num_traits::NumAssign,
     reflect::prelude::*,
     visitor::prelude::*,
@@ -60,6 +63,30 @@ impl Visit for AxisAlignedBoundingBox {
     }
 }
 
+impl Visit for OrientedBoundingBox {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.center.visit("Center", &mut region)?;
+        self.axes.visit("Axes", &mut region)?;
+        self.half_extents.visit("HalfExtents", &mut region)?;
+
+        Ok(())
+    }
+}
+
+impl Visit for Capsule {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.begin.visit("Begin", &mut region)?;
+        self.end.visit("End", &mut region)?;
+        self.radius.visit("Radius", &mut region)?;
+
+        Ok(())
+    }
+}
+
 impl Visit for Frustum {
     fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
         let mut region = visitor.enter_region(name)?;
@@ -0,0 +1,397 @@
+//! Memory usage tracking. It consists of two parts - an instrumented allocator ([`TrackingAllocator`]) and
+//! allocation tags ([`MemoryTag`]). The allocator is optional, it must be installed as a global allocator
+//! by a final executable (a game or the editor). Allocation tags are set by the engine for its subsystems
+//! (renderer, user interface, physics, resources, etc.) using [`scope`] and then every allocation made in
+//! that scope is attributed to the respective subsystem. Memory usage can be fetched at any time using
+//! [`report`].
+//!
+//! ## Example
+//!
+//! ```rust
+//! use fyrox_core::memory::{self, MemoryTag, TrackingAllocator};
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: TrackingAllocator = TrackingAllocator::system();
+//!
+//! fn main() {
+//!     {
+//!         let _scope = memory::scope(MemoryTag::Renderer);
+//!         let _buffer = vec![0u8; 1024];
+//!         // The buffer is attributed to the renderer.
+//!     }
+//!
+//!     println!("{}", memory::report());
+//! }
+//! ```
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    fmt::{Display, Formatter},
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    task::{Context, Poll},
+};
+
+/// A tag of a subsystem, that is used to attribute allocations.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum MemoryTag {
+    /// Any allocation that happened outside of any tagged scope.
+    General = 0,
+    /// Renderer allocations.
+    Renderer = 1,
+    /// User interface allocations.
+    Ui = 2,
+    /// Physics allocations.
+    Physics = 3,
+    /// Resources allocations.
+    Resources = 4,
+    /// Sound allocations.
+    Sound = 5,
+    /// Scene graph allocations.
+    Scene = 6,
+    /// Scripts and plugins allocations.
+    Scripts = 7,
+}
+
+impl MemoryTag {
+    /// Total amount of tags.
+    pub const COUNT: usize = 8;
+
+    /// All possible tags.
+    pub const ALL: [MemoryTag; Self::COUNT] = [
+        MemoryTag::General,
+        MemoryTag::Renderer,
+        MemoryTag::Ui,
+        MemoryTag::Physics,
+        MemoryTag::Resources,
+        MemoryTag::Sound,
+        MemoryTag::Scene,
+        MemoryTag::Scripts,
+    ];
+
+    fn from_u8(value: u8) -> Self {
+        Self::ALL
+            .get(value as usize)
+            .copied()
+            .unwrap_or(MemoryTag::General)
+    }
+
+    /// Returns a human-readable name of the tag.
+    pub fn name(self) -> &'static str {
+        match self {
+            MemoryTag::General => "General",
+            MemoryTag::Renderer => "Renderer",
+            MemoryTag::Ui => "UI",
+            MemoryTag::Physics => "Physics",
+            MemoryTag::Resources => "Resources",
+            MemoryTag::Sound => "Sound",
+            MemoryTag::Scene => "Scene",
+            MemoryTag::Scripts => "Scripts",
+        }
+    }
+}
+
+thread_local! {
+    static CURRENT_TAG: Cell<u8> = const { Cell::new(MemoryTag::General as u8) };
+}
+
+/// Returns current allocation tag of the calling thread.
+pub fn current_tag() -> MemoryTag {
+    MemoryTag::from_u8(
+        CURRENT_TAG
+            .try_with(|tag| tag.get())
+            .unwrap_or(MemoryTag::General as u8),
+    )
+}
+
+/// A guard, that restores previous allocation tag when dropped. See [`scope`] for more info.
+#[must_use = "the tag is reset as soon as the scope is dropped"]
+pub struct MemoryScope {
+    prev: u8,
+}
+
+impl Drop for MemoryScope {
+    fn drop(&mut self) {
+        let _ = CURRENT_TAG.try_with(|tag| tag.set(self.prev));
+    }
+}
+
+/// Sets allocation tag of the calling thread until the returned guard is dropped. Scopes can be nested,
+/// the innermost scope wins.
+pub fn scope(tag: MemoryTag) -> MemoryScope {
+    let prev = CURRENT_TAG
+        .try_with(|current| current.replace(tag as u8))
+        .unwrap_or(MemoryTag::General as u8);
+    MemoryScope { prev }
+}
+
+/// A future, that sets an allocation tag every time it is polled. See [`tagged`] for more info.
+pub struct TaggedFuture<F> {
+    tag: MemoryTag,
+    future: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for TaggedFuture<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let _scope = scope(self.tag);
+        self.future.as_mut().poll(cx)
+    }
+}
+
+/// Wraps the given future so every allocation made while it is polled is attributed to the given tag.
+/// Unlike [`scope`], it works correctly for futures that are moved between threads.
+pub fn tagged<F: Future>(tag: MemoryTag, future: F) -> TaggedFuture<F> {
+    TaggedFuture {
+        tag,
+        future: Box::pin(future),
+    }
+}
+
+struct TagCounters {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+    total: AtomicUsize,
+    allocations: AtomicUsize,
+    live_allocations: AtomicUsize,
+}
+
+impl TagCounters {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const NEW: Self = Self {
+        current: AtomicUsize::new(0),
+        peak: AtomicUsize::new(0),
+        total: AtomicUsize::new(0),
+        allocations: AtomicUsize::new(0),
+        live_allocations: AtomicUsize::new(0),
+    };
+}
+
+static COUNTERS: [TagCounters; MemoryTag::COUNT] = [TagCounters::NEW; MemoryTag::COUNT];
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+fn on_alloc(tag: u8, size: usize) {
+    let counters = &COUNTERS[tag as usize];
+    let current = counters.current.fetch_add(size, Ordering::Relaxed) + size;
+    counters.peak.fetch_max(current, Ordering::Relaxed);
+    counters.total.fetch_add(size, Ordering::Relaxed);
+    counters.allocations.fetch_add(1, Ordering::Relaxed);
+    counters.live_allocations.fetch_add(1, Ordering::Relaxed);
+}
+
+fn on_dealloc(tag: u8, size: usize) {
+    let counters = &COUNTERS[tag as usize];
+    counters.current.fetch_sub(size, Ordering::Relaxed);
+    counters.live_allocations.fetch_sub(1, Ordering::Relaxed);
+}
+
+/// An instrumented allocator, that wraps some other allocator and attributes every allocation to current
+/// [`MemoryTag`]. Every allocation is prefixed with a small header, that stores the tag, so deallocations
+/// are attributed correctly even if they happen on a different thread or in a different scope. The
+/// overhead is one alignment unit (at least 16 bytes) per allocation.
+pub struct TrackingAllocator<A = System> {
+    inner: A,
+}
+
+impl TrackingAllocator<System> {
+    /// Creates new tracking allocator, that uses system allocator.
+    pub const fn system() -> Self {
+        Self { inner: System }
+    }
+}
+
+impl<A> TrackingAllocator<A> {
+    /// Creates new tracking allocator, that wraps the given allocator.
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+
+    #[inline]
+    fn header_size(layout: &Layout) -> usize {
+        layout.align().max(16)
+    }
+
+    #[inline]
+    fn extended_layout(layout: &Layout) -> Option<Layout> {
+        Layout::from_size_align(
+            layout.size().checked_add(Self::header_size(layout))?,
+            layout.align().max(16),
+        )
+        .ok()
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for TrackingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        INSTALLED.store(true, Ordering::Relaxed);
+
+        let Some(extended) = Self::extended_layout(&layout) else {
+            return std::ptr::null_mut();
+        };
+        let base = self.inner.alloc(extended);
+        if base.is_null() {
+            return base;
+        }
+        let tag = current_tag() as u8;
+        let ptr = base.add(Self::header_size(&layout));
+        // Store the tag right before the actual data.
+        ptr.sub(1).write(tag);
+        on_alloc(tag, layout.size());
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let tag = ptr.sub(1).read();
+        on_dealloc(tag, layout.size());
+        let base = ptr.sub(Self::header_size(&layout));
+        // The layout was valid on allocation, so it is valid here as well.
+        self.inner
+            .dealloc(base, Self::extended_layout(&layout).unwrap());
+    }
+}
+
+/// Memory usage statistics of a single tag.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TagMemoryStatistics {
+    /// Amount of bytes that is currently allocated.
+    pub current_bytes: usize,
+    /// Maximum amount of bytes that was allocated at the same time.
+    pub peak_bytes: usize,
+    /// Total amount of bytes that was ever allocated.
+    pub total_bytes: usize,
+    /// Total amount of allocations.
+    pub allocations: usize,
+    /// Amount of allocations that are not yet deallocated.
+    pub live_allocations: usize,
+}
+
+/// Memory usage report.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    /// `true` if [`TrackingAllocator`] is installed as a global allocator. If it is not installed, every
+    /// statistics in the report is zero.
+    pub is_tracking: bool,
+    /// Per-tag statistics.
+    pub tags: [TagMemoryStatistics; MemoryTag::COUNT],
+}
+
+impl MemoryReport {
+    /// Returns statistics of the given tag.
+    pub fn tag(&self, tag: MemoryTag) -> &TagMemoryStatistics {
+        &self.tags[tag as usize]
+    }
+
+    /// Returns total amount of bytes, that is currently allocated.
+    pub fn total_current_bytes(&self) -> usize {
+        self.tags.iter().map(|t| t.current_bytes).sum()
+    }
+}
+
+fn format_bytes(f: &mut Formatter<'_>, bytes: usize) -> std::fmt::Result {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    write!(f, "{:.2} {}", value, UNITS[unit])
+}
+
+impl Display for MemoryReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if !self.is_tracking {
+            return writeln!(
+                f,
+                "Memory tracking is disabled. Install TrackingAllocator as global allocator to enable it."
+            );
+        }
+
+        writeln!(f, "Memory Usage")?;
+        for tag in MemoryTag::ALL {
+            let stats = self.tag(tag);
+            write!(f, "{}: ", tag.name())?;
+            format_bytes(f, stats.current_bytes)?;
+            write!(f, " (peak ")?;
+            format_bytes(f, stats.peak_bytes)?;
+            writeln!(f, ", {} allocations)", stats.live_allocations)?;
+        }
+        write!(f, "Total: ")?;
+        format_bytes(f, self.total_current_bytes())?;
+        writeln!(f)
+    }
+}
+
+/// Returns current memory usage report.
+pub fn report() -> MemoryReport {
+    let mut report = MemoryReport {
+        is_tracking: INSTALLED.load(Ordering::Relaxed),
+        tags: Default::default(),
+    };
+    for (stats, counters) in report.tags.iter_mut().zip(COUNTERS.iter()) {
+        *stats = TagMemoryStatistics {
+            current_bytes: counters.current.load(Ordering::Relaxed),
+            peak_bytes: counters.peak.load(Ordering::Relaxed),
+            total_bytes: counters.total.load(Ordering::Relaxed),
+            allocations: counters.allocations.load(Ordering::Relaxed),
+            live_allocations: counters.live_allocations.load(Ordering::Relaxed),
+        };
+    }
+    report
+}
+
+/// Resets peak values of every tag to current values.
+pub fn reset_peaks() {
+    for counters in COUNTERS.iter() {
+        counters
+            .peak
+            .store(counters.current.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_scopes() {
+        assert_eq!(current_tag(), MemoryTag::General);
+        {
+            let _renderer = scope(MemoryTag::Renderer);
+            assert_eq!(current_tag(), MemoryTag::Renderer);
+            {
+                let _ui = scope(MemoryTag::Ui);
+                assert_eq!(current_tag(), MemoryTag::Ui);
+            }
+            assert_eq!(current_tag(), MemoryTag::Renderer);
+        }
+        assert_eq!(current_tag(), MemoryTag::General);
+    }
+
+    #[test]
+    fn test_tracking_allocator() {
+        let allocator = TrackingAllocator::system();
+        let layout = Layout::from_size_align(1000, 64).unwrap();
+        let before = report().tag(MemoryTag::Physics).current_bytes;
+        unsafe {
+            let ptr = {
+                let _scope = scope(MemoryTag::Physics);
+                allocator.alloc(layout)
+            };
+            assert!(!ptr.is_null());
+            assert_eq!(ptr as usize % 64, 0);
+            assert_eq!(
+                report().tag(MemoryTag::Physics).current_bytes,
+                before + 1000
+            );
+            // Deallocation outside of the scope must be attributed to the original tag.
+            allocator.dealloc(ptr, layout);
+        }
+        assert_eq!(report().tag(MemoryTag::Physics).current_bytes, before);
+        assert!(report().is_tracking);
+    }
+}
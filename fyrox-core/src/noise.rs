@@ -0,0 +1,667 @@
+//! Coherent noise functions (Perlin, Simplex, Worley) and their combinators (fractal Brownian motion,
+//! domain warping). Noise is the basic building block for procedural content - terrains, materials,
+//! clouds, tile maps, etc. All noise functions are deterministic - the same seed always gives the same
+//! result on every platform.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use fyrox_core::{
+//!     algebra::Vector2,
+//!     noise::{bake_2d, Fbm, Noise, Perlin},
+//! };
+//!
+//! let noise = Fbm::new(Perlin::new(123)).with_octaves(4);
+//! let height = noise.sample_2d(Vector2::new(1.5, 2.5));
+//! assert!(height.abs() <= 1.0);
+//!
+//! // 64x64 samples covering 4x4 units of noise space.
+//! let pixels = bake_2d(&noise, 64, 64, Vector2::new(4.0, 4.0));
+//! assert_eq!(pixels.len(), 64 * 64);
+//! ```
+
+use crate::{
+    algebra::{Vector2, Vector3},
+    rng::Pcg32,
+};
+use rand::{seq::SliceRandom, SeedableRng};
+
+/// A coherent noise function. Implementors must define [`Noise::sample_3d`], lower dimensions are
+/// sampled as slices of 3D noise by default. The result is expected to be in `[-1; 1]` range.
+pub trait Noise {
+    /// Samples the noise at the given 3D point.
+    fn sample_3d(&self, point: Vector3<f32>) -> f32;
+
+    /// Samples the noise at the given 2D point.
+    fn sample_2d(&self, point: Vector2<f32>) -> f32 {
+        self.sample_3d(Vector3::new(point.x, point.y, 0.0))
+    }
+
+    /// Samples the noise at the given 1D point.
+    fn sample_1d(&self, x: f32) -> f32 {
+        self.sample_2d(Vector2::new(x, 0.0))
+    }
+}
+
+impl<N: Noise + ?Sized> Noise for &N {
+    fn sample_3d(&self, point: Vector3<f32>) -> f32 {
+        (**self).sample_3d(point)
+    }
+
+    fn sample_2d(&self, point: Vector2<f32>) -> f32 {
+        (**self).sample_2d(point)
+    }
+
+    fn sample_1d(&self, x: f32) -> f32 {
+        (**self).sample_1d(x)
+    }
+}
+
+impl<N: Noise + ?Sized> Noise for Box<N> {
+    fn sample_3d(&self, point: Vector3<f32>) -> f32 {
+        (**self).sample_3d(point)
+    }
+
+    fn sample_2d(&self, point: Vector2<f32>) -> f32 {
+        (**self).sample_2d(point)
+    }
+
+    fn sample_1d(&self, x: f32) -> f32 {
+        (**self).sample_1d(x)
+    }
+}
+
+/// Shuffled table of 256 values, duplicated to avoid index wrapping.
+#[derive(Clone, Debug)]
+struct PermutationTable([u8; 512]);
+
+impl PermutationTable {
+    fn new(seed: u64) -> Self {
+        let mut values: [u8; 256] = std::array::from_fn(|i| i as u8);
+        values.shuffle(&mut Pcg32::seed_from_u64(seed));
+        Self(std::array::from_fn(|i| values[i & 255]))
+    }
+
+    #[inline]
+    fn hash(&self, i: i32) -> usize {
+        self.0[(i & 255) as usize] as usize
+    }
+
+    #[inline]
+    fn hash3(&self, x: i32, y: i32, z: i32) -> usize {
+        self.hash(x + self.hash(y + self.hash(z) as i32) as i32)
+    }
+}
+
+#[inline]
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+#[inline]
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Gradients of 3D noise - directions to the middles of the edges of a cube.
+const GRAD3: [[f32; 3]; 12] = [
+    [1.0, 1.0, 0.0],
+    [-1.0, 1.0, 0.0],
+    [1.0, -1.0, 0.0],
+    [-1.0, -1.0, 0.0],
+    [1.0, 0.0, 1.0],
+    [-1.0, 0.0, 1.0],
+    [1.0, 0.0, -1.0],
+    [-1.0, 0.0, -1.0],
+    [0.0, 1.0, 1.0],
+    [0.0, -1.0, 1.0],
+    [0.0, 1.0, -1.0],
+    [0.0, -1.0, -1.0],
+];
+
+#[inline]
+fn grad3(hash: usize, x: f32, y: f32, z: f32) -> f32 {
+    let g = GRAD3[hash % 12];
+    g[0] * x + g[1] * y + g[2] * z
+}
+
+/// Classic (improved) Perlin gradient noise.
+///
+/// # References
+///
+/// "Improving Noise" by Ken Perlin, 2002.
+#[derive(Clone, Debug)]
+pub struct Perlin {
+    permutations: PermutationTable,
+}
+
+impl Default for Perlin {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Perlin {
+    /// Creates new Perlin noise with the given seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            permutations: PermutationTable::new(seed),
+        }
+    }
+}
+
+impl Noise for Perlin {
+    fn sample_3d(&self, point: Vector3<f32>) -> f32 {
+        let p = &self.permutations;
+
+        let xf = point.x.floor();
+        let yf = point.y.floor();
+        let zf = point.z.floor();
+        let (xi, yi, zi) = (xf as i32, yf as i32, zf as i32);
+        let (x, y, z) = (point.x - xf, point.y - yf, point.z - zf);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        let corner = |dx: i32, dy: i32, dz: i32| {
+            grad3(
+                p.hash3(xi + dx, yi + dy, zi + dz),
+                x - dx as f32,
+                y - dy as f32,
+                z - dz as f32,
+            )
+        };
+
+        let result = lerp(
+            lerp(
+                lerp(corner(0, 0, 0), corner(1, 0, 0), u),
+                lerp(corner(0, 1, 0), corner(1, 1, 0), u),
+                v,
+            ),
+            lerp(
+                lerp(corner(0, 0, 1), corner(1, 0, 1), u),
+                lerp(corner(0, 1, 1), corner(1, 1, 1), u),
+                v,
+            ),
+            w,
+        );
+
+        result.clamp(-1.0, 1.0)
+    }
+}
+
+/// Simplex noise. It has less directional artifacts than [`Perlin`] noise and is cheaper in higher
+/// dimensions.
+///
+/// # References
+///
+/// "Simplex noise demystified" by Stefan Gustavson, 2005.
+#[derive(Clone, Debug)]
+pub struct Simplex {
+    permutations: PermutationTable,
+}
+
+impl Default for Simplex {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Simplex {
+    /// Creates new Simplex noise with the given seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            permutations: PermutationTable::new(seed),
+        }
+    }
+}
+
+impl Noise for Simplex {
+    fn sample_3d(&self, point: Vector3<f32>) -> f32 {
+        const F3: f32 = 1.0 / 3.0;
+        const G3: f32 = 1.0 / 6.0;
+
+        let p = &self.permutations;
+
+        // Skew the input space to determine which simplex cell we're in.
+        let s = (point.x + point.y + point.z) * F3;
+        let i = (point.x + s).floor();
+        let j = (point.y + s).floor();
+        let k = (point.z + s).floor();
+        let t = (i + j + k) * G3;
+        let x0 = point.x - (i - t);
+        let y0 = point.y - (j - t);
+        let z0 = point.z - (k - t);
+
+        // Determine which simplex we are in.
+        let (i1, j1, k1, i2, j2, k2) = if x0 >= y0 {
+            if y0 >= z0 {
+                (1, 0, 0, 1, 1, 0)
+            } else if x0 >= z0 {
+                (1, 0, 0, 1, 0, 1)
+            } else {
+                (0, 0, 1, 1, 0, 1)
+            }
+        } else if y0 < z0 {
+            (0, 0, 1, 0, 1, 1)
+        } else if x0 < z0 {
+            (0, 1, 0, 0, 1, 1)
+        } else {
+            (0, 1, 0, 1, 1, 0)
+        };
+
+        let offsets = [
+            (0, 0, 0, 0.0),
+            (i1, j1, k1, G3),
+            (i2, j2, k2, 2.0 * G3),
+            (1, 1, 1, 3.0 * G3),
+        ];
+
+        let (i, j, k) = (i as i32, j as i32, k as i32);
+        let mut sum = 0.0;
+        for (di, dj, dk, g) in offsets {
+            let x = x0 - di as f32 + g;
+            let y = y0 - dj as f32 + g;
+            let z = z0 - dk as f32 + g;
+            let t = 0.6 - x * x - y * y - z * z;
+            if t > 0.0 {
+                let t2 = t * t;
+                sum += t2 * t2 * grad3(p.hash3(i + di, j + dj, k + dk), x, y, z);
+            }
+        }
+
+        // Scale the result to cover [-1; 1] range.
+        (32.0 * sum).clamp(-1.0, 1.0)
+    }
+
+    fn sample_2d(&self, point: Vector2<f32>) -> f32 {
+        let f2 = 0.5 * (3.0f32.sqrt() - 1.0);
+        let g2 = (3.0 - 3.0f32.sqrt()) / 6.0;
+
+        let p = &self.permutations;
+
+        let s = (point.x + point.y) * f2;
+        let i = (point.x + s).floor();
+        let j = (point.y + s).floor();
+        let t = (i + j) * g2;
+        let x0 = point.x - (i - t);
+        let y0 = point.y - (j - t);
+
+        let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+
+        let offsets = [(0, 0, 0.0), (i1, j1, g2), (1, 1, 2.0 * g2)];
+
+        let (i, j) = (i as i32, j as i32);
+        let mut sum = 0.0;
+        for (di, dj, g) in offsets {
+            let x = x0 - di as f32 + g;
+            let y = y0 - dj as f32 + g;
+            let t = 0.5 - x * x - y * y;
+            if t > 0.0 {
+                let t2 = t * t;
+                sum += t2 * t2 * grad3(p.hash3(i + di, j + dj, 0), x, y, 0.0);
+            }
+        }
+
+        (70.0 * sum).clamp(-1.0, 1.0)
+    }
+}
+
+/// Distance function used to calculate distance to feature points of [`Worley`] noise.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum WorleyDistance {
+    /// Straight-line distance, gives round cells.
+    #[default]
+    Euclidean,
+    /// Sum of absolute differences of coordinates, gives diamond-like cells.
+    Manhattan,
+    /// Maximum of absolute differences of coordinates, gives square-like cells.
+    Chebyshev,
+}
+
+impl WorleyDistance {
+    #[inline]
+    fn distance(self, v: Vector3<f32>) -> f32 {
+        match self {
+            WorleyDistance::Euclidean => v.norm(),
+            WorleyDistance::Manhattan => v.x.abs() + v.y.abs() + v.z.abs(),
+            WorleyDistance::Chebyshev => v.x.abs().max(v.y.abs()).max(v.z.abs()),
+        }
+    }
+}
+
+/// Defines which value is returned by [`Worley`] noise.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum WorleyReturn {
+    /// Distance to the closest feature point.
+    #[default]
+    F1,
+    /// Distance to the second closest feature point.
+    F2,
+    /// Difference between second and first distances, gives cell borders.
+    F2MinusF1,
+}
+
+/// Worley (cellular) noise. The space is split into unit cells with one random feature point in each
+/// cell, the noise value is based on distances to the closest feature points.
+///
+/// # References
+///
+/// "A Cellular Texture Basis Function" by Steven Worley, 1996.
+#[derive(Clone, Debug)]
+pub struct Worley {
+    permutations: PermutationTable,
+    distance: WorleyDistance,
+    return_type: WorleyReturn,
+}
+
+impl Default for Worley {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Worley {
+    /// Creates new Worley noise with the given seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            permutations: PermutationTable::new(seed),
+            distance: Default::default(),
+            return_type: Default::default(),
+        }
+    }
+
+    /// Sets the distance function.
+    pub fn with_distance(mut self, distance: WorleyDistance) -> Self {
+        self.distance = distance;
+        self
+    }
+
+    /// Sets the type of the returned value.
+    pub fn with_return_type(mut self, return_type: WorleyReturn) -> Self {
+        self.return_type = return_type;
+        self
+    }
+
+    #[inline]
+    fn feature_point(&self, x: i32, y: i32, z: i32) -> Vector3<f32> {
+        let p = &self.permutations;
+        let h = p.hash3(x, y, z) as i32;
+        Vector3::new(
+            x as f32 + p.hash(h) as f32 / 255.0,
+            y as f32 + p.hash(h + 1) as f32 / 255.0,
+            z as f32 + p.hash(h + 2) as f32 / 255.0,
+        )
+    }
+}
+
+impl Noise for Worley {
+    fn sample_3d(&self, point: Vector3<f32>) -> f32 {
+        let cx = point.x.floor() as i32;
+        let cy = point.y.floor() as i32;
+        let cz = point.z.floor() as i32;
+
+        let mut f1 = f32::MAX;
+        let mut f2 = f32::MAX;
+        for z in cz - 1..=cz + 1 {
+            for y in cy - 1..=cy + 1 {
+                for x in cx - 1..=cx + 1 {
+                    let d = self.distance.distance(self.feature_point(x, y, z) - point);
+                    if d < f1 {
+                        f2 = f1;
+                        f1 = d;
+                    } else if d < f2 {
+                        f2 = d;
+                    }
+                }
+            }
+        }
+
+        let value = match self.return_type {
+            WorleyReturn::F1 => f1,
+            WorleyReturn::F2 => f2,
+            WorleyReturn::F2MinusF1 => f2 - f1,
+        };
+
+        // Distances are mostly in [0; 1] range, remap them to [-1; 1].
+        (value * 2.0 - 1.0).clamp(-1.0, 1.0)
+    }
+}
+
+/// Fractal Brownian motion - a sum of several octaves of the source noise with increasing frequency
+/// and decreasing amplitude. It adds fine details to the source noise.
+#[derive(Clone, Debug)]
+pub struct Fbm<N> {
+    /// Source noise.
+    pub source: N,
+    /// Number of octaves. Each octave adds finer details.
+    pub octaves: usize,
+    /// Frequency multiplier between successive octaves.
+    pub lacunarity: f32,
+    /// Amplitude multiplier between successive octaves.
+    pub gain: f32,
+    /// Frequency of the first octave.
+    pub frequency: f32,
+}
+
+impl<N: Noise> Fbm<N> {
+    /// Creates new fractal noise with 6 octaves, lacunarity of 2.0 and gain of 0.5.
+    pub fn new(source: N) -> Self {
+        Self {
+            source,
+            octaves: 6,
+            lacunarity: 2.0,
+            gain: 0.5,
+            frequency: 1.0,
+        }
+    }
+
+    /// Sets the number of octaves.
+    pub fn with_octaves(mut self, octaves: usize) -> Self {
+        self.octaves = octaves;
+        self
+    }
+
+    /// Sets the frequency multiplier between successive octaves.
+    pub fn with_lacunarity(mut self, lacunarity: f32) -> Self {
+        self.lacunarity = lacunarity;
+        self
+    }
+
+    /// Sets the amplitude multiplier between successive octaves.
+    pub fn with_gain(mut self, gain: f32) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    /// Sets the frequency of the first octave.
+    pub fn with_frequency(mut self, frequency: f32) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    fn accumulate<F: FnMut(f32) -> f32>(&self, mut sample: F) -> f32 {
+        let mut sum = 0.0;
+        let mut amplitude = 1.0;
+        let mut total_amplitude = 0.0;
+        let mut frequency = self.frequency;
+        for _ in 0..self.octaves {
+            sum += amplitude * sample(frequency);
+            total_amplitude += amplitude;
+            amplitude *= self.gain;
+            frequency *= self.lacunarity;
+        }
+        if total_amplitude > 0.0 {
+            sum / total_amplitude
+        } else {
+            0.0
+        }
+    }
+}
+
+impl<N: Noise> Noise for Fbm<N> {
+    fn sample_3d(&self, point: Vector3<f32>) -> f32 {
+        self.accumulate(|frequency| self.source.sample_3d(point.scale(frequency)))
+    }
+
+    fn sample_2d(&self, point: Vector2<f32>) -> f32 {
+        self.accumulate(|frequency| self.source.sample_2d(point.scale(frequency)))
+    }
+
+    fn sample_1d(&self, x: f32) -> f32 {
+        self.accumulate(|frequency| self.source.sample_1d(x * frequency))
+    }
+}
+
+/// Domain warping - distorts the input coordinates of the source noise using another noise. It gives
+/// organic, "flowing" patterns, that are hard to get with other methods.
+///
+/// # References
+///
+/// "Domain Warping" by Inigo Quilez, 2002.
+#[derive(Clone, Debug)]
+pub struct DomainWarp<N, W> {
+    /// Source noise.
+    pub source: N,
+    /// Noise that is used to offset the coordinates.
+    pub warp: W,
+    /// Strength of the distortion.
+    pub amplitude: f32,
+}
+
+impl<N: Noise, W: Noise> DomainWarp<N, W> {
+    /// Creates new domain warping noise.
+    pub fn new(source: N, warp: W, amplitude: f32) -> Self {
+        Self {
+            source,
+            warp,
+            amplitude,
+        }
+    }
+}
+
+// Arbitrary offsets to decorrelate warp components.
+const WARP_OFFSET_Y: Vector3<f32> = Vector3::new(5.2, 1.3, 7.1);
+const WARP_OFFSET_Z: Vector3<f32> = Vector3::new(1.7, 9.2, 3.4);
+
+impl<N: Noise, W: Noise> Noise for DomainWarp<N, W> {
+    fn sample_3d(&self, point: Vector3<f32>) -> f32 {
+        let offset = Vector3::new(
+            self.warp.sample_3d(point),
+            self.warp.sample_3d(point + WARP_OFFSET_Y),
+            self.warp.sample_3d(point + WARP_OFFSET_Z),
+        );
+        self.source.sample_3d(point + offset.scale(self.amplitude))
+    }
+
+    fn sample_2d(&self, point: Vector2<f32>) -> f32 {
+        let offset = Vector2::new(
+            self.warp.sample_2d(point),
+            self.warp.sample_2d(point + WARP_OFFSET_Y.xy()),
+        );
+        self.source.sample_2d(point + offset.scale(self.amplitude))
+    }
+
+    fn sample_1d(&self, x: f32) -> f32 {
+        self.source
+            .sample_1d(x + self.warp.sample_1d(x) * self.amplitude)
+    }
+}
+
+/// Samples the noise on a regular grid of `width x height` points covering `[0; size]` region of
+/// noise space. The result is stored in row-major order and contains values in `[-1; 1]` range.
+pub fn bake_2d<N: Noise + ?Sized>(
+    noise: &N,
+    width: usize,
+    height: usize,
+    size: Vector2<f32>,
+) -> Vec<f32> {
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let ky = y as f32 / height.max(1) as f32;
+        for x in 0..width {
+            let kx = x as f32 / width.max(1) as f32;
+            pixels.push(noise.sample_2d(Vector2::new(kx * size.x, ky * size.y)));
+        }
+    }
+    pixels
+}
+
+/// Same as [`bake_2d`], but remaps the values to `[0; 255]` range. The result could be used directly
+/// as pixels of a single-channel 8-bit texture.
+pub fn bake_2d_u8<N: Noise + ?Sized>(
+    noise: &N,
+    width: usize,
+    height: usize,
+    size: Vector2<f32>,
+) -> Vec<u8> {
+    bake_2d(noise, width, height, size)
+        .into_iter()
+        .map(|v| ((v * 0.5 + 0.5).clamp(0.0, 1.0) * 255.0) as u8)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        algebra::{Vector2, Vector3},
+        noise::{
+            bake_2d, bake_2d_u8, DomainWarp, Fbm, Noise, Perlin, Simplex, Worley, WorleyReturn,
+        },
+    };
+
+    fn check_range<N: Noise>(noise: &N) {
+        for i in 0..500 {
+            let p = Vector3::new(i as f32 * 0.37, i as f32 * 0.21 - 30.0, i as f32 * 0.13);
+            for v in [
+                noise.sample_1d(p.x),
+                noise.sample_2d(p.xy()),
+                noise.sample_3d(p),
+            ] {
+                assert!((-1.0..=1.0).contains(&v), "{v}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_noise_range() {
+        check_range(&Perlin::new(1));
+        check_range(&Simplex::new(2));
+        check_range(&Worley::new(3));
+        check_range(&Worley::new(3).with_return_type(WorleyReturn::F2MinusF1));
+        check_range(&Fbm::new(Perlin::new(4)));
+        check_range(&DomainWarp::new(Simplex::new(5), Perlin::new(6), 0.5));
+    }
+
+    #[test]
+    fn test_noise_determinism() {
+        let p = Vector3::new(1.3, 2.7, -0.4);
+        assert_eq!(Perlin::new(10).sample_3d(p), Perlin::new(10).sample_3d(p));
+        assert_ne!(Perlin::new(10).sample_3d(p), Perlin::new(11).sample_3d(p));
+        assert_eq!(Simplex::new(10).sample_3d(p), Simplex::new(10).sample_3d(p));
+        assert_eq!(Worley::new(10).sample_3d(p), Worley::new(10).sample_3d(p));
+    }
+
+    #[test]
+    fn test_perlin_is_zero_at_lattice_points() {
+        let noise = Perlin::new(42);
+        assert_eq!(noise.sample_3d(Vector3::new(1.0, 2.0, 3.0)), 0.0);
+    }
+
+    #[test]
+    fn test_noise_is_continuous() {
+        let noise = Fbm::new(Simplex::new(7)).with_octaves(3);
+        let a = noise.sample_2d(Vector2::new(0.5, 0.5));
+        let b = noise.sample_2d(Vector2::new(0.5001, 0.5));
+        assert!((a - b).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_bake() {
+        let noise = Perlin::new(0);
+        let pixels = bake_2d(&noise, 16, 8, Vector2::new(2.0, 1.0));
+        assert_eq!(pixels.len(), 16 * 8);
+        let bytes = bake_2d_u8(&noise, 16, 8, Vector2::new(2.0, 1.0));
+        assert_eq!(bytes.len(), 16 * 8);
+        // Origin is a lattice point of Perlin noise, so the value is exactly in the middle.
+        assert_eq!(bytes[0], 127);
+    }
+}
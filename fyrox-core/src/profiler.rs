@@ -1,6 +1,11 @@
 //! Built-in scoped profiler. You must compile with feature "enable_profiler" to
 //! force profiler gather info! It is disabled by default because it is not cheap
 //! and takes 3-5% of performance for internal needs.
+//!
+//! The profiler can also forward its scopes to external profilers - [puffin](https://github.com/EmbarkStudios/puffin)
+//! (feature "profiler_puffin") and [Tracy](https://github.com/wolfpld/tracy) (feature "profiler_tracy").
+//! Every [`scope_profile`](crate::scope_profile) and [`profile_span`](crate::profile_span) scope becomes
+//! a span in the external profiler, and [`profile_plot`](crate::profile_plot) values become plots (Tracy only).
 
 #![allow(dead_code)]
 
@@ -12,6 +17,32 @@ use std::{
     sync::{Arc, Mutex},
 };
 
+#[cfg(feature = "profiler_puffin")]
+pub use puffin;
+#[cfg(feature = "profiler_tracy")]
+pub use tracy_client;
+
+/// Starts external profilers (if any is enabled). It is called by the engine automatically.
+pub fn start_external_profilers() {
+    #[cfg(feature = "profiler_puffin")]
+    puffin::set_scopes_on(true);
+
+    #[cfg(feature = "profiler_tracy")]
+    {
+        let _ = tracy_client::Client::start();
+    }
+}
+
+/// Marks the end of a frame for external profilers (if any is enabled). It is called by the engine
+/// automatically.
+pub fn frame_mark() {
+    #[cfg(feature = "profiler_puffin")]
+    puffin::GlobalProfiler::lock().new_frame();
+
+    #[cfg(feature = "profiler_tracy")]
+    tracy_client::frame_mark();
+}
+
 pub fn print() -> Result<String, fmt::Error> {
     #[cfg(feature = "enable_profiler")]
     {
@@ -274,6 +305,72 @@ pub fn type_name_of<T>(_: T) -> &'static str {
     std::any::type_name::<T>()
 }
 
+/// Strips the name of a local function (used by the profiling macros) from the given type name.
+#[inline]
+pub fn function_name(type_name: &'static str) -> &'static str {
+    type_name.strip_suffix("::scope").unwrap_or(type_name)
+}
+
+#[doc(hidden)]
+#[cfg(feature = "profiler_puffin")]
+#[macro_export]
+macro_rules! __puffin_scope {
+    () => {
+        $crate::profiler::puffin::profile_function!();
+    };
+    ($name:expr) => {
+        $crate::profiler::puffin::profile_scope!($name);
+    };
+}
+
+#[doc(hidden)]
+#[cfg(not(feature = "profiler_puffin"))]
+#[macro_export]
+macro_rules! __puffin_scope {
+    ($($args:tt)*) => {};
+}
+
+#[doc(hidden)]
+#[cfg(feature = "profiler_tracy")]
+#[macro_export]
+macro_rules! __tracy_span {
+    ($name:expr, $function:expr) => {
+        let _tracy_span = $crate::profiler::tracy_client::Client::running()
+            .map(|client| client.span_alloc($name, $function, file!(), line!(), 0));
+    };
+}
+
+#[doc(hidden)]
+#[cfg(not(feature = "profiler_tracy"))]
+#[macro_export]
+macro_rules! __tracy_span {
+    ($($args:tt)*) => {};
+}
+
+#[doc(hidden)]
+#[cfg(feature = "profiler_tracy")]
+#[macro_export]
+macro_rules! __tracy_plot {
+    ($name:literal, $value:expr) => {
+        if let Some(client) = $crate::profiler::tracy_client::Client::running() {
+            client.plot(
+                $crate::profiler::tracy_client::plot_name!($name),
+                $value as f64,
+            );
+        }
+    };
+}
+
+#[doc(hidden)]
+#[cfg(not(feature = "profiler_tracy"))]
+#[macro_export]
+macro_rules! __tracy_plot {
+    ($name:literal, $value:expr) => {
+        let _ = $value;
+    };
+}
+
+/// Profiles the enclosing function until the end of the enclosing scope.
 #[cfg(feature = "enable_profiler")]
 #[macro_export]
 macro_rules! scope_profile {
@@ -283,13 +380,59 @@ macro_rules! scope_profile {
             $crate::profiler::type_name_of(scope)
         };
         let _scope_guard = $crate::profiler::ScopeDefinition::new(function_name, line!());
+        $crate::__puffin_scope!();
+        $crate::__tracy_span!(None, $crate::profiler::function_name(function_name));
     };
 }
 
+/// Profiles the enclosing function until the end of the enclosing scope.
 #[cfg(not(feature = "enable_profiler"))]
 #[macro_export]
 macro_rules! scope_profile {
-    () => {};
+    () => {
+        $crate::__puffin_scope!();
+        $crate::__tracy_span!(None, {
+            fn scope() {}
+            $crate::profiler::function_name($crate::profiler::type_name_of(scope))
+        });
+    };
+}
+
+/// Creates a named span in external profilers, that lasts until the end of the enclosing scope. Unlike
+/// [`scope_profile`](crate::scope_profile), it allows to profile parts of a function. Does nothing if no external profiler is
+/// enabled.
+///
+/// ```rust
+/// fn update() {
+///     {
+///         fyrox_core::profile_span!("Physics");
+///         // ...
+///     }
+///     {
+///         fyrox_core::profile_span!("Animation");
+///         // ...
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! profile_span {
+    ($name:literal) => {
+        $crate::__puffin_scope!($name);
+        $crate::__tracy_span!(Some($name), "");
+    };
+}
+
+/// Sends a value of a named plot to external profilers (Tracy only). Does nothing if no external
+/// profiler is enabled.
+///
+/// ```rust
+/// fyrox_core::profile_plot!("Draw Calls", 123);
+/// ```
+#[macro_export]
+macro_rules! profile_plot {
+    ($name:literal, $value:expr) => {
+        $crate::__tracy_plot!($name, $value);
+    };
 }
 
 #[cfg(test)]
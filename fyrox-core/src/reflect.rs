@@ -90,6 +90,13 @@ pub struct FieldInfo<'a, 'b> {
 
     /// Maximum amount of decimal places for a numeric property.
     pub precision: Option<usize>,
+
+    /// Units of measurement of the property (for example `m/s`). Could be empty.
+    pub units: &'b str,
+
+    /// A name of a custom property editor that should be used to edit the property, instead of
+    /// the default one for the property type.
+    pub editor: Option<&'b str>,
 }
 
 impl<'a, 'b> FieldInfo<'a, 'b> {
@@ -119,6 +126,8 @@ impl<'a, 'b> fmt::Debug for FieldInfo<'a, 'b> {
             .field("step", &self.step)
             .field("precision", &self.precision)
             .field("description", &self.description)
+            .field("units", &self.units)
+            .field("editor", &self.editor)
             .finish()
     }
 }
@@ -138,6 +147,8 @@ impl<'a, 'b> PartialEq<Self> for FieldInfo<'a, 'b> {
             && self.step == other.step
             && self.precision == other.precision
             && self.description == other.description
+            && self.units == other.units
+            && self.editor == other.editor
     }
 }
 
@@ -168,6 +179,11 @@ impl<T: Reflect> ReflectBase for T {
 /// - `#[reflect(deref)]`: Delegate the field access with deref
 /// - `#[reflect(field = <method call>)]`
 /// - `#[reflect(field_mut = <method call>)]`
+/// - `#[reflect(min_value = 0.0, max_value = 1.0, step = 0.1, precision = 3)]`: Range, step and
+/// precision of a numeric field.
+/// - `#[reflect(description = "...")]`: Description of the field, shown as a tooltip.
+/// - `#[reflect(units = "m/s")]`: Units of measurement of the field, shown next to its name.
+/// - `#[reflect(editor = "slider")]`: A name of a custom property editor for the field.
 ///
 /// # Additional Trait Bounds
 ///
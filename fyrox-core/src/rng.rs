@@ -0,0 +1,321 @@
+//! Deterministic pseudo-random numbers generation. Unlike `rand::thread_rng`, generators from this
+//! module are seedable, produce the same sequences on every platform and can be serialized together
+//! with their state, which makes them suitable for replays, networked simulations and procedural
+//! generation. See [`Pcg32`] and [`RngStreams`] docs for more info.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use fyrox_core::{rand::Rng, rng::RngStreams};
+//!
+//! let mut streams = RngStreams::new(42);
+//! let damage = streams.gameplay().gen_range(10..20);
+//! // Spawning more particles does not affect the gameplay stream.
+//! let _ = streams.vfx().gen::<f32>();
+//! assert!((10..20).contains(&damage));
+//! ```
+
+use crate::visitor::prelude::*;
+use fxhash::FxHashMap;
+use rand::{Error, RngCore, SeedableRng};
+
+/// PCG32 (PCG-XSH-RR 64/32) pseudo-random numbers generator. It is small, fast and has good
+/// statistical properties. Unlike `StdRng`, its algorithm is fixed and will never change, so the same
+/// seed always gives the same sequence. The generator supports independent streams - two generators
+/// with the same seed but with different streams produce uncorrelated sequences.
+///
+/// # References
+///
+/// "PCG: A Family of Simple Fast Space-Efficient Statistically Good Algorithms for Random Number
+/// Generation" by Melissa E. O'Neill, 2014.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Pcg32 {
+    state: u64,
+    increment: u64,
+}
+
+const PCG_MULTIPLIER: u64 = 6364136223846793005;
+
+impl Default for Pcg32 {
+    fn default() -> Self {
+        Self::new(0xCAFEF00DD15EA5E5, 0xA02BDBF7BB3C0A7)
+    }
+}
+
+impl Pcg32 {
+    /// Creates new generator with the given seed and stream index.
+    pub fn new(seed: u64, stream: u64) -> Self {
+        let mut rng = Self {
+            state: 0,
+            increment: (stream << 1) | 1,
+        };
+        rng.step();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.step();
+        rng
+    }
+
+    #[inline]
+    fn step(&mut self) {
+        self.state = self
+            .state
+            .wrapping_mul(PCG_MULTIPLIER)
+            .wrapping_add(self.increment);
+    }
+
+    /// Returns the next random number in `[0; 1)` range.
+    #[inline]
+    pub fn next_f32(&mut self) -> f32 {
+        // 24 bits of mantissa.
+        (self.next_u32() >> 8) as f32 * (1.0 / (1u32 << 24) as f32)
+    }
+}
+
+impl RngCore for Pcg32 {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.step();
+        let xor_shifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rot = (old >> 59) as u32;
+        xor_shifted.rotate_right(rot)
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        let lo = self.next_u32() as u64;
+        let hi = self.next_u32() as u64;
+        (hi << 32) | lo
+    }
+
+    #[inline]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            let bytes = self.next_u32().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    #[inline]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for Pcg32 {
+    /// First 8 bytes is the seed, the last 8 bytes is the stream index.
+    type Seed = [u8; 16];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        let mut state = [0u8; 8];
+        state.copy_from_slice(&seed[..8]);
+        let mut stream = [0u8; 8];
+        stream.copy_from_slice(&seed[8..]);
+        Self::new(u64::from_le_bytes(state), u64::from_le_bytes(stream))
+    }
+
+    fn seed_from_u64(state: u64) -> Self {
+        Self::new(split_mix64(state), 0)
+    }
+}
+
+impl Visit for Pcg32 {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.state.visit("State", &mut region)?;
+        self.increment.visit("Increment", &mut region)?;
+
+        Ok(())
+    }
+}
+
+/// SplitMix64 hash. It is used to turn "bad" seeds (such as 0, 1, 2, ...) into well-distributed
+/// initial states.
+#[inline]
+pub fn split_mix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// FNV-1a hash of a string. Unlike the standard hasher, it is guaranteed to be stable between
+/// compiler versions and platforms, which is important for stream ids.
+#[inline]
+fn stable_hash(name: &str) -> u64 {
+    name.bytes().fold(0xCBF29CE484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001B3)
+    })
+}
+
+/// A set of named independent random streams derived from a single seed. Each subsystem should use its
+/// own stream, so the amount of random numbers consumed by one subsystem does not affect the others.
+/// For example, changing particle effects (which use [`Self::VFX`] stream) will not change gameplay
+/// outcomes (which use [`Self::GAMEPLAY`] stream) of a recorded replay.
+///
+/// Streams are created lazily on first access, and their state is serialized, so a saved game will
+/// continue the same sequences after loading.
+#[derive(Clone, Debug, Default)]
+pub struct RngStreams {
+    seed: u64,
+    streams: FxHashMap<String, Pcg32>,
+}
+
+impl RngStreams {
+    /// Name of the stream for gameplay logic.
+    pub const GAMEPLAY: &'static str = "Gameplay";
+    /// Name of the stream for visual effects.
+    pub const VFX: &'static str = "Vfx";
+    /// Name of the stream for artificial intelligence.
+    pub const AI: &'static str = "Ai";
+    /// Name of the stream for procedural generation.
+    pub const PROCEDURAL: &'static str = "Procedural";
+
+    /// Creates new set of streams using the given seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: Default::default(),
+        }
+    }
+
+    /// Returns the seed of the set.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Sets new seed and resets all the streams.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.streams.clear();
+    }
+
+    /// Resets all the streams to their initial state.
+    pub fn reset(&mut self) {
+        self.streams.clear();
+    }
+
+    /// Creates a generator for the stream with the given name without affecting the set. The
+    /// result depends only on the seed and the name.
+    pub fn make_stream(&self, name: &str) -> Pcg32 {
+        let id = stable_hash(name);
+        Pcg32::new(split_mix64(self.seed ^ id), id)
+    }
+
+    /// Returns a generator of the stream with the given name. The stream is created if it does not
+    /// exist.
+    pub fn stream(&mut self, name: &str) -> &mut Pcg32 {
+        if !self.streams.contains_key(name) {
+            let stream = self.make_stream(name);
+            self.streams.insert(name.to_owned(), stream);
+        }
+        self.streams.get_mut(name).unwrap()
+    }
+
+    /// Returns the generator of the gameplay stream.
+    pub fn gameplay(&mut self) -> &mut Pcg32 {
+        self.stream(Self::GAMEPLAY)
+    }
+
+    /// Returns the generator of the visual effects stream.
+    pub fn vfx(&mut self) -> &mut Pcg32 {
+        self.stream(Self::VFX)
+    }
+
+    /// Returns the generator of the artificial intelligence stream.
+    pub fn ai(&mut self) -> &mut Pcg32 {
+        self.stream(Self::AI)
+    }
+
+    /// Returns the generator of the procedural generation stream.
+    pub fn procedural(&mut self) -> &mut Pcg32 {
+        self.stream(Self::PROCEDURAL)
+    }
+}
+
+impl Visit for RngStreams {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.seed.visit("Seed", &mut region)?;
+        self.streams.visit("Streams", &mut region)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        rng::{Pcg32, RngStreams},
+        visitor::prelude::*,
+    };
+    use rand::{Rng, RngCore};
+
+    #[test]
+    fn test_pcg32_reference_sequence() {
+        // Reference values from the PCG32 demo program (pcg32-demo.c), seed 42, stream 54.
+        let mut rng = Pcg32::new(42, 54);
+        let expected = [
+            0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e,
+        ];
+        for value in expected {
+            assert_eq!(rng.next_u32(), value);
+        }
+    }
+
+    #[test]
+    fn test_pcg32_f32_range() {
+        let mut rng = Pcg32::default();
+        for _ in 0..1000 {
+            let v = rng.next_f32();
+            assert!((0.0..1.0).contains(&v));
+        }
+    }
+
+    #[test]
+    fn test_streams_are_independent() {
+        let mut a = RngStreams::new(123);
+        let mut b = RngStreams::new(123);
+
+        // Consume some numbers from vfx stream of the first set only.
+        for _ in 0..100 {
+            a.vfx().next_u32();
+        }
+
+        let va: Vec<u32> = (0..10).map(|_| a.gameplay().gen()).collect();
+        let vb: Vec<u32> = (0..10).map(|_| b.gameplay().gen()).collect();
+        assert_eq!(va, vb);
+
+        assert_ne!(
+            a.make_stream(RngStreams::AI),
+            a.make_stream(RngStreams::VFX)
+        );
+        assert_ne!(
+            RngStreams::new(1).make_stream(RngStreams::AI),
+            RngStreams::new(2).make_stream(RngStreams::AI)
+        );
+    }
+
+    #[test]
+    fn test_streams_visit() {
+        let mut streams = RngStreams::new(777);
+        streams.gameplay().next_u32();
+        streams.ai().next_u32();
+        let mut expected = streams.clone();
+
+        let mut visitor = Visitor::new();
+        streams.visit("Rng", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut visitor = Visitor::load_from_memory(&data).unwrap();
+        let mut loaded = RngStreams::default();
+        loaded.visit("Rng", &mut visitor).unwrap();
+
+        assert_eq!(loaded.seed(), 777);
+        assert_eq!(loaded.gameplay().next_u32(), expected.gameplay().next_u32());
+        assert_eq!(loaded.ai().next_u32(), expected.ai().next_u32());
+    }
+}
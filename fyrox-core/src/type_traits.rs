@@ -1,5 +1,6 @@
 pub use fyrox_core_derive::ComponentProvider;
 pub use fyrox_core_derive::TypeUuidProvider;
+use nalgebra::{Scalar, Vector2, Vector3, Vector4};
 use std::any::{Any, TypeId};
 use std::path::PathBuf;
 use uuid::Uuid;
@@ -71,6 +72,33 @@ impl<T: TypeUuidProvider> TypeUuidProvider for Vec<T> {
     }
 }
 
+impl<T: TypeUuidProvider + Scalar> TypeUuidProvider for Vector2<T> {
+    fn type_uuid() -> Uuid {
+        combine_uuids(
+            uuid::uuid!("7d1b1e3a-1a4b-4bd6-a4a5-0a7d5f8b3c21"),
+            T::type_uuid(),
+        )
+    }
+}
+
+impl<T: TypeUuidProvider + Scalar> TypeUuidProvider for Vector3<T> {
+    fn type_uuid() -> Uuid {
+        combine_uuids(
+            uuid::uuid!("2f8a5c6e-93d4-4e0b-8f5e-6c1b9d2a7e44"),
+            T::type_uuid(),
+        )
+    }
+}
+
+impl<T: TypeUuidProvider + Scalar> TypeUuidProvider for Vector4<T> {
+    fn type_uuid() -> Uuid {
+        combine_uuids(
+            uuid::uuid!("c5e3d7a1-6b2f-4a8e-b9d0-3f4e1a2b5c67"),
+            T::type_uuid(),
+        )
+    }
+}
+
 #[inline]
 pub fn combine_uuids(a: Uuid, b: Uuid) -> Uuid {
     let mut combined_bytes = a.into_bytes();
@@ -0,0 +1,183 @@
+//! Benchmarks for animation pose evaluation.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use fyrox_impl::{
+    core::{
+        algebra::Vector2,
+        math::curve::{Curve, CurveKey, CurveKeyKind},
+    },
+    generic_animation::container::{TrackDataContainer, TrackValueKind},
+    graph::BaseSceneGraph,
+    scene::{
+        animation::{
+            Animation, AnimationContainer, AnimationPlayerBuilder, AnimationPoseExt, Track,
+        },
+        base::BaseBuilder,
+        graph::{Graph, GraphUpdateSwitches},
+        pivot::PivotBuilder,
+    },
+};
+
+fn make_curve(keys: usize, phase: f32) -> Curve {
+    let mut curve = Curve::default();
+    for i in 0..keys {
+        let t = i as f32 / (keys - 1) as f32;
+        curve.add_key(CurveKey::new(
+            t,
+            (t * std::f32::consts::TAU + phase).sin(),
+            CurveKeyKind::Linear,
+        ));
+    }
+    curve
+}
+
+fn make_container(kind: TrackValueKind, keys: usize) -> TrackDataContainer {
+    let mut container = TrackDataContainer::new(kind);
+    for (i, curve) in container.curves_mut().iter_mut().enumerate() {
+        *curve = make_curve(keys, i as f32);
+    }
+    container
+}
+
+/// Creates a "skeleton" of `bone_count` nodes and an animation that animates position and rotation of
+/// every bone.
+fn generate_skeleton_animation(bone_count: usize, keys: usize) -> (Graph, Animation) {
+    let mut graph = Graph::new();
+    let mut parent = graph.get_root();
+    let mut animation = Animation::default();
+
+    for _ in 0..bone_count {
+        let bone = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        graph.link_nodes(bone, parent);
+        parent = bone;
+
+        let mut position = Track::new_position().with_target(bone);
+        position.set_data_container(make_container(TrackValueKind::Vector3, keys));
+        animation.add_track(position);
+
+        let mut rotation = Track::new_rotation().with_target(bone);
+        rotation.set_data_container(make_container(TrackValueKind::UnitQuaternion, keys));
+        animation.add_track(rotation);
+    }
+
+    animation.set_time_slice(0.0..1.0);
+    animation.set_loop(true);
+
+    (graph, animation)
+}
+
+fn pose_evaluation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pose_evaluation");
+
+    for bone_count in [16, 64, 256] {
+        let (_, mut animation) = generate_skeleton_animation(bone_count, 32);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(bone_count),
+            &bone_count,
+            |b, _| b.iter(|| animation.tick(black_box(1.0 / 60.0))),
+        );
+    }
+
+    group.finish();
+}
+
+fn pose_application(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pose_application");
+
+    for bone_count in [16, 64, 256] {
+        let (mut graph, mut animation) = generate_skeleton_animation(bone_count, 32);
+        animation.tick(0.1);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(bone_count),
+            &bone_count,
+            |b, _| {
+                b.iter(|| {
+                    animation.pose().apply(&mut graph);
+                    graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn pose_blending(c: &mut Criterion) {
+    let (_, mut a) = generate_skeleton_animation(64, 32);
+    // Both skeletons are generated the same way, so handles of their bones match.
+    let (_, mut b) = generate_skeleton_animation(64, 32);
+    a.tick(0.25);
+    b.tick(0.5);
+
+    c.bench_function("pose_blending_64_bones", |bencher| {
+        let mut pose = a.pose().clone();
+        bencher.iter(|| {
+            a.pose().clone_into(&mut pose);
+            pose.blend_with(black_box(b.pose()), 0.5);
+        })
+    });
+}
+
+/// Adds a "character" with its own skeleton and animation player to the graph.
+fn add_animated_character(graph: &mut Graph, bone_count: usize, keys: usize) {
+    let mut parent = graph.get_root();
+    let mut animation = Animation::default();
+
+    for _ in 0..bone_count {
+        let bone = PivotBuilder::new(BaseBuilder::new()).build(graph);
+        graph.link_nodes(bone, parent);
+        parent = bone;
+
+        let mut rotation = Track::new_rotation().with_target(bone);
+        rotation.set_data_container(make_container(TrackValueKind::UnitQuaternion, keys));
+        animation.add_track(rotation);
+    }
+
+    animation.set_time_slice(0.0..1.0);
+    animation.set_loop(true);
+
+    let mut animations = AnimationContainer::new();
+    animations.add(animation);
+    AnimationPlayerBuilder::new(BaseBuilder::new())
+        .with_animations(animations)
+        .build(graph);
+}
+
+fn crowd_animation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("crowd_animation_256_characters");
+
+    let mut graph = Graph::new();
+    for _ in 0..256 {
+        add_animated_character(&mut graph, 48, 32);
+    }
+
+    for parallel_animation in [false, true] {
+        group.bench_with_input(
+            BenchmarkId::new("parallel", parallel_animation),
+            &parallel_animation,
+            |b, &parallel_animation| {
+                b.iter(|| {
+                    graph.update(
+                        Vector2::new(1.0, 1.0),
+                        1.0 / 60.0,
+                        GraphUpdateSwitches {
+                            parallel_animation,
+                            ..Default::default()
+                        },
+                    )
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    pose_evaluation,
+    pose_application,
+    pose_blending,
+    crowd_animation
+);
+criterion_main!(benches);
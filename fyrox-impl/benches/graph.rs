@@ -0,0 +1,85 @@
+//! Benchmarks for scene graph hot paths.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use fyrox_impl::{
+    core::{algebra::Vector2, algebra::Vector3, pool::Handle},
+    graph::BaseSceneGraph,
+    scene::{
+        base::BaseBuilder, graph::Graph, node::Node, pivot::PivotBuilder,
+        transform::TransformBuilder,
+    },
+};
+
+/// Generates a graph with `depth` levels of hierarchy where every node has `branching` children.
+fn generate_hierarchy(depth: usize, branching: usize) -> Graph {
+    fn add_level(graph: &mut Graph, parent: Handle<Node>, depth: usize, branching: usize) {
+        if depth == 0 {
+            return;
+        }
+
+        for i in 0..branching {
+            let node = PivotBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(i as f32, 1.0, 0.0))
+                        .build(),
+                ),
+            )
+            .build(graph);
+            graph.link_nodes(node, parent);
+            add_level(graph, node, depth - 1, branching);
+        }
+    }
+
+    let mut graph = Graph::new();
+    let root = graph.get_root();
+    add_level(&mut graph, root, depth, branching);
+    graph
+}
+
+fn graph_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("graph_update");
+
+    for (depth, branching) in [(3, 10), (4, 10), (10, 2)] {
+        let mut graph = generate_hierarchy(depth, branching);
+        let node_count = graph.node_count();
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{node_count}_nodes_{depth}x{branching}")),
+            &node_count,
+            |b, _| {
+                b.iter(|| {
+                    graph.update(
+                        black_box(Vector2::new(1920.0, 1080.0)),
+                        black_box(1.0 / 60.0),
+                        Default::default(),
+                    )
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn graph_transform_propagation(c: &mut Criterion) {
+    let mut graph = generate_hierarchy(4, 10);
+    let root_children = graph[graph.get_root()].children().to_vec();
+
+    c.bench_function("graph_transform_propagation", |b| {
+        let mut offset = 0.0;
+        b.iter(|| {
+            // Invalidate transforms of the whole hierarchy to force full propagation.
+            offset += 0.01;
+            for &child in root_children.iter() {
+                graph[child]
+                    .local_transform_mut()
+                    .set_position(Vector3::new(offset, 0.0, 0.0));
+            }
+            graph.update_hierarchical_data();
+        })
+    });
+}
+
+criterion_group!(benches, graph_update, graph_transform_propagation);
+criterion_main!(benches);
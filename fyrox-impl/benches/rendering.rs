@@ -0,0 +1,157 @@
+//! Benchmarks for CPU-side rendering preparation: render bundle collection, tile map batching and
+//! frustum culling.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use fyrox_impl::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, Point3, Vector2, Vector3},
+        math::{aabb::AxisAlignedBoundingBox, frustum::Frustum},
+        sstorage::ImmutableString,
+    },
+    renderer::bundle::{ObserverInfo, RenderDataBundleStorage},
+    scene::{
+        base::BaseBuilder,
+        collider::BitMask,
+        graph::Graph,
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            MeshBuilder,
+        },
+        tilemap::{Tile, TileMapBuilder},
+        transform::TransformBuilder,
+    },
+};
+
+fn observer() -> ObserverInfo {
+    let view_matrix = Matrix4::look_at_rh(
+        &Point3::new(0.0, 10.0, -30.0),
+        &Point3::new(0.0, 0.0, 0.0),
+        &Vector3::y_axis(),
+    );
+    let projection_matrix = Matrix4::new_perspective(16.0 / 9.0, 1.57, 0.025, 1000.0);
+    ObserverInfo {
+        observer_position: Vector3::new(0.0, 10.0, -30.0),
+        z_near: 0.025,
+        z_far: 1000.0,
+        view_matrix,
+        projection_matrix,
+        render_mask: BitMask(u32::MAX),
+    }
+}
+
+/// Generates a grid of `side * side` meshes, all of them share the same surface data, so they could be
+/// batched.
+fn generate_mesh_grid(side: usize) -> Graph {
+    let mut graph = Graph::new();
+    let data = SurfaceResource::new_ok(
+        ResourceKind::Embedded,
+        SurfaceData::make_cube(Matrix4::identity()),
+    );
+    for z in 0..side {
+        for x in 0..side {
+            MeshBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(
+                            x as f32 * 2.0 - side as f32,
+                            0.0,
+                            z as f32 * 2.0,
+                        ))
+                        .build(),
+                ),
+            )
+            .with_surfaces(vec![SurfaceBuilder::new(data.clone()).build()])
+            .build(&mut graph);
+        }
+    }
+    graph.update_hierarchical_data();
+    graph
+}
+
+fn generate_tile_map(side: i32) -> Graph {
+    let mut graph = Graph::new();
+    let mut tiles = Vec::new();
+    for y in 0..side {
+        for x in 0..side {
+            tiles.push(Tile::new(Vector2::new(x - side / 2, y - side / 2), 0));
+        }
+    }
+    TileMapBuilder::new(BaseBuilder::new())
+        .with_tiles(tiles)
+        .build(&mut graph);
+    graph.update_hierarchical_data();
+    graph
+}
+
+fn bundle_collection(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bundle_collection");
+
+    for side in [10, 32, 64] {
+        let graph = generate_mesh_grid(side);
+        group.bench_with_input(BenchmarkId::from_parameter(side * side), &side, |b, _| {
+            b.iter(|| {
+                RenderDataBundleStorage::from_graph(
+                    black_box(&graph),
+                    observer(),
+                    ImmutableString::new("GBuffer"),
+                )
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn tile_map_collection(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tile_map_collection");
+
+    for side in [16, 64, 128] {
+        let graph = generate_tile_map(side);
+        group.bench_with_input(BenchmarkId::from_parameter(side * side), &side, |b, _| {
+            b.iter(|| {
+                RenderDataBundleStorage::from_graph(
+                    black_box(&graph),
+                    observer(),
+                    ImmutableString::new("Forward"),
+                )
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn frustum_culling(c: &mut Criterion) {
+    let observer = observer();
+    let frustum =
+        Frustum::from_view_projection_matrix(observer.projection_matrix * observer.view_matrix)
+            .unwrap();
+    let boxes = (0..10_000)
+        .map(|i| {
+            let p = Vector3::new(
+                (i % 100) as f32 - 50.0,
+                ((i / 100) % 10) as f32,
+                (i / 100) as f32 - 50.0,
+            );
+            AxisAlignedBoundingBox::from_min_max(p, p + Vector3::repeat(1.0))
+        })
+        .collect::<Vec<_>>();
+
+    c.bench_function("frustum_culling_10000_aabbs", |b| {
+        b.iter(|| {
+            boxes
+                .iter()
+                .filter(|aabb| frustum.is_intersects_aabb(aabb))
+                .count()
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    bundle_collection,
+    tile_map_collection,
+    frustum_culling
+);
+criterion_main!(benches);
@@ -0,0 +1,487 @@
+//! Mouse cursor management. It allows a game to change the appearance of the cursor (including custom
+//! cursors made of textures), to override it for particular widgets and to confine or lock the cursor
+//! in the window. See [`CursorManager`] docs for more info and usage examples.
+
+use crate::{
+    core::{algebra::Vector2, log::Log, parking_lot::Mutex, pool::Handle},
+    dpi::PhysicalPosition,
+    graph::BaseSceneGraph,
+    gui::{
+        image::{Image, ImageBuilder, ImageMessage},
+        message::{CursorIcon, MessageDirection},
+        widget::{WidgetBuilder, WidgetMessage},
+        UiNode, UserInterface,
+    },
+    resource::texture::{TextureKind, TextureResource},
+    utils::translate_cursor_icon,
+    window::{CursorGrabMode, Window},
+};
+use fxhash::FxHashMap;
+use std::sync::Arc;
+
+/// Custom cursor made of a texture.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CustomCursor {
+    /// A texture of the cursor. Only rectangular textures are supported.
+    pub texture: TextureResource,
+    /// Position (in pixels) of the "active" point of the cursor, relative to the top-left corner of
+    /// the texture.
+    pub hotspot: Vector2<u32>,
+}
+
+impl CustomCursor {
+    /// Creates new custom cursor from the given texture and a hotspot.
+    pub fn new(texture: TextureResource, hotspot: Vector2<u32>) -> Self {
+        Self { texture, hotspot }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Appearance {
+    Icon(CursorIcon),
+    Custom(CustomCursor),
+}
+
+#[derive(Default)]
+struct SoftwareCursor {
+    widget: Handle<UiNode>,
+    texture: Option<TextureResource>,
+    position: Vector2<f32>,
+    size: Vector2<f32>,
+    visible: bool,
+}
+
+struct CursorState {
+    icon: Option<CursorIcon>,
+    custom: Option<CustomCursor>,
+    widget_cursors: FxHashMap<Handle<UiNode>, CustomCursor>,
+    visible: bool,
+    grab_mode: CursorGrabMode,
+    relative_mode: bool,
+    applied_icon: Option<CursorIcon>,
+    applied_visibility: Option<bool>,
+    applied_grab_mode: Option<CursorGrabMode>,
+    active_grab_mode: CursorGrabMode,
+    had_focus: bool,
+    software_cursor: SoftwareCursor,
+}
+
+/// Cursor manager is a shared handle, that controls the mouse cursor of the main window. It picks the
+/// appearance of the cursor using the following order (first match wins):
+///
+/// 1) A custom cursor of a widget under the cursor (or any of its ancestors), see
+///    [`CursorManager::set_widget_cursor`].
+/// 2) A cursor icon of a widget under the cursor (see [`crate::gui::widget::Widget::cursor`]).
+/// 3) Custom cursor of the window, see [`CursorManager::set_custom_cursor`].
+/// 4) Cursor icon of the window, see [`CursorManager::set_icon`].
+///
+/// Custom cursors are drawn by the first user interface of the engine (as a topmost image, that
+/// follows the mouse) and the system cursor is hidden meanwhile. This works the same way on every
+/// platform; the size of the cursor is scaled using the scale factor of the window, so the cursor
+/// has the same size as the system one on high-DPI displays.
+///
+/// The manager also allows to confine the cursor in the window or lock it (see
+/// [`CursorManager::set_grab_mode`]), and to switch to relative mode (see
+/// [`CursorManager::set_relative_mode`]), which is useful for first-person cameras. Platforms support
+/// different grab modes, the manager falls back to a supported mode if possible (see
+/// [`CursorManager::active_grab_mode`]) and restores the grab when the window gets focus back.
+///
+/// The engine updates the manager right after user interfaces. The manager can be cloned and stored
+/// anywhere (in a plugin or a script, for example).
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::Vector2, reflect::prelude::*, visitor::prelude::*},
+/// #     engine::cursor::CustomCursor,
+/// #     plugin::{Plugin, PluginContext},
+/// #     resource::texture::Texture,
+/// # };
+/// #[derive(Visit, Reflect, Debug, Default)]
+/// struct Game;
+///
+/// impl Plugin for Game {
+///     fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+///         let texture = context
+///             .resource_manager
+///             .request::<Texture>("data/cursor.png");
+///         context
+///             .cursor
+///             .set_custom_cursor(Some(CustomCursor::new(texture, Vector2::new(2, 2))));
+///
+///         // Hide the cursor and lock it in the window, so the mouse could be used to rotate a
+///         // camera.
+///         context.cursor.set_relative_mode(true);
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct CursorManager {
+    state: Arc<Mutex<CursorState>>,
+}
+
+impl Default for CursorManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CursorManager {
+    /// Creates new cursor manager, which uses cursor icons of the first user interface.
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(CursorState {
+                icon: None,
+                custom: None,
+                widget_cursors: Default::default(),
+                visible: true,
+                grab_mode: CursorGrabMode::None,
+                relative_mode: false,
+                applied_icon: None,
+                applied_visibility: None,
+                applied_grab_mode: None,
+                active_grab_mode: CursorGrabMode::None,
+                had_focus: false,
+                software_cursor: Default::default(),
+            })),
+        }
+    }
+
+    /// Sets new cursor icon of the window. `None` means the default icon.
+    pub fn set_icon(&self, icon: Option<CursorIcon>) {
+        self.state.lock().icon = icon;
+    }
+
+    /// Returns current cursor icon of the window.
+    pub fn icon(&self) -> Option<CursorIcon> {
+        self.state.lock().icon
+    }
+
+    /// Sets new custom cursor of the window. `None` means that the cursor icon of the window will be
+    /// used instead.
+    pub fn set_custom_cursor(&self, cursor: Option<CustomCursor>) {
+        self.state.lock().custom = cursor;
+    }
+
+    /// Returns a copy of current custom cursor of the window.
+    pub fn custom_cursor(&self) -> Option<CustomCursor> {
+        self.state.lock().custom.clone()
+    }
+
+    /// Sets new custom cursor, that will be used when the cursor is over the given widget (or any of
+    /// its descendants). `None` removes the custom cursor of the widget.
+    pub fn set_widget_cursor(&self, widget: Handle<UiNode>, cursor: Option<CustomCursor>) {
+        let mut state = self.state.lock();
+        match cursor {
+            Some(cursor) => {
+                state.widget_cursors.insert(widget, cursor);
+            }
+            None => {
+                state.widget_cursors.remove(&widget);
+            }
+        }
+    }
+
+    /// Returns a copy of the custom cursor of the given widget.
+    pub fn widget_cursor(&self, widget: Handle<UiNode>) -> Option<CustomCursor> {
+        self.state.lock().widget_cursors.get(&widget).cloned()
+    }
+
+    /// Shows or hides the cursor.
+    pub fn set_visible(&self, visible: bool) {
+        self.state.lock().visible = visible;
+    }
+
+    /// Returns `true` if the cursor is visible (it is always hidden in relative mode).
+    pub fn is_visible(&self) -> bool {
+        self.state.lock().visible
+    }
+
+    /// Sets desired grab mode of the cursor. [`CursorGrabMode::Confined`] keeps the cursor inside the
+    /// window, [`CursorGrabMode::Locked`] keeps the cursor at its position. Keep in mind, that not
+    /// every platform supports every mode (Windows and X11 does not support locking, macOS does
+    /// not support confining). Locking falls back to confining if it is not supported.
+    pub fn set_grab_mode(&self, mode: CursorGrabMode) {
+        self.state.lock().grab_mode = mode;
+    }
+
+    /// Returns desired grab mode of the cursor.
+    pub fn grab_mode(&self) -> CursorGrabMode {
+        self.state.lock().grab_mode
+    }
+
+    /// Returns the grab mode, that is currently active. It could be different from the desired one,
+    /// if the platform does not support it.
+    pub fn active_grab_mode(&self) -> CursorGrabMode {
+        self.state.lock().active_grab_mode
+    }
+
+    /// Enables or disables relative mode. In this mode the cursor is hidden and locked in the window,
+    /// so the mouse could be used to rotate a camera (use `DeviceEvent::MouseMotion` to fetch the
+    /// movement). If the platform does not support locking, the cursor is confined in the window and
+    /// moved to the center of the window on every update.
+    pub fn set_relative_mode(&self, enabled: bool) {
+        self.state.lock().relative_mode = enabled;
+    }
+
+    /// Returns `true` if relative mode is enabled.
+    pub fn is_relative_mode(&self) -> bool {
+        self.state.lock().relative_mode
+    }
+
+    pub(crate) fn update(&self, window: &Window, ui: Option<&mut UserInterface>) {
+        let mut state = self.state.lock();
+        let state = &mut *state;
+
+        let appearance = match ui.as_deref() {
+            Some(ui) => state.appearance(ui),
+            None => Appearance::Icon(state.icon.unwrap_or_default()),
+        };
+
+        let grab_mode = if state.relative_mode {
+            CursorGrabMode::Locked
+        } else {
+            state.grab_mode
+        };
+        // Some platforms release the grab when the window loses focus, restore it back.
+        let has_focus = window.has_focus();
+        let focus_gained = has_focus && !state.had_focus;
+        state.had_focus = has_focus;
+        if state.applied_grab_mode != Some(grab_mode) || focus_gained {
+            state.active_grab_mode = apply_grab_mode(window, grab_mode);
+            state.applied_grab_mode = Some(grab_mode);
+        }
+
+        if state.relative_mode && has_focus && state.active_grab_mode == CursorGrabMode::Confined {
+            let size = window.inner_size();
+            let _ =
+                window.set_cursor_position(PhysicalPosition::new(size.width / 2, size.height / 2));
+        }
+
+        let custom = match appearance {
+            Appearance::Icon(icon) => {
+                if state.applied_icon != Some(icon) {
+                    window.set_cursor_icon(translate_cursor_icon(icon));
+                    state.applied_icon = Some(icon);
+                }
+                None
+            }
+            Appearance::Custom(cursor) => Some(cursor),
+        };
+
+        let mut software_cursor_visible = false;
+        if let Some(ui) = ui {
+            software_cursor_visible = state.software_cursor.update(
+                ui,
+                custom
+                    .as_ref()
+                    .filter(|_| state.visible && !state.relative_mode),
+                window.scale_factor() as f32,
+            );
+        }
+
+        let visible = state.visible && !state.relative_mode && !software_cursor_visible;
+        if state.applied_visibility != Some(visible) {
+            window.set_cursor_visible(visible);
+            state.applied_visibility = Some(visible);
+        }
+    }
+}
+
+impl CursorState {
+    fn appearance(&self, ui: &UserInterface) -> Appearance {
+        let mut handle = ui.picked_node();
+        while let Some(node) = ui.try_get(handle) {
+            if let Some(cursor) = self.widget_cursors.get(&handle) {
+                return Appearance::Custom(cursor.clone());
+            }
+            if node.cursor().is_some() {
+                break;
+            }
+            handle = node.parent();
+        }
+
+        if ui.cursor() != CursorIcon::Default {
+            Appearance::Icon(ui.cursor())
+        } else if let Some(custom) = self.custom.as_ref() {
+            Appearance::Custom(custom.clone())
+        } else {
+            Appearance::Icon(self.icon.unwrap_or_default())
+        }
+    }
+}
+
+impl SoftwareCursor {
+    // Returns `true` if the cursor is shown.
+    fn update(
+        &mut self,
+        ui: &mut UserInterface,
+        cursor: Option<&CustomCursor>,
+        scale_factor: f32,
+    ) -> bool {
+        let size = cursor.and_then(|cursor| texture_size(&cursor.texture));
+
+        let (Some(cursor), Some(size)) = (cursor, size) else {
+            if self.visible && ui.try_get(self.widget).is_some() {
+                ui.send_message(WidgetMessage::visibility(
+                    self.widget,
+                    MessageDirection::ToWidget,
+                    false,
+                ));
+            }
+            self.visible = false;
+            return false;
+        };
+
+        if ui
+            .try_get(self.widget)
+            .and_then(|n| n.cast::<Image>())
+            .is_none()
+        {
+            // The user interface was cleared (or it is a new one), create the widget again.
+            *self = Self {
+                widget: ImageBuilder::new(
+                    WidgetBuilder::new()
+                        .with_visibility(false)
+                        .with_hit_test_visibility(false),
+                )
+                .build(&mut ui.build_ctx()),
+                ..Default::default()
+            };
+        }
+
+        let widget = self.widget;
+
+        if self.texture.as_ref() != Some(&cursor.texture) {
+            self.texture = Some(cursor.texture.clone());
+            ui.send_message(ImageMessage::texture(
+                widget,
+                MessageDirection::ToWidget,
+                Some(cursor.texture.clone().into_untyped()),
+            ));
+        }
+
+        let size = size.scale(scale_factor);
+        if self.size != size {
+            self.size = size;
+            ui.send_message(WidgetMessage::width(
+                widget,
+                MessageDirection::ToWidget,
+                size.x,
+            ));
+            ui.send_message(WidgetMessage::height(
+                widget,
+                MessageDirection::ToWidget,
+                size.y,
+            ));
+        }
+
+        let position = ui.cursor_position()
+            - Vector2::new(cursor.hotspot.x as f32, cursor.hotspot.y as f32).scale(scale_factor);
+        if self.position != position {
+            self.position = position;
+            ui.send_message(WidgetMessage::desired_position(
+                widget,
+                MessageDirection::ToWidget,
+                position,
+            ));
+        }
+
+        if !self.visible {
+            self.visible = true;
+            ui.send_message(WidgetMessage::visibility(
+                widget,
+                MessageDirection::ToWidget,
+                true,
+            ));
+        }
+
+        // Keep the cursor on top of every other widget, including the ones that were added later.
+        if ui.node(ui.root()).children().last() != Some(&widget) {
+            ui.send_message(WidgetMessage::topmost(widget, MessageDirection::ToWidget));
+        }
+
+        true
+    }
+}
+
+fn texture_size(texture: &TextureResource) -> Option<Vector2<f32>> {
+    let mut state = texture.state();
+    match state.data()?.kind() {
+        TextureKind::Rectangle { width, height } => Some(Vector2::new(width as f32, height as f32)),
+        _ => None,
+    }
+}
+
+fn apply_grab_mode(window: &Window, mode: CursorGrabMode) -> CursorGrabMode {
+    match window.set_cursor_grab(mode) {
+        Ok(()) => mode,
+        // Windows and X11 does not support locking, confining the cursor is the closest option.
+        Err(_) if mode == CursorGrabMode::Locked => {
+            match window.set_cursor_grab(CursorGrabMode::Confined) {
+                Ok(()) => CursorGrabMode::Confined,
+                Err(err) => {
+                    Log::warn(format!("Unable to lock the cursor. Reason: {err}"));
+                    CursorGrabMode::None
+                }
+            }
+        }
+        Err(err) => {
+            Log::warn(format!(
+                "Unable to set {mode:?} cursor grab mode. Reason: {err}"
+            ));
+            CursorGrabMode::None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        asset::untyped::ResourceKind,
+        gui::{border::BorderBuilder, message::OsEvent},
+        resource::texture::Texture,
+    };
+
+    fn cursor(hotspot: u32) -> CustomCursor {
+        CustomCursor::new(
+            TextureResource::new_ok(ResourceKind::Embedded, Texture::default()),
+            Vector2::new(hotspot, hotspot),
+        )
+    }
+
+    #[test]
+    fn test_appearance() {
+        let screen_size = Vector2::new(100.0, 100.0);
+        let mut ui = UserInterface::new(screen_size);
+        let widget = BorderBuilder::new(WidgetBuilder::new().with_width(50.0).with_height(50.0))
+            .build(&mut ui.build_ctx());
+        ui.update(screen_size, 0.0, &Default::default());
+        // Picking uses drawing commands.
+        ui.draw();
+        ui.process_os_event(&OsEvent::CursorMoved {
+            position: Vector2::new(10.0, 10.0),
+        });
+        ui.update(screen_size, 0.0, &Default::default());
+        assert_eq!(ui.picked_node(), widget);
+
+        let manager = CursorManager::new();
+        let check = |appearance: Appearance| {
+            assert_eq!(manager.state.lock().appearance(&ui), appearance);
+        };
+
+        let (window_cursor, widget_cursor) = (cursor(1), cursor(2));
+
+        check(Appearance::Icon(CursorIcon::Default));
+        manager.set_icon(Some(CursorIcon::Wait));
+        check(Appearance::Icon(CursorIcon::Wait));
+        manager.set_custom_cursor(Some(window_cursor.clone()));
+        check(Appearance::Custom(window_cursor.clone()));
+        manager.set_widget_cursor(widget, Some(widget_cursor.clone()));
+        check(Appearance::Custom(widget_cursor));
+        manager.set_widget_cursor(widget, None);
+        check(Appearance::Custom(window_cursor));
+    }
+}
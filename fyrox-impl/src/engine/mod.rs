@@ -3,8 +3,10 @@
 
 #![warn(missing_docs)]
 
+pub mod cursor;
 pub mod error;
 pub mod executor;
+pub mod presence;
 pub mod task;
 
 mod hotreload;
@@ -17,10 +19,21 @@ use crate::{
         Resource,
     },
     core::{
-        algebra::Vector2, futures::executor::block_on, instant, log::Log, pool::Handle,
-        reflect::Reflect, task::TaskPool, variable::try_inherit_properties, visitor::VisitError,
+        algebra::Vector2,
+        futures::executor::block_on,
+        instant,
+        log::Log,
+        memory::{self, MemoryTag},
+        pool::Handle,
+        profiler,
+        reflect::Reflect,
+        task::TaskPool,
+        variable::try_inherit_properties,
+        visitor::VisitError,
+    },
+    engine::{
+        cursor::CursorManager, error::EngineError, presence::PresenceManager, task::TaskPoolHandler,
     },
-    engine::{error::EngineError, task::TaskPoolHandler},
     event::Event,
     graph::{BaseSceneGraph, NodeMapping, SceneGraph},
     gui::{
@@ -29,20 +42,29 @@ use crate::{
     },
     material::{
         self,
+        graph::{loader::MaterialGraphLoader, MaterialGraph},
         loader::MaterialLoader,
         shader::{loader::ShaderLoader, Shader, ShaderResource, ShaderResourceExtension},
         Material,
     },
+    platform::shell::PlatformManager,
     plugin::{Plugin, PluginContext, PluginRegistrationContext},
-    renderer::{framework::error::FrameworkError, framework::state::GlKind, Renderer},
+    renderer::{
+        framework::error::FrameworkError, framework::state::GlKind, Renderer, ScreenTransition,
+    },
     resource::{
         curve::{loader::CurveLoader, CurveResourceState},
+        dialogue::{loader::DialogueLoader, DialogueResourceState},
+        gradient::{loader::ColorGradientLoader, ColorGradientResourceState},
+        item::{loader::ItemLoader, ItemResourceState},
         model::{loader::ModelLoader, Model, ModelResource},
+        svg::{loader::SvgImageLoader, SvgImage},
         texture::{self, loader::TextureLoader, Texture, TextureKind},
+        timeline::{loader::TimelineLoader, TimelineResourceState},
     },
     scene::{
         base::NodeScriptMessage,
-        camera::SkyBoxKind,
+        camera::{Camera, SkyBoxKind},
         graph::{GraphUpdateSwitches, NodePool},
         navmesh,
         node::{constructor::NodeConstructorContainer, Node},
@@ -293,12 +315,32 @@ struct SceneLoadingOptions {
 /// plugin context. `Game::new` requests a new scene, which internally asks a resource manager to
 /// load the scene. Then, when the scene is fully loaded, the engine calls `Plugin::on_scene_loaded`
 /// method which allows you to do something with the newly loaded scene by taking a reference of it.
+///
+/// ## Transitions
+///
+/// Switching scenes usually shows a few black frames, which could be hidden by a screen transition.
+/// Use [`AsyncSceneLoader::set_transition`] to specify one, and the engine will show the last
+/// rendered frame (with optional effects) while scenes are loading. The transition ends once all
+/// requested scenes are loaded and crossfades to the first frame of the new scene. User interfaces
+/// are still drawn on top of the transition, so a loading screen could be shown as well:
+///
+/// ```rust
+/// use fyrox_impl::{plugin::PluginContext, renderer::ScreenTransition};
+///
+/// fn load_level(context: &mut PluginContext) {
+///     context
+///         .async_scene_loader
+///         .set_transition(Some(ScreenTransition::default().with_blur_radius(12.0)));
+///     context.async_scene_loader.request("data/level.rgs");
+/// }
+/// ```
 pub struct AsyncSceneLoader {
     resource_manager: ResourceManager,
     serialization_context: Arc<SerializationContext>,
     receiver: Receiver<SceneLoadingResult>,
     sender: Sender<SceneLoadingResult>,
     loading_scenes: FxHashMap<PathBuf, LoadingScene>,
+    transition: Option<ScreenTransition>,
 }
 
 struct LoadingScene {
@@ -324,6 +366,7 @@ impl AsyncSceneLoader {
             receiver,
             sender,
             loading_scenes: Default::default(),
+            transition: None,
         }
     }
 
@@ -407,6 +450,17 @@ impl AsyncSceneLoader {
     pub fn request_raw<P: AsRef<Path>>(&mut self, path: P) {
         self.request_with_options(path, SceneLoadingOptions { derived: false });
     }
+
+    /// Sets a screen transition, that will be shown while scenes are loading. `None` disables
+    /// transitions (default). See [`AsyncSceneLoader`] docs for more info.
+    pub fn set_transition(&mut self, transition: Option<ScreenTransition>) {
+        self.transition = transition;
+    }
+
+    /// Returns current screen transition, that is used while scenes are loading.
+    pub fn transition(&self) -> Option<&ScreenTransition> {
+        self.transition.as_ref()
+    }
 }
 
 /// See module docs.
@@ -432,6 +486,15 @@ pub struct Engine {
     /// Task pool for asynchronous task management.
     pub task_pool: TaskPoolHandler,
 
+    /// Platform presence manager. See [`PresenceManager`] docs for more info.
+    pub presence: PresenceManager,
+
+    /// Mouse cursor manager. See [`CursorManager`] docs for more info.
+    pub cursor: CursorManager,
+
+    /// Desktop platform integration. See [`PlatformManager`] docs for more info.
+    pub platform: PlatformManager,
+
     performance_statistics: PerformanceStatistics,
 
     model_events_receiver: Receiver<ResourceEvent>,
@@ -636,6 +699,8 @@ pub struct ScriptedScene {
     /// Script message sender.
     pub message_sender: ScriptMessageSender,
     message_dispatcher: ScriptMessageDispatcher,
+    // Index of the current frame, it is used to spread updates of scripts with reduced update rate.
+    frame_index: u32,
 }
 
 /// Script processor is used to run script methods in a strict order.
@@ -664,6 +729,7 @@ impl ScriptProcessor {
             handle: scene,
             message_sender: ScriptMessageSender { sender: tx },
             message_dispatcher: ScriptMessageDispatcher::new(rx),
+            frame_index: 0,
         });
 
         self.wait_list
@@ -690,6 +756,8 @@ impl ScriptProcessor {
 
         self.scripted_scenes
             .retain(|s| scenes.is_valid_handle(s.handle));
+        self.scripted_scenes
+            .sort_by_key(|s| scenes[s.handle].composition.update_order);
 
         'scene_loop: for scripted_scene in self.scripted_scenes.iter_mut() {
             let scene = &mut scenes[scripted_scene.handle];
@@ -699,6 +767,18 @@ impl ScriptProcessor {
                 continue 'scene_loop;
             }
 
+            let dt = dt * scene.composition.time_scale;
+
+            let frame_index = scripted_scene.frame_index;
+            scripted_scene.frame_index = frame_index.wrapping_add(1);
+
+            // Scripts with distance-based update rate use the first enabled camera as observer.
+            let observer_position = scene
+                .graph
+                .linear_iter()
+                .find(|node| node.is_globally_enabled() && node.cast::<Camera>().is_some())
+                .map(|camera| camera.global_position());
+
             // Fill in initial handles to nodes to initialize, start, update.
             let mut update_queue = VecDeque::new();
             let mut start_queue = VecDeque::new();
@@ -709,11 +789,25 @@ impl ScriptProcessor {
                     .retain(|e| e.script.is_some() && !e.should_be_deleted);
 
                 if node.is_globally_enabled() {
-                    for (i, entry) in node.scripts.iter().enumerate() {
-                        if let Some(script) = entry.script.as_ref() {
+                    let node_position = node.global_position();
+                    for (i, entry) in node.scripts.iter_mut().enumerate() {
+                        if let Some(script) = entry.script.as_mut() {
                             if script.initialized {
                                 if script.started {
-                                    update_queue.push_back((handle, i));
+                                    let interval =
+                                        script.update_rate().interval(observer_position.map(
+                                            |observer| observer.metric_distance(&node_position),
+                                        ));
+
+                                    // Spread updates of the scripts with the same interval evenly
+                                    // across frames.
+                                    if frame_index.wrapping_add(handle.index()) % interval == 0 {
+                                        script.update_dt = Some(script.accumulated_dt + dt);
+                                        script.accumulated_dt = 0.0;
+                                        update_queue.push_back((handle, i));
+                                    } else {
+                                        script.accumulated_dt += dt;
+                                    }
                                 } else {
                                     start_queue.push_back((handle, i));
                                 }
@@ -779,6 +873,24 @@ impl ScriptProcessor {
                                     },
                                 );
                             }
+                            NodeScriptMessage::RecycleScript {
+                                handle,
+                                script_index,
+                            } => {
+                                context.handle = handle;
+                                context.script_index = script_index;
+
+                                process_node_script(
+                                    script_index,
+                                    &mut context,
+                                    &mut |script, context| {
+                                        // Uninitialized scripts will be initialized as usual.
+                                        if script.initialized {
+                                            script.on_recycle(context);
+                                        }
+                                    },
+                                );
+                            }
                             NodeScriptMessage::DestroyScript {
                                 handle,
                                 script,
@@ -787,6 +899,9 @@ impl ScriptProcessor {
                                 // Destruction is delayed to the end of the frame.
                                 destruction_queue.push_back((handle, script, script_index));
                             }
+                            NodeScriptMessage::SendMessage(message) => {
+                                context.message_sender.send(message);
+                            }
                         }
                     }
 
@@ -833,7 +948,9 @@ impl ScriptProcessor {
                         context.script_index = script_index;
 
                         process_node_script(script_index, &mut context, &mut |script, context| {
+                            context.dt = script.update_dt.take().unwrap_or(dt);
                             script.on_update(context);
+                            context.dt = dt;
                         });
                     }
                 }
@@ -1197,6 +1314,7 @@ pub(crate) fn initialize_resource_manager_loaders(
         material::STANDARD_TERRAIN.clone(),
         material::STANDARD_TWOSIDES.clone(),
         material::STANDARD_PARTICLE_SYSTEM.clone(),
+        material::STANDARD_VECTOR_SHAPE.clone(),
     ] {
         state.built_in_resources.insert(
             material.kind().path_owned().unwrap(),
@@ -1220,8 +1338,16 @@ pub(crate) fn initialize_resource_manager_loaders(
 
     state.constructors_container.add::<Texture>();
     state.constructors_container.add::<Shader>();
+    state.constructors_container.add::<MaterialGraph>();
     state.constructors_container.add::<Model>();
     state.constructors_container.add::<CurveResourceState>();
+    state
+        .constructors_container
+        .add::<ColorGradientResourceState>();
+    state.constructors_container.add::<TimelineResourceState>();
+    state.constructors_container.add::<DialogueResourceState>();
+    state.constructors_container.add::<ItemResourceState>();
+    state.constructors_container.add::<SvgImage>();
     state.constructors_container.add::<SoundBuffer>();
     state.constructors_container.add::<HrirSphereResourceData>();
     state.constructors_container.add::<Material>();
@@ -1239,7 +1365,13 @@ pub(crate) fn initialize_resource_manager_loaders(
         default_import_options: Default::default(),
     });
     loaders.set(ShaderLoader);
+    loaders.set(MaterialGraphLoader);
     loaders.set(CurveLoader);
+    loaders.set(ColorGradientLoader);
+    loaders.set(TimelineLoader);
+    loaders.set(DialogueLoader);
+    loaders.set(ItemLoader);
+    loaders.set(SvgImageLoader);
     loaders.set(HrirSphereLoader);
     loaders.set(MaterialLoader {
         resource_manager: resource_manager.clone(),
@@ -1340,6 +1472,8 @@ impl Engine {
         let (rx, tx) = channel();
         resource_manager.state().event_broadcaster.add(rx);
 
+        profiler::start_external_profilers();
+
         let sound_engine = SoundEngine::without_device();
 
         let user_interfaces =
@@ -1364,6 +1498,9 @@ impl Engine {
             plugins_enabled: false,
             elapsed_time: 0.0,
             task_pool: TaskPoolHandler::new(task_pool),
+            presence: Default::default(),
+            cursor: Default::default(),
+            platform: Default::default(),
         })
     }
 
@@ -1745,6 +1882,19 @@ impl Engine {
         lag: &mut f32,
         window_target: &EventLoopWindowTarget<()>,
     ) {
+        if let Some(transition) = self.async_scene_loader.transition.as_ref() {
+            if self
+                .async_scene_loader
+                .loading_scenes
+                .values()
+                .any(|request| !request.reported)
+            {
+                if let GraphicsContext::Initialized(ref mut ctx) = self.graphics_context {
+                    ctx.renderer.begin_screen_transition(transition.clone());
+                }
+            }
+        }
+
         let len = self.async_scene_loader.loading_scenes.len();
         let mut n = 0;
         while n < len {
@@ -1770,6 +1920,9 @@ impl Engine {
                             async_scene_loader: &mut self.async_scene_loader,
                             window_target: Some(window_target),
                             task_pool: &mut self.task_pool,
+                            presence: &self.presence,
+                            cursor: &self.cursor,
+                            platform: &self.platform,
                         };
 
                         for plugin in self.plugins.iter_mut() {
@@ -1782,7 +1935,9 @@ impl Engine {
             n += 1;
         }
 
+        let mut received = false;
         while let Ok(loading_result) = self.async_scene_loader.receiver.try_recv() {
+            received = true;
             if let Some(request) = self
                 .async_scene_loader
                 .loading_scenes
@@ -1803,6 +1958,9 @@ impl Engine {
                     async_scene_loader: &mut self.async_scene_loader,
                     window_target: Some(window_target),
                     task_pool: &mut self.task_pool,
+                    presence: &self.presence,
+                    cursor: &self.cursor,
+                    platform: &self.platform,
                 };
 
                 match loading_result.result {
@@ -1825,6 +1983,7 @@ impl Engine {
                                             &mut |_, _, _| {},
                                         )
                                         .0,
+                                    shared_skeleton: None,
                                 },
                             );
 
@@ -1911,6 +2070,17 @@ impl Engine {
                 }
             }
         }
+
+        // Plugins could request more scenes while handling loaded ones, keep the transition until
+        // everything is loaded.
+        if received
+            && self.async_scene_loader.transition.is_some()
+            && self.async_scene_loader.loading_scenes.is_empty()
+        {
+            if let GraphicsContext::Initialized(ref mut ctx) = self.graphics_context {
+                ctx.renderer.end_screen_transition();
+            }
+        }
     }
 
     /// Performs pre update for the engine.
@@ -1934,19 +2104,28 @@ impl Engine {
         lag: &mut f32,
         switches: FxHashMap<Handle<Scene>, GraphUpdateSwitches>,
     ) {
+        fyrox_core::profile_span!("Engine::pre_update");
+
         self.resource_manager.state().update(dt);
         self.handle_model_events();
 
         let window_size = if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
             let inner_size = ctx.window.inner_size();
             let window_size = Vector2::new(inner_size.width as f32, inner_size.height as f32);
+            let _renderer_scope = memory::scope(MemoryTag::Renderer);
             ctx.renderer.update_caches(dt);
             window_size
         } else {
             Vector2::new(1.0, 1.0)
         };
 
-        for (handle, scene) in self.scenes.pair_iter_mut().filter(|(_, s)| *s.enabled) {
+        let scene_scope = memory::scope(MemoryTag::Scene);
+        for handle in self.scenes.update_order() {
+            let scene = &mut self.scenes[handle];
+            if !*scene.enabled {
+                continue;
+            }
+
             let frame_size =
                 scene
                     .rendering_options
@@ -1960,15 +2139,26 @@ impl Engine {
                         }
                     });
 
+            fyrox_core::profile_span!("Scene::update");
             scene.update(
                 frame_size,
-                dt,
+                dt * scene.composition.time_scale,
                 switches.get(&handle).cloned().unwrap_or_default(),
             );
         }
+        drop(scene_scope);
+
+        self.handle_engine_tasks(dt, window_target, lag);
 
-        self.update_plugins(dt, window_target, lag);
-        self.handle_scripts(dt);
+        let _scripts_scope = memory::scope(MemoryTag::Scripts);
+        {
+            fyrox_core::profile_span!("Plugins::update");
+            self.update_plugins(dt, window_target, lag);
+        }
+        {
+            fyrox_core::profile_span!("Scripts::update");
+            self.handle_scripts(dt);
+        }
     }
 
     /// Performs post update for the engine.
@@ -1980,11 +2170,17 @@ impl Engine {
             let inner_size = ctx.window.inner_size();
             let window_size = Vector2::new(inner_size.width as f32, inner_size.height as f32);
 
+            fyrox_core::profile_span!("UserInterface::update");
             let time = instant::Instant::now();
+            let _ui_scope = memory::scope(MemoryTag::Ui);
             for ui in self.user_interfaces.iter_mut() {
                 ui.update(window_size, dt, ui_update_switches);
             }
             self.performance_statistics.ui_time = instant::Instant::now() - time;
+
+            self.cursor
+                .update(&ctx.window, self.user_interfaces.iter_mut().next());
+            self.platform.update(&ctx.window);
             self.elapsed_time += dt;
         }
     }
@@ -2017,6 +2213,40 @@ impl Engine {
         self.performance_statistics.scripts_time = instant::Instant::now() - time;
     }
 
+    fn handle_engine_tasks(
+        &mut self,
+        dt: f32,
+        window_target: &EventLoopWindowTarget<()>,
+        lag: &mut f32,
+    ) {
+        while let Some(result) = self.task_pool.next_engine_task_result() {
+            if let Some(engine_task_handler) = self.task_pool.pop_engine_task_handler(result.id) {
+                (engine_task_handler)(
+                    result.payload,
+                    &mut PluginContext {
+                        scenes: &mut self.scenes,
+                        resource_manager: &self.resource_manager,
+                        graphics_context: &mut self.graphics_context,
+                        dt,
+                        lag,
+                        user_interfaces: &mut self.user_interfaces,
+                        serialization_context: &self.serialization_context,
+                        widget_constructors: &self.widget_constructors,
+                        performance_statistics: &self.performance_statistics,
+                        elapsed_time: self.elapsed_time,
+                        script_processor: &self.script_processor,
+                        async_scene_loader: &mut self.async_scene_loader,
+                        window_target: Some(window_target),
+                        task_pool: &mut self.task_pool,
+                        presence: &self.presence,
+                        cursor: &self.cursor,
+                        platform: &self.platform,
+                    },
+                )
+            }
+        }
+    }
+
     fn handle_async_tasks(
         &mut self,
         dt: f32,
@@ -2044,6 +2274,9 @@ impl Engine {
                         async_scene_loader: &mut self.async_scene_loader,
                         window_target: Some(window_target),
                         task_pool: &mut self.task_pool,
+                        presence: &self.presence,
+                        cursor: &self.cursor,
+                        platform: &self.platform,
                     },
                 )
             } else if let Some(node_task_handler) = self.task_pool.pop_node_task_handler(result.id)
@@ -2138,12 +2371,18 @@ impl Engine {
                 async_scene_loader: &mut self.async_scene_loader,
                 window_target: Some(window_target),
                 task_pool: &mut self.task_pool,
+                presence: &self.presence,
+                cursor: &self.cursor,
+                platform: &self.platform,
             };
 
             for plugin in self.plugins.iter_mut() {
                 plugin.update(&mut context);
             }
 
+            // Deliver activity changes made by plugins and scripts.
+            self.presence.update(dt);
+
             let mut uis = self
                 .user_interfaces
                 .pair_iter()
@@ -2171,6 +2410,9 @@ impl Engine {
                         async_scene_loader: &mut self.async_scene_loader,
                         window_target: Some(window_target),
                         task_pool: &mut self.task_pool,
+                        presence: &self.presence,
+                        cursor: &self.cursor,
+                        platform: &self.platform,
                     };
 
                     for plugin in self.plugins.iter_mut() {
@@ -2209,6 +2451,9 @@ impl Engine {
                         async_scene_loader: &mut self.async_scene_loader,
                         window_target: Some(window_target),
                         task_pool: &mut self.task_pool,
+                        presence: &self.presence,
+                        cursor: &self.cursor,
+                        platform: &self.platform,
                     },
                 );
             }
@@ -2238,6 +2483,9 @@ impl Engine {
                     async_scene_loader: &mut self.async_scene_loader,
                     window_target: Some(window_target),
                     task_pool: &mut self.task_pool,
+                    presence: &self.presence,
+                    cursor: &self.cursor,
+                    platform: &self.platform,
                 });
             }
         }
@@ -2266,6 +2514,9 @@ impl Engine {
                     async_scene_loader: &mut self.async_scene_loader,
                     window_target: Some(window_target),
                     task_pool: &mut self.task_pool,
+                    presence: &self.presence,
+                    cursor: &self.cursor,
+                    platform: &self.platform,
                 });
             }
         }
@@ -2294,6 +2545,9 @@ impl Engine {
                     async_scene_loader: &mut self.async_scene_loader,
                     window_target: Some(window_target),
                     task_pool: &mut self.task_pool,
+                    presence: &self.presence,
+                    cursor: &self.cursor,
+                    platform: &self.platform,
                 });
             }
         }
@@ -2367,6 +2621,21 @@ impl Engine {
                     for scene in self.scenes.iter_mut() {
                         scene.resolve(&self.resource_manager);
                     }
+                } else if let Some(shader) = resource.try_cast::<Shader>() {
+                    Log::info(format!(
+                        "A shader resource {} was reloaded, syncing materials...",
+                        shader.kind()
+                    ));
+
+                    // GPU programs of the shader are re-created by the renderer, the materials
+                    // must be synced with the new set of properties and features of the shader.
+                    for scene in self.scenes.iter_mut() {
+                        scene
+                            .graph
+                            .sync_materials_to_shaders(&self.resource_manager, |material| {
+                                material.shader() == &shader
+                            });
+                    }
                 }
             }
         }
@@ -2376,6 +2645,9 @@ impl Engine {
     /// see anything.
     #[inline]
     pub fn render(&mut self) -> Result<(), FrameworkError> {
+        fyrox_core::profile_span!("Engine::render");
+        let _renderer_scope = memory::scope(MemoryTag::Renderer);
+
         for ui in self.user_interfaces.iter_mut() {
             ui.draw();
         }
@@ -2402,8 +2674,16 @@ impl Engine {
                         .map(|ui| ui.get_drawing_context()),
                 )?;
             }
+
+            let statistics = ctx.renderer.get_statistics();
+            fyrox_core::profile_plot!("FPS", statistics.frames_per_second);
+            fyrox_core::profile_plot!("Draw Calls", statistics.geometry.draw_calls);
+            fyrox_core::profile_plot!("Triangles", statistics.geometry.triangles_rendered);
+            fyrox_core::profile_plot!("Pure Frame Time", statistics.pure_frame_time);
         }
 
+        profiler::frame_mark();
+
         Ok(())
     }
 
@@ -2437,6 +2717,9 @@ impl Engine {
                             async_scene_loader: &mut self.async_scene_loader,
                             window_target,
                             task_pool: &mut self.task_pool,
+                            presence: &self.presence,
+                            cursor: &self.cursor,
+                            platform: &self.platform,
                         },
                     );
                 }
@@ -2460,6 +2743,9 @@ impl Engine {
                         async_scene_loader: &mut self.async_scene_loader,
                         window_target,
                         task_pool: &mut self.task_pool,
+                        presence: &self.presence,
+                        cursor: &self.cursor,
+                        platform: &self.platform,
                     });
                 }
             }
@@ -2852,6 +3138,9 @@ impl Engine {
             async_scene_loader: &mut self.async_scene_loader,
             window_target: Some(window_target),
             task_pool: &mut self.task_pool,
+            presence: &self.presence,
+            cursor: &self.cursor,
+            platform: &self.platform,
         });
 
         Log::info(format!(
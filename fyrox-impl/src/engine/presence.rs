@@ -0,0 +1,780 @@
+//! Platform presence (rich presence) support. It allows a game to tell a platform (Discord, for example)
+//! what the player is currently doing - "In level 3, 2/4 players". See [`PresenceManager`] docs for more
+//! info and usage examples.
+
+use crate::core::{log::Log, parking_lot::Mutex};
+use std::{
+    fmt::{Display, Formatter, Write as _},
+    io::{Read, Write},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// An error that may occur when communicating with a presence backend.
+#[derive(Debug)]
+pub enum PresenceError {
+    /// An I/O error has occurred.
+    Io(std::io::Error),
+    /// A backend is not connected to the platform.
+    NotConnected,
+    /// A backend is not supported on the current platform.
+    Unsupported,
+    /// A platform has responded with unexpected data.
+    Protocol(String),
+}
+
+impl Display for PresenceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PresenceError::Io(v) => Display::fmt(v, f),
+            PresenceError::NotConnected => write!(f, "Presence backend is not connected."),
+            PresenceError::Unsupported => {
+                write!(f, "Presence backend is not supported on this platform.")
+            }
+            PresenceError::Protocol(v) => write!(f, "Presence protocol error: {v}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for PresenceError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// Party information of an activity.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PresenceParty {
+    /// Unique id of the party.
+    pub id: String,
+    /// Current amount of players in the party.
+    pub size: u32,
+    /// Maximum amount of players in the party.
+    pub max: u32,
+}
+
+/// Description of what the player is currently doing.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PresenceActivity {
+    /// Current state of the player, for example "In level 3".
+    pub state: Option<String>,
+    /// Additional details, for example "Capture the flag".
+    pub details: Option<String>,
+    /// Unix timestamp (in seconds) of the moment when the activity has started. Platforms usually
+    /// show elapsed time using it.
+    pub start_timestamp: Option<u64>,
+    /// Name of a large image asset (registered on the platform side).
+    pub large_image: Option<String>,
+    /// Tooltip text of the large image.
+    pub large_text: Option<String>,
+    /// Name of a small image asset (registered on the platform side).
+    pub small_image: Option<String>,
+    /// Tooltip text of the small image.
+    pub small_text: Option<String>,
+    /// Optional party information.
+    pub party: Option<PresenceParty>,
+}
+
+impl PresenceActivity {
+    /// Creates new activity with the given state.
+    pub fn new(state: impl Into<String>) -> Self {
+        Self {
+            state: Some(state.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Sets new details of the activity.
+    pub fn with_details(mut self, details: impl Into<String>) -> Self {
+        self.details = Some(details.into());
+        self
+    }
+
+    /// Sets start timestamp of the activity to the current time.
+    pub fn with_start_now(mut self) -> Self {
+        self.start_timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_secs());
+        self
+    }
+
+    /// Sets new large image of the activity.
+    pub fn with_large_image(mut self, image: impl Into<String>, text: impl Into<String>) -> Self {
+        self.large_image = Some(image.into());
+        self.large_text = Some(text.into());
+        self
+    }
+
+    /// Sets new small image of the activity.
+    pub fn with_small_image(mut self, image: impl Into<String>, text: impl Into<String>) -> Self {
+        self.small_image = Some(image.into());
+        self.small_text = Some(text.into());
+        self
+    }
+
+    /// Sets party information of the activity.
+    pub fn with_party(mut self, id: impl Into<String>, size: u32, max: u32) -> Self {
+        self.party = Some(PresenceParty {
+            id: id.into(),
+            size,
+            max,
+        });
+        self
+    }
+}
+
+/// Presence backend is an interface to a particular platform. Backends run on a separate thread
+/// (see [`PresenceManager`]), so their methods are allowed to block.
+pub trait PresenceBackend: Send + 'static {
+    /// Tries to connect to the platform.
+    fn connect(&mut self) -> Result<(), PresenceError>;
+
+    /// Returns `true` if the backend is connected to the platform.
+    fn is_connected(&self) -> bool;
+
+    /// Sends the given activity to the platform. `None` clears current activity.
+    fn set_activity(&mut self, activity: Option<&PresenceActivity>) -> Result<(), PresenceError>;
+
+    /// Closes the connection to the platform.
+    fn disconnect(&mut self);
+}
+
+#[cfg(unix)]
+type IpcStream = std::os::unix::net::UnixStream;
+#[cfg(windows)]
+type IpcStream = std::fs::File;
+
+/// Discord backend, that uses local IPC channel of the Discord client. It requires an application
+/// id, that can be obtained in the Discord developer portal.
+pub struct DiscordPresenceBackend {
+    client_id: String,
+    #[cfg(any(unix, windows))]
+    stream: Option<IpcStream>,
+    nonce: u64,
+}
+
+// A hung Discord client must not stall the presence thread forever.
+#[cfg(unix)]
+const DISCORD_IO_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+const DISCORD_OP_HANDSHAKE: u32 = 0;
+const DISCORD_OP_FRAME: u32 = 1;
+const DISCORD_OP_CLOSE: u32 = 2;
+
+impl DiscordPresenceBackend {
+    /// Creates new Discord backend for an application with the given id.
+    pub fn new(client_id: impl Into<String>) -> Self {
+        Self {
+            client_id: client_id.into(),
+            #[cfg(any(unix, windows))]
+            stream: None,
+            nonce: 0,
+        }
+    }
+
+    #[cfg(unix)]
+    fn open_stream() -> Result<IpcStream, PresenceError> {
+        let dir = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"]
+            .iter()
+            .find_map(|var| std::env::var(var).ok())
+            .unwrap_or_else(|| "/tmp".to_string());
+        let mut last_error = PresenceError::NotConnected;
+        for i in 0..10 {
+            match IpcStream::connect(format!("{dir}/discord-ipc-{i}")) {
+                Ok(stream) => {
+                    stream.set_read_timeout(Some(DISCORD_IO_TIMEOUT))?;
+                    stream.set_write_timeout(Some(DISCORD_IO_TIMEOUT))?;
+                    return Ok(stream);
+                }
+                Err(err) => last_error = err.into(),
+            }
+        }
+        Err(last_error)
+    }
+
+    #[cfg(windows)]
+    fn open_stream() -> Result<IpcStream, PresenceError> {
+        let mut last_error = PresenceError::NotConnected;
+        for i in 0..10 {
+            match std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(format!(r"\\?\pipe\discord-ipc-{i}"))
+            {
+                Ok(stream) => return Ok(stream),
+                Err(err) => last_error = err.into(),
+            }
+        }
+        Err(last_error)
+    }
+
+    #[cfg(any(unix, windows))]
+    fn send(&mut self, opcode: u32, payload: &str) -> Result<String, PresenceError> {
+        let stream = self.stream.as_mut().ok_or(PresenceError::NotConnected)?;
+
+        let mut frame = Vec::with_capacity(8 + payload.len());
+        frame.extend_from_slice(&opcode.to_le_bytes());
+        frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        frame.extend_from_slice(payload.as_bytes());
+        stream.write_all(&frame)?;
+
+        let mut header = [0u8; 8];
+        stream.read_exact(&mut header)?;
+        let response_opcode = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let length = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        let mut response = vec![0u8; length as usize];
+        stream.read_exact(&mut response)?;
+        let response = String::from_utf8_lossy(&response).into_owned();
+
+        if response_opcode == DISCORD_OP_CLOSE {
+            Err(PresenceError::Protocol(response))
+        } else {
+            Ok(response)
+        }
+    }
+
+    fn activity_json(&mut self, activity: Option<&PresenceActivity>) -> String {
+        self.nonce += 1;
+
+        let activity = match activity {
+            Some(activity) => {
+                let mut fields = Vec::new();
+                if let Some(state) = activity.state.as_ref() {
+                    fields.push(format!("\"state\":{}", json_string(state)));
+                }
+                if let Some(details) = activity.details.as_ref() {
+                    fields.push(format!("\"details\":{}", json_string(details)));
+                }
+                if let Some(start) = activity.start_timestamp {
+                    fields.push(format!("\"timestamps\":{{\"start\":{start}}}"));
+                }
+                let mut assets = Vec::new();
+                for (name, value) in [
+                    ("large_image", &activity.large_image),
+                    ("large_text", &activity.large_text),
+                    ("small_image", &activity.small_image),
+                    ("small_text", &activity.small_text),
+                ] {
+                    if let Some(value) = value {
+                        assets.push(format!("\"{name}\":{}", json_string(value)));
+                    }
+                }
+                if !assets.is_empty() {
+                    fields.push(format!("\"assets\":{{{}}}", assets.join(",")));
+                }
+                if let Some(party) = activity.party.as_ref() {
+                    fields.push(format!(
+                        "\"party\":{{\"id\":{},\"size\":[{},{}]}}",
+                        json_string(&party.id),
+                        party.size,
+                        party.max
+                    ));
+                }
+                format!("{{{}}}", fields.join(","))
+            }
+            None => "null".to_string(),
+        };
+
+        format!(
+            "{{\"cmd\":\"SET_ACTIVITY\",\"args\":{{\"pid\":{},\"activity\":{}}},\"nonce\":\"{}\"}}",
+            std::process::id(),
+            activity,
+            self.nonce
+        )
+    }
+}
+
+impl PresenceBackend for DiscordPresenceBackend {
+    #[cfg(any(unix, windows))]
+    fn connect(&mut self) -> Result<(), PresenceError> {
+        self.stream = Some(Self::open_stream()?);
+        let handshake = format!("{{\"v\":1,\"client_id\":{}}}", json_string(&self.client_id));
+        match self.send(DISCORD_OP_HANDSHAKE, &handshake) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                self.stream = None;
+                Err(err)
+            }
+        }
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn connect(&mut self) -> Result<(), PresenceError> {
+        Err(PresenceError::Unsupported)
+    }
+
+    fn is_connected(&self) -> bool {
+        #[cfg(any(unix, windows))]
+        {
+            self.stream.is_some()
+        }
+        #[cfg(not(any(unix, windows)))]
+        {
+            false
+        }
+    }
+
+    fn set_activity(&mut self, activity: Option<&PresenceActivity>) -> Result<(), PresenceError> {
+        let payload = self.activity_json(activity);
+        #[cfg(any(unix, windows))]
+        {
+            match self.send(DISCORD_OP_FRAME, &payload) {
+                Ok(_) => Ok(()),
+                Err(err) => {
+                    // Any failure means that the pipe is most likely broken, drop it and let
+                    // the manager reconnect.
+                    self.stream = None;
+                    Err(err)
+                }
+            }
+        }
+        #[cfg(not(any(unix, windows)))]
+        {
+            let _ = payload;
+            Err(PresenceError::Unsupported)
+        }
+    }
+
+    fn disconnect(&mut self) {
+        #[cfg(any(unix, windows))]
+        {
+            if let Some(mut stream) = self.stream.take() {
+                let mut frame = Vec::with_capacity(10);
+                frame.extend_from_slice(&DISCORD_OP_CLOSE.to_le_bytes());
+                frame.extend_from_slice(&2u32.to_le_bytes());
+                frame.extend_from_slice(b"{}");
+                let _ = stream.write_all(&frame);
+            }
+        }
+    }
+}
+
+fn json_string(str: &str) -> String {
+    let mut out = String::with_capacity(str.len() + 2);
+    out.push('"');
+    for c in str.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+enum WorkerCommand {
+    Connect,
+    SetActivity(Option<PresenceActivity>),
+    Shutdown,
+}
+
+enum WorkerEvent {
+    Connected(Result<(), PresenceError>),
+    ActivitySet(Result<(), PresenceError>),
+}
+
+// A thread, that owns a backend and executes blocking requests to it.
+struct PresenceWorker {
+    sender: Sender<WorkerCommand>,
+    receiver: Receiver<WorkerEvent>,
+}
+
+impl PresenceWorker {
+    fn spawn(mut backend: Box<dyn PresenceBackend>) -> Self {
+        let (command_sender, command_receiver) = mpsc::channel();
+        let (event_sender, event_receiver) = mpsc::channel();
+
+        let spawn_result = std::thread::Builder::new()
+            .name("Presence".to_string())
+            .spawn(move || {
+                while let Ok(command) = command_receiver.recv() {
+                    let event = match command {
+                        WorkerCommand::Connect => WorkerEvent::Connected(backend.connect()),
+                        WorkerCommand::SetActivity(activity) => {
+                            WorkerEvent::ActivitySet(backend.set_activity(activity.as_ref()))
+                        }
+                        WorkerCommand::Shutdown => break,
+                    };
+                    if event_sender.send(event).is_err() {
+                        break;
+                    }
+                }
+                backend.disconnect();
+            });
+
+        if let Err(err) = spawn_result {
+            Log::err(format!("Unable to spawn presence thread. Reason: {err}"));
+        }
+
+        Self {
+            sender: command_sender,
+            receiver: event_receiver,
+        }
+    }
+
+    fn send(&self, command: WorkerCommand) -> bool {
+        self.sender.send(command).is_ok()
+    }
+}
+
+impl Drop for PresenceWorker {
+    fn drop(&mut self) {
+        // Do not wait for the thread, it could be blocked by the platform. It will exit as soon as
+        // it finishes its current request.
+        let _ = self.sender.send(WorkerCommand::Shutdown);
+    }
+}
+
+struct PresenceState {
+    worker: Option<PresenceWorker>,
+    connected: bool,
+    // `true` if the worker is busy with a request, the manager sends at most one request at a time.
+    pending: bool,
+    current: Option<PresenceActivity>,
+    dirty: bool,
+    min_update_interval: f32,
+    update_timer: f32,
+    reconnect_interval: f32,
+    reconnect_timer: f32,
+    failed_attempts: u32,
+}
+
+impl PresenceState {
+    fn handle_event(&mut self, event: WorkerEvent) {
+        self.pending = false;
+        match event {
+            WorkerEvent::Connected(Ok(())) => {
+                Log::info("Presence backend was connected successfully.");
+                self.connected = true;
+                self.failed_attempts = 0;
+                self.dirty = true;
+            }
+            WorkerEvent::Connected(Err(err)) => {
+                if self.failed_attempts == 0 {
+                    Log::warn(format!("Unable to connect presence backend. Reason: {err}"));
+                }
+                self.failed_attempts = self.failed_attempts.saturating_add(1);
+                self.reconnect_timer = self.reconnect_interval
+                    * (1u32 << self.failed_attempts.min(4).saturating_sub(1)) as f32;
+            }
+            WorkerEvent::ActivitySet(Ok(())) => (),
+            WorkerEvent::ActivitySet(Err(err)) => {
+                Log::warn(format!("Unable to update presence. Reason: {err}"));
+                // Any failure means that the connection is most likely broken, reconnect and send
+                // the activity again.
+                self.connected = false;
+                self.dirty = true;
+                self.reconnect_timer = self.reconnect_interval;
+            }
+        }
+    }
+}
+
+/// Presence manager is a shared handle, that holds an optional presence backend and takes care of rate
+/// limiting (platforms usually allow only a few updates per several seconds) and reconnection (a player
+/// may start the platform client after the game was started). The engine updates the manager right after
+/// plugins update, so activity changes are delivered at the end of a frame. The manager can be cloned
+/// and stored anywhere (in a plugin, for example), so scripts can change the activity too.
+///
+/// The backend runs on its own thread and the manager only exchanges messages with it, so a slow or
+/// hung platform client never stalls the game.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{reflect::prelude::*, visitor::prelude::*},
+/// #     engine::presence::{DiscordPresenceBackend, PresenceActivity},
+/// #     plugin::{Plugin, PluginContext},
+/// # };
+/// #[derive(Visit, Reflect, Debug, Default)]
+/// struct Game;
+///
+/// impl Plugin for Game {
+///     fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+///         context
+///             .presence
+///             .set_backend(DiscordPresenceBackend::new("1234567890"));
+///         context.presence.set_activity(
+///             PresenceActivity::new("In level 3")
+///                 .with_party("lobby", 2, 4)
+///                 .with_start_now(),
+///         );
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct PresenceManager {
+    state: Arc<Mutex<PresenceState>>,
+}
+
+impl Default for PresenceManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PresenceManager {
+    /// Creates new presence manager without any backend.
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(PresenceState {
+                worker: None,
+                connected: false,
+                pending: false,
+                current: None,
+                dirty: false,
+                min_update_interval: 4.0,
+                update_timer: 0.0,
+                reconnect_interval: 5.0,
+                reconnect_timer: 0.0,
+                failed_attempts: 0,
+            })),
+        }
+    }
+
+    /// Sets new backend. Previous backend (if any) will be disconnected. Current activity will be sent
+    /// to the new backend as soon as it is connected.
+    pub fn set_backend<B: PresenceBackend>(&self, backend: B) {
+        let mut state = self.state.lock();
+        state.worker = Some(PresenceWorker::spawn(Box::new(backend)));
+        state.connected = false;
+        state.pending = false;
+        state.dirty = true;
+        state.reconnect_timer = 0.0;
+        state.failed_attempts = 0;
+    }
+
+    /// Removes current backend (if any) and disconnects it.
+    pub fn remove_backend(&self) {
+        let mut state = self.state.lock();
+        state.worker = None;
+        state.connected = false;
+        state.pending = false;
+    }
+
+    /// Sets new activity. The activity will be sent to the platform on next update (it could be delayed
+    /// due to rate limiting). Setting the same activity multiple times does nothing.
+    pub fn set_activity(&self, activity: PresenceActivity) {
+        let mut state = self.state.lock();
+        if state.current.as_ref() != Some(&activity) {
+            state.current = Some(activity);
+            state.dirty = true;
+        }
+    }
+
+    /// Clears current activity.
+    pub fn clear_activity(&self) {
+        let mut state = self.state.lock();
+        if state.current.is_some() {
+            state.current = None;
+            state.dirty = true;
+        }
+    }
+
+    /// Returns a copy of current activity.
+    pub fn activity(&self) -> Option<PresenceActivity> {
+        self.state.lock().current.clone()
+    }
+
+    /// Sets minimal amount of time (in seconds) between two consecutive activity updates. Default is
+    /// 4 seconds, which matches Discord limits.
+    pub fn set_min_update_interval(&self, interval: f32) {
+        self.state.lock().min_update_interval = interval.max(0.0);
+    }
+
+    /// Sets base amount of time (in seconds) between reconnection attempts. Every failed attempt doubles
+    /// the interval (up to 8 times). Default is 5 seconds.
+    pub fn set_reconnect_interval(&self, interval: f32) {
+        self.state.lock().reconnect_interval = interval.max(0.0);
+    }
+
+    /// Returns `true` if there's a backend and it is connected.
+    pub fn is_connected(&self) -> bool {
+        self.state.lock().connected
+    }
+
+    /// Updates the manager - handles responses of the backend, schedules reconnection if needed and
+    /// sends pending activity. It never blocks, all requests are executed by the backend thread. It
+    /// is called by the engine automatically.
+    pub fn update(&self, dt: f32) {
+        let mut guard = self.state.lock();
+        let state = &mut *guard;
+
+        state.update_timer -= dt;
+
+        let Some(worker) = state.worker.as_ref() else {
+            return;
+        };
+
+        let events = worker.receiver.try_iter().collect::<Vec<_>>();
+        for event in events {
+            state.handle_event(event);
+        }
+
+        if state.pending {
+            return;
+        }
+
+        let command = if !state.connected {
+            state.reconnect_timer -= dt;
+            if state.reconnect_timer > 0.0 {
+                return;
+            }
+            WorkerCommand::Connect
+        } else if state.dirty && state.update_timer <= 0.0 {
+            state.dirty = false;
+            state.update_timer = state.min_update_interval;
+            WorkerCommand::SetActivity(state.current.clone())
+        } else {
+            return;
+        };
+
+        if let Some(worker) = state.worker.as_ref() {
+            if worker.send(command) {
+                state.pending = true;
+            } else {
+                Log::err("Presence thread has stopped unexpectedly.");
+                state.worker = None;
+                state.connected = false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    #[derive(Default)]
+    struct MockBackend {
+        connected: bool,
+        updates: Arc<AtomicUsize>,
+    }
+
+    impl PresenceBackend for MockBackend {
+        fn connect(&mut self) -> Result<(), PresenceError> {
+            self.connected = true;
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            self.connected
+        }
+
+        fn set_activity(&mut self, _: Option<&PresenceActivity>) -> Result<(), PresenceError> {
+            self.updates.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn disconnect(&mut self) {
+            self.connected = false;
+        }
+    }
+
+    // Updates the manager until the condition is met, the backend works on another thread.
+    fn update_until(manager: &PresenceManager, condition: impl Fn() -> bool) {
+        for _ in 0..1000 {
+            manager.update(0.0);
+            if condition() {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        panic!("Presence backend did not respond in time.");
+    }
+
+    #[test]
+    fn test_rate_limiting() {
+        let updates = Arc::new(AtomicUsize::new(0));
+        let manager = PresenceManager::new();
+        manager.set_backend(MockBackend {
+            connected: false,
+            updates: updates.clone(),
+        });
+
+        manager.set_activity(PresenceActivity::new("Menu"));
+        manager.update(0.1);
+        update_until(&manager, || updates.load(Ordering::SeqCst) == 1);
+        assert!(manager.is_connected());
+
+        manager.set_activity(PresenceActivity::new("In level 1"));
+        manager.update(1.0);
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(updates.load(Ordering::SeqCst), 1);
+
+        manager.update(3.5);
+        update_until(&manager, || updates.load(Ordering::SeqCst) == 2);
+
+        // Same activity must not cause an update.
+        manager.set_activity(PresenceActivity::new("In level 1"));
+        manager.update(5.0);
+        std::thread::sleep(Duration::from_millis(20));
+        manager.update(0.0);
+        assert_eq!(updates.load(Ordering::SeqCst), 2);
+    }
+
+    struct BlockingBackend {
+        connected: bool,
+        release: Receiver<()>,
+    }
+
+    impl PresenceBackend for BlockingBackend {
+        fn connect(&mut self) -> Result<(), PresenceError> {
+            let _ = self.release.recv();
+            self.connected = true;
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            self.connected
+        }
+
+        fn set_activity(&mut self, _: Option<&PresenceActivity>) -> Result<(), PresenceError> {
+            Ok(())
+        }
+
+        fn disconnect(&mut self) {
+            self.connected = false;
+        }
+    }
+
+    #[test]
+    fn test_update_does_not_block() {
+        let (release, receiver) = mpsc::channel();
+        let manager = PresenceManager::new();
+        manager.set_backend(BlockingBackend {
+            connected: false,
+            release: receiver,
+        });
+
+        // The backend hangs in `connect`, but the manager must keep updating.
+        for _ in 0..10 {
+            manager.update(0.1);
+        }
+        assert!(!manager.is_connected());
+
+        release.send(()).unwrap();
+        update_until(&manager, || manager.is_connected());
+    }
+
+    #[test]
+    fn test_json_string_escaping() {
+        assert_eq!(json_string("a\"b\\c\n"), "\"a\\\"b\\\\c\\n\"");
+    }
+}
@@ -4,15 +4,20 @@ use crate::plugin::PluginContainer;
 use crate::{
     core::{
         pool::Handle,
-        task::{AsyncTask, AsyncTaskResult, TaskPool},
+        task::{AsyncTask, AsyncTaskResult, TaskPool, TaskResult},
         uuid::Uuid,
     },
     plugin::{Plugin, PluginContext},
     scene::{node::Node, Scene},
     script::{ScriptContext, ScriptTrait},
+    utils::lightmap::{CancellationToken, ProgressIndicator},
 };
 use fxhash::FxHashMap;
-use std::sync::Arc;
+use std::sync::{
+    atomic::{self, AtomicBool},
+    mpsc::{self, Receiver, Sender},
+    Arc,
+};
 
 pub(crate) type NodeTaskHandlerClosure = Box<
     dyn for<'a, 'b, 'c> Fn(
@@ -30,6 +35,9 @@ pub(crate) type PluginTaskHandler = Box<
     ),
 >;
 
+pub(crate) type EngineTaskHandler =
+    Box<dyn for<'a, 'b> Fn(Box<dyn AsyncTaskResult>, &mut PluginContext<'a, 'b>)>;
+
 pub(crate) struct NodeTaskHandler {
     pub(crate) scene_handle: Handle<Scene>,
     pub(crate) node_handle: Handle<Node>,
@@ -56,18 +64,83 @@ pub(crate) struct NodeTaskHandler {
 /// on a scene node basis - when a task is done, the "on-complete" closure will be provided with a
 /// wide context, allowing you to modify the caller's node state. See the docs for the respective
 /// methods for more info.
+///
+/// Long-running engine-level computations (navmesh baking, lightmap baking, LOD generation, etc.)
+/// should use [`TaskPoolHandler::spawn_engine_task`] - such tasks are able to report their progress
+/// and could be cancelled using [`TaskHandle`]. Unlike plugin tasks, engine tasks are handled even
+/// if plugins are disabled (for example, in the editor).
 pub struct TaskPoolHandler {
     task_pool: Arc<TaskPool>,
     plugin_task_handlers: FxHashMap<Uuid, PluginTaskHandler>,
     node_task_handlers: FxHashMap<Uuid, NodeTaskHandler>,
+    engine_task_handlers: FxHashMap<Uuid, EngineTaskHandler>,
+    engine_tasks: Vec<TaskHandle>,
+    engine_task_sender: Sender<TaskResult>,
+    engine_task_receiver: Receiver<TaskResult>,
+}
+
+/// A handle of a long-running engine task (navmesh baking, lightmap baking, LOD generation, etc.)
+/// spawned by [`TaskPoolHandler::spawn_engine_task`]. It could be used to show the progress of the
+/// task and to cancel it.
+#[derive(Clone)]
+pub struct TaskHandle {
+    id: Uuid,
+    name: String,
+    cancellation_token: CancellationToken,
+    progress_indicator: ProgressIndicator,
+    finished: Arc<AtomicBool>,
+}
+
+impl TaskHandle {
+    /// Returns unique id of the task.
+    #[inline]
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Returns human-readable name of the task.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the cancellation token, that is shared with the task itself.
+    #[inline]
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation_token
+    }
+
+    /// Returns the progress indicator, that is shared with the task itself.
+    #[inline]
+    pub fn progress_indicator(&self) -> &ProgressIndicator {
+        &self.progress_indicator
+    }
+
+    /// Requests cancellation of the task. The "on-complete" closure of the cancelled task will not be
+    /// called.
+    #[inline]
+    pub fn cancel(&self) {
+        self.cancellation_token.cancel()
+    }
+
+    /// Returns `true` if the task has finished its work (either successfully or by cancellation).
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(atomic::Ordering::Acquire)
+    }
 }
 
 impl TaskPoolHandler {
     pub(crate) fn new(task_pool: Arc<TaskPool>) -> Self {
+        let (engine_task_sender, engine_task_receiver) = mpsc::channel();
         Self {
             task_pool,
             plugin_task_handlers: Default::default(),
             node_task_handlers: Default::default(),
+            engine_task_handlers: Default::default(),
+            engine_tasks: Default::default(),
+            engine_task_sender,
+            engine_task_receiver,
         }
     }
 
@@ -219,6 +292,111 @@ impl TaskPoolHandler {
         );
     }
 
+    /// Spawns a long-running engine task (navmesh baking, lightmap baking, LOD generation, etc.) that
+    /// runs on a background thread, reports its progress and could be cancelled using the returned
+    /// [`TaskHandle`]. The task is created by the `task` closure which receives a [`CancellationToken`]
+    /// and a [`ProgressIndicator`] - the task should use them to check whether it was cancelled and to
+    /// report its progress. When the task is done, the `on_complete` closure is called on the main
+    /// thread at the beginning of the next update iteration. The closure is not called if the task
+    /// was cancelled.
+    ///
+    /// ## Example
+    ///
+    /// ```rust ,no_run
+    /// # use fyrox_impl::plugin::PluginContext;
+    /// fn bake(context: &mut PluginContext) {
+    ///     let handle = context.task_pool.spawn_engine_task(
+    ///         "Heavy Computation",
+    ///         |cancellation_token, progress_indicator| async move {
+    ///             let mut sum = 0u64;
+    ///             for i in 0..1000u64 {
+    ///                 if cancellation_token.is_cancelled() {
+    ///                     break;
+    ///                 }
+    ///                 sum += i;
+    ///                 progress_indicator.set_progress(i as u32, 1000);
+    ///             }
+    ///             sum
+    ///         },
+    ///         |sum, _context| println!("The sum is {sum}"),
+    ///     );
+    ///
+    ///     println!(
+    ///         "{} - {}%",
+    ///         handle.name(),
+    ///         handle.progress_indicator().progress_percent()
+    ///     );
+    /// }
+    /// ```
+    #[inline]
+    pub fn spawn_engine_task<N, F, Fut, T, C>(
+        &mut self,
+        name: N,
+        task: F,
+        on_complete: C,
+    ) -> TaskHandle
+    where
+        N: Into<String>,
+        F: FnOnce(CancellationToken, ProgressIndicator) -> Fut,
+        Fut: AsyncTask<T>,
+        T: AsyncTaskResult,
+        for<'a, 'b> C: Fn(T, &mut PluginContext<'a, 'b>) + 'static,
+    {
+        let handle = TaskHandle {
+            id: Uuid::new_v4(),
+            name: name.into(),
+            cancellation_token: CancellationToken::new(),
+            progress_indicator: ProgressIndicator::new(),
+            finished: Default::default(),
+        };
+
+        let future = task(
+            handle.cancellation_token.clone(),
+            handle.progress_indicator.clone(),
+        );
+        let id = handle.id;
+        let finished = handle.finished.clone();
+        let sender = self.engine_task_sender.clone();
+        self.task_pool.spawn_task(async move {
+            let result = future.await;
+            finished.store(true, atomic::Ordering::Release);
+            // The handler could be dropped already, nobody is interested in the result then.
+            let _ = sender.send(TaskResult {
+                id,
+                payload: Box::new(result),
+            });
+        });
+
+        let cancellation_token = handle.cancellation_token.clone();
+        self.engine_task_handlers.insert(
+            id,
+            Box::new(move |result, context| {
+                if cancellation_token.is_cancelled() {
+                    return;
+                }
+                let typed = result.downcast::<T>().expect("Types must match!");
+                on_complete(*typed, context)
+            }),
+        );
+        self.engine_tasks.push(handle.clone());
+        handle
+    }
+
+    /// Returns handles of all engine tasks that are still running. It could be used to show progress
+    /// of the tasks.
+    #[inline]
+    pub fn engine_tasks(&self) -> &[TaskHandle] {
+        &self.engine_tasks
+    }
+
+    /// Requests cancellation of all running engine tasks.
+    #[inline]
+    pub fn cancel_engine_tasks(&self) {
+        for task in self.engine_tasks.iter() {
+            task.cancel();
+        }
+    }
+
     /// Returns a reference to the underlying, low level task pool, that could be used to for special
     /// cases.
     #[inline]
@@ -231,8 +409,61 @@ impl TaskPoolHandler {
         self.plugin_task_handlers.remove(&id)
     }
 
+    #[inline]
+    pub(crate) fn next_engine_task_result(&self) -> Option<TaskResult> {
+        self.engine_task_receiver.try_recv().ok()
+    }
+
+    #[inline]
+    pub(crate) fn pop_engine_task_handler(&mut self, id: Uuid) -> Option<EngineTaskHandler> {
+        self.engine_tasks.retain(|task| task.id != id);
+        self.engine_task_handlers.remove(&id)
+    }
+
     #[inline]
     pub(crate) fn pop_node_task_handler(&mut self, id: Uuid) -> Option<NodeTaskHandler> {
         self.node_task_handlers.remove(&id)
     }
 }
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod test {
+    use crate::{core::task::TaskPool, engine::task::TaskPoolHandler};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_engine_task() {
+        let mut handler = TaskPoolHandler::new(Arc::new(TaskPool::new()));
+        let handle = handler.spawn_engine_task(
+            "Sum",
+            |cancellation_token, progress_indicator| async move {
+                let mut sum = 0u32;
+                for i in 0..10 {
+                    if cancellation_token.is_cancelled() {
+                        break;
+                    }
+                    sum += i;
+                    progress_indicator.set_progress(i + 1, 10);
+                }
+                sum
+            },
+            |_, _| {},
+        );
+        assert_eq!(handler.engine_tasks().len(), 1);
+
+        let result = loop {
+            if let Some(result) = handler.next_engine_task_result() {
+                break result;
+            }
+            std::thread::yield_now();
+        };
+        assert_eq!(result.id, handle.id());
+        assert_eq!(*result.payload.downcast::<u32>().unwrap(), 45);
+        assert!(handle.is_finished());
+        assert_eq!(handle.progress_indicator().progress_percent(), 100);
+
+        assert!(handler.pop_engine_task_handler(handle.id()).is_some());
+        assert!(handler.engine_tasks().is_empty());
+    }
+}
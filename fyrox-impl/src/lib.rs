@@ -11,6 +11,7 @@
 
 pub mod engine;
 pub mod material;
+pub mod platform;
 pub mod plugin;
 pub mod renderer;
 pub mod resource;
@@ -0,0 +1,34 @@
+//! Material graph loader.
+
+use crate::{
+    asset::{
+        io::ResourceIo,
+        loader::{BoxedLoaderFuture, LoaderPayload, ResourceLoader},
+    },
+    core::{uuid::Uuid, TypeUuidProvider},
+    material::graph::MaterialGraph,
+};
+use fyrox_resource::state::LoadError;
+use std::{path::PathBuf, sync::Arc};
+
+/// Default implementation for material graph loading.
+pub struct MaterialGraphLoader;
+
+impl ResourceLoader for MaterialGraphLoader {
+    fn extensions(&self) -> &[&str] {
+        &["material_graph"]
+    }
+
+    fn data_type_uuid(&self) -> Uuid {
+        MaterialGraph::type_uuid()
+    }
+
+    fn load(&self, path: PathBuf, io: Arc<dyn ResourceIo>) -> BoxedLoaderFuture {
+        Box::pin(async move {
+            let graph = MaterialGraph::from_file(&path, io.as_ref())
+                .await
+                .map_err(LoadError::new)?;
+            Ok(LoaderPayload::new(graph))
+        })
+    }
+}
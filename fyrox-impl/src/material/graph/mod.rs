@@ -0,0 +1,1290 @@
+//! Material graph is a node-based description of a material surface, that is compiled into a
+//! regular [`Shader`]. See [`MaterialGraphDefinition`] docs for more info.
+//!
+//! # Structure
+//!
+//! Material graph is stored in RON format and has the following structure:
+//!
+//! ```ron
+//! (
+//!     name: "RustyMetal",
+//!
+//!     // A set of nodes. Nodes reference their inputs by an index in this array.
+//!     nodes: [
+//!         // 0: First texture coordinates of a vertex.
+//!         TexCoord,
+//!         // 1: A property, that could be changed in a material (a uniform in the shader).
+//!         Property(name: "tiling", value: Vector2((4.0, 4.0))),
+//!         // 2: Tiled texture coordinates.
+//!         Binary(op: Multiply, a: 0, b: 1),
+//!         // 3: Texture property, sampled using the tiled texture coordinates.
+//!         Texture(name: "albedoTexture", fallback: White, uv: Some(2)),
+//!         // 4: Normal map, unpacked from [0; 1] range to [-1; 1] range.
+//!         Texture(name: "normalTexture", fallback: Normal, uv: Some(2)),
+//!         Unary(op: UnpackNormal, input: 4),
+//!         // 6: Alpha channel of the albedo texture.
+//!         Swizzle(input: 3, components: "a"),
+//!         // 7: Constant value.
+//!         Constant(Float(0.8)),
+//!     ],
+//!
+//!     // Connections of the nodes to the inputs of the standard PBR lighting model. Every
+//!     // output is optional and has a sensible default value.
+//!     output: (
+//!         base_color: Some(3),
+//!         normal: Some(5),
+//!         metallic: Some(7),
+//!         opacity: Some(6),
+//!     ),
+//!
+//!     // Fragments with opacity less than this value are discarded in deferred and shadow passes.
+//!     alpha_cutoff: 0.5,
+//! )
+//! ```
+//!
+//! # Compilation
+//!
+//! Material graph is compiled into a shader with the same set of render passes as the standard
+//! shader, so materials that use the shader are rendered in the deferred, forward, transparency and
+//! shadow passes just like materials with the standard shader. Every `Property` and `Texture` node
+//! becomes a shader property, that could be set in a [`crate::material::Material`] as usual.
+//!
+//! Values of nodes have one of the types defined in [`ValueType`]. Operands of math nodes must
+//! have the same type, or one of them must be a scalar. Values connected to other inputs (texture
+//! coordinates, material outputs, etc.) are converted implicitly - extra components are dropped,
+//! missing components are filled with zeros (or one for `w` component).
+
+use crate::{
+    asset::{
+        io::ResourceIo, untyped::ResourceKind, Resource, ResourceData, MATERIAL_GRAPH_RESOURCE_UUID,
+    },
+    core::{
+        algebra::{Vector2, Vector3, Vector4},
+        color::Color,
+        io::FileLoadError,
+        reflect::prelude::*,
+        uuid::Uuid,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    material::shader::{
+        PropertyDefinition, PropertyKind, RenderPassDefinition, SamplerFallback, Shader,
+        ShaderDefinition, ShaderResource, STANDARD_SHADER_SRC,
+    },
+};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use std::{
+    any::Any,
+    error::Error,
+    fmt::{Display, Formatter},
+    fs::File,
+    io::{Cursor, Write},
+    path::Path,
+};
+
+pub mod loader;
+
+/// An index of a node in [`MaterialGraphDefinition::nodes`].
+pub type MaterialNodeId = u32;
+
+/// A type of a value produced by a node of a material graph.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ValueType {
+    /// A single real number.
+    Float,
+    /// A vector with two components.
+    Vector2,
+    /// A vector with three components.
+    Vector3,
+    /// A vector with four components.
+    Vector4,
+}
+
+impl ValueType {
+    fn from_dimension(dimension: usize) -> Option<Self> {
+        match dimension {
+            1 => Some(Self::Float),
+            2 => Some(Self::Vector2),
+            3 => Some(Self::Vector3),
+            4 => Some(Self::Vector4),
+            _ => None,
+        }
+    }
+
+    /// Returns an amount of components of the type.
+    pub fn dimension(self) -> usize {
+        match self {
+            Self::Float => 1,
+            Self::Vector2 => 2,
+            Self::Vector3 => 3,
+            Self::Vector4 => 4,
+        }
+    }
+
+    fn glsl_name(self) -> &'static str {
+        match self {
+            Self::Float => "float",
+            Self::Vector2 => "vec2",
+            Self::Vector3 => "vec3",
+            Self::Vector4 => "vec4",
+        }
+    }
+}
+
+/// A value of constants and properties of a material graph.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Reflect, Visit)]
+pub enum MaterialValue {
+    /// A single real number.
+    Float(f32),
+    /// Two-dimensional vector.
+    Vector2(Vector2<f32>),
+    /// Three-dimensional vector.
+    Vector3(Vector3<f32>),
+    /// Four-dimensional vector.
+    Vector4(Vector4<f32>),
+    /// A color in sRGB color space. It is converted to linear color space and has
+    /// [`ValueType::Vector4`] type in the graph.
+    Color {
+        /// Red channel of the color.
+        r: u8,
+        /// Green channel of the color.
+        g: u8,
+        /// Blue channel of the color.
+        b: u8,
+        /// Alpha channel of the color.
+        a: u8,
+    },
+}
+
+impl Default for MaterialValue {
+    fn default() -> Self {
+        Self::Float(0.0)
+    }
+}
+
+impl MaterialValue {
+    /// Returns a type of the value in the graph.
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Self::Float(_) => ValueType::Float,
+            Self::Vector2(_) => ValueType::Vector2,
+            Self::Vector3(_) => ValueType::Vector3,
+            Self::Vector4(_) | Self::Color { .. } => ValueType::Vector4,
+        }
+    }
+
+    fn to_glsl(&self) -> String {
+        match self {
+            Self::Float(v) => float_literal(*v),
+            Self::Vector2(v) => vector_literal(v.as_slice()),
+            Self::Vector3(v) => vector_literal(v.as_slice()),
+            Self::Vector4(v) => vector_literal(v.as_slice()),
+            Self::Color { r, g, b, a } => vector_literal(
+                Color::from_rgba(*r, *g, *b, *a)
+                    .srgb_to_linear_f32()
+                    .as_slice(),
+            ),
+        }
+    }
+
+    fn to_property_kind(&self) -> PropertyKind {
+        match self {
+            Self::Float(v) => PropertyKind::Float(*v),
+            Self::Vector2(v) => PropertyKind::Vector2(*v),
+            Self::Vector3(v) => PropertyKind::Vector3(*v),
+            Self::Vector4(v) => PropertyKind::Vector4(*v),
+            Self::Color { r, g, b, a } => PropertyKind::Color {
+                r: *r,
+                g: *g,
+                b: *b,
+                a: *a,
+            },
+        }
+    }
+}
+
+/// An operation with a single operand.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Default, Reflect, Visit)]
+pub enum UnaryOp {
+    /// `-x`
+    #[default]
+    Negate,
+    /// `1 - x`
+    OneMinus,
+    /// Absolute value of every component.
+    Abs,
+    /// Rounds every component down.
+    Floor,
+    /// Fractional part of every component.
+    Fract,
+    /// Square root of every component.
+    Sqrt,
+    /// Sine of every component.
+    Sin,
+    /// Cosine of every component.
+    Cos,
+    /// Clamps every component to `[0; 1]` range.
+    Saturate,
+    /// Normalizes a vector.
+    Normalize,
+    /// Length of a vector, the result is a scalar.
+    Length,
+    /// Converts a value sampled from a normal map from `[0; 1]` range to a normalized vector with
+    /// components in `[-1; 1]` range. The result is a three-dimensional vector.
+    UnpackNormal,
+}
+
+/// An operation with two operands.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Default, Reflect, Visit)]
+pub enum BinaryOp {
+    /// `a + b`
+    #[default]
+    Add,
+    /// `a - b`
+    Subtract,
+    /// `a * b` (per-component)
+    Multiply,
+    /// `a / b` (per-component)
+    Divide,
+    /// Per-component minimum.
+    Min,
+    /// Per-component maximum.
+    Max,
+    /// `a` raised to the power of `b` (per-component).
+    Power,
+    /// `0` if `b < a`, `1` otherwise (per-component).
+    Step,
+    /// Dot product of the vectors, the result is a scalar.
+    Dot,
+}
+
+/// A node of a material graph.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default, Reflect, Visit)]
+pub enum MaterialNode {
+    /// First texture coordinates of a vertex ([`ValueType::Vector2`]).
+    #[default]
+    TexCoord,
+    /// Second texture coordinates of a vertex, usually used for lightmaps ([`ValueType::Vector2`]).
+    SecondTexCoord,
+    /// Position of a fragment in world space ([`ValueType::Vector3`]).
+    WorldPosition,
+    /// Interpolated normal of a vertex in world space ([`ValueType::Vector3`]).
+    WorldNormal,
+    /// Position of the camera in world space ([`ValueType::Vector3`]).
+    CameraPosition,
+    /// A constant value.
+    Constant(MaterialValue),
+    /// A value, that could be changed in a material. The name must be unique among the properties
+    /// and textures of the graph, unless the nodes define exactly the same property.
+    Property {
+        /// A name of the property.
+        name: String,
+        /// Default value of the property.
+        value: MaterialValue,
+    },
+    /// Samples a texture, that could be changed in a material ([`ValueType::Vector4`]).
+    Texture {
+        /// A name of the texture property.
+        name: String,
+        /// A value, that will be used if the texture is not set. See [`SamplerFallback`].
+        fallback: SamplerFallback,
+        /// Texture coordinates. First texture coordinates of a vertex are used if not set.
+        #[serde(default)]
+        uv: Option<MaterialNodeId>,
+    },
+    /// An operation with a single operand.
+    Unary {
+        /// An operation.
+        op: UnaryOp,
+        /// An operand.
+        input: MaterialNodeId,
+    },
+    /// An operation with two operands.
+    Binary {
+        /// An operation.
+        op: BinaryOp,
+        /// First operand.
+        a: MaterialNodeId,
+        /// Second operand.
+        b: MaterialNodeId,
+    },
+    /// Linear interpolation between `a` and `b`.
+    Mix {
+        /// First value.
+        a: MaterialNodeId,
+        /// Second value.
+        b: MaterialNodeId,
+        /// Interpolation factor, either a scalar or a value of the same type as `a` and `b`.
+        factor: MaterialNodeId,
+    },
+    /// Selects components of a vector, for example `"xy"`, `"a"`, `"bgr"`.
+    Swizzle {
+        /// Source vector.
+        input: MaterialNodeId,
+        /// Names of components (`xyzw` or `rgba`), from one to four.
+        components: String,
+    },
+    /// Concatenates components of the inputs into a vector, for example a vector with two
+    /// components and a scalar are combined into a vector with three components.
+    Combine {
+        /// A set of values, that will be concatenated.
+        inputs: Vec<MaterialNodeId>,
+    },
+}
+
+/// Connections of the nodes to the inputs of the standard PBR lighting model.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default, Reflect, Visit)]
+pub struct MaterialGraphOutput {
+    /// Albedo of the surface ([`ValueType::Vector3`]). White by default.
+    #[serde(default)]
+    pub base_color: Option<MaterialNodeId>,
+    /// Opacity of the surface ([`ValueType::Float`]). One by default.
+    #[serde(default)]
+    pub opacity: Option<MaterialNodeId>,
+    /// Normal of the surface in tangent space ([`ValueType::Vector3`]), for example an unpacked
+    /// normal map. `(0, 0, 1)` by default.
+    #[serde(default)]
+    pub normal: Option<MaterialNodeId>,
+    /// Metallic factor of the surface ([`ValueType::Float`]). Zero by default.
+    #[serde(default)]
+    pub metallic: Option<MaterialNodeId>,
+    /// Roughness of the surface ([`ValueType::Float`]). One by default.
+    #[serde(default)]
+    pub roughness: Option<MaterialNodeId>,
+    /// Ambient occlusion factor of the surface ([`ValueType::Float`]). One by default.
+    #[serde(default)]
+    pub ambient_occlusion: Option<MaterialNodeId>,
+    /// Emitted light ([`ValueType::Vector3`]). Black by default.
+    #[serde(default)]
+    pub emission: Option<MaterialNodeId>,
+}
+
+fn default_alpha_cutoff() -> f32 {
+    0.5
+}
+
+/// A definition of a material graph. See the module docs for more info.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Reflect, Visit)]
+pub struct MaterialGraphDefinition {
+    /// A name of the graph. It is used as a name of the compiled shader.
+    pub name: String,
+    /// A set of nodes of the graph.
+    pub nodes: Vec<MaterialNode>,
+    /// Connections of the nodes to the inputs of the lighting model.
+    pub output: MaterialGraphOutput,
+    /// Fragments with opacity less than this value are discarded in the deferred and shadow
+    /// passes.
+    #[serde(default = "default_alpha_cutoff")]
+    #[visit(optional)]
+    pub alpha_cutoff: f32,
+}
+
+impl Default for MaterialGraphDefinition {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            nodes: Default::default(),
+            output: Default::default(),
+            alpha_cutoff: default_alpha_cutoff(),
+        }
+    }
+}
+
+impl MaterialGraphDefinition {
+    fn from_buf(buf: Vec<u8>) -> Result<Self, MaterialGraphError> {
+        Ok(ron::de::from_reader(Cursor::new(buf))?)
+    }
+
+    fn from_str(str: &str) -> Result<Self, MaterialGraphError> {
+        Ok(ron::de::from_str(str)?)
+    }
+
+    /// Compiles the graph into a shader definition with the same set of render passes as the
+    /// standard shader has.
+    pub fn compile(&self) -> Result<ShaderDefinition, MaterialGraphError> {
+        let mut compiler = Compiler {
+            definition: self,
+            states: vec![NodeState::Unvisited; self.nodes.len()],
+            body: String::new(),
+            uniforms: String::new(),
+            properties: vec![
+                PropertyDefinition {
+                    name: "lightmapTexture".to_string(),
+                    kind: PropertyKind::Sampler {
+                        default: None,
+                        fallback: SamplerFallback::Black,
+                    },
+                },
+                PropertyDefinition {
+                    name: "layerIndex".to_string(),
+                    kind: PropertyKind::UInt(0),
+                },
+            ],
+        };
+
+        let output = &self.output;
+        let outputs = [
+            (
+                "baseColor",
+                output.base_color,
+                ValueType::Vector3,
+                "vec3(1.0)",
+            ),
+            ("opacity", output.opacity, ValueType::Float, "1.0"),
+            (
+                "normal",
+                output.normal,
+                ValueType::Vector3,
+                "vec3(0.0, 0.0, 1.0)",
+            ),
+            ("metallic", output.metallic, ValueType::Float, "0.0"),
+            ("roughness", output.roughness, ValueType::Float, "1.0"),
+            (
+                "ambientOcclusion",
+                output.ambient_occlusion,
+                ValueType::Float,
+                "1.0",
+            ),
+            ("emission", output.emission, ValueType::Vector3, "vec3(0.0)"),
+        ];
+        let mut assignments = String::new();
+        for (name, node, value_type, default) in outputs {
+            let value = match node {
+                Some(node) => compiler.input(node, value_type)?,
+                None => default.to_string(),
+            };
+            assignments += &format!("    mg_output.{name} = {value};\n");
+        }
+
+        let common = format!(
+            r#"
+struct TMaterialGraphOutput
+{{
+    vec3 baseColor;
+    float opacity;
+    vec3 normal;
+    float metallic;
+    float roughness;
+    float ambientOcclusion;
+    vec3 emission;
+}};
+
+uniform sampler2D lightmapTexture;
+uniform uint layerIndex;
+{uniforms}
+uniform vec3 fyrox_cameraPosition;
+
+in vec3 position;
+in vec3 normal;
+in vec2 texCoord;
+in vec3 tangent;
+in vec3 binormal;
+in vec2 secondTexCoord;
+
+TMaterialGraphOutput EvaluateMaterialGraph()
+{{
+{body}
+    TMaterialGraphOutput mg_output;
+{assignments}
+    return mg_output;
+}}
+"#,
+            uniforms = compiler.uniforms,
+            body = compiler.body,
+        );
+
+        let standard = Shader::from_string(STANDARD_SHADER_SRC)
+            .expect("The standard shader must be valid!")
+            .definition;
+        let vertex_shader = standard
+            .passes
+            .iter()
+            .find(|pass| pass.name == "GBuffer")
+            .map(|pass| pass.vertex_shader.clone())
+            .unwrap_or_default();
+
+        let alpha_cutoff = float_literal(self.alpha_cutoff);
+        let passes = standard
+            .passes
+            .into_iter()
+            .filter_map(|pass| {
+                let main = pass_main(&pass.name, &alpha_cutoff)?;
+                Some(RenderPassDefinition {
+                    name: pass.name,
+                    draw_parameters: pass.draw_parameters,
+                    vertex_shader: vertex_shader.clone(),
+                    fragment_shader: format!("{common}{main}"),
+                    ..Default::default()
+                })
+            })
+            .collect();
+
+        Ok(ShaderDefinition {
+            name: self.name.clone(),
+            passes,
+            properties: compiler.properties,
+            features: Default::default(),
+        })
+    }
+}
+
+fn pass_main(pass_name: &str, alpha_cutoff: &str) -> Option<String> {
+    let main = match pass_name {
+        "GBuffer" => format!(
+            r#"
+layout(location = 0) out vec4 outColor;
+layout(location = 1) out vec4 outNormal;
+layout(location = 2) out vec4 outAmbient;
+layout(location = 3) out vec4 outMaterial;
+layout(location = 4) out uint outDecalMask;
+
+uniform vec4 fyrox_weather;
+
+void main()
+{{
+    TMaterialGraphOutput m = EvaluateMaterialGraph();
+    if (m.opacity < {alpha_cutoff}) {{
+        discard;
+    }}
+
+    mat3 tangentSpace = mat3(tangent, binormal, normal);
+    vec3 worldNormal = normalize(tangentSpace * m.normal);
+    outNormal = vec4(worldNormal * 0.5 + 0.5, 1.0);
+
+    outColor = vec4(m.baseColor, 1.0);
+    float roughness = m.roughness;
+    S_ApplyWeather(outColor.rgb, roughness, worldNormal, fyrox_weather);
+
+    outMaterial = vec4(m.metallic, roughness, m.ambientOcclusion, 1.0);
+    outAmbient = vec4(m.emission + texture(lightmapTexture, secondTexCoord).rgb, 1.0);
+    outDecalMask = layerIndex;
+}}
+"#
+        ),
+        "Forward" => r#"
+out vec4 FragColor;
+
+void main()
+{
+    TMaterialGraphOutput m = EvaluateMaterialGraph();
+    FragColor = vec4(m.baseColor, m.opacity);
+}
+"#
+        .to_string(),
+        "OrderIndependentTransparency" => r#"
+layout(location = 0) out vec4 outAccumulation;
+layout(location = 1) out float outWeight;
+
+void main()
+{
+    TMaterialGraphOutput m = EvaluateMaterialGraph();
+    S_WriteWeightedBlended(vec4(m.baseColor, m.opacity), gl_FragCoord.z, outAccumulation, outWeight);
+}
+"#
+        .to_string(),
+        "DirectionalShadow" | "SpotShadow" => format!(
+            r#"
+void main()
+{{
+    if (EvaluateMaterialGraph().opacity < {alpha_cutoff}) discard;
+}}
+"#
+        ),
+        "PointShadow" => format!(
+            r#"
+uniform vec3 fyrox_lightPosition;
+
+layout(location = 0) out float depth;
+
+void main()
+{{
+    if (EvaluateMaterialGraph().opacity < {alpha_cutoff}) discard;
+    depth = length(fyrox_lightPosition - position);
+}}
+"#
+        ),
+        _ => return None,
+    };
+    Some(main)
+}
+
+fn float_literal(value: f32) -> String {
+    // Debug formatting always keeps the fractional part (`1.0` instead of `1`), which is required
+    // for float literals in GLSL.
+    format!("{value:?}")
+}
+
+fn vector_literal(components: &[f32]) -> String {
+    let args = components
+        .iter()
+        .map(|v| float_literal(*v))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("vec{}({args})", components.len())
+}
+
+/// Converts a value of one type to another. Extra components are dropped, missing components are
+/// filled with zeros (or one for `w` component), scalars are splatted.
+fn convert(expr: &str, from: ValueType, to: ValueType) -> String {
+    use ValueType::*;
+    match (from, to) {
+        _ if from == to => expr.to_string(),
+        (Float, _) => format!("{}({expr})", to.glsl_name()),
+        (_, Float) => format!("{expr}.x"),
+        (Vector3 | Vector4, Vector2) => format!("{expr}.xy"),
+        (Vector4, Vector3) => format!("{expr}.xyz"),
+        (Vector2, Vector3) => format!("vec3({expr}, 0.0)"),
+        (Vector2, Vector4) => format!("vec4({expr}, 0.0, 1.0)"),
+        (Vector3, Vector4) => format!("vec4({expr}, 1.0)"),
+        _ => expr.to_string(),
+    }
+}
+
+const RESERVED_NAMES: &[&str] = &[
+    "main",
+    "position",
+    "normal",
+    "texCoord",
+    "tangent",
+    "binormal",
+    "secondTexCoord",
+    "lightmapTexture",
+    "layerIndex",
+    "depth",
+    "outColor",
+    "outNormal",
+    "outAmbient",
+    "outMaterial",
+    "outDecalMask",
+    "outAccumulation",
+    "outWeight",
+    "FragColor",
+    "TMaterialGraphOutput",
+    "EvaluateMaterialGraph",
+];
+
+const RESERVED_PREFIXES: &[&str] = &["fyrox_", "gl_", "mg_", "S_"];
+
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !RESERVED_NAMES.contains(&name)
+        && !RESERVED_PREFIXES
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+}
+
+#[derive(Copy, Clone)]
+enum NodeState {
+    Unvisited,
+    InProgress,
+    Done(ValueType),
+}
+
+struct Compiler<'a> {
+    definition: &'a MaterialGraphDefinition,
+    states: Vec<NodeState>,
+    body: String,
+    uniforms: String,
+    properties: Vec<PropertyDefinition>,
+}
+
+fn variable(id: MaterialNodeId) -> String {
+    format!("mg_{id}")
+}
+
+impl<'a> Compiler<'a> {
+    fn input(
+        &mut self,
+        id: MaterialNodeId,
+        value_type: ValueType,
+    ) -> Result<String, MaterialGraphError> {
+        let input_type = self.node(id)?;
+        Ok(convert(&variable(id), input_type, value_type))
+    }
+
+    // Returns a common type of two operands. A scalar is promoted to the type of the other operand.
+    fn unify(
+        &mut self,
+        node: MaterialNodeId,
+        a: MaterialNodeId,
+        b: MaterialNodeId,
+    ) -> Result<ValueType, MaterialGraphError> {
+        let a_type = self.node(a)?;
+        let b_type = self.node(b)?;
+        if a_type == b_type || b_type == ValueType::Float {
+            Ok(a_type)
+        } else if a_type == ValueType::Float {
+            Ok(b_type)
+        } else {
+            Err(MaterialGraphError::TypeMismatch {
+                node,
+                left: a_type,
+                right: b_type,
+            })
+        }
+    }
+
+    fn add_property(
+        &mut self,
+        node: MaterialNodeId,
+        name: &str,
+        kind: PropertyKind,
+        glsl_type: &str,
+    ) -> Result<(), MaterialGraphError> {
+        if !is_valid_name(name) {
+            return Err(MaterialGraphError::InvalidName {
+                node,
+                name: name.to_string(),
+            });
+        }
+        if let Some(existing) = self.properties.iter().find(|p| p.name == name) {
+            return if existing.kind == kind {
+                Ok(())
+            } else {
+                Err(MaterialGraphError::NameConflict {
+                    node,
+                    name: name.to_string(),
+                })
+            };
+        }
+        self.uniforms += &format!("uniform {glsl_type} {name};\n");
+        self.properties.push(PropertyDefinition {
+            name: name.to_string(),
+            kind,
+        });
+        Ok(())
+    }
+
+    fn node(&mut self, id: MaterialNodeId) -> Result<ValueType, MaterialGraphError> {
+        let definition = self.definition;
+        let node = definition
+            .nodes
+            .get(id as usize)
+            .ok_or(MaterialGraphError::InvalidReference { reference: id })?;
+
+        match self.states[id as usize] {
+            NodeState::Done(value_type) => return Ok(value_type),
+            NodeState::InProgress => return Err(MaterialGraphError::Cycle { node: id }),
+            NodeState::Unvisited => (),
+        }
+        self.states[id as usize] = NodeState::InProgress;
+
+        let (value_type, expr) = match node {
+            MaterialNode::TexCoord => (ValueType::Vector2, "texCoord".to_string()),
+            MaterialNode::SecondTexCoord => (ValueType::Vector2, "secondTexCoord".to_string()),
+            MaterialNode::WorldPosition => (ValueType::Vector3, "position".to_string()),
+            MaterialNode::WorldNormal => (ValueType::Vector3, "normalize(normal)".to_string()),
+            MaterialNode::CameraPosition => {
+                (ValueType::Vector3, "fyrox_cameraPosition".to_string())
+            }
+            MaterialNode::Constant(value) => (value.value_type(), value.to_glsl()),
+            MaterialNode::Property { name, value } => {
+                let value_type = value.value_type();
+                self.add_property(id, name, value.to_property_kind(), value_type.glsl_name())?;
+                (value_type, name.clone())
+            }
+            MaterialNode::Texture { name, fallback, uv } => {
+                self.add_property(
+                    id,
+                    name,
+                    PropertyKind::Sampler {
+                        default: None,
+                        fallback: *fallback,
+                    },
+                    "sampler2D",
+                )?;
+                let uv = match uv {
+                    Some(uv) => self.input(*uv, ValueType::Vector2)?,
+                    None => "texCoord".to_string(),
+                };
+                (ValueType::Vector4, format!("texture({name}, {uv})"))
+            }
+            MaterialNode::Unary { op, input } => {
+                let input_type = self.node(*input)?;
+                let x = variable(*input);
+                match op {
+                    UnaryOp::Negate => (input_type, format!("(-{x})")),
+                    UnaryOp::OneMinus => (input_type, format!("(1.0 - {x})")),
+                    UnaryOp::Abs => (input_type, format!("abs({x})")),
+                    UnaryOp::Floor => (input_type, format!("floor({x})")),
+                    UnaryOp::Fract => (input_type, format!("fract({x})")),
+                    UnaryOp::Sqrt => (input_type, format!("sqrt({x})")),
+                    UnaryOp::Sin => (input_type, format!("sin({x})")),
+                    UnaryOp::Cos => (input_type, format!("cos({x})")),
+                    UnaryOp::Saturate => (input_type, format!("clamp({x}, 0.0, 1.0)")),
+                    UnaryOp::Normalize => (input_type, format!("normalize({x})")),
+                    UnaryOp::Length => (ValueType::Float, format!("length({x})")),
+                    UnaryOp::UnpackNormal => (
+                        ValueType::Vector3,
+                        format!(
+                            "normalize({} * 2.0 - 1.0)",
+                            convert(&x, input_type, ValueType::Vector3)
+                        ),
+                    ),
+                }
+            }
+            MaterialNode::Binary { op, a, b } => {
+                let value_type = self.unify(id, *a, *b)?;
+                let a = self.input(*a, value_type)?;
+                let b = self.input(*b, value_type)?;
+                match op {
+                    BinaryOp::Add => (value_type, format!("({a} + {b})")),
+                    BinaryOp::Subtract => (value_type, format!("({a} - {b})")),
+                    BinaryOp::Multiply => (value_type, format!("({a} * {b})")),
+                    BinaryOp::Divide => (value_type, format!("({a} / {b})")),
+                    BinaryOp::Min => (value_type, format!("min({a}, {b})")),
+                    BinaryOp::Max => (value_type, format!("max({a}, {b})")),
+                    BinaryOp::Power => (value_type, format!("pow({a}, {b})")),
+                    BinaryOp::Step => (value_type, format!("step({a}, {b})")),
+                    BinaryOp::Dot => (ValueType::Float, format!("dot({a}, {b})")),
+                }
+            }
+            MaterialNode::Mix { a, b, factor } => {
+                let value_type = self.unify(id, *a, *b)?;
+                let factor_type = self.node(*factor)?;
+                if factor_type != ValueType::Float && factor_type != value_type {
+                    return Err(MaterialGraphError::TypeMismatch {
+                        node: id,
+                        left: value_type,
+                        right: factor_type,
+                    });
+                }
+                let a = self.input(*a, value_type)?;
+                let b = self.input(*b, value_type)?;
+                (value_type, format!("mix({a}, {b}, {})", variable(*factor)))
+            }
+            MaterialNode::Swizzle { input, components } => {
+                let input_type = self.node(*input)?;
+                let swizzle = components
+                    .chars()
+                    .map(|c| {
+                        "xyzw"
+                            .find(c)
+                            .or_else(|| "rgba".find(c))
+                            .filter(|index| *index < input_type.dimension())
+                            .map(|index| ['x', 'y', 'z', 'w'][index])
+                    })
+                    .collect::<Option<String>>();
+                let value_type = ValueType::from_dimension(components.chars().count());
+                let (Some(swizzle), Some(value_type)) = (swizzle, value_type) else {
+                    return Err(MaterialGraphError::InvalidSwizzle {
+                        node: id,
+                        components: components.clone(),
+                    });
+                };
+                let x = variable(*input);
+                if input_type == ValueType::Float {
+                    // Scalars cannot be swizzled in GLSL ES, splat them instead.
+                    (value_type, convert(&x, input_type, value_type))
+                } else {
+                    (value_type, format!("{x}.{swizzle}"))
+                }
+            }
+            MaterialNode::Combine { inputs } => {
+                let mut count = 0;
+                let mut args = Vec::new();
+                for input in inputs {
+                    count += self.node(*input)?.dimension();
+                    args.push(variable(*input));
+                }
+                let value_type = ValueType::from_dimension(count)
+                    .filter(|value_type| *value_type != ValueType::Float)
+                    .ok_or(MaterialGraphError::InvalidComponentCount { node: id, count })?;
+                (
+                    value_type,
+                    format!("{}({})", value_type.glsl_name(), args.join(", ")),
+                )
+            }
+        };
+
+        self.body += &format!(
+            "    {} {} = {expr};\n",
+            value_type.glsl_name(),
+            variable(id)
+        );
+        self.states[id as usize] = NodeState::Done(value_type);
+        Ok(value_type)
+    }
+}
+
+/// A set of possible error variants that can occur during material graph loading or compilation.
+#[derive(Debug)]
+pub enum MaterialGraphError {
+    /// An i/o error has occurred.
+    Io(FileLoadError),
+
+    /// A parsing error has occurred.
+    ParseError(ron::error::SpannedError),
+
+    /// A node references a node that does not exist.
+    InvalidReference {
+        /// An index of the missing node.
+        reference: MaterialNodeId,
+    },
+
+    /// A node depends on itself.
+    Cycle {
+        /// An index of the node, that closes the cycle.
+        node: MaterialNodeId,
+    },
+
+    /// Operands of a node have incompatible types.
+    TypeMismatch {
+        /// An index of the node.
+        node: MaterialNodeId,
+        /// A type of the first operand.
+        left: ValueType,
+        /// A type of the second operand.
+        right: ValueType,
+    },
+
+    /// A swizzle node has invalid components for its input.
+    InvalidSwizzle {
+        /// An index of the node.
+        node: MaterialNodeId,
+        /// Requested components.
+        components: String,
+    },
+
+    /// A combine node produces a vector with unsupported amount of components.
+    InvalidComponentCount {
+        /// An index of the node.
+        node: MaterialNodeId,
+        /// Total amount of components of the inputs.
+        count: usize,
+    },
+
+    /// A name of a property is not a valid identifier or it is reserved.
+    InvalidName {
+        /// An index of the node.
+        node: MaterialNodeId,
+        /// The name.
+        name: String,
+    },
+
+    /// Two nodes define different properties with the same name.
+    NameConflict {
+        /// An index of the node.
+        node: MaterialNodeId,
+        /// The name.
+        name: String,
+    },
+
+    /// The material graph resource is not loaded.
+    NotLoaded,
+}
+
+impl Display for MaterialGraphError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MaterialGraphError::Io(v) => {
+                write!(f, "A file load error has occurred {v:?}")
+            }
+            MaterialGraphError::ParseError(v) => {
+                write!(f, "A parsing error has occurred {v:?}")
+            }
+            MaterialGraphError::InvalidReference { reference } => {
+                write!(f, "Node {reference} does not exist")
+            }
+            MaterialGraphError::Cycle { node } => {
+                write!(f, "Node {node} depends on itself")
+            }
+            MaterialGraphError::TypeMismatch { node, left, right } => {
+                write!(
+                    f,
+                    "Node {node} has operands of incompatible types {left:?} and {right:?}"
+                )
+            }
+            MaterialGraphError::InvalidSwizzle { node, components } => {
+                write!(f, "Node {node} has invalid swizzle components {components}")
+            }
+            MaterialGraphError::InvalidComponentCount { node, count } => {
+                write!(
+                    f,
+                    "Node {node} combines {count} components, but vectors must have 2-4 components"
+                )
+            }
+            MaterialGraphError::InvalidName { node, name } => {
+                write!(
+                    f,
+                    "Node {node} has invalid or reserved property name {name}"
+                )
+            }
+            MaterialGraphError::NameConflict { node, name } => {
+                write!(
+                    f,
+                    "Node {node} defines property {name}, that is already defined differently"
+                )
+            }
+            MaterialGraphError::NotLoaded => {
+                write!(f, "Material graph is not loaded")
+            }
+        }
+    }
+}
+
+impl From<ron::error::SpannedError> for MaterialGraphError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        Self::ParseError(e)
+    }
+}
+
+impl From<FileLoadError> for MaterialGraphError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// State of the [`MaterialGraphResource`].
+#[derive(Default, Debug, Reflect, Visit)]
+pub struct MaterialGraph {
+    /// Actual definition of the graph.
+    pub definition: MaterialGraphDefinition,
+}
+
+impl TypeUuidProvider for MaterialGraph {
+    fn type_uuid() -> Uuid {
+        MATERIAL_GRAPH_RESOURCE_UUID
+    }
+}
+
+impl ResourceData for MaterialGraph {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+
+    fn save(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut file = File::create(path)?;
+        file.write_all(
+            ron::ser::to_string_pretty(&self.definition, PrettyConfig::default())?.as_bytes(),
+        )?;
+        Ok(())
+    }
+
+    fn can_be_saved(&self) -> bool {
+        true
+    }
+}
+
+impl MaterialGraph {
+    /// Creates a material graph from file.
+    pub async fn from_file<P: AsRef<Path>>(
+        path: P,
+        io: &dyn ResourceIo,
+    ) -> Result<Self, MaterialGraphError> {
+        let content = io.load_file(path.as_ref()).await?;
+        Ok(Self {
+            definition: MaterialGraphDefinition::from_buf(content)?,
+        })
+    }
+
+    /// Creates a material graph from string.
+    pub fn from_string(str: &str) -> Result<Self, MaterialGraphError> {
+        Ok(Self {
+            definition: MaterialGraphDefinition::from_str(str)?,
+        })
+    }
+
+    /// Compiles the graph into a shader. See [`MaterialGraphDefinition::compile`].
+    pub fn compile(&self) -> Result<Shader, MaterialGraphError> {
+        Ok(Shader {
+            definition: self.definition.compile()?,
+            cache_index: Default::default(),
+        })
+    }
+}
+
+/// Type alias for material graph resources.
+pub type MaterialGraphResource = Resource<MaterialGraph>;
+
+/// Extension trait for material graph resources.
+pub trait MaterialGraphResourceExtension {
+    /// Compiles the graph into an embedded shader resource, that could be used to create
+    /// materials.
+    ///
+    /// ```rust
+    /// # use fyrox_impl::{
+    /// #     asset::manager::ResourceManager,
+    /// #     material::{
+    /// #         graph::{MaterialGraphError, MaterialGraphResource, MaterialGraphResourceExtension},
+    /// #         Material,
+    /// #     },
+    /// # };
+    /// fn create_material(
+    ///     graph: &MaterialGraphResource,
+    ///     resource_manager: ResourceManager,
+    /// ) -> Result<Material, MaterialGraphError> {
+    ///     let shader = graph.compile_shader()?;
+    ///     Ok(Material::from_shader(shader, Some(resource_manager)))
+    /// }
+    /// ```
+    fn compile_shader(&self) -> Result<ShaderResource, MaterialGraphError>;
+}
+
+impl MaterialGraphResourceExtension for MaterialGraphResource {
+    fn compile_shader(&self) -> Result<ShaderResource, MaterialGraphError> {
+        let mut state = self.state();
+        let graph = state.data().ok_or(MaterialGraphError::NotLoaded)?;
+        Ok(ShaderResource::new_ok(
+            ResourceKind::Embedded,
+            graph.compile()?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::material::{
+        graph::{MaterialGraph, MaterialGraphError, ValueType},
+        shader::{PropertyKind, SamplerFallback},
+    };
+
+    const GRAPH: &str = r#"
+(
+    name: "Test",
+    nodes: [
+        TexCoord,
+        Property(name: "tiling", value: Vector2((4.0, 4.0))),
+        Binary(op: Multiply, a: 0, b: 1),
+        Texture(name: "albedoTexture", fallback: White, uv: Some(2)),
+        Texture(name: "normalTexture", fallback: Normal, uv: Some(2)),
+        Unary(op: UnpackNormal, input: 4),
+        Swizzle(input: 3, components: "a"),
+        Constant(Float(0.8)),
+        Property(name: "tint", value: Color(r: 255, g: 255, b: 255, a: 255)),
+        Binary(op: Multiply, a: 3, b: 8),
+        Texture(name: "albedoTexture", fallback: White, uv: None),
+    ],
+    output: (
+        base_color: Some(9),
+        normal: Some(5),
+        metallic: Some(7),
+        opacity: Some(6),
+    ),
+)
+"#;
+
+    #[test]
+    fn test_compile() {
+        let graph = MaterialGraph::from_string(GRAPH).unwrap();
+        let shader = graph.compile().unwrap();
+        let definition = &shader.definition;
+
+        assert_eq!(definition.name, "Test");
+        let passes = definition
+            .passes
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            passes,
+            [
+                "GBuffer",
+                "Forward",
+                "OrderIndependentTransparency",
+                "DirectionalShadow",
+                "SpotShadow",
+                "PointShadow"
+            ]
+        );
+
+        let properties = definition
+            .properties
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            properties,
+            [
+                "lightmapTexture",
+                "layerIndex",
+                "albedoTexture",
+                "tiling",
+                "tint",
+                "normalTexture"
+            ]
+        );
+        assert_eq!(
+            definition.properties[2].kind,
+            PropertyKind::Sampler {
+                default: None,
+                fallback: SamplerFallback::White
+            }
+        );
+
+        let gbuffer = &definition.passes[0].fragment_shader;
+        assert!(gbuffer.contains("uniform sampler2D albedoTexture;"));
+        assert!(gbuffer.contains("uniform vec2 tiling;"));
+        assert!(gbuffer.contains("vec2 mg_1 = tiling;"));
+        assert!(gbuffer.contains("vec2 mg_2 = (mg_0 * mg_1);"));
+        assert!(gbuffer.contains("vec4 mg_3 = texture(albedoTexture, mg_2);"));
+        assert!(gbuffer.contains("float mg_6 = mg_3.w;"));
+        assert!(gbuffer.contains("mg_output.baseColor = mg_9.xyz;"));
+        assert!(gbuffer.contains("mg_output.roughness = 1.0;"));
+        // Unconnected nodes are not compiled.
+        assert!(!gbuffer.contains("mg_10"));
+    }
+
+    #[test]
+    fn test_errors() {
+        let compile = |nodes: &str| {
+            MaterialGraph::from_string(&format!(
+                "(name: \"Test\", nodes: [{nodes}], output: (base_color: Some(0)))"
+            ))
+            .unwrap()
+            .compile()
+            .err()
+        };
+
+        assert!(matches!(
+            compile("Unary(op: Sin, input: 1), Unary(op: Cos, input: 0)"),
+            Some(MaterialGraphError::Cycle { node: 0 })
+        ));
+        assert!(matches!(
+            compile("Binary(op: Add, a: 1, b: 2), TexCoord, WorldPosition"),
+            Some(MaterialGraphError::TypeMismatch {
+                node: 0,
+                left: ValueType::Vector2,
+                right: ValueType::Vector3
+            })
+        ));
+        assert!(matches!(
+            compile("Swizzle(input: 1, components: \"xz\"), TexCoord"),
+            Some(MaterialGraphError::InvalidSwizzle { node: 0, .. })
+        ));
+        assert!(matches!(
+            compile("Unary(op: Abs, input: 5)"),
+            Some(MaterialGraphError::InvalidReference { reference: 5 })
+        ));
+        assert!(matches!(
+            compile("Property(name: \"fyrox_time\", value: Float(0.0))"),
+            Some(MaterialGraphError::InvalidName { node: 0, .. })
+        ));
+        assert!(matches!(
+            compile(
+                "Binary(op: Add, a: 1, b: 2), \
+                Property(name: \"a\", value: Float(0.0)), \
+                Property(name: \"a\", value: Float(1.0))"
+            ),
+            Some(MaterialGraphError::NameConflict { node: 2, .. })
+        ));
+        assert!(matches!(
+            compile("Combine(inputs: [1, 1, 1]), TexCoord"),
+            Some(MaterialGraphError::InvalidComponentCount { node: 0, count: 6 })
+        ));
+        assert!(compile("Combine(inputs: [1, 2]), TexCoord, Constant(Float(1.0))").is_none());
+    }
+}
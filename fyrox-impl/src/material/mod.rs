@@ -5,7 +5,7 @@
 #![warn(missing_docs)]
 
 use crate::{
-    asset::{io::ResourceIo, manager::ResourceManager, Resource, ResourceData},
+    asset::{self, io::ResourceIo, manager::ResourceManager, Resource, ResourceData},
     core::{
         algebra::{Matrix2, Matrix3, Matrix4, Vector2, Vector3, Vector4},
         color::Color,
@@ -18,10 +18,13 @@ use crate::{
         visitor::{prelude::*, RegionGuard},
         TypeUuidProvider,
     },
-    material::shader::{PropertyKind, SamplerFallback, ShaderResource, ShaderResourceExtension},
+    material::shader::{
+        PropertyKind, SamplerFallback, Shader, ShaderPermutation, ShaderResource,
+        ShaderResourceExtension,
+    },
     resource::texture::{Texture, TextureResource},
 };
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet};
 use fyrox_resource::state::ResourceState;
 use fyrox_resource::untyped::ResourceKind;
 use lazy_static::lazy_static;
@@ -34,6 +37,7 @@ use std::{
     sync::Arc,
 };
 
+pub mod graph;
 pub mod loader;
 pub mod shader;
 
@@ -292,6 +296,11 @@ impl PropertyValue {
             None
         }
     }
+
+    /// Returns `true` if both values have the same type, the actual values are not compared.
+    pub fn is_same_type(&self, other: &PropertyValue) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
 }
 
 impl Default for PropertyValue {
@@ -400,6 +409,7 @@ impl Default for PropertyValue {
 pub struct Material {
     shader: ShaderResource,
     properties: FxHashMap<ImmutableString, PropertyValue>,
+    features: Vec<ImmutableString>,
 }
 
 impl Visit for Material {
@@ -418,6 +428,7 @@ impl Visit for Material {
         shader.visit("Shader", &mut region)?;
         self.shader = shader;
         self.properties.visit("Properties", &mut region)?;
+        let _ = self.features.visit("Features", &mut region); // Backward compatibility.
 
         Ok(())
     }
@@ -460,6 +471,42 @@ impl ResourceData for Material {
     }
 }
 
+/// A set of named property values, that are shared across all materials. A global property is
+/// bound to every material shader, that declares a property with the same name and type. See
+/// [`crate::renderer::Renderer::set_global_property`] for more info.
+pub type GlobalMaterialProperties = FxHashMap<ImmutableString, PropertyValue>;
+
+/// Collects every shader permutation used by the materials of the given entity (a scene, for
+/// example). It uses reflection to find the materials, so the materials stored in fields marked with
+/// `#[reflect(hidden)]` attribute are ignored. Materials that are not loaded yet are ignored as
+/// well. See [`crate::renderer::Renderer::precompile_shader_permutations`] for the typical use.
+pub fn collect_used_shader_permutations(
+    entity: &dyn Reflect,
+) -> FxHashSet<(ShaderResource, ShaderPermutation)> {
+    let mut resources = FxHashSet::default();
+    asset::collect_used_resources(entity, &mut resources);
+
+    let mut permutations = FxHashSet::default();
+    for material in resources
+        .iter()
+        .filter_map(|resource| resource.try_cast::<Material>())
+    {
+        let mut material_state = material.state();
+        let Some(material) = material_state.data() else {
+            continue;
+        };
+        let shader = material.shader().clone();
+        let mut shader_state = shader.state();
+        let Some(shader_data) = shader_state.data() else {
+            continue;
+        };
+        let permutation = material.permutation(shader_data);
+        drop(shader_state);
+        permutations.insert((shader, permutation));
+    }
+    permutations
+}
+
 /// A set of possible errors that can occur when working with materials.
 #[derive(Debug)]
 pub enum MaterialError {
@@ -468,6 +515,11 @@ pub enum MaterialError {
         /// Name of the property.
         property_name: String,
     },
+    /// A feature is not declared in the shader.
+    NoSuchFeature {
+        /// Name of the feature.
+        feature_name: String,
+    },
     /// Attempt to set a value of wrong type to a property.
     TypeMismatch {
         /// Name of the property.
@@ -499,6 +551,9 @@ impl Display for MaterialError {
             MaterialError::NoSuchProperty { property_name } => {
                 write!(f, "Unable to find material property {property_name}")
             }
+            MaterialError::NoSuchFeature { feature_name } => {
+                write!(f, "Unable to find shader feature {feature_name}")
+            }
             MaterialError::TypeMismatch {
                 property_name,
                 expected,
@@ -553,6 +608,15 @@ lazy_static! {
     );
 }
 
+lazy_static! {
+    /// Standard vector shape material. Keep in mind that this material is global, any modification
+    /// of it will reflect on every other usage of it.
+    pub static ref STANDARD_VECTOR_SHAPE: MaterialResource = MaterialResource::new_ok(
+        "__StandardVectorShapeMaterial".into(),
+        Material::from_shader(ShaderResource::standard_vector_shape(), None),
+    );
+}
+
 lazy_static! {
     /// Standard terrain material. Keep in mind that this material is global, any modification
     /// of it will reflect on every other usage of it.
@@ -619,6 +683,11 @@ impl Material {
         Self::from_shader(ShaderResource::standard_sprite(), None)
     }
 
+    /// Creates new instance of standard vector shape material.
+    pub fn standard_vector_shape() -> Self {
+        Self::from_shader(ShaderResource::standard_vector_shape(), None)
+    }
+
     /// Creates new instance of standard material that renders both sides of a face.
     pub fn standard_two_sides() -> Self {
         Self::from_shader(ShaderResource::standard_twosides(), None)
@@ -679,6 +748,7 @@ impl Material {
         Self {
             shader,
             properties: property_values,
+            features: Default::default(),
         }
     }
 
@@ -695,6 +765,7 @@ impl Material {
         let mut material = Material {
             shader: Default::default(),
             properties: Default::default(),
+            features: Default::default(),
         };
         let mut visitor = Visitor::load_from_memory(&content)?;
         visitor.blackboard.register(Arc::new(resource_manager));
@@ -858,53 +929,117 @@ impl Material {
     pub fn sync_to_shader(&mut self, resource_manager: &ResourceManager) -> bool {
         let shader_kind = self.shader.kind().clone();
         if let Some(shader) = self.shader.state().data() {
-            if shader.definition.properties.len() > self.properties.len() {
-                // Some property was added to the shader, but missing in the material.
-                for property_definition in shader.definition.properties.iter() {
-                    let name = ImmutableString::new(&property_definition.name);
-                    if !self.properties.contains_key(&name) {
-                        // Add the property with default values.
-                        self.properties.insert(
-                            name.clone(),
-                            PropertyValue::from_property_kind(
-                                &property_definition.kind,
-                                Some(resource_manager),
-                            ),
-                        );
-
-                        Log::info(format!(
-                            "Added {} property to the material instance, since it exists in the \
-                            shader {}, but not in the material instance.",
-                            name, shader_kind
-                        ));
-                    }
+            // Some property was added to the shader, but missing in the material. Both cases are
+            // checked, because a property could be renamed in the shader.
+            for property_definition in shader.definition.properties.iter() {
+                let name = ImmutableString::new(&property_definition.name);
+                if !self.properties.contains_key(&name) {
+                    // Add the property with default values.
+                    self.properties.insert(
+                        name.clone(),
+                        PropertyValue::from_property_kind(
+                            &property_definition.kind,
+                            Some(resource_manager),
+                        ),
+                    );
+
+                    Log::info(format!(
+                        "Added {} property to the material instance, since it exists in the \
+                        shader {}, but not in the material instance.",
+                        name, shader_kind
+                    ));
                 }
-            } else {
-                // Some property was removed from the shader, but still exists in the material.
-                for property_name in self.properties.keys().cloned().collect::<Vec<_>>() {
-                    if shader
-                        .definition
-                        .properties
-                        .iter()
-                        .all(|p| p.name != property_name.as_ref())
-                    {
-                        self.properties.remove(&property_name);
-
-                        Log::info(format!(
-                            "Removing {} property from the material instance, since it does \
+            }
+
+            // Some property was removed from the shader, but still exists in the material.
+            for property_name in self.properties.keys().cloned().collect::<Vec<_>>() {
+                if shader
+                    .definition
+                    .properties
+                    .iter()
+                    .all(|p| p.name != property_name.as_ref())
+                {
+                    self.properties.remove(&property_name);
+
+                    Log::info(format!(
+                        "Removing {} property from the material instance, since it does \
                         not exists in the shader {}.",
-                            property_name, shader_kind
-                        ));
-                    }
+                        property_name, shader_kind
+                    ));
                 }
             }
 
+            // Some feature was removed from the shader, but still enabled in the material.
+            self.features.retain(|feature| {
+                let exists = shader.feature_index(feature).is_some();
+                if !exists {
+                    Log::info(format!(
+                        "Disabling {} feature of the material instance, since it does not \
+                        exists in the shader {}.",
+                        feature, shader_kind
+                    ));
+                }
+                exists
+            });
+
             return true;
         }
 
         false
     }
 
+    /// Enables or disables a feature of the shader with the given name. Every enabled feature is
+    /// defined as a preprocessor macro in the source code of the shader, see
+    /// [shader module docs](self::shader) for more info. Returns an error if the shader does not
+    /// declare such feature. Features could be set before the shader is loaded, in this case the
+    /// check is not performed and unknown features are ignored by the renderer.
+    ///
+    /// # Performance
+    ///
+    /// Every unique combination of enabled features requires its own set of GPU programs, which
+    /// are compiled on first use. Try to keep the amount of combinations low.
+    pub fn set_feature(
+        &mut self,
+        name: &ImmutableString,
+        enabled: bool,
+    ) -> Result<(), MaterialError> {
+        if let Some(shader) = self.shader.state().data() {
+            if shader.feature_index(name).is_none() {
+                return Err(MaterialError::NoSuchFeature {
+                    feature_name: name.deref().to_owned(),
+                });
+            }
+        }
+
+        match self.features.iter().position(|feature| feature == name) {
+            Some(index) if !enabled => {
+                self.features.remove(index);
+            }
+            None if enabled => {
+                self.features.push(name.clone());
+            }
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if a feature with the given name is enabled in the material.
+    pub fn is_feature_enabled(&self, name: &ImmutableString) -> bool {
+        self.features.contains(name)
+    }
+
+    /// Returns a list of enabled features of the material.
+    pub fn features(&self) -> &[ImmutableString] {
+        &self.features
+    }
+
+    /// Returns a shader permutation for the enabled features of the material. The given shader
+    /// must be the shader of the material.
+    pub fn permutation(&self, shader: &Shader) -> ShaderPermutation {
+        shader.permutation(self.features.iter().map(|feature| feature.deref()))
+    }
+
     /// Returns a reference to current shader.
     pub fn shader(&self) -> &ShaderResource {
         &self.shader
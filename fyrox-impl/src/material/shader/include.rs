@@ -0,0 +1,370 @@
+//! Shader includes allow shaders to share common code (lighting, noise functions, etc.) instead of
+//! copying it into every shader. See [`ShaderIncludes`] docs for more info.
+
+use fxhash::FxHashMap;
+use std::{
+    collections::hash_map::Entry,
+    error::Error,
+    fmt::{Display, Formatter, Write},
+};
+
+/// An error, that may occur when includes of a shader are resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShaderIncludeError {
+    /// There is no chunk with the given name.
+    NotFound {
+        /// Name of the missing chunk.
+        name: String,
+        /// Line (starting from 1) with the include directive.
+        line: usize,
+    },
+    /// A chunk includes itself, directly or through other chunks.
+    Cyclic {
+        /// Names of the chunks that form the cycle, the last one is included by the first one.
+        chain: Vec<String>,
+    },
+    /// An include directive does not contain a valid name.
+    Malformed {
+        /// Line (starting from 1) with the include directive.
+        line: usize,
+    },
+}
+
+impl Display for ShaderIncludeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShaderIncludeError::NotFound { name, line } => {
+                write!(
+                    f,
+                    "Shader chunk {name} is not found (included at line {line})"
+                )
+            }
+            ShaderIncludeError::Cyclic { chain } => {
+                write!(f, "Cyclic shader includes: {}", chain.join(" -> "))
+            }
+            ShaderIncludeError::Malformed { line } => {
+                write!(f, "Malformed include directive at line {line}")
+            }
+        }
+    }
+}
+
+impl Error for ShaderIncludeError {}
+
+/// A virtual file system with shared chunks of shader code. Any shader source (of any render pass
+/// and any shader stage) could include a chunk by its name:
+///
+/// ```glsl
+/// #include "lighting/toon.glsl"
+///
+/// void main() {
+///     fragColor = ToonLighting(normal, lightDirection);
+/// }
+/// ```
+///
+/// The name could also be enclosed in angle brackets (`#include <lighting/toon.glsl>`), there is no
+/// difference between the two forms. Chunks could include other chunks. Every chunk is compiled
+/// only once per source, so there is no need in include guards, and two chunks could both include
+/// a third one safely. Includes are resolved before compilation, after the defines of enabled
+/// features (see [`super::ShaderPermutation`]), so chunks could use `#ifdef` as well.
+///
+/// Include directives could be placed in conditional blocks (`#ifdef`, `#if`, etc.). Conditions are
+/// evaluated by the shader compiler, so a chunk is inserted in every branch, that includes it, and
+/// its copies are wrapped in generated include guards. A chunk is not inserted again only if it was
+/// already inserted in the same or in an enclosing branch. Every inserted chunk is surrounded by
+/// `#line` directives, so compilation errors point to the lines of the chunk. The source string
+/// number of a chunk in an error message could be converted to the chunk name using
+/// [`ShaderIncludes::chunk_name`], the number of the shader source itself is zero.
+///
+/// Chunks are registered in the renderer (see
+/// [`crate::renderer::Renderer::register_shader_include`]).
+///
+/// ```rust
+/// use fyrox_impl::material::shader::include::ShaderIncludes;
+///
+/// let mut includes = ShaderIncludes::default();
+/// includes.register("math.glsl", "float Square(float x) { return x * x; }");
+///
+/// let source = includes
+///     .resolve("#include \"math.glsl\"\nvoid main() { float a = Square(2.0); }")
+///     .unwrap();
+/// assert!(source.contains("float Square"));
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct ShaderIncludes {
+    chunks: FxHashMap<String, Chunk>,
+    last_id: u32,
+}
+
+#[derive(Debug, Clone)]
+struct Chunk {
+    // Source string number of the chunk in `#line` directives.
+    id: u32,
+    source: String,
+}
+
+// Splits a preprocessor directive into its name and the rest of the line.
+fn parse_directive(line: &str) -> Option<(&str, &str)> {
+    let rest = line.trim_start().strip_prefix('#')?.trim_start();
+    let end = rest
+        .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .unwrap_or(rest.len());
+    Some((&rest[..end], rest[end..].trim()))
+}
+
+fn parse_include_name(args: &str) -> Result<&str, ()> {
+    let name = args
+        .strip_prefix('"')
+        .and_then(|r| r.strip_suffix('"'))
+        .or_else(|| args.strip_prefix('<').and_then(|r| r.strip_suffix('>')));
+    match name {
+        Some(name) if !name.is_empty() => Ok(name),
+        _ => Err(()),
+    }
+}
+
+#[derive(Default)]
+struct ResolveContext<'a> {
+    output: String,
+    // Conditional branches, that enclose the current line. Every branch has unique id.
+    branches: Vec<usize>,
+    last_branch: usize,
+    // Inserted chunks and the branches they were inserted in.
+    included: Vec<(&'a str, Vec<usize>)>,
+    // Chunks, that are being resolved right now.
+    stack: Vec<&'a str>,
+}
+
+impl ResolveContext<'_> {
+    fn enter_branch(&mut self) {
+        self.last_branch += 1;
+        self.branches.push(self.last_branch);
+    }
+
+    fn is_included(&self, name: &str) -> bool {
+        // A chunk inserted in the same or in an enclosing branch is always compiled before the
+        // current line.
+        self.included
+            .iter()
+            .any(|(n, branches)| *n == name && self.branches.starts_with(branches))
+    }
+}
+
+impl ShaderIncludes {
+    /// Registers a new chunk or replaces the source of an existing one.
+    pub fn register<N: Into<String>, S: Into<String>>(&mut self, name: N, source: S) {
+        let source = source.into();
+        match self.chunks.entry(name.into()) {
+            Entry::Occupied(mut entry) => entry.get_mut().source = source,
+            Entry::Vacant(entry) => {
+                self.last_id += 1;
+                entry.insert(Chunk {
+                    id: self.last_id,
+                    source,
+                });
+            }
+        }
+    }
+
+    /// Removes a chunk and returns its source.
+    pub fn unregister(&mut self, name: &str) -> Option<String> {
+        self.chunks.remove(name).map(|chunk| chunk.source)
+    }
+
+    /// Returns the source of a chunk.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.chunks.get(name).map(|chunk| chunk.source.as_str())
+    }
+
+    /// Returns the name of a chunk with the given source string number, that is used in `#line`
+    /// directives of resolved sources.
+    pub fn chunk_name(&self, source_string: u32) -> Option<&str> {
+        self.chunks
+            .iter()
+            .find_map(|(name, chunk)| (chunk.id == source_string).then_some(name.as_str()))
+    }
+
+    /// Returns an iterator over the names of all registered chunks.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.chunks.keys().map(|s| s.as_str())
+    }
+
+    /// Replaces every include directive in the source with the source of a respective chunk.
+    pub fn resolve(&self, source: &str) -> Result<String, ShaderIncludeError> {
+        let mut context = ResolveContext {
+            output: String::with_capacity(source.len()),
+            ..Default::default()
+        };
+        self.resolve_recursive(source, 0, &mut context)?;
+        Ok(context.output)
+    }
+
+    fn resolve_recursive<'a>(
+        &'a self,
+        source: &'a str,
+        source_string: u32,
+        context: &mut ResolveContext<'a>,
+    ) -> Result<(), ShaderIncludeError> {
+        for (index, line) in source.lines().enumerate() {
+            let name = match parse_directive(line) {
+                Some(("include", args)) => parse_include_name(args)
+                    .map_err(|_| ShaderIncludeError::Malformed { line: index + 1 })?,
+                directive => {
+                    match directive {
+                        Some(("if" | "ifdef" | "ifndef", _)) => context.enter_branch(),
+                        Some(("elif" | "else", _)) => {
+                            context.branches.pop();
+                            context.enter_branch();
+                        }
+                        Some(("endif", _)) => {
+                            context.branches.pop();
+                        }
+                        _ => (),
+                    }
+                    context.output.push_str(line);
+                    context.output.push('\n');
+                    continue;
+                }
+            };
+
+            if context.stack.contains(&name) {
+                let mut chain = context
+                    .stack
+                    .iter()
+                    .skip_while(|n| **n != name)
+                    .map(|n| n.to_string())
+                    .collect::<Vec<_>>();
+                chain.push(name.to_string());
+                return Err(ShaderIncludeError::Cyclic { chain });
+            }
+
+            if context.is_included(name) {
+                // Keep line numbers of the source intact.
+                context.output.push('\n');
+                continue;
+            }
+
+            let (name, chunk) =
+                self.chunks
+                    .get_key_value(name)
+                    .ok_or_else(|| ShaderIncludeError::NotFound {
+                        name: name.to_string(),
+                        line: index + 1,
+                    })?;
+
+            // The guard prevents the second compilation of the chunk when it is inserted in
+            // several branches, that are active at the same time.
+            let id = chunk.id;
+            writeln!(
+                context.output,
+                "#ifndef FYROX_INCLUDE_{id}\n#define FYROX_INCLUDE_{id}\n#line 1 {id}"
+            )
+            .unwrap();
+
+            // Conditional blocks of the chunk must not leak outside of it.
+            let depth = context.branches.len();
+            context.stack.push(name);
+            self.resolve_recursive(&chunk.source, id, context)?;
+            context.stack.pop();
+            context.branches.truncate(depth);
+            context.included.push((name, context.branches.clone()));
+
+            writeln!(
+                context.output,
+                "#endif\n#line {} {source_string}",
+                index + 2
+            )
+            .unwrap();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let mut includes = ShaderIncludes::default();
+        includes.register("a.glsl", "float A;");
+        includes.register("b.glsl", "#include \"a.glsl\"\nfloat B;");
+
+        assert_eq!(
+            includes
+                .resolve("#include <b.glsl>\n  #  include \"a.glsl\"\nvoid main() {}")
+                .unwrap(),
+            "#ifndef FYROX_INCLUDE_2\n\
+            #define FYROX_INCLUDE_2\n\
+            #line 1 2\n\
+            #ifndef FYROX_INCLUDE_1\n\
+            #define FYROX_INCLUDE_1\n\
+            #line 1 1\n\
+            float A;\n\
+            #endif\n\
+            #line 2 2\n\
+            float B;\n\
+            #endif\n\
+            #line 2 0\n\
+            \n\
+            void main() {}\n"
+        );
+        assert_eq!(includes.chunk_name(2), Some("b.glsl"));
+        assert_eq!(includes.chunk_name(0), None);
+    }
+
+    #[test]
+    fn test_conditional_includes() {
+        let mut includes = ShaderIncludes::default();
+        includes.register("a.glsl", "float A;");
+
+        let source = includes
+            .resolve(
+                "#ifdef X\n\
+                #include \"a.glsl\"\n\
+                #include \"a.glsl\"\n\
+                #else\n\
+                #include \"a.glsl\"\n\
+                #endif\n\
+                #ifdef Y\n\
+                #include \"a.glsl\"\n\
+                #endif\n\
+                #include \"a.glsl\"\n\
+                #include \"a.glsl\"",
+            )
+            .unwrap();
+
+        // Once per branch (X, not X, Y) and once outside of any branch, the rest is deduplicated.
+        assert_eq!(source.matches("float A;").count(), 4);
+        assert_eq!(source.matches("#ifndef FYROX_INCLUDE_1").count(), 4);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut includes = ShaderIncludes::default();
+        includes.register("a.glsl", "#include \"b.glsl\"");
+        includes.register("b.glsl", "#include \"a.glsl\"");
+
+        assert_eq!(
+            includes.resolve("void main() {}\n#include \"c.glsl\""),
+            Err(ShaderIncludeError::NotFound {
+                name: "c.glsl".to_string(),
+                line: 2
+            })
+        );
+        assert_eq!(
+            includes.resolve("#include c.glsl"),
+            Err(ShaderIncludeError::Malformed { line: 1 })
+        );
+        assert_eq!(
+            includes.resolve("#include \"a.glsl\""),
+            Err(ShaderIncludeError::Cyclic {
+                chain: vec![
+                    "a.glsl".to_string(),
+                    "b.glsl".to_string(),
+                    "a.glsl".to_string()
+                ]
+            })
+        );
+    }
+}
@@ -20,6 +20,9 @@
 //!         )
 //!     ],
 //!
+//!     // A set of optional features (see a section `Features` for more info).
+//!     features: ["USE_DETAIL_TEXTURE"],
+//!
 //!     // A set of render passes (see a section `Render pass` for more info)
 //!     passes: [
 //!         (
@@ -115,8 +118,11 @@
 //! ```
 //!
 //! Shader should contain at least one render pass to actually do some job. A shader could not
-//! have properties at all. Currently only vertex and fragment programs are supported. Each
-//! program mush be written in GLSL. Comprehensive GLSL documentation can be found
+//! have properties at all. Each render pass must have vertex and fragment programs, optionally it
+//! could also have `geometry_shader`, `tessellation_control_shader` and
+//! `tessellation_evaluation_shader` programs. These stages are not supported on every platform,
+//! a render pass that uses unsupported stages is ignored (with an error message in the log).
+//! Each program mush be written in GLSL. Comprehensive GLSL documentation can be found
 //! [here](https://www.khronos.org/opengl/wiki/Core_Language_(GLSL))
 //!
 //! # Render pass
@@ -156,6 +162,8 @@
 //! | fyrox_worldMatrix          | `mat4`       | Local-to-world transformation.                                                                                    |
 //! | fyrox_worldViewProjection  | `mat4`       | Local-to-clip-space transform.                                                                                    |
 //! | fyrox_boneMatrices         | `sampler2D`  | Array of bone matrices packed into a texture. Use `S_FetchMatrix` built-in method to fetch a matrix by its index. |
+//! | fyrox_prevWorldMatrix      | `mat4`       | Local-to-world transformation of the previous frame. Could be used to calculate per-object motion vectors.        |
+//! | fyrox_prevBoneMatrices     | `sampler2D`  | Array of bone matrices of the previous frame packed into a texture. Same layout as `fyrox_boneMatrices`.          |
 //! | fyrox_useSkeletalAnimation | `bool`       | Whether skinned meshes is rendering or not.                                                                       |
 //! | fyrox_cameraPosition       | `vec3`       | Position of the camera.                                                                                           |
 //! | fyrox_usePOM               | `bool`       | Whether to use parallax mapping or not.                                                                           |
@@ -163,6 +171,13 @@
 //! | fyrox_blendShapesStorage   | `sampler3D`  | 3D texture of layered blend shape storage. Use `S_FetchBlendShapeOffsets` built-in method to fetch info.          |
 //! | fyrox_blendShapesWeights   | `float[128]` | Weights of all available blend shapes.                                                                            |
 //! | fyrox_blendShapesCount     | `int`        | Total amount of blend shapes.                                                                                     |
+//! | fyrox_weather              | `vec4`       | Scene weather: wetness (x), snow coverage (y), time (z) and precipitation intensity (w). Use `S_ApplyWeather`.    |
+//! | fyrox_wind                 | `vec4`       | Velocity of the wind (xyz) and its speed (w). Could be used to animate vegetation.                                |
+//! | fyrox_clusterGrid          | `sampler2D`  | Ranges of light indices of light clusters. Use `S_ClusteredLighting` built-in method to calculate lighting.       |
+//! | fyrox_clusterLights        | `sampler2D`  | Parameters of point and spot lights of the light clusters.                                                        |
+//! | fyrox_clusterLightIndices  | `sampler2D`  | Indices of the lights of all the light clusters.                                                                  |
+//! | fyrox_clusterParameters    | `vec4`       | Depth slicing scale (x) and bias (y) of the light clusters, inverse size of the frame (zw).                       |
+//! | fyrox_clusterDepthPlane    | `vec4`       | World-space plane, distance to which is the view depth of a point (for light clusters).                           |
 //!
 //! To use any of the properties, just define a uniform with an appropriate name:
 //!
@@ -173,6 +188,33 @@
 //!
 //! This list will be extended in future releases.
 //!
+//! # Features
+//!
+//! Branch-heavy "uber" shaders could be very slow on low-end GPUs. To avoid this, a shader could
+//! declare a list of features, that could be enabled or disabled per material (see
+//! [`super::Material::set_feature`]). Every enabled feature is added to the source code of every
+//! program of the shader as `#define FEATURE_NAME`, so parts of the code could be compiled
+//! conditionally:
+//!
+//! ```glsl
+//! #ifdef USE_DETAIL_TEXTURE
+//!     diffuseColor *= texture(detailTexture, detailTexCoord);
+//! #endif
+//! ```
+//!
+//! Every unique combination of enabled features (see [`ShaderPermutation`]) is compiled into a
+//! separate set of GPU programs on first use. Use
+//! [`crate::renderer::Renderer::precompile_shader_permutations`] to compile the permutations used
+//! by a scene ahead of time and avoid hitches during the rendering. A shader could have at most
+//! [`MAX_SHADER_FEATURES`] features.
+//!
+//! # Includes
+//!
+//! Code, that is shared between multiple shaders (lighting models, noise functions, etc.), could be
+//! moved into a chunk and included in any shader source with `#include "chunk_name.glsl"`. Chunks
+//! are stored in a virtual file system of the renderer, see [`include::ShaderIncludes`] docs for
+//! more info.
+//!
 //! # Drawing parameters
 //!
 //! Drawing parameters defines which GPU functions to use and at which state. For example, to render
@@ -254,6 +296,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
+pub mod include;
 pub mod loader;
 
 /// A name of the standard shader.
@@ -293,24 +336,56 @@ pub const STANDARD_SPRITE_SHADER_NAME: &str = "StandardSprite";
 /// A source code of the standard terrain shader.
 pub const STANDARD_TERRAIN_SHADER_SRC: &str = include_str!("standard/terrain.shader");
 
+/// A name of the standard tile shader.
+pub const STANDARD_TILE_SHADER_NAME: &str = "StandardTile";
+
+/// A source code of the standard tile shader. It is used to draw tiles of tile maps using hardware
+/// instancing.
+pub const STANDARD_TILE_SHADER_SRC: &str = include_str!("standard/standard_tile.shader");
+
+/// A name of the standard tile array shader.
+pub const STANDARD_TILE_ARRAY_SHADER_NAME: &str = "StandardTileArray";
+
+/// A source code of the standard tile array shader. It is used to draw tiles of tile sets, that are
+/// packed into texture arrays, using hardware instancing.
+pub const STANDARD_TILE_ARRAY_SHADER_SRC: &str =
+    include_str!("standard/standard_tile_array.shader");
+
+/// A name of the standard vector shape shader.
+pub const STANDARD_VECTOR_SHAPE_SHADER_NAME: &str = "StandardVectorShape";
+
+/// A source code of the standard vector shape shader. It is used to draw vector shapes using signed
+/// distance fields.
+pub const STANDARD_VECTOR_SHAPE_SHADER_SRC: &str =
+    include_str!("standard/standard_vector_shape.shader");
+
+/// Max amount of features of a shader. See [`ShaderPermutation`] for more info.
+pub const MAX_SHADER_FEATURES: usize = 64;
+
 /// A list of names of standard shaders.
-pub const STANDARD_SHADER_NAMES: [&str; 6] = [
+pub const STANDARD_SHADER_NAMES: [&str; 9] = [
     STANDARD_SHADER_NAME,
     STANDARD_2D_SHADER_NAME,
     STANDARD_PARTICLE_SYSTEM_SHADER_NAME,
     STANDARD_SPRITE_SHADER_NAME,
     STANDARD_TWOSIDES_SHADER_NAME,
     STANDARD_TERRAIN_SHADER_NAME,
+    STANDARD_TILE_SHADER_NAME,
+    STANDARD_VECTOR_SHAPE_SHADER_NAME,
+    STANDARD_TILE_ARRAY_SHADER_NAME,
 ];
 
 /// A list of source code of standard shaders.
-pub const STANDARD_SHADER_SOURCES: [&str; 6] = [
+pub const STANDARD_SHADER_SOURCES: [&str; 9] = [
     STANDARD_SHADER_SRC,
     STANDARD_2D_SHADER_SRC,
     STANDARD_PARTICLE_SYSTEM_SHADER_SRC,
     STANDARD_SPRITE_SHADER_SRC,
     STANDARD_TWOSIDES_SHADER_SRC,
     STANDARD_TERRAIN_SHADER_SRC,
+    STANDARD_TILE_SHADER_SRC,
+    STANDARD_VECTOR_SHAPE_SHADER_SRC,
+    STANDARD_TILE_ARRAY_SHADER_SRC,
 ];
 
 /// Internal state of the shader.
@@ -485,6 +560,22 @@ pub struct RenderPassDefinition {
     pub vertex_shader: String,
     /// A source code of fragment shader.
     pub fragment_shader: String,
+    /// An optional source code of geometry shader. Geometry shaders are not supported on OpenGL ES,
+    /// render passes that use them will be ignored on such platforms.
+    #[serde(default)]
+    #[visit(optional)]
+    pub geometry_shader: Option<String>,
+    /// An optional source code of tessellation control shader. It can be used only with
+    /// tessellation evaluation shader.
+    #[serde(default)]
+    #[visit(optional)]
+    pub tessellation_control_shader: Option<String>,
+    /// An optional source code of tessellation evaluation shader. Tessellation requires OpenGL 4.0,
+    /// render passes that use tessellation will be ignored on platforms that does not support it.
+    /// Every triangle of a mesh is passed to tessellation stages as a patch of three vertices.
+    #[serde(default)]
+    #[visit(optional)]
+    pub tessellation_evaluation_shader: Option<String>,
 }
 
 /// A definition of the shader.
@@ -496,6 +587,11 @@ pub struct ShaderDefinition {
     pub passes: Vec<RenderPassDefinition>,
     /// A set of property definitions.
     pub properties: Vec<PropertyDefinition>,
+    /// A set of names of optional features of the shader. Every feature enabled in a material is
+    /// defined as a preprocessor macro with the same name. See the module docs for more info.
+    #[serde(default)]
+    #[visit(optional)]
+    pub features: Vec<String>,
 }
 
 impl ShaderDefinition {
@@ -508,7 +604,69 @@ impl ShaderDefinition {
     }
 }
 
+/// A combination of enabled features of a shader. Every bit of the value corresponds to a feature
+/// with the same index in [`ShaderDefinition::features`]. The renderer compiles a separate set of
+/// GPU programs for every permutation used by materials.
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShaderPermutation(pub u64);
+
+impl ShaderPermutation {
+    /// A permutation without any enabled features.
+    pub const BASE: Self = Self(0);
+
+    /// Returns `true` if a feature with the given index is enabled in the permutation.
+    pub fn is_enabled(self, index: usize) -> bool {
+        index < MAX_SHADER_FEATURES && self.0 & (1 << index) != 0
+    }
+
+    /// Returns a copy of the permutation with a feature at the given index enabled. Indices outside
+    /// of `[0; MAX_SHADER_FEATURES)` range are ignored.
+    pub fn with_feature(self, index: usize) -> Self {
+        if index < MAX_SHADER_FEATURES {
+            Self(self.0 | (1 << index))
+        } else {
+            self
+        }
+    }
+}
+
 impl Shader {
+    /// Returns an index of a feature with the given name, or `None` if the shader does not declare
+    /// such feature.
+    pub fn feature_index(&self, name: &str) -> Option<usize> {
+        self.definition
+            .features
+            .iter()
+            .take(MAX_SHADER_FEATURES)
+            .position(|feature| feature == name)
+    }
+
+    /// Creates a permutation from a set of feature names. Unknown features are ignored.
+    pub fn permutation<'a, I>(&self, features: I) -> ShaderPermutation
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        features
+            .into_iter()
+            .filter_map(|name| self.feature_index(name))
+            .fold(ShaderPermutation::BASE, |permutation, index| {
+                permutation.with_feature(index)
+            })
+    }
+
+    /// Returns a set of preprocessor definitions of every feature enabled in the given permutation.
+    pub fn permutation_defines(&self, permutation: ShaderPermutation) -> String {
+        let mut defines = String::new();
+        for (index, feature) in self.definition.features.iter().enumerate() {
+            if permutation.is_enabled(index) {
+                defines += "#define ";
+                defines += feature;
+                defines += "\n";
+            }
+        }
+        defines
+    }
+
     /// Creates a shader from file.
     pub async fn from_file<P: AsRef<Path>>(
         path: P,
@@ -618,6 +776,15 @@ pub trait ShaderResourceExtension: Sized {
     /// Returns an instance of standard two-sides terrain shader.
     fn standard_twosides() -> Self;
 
+    /// Returns an instance of standard tile shader.
+    fn standard_tile() -> Self;
+
+    /// Returns an instance of standard vector shape shader.
+    fn standard_vector_shape() -> Self;
+
+    /// Returns an instance of standard tile array shader.
+    fn standard_tile_array() -> Self;
+
     /// Returns a list of standard shader.
     fn standard_shaders() -> Vec<ShaderResource>;
 }
@@ -651,6 +818,18 @@ impl ShaderResourceExtension for ShaderResource {
         STANDARD_TWOSIDES.clone()
     }
 
+    fn standard_tile() -> Self {
+        STANDARD_TILE.clone()
+    }
+
+    fn standard_vector_shape() -> Self {
+        STANDARD_VECTOR_SHAPE.clone()
+    }
+
+    fn standard_tile_array() -> Self {
+        STANDARD_TILE_ARRAY.clone()
+    }
+
     fn standard_shaders() -> Vec<ShaderResource> {
         vec![
             Self::standard(),
@@ -659,6 +838,9 @@ impl ShaderResourceExtension for ShaderResource {
             Self::standard_sprite(),
             Self::standard_terrain(),
             Self::standard_twosides(),
+            Self::standard_tile(),
+            Self::standard_vector_shape(),
+            Self::standard_tile_array(),
         ]
     }
 }
@@ -705,12 +887,35 @@ lazy_static! {
     );
 }
 
+lazy_static! {
+    static ref STANDARD_TILE: ShaderResource = ShaderResource::new_ok(
+        STANDARD_TILE_SHADER_NAME.into(),
+        Shader::from_string(STANDARD_TILE_SHADER_SRC).unwrap(),
+    );
+}
+
+lazy_static! {
+    static ref STANDARD_VECTOR_SHAPE: ShaderResource = ShaderResource::new_ok(
+        STANDARD_VECTOR_SHAPE_SHADER_NAME.into(),
+        Shader::from_string(STANDARD_VECTOR_SHAPE_SHADER_SRC).unwrap(),
+    );
+}
+
+lazy_static! {
+    static ref STANDARD_TILE_ARRAY: ShaderResource = ShaderResource::new_ok(
+        STANDARD_TILE_ARRAY_SHADER_NAME.into(),
+        Shader::from_string(STANDARD_TILE_ARRAY_SHADER_SRC).unwrap(),
+    );
+}
+
 #[cfg(test)]
 mod test {
+    use crate::core::sstorage::ImmutableString;
     use crate::material::shader::{
         PropertyDefinition, PropertyKind, RenderPassDefinition, SamplerFallback, ShaderDefinition,
-        ShaderResource, ShaderResourceExtension,
+        ShaderPermutation, ShaderResource, ShaderResourceExtension,
     };
+    use crate::material::Material;
 
     #[test]
     fn test_shader_load() {
@@ -771,9 +976,53 @@ mod test {
                 draw_parameters: Default::default(),
                 vertex_shader: "<CODE>".to_string(),
                 fragment_shader: "<CODE>".to_string(),
+                ..Default::default()
             }],
+            features: Default::default(),
         };
 
         assert_eq!(data.definition, reference_definition);
     }
+
+    #[test]
+    fn test_shader_permutation() {
+        let code = r#"
+            (
+                name: "TestShader",
+                properties: [],
+                passes: [],
+                features: ["USE_FOG", "USE_DETAIL", "USE_RIM"],
+            )
+            "#;
+
+        let shader = ShaderResource::from_str(code, "test".into()).unwrap();
+        let data = shader.data_ref();
+
+        assert_eq!(data.feature_index("USE_RIM"), Some(2));
+        assert_eq!(data.feature_index("USE_UNKNOWN"), None);
+
+        let permutation = data.permutation(["USE_RIM", "USE_FOG", "USE_UNKNOWN"]);
+        assert_eq!(permutation, ShaderPermutation(0b101));
+        assert!(permutation.is_enabled(0));
+        assert!(!permutation.is_enabled(1));
+        assert_eq!(
+            data.permutation_defines(permutation),
+            "#define USE_FOG\n#define USE_RIM\n"
+        );
+        assert!(data.permutation_defines(ShaderPermutation::BASE).is_empty());
+        drop(data);
+
+        let mut material = Material::from_shader(shader.clone(), None);
+        assert!(material
+            .set_feature(&ImmutableString::new("USE_DETAIL"), true)
+            .is_ok());
+        assert!(material
+            .set_feature(&ImmutableString::new("USE_UNKNOWN"), true)
+            .is_err());
+        assert!(material.is_feature_enabled(&ImmutableString::new("USE_DETAIL")));
+        assert_eq!(
+            material.permutation(&shader.data_ref()),
+            ShaderPermutation(0b010)
+        );
+    }
 }
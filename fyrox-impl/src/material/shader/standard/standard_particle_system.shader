@@ -52,14 +52,18 @@
                layout(location = 2) in float particleSize;
                layout(location = 3) in float particleRotation;
                layout(location = 4) in vec4 vertexColor;
+               layout(location = 5) in vec3 vertexAxis;
+               layout(location = 6) in float vertexSoftDistance;
 
                uniform mat4 fyrox_viewProjectionMatrix;
                uniform mat4 fyrox_worldMatrix;
                uniform vec3 fyrox_cameraUpVector;
                uniform vec3 fyrox_cameraSideVector;
+               uniform vec3 fyrox_cameraPosition;
 
                out vec2 texCoord;
                out vec4 color;
+               flat out float softDistance;
 
                vec2 rotateVec2(vec2 v, float angle)
                {
@@ -73,9 +77,17 @@
                {
                    color = S_SRGBToLinear(vertexColor);
                    texCoord = vertexTexCoord;
+                   softDistance = vertexSoftDistance;
                    vec2 vertexOffset = rotateVec2(vertexTexCoord * 2.0 - 1.0, particleRotation);
                    vec4 worldPosition = fyrox_worldMatrix * vec4(vertexPosition, 1.0);
-                   vec3 offset = (vertexOffset.x * fyrox_cameraSideVector + vertexOffset.y * fyrox_cameraUpVector) * particleSize;
+                   vec3 offset = S_BillboardOffset(
+                       vertexOffset,
+                       worldPosition.xyz,
+                       vertexAxis,
+                       fyrox_cameraPosition,
+                       fyrox_cameraSideVector,
+                       fyrox_cameraUpVector
+                   ) * particleSize;
                    gl_Position = fyrox_viewProjectionMatrix * (worldPosition + vec4(offset.x, offset.y, offset.z, 0.0));
                }
                "#,
@@ -92,19 +104,18 @@
                out vec4 FragColor;
                in vec2 texCoord;
                in vec4 color;
-
-               float toProjSpace(float z)
-               {
-                   return (fyrox_zFar * fyrox_zNear) / (fyrox_zFar - z * (fyrox_zFar - fyrox_zNear));
-               }
+               flat in float softDistance;
 
                void main()
                {
-                   ivec2 depthTextureSize = textureSize(fyrox_sceneDepth, 0);
-                   vec2 pixelSize = vec2(1.0 / float(depthTextureSize.x), 1.0 / float(depthTextureSize.y));
-                   float sceneDepth = toProjSpace(texture(fyrox_sceneDepth, gl_FragCoord.xy * pixelSize).r);
-                   float fragmentDepth = toProjSpace(gl_FragCoord.z);
-                   float depthOpacity = smoothstep((sceneDepth - fragmentDepth) * softBoundarySharpnessFactor, 0.0, 1.0);
+                   float depthOpacity;
+                   if (softDistance > 0.0) {
+                       // Per-emitter fade distance.
+                       depthOpacity = S_SoftParticleFade(fyrox_sceneDepth, gl_FragCoord, fyrox_zNear, fyrox_zFar, softDistance);
+                   } else {
+                       // Fallback to the sharpness factor of the material.
+                       depthOpacity = S_SoftParticleFade(fyrox_sceneDepth, gl_FragCoord, fyrox_zNear, fyrox_zFar, 1.0 / max(softBoundarySharpnessFactor, 0.000001));
+                   }
                    FragColor = color * S_SRGBToLinear(texture(diffuseTexture, texCoord)).r;
                    FragColor.a *= depthOpacity;
                }
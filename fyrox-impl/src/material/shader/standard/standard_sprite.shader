@@ -47,14 +47,18 @@
                 layout(location = 1) in vec2 vertexTexCoord;
                 layout(location = 2) in vec2 vertexParams;
                 layout(location = 3) in vec4 vertexColor;
+                layout(location = 4) in vec3 vertexAxis;
+                layout(location = 5) in float vertexDepthFadeDistance;
 
                 uniform mat4 fyrox_viewProjectionMatrix;
                 uniform mat4 fyrox_worldMatrix;
                 uniform vec3 fyrox_cameraUpVector;
                 uniform vec3 fyrox_cameraSideVector;
+                uniform vec3 fyrox_cameraPosition;
 
                 out vec2 texCoord;
                 out vec4 color;
+                flat out float depthFadeDistance;
 
                 vec2 rotateVec2(vec2 v, float angle)
                 {
@@ -71,9 +75,17 @@
 
                     texCoord = vertexTexCoord;
                     color = vertexColor;
+                    depthFadeDistance = vertexDepthFadeDistance;
                     vec2 vertexOffset = rotateVec2(vertexTexCoord * 2.0 - 1.0, rotation);
                     vec4 worldPosition = fyrox_worldMatrix * vec4(vertexPosition, 1.0);
-                    vec3 offset = (vertexOffset.x * fyrox_cameraSideVector + vertexOffset.y * fyrox_cameraUpVector) * size;
+                    vec3 offset = S_BillboardOffset(
+                        vertexOffset,
+                        worldPosition.xyz,
+                        vertexAxis,
+                        fyrox_cameraPosition,
+                        fyrox_cameraSideVector,
+                        fyrox_cameraUpVector
+                    ) * size;
                     gl_Position = fyrox_viewProjectionMatrix * (worldPosition + vec4(offset.x, offset.y, offset.z, 0.0));
                 }
                "#,
@@ -82,14 +94,22 @@
                r#"
                 uniform sampler2D diffuseTexture;
 
+                uniform sampler2D fyrox_sceneDepth;
+                uniform float fyrox_zNear;
+                uniform float fyrox_zFar;
+
                 out vec4 FragColor;
 
                 in vec2 texCoord;
                 in vec4 color;
+                flat in float depthFadeDistance;
 
                 void main()
                 {
                     FragColor = color * S_SRGBToLinear(texture(diffuseTexture, texCoord));
+                    if (depthFadeDistance > 0.0) {
+                        FragColor.a *= S_SoftParticleFade(fyrox_sceneDepth, gl_FragCoord, fyrox_zNear, fyrox_zFar, depthFadeDistance);
+                    }
                 }
                "#,
         )
@@ -0,0 +1,105 @@
+(
+    name: "StandardVectorShapeShader",
+
+    properties: [
+        (
+            name: "sdfTexture",
+            kind: Sampler(default: None, fallback: Black),
+        ),
+    ],
+
+    passes: [
+        (
+            name: "Forward",
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: true,
+                blend: Some(BlendParameters(
+                    func: BlendFunc(
+                        sfactor: SrcAlpha,
+                        dfactor: OneMinusSrcAlpha,
+                        alpha_sfactor: SrcAlpha,
+                        alpha_dfactor: OneMinusSrcAlpha,
+                    ),
+                    equation: BlendEquation(
+                        rgb: Add,
+                        alpha: Add
+                    )
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+               r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+                layout(location = 2) in vec4 vertexFillColor;
+                layout(location = 3) in vec4 vertexOutlineColor;
+                layout(location = 4) in vec2 vertexParams;
+
+                uniform mat4 fyrox_viewProjectionMatrix;
+
+                out vec2 texCoord;
+                out vec4 fillColor;
+                out vec4 outlineColor;
+                flat out vec2 params;
+
+                void main()
+                {
+                    texCoord = vertexTexCoord;
+                    fillColor = S_SRGBToLinear(vertexFillColor);
+                    outlineColor = S_SRGBToLinear(vertexOutlineColor);
+                    params = vertexParams;
+                    gl_Position = fyrox_viewProjectionMatrix * vec4(vertexPosition, 1.0);
+                }
+               "#,
+
+           fragment_shader:
+               r#"
+                // Distances are encoded as `0.5 - distance / (2.0 * spread)`, so the edge of the shape
+                // is at 0.5 and the values above 0.5 are inside the shape.
+                uniform sampler2D sdfTexture;
+
+                out vec4 FragColor;
+
+                in vec2 texCoord;
+                in vec4 fillColor;
+                in vec4 outlineColor;
+                // x - spread of the distance field, y - outline thickness (both in local units).
+                flat in vec2 params;
+
+                void main()
+                {
+                    float spread = params.x;
+                    float outlineThickness = params.y;
+
+                    float distance = (0.5 - texture(sdfTexture, texCoord).r) * 2.0 * spread;
+                    // Width of the anti-aliased edge is one pixel at any scale.
+                    float edgeWidth = max(fwidth(distance), 0.00001);
+
+                    float fillCoverage = clamp(0.5 - distance / edgeWidth, 0.0, 1.0);
+                    float shapeCoverage = clamp(0.5 - (distance - outlineThickness) / edgeWidth, 0.0, 1.0);
+
+                    FragColor = mix(outlineColor, fillColor, fillCoverage);
+                    FragColor.a *= shapeCoverage;
+
+                    if (FragColor.a <= 0.0) {
+                        discard;
+                    }
+                }
+               "#,
+        )
+    ],
+)
@@ -138,9 +138,21 @@
                     float height = texture(heightMapTexture, actualTexCoords).r;
                     vec4 finalVertexPosition = vec4(vertexPosition.x, height, vertexPosition.z, 1.0);
 
-                    mat3 nm = mat3(fyrox_worldMatrix);
-                    normal = normalize(nm * vertexNormal);
-                    tangent = normalize(nm * vertexTangent.xyz);
+                    // Normals are calculated from the height map, so any modification of the height
+                    // map (including runtime deformation) is reflected in lighting immediately.
+                    vec2 texelSize = 1.0 / vec2(textureSize(heightMapTexture, 0));
+                    float left = texture(heightMapTexture, actualTexCoords - vec2(texelSize.x, 0.0)).r;
+                    float right = texture(heightMapTexture, actualTexCoords + vec2(texelSize.x, 0.0)).r;
+                    float back = texture(heightMapTexture, actualTexCoords - vec2(0.0, texelSize.y)).r;
+                    float front = texture(heightMapTexture, actualTexCoords + vec2(0.0, texelSize.y)).r;
+                    // Height derivatives in the local space of the node.
+                    float dx = (right - left) * nodeUvOffsets.z / (2.0 * texelSize.x);
+                    float dz = (front - back) * nodeUvOffsets.w / (2.0 * texelSize.y);
+
+                    mat3 worldMatrix = mat3(fyrox_worldMatrix);
+                    mat3 nm = transpose(inverse(worldMatrix));
+                    normal = normalize(nm * vec3(-dx, 1.0, -dz));
+                    tangent = normalize(worldMatrix * vec3(1.0, dx, 0.0));
                     binormal = normalize(vertexTangent.w * cross(normal, tangent));
                     texCoord = actualTexCoords;
                     position = vec3(fyrox_worldMatrix * finalVertexPosition);
@@ -177,6 +189,7 @@
                 // required data to these uniforms.
                 uniform vec3 fyrox_cameraPosition;
                 uniform bool fyrox_usePOM;
+                uniform vec4 fyrox_weather;
 
                 in vec3 position;
                 in vec3 normal;
@@ -207,10 +220,14 @@
                     outColor = diffuseColor * texture(diffuseTexture, tc);
 
                     vec4 n = normalize(texture(normalTexture, tc) * 2.0 - 1.0);
-                    outNormal = vec4(normalize(tangentSpace * n.xyz) * 0.5 + 0.5, 1.0);
+                    vec3 worldNormal = normalize(tangentSpace * n.xyz);
+                    outNormal = vec4(worldNormal * 0.5 + 0.5, 1.0);
+
+                    float roughness = texture(roughnessTexture, tc).r;
+                    S_ApplyWeather(outColor.rgb, roughness, worldNormal, fyrox_weather);
 
                     outMaterial.x = texture(metallicTexture, tc).r;
-                    outMaterial.y = texture(roughnessTexture, tc).r;
+                    outMaterial.y = roughness;
                     outMaterial.z = texture(aoTexture, tc).r;
                     outMaterial.a = 1.0;
 
@@ -0,0 +1,160 @@
+//! System clipboard access. The clipboard is shared between the game and other applications on
+//! desktop platforms. On WebAssembly and other platforms without clipboard access (or when the
+//! `platform_services` feature is disabled) the clipboard works as an in-process storage, so
+//! copy-paste still works inside the game.
+//!
+//! Note that the user interface has its own clipboard for text boxes (see
+//! [`crate::gui::UserInterface::clipboard`]), both of them use the same system clipboard.
+
+use crate::{core::parking_lot::Mutex, platform::PlatformError};
+use lazy_static::lazy_static;
+
+/// An image in the clipboard.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClipboardImage {
+    /// Width of the image in pixels.
+    pub width: usize,
+    /// Height of the image in pixels.
+    pub height: usize,
+    /// Pixels of the image in RGBA8 format, row by row from top to bottom.
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Default)]
+struct FallbackClipboard {
+    text: Option<String>,
+    image: Option<ClipboardImage>,
+}
+
+#[derive(Default)]
+struct ClipboardState {
+    // The system clipboard is created on first use. On some platforms (X11, for example) the content
+    // of the clipboard is served by the application, so the clipboard must be kept alive.
+    #[cfg(all(feature = "platform_services", not(target_arch = "wasm32")))]
+    system: Option<arboard::Clipboard>,
+    fallback: FallbackClipboard,
+}
+
+lazy_static! {
+    static ref CLIPBOARD: Mutex<ClipboardState> = Mutex::new(Default::default());
+}
+
+#[cfg(all(feature = "platform_services", not(target_arch = "wasm32")))]
+impl ClipboardState {
+    fn system(&mut self) -> Option<&mut arboard::Clipboard> {
+        if self.system.is_none() {
+            match arboard::Clipboard::new() {
+                Ok(clipboard) => self.system = Some(clipboard),
+                Err(err) => crate::core::log::Log::warn(format!(
+                    "System clipboard is unavailable, fallback clipboard will be used. Reason: {err}"
+                )),
+            }
+        }
+        self.system.as_mut()
+    }
+}
+
+#[cfg(all(feature = "platform_services", not(target_arch = "wasm32")))]
+fn convert_error(error: arboard::Error) -> PlatformError {
+    match error {
+        arboard::Error::ContentNotAvailable => PlatformError::ClipboardEmpty,
+        arboard::Error::ClipboardNotSupported => PlatformError::Unsupported,
+        _ => PlatformError::Clipboard(error.to_string()),
+    }
+}
+
+/// Returns the text, that is currently in the clipboard.
+pub fn text() -> Result<String, PlatformError> {
+    let state = &mut *CLIPBOARD.lock();
+
+    #[cfg(all(feature = "platform_services", not(target_arch = "wasm32")))]
+    if let Some(system) = state.system() {
+        return system.get_text().map_err(convert_error);
+    }
+
+    state
+        .fallback
+        .text
+        .clone()
+        .ok_or(PlatformError::ClipboardEmpty)
+}
+
+/// Puts the text in the clipboard.
+pub fn set_text<S: AsRef<str>>(text: S) -> Result<(), PlatformError> {
+    let mut state = CLIPBOARD.lock();
+
+    #[cfg(all(feature = "platform_services", not(target_arch = "wasm32")))]
+    if let Some(system) = state.system() {
+        return system
+            .set_text(text.as_ref().to_string())
+            .map_err(convert_error);
+    }
+
+    state.fallback.image = None;
+    state.fallback.text = Some(text.as_ref().to_string());
+    Ok(())
+}
+
+/// Returns the image, that is currently in the clipboard.
+pub fn image() -> Result<ClipboardImage, PlatformError> {
+    let state = &mut *CLIPBOARD.lock();
+
+    #[cfg(all(feature = "platform_services", not(target_arch = "wasm32")))]
+    if let Some(system) = state.system() {
+        return system
+            .get_image()
+            .map(|image| ClipboardImage {
+                width: image.width,
+                height: image.height,
+                bytes: image.bytes.into_owned(),
+            })
+            .map_err(convert_error);
+    }
+
+    state
+        .fallback
+        .image
+        .clone()
+        .ok_or(PlatformError::ClipboardEmpty)
+}
+
+/// Puts the image in the clipboard. The size of the pixel data must match the size of the image.
+pub fn set_image(image: ClipboardImage) -> Result<(), PlatformError> {
+    if image.bytes.len() != image.width * image.height * 4 {
+        return Err(PlatformError::Clipboard(format!(
+            "Image data size mismatch. Expected {} bytes, got {}.",
+            image.width * image.height * 4,
+            image.bytes.len()
+        )));
+    }
+
+    let mut state = CLIPBOARD.lock();
+
+    #[cfg(all(feature = "platform_services", not(target_arch = "wasm32")))]
+    if let Some(system) = state.system() {
+        return system
+            .set_image(arboard::ImageData {
+                width: image.width,
+                height: image.height,
+                bytes: image.bytes.into(),
+            })
+            .map_err(convert_error);
+    }
+
+    state.fallback.text = None;
+    state.fallback.image = Some(image);
+    Ok(())
+}
+
+/// Removes everything from the clipboard.
+pub fn clear() -> Result<(), PlatformError> {
+    let mut state = CLIPBOARD.lock();
+
+    #[cfg(all(feature = "platform_services", not(target_arch = "wasm32")))]
+    if let Some(system) = state.system() {
+        return system.clear().map_err(convert_error);
+    }
+
+    state.fallback = Default::default();
+    Ok(())
+}
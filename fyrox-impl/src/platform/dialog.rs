@@ -0,0 +1,263 @@
+//! Native file dialogs. See [`FileDialog`] docs for more info.
+
+use crate::platform::PlatformError;
+use std::path::PathBuf;
+
+/// A filter of a file dialog, only files with the given extensions will be shown.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileFilter {
+    /// Human-readable name of the filter (for example, "Images").
+    pub name: String,
+    /// A list of extensions without leading dot (for example, `["png", "jpg"]`).
+    pub extensions: Vec<String>,
+}
+
+/// A file picked by [`FileDialog::pick_file_data`].
+#[derive(Clone, Debug)]
+pub struct PickedFile {
+    /// Name of the file.
+    pub name: String,
+    /// Full path to the file. It is always `None` on WebAssembly, because browsers do not expose
+    /// paths of files.
+    pub path: Option<PathBuf>,
+    /// Content of the file.
+    pub data: Vec<u8>,
+}
+
+/// Native open/save file dialog. There are two sets of methods:
+///
+/// - Synchronous methods ([`Self::pick_file`], [`Self::save_file`], etc.) - they block until the user
+///   closes the dialog. They're available on desktop platforms only, on other platforms they return
+///   `None` immediately.
+/// - Asynchronous methods ([`Self::pick_file_data`]) - they return a future, that should be spawned
+///   in the task pool (see [`crate::engine::task::TaskPoolHandler`]). They're available on every
+///   platform, including WebAssembly.
+///
+/// Native dialogs require `platform_services` feature, without it every dialog behaves as if the
+/// user closed it.
+///
+/// ```rust,no_run
+/// # use fyrox_impl::{
+/// #     core::{reflect::prelude::*, visitor::prelude::*},
+/// #     platform::dialog::{FileDialog, PickedFile},
+/// #     plugin::{Plugin, PluginContext},
+/// # };
+/// # #[derive(Default, Debug, Visit, Reflect)]
+/// # struct MyPlugin {
+/// #     save_data: Vec<u8>,
+/// # }
+/// # impl Plugin for MyPlugin {}
+/// fn load_save_file(ctx: &mut PluginContext) {
+///     ctx.task_pool.spawn_plugin_task(
+///         FileDialog::new()
+///             .with_filter("Save Files", &["sav"])
+///             .pick_file_data(),
+///         |file: Option<PickedFile>, plugin: &mut MyPlugin, _ctx| {
+///             if let Some(file) = file {
+///                 plugin.save_data = file.data;
+///             }
+///         },
+///     );
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FileDialog {
+    title: Option<String>,
+    directory: Option<PathBuf>,
+    file_name: Option<String>,
+    filters: Vec<FileFilter>,
+}
+
+impl FileDialog {
+    /// Creates a new file dialog without filters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets desired title of the dialog.
+    pub fn with_title<S: AsRef<str>>(mut self, title: S) -> Self {
+        self.title = Some(title.as_ref().to_string());
+        self
+    }
+
+    /// Sets desired initial directory of the dialog.
+    pub fn with_directory<P: Into<PathBuf>>(mut self, directory: P) -> Self {
+        self.directory = Some(directory.into());
+        self
+    }
+
+    /// Sets desired initial file name of the dialog.
+    pub fn with_file_name<S: AsRef<str>>(mut self, file_name: S) -> Self {
+        self.file_name = Some(file_name.as_ref().to_string());
+        self
+    }
+
+    /// Adds a new filter to the dialog.
+    pub fn with_filter<S: AsRef<str>>(mut self, name: S, extensions: &[&str]) -> Self {
+        self.filters.push(FileFilter {
+            name: name.as_ref().to_string(),
+            extensions: extensions.iter().map(|e| e.to_string()).collect(),
+        });
+        self
+    }
+
+    /// Returns the filters of the dialog.
+    pub fn filters(&self) -> &[FileFilter] {
+        &self.filters
+    }
+
+    #[cfg(all(feature = "platform_services", not(target_arch = "wasm32")))]
+    fn native(self) -> rfd::FileDialog {
+        let mut dialog = rfd::FileDialog::new();
+        if let Some(title) = self.title {
+            dialog = dialog.set_title(title);
+        }
+        if let Some(directory) = self.directory {
+            dialog = dialog.set_directory(directory);
+        }
+        if let Some(file_name) = self.file_name {
+            dialog = dialog.set_file_name(file_name);
+        }
+        for filter in self.filters {
+            dialog = dialog.add_filter(filter.name, &filter.extensions);
+        }
+        dialog
+    }
+
+    #[cfg(feature = "platform_services")]
+    fn native_async(self) -> rfd::AsyncFileDialog {
+        let mut dialog = rfd::AsyncFileDialog::new();
+        if let Some(title) = self.title {
+            dialog = dialog.set_title(title);
+        }
+        if let Some(directory) = self.directory {
+            dialog = dialog.set_directory(directory);
+        }
+        if let Some(file_name) = self.file_name {
+            dialog = dialog.set_file_name(file_name);
+        }
+        for filter in self.filters {
+            dialog = dialog.add_filter(filter.name, &filter.extensions);
+        }
+        dialog
+    }
+
+    /// Shows the dialog and blocks until the user picks a file. Returns `None` if the user closed
+    /// the dialog or if the platform does not support synchronous dialogs.
+    pub fn pick_file(self) -> Option<PathBuf> {
+        #[cfg(all(feature = "platform_services", not(target_arch = "wasm32")))]
+        {
+            self.native().pick_file()
+        }
+
+        #[cfg(not(all(feature = "platform_services", not(target_arch = "wasm32"))))]
+        {
+            None
+        }
+    }
+
+    /// Shows the dialog and blocks until the user picks one or more files. Returns `None` if the user
+    /// closed the dialog or if the platform does not support synchronous dialogs.
+    pub fn pick_files(self) -> Option<Vec<PathBuf>> {
+        #[cfg(all(feature = "platform_services", not(target_arch = "wasm32")))]
+        {
+            self.native().pick_files()
+        }
+
+        #[cfg(not(all(feature = "platform_services", not(target_arch = "wasm32"))))]
+        {
+            None
+        }
+    }
+
+    /// Shows the dialog and blocks until the user picks a folder. Returns `None` if the user closed
+    /// the dialog or if the platform does not support synchronous dialogs.
+    pub fn pick_folder(self) -> Option<PathBuf> {
+        #[cfg(all(feature = "platform_services", not(target_arch = "wasm32")))]
+        {
+            self.native().pick_folder()
+        }
+
+        #[cfg(not(all(feature = "platform_services", not(target_arch = "wasm32"))))]
+        {
+            None
+        }
+    }
+
+    /// Shows save dialog and blocks until the user selects a path. Returns `None` if the user closed
+    /// the dialog or if the platform does not support synchronous dialogs.
+    pub fn save_file(self) -> Option<PathBuf> {
+        #[cfg(all(feature = "platform_services", not(target_arch = "wasm32")))]
+        {
+            self.native().save_file()
+        }
+
+        #[cfg(not(all(feature = "platform_services", not(target_arch = "wasm32"))))]
+        {
+            None
+        }
+    }
+
+    /// Shows the dialog asynchronously and reads the content of the picked file. The future resolves
+    /// to `None` if the user closed the dialog. Works on every platform, including WebAssembly.
+    pub fn pick_file_data(self) -> impl std::future::Future<Output = Option<PickedFile>> {
+        #[cfg(feature = "platform_services")]
+        let dialog = self.native_async();
+        #[cfg(not(feature = "platform_services"))]
+        let _ = self;
+
+        async move {
+            #[cfg(feature = "platform_services")]
+            {
+                let handle = dialog.pick_file().await?;
+
+                #[cfg(not(target_arch = "wasm32"))]
+                let path = Some(handle.path().to_path_buf());
+                #[cfg(target_arch = "wasm32")]
+                let path = None;
+
+                Some(PickedFile {
+                    name: handle.file_name(),
+                    path,
+                    data: handle.read().await,
+                })
+            }
+
+            #[cfg(not(feature = "platform_services"))]
+            {
+                None
+            }
+        }
+    }
+
+    /// Shows save dialog asynchronously and writes the data to the selected file. Returns `Ok(false)`
+    /// if the user closed the dialog. It is not supported on WebAssembly.
+    pub fn save_file_data(
+        self,
+        data: Vec<u8>,
+    ) -> impl std::future::Future<Output = Result<bool, PlatformError>> {
+        #[cfg(all(feature = "platform_services", not(target_arch = "wasm32")))]
+        let dialog = self.native_async();
+        #[cfg(not(all(feature = "platform_services", not(target_arch = "wasm32"))))]
+        let _ = self;
+
+        async move {
+            #[cfg(all(feature = "platform_services", not(target_arch = "wasm32")))]
+            {
+                match dialog.save_file().await {
+                    Some(handle) => {
+                        handle.write(&data).await?;
+                        Ok(true)
+                    }
+                    None => Ok(false),
+                }
+            }
+
+            #[cfg(not(all(feature = "platform_services", not(target_arch = "wasm32"))))]
+            {
+                let _ = data;
+                Err(PlatformError::Unsupported)
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Platform services - clipboard, native file dialogs, opening URLs, revealing files in a file
+//! manager and integration with the shell of the OS (see [`shell::PlatformManager`]). All the services are available from scripts, plugins and editor plugins, so there's no
+//! need to pull separate crates for each of them (which could also bring their own event loops that
+//! conflict with the engine's one).
+//!
+//! Native implementations of the services are enabled by `platform_services` feature, it is
+//! disabled by default to keep the dependency tree of games small. Every service has a fallback for
+//! platforms, that do not support it (or when the feature is disabled). For example, WebAssembly has
+//! no access to the system clipboard without user interaction, so the clipboard works as an
+//! in-process storage there, and synchronous file dialogs are replaced with asynchronous ones (see
+//! [`dialog::FileDialog::pick_file_data`]). Services, that cannot be emulated, return
+//! [`PlatformError::Unsupported`].
+//!
+//! ```rust,no_run
+//! # use fyrox_impl::platform::{self, clipboard, dialog::FileDialog};
+//! fn export_report(report: &str) {
+//!     clipboard::set_text(report).unwrap();
+//!
+//!     if let Some(path) = FileDialog::new()
+//!         .with_title("Save Report")
+//!         .with_filter("Text", &["txt"])
+//!         .save_file()
+//!     {
+//!         std::fs::write(&path, report).unwrap();
+//!         platform::reveal_path(&path).unwrap();
+//!     }
+//! }
+//! ```
+
+use std::{
+    fmt::{Display, Formatter},
+    path::Path,
+};
+
+pub mod clipboard;
+pub mod dialog;
+pub mod shell;
+
+/// An error, that may occur when using platform services.
+#[derive(Debug)]
+pub enum PlatformError {
+    /// The service is not supported on the current platform.
+    Unsupported,
+    /// The clipboard does not contain the requested data.
+    ClipboardEmpty,
+    /// Platform-specific clipboard error.
+    Clipboard(String),
+    /// An I/O error, for example when an external application failed to start.
+    Io(std::io::Error),
+    /// The platform has reported an error.
+    Os(String),
+}
+
+impl Display for PlatformError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlatformError::Unsupported => {
+                write!(f, "The service is not supported on the current platform.")
+            }
+            PlatformError::ClipboardEmpty => {
+                write!(f, "The clipboard does not contain the requested data.")
+            }
+            PlatformError::Clipboard(v) => write!(f, "Clipboard error: {v}"),
+            PlatformError::Io(v) => write!(f, "I/O error: {v}"),
+            PlatformError::Os(v) => write!(f, "Platform error: {v}"),
+        }
+    }
+}
+
+impl std::error::Error for PlatformError {}
+
+impl From<std::io::Error> for PlatformError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Opens the given URL in the default web browser. On WebAssembly the URL is opened in a new tab.
+pub fn open_url(url: &str) -> Result<(), PlatformError> {
+    #[cfg(all(feature = "platform_services", not(target_arch = "wasm32")))]
+    {
+        open::that(url)?;
+        Ok(())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        crate::core::web_sys::window()
+            .and_then(|window| window.open_with_url(url).ok())
+            .map(|_| ())
+            .ok_or(PlatformError::Unsupported)
+    }
+
+    #[cfg(all(not(feature = "platform_services"), not(target_arch = "wasm32")))]
+    {
+        let _ = url;
+        Err(PlatformError::Unsupported)
+    }
+}
+
+/// Opens the given file or directory using the default application (for example, opens a text
+/// file in the text editor, or a directory in the file manager).
+pub fn open_path<P: AsRef<Path>>(path: P) -> Result<(), PlatformError> {
+    #[cfg(all(feature = "platform_services", not(target_arch = "wasm32")))]
+    {
+        open::that(path.as_ref().canonicalize()?)?;
+        Ok(())
+    }
+
+    #[cfg(not(all(feature = "platform_services", not(target_arch = "wasm32"))))]
+    {
+        let _ = path;
+        Err(PlatformError::Unsupported)
+    }
+}
+
+/// Opens the file manager and selects the given file or directory in it. On platforms, where the
+/// file manager cannot select a file (most of Linux file managers), the parent directory of the file
+/// is opened.
+pub fn reveal_path<P: AsRef<Path>>(path: P) -> Result<(), PlatformError> {
+    #[cfg(all(feature = "platform_services", not(target_arch = "wasm32")))]
+    {
+        use std::process::Command;
+
+        let path = path.as_ref().canonicalize()?;
+
+        if cfg!(target_os = "windows") {
+            // Explorer returns non-zero exit code even on success, so the status is ignored.
+            Command::new("explorer")
+                .arg("/select,")
+                .arg(&path)
+                .spawn()?
+                .wait()?;
+        } else if cfg!(target_os = "macos") {
+            Command::new("open").arg("-R").arg(&path).spawn()?.wait()?;
+        } else {
+            let directory = if path.is_dir() {
+                path.as_path()
+            } else {
+                path.parent().unwrap_or(path.as_path())
+            };
+            open_path(directory)?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(all(feature = "platform_services", not(target_arch = "wasm32"))))]
+    {
+        let _ = path;
+        Err(PlatformError::Unsupported)
+    }
+}
@@ -0,0 +1,671 @@
+//! Integration with the shell of the OS - window icon, taskbar progress and OS notifications. It
+//! allows a game to use these features without any platform-specific code. See [`PlatformManager`]
+//! docs for more info and usage examples.
+
+use crate::{
+    core::{log::Log, parking_lot::Mutex},
+    platform::PlatformError,
+    resource::texture::{Texture, TextureKind, TexturePixelKind, TextureResource},
+    window::{Icon, Window},
+};
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+use std::sync::Arc;
+
+/// State of a progress indicator on the taskbar button of the window.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum TaskbarProgress {
+    /// No progress indicator.
+    #[default]
+    None,
+    /// Progress indicator without a particular value ("marquee").
+    Indeterminate,
+    /// Normal progress, the value is in `[0; 1]` range.
+    Normal(f32),
+    /// Paused progress, the value is in `[0; 1]` range.
+    Paused(f32),
+    /// Progress of a failed operation, the value is in `[0; 1]` range.
+    Error(f32),
+}
+
+/// A simple OS notification.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Notification {
+    /// Title of the notification.
+    pub title: String,
+    /// Text of the notification.
+    pub body: String,
+}
+
+impl Notification {
+    /// Creates new notification with the given title and text.
+    pub fn new(title: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            body: body.into(),
+        }
+    }
+}
+
+/// Platform backend is an interface to the shell of a particular platform.
+pub trait PlatformBackend: Send + 'static {
+    /// Shows the given progress on the taskbar button of the window.
+    fn set_taskbar_progress(
+        &mut self,
+        window: RawWindowHandle,
+        progress: TaskbarProgress,
+    ) -> Result<(), PlatformError>;
+
+    /// Shows the given notification.
+    fn notify(
+        &mut self,
+        window: RawWindowHandle,
+        notification: &Notification,
+    ) -> Result<(), PlatformError>;
+}
+
+/// Default platform backend. Taskbar progress is supported on Windows only. Notifications are shown
+/// using the tools available in the OS - toast notifications on Windows (via PowerShell),
+/// `osascript` on macOS, `notify-send` (libnotify) on Linux and BSD. Other platforms do not support
+/// any of the features. Native implementations require `platform_services` feature, see
+/// [`crate::platform`] docs.
+#[derive(Default)]
+pub struct DefaultPlatformBackend {
+    #[cfg(all(feature = "platform_services", windows))]
+    taskbar: Option<windows::TaskbarList>,
+}
+
+impl PlatformBackend for DefaultPlatformBackend {
+    #[cfg(all(feature = "platform_services", windows))]
+    fn set_taskbar_progress(
+        &mut self,
+        window: RawWindowHandle,
+        progress: TaskbarProgress,
+    ) -> Result<(), PlatformError> {
+        let RawWindowHandle::Win32(handle) = window else {
+            return Err(PlatformError::Unsupported);
+        };
+        if self.taskbar.is_none() {
+            self.taskbar = Some(windows::TaskbarList::new()?);
+        }
+        match self.taskbar.as_ref() {
+            Some(taskbar) => taskbar.set_progress(handle.hwnd, progress),
+            None => Err(PlatformError::Unsupported),
+        }
+    }
+
+    #[cfg(not(all(feature = "platform_services", windows)))]
+    fn set_taskbar_progress(
+        &mut self,
+        _window: RawWindowHandle,
+        _progress: TaskbarProgress,
+    ) -> Result<(), PlatformError> {
+        Err(PlatformError::Unsupported)
+    }
+
+    fn notify(
+        &mut self,
+        _window: RawWindowHandle,
+        notification: &Notification,
+    ) -> Result<(), PlatformError> {
+        #[cfg(all(feature = "platform_services", windows))]
+        {
+            use std::os::windows::process::CommandExt;
+
+            const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+            // The text is passed via environment variables, so it is never parsed as a part of
+            // the script, whatever quotes it contains.
+            const SCRIPT: &str = "[Windows.UI.Notifications.ToastNotificationManager, \
+                Windows.UI.Notifications, ContentType = WindowsRuntime] > $null;\
+                $template = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent(\
+                [Windows.UI.Notifications.ToastTemplateType]::ToastText02);\
+                $text = $template.GetElementsByTagName('text');\
+                $text.Item(0).AppendChild($template.CreateTextNode($env:FYROX_NOTIFICATION_TITLE)) > $null;\
+                $text.Item(1).AppendChild($template.CreateTextNode($env:FYROX_NOTIFICATION_BODY)) > $null;\
+                $toast = [Windows.UI.Notifications.ToastNotification]::new($template);\
+                [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier(\
+                '{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\\WindowsPowerShell\\v1.0\\powershell.exe')\
+                .Show($toast);";
+
+            spawn_detached(
+                std::process::Command::new("powershell")
+                    .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+                    .env("FYROX_NOTIFICATION_TITLE", &notification.title)
+                    .env("FYROX_NOTIFICATION_BODY", &notification.body)
+                    .creation_flags(CREATE_NO_WINDOW),
+            )
+        }
+        #[cfg(all(feature = "platform_services", target_os = "macos"))]
+        {
+            let script = format!(
+                "display notification {} with title {}",
+                apple_script_string(&notification.body),
+                apple_script_string(&notification.title)
+            );
+            spawn_detached(std::process::Command::new("osascript").args(["-e", &script]))
+        }
+        #[cfg(all(
+            feature = "platform_services",
+            unix,
+            not(target_os = "macos"),
+            not(target_os = "ios"),
+            not(target_os = "android")
+        ))]
+        {
+            spawn_detached(
+                std::process::Command::new("notify-send")
+                    .arg("--")
+                    .arg(&notification.title)
+                    .arg(&notification.body),
+            )
+        }
+        #[cfg(not(all(
+            feature = "platform_services",
+            any(windows, all(unix, not(target_os = "ios"), not(target_os = "android")))
+        )))]
+        {
+            let _ = notification;
+            Err(PlatformError::Unsupported)
+        }
+    }
+}
+
+#[cfg(all(
+    feature = "platform_services",
+    any(windows, all(unix, not(target_os = "ios"), not(target_os = "android")))
+))]
+fn spawn_detached(command: &mut std::process::Command) -> Result<(), PlatformError> {
+    let mut child = command
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+    // Reap the process in background, notification tools may run for a while.
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+#[cfg(all(feature = "platform_services", target_os = "macos"))]
+fn apple_script_string(str: &str) -> String {
+    format!("\"{}\"", str.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(all(feature = "platform_services", windows))]
+mod windows {
+    //! Minimal bindings to `ITaskbarList3` COM interface.
+
+    use super::TaskbarProgress;
+    use crate::platform::PlatformError;
+    use std::ffi::c_void;
+
+    type HResult = i32;
+
+    #[repr(C)]
+    struct Guid {
+        data1: u32,
+        data2: u16,
+        data3: u16,
+        data4: [u8; 8],
+    }
+
+    const CLSID_TASKBAR_LIST: Guid = Guid {
+        data1: 0x56fdf344,
+        data2: 0xfd6d,
+        data3: 0x11d0,
+        data4: [0x95, 0x8a, 0x00, 0x60, 0x97, 0xc9, 0xa0, 0x90],
+    };
+
+    const IID_ITASKBAR_LIST3: Guid = Guid {
+        data1: 0xea1afb91,
+        data2: 0x9e28,
+        data3: 0x4b86,
+        data4: [0x90, 0xe9, 0x9e, 0x9f, 0x8a, 0x5e, 0xef, 0xaf],
+    };
+
+    const CLSCTX_INPROC_SERVER: u32 = 0x1;
+    const COINIT_APARTMENTTHREADED: u32 = 0x2;
+
+    const TBPF_NOPROGRESS: u32 = 0x0;
+    const TBPF_INDETERMINATE: u32 = 0x1;
+    const TBPF_NORMAL: u32 = 0x2;
+    const TBPF_ERROR: u32 = 0x4;
+    const TBPF_PAUSED: u32 = 0x8;
+
+    const PROGRESS_RANGE: u64 = 10000;
+
+    #[link(name = "ole32")]
+    extern "system" {
+        fn CoInitializeEx(reserved: *mut c_void, co_init: u32) -> HResult;
+        fn CoCreateInstance(
+            clsid: *const Guid,
+            outer: *mut c_void,
+            context: u32,
+            iid: *const Guid,
+            out: *mut *mut c_void,
+        ) -> HResult;
+    }
+
+    // Methods that are not used are declared as pointer-sized placeholders to keep the layout.
+    #[repr(C)]
+    struct TaskbarList3Vtbl {
+        query_interface: usize,
+        add_ref: usize,
+        release: unsafe extern "system" fn(this: *mut TaskbarList3) -> u32,
+        hr_init: unsafe extern "system" fn(this: *mut TaskbarList3) -> HResult,
+        add_tab: usize,
+        delete_tab: usize,
+        activate_tab: usize,
+        set_active_alt: usize,
+        mark_fullscreen_window: usize,
+        set_progress_value: unsafe extern "system" fn(
+            this: *mut TaskbarList3,
+            hwnd: *mut c_void,
+            completed: u64,
+            total: u64,
+        ) -> HResult,
+        set_progress_state: unsafe extern "system" fn(
+            this: *mut TaskbarList3,
+            hwnd: *mut c_void,
+            flags: u32,
+        ) -> HResult,
+    }
+
+    #[repr(C)]
+    struct TaskbarList3 {
+        vtbl: *const TaskbarList3Vtbl,
+    }
+
+    pub struct TaskbarList {
+        ptr: *mut TaskbarList3,
+    }
+
+    // The object is created and used only on the main thread, where the engine updates the
+    // platform manager.
+    unsafe impl Send for TaskbarList {}
+
+    fn check(result: HResult) -> Result<(), PlatformError> {
+        if result < 0 {
+            Err(PlatformError::Os(format!(
+                "HRESULT 0x{:08x}",
+                result as u32
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    impl TaskbarList {
+        pub fn new() -> Result<Self, PlatformError> {
+            unsafe {
+                // COM could be already initialized on the thread by the windowing library in a
+                // different mode, this is fine for the taskbar list.
+                CoInitializeEx(std::ptr::null_mut(), COINIT_APARTMENTTHREADED);
+
+                let mut ptr = std::ptr::null_mut();
+                check(CoCreateInstance(
+                    &CLSID_TASKBAR_LIST,
+                    std::ptr::null_mut(),
+                    CLSCTX_INPROC_SERVER,
+                    &IID_ITASKBAR_LIST3,
+                    &mut ptr,
+                ))?;
+                let list = Self {
+                    ptr: ptr as *mut TaskbarList3,
+                };
+                check(((*(*list.ptr).vtbl).hr_init)(list.ptr))?;
+                Ok(list)
+            }
+        }
+
+        pub fn set_progress(
+            &self,
+            hwnd: *mut c_void,
+            progress: TaskbarProgress,
+        ) -> Result<(), PlatformError> {
+            let (flags, value) = match progress {
+                TaskbarProgress::None => (TBPF_NOPROGRESS, None),
+                TaskbarProgress::Indeterminate => (TBPF_INDETERMINATE, None),
+                TaskbarProgress::Normal(v) => (TBPF_NORMAL, Some(v)),
+                TaskbarProgress::Paused(v) => (TBPF_PAUSED, Some(v)),
+                TaskbarProgress::Error(v) => (TBPF_ERROR, Some(v)),
+            };
+            unsafe {
+                let vtbl = &*(*self.ptr).vtbl;
+                check((vtbl.set_progress_state)(self.ptr, hwnd, flags))?;
+                if let Some(value) = value {
+                    let completed = (value.clamp(0.0, 1.0) * PROGRESS_RANGE as f32) as u64;
+                    check((vtbl.set_progress_value)(
+                        self.ptr,
+                        hwnd,
+                        completed,
+                        PROGRESS_RANGE,
+                    ))?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl Drop for TaskbarList {
+        fn drop(&mut self) {
+            unsafe {
+                ((*(*self.ptr).vtbl).release)(self.ptr);
+            }
+        }
+    }
+}
+
+/// Converts the first mip level of a rectangular texture to RGBA8 pixels, that could be used as an
+/// icon. Returns `None` if the texture has unsupported kind or pixel format.
+fn icon_pixels(texture: &Texture) -> Option<(Vec<u8>, u32, u32)> {
+    let TextureKind::Rectangle { width, height } = texture.kind() else {
+        return None;
+    };
+    let data = texture.mip_level_data(0);
+    let pixels = match texture.pixel_kind() {
+        TexturePixelKind::RGBA8 => data.to_vec(),
+        TexturePixelKind::BGRA8 => data
+            .chunks_exact(4)
+            .flat_map(|p| [p[2], p[1], p[0], p[3]])
+            .collect(),
+        TexturePixelKind::RGB8 => data
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        TexturePixelKind::BGR8 => data
+            .chunks_exact(3)
+            .flat_map(|p| [p[2], p[1], p[0], 255])
+            .collect(),
+        TexturePixelKind::R8 => data.iter().flat_map(|v| [*v, *v, *v, 255]).collect(),
+        TexturePixelKind::RG8 => data
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        _ => return None,
+    };
+    Some((pixels, width, height))
+}
+
+struct PlatformState {
+    backend: Option<Box<dyn PlatformBackend>>,
+    icon: Option<TextureResource>,
+    icon_dirty: bool,
+    progress: TaskbarProgress,
+    progress_dirty: bool,
+    progress_supported: bool,
+    notifications: Vec<Notification>,
+}
+
+impl PlatformState {
+    fn flush(&mut self, window: RawWindowHandle) {
+        let Some(backend) = self.backend.as_mut() else {
+            self.notifications.clear();
+            return;
+        };
+
+        if self.progress_dirty && self.progress_supported {
+            self.progress_dirty = false;
+            match backend.set_taskbar_progress(window, self.progress) {
+                Ok(()) => (),
+                Err(PlatformError::Unsupported) => {
+                    // Do not try again, progress may change every frame.
+                    self.progress_supported = false;
+                    Log::warn("Taskbar progress is not supported on this platform.");
+                }
+                Err(err) => Log::warn(format!("Unable to set taskbar progress. Reason: {err}")),
+            }
+        }
+
+        for notification in self.notifications.drain(..) {
+            if let Err(err) = backend.notify(window, &notification) {
+                Log::warn(format!("Unable to show a notification. Reason: {err}"));
+            }
+        }
+    }
+
+    fn update_icon(&mut self, window: &Window) {
+        if !self.icon_dirty {
+            return;
+        }
+
+        let Some(texture) = self.icon.as_ref() else {
+            window.set_window_icon(None);
+            self.icon_dirty = false;
+            return;
+        };
+
+        if texture.is_loading() {
+            return;
+        }
+        self.icon_dirty = false;
+
+        let mut state = texture.state();
+        let Some(data) = state.data() else {
+            Log::err("Unable to set window icon, because its texture has failed to load.");
+            return;
+        };
+        match icon_pixels(data)
+            .map(|(pixels, width, height)| Icon::from_rgba(pixels, width, height))
+        {
+            Some(Ok(icon)) => window.set_window_icon(Some(icon)),
+            Some(Err(err)) => Log::err(format!("Unable to set window icon. Reason: {err}")),
+            None => Log::err(
+                "Unable to set window icon. Only rectangular textures with 8-bit RGB(A), BGR(A), \
+                R or RG pixels could be used as icons.",
+            ),
+        }
+    }
+}
+
+/// Platform manager is a shared handle, that integrates a game with the shell of the OS: it sets the
+/// icon of the main window, shows progress on its taskbar button and shows OS notifications. The
+/// actual work is done by a [`PlatformBackend`], [`DefaultPlatformBackend`] is used by default; it
+/// could be replaced with a custom one (for example, to use a store-specific notification API).
+///
+/// Every request is applied by the engine when it updates the manager (right after user interfaces),
+/// so the manager can be cloned and used anywhere (in a plugin or a script, for example). The window
+/// icon is set as soon as its texture is loaded. Features, that are not supported by the platform,
+/// are ignored (with a warning in the log), so a game does not need any platform-specific code.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{reflect::prelude::*, visitor::prelude::*},
+/// #     platform::shell::{Notification, TaskbarProgress},
+/// #     plugin::{Plugin, PluginContext},
+/// #     resource::texture::Texture,
+/// # };
+/// #[derive(Visit, Reflect, Debug, Default)]
+/// struct Game {
+///     // Progress of a long operation, for example downloading of an update.
+///     download_progress: Option<f32>,
+/// }
+///
+/// impl Plugin for Game {
+///     fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+///         let icon = context.resource_manager.request::<Texture>("data/icon.png");
+///         context.platform.set_window_icon(Some(icon));
+///     }
+///
+///     fn update(&mut self, context: &mut PluginContext) {
+///         context
+///             .platform
+///             .set_taskbar_progress(match self.download_progress {
+///                 Some(progress) => TaskbarProgress::Normal(progress),
+///                 None => TaskbarProgress::None,
+///             });
+///     }
+/// }
+///
+/// fn on_match_found(context: &PluginContext) {
+///     context
+///         .platform
+///         .notify(Notification::new("Match found", "Your match is about to start!"));
+/// }
+/// ```
+#[derive(Clone)]
+pub struct PlatformManager {
+    state: Arc<Mutex<PlatformState>>,
+}
+
+impl Default for PlatformManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlatformManager {
+    /// Creates new platform manager with [`DefaultPlatformBackend`].
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(PlatformState {
+                backend: Some(Box::<DefaultPlatformBackend>::default()),
+                icon: None,
+                icon_dirty: false,
+                progress: TaskbarProgress::None,
+                progress_dirty: false,
+                progress_supported: true,
+                notifications: Default::default(),
+            })),
+        }
+    }
+
+    /// Sets new backend. Current taskbar progress will be sent to the new backend on next update.
+    pub fn set_backend<B: PlatformBackend>(&self, backend: B) {
+        let mut state = self.state.lock();
+        state.backend = Some(Box::new(backend));
+        state.progress_supported = true;
+        state.progress_dirty = state.progress != TaskbarProgress::None;
+    }
+
+    /// Removes current backend (if any). Taskbar progress and notifications will be ignored.
+    pub fn remove_backend(&self) {
+        self.state.lock().backend = None;
+    }
+
+    /// Sets new icon of the main window. `None` restores the default icon. Only rectangular
+    /// textures with 8-bit RGB(A), BGR(A), R or RG pixels are supported. Icons are not supported on
+    /// Web and Android.
+    pub fn set_window_icon(&self, icon: Option<TextureResource>) {
+        let mut state = self.state.lock();
+        if state.icon != icon {
+            state.icon = icon;
+            state.icon_dirty = true;
+        }
+    }
+
+    /// Returns current icon of the main window.
+    pub fn window_icon(&self) -> Option<TextureResource> {
+        self.state.lock().icon.clone()
+    }
+
+    /// Sets new progress on the taskbar button of the main window. Setting the same progress
+    /// multiple times does nothing.
+    pub fn set_taskbar_progress(&self, progress: TaskbarProgress) {
+        let mut state = self.state.lock();
+        if state.progress != progress {
+            state.progress = progress;
+            state.progress_dirty = true;
+        }
+    }
+
+    /// Returns current progress on the taskbar button of the main window.
+    pub fn taskbar_progress(&self) -> TaskbarProgress {
+        self.state.lock().progress
+    }
+
+    /// Shows the given notification on next update.
+    pub fn notify(&self, notification: Notification) {
+        self.state.lock().notifications.push(notification);
+    }
+
+    /// Applies all pending changes to the given window. It is called by the engine automatically.
+    pub(crate) fn update(&self, window: &Window) {
+        let mut state = self.state.lock();
+        state.update_icon(window);
+        state.flush(window.raw_window_handle());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use raw_window_handle::WebWindowHandle;
+
+    #[derive(Default)]
+    struct MockBackend {
+        progress: Arc<Mutex<Vec<TaskbarProgress>>>,
+        notifications: Arc<Mutex<Vec<Notification>>>,
+    }
+
+    impl PlatformBackend for MockBackend {
+        fn set_taskbar_progress(
+            &mut self,
+            _window: RawWindowHandle,
+            progress: TaskbarProgress,
+        ) -> Result<(), PlatformError> {
+            self.progress.lock().push(progress);
+            Ok(())
+        }
+
+        fn notify(
+            &mut self,
+            _window: RawWindowHandle,
+            notification: &Notification,
+        ) -> Result<(), PlatformError> {
+            self.notifications.lock().push(notification.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_requests() {
+        let window = RawWindowHandle::Web(WebWindowHandle::empty());
+        let backend = MockBackend::default();
+        let (progress, notifications) = (backend.progress.clone(), backend.notifications.clone());
+        let manager = PlatformManager::new();
+        manager.set_backend(backend);
+
+        manager.set_taskbar_progress(TaskbarProgress::Normal(0.5));
+        manager.notify(Notification::new("Title", "Body"));
+        manager.state.lock().flush(window);
+        assert_eq!(*progress.lock(), [TaskbarProgress::Normal(0.5)]);
+        assert_eq!(*notifications.lock(), [Notification::new("Title", "Body")]);
+
+        // Same progress must not be sent again, notifications must be sent only once.
+        manager.set_taskbar_progress(TaskbarProgress::Normal(0.5));
+        manager.state.lock().flush(window);
+        assert_eq!(progress.lock().len(), 1);
+        assert_eq!(notifications.lock().len(), 1);
+
+        manager.set_taskbar_progress(TaskbarProgress::None);
+        manager.state.lock().flush(window);
+        assert_eq!(progress.lock().last(), Some(&TaskbarProgress::None));
+    }
+
+    #[test]
+    fn test_icon_pixels() {
+        let texture = Texture::from_bytes(
+            TextureKind::Rectangle {
+                width: 2,
+                height: 1,
+            },
+            TexturePixelKind::BGR8,
+            vec![1, 2, 3, 4, 5, 6],
+        )
+        .unwrap();
+        assert_eq!(
+            icon_pixels(&texture),
+            Some((vec![3, 2, 1, 255, 6, 5, 4, 255], 2, 1))
+        );
+
+        let texture = Texture::from_bytes(
+            TextureKind::Line { length: 2 },
+            TexturePixelKind::RGBA8,
+            vec![0; 8],
+        )
+        .unwrap();
+        assert_eq!(icon_pixels(&texture), None);
+    }
+}
@@ -11,14 +11,15 @@ use crate::{
         visitor::VisitError,
     },
     engine::{
-        task::TaskPoolHandler, AsyncSceneLoader, GraphicsContext, PerformanceStatistics,
-        ScriptProcessor, SerializationContext,
+        cursor::CursorManager, presence::PresenceManager, task::TaskPoolHandler, AsyncSceneLoader,
+        GraphicsContext, PerformanceStatistics, ScriptProcessor, SerializationContext,
     },
     event::Event,
     gui::{
         constructor::WidgetConstructorContainer,
         inspector::editors::PropertyEditorDefinitionContainer, message::UiMessage, UiContainer,
     },
+    platform::shell::PlatformManager,
     plugin::dynamic::DynamicPlugin,
     scene::{Scene, SceneContainer},
 };
@@ -182,6 +183,18 @@ pub struct PluginContext<'a, 'b> {
 
     /// Task pool for asynchronous task management.
     pub task_pool: &'a mut TaskPoolHandler,
+
+    /// Platform presence manager, that could be used to tell a platform (Discord, for example) what the
+    /// player is currently doing. See [`PresenceManager`] docs for more info.
+    pub presence: &'a PresenceManager,
+
+    /// Mouse cursor manager, that could be used to change the appearance of the cursor or to lock it
+    /// in the window. See [`CursorManager`] docs for more info.
+    pub cursor: &'a CursorManager,
+
+    /// Desktop platform integration, that could be used to set the window icon, to show progress on
+    /// the taskbar and to show OS notifications. See [`PlatformManager`] docs for more info.
+    pub platform: &'a PlatformManager,
 }
 
 /// Base plugin automatically implements type casting for plugins.
@@ -0,0 +1,141 @@
+//! Applies baked ambient occlusion of ambient occlusion volumes (see
+//! [`crate::scene::ao_volume::AoVolume`]) to the ambient lighting in the deferred lighting pass.
+
+use crate::{
+    core::{math::Rect, scope_profile, sstorage::ImmutableString},
+    renderer::{
+        cache::texture::TextureCache,
+        framework::{
+            error::FrameworkError,
+            framebuffer::{BlendParameters, FrameBuffer},
+            fullscreen::{FullscreenPass, FULLSCREEN_VERTEX_SHADER},
+            geometry_buffer::GeometryBuffer,
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::{Coordinate, WrapMode},
+            state::{BlendFactor, BlendFunc, PipelineState},
+        },
+        gbuffer::GBuffer,
+        RenderPassStatistics,
+    },
+    scene::{ao_volume::AoVolume, camera::Camera, graph::Graph},
+};
+
+struct AoVolumeShader {
+    program: GpuProgram,
+    depth_texture: UniformLocation,
+    normal_texture: UniformLocation,
+    ao_texture: UniformLocation,
+    inv_view_proj: UniformLocation,
+    inv_volume_transform: UniformLocation,
+    intensity: UniformLocation,
+    normal_bias: UniformLocation,
+}
+
+impl AoVolumeShader {
+    fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/ao_volume_fs.glsl");
+        let program = GpuProgram::from_source(
+            state,
+            "AoVolumeShader",
+            FULLSCREEN_VERTEX_SHADER,
+            fragment_source,
+        )?;
+        Ok(Self {
+            depth_texture: program
+                .uniform_location(state, &ImmutableString::new("depthTexture"))?,
+            normal_texture: program
+                .uniform_location(state, &ImmutableString::new("normalTexture"))?,
+            ao_texture: program.uniform_location(state, &ImmutableString::new("aoTexture"))?,
+            inv_view_proj: program.uniform_location(state, &ImmutableString::new("invViewProj"))?,
+            inv_volume_transform: program
+                .uniform_location(state, &ImmutableString::new("invVolumeTransform"))?,
+            intensity: program.uniform_location(state, &ImmutableString::new("intensity"))?,
+            normal_bias: program.uniform_location(state, &ImmutableString::new("normalBias"))?,
+            program,
+        })
+    }
+}
+
+pub(crate) struct AoVolumeRenderer {
+    shader: AoVolumeShader,
+}
+
+impl AoVolumeRenderer {
+    pub(crate) fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: AoVolumeShader::new(state)?,
+        })
+    }
+
+    /// Darkens the contents of the frame buffer by the baked occlusion of every enabled volume. Must
+    /// be called right after the ambient light pass, so only the ambient lighting is affected.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn render(
+        &self,
+        state: &PipelineState,
+        quad: &GeometryBuffer,
+        frame_buffer: &mut FrameBuffer,
+        viewport: Rect<i32>,
+        gbuffer: &GBuffer,
+        camera: &Camera,
+        graph: &Graph,
+        textures: &mut TextureCache,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!();
+
+        let mut stats = RenderPassStatistics::default();
+
+        let pass = FullscreenPass::new(state, quad);
+        let inv_view_projection = camera
+            .view_projection_matrix()
+            .try_inverse()
+            .unwrap_or_default();
+        let depth = gbuffer.depth();
+        let normal = gbuffer.normal_texture();
+
+        let shader = &self.shader;
+        for volume in graph.linear_iter().filter_map(|n| n.cast::<AoVolume>()) {
+            if !volume.global_visibility() || !volume.is_globally_enabled() {
+                continue;
+            }
+            let Some(baked) = volume.baked() else {
+                continue;
+            };
+            let Some(texture) = textures.get(state, baked) else {
+                continue;
+            };
+            texture
+                .borrow_mut()
+                .bind_mut(state, 0)
+                .set_wrap(Coordinate::R, WrapMode::ClampToEdge);
+
+            let Some(inv_volume_transform) = volume.global_transform().try_inverse() else {
+                continue;
+            };
+            // Half of the diagonal of a voxel, it moves the shaded point out of the occupied voxels.
+            let normal_bias = volume.voxel_size().norm() * 0.5;
+
+            stats += pass.draw(
+                frame_buffer,
+                viewport,
+                &shader.program,
+                Some(BlendParameters {
+                    func: BlendFunc::new(BlendFactor::Zero, BlendFactor::SrcColor),
+                    ..Default::default()
+                }),
+                |program_binding| {
+                    program_binding
+                        .set_texture(&shader.depth_texture, &depth)
+                        .set_texture(&shader.normal_texture, &normal)
+                        .set_texture(&shader.ao_texture, texture)
+                        .set_matrix4(&shader.inv_view_proj, &inv_view_projection)
+                        .set_matrix4(&shader.inv_volume_transform, &inv_volume_transform)
+                        .set_f32(&shader.intensity, volume.intensity())
+                        .set_f32(&shader.normal_bias, normal_bias);
+                },
+            )?;
+        }
+
+        Ok(stats)
+    }
+}
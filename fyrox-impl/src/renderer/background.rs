@@ -0,0 +1,113 @@
+//! Prepares the frame of a camera before lighting, according to its background settings (see
+//! [`CameraBackground`]). Every clear is limited to the viewport of the camera, so multiple cameras
+//! could render into the same frame without destroying each other's results.
+
+use crate::{
+    core::{color::Color, math::Rect, scope_profile, sstorage::ImmutableString},
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{DrawParameters, FrameBuffer},
+            geometry_buffer::{ElementRange, GeometryBuffer},
+            gpu_program::{GpuProgram, UniformLocation},
+            state::PipelineState,
+        },
+        make_viewport_matrix, RenderPassStatistics,
+    },
+    scene::camera::CameraBackground,
+};
+
+struct GradientShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    top_color: UniformLocation,
+    bottom_color: UniformLocation,
+}
+
+impl GradientShader {
+    fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/background_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "BackgroundShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            top_color: program.uniform_location(state, &ImmutableString::new("topColor"))?,
+            bottom_color: program.uniform_location(state, &ImmutableString::new("bottomColor"))?,
+            program,
+        })
+    }
+}
+
+pub struct BackgroundRenderer {
+    gradient_shader: GradientShader,
+}
+
+impl BackgroundRenderer {
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            gradient_shader: GradientShader::new(state)?,
+        })
+    }
+
+    /// Fills the viewport of the frame buffer with the background. Depth is kept intact, stencil is
+    /// always cleared. `clear_color` is used for [`CameraBackground::Skybox`], the skybox itself is
+    /// drawn later by the lighting pass.
+    pub(crate) fn render(
+        &self,
+        state: &PipelineState,
+        viewport: Rect<i32>,
+        frame_buffer: &mut FrameBuffer,
+        quad: &GeometryBuffer,
+        background: CameraBackground,
+        clear_color: Color,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!();
+
+        let mut stats = RenderPassStatistics::default();
+
+        match background {
+            CameraBackground::Skybox => {
+                frame_buffer.clear_viewport(state, viewport, Some(clear_color), None, Some(0));
+            }
+            CameraBackground::SolidColor(color) => {
+                frame_buffer.clear_viewport(state, viewport, Some(color), None, Some(0));
+            }
+            CameraBackground::Gradient { top, bottom } => {
+                frame_buffer.clear_viewport(state, viewport, None, None, Some(0));
+
+                let shader = &self.gradient_shader;
+                stats += frame_buffer.draw(
+                    quad,
+                    state,
+                    viewport,
+                    &shader.program,
+                    &DrawParameters {
+                        cull_face: None,
+                        color_write: Default::default(),
+                        depth_write: false,
+                        stencil_test: None,
+                        depth_test: false,
+                        blend: None,
+                        stencil_op: Default::default(),
+                    },
+                    ElementRange::Full,
+                    |mut program_binding| {
+                        program_binding
+                            .set_matrix4(&shader.wvp_matrix, &make_viewport_matrix(viewport))
+                            .set_srgb_color(&shader.top_color, &top)
+                            .set_srgb_color(&shader.bottom_color, &bottom);
+                    },
+                )?;
+            }
+            CameraBackground::None => {
+                // Keep whatever was rendered before (by previous cameras, for example).
+                frame_buffer.clear_viewport(state, viewport, None, None, Some(0));
+            }
+        }
+
+        Ok(stats)
+    }
+}
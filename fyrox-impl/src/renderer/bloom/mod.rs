@@ -1,99 +1,101 @@
-use crate::core::sstorage::ImmutableString;
-use crate::renderer::framework::geometry_buffer::ElementRange;
+//! Bloom is rendered using a chain of progressively downsampled mips (each is half of the size of the
+//! previous one), that is upsampled back with additive blending. It is much cheaper than a full-screen
+//! blur, and the glow is wide and stable at any resolution. See [`BloomSettings`] docs for more info.
+
 use crate::{
-    core::{math::Rect, scope_profile},
+    core::{algebra::Vector2, color::Color, scope_profile, sstorage::ImmutableString},
     renderer::{
-        bloom::blur::GaussianBlur,
         framework::{
             error::FrameworkError,
-            framebuffer::{Attachment, AttachmentKind, DrawParameters, FrameBuffer},
+            framebuffer::BlendParameters,
+            fullscreen::{
+                mip_chain_sizes, DownsampleFilter, DownsamplePass, FullscreenPass, RenderTarget,
+                UpsamplePass, FULLSCREEN_VERTEX_SHADER,
+            },
             geometry_buffer::GeometryBuffer,
             gpu_program::{GpuProgram, UniformLocation},
-            gpu_texture::{
-                Coordinate, GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter,
-                PixelKind, WrapMode,
-            },
-            state::PipelineState,
+            gpu_texture::{GpuTexture, MagnificationFilter, PixelKind},
+            state::{BlendFactor, BlendFunc, PipelineState},
         },
-        make_viewport_matrix, RenderPassStatistics,
+        RenderPassStatistics,
     },
+    scene::camera::BloomSettings,
 };
 use std::{cell::RefCell, rc::Rc};
 
-mod blur;
+// Max amount of mips in the chain, the first mip has half of the size of the frame.
+const MAX_MIP_COUNT: usize = 8;
 
-struct Shader {
+/// Downsamples the frame to the first mip and extracts bright pixels from it. The rest of the chain
+/// is built using generic downsampling.
+struct PrefilterShader {
     program: GpuProgram,
-    world_view_projection_matrix: UniformLocation,
-    hdr_sampler: UniformLocation,
+    src_sampler: UniformLocation,
+    src_texel_size: UniformLocation,
+    threshold: UniformLocation,
+    soft_knee: UniformLocation,
 }
 
-impl Shader {
+impl PrefilterShader {
     fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
-        let fragment_source = include_str!("../shaders/bloom_fs.glsl");
-        let vertex_source = include_str!("../shaders/flat_vs.glsl");
+        let fragment_source = include_str!("../shaders/bloom_prefilter_fs.glsl");
 
-        let program =
-            GpuProgram::from_source(state, "BloomShader", vertex_source, fragment_source)?;
+        let program = GpuProgram::from_source(
+            state,
+            "BloomPrefilterShader",
+            FULLSCREEN_VERTEX_SHADER,
+            fragment_source,
+        )?;
         Ok(Self {
-            world_view_projection_matrix: program
-                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
-            hdr_sampler: program.uniform_location(state, &ImmutableString::new("hdrSampler"))?,
+            src_sampler: program.uniform_location(state, &ImmutableString::new("srcSampler"))?,
+            src_texel_size: program
+                .uniform_location(state, &ImmutableString::new("srcTexelSize"))?,
+            threshold: program.uniform_location(state, &ImmutableString::new("threshold"))?,
+            soft_knee: program.uniform_location(state, &ImmutableString::new("softKnee"))?,
             program,
         })
     }
 }
 
 pub struct BloomRenderer {
-    shader: Shader,
-    framebuffer: FrameBuffer,
-    blur: GaussianBlur,
+    prefilter_shader: PrefilterShader,
+    downsample: DownsamplePass,
+    upsample: UpsamplePass,
+    mips: Vec<RenderTarget>,
     width: usize,
     height: usize,
 }
 
 impl BloomRenderer {
     pub fn new(state: &PipelineState, width: usize, height: usize) -> Result<Self, FrameworkError> {
-        let frame = {
-            let kind = GpuTextureKind::Rectangle { width, height };
-            let mut texture = GpuTexture::new(
-                state,
-                kind,
-                PixelKind::RGBA16F,
-                MinificationFilter::Nearest,
-                MagnificationFilter::Nearest,
-                1,
-                None,
-            )?;
-            texture
-                .bind_mut(state, 0)
-                .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
-                .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
-            texture
-        };
+        // Filtering must be linear, both filters are sampling between texels.
+        let mips = mip_chain_sizes(width, height, MAX_MIP_COUNT)
+            .into_iter()
+            .map(|(mip_width, mip_height)| {
+                RenderTarget::new(
+                    state,
+                    mip_width,
+                    mip_height,
+                    PixelKind::RGBA16F,
+                    MagnificationFilter::Linear,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
         Ok(Self {
-            shader: Shader::new(state)?,
-            blur: GaussianBlur::new(state, width, height, PixelKind::RGBA16F)?,
-            framebuffer: FrameBuffer::new(
-                state,
-                None,
-                vec![Attachment {
-                    kind: AttachmentKind::Color,
-                    texture: Rc::new(RefCell::new(frame)),
-                }],
-            )?,
+            prefilter_shader: PrefilterShader::new(state)?,
+            downsample: DownsamplePass::new(state)?,
+            upsample: UpsamplePass::new(state)?,
+            mips,
             width,
             height,
         })
     }
 
-    fn glow_texture(&self) -> Rc<RefCell<GpuTexture>> {
-        self.framebuffer.color_attachments()[0].texture.clone()
-    }
-
+    /// Returns a texture with the bloom, it has half of the size of the frame and must be sampled
+    /// with linear filtering.
     pub fn result(&self) -> Rc<RefCell<GpuTexture>> {
-        self.blur.result()
+        self.mips[0].texture()
     }
 
     pub(crate) fn render(
@@ -101,41 +103,60 @@ impl BloomRenderer {
         state: &PipelineState,
         quad: &GeometryBuffer,
         hdr_scene_frame: Rc<RefCell<GpuTexture>>,
+        settings: &BloomSettings,
     ) -> Result<RenderPassStatistics, FrameworkError> {
         scope_profile!();
 
         let mut stats = RenderPassStatistics::default();
 
-        let viewport = Rect::new(0, 0, self.width as i32, self.height as i32);
+        let pass = FullscreenPass::new(state, quad);
+        let mip_count = (settings.mip_count as usize).clamp(1, self.mips.len());
 
-        let shader = &self.shader;
-        stats += self.framebuffer.draw(
-            quad,
-            state,
-            viewport,
-            &shader.program,
-            &DrawParameters {
-                cull_face: None,
-                color_write: Default::default(),
-                depth_write: false,
-                stencil_test: None,
-                depth_test: false,
-                blend: None,
-                stencil_op: Default::default(),
-            },
-            ElementRange::Full,
-            |mut program_binding| {
-                program_binding
-                    .set_matrix4(
-                        &shader.world_view_projection_matrix,
-                        &(make_viewport_matrix(viewport)),
-                    )
-                    .set_texture(&shader.hdr_sampler, &hdr_scene_frame);
-            },
-        )?;
+        // Extract bright pixels and downsample them through the chain.
+        let shader = &self.prefilter_shader;
+        let src_texel_size = Vector2::new(1.0 / self.width as f32, 1.0 / self.height as f32);
+        stats += self.mips[0].draw(&pass, &shader.program, None, |program_binding| {
+            program_binding
+                .set_texture(&shader.src_sampler, &hdr_scene_frame)
+                .set_vector2(&shader.src_texel_size, &src_texel_size)
+                .set_f32(&shader.threshold, settings.threshold.max(0.0))
+                .set_f32(&shader.soft_knee, settings.soft_knee.clamp(0.0, 1.0));
+        })?;
+
+        for i in 1..mip_count {
+            let (previous, rest) = self.mips.split_at_mut(i);
+            let previous = &previous[i - 1];
+            stats += self.downsample.render(
+                &pass,
+                &mut rest[0],
+                &previous.texture(),
+                previous.texel_size(),
+                DownsampleFilter::Wide,
+            )?;
+        }
 
-        stats += self.blur.render(state, quad, self.glow_texture())?;
+        // Then go back up the chain, blurring every mip and adding it to the larger one.
+        for i in (0..mip_count - 1).rev() {
+            let (current, rest) = self.mips.split_at_mut(i + 1);
+            let source = &rest[0];
+            stats += self.upsample.render(
+                &pass,
+                &mut current[i],
+                &source.texture(),
+                source.texel_size(),
+                settings.radius.max(0.0),
+                Some(BlendParameters {
+                    func: BlendFunc::new(BlendFactor::One, BlendFactor::One),
+                    ..Default::default()
+                }),
+            )?;
+        }
 
         Ok(stats)
     }
+
+    /// Clears the result, it is used when the bloom is disabled.
+    pub(crate) fn clear(&mut self, state: &PipelineState) {
+        self.mips[0].clear(state, Color::TRANSPARENT);
+    }
 }
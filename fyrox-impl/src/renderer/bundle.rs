@@ -12,6 +12,7 @@ use crate::{
     material::MaterialResource,
     renderer::{cache::TimeToLive, framework::geometry_buffer::ElementRange},
     scene::{
+        collider::BitMask,
         graph::Graph,
         mesh::{
             buffer::{
@@ -45,6 +46,9 @@ pub struct ObserverInfo {
     pub view_matrix: Matrix4<f32>,
     /// Projection matrix of the observer.
     pub projection_matrix: Matrix4<f32>,
+    /// A set of render layers, that are visible to the observer. Nodes, that do not belong to any
+    /// of the layers, are skipped (see [`crate::scene::base::Base::set_render_mask`]).
+    pub render_mask: BitMask,
 }
 
 /// Render context is used to collect render data from the scene nodes. It provides all required information about
@@ -106,6 +110,15 @@ impl PersistentIdentifier {
         hasher.write_usize(index);
         Self(hasher.finish())
     }
+
+    /// Creates a new persistent identifier, that is used to identify the data of the previous frame, that is
+    /// associated with this identifier (for example - bone matrices of the previous frame).
+    pub fn previous_frame(self) -> Self {
+        let mut hasher = DefaultHasher::new();
+        hasher.write_u64(self.0);
+        hasher.write_u8(1);
+        Self(hasher.finish())
+    }
 }
 
 /// A set of data of a surface for rendering.  
@@ -114,6 +127,13 @@ pub struct SurfaceInstanceData {
     pub world_transform: Matrix4<f32>,
     /// A set of bone matrices.
     pub bone_matrices: Vec<Matrix4<f32>>,
+    /// A world matrix of the previous frame. It is used to calculate per-object motion vectors. Scene nodes
+    /// should set it to the same value as [`Self::world_transform`], the renderer replaces it with the actual
+    /// value from the previous frame (see [`MotionHistory`]).
+    pub prev_world_transform: Matrix4<f32>,
+    /// A set of bone matrices of the previous frame. Scene nodes should leave it empty, the renderer fills it
+    /// with the actual bone matrices from the previous frame (see [`MotionHistory`]).
+    pub prev_bone_matrices: Vec<Matrix4<f32>>,
     /// A depth-hack value.
     pub depth_offset: f32,
     /// A set of weights for each blend shape in the surface.
@@ -145,6 +165,11 @@ pub struct RenderDataBundle {
     pub render_path: RenderPath,
     /// A decal layer index of the bundle.
     pub decal_layer_index: u8,
+    /// Amount of hardware instances of the bundle. If set, every surface instance of the bundle is
+    /// drawn using a single instanced draw call, per-instance data is taken from the vertex attributes
+    /// with non-zero divisor. Only forward and deferred (G-Buffer) passes support such bundles, every
+    /// other pass ignores them.
+    pub hardware_instance_count: Option<usize>,
     sort_index: u64,
 }
 
@@ -208,6 +233,27 @@ pub trait RenderDataBundleStorageTrait {
         sort_index: u64,
         instance_data: SurfaceInstanceData,
     );
+
+    /// Adds a new surface instance to the storage, that will be drawn using hardware instancing (see
+    /// [`RenderDataBundle::hardware_instance_count`]). Unlike [`Self::push`], such instances are never
+    /// merged with other instances of the same surface data, since every instance has its own amount of
+    /// hardware instances.
+    ///
+    /// ## When to use
+    ///
+    /// This method is used to draw lots of copies of the same geometry with a single draw call, when
+    /// per-copy data is small (for example - tiles of a tile map). Vertex buffer of the surface data
+    /// should contain per-instance attributes (with non-zero divisor) and the material's shader must
+    /// be able to read them.
+    fn push_instanced(
+        &mut self,
+        data: &SurfaceResource,
+        material: &MaterialResource,
+        render_path: RenderPath,
+        sort_index: u64,
+        hardware_instance_count: usize,
+        instance_data: SurfaceInstanceData,
+    );
 }
 
 /// Bundle storage handles bundle generation for a scene before rendering. It is used to optimize
@@ -277,6 +323,10 @@ impl RenderDataBundleStorage {
         while let Some(handle) = stack.pop() {
             if lod_filter[handle.index() as usize] {
                 let node = graph.node(handle);
+                if (node.render_mask() & observer_info.render_mask).0 == 0 {
+                    stack.extend_from_slice(node.children());
+                    continue;
+                }
                 if let RdcControlFlow::Continue = node.collect_render_data(&mut ctx) {
                     stack.extend_from_slice(node.children());
                 }
@@ -368,6 +418,8 @@ impl RenderDataBundleStorageTrait for RenderDataBundleStorage {
                     SurfaceInstanceData {
                         world_transform: Matrix4::identity(),
                         bone_matrices: Default::default(),
+                        prev_world_transform: Matrix4::identity(),
+                        prev_bone_matrices: Default::default(),
                         depth_offset: Default::default(),
                         blend_shapes_weights: Default::default(),
                         element_range: Default::default(),
@@ -379,6 +431,7 @@ impl RenderDataBundleStorageTrait for RenderDataBundleStorage {
                 is_skinned,
                 render_path,
                 decal_layer_index,
+                hardware_instance_count: None,
                 // Temporary buffer lives one frame.
                 time_to_live: TimeToLive(0.0),
             });
@@ -428,6 +481,7 @@ impl RenderDataBundleStorageTrait for RenderDataBundleStorage {
                 is_skinned,
                 render_path,
                 decal_layer_index,
+                hardware_instance_count: None,
                 time_to_live: Default::default(),
             });
             self.bundles.last_mut().unwrap()
@@ -435,4 +489,101 @@ impl RenderDataBundleStorageTrait for RenderDataBundleStorage {
 
         bundle.instances.push(instance_data)
     }
+
+    fn push_instanced(
+        &mut self,
+        data: &SurfaceResource,
+        material: &MaterialResource,
+        render_path: RenderPath,
+        sort_index: u64,
+        hardware_instance_count: usize,
+        instance_data: SurfaceInstanceData,
+    ) {
+        if hardware_instance_count == 0 {
+            return;
+        }
+
+        // Instanced bundles are never shared, so there's no need to register them in the bundle map.
+        self.bundles.push(RenderDataBundle {
+            data: data.clone(),
+            sort_index,
+            instances: vec![instance_data],
+            material: material.clone(),
+            is_skinned: false,
+            render_path,
+            decal_layer_index: 0,
+            hardware_instance_count: Some(hardware_instance_count),
+            time_to_live: Default::default(),
+        });
+    }
+}
+
+struct InstanceHistoryEntry {
+    world_transform: Matrix4<f32>,
+    bone_matrices: Vec<Matrix4<f32>>,
+}
+
+/// Motion history keeps world matrices and bone matrices of every surface instance (identified by its
+/// [`PersistentIdentifier`]) for two last frames. It is used to supply surface instances with the data of the
+/// previous frame, which is then used to calculate per-object motion vectors (for temporal anti-aliasing, motion
+/// blur, etc.). Without it, skinned meshes would have motion vectors that take only the movement of the mesh
+/// itself into account, which results in ghosting on animated characters.
+#[derive(Default)]
+pub struct MotionHistory {
+    previous: FxHashMap<PersistentIdentifier, InstanceHistoryEntry>,
+    current: FxHashMap<PersistentIdentifier, InstanceHistoryEntry>,
+}
+
+impl MotionHistory {
+    /// Prepares the history for a new frame. Must be called once per frame, before any call of
+    /// [`Self::apply`].
+    pub fn begin_frame(&mut self) {
+        std::mem::swap(&mut self.previous, &mut self.current);
+        self.current.clear();
+    }
+
+    /// Fills the previous frame data of every instance in the given bundle storage and remembers the current
+    /// data of the instances, so it will be available in the next frame. Instances that weren't rendered in
+    /// the previous frame will have their previous data equal to the current data (zero motion). The method
+    /// could be called multiple times per frame (for example, for every camera), only the first set of data
+    /// of an instance is remembered.
+    pub fn apply(&mut self, storage: &mut RenderDataBundleStorage) {
+        for bundle in storage.bundles.iter_mut() {
+            for instance in bundle.instances.iter_mut() {
+                let id = instance.persistent_identifier;
+
+                if let Some(previous) = self.previous.get(&id) {
+                    instance.prev_world_transform = previous.world_transform;
+                    instance
+                        .prev_bone_matrices
+                        .clone_from(&previous.bone_matrices);
+                } else {
+                    instance.prev_world_transform = instance.world_transform;
+                    instance
+                        .prev_bone_matrices
+                        .clone_from(&instance.bone_matrices);
+                }
+
+                self.current
+                    .entry(id)
+                    .or_insert_with(|| InstanceHistoryEntry {
+                        world_transform: instance.world_transform,
+                        bone_matrices: instance.bone_matrices.clone(),
+                    });
+            }
+        }
+    }
+
+    /// Returns a world matrix of an instance with the given identifier, that was used in the previous frame.
+    pub fn previous_world_transform(&self, id: PersistentIdentifier) -> Option<&Matrix4<f32>> {
+        self.previous.get(&id).map(|entry| &entry.world_transform)
+    }
+
+    /// Returns a set of bone matrices of an instance with the given identifier, that was used in the previous
+    /// frame.
+    pub fn previous_bone_matrices(&self, id: PersistentIdentifier) -> Option<&[Matrix4<f32>]> {
+        self.previous
+            .get(&id)
+            .map(|entry| entry.bone_matrices.as_slice())
+    }
 }
@@ -1,67 +1,367 @@
+//! Geometry cache stores GPU buffers for surfaces. Surfaces with the same vertex layout share a
+//! set of large vertex and index buffers (geometry pools), which reduces the amount of GPU
+//! objects and buffer bindings in scenes with lots of small meshes.
+
 use crate::{
+    core::math::TriangleDefinition,
     renderer::{
         cache::{TemporaryCache, TimeToLive},
         framework::{
             error::FrameworkError,
-            geometry_buffer::{GeometryBuffer, GeometryBufferKind},
+            geometry_buffer::{
+                BufferBuilder, ElementKind, ElementRange, GeometryBuffer, GeometryBufferBuilder,
+                GeometryBufferKind,
+            },
             state::PipelineState,
         },
     },
     scene::mesh::surface::{SurfaceData, SurfaceResource},
 };
 use fyrox_core::log::Log;
+use std::{cell::RefCell, ops::Range, rc::Rc};
 
-struct SurfaceRenderData {
+/// Initial capacity (in vertices and triangles) of a geometry pool.
+const INITIAL_POOL_CAPACITY: usize = 16384;
+
+/// Ranges of vertices are aligned to this amount of vertices, so the shaders that rely on
+/// `gl_VertexID % 4` (for example, quad-based rendering of tiles) keep working with pooled
+/// geometry.
+const VERTEX_ALIGNMENT: usize = 4;
+
+/// A pool is compacted when the free space between its allocations exceeds this fraction of its
+/// capacity.
+const COMPACTION_THRESHOLD: f32 = 0.25;
+
+/// First-fit allocator of ranges of a linear storage.
+#[derive(Default)]
+struct RangeAllocator {
+    capacity: usize,
+    // Sorted, non-overlapping and non-adjacent free ranges.
+    free: Vec<Range<usize>>,
+}
+
+impl RangeAllocator {
+    fn allocate(&mut self, size: usize) -> Option<Range<usize>> {
+        if size == 0 {
+            return Some(0..0);
+        }
+
+        let index = self.free.iter().position(|range| range.len() >= size)?;
+        let range = &mut self.free[index];
+        let start = range.start;
+        range.start += size;
+        if range.start == range.end {
+            self.free.remove(index);
+        }
+        Some(start..start + size)
+    }
+
+    fn free(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+
+        let index = self.free.partition_point(|free| free.start < range.start);
+        self.free.insert(index, range);
+
+        if index + 1 < self.free.len() && self.free[index].end == self.free[index + 1].start {
+            self.free[index].end = self.free.remove(index + 1).end;
+        }
+
+        if index > 0 && self.free[index - 1].end == self.free[index].start {
+            self.free[index - 1].end = self.free.remove(index).end;
+        }
+    }
+
+    fn grow(&mut self, capacity: usize) {
+        let old_capacity = std::mem::replace(&mut self.capacity, capacity);
+        self.free(old_capacity..capacity);
+    }
+
+    /// Returns the amount of free space, that is located between the allocated ranges.
+    fn fragmented_space(&self) -> usize {
+        self.free
+            .iter()
+            .filter(|range| range.end != self.capacity)
+            .map(|range| range.len())
+            .sum()
+    }
+
+    fn is_fragmented(&self) -> bool {
+        self.fragmented_space() as f32 > self.capacity as f32 * COMPACTION_THRESHOLD
+    }
+
+    /// Marks the first `used` elements of the storage as allocated and the rest as free.
+    fn reset(&mut self, used: usize) {
+        self.free.clear();
+        self.free(used..self.capacity);
+    }
+}
+
+/// A set of large vertex and index buffers, that stores geometry of many surfaces with the same
+/// vertex layout. Indices of the surfaces are offset by the first vertex of their allocations,
+/// this way a surface could be drawn with a simple range of elements of the pool.
+struct GeometryPool {
+    layout_hash: u64,
+    vertex_size: usize,
     buffer: GeometryBuffer,
+    vertices: RangeAllocator,
+    triangles: RangeAllocator,
+    // Whether there were any allocations since the last update or not.
+    allocated: bool,
+}
+
+fn create_pool_buffer(
+    data: &SurfaceData,
+    state: &PipelineState,
+    vertex_capacity: usize,
+    triangle_capacity: usize,
+) -> Result<GeometryBuffer, FrameworkError> {
+    let mut buffer = GeometryBufferBuilder::new(ElementKind::Triangle)
+        .with_buffer_builder(BufferBuilder::from_vertex_layout(
+            &data.vertex_buffer,
+            GeometryBufferKind::StaticDraw,
+        ))
+        .build(state)?;
+
+    buffer.allocate_buffer_data(
+        state,
+        0,
+        vertex_capacity * data.vertex_buffer.vertex_size() as usize,
+    );
+    buffer.bind(state).allocate_triangles(triangle_capacity);
+
+    Ok(buffer)
+}
+
+impl GeometryPool {
+    fn new(data: &SurfaceData, state: &PipelineState) -> Result<Self, FrameworkError> {
+        let mut vertices = RangeAllocator::default();
+        vertices.grow(INITIAL_POOL_CAPACITY);
+        let mut triangles = RangeAllocator::default();
+        triangles.grow(INITIAL_POOL_CAPACITY);
+
+        Ok(Self {
+            layout_hash: data.vertex_buffer.layout_hash(),
+            vertex_size: data.vertex_buffer.vertex_size() as usize,
+            buffer: create_pool_buffer(data, state, INITIAL_POOL_CAPACITY, INITIAL_POOL_CAPACITY)?,
+            vertices,
+            triangles,
+            allocated: false,
+        })
+    }
+
+    /// Grows the storage of the pool, so it could fit at least the given amount of vertices and
+    /// triangles. Existing content of the pool is copied on GPU side.
+    fn grow(
+        &mut self,
+        data: &SurfaceData,
+        state: &PipelineState,
+        vertex_count: usize,
+        triangle_count: usize,
+    ) -> Result<(), FrameworkError> {
+        fn new_capacity(capacity: usize, additional: usize) -> usize {
+            if additional > 0 {
+                (capacity * 2).max(capacity + additional)
+            } else {
+                capacity
+            }
+        }
+
+        let vertex_capacity = new_capacity(self.vertices.capacity, vertex_count);
+        let triangle_capacity = new_capacity(self.triangles.capacity, triangle_count);
+
+        let buffer = create_pool_buffer(data, state, vertex_capacity, triangle_capacity)?;
+        buffer.copy_data_from(state, &self.buffer);
+        self.buffer = buffer;
+
+        self.vertices.grow(vertex_capacity);
+        self.triangles.grow(triangle_capacity);
+
+        Ok(())
+    }
+
+    fn allocate(
+        &mut self,
+        data: &SurfaceData,
+        state: &PipelineState,
+        vertex_count: usize,
+        triangle_count: usize,
+    ) -> Result<(Range<usize>, Range<usize>), FrameworkError> {
+        self.allocated = true;
+
+        let vertices = match self.vertices.allocate(vertex_count) {
+            Some(vertices) => vertices,
+            None => {
+                self.grow(data, state, vertex_count, 0)?;
+                self.vertices
+                    .allocate(vertex_count)
+                    .expect("Pool must have enough space after growth!")
+            }
+        };
+
+        let triangles = match self.triangles.allocate(triangle_count) {
+            Some(triangles) => triangles,
+            None => {
+                self.grow(data, state, 0, triangle_count)?;
+                self.triangles
+                    .allocate(triangle_count)
+                    .expect("Pool must have enough space after growth!")
+            }
+        };
+
+        Ok((vertices, triangles))
+    }
+}
+
+type ReleasedAllocations = Rc<RefCell<Vec<(usize, Range<usize>, Range<usize>)>>>;
+
+/// Ranges of vertices and triangles of a surface in a geometry pool. The ranges are returned back
+/// to the pool when the allocation is dropped.
+struct PoolAllocation {
+    pool: usize,
+    vertices: Range<usize>,
+    triangles: Range<usize>,
+    released: ReleasedAllocations,
+}
+
+impl Drop for PoolAllocation {
+    fn drop(&mut self) {
+        self.released
+            .borrow_mut()
+            .push((self.pool, self.vertices.clone(), self.triangles.clone()));
+    }
+}
+
+enum SurfaceStorage {
+    /// Surfaces with blend shapes or per-instance attributes can't be pooled, because their
+    /// shaders rely on the actual index of vertices, so they have their own buffers.
+    Dedicated(GeometryBuffer),
+    Pooled(Option<PoolAllocation>),
+}
+
+struct SurfaceRenderData {
+    storage: SurfaceStorage,
     vertex_modifications_count: u64,
     triangles_modifications_count: u64,
     layout_hash: u64,
+    // Whether the pooled geometry must be uploaded on next access or not. It is set when the
+    // surface is moved to a new place in the pool.
+    needs_upload: bool,
+}
+
+/// Geometry of a surface, that is ready for rendering.
+pub struct SurfaceGeometry<'a> {
+    /// A geometry buffer, that contains the surface. The buffer could be shared across multiple
+    /// surfaces, use [`Self::element_range`] to get the elements of the surface in the buffer.
+    pub buffer: &'a GeometryBuffer,
+    elements: Range<usize>,
+}
+
+impl<'a> SurfaceGeometry<'a> {
+    /// Converts the given range of elements of the surface to the range of elements in the buffer.
+    pub fn element_range(&self, range: ElementRange) -> Result<ElementRange, FrameworkError> {
+        match range {
+            ElementRange::Full => Ok(ElementRange::Specific {
+                offset: self.elements.start,
+                count: self.elements.len(),
+            }),
+            ElementRange::Specific { offset, count } => {
+                if offset + count > self.elements.len() {
+                    Err(FrameworkError::InvalidElementRange {
+                        start: offset,
+                        end: offset + count,
+                        total: self.elements.len(),
+                    })
+                } else {
+                    Ok(ElementRange::Specific {
+                        offset: self.elements.start + offset,
+                        count,
+                    })
+                }
+            }
+        }
+    }
 }
 
 #[derive(Default)]
 pub struct GeometryCache {
     buffer: TemporaryCache<SurfaceRenderData>,
+    pools: Vec<GeometryPool>,
+    released: ReleasedAllocations,
+}
+
+fn is_poolable(data: &SurfaceData) -> bool {
+    data.blend_shapes_container.is_none()
+        && data
+            .vertex_buffer
+            .layout()
+            .iter()
+            .all(|attribute| attribute.divisor == 0)
 }
 
-fn create_geometry_buffer(
+fn create_render_data(
     data: &SurfaceData,
     state: &PipelineState,
 ) -> Result<SurfaceRenderData, FrameworkError> {
-    let geometry_buffer =
-        GeometryBuffer::from_surface_data(data, GeometryBufferKind::StaticDraw, state)?;
+    let storage = if is_poolable(data) {
+        SurfaceStorage::Pooled(None)
+    } else {
+        SurfaceStorage::Dedicated(GeometryBuffer::from_surface_data(
+            data,
+            GeometryBufferKind::StaticDraw,
+            state,
+        )?)
+    };
 
     Ok(SurfaceRenderData {
-        buffer: geometry_buffer,
+        storage,
         vertex_modifications_count: data.vertex_buffer.modifications_count(),
         triangles_modifications_count: data.geometry_buffer.modifications_count(),
         layout_hash: data.vertex_buffer.layout_hash(),
+        needs_upload: true,
     })
 }
 
+fn aligned_vertex_count(data: &SurfaceData) -> usize {
+    let count = data.vertex_buffer.vertex_count() as usize;
+    (count + VERTEX_ALIGNMENT - 1) / VERTEX_ALIGNMENT * VERTEX_ALIGNMENT
+}
+
 impl GeometryCache {
     pub fn get<'a>(
         &'a mut self,
         state: &PipelineState,
         data: &SurfaceResource,
         time_to_live: TimeToLive,
-    ) -> Option<&'a mut GeometryBuffer> {
+    ) -> Option<SurfaceGeometry<'a>> {
         let data = data.data_ref();
 
-        match self
-            .buffer
-            .get_entry_mut_or_insert_with(&data.cache_index, time_to_live, || {
-                create_geometry_buffer(&data, state)
-            }) {
-            Ok(entry) => {
+        self.release_allocations();
+
+        let entry =
+            match self
+                .buffer
+                .get_entry_mut_or_insert_with(&data.cache_index, time_to_live, || {
+                    create_render_data(&data, state)
+                }) {
+                Ok(entry) => entry,
+                Err(err) => {
+                    Log::err(err.to_string());
+                    return None;
+                }
+            };
+
+        let entry = &mut entry.value;
+        let allocation = match entry.storage {
+            SurfaceStorage::Dedicated(ref mut buffer) => {
                 // We also must check if buffer's layout changed, and if so - recreate the entire
                 // buffer.
                 if entry.layout_hash == data.vertex_buffer.layout_hash() {
                     if data.vertex_buffer.modifications_count() != entry.vertex_modifications_count
                     {
                         // Vertices has changed, upload the new content.
-                        entry
-                            .buffer
-                            .set_buffer_data(state, 0, data.vertex_buffer.raw_data());
+                        buffer.set_buffer_data(state, 0, data.vertex_buffer.raw_data());
 
                         entry.vertex_modifications_count = data.vertex_buffer.modifications_count();
                     }
@@ -70,8 +370,7 @@ impl GeometryCache {
                         != entry.triangles_modifications_count
                     {
                         // Triangles has changed, upload the new content.
-                        entry
-                            .buffer
+                        buffer
                             .bind(state)
                             .set_triangles(data.geometry_buffer.triangles_ref());
 
@@ -79,20 +378,218 @@ impl GeometryCache {
                             data.geometry_buffer.modifications_count();
                     }
                 }
-                Some(&mut entry.buffer)
+
+                return Some(SurfaceGeometry {
+                    elements: 0..buffer.element_count(),
+                    buffer,
+                });
             }
-            Err(err) => {
-                Log::err(err.to_string());
-                None
+            SurfaceStorage::Pooled(ref mut allocation) => allocation,
+        };
+
+        let vertex_count = aligned_vertex_count(&data);
+        let triangle_count = data.geometry_buffer.len();
+        let layout_hash = data.vertex_buffer.layout_hash();
+
+        // The surface must be moved to a new place, if its size or layout has changed.
+        if allocation.as_ref().map_or(true, |allocation| {
+            allocation.vertices.len() != vertex_count
+                || allocation.triangles.len() != triangle_count
+                || entry.layout_hash != layout_hash
+        }) {
+            *allocation = None;
+
+            let pool_index = match self
+                .pools
+                .iter()
+                .position(|pool| pool.layout_hash == layout_hash)
+            {
+                Some(pool_index) => pool_index,
+                None => match GeometryPool::new(&data, state) {
+                    Ok(pool) => {
+                        self.pools.push(pool);
+                        self.pools.len() - 1
+                    }
+                    Err(err) => {
+                        Log::err(err.to_string());
+                        return None;
+                    }
+                },
+            };
+
+            let (vertices, triangles) =
+                match self.pools[pool_index].allocate(&data, state, vertex_count, triangle_count) {
+                    Ok(ranges) => ranges,
+                    Err(err) => {
+                        Log::err(err.to_string());
+                        return None;
+                    }
+                };
+
+            *allocation = Some(PoolAllocation {
+                pool: pool_index,
+                vertices,
+                triangles,
+                released: self.released.clone(),
+            });
+            entry.layout_hash = layout_hash;
+            entry.needs_upload = true;
+        }
+
+        let allocation = allocation.as_ref()?;
+        let pool = &mut self.pools[allocation.pool];
+
+        if entry.needs_upload
+            || data.vertex_buffer.modifications_count() != entry.vertex_modifications_count
+        {
+            pool.buffer.set_buffer_sub_data(
+                state,
+                0,
+                allocation.vertices.start * pool.vertex_size,
+                data.vertex_buffer.raw_data(),
+            );
+
+            entry.vertex_modifications_count = data.vertex_buffer.modifications_count();
+        }
+
+        if entry.needs_upload
+            || data.geometry_buffer.modifications_count() != entry.triangles_modifications_count
+        {
+            let first_vertex = allocation.vertices.start as u32;
+            let triangles = data
+                .geometry_buffer
+                .triangles_ref()
+                .iter()
+                .map(|triangle| TriangleDefinition(triangle.0.map(|i| i + first_vertex)))
+                .collect::<Vec<_>>();
+
+            pool.buffer
+                .bind(state)
+                .set_triangles_at(allocation.triangles.start, &triangles);
+
+            entry.triangles_modifications_count = data.geometry_buffer.modifications_count();
+        }
+
+        entry.needs_upload = false;
+
+        Some(SurfaceGeometry {
+            buffer: &pool.buffer,
+            elements: allocation.triangles.clone(),
+        })
+    }
+
+    fn release_allocations(&mut self) {
+        for (pool, vertices, triangles) in self.released.borrow_mut().drain(..) {
+            if let Some(pool) = self.pools.get_mut(pool) {
+                pool.vertices.free(vertices);
+                pool.triangles.free(triangles);
             }
         }
     }
 
+    /// Moves every allocation of the pool to the beginning of the pool, eliminating the free space
+    /// between them. Moved surfaces are uploaded again on next access.
+    fn compact(&mut self, pool_index: usize) {
+        let mut allocations = self
+            .buffer
+            .buffer
+            .iter_mut()
+            .filter_map(|entry| {
+                let entry = &mut entry.value;
+                match entry.storage {
+                    SurfaceStorage::Pooled(Some(ref mut allocation))
+                        if allocation.pool == pool_index =>
+                    {
+                        Some((allocation, &mut entry.needs_upload))
+                    }
+                    _ => None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let pool = &mut self.pools[pool_index];
+
+        allocations.sort_by_key(|(allocation, _)| allocation.vertices.start);
+        let mut offset = 0;
+        for (allocation, needs_upload) in allocations.iter_mut() {
+            let count = allocation.vertices.len();
+            if allocation.vertices.start != offset {
+                allocation.vertices = offset..offset + count;
+                **needs_upload = true;
+            }
+            offset += count;
+        }
+        pool.vertices.reset(offset);
+
+        allocations.sort_by_key(|(allocation, _)| allocation.triangles.start);
+        let mut offset = 0;
+        for (allocation, needs_upload) in allocations.iter_mut() {
+            let count = allocation.triangles.len();
+            if allocation.triangles.start != offset {
+                allocation.triangles = offset..offset + count;
+                **needs_upload = true;
+            }
+            offset += count;
+        }
+        pool.triangles.reset(offset);
+    }
+
     pub fn update(&mut self, dt: f32) {
         self.buffer.update(dt);
+
+        self.release_allocations();
+
+        // Compaction is done only in the frames without new allocations, to not make the frames
+        // with heavy uploads even heavier.
+        for pool_index in 0..self.pools.len() {
+            let pool = &self.pools[pool_index];
+            if !pool.allocated && (pool.vertices.is_fragmented() || pool.triangles.is_fragmented())
+            {
+                self.compact(pool_index);
+            }
+            self.pools[pool_index].allocated = false;
+        }
     }
 
     pub fn clear(&mut self) {
         self.buffer.clear();
+        self.pools.clear();
+        self.released.borrow_mut().clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::renderer::cache::geometry::RangeAllocator;
+
+    #[test]
+    fn test_range_allocator() {
+        let mut allocator = RangeAllocator::default();
+        allocator.grow(10);
+
+        let a = allocator.allocate(4).unwrap();
+        let b = allocator.allocate(4).unwrap();
+        assert_eq!(a, 0..4);
+        assert_eq!(b, 4..8);
+        assert_eq!(allocator.allocate(0), Some(0..0));
+        assert!(allocator.allocate(3).is_none());
+
+        allocator.free(a);
+        assert_eq!(allocator.fragmented_space(), 4);
+        assert!(allocator.is_fragmented());
+        assert_eq!(allocator.allocate(2), Some(0..2));
+
+        // Freed ranges must be merged with their neighbours.
+        allocator.free(0..2);
+        allocator.free(b);
+        assert_eq!(allocator.free, vec![0..10]);
+        assert_eq!(allocator.fragmented_space(), 0);
+
+        allocator.allocate(10).unwrap();
+        allocator.grow(16);
+        assert_eq!(allocator.allocate(6), Some(10..16));
+
+        allocator.reset(3);
+        assert_eq!(allocator.free, vec![3..16]);
     }
 }
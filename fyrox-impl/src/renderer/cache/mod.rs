@@ -4,6 +4,7 @@ use crate::{
     asset::entry::DEFAULT_RESOURCE_LIFETIME,
     core::sparse::{AtomicIndex, SparseBuffer},
 };
+use fxhash::FxHashMap;
 use std::{
     ops::{Deref, DerefMut},
     sync::Arc,
@@ -68,6 +69,42 @@ impl<T> DerefMut for CacheEntry<T> {
     }
 }
 
+/// Keeps track of the cache indices of resources. Reloading of a resource replaces its data together
+/// with the cache index, so the cache entry of the previous data could only be found this way.
+#[derive(Default)]
+pub struct ResourceCacheIndices {
+    map: FxHashMap<u64, Arc<AtomicIndex>>,
+}
+
+impl ResourceCacheIndices {
+    /// Remembers the cache index of the current data of a resource. Returns the cache index of the
+    /// previous data of the resource, if it differs from the given one.
+    pub fn register(
+        &mut self,
+        resource_key: u64,
+        index: &Arc<AtomicIndex>,
+    ) -> Option<Arc<AtomicIndex>> {
+        self.map
+            .insert(resource_key, index.clone())
+            .filter(|previous| !Arc::ptr_eq(previous, index))
+    }
+
+    /// Returns the cache index, that was registered for a resource.
+    pub fn get(&self, resource_key: u64) -> Option<&Arc<AtomicIndex>> {
+        self.map.get(&resource_key)
+    }
+
+    /// Forgets the indices of the entries that were removed from a cache.
+    pub fn update(&mut self) {
+        self.map
+            .retain(|_, index| index.get() != AtomicIndex::UNASSIGNED_INDEX);
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+}
+
 pub struct TemporaryCache<T> {
     pub buffer: SparseBuffer<CacheEntry<T>>,
 }
@@ -162,6 +199,21 @@ impl<T> TemporaryCache<T> {
             .map(|entry| &entry.value)
     }
 
+    /// Removes the entry of the previous data of a resource, if the current data of the resource
+    /// has no entry yet. See [`ResourceCacheIndices`] for more info.
+    pub fn remove_stale(
+        &mut self,
+        indices: &mut ResourceCacheIndices,
+        resource_key: u64,
+        index: &Arc<AtomicIndex>,
+    ) {
+        if self.buffer.get(index).is_none() {
+            if let Some(stale) = indices.register(resource_key, index) {
+                self.remove(&stale);
+            }
+        }
+    }
+
     pub fn update(&mut self, dt: f32) {
         for entry in self.buffer.iter_mut() {
             *entry.time_to_live -= dt;
@@ -184,3 +236,42 @@ impl<T> TemporaryCache<T> {
         self.buffer.free(index);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::sparse::AtomicIndex,
+        renderer::cache::{ResourceCacheIndices, TemporaryCache, TimeToLive},
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn test_remove_stale() {
+        let mut cache = TemporaryCache::<u32>::default();
+        let mut indices = ResourceCacheIndices::default();
+
+        let old_index = Arc::new(AtomicIndex::unassigned());
+        cache.remove_stale(&mut indices, 1, &old_index);
+        cache
+            .get_or_insert_with(&old_index, TimeToLive::default(), || Ok::<_, ()>(1))
+            .unwrap();
+        assert!(cache.buffer.get(&old_index).is_some());
+
+        // Existing entry of the current data is kept.
+        cache.remove_stale(&mut indices, 1, &old_index);
+        assert!(cache.buffer.get(&old_index).is_some());
+
+        // New data of the resource (after reloading) gets its own index, the old entry is removed.
+        let new_index = Arc::new(AtomicIndex::unassigned());
+        cache.remove_stale(&mut indices, 1, &new_index);
+        assert_eq!(old_index.get(), AtomicIndex::UNASSIGNED_INDEX);
+        cache
+            .get_or_insert_with(&new_index, TimeToLive::default(), || Ok::<_, ()>(2))
+            .unwrap();
+        assert_eq!(cache.buffer.iter().count(), 1);
+
+        cache.remove(&new_index);
+        indices.update();
+        assert!(indices.get(1).is_none());
+    }
+}
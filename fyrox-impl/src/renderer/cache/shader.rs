@@ -1,14 +1,20 @@
-use crate::renderer::cache::TemporaryCache;
+use crate::renderer::cache::{ResourceCacheIndices, TemporaryCache};
 use crate::renderer::framework::error::FrameworkError;
 use crate::{
     core::sstorage::ImmutableString,
-    material::shader::{Shader, ShaderResource},
+    material::{
+        shader::{include::ShaderIncludes, Shader, ShaderPermutation, ShaderResource},
+        Material,
+    },
     renderer::framework::{
-        framebuffer::DrawParameters, gpu_program::GpuProgram, state::PipelineState,
+        framebuffer::DrawParameters,
+        gpu_program::{GpuProgram, GpuProgramSources},
+        state::PipelineState,
     },
 };
 use fxhash::FxHashMap;
 use fyrox_core::log::Log;
+use std::collections::hash_map::Entry;
 
 pub struct RenderPassData {
     pub program: GpuProgram,
@@ -20,15 +26,88 @@ pub struct ShaderSet {
 }
 
 impl ShaderSet {
-    pub fn new(state: &PipelineState, shader: &Shader) -> Result<Self, FrameworkError> {
+    pub fn new(
+        state: &PipelineState,
+        shader: &Shader,
+        includes: &ShaderIncludes,
+    ) -> Result<Self, FrameworkError> {
+        Self::with_permutation(state, shader, ShaderPermutation::BASE, includes)
+    }
+
+    /// Compiles every render pass of the shader with the features of the given permutation
+    /// defined as preprocessor macros. Include directives are resolved using the given chunks.
+    pub fn with_permutation(
+        state: &PipelineState,
+        shader: &Shader,
+        permutation: ShaderPermutation,
+        includes: &ShaderIncludes,
+    ) -> Result<Self, FrameworkError> {
+        let defines = shader.permutation_defines(permutation);
+        let preprocess = |source: &str| -> Result<String, FrameworkError> {
+            let source = includes.resolve(source).map_err(|e| {
+                FrameworkError::Custom(format!(
+                    "Failed to resolve includes of {} shader. Reason: {}",
+                    shader.definition.name, e
+                ))
+            })?;
+            Ok(format!("{defines}{source}"))
+        };
+
         let mut map = FxHashMap::default();
         for render_pass in shader.definition.passes.iter() {
-            let program_name = format!("{}_{}", shader.definition.name, render_pass.name);
-            match GpuProgram::from_source(
+            let program_name = if permutation == ShaderPermutation::BASE {
+                format!("{}_{}", shader.definition.name, render_pass.name)
+            } else {
+                format!(
+                    "{}_{}_{:x}",
+                    shader.definition.name, render_pass.name, permutation.0
+                )
+            };
+
+            // Optional stages are not available everywhere, skip such passes instead of failing
+            // the entire shader, so the rest of its passes could still be used.
+            let uses_geometry = render_pass.geometry_shader.is_some();
+            let uses_tessellation = render_pass.tessellation_control_shader.is_some()
+                || render_pass.tessellation_evaluation_shader.is_some();
+            if (uses_geometry && !state.supports_geometry_shaders())
+                || (uses_tessellation && !state.supports_tessellation())
+            {
+                Log::err(format!(
+                    "Render pass {} is ignored, because it uses shader stages that are not \
+                    supported by the graphics API.",
+                    program_name
+                ));
+                continue;
+            }
+
+            let vertex = preprocess(&render_pass.vertex_shader)?;
+            let fragment = preprocess(&render_pass.fragment_shader)?;
+            let geometry = render_pass
+                .geometry_shader
+                .as_deref()
+                .map(preprocess)
+                .transpose()?;
+            let tessellation_control = render_pass
+                .tessellation_control_shader
+                .as_deref()
+                .map(preprocess)
+                .transpose()?;
+            let tessellation_evaluation = render_pass
+                .tessellation_evaluation_shader
+                .as_deref()
+                .map(preprocess)
+                .transpose()?;
+
+            match GpuProgram::from_sources(
                 state,
                 &program_name,
-                &render_pass.vertex_shader,
-                &render_pass.fragment_shader,
+                GpuProgramSources {
+                    vertex: &vertex,
+                    fragment: &fragment,
+                    geometry: geometry.as_deref(),
+                    tessellation_control: tessellation_control.as_deref(),
+                    tessellation_evaluation: tessellation_evaluation.as_deref(),
+                },
             ) {
                 Ok(gpu_program) => {
                     map.insert(
@@ -52,9 +131,17 @@ impl ShaderSet {
     }
 }
 
+/// Compiled permutations of a shader, see [`ShaderPermutation`] for more info.
+#[derive(Default)]
+pub struct ShaderPermutations {
+    pub sets: FxHashMap<ShaderPermutation, ShaderSet>,
+}
+
 #[derive(Default)]
 pub struct ShaderCache {
-    pub(super) cache: TemporaryCache<ShaderSet>,
+    pub(super) cache: TemporaryCache<ShaderPermutations>,
+    indices: ResourceCacheIndices,
+    includes: ShaderIncludes,
 }
 
 impl ShaderCache {
@@ -65,35 +152,122 @@ impl ShaderCache {
         }
     }
 
+    /// Returns the chunks, that could be included by shaders.
+    pub fn includes(&self) -> &ShaderIncludes {
+        &self.includes
+    }
+
+    /// Registers a new chunk, that could be included by shaders, or replaces an existing one. Every
+    /// compiled shader is removed, since it may use the previous version of the chunk, and will be
+    /// compiled again on first use.
+    pub fn register_include(&mut self, name: &str, source: &str) {
+        if self.includes.get(name) != Some(source) {
+            self.includes.register(name, source);
+            self.clear();
+        }
+    }
+
+    /// Returns a base permutation (without any enabled features) of the shader.
     pub fn get(
         &mut self,
         pipeline_state: &PipelineState,
         shader: &ShaderResource,
+    ) -> Option<&ShaderSet> {
+        self.get_permutation(pipeline_state, shader, ShaderPermutation::BASE)
+    }
+
+    /// Returns a permutation of the shader for the features enabled in the material.
+    pub fn get_for_material(
+        &mut self,
+        pipeline_state: &PipelineState,
+        material: &Material,
+    ) -> Option<&ShaderSet> {
+        let shader_resource = material.shader();
+        let mut shader_state = shader_resource.state();
+        let shader = shader_state.data()?;
+        let permutation = material.permutation(shader);
+        self.get_or_compile(pipeline_state, shader_resource.key(), shader, permutation)
+    }
+
+    /// Returns the given permutation of the shader. The permutation is compiled on first use.
+    pub fn get_permutation(
+        &mut self,
+        pipeline_state: &PipelineState,
+        shader: &ShaderResource,
+        permutation: ShaderPermutation,
     ) -> Option<&ShaderSet> {
         let mut shader_state = shader.state();
+        let shader_data = shader_state.data()?;
+        self.get_or_compile(pipeline_state, shader.key(), shader_data, permutation)
+    }
 
-        if let Some(shader_state) = shader_state.data() {
-            match self.cache.get_or_insert_with(
-                &shader_state.cache_index,
-                Default::default(),
-                || ShaderSet::new(pipeline_state, shader_state),
-            ) {
-                Ok(shader_set) => Some(shader_set),
-                Err(error) => {
-                    Log::err(format!("{}", error));
-                    None
+    /// Compiles the reloaded shader and removes the GPU programs of its previous data. Every
+    /// permutation, that was compiled for the previous data, is compiled again, so the materials
+    /// that use the shader will pick up the changes on the next frame without any stalls.
+    pub fn reload(&mut self, pipeline_state: &PipelineState, shader: &ShaderResource) {
+        let mut shader_state = shader.state();
+        let Some(shader_data) = shader_state.data() else {
+            return;
+        };
+
+        let mut permutations = self
+            .indices
+            .get(shader.key())
+            .and_then(|index| self.cache.buffer.get(index))
+            .map(|entry| entry.sets.keys().copied().collect::<Vec<_>>())
+            .unwrap_or_default();
+        if !permutations.contains(&ShaderPermutation::BASE) {
+            permutations.push(ShaderPermutation::BASE);
+        }
+
+        for permutation in permutations {
+            self.get_or_compile(pipeline_state, shader.key(), shader_data, permutation);
+        }
+    }
+
+    fn get_or_compile(
+        &mut self,
+        pipeline_state: &PipelineState,
+        resource_key: u64,
+        shader: &Shader,
+        permutation: ShaderPermutation,
+    ) -> Option<&ShaderSet> {
+        self.cache
+            .remove_stale(&mut self.indices, resource_key, &shader.cache_index);
+
+        let permutations = self
+            .cache
+            .get_mut_or_insert_with(&shader.cache_index, Default::default(), || {
+                Ok::<_, FrameworkError>(ShaderPermutations::default())
+            })
+            .ok()?;
+
+        match permutations.sets.entry(permutation) {
+            Entry::Occupied(entry) => Some(entry.into_mut()),
+            Entry::Vacant(entry) => {
+                match ShaderSet::with_permutation(
+                    pipeline_state,
+                    shader,
+                    permutation,
+                    &self.includes,
+                ) {
+                    Ok(shader_set) => Some(entry.insert(shader_set)),
+                    Err(error) => {
+                        Log::err(format!("{}", error));
+                        None
+                    }
                 }
             }
-        } else {
-            None
         }
     }
 
     pub fn update(&mut self, dt: f32) {
-        self.cache.update(dt)
+        self.cache.update(dt);
+        self.indices.update();
     }
 
     pub fn clear(&mut self) {
         self.cache.clear();
+        self.indices.clear();
     }
 }
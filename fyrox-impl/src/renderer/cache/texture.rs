@@ -5,7 +5,7 @@ use crate::{
         scope_profile,
     },
     renderer::{
-        cache::TemporaryCache,
+        cache::{ResourceCacheIndices, TemporaryCache},
         framework::{
             error::FrameworkError,
             gpu_texture::{Coordinate, GpuTexture, PixelKind},
@@ -24,6 +24,7 @@ pub(crate) struct TextureRenderData {
 #[derive(Default)]
 pub struct TextureCache {
     pub(crate) map: TemporaryCache<TextureRenderData>,
+    indices: ResourceCacheIndices,
 }
 
 fn create_gpu_texture(
@@ -47,14 +48,19 @@ fn create_gpu_texture(
 
 impl TextureCache {
     /// Unconditionally uploads requested texture into GPU memory, previous GPU texture will be automatically
-    /// destroyed.
+    /// destroyed. GPU texture of the previous data of the texture (if it was reloaded) is destroyed as well.
     pub fn upload(
         &mut self,
         state: &PipelineState,
-        texture: &TextureResource,
+        texture_resource: &TextureResource,
     ) -> Result<(), FrameworkError> {
-        let mut texture = texture.state();
+        let mut texture = texture_resource.state();
         if let Some(texture) = texture.data() {
+            self.map.remove_stale(
+                &mut self.indices,
+                texture_resource.key(),
+                &texture.cache_index,
+            );
             self.map.get_entry_mut_or_insert_with(
                 &texture.cache_index,
                 Default::default(),
@@ -78,6 +84,12 @@ impl TextureCache {
         let mut texture_data_guard = texture_resource.state();
 
         if let Some(texture) = texture_data_guard.data() {
+            self.map.remove_stale(
+                &mut self.indices,
+                texture_resource.key(),
+                &texture.cache_index,
+            );
+
             match self
                 .map
                 .get_mut_or_insert_with(&texture.cache_index, Default::default(), || {
@@ -163,11 +175,13 @@ impl TextureCache {
     }
 
     pub fn update(&mut self, dt: f32) {
-        self.map.update(dt)
+        self.map.update(dt);
+        self.indices.update();
     }
 
     pub fn clear(&mut self) {
         self.map.clear();
+        self.indices.clear();
     }
 
     pub fn unload(&mut self, texture: TextureResource) {
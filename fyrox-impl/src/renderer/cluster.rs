@@ -0,0 +1,495 @@
+//! Clustered light culling. View frustum of a camera is split into a 3D grid of clusters ("froxels"):
+//! uniformly in screen space and exponentially along view depth. Every point and spot light is assigned
+//! to all the clusters it touches, so a fragment needs to check only the lights of its own cluster. This
+//! makes hundreds of small dynamic lights feasible both for the deferred renderer and for materials with
+//! forward render path.
+//!
+//! Clusters are built on CPU every frame and uploaded to textures, textures are used instead of SSBO for
+//! the same reasons as in [`crate::renderer::storage::MatrixStorage`].
+//!
+//! Clustered lights are available in shaders using the following built-in properties:
+//!
+//! - `fyrox_clusterGrid` - `sampler2D`, ranges of light indices of every cluster.
+//! - `fyrox_clusterLights` - `sampler2D`, parameters of the lights.
+//! - `fyrox_clusterLightIndices` - `sampler2D`, indices of the lights of all clusters.
+//! - `fyrox_clusterParameters` - `vec4`, depth slicing scale (x) and bias (y), inverse size of the frame (zw).
+//! - `fyrox_clusterDepthPlane` - `vec4`, world-space plane, distance to which is the view depth.
+//!
+//! Use `S_ClusteredLighting` built-in method to calculate lighting of a fragment.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, Vector2, Vector3, Vector4},
+        pool::Handle,
+    },
+    graph::SceneGraph,
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            gpu_texture::{
+                GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter, PixelKind,
+            },
+            state::PipelineState,
+        },
+        QualitySettings,
+    },
+    scene::{
+        camera::Camera,
+        graph::Graph,
+        light::{point::PointLight, spot::SpotLight},
+        node::Node,
+    },
+};
+use fxhash::FxHashSet;
+use std::{cell::RefCell, rc::Rc};
+
+/// Amount of clusters along screen X axis. Keep in sync with `S_CLUSTER_GRID_X` in shared.glsl.
+pub const CLUSTER_GRID_X: usize = 16;
+/// Amount of clusters along screen Y axis. Keep in sync with `S_CLUSTER_GRID_Y` in shared.glsl.
+pub const CLUSTER_GRID_Y: usize = 9;
+/// Amount of depth slices. Keep in sync with `S_CLUSTER_GRID_Z` in shared.glsl.
+pub const CLUSTER_GRID_Z: usize = 24;
+
+/// Max amount of lights that could be assigned to clusters in a frame, the rest of the lights are
+/// ignored.
+pub const MAX_CLUSTERED_LIGHTS: usize = 1024;
+
+const CLUSTER_COUNT: usize = CLUSTER_GRID_X * CLUSTER_GRID_Y * CLUSTER_GRID_Z;
+
+// Amount of texels per light in the lights texture.
+const LIGHT_TEXELS: usize = 4;
+
+// Width of the texture with light indices.
+const INDICES_TEXTURE_WIDTH: usize = 1024;
+
+struct ClusteredLight {
+    position: Vector3<f32>,
+    radius: f32,
+    color: Vector3<f32>,
+    direction: Vector3<f32>,
+    half_hotspot_cone_angle_cos: f32,
+    half_cone_angle_cos: f32,
+    // The light is rendered by the deferred renderer separately (for example, it casts shadows).
+    separate: bool,
+}
+
+/// Range of clusters (inclusive) that is touched by a light.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct ClusterRange {
+    pub min: Vector3<usize>,
+    pub max: Vector3<usize>,
+}
+
+/// Depth slicing parameters: `slice = ln(depth) * scale + bias`.
+fn depth_slicing(z_near: f32, z_far: f32) -> Vector2<f32> {
+    let z_near = z_near.max(0.001);
+    let z_far = z_far.max(z_near * 1.001);
+    let scale = CLUSTER_GRID_Z as f32 / (z_far / z_near).ln();
+    Vector2::new(scale, -z_near.ln() * scale)
+}
+
+fn depth_slice(depth: f32, slicing: Vector2<f32>) -> usize {
+    (depth.max(0.0001).ln() * slicing.x + slicing.y).clamp(0.0, (CLUSTER_GRID_Z - 1) as f32)
+        as usize
+}
+
+fn screen_tile(uv: f32, count: usize) -> usize {
+    (uv * count as f32).clamp(0.0, (count - 1) as f32) as usize
+}
+
+/// Calculates the range of clusters that is touched by a sphere with the given view-space center and
+/// radius. `None` is returned if the sphere is outside of the depth range of the camera.
+pub(crate) fn cluster_range(
+    view_center: Vector3<f32>,
+    radius: f32,
+    projection: &Matrix4<f32>,
+    z_near: f32,
+    z_far: f32,
+) -> Option<ClusterRange> {
+    let depth = -view_center.z;
+    let min_depth = depth - radius;
+    let max_depth = depth + radius;
+    if max_depth < z_near || min_depth > z_far {
+        return None;
+    }
+
+    let slicing = depth_slicing(z_near, z_far);
+    let min_z = depth_slice(min_depth.max(z_near), slicing);
+    let max_z = depth_slice(max_depth.min(z_far), slicing);
+
+    // A sphere that crosses the near plane could cover any part of the screen.
+    let (min_uv, max_uv) = if min_depth <= z_near {
+        (Vector2::new(0.0, 0.0), Vector2::new(1.0, 1.0))
+    } else {
+        let mut min_uv = Vector2::repeat(f32::MAX);
+        let mut max_uv = Vector2::repeat(f32::MIN);
+        for i in 0..8 {
+            let corner = view_center
+                + Vector3::new(
+                    if i & 1 == 0 { -radius } else { radius },
+                    if i & 2 == 0 { -radius } else { radius },
+                    if i & 4 == 0 { -radius } else { radius },
+                );
+            let ndc = projection.transform_point(&Point3::from(corner));
+            let uv = Vector2::new(ndc.x * 0.5 + 0.5, ndc.y * 0.5 + 0.5);
+            min_uv = min_uv.inf(&uv);
+            max_uv = max_uv.sup(&uv);
+        }
+        if max_uv.x < 0.0 || max_uv.y < 0.0 || min_uv.x > 1.0 || min_uv.y > 1.0 {
+            return None;
+        }
+        (min_uv, max_uv)
+    };
+
+    Some(ClusterRange {
+        min: Vector3::new(
+            screen_tile(min_uv.x, CLUSTER_GRID_X),
+            screen_tile(min_uv.y, CLUSTER_GRID_Y),
+            min_z,
+        ),
+        max: Vector3::new(
+            screen_tile(max_uv.x, CLUSTER_GRID_X),
+            screen_tile(max_uv.y, CLUSTER_GRID_Y),
+            max_z,
+        ),
+    })
+}
+
+fn make_texture(
+    state: &PipelineState,
+    width: usize,
+    pixel_kind: PixelKind,
+    data: &[u8],
+) -> Result<Rc<RefCell<GpuTexture>>, FrameworkError> {
+    Ok(Rc::new(RefCell::new(GpuTexture::new(
+        state,
+        GpuTextureKind::Rectangle { width, height: 1 },
+        pixel_kind,
+        MinificationFilter::Nearest,
+        MagnificationFilter::Nearest,
+        1,
+        Some(data),
+    )?)))
+}
+
+fn upload<T>(
+    state: &PipelineState,
+    texture: &Rc<RefCell<GpuTexture>>,
+    width: usize,
+    height: usize,
+    pixel_kind: PixelKind,
+    data: &[T],
+) -> Result<(), FrameworkError> {
+    texture.borrow_mut().bind_mut(state, 0).set_data(
+        GpuTextureKind::Rectangle { width, height },
+        pixel_kind,
+        1,
+        Some(crate::core::array_as_u8_slice(data)),
+    )?;
+    Ok(())
+}
+
+/// Per-frame light clusters of a camera, see module docs for more info.
+pub struct LightClusters {
+    grid_texture: Rc<RefCell<GpuTexture>>,
+    lights_texture: Rc<RefCell<GpuTexture>>,
+    indices_texture: Rc<RefCell<GpuTexture>>,
+    lights: Vec<ClusteredLight>,
+    clusters: Vec<Vec<u32>>,
+    clustered: FxHashSet<Handle<Node>>,
+    parameters: Vector4<f32>,
+    depth_plane: Vector4<f32>,
+}
+
+impl LightClusters {
+    /// Creates new empty clusters.
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let empty = [Vector4::<f32>::zeros(); LIGHT_TEXELS];
+        Ok(Self {
+            grid_texture: make_texture(
+                state,
+                1,
+                PixelKind::RGBA32F,
+                crate::core::array_as_u8_slice(&empty[..1]),
+            )?,
+            lights_texture: make_texture(
+                state,
+                LIGHT_TEXELS,
+                PixelKind::RGBA32F,
+                crate::core::array_as_u8_slice(&empty),
+            )?,
+            indices_texture: make_texture(
+                state,
+                1,
+                PixelKind::R32F,
+                crate::core::array_as_u8_slice(&[0.0f32]),
+            )?,
+            lights: Default::default(),
+            clusters: vec![Default::default(); CLUSTER_COUNT],
+            clustered: Default::default(),
+            parameters: Default::default(),
+            depth_plane: Default::default(),
+        })
+    }
+
+    /// Assigns lights of the graph to the clusters of the given camera and uploads the clusters to
+    /// the GPU.
+    pub(crate) fn update(
+        &mut self,
+        state: &PipelineState,
+        camera: &Camera,
+        graph: &Graph,
+        settings: &QualitySettings,
+        frame_size: Vector2<f32>,
+    ) -> Result<(), FrameworkError> {
+        self.lights.clear();
+        self.clustered.clear();
+        for cluster in self.clusters.iter_mut() {
+            cluster.clear();
+        }
+
+        let view_matrix = camera.view_matrix();
+        let projection_matrix = camera.projection_matrix();
+        let z_near = camera.projection().z_near();
+        let z_far = camera.projection().z_far();
+        let frustum = camera.frustum();
+        let camera_position = camera.global_position();
+
+        for (handle, node) in graph.pair_iter() {
+            if self.lights.len() == MAX_CLUSTERED_LIGHTS {
+                break;
+            }
+
+            if !node.global_visibility()
+                || !node.is_globally_enabled()
+                || (node.render_mask() & camera.render_mask()).0 == 0
+            {
+                continue;
+            }
+
+            let distance_to_camera = (node.global_position() - camera_position).norm();
+
+            let (
+                base_light,
+                raw_radius,
+                half_hotspot_cone_angle_cos,
+                half_cone_angle_cos,
+                separate,
+            ) = if let Some(spot) = node.cast::<SpotLight>() {
+                (
+                    spot.base_light_ref(),
+                    spot.distance(),
+                    (spot.hotspot_cone_angle() * 0.5).cos(),
+                    (spot.full_cone_angle() * 0.5).cos(),
+                    spot.cookie_texture_ref().is_some()
+                        || (spot.base_light_ref().is_cast_shadows()
+                            && settings.spot_shadows_enabled
+                            && distance_to_camera <= settings.spot_shadows_distance),
+                )
+            } else if let Some(point) = node.cast::<PointLight>() {
+                (
+                    point.base_light_ref(),
+                    point.radius(),
+                    // Point lights emit light in all directions.
+                    -1.0,
+                    -2.0,
+                    point.base_light_ref().is_cast_shadows()
+                        && settings.point_shadows_enabled
+                        && distance_to_camera <= settings.point_shadows_distance,
+                )
+            } else {
+                continue;
+            };
+
+            let scale = node.local_transform().scale();
+            let radius = raw_radius * scale.x.max(scale.y).max(scale.z);
+            let position = node.global_position();
+
+            if !frustum.is_intersects_sphere(position, radius) {
+                continue;
+            }
+
+            let view_center = view_matrix.transform_point(&Point3::from(position)).coords;
+            let Some(range) = cluster_range(view_center, radius, &projection_matrix, z_near, z_far)
+            else {
+                continue;
+            };
+
+            let index = self.lights.len() as u32;
+            for z in range.min.z..=range.max.z {
+                for y in range.min.y..=range.max.y {
+                    for x in range.min.x..=range.max.x {
+                        self.clusters[(z * CLUSTER_GRID_Y + y) * CLUSTER_GRID_X + x].push(index);
+                    }
+                }
+            }
+
+            if !separate {
+                self.clustered.insert(handle);
+            }
+
+            self.lights.push(ClusteredLight {
+                position,
+                radius,
+                color: base_light.color().srgb_to_linear_f32().xyz() * base_light.intensity(),
+                direction: node
+                    .up_vector()
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_else(Vector3::z),
+                half_hotspot_cone_angle_cos,
+                half_cone_angle_cos,
+                separate,
+            });
+        }
+
+        let slicing = depth_slicing(z_near, z_far);
+        self.parameters = Vector4::new(
+            slicing.x,
+            slicing.y,
+            1.0 / frame_size.x.max(1.0),
+            1.0 / frame_size.y.max(1.0),
+        );
+        // View depth is the distance along the look vector of the camera.
+        let row = view_matrix.row(2);
+        self.depth_plane = -Vector4::new(row[0], row[1], row[2], row[3]);
+
+        self.upload(state)
+    }
+
+    fn upload(&self, state: &PipelineState) -> Result<(), FrameworkError> {
+        let mut grid = Vec::with_capacity(CLUSTER_COUNT);
+        let mut indices = Vec::new();
+        for cluster in self.clusters.iter() {
+            grid.push(Vector4::new(
+                indices.len() as f32,
+                cluster.len() as f32,
+                0.0,
+                0.0,
+            ));
+            indices.extend(cluster.iter().map(|index| *index as f32));
+        }
+        upload(
+            state,
+            &self.grid_texture,
+            CLUSTER_GRID_X * CLUSTER_GRID_Y,
+            CLUSTER_GRID_Z,
+            PixelKind::RGBA32F,
+            &grid,
+        )?;
+
+        let indices_height =
+            ((indices.len() + INDICES_TEXTURE_WIDTH - 1) / INDICES_TEXTURE_WIDTH).max(1);
+        indices.resize(indices_height * INDICES_TEXTURE_WIDTH, 0.0);
+        upload(
+            state,
+            &self.indices_texture,
+            INDICES_TEXTURE_WIDTH,
+            indices_height,
+            PixelKind::R32F,
+            &indices,
+        )?;
+
+        let mut lights = Vec::with_capacity(self.lights.len().max(1) * LIGHT_TEXELS);
+        for light in self.lights.iter() {
+            lights.push(light.position.push(light.radius));
+            lights.push(light.color.push(if light.separate { 1.0 } else { 0.0 }));
+            lights.push(light.direction.push(light.half_hotspot_cone_angle_cos));
+            lights.push(Vector4::new(light.half_cone_angle_cos, 0.0, 0.0, 0.0));
+        }
+        if lights.is_empty() {
+            lights.resize(LIGHT_TEXELS, Vector4::zeros());
+        }
+        upload(
+            state,
+            &self.lights_texture,
+            LIGHT_TEXELS,
+            lights.len() / LIGHT_TEXELS,
+            PixelKind::RGBA32F,
+            &lights,
+        )
+    }
+
+    /// Returns `true` if the given light is rendered using the clusters, such lights must not be
+    /// rendered by the deferred renderer separately.
+    pub fn is_clustered(&self, light: Handle<Node>) -> bool {
+        self.clustered.contains(&light)
+    }
+
+    /// Returns total amount of lights in the clusters.
+    pub fn light_count(&self) -> usize {
+        self.lights.len()
+    }
+
+    /// Returns amount of lights that are rendered only using the clusters.
+    pub fn clustered_light_count(&self) -> usize {
+        self.clustered.len()
+    }
+
+    /// Returns a texture with the ranges of light indices of every cluster.
+    pub fn grid_texture(&self) -> &Rc<RefCell<GpuTexture>> {
+        &self.grid_texture
+    }
+
+    /// Returns a texture with the parameters of every light.
+    pub fn lights_texture(&self) -> &Rc<RefCell<GpuTexture>> {
+        &self.lights_texture
+    }
+
+    /// Returns a texture with the light indices of all the clusters.
+    pub fn indices_texture(&self) -> &Rc<RefCell<GpuTexture>> {
+        &self.indices_texture
+    }
+
+    /// Returns depth slicing scale and bias (xy) and inverse size of the frame (zw).
+    pub fn parameters(&self) -> Vector4<f32> {
+        self.parameters
+    }
+
+    /// Returns world-space plane, distance to which is the view depth of a point.
+    pub fn depth_plane(&self) -> Vector4<f32> {
+        self.depth_plane
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Matrix4, Vector3},
+        renderer::cluster::{cluster_range, CLUSTER_GRID_X, CLUSTER_GRID_Y},
+    };
+
+    #[test]
+    fn test_cluster_range() {
+        let projection = Matrix4::new_perspective(16.0 / 9.0, 60.0f32.to_radians(), 0.1, 100.0);
+
+        // Small light in the center of the view.
+        let range =
+            cluster_range(Vector3::new(0.0, 0.0, -10.0), 0.5, &projection, 0.1, 100.0).unwrap();
+        assert!(range.min.x <= CLUSTER_GRID_X / 2 && range.max.x >= CLUSTER_GRID_X / 2 - 1);
+        assert!(range.min.y <= CLUSTER_GRID_Y / 2 && range.max.y >= CLUSTER_GRID_Y / 2);
+        assert!(range.max.x - range.min.x < 3);
+        assert!(range.max.z - range.min.z < 3);
+
+        // Light behind the camera.
+        assert!(
+            cluster_range(Vector3::new(0.0, 0.0, 10.0), 1.0, &projection, 0.1, 100.0).is_none()
+        );
+
+        // Light far to the side.
+        assert!(cluster_range(
+            Vector3::new(100.0, 0.0, -10.0),
+            1.0,
+            &projection,
+            0.1,
+            100.0
+        )
+        .is_none());
+
+        // Light around the camera covers the entire screen.
+        let range =
+            cluster_range(Vector3::new(0.0, 0.0, 0.0), 2.0, &projection, 0.1, 100.0).unwrap();
+        assert_eq!(range.min, Vector3::new(0, 0, 0));
+        assert_eq!(range.max.x, CLUSTER_GRID_X - 1);
+        assert_eq!(range.max.y, CLUSTER_GRID_Y - 1);
+    }
+}
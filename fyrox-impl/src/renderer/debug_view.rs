@@ -0,0 +1,369 @@
+//! Debug view modes allow you to look at various intermediate data of the renderer, which is
+//! useful to find issues with geometry, materials and lighting. See [`DebugViewMode`] docs for
+//! more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2, Vector3},
+        color::Color,
+        math::Rect,
+        reflect::prelude::*,
+        scope_profile,
+        sstorage::ImmutableString,
+        uuid_provider,
+    },
+    renderer::{
+        bundle::RenderDataBundleStorage,
+        framework::{
+            error::FrameworkError,
+            framebuffer::{BlendParameters, DrawParameters, FrameBuffer},
+            geometry_buffer::{ElementRange, GeometryBuffer, GeometryBufferKind},
+            gpu_program::{GpuProgram, UniformLocation},
+            state::{BlendFactor, BlendFunc, PipelineState, PolygonFace, PolygonFillMode},
+        },
+        gbuffer::GBuffer,
+        GeometryCache, RenderPassStatistics,
+    },
+    scene::{camera::Camera, mesh::surface::SurfaceData},
+};
+use serde::{Deserialize, Serialize};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Debug view mode defines which data of the renderer will be shown on screen.
+#[derive(
+    Copy,
+    Clone,
+    Hash,
+    PartialEq,
+    Eq,
+    Debug,
+    Default,
+    Serialize,
+    Deserialize,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum DebugViewMode {
+    /// Normal rendering, no debug information is shown.
+    #[default]
+    Shaded,
+    /// Edges of every triangle are drawn on top of the shaded frame.
+    Wireframe,
+    /// Shows how many times each pixel was drawn, brighter pixels are drawn more times.
+    Overdraw,
+    /// Shows lighting only, diffuse color of every object is replaced with white color.
+    LightingOnly,
+    /// Shows world-space normals of deferred objects.
+    Normals,
+    /// Shows metallic (red), roughness (green) and ambient occlusion (blue) of deferred objects.
+    RoughnessMetallic,
+    /// Shows which mip level of diffuse textures is sampled. Green color means that one texel is
+    /// mapped to one pixel, red color means that a texture has too high resolution and blue color
+    /// means that a texture has too low resolution.
+    MipLevel,
+    /// Tints every cascade of directional light shadows with its own color - red, green and blue
+    /// for the first, second and third cascade respectively.
+    ShadowCascades,
+}
+
+uuid_provider!(DebugViewMode = "0f3c7b8e-54a1-4d2c-9b6e-2e8d1f4a7c35");
+
+struct DebugViewShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    depth_texture: UniformLocation,
+    normal_texture: UniformLocation,
+    material_texture: UniformLocation,
+    mode: UniformLocation,
+}
+
+impl DebugViewShader {
+    fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/debug_view_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "DebugViewShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            depth_texture: program
+                .uniform_location(state, &ImmutableString::new("depthTexture"))?,
+            normal_texture: program
+                .uniform_location(state, &ImmutableString::new("normalTexture"))?,
+            material_texture: program
+                .uniform_location(state, &ImmutableString::new("materialTexture"))?,
+            mode: program.uniform_location(state, &ImmutableString::new("mode"))?,
+            program,
+        })
+    }
+}
+
+struct DebugViewGeometryShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    show_mip_level: UniformLocation,
+    color: UniformLocation,
+    texture_size: UniformLocation,
+}
+
+impl DebugViewGeometryShader {
+    fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/debug_view_geometry_fs.glsl");
+        let vertex_source = include_str!("shaders/debug_view_geometry_vs.glsl");
+
+        let program = GpuProgram::from_source(
+            state,
+            "DebugViewGeometryShader",
+            vertex_source,
+            fragment_source,
+        )?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            show_mip_level: program
+                .uniform_location(state, &ImmutableString::new("showMipLevel"))?,
+            color: program.uniform_location(state, &ImmutableString::new("color"))?,
+            texture_size: program.uniform_location(state, &ImmutableString::new("textureSize"))?,
+            program,
+        })
+    }
+}
+
+pub(crate) struct DebugViewRenderContext<'a> {
+    pub state: &'a PipelineState,
+    pub mode: DebugViewMode,
+    pub viewport: Rect<i32>,
+    pub camera: &'a Camera,
+    pub gbuffer: &'a GBuffer,
+    pub bundle_storage: &'a RenderDataBundleStorage,
+    pub geom_cache: &'a mut GeometryCache,
+    pub frame_buffer: &'a mut FrameBuffer,
+}
+
+pub(crate) struct DebugViewRenderer {
+    shader: DebugViewShader,
+    geometry_shader: DebugViewGeometryShader,
+    quad: GeometryBuffer,
+}
+
+impl DebugViewRenderer {
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: DebugViewShader::new(state)?,
+            geometry_shader: DebugViewGeometryShader::new(state)?,
+            quad: GeometryBuffer::from_surface_data(
+                &SurfaceData::make_unit_xy_quad(),
+                GeometryBufferKind::StaticDraw,
+                state,
+            )?,
+        })
+    }
+
+    fn render_gbuffer_data(
+        &self,
+        state: &PipelineState,
+        viewport: Rect<i32>,
+        gbuffer: &GBuffer,
+        frame_buffer: &mut FrameBuffer,
+        mode: i32,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let frame_matrix = Matrix4::new_orthographic(
+            0.0,
+            viewport.w() as f32,
+            viewport.h() as f32,
+            0.0,
+            -1.0,
+            1.0,
+        ) * Matrix4::new_nonuniform_scaling(&Vector3::new(
+            viewport.w() as f32,
+            viewport.h() as f32,
+            0.0,
+        ));
+
+        let depth = gbuffer.depth();
+        let normal = gbuffer.normal_texture();
+        let material = gbuffer.material_texture();
+
+        let mut statistics = RenderPassStatistics::default();
+        statistics += frame_buffer.draw(
+            &self.quad,
+            state,
+            viewport,
+            &self.shader.program,
+            &DrawParameters {
+                cull_face: None,
+                color_write: Default::default(),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: false,
+                blend: None,
+                stencil_op: Default::default(),
+            },
+            ElementRange::Full,
+            |mut program_binding| {
+                program_binding
+                    .set_matrix4(&self.shader.wvp_matrix, &frame_matrix)
+                    .set_texture(&self.shader.depth_texture, &depth)
+                    .set_texture(&self.shader.normal_texture, &normal)
+                    .set_texture(&self.shader.material_texture, &material)
+                    .set_i32(&self.shader.mode, mode);
+            },
+        )?;
+        Ok(statistics)
+    }
+
+    fn render_geometry(
+        &self,
+        args: DebugViewRenderContext,
+        draw_params: &DrawParameters,
+        color: Color,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let DebugViewRenderContext {
+            state,
+            mode,
+            viewport,
+            camera,
+            bundle_storage,
+            geom_cache,
+            frame_buffer,
+            ..
+        } = args;
+
+        let mut statistics = RenderPassStatistics::default();
+
+        let show_mip_level = mode == DebugViewMode::MipLevel;
+        let color = color.as_frgba();
+        let view_projection = camera.view_projection_matrix();
+
+        // Hardware-instanced bundles are supported only by forward and deferred renderers.
+        for bundle in bundle_storage
+            .bundles
+            .iter()
+            .filter(|b| b.hardware_instance_count.is_none())
+        {
+            let texture_size = if show_mip_level {
+                let mut material_state = bundle.material.state();
+                let Some(material) = material_state.data() else {
+                    continue;
+                };
+
+                material
+                    .property_ref(&ImmutableString::new("diffuseTexture"))
+                    .and_then(|p| p.as_sampler())
+                    .and_then(|texture| {
+                        let mut texture_state = texture.state();
+                        texture_state
+                            .data()
+                            .and_then(|texture| texture.kind().rectangle_size())
+                    })
+                    .map(|size| size.cast::<f32>())
+                    .unwrap_or_else(|| Vector2::new(1.0, 1.0))
+            } else {
+                Vector2::new(1.0, 1.0)
+            };
+
+            let Some(geometry) = geom_cache.get(state, &bundle.data, bundle.time_to_live) else {
+                continue;
+            };
+
+            for instance in bundle.instances.iter() {
+                statistics += frame_buffer.draw(
+                    geometry.buffer,
+                    state,
+                    viewport,
+                    &self.geometry_shader.program,
+                    draw_params,
+                    geometry.element_range(instance.element_range)?,
+                    |mut program_binding| {
+                        program_binding
+                            .set_matrix4(
+                                &self.geometry_shader.wvp_matrix,
+                                &(view_projection * instance.world_transform),
+                            )
+                            .set_bool(&self.geometry_shader.show_mip_level, show_mip_level)
+                            .set_vector4(&self.geometry_shader.color, &color)
+                            .set_vector2(&self.geometry_shader.texture_size, &texture_size);
+                    },
+                )?;
+            }
+        }
+
+        Ok(statistics)
+    }
+
+    pub(crate) fn render(
+        &self,
+        args: DebugViewRenderContext,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!();
+
+        let state = args.state;
+
+        match args.mode {
+            DebugViewMode::Wireframe => {
+                state.set_polygon_fill_mode(PolygonFace::FrontAndBack, PolygonFillMode::Line);
+                let result = self.render_geometry(
+                    args,
+                    &DrawParameters {
+                        cull_face: None,
+                        color_write: Default::default(),
+                        depth_write: false,
+                        stencil_test: None,
+                        depth_test: true,
+                        blend: None,
+                        stencil_op: Default::default(),
+                    },
+                    Color::opaque(255, 140, 0),
+                );
+                state.set_polygon_fill_mode(PolygonFace::FrontAndBack, PolygonFillMode::Fill);
+                result
+            }
+            DebugViewMode::Overdraw => {
+                args.frame_buffer
+                    .clear(state, args.viewport, Some(Color::BLACK), None, None);
+                self.render_geometry(
+                    args,
+                    &DrawParameters {
+                        cull_face: None,
+                        color_write: Default::default(),
+                        depth_write: false,
+                        stencil_test: None,
+                        depth_test: false,
+                        blend: Some(BlendParameters {
+                            func: BlendFunc::new(BlendFactor::One, BlendFactor::One),
+                            ..Default::default()
+                        }),
+                        stencil_op: Default::default(),
+                    },
+                    Color::opaque(26, 13, 3),
+                )
+            }
+            DebugViewMode::MipLevel => self.render_geometry(
+                args,
+                &DrawParameters {
+                    cull_face: None,
+                    color_write: Default::default(),
+                    depth_write: false,
+                    stencil_test: None,
+                    depth_test: true,
+                    blend: None,
+                    stencil_op: Default::default(),
+                },
+                Color::WHITE,
+            ),
+            DebugViewMode::Normals => {
+                self.render_gbuffer_data(state, args.viewport, args.gbuffer, args.frame_buffer, 0)
+            }
+            DebugViewMode::RoughnessMetallic => {
+                self.render_gbuffer_data(state, args.viewport, args.gbuffer, args.frame_buffer, 1)
+            }
+            // These modes are handled by the G-Buffer and lighting passes.
+            DebugViewMode::Shaded | DebugViewMode::LightingOnly | DebugViewMode::ShadowCascades => {
+                Ok(Default::default())
+            }
+        }
+    }
+}
@@ -1,4 +1,6 @@
 //! Forward renderer is used to render transparent meshes and meshes with custom blending options.
+//! It also renders meshes with order-independent transparency render path into weighted blended
+//! OIT targets, see [`crate::renderer::oit`] for more info.
 //!
 //! # Notes
 //!
@@ -14,14 +16,21 @@ use crate::{
         scope_profile,
         sstorage::ImmutableString,
     },
+    material::GlobalMaterialProperties,
     renderer::{
         apply_material,
         bundle::RenderDataBundleStorage,
         cache::{shader::ShaderCache, texture::TextureCache},
+        cluster::LightClusters,
         framework::{
-            error::FrameworkError, framebuffer::FrameBuffer, gpu_texture::GpuTexture,
-            state::PipelineState,
+            error::FrameworkError,
+            framebuffer::{BlendParameters, DrawParameters, FrameBuffer},
+            geometry_buffer::ElementRange,
+            gpu_program::GpuProgramBinding,
+            gpu_texture::GpuTexture,
+            state::{BlendFactor, BlendFunc, PipelineState},
         },
+        stencil_mask::StencilMasks,
         storage::MatrixStorageCache,
         GeometryCache, LightData, MaterialContext, QualitySettings, RenderPassStatistics,
     },
@@ -30,6 +39,7 @@ use crate::{
         graph::Graph,
         light::{directional::DirectionalLight, point::PointLight, spot::SpotLight},
         mesh::RenderPath,
+        weather::WeatherShaderData,
     },
 };
 use fyrox_core::math::Matrix4Ext;
@@ -37,6 +47,7 @@ use std::{cell::RefCell, rc::Rc};
 
 pub(crate) struct ForwardRenderer {
     render_pass_name: ImmutableString,
+    oit_render_pass_name: ImmutableString,
 }
 
 pub(crate) struct ForwardRenderContext<'a, 'b> {
@@ -55,20 +66,43 @@ pub(crate) struct ForwardRenderContext<'a, 'b> {
     pub black_dummy: Rc<RefCell<GpuTexture>>,
     pub volume_dummy: Rc<RefCell<GpuTexture>>,
     pub scene_depth: Rc<RefCell<GpuTexture>>,
+    pub light_clusters: &'a LightClusters,
     pub matrix_storage: &'a mut MatrixStorageCache,
+    pub global_properties: &'a GlobalMaterialProperties,
     pub ambient_light: Color,
+    pub weather: WeatherShaderData,
+    pub stencil_masks: &'a StencilMasks,
 }
 
 impl ForwardRenderer {
     pub(crate) fn new() -> Self {
         Self {
             render_pass_name: ImmutableString::new("Forward"),
+            oit_render_pass_name: ImmutableString::new("OrderIndependentTransparency"),
         }
     }
 
+    /// Renders all bundles with [`RenderPath::Forward`] render path.
     pub(crate) fn render(
         &self,
         args: ForwardRenderContext,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        self.render_bundles(args, RenderPath::Forward)
+    }
+
+    /// Renders all bundles with [`RenderPath::OrderIndependent`] render path. The frame buffer must
+    /// be the one of [`crate::renderer::oit::OitRenderer`].
+    pub(crate) fn render_order_independent(
+        &self,
+        args: ForwardRenderContext,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        self.render_bundles(args, RenderPath::OrderIndependent)
+    }
+
+    fn render_bundles(
+        &self,
+        args: ForwardRenderContext,
+        render_path: RenderPath,
     ) -> Result<RenderPassStatistics, FrameworkError> {
         scope_profile!();
 
@@ -90,8 +124,12 @@ impl ForwardRenderer {
             black_dummy,
             volume_dummy,
             scene_depth,
+            light_clusters,
             matrix_storage,
+            global_properties,
             ambient_light,
+            weather,
+            stencil_masks,
         } = args;
 
         let initial_view_projection = camera.view_projection_matrix();
@@ -106,7 +144,10 @@ impl ForwardRenderer {
 
         let mut light_data = LightData::default();
         for light in graph.linear_iter() {
-            if !light.global_visibility() || light_data.count == light_data.parameters.len() {
+            if !light.global_visibility()
+                || (light.render_mask() & camera.render_mask()).0 == 0
+                || light_data.count == light_data.parameters.len()
+            {
                 continue;
             }
 
@@ -153,7 +194,7 @@ impl ForwardRenderer {
         for bundle in bundle_storage
             .bundles
             .iter()
-            .filter(|b| b.render_path == RenderPath::Forward)
+            .filter(|b| b.render_path == render_path)
         {
             let mut material_state = bundle.material.state();
 
@@ -172,13 +213,25 @@ impl ForwardRenderer {
                 .as_ref()
                 .and_then(|c| c.blend_shape_storage.clone());
 
+            let pass_name = if render_path == RenderPath::OrderIndependent {
+                &self.oit_render_pass_name
+            } else {
+                &self.render_pass_name
+            };
+
             let Some(render_pass) = shader_cache
-                .get(state, material.shader())
-                .and_then(|shader_set| shader_set.render_passes.get(&self.render_pass_name))
+                .get_for_material(state, material)
+                .and_then(|shader_set| shader_set.render_passes.get(pass_name))
             else {
                 continue;
             };
 
+            let draw_params = if render_path == RenderPath::OrderIndependent {
+                order_independent_draw_params(&render_pass.draw_params)
+            } else {
+                render_pass.draw_params.clone()
+            };
+
             for instance in bundle.instances.iter() {
                 let view_projection = if instance.depth_offset != 0.0 {
                     let mut projection = camera.projection_matrix();
@@ -188,47 +241,89 @@ impl ForwardRenderer {
                     initial_view_projection
                 };
 
-                statistics += framebuffer.draw(
-                    geometry,
-                    state,
-                    viewport,
-                    &render_pass.program,
-                    &render_pass.draw_params,
-                    instance.element_range,
-                    |mut program_binding| {
-                        apply_material(MaterialContext {
-                            material,
-                            program_binding: &mut program_binding,
-                            texture_cache,
-                            world_matrix: &instance.world_transform,
-                            view_projection_matrix: &view_projection,
-                            wvp_matrix: &(view_projection * instance.world_transform),
-                            bone_matrices: &instance.bone_matrices,
-                            use_skeletal_animation: bundle.is_skinned,
-                            camera_position: &camera.global_position(),
-                            camera_up_vector: &camera_up,
-                            camera_side_vector: &camera_side,
-                            z_near: camera.projection().z_near(),
-                            z_far: camera.projection().z_far(),
-                            use_pom: quality_settings.use_parallax_mapping,
-                            light_position: &Default::default(),
-                            blend_shapes_storage: blend_shapes_storage.as_ref(),
-                            blend_shapes_weights: &instance.blend_shapes_weights,
-                            normal_dummy: &normal_dummy,
-                            white_dummy: &white_dummy,
-                            black_dummy: &black_dummy,
-                            volume_dummy: &volume_dummy,
-                            matrix_storage,
-                            persistent_identifier: instance.persistent_identifier,
-                            light_data: Some(&light_data),
-                            ambient_light,
-                            scene_depth: Some(&scene_depth),
-                        });
-                    },
-                )?;
+                let apply_uniforms = |mut program_binding: GpuProgramBinding| {
+                    apply_material(MaterialContext {
+                        material,
+                        global_properties,
+                        program_binding: &mut program_binding,
+                        texture_cache,
+                        world_matrix: &instance.world_transform,
+                        view_projection_matrix: &view_projection,
+                        wvp_matrix: &(view_projection * instance.world_transform),
+                        bone_matrices: &instance.bone_matrices,
+                        prev_world_matrix: &instance.prev_world_transform,
+                        prev_bone_matrices: &instance.prev_bone_matrices,
+                        use_skeletal_animation: bundle.is_skinned,
+                        camera_position: &camera.global_position(),
+                        camera_up_vector: &camera_up,
+                        camera_side_vector: &camera_side,
+                        z_near: camera.projection().z_near(),
+                        z_far: camera.projection().z_far(),
+                        use_pom: quality_settings.use_parallax_mapping,
+                        light_position: &Default::default(),
+                        blend_shapes_storage: blend_shapes_storage.as_ref(),
+                        blend_shapes_weights: &instance.blend_shapes_weights,
+                        normal_dummy: &normal_dummy,
+                        white_dummy: &white_dummy,
+                        black_dummy: &black_dummy,
+                        volume_dummy: &volume_dummy,
+                        matrix_storage,
+                        persistent_identifier: instance.persistent_identifier,
+                        light_data: Some(&light_data),
+                        ambient_light,
+                        weather,
+                        scene_depth: Some(&scene_depth),
+                        light_clusters: Some(light_clusters),
+                    });
+                };
+
+                let instance_draw_params =
+                    stencil_masks.draw_params(instance.node_handle, &draw_params);
+
+                statistics += match bundle.hardware_instance_count {
+                    Some(count) => framebuffer.draw_instances(
+                        count,
+                        geometry.buffer,
+                        state,
+                        viewport,
+                        &render_pass.program,
+                        &instance_draw_params,
+                        geometry.element_range(ElementRange::Full)?,
+                        apply_uniforms,
+                    )?,
+                    None => framebuffer.draw(
+                        geometry.buffer,
+                        state,
+                        viewport,
+                        &render_pass.program,
+                        &instance_draw_params,
+                        geometry.element_range(instance.element_range)?,
+                        apply_uniforms,
+                    )?,
+                };
             }
         }
 
         Ok(statistics)
     }
 }
+
+// Weighted blended OIT relies on specific blending and it must not write depth, so these parameters are
+// forced regardless of what the shader of a material specifies. Colors and weights are accumulated
+// additively, while revealage (stored in alpha) is accumulated multiplicatively.
+fn order_independent_draw_params(draw_params: &DrawParameters) -> DrawParameters {
+    DrawParameters {
+        depth_write: false,
+        depth_test: true,
+        blend: Some(BlendParameters {
+            func: BlendFunc::new_separate(
+                BlendFactor::One,
+                BlendFactor::One,
+                BlendFactor::Zero,
+                BlendFactor::OneMinusSrcAlpha,
+            ),
+            ..Default::default()
+        }),
+        ..draw_params.clone()
+    }
+}
@@ -116,6 +116,15 @@ unsafe fn set_attachment(state: &PipelineState, gl_attachment_kind: u32, texture
                 0,
             );
         }
+        GpuTextureKind::Array { .. } => {
+            state.gl.framebuffer_texture_layer(
+                glow::FRAMEBUFFER,
+                gl_attachment_kind,
+                Some(texture.id()),
+                0,
+                0,
+            );
+        }
     }
 }
 
@@ -222,6 +231,34 @@ impl FrameBuffer {
         self.fbo
     }
 
+    /// Same as [`Self::clear`], but only the pixels within the given viewport are cleared (the clear
+    /// is limited using scissor test).
+    pub fn clear_viewport(
+        &mut self,
+        state: &PipelineState,
+        viewport: Rect<i32>,
+        color: Option<Color>,
+        depth: Option<f32>,
+        stencil: Option<i32>,
+    ) {
+        state.set_scissor_test(true);
+        state.set_scissor_box(viewport.x(), viewport.y(), viewport.w(), viewport.h());
+        self.clear(state, viewport, color, depth, stencil);
+        state.set_scissor_test(false);
+    }
+
+    /// Clears a color attachment with the given index, other attachments are kept intact. Unlike [`Self::clear`],
+    /// it respects current color write mask, so it could be used to clear only specific channels of the attachment.
+    pub fn clear_color_attachment(&mut self, state: &PipelineState, index: usize, color: Color) {
+        state.set_framebuffer(self.id());
+
+        unsafe {
+            state
+                .gl
+                .clear_buffer_f32_slice(glow::COLOR, index as u32, &color.as_frgba().data.0[0]);
+        }
+    }
+
     pub fn clear(
         &mut self,
         state: &PipelineState,
@@ -351,7 +388,10 @@ impl FrameBuffer {
 
         pre_draw(self.id(), state, viewport, program, params, apply_uniforms);
 
-        geometry.bind(state).draw(element_range)
+        geometry
+            .bind(state)
+            .with_patches(program.uses_tessellation())
+            .draw(element_range)
     }
 
     pub fn draw_instances<F: FnOnce(GpuProgramBinding<'_, '_>)>(
@@ -362,12 +402,16 @@ impl FrameBuffer {
         viewport: Rect<i32>,
         program: &GpuProgram,
         params: &DrawParameters,
+        element_range: ElementRange,
         apply_uniforms: F,
-    ) -> DrawCallStatistics {
+    ) -> Result<DrawCallStatistics, FrameworkError> {
         scope_profile!();
 
         pre_draw(self.id(), state, viewport, program, params, apply_uniforms);
-        geometry.bind(state).draw_instances(count)
+        geometry
+            .bind(state)
+            .with_patches(program.uses_tessellation())
+            .draw_instances(count, element_range)
     }
 }
 
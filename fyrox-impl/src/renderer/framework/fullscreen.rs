@@ -0,0 +1,606 @@
+//! Reusable building blocks for full-screen passes. [`FullscreenPass`] draws a unit quad over a
+//! viewport of a frame buffer, [`RenderTarget`] and [`PingPongBuffer`] are the typical targets for
+//! such passes. There is also a set of common primitives - [`CopyPass`], [`SeparableBlurPass`],
+//! [`DownsamplePass`] and [`UpsamplePass`] - that are used by the built-in effects of the renderer
+//! and could be used by custom render passes as well.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2, Vector3},
+        color::Color,
+        math::Rect,
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{
+                Attachment, AttachmentKind, BlendParameters, DrawParameters, FrameBuffer,
+            },
+            geometry_buffer::{
+                DrawCallStatistics, ElementRange, GeometryBuffer, GeometryBufferKind,
+            },
+            gpu_program::{GpuProgram, GpuProgramBinding, UniformLocation},
+            gpu_texture::{
+                Coordinate, GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter,
+                PixelKind, WrapMode,
+            },
+            state::PipelineState,
+        },
+        RenderPassStatistics,
+    },
+    scene::mesh::surface::SurfaceData,
+};
+use lazy_static::lazy_static;
+use std::{cell::RefCell, rc::Rc};
+
+/// Max radius of the kernel of [`SeparableBlurPass`]. Keep in sync with the shader.
+pub const MAX_BLUR_RADIUS: usize = 16;
+
+lazy_static! {
+    static ref WORLD_VIEW_PROJECTION: ImmutableString = ImmutableString::new("worldViewProjection");
+}
+
+/// Vertex shader, that could be used by custom full-screen passes. It passes texture coordinates
+/// of the quad to the fragment shader as `texCoord`.
+pub const FULLSCREEN_VERTEX_SHADER: &str = include_str!("shaders/fullscreen_vs.glsl");
+
+/// Draw parameters of a full-screen pass: no culling and no depth test or write.
+pub fn fullscreen_draw_parameters(blend: Option<BlendParameters>) -> DrawParameters {
+    DrawParameters {
+        cull_face: None,
+        color_write: Default::default(),
+        depth_write: false,
+        stencil_test: None,
+        depth_test: false,
+        blend,
+        stencil_op: Default::default(),
+    }
+}
+
+/// Returns a matrix, that projects the unit quad to the given viewport.
+pub fn fullscreen_matrix(viewport: Rect<i32>) -> Matrix4<f32> {
+    Matrix4::new_orthographic(
+        0.0,
+        viewport.w() as f32,
+        viewport.h() as f32,
+        0.0,
+        -1.0,
+        1.0,
+    ) * Matrix4::new_nonuniform_scaling(&Vector3::new(
+        viewport.w() as f32,
+        viewport.h() as f32,
+        0.0,
+    ))
+}
+
+/// Calculates sizes of a chain of progressively downsampled mips. Each mip has half of the size of
+/// the previous one, the first mip has half of the given size. The chain is never empty, the first
+/// mip of a degenerated size is clamped to one pixel.
+pub fn mip_chain_sizes(width: usize, height: usize, max_count: usize) -> Vec<(usize, usize)> {
+    let mut sizes = Vec::new();
+    let (mut mip_width, mut mip_height) = (width, height);
+    while sizes.len() < max_count && mip_width > 1 && mip_height > 1 {
+        mip_width /= 2;
+        mip_height /= 2;
+        sizes.push((mip_width, mip_height));
+    }
+    if sizes.is_empty() {
+        sizes.push((width.max(1), height.max(1)));
+    }
+    sizes
+}
+
+/// Normalized one-sided Gaussian kernel. The first weight is the weight of the central texel, the
+/// rest are used for the texels on both sides of it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GaussianKernel {
+    weights: Vec<f32>,
+}
+
+impl GaussianKernel {
+    /// Creates a kernel with the given radius (clamped to [`MAX_BLUR_RADIUS`]) and standard
+    /// deviation. Non-positive deviation is replaced with the half of the radius.
+    pub fn new(radius: usize, sigma: f32) -> Self {
+        let radius = radius.min(MAX_BLUR_RADIUS);
+        let sigma = if sigma > 0.0 {
+            sigma
+        } else {
+            (radius as f32 * 0.5).max(f32::EPSILON)
+        };
+
+        let mut weights = (0..=radius)
+            .map(|i| (-((i * i) as f32) / (2.0 * sigma * sigma)).exp())
+            .collect::<Vec<_>>();
+
+        let sum = weights[0] + 2.0 * weights[1..].iter().sum::<f32>();
+        for weight in weights.iter_mut() {
+            *weight /= sum;
+        }
+
+        Self { weights }
+    }
+
+    /// Returns the radius of the kernel.
+    pub fn radius(&self) -> usize {
+        self.weights.len() - 1
+    }
+
+    /// Returns the weights of the kernel.
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+}
+
+/// A helper, that draws the unit quad over a viewport of a frame buffer. The quad must be created
+/// with [`FullscreenPass::make_quad`] (the renderer has one already). The pass sets the
+/// `worldViewProjection` uniform automatically, if the program has it.
+pub struct FullscreenPass<'a> {
+    /// Pipeline state.
+    pub state: &'a PipelineState,
+    /// Unit quad, that is used for drawing.
+    pub quad: &'a GeometryBuffer,
+}
+
+impl<'a> FullscreenPass<'a> {
+    /// Creates a new full-screen pass.
+    pub fn new(state: &'a PipelineState, quad: &'a GeometryBuffer) -> Self {
+        Self { state, quad }
+    }
+
+    /// Creates the unit quad, that could be used with the pass.
+    pub fn make_quad(state: &PipelineState) -> Result<GeometryBuffer, FrameworkError> {
+        GeometryBuffer::from_surface_data(
+            &SurfaceData::make_unit_xy_quad(),
+            GeometryBufferKind::StaticDraw,
+            state,
+        )
+    }
+
+    /// Draws the quad over the viewport of the frame buffer using the given program.
+    pub fn draw<F: FnOnce(&mut GpuProgramBinding<'_, '_>)>(
+        &self,
+        framebuffer: &mut FrameBuffer,
+        viewport: Rect<i32>,
+        program: &GpuProgram,
+        blend: Option<BlendParameters>,
+        apply_uniforms: F,
+    ) -> Result<DrawCallStatistics, FrameworkError> {
+        framebuffer.draw(
+            self.quad,
+            self.state,
+            viewport,
+            program,
+            &fullscreen_draw_parameters(blend),
+            ElementRange::Full,
+            |mut program_binding| {
+                if let Some(location) = program_binding.uniform_location(&WORLD_VIEW_PROJECTION) {
+                    program_binding.set_matrix4(&location, &fullscreen_matrix(viewport));
+                }
+                apply_uniforms(&mut program_binding);
+            },
+        )
+    }
+}
+
+/// A frame buffer with a single color attachment of a fixed size. The texture is clamped to edge.
+pub struct RenderTarget {
+    framebuffer: FrameBuffer,
+    width: usize,
+    height: usize,
+}
+
+impl RenderTarget {
+    /// Creates a new render target. Use linear filtering, if the target will be sampled between
+    /// texels (for example, by [`DownsamplePass`] with the wide filter).
+    pub fn new(
+        state: &PipelineState,
+        width: usize,
+        height: usize,
+        pixel_kind: PixelKind,
+        filter: MagnificationFilter,
+    ) -> Result<Self, FrameworkError> {
+        let mut texture = GpuTexture::new(
+            state,
+            GpuTextureKind::Rectangle { width, height },
+            pixel_kind,
+            match filter {
+                MagnificationFilter::Nearest => MinificationFilter::Nearest,
+                MagnificationFilter::Linear => MinificationFilter::Linear,
+            },
+            filter,
+            1,
+            None,
+        )?;
+        texture
+            .bind_mut(state, 0)
+            .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+            .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
+
+        Ok(Self {
+            framebuffer: FrameBuffer::new(
+                state,
+                None,
+                vec![Attachment {
+                    kind: AttachmentKind::Color,
+                    texture: Rc::new(RefCell::new(texture)),
+                }],
+            )?,
+            width,
+            height,
+        })
+    }
+
+    /// Returns the frame buffer of the target.
+    pub fn framebuffer(&self) -> &FrameBuffer {
+        &self.framebuffer
+    }
+
+    /// Returns the frame buffer of the target.
+    pub fn framebuffer_mut(&mut self) -> &mut FrameBuffer {
+        &mut self.framebuffer
+    }
+
+    /// Returns the texture of the target.
+    pub fn texture(&self) -> Rc<RefCell<GpuTexture>> {
+        self.framebuffer.color_attachments()[0].texture.clone()
+    }
+
+    /// Returns the size of the target in pixels.
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Returns the viewport, that covers the entire target.
+    pub fn viewport(&self) -> Rect<i32> {
+        Rect::new(0, 0, self.width as i32, self.height as i32)
+    }
+
+    /// Returns the size of a texel in texture coordinates.
+    pub fn texel_size(&self) -> Vector2<f32> {
+        Vector2::new(1.0 / self.width as f32, 1.0 / self.height as f32)
+    }
+
+    /// Clears the target with the given color.
+    pub fn clear(&mut self, state: &PipelineState, color: Color) {
+        let viewport = self.viewport();
+        self.framebuffer
+            .clear(state, viewport, Some(color), None, None);
+    }
+
+    /// Draws the quad over the entire target.
+    pub fn draw<F: FnOnce(&mut GpuProgramBinding<'_, '_>)>(
+        &mut self,
+        pass: &FullscreenPass,
+        program: &GpuProgram,
+        blend: Option<BlendParameters>,
+        apply_uniforms: F,
+    ) -> Result<DrawCallStatistics, FrameworkError> {
+        let viewport = self.viewport();
+        pass.draw(
+            &mut self.framebuffer,
+            viewport,
+            program,
+            blend,
+            apply_uniforms,
+        )
+    }
+}
+
+/// Two render targets of the same size and format, that are used for multi-pass effects. Every pass
+/// reads the result of the previous one and writes to the other target, then the targets are
+/// swapped.
+pub struct PingPongBuffer {
+    targets: [RenderTarget; 2],
+    current: usize,
+}
+
+impl PingPongBuffer {
+    /// Creates a new buffer, see [`RenderTarget::new`] for more info about the arguments.
+    pub fn new(
+        state: &PipelineState,
+        width: usize,
+        height: usize,
+        pixel_kind: PixelKind,
+        filter: MagnificationFilter,
+    ) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            targets: [
+                RenderTarget::new(state, width, height, pixel_kind, filter)?,
+                RenderTarget::new(state, width, height, pixel_kind, filter)?,
+            ],
+            current: 0,
+        })
+    }
+
+    /// Returns the target with the result of the last pass.
+    pub fn read(&self) -> &RenderTarget {
+        &self.targets[self.current]
+    }
+
+    /// Returns the target, that should be used by the next pass.
+    pub fn write(&mut self) -> &mut RenderTarget {
+        &mut self.targets[1 - self.current]
+    }
+
+    /// Returns the texture with the result of the last pass.
+    pub fn result(&self) -> Rc<RefCell<GpuTexture>> {
+        self.read().texture()
+    }
+
+    /// Makes the written target the one to read from. Must be called after every pass.
+    pub fn swap(&mut self) {
+        self.current = 1 - self.current;
+    }
+
+    /// Returns the size of the targets in pixels.
+    pub fn size(&self) -> (usize, usize) {
+        self.read().size()
+    }
+}
+
+fn make_program(
+    state: &PipelineState,
+    name: &str,
+    fragment_source: &str,
+) -> Result<GpuProgram, FrameworkError> {
+    GpuProgram::from_source(state, name, FULLSCREEN_VERTEX_SHADER, fragment_source)
+}
+
+/// Copies a texture to a render target, the texture is stretched to the size of the target.
+pub struct CopyPass {
+    program: GpuProgram,
+    src_sampler: UniformLocation,
+}
+
+impl CopyPass {
+    /// Creates a new copy pass.
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let program = make_program(state, "CopyShader", include_str!("shaders/copy_fs.glsl"))?;
+        Ok(Self {
+            src_sampler: program.uniform_location(state, &ImmutableString::new("srcSampler"))?,
+            program,
+        })
+    }
+
+    /// Copies the source to the target, optionally blending it with the content of the target.
+    pub fn render(
+        &self,
+        pass: &FullscreenPass,
+        target: &mut RenderTarget,
+        source: &Rc<RefCell<GpuTexture>>,
+        blend: Option<BlendParameters>,
+    ) -> Result<DrawCallStatistics, FrameworkError> {
+        target.draw(pass, &self.program, blend, |program_binding| {
+            program_binding.set_texture(&self.src_sampler, source);
+        })
+    }
+}
+
+/// Separable Gaussian blur, it blurs the image horizontally and then vertically, which is much
+/// cheaper than a two-dimensional kernel of the same radius.
+pub struct SeparableBlurPass {
+    program: GpuProgram,
+    src_sampler: UniformLocation,
+    direction: UniformLocation,
+    weights: UniformLocation,
+    radius: UniformLocation,
+}
+
+impl SeparableBlurPass {
+    /// Creates a new blur pass.
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let program = make_program(
+            state,
+            "SeparableBlurShader",
+            include_str!("shaders/separable_blur_fs.glsl"),
+        )?;
+        Ok(Self {
+            src_sampler: program.uniform_location(state, &ImmutableString::new("srcSampler"))?,
+            direction: program.uniform_location(state, &ImmutableString::new("direction"))?,
+            weights: program.uniform_location(state, &ImmutableString::new("weights"))?,
+            radius: program.uniform_location(state, &ImmutableString::new("radius"))?,
+            program,
+        })
+    }
+
+    /// Blurs the source in one direction and writes the result to the target. The direction is
+    /// measured in texels of the source, `(1, 0)` for horizontal blur and `(0, 1)` for vertical.
+    pub fn render_direction(
+        &self,
+        pass: &FullscreenPass,
+        target: &mut RenderTarget,
+        source: &Rc<RefCell<GpuTexture>>,
+        direction: Vector2<f32>,
+        kernel: &GaussianKernel,
+    ) -> Result<DrawCallStatistics, FrameworkError> {
+        let source_size = match source.borrow().kind() {
+            GpuTextureKind::Rectangle { width, height } => {
+                Vector2::new(width.max(1) as f32, height.max(1) as f32)
+            }
+            _ => Vector2::new(1.0, 1.0),
+        };
+        let direction = direction.component_div(&source_size);
+
+        target.draw(pass, &self.program, None, |program_binding| {
+            program_binding
+                .set_texture(&self.src_sampler, source)
+                .set_vector2(&self.direction, &direction)
+                .set_f32_slice(&self.weights, kernel.weights())
+                .set_i32(&self.radius, kernel.radius() as i32);
+        })
+    }
+
+    /// Blurs the source in both directions, the result is stored in the buffer (see
+    /// [`PingPongBuffer::result`]). The source could be the result of the buffer itself.
+    pub fn render(
+        &self,
+        pass: &FullscreenPass,
+        buffer: &mut PingPongBuffer,
+        source: &Rc<RefCell<GpuTexture>>,
+        kernel: &GaussianKernel,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut stats = RenderPassStatistics::default();
+
+        stats +=
+            self.render_direction(pass, buffer.write(), source, Vector2::new(1.0, 0.0), kernel)?;
+        buffer.swap();
+
+        let horizontal = buffer.result();
+        stats += self.render_direction(
+            pass,
+            buffer.write(),
+            &horizontal,
+            Vector2::new(0.0, 1.0),
+            kernel,
+        )?;
+        buffer.swap();
+
+        Ok(stats)
+    }
+}
+
+/// Filter of [`DownsamplePass`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DownsampleFilter {
+    /// Averages 2x2 block of texels, works with any filtering of the source.
+    Box,
+    /// 13-tap filter, that is much more stable when the result is downsampled further. The source
+    /// must use linear filtering.
+    Wide,
+}
+
+/// Downsamples a texture to a render target, that has half of the size of the texture.
+pub struct DownsamplePass {
+    program: GpuProgram,
+    src_sampler: UniformLocation,
+    src_texel_size: UniformLocation,
+    wide: UniformLocation,
+}
+
+impl DownsamplePass {
+    /// Creates a new downsample pass.
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let program = make_program(
+            state,
+            "DownsampleShader",
+            include_str!("shaders/downsample_fs.glsl"),
+        )?;
+        Ok(Self {
+            src_sampler: program.uniform_location(state, &ImmutableString::new("srcSampler"))?,
+            src_texel_size: program
+                .uniform_location(state, &ImmutableString::new("srcTexelSize"))?,
+            wide: program.uniform_location(state, &ImmutableString::new("wide"))?,
+            program,
+        })
+    }
+
+    /// Downsamples the source with the given texel size to the target.
+    pub fn render(
+        &self,
+        pass: &FullscreenPass,
+        target: &mut RenderTarget,
+        source: &Rc<RefCell<GpuTexture>>,
+        src_texel_size: Vector2<f32>,
+        filter: DownsampleFilter,
+    ) -> Result<DrawCallStatistics, FrameworkError> {
+        target.draw(pass, &self.program, None, |program_binding| {
+            program_binding
+                .set_texture(&self.src_sampler, source)
+                .set_vector2(&self.src_texel_size, &src_texel_size)
+                .set_bool(&self.wide, filter == DownsampleFilter::Wide);
+        })
+    }
+}
+
+/// Upsamples a texture to a render target using a 3x3 tent filter. It is usually used together with
+/// additive blending to accumulate a chain of mips.
+pub struct UpsamplePass {
+    program: GpuProgram,
+    src_sampler: UniformLocation,
+    src_texel_size: UniformLocation,
+    radius: UniformLocation,
+}
+
+impl UpsamplePass {
+    /// Creates a new upsample pass.
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let program = make_program(
+            state,
+            "UpsampleShader",
+            include_str!("shaders/upsample_fs.glsl"),
+        )?;
+        Ok(Self {
+            src_sampler: program.uniform_location(state, &ImmutableString::new("srcSampler"))?,
+            src_texel_size: program
+                .uniform_location(state, &ImmutableString::new("srcTexelSize"))?,
+            radius: program.uniform_location(state, &ImmutableString::new("radius"))?,
+            program,
+        })
+    }
+
+    /// Upsamples the source with the given texel size to the target. The radius scales the
+    /// distance between the taps of the filter, `1.0` is the default.
+    pub fn render(
+        &self,
+        pass: &FullscreenPass,
+        target: &mut RenderTarget,
+        source: &Rc<RefCell<GpuTexture>>,
+        src_texel_size: Vector2<f32>,
+        radius: f32,
+        blend: Option<BlendParameters>,
+    ) -> Result<DrawCallStatistics, FrameworkError> {
+        target.draw(pass, &self.program, blend, |program_binding| {
+            program_binding
+                .set_texture(&self.src_sampler, source)
+                .set_vector2(&self.src_texel_size, &src_texel_size)
+                .set_f32(&self.radius, radius);
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Point3, Vector2},
+            math::Rect,
+        },
+        renderer::framework::fullscreen::{
+            fullscreen_matrix, mip_chain_sizes, GaussianKernel, MAX_BLUR_RADIUS,
+        },
+    };
+
+    #[test]
+    fn test_mip_chain_sizes() {
+        assert_eq!(
+            mip_chain_sizes(64, 32, 8),
+            vec![(32, 16), (16, 8), (8, 4), (4, 2), (2, 1)]
+        );
+        assert_eq!(mip_chain_sizes(1920, 1080, 2), vec![(960, 540), (480, 270)]);
+        assert_eq!(mip_chain_sizes(1, 0, 8), vec![(1, 1)]);
+    }
+
+    #[test]
+    fn test_gaussian_kernel() {
+        for radius in [0, 1, 4, MAX_BLUR_RADIUS, MAX_BLUR_RADIUS + 10] {
+            let kernel = GaussianKernel::new(radius, 0.0);
+            assert_eq!(kernel.radius(), radius.min(MAX_BLUR_RADIUS));
+            let weights = kernel.weights();
+            let sum = weights[0] + 2.0 * weights[1..].iter().sum::<f32>();
+            assert!((sum - 1.0).abs() < 1.0e-5);
+            assert!(weights.windows(2).all(|w| w[0] >= w[1]));
+        }
+    }
+
+    #[test]
+    fn test_fullscreen_matrix() {
+        let matrix = fullscreen_matrix(Rect::new(0, 0, 200, 100));
+        let project = |x: f32, y: f32| {
+            let p = matrix.transform_point(&Point3::new(x, y, 0.0));
+            Vector2::new(p.x, p.y)
+        };
+        assert_eq!(project(0.0, 0.0), Vector2::new(-1.0, 1.0));
+        assert_eq!(project(1.0, 1.0), Vector2::new(1.0, -1.0));
+    }
+}
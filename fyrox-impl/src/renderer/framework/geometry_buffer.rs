@@ -156,6 +156,7 @@ pub enum GeometryBufferKind {
 pub enum ElementKind {
     Triangle,
     Line,
+    Point,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -175,6 +176,7 @@ impl ElementKind {
         match self {
             ElementKind::Triangle => 3,
             ElementKind::Line => 2,
+            ElementKind::Point => 1,
         }
     }
 }
@@ -182,6 +184,7 @@ impl ElementKind {
 pub struct GeometryBufferBinding<'a> {
     state: &'a PipelineState,
     buffer: &'a GeometryBuffer,
+    patches: bool,
 }
 
 #[derive(Debug, Copy, Clone, Default)]
@@ -214,6 +217,57 @@ impl<'a> GeometryBufferBinding<'a> {
         self
     }
 
+    pub fn set_points(self, points: &[u32]) -> Self {
+        scope_profile!();
+
+        assert_eq!(self.buffer.element_kind, ElementKind::Point);
+        self.buffer.element_count.set(points.len());
+
+        unsafe {
+            self.set_elements(array_as_u8_slice(points));
+        }
+
+        self
+    }
+
+    /// Allocates storage for the given amount of triangles, the content of the storage is
+    /// undefined. Use [`Self::set_triangles_at`] to fill it.
+    pub fn allocate_triangles(self, count: usize) -> Self {
+        scope_profile!();
+
+        assert_eq!(self.buffer.element_kind, ElementKind::Triangle);
+        self.buffer.element_count.set(count);
+
+        unsafe {
+            self.state.gl.buffer_data_size(
+                glow::ELEMENT_ARRAY_BUFFER,
+                (count * size_of::<TriangleDefinition>()) as i32,
+                glow::DYNAMIC_DRAW,
+            );
+        }
+
+        self
+    }
+
+    /// Writes the given triangles at the given offset (in triangles) of the storage. The storage
+    /// must be large enough to fit the triangles.
+    pub fn set_triangles_at(self, offset: usize, triangles: &[TriangleDefinition]) -> Self {
+        scope_profile!();
+
+        assert_eq!(self.buffer.element_kind, ElementKind::Triangle);
+        assert!(offset + triangles.len() <= self.buffer.element_count.get());
+
+        unsafe {
+            self.state.gl.buffer_sub_data_u8_slice(
+                glow::ELEMENT_ARRAY_BUFFER,
+                (offset * size_of::<TriangleDefinition>()) as i32,
+                array_as_u8_slice(triangles),
+            );
+        }
+
+        self
+    }
+
     unsafe fn set_elements(&self, data: &[u8]) {
         scope_profile!();
 
@@ -222,9 +276,10 @@ impl<'a> GeometryBufferBinding<'a> {
             .buffer_data_u8_slice(glow::ELEMENT_ARRAY_BUFFER, data, glow::DYNAMIC_DRAW);
     }
 
-    pub fn draw(&self, element_range: ElementRange) -> Result<DrawCallStatistics, FrameworkError> {
-        scope_profile!();
-
+    fn element_window(
+        &self,
+        element_range: ElementRange,
+    ) -> Result<(usize, usize), FrameworkError> {
         let (offset, count) = match element_range {
             ElementRange::Full => (0, self.buffer.element_count.get()),
             ElementRange::Specific { offset, count } => (offset, count),
@@ -239,22 +294,48 @@ impl<'a> GeometryBufferBinding<'a> {
                 total: self.buffer.element_count.get(),
             })
         } else {
-            let index_per_element = self.buffer.element_kind.index_per_element();
-            let start_index = offset * index_per_element;
-            let index_count = count * index_per_element;
+            Ok((offset, count))
+        }
+    }
 
-            unsafe {
-                self.draw_internal(start_index, index_count);
-            }
+    pub fn draw(&self, element_range: ElementRange) -> Result<DrawCallStatistics, FrameworkError> {
+        scope_profile!();
+
+        let (offset, count) = self.element_window(element_range)?;
 
-            Ok(DrawCallStatistics { triangles: count })
+        let index_per_element = self.buffer.element_kind.index_per_element();
+        let start_index = offset * index_per_element;
+        let index_count = count * index_per_element;
+
+        unsafe {
+            self.draw_internal(start_index, index_count);
         }
+
+        Ok(DrawCallStatistics { triangles: count })
+    }
+
+    /// Makes the binding to draw every element as a patch, which is required by programs with
+    /// tessellation stages.
+    pub fn with_patches(mut self, patches: bool) -> Self {
+        self.patches = patches;
+        self
     }
 
     fn mode(&self) -> u32 {
+        if self.patches {
+            unsafe {
+                self.state.gl.patch_parameter_i32(
+                    glow::PATCH_VERTICES,
+                    self.buffer.element_kind.index_per_element() as i32,
+                );
+            }
+            return glow::PATCHES;
+        }
+
         match self.buffer.element_kind {
             ElementKind::Triangle => glow::TRIANGLES,
             ElementKind::Line => glow::LINES,
+            ElementKind::Point => glow::POINTS,
         }
     }
 
@@ -272,23 +353,29 @@ impl<'a> GeometryBufferBinding<'a> {
         }
     }
 
-    pub fn draw_instances(&self, count: usize) -> DrawCallStatistics {
+    pub fn draw_instances(
+        &self,
+        count: usize,
+        element_range: ElementRange,
+    ) -> Result<DrawCallStatistics, FrameworkError> {
+        let (offset, element_count) = self.element_window(element_range)?;
+
         let index_per_element = self.buffer.element_kind.index_per_element();
-        let index_count = self.buffer.element_count.get() * index_per_element;
+        let index_count = element_count * index_per_element;
         if index_count > 0 {
             unsafe {
                 self.state.gl.draw_elements_instanced(
                     self.mode(),
                     index_count as i32,
                     glow::UNSIGNED_INT,
-                    0,
+                    (offset * index_per_element * size_of::<u32>()) as i32,
                     count as i32,
                 )
             }
         }
-        DrawCallStatistics {
-            triangles: self.buffer.element_count.get() * count,
-        }
+        Ok(DrawCallStatistics {
+            triangles: element_count * count,
+        })
     }
 }
 
@@ -336,6 +423,100 @@ impl GeometryBuffer {
         buffer.size_bytes = size;
     }
 
+    /// Allocates storage of the given size for the given buffer, the content of the storage is
+    /// undefined.
+    pub fn allocate_buffer_data(
+        &mut self,
+        state: &PipelineState,
+        buffer: usize,
+        size_bytes: usize,
+    ) {
+        scope_profile!();
+
+        let buffer = &mut self.buffers[buffer];
+
+        state.set_vertex_buffer_object(Some(buffer.id));
+
+        unsafe {
+            state
+                .gl
+                .buffer_data_size(glow::ARRAY_BUFFER, size_bytes as i32, buffer.kind as u32);
+        }
+
+        buffer.size_bytes = size_bytes;
+    }
+
+    /// Writes the given data at the given offset (in bytes) of the buffer. The buffer must be
+    /// large enough to fit the data.
+    pub fn set_buffer_sub_data<T>(
+        &mut self,
+        state: &PipelineState,
+        buffer: usize,
+        offset_bytes: usize,
+        data: &[T],
+    ) {
+        scope_profile!();
+
+        let buffer = &mut self.buffers[buffer];
+
+        assert!(offset_bytes + std::mem::size_of_val(data) <= buffer.size_bytes);
+
+        state.set_vertex_buffer_object(Some(buffer.id));
+
+        unsafe {
+            state.gl.buffer_sub_data_u8_slice(
+                glow::ARRAY_BUFFER,
+                offset_bytes as i32,
+                array_as_u8_slice(data),
+            );
+        }
+    }
+
+    /// Copies the content of every buffer and the elements of the given geometry buffer to this
+    /// buffer on GPU side. Both geometry buffers must have the same set of buffers, the storage of
+    /// this buffer must be large enough to fit the data.
+    pub fn copy_data_from(&self, state: &PipelineState, source: &GeometryBuffer) {
+        scope_profile!();
+
+        assert_eq!(self.buffers.len(), source.buffers.len());
+        assert_eq!(self.element_kind, source.element_kind);
+
+        let element_size = self.element_kind.index_per_element() * size_of::<u32>();
+
+        let mut copies = self
+            .buffers
+            .iter()
+            .zip(source.buffers.iter())
+            .map(|(dest, source)| (source.id, dest.id, source.size_bytes.min(dest.size_bytes)))
+            .collect::<Vec<_>>();
+        copies.push((
+            source.element_buffer_object,
+            self.element_buffer_object,
+            source.element_count.get().min(self.element_count.get()) * element_size,
+        ));
+
+        unsafe {
+            for (source, dest, size) in copies {
+                if size == 0 {
+                    continue;
+                }
+
+                state.gl.bind_buffer(glow::COPY_READ_BUFFER, Some(source));
+                state.gl.bind_buffer(glow::COPY_WRITE_BUFFER, Some(dest));
+                state.gl.copy_buffer_sub_data(
+                    glow::COPY_READ_BUFFER,
+                    glow::COPY_WRITE_BUFFER,
+                    0,
+                    0,
+                    size as i32,
+                );
+            }
+
+            state.gl.bind_buffer(glow::COPY_READ_BUFFER, None);
+            state.gl.bind_buffer(glow::COPY_WRITE_BUFFER, None);
+        }
+    }
+
     pub fn bind<'a>(&'a self, state: &'a PipelineState) -> GeometryBufferBinding<'a> {
         scope_profile!();
 
@@ -352,6 +533,7 @@ impl GeometryBuffer {
         GeometryBufferBinding {
             state,
             buffer: self,
+            patches: false,
         }
     }
 
@@ -399,6 +581,16 @@ impl BufferBuilder {
     }
 
     pub fn from_vertex_buffer(buffer: &VertexBuffer, kind: GeometryBufferKind) -> Self {
+        Self {
+            data: buffer.raw_data().as_ptr(),
+            data_size: buffer.raw_data().len(),
+            ..Self::from_vertex_layout(buffer, kind)
+        }
+    }
+
+    /// Creates a builder of a buffer with the vertex layout of the given vertex buffer, but without
+    /// any data.
+    pub fn from_vertex_layout(buffer: &VertexBuffer, kind: GeometryBufferKind) -> Self {
         Self {
             element_size: buffer.vertex_size() as usize,
             kind,
@@ -430,8 +622,8 @@ impl BufferBuilder {
                     divisor: a.divisor as u32,
                 })
                 .collect(),
-            data: buffer.raw_data().as_ptr(),
-            data_size: buffer.raw_data().len(),
+            data: std::ptr::null(),
+            data_size: 0,
         }
     }
 
@@ -21,6 +21,18 @@ pub struct GpuProgram {
     uniform_locations: RefCell<FxHashMap<ImmutableString, Option<UniformLocation>>>,
     pub(crate) built_in_uniform_locations:
         [Option<UniformLocation>; BuiltInUniform::Count as usize],
+    uses_tessellation: bool,
+}
+
+/// Source code of every stage of a GPU program. Vertex and fragment stages are mandatory, the
+/// rest are optional and require support from the graphics API.
+#[derive(Default)]
+pub struct GpuProgramSources<'a> {
+    pub vertex: &'a str,
+    pub fragment: &'a str,
+    pub geometry: Option<&'a str>,
+    pub tessellation_control: Option<&'a str>,
+    pub tessellation_evaluation: Option<&'a str>,
 }
 
 #[repr(usize)]
@@ -29,6 +41,8 @@ pub enum BuiltInUniform {
     ViewProjectionMatrix,
     WorldViewProjectionMatrix,
     BoneMatrices,
+    PrevWorldMatrix,
+    PrevBoneMatrices,
     UseSkeletalAnimation,
     CameraPosition,
     CameraUpVector,
@@ -46,7 +60,14 @@ pub enum BuiltInUniform {
     LightsPosition,
     LightsDirection,
     LightsParameters,
+    ClusterGrid,
+    ClusterLights,
+    ClusterLightIndices,
+    ClusterParameters,
+    ClusterDepthPlane,
     AmbientLight,
+    Weather,
+    Wind,
     // Must be last.
     Count,
 }
@@ -65,7 +86,11 @@ unsafe fn create_shader(
     source: &str,
     gl_kind: GlKind,
 ) -> Result<glow::Shader, FrameworkError> {
-    let merged_source = prepare_source_code(source, gl_kind);
+    let mut merged_source = prepare_source_code(source, gl_kind);
+    if actual_type == glow::TESS_CONTROL_SHADER || actual_type == glow::TESS_EVALUATION_SHADER {
+        // Tessellation stages are available only since GLSL 4.00.
+        merged_source = merged_source.replacen("#version 330 core", "#version 400 core", 1);
+    }
 
     let shader = state.gl.create_shader(actual_type)?;
     state.gl.shader_source(shader, &merged_source);
@@ -440,6 +465,10 @@ fn fetch_built_in_uniform_locations(
 
     locations[BuiltInUniform::BoneMatrices as usize] =
         fetch_uniform_location(state, program, "fyrox_boneMatrices");
+    locations[BuiltInUniform::PrevWorldMatrix as usize] =
+        fetch_uniform_location(state, program, "fyrox_prevWorldMatrix");
+    locations[BuiltInUniform::PrevBoneMatrices as usize] =
+        fetch_uniform_location(state, program, "fyrox_prevBoneMatrices");
     locations[BuiltInUniform::UseSkeletalAnimation as usize] =
         fetch_uniform_location(state, program, "fyrox_useSkeletalAnimation");
 
@@ -476,10 +505,23 @@ fn fetch_built_in_uniform_locations(
         fetch_uniform_location(state, program, "fyrox_lightsDirection");
     locations[BuiltInUniform::LightsParameters as usize] =
         fetch_uniform_location(state, program, "fyrox_lightsParameters");
+    locations[BuiltInUniform::ClusterGrid as usize] =
+        fetch_uniform_location(state, program, "fyrox_clusterGrid");
+    locations[BuiltInUniform::ClusterLights as usize] =
+        fetch_uniform_location(state, program, "fyrox_clusterLights");
+    locations[BuiltInUniform::ClusterLightIndices as usize] =
+        fetch_uniform_location(state, program, "fyrox_clusterLightIndices");
+    locations[BuiltInUniform::ClusterParameters as usize] =
+        fetch_uniform_location(state, program, "fyrox_clusterParameters");
+    locations[BuiltInUniform::ClusterDepthPlane as usize] =
+        fetch_uniform_location(state, program, "fyrox_clusterDepthPlane");
     locations[BuiltInUniform::AmbientLight as usize] =
         fetch_uniform_location(state, program, "fyrox_ambientLightColor");
     locations[BuiltInUniform::LightPosition as usize] =
         fetch_uniform_location(state, program, "fyrox_lightPosition");
+    locations[BuiltInUniform::Weather as usize] =
+        fetch_uniform_location(state, program, "fyrox_weather");
+    locations[BuiltInUniform::Wind as usize] = fetch_uniform_location(state, program, "fyrox_wind");
 
     locations
 }
@@ -491,26 +533,89 @@ impl GpuProgram {
         vertex_source: &str,
         fragment_source: &str,
     ) -> Result<GpuProgram, FrameworkError> {
-        unsafe {
-            let vertex_shader = create_shader(
-                state,
-                format!("{}_VertexShader", name),
-                glow::VERTEX_SHADER,
-                vertex_source,
-                state.gl_kind(),
-            )?;
-            let fragment_shader = create_shader(
-                state,
-                format!("{}_FragmentShader", name),
+        Self::from_sources(
+            state,
+            name,
+            GpuProgramSources {
+                vertex: vertex_source,
+                fragment: fragment_source,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Creates a new program from the given set of sources. Optional stages are checked against
+    /// the capabilities of the current graphics API, an error is returned if a stage is not
+    /// supported.
+    pub fn from_sources(
+        state: &PipelineState,
+        name: &str,
+        sources: GpuProgramSources,
+    ) -> Result<GpuProgram, FrameworkError> {
+        if sources.geometry.is_some() && !state.supports_geometry_shaders() {
+            return Err(FrameworkError::Custom(format!(
+                "{} shader uses geometry stage, which is not supported by the graphics API!",
+                name
+            )));
+        }
+
+        let uses_tessellation = sources.tessellation_evaluation.is_some();
+        if sources.tessellation_control.is_some() && !uses_tessellation {
+            return Err(FrameworkError::Custom(format!(
+                "{} shader has tessellation control stage without tessellation evaluation stage!",
+                name
+            )));
+        }
+        if uses_tessellation && !state.supports_tessellation() {
+            return Err(FrameworkError::Custom(format!(
+                "{} shader uses tessellation stages, which are not supported by the graphics API!",
+                name
+            )));
+        }
+
+        let stages = [
+            ("VertexShader", glow::VERTEX_SHADER, Some(sources.vertex)),
+            (
+                "TessellationControlShader",
+                glow::TESS_CONTROL_SHADER,
+                sources.tessellation_control,
+            ),
+            (
+                "TessellationEvaluationShader",
+                glow::TESS_EVALUATION_SHADER,
+                sources.tessellation_evaluation,
+            ),
+            ("GeometryShader", glow::GEOMETRY_SHADER, sources.geometry),
+            (
+                "FragmentShader",
                 glow::FRAGMENT_SHADER,
-                fragment_source,
-                state.gl_kind(),
-            )?;
+                Some(sources.fragment),
+            ),
+        ];
+
+        unsafe {
             let program = state.gl.create_program()?;
-            state.gl.attach_shader(program, vertex_shader);
-            state.gl.delete_shader(vertex_shader);
-            state.gl.attach_shader(program, fragment_shader);
-            state.gl.delete_shader(fragment_shader);
+            for (stage_name, stage_type, source) in stages {
+                let Some(source) = source else {
+                    continue;
+                };
+
+                let shader = match create_shader(
+                    state,
+                    format!("{}_{}", name, stage_name),
+                    stage_type,
+                    source,
+                    state.gl_kind(),
+                ) {
+                    Ok(shader) => shader,
+                    Err(err) => {
+                        state.gl.delete_program(program);
+                        return Err(err);
+                    }
+                };
+                state.gl.attach_shader(program, shader);
+                state.gl.delete_shader(shader);
+            }
             state.gl.link_program(program);
             let status = state.gl.get_program_link_status(program);
             let link_message = state.gl.get_program_info_log(program);
@@ -543,11 +648,18 @@ impl GpuProgram {
                     thread_mark: PhantomData,
                     uniform_locations: Default::default(),
                     built_in_uniform_locations: fetch_built_in_uniform_locations(state, program),
+                    uses_tessellation,
                 })
             }
         }
     }
 
+    /// Returns `true` if the program has tessellation stages, such programs must be used to draw
+    /// patches instead of triangles.
+    pub fn uses_tessellation(&self) -> bool {
+        self.uses_tessellation
+    }
+
     pub fn uniform_location_internal(
         &self,
         state: &PipelineState,
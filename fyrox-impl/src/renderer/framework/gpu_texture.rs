@@ -28,6 +28,11 @@ pub enum GpuTextureKind {
         height: usize,
         depth: usize,
     },
+    Array {
+        width: usize,
+        height: usize,
+        layers: usize,
+    },
 }
 
 impl From<TextureKind> for GpuTextureKind {
@@ -53,6 +58,15 @@ impl From<TextureKind> for GpuTextureKind {
                 height: height as usize,
                 depth: depth as usize,
             },
+            TextureKind::Array {
+                width,
+                height,
+                layers,
+            } => GpuTextureKind::Array {
+                width: width as usize,
+                height: height as usize,
+                layers: layers as usize,
+            },
         }
     }
 }
@@ -64,6 +78,7 @@ impl GpuTextureKind {
             Self::Rectangle { .. } => glow::TEXTURE_2D,
             Self::Cube { .. } => glow::TEXTURE_CUBE_MAP,
             Self::Volume { .. } => glow::TEXTURE_3D,
+            Self::Array { .. } => glow::TEXTURE_2D_ARRAY,
         }
     }
 }
@@ -760,6 +775,22 @@ impl<'a> TextureBinding<'a> {
                         break 'mip_loop;
                     }
                 }
+                GpuTextureKind::Array {
+                    width,
+                    height,
+                    layers,
+                } => {
+                    // Layers are not affected by mip levels.
+                    if let (Some(width), Some(height)) = (
+                        width.checked_shr(mip as u32),
+                        height.checked_shr(mip as u32),
+                    ) {
+                        desired_byte_count +=
+                            layers * image_2d_size_bytes(pixel_kind, width, height);
+                    } else {
+                        break 'mip_loop;
+                    }
+                }
             };
         }
 
@@ -982,6 +1013,54 @@ impl<'a> TextureBinding<'a> {
                                 );
                             }
 
+                            mip_byte_offset += size as usize;
+                        } else {
+                            // No need to add degenerated mips (0x1, 0x2, 4x0, etc).
+                            break 'mip_loop2;
+                        }
+                    }
+                    GpuTextureKind::Array {
+                        width,
+                        height,
+                        layers,
+                    } => {
+                        if let (Some(width), Some(height)) = (
+                            width.checked_shr(mip as u32),
+                            height.checked_shr(mip as u32),
+                        ) {
+                            let size =
+                                (layers * image_2d_size_bytes(pixel_kind, width, height)) as i32;
+                            let pixels = data.map(|data| {
+                                &data[mip_byte_offset..(mip_byte_offset + size as usize)]
+                            });
+
+                            if is_compressed {
+                                self.state.gl.compressed_tex_image_3d(
+                                    glow::TEXTURE_2D_ARRAY,
+                                    mip as i32,
+                                    internal_format as i32,
+                                    width as i32,
+                                    height as i32,
+                                    layers as i32,
+                                    0,
+                                    size,
+                                    pixels.ok_or(FrameworkError::EmptyTextureData)?,
+                                );
+                            } else {
+                                self.state.gl.tex_image_3d(
+                                    glow::TEXTURE_2D_ARRAY,
+                                    mip as i32,
+                                    internal_format as i32,
+                                    width as i32,
+                                    height as i32,
+                                    layers as i32,
+                                    0,
+                                    format,
+                                    data_type,
+                                    pixels,
+                                );
+                            }
+
                             mip_byte_offset += size as usize;
                         } else {
                             // No need to add degenerated mips (0x1, 0x2, 4x0, etc).
@@ -1115,7 +1194,12 @@ impl GpuTexture {
     }
 
     pub fn bind(&self, state: &PipelineState, sampler_index: u32) {
-        for kind in [glow::TEXTURE_2D, glow::TEXTURE_3D, glow::TEXTURE_CUBE_MAP] {
+        for kind in [
+            glow::TEXTURE_2D,
+            glow::TEXTURE_3D,
+            glow::TEXTURE_CUBE_MAP,
+            glow::TEXTURE_2D_ARRAY,
+        ] {
             state.set_texture(
                 sampler_index,
                 kind,
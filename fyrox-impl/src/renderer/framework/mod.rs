@@ -2,6 +2,7 @@
 
 pub mod error;
 pub mod framebuffer;
+pub mod fullscreen;
 pub mod geometry_buffer;
 pub mod gpu_program;
 pub mod gpu_texture;
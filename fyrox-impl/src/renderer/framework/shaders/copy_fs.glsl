@@ -0,0 +1,9 @@
+uniform sampler2D srcSampler;
+
+in vec2 texCoord;
+
+out vec4 outColor;
+
+void main() {
+    outColor = texture(srcSampler, texCoord);
+}
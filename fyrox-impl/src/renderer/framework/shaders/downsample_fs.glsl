@@ -0,0 +1,43 @@
+// Halves the size of the source. The box filter averages 2x2 block of texels and works with both
+// nearest and linear filtering. The wide filter is the 13-tap filter from "Next Generation Post
+// Processing in Call of Duty: Advanced Warfare" by Jorge Jimenez, it requires linear filtering.
+
+uniform sampler2D srcSampler;
+uniform vec2 srcTexelSize;
+uniform bool wide;
+
+in vec2 texCoord;
+
+out vec4 outColor;
+
+vec4 Sample(vec2 offset) {
+    return texture(srcSampler, texCoord + offset * srcTexelSize);
+}
+
+void main() {
+    if (wide) {
+        vec4 a = Sample(vec2(-2.0, 2.0));
+        vec4 b = Sample(vec2(0.0, 2.0));
+        vec4 c = Sample(vec2(2.0, 2.0));
+        vec4 d = Sample(vec2(-2.0, 0.0));
+        vec4 e = Sample(vec2(0.0, 0.0));
+        vec4 f = Sample(vec2(2.0, 0.0));
+        vec4 g = Sample(vec2(-2.0, -2.0));
+        vec4 h = Sample(vec2(0.0, -2.0));
+        vec4 i = Sample(vec2(2.0, -2.0));
+        vec4 j = Sample(vec2(-1.0, 1.0));
+        vec4 k = Sample(vec2(1.0, 1.0));
+        vec4 l = Sample(vec2(-1.0, -1.0));
+        vec4 m = Sample(vec2(1.0, -1.0));
+
+        outColor = (j + k + l + m) * 0.125
+            + (a + c + g + i) * 0.03125
+            + (b + d + f + h) * 0.0625
+            + e * 0.125;
+    } else {
+        outColor = (Sample(vec2(-0.5, -0.5))
+            + Sample(vec2(0.5, -0.5))
+            + Sample(vec2(0.5, 0.5))
+            + Sample(vec2(-0.5, 0.5))) * 0.25;
+    }
+}
@@ -9,4 +9,4 @@ void main()
 {
     texCoord = vertexTexCoord;
     gl_Position = worldViewProjection * vec4(vertexPosition, 1.0);
-}
\ No newline at end of file
+}
@@ -0,0 +1,24 @@
+// One direction of a separable Gaussian blur. Weights are one-sided: the first one is the weight of
+// the central texel, the rest are used for both sides. Keep the max radius in sync with
+// `MAX_BLUR_RADIUS` in `fullscreen.rs`.
+
+#define MAX_RADIUS 16
+
+uniform sampler2D srcSampler;
+// Texel size of the source multiplied by the direction of the blur.
+uniform vec2 direction;
+uniform float weights[MAX_RADIUS + 1];
+uniform int radius;
+
+in vec2 texCoord;
+
+out vec4 outColor;
+
+void main() {
+    vec4 result = texture(srcSampler, texCoord) * weights[0];
+    for (int i = 1; i <= radius; ++i) {
+        vec2 offset = direction * float(i);
+        result += (texture(srcSampler, texCoord + offset) + texture(srcSampler, texCoord - offset)) * weights[i];
+    }
+    outColor = result;
+}
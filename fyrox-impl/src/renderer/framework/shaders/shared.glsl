@@ -364,4 +364,190 @@ TBlendShapeOffsets S_FetchBlendShapeOffsets(in sampler3D storage, int vertexInde
     vec3 normal = texelFetch(storage, ivec3(pos.x + 1, pos.y, pos.z), 0).xyz;
     vec3 tangent = texelFetch(storage, ivec3(pos.x + 2, pos.y, pos.z), 0).xyz;
     return TBlendShapeOffsets(position, normal, tangent);
-}
\ No newline at end of file
+}
+// Applies wetness and snow coverage of the scene weather (`fyrox_weather` built-in property) to
+// surface properties. Wet surfaces are darker and glossier, snow covers surfaces that face up.
+void S_ApplyWeather(inout vec3 albedo, inout float roughness, vec3 worldNormal, vec4 weather) {
+    float wetness = clamp(weather.x, 0.0, 1.0);
+    albedo *= mix(1.0, 0.6, wetness);
+    roughness = mix(roughness, 0.1, wetness * 0.8);
+
+    float snow = clamp(weather.y, 0.0, 1.0);
+    float coverage = smoothstep(1.0 - snow, 1.0 - snow * 0.5, worldNormal.y) * snow;
+    albedo = mix(albedo, vec3(0.9, 0.92, 0.95), coverage);
+    roughness = mix(roughness, 0.8, coverage);
+}
+
+// 3D grids (for example, the grids of smoke simulation) are stored in 2D textures - "atlases", where
+// all the slices of a grid are placed in a row. A grid with N cells along each axis is stored in
+// N*N x N texture.
+
+// Returns a cell of a grid of the given size, that corresponds to a fragment of its atlas.
+ivec3 S_VolumeAtlasCell(vec2 fragCoord, int size) {
+    ivec2 p = ivec2(fragCoord);
+    return ivec3(p.x % size, p.y, p.x / size);
+}
+
+// Fetches a value of a cell, coordinates of the cell are clamped to the bounds of the grid.
+vec4 S_FetchVolumeAtlas(in sampler2D atlas, ivec3 cell, int size) {
+    ivec3 c = clamp(cell, ivec3(0), ivec3(size - 1));
+    return texelFetch(atlas, ivec2(c.z * size + c.x, c.y), 0);
+}
+
+// Samples a grid using trilinear filtering, the position must be in [0; 1] range. The atlas must
+// have linear filtering.
+vec4 S_SampleVolumeAtlas(in sampler2D atlas, vec3 position, float size) {
+    vec3 cell = clamp(position * size - 0.5, vec3(0.0), vec3(size - 1.0));
+    float z0 = floor(cell.z);
+    float z1 = min(z0 + 1.0, size - 1.0);
+    // Coordinates within a slice are clamped to texel centers, so filtering does not pick up
+    // texels of the neighbour slices.
+    vec2 xy = cell.xy + 0.5;
+    float width = size * size;
+    vec4 a = texture(atlas, vec2((z0 * size + xy.x) / width, xy.y / size));
+    vec4 b = texture(atlas, vec2((z1 * size + xy.x) / width, xy.y / size));
+    return mix(a, b, cell.z - z0);
+}
+
+// Weighted blended order-independent transparency (McGuire and Bavoil, 2013). Fragments closer to the
+// camera get larger weights, so they dominate the blended result. The depth must be in [0; 1] range
+// (for example gl_FragCoord.z).
+float S_WeightedBlendedWeight(float alpha, float depth) {
+    return alpha * clamp(3.0e3 * pow(1.0 - depth, 3.0), 1.0e-2, 3.0e3);
+}
+
+// Writes a fragment to order-independent transparency targets. The first target accumulates premultiplied
+// weighted color in rgb and revealage in alpha, the second one accumulates weights. The color must
+// be in linear space and must not be premultiplied.
+void S_WriteWeightedBlended(vec4 color, float depth, out vec4 accumulation, out float weight) {
+    float w = S_WeightedBlendedWeight(color.a, depth);
+    accumulation = vec4(color.rgb * w, color.a);
+    weight = w;
+}
+
+// Clustered lighting. View frustum is split into a grid of clusters: uniformly in screen space and
+// exponentially along view depth. Every cluster stores a range in the list of indices of the lights
+// that touch the cluster. Keep in sync with `crate::renderer::cluster`.
+const int S_CLUSTER_GRID_X = 16;
+const int S_CLUSTER_GRID_Y = 9;
+const int S_CLUSTER_GRID_Z = 24;
+
+struct TClusterLight {
+    vec3 position;
+    float radius;
+    // Linear color premultiplied with the intensity.
+    vec3 color;
+    // Whether the light is rendered separately by the deferred renderer (for example, it casts shadows).
+    bool separate;
+    vec3 direction;
+    float halfHotspotConeAngleCos;
+    float halfConeAngleCos;
+};
+
+// Returns offset and count of the light indices of a cluster. See `fyrox_clusterParameters` built-in
+// property for the meaning of the parameters.
+ivec2 S_ClusterLightRange(in sampler2D grid, vec2 screenCoord, float viewDepth, vec4 parameters) {
+    float slice = log(max(viewDepth, 1.0e-4)) * parameters.x + parameters.y;
+    int z = int(clamp(slice, 0.0, float(S_CLUSTER_GRID_Z - 1)));
+    ivec2 xy = clamp(
+        ivec2(screenCoord * vec2(S_CLUSTER_GRID_X, S_CLUSTER_GRID_Y)),
+        ivec2(0),
+        ivec2(S_CLUSTER_GRID_X - 1, S_CLUSTER_GRID_Y - 1));
+    return ivec2(texelFetch(grid, ivec2(xy.y * S_CLUSTER_GRID_X + xy.x, z), 0).xy);
+}
+
+// Fetches i-th light of the list of light indices.
+TClusterLight S_FetchClusterLight(in sampler2D lights, in sampler2D indices, int i) {
+    int index = int(texelFetch(indices, S_LinearIndexToPosition(i, textureSize(indices, 0).x), 0).r);
+    vec4 positionRadius = texelFetch(lights, ivec2(0, index), 0);
+    vec4 colorSeparate = texelFetch(lights, ivec2(1, index), 0);
+    vec4 directionHotspot = texelFetch(lights, ivec2(2, index), 0);
+    vec4 cone = texelFetch(lights, ivec2(3, index), 0);
+    return TClusterLight(
+        positionRadius.xyz,
+        positionRadius.w,
+        colorSeparate.rgb,
+        colorSeparate.a > 0.5,
+        directionHotspot.xyz,
+        directionHotspot.w,
+        cone.x);
+}
+
+// Calculates lighting of a fragment from all the lights of its cluster. Screen coordinates must be in
+// [0; 1] range, view depth could be calculated using `fyrox_clusterDepthPlane` built-in property. Light
+// color and direction of the context are ignored. Lights that are rendered separately by the deferred
+// renderer could be skipped to not lit fragments twice.
+vec3 S_ClusteredLighting(
+    in sampler2D grid,
+    in sampler2D lights,
+    in sampler2D indices,
+    vec4 parameters,
+    vec2 screenCoord,
+    float viewDepth,
+    vec3 fragmentPosition,
+    bool skipSeparate,
+    TPBRContext ctx)
+{
+    ivec2 range = S_ClusterLightRange(grid, screenCoord, viewDepth, parameters);
+    vec3 lighting = vec3(0.0);
+    for (int i = range.x; i < range.x + range.y; ++i) {
+        TClusterLight light = S_FetchClusterLight(lights, indices, i);
+        if (skipSeparate && light.separate) {
+            continue;
+        }
+
+        vec3 fragmentToLight = light.position - fragmentPosition;
+        float distance = length(fragmentToLight);
+        if (distance >= light.radius) {
+            continue;
+        }
+
+        ctx.fragmentToLight = fragmentToLight / max(distance, 1.0e-5);
+        ctx.lightColor = light.color;
+
+        float distanceAttenuation = S_LightDistanceAttenuation(distance, light.radius);
+        float spotAngleCos = dot(light.direction, ctx.fragmentToLight);
+        float coneFactor = smoothstep(light.halfConeAngleCos, light.halfHotspotConeAngleCos, spotAngleCos);
+
+        lighting += distanceAttenuation * coneFactor * S_PBR_CalculateLight(ctx);
+    }
+    return lighting;
+}
+
+// Returns world-space offset of a vertex of a billboard. `vertexOffset` is a position of the vertex
+// relative to the center of the billboard in [-1; 1] range. Zero `axis` means that the billboard faces
+// the camera, otherwise the billboard rotates only around the axis to face the camera.
+vec3 S_BillboardOffset(
+    vec2 vertexOffset,
+    vec3 worldPosition,
+    vec3 axis,
+    vec3 cameraPosition,
+    vec3 cameraSideVector,
+    vec3 cameraUpVector)
+{
+    vec3 side = cameraSideVector;
+    vec3 up = cameraUpVector;
+    if (dot(axis, axis) > 0.000001) {
+        up = normalize(axis);
+        vec3 lockedSide = cross(cameraPosition - worldPosition, up);
+        float lockedSideLength = length(lockedSide);
+        if (lockedSideLength > 0.000001) {
+            side = lockedSide / lockedSideLength;
+        }
+    }
+    return vertexOffset.x * side + vertexOffset.y * up;
+}
+
+// Converts a value from a depth buffer to linear (view-space) depth.
+float S_LinearizeDepth(float depth, float zNear, float zFar) {
+    return (zFar * zNear) / (zFar - depth * (zFar - zNear));
+}
+
+// Returns opacity of a fragment of a soft particle (or any other billboard), that fades out when it
+// approaches opaque geometry stored in the scene depth buffer. `fadeDistance` is defined in world units.
+float S_SoftParticleFade(in sampler2D sceneDepth, vec4 fragCoord, float zNear, float zFar, float fadeDistance) {
+    vec2 pixelSize = 1.0 / vec2(textureSize(sceneDepth, 0));
+    float sceneLinearDepth = S_LinearizeDepth(texture(sceneDepth, fragCoord.xy * pixelSize).r, zNear, zFar);
+    float fragmentLinearDepth = S_LinearizeDepth(fragCoord.z, zNear, zFar);
+    return clamp((sceneLinearDepth - fragmentLinearDepth) / max(fadeDistance, 0.000001), 0.0, 1.0);
+}
@@ -0,0 +1,23 @@
+// 3x3 tent upsampling filter. The radius scales the distance between the taps.
+
+uniform sampler2D srcSampler;
+uniform vec2 srcTexelSize;
+uniform float radius;
+
+in vec2 texCoord;
+
+out vec4 outColor;
+
+vec4 Sample(vec2 offset) {
+    return texture(srcSampler, texCoord + offset * srcTexelSize * radius);
+}
+
+void main() {
+    vec4 result = Sample(vec2(0.0, 0.0)) * 4.0;
+
+    result += (Sample(vec2(0.0, 1.0)) + Sample(vec2(-1.0, 0.0)) + Sample(vec2(1.0, 0.0)) + Sample(vec2(0.0, -1.0))) * 2.0;
+
+    result += Sample(vec2(-1.0, 1.0)) + Sample(vec2(1.0, 1.0)) + Sample(vec2(-1.0, -1.0)) + Sample(vec2(1.0, -1.0));
+
+    outColor = result / 16.0;
+}
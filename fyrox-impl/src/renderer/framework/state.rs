@@ -190,6 +190,19 @@ impl Default for BlendFunc {
     }
 }
 
+/// Defines winding order of front-facing polygons.
+#[derive(Copy, Clone, PartialOrd, PartialEq, Ord, Eq, Hash, Debug, Default)]
+#[repr(u32)]
+pub enum FrontFace {
+    /// Polygons with counter-clockwise winding are front-facing.
+    #[default]
+    CounterClockwise = glow::CCW,
+
+    /// Polygons with clockwise winding are front-facing. It is used when rendering with mirrored
+    /// transformations, that flip the winding of every polygon.
+    Clockwise = glow::CW,
+}
+
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub enum GlKind {
     OpenGL,
@@ -207,6 +220,7 @@ struct InnerState {
     stencil_test: bool,
     cull_face: CullFace,
     culling: bool,
+    front_face: FrontFace,
     stencil_mask: u32,
     clear_color: Color,
     clear_stencil: i32,
@@ -246,6 +260,7 @@ impl InnerState {
             stencil_test: false,
             cull_face: CullFace::Back,
             culling: false,
+            front_face: FrontFace::CounterClockwise,
             stencil_mask: 0xFFFF_FFFF,
             clear_color: Color::from_rgba(0, 0, 0, 0),
             clear_stencil: 0,
@@ -572,6 +587,28 @@ impl PipelineState {
         self.state.borrow().gl_kind
     }
 
+    /// Returns `true` if the current graphics API supports geometry shaders (OpenGL 3.2+).
+    pub fn supports_geometry_shaders(&self) -> bool {
+        let version = self.gl.version();
+        self.gl_kind() == GlKind::OpenGL && (version.major, version.minor) >= (3, 2)
+    }
+
+    /// Returns `true` if the current graphics API supports tessellation shaders (OpenGL 4.0+).
+    pub fn supports_tessellation(&self) -> bool {
+        self.gl_kind() == GlKind::OpenGL && self.gl.version().major >= 4
+    }
+
+    /// Returns `true` if floating-point textures could be used as render targets. It is a core
+    /// feature of OpenGL 3.0+, OpenGL ES and WebGL require `EXT_color_buffer_float` extension.
+    pub fn supports_float_render_targets(&self) -> bool {
+        if self.gl_kind() == GlKind::OpenGL {
+            return true;
+        }
+        let extensions = self.gl.supported_extensions();
+        extensions.contains("GL_EXT_color_buffer_float")
+            || extensions.contains("EXT_color_buffer_float")
+    }
+
     pub fn set_polygon_fill_mode(
         &self,
         polygon_face: PolygonFace,
@@ -702,6 +739,15 @@ impl PipelineState {
         }
     }
 
+    pub fn set_front_face(&self, front_face: FrontFace) {
+        let mut state = self.state.borrow_mut();
+        if state.front_face != front_face {
+            state.front_face = front_face;
+
+            unsafe { self.gl.front_face(state.front_face as u32) }
+        }
+    }
+
     pub fn set_culling(&self, culling: bool) {
         let mut state = self.state.borrow_mut();
         if state.culling != culling {
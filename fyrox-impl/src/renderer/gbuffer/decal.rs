@@ -10,12 +10,19 @@ pub struct DecalShader {
     pub scene_depth: UniformLocation,
     pub diffuse_texture: UniformLocation,
     pub normal_texture: UniformLocation,
+    pub material_texture: UniformLocation,
     pub inv_view_proj: UniformLocation,
     pub inv_world_decal: UniformLocation,
     pub resolution: UniformLocation,
     pub color: UniformLocation,
     pub layer_index: UniformLocation,
     pub decal_mask: UniformLocation,
+    pub metallic: UniformLocation,
+    pub roughness: UniformLocation,
+    pub normal_strength: UniformLocation,
+    pub material_strength: UniformLocation,
+    pub projection_axis: UniformLocation,
+    pub angle_fade: UniformLocation,
     pub program: GpuProgram,
 }
 
@@ -34,6 +41,8 @@ impl DecalShader {
                 .uniform_location(state, &ImmutableString::new("diffuseTexture"))?,
             normal_texture: program
                 .uniform_location(state, &ImmutableString::new("normalTexture"))?,
+            material_texture: program
+                .uniform_location(state, &ImmutableString::new("materialTexture"))?,
             inv_view_proj: program.uniform_location(state, &ImmutableString::new("invViewProj"))?,
             inv_world_decal: program
                 .uniform_location(state, &ImmutableString::new("invWorldDecal"))?,
@@ -41,6 +50,15 @@ impl DecalShader {
             color: program.uniform_location(state, &ImmutableString::new("color"))?,
             layer_index: program.uniform_location(state, &ImmutableString::new("layerIndex"))?,
             decal_mask: program.uniform_location(state, &ImmutableString::new("decalMask"))?,
+            metallic: program.uniform_location(state, &ImmutableString::new("metallic"))?,
+            roughness: program.uniform_location(state, &ImmutableString::new("roughness"))?,
+            normal_strength: program
+                .uniform_location(state, &ImmutableString::new("normalStrength"))?,
+            material_strength: program
+                .uniform_location(state, &ImmutableString::new("materialStrength"))?,
+            projection_axis: program
+                .uniform_location(state, &ImmutableString::new("projectionAxis"))?,
+            angle_fade: program.uniform_location(state, &ImmutableString::new("angleFade"))?,
             program,
         })
     }
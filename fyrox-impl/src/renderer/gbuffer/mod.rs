@@ -11,16 +11,18 @@
 
 use crate::{
     core::{
-        algebra::{Matrix4, Vector2},
+        algebra::{Matrix4, Vector2, Vector3},
         color::Color,
         math::Rect,
         scope_profile,
         sstorage::ImmutableString,
     },
+    material::GlobalMaterialProperties,
     renderer::{
         apply_material,
         bundle::RenderDataBundleStorage,
         cache::shader::ShaderCache,
+        flat_shader::FlatShader,
         framework::{
             error::FrameworkError,
             framebuffer::{
@@ -32,9 +34,10 @@ use crate::{
                 Coordinate, GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter,
                 PixelKind, WrapMode,
             },
-            state::{BlendFactor, BlendFunc, PipelineState},
+            state::{BlendFactor, BlendFunc, ColorMask, PipelineState},
         },
         gbuffer::decal::DecalShader,
+        stencil_mask::StencilMasks,
         storage::MatrixStorageCache,
         GeometryCache, MaterialContext, RenderPassStatistics, TextureCache,
     },
@@ -43,6 +46,7 @@ use crate::{
         decal::Decal,
         graph::Graph,
         mesh::{surface::SurfaceData, RenderPath},
+        weather::WeatherShaderData,
     },
 };
 use fyrox_core::math::Matrix4Ext;
@@ -76,6 +80,12 @@ pub(crate) struct GBufferRenderContext<'a, 'b> {
     pub use_parallax_mapping: bool,
     pub graph: &'b Graph,
     pub matrix_storage: &'a mut MatrixStorageCache,
+    pub global_properties: &'a GlobalMaterialProperties,
+    pub weather: WeatherShaderData,
+    pub flat_shader: &'a FlatShader,
+    pub stencil_masks: &'a StencilMasks,
+    /// Whether to clear the depth buffer or to keep the depth of previously rendered camera.
+    pub clear_depth: bool,
 }
 
 impl GBuffer {
@@ -169,6 +179,7 @@ impl GBuffer {
             .bind_mut(state, 0)
             .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
             .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
+        let material_texture = Rc::new(RefCell::new(material_texture));
 
         let framebuffer = FrameBuffer::new(
             state,
@@ -191,7 +202,7 @@ impl GBuffer {
                 },
                 Attachment {
                     kind: AttachmentKind::Color,
-                    texture: Rc::new(RefCell::new(material_texture)),
+                    texture: material_texture.clone(),
                 },
                 Attachment {
                     kind: AttachmentKind::Color,
@@ -212,6 +223,10 @@ impl GBuffer {
                     kind: AttachmentKind::Color,
                     texture: normal_texture,
                 },
+                Attachment {
+                    kind: AttachmentKind::Color,
+                    texture: material_texture,
+                },
             ],
         )?;
 
@@ -258,6 +273,21 @@ impl GBuffer {
         self.framebuffer.color_attachments()[4].texture.clone()
     }
 
+    /// Replaces diffuse color of every pixel with white color, alpha channel is kept intact. It is
+    /// used to show lighting only, without any influence of diffuse textures.
+    pub(crate) fn override_diffuse_with_white(&mut self, state: &PipelineState) {
+        state.set_framebuffer(self.framebuffer.id());
+        state.set_color_write(ColorMask {
+            red: true,
+            green: true,
+            blue: true,
+            alpha: false,
+        });
+        self.framebuffer
+            .clear_color_attachment(state, 0, Color::WHITE);
+        state.set_color_write(Default::default());
+    }
+
     pub(crate) fn fill(
         &mut self,
         args: GBufferRenderContext,
@@ -280,6 +310,11 @@ impl GBuffer {
             volume_dummy,
             graph,
             matrix_storage,
+            global_properties,
+            weather,
+            flat_shader,
+            stencil_masks,
+            clear_depth,
             ..
         } = args;
 
@@ -288,12 +323,22 @@ impl GBuffer {
             state,
             viewport,
             Some(Color::from_rgba(0, 0, 0, 0)),
-            Some(1.0),
+            clear_depth.then_some(1.0),
             Some(0),
         );
 
         let initial_view_projection = camera.view_projection_matrix();
 
+        statistics += stencil_masks.write(
+            state,
+            &mut self.framebuffer,
+            viewport,
+            graph,
+            &initial_view_projection,
+            geom_cache,
+            flat_shader,
+        )?;
+
         let inv_view = camera.inv_view_matrix().unwrap();
 
         let camera_up = inv_view.up();
@@ -322,7 +367,7 @@ impl GBuffer {
                 .and_then(|c| c.blend_shape_storage.clone());
 
             let Some(render_pass) = shader_cache
-                .get(state, material.shader())
+                .get_for_material(state, material)
                 .and_then(|shader_set| shader_set.render_passes.get(&self.render_pass_name))
             else {
                 continue;
@@ -340,6 +385,7 @@ impl GBuffer {
 
                     apply_material(MaterialContext {
                         material,
+                        global_properties,
                         program_binding: &mut program_binding,
                         texture_cache,
                         matrix_storage,
@@ -347,6 +393,8 @@ impl GBuffer {
                         view_projection_matrix: &view_projection,
                         wvp_matrix: &(view_projection * instance.world_transform),
                         bone_matrices: &instance.bone_matrices,
+                        prev_world_matrix: &instance.prev_world_transform,
+                        prev_bone_matrices: &instance.prev_bone_matrices,
                         use_skeletal_animation: bundle.is_skinned,
                         camera_position: &camera.global_position(),
                         camera_up_vector: &camera_up,
@@ -363,23 +411,43 @@ impl GBuffer {
                         persistent_identifier: instance.persistent_identifier,
                         light_data: None,
                         ambient_light: Color::WHITE, // TODO
-                        scene_depth: None,           // TODO. Add z-pre-pass.
+                        weather,
+                        scene_depth: None, // TODO. Add z-pre-pass.
+                        light_clusters: None,
                         z_far: camera.projection().z_far(),
                     });
                 };
 
-                statistics += self.framebuffer.draw(
-                    geometry,
-                    state,
-                    viewport,
-                    &render_pass.program,
-                    &render_pass.draw_params,
-                    instance.element_range,
-                    apply_uniforms,
-                )?;
+                let draw_params =
+                    stencil_masks.draw_params(instance.node_handle, &render_pass.draw_params);
+
+                statistics += match bundle.hardware_instance_count {
+                    Some(count) => self.framebuffer.draw_instances(
+                        count,
+                        geometry.buffer,
+                        state,
+                        viewport,
+                        &render_pass.program,
+                        &draw_params,
+                        geometry.element_range(ElementRange::Full)?,
+                        apply_uniforms,
+                    )?,
+                    None => self.framebuffer.draw(
+                        geometry.buffer,
+                        state,
+                        viewport,
+                        &render_pass.program,
+                        &draw_params,
+                        geometry.element_range(instance.element_range)?,
+                        apply_uniforms,
+                    )?,
+                };
             }
         }
 
+        // The stencil buffer is copied to the frame buffer of the scene and used by the lighting.
+        stencil_masks.clear(state, &mut self.framebuffer, viewport);
+
         let inv_view_proj = initial_view_projection.try_inverse().unwrap_or_default();
         let depth = self.depth();
         let decal_mask = self.decal_mask_texture();
@@ -387,13 +455,25 @@ impl GBuffer {
 
         // Render decals after because we need to modify diffuse texture of G-Buffer and use depth texture
         // for rendering. We'll render in the G-Buffer, but depth will be used from final frame, since
-        // decals do not modify depth (only diffuse and normal maps).
+        // decals do not modify depth (only diffuse, normal and material maps).
+        let mut decals = graph
+            .linear_iter()
+            .filter_map(|n| n.cast::<Decal>())
+            .collect::<Vec<_>>();
+        // Stable sort keeps graph order for decals with the same sorting order.
+        decals.sort_by_key(|decal| decal.sorting_order());
+
         let unit_cube = &self.cube;
-        for decal in graph.linear_iter().filter_map(|n| n.cast::<Decal>()) {
+        for decal in decals {
             let shader = &self.decal_shader;
             let program = &self.decal_shader.program;
 
             let world_view_proj = initial_view_projection * decal.global_transform();
+            let projection_axis = decal
+                .global_transform()
+                .transform_vector(&Vector3::y())
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::y);
 
             statistics += self.decal_framebuffer.draw(
                 unit_cube,
@@ -406,8 +486,14 @@ impl GBuffer {
                     depth_write: false,
                     stencil_test: None,
                     depth_test: false,
+                    // Alpha channels of the G-Buffer are kept intact, they're used for layer blending.
                     blend: Some(BlendParameters {
-                        func: BlendFunc::new(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
+                        func: BlendFunc::new_separate(
+                            BlendFactor::SrcAlpha,
+                            BlendFactor::OneMinusSrcAlpha,
+                            BlendFactor::Zero,
+                            BlendFactor::One,
+                        ),
                         ..Default::default()
                     }),
                     stencil_op: Default::default(),
@@ -437,9 +523,28 @@ impl GBuffer {
                                 .and_then(|t| texture_cache.get(state, t))
                                 .unwrap_or(&normal_dummy),
                         )
+                        .set_texture(
+                            &shader.material_texture,
+                            decal
+                                .material_texture()
+                                .and_then(|t| texture_cache.get(state, t))
+                                .unwrap_or(&white_dummy),
+                        )
                         .set_texture(&shader.decal_mask, &decal_mask)
                         .set_u32(&shader.layer_index, decal.layer() as u32)
-                        .set_linear_color(&shader.color, &decal.color());
+                        .set_linear_color(&shader.color, &decal.color())
+                        .set_f32(&shader.metallic, decal.metallic())
+                        .set_f32(&shader.roughness, decal.roughness())
+                        .set_f32(&shader.normal_strength, decal.normal_strength())
+                        .set_f32(&shader.material_strength, decal.material_strength())
+                        .set_vector3(&shader.projection_axis, &projection_axis)
+                        .set_vector2(
+                            &shader.angle_fade,
+                            &Vector2::new(
+                                decal.angle_fade_start().cos(),
+                                decal.angle_fade_end().cos(),
+                            ),
+                        );
                 },
             )?;
         }
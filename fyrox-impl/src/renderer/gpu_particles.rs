@@ -0,0 +1,835 @@
+//! Simulates and renders particle systems with [`ParticleSimulationMode::Gpu`] mode. Every particle
+//! is a texel in a set of floating-point state textures, the simulation is done by ping-ponging
+//! the textures using a full-screen pass (so no compute shaders or transform feedback is needed).
+//! New particles are written into their slots by drawing points. If the graphics API does not
+//! support floating-point render targets, the simulation is done on CPU and the state textures are
+//! uploaded every frame. In both cases particles are rendered using instancing.
+//!
+//! Layout of the state textures:
+//!
+//! - 0: position in local coordinates (xyz), age (w).
+//! - 1: velocity (xyz), lifetime (w), zero lifetime means that the slot is free.
+//! - 2: size (x), size modifier (y), rotation (z), rotation speed (w).
+//! - 3: emitter index (x), spawn delay (y).
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2, Vector3, Vector4},
+        color::Color,
+        log::Log,
+        math::{frustum::Frustum, Matrix4Ext, Rect},
+        pool::Handle,
+        scope_profile,
+        sstorage::ImmutableString,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    material::PropertyValue,
+    renderer::{
+        cache::texture::TextureCache,
+        framework::{
+            error::FrameworkError,
+            framebuffer::{
+                Attachment, AttachmentKind, BlendParameters, DrawParameters, FrameBuffer,
+            },
+            geometry_buffer::{
+                AttributeDefinition, AttributeKind, BufferBuilder, ElementKind, ElementRange,
+                GeometryBuffer, GeometryBufferBuilder, GeometryBufferKind,
+            },
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::{
+                Coordinate, GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter,
+                PixelKind, WrapMode,
+            },
+            state::{BlendFactor, BlendFunc, PipelineState},
+        },
+        make_viewport_matrix, RenderPassStatistics,
+    },
+    scene::{
+        camera::Camera,
+        graph::Graph,
+        mesh::surface::SurfaceData,
+        node::Node,
+        particle_system::{
+            gpu::{GpuParticleBatch, GpuParticleSpawn},
+            ParticleSimulationMode, ParticleSystem,
+        },
+        sprite::BillboardMode,
+        Scene,
+    },
+};
+use fxhash::FxHashMap;
+use std::{cell::RefCell, rc::Rc};
+
+/// Width of the state textures. Keep it a power of two, so the index of a slot could be easily
+/// mapped to texel coordinates.
+const STATE_WIDTH: usize = 256;
+
+/// Max amount of emitters, that have their own billboard mode and soft particle distance. Keep in
+/// sync with the arrays in particle_vs.glsl.
+const MAX_EMITTERS: usize = 16;
+
+/// Amount of samples of the color-over-lifetime gradient.
+const GRADIENT_SAMPLES: usize = 128;
+
+const STATE_TEXTURE_COUNT: usize = 4;
+
+struct SimulationShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    position_age_sampler: UniformLocation,
+    velocity_lifetime_sampler: UniformLocation,
+    size_rotation_sampler: UniformLocation,
+    parameters_sampler: UniformLocation,
+    acceleration: UniformLocation,
+    dt: UniformLocation,
+}
+
+impl SimulationShader {
+    fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/particle_simulate_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program = GpuProgram::from_source(
+            state,
+            "ParticleSimulationShader",
+            vertex_source,
+            fragment_source,
+        )?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            position_age_sampler: program
+                .uniform_location(state, &ImmutableString::new("positionAgeSampler"))?,
+            velocity_lifetime_sampler: program
+                .uniform_location(state, &ImmutableString::new("velocityLifetimeSampler"))?,
+            size_rotation_sampler: program
+                .uniform_location(state, &ImmutableString::new("sizeRotationSampler"))?,
+            parameters_sampler: program
+                .uniform_location(state, &ImmutableString::new("parametersSampler"))?,
+            acceleration: program.uniform_location(state, &ImmutableString::new("acceleration"))?,
+            dt: program.uniform_location(state, &ImmutableString::new("dt"))?,
+            program,
+        })
+    }
+}
+
+struct SpawnShader {
+    program: GpuProgram,
+    state_size: UniformLocation,
+}
+
+impl SpawnShader {
+    fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/particle_spawn_fs.glsl");
+        let vertex_source = include_str!("shaders/particle_spawn_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "ParticleSpawnShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            state_size: program.uniform_location(state, &ImmutableString::new("stateSize"))?,
+            program,
+        })
+    }
+}
+
+struct ParticleShader {
+    program: GpuProgram,
+    position_age_sampler: UniformLocation,
+    velocity_lifetime_sampler: UniformLocation,
+    size_rotation_sampler: UniformLocation,
+    parameters_sampler: UniformLocation,
+    color_over_lifetime: UniformLocation,
+    state_width: UniformLocation,
+    view_projection: UniformLocation,
+    world_matrix: UniformLocation,
+    camera_position: UniformLocation,
+    camera_up_vector: UniformLocation,
+    camera_side_vector: UniformLocation,
+    emitter_axes: UniformLocation,
+    emitter_soft_distances: UniformLocation,
+    diffuse_texture: UniformLocation,
+    scene_depth: UniformLocation,
+    soft_boundary_sharpness_factor: UniformLocation,
+    z_near: UniformLocation,
+    z_far: UniformLocation,
+}
+
+impl ParticleShader {
+    fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/particle_fs.glsl");
+        let vertex_source = include_str!("shaders/particle_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "GpuParticleShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            position_age_sampler: program
+                .uniform_location(state, &ImmutableString::new("positionAgeSampler"))?,
+            velocity_lifetime_sampler: program
+                .uniform_location(state, &ImmutableString::new("velocityLifetimeSampler"))?,
+            size_rotation_sampler: program
+                .uniform_location(state, &ImmutableString::new("sizeRotationSampler"))?,
+            parameters_sampler: program
+                .uniform_location(state, &ImmutableString::new("parametersSampler"))?,
+            color_over_lifetime: program
+                .uniform_location(state, &ImmutableString::new("colorOverLifetime"))?,
+            state_width: program.uniform_location(state, &ImmutableString::new("stateWidth"))?,
+            view_projection: program
+                .uniform_location(state, &ImmutableString::new("viewProjection"))?,
+            world_matrix: program.uniform_location(state, &ImmutableString::new("worldMatrix"))?,
+            camera_position: program
+                .uniform_location(state, &ImmutableString::new("cameraPosition"))?,
+            camera_up_vector: program
+                .uniform_location(state, &ImmutableString::new("cameraUpVector"))?,
+            camera_side_vector: program
+                .uniform_location(state, &ImmutableString::new("cameraSideVector"))?,
+            emitter_axes: program.uniform_location(state, &ImmutableString::new("emitterAxes"))?,
+            emitter_soft_distances: program
+                .uniform_location(state, &ImmutableString::new("emitterSoftDistances"))?,
+            diffuse_texture: program
+                .uniform_location(state, &ImmutableString::new("diffuseTexture"))?,
+            scene_depth: program.uniform_location(state, &ImmutableString::new("sceneDepth"))?,
+            soft_boundary_sharpness_factor: program
+                .uniform_location(state, &ImmutableString::new("softBoundarySharpnessFactor"))?,
+            z_near: program.uniform_location(state, &ImmutableString::new("zNear"))?,
+            z_far: program.uniform_location(state, &ImmutableString::new("zFar"))?,
+            program,
+        })
+    }
+}
+
+/// A particle in the layout of the state textures.
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[repr(C)]
+struct ParticleTexel {
+    position_age: Vector4<f32>,
+    velocity_lifetime: Vector4<f32>,
+    size_rotation: Vector4<f32>,
+    parameters: Vector4<f32>,
+}
+
+impl ParticleTexel {
+    fn from_spawn(spawn: &GpuParticleSpawn) -> Self {
+        Self {
+            position_age: spawn.position.push(0.0),
+            velocity_lifetime: spawn.velocity.push(spawn.lifetime),
+            size_rotation: Vector4::new(
+                spawn.size,
+                spawn.size_modifier,
+                spawn.rotation,
+                spawn.rotation_speed,
+            ),
+            parameters: Vector4::new(spawn.emitter_index as f32, spawn.delay, 0.0, 0.0),
+        }
+    }
+
+    /// The same as particle_simulate_fs.glsl, it is used when the simulation is done on CPU.
+    fn simulate(&mut self, acceleration: Vector3<f32>, dt: f32) {
+        let step_dt = (dt - self.parameters.y).max(0.0);
+        self.parameters.y = 0.0;
+
+        if self.velocity_lifetime.w > 0.0 && step_dt > 0.0 {
+            self.position_age.w += step_dt;
+            if self.position_age.w >= self.velocity_lifetime.w {
+                self.velocity_lifetime.w = 0.0;
+            } else {
+                let velocity = self.velocity_lifetime.xyz() + acceleration.scale(step_dt * step_dt);
+                self.velocity_lifetime = velocity.push(self.velocity_lifetime.w);
+                self.position_age = (self.position_age.xyz() + velocity).push(self.position_age.w);
+                self.size_rotation.x =
+                    (self.size_rotation.x + self.size_rotation.y * step_dt).max(0.0);
+                self.size_rotation.z += self.size_rotation.w * step_dt;
+            }
+        }
+    }
+}
+
+/// A vertex of the spawn pass, every spawned particle is a point.
+#[repr(C)]
+struct SpawnVertex {
+    slot: Vector2<f32>,
+    particle: ParticleTexel,
+}
+
+fn state_size(capacity: u32) -> (usize, usize) {
+    (
+        STATE_WIDTH,
+        ((capacity as usize + STATE_WIDTH - 1) / STATE_WIDTH).max(1),
+    )
+}
+
+fn slot_position(slot: u32) -> Vector2<f32> {
+    Vector2::new(
+        (slot as usize % STATE_WIDTH) as f32,
+        (slot as usize / STATE_WIDTH) as f32,
+    )
+}
+
+fn create_state_texture(
+    state: &PipelineState,
+    width: usize,
+    height: usize,
+) -> Result<GpuTexture, FrameworkError> {
+    let mut texture = GpuTexture::new(
+        state,
+        GpuTextureKind::Rectangle { width, height },
+        PixelKind::RGBA32F,
+        MinificationFilter::Nearest,
+        MagnificationFilter::Nearest,
+        1,
+        None,
+    )?;
+    texture
+        .bind_mut(state, 0)
+        .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+        .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
+    Ok(texture)
+}
+
+fn create_state_framebuffer(
+    state: &PipelineState,
+    width: usize,
+    height: usize,
+) -> Result<FrameBuffer, FrameworkError> {
+    let mut color_attachments = Vec::with_capacity(STATE_TEXTURE_COUNT);
+    for _ in 0..STATE_TEXTURE_COUNT {
+        color_attachments.push(Attachment {
+            kind: AttachmentKind::Color,
+            texture: Rc::new(RefCell::new(create_state_texture(state, width, height)?)),
+        });
+    }
+    let mut framebuffer = FrameBuffer::new(state, None, color_attachments)?;
+    framebuffer.clear(
+        state,
+        Rect::new(0, 0, width as i32, height as i32),
+        Some(Color::TRANSPARENT),
+        None,
+        None,
+    );
+    Ok(framebuffer)
+}
+
+fn state_textures(framebuffer: &FrameBuffer) -> [Rc<RefCell<GpuTexture>>; STATE_TEXTURE_COUNT] {
+    std::array::from_fn(|i| framebuffer.color_attachments()[i].texture.clone())
+}
+
+struct GpuParticleState {
+    current: FrameBuffer,
+    next: FrameBuffer,
+}
+
+struct CpuParticleState {
+    particles: Vec<ParticleTexel>,
+    textures: [Rc<RefCell<GpuTexture>>; STATE_TEXTURE_COUNT],
+    pixels: Vec<Vector4<f32>>,
+}
+
+impl CpuParticleState {
+    fn upload(&mut self, state: &PipelineState) -> Result<(), FrameworkError> {
+        let height = self.particles.len() / STATE_WIDTH;
+        for (i, texture) in self.textures.iter().enumerate() {
+            self.pixels.clear();
+            self.pixels
+                .extend(self.particles.iter().map(|particle| match i {
+                    0 => particle.position_age,
+                    1 => particle.velocity_lifetime,
+                    2 => particle.size_rotation,
+                    _ => particle.parameters,
+                }));
+            texture.borrow_mut().bind_mut(state, 0).set_data(
+                GpuTextureKind::Rectangle {
+                    width: STATE_WIDTH,
+                    height,
+                },
+                PixelKind::RGBA32F,
+                1,
+                Some(crate::core::array_as_u8_slice(&self.pixels)),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+enum ParticleSimulationState {
+    Gpu(GpuParticleState),
+    Cpu(CpuParticleState),
+}
+
+struct ParticleSystemInstance {
+    capacity: u32,
+    // Amount of slots, that were used at least once. Instances above this count are never drawn.
+    slot_count: u32,
+    state: ParticleSimulationState,
+    color_over_lifetime: Rc<RefCell<GpuTexture>>,
+    alive: bool,
+}
+
+impl ParticleSystemInstance {
+    fn new(state: &PipelineState, capacity: u32) -> Result<Self, FrameworkError> {
+        let (width, height) = state_size(capacity);
+
+        let create_gpu_state = || -> Result<GpuParticleState, FrameworkError> {
+            Ok(GpuParticleState {
+                current: create_state_framebuffer(state, width, height)?,
+                next: create_state_framebuffer(state, width, height)?,
+            })
+        };
+
+        let create_cpu_state = || -> Result<CpuParticleState, FrameworkError> {
+            let mut cpu_state = CpuParticleState {
+                particles: vec![Default::default(); width * height],
+                textures: [
+                    Rc::new(RefCell::new(create_state_texture(state, width, height)?)),
+                    Rc::new(RefCell::new(create_state_texture(state, width, height)?)),
+                    Rc::new(RefCell::new(create_state_texture(state, width, height)?)),
+                    Rc::new(RefCell::new(create_state_texture(state, width, height)?)),
+                ],
+                pixels: Default::default(),
+            };
+            cpu_state.upload(state)?;
+            Ok(cpu_state)
+        };
+
+        let simulation_state = if state.supports_float_render_targets() {
+            match create_gpu_state() {
+                Ok(gpu_state) => ParticleSimulationState::Gpu(gpu_state),
+                Err(err) => {
+                    Log::warn(format!(
+                        "Unable to create GPU particle simulation, falling back to CPU. Reason: {err:?}"
+                    ));
+                    ParticleSimulationState::Cpu(create_cpu_state()?)
+                }
+            }
+        } else {
+            ParticleSimulationState::Cpu(create_cpu_state()?)
+        };
+
+        let mut color_over_lifetime = GpuTexture::new(
+            state,
+            GpuTextureKind::Rectangle {
+                width: GRADIENT_SAMPLES,
+                height: 1,
+            },
+            PixelKind::RGBA8,
+            MinificationFilter::Linear,
+            MagnificationFilter::Linear,
+            1,
+            None,
+        )?;
+        color_over_lifetime
+            .bind_mut(state, 0)
+            .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+            .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
+
+        Ok(Self {
+            capacity,
+            slot_count: 0,
+            state: simulation_state,
+            color_over_lifetime: Rc::new(RefCell::new(color_over_lifetime)),
+            alive: true,
+        })
+    }
+
+    fn state_textures(&self) -> [Rc<RefCell<GpuTexture>>; STATE_TEXTURE_COUNT] {
+        match self.state {
+            ParticleSimulationState::Gpu(ref gpu_state) => state_textures(&gpu_state.current),
+            ParticleSimulationState::Cpu(ref cpu_state) => cpu_state.textures.clone(),
+        }
+    }
+}
+
+pub struct GpuParticleRenderer {
+    simulation_shader: SimulationShader,
+    spawn_shader: SpawnShader,
+    particle_shader: ParticleShader,
+    spawn_geometry: GeometryBuffer,
+    quad: GeometryBuffer,
+    spawn_vertices: Vec<SpawnVertex>,
+    spawn_indices: Vec<u32>,
+    gradient: Vec<Color>,
+    instances: FxHashMap<(Handle<Scene>, Handle<Node>), ParticleSystemInstance>,
+}
+
+impl GpuParticleRenderer {
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let mut spawn_buffer =
+            BufferBuilder::new::<SpawnVertex>(GeometryBufferKind::DynamicDraw, None)
+                .with_attribute(AttributeDefinition {
+                    location: 0,
+                    kind: AttributeKind::Float2,
+                    normalized: false,
+                    divisor: 0,
+                });
+        for location in 1..=STATE_TEXTURE_COUNT as u32 {
+            spawn_buffer = spawn_buffer.with_attribute(AttributeDefinition {
+                location,
+                kind: AttributeKind::Float4,
+                normalized: false,
+                divisor: 0,
+            });
+        }
+
+        Ok(Self {
+            simulation_shader: SimulationShader::new(state)?,
+            spawn_shader: SpawnShader::new(state)?,
+            particle_shader: ParticleShader::new(state)?,
+            spawn_geometry: GeometryBufferBuilder::new(ElementKind::Point)
+                .with_buffer_builder(spawn_buffer)
+                .build(state)?,
+            quad: GeometryBuffer::from_surface_data(
+                &SurfaceData::make_unit_xy_quad(),
+                GeometryBufferKind::StaticDraw,
+                state,
+            )?,
+            spawn_vertices: Default::default(),
+            spawn_indices: Default::default(),
+            gradient: vec![Color::WHITE; GRADIENT_SAMPLES],
+            instances: Default::default(),
+        })
+    }
+
+    fn step_gpu(
+        &mut self,
+        state: &PipelineState,
+        quad: &GeometryBuffer,
+        gpu_state: &mut GpuParticleState,
+        capacity: u32,
+        batch: &GpuParticleBatch,
+        acceleration: Vector3<f32>,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut stats = RenderPassStatistics::default();
+
+        let (width, height) = state_size(capacity);
+        let viewport = Rect::new(0, 0, width as i32, height as i32);
+
+        if batch.reset {
+            gpu_state
+                .current
+                .clear(state, viewport, Some(Color::TRANSPARENT), None, None);
+        }
+
+        if !batch.spawns.is_empty() {
+            self.spawn_vertices.clear();
+            self.spawn_indices.clear();
+            for spawn in batch.spawns.iter() {
+                self.spawn_indices.push(self.spawn_vertices.len() as u32);
+                self.spawn_vertices.push(SpawnVertex {
+                    slot: slot_position(spawn.slot),
+                    particle: ParticleTexel::from_spawn(spawn),
+                });
+            }
+            self.spawn_geometry
+                .set_buffer_data(state, 0, &self.spawn_vertices);
+            self.spawn_geometry
+                .bind(state)
+                .set_points(&self.spawn_indices);
+
+            let shader = &self.spawn_shader;
+            stats += gpu_state.current.draw(
+                &self.spawn_geometry,
+                state,
+                viewport,
+                &shader.program,
+                &DrawParameters {
+                    cull_face: None,
+                    color_write: Default::default(),
+                    depth_write: false,
+                    stencil_test: None,
+                    depth_test: false,
+                    blend: None,
+                    stencil_op: Default::default(),
+                },
+                ElementRange::Full,
+                |mut program_binding| {
+                    program_binding.set_vector2(
+                        &shader.state_size,
+                        &Vector2::new(width as f32, height as f32),
+                    );
+                },
+            )?;
+        }
+
+        if batch.dt > 0.0 {
+            let shader = &self.simulation_shader;
+            let [position_age, velocity_lifetime, size_rotation, parameters] =
+                state_textures(&gpu_state.current);
+            stats += gpu_state.next.draw(
+                quad,
+                state,
+                viewport,
+                &shader.program,
+                &DrawParameters {
+                    cull_face: None,
+                    color_write: Default::default(),
+                    depth_write: false,
+                    stencil_test: None,
+                    depth_test: false,
+                    blend: None,
+                    stencil_op: Default::default(),
+                },
+                ElementRange::Full,
+                |mut program_binding| {
+                    program_binding
+                        .set_matrix4(&shader.wvp_matrix, &make_viewport_matrix(viewport))
+                        .set_texture(&shader.position_age_sampler, &position_age)
+                        .set_texture(&shader.velocity_lifetime_sampler, &velocity_lifetime)
+                        .set_texture(&shader.size_rotation_sampler, &size_rotation)
+                        .set_texture(&shader.parameters_sampler, &parameters)
+                        .set_vector3(&shader.acceleration, &acceleration)
+                        .set_f32(&shader.dt, batch.dt);
+                },
+            )?;
+            std::mem::swap(&mut gpu_state.current, &mut gpu_state.next);
+        }
+
+        Ok(stats)
+    }
+
+    /// Performs a simulation step for every GPU particle system in the scene. Must be called once
+    /// per frame.
+    pub(crate) fn update(
+        &mut self,
+        state: &PipelineState,
+        quad: &GeometryBuffer,
+        scene_handle: Handle<Scene>,
+        graph: &Graph,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!();
+
+        let mut stats = RenderPassStatistics::default();
+
+        for (key, instance) in self.instances.iter_mut() {
+            if key.0 == scene_handle {
+                instance.alive = false;
+            }
+        }
+
+        for (handle, node) in graph.pair_iter() {
+            let Some(particle_system) = node.cast::<ParticleSystem>() else {
+                continue;
+            };
+            if particle_system.simulation_mode() != ParticleSimulationMode::Gpu {
+                continue;
+            }
+
+            let capacity = particle_system.gpu_capacity();
+            let batch = particle_system.take_gpu_batch();
+
+            let key = (scene_handle, handle);
+            let mut instance = match self.instances.remove(&key) {
+                Some(instance) if instance.capacity == capacity => instance,
+                _ => ParticleSystemInstance::new(state, capacity)?,
+            };
+            instance.alive = true;
+
+            if batch.reset {
+                instance.slot_count = 0;
+            }
+            for spawn in batch.spawns.iter() {
+                instance.slot_count = instance.slot_count.max(spawn.slot + 1);
+            }
+
+            let acceleration = particle_system.acceleration();
+            match instance.state {
+                ParticleSimulationState::Gpu(ref mut gpu_state) => {
+                    stats +=
+                        self.step_gpu(state, quad, gpu_state, capacity, &batch, acceleration)?;
+                }
+                ParticleSimulationState::Cpu(ref mut cpu_state) => {
+                    if batch.reset {
+                        cpu_state.particles.fill(Default::default());
+                    }
+                    for spawn in batch.spawns.iter() {
+                        if let Some(particle) = cpu_state.particles.get_mut(spawn.slot as usize) {
+                            *particle = ParticleTexel::from_spawn(spawn);
+                        }
+                    }
+                    if batch.dt > 0.0 {
+                        for particle in
+                            cpu_state.particles[..instance.slot_count as usize].iter_mut()
+                        {
+                            particle.simulate(acceleration, batch.dt);
+                        }
+                    }
+                    cpu_state.upload(state)?;
+                }
+            }
+
+            particle_system.sample_color_over_lifetime(&mut self.gradient);
+            instance
+                .color_over_lifetime
+                .borrow_mut()
+                .bind_mut(state, 0)
+                .set_data(
+                    GpuTextureKind::Rectangle {
+                        width: GRADIENT_SAMPLES,
+                        height: 1,
+                    },
+                    PixelKind::RGBA8,
+                    1,
+                    Some(crate::core::array_as_u8_slice(&self.gradient)),
+                )?;
+
+            self.instances.insert(key, instance);
+        }
+
+        self.instances.retain(|_, instance| instance.alive);
+
+        Ok(stats)
+    }
+
+    /// Removes simulations of the scenes, that do not satisfy the given predicate.
+    pub(crate) fn retain_scenes(&mut self, mut predicate: impl FnMut(Handle<Scene>) -> bool) {
+        self.instances.retain(|(scene, _), _| predicate(*scene));
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn render(
+        &self,
+        state: &PipelineState,
+        viewport: Rect<i32>,
+        frame_buffer: &mut FrameBuffer,
+        scene_depth: Rc<RefCell<GpuTexture>>,
+        camera: &Camera,
+        scene_handle: Handle<Scene>,
+        graph: &Graph,
+        texture_cache: &mut TextureCache,
+        white_dummy: Rc<RefCell<GpuTexture>>,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!();
+
+        let mut stats = RenderPassStatistics::default();
+
+        let view_projection = camera.view_projection_matrix();
+        let frustum = Frustum::from_view_projection_matrix(view_projection).unwrap_or_default();
+        let inv_view = camera.inv_view_matrix().unwrap_or_else(Matrix4::identity);
+        let camera_position = camera.global_position();
+
+        let shader = &self.particle_shader;
+        for (key, instance) in self.instances.iter() {
+            if key.0 != scene_handle || instance.slot_count == 0 {
+                continue;
+            }
+            let Some(particle_system) = graph
+                .try_get(key.1)
+                .and_then(|n| n.cast::<ParticleSystem>())
+            else {
+                continue;
+            };
+            if !particle_system.global_visibility()
+                || !particle_system.is_globally_enabled()
+                || (particle_system.frustum_culling()
+                    && !frustum.is_intersects_aabb(&particle_system.world_bounding_box()))
+            {
+                continue;
+            }
+
+            let mut emitter_axes = [Vector4::default(); MAX_EMITTERS];
+            let mut emitter_soft_distances = [0.0; MAX_EMITTERS];
+            for ((axis, soft_distance), emitter) in emitter_axes
+                .iter_mut()
+                .zip(emitter_soft_distances.iter_mut())
+                .zip(particle_system.emitters.iter())
+            {
+                *axis = match emitter.billboard_mode() {
+                    BillboardMode::FaceCamera => Vector4::default(),
+                    BillboardMode::VelocityAligned => Vector4::new(0.0, 0.0, 0.0, 1.0),
+                    BillboardMode::AxisLocked(axis) => axis.push(2.0),
+                };
+                *soft_distance = emitter.soft_particle_distance();
+            }
+
+            let material = particle_system.texture_ref().data_ref();
+            let diffuse_texture = material
+                .property_ref(&ImmutableString::new("diffuseTexture"))
+                .and_then(|property| property.as_sampler())
+                .and_then(|texture| texture_cache.get(state, &texture).cloned())
+                .unwrap_or_else(|| white_dummy.clone());
+            let soft_boundary_sharpness_factor = material
+                .property_ref(&ImmutableString::new("softBoundarySharpnessFactor"))
+                .and_then(|property| match property {
+                    PropertyValue::Float(value) => Some(*value),
+                    _ => None,
+                })
+                .unwrap_or(100.0);
+            drop(material);
+
+            let [position_age, velocity_lifetime, size_rotation, parameters] =
+                instance.state_textures();
+
+            stats += frame_buffer.draw_instances(
+                instance.slot_count as usize,
+                &self.quad,
+                state,
+                viewport,
+                &shader.program,
+                &DrawParameters {
+                    cull_face: None,
+                    color_write: Default::default(),
+                    depth_write: false,
+                    stencil_test: None,
+                    depth_test: true,
+                    blend: Some(BlendParameters {
+                        func: BlendFunc::new(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
+                        ..Default::default()
+                    }),
+                    stencil_op: Default::default(),
+                },
+                ElementRange::Full,
+                |mut program_binding| {
+                    program_binding
+                        .set_texture(&shader.position_age_sampler, &position_age)
+                        .set_texture(&shader.velocity_lifetime_sampler, &velocity_lifetime)
+                        .set_texture(&shader.size_rotation_sampler, &size_rotation)
+                        .set_texture(&shader.parameters_sampler, &parameters)
+                        .set_texture(&shader.color_over_lifetime, &instance.color_over_lifetime)
+                        .set_i32(&shader.state_width, STATE_WIDTH as i32)
+                        .set_matrix4(&shader.view_projection, &view_projection)
+                        .set_matrix4(&shader.world_matrix, &particle_system.global_transform())
+                        .set_vector3(&shader.camera_position, &camera_position)
+                        .set_vector3(&shader.camera_up_vector, &inv_view.up())
+                        .set_vector3(&shader.camera_side_vector, &inv_view.side())
+                        .set_vector4_slice(&shader.emitter_axes, &emitter_axes)
+                        .set_f32_slice(&shader.emitter_soft_distances, &emitter_soft_distances)
+                        .set_texture(&shader.diffuse_texture, &diffuse_texture)
+                        .set_texture(&shader.scene_depth, &scene_depth)
+                        .set_f32(
+                            &shader.soft_boundary_sharpness_factor,
+                            soft_boundary_sharpness_factor,
+                        )
+                        .set_f32(&shader.z_near, camera.projection().z_near())
+                        .set_f32(&shader.z_far, camera.projection().z_far());
+                },
+            )?;
+        }
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Vector3, Vector4},
+        renderer::gpu_particles::ParticleTexel,
+    };
+
+    #[test]
+    fn test_cpu_fallback_simulation() {
+        let mut particle = ParticleTexel {
+            position_age: Vector4::new(0.0, 0.0, 0.0, 0.0),
+            velocity_lifetime: Vector4::new(1.0, 0.0, 0.0, 1.0),
+            size_rotation: Vector4::new(1.0, -1.0, 0.0, 2.0),
+            parameters: Vector4::new(0.0, 0.25, 0.0, 0.0),
+        };
+
+        // The spawn delay is taken into account only once.
+        particle.simulate(Vector3::default(), 0.5);
+        assert_eq!(particle.position_age, Vector4::new(1.0, 0.0, 0.0, 0.25));
+        assert_eq!(particle.size_rotation, Vector4::new(0.75, -1.0, 0.5, 2.0));
+        assert_eq!(particle.parameters.y, 0.0);
+
+        // The particle dies when its age exceeds the lifetime.
+        particle.simulate(Vector3::default(), 1.0);
+        assert_eq!(particle.velocity_lifetime.w, 0.0);
+    }
+}
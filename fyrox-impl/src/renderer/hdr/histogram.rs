@@ -0,0 +1,185 @@
+use crate::renderer::framework::geometry_buffer::ElementRange;
+use crate::{
+    core::{color::Color, sstorage::ImmutableString},
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::BlendParameters,
+            fullscreen::{
+                fullscreen_draw_parameters, FullscreenPass, RenderTarget, FULLSCREEN_VERTEX_SHADER,
+            },
+            geometry_buffer::GeometryBuffer,
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::{MagnificationFilter, PixelKind},
+            state::{BlendFactor, BlendFunc, PipelineState},
+        },
+        RenderPassStatistics,
+    },
+};
+
+// Must be in sync with the shaders.
+const BIN_COUNT: usize = 64;
+
+// Exposure value for ISO 100 relates to luminance as `L = 2^EV * 12.5 / 100`.
+fn ev100_to_log2_luminance(ev100: f32) -> f32 {
+    ev100 + (12.5f32 / 100.0).log2()
+}
+
+pub struct HistogramShader {
+    pub program: GpuProgram,
+    pub lum_sampler: UniformLocation,
+    pub min_log_lum: UniformLocation,
+    pub inv_log_lum_range: UniformLocation,
+}
+
+impl HistogramShader {
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("../shaders/hdr_histogram_fs.glsl");
+        let vertex_source = include_str!("../shaders/hdr_histogram_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "HistogramShader", vertex_source, fragment_source)?;
+
+        Ok(Self {
+            lum_sampler: program.uniform_location(state, &ImmutableString::new("lumSampler"))?,
+            min_log_lum: program.uniform_location(state, &ImmutableString::new("minLogLum"))?,
+            inv_log_lum_range: program
+                .uniform_location(state, &ImmutableString::new("invLogLumRange"))?,
+            program,
+        })
+    }
+}
+
+pub struct MeteringShader {
+    pub program: GpuProgram,
+    pub histogram_sampler: UniformLocation,
+    pub min_log_lum: UniformLocation,
+    pub log_lum_range: UniformLocation,
+    pub low_percentile: UniformLocation,
+    pub high_percentile: UniformLocation,
+}
+
+impl MeteringShader {
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("../shaders/hdr_metering_fs.glsl");
+
+        let program = GpuProgram::from_source(
+            state,
+            "MeteringShader",
+            FULLSCREEN_VERTEX_SHADER,
+            fragment_source,
+        )?;
+
+        Ok(Self {
+            histogram_sampler: program
+                .uniform_location(state, &ImmutableString::new("histogramSampler"))?,
+            min_log_lum: program.uniform_location(state, &ImmutableString::new("minLogLum"))?,
+            log_lum_range: program.uniform_location(state, &ImmutableString::new("logLumRange"))?,
+            low_percentile: program
+                .uniform_location(state, &ImmutableString::new("lowPercentile"))?,
+            high_percentile: program
+                .uniform_location(state, &ImmutableString::new("highPercentile"))?,
+            program,
+        })
+    }
+}
+
+/// Parameters of histogram-based metering.
+pub struct MeteringSettings {
+    pub min_ev: f32,
+    pub max_ev: f32,
+    pub low_percentile: f32,
+    pub high_percentile: f32,
+}
+
+/// Log-luminance histogram of a frame. Since the engine is limited by OpenGL 3.3 (and 4.1 on
+/// macOS), there are no compute shaders, so the histogram is built by scattering: every pixel
+/// of the luminance buffer is drawn as an instance of a quad, that covers a single bin of the
+/// histogram, and the bins are accumulated using additive blending.
+pub struct LuminanceHistogram {
+    bins: RenderTarget,
+    histogram_shader: HistogramShader,
+    metering_shader: MeteringShader,
+}
+
+impl LuminanceHistogram {
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            bins: RenderTarget::new(
+                state,
+                BIN_COUNT,
+                1,
+                PixelKind::R32F,
+                MagnificationFilter::Nearest,
+            )?,
+            histogram_shader: HistogramShader::new(state)?,
+            metering_shader: MeteringShader::new(state)?,
+        })
+    }
+
+    /// Builds the histogram of the given luminance buffer and writes metered average luminance
+    /// to the output buffer.
+    pub fn render(
+        &mut self,
+        state: &PipelineState,
+        quad: &GeometryBuffer,
+        frame_luminance: &RenderTarget,
+        output: &mut RenderTarget,
+        settings: &MeteringSettings,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut stats = RenderPassStatistics::default();
+
+        let min_log_lum = ev100_to_log2_luminance(settings.min_ev);
+        let log_lum_range =
+            (ev100_to_log2_luminance(settings.max_ev) - min_log_lum).max(f32::EPSILON);
+
+        self.bins.clear(state, Color::TRANSPARENT);
+
+        let shader = &self.histogram_shader;
+        let lum_texture = frame_luminance.texture();
+        let (lum_width, lum_height) = frame_luminance.size();
+        let viewport = self.bins.viewport();
+        stats += self.bins.framebuffer_mut().draw_instances(
+            lum_width * lum_height,
+            quad,
+            state,
+            viewport,
+            &shader.program,
+            &fullscreen_draw_parameters(Some(BlendParameters {
+                func: BlendFunc::new(BlendFactor::One, BlendFactor::One),
+                ..Default::default()
+            })),
+            ElementRange::Full,
+            |mut program_binding| {
+                program_binding
+                    .set_texture(&shader.lum_sampler, &lum_texture)
+                    .set_f32(&shader.min_log_lum, min_log_lum)
+                    .set_f32(&shader.inv_log_lum_range, 1.0 / log_lum_range);
+            },
+        )?;
+
+        let shader = &self.metering_shader;
+        let histogram = self.bins.texture();
+        stats += output.draw(
+            &FullscreenPass::new(state, quad),
+            &shader.program,
+            None,
+            |program_binding| {
+                program_binding
+                    .set_texture(&shader.histogram_sampler, &histogram)
+                    .set_f32(&shader.min_log_lum, min_log_lum)
+                    .set_f32(&shader.log_lum_range, log_lum_range)
+                    .set_f32(
+                        &shader.low_percentile,
+                        settings.low_percentile.clamp(0.0, 1.0),
+                    )
+                    .set_f32(
+                        &shader.high_percentile,
+                        settings.high_percentile.clamp(0.0, 1.0),
+                    );
+            },
+        )?;
+
+        Ok(stats)
+    }
+}
@@ -1,6 +1,7 @@
 use crate::core::sstorage::ImmutableString;
 use crate::renderer::framework::{
     error::FrameworkError,
+    fullscreen::FULLSCREEN_VERTEX_SHADER,
     gpu_program::{GpuProgram, UniformLocation},
     state::PipelineState,
 };
@@ -9,20 +10,20 @@ pub struct LuminanceShader {
     pub program: GpuProgram,
     pub frame_sampler: UniformLocation,
     pub inv_size: UniformLocation,
-    pub wvp_matrix: UniformLocation,
 }
 
 impl LuminanceShader {
     pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
         let fragment_source = include_str!("../shaders/hdr_luminance_fs.glsl");
-        let vertex_source = include_str!("../shaders/flat_vs.glsl");
 
-        let program =
-            GpuProgram::from_source(state, "LuminanceShader", vertex_source, fragment_source)?;
+        let program = GpuProgram::from_source(
+            state,
+            "LuminanceShader",
+            FULLSCREEN_VERTEX_SHADER,
+            fragment_source,
+        )?;
 
         Ok(Self {
-            wvp_matrix: program
-                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
             frame_sampler: program
                 .uniform_location(state, &ImmutableString::new("frameSampler"))?,
             inv_size: program.uniform_location(state, &ImmutableString::new("invSize"))?,
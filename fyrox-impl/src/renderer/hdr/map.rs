@@ -11,6 +11,7 @@ pub struct MapShader {
     pub hdr_sampler: UniformLocation,
     pub lum_sampler: UniformLocation,
     pub bloom_sampler: UniformLocation,
+    pub bloom_intensity: UniformLocation,
     pub color_map_sampler: UniformLocation,
     pub use_color_grading: UniformLocation,
     pub key_value: UniformLocation,
@@ -18,6 +19,10 @@ pub struct MapShader {
     pub max_luminance: UniformLocation,
     pub auto_exposure: UniformLocation,
     pub fixed_exposure: UniformLocation,
+    pub physical_exposure: UniformLocation,
+    pub min_ev: UniformLocation,
+    pub max_ev: UniformLocation,
+    pub exposure_compensation: UniformLocation,
 }
 
 impl MapShader {
@@ -35,6 +40,8 @@ impl MapShader {
             lum_sampler: program.uniform_location(state, &ImmutableString::new("lumSampler"))?,
             bloom_sampler: program
                 .uniform_location(state, &ImmutableString::new("bloomSampler"))?,
+            bloom_intensity: program
+                .uniform_location(state, &ImmutableString::new("bloomIntensity"))?,
             color_map_sampler: program
                 .uniform_location(state, &ImmutableString::new("colorMapSampler"))?,
             use_color_grading: program
@@ -48,6 +55,12 @@ impl MapShader {
                 .uniform_location(state, &ImmutableString::new("autoExposure"))?,
             fixed_exposure: program
                 .uniform_location(state, &ImmutableString::new("fixedExposure"))?,
+            physical_exposure: program
+                .uniform_location(state, &ImmutableString::new("physicalExposure"))?,
+            min_ev: program.uniform_location(state, &ImmutableString::new("minEv"))?,
+            max_ev: program.uniform_location(state, &ImmutableString::new("maxEv"))?,
+            exposure_compensation: program
+                .uniform_location(state, &ImmutableString::new("exposureCompensation"))?,
             program,
         })
     }
@@ -1,15 +1,14 @@
 use crate::renderer::framework::geometry_buffer::ElementRange;
 use crate::{
-    core::{
-        algebra::{Matrix4, Vector2, Vector3},
-        color::Color,
-        math::Rect,
-    },
+    core::{color::Color, math::Rect},
     renderer::{
         cache::texture::TextureCache,
         framework::{
             error::FrameworkError,
-            framebuffer::{Attachment, AttachmentKind, DrawParameters, FrameBuffer},
+            framebuffer::{DrawParameters, FrameBuffer},
+            fullscreen::{
+                DownsampleFilter, DownsamplePass, FullscreenPass, PingPongBuffer, RenderTarget,
+            },
             geometry_buffer::{DrawCallStatistics, GeometryBuffer},
             gpu_texture::{
                 GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter, PixelKind,
@@ -17,8 +16,8 @@ use crate::{
             state::PipelineState,
         },
         hdr::{
-            adaptation::{AdaptationChain, AdaptationShader},
-            downscale::DownscaleShader,
+            adaptation::AdaptationShader,
+            histogram::{LuminanceHistogram, MeteringSettings},
             luminance::LuminanceShader,
             map::MapShader,
         },
@@ -26,104 +25,82 @@ use crate::{
     },
     scene::camera::{ColorGradingLut, Exposure},
 };
-use fyrox_core::{transmute_slice, value_as_u8_slice};
 use std::{cell::RefCell, rc::Rc};
 
 mod adaptation;
-mod downscale;
+mod histogram;
 mod luminance;
 mod map;
 
-#[allow(dead_code)] // TODO
 pub enum LuminanceCalculationMethod {
-    Histogram,
+    Histogram(MeteringSettings),
     DownSampling,
 }
 
-pub struct LumBuffer {
-    framebuffer: FrameBuffer,
-    size: usize,
-}
-
-impl LumBuffer {
-    fn new(state: &PipelineState, size: usize) -> Result<Self, FrameworkError> {
-        let texture = GpuTexture::new(
-            state,
-            GpuTextureKind::Rectangle {
-                width: size,
-                height: size,
-            },
-            PixelKind::R32F,
-            MinificationFilter::Nearest,
-            MagnificationFilter::Nearest,
-            1,
-            None,
-        )?;
-        Ok(Self {
-            framebuffer: FrameBuffer::new(
-                state,
-                None,
-                vec![Attachment {
-                    kind: AttachmentKind::Color,
-                    texture: Rc::new(RefCell::new(texture)),
-                }],
-            )?,
-            size,
-        })
-    }
-
-    fn clear(&mut self, state: &PipelineState) {
-        self.framebuffer.clear(
-            state,
-            Rect::new(0, 0, self.size as i32, self.size as i32),
-            Some(Color::BLACK),
-            None,
-            None,
-        );
-    }
-
-    fn matrix(&self) -> Matrix4<f32> {
-        Matrix4::new_orthographic(0.0, self.size as f32, self.size as f32, 0.0, -1.0, 1.0)
-            * Matrix4::new_nonuniform_scaling(&Vector3::new(
-                self.size as f32,
-                self.size as f32,
-                0.0,
-            ))
+impl LuminanceCalculationMethod {
+    fn from_exposure(exposure: &Exposure) -> Self {
+        match *exposure {
+            Exposure::Histogram {
+                min_ev,
+                max_ev,
+                low_percentile,
+                high_percentile,
+                ..
+            } => Self::Histogram(MeteringSettings {
+                min_ev,
+                max_ev,
+                low_percentile,
+                high_percentile,
+            }),
+            Exposure::Auto { .. } | Exposure::Manual(_) => Self::DownSampling,
+        }
     }
+}
 
-    fn texture(&self) -> Rc<RefCell<GpuTexture>> {
-        self.framebuffer.color_attachments()[0].texture.clone()
-    }
+fn make_lum_buffer(state: &PipelineState, size: usize) -> Result<RenderTarget, FrameworkError> {
+    RenderTarget::new(
+        state,
+        size,
+        size,
+        PixelKind::R32F,
+        MagnificationFilter::Nearest,
+    )
 }
 
 pub struct HighDynamicRangeRenderer {
-    adaptation_chain: AdaptationChain,
-    downscale_chain: [LumBuffer; 6],
-    frame_luminance: LumBuffer,
+    adaptation_buffer: PingPongBuffer,
+    downscale_chain: [RenderTarget; 6],
+    frame_luminance: RenderTarget,
     adaptation_shader: AdaptationShader,
     luminance_shader: LuminanceShader,
-    downscale_shader: DownscaleShader,
+    downsample: DownsamplePass,
     map_shader: MapShader,
     stub_lut: Rc<RefCell<GpuTexture>>,
-    lum_calculation_method: LuminanceCalculationMethod,
+    histogram: LuminanceHistogram,
 }
 
 impl HighDynamicRangeRenderer {
     pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
         Ok(Self {
-            frame_luminance: LumBuffer::new(state, 64)?,
+            frame_luminance: make_lum_buffer(state, 64)?,
             downscale_chain: [
-                LumBuffer::new(state, 32)?,
-                LumBuffer::new(state, 16)?,
-                LumBuffer::new(state, 8)?,
-                LumBuffer::new(state, 4)?,
-                LumBuffer::new(state, 2)?,
-                LumBuffer::new(state, 1)?,
+                make_lum_buffer(state, 32)?,
+                make_lum_buffer(state, 16)?,
+                make_lum_buffer(state, 8)?,
+                make_lum_buffer(state, 4)?,
+                make_lum_buffer(state, 2)?,
+                make_lum_buffer(state, 1)?,
             ],
-            adaptation_chain: AdaptationChain::new(state)?,
+            adaptation_buffer: PingPongBuffer::new(
+                state,
+                1,
+                1,
+                PixelKind::R32F,
+                MagnificationFilter::Nearest,
+            )?,
             adaptation_shader: AdaptationShader::new(state)?,
             luminance_shader: LuminanceShader::new(state)?,
-            downscale_shader: DownscaleShader::new(state)?,
+            downsample: DownsamplePass::new(state)?,
             map_shader: MapShader::new(state)?,
             stub_lut: Rc::new(RefCell::new(GpuTexture::new(
                 state,
@@ -138,7 +115,7 @@ impl HighDynamicRangeRenderer {
                 1,
                 Some(&[0, 0, 0]),
             )?)),
-            lum_calculation_method: LuminanceCalculationMethod::DownSampling,
+            histogram: LuminanceHistogram::new(state)?,
         })
     }
 
@@ -148,35 +125,17 @@ impl HighDynamicRangeRenderer {
         scene_frame: Rc<RefCell<GpuTexture>>,
         quad: &GeometryBuffer,
     ) -> Result<DrawCallStatistics, FrameworkError> {
-        self.frame_luminance.clear(state);
-        let frame_matrix = self.frame_luminance.matrix();
+        self.frame_luminance.clear(state, Color::BLACK);
 
         let shader = &self.luminance_shader;
-        let inv_size = 1.0 / self.frame_luminance.size as f32;
-        self.frame_luminance.framebuffer.draw(
-            quad,
-            state,
-            Rect::new(
-                0,
-                0,
-                self.frame_luminance.size as i32,
-                self.frame_luminance.size as i32,
-            ),
+        let inv_size = self.frame_luminance.texel_size();
+        self.frame_luminance.draw(
+            &FullscreenPass::new(state, quad),
             &shader.program,
-            &DrawParameters {
-                cull_face: None,
-                color_write: Default::default(),
-                depth_write: false,
-                stencil_test: None,
-                depth_test: false,
-                blend: None,
-                stencil_op: Default::default(),
-            },
-            ElementRange::Full,
-            |mut program_binding| {
+            None,
+            |program_binding| {
                 program_binding
-                    .set_matrix4(&shader.wvp_matrix, &frame_matrix)
-                    .set_vector2(&shader.inv_size, &Vector2::new(inv_size, inv_size))
+                    .set_vector2(&shader.inv_size, &inv_size)
                     .set_texture(&shader.frame_sampler, &scene_frame);
             },
         )
@@ -186,95 +145,34 @@ impl HighDynamicRangeRenderer {
         &mut self,
         state: &PipelineState,
         quad: &GeometryBuffer,
+        method: LuminanceCalculationMethod,
     ) -> Result<RenderPassStatistics, FrameworkError> {
         let mut stats = RenderPassStatistics::default();
 
-        match self.lum_calculation_method {
-            LuminanceCalculationMethod::Histogram => {
-                let luminance_range = 0.00778f32..8.0f32;
-                let log2_luminance_range = luminance_range.start.log2()..luminance_range.end.log2();
-                let log2_lum_range = luminance_range.end.log2() - luminance_range.start.log2();
-
-                // TODO: Cloning memory from GPU to CPU is slow, but since the engine is limited
-                // by macOS's OpenGL 4.1 support and lack of compute shaders we'll build histogram
-                // manually on CPU anyway. Replace this with compute shaders whenever possible.
-                let data = self
-                    .frame_luminance
-                    .texture()
-                    .borrow_mut()
-                    .bind_mut(state, 0)
-                    .read_pixels(state);
-
-                let pixels = transmute_slice::<u8, f32>(&data);
-
-                // Build histogram.
-                let mut bins = [0usize; 64];
-                for &luminance in pixels {
-                    let k = (luminance.log2() - log2_luminance_range.start) / log2_lum_range;
-                    let index =
-                        ((bins.len() as f32 * k) as usize).clamp(0, bins.len().saturating_sub(1));
-                    bins[index] += 1;
-                }
-
-                // Compute mean value.
-                let mut total_luminance = 0.0;
-                let mut counter = 0;
-                for (bin_index, count) in bins.iter().cloned().enumerate() {
-                    let avg_luminance = log2_luminance_range.start
-                        + (bin_index + 1) as f32 / bins.len() as f32 * log2_lum_range;
-                    total_luminance += avg_luminance * (count as f32);
-                    counter += count;
-                }
-
-                let weighted_lum = (total_luminance / counter as f32).exp2();
-                let avg_lum = luminance_range.start
-                    + weighted_lum * (luminance_range.end - luminance_range.start);
-
-                self.downscale_chain
-                    .last()
-                    .unwrap()
-                    .texture()
-                    .borrow_mut()
-                    .bind_mut(state, 0)
-                    .set_data(
-                        GpuTextureKind::Rectangle {
-                            width: 1,
-                            height: 1,
-                        },
-                        PixelKind::R32F,
-                        1,
-                        Some(value_as_u8_slice(&avg_lum)),
-                    )?;
+        match method {
+            LuminanceCalculationMethod::Histogram(settings) => {
+                stats += self.histogram.render(
+                    state,
+                    quad,
+                    &self.frame_luminance,
+                    self.downscale_chain.last_mut().unwrap(),
+                    &settings,
+                )?;
             }
             LuminanceCalculationMethod::DownSampling => {
-                let shader = &self.downscale_shader;
+                let pass = FullscreenPass::new(state, quad);
                 let mut prev_luminance = self.frame_luminance.texture();
+                let mut prev_texel_size = self.frame_luminance.texel_size();
                 for lum_buffer in self.downscale_chain.iter_mut() {
-                    let inv_size = 1.0 / lum_buffer.size as f32;
-                    let matrix = lum_buffer.matrix();
-                    stats += lum_buffer.framebuffer.draw(
-                        quad,
-                        state,
-                        Rect::new(0, 0, lum_buffer.size as i32, lum_buffer.size as i32),
-                        &shader.program,
-                        &DrawParameters {
-                            cull_face: None,
-                            color_write: Default::default(),
-                            depth_write: false,
-                            stencil_test: None,
-                            depth_test: false,
-                            blend: None,
-                            stencil_op: Default::default(),
-                        },
-                        ElementRange::Full,
-                        |mut program_binding| {
-                            program_binding
-                                .set_matrix4(&shader.wvp_matrix, &matrix)
-                                .set_vector2(&shader.inv_size, &Vector2::new(inv_size, inv_size))
-                                .set_texture(&shader.lum_sampler, &prev_luminance);
-                        },
+                    stats += self.downsample.render(
+                        &pass,
+                        lum_buffer,
+                        &prev_luminance,
+                        prev_texel_size,
+                        DownsampleFilter::Box,
                     )?;
 
+                    prev_texel_size = lum_buffer.texel_size();
                     prev_luminance = lum_buffer.texture();
                 }
             }
@@ -288,37 +186,35 @@ impl HighDynamicRangeRenderer {
         state: &PipelineState,
         quad: &GeometryBuffer,
         dt: f32,
+        exposure: &Exposure,
     ) -> Result<DrawCallStatistics, FrameworkError> {
+        let (speed_up, speed_down) = match *exposure {
+            Exposure::Histogram {
+                speed_up,
+                speed_down,
+                ..
+            } => (1.0 - (-dt * speed_up).exp(), 1.0 - (-dt * speed_down).exp()),
+            // Instant adaptation to darker frames.
+            Exposure::Auto { .. } | Exposure::Manual(_) => (0.3 * dt, 1.0),
+        };
+
         let new_lum = self.downscale_chain.last().unwrap().texture();
-        let ctx = self.adaptation_chain.begin();
-        let viewport = Rect::new(0, 0, ctx.lum_buffer.size as i32, ctx.lum_buffer.size as i32);
+        let prev_lum = self.adaptation_buffer.result();
         let shader = &self.adaptation_shader;
-        let matrix = ctx.lum_buffer.matrix();
-        let prev_lum = ctx.prev_lum;
-        ctx.lum_buffer.framebuffer.draw(
-            quad,
-            state,
-            viewport,
+        let stats = self.adaptation_buffer.write().draw(
+            &FullscreenPass::new(state, quad),
             &shader.program,
-            &DrawParameters {
-                cull_face: None,
-                color_write: Default::default(),
-                depth_write: false,
-                stencil_test: None,
-                depth_test: false,
-                blend: None,
-                stencil_op: Default::default(),
-            },
-            ElementRange::Full,
-            |mut program_binding| {
+            None,
+            |program_binding| {
                 program_binding
-                    .set_matrix4(&shader.wvp_matrix, &matrix)
                     .set_texture(&shader.old_lum_sampler, &prev_lum)
                     .set_texture(&shader.new_lum_sampler, &new_lum)
-                    .set_f32(&shader.speed, 0.3 * dt) // TODO: Make configurable
-                ;
+                    .set_f32(&shader.speed_up, speed_up)
+                    .set_f32(&shader.speed_down, speed_down);
             },
-        )
+        )?;
+        self.adaptation_buffer.swap();
+        Ok(stats)
     }
 
     fn map_hdr_to_ldr(
@@ -326,6 +222,7 @@ impl HighDynamicRangeRenderer {
         state: &PipelineState,
         hdr_scene_frame: Rc<RefCell<GpuTexture>>,
         bloom_texture: Rc<RefCell<GpuTexture>>,
+        bloom_intensity: f32,
         ldr_framebuffer: &mut FrameBuffer,
         viewport: Rect<i32>,
         quad: &GeometryBuffer,
@@ -336,7 +233,7 @@ impl HighDynamicRangeRenderer {
     ) -> Result<DrawCallStatistics, FrameworkError> {
         let shader = &self.map_shader;
         let frame_matrix = make_viewport_matrix(viewport);
-        let avg_lum = self.adaptation_chain.avg_lum_texture();
+        let avg_lum = self.adaptation_buffer.result();
 
         let color_grading_lut_tex = color_grading_lut
             .and_then(|l| texture_cache.get(state, l.lut_ref()))
@@ -362,6 +259,7 @@ impl HighDynamicRangeRenderer {
                     .set_matrix4(&shader.wvp_matrix, &frame_matrix)
                     .set_texture(&shader.lum_sampler, &avg_lum)
                     .set_texture(&shader.bloom_sampler, &bloom_texture)
+                    .set_f32(&shader.bloom_intensity, bloom_intensity.max(0.0))
                     .set_texture(&shader.hdr_sampler, &hdr_scene_frame)
                     .set_bool(
                         &shader.use_color_grading,
@@ -377,10 +275,24 @@ impl HighDynamicRangeRenderer {
                     } => {
                         program_binding
                             .set_bool(&shader.auto_exposure, true)
+                            .set_bool(&shader.physical_exposure, false)
                             .set_f32(&shader.key_value, key_value)
                             .set_f32(&shader.min_luminance, min_luminance)
                             .set_f32(&shader.max_luminance, max_luminance);
                     }
+                    Exposure::Histogram {
+                        min_ev,
+                        max_ev,
+                        compensation,
+                        ..
+                    } => {
+                        program_binding
+                            .set_bool(&shader.auto_exposure, true)
+                            .set_bool(&shader.physical_exposure, true)
+                            .set_f32(&shader.min_ev, min_ev)
+                            .set_f32(&shader.max_ev, max_ev)
+                            .set_f32(&shader.exposure_compensation, compensation);
+                    }
                     Exposure::Manual(fixed_exposure) => {
                         program_binding
                             .set_bool(&shader.auto_exposure, false)
@@ -396,6 +308,7 @@ impl HighDynamicRangeRenderer {
         state: &PipelineState,
         hdr_scene_frame: Rc<RefCell<GpuTexture>>,
         bloom_texture: Rc<RefCell<GpuTexture>>,
+        bloom_intensity: f32,
         ldr_framebuffer: &mut FrameBuffer,
         viewport: Rect<i32>,
         quad: &GeometryBuffer,
@@ -407,12 +320,17 @@ impl HighDynamicRangeRenderer {
     ) -> Result<RenderPassStatistics, FrameworkError> {
         let mut stats = RenderPassStatistics::default();
         stats += self.calculate_frame_luminance(state, hdr_scene_frame.clone(), quad)?;
-        stats += self.calculate_avg_frame_luminance(state, quad)?;
-        stats += self.adaptation(state, quad, dt)?;
+        stats += self.calculate_avg_frame_luminance(
+            state,
+            quad,
+            LuminanceCalculationMethod::from_exposure(&exposure),
+        )?;
+        stats += self.adaptation(state, quad, dt, &exposure)?;
         stats += self.map_hdr_to_ldr(
             state,
             hdr_scene_frame,
             bloom_texture,
+            bloom_intensity,
             ldr_framebuffer,
             viewport,
             quad,
@@ -0,0 +1,61 @@
+use crate::core::sstorage::ImmutableString;
+use crate::renderer::framework::{
+    error::FrameworkError,
+    gpu_program::{GpuProgram, UniformLocation},
+    state::PipelineState,
+};
+
+pub struct ClusteredLightShader {
+    pub program: GpuProgram,
+    pub wvp_matrix: UniformLocation,
+    pub depth_sampler: UniformLocation,
+    pub color_sampler: UniformLocation,
+    pub normal_sampler: UniformLocation,
+    pub material_sampler: UniformLocation,
+    pub cluster_grid: UniformLocation,
+    pub cluster_lights: UniformLocation,
+    pub cluster_light_indices: UniformLocation,
+    pub inv_view_proj_matrix: UniformLocation,
+    pub camera_position: UniformLocation,
+    pub cluster_parameters: UniformLocation,
+    pub cluster_depth_plane: UniformLocation,
+}
+
+impl ClusteredLightShader {
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("../shaders/deferred_clustered_light_fs.glsl");
+        let vertex_source = include_str!("../shaders/deferred_light_vs.glsl");
+        let program = GpuProgram::from_source(
+            state,
+            "ClusteredLightShader",
+            vertex_source,
+            fragment_source,
+        )?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            depth_sampler: program
+                .uniform_location(state, &ImmutableString::new("depthTexture"))?,
+            color_sampler: program
+                .uniform_location(state, &ImmutableString::new("colorTexture"))?,
+            normal_sampler: program
+                .uniform_location(state, &ImmutableString::new("normalTexture"))?,
+            material_sampler: program
+                .uniform_location(state, &ImmutableString::new("materialTexture"))?,
+            cluster_grid: program.uniform_location(state, &ImmutableString::new("clusterGrid"))?,
+            cluster_lights: program
+                .uniform_location(state, &ImmutableString::new("clusterLights"))?,
+            cluster_light_indices: program
+                .uniform_location(state, &ImmutableString::new("clusterLightIndices"))?,
+            inv_view_proj_matrix: program
+                .uniform_location(state, &ImmutableString::new("invViewProj"))?,
+            camera_position: program
+                .uniform_location(state, &ImmutableString::new("cameraPosition"))?,
+            cluster_parameters: program
+                .uniform_location(state, &ImmutableString::new("clusterParameters"))?,
+            cluster_depth_plane: program
+                .uniform_location(state, &ImmutableString::new("clusterDepthPlane"))?,
+            program,
+        })
+    }
+}
@@ -28,7 +28,8 @@ pub struct DirectionalLightShader {
     pub shadow_bias: UniformLocation,
     pub shadows_enabled: UniformLocation,
     pub soft_shadows: UniformLocation,
-    pub shadow_map_inv_size: UniformLocation,
+    pub shadow_map_inv_sizes: UniformLocation,
+    pub show_cascades: UniformLocation,
 }
 
 impl DirectionalLightShader {
@@ -76,8 +77,10 @@ impl DirectionalLightShader {
             shadows_enabled: program
                 .uniform_location(state, &ImmutableString::new("shadowsEnabled"))?,
             soft_shadows: program.uniform_location(state, &ImmutableString::new("softShadows"))?,
-            shadow_map_inv_size: program
-                .uniform_location(state, &ImmutableString::new("shadowMapInvSize"))?,
+            shadow_map_inv_sizes: program
+                .uniform_location(state, &ImmutableString::new("shadowMapInvSizes"))?,
+            show_cascades: program
+                .uniform_location(state, &ImmutableString::new("showCascades"))?,
             program,
         })
     }
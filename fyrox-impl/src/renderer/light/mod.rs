@@ -4,11 +4,15 @@ use crate::{
         algebra::{Matrix4, Point3, Vector2, Vector3},
         color::Color,
         math::{frustum::Frustum, Matrix4Ext, Rect, TriangleDefinition},
+        pool::Handle,
         scope_profile,
     },
     graph::SceneGraph,
+    material::GlobalMaterialProperties,
     renderer::{
+        ao_volume::AoVolumeRenderer,
         cache::shader::ShaderCache,
+        cluster::LightClusters,
         flat_shader::FlatShader,
         framework::{
             error::FrameworkError,
@@ -22,22 +26,25 @@ use crate::{
         },
         gbuffer::GBuffer,
         light::{
-            ambient::AmbientLightShader, directional::DirectionalLightShader,
-            point::PointLightShader, spot::SpotLightShader,
+            ambient::AmbientLightShader, clustered::ClusteredLightShader,
+            directional::DirectionalLightShader, point::PointLightShader, spot::SpotLightShader,
         },
         light_volume::LightVolumeRenderer,
+        probe_volume::ProbeVolumeRenderer,
         shadow::{
             csm::{CsmRenderContext, CsmRenderer},
+            occlusion::ShadowCasterCuller,
             point::{PointShadowMapRenderContext, PointShadowMapRenderer},
             spot::SpotShadowMapRenderer,
         },
         skybox_shader::SkyboxShader,
         ssao::ScreenSpaceAmbientOcclusionRenderer,
+        ssr::ScreenSpaceReflectionsRenderer,
         storage::MatrixStorageCache,
         GeometryCache, QualitySettings, RenderPassStatistics, TextureCache,
     },
     scene::{
-        camera::Camera,
+        camera::{Camera, CameraBackground},
         light::{directional::DirectionalLight, point::PointLight, spot::SpotLight},
         mesh::{
             buffer::{TriangleBuffer, VertexBuffer},
@@ -50,16 +57,19 @@ use crate::{
 use std::{cell::RefCell, rc::Rc};
 
 pub mod ambient;
+pub mod clustered;
 pub mod directional;
 pub mod point;
 pub mod spot;
 
 pub struct DeferredLightRenderer {
     pub ssao_renderer: ScreenSpaceAmbientOcclusionRenderer,
+    ssr_renderer: ScreenSpaceReflectionsRenderer,
     spot_light_shader: SpotLightShader,
     point_light_shader: PointLightShader,
     directional_light_shader: DirectionalLightShader,
     ambient_light_shader: AmbientLightShader,
+    clustered_light_shader: ClusteredLightShader,
     quad: GeometryBuffer,
     sphere: GeometryBuffer,
     skybox: GeometryBuffer,
@@ -69,6 +79,7 @@ pub struct DeferredLightRenderer {
     point_shadow_map_renderer: PointShadowMapRenderer,
     csm_renderer: CsmRenderer,
     light_volume: LightVolumeRenderer,
+    ao_volume_renderer: AoVolumeRenderer,
 }
 
 pub(crate) struct DeferredRendererContext<'a> {
@@ -86,7 +97,14 @@ pub(crate) struct DeferredRendererContext<'a> {
     pub white_dummy: Rc<RefCell<GpuTexture>>,
     pub black_dummy: Rc<RefCell<GpuTexture>>,
     pub volume_dummy: Rc<RefCell<GpuTexture>>,
+    pub environment_dummy: Rc<RefCell<GpuTexture>>,
+    pub light_clusters: &'a LightClusters,
     pub matrix_storage: &'a mut MatrixStorageCache,
+    pub global_properties: &'a GlobalMaterialProperties,
+    pub show_cascades: bool,
+    pub shadow_caster_culler: &'a mut ShadowCasterCuller,
+    pub probe_volume_renderer: &'a ProbeVolumeRenderer,
+    pub scene_handle: Handle<Scene>,
 }
 
 impl DeferredLightRenderer {
@@ -135,11 +153,14 @@ impl DeferredLightRenderer {
                 state,
                 frame_size.0 as usize,
                 frame_size.1 as usize,
+                settings.ssao_half_resolution,
             )?,
+            ssr_renderer: ScreenSpaceReflectionsRenderer::new(state)?,
             spot_light_shader: SpotLightShader::new(state)?,
             point_light_shader: PointLightShader::new(state)?,
             directional_light_shader: DirectionalLightShader::new(state)?,
             ambient_light_shader: AmbientLightShader::new(state)?,
+            clustered_light_shader: ClusteredLightShader::new(state)?,
             quad: GeometryBuffer::from_surface_data(
                 &SurfaceData::make_unit_xy_quad(),
                 GeometryBufferKind::StaticDraw,
@@ -184,9 +205,10 @@ impl DeferredLightRenderer {
                 quality_defaults.point_shadow_map_precision,
             )?,
             light_volume: LightVolumeRenderer::new(state)?,
+            ao_volume_renderer: AoVolumeRenderer::new(state)?,
             csm_renderer: CsmRenderer::new(
                 state,
-                quality_defaults.csm_settings.size,
+                quality_defaults.csm_settings.cascade_sizes,
                 quality_defaults.csm_settings.precision,
             )?,
         })
@@ -216,15 +238,23 @@ impl DeferredLightRenderer {
             )?;
         }
         if settings.csm_settings.precision != self.csm_renderer.precision()
-            || settings.csm_settings.size != self.csm_renderer.size()
+            || settings.csm_settings.cascade_sizes != self.csm_renderer.sizes()
         {
             self.csm_renderer = CsmRenderer::new(
                 state,
-                settings.csm_settings.size,
+                settings.csm_settings.cascade_sizes,
                 settings.csm_settings.precision,
             )?;
         }
-        self.ssao_renderer.set_radius(settings.ssao_radius);
+        if settings.ssao_half_resolution != self.ssao_renderer.is_half_resolution() {
+            let (width, height) = self.ssao_renderer.frame_size();
+            self.ssao_renderer = ScreenSpaceAmbientOcclusionRenderer::new(
+                state,
+                width,
+                height,
+                settings.ssao_half_resolution,
+            )?;
+        }
         Ok(())
     }
 
@@ -237,6 +267,7 @@ impl DeferredLightRenderer {
             state,
             frame_size.0 as usize,
             frame_size.1 as usize,
+            self.ssao_renderer.is_half_resolution(),
         )?;
         Ok(())
     }
@@ -265,9 +296,18 @@ impl DeferredLightRenderer {
             frame_buffer,
             black_dummy,
             volume_dummy,
+            environment_dummy,
+            light_clusters,
             matrix_storage,
+            global_properties,
+            show_cascades,
+            shadow_caster_culler,
+            probe_volume_renderer,
+            scene_handle,
         } = args;
 
+        shadow_caster_culler.begin_frame(state);
+
         let viewport = Rect::new(0, 0, gbuffer.width, gbuffer.height);
         let frustum = Frustum::from_view_projection_matrix(camera.view_projection_matrix())
             .unwrap_or_default();
@@ -296,13 +336,18 @@ impl DeferredLightRenderer {
             pass_stats += self.ssao_renderer.render(
                 state,
                 gbuffer,
+                settings,
                 projection_matrix,
                 camera.view_matrix().basis(),
+                view_projection,
             )?;
         }
 
-        // Render skybox (if any).
-        if let Some(skybox) = camera.skybox_ref() {
+        // Render skybox (if any and if the camera wants it as its background).
+        if let Some(skybox) = camera
+            .skybox_ref()
+            .filter(|_| camera.background() == CameraBackground::Skybox)
+        {
             let size = camera.projection().z_far() / 2.0f32.sqrt();
             let scale = Matrix4::new_scaling(size);
             let wvp = Matrix4::new_translation(&camera.global_position()) * scale;
@@ -388,43 +433,148 @@ impl DeferredLightRenderer {
             },
         )?;
 
+        // Baked occlusion of ambient occlusion volumes (if any).
+        pass_stats += self.ao_volume_renderer.render(
+            state,
+            &self.quad,
+            frame_buffer,
+            viewport,
+            gbuffer,
+            camera,
+            &scene.graph,
+            textures,
+        )?;
+
+        // Indirect light of probe volumes (if any).
+        pass_stats += probe_volume_renderer.render(
+            state,
+            &self.quad,
+            frame_buffer,
+            viewport,
+            gbuffer,
+            if settings.use_ssao {
+                &ao_map
+            } else {
+                &white_dummy
+            },
+            camera,
+            scene_handle,
+            &scene.graph,
+        )?;
+
+        // Lights without shadows are rendered all at once using light clusters.
+        let use_light_clustering =
+            settings.use_light_clustering && light_clusters.clustered_light_count() > 0;
+        if use_light_clustering {
+            let shader = &self.clustered_light_shader;
+
+            light_stats.clustered_lights_rendered += light_clusters.clustered_light_count();
+
+            pass_stats += frame_buffer.draw(
+                &self.quad,
+                state,
+                viewport,
+                &shader.program,
+                &DrawParameters {
+                    cull_face: None,
+                    color_write: Default::default(),
+                    depth_write: false,
+                    stencil_test: None,
+                    depth_test: false,
+                    blend: Some(BlendParameters {
+                        func: BlendFunc::new(BlendFactor::One, BlendFactor::One),
+                        ..Default::default()
+                    }),
+                    stencil_op: Default::default(),
+                },
+                ElementRange::Full,
+                |mut program_binding| {
+                    program_binding
+                        .set_matrix4(&shader.wvp_matrix, &frame_matrix)
+                        .set_matrix4(&shader.inv_view_proj_matrix, &inv_view_projection)
+                        .set_vector3(&shader.camera_position, &camera_global_position)
+                        .set_vector4(&shader.cluster_parameters, &light_clusters.parameters())
+                        .set_vector4(&shader.cluster_depth_plane, &light_clusters.depth_plane())
+                        .set_texture(&shader.depth_sampler, &gbuffer_depth_map)
+                        .set_texture(&shader.color_sampler, &gbuffer_diffuse_map)
+                        .set_texture(&shader.normal_sampler, &gbuffer_normal_map)
+                        .set_texture(&shader.material_sampler, &gbuffer_material_map)
+                        .set_texture(&shader.cluster_grid, light_clusters.grid_texture())
+                        .set_texture(&shader.cluster_lights, light_clusters.lights_texture())
+                        .set_texture(
+                            &shader.cluster_light_indices,
+                            light_clusters.indices_texture(),
+                        );
+                },
+            )?;
+        }
+
         for (light_handle, light) in scene.graph.pair_iter() {
-            if !light.global_visibility() || !light.is_globally_enabled() {
+            if !light.global_visibility()
+                || !light.is_globally_enabled()
+                || (light.render_mask() & camera.render_mask()).0 == 0
+            {
+                continue;
+            }
+
+            if use_light_clustering && light_clusters.is_clustered(light_handle) {
+                if settings.light_scatter_enabled {
+                    pass_stats += self.light_volume.render_volume(
+                        state,
+                        light,
+                        light_handle,
+                        gbuffer,
+                        &self.quad,
+                        camera.view_matrix(),
+                        inv_projection,
+                        view_projection,
+                        viewport,
+                        &scene.graph,
+                        frame_buffer,
+                    )?;
+                }
                 continue;
             }
 
             let distance_to_camera = (light.global_position() - camera.global_position()).norm();
 
-            let (raw_radius, shadows_distance, shadows_enabled, shadows_fade_out_range) =
-                if let Some(spot_light) = light.cast::<SpotLight>() {
-                    (
-                        spot_light.distance(),
-                        settings.spot_shadows_distance,
-                        spot_light.base_light_ref().is_cast_shadows()
-                            && distance_to_camera <= settings.spot_shadows_distance
-                            && settings.spot_shadows_enabled,
-                        settings.spot_shadows_fade_out_range,
-                    )
-                } else if let Some(point_light) = light.cast::<PointLight>() {
-                    (
-                        point_light.radius(),
-                        settings.point_shadows_distance,
-                        point_light.base_light_ref().is_cast_shadows()
-                            && distance_to_camera <= settings.point_shadows_distance
-                            && settings.point_shadows_enabled,
-                        settings.point_shadows_fade_out_range,
-                    )
-                } else if let Some(directional) = light.cast::<DirectionalLight>() {
-                    (
-                        f32::MAX,
-                        0.0,
-                        directional.base_light_ref().is_cast_shadows()
-                            && settings.csm_settings.enabled,
-                        0.0,
-                    )
-                } else {
-                    continue;
-                };
+            let (
+                raw_radius,
+                shadows_distance,
+                shadows_enabled,
+                shadows_fade_out_range,
+                shadow_mask,
+            ) = if let Some(spot_light) = light.cast::<SpotLight>() {
+                (
+                    spot_light.distance(),
+                    settings.spot_shadows_distance,
+                    spot_light.base_light_ref().is_cast_shadows()
+                        && distance_to_camera <= settings.spot_shadows_distance
+                        && settings.spot_shadows_enabled,
+                    settings.spot_shadows_fade_out_range,
+                    spot_light.base_light_ref().shadow_mask(),
+                )
+            } else if let Some(point_light) = light.cast::<PointLight>() {
+                (
+                    point_light.radius(),
+                    settings.point_shadows_distance,
+                    point_light.base_light_ref().is_cast_shadows()
+                        && distance_to_camera <= settings.point_shadows_distance
+                        && settings.point_shadows_enabled,
+                    settings.point_shadows_fade_out_range,
+                    point_light.base_light_ref().shadow_mask(),
+                )
+            } else if let Some(directional) = light.cast::<DirectionalLight>() {
+                (
+                    f32::MAX,
+                    0.0,
+                    directional.base_light_ref().is_cast_shadows() && settings.csm_settings.enabled,
+                    0.0,
+                    directional.base_light_ref().shadow_mask(),
+                )
+            } else {
+                continue;
+            };
 
             let light_position = light.global_position();
             let scl = light.local_transform().scale();
@@ -500,6 +650,12 @@ impl DeferredLightRenderer {
                         black_dummy.clone(),
                         volume_dummy.clone(),
                         matrix_storage,
+                        global_properties,
+                        light_handle,
+                        shadow_mask,
+                        settings
+                            .use_shadow_caster_occlusion_culling
+                            .then_some(&mut *shadow_caster_culler),
                     )?;
 
                     light_stats.spot_shadow_maps_rendered += 1;
@@ -520,6 +676,12 @@ impl DeferredLightRenderer {
                                 black_dummy: black_dummy.clone(),
                                 volume_dummy: volume_dummy.clone(),
                                 matrix_storage,
+                                global_properties,
+                                light_handle,
+                                render_mask: shadow_mask,
+                                occlusion_culler: settings
+                                    .use_shadow_caster_occlusion_culling
+                                    .then_some(&mut *shadow_caster_culler),
                             })?;
 
                     light_stats.point_shadow_maps_rendered += 1;
@@ -538,6 +700,12 @@ impl DeferredLightRenderer {
                         black_dummy: black_dummy.clone(),
                         volume_dummy: volume_dummy.clone(),
                         matrix_storage,
+                        global_properties,
+                        settings: &settings.csm_settings,
+                        light_handle,
+                        occlusion_culler: settings
+                            .use_shadow_caster_occlusion_culling
+                            .then_some(&mut *shadow_caster_culler),
                     })?;
 
                     light_stats.csm_rendered += 1;
@@ -777,7 +945,11 @@ impl DeferredLightRenderer {
                             self.csm_renderer.cascades()[1].view_proj_matrix,
                             self.csm_renderer.cascades()[2].view_proj_matrix,
                         ];
-                        let csm_map_size = self.csm_renderer.size() as f32;
+                        let inv_sizes = [
+                            1.0 / self.csm_renderer.cascades()[0].size as f32,
+                            1.0 / self.csm_renderer.cascades()[1].size as f32,
+                            1.0 / self.csm_renderer.cascades()[2].size as f32,
+                        ];
 
                         program_binding
                             .set_vector3(&shader.light_direction, &emit_direction)
@@ -814,7 +986,8 @@ impl DeferredLightRenderer {
                             .set_f32(&shader.shadow_bias, directional.csm_options.shadow_bias())
                             .set_bool(&shader.shadows_enabled, shadows_enabled)
                             .set_bool(&shader.soft_shadows, settings.csm_settings.pcf)
-                            .set_f32(&shader.shadow_map_inv_size, 1.0 / csm_map_size);
+                            .set_f32_slice(&shader.shadow_map_inv_sizes, &inv_sizes)
+                            .set_bool(&shader.show_cascades, show_cascades);
                     },
                 )?
             } else {
@@ -838,6 +1011,29 @@ impl DeferredLightRenderer {
             }
         }
 
+        // Add reflections on top of the lit frame. Environment map of the camera (or its skybox) is
+        // used for the parts of reflections that could not be found on screen.
+        if settings.ssr_settings.enabled {
+            let environment_map = camera
+                .environment_ref()
+                .or_else(|| camera.skybox_ref().and_then(|skybox| skybox.cubemap_ref()))
+                .and_then(|environment| textures.get(state, environment))
+                .cloned()
+                .unwrap_or(environment_dummy);
+
+            pass_stats += self.ssr_renderer.render(
+                state,
+                gbuffer,
+                frame_buffer,
+                &self.quad,
+                camera,
+                &settings.ssr_settings,
+                environment_map,
+            )?;
+        }
+
+        light_stats.shadow_casters_culled = shadow_caster_culler.culled_count();
+
         Ok((pass_stats, light_stats))
     }
 }
@@ -16,21 +16,36 @@ pub mod framework;
 pub mod bundle;
 pub mod cache;
 pub mod debug_renderer;
+pub mod debug_view;
 pub mod storage;
 pub mod ui_renderer;
 
+mod ao_volume;
+mod background;
 mod bloom;
+mod cluster;
 mod flat_shader;
 mod forward_renderer;
 mod fxaa;
 mod gbuffer;
+mod gpu_particles;
 mod hdr;
 mod light;
 mod light_volume;
+mod ocean;
+mod oit;
+mod portal;
+mod post_effect;
+mod probe_volume;
+mod screen_transition;
 mod shadow;
 mod skybox_shader;
+mod smoke;
 mod ssao;
+mod ssr;
 mod stats;
+mod stencil_mask;
+mod weather;
 
 use crate::renderer::cache::texture::TextureRenderData;
 
@@ -51,19 +66,28 @@ use crate::{
     },
     gui::draw::DrawingContext,
     material::{
-        shader::{SamplerFallback, Shader, ShaderResource, ShaderResourceExtension},
-        Material, PropertyValue,
+        collect_used_shader_permutations,
+        shader::{
+            include::ShaderIncludes, SamplerFallback, Shader, ShaderResource,
+            ShaderResourceExtension,
+        },
+        GlobalMaterialProperties, Material, MaterialError, PropertyValue,
     },
     renderer::{
+        background::BackgroundRenderer,
         bloom::BloomRenderer,
-        bundle::{ObserverInfo, PersistentIdentifier, RenderDataBundleStorage},
+        bundle::{MotionHistory, ObserverInfo, PersistentIdentifier, RenderDataBundleStorage},
         cache::{geometry::GeometryCache, shader::ShaderCache, texture::TextureCache},
+        cluster::LightClusters,
         debug_renderer::DebugRenderer,
+        debug_view::{DebugViewMode, DebugViewRenderContext, DebugViewRenderer},
         flat_shader::FlatShader,
         forward_renderer::{ForwardRenderContext, ForwardRenderer},
         framework::{
             error::FrameworkError,
-            framebuffer::{Attachment, AttachmentKind, DrawParameters, FrameBuffer},
+            framebuffer::{
+                Attachment, AttachmentKind, BlendParameters, DrawParameters, FrameBuffer,
+            },
             geometry_buffer::{
                 DrawCallStatistics, ElementRange, GeometryBuffer, GeometryBufferKind,
             },
@@ -72,17 +96,37 @@ use crate::{
                 Coordinate, GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter,
                 PixelKind, WrapMode,
             },
-            state::{GlKind, PipelineState, PolygonFace, PolygonFillMode},
+            state::{
+                BlendFactor, BlendFunc, FrontFace, GlKind, PipelineState, PolygonFace,
+                PolygonFillMode,
+            },
         },
         fxaa::FxaaRenderer,
         gbuffer::{GBuffer, GBufferRenderContext},
+        gpu_particles::GpuParticleRenderer,
         hdr::HighDynamicRangeRenderer,
         light::{DeferredLightRenderer, DeferredRendererContext},
+        ocean::OceanRenderer,
+        oit::OitRenderer,
+        portal::PortalRenderer,
+        post_effect::PostEffectRenderContext,
+        probe_volume::ProbeVolumeRenderer,
+        screen_transition::ScreenTransitionRenderer,
+        shadow::occlusion::ShadowCasterCuller,
+        smoke::SmokeRenderer,
+        stencil_mask::StencilMasks,
         storage::MatrixStorageCache,
         ui_renderer::{UiRenderContext, UiRenderer},
+        weather::PrecipitationRenderer,
     },
     resource::texture::{Texture, TextureKind, TextureResource},
-    scene::{camera::Camera, mesh::surface::SurfaceData, Scene, SceneContainer},
+    scene::{
+        camera::Camera,
+        light::directional::{FrustumSplitOptions, CSM_NUM_CASCADES},
+        mesh::{surface::SurfaceData, RenderPath},
+        weather::WeatherShaderData,
+        RenderTargetUpdateMode, Scene, SceneContainer,
+    },
 };
 use fxhash::FxHashMap;
 use fyrox_core::algebra::Vector4;
@@ -102,6 +146,7 @@ use strum_macros::{AsRefStr, EnumString, VariantNames};
 #[cfg(not(target_arch = "wasm32"))]
 use winit::window::Window;
 
+pub use screen_transition::ScreenTransition;
 pub use stats::*;
 
 lazy_static! {
@@ -147,14 +192,47 @@ pub enum ShadowMapPrecision {
 
 uuid_provider!(ShadowMapPrecision = "f9b2755b-248e-46ba-bcab-473eac1acdb8");
 
+/// Algorithm, that is used to calculate screen space ambient occlusion.
+#[derive(
+    Copy,
+    Clone,
+    Hash,
+    PartialOrd,
+    PartialEq,
+    Eq,
+    Ord,
+    Debug,
+    Default,
+    Serialize,
+    Deserialize,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum AmbientOcclusionMode {
+    /// Classic hemisphere sampling. It is fast, but the occlusion looks flat on large-scale
+    /// geometry.
+    #[default]
+    Ssao,
+    /// Ground-truth ambient occlusion. It integrates visibility between horizons found along a
+    /// few slices around each pixel, which gives much more physically plausible results. It is
+    /// noisy by itself, so it should be used with temporal accumulation.
+    Gtao,
+}
+
+uuid_provider!(AmbientOcclusionMode = "0b5c9f3e-6a43-4d2f-9e1a-7b3f5a8c2d41");
+
 /// Cascaded-shadow maps settings.
-#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Reflect, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+#[serde(default)]
 pub struct CsmSettings {
     /// Whether cascaded shadow maps enabled or not.
     pub enabled: bool,
 
-    /// Size of texture for each cascade.
-    pub size: usize,
+    /// Size of texture for each cascade. Distant cascades cover larger areas, so they could use
+    /// smaller textures without significant loss of quality.
+    pub cascade_sizes: [usize; CSM_NUM_CASCADES],
 
     /// Bit-wise precision for each cascade, the lower precision the better performance is,
     /// but the more artifacts may occur.
@@ -162,15 +240,78 @@ pub struct CsmSettings {
 
     /// Whether to use Percentage-Closer Filtering or not.
     pub pcf: bool,
+
+    /// Whether to stabilize cascades or not. Stabilized cascades have fixed size and their
+    /// position is snapped to texels of shadow maps, which eliminates shimmering of shadow edges
+    /// when the camera moves or rotates. The price is a bit lower effective resolution of the
+    /// shadow maps.
+    pub stabilize: bool,
+
+    /// Optional split options that will be used for every directional light instead of their own
+    /// split options.
+    pub split_options: Option<FrustumSplitOptions>,
 }
 
 impl Default for CsmSettings {
     fn default() -> Self {
         Self {
             enabled: true,
-            size: 2048,
+            cascade_sizes: [2048; CSM_NUM_CASCADES],
             precision: ShadowMapPrecision::Full,
             pcf: true,
+            stabilize: true,
+            split_options: None,
+        }
+    }
+}
+
+/// Screen-space reflections settings.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+#[serde(default)]
+pub struct SsrSettings {
+    /// Whether screen-space reflections enabled or not. When disabled, surfaces reflect only the
+    /// environment map of the camera (or its skybox).
+    pub enabled: bool,
+
+    /// Max amount of steps of each reflected ray. The more steps, the more precise reflections are,
+    /// but the lower performance is.
+    #[reflect(min_value = 1.0, max_value = 256.0)]
+    pub max_steps: u32,
+
+    /// Max distance (in view space) that a reflected ray could travel.
+    #[reflect(min_value = 0.0)]
+    pub max_distance: f32,
+
+    /// Thickness of surfaces in the depth buffer. A ray hits a surface only if it is behind the
+    /// surface no more than this value. Too small values produce holes in reflections, too large -
+    /// reflections of objects that should not be visible.
+    #[reflect(min_value = 0.0)]
+    pub thickness: f32,
+
+    /// Width (in normalized screen coordinates) of the screen border where reflections fade out to
+    /// the environment map.
+    #[reflect(min_value = 0.0, max_value = 0.5)]
+    pub edge_fade: f32,
+
+    /// Surfaces with roughness above this value do not trace rays, reflections smoothly fade out
+    /// as the roughness gets closer to this value.
+    #[reflect(min_value = 0.0, max_value = 1.0)]
+    pub max_roughness: f32,
+
+    /// Whether to trace rays in half resolution or not.
+    pub half_resolution: bool,
+}
+
+impl Default for SsrSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_steps: 64,
+            max_distance: 20.0,
+            thickness: 0.25,
+            edge_fade: 0.1,
+            max_roughness: 0.6,
+            half_resolution: true,
         }
     }
 }
@@ -220,11 +361,42 @@ pub struct QualitySettings {
     /// Radius of sampling hemisphere used in SSAO, it defines much ambient
     /// occlusion will be in your scene.
     pub ssao_radius: f32,
+    /// Algorithm, that is used to calculate ambient occlusion.
+    #[serde(default)]
+    pub ssao_mode: AmbientOcclusionMode,
+    /// Whether to calculate ambient occlusion in half resolution or not. Half resolution map
+    /// is upscaled using depth-aware (bilateral) filter, so the occlusion does not leak over
+    /// edges of objects.
+    #[serde(default = "default_true")]
+    pub ssao_half_resolution: bool,
+    /// Whether to accumulate ambient occlusion over multiple frames or not. Accumulation
+    /// removes noise of [`AmbientOcclusionMode::Gtao`], it does nothing for other modes.
+    #[serde(default = "default_true")]
+    pub ssao_temporal_accumulation: bool,
+
+    /// Screen-space reflections settings.
+    #[serde(default)]
+    pub ssr_settings: SsrSettings,
+
+    /// Whether to render point and spot lights without shadows all at once using light clusters or
+    /// not. Clusters make hundreds of small lights feasible, while each light rendered separately
+    /// costs a few full-screen passes. Light clusters are always available to the materials with
+    /// forward render path, regardless of this setting.
+    #[serde(default = "default_true")]
+    pub use_light_clustering: bool,
 
     /// Global switch to enable or disable light scattering. Each light can have
     /// its own scatter switch, but this one is able to globally disable scatter.
     pub light_scatter_enabled: bool,
 
+    /// Whether to skip shadow casters, that are hidden from a light by other shadow casters, when
+    /// rendering shadow maps or not. Visibility of the casters is checked using hardware occlusion
+    /// queries and the results become available with a delay of a few frames, so a caster that
+    /// suddenly becomes visible to a light may cast no shadow for a few frames. Significantly
+    /// reduces the amount of triangles in shadow passes of interior scenes.
+    #[serde(default = "default_true")]
+    pub use_shadow_caster_occlusion_culling: bool,
+
     /// Whether to use Fast Approximate AntiAliasing or not.
     pub fxaa: bool,
 
@@ -235,6 +407,10 @@ pub struct QualitySettings {
     pub use_bloom: bool,
 }
 
+fn default_true() -> bool {
+    true
+}
+
 impl Default for QualitySettings {
     fn default() -> Self {
         Self::high()
@@ -259,9 +435,26 @@ impl QualitySettings {
 
             use_ssao: true,
             ssao_radius: 0.5,
+            ssao_mode: AmbientOcclusionMode::Gtao,
+            ssao_half_resolution: false,
+            ssao_temporal_accumulation: true,
+
+            ssr_settings: SsrSettings {
+                enabled: true,
+                max_steps: 128,
+                max_distance: 30.0,
+                thickness: 0.25,
+                edge_fade: 0.1,
+                max_roughness: 0.7,
+                half_resolution: false,
+            },
+
+            use_light_clustering: true,
 
             light_scatter_enabled: true,
 
+            use_shadow_caster_occlusion_culling: true,
+
             point_shadow_map_precision: ShadowMapPrecision::Full,
             spot_shadow_map_precision: ShadowMapPrecision::Full,
 
@@ -292,9 +485,26 @@ impl QualitySettings {
 
             use_ssao: true,
             ssao_radius: 0.5,
+            ssao_mode: AmbientOcclusionMode::Gtao,
+            ssao_half_resolution: true,
+            ssao_temporal_accumulation: true,
+
+            ssr_settings: SsrSettings {
+                enabled: true,
+                max_steps: 64,
+                max_distance: 20.0,
+                thickness: 0.25,
+                edge_fade: 0.1,
+                max_roughness: 0.6,
+                half_resolution: true,
+            },
+
+            use_light_clustering: true,
 
             light_scatter_enabled: true,
 
+            use_shadow_caster_occlusion_culling: true,
+
             point_shadow_map_precision: ShadowMapPrecision::Full,
             spot_shadow_map_precision: ShadowMapPrecision::Full,
 
@@ -306,9 +516,11 @@ impl QualitySettings {
 
             csm_settings: CsmSettings {
                 enabled: true,
-                size: 2048,
+                cascade_sizes: [2048; CSM_NUM_CASCADES],
                 precision: ShadowMapPrecision::Full,
                 pcf: true,
+                stabilize: true,
+                split_options: None,
             },
         }
     }
@@ -330,9 +542,26 @@ impl QualitySettings {
 
             use_ssao: true,
             ssao_radius: 0.5,
+            ssao_mode: AmbientOcclusionMode::Ssao,
+            ssao_half_resolution: true,
+            ssao_temporal_accumulation: false,
+
+            ssr_settings: SsrSettings {
+                enabled: false,
+                max_steps: 32,
+                max_distance: 15.0,
+                thickness: 0.35,
+                edge_fade: 0.1,
+                max_roughness: 0.5,
+                half_resolution: true,
+            },
+
+            use_light_clustering: true,
 
             light_scatter_enabled: false,
 
+            use_shadow_caster_occlusion_culling: true,
+
             point_shadow_map_precision: ShadowMapPrecision::Half,
             spot_shadow_map_precision: ShadowMapPrecision::Half,
 
@@ -344,9 +573,11 @@ impl QualitySettings {
 
             csm_settings: CsmSettings {
                 enabled: true,
-                size: 512,
+                cascade_sizes: [512; CSM_NUM_CASCADES],
                 precision: ShadowMapPrecision::Full,
                 pcf: false,
+                stabilize: true,
+                split_options: None,
             },
         }
     }
@@ -368,9 +599,26 @@ impl QualitySettings {
 
             use_ssao: false,
             ssao_radius: 0.5,
+            ssao_mode: AmbientOcclusionMode::Ssao,
+            ssao_half_resolution: true,
+            ssao_temporal_accumulation: false,
+
+            ssr_settings: SsrSettings {
+                enabled: false,
+                max_steps: 16,
+                max_distance: 10.0,
+                thickness: 0.5,
+                edge_fade: 0.1,
+                max_roughness: 0.4,
+                half_resolution: true,
+            },
+
+            use_light_clustering: true,
 
             light_scatter_enabled: false,
 
+            use_shadow_caster_occlusion_culling: false,
+
             point_shadow_map_precision: ShadowMapPrecision::Half,
             spot_shadow_map_precision: ShadowMapPrecision::Half,
 
@@ -382,9 +630,11 @@ impl QualitySettings {
 
             csm_settings: CsmSettings {
                 enabled: true,
-                size: 512,
+                cascade_sizes: [512; CSM_NUM_CASCADES],
                 precision: ShadowMapPrecision::Half,
                 pcf: false,
+                stabilize: true,
+                split_options: None,
             },
         }
     }
@@ -464,8 +714,23 @@ pub struct AssociatedSceneData {
     /// bleeding effect (glow effect).
     pub bloom_renderer: BloomRenderer,
 
+    /// Accumulation targets of weighted blended order-independent transparency. They share depth
+    /// buffer with the scene frame.
+    pub oit_renderer: OitRenderer,
+
     /// Rendering statistics for a scene.
     pub statistics: SceneStatistics,
+
+    /// World and bone matrices of scene objects from the previous frame, used for per-object motion
+    /// vectors.
+    pub motion_history: MotionHistory,
+
+    /// Results of the occlusion tests of shadow casters of the scene lights.
+    pub(crate) shadow_caster_culler: ShadowCasterCuller,
+
+    /// Amount of frames since the last render of the scene, it is used to schedule rendering of
+    /// the scenes with render targets.
+    pub(crate) frames_since_render: u32,
 }
 
 impl AssociatedSceneData {
@@ -492,8 +757,9 @@ impl AssociatedSceneData {
             GpuTextureKind::Rectangle { width, height },
             // Intermediate scene frame will be rendered in HDR render target.
             PixelKind::RGBA16F,
-            MinificationFilter::Nearest,
-            MagnificationFilter::Nearest,
+            // Linear filtering is needed for bloom downsampling.
+            MinificationFilter::Linear,
+            MagnificationFilter::Linear,
             1,
             None,
         )?;
@@ -548,7 +814,7 @@ impl AssociatedSceneData {
             state,
             Some(Attachment {
                 kind: AttachmentKind::DepthStencil,
-                texture: depth_stencil,
+                texture: depth_stencil.clone(),
             }),
             vec![Attachment {
                 kind: AttachmentKind::Color,
@@ -560,10 +826,14 @@ impl AssociatedSceneData {
             gbuffer: GBuffer::new(state, width, height)?,
             hdr_renderer: HighDynamicRangeRenderer::new(state)?,
             bloom_renderer: BloomRenderer::new(state, width, height)?,
+            oit_renderer: OitRenderer::new(state, width, height, depth_stencil)?,
             hdr_scene_framebuffer,
             ldr_scene_framebuffer,
             ldr_temp_framebuffer,
             statistics: Default::default(),
+            motion_history: Default::default(),
+            shadow_caster_culler: ShadowCasterCuller::new(state)?,
+            frames_since_render: 0,
         })
     }
 
@@ -608,18 +878,7 @@ impl AssociatedSceneData {
 }
 
 pub(crate) fn make_viewport_matrix(viewport: Rect<i32>) -> Matrix4<f32> {
-    Matrix4::new_orthographic(
-        0.0,
-        viewport.w() as f32,
-        viewport.h() as f32,
-        0.0,
-        -1.0,
-        1.0,
-    ) * Matrix4::new_nonuniform_scaling(&Vector3::new(
-        viewport.w() as f32,
-        viewport.h() as f32,
-        0.0,
-    ))
+    framework::fullscreen::fullscreen_matrix(viewport)
 }
 
 /// See module docs.
@@ -658,12 +917,24 @@ pub struct Renderer {
     geometry_cache: GeometryCache,
     forward_renderer: ForwardRenderer,
     fxaa_renderer: FxaaRenderer,
+    screen_transition_renderer: ScreenTransitionRenderer,
+    precipitation_renderer: PrecipitationRenderer,
+    smoke_renderer: SmokeRenderer,
+    gpu_particle_renderer: GpuParticleRenderer,
+    ocean_renderer: OceanRenderer,
+    portal_renderer: PortalRenderer,
+    probe_volume_renderer: ProbeVolumeRenderer,
+    background_renderer: BackgroundRenderer,
+    light_clusters: LightClusters,
+    debug_view_renderer: DebugViewRenderer,
+    debug_view: DebugViewMode,
     texture_event_receiver: Receiver<ResourceEvent>,
     shader_event_receiver: Receiver<ResourceEvent>,
     matrix_storage: MatrixStorageCache,
     // TextureId -> FrameBuffer mapping. This mapping is used for temporal frame buffers
     // like ones used to render UI instances.
     ui_frame_buffers: FxHashMap<u64, FrameBuffer>,
+    global_properties: GlobalMaterialProperties,
     /// Pipeline state.
     pub state: SharedPipelineState,
 }
@@ -734,6 +1005,9 @@ pub struct SceneRenderPassContext<'a, 'b> {
     /// A storage that contains "pre-compiled" groups of render data (batches).
     pub bundle_storage: &'a RenderDataBundleStorage,
 
+    /// A set of global material properties, that should be passed to [`apply_material`].
+    pub global_properties: &'a GlobalMaterialProperties,
+
     /// Current quality settings of the renderer.
     pub quality_settings: &'a QualitySettings,
 
@@ -838,6 +1112,7 @@ fn blit_pixels(
     shader: &FlatShader,
     viewport: Rect<i32>,
     quad: &GeometryBuffer,
+    blend: Option<BlendParameters>,
 ) -> Result<DrawCallStatistics, FrameworkError> {
     framebuffer.draw(
         quad,
@@ -850,7 +1125,7 @@ fn blit_pixels(
             depth_write: true,
             stencil_test: None,
             depth_test: false,
-            blend: None,
+            blend,
             stencil_op: Default::default(),
         },
         ElementRange::Full,
@@ -899,6 +1174,7 @@ impl<const N: usize> Default for LightData<N> {
 #[allow(missing_docs)] // TODO
 pub struct MaterialContext<'a, 'b, 'c> {
     pub material: &'a Material,
+    pub global_properties: &'a GlobalMaterialProperties,
     pub program_binding: &'a mut GpuProgramBinding<'b, 'c>,
     pub texture_cache: &'a mut TextureCache,
     pub matrix_storage: &'a mut MatrixStorageCache,
@@ -909,6 +1185,8 @@ pub struct MaterialContext<'a, 'b, 'c> {
     pub view_projection_matrix: &'a Matrix4<f32>,
     pub wvp_matrix: &'a Matrix4<f32>,
     pub bone_matrices: &'a [Matrix4<f32>],
+    pub prev_world_matrix: &'a Matrix4<f32>,
+    pub prev_bone_matrices: &'a [Matrix4<f32>],
     pub use_skeletal_animation: bool,
     pub use_pom: bool,
     pub light_position: &'a Vector3<f32>,
@@ -916,9 +1194,11 @@ pub struct MaterialContext<'a, 'b, 'c> {
     pub blend_shapes_weights: &'a [f32],
     pub light_data: Option<&'a LightData>,
     pub ambient_light: Color,
+    pub weather: WeatherShaderData,
     // TODO: Add depth pre-pass to remove Option here. Current architecture allows only forward
     // renderer to have access to depth buffer that is available from G-Buffer.
     pub scene_depth: Option<&'a Rc<RefCell<GpuTexture>>>,
+    pub light_clusters: Option<&'a LightClusters>,
 
     pub camera_position: &'a Vector3<f32>,
     pub camera_up_vector: &'a Vector3<f32>,
@@ -963,6 +1243,25 @@ pub fn apply_material(ctx: MaterialContext) {
 
         ctx.program_binding.set_texture(location, storage.texture());
     }
+    if let Some(location) = &built_in_uniforms[BuiltInUniform::PrevWorldMatrix as usize] {
+        ctx.program_binding
+            .set_matrix4(location, ctx.prev_world_matrix);
+    }
+    if let Some(location) = &built_in_uniforms[BuiltInUniform::PrevBoneMatrices as usize] {
+        let active_sampler = ctx.program_binding.active_sampler();
+
+        let storage = ctx
+            .matrix_storage
+            .try_bind_and_upload(
+                ctx.program_binding.state,
+                ctx.persistent_identifier.previous_frame(),
+                ctx.prev_bone_matrices,
+                active_sampler,
+            )
+            .expect("Failed to upload previous bone matrices!");
+
+        ctx.program_binding.set_texture(location, storage.texture());
+    }
     if let Some(location) = &built_in_uniforms[BuiltInUniform::UseSkeletalAnimation as usize] {
         ctx.program_binding
             .set_bool(location, ctx.use_skeletal_animation);
@@ -1028,10 +1327,40 @@ pub fn apply_material(ctx: MaterialContext) {
         }
     }
 
+    if let Some(light_clusters) = ctx.light_clusters {
+        if let Some(location) = &built_in_uniforms[BuiltInUniform::ClusterGrid as usize] {
+            ctx.program_binding
+                .set_texture(location, light_clusters.grid_texture());
+        }
+        if let Some(location) = &built_in_uniforms[BuiltInUniform::ClusterLights as usize] {
+            ctx.program_binding
+                .set_texture(location, light_clusters.lights_texture());
+        }
+        if let Some(location) = &built_in_uniforms[BuiltInUniform::ClusterLightIndices as usize] {
+            ctx.program_binding
+                .set_texture(location, light_clusters.indices_texture());
+        }
+        if let Some(location) = &built_in_uniforms[BuiltInUniform::ClusterParameters as usize] {
+            ctx.program_binding
+                .set_vector4(location, &light_clusters.parameters());
+        }
+        if let Some(location) = &built_in_uniforms[BuiltInUniform::ClusterDepthPlane as usize] {
+            ctx.program_binding
+                .set_vector4(location, &light_clusters.depth_plane());
+        }
+    }
+
     if let Some(location) = &built_in_uniforms[BuiltInUniform::AmbientLight as usize] {
         ctx.program_binding
             .set_srgb_color(location, &ctx.ambient_light);
     }
+    if let Some(location) = &built_in_uniforms[BuiltInUniform::Weather as usize] {
+        ctx.program_binding
+            .set_vector4(location, &ctx.weather.weather);
+    }
+    if let Some(location) = &built_in_uniforms[BuiltInUniform::Wind as usize] {
+        ctx.program_binding.set_vector4(location, &ctx.weather.wind);
+    }
 
     if let Some(location) = &built_in_uniforms[BuiltInUniform::BlendShapesStorage as usize] {
         if let Some(texture) = ctx
@@ -1056,8 +1385,13 @@ pub fn apply_material(ctx: MaterialContext) {
             .set_i32(location, ctx.blend_shapes_weights.len() as i32);
     }
 
-    // Apply material properties.
+    // Apply material properties, global properties override the values of the material.
     for (name, value) in ctx.material.properties() {
+        let value = match ctx.global_properties.get(name) {
+            Some(global) if global.is_same_type(value) => global,
+            _ => value,
+        };
+
         if let Some(uniform) = ctx.program_binding.uniform_location(name) {
             match value {
                 PropertyValue::Float(v) => {
@@ -1274,12 +1608,24 @@ impl Renderer {
             forward_renderer: ForwardRenderer::new(),
             ui_frame_buffers: Default::default(),
             fxaa_renderer: FxaaRenderer::new(&state)?,
+            screen_transition_renderer: ScreenTransitionRenderer::new(&state)?,
+            precipitation_renderer: PrecipitationRenderer::new(&state)?,
+            smoke_renderer: SmokeRenderer::new(&state)?,
+            gpu_particle_renderer: GpuParticleRenderer::new(&state)?,
+            ocean_renderer: OceanRenderer::new(&state)?,
+            portal_renderer: PortalRenderer::new(&state)?,
+            probe_volume_renderer: ProbeVolumeRenderer::new(&state)?,
+            background_renderer: BackgroundRenderer::new(&state)?,
+            light_clusters: LightClusters::new(&state)?,
+            debug_view_renderer: DebugViewRenderer::new(&state)?,
+            debug_view: Default::default(),
             statistics: Statistics::default(),
             shader_event_receiver,
             texture_event_receiver,
             shader_cache,
             scene_render_passes: Default::default(),
             matrix_storage: MatrixStorageCache::new(&state)?,
+            global_properties: Default::default(),
             state,
         })
     }
@@ -1325,6 +1671,37 @@ impl Renderer {
         self.backbuffer_clear_color = color;
     }
 
+    /// Registers a chunk of shader code, that could be included by any shader with
+    /// `#include "name"`. See [`crate::material::shader::include::ShaderIncludes`] docs for more
+    /// info. Chunks should be registered before scenes are rendered, because registering a chunk
+    /// forces every shader to be compiled again.
+    pub fn register_shader_include(&mut self, name: &str, source: &str) {
+        self.shader_cache.register_include(name, source);
+    }
+
+    /// Returns the chunks of shader code, that could be included by shaders.
+    pub fn shader_includes(&self) -> &ShaderIncludes {
+        self.shader_cache.includes()
+    }
+
+    /// Starts a screen transition. The frame that will be rendered next (without user interfaces)
+    /// is captured and shown instead of scenes until [`Self::end_screen_transition`] is called.
+    /// Calling this method while a transition is shown only changes its parameters. See
+    /// [`ScreenTransition`] docs for more info.
+    pub fn begin_screen_transition(&mut self, transition: ScreenTransition) {
+        self.screen_transition_renderer.begin(transition);
+    }
+
+    /// Ends current screen transition, the captured frame will crossfade to the current frame.
+    pub fn end_screen_transition(&mut self) {
+        self.screen_transition_renderer.end();
+    }
+
+    /// Returns `true` if a screen transition is in progress (including the final crossfade).
+    pub fn is_screen_transition_active(&self) -> bool {
+        self.screen_transition_renderer.is_active()
+    }
+
     /// Returns a reference to current pipeline state.
     pub fn pipeline_state(&self) -> &PipelineState {
         &self.state
@@ -1375,6 +1752,93 @@ impl Renderer {
         self.quality_settings
     }
 
+    /// Sets new debug view mode, that defines which data of the renderer will be shown on screen.
+    /// See [`DebugViewMode`] docs for more info.
+    pub fn set_debug_view(&mut self, mode: DebugViewMode) {
+        self.debug_view = mode;
+    }
+
+    /// Returns current debug view mode.
+    pub fn debug_view(&self) -> DebugViewMode {
+        self.debug_view
+    }
+
+    /// Sets a new value of a global material property. Global properties are bound automatically
+    /// to every material shader, that declares a property with the same name and type, so global
+    /// effects (wind, wetness, player position, etc.) do not require to modify every material
+    /// instance each frame. A global property overrides the value of the property of a material,
+    /// properties of other types are left untouched.
+    ///
+    /// # Type checking
+    ///
+    /// The type of a global property cannot be changed once it is set, an attempt to set a value of
+    /// a different type results in an error. Use [`Self::remove_global_property`] first to change
+    /// the type.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use fyrox_impl::{
+    /// #     core::algebra::Vector4, material::PropertyValue, renderer::Renderer,
+    /// # };
+    /// fn update_wind(renderer: &mut Renderer, time: f32) {
+    ///     renderer
+    ///         .set_global_property(
+    ///             "windParams",
+    ///             PropertyValue::Vector4(Vector4::new(1.0, 0.0, 0.5, time)),
+    ///         )
+    ///         .unwrap();
+    /// }
+    /// ```
+    pub fn set_global_property(
+        &mut self,
+        name: &str,
+        value: PropertyValue,
+    ) -> Result<Option<PropertyValue>, MaterialError> {
+        let name = ImmutableString::new(name);
+        if let Some(existing) = self.global_properties.get(&name) {
+            if !existing.is_same_type(&value) {
+                return Err(MaterialError::TypeMismatch {
+                    property_name: name.to_string(),
+                    expected: existing.clone(),
+                    given: value,
+                });
+            }
+        }
+        Ok(self.global_properties.insert(name, value))
+    }
+
+    /// Returns a value of a global material property with the given name (if any).
+    pub fn global_property(&self, name: &str) -> Option<&PropertyValue> {
+        self.global_properties.get(&ImmutableString::new(name))
+    }
+
+    /// Removes a global material property with the given name and returns its value (if any).
+    pub fn remove_global_property(&mut self, name: &str) -> Option<PropertyValue> {
+        self.global_properties.remove(&ImmutableString::new(name))
+    }
+
+    /// Returns a reference to the set of global material properties.
+    pub fn global_properties(&self) -> &GlobalMaterialProperties {
+        &self.global_properties
+    }
+
+    /// Compiles every shader permutation used by the materials of the given scene, so they won't
+    /// be compiled on first use during the rendering, which may cause noticeable hitches. It is a
+    /// good idea to call this method right after a scene was loaded. Returns the amount of
+    /// permutations, that are ready to use. See [shader module docs](crate::material::shader) for
+    /// more info about permutations.
+    pub fn precompile_shader_permutations(&mut self, scene: &Scene) -> usize {
+        collect_used_shader_permutations(scene)
+            .into_iter()
+            .filter(|(shader, permutation)| {
+                self.shader_cache
+                    .get_permutation(&self.state, shader, *permutation)
+                    .is_some()
+            })
+            .count()
+    }
+
     /// Removes all cached GPU data, forces renderer to re-upload data to GPU.
     /// Do not call this method until you absolutely need! It may cause **significant**
     /// performance lag!
@@ -1488,9 +1952,9 @@ impl Renderer {
         while let Ok(event) = self.shader_event_receiver.try_recv() {
             if let ResourceEvent::Loaded(resource) | ResourceEvent::Reloaded(resource) = event {
                 if let Some(shader) = resource.try_cast::<Shader>() {
-                    // Remove and immediately "touch" the shader cache to force upload shader.
-                    self.shader_cache.remove(&shader);
-                    let _ = self.shader_cache.get(&self.state, &shader);
+                    // Compile the new data of the shader right away and drop the GPU programs of
+                    // its previous data, instead of keeping them alive until they time out.
+                    self.shader_cache.reload(&self.state, &shader);
                 }
             }
         }
@@ -1516,13 +1980,9 @@ impl Renderer {
         scene: &Scene,
         dt: f32,
     ) -> Result<&AssociatedSceneData, FrameworkError> {
-        let graph = &scene.graph;
-
         let backbuffer_width = self.frame_size.0 as f32;
         let backbuffer_height = self.frame_size.1 as f32;
 
-        let window_viewport = Rect::new(0, 0, self.frame_size.0 as i32, self.frame_size.1 as i32);
-
         let frame_size = scene
             .rendering_options
             .render_target
@@ -1542,41 +2002,79 @@ impl Renderer {
             // Clamp to [1.0; infinity] range.
             .sup(&Vector2::new(1.0, 1.0));
 
-        let state = &mut self.state;
+        let width = frame_size.x as usize;
+        let height = frame_size.y as usize;
 
-        let scene_associated_data = self
-            .scene_data_map
-            .entry(scene_handle)
-            .and_modify(|data| {
-                if data.gbuffer.width != frame_size.x as i32
-                    || data.gbuffer.height != frame_size.y as i32
-                {
-                    let width = frame_size.x as usize;
-                    let height = frame_size.y as usize;
-
-                    Log::info(format!(
-                        "Associated scene rendering data was re-created for scene {}, because render frame size was changed. Old is {}x{}, new {}x{}!",
-                        scene_handle,
-                        data.gbuffer.width,data.gbuffer.height,width,height
-                    ));
-
-                    *data = AssociatedSceneData::new(state, width, height).unwrap();
-                }
-            })
-            .or_insert_with(|| {
-                let width = frame_size.x as usize;
-                let height = frame_size.y as usize;
+        // The data is taken out of the map while rendering, because portals need the renderer to
+        // render scene multiple times.
+        let mut scene_associated_data = match self.scene_data_map.remove(&scene_handle) {
+            Some(data)
+                if data.gbuffer.width == width as i32 && data.gbuffer.height == height as i32 =>
+            {
+                data
+            }
+            Some(data) => {
+                Log::info(format!(
+                    "Associated scene rendering data was re-created for scene {}, because render frame size was changed. Old is {}x{}, new {}x{}!",
+                    scene_handle,
+                    data.gbuffer.width,data.gbuffer.height,width,height
+                ));
 
+                AssociatedSceneData::new(&self.state, width, height)?
+            }
+            None => {
                 Log::info(format!(
                     "A new associated scene rendering data was created for scene {}!",
                     scene_handle
                 ));
 
-                AssociatedSceneData::new(state, width, height).unwrap()
-            });
+                AssociatedSceneData::new(&self.state, width, height)?
+            }
+        };
+
+        // Scenes with render targets could have their own (lower) quality settings.
+        let quality_settings = self.quality_settings;
+        if scene.rendering_options.render_target.is_some() {
+            self.quality_settings = scene
+                .rendering_options
+                .render_target_quality
+                .apply(&quality_settings);
+        }
+
+        scene_associated_data.frames_since_render = 0;
+        let result = self.render_scene_frame(
+            scene_handle,
+            scene,
+            frame_size,
+            dt,
+            &mut scene_associated_data,
+        );
+
+        self.quality_settings = quality_settings;
+
+        let scene_associated_data = self
+            .scene_data_map
+            .entry(scene_handle)
+            .or_insert(scene_associated_data);
+
+        result.map(|_| &*scene_associated_data)
+    }
+
+    fn render_scene_frame(
+        &mut self,
+        scene_handle: Handle<Scene>,
+        scene: &Scene,
+        frame_size: Vector2<f32>,
+        dt: f32,
+        scene_associated_data: &mut AssociatedSceneData,
+    ) -> Result<(), FrameworkError> {
+        let graph = &scene.graph;
+
+        let window_viewport = Rect::new(0, 0, self.frame_size.0 as i32, self.frame_size.1 as i32);
 
-        let pipeline_stats = state.pipeline_statistics();
+        let pipeline_stats = self.state.pipeline_statistics();
         scene_associated_data.statistics = Default::default();
+        scene_associated_data.motion_history.begin_frame();
 
         // If we specified a texture to draw to, we have to register it in texture cache
         // so it can be used in later on as texture. This is useful in case if you need
@@ -1594,155 +2092,61 @@ impl Renderer {
             );
         }
 
-        for camera in graph
+        // Smoke and GPU particles are simulated once per frame, not per camera.
+        scene_associated_data.statistics +=
+            self.smoke_renderer
+                .update(&self.state, &self.quad, scene_handle, graph, dt)?;
+        scene_associated_data.statistics +=
+            self.gpu_particle_renderer
+                .update(&self.state, &self.quad, scene_handle, graph)?;
+        self.probe_volume_renderer
+            .update(&self.state, scene_handle, scene)?;
+
+        let mut cameras = graph
             .linear_iter()
             .filter(|&node| node.is_globally_enabled())
             .filter_map(|node| node.cast::<Camera>().filter(|c| c.is_enabled()))
-        {
-            let viewport = camera.viewport_pixels(frame_size);
-
-            let bundle_storage = RenderDataBundleStorage::from_graph(
-                graph,
-                ObserverInfo {
-                    observer_position: camera.global_position(),
-                    z_near: camera.projection().z_near(),
-                    z_far: camera.projection().z_far(),
-                    view_matrix: camera.view_matrix(),
-                    projection_matrix: camera.projection_matrix(),
-                },
-                GBUFFER_PASS_NAME.clone(),
-            );
-
-            state.set_polygon_fill_mode(
-                PolygonFace::FrontAndBack,
-                scene.rendering_options.polygon_rasterization_mode,
-            );
-
-            scene_associated_data.statistics +=
-                scene_associated_data.gbuffer.fill(GBufferRenderContext {
-                    state,
-                    camera,
-                    geom_cache: &mut self.geometry_cache,
-                    bundle_storage: &bundle_storage,
-                    texture_cache: &mut self.texture_cache,
-                    shader_cache: &mut self.shader_cache,
-                    environment_dummy: self.environment_dummy.clone(),
-                    use_parallax_mapping: self.quality_settings.use_parallax_mapping,
-                    normal_dummy: self.normal_dummy.clone(),
-                    white_dummy: self.white_dummy.clone(),
-                    black_dummy: self.black_dummy.clone(),
-                    volume_dummy: self.volume_dummy.clone(),
-                    graph,
-                    matrix_storage: &mut self.matrix_storage,
-                })?;
-
-            state.set_polygon_fill_mode(PolygonFace::FrontAndBack, PolygonFillMode::Fill);
-
-            scene_associated_data.copy_depth_stencil_to_scene_framebuffer(state);
-
-            scene_associated_data.hdr_scene_framebuffer.clear(
-                state,
-                viewport,
-                Some(
-                    scene
-                        .rendering_options
-                        .clear_color
-                        .unwrap_or(self.backbuffer_clear_color),
-                ),
-                None, // Keep depth, we've just copied valid data in it.
-                Some(0),
-            );
-
-            let (pass_stats, light_stats) =
-                self.deferred_light_renderer
-                    .render(DeferredRendererContext {
-                        state,
-                        scene,
-                        camera,
-                        gbuffer: &mut scene_associated_data.gbuffer,
-                        white_dummy: self.white_dummy.clone(),
-                        ambient_color: scene.rendering_options.ambient_lighting_color,
-                        settings: &self.quality_settings,
-                        textures: &mut self.texture_cache,
-                        geometry_cache: &mut self.geometry_cache,
-                        frame_buffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                        shader_cache: &mut self.shader_cache,
-                        normal_dummy: self.normal_dummy.clone(),
-                        black_dummy: self.black_dummy.clone(),
-                        volume_dummy: self.volume_dummy.clone(),
-                        matrix_storage: &mut self.matrix_storage,
-                    })?;
-
-            scene_associated_data.statistics += light_stats;
-            scene_associated_data.statistics += pass_stats;
+            .collect::<Vec<_>>();
+        // The sort is stable, so the cameras with the same order keep their graph order.
+        cameras.sort_by_key(|camera| camera.render_order());
 
-            let depth = scene_associated_data.gbuffer.depth();
+        for (camera_index, camera) in cameras.into_iter().enumerate() {
+            let viewport = camera.viewport_pixels(frame_size);
 
-            scene_associated_data.statistics +=
-                self.forward_renderer.render(ForwardRenderContext {
-                    state,
-                    graph,
-                    camera,
-                    geom_cache: &mut self.geometry_cache,
-                    texture_cache: &mut self.texture_cache,
-                    shader_cache: &mut self.shader_cache,
-                    bundle_storage: &bundle_storage,
-                    framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                    viewport,
-                    quality_settings: &self.quality_settings,
-                    white_dummy: self.white_dummy.clone(),
-                    normal_dummy: self.normal_dummy.clone(),
-                    black_dummy: self.black_dummy.clone(),
-                    volume_dummy: self.volume_dummy.clone(),
-                    scene_depth: depth,
-                    matrix_storage: &mut self.matrix_storage,
-                    ambient_light: scene.rendering_options.ambient_lighting_color,
-                })?;
+            let bundle_storage = self.render_hdr_frame(
+                scene_handle,
+                scene,
+                camera,
+                frame_size,
+                scene_associated_data,
+                0,
+                // There's no valid depth before the first camera.
+                camera_index == 0 || camera.clear_depth(),
+            )?;
 
-            for render_pass in self.scene_render_passes.iter() {
-                scene_associated_data.statistics +=
-                    render_pass
-                        .borrow_mut()
-                        .on_hdr_render(SceneRenderPassContext {
-                            pipeline_state: state,
-                            texture_cache: &mut self.texture_cache,
-                            geometry_cache: &mut self.geometry_cache,
-                            shader_cache: &mut self.shader_cache,
-                            quality_settings: &self.quality_settings,
-                            bundle_storage: &bundle_storage,
-                            viewport,
-                            scene,
-                            camera,
-                            scene_handle,
-                            white_dummy: self.white_dummy.clone(),
-                            normal_dummy: self.normal_dummy.clone(),
-                            metallic_dummy: self.metallic_dummy.clone(),
-                            environment_dummy: self.environment_dummy.clone(),
-                            black_dummy: self.black_dummy.clone(),
-                            volume_dummy: self.volume_dummy.clone(),
-                            depth_texture: scene_associated_data.gbuffer.depth(),
-                            normal_texture: scene_associated_data.gbuffer.normal_texture(),
-                            ambient_texture: scene_associated_data.gbuffer.ambient_texture(),
-                            framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                            ui_renderer: &mut self.ui_renderer,
-                            matrix_storage: &mut self.matrix_storage,
-                        })?;
-            }
+            let state = &mut self.state;
 
             let quad = &self.quad;
 
             // Prepare glow map.
-            scene_associated_data.statistics += scene_associated_data.bloom_renderer.render(
-                state,
-                quad,
-                scene_associated_data.hdr_scene_frame_texture(),
-            )?;
+            let bloom_settings = camera.bloom_settings();
+            if self.quality_settings.use_bloom && bloom_settings.enabled {
+                scene_associated_data.statistics += scene_associated_data.bloom_renderer.render(
+                    state,
+                    quad,
+                    scene_associated_data.hdr_scene_frame_texture(),
+                    &bloom_settings,
+                )?;
+            } else {
+                scene_associated_data.bloom_renderer.clear(state);
+            }
 
             // Convert high dynamic range frame to low dynamic range (sRGB) with tone mapping and gamma correction.
             scene_associated_data.statistics += scene_associated_data.hdr_renderer.render(
                 state,
                 scene_associated_data.hdr_scene_frame_texture(),
                 scene_associated_data.bloom_renderer.result(),
+                bloom_settings.intensity,
                 &mut scene_associated_data.ldr_scene_framebuffer,
                 viewport,
                 quad,
@@ -1771,10 +2175,47 @@ impl Renderer {
                     &self.flat_shader,
                     viewport,
                     quad,
+                    None,
                 )?;
             }
 
+            // Apply custom post effects of the camera.
+            if !camera.post_effects().is_empty() {
+                let scene_depth = scene_associated_data.gbuffer.depth();
+                scene_associated_data.statistics +=
+                    post_effect::render_post_effects(PostEffectRenderContext {
+                        state,
+                        camera,
+                        viewport,
+                        quad: &self.quad,
+                        flat_shader: &self.flat_shader,
+                        frame_buffer: &mut scene_associated_data.ldr_scene_framebuffer,
+                        temp_frame_buffer: &mut scene_associated_data.ldr_temp_framebuffer,
+                        scene_depth,
+                        weather: scene.weather.shader_data(),
+                        shader_cache: &mut self.shader_cache,
+                        texture_cache: &mut self.texture_cache,
+                        matrix_storage: &mut self.matrix_storage,
+                        global_properties: &self.global_properties,
+                        normal_dummy: &self.normal_dummy,
+                        white_dummy: &self.white_dummy,
+                        black_dummy: &self.black_dummy,
+                        volume_dummy: &self.volume_dummy,
+                    })?;
+            }
+
             // Render debug geometry in the LDR frame buffer.
+            scene_associated_data.statistics +=
+                self.debug_view_renderer.render(DebugViewRenderContext {
+                    state,
+                    mode: self.debug_view,
+                    viewport,
+                    camera,
+                    gbuffer: &scene_associated_data.gbuffer,
+                    bundle_storage: &bundle_storage,
+                    geom_cache: &mut self.geometry_cache,
+                    frame_buffer: &mut scene_associated_data.ldr_scene_framebuffer,
+                })?;
             scene_associated_data.statistics += self.debug_renderer.render(
                 state,
                 viewport,
@@ -1794,6 +2235,7 @@ impl Renderer {
                             shader_cache: &mut self.shader_cache,
                             quality_settings: &self.quality_settings,
                             bundle_storage: &bundle_storage,
+                            global_properties: &self.global_properties,
                             viewport,
                             scene,
                             camera,
@@ -1814,6 +2256,8 @@ impl Renderer {
             }
         }
 
+        let state = &mut self.state;
+
         // Optionally render everything into back buffer.
         if scene.rendering_options.render_target.is_none() {
             let quad = &self.quad;
@@ -1824,13 +2268,376 @@ impl Renderer {
                 &self.flat_shader,
                 window_viewport,
                 quad,
+                scene.composition.alpha_blend.then(|| BlendParameters {
+                    func: BlendFunc::new(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
+                    ..Default::default()
+                }),
             )?;
         }
 
         self.statistics += scene_associated_data.statistics;
         scene_associated_data.statistics.pipeline = state.pipeline_statistics() - pipeline_stats;
 
-        Ok(scene_associated_data)
+        self.portal_renderer.retain_portals(scene_handle, graph);
+
+        Ok(())
+    }
+
+    /// Renders the scene from the given camera into the high dynamic range frame buffer of the given
+    /// scene data. Views of the portals, that are visible from the camera, are rendered first (recursively)
+    /// and then composited into the frame.
+    fn render_hdr_frame(
+        &mut self,
+        scene_handle: Handle<Scene>,
+        scene: &Scene,
+        camera: &Camera,
+        frame_size: Vector2<f32>,
+        scene_associated_data: &mut AssociatedSceneData,
+        portal_level: u32,
+        clear_depth: bool,
+    ) -> Result<RenderDataBundleStorage, FrameworkError> {
+        let graph = &scene.graph;
+        let viewport = camera.viewport_pixels(frame_size);
+
+        let portals = PortalRenderer::visible_portals(camera, graph);
+        for portal in portals.iter() {
+            let Some(portal_camera) = portal
+                .camera
+                .as_ref()
+                .filter(|_| portal_level < portal.recursion_depth)
+            else {
+                continue;
+            };
+
+            let key = (scene_handle, portal.handle, portal_level);
+            let mut view = self
+                .portal_renderer
+                .take_view(&self.state, key, frame_size)?;
+            let result = self.render_hdr_frame(
+                scene_handle,
+                scene,
+                portal_camera,
+                frame_size,
+                &mut view,
+                portal_level + 1,
+                true,
+            );
+            scene_associated_data.statistics += view.statistics.geometry;
+            scene_associated_data.statistics += view.statistics.lighting;
+            self.portal_renderer.put_view(key, view);
+            result?;
+        }
+
+        // Mirrored cameras flip the winding of every polygon.
+        let front_face = if camera.view_matrix().determinant() < 0.0 {
+            FrontFace::Clockwise
+        } else {
+            FrontFace::CounterClockwise
+        };
+
+        let state = &mut self.state;
+
+        let mut bundle_storage = RenderDataBundleStorage::from_graph(
+            graph,
+            ObserverInfo {
+                observer_position: camera.global_position(),
+                z_near: camera.projection().z_near(),
+                z_far: camera.projection().z_far(),
+                view_matrix: camera.view_matrix(),
+                projection_matrix: camera.projection_matrix(),
+                render_mask: camera.render_mask(),
+            },
+            GBUFFER_PASS_NAME.clone(),
+        );
+        scene_associated_data
+            .motion_history
+            .apply(&mut bundle_storage);
+
+        let stencil_masks = StencilMasks::from_graph(graph);
+
+        state.set_polygon_fill_mode(
+            PolygonFace::FrontAndBack,
+            scene.rendering_options.polygon_rasterization_mode,
+        );
+
+        state.set_front_face(front_face);
+        scene_associated_data.statistics +=
+            scene_associated_data.gbuffer.fill(GBufferRenderContext {
+                state,
+                camera,
+                geom_cache: &mut self.geometry_cache,
+                bundle_storage: &bundle_storage,
+                texture_cache: &mut self.texture_cache,
+                shader_cache: &mut self.shader_cache,
+                environment_dummy: self.environment_dummy.clone(),
+                use_parallax_mapping: self.quality_settings.use_parallax_mapping,
+                normal_dummy: self.normal_dummy.clone(),
+                white_dummy: self.white_dummy.clone(),
+                black_dummy: self.black_dummy.clone(),
+                volume_dummy: self.volume_dummy.clone(),
+                graph,
+                matrix_storage: &mut self.matrix_storage,
+                global_properties: &self.global_properties,
+                weather: scene.weather.shader_data(),
+                flat_shader: &self.flat_shader,
+                stencil_masks: &stencil_masks,
+                clear_depth,
+            })?;
+
+        state.set_front_face(FrontFace::CounterClockwise);
+        state.set_polygon_fill_mode(PolygonFace::FrontAndBack, PolygonFillMode::Fill);
+
+        if self.debug_view == DebugViewMode::LightingOnly {
+            scene_associated_data
+                .gbuffer
+                .override_diffuse_with_white(state);
+        }
+
+        scene_associated_data.copy_depth_stencil_to_scene_framebuffer(state);
+
+        // Depth is kept intact, we've just copied valid data in it.
+        scene_associated_data.statistics += self.background_renderer.render(
+            state,
+            viewport,
+            &mut scene_associated_data.hdr_scene_framebuffer,
+            &self.quad,
+            camera.background(),
+            scene
+                .rendering_options
+                .clear_color
+                .unwrap_or(self.backbuffer_clear_color),
+        )?;
+
+        self.light_clusters
+            .update(state, camera, graph, &self.quality_settings, frame_size)?;
+
+        let (pass_stats, light_stats) =
+            self.deferred_light_renderer
+                .render(DeferredRendererContext {
+                    state,
+                    scene,
+                    camera,
+                    gbuffer: &mut scene_associated_data.gbuffer,
+                    white_dummy: self.white_dummy.clone(),
+                    ambient_color: scene.rendering_options.ambient_lighting_color,
+                    settings: &self.quality_settings,
+                    textures: &mut self.texture_cache,
+                    geometry_cache: &mut self.geometry_cache,
+                    frame_buffer: &mut scene_associated_data.hdr_scene_framebuffer,
+                    shader_cache: &mut self.shader_cache,
+                    normal_dummy: self.normal_dummy.clone(),
+                    black_dummy: self.black_dummy.clone(),
+                    volume_dummy: self.volume_dummy.clone(),
+                    environment_dummy: self.environment_dummy.clone(),
+                    light_clusters: &self.light_clusters,
+                    matrix_storage: &mut self.matrix_storage,
+                    global_properties: &self.global_properties,
+                    show_cascades: self.debug_view == DebugViewMode::ShadowCascades,
+                    shadow_caster_culler: &mut scene_associated_data.shadow_caster_culler,
+                    probe_volume_renderer: &self.probe_volume_renderer,
+                    scene_handle,
+                })?;
+
+        scene_associated_data.statistics += light_stats;
+        scene_associated_data.statistics += pass_stats;
+
+        let depth = scene_associated_data.gbuffer.depth();
+
+        scene_associated_data.statistics += self.portal_renderer.render(
+            state,
+            viewport,
+            &mut scene_associated_data.hdr_scene_framebuffer,
+            &self.quad,
+            camera,
+            graph,
+            scene_handle,
+            &portals,
+            portal_level,
+            self.black_dummy.clone(),
+        )?;
+
+        state.set_front_face(front_face);
+        scene_associated_data.statistics += self.ocean_renderer.render(
+            state,
+            viewport,
+            &mut scene_associated_data.hdr_scene_framebuffer,
+            depth.clone(),
+            camera,
+            graph,
+        )?;
+
+        // The stencil buffer is cleared by the lighting, so the masks must be written again.
+        scene_associated_data.statistics += stencil_masks.write(
+            state,
+            &mut scene_associated_data.hdr_scene_framebuffer,
+            viewport,
+            graph,
+            &camera.view_projection_matrix(),
+            &mut self.geometry_cache,
+            &self.flat_shader,
+        )?;
+
+        scene_associated_data.statistics += self.forward_renderer.render(ForwardRenderContext {
+            state,
+            graph,
+            camera,
+            geom_cache: &mut self.geometry_cache,
+            texture_cache: &mut self.texture_cache,
+            shader_cache: &mut self.shader_cache,
+            bundle_storage: &bundle_storage,
+            framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
+            viewport,
+            quality_settings: &self.quality_settings,
+            white_dummy: self.white_dummy.clone(),
+            normal_dummy: self.normal_dummy.clone(),
+            black_dummy: self.black_dummy.clone(),
+            volume_dummy: self.volume_dummy.clone(),
+            scene_depth: depth.clone(),
+            light_clusters: &self.light_clusters,
+            matrix_storage: &mut self.matrix_storage,
+            global_properties: &self.global_properties,
+            ambient_light: scene.rendering_options.ambient_lighting_color,
+            weather: scene.weather.shader_data(),
+            stencil_masks: &stencil_masks,
+        })?;
+
+        if bundle_storage
+            .bundles
+            .iter()
+            .any(|b| b.render_path == RenderPath::OrderIndependent)
+        {
+            scene_associated_data.oit_renderer.clear(state, viewport);
+            scene_associated_data.statistics +=
+                self.forward_renderer
+                    .render_order_independent(ForwardRenderContext {
+                        state,
+                        graph,
+                        camera,
+                        geom_cache: &mut self.geometry_cache,
+                        texture_cache: &mut self.texture_cache,
+                        shader_cache: &mut self.shader_cache,
+                        bundle_storage: &bundle_storage,
+                        framebuffer: scene_associated_data.oit_renderer.framebuffer_mut(),
+                        viewport,
+                        quality_settings: &self.quality_settings,
+                        white_dummy: self.white_dummy.clone(),
+                        normal_dummy: self.normal_dummy.clone(),
+                        black_dummy: self.black_dummy.clone(),
+                        volume_dummy: self.volume_dummy.clone(),
+                        scene_depth: depth.clone(),
+                        light_clusters: &self.light_clusters,
+                        matrix_storage: &mut self.matrix_storage,
+                        global_properties: &self.global_properties,
+                        ambient_light: scene.rendering_options.ambient_lighting_color,
+                        weather: scene.weather.shader_data(),
+                        stencil_masks: &stencil_masks,
+                    })?;
+            scene_associated_data.statistics += scene_associated_data.oit_renderer.composite(
+                state,
+                &self.quad,
+                viewport,
+                &mut scene_associated_data.hdr_scene_framebuffer,
+            )?;
+        }
+
+        stencil_masks.clear(
+            state,
+            &mut scene_associated_data.hdr_scene_framebuffer,
+            viewport,
+        );
+
+        state.set_front_face(FrontFace::CounterClockwise);
+
+        scene_associated_data.statistics += self.precipitation_renderer.render(
+            state,
+            viewport,
+            &mut scene_associated_data.hdr_scene_framebuffer,
+            depth.clone(),
+            camera,
+            &scene.weather,
+        )?;
+
+        scene_associated_data.statistics += self.gpu_particle_renderer.render(
+            state,
+            viewport,
+            &mut scene_associated_data.hdr_scene_framebuffer,
+            depth.clone(),
+            camera,
+            scene_handle,
+            graph,
+            &mut self.texture_cache,
+            self.white_dummy.clone(),
+        )?;
+
+        scene_associated_data.statistics += self.smoke_renderer.render(
+            state,
+            viewport,
+            &mut scene_associated_data.hdr_scene_framebuffer,
+            depth,
+            camera,
+            scene_handle,
+            graph,
+        )?;
+
+        for render_pass in self.scene_render_passes.iter() {
+            scene_associated_data.statistics +=
+                render_pass
+                    .borrow_mut()
+                    .on_hdr_render(SceneRenderPassContext {
+                        pipeline_state: state,
+                        texture_cache: &mut self.texture_cache,
+                        geometry_cache: &mut self.geometry_cache,
+                        shader_cache: &mut self.shader_cache,
+                        quality_settings: &self.quality_settings,
+                        bundle_storage: &bundle_storage,
+                        global_properties: &self.global_properties,
+                        viewport,
+                        scene,
+                        camera,
+                        scene_handle,
+                        white_dummy: self.white_dummy.clone(),
+                        normal_dummy: self.normal_dummy.clone(),
+                        metallic_dummy: self.metallic_dummy.clone(),
+                        environment_dummy: self.environment_dummy.clone(),
+                        black_dummy: self.black_dummy.clone(),
+                        volume_dummy: self.volume_dummy.clone(),
+                        depth_texture: scene_associated_data.gbuffer.depth(),
+                        normal_texture: scene_associated_data.gbuffer.normal_texture(),
+                        ambient_texture: scene_associated_data.gbuffer.ambient_texture(),
+                        framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
+                        ui_renderer: &mut self.ui_renderer,
+                        matrix_storage: &mut self.matrix_storage,
+                    })?;
+        }
+
+        Ok(bundle_storage)
+    }
+
+    /// Checks whether the scene must be rendered in the current frame or not, according to the
+    /// update mode of its render target.
+    fn is_scene_render_scheduled(&mut self, scene_handle: Handle<Scene>, scene: &Scene) -> bool {
+        let options = &scene.rendering_options;
+        let render_requested = scene.take_render_request();
+
+        if options.render_target.is_none() {
+            return true;
+        }
+
+        // The scene was never rendered, its render target must be filled at least once.
+        let Some(data) = self.scene_data_map.get_mut(&scene_handle) else {
+            return true;
+        };
+
+        data.frames_since_render = data.frames_since_render.saturating_add(1);
+
+        render_requested
+            || match options.render_target_update_mode {
+                RenderTargetUpdateMode::EveryFrame => true,
+                RenderTargetUpdateMode::Interval { frames } => {
+                    data.frames_since_render >= frames.max(1)
+                }
+                RenderTargetUpdateMode::OnDemand => false,
+            }
     }
 
     fn render_frame<'a>(
@@ -1849,6 +2656,14 @@ impl Renderer {
         // Make sure to drop associated data for destroyed scenes.
         self.scene_data_map
             .retain(|h, _| scenes.is_valid_handle(*h));
+        self.smoke_renderer
+            .retain_scenes(|h| scenes.is_valid_handle(h));
+        self.gpu_particle_renderer
+            .retain_scenes(|h| scenes.is_valid_handle(h));
+        self.portal_renderer
+            .retain_scenes(|h| scenes.is_valid_handle(h));
+        self.probe_volume_renderer
+            .retain_scenes(|h| scenes.is_valid_handle(h));
 
         // We have to invalidate resource bindings cache because some textures or programs,
         // or other GL resources can be destroyed and then on their "names" some new resource
@@ -1870,13 +2685,27 @@ impl Renderer {
         let backbuffer_width = self.frame_size.0 as f32;
         let backbuffer_height = self.frame_size.1 as f32;
 
-        for (scene_handle, scene) in scenes.pair_iter().filter(|(_, s)| *s.enabled) {
-            self.render_scene(scene_handle, scene, dt)?;
+        for scene_handle in scenes.render_order() {
+            let scene = &scenes[scene_handle];
+            if !*scene.enabled {
+                continue;
+            }
+            if self.is_scene_render_scheduled(scene_handle, scene) {
+                self.render_scene(scene_handle, scene, dt)?;
+            }
         }
 
         self.pipeline_state()
             .set_polygon_fill_mode(PolygonFace::FrontAndBack, PolygonFillMode::Fill);
 
+        // Screen transition is drawn on top of scenes, but behind UI so it could show loading screens.
+        self.statistics += self.screen_transition_renderer.render(
+            &self.state,
+            window_viewport,
+            &mut self.backbuffer,
+            dt,
+        )?;
+
         // Render UI on top of everything without gamma correction.
         for drawing_context in drawing_contexts {
             self.statistics += self.ui_renderer.render(UiRenderContext {
@@ -0,0 +1,267 @@
+//! Renders ocean surfaces (see [`crate::scene::ocean::Ocean`]) using a projected grid. The grid is a
+//! regular grid in screen space, its vertices are projected onto the plane of the ocean in the vertex
+//! shader and displaced by the waves, so the detail of the surface is uniform on screen at any distance.
+
+use crate::{
+    core::{
+        algebra::{Vector2, Vector3, Vector4},
+        math::{Rect, TriangleDefinition},
+        scope_profile,
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{BlendParameters, DrawParameters, FrameBuffer},
+            geometry_buffer::{ElementRange, GeometryBuffer, GeometryBufferKind},
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::GpuTexture,
+            state::{BlendFactor, BlendFunc, PipelineState},
+        },
+        RenderPassStatistics,
+    },
+    scene::{
+        camera::Camera,
+        graph::Graph,
+        light::directional::DirectionalLight,
+        mesh::{
+            buffer::{TriangleBuffer, VertexBuffer},
+            surface::SurfaceData,
+            vertex::StaticVertex,
+        },
+        ocean::{Ocean, MAX_OCEAN_WAVES},
+    },
+};
+use std::{cell::RefCell, rc::Rc};
+
+struct OceanShader {
+    program: GpuProgram,
+    view_projection: UniformLocation,
+    inv_view_projection: UniformLocation,
+    ocean_level: UniformLocation,
+    max_distance: UniformLocation,
+    time: UniformLocation,
+    wave_count: UniformLocation,
+    wave_directions: UniformLocation,
+    wave_amplitudes: UniformLocation,
+    scene_depth: UniformLocation,
+    camera_position: UniformLocation,
+    deep_color: UniformLocation,
+    shallow_color: UniformLocation,
+    sky_color: UniformLocation,
+    foam_color: UniformLocation,
+    transparency_depth: UniformLocation,
+    sun_direction: UniformLocation,
+    sun_color: UniformLocation,
+}
+
+impl OceanShader {
+    fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/ocean_fs.glsl");
+        let vertex_source = include_str!("shaders/ocean_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "OceanShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            view_projection: program
+                .uniform_location(state, &ImmutableString::new("viewProjection"))?,
+            inv_view_projection: program
+                .uniform_location(state, &ImmutableString::new("invViewProjection"))?,
+            ocean_level: program.uniform_location(state, &ImmutableString::new("oceanLevel"))?,
+            max_distance: program.uniform_location(state, &ImmutableString::new("maxDistance"))?,
+            time: program.uniform_location(state, &ImmutableString::new("time"))?,
+            wave_count: program.uniform_location(state, &ImmutableString::new("waveCount"))?,
+            wave_directions: program
+                .uniform_location(state, &ImmutableString::new("waveDirections"))?,
+            wave_amplitudes: program
+                .uniform_location(state, &ImmutableString::new("waveAmplitudes"))?,
+            scene_depth: program.uniform_location(state, &ImmutableString::new("sceneDepth"))?,
+            camera_position: program
+                .uniform_location(state, &ImmutableString::new("cameraPosition"))?,
+            deep_color: program.uniform_location(state, &ImmutableString::new("deepColor"))?,
+            shallow_color: program
+                .uniform_location(state, &ImmutableString::new("shallowColor"))?,
+            sky_color: program.uniform_location(state, &ImmutableString::new("skyColor"))?,
+            foam_color: program.uniform_location(state, &ImmutableString::new("foamColor"))?,
+            transparency_depth: program
+                .uniform_location(state, &ImmutableString::new("transparencyDepth"))?,
+            sun_direction: program
+                .uniform_location(state, &ImmutableString::new("sunDirection"))?,
+            sun_color: program.uniform_location(state, &ImmutableString::new("sunColor"))?,
+            program,
+        })
+    }
+}
+
+// Creates a grid with the given amount of cells along each axis in `[0; 1]` range.
+fn make_grid(resolution: usize) -> SurfaceData {
+    let row = resolution + 1;
+
+    let mut vertices = Vec::with_capacity(row * row);
+    for y in 0..row {
+        for x in 0..row {
+            let position = Vector2::new(x as f32, y as f32) / resolution as f32;
+            vertices.push(StaticVertex {
+                position: Vector3::new(position.x, position.y, 0.0),
+                tex_coord: position,
+                normal: Vector3::z(),
+                tangent: Vector4::default(),
+            });
+        }
+    }
+
+    let mut triangles = Vec::with_capacity(resolution * resolution * 2);
+    for y in 0..resolution {
+        for x in 0..resolution {
+            let i0 = (y * row + x) as u32;
+            let i1 = i0 + 1;
+            let i2 = i0 + row as u32;
+            let i3 = i2 + 1;
+            triangles.push(TriangleDefinition([i0, i1, i3]));
+            triangles.push(TriangleDefinition([i0, i3, i2]));
+        }
+    }
+
+    SurfaceData::new(
+        VertexBuffer::new(vertices.len(), vertices).unwrap(),
+        TriangleBuffer::new(triangles),
+    )
+}
+
+struct Grid {
+    resolution: usize,
+    geometry: GeometryBuffer,
+}
+
+pub struct OceanRenderer {
+    shader: OceanShader,
+    grid: Option<Grid>,
+}
+
+impl OceanRenderer {
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: OceanShader::new(state)?,
+            grid: None,
+        })
+    }
+
+    pub(crate) fn render(
+        &mut self,
+        state: &PipelineState,
+        viewport: Rect<i32>,
+        frame_buffer: &mut FrameBuffer,
+        scene_depth: Rc<RefCell<GpuTexture>>,
+        camera: &Camera,
+        graph: &Graph,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!();
+
+        let mut stats = RenderPassStatistics::default();
+
+        let view_projection = camera.view_projection_matrix();
+        let inv_view_projection = view_projection.try_inverse().unwrap_or_default();
+        let camera_position = camera.global_position();
+
+        // The sun is the first directional light of the scene.
+        let (sun_direction, sun_color) = graph
+            .linear_iter()
+            .filter(|node| node.global_visibility() && node.is_globally_enabled())
+            .find_map(|node| node.cast::<DirectionalLight>())
+            .map(|light| {
+                let base = light.base_light_ref();
+                let color = base.color().srgb_to_linear_f32().xyz() * base.intensity();
+                (
+                    light
+                        .up_vector()
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_else(Vector3::y),
+                    Vector4::new(color.x, color.y, color.z, 1.0),
+                )
+            })
+            .unwrap_or((Vector3::y(), Vector4::default()));
+
+        for node in graph.linear_iter() {
+            let Some(ocean) = node.cast::<Ocean>() else {
+                continue;
+            };
+            if !ocean.global_visibility() || !ocean.is_globally_enabled() {
+                continue;
+            }
+
+            let resolution = ocean.grid_resolution().clamp(16, 512) as usize;
+            if self.grid.as_ref().map(|grid| grid.resolution) != Some(resolution) {
+                self.grid = Some(Grid {
+                    resolution,
+                    geometry: GeometryBuffer::from_surface_data(
+                        &make_grid(resolution),
+                        GeometryBufferKind::StaticDraw,
+                        state,
+                    )?,
+                });
+            }
+            let Some(grid) = self.grid.as_ref() else {
+                continue;
+            };
+
+            let mut wave_directions = [Vector4::default(); MAX_OCEAN_WAVES];
+            let mut wave_amplitudes = [Vector4::default(); MAX_OCEAN_WAVES];
+            let waves = ocean.wave_parameters();
+            for (i, wave) in waves.iter().enumerate() {
+                wave_directions[i] = Vector4::new(
+                    wave.direction.x,
+                    wave.direction.y,
+                    wave.wave_number,
+                    wave.angular_frequency,
+                );
+                wave_amplitudes[i] =
+                    Vector4::new(wave.amplitude, wave.horizontal_amplitude, wave.phase, 0.0);
+            }
+
+            let shader = &self.shader;
+            stats += frame_buffer.draw(
+                &grid.geometry,
+                state,
+                viewport,
+                &shader.program,
+                &DrawParameters {
+                    // The surface could be seen from both sides.
+                    cull_face: None,
+                    color_write: Default::default(),
+                    // Scene depth is sampled for the transparency, so it must stay read-only.
+                    depth_write: false,
+                    stencil_test: None,
+                    depth_test: true,
+                    blend: Some(BlendParameters {
+                        func: BlendFunc::new(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
+                        ..Default::default()
+                    }),
+                    stencil_op: Default::default(),
+                },
+                ElementRange::Full,
+                |mut program_binding| {
+                    program_binding
+                        .set_matrix4(&shader.view_projection, &view_projection)
+                        .set_matrix4(&shader.inv_view_projection, &inv_view_projection)
+                        .set_f32(&shader.ocean_level, ocean.level())
+                        .set_f32(&shader.max_distance, ocean.max_distance())
+                        .set_f32(&shader.time, ocean.time())
+                        .set_i32(&shader.wave_count, waves.len() as i32)
+                        .set_vector4_slice(&shader.wave_directions, &wave_directions)
+                        .set_vector4_slice(&shader.wave_amplitudes, &wave_amplitudes)
+                        .set_texture(&shader.scene_depth, &scene_depth)
+                        .set_vector3(&shader.camera_position, &camera_position)
+                        .set_linear_color(&shader.deep_color, &ocean.deep_color())
+                        .set_linear_color(&shader.shallow_color, &ocean.shallow_color())
+                        .set_linear_color(&shader.sky_color, &ocean.sky_color())
+                        .set_linear_color(&shader.foam_color, &ocean.foam_color())
+                        .set_f32(&shader.transparency_depth, ocean.transparency_depth())
+                        .set_vector3(&shader.sun_direction, &sun_direction)
+                        .set_vector4(&shader.sun_color, &sun_color);
+                },
+            )?;
+        }
+
+        Ok(stats)
+    }
+}
@@ -0,0 +1,172 @@
+//! Weighted blended order-independent transparency (OIT). Meshes with
+//! [`crate::scene::mesh::RenderPath::OrderIndependent`] render path are rendered into two targets
+//! in any order:
+//!
+//! - accumulation target - contains sum of weighted premultiplied colors in rgb and revealage (product
+//! of `1 - alpha` of all fragments) in alpha.
+//! - weight target - contains sum of weights of all fragments.
+//!
+//! Then the targets are composited onto the frame by a single full-screen pass. The weights depend on
+//! depth of the fragments, so closer surfaces dominate the result. OpenGL 3.3 does not support separate
+//! blending per attachment, that's why revealage is packed into the alpha channel of the accumulation
+//! target and accumulated using separate alpha blending.
+//!
+//! The targets share depth buffer with the scene, so opaque geometry correctly occludes transparent
+//! surfaces.
+
+use crate::{
+    core::{color::Color, math::Rect, scope_profile, sstorage::ImmutableString},
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{
+                Attachment, AttachmentKind, BlendParameters, DrawParameters, FrameBuffer,
+            },
+            geometry_buffer::{ElementRange, GeometryBuffer},
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::{
+                GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter, PixelKind,
+            },
+            state::{BlendFactor, BlendFunc, PipelineState},
+        },
+        make_viewport_matrix, RenderPassStatistics,
+    },
+};
+use std::{cell::RefCell, rc::Rc};
+
+struct CompositeShader {
+    program: GpuProgram,
+    world_view_projection_matrix: UniformLocation,
+    accumulation_texture: UniformLocation,
+    weight_texture: UniformLocation,
+}
+
+impl CompositeShader {
+    fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/oit_composite_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "OitCompositeShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            world_view_projection_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            accumulation_texture: program
+                .uniform_location(state, &ImmutableString::new("accumulationTexture"))?,
+            weight_texture: program
+                .uniform_location(state, &ImmutableString::new("weightTexture"))?,
+            program,
+        })
+    }
+}
+
+pub struct OitRenderer {
+    framebuffer: FrameBuffer,
+    composite_shader: CompositeShader,
+}
+
+impl OitRenderer {
+    pub fn new(
+        state: &PipelineState,
+        width: usize,
+        height: usize,
+        depth_stencil: Rc<RefCell<GpuTexture>>,
+    ) -> Result<Self, FrameworkError> {
+        let make_target = |pixel_kind| {
+            GpuTexture::new(
+                state,
+                GpuTextureKind::Rectangle { width, height },
+                pixel_kind,
+                MinificationFilter::Nearest,
+                MagnificationFilter::Nearest,
+                1,
+                None,
+            )
+            .map(|texture| Attachment {
+                kind: AttachmentKind::Color,
+                texture: Rc::new(RefCell::new(texture)),
+            })
+        };
+
+        Ok(Self {
+            framebuffer: FrameBuffer::new(
+                state,
+                Some(Attachment {
+                    kind: AttachmentKind::DepthStencil,
+                    texture: depth_stencil,
+                }),
+                vec![
+                    make_target(PixelKind::RGBA16F)?,
+                    make_target(PixelKind::R16F)?,
+                ],
+            )?,
+            composite_shader: CompositeShader::new(state)?,
+        })
+    }
+
+    /// Returns a frame buffer, that should be used to render transparent surfaces.
+    pub fn framebuffer_mut(&mut self) -> &mut FrameBuffer {
+        &mut self.framebuffer
+    }
+
+    /// Prepares the targets for a new frame. Accumulated colors and weights are set to zero, and
+    /// revealage is set to one (fully revealed).
+    pub(crate) fn clear(&mut self, state: &PipelineState, viewport: Rect<i32>) {
+        // Weight target has only red channel, so it will be cleared with zero.
+        self.framebuffer.clear(
+            state,
+            viewport,
+            Some(Color::from_rgba(0, 0, 0, 255)),
+            None,
+            None,
+        );
+    }
+
+    /// Blends accumulated transparent surfaces onto the given frame buffer.
+    pub(crate) fn composite(
+        &mut self,
+        state: &PipelineState,
+        quad: &GeometryBuffer,
+        viewport: Rect<i32>,
+        frame_buffer: &mut FrameBuffer,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!();
+
+        let mut stats = RenderPassStatistics::default();
+
+        let shader = &self.composite_shader;
+        let accumulation = self.framebuffer.color_attachments()[0].texture.clone();
+        let weight = self.framebuffer.color_attachments()[1].texture.clone();
+
+        stats += frame_buffer.draw(
+            quad,
+            state,
+            viewport,
+            &shader.program,
+            &DrawParameters {
+                cull_face: None,
+                color_write: Default::default(),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: false,
+                blend: Some(BlendParameters {
+                    func: BlendFunc::new(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
+                    ..Default::default()
+                }),
+                stencil_op: Default::default(),
+            },
+            ElementRange::Full,
+            |mut program_binding| {
+                program_binding
+                    .set_matrix4(
+                        &shader.world_view_projection_matrix,
+                        &make_viewport_matrix(viewport),
+                    )
+                    .set_texture(&shader.accumulation_texture, &accumulation)
+                    .set_texture(&shader.weight_texture, &weight);
+            },
+        )?;
+
+        Ok(stats)
+    }
+}
@@ -0,0 +1,292 @@
+//! Renders portals (see [`crate::scene::portal::Portal`]). Every visible portal gets a virtual camera,
+//! the scene is rendered from it into a separate set of frame buffers ("view"), the view is then
+//! composited into the frame only where the portal is visible. The visible part of the portal is marked
+//! in the stencil buffer, so anything in front of the portal correctly occludes it.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2, Vector3},
+        math::Rect,
+        pool::Handle,
+        scope_profile,
+        sstorage::ImmutableString,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{DrawParameters, FrameBuffer},
+            geometry_buffer::{ElementRange, GeometryBuffer, GeometryBufferKind},
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::GpuTexture,
+            state::{ColorMask, CompareFunc, PipelineState, StencilAction, StencilFunc, StencilOp},
+        },
+        make_viewport_matrix, AssociatedSceneData, RenderPassStatistics,
+    },
+    scene::{
+        camera::Camera,
+        graph::Graph,
+        mesh::surface::SurfaceData,
+        node::Node,
+        portal::{oblique_projection, Portal},
+        Scene,
+    },
+};
+use fxhash::FxHashMap;
+use std::{cell::RefCell, rc::Rc};
+
+struct PortalShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    view_texture: UniformLocation,
+    use_view: UniformLocation,
+    fallback_color: UniformLocation,
+}
+
+impl PortalShader {
+    fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/portal_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "PortalShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            view_texture: program.uniform_location(state, &ImmutableString::new("viewTexture"))?,
+            use_view: program.uniform_location(state, &ImmutableString::new("useView"))?,
+            fallback_color: program
+                .uniform_location(state, &ImmutableString::new("fallbackColor"))?,
+            program,
+        })
+    }
+}
+
+/// A portal, that is visible from a camera.
+pub(crate) struct VisiblePortal {
+    /// Handle of the portal node.
+    pub handle: Handle<Node>,
+    /// Virtual camera of the portal, `None` if the portal has no valid destination.
+    pub camera: Option<Camera>,
+    /// Recursion depth of the portal.
+    pub recursion_depth: u32,
+}
+
+/// Identifies a view of a portal: the scene, the portal node and the recursion level.
+type ViewKey = (Handle<Scene>, Handle<Node>, u32);
+
+pub struct PortalRenderer {
+    shader: PortalShader,
+    portal_quad: GeometryBuffer,
+    views: FxHashMap<ViewKey, AssociatedSceneData>,
+}
+
+impl PortalRenderer {
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: PortalShader::new(state)?,
+            portal_quad: GeometryBuffer::from_surface_data(
+                &SurfaceData::make_quad(&Matrix4::identity()),
+                GeometryBufferKind::StaticDraw,
+                state,
+            )?,
+            views: Default::default(),
+        })
+    }
+
+    /// Collects all portals that could be seen by the given camera and creates virtual cameras for them.
+    pub(crate) fn visible_portals(camera: &Camera, graph: &Graph) -> Vec<VisiblePortal> {
+        let frustum = camera.frustum();
+        let camera_position = camera.global_position();
+
+        graph
+            .pair_iter()
+            .filter_map(|(handle, node)| {
+                let portal = node.cast::<Portal>()?;
+
+                if !portal.global_visibility()
+                    || !portal.is_globally_enabled()
+                    || !portal.is_in_front(camera_position)
+                    || !frustum.is_intersects_aabb(&portal.world_bounding_box())
+                {
+                    return None;
+                }
+
+                Some(VisiblePortal {
+                    handle,
+                    camera: Self::make_virtual_camera(camera, portal, graph),
+                    recursion_depth: portal.recursion_depth(),
+                })
+            })
+            .collect()
+    }
+
+    fn make_virtual_camera(camera: &Camera, portal: &Portal, graph: &Graph) -> Option<Camera> {
+        let inv_portal_transform = portal.portal_transform(graph)?.try_inverse()?;
+        let view_matrix = camera.view_matrix() * inv_portal_transform;
+
+        // Align near clipping plane with the destination, so nothing behind it could be seen.
+        let view_space_clip_plane =
+            view_matrix.try_inverse()?.transpose() * portal.clip_plane(graph)?;
+        let projection_matrix =
+            oblique_projection(camera.projection_matrix(), view_space_clip_plane);
+
+        Some(camera.make_virtual(view_matrix, projection_matrix))
+    }
+
+    /// Takes a view of the portal out of the renderer, the view must be put back using
+    /// [`Self::put_view`] before rendering the portal. The view is (re)created if its size does not
+    /// match the given frame size.
+    pub(crate) fn take_view(
+        &mut self,
+        state: &PipelineState,
+        key: ViewKey,
+        frame_size: Vector2<f32>,
+    ) -> Result<AssociatedSceneData, FrameworkError> {
+        let width = frame_size.x as usize;
+        let height = frame_size.y as usize;
+
+        let mut view = match self.views.remove(&key) {
+            Some(view)
+                if view.gbuffer.width == width as i32 && view.gbuffer.height == height as i32 =>
+            {
+                view
+            }
+            _ => AssociatedSceneData::new(state, width, height)?,
+        };
+
+        view.statistics = Default::default();
+        view.motion_history.begin_frame();
+
+        Ok(view)
+    }
+
+    pub(crate) fn put_view(&mut self, key: ViewKey, view: AssociatedSceneData) {
+        self.views.insert(key, view);
+    }
+
+    /// Removes views of the scenes, that do not satisfy the given predicate.
+    pub(crate) fn retain_scenes(&mut self, mut predicate: impl FnMut(Handle<Scene>) -> bool) {
+        self.views.retain(|(scene, _, _), _| predicate(*scene));
+    }
+
+    /// Removes views of the portals, that were deleted from the given scene.
+    pub(crate) fn retain_portals(&mut self, scene_handle: Handle<Scene>, graph: &Graph) {
+        self.views.retain(|(scene, portal, _), _| {
+            *scene != scene_handle
+                || graph
+                    .try_get(*portal)
+                    .is_some_and(|node| node.cast::<Portal>().is_some())
+        });
+    }
+
+    /// Composites views of the given portals into the frame buffer. Portals without a view on the
+    /// given recursion level are filled with their fallback color.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn render(
+        &self,
+        state: &PipelineState,
+        viewport: Rect<i32>,
+        frame_buffer: &mut FrameBuffer,
+        quad: &GeometryBuffer,
+        camera: &Camera,
+        graph: &Graph,
+        scene_handle: Handle<Scene>,
+        portals: &[VisiblePortal],
+        level: u32,
+        black_dummy: Rc<RefCell<GpuTexture>>,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!();
+
+        let mut stats = RenderPassStatistics::default();
+
+        let view_projection = camera.view_projection_matrix();
+        let frame_matrix = make_viewport_matrix(viewport);
+
+        for visible_portal in portals {
+            let Some(portal) = graph
+                .try_get(visible_portal.handle)
+                .and_then(|node| node.cast::<Portal>())
+            else {
+                continue;
+            };
+
+            let view_texture = self
+                .views
+                .get(&(scene_handle, visible_portal.handle, level))
+                .filter(|_| visible_portal.camera.is_some() && level < portal.recursion_depth())
+                .map(|view| view.hdr_scene_frame_texture());
+
+            frame_buffer.clear(state, viewport, None, None, Some(0));
+
+            // Mark visible pixels of the portal in the stencil buffer. Depth of the portal is written
+            // as well, so the portal occludes everything behind it like any other surface.
+            let size = portal.size();
+            let world = portal.global_transform()
+                * Matrix4::new_nonuniform_scaling(&Vector3::new(size.x, size.y, 1.0));
+            let shader = &self.shader;
+            stats += frame_buffer.draw(
+                &self.portal_quad,
+                state,
+                viewport,
+                &shader.program,
+                &DrawParameters {
+                    cull_face: None,
+                    color_write: ColorMask::all(false),
+                    depth_write: true,
+                    stencil_test: Some(StencilFunc {
+                        func: CompareFunc::Always,
+                        ref_value: 1,
+                        ..Default::default()
+                    }),
+                    depth_test: true,
+                    blend: None,
+                    stencil_op: StencilOp {
+                        zpass: StencilAction::Replace,
+                        ..Default::default()
+                    },
+                },
+                ElementRange::Full,
+                |mut program_binding| {
+                    program_binding
+                        .set_matrix4(&shader.wvp_matrix, &(view_projection * world))
+                        .set_bool(&shader.use_view, false);
+                },
+            )?;
+
+            // Fill the marked pixels with the view.
+            stats += frame_buffer.draw(
+                quad,
+                state,
+                viewport,
+                &shader.program,
+                &DrawParameters {
+                    cull_face: None,
+                    color_write: Default::default(),
+                    depth_write: false,
+                    stencil_test: Some(StencilFunc {
+                        func: CompareFunc::Equal,
+                        ref_value: 1,
+                        ..Default::default()
+                    }),
+                    depth_test: false,
+                    blend: None,
+                    stencil_op: Default::default(),
+                },
+                ElementRange::Full,
+                |mut program_binding| {
+                    program_binding
+                        .set_matrix4(&shader.wvp_matrix, &frame_matrix)
+                        .set_texture(
+                            &shader.view_texture,
+                            view_texture.as_ref().unwrap_or(&black_dummy),
+                        )
+                        .set_bool(&shader.use_view, view_texture.is_some())
+                        .set_linear_color(&shader.fallback_color, &portal.fallback_color());
+                },
+            )?;
+        }
+
+        Ok(stats)
+    }
+}
@@ -0,0 +1,172 @@
+//! Renders user-defined post effects of cameras. See [`crate::scene::camera::post_effect::PostEffect`]
+//! docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2},
+        color::Color,
+        math::{Matrix4Ext, Rect},
+        sstorage::ImmutableString,
+    },
+    material::GlobalMaterialProperties,
+    renderer::{
+        apply_material, blit_pixels,
+        bundle::PersistentIdentifier,
+        cache::{shader::ShaderCache, texture::TextureCache},
+        flat_shader::FlatShader,
+        framework::{
+            error::FrameworkError, framebuffer::FrameBuffer, geometry_buffer::ElementRange,
+            geometry_buffer::GeometryBuffer, gpu_texture::GpuTexture, state::PipelineState,
+        },
+        make_viewport_matrix,
+        storage::MatrixStorageCache,
+        MaterialContext, RenderPassStatistics,
+    },
+    scene::{camera::Camera, weather::WeatherShaderData},
+};
+use lazy_static::lazy_static;
+use std::{cell::RefCell, rc::Rc};
+
+lazy_static! {
+    static ref POST_EFFECT_PASS_NAME: ImmutableString = ImmutableString::new("PostEffect");
+    static ref INPUT_COLOR: ImmutableString = ImmutableString::new("fyrox_inputColor");
+    static ref INPUT_SIZE: ImmutableString = ImmutableString::new("fyrox_inputSize");
+}
+
+pub(crate) struct PostEffectRenderContext<'a> {
+    pub state: &'a PipelineState,
+    pub camera: &'a Camera,
+    pub viewport: Rect<i32>,
+    pub quad: &'a GeometryBuffer,
+    pub flat_shader: &'a FlatShader,
+    /// Frame buffer with the final frame of the camera, it is the input of the first effect and
+    /// the output of the last one.
+    pub frame_buffer: &'a mut FrameBuffer,
+    /// Intermediate frame buffer of the same size as the frame buffer.
+    pub temp_frame_buffer: &'a mut FrameBuffer,
+    pub scene_depth: Rc<RefCell<GpuTexture>>,
+    pub weather: WeatherShaderData,
+    pub shader_cache: &'a mut ShaderCache,
+    pub texture_cache: &'a mut TextureCache,
+    pub matrix_storage: &'a mut MatrixStorageCache,
+    pub global_properties: &'a GlobalMaterialProperties,
+    pub normal_dummy: &'a Rc<RefCell<GpuTexture>>,
+    pub white_dummy: &'a Rc<RefCell<GpuTexture>>,
+    pub black_dummy: &'a Rc<RefCell<GpuTexture>>,
+    pub volume_dummy: &'a Rc<RefCell<GpuTexture>>,
+}
+
+/// Applies every enabled post effect of the camera one after another. Each effect reads the frame
+/// buffer, writes the result to the temporary frame buffer, which is then copied back.
+pub(crate) fn render_post_effects(
+    ctx: PostEffectRenderContext,
+) -> Result<RenderPassStatistics, FrameworkError> {
+    let PostEffectRenderContext {
+        state,
+        camera,
+        viewport,
+        quad,
+        flat_shader,
+        frame_buffer,
+        temp_frame_buffer,
+        scene_depth,
+        weather,
+        shader_cache,
+        texture_cache,
+        matrix_storage,
+        global_properties,
+        normal_dummy,
+        white_dummy,
+        black_dummy,
+        volume_dummy,
+    } = ctx;
+
+    let mut statistics = RenderPassStatistics::default();
+
+    let frame_matrix = make_viewport_matrix(viewport);
+    let input_size = Vector2::new(viewport.w() as f32, viewport.h() as f32);
+    let view_projection = camera.view_projection_matrix();
+    let (camera_up, camera_side) = camera
+        .inv_view_matrix()
+        .map(|inv_view| (inv_view.up(), inv_view.side()))
+        .unwrap_or_default();
+
+    for effect in camera.post_effects().iter().filter(|e| e.enabled) {
+        let mut material_state = effect.material.state();
+        let Some(material) = material_state.data() else {
+            continue;
+        };
+
+        let Some(render_pass) = shader_cache
+            .get_for_material(state, material)
+            .and_then(|shader_set| shader_set.render_passes.get(&POST_EFFECT_PASS_NAME))
+        else {
+            continue;
+        };
+
+        let input_color = frame_buffer.color_attachments()[0].texture.clone();
+
+        statistics += temp_frame_buffer.draw(
+            quad,
+            state,
+            viewport,
+            &render_pass.program,
+            &render_pass.draw_params,
+            ElementRange::Full,
+            |mut program_binding| {
+                apply_material(MaterialContext {
+                    material,
+                    global_properties,
+                    program_binding: &mut program_binding,
+                    texture_cache,
+                    matrix_storage,
+                    persistent_identifier: PersistentIdentifier(0),
+                    world_matrix: &Matrix4::identity(),
+                    view_projection_matrix: &view_projection,
+                    wvp_matrix: &frame_matrix,
+                    bone_matrices: &[],
+                    prev_world_matrix: &Matrix4::identity(),
+                    prev_bone_matrices: &[],
+                    use_skeletal_animation: false,
+                    use_pom: false,
+                    light_position: &Default::default(),
+                    blend_shapes_storage: None,
+                    blend_shapes_weights: &[],
+                    light_data: None,
+                    ambient_light: Color::WHITE,
+                    weather,
+                    scene_depth: Some(&scene_depth),
+                    light_clusters: None,
+                    camera_position: &camera.global_position(),
+                    camera_up_vector: &camera_up,
+                    camera_side_vector: &camera_side,
+                    z_near: camera.projection().z_near(),
+                    z_far: camera.projection().z_far(),
+                    normal_dummy,
+                    white_dummy,
+                    black_dummy,
+                    volume_dummy,
+                });
+
+                if let Some(location) = program_binding.uniform_location(&INPUT_COLOR) {
+                    program_binding.set_texture(&location, &input_color);
+                }
+                if let Some(location) = program_binding.uniform_location(&INPUT_SIZE) {
+                    program_binding.set_vector2(&location, &input_size);
+                }
+            },
+        )?;
+
+        statistics += blit_pixels(
+            state,
+            frame_buffer,
+            temp_frame_buffer.color_attachments()[0].texture.clone(),
+            flat_shader,
+            viewport,
+            quad,
+            None,
+        )?;
+    }
+
+    Ok(statistics)
+}
@@ -0,0 +1,674 @@
+//! Updates and renders probe volumes (see [`crate::scene::probe_volume::ProbeVolume`]). Probes are
+//! updated on CPU by tracing rays against a snapshot of the scene, the result is uploaded to a volume
+//! texture, that is sampled in the deferred lighting pass.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, Quaternion, UnitQuaternion, Vector2, Vector3},
+        math::Rect,
+        pool::Handle,
+        rng::{split_mix64, Pcg32},
+        scope_profile,
+        sstorage::ImmutableString,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{BlendParameters, FrameBuffer},
+            fullscreen::{FullscreenPass, FULLSCREEN_VERTEX_SHADER},
+            geometry_buffer::GeometryBuffer,
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::PixelKind,
+            gpu_texture::{GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter},
+            state::{BlendFactor, BlendFunc, PipelineState},
+        },
+        gbuffer::GBuffer,
+        RenderPassStatistics,
+    },
+    scene::{camera::Camera, graph::Graph, node::Node, probe_volume::ProbeVolume, Scene},
+    utils::path_tracer::TracingScene,
+};
+use fxhash::FxHashMap;
+use rayon::prelude::*;
+use std::{cell::RefCell, rc::Rc};
+
+/// Amount of texture slices per layer of probes: six directions of irradiance and three slices of
+/// distances. Keep in sync with the shader.
+const SLICES_PER_PROBE: usize = 9;
+
+/// Directions of the faces of the ambient cube of a probe.
+const FACE_DIRECTIONS: [Vector3<f32>; 6] = [
+    Vector3::new(1.0, 0.0, 0.0),
+    Vector3::new(-1.0, 0.0, 0.0),
+    Vector3::new(0.0, 1.0, 0.0),
+    Vector3::new(0.0, -1.0, 0.0),
+    Vector3::new(0.0, 0.0, 1.0),
+    Vector3::new(0.0, 0.0, -1.0),
+];
+
+/// An offset along the normal of a hit point, that is used to sample the probes at the hit point.
+const SURFACE_OFFSET: f32 = 0.01;
+
+/// Returns the weights of the faces of an ambient cube for the given unit direction, the weights
+/// always sum up to one.
+fn ambient_cube_weights(direction: &Vector3<f32>) -> [f32; 6] {
+    let sq = direction.component_mul(direction);
+    [
+        if direction.x > 0.0 { sq.x } else { 0.0 },
+        if direction.x < 0.0 { sq.x } else { 0.0 },
+        if direction.y > 0.0 { sq.y } else { 0.0 },
+        if direction.y < 0.0 { sq.y } else { 0.0 },
+        if direction.z > 0.0 { sq.z } else { 0.0 },
+        if direction.z < 0.0 { sq.z } else { 0.0 },
+    ]
+}
+
+/// Returns i-th of n directions, that are evenly distributed over the unit sphere.
+fn fibonacci_direction(i: usize, n: usize) -> Vector3<f32> {
+    let golden_angle = std::f32::consts::PI * (3.0 - 5.0f32.sqrt());
+    let z = 1.0 - (2.0 * i as f32 + 1.0) / n as f32;
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    let phi = golden_angle * i as f32;
+    Vector3::new(r * phi.cos(), r * phi.sin(), z)
+}
+
+/// Uniformly distributed random rotation.
+fn random_rotation(rng: &mut Pcg32) -> UnitQuaternion<f32> {
+    let (u1, u2, u3) = (rng.next_f32(), rng.next_f32(), rng.next_f32());
+    let tau = 2.0 * std::f32::consts::PI;
+    let (a, b) = ((1.0 - u1).sqrt(), u1.sqrt());
+    UnitQuaternion::new_normalize(Quaternion::new(
+        b * (tau * u3).cos(),
+        a * (tau * u2).sin(),
+        a * (tau * u2).cos(),
+        b * (tau * u3).sin(),
+    ))
+}
+
+#[derive(Copy, Clone, Default, Debug)]
+struct Probe {
+    /// Irradiance divided by PI for each face of the ambient cube, so it could be simply multiplied
+    /// by albedo to get reflected light.
+    irradiance: [Vector3<f32>; 6],
+    /// Mean distance and mean squared distance to the surrounding geometry for each face.
+    depth: [Vector2<f32>; 6],
+    /// `false` until the probe is updated for the first time.
+    valid: bool,
+}
+
+impl Probe {
+    fn irradiance(&self, direction: &Vector3<f32>) -> Vector3<f32> {
+        ambient_cube_weights(direction)
+            .iter()
+            .zip(self.irradiance.iter())
+            .map(|(weight, irradiance)| irradiance.scale(*weight))
+            .sum()
+    }
+
+    fn blend(&mut self, new: &Probe, hysteresis: f32) {
+        if !self.valid {
+            *self = *new;
+            return;
+        }
+        for (old, new) in self.irradiance.iter_mut().zip(new.irradiance.iter()) {
+            *old = old.lerp(new, 1.0 - hysteresis);
+        }
+        for (old, new) in self.depth.iter_mut().zip(new.depth.iter()) {
+            *old = old.lerp(new, 1.0 - hysteresis);
+        }
+    }
+}
+
+/// Parameters of a probe update, that are taken from a probe volume.
+#[derive(Clone, Debug)]
+pub(crate) struct ProbeUpdateSettings {
+    pub rays_per_probe: usize,
+    pub probes_per_frame: usize,
+    pub hysteresis: f32,
+    pub sky: Vector3<f32>,
+}
+
+impl ProbeUpdateSettings {
+    fn from_volume(volume: &ProbeVolume) -> Self {
+        Self {
+            rays_per_probe: volume.rays_per_probe() as usize,
+            probes_per_frame: volume.probes_per_frame() as usize,
+            hysteresis: volume.hysteresis(),
+            sky: volume.sky_color().srgb_to_linear().as_frgb() * volume.sky_intensity(),
+        }
+    }
+}
+
+/// CPU-side state of the probes of a probe volume.
+pub(crate) struct ProbeGrid {
+    count: Vector3<usize>,
+    transform: Matrix4<f32>,
+    inv_transform: Matrix4<f32>,
+    probes: Vec<Probe>,
+    next_probe: usize,
+    update_index: u64,
+}
+
+impl ProbeGrid {
+    pub(crate) fn new(count: Vector3<usize>) -> Self {
+        let count = count.map(|n| n.max(1));
+        Self {
+            count,
+            transform: Matrix4::identity(),
+            inv_transform: Matrix4::identity(),
+            probes: vec![Probe::default(); count.x * count.y * count.z],
+            next_probe: 0,
+            update_index: 0,
+        }
+    }
+
+    pub(crate) fn count(&self) -> Vector3<usize> {
+        self.count
+    }
+
+    fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        (z * self.count.y + y) * self.count.x + x
+    }
+
+    fn coordinates(&self, index: usize) -> Vector3<usize> {
+        Vector3::new(
+            index % self.count.x,
+            (index / self.count.x) % self.count.y,
+            index / (self.count.x * self.count.y),
+        )
+    }
+
+    fn probe_position(&self, coordinates: Vector3<usize>) -> Vector3<f32> {
+        let count = self.count.cast::<f32>();
+        let local = (coordinates.cast::<f32>() + Vector3::repeat(0.5)).component_div(&count)
+            - Vector3::repeat(0.5);
+        self.transform.transform_point(&Point3::from(local)).coords
+    }
+
+    /// Returns the largest distance, that is stored in the probes. It is slightly larger than the
+    /// distance between neighbouring probes.
+    fn max_depth(&self) -> f32 {
+        let cell = Vector3::new(
+            self.transform.column(0).xyz().norm() / self.count.x as f32,
+            self.transform.column(1).xyz().norm() / self.count.y as f32,
+            self.transform.column(2).xyz().norm() / self.count.z as f32,
+        );
+        cell.norm().max(f32::EPSILON) * 1.5
+    }
+
+    /// Samples irradiance (divided by PI) at the given point using trilinear interpolation between
+    /// the nearest probes. Visibility is not taken into account. Points outside of the volume
+    /// receive no light.
+    pub(crate) fn sample_irradiance(
+        &self,
+        position: Vector3<f32>,
+        normal: Vector3<f32>,
+    ) -> Vector3<f32> {
+        let local = self.inv_transform.transform_point(&Point3::from(position));
+        if local.coords.iter().any(|c| c.abs() > 0.5) {
+            return Vector3::default();
+        }
+
+        let count = self.count.cast::<f32>();
+        let grid =
+            (local.coords + Vector3::repeat(0.5)).component_mul(&count) - Vector3::repeat(0.5);
+        let max = self.count.map(|n| n - 1);
+        let base = grid
+            .map(|c| c.floor().max(0.0))
+            .zip_map(&max, |c, m| (c as usize).min(m));
+        let alpha = (grid - base.cast::<f32>()).map(|a| a.clamp(0.0, 1.0));
+
+        let mut sum = Vector3::default();
+        let mut weight_sum = 0.0;
+        for i in 0..8 {
+            let offset = Vector3::new(i & 1, (i >> 1) & 1, (i >> 2) & 1);
+            let coordinates = (base + offset).zip_map(&max, |c, m| c.min(m));
+            let probe = &self.probes[self.index(coordinates.x, coordinates.y, coordinates.z)];
+            if !probe.valid {
+                continue;
+            }
+
+            let trilinear = offset
+                .cast::<f32>()
+                .zip_map(&alpha, |o, a| if o > 0.5 { a } else { 1.0 - a });
+            let to_probe = (self.probe_position(coordinates) - position)
+                .try_normalize(f32::EPSILON)
+                .unwrap_or(normal);
+            let backface = (to_probe.dot(&normal) + 1.0) * 0.5;
+            let weight = trilinear.x * trilinear.y * trilinear.z * (backface * backface + 0.2);
+
+            sum += probe.irradiance(&normal).scale(weight);
+            weight_sum += weight;
+        }
+
+        if weight_sum > 0.0 {
+            sum / weight_sum
+        } else {
+            Vector3::default()
+        }
+    }
+
+    fn trace_probe(
+        &self,
+        index: usize,
+        scene: &TracingScene,
+        settings: &ProbeUpdateSettings,
+        rotation: &UnitQuaternion<f32>,
+    ) -> Probe {
+        let origin = self.probe_position(self.coordinates(index));
+        let max_depth = self.max_depth();
+        let ray_count = settings.rays_per_probe.max(1);
+
+        let mut probe = Probe {
+            valid: true,
+            ..Default::default()
+        };
+        let mut depth_weights = [0.0f32; 6];
+        for i in 0..ray_count {
+            let direction = rotation * fibonacci_direction(i, ray_count);
+            let (radiance, distance) =
+                match scene.trace(origin, direction, scene.max_distance(), false) {
+                    Some(hit) => {
+                        // Light of the probes at the hit point gives one more bounce each update.
+                        let bounce = self.sample_irradiance(
+                            hit.position + hit.normal.scale(SURFACE_OFFSET),
+                            hit.normal,
+                        );
+                        let radiance = hit.material.emission
+                            + scene.direct_lighting(&hit)
+                            + hit.material.albedo.component_mul(&bounce);
+                        (radiance, (hit.position - origin).norm().min(max_depth))
+                    }
+                    None => (settings.sky, max_depth),
+                };
+
+            for (face, face_direction) in FACE_DIRECTIONS.iter().enumerate() {
+                let cos_theta = face_direction.dot(&direction);
+                if cos_theta > 0.0 {
+                    probe.irradiance[face] += radiance.scale(cos_theta);
+                    // Distances use narrower lobe to preserve more details.
+                    let depth_weight = cos_theta.powi(4);
+                    probe.depth[face] +=
+                        Vector2::new(distance, distance * distance).scale(depth_weight);
+                    depth_weights[face] += depth_weight;
+                }
+            }
+        }
+
+        // Monte-Carlo estimate of the cosine-weighted integral over the sphere, divided by PI.
+        let scale = 4.0 / ray_count as f32;
+        for irradiance in probe.irradiance.iter_mut() {
+            *irradiance = irradiance.scale(scale);
+        }
+        for (depth, weight) in probe.depth.iter_mut().zip(depth_weights) {
+            *depth = if weight > 0.0 {
+                *depth / weight
+            } else {
+                Vector2::new(max_depth, max_depth * max_depth)
+            };
+        }
+
+        probe
+    }
+
+    /// Updates the next portion of the probes. Returns `true` if every probe of the grid was
+    /// updated at least once since the last time this method returned `true`.
+    pub(crate) fn update(
+        &mut self,
+        scene: &TracingScene,
+        settings: &ProbeUpdateSettings,
+        transform: Matrix4<f32>,
+    ) -> bool {
+        scope_profile!();
+
+        self.transform = transform;
+        self.inv_transform = transform.try_inverse().unwrap_or_else(Matrix4::identity);
+
+        let total = self.probes.len();
+        let count = settings.probes_per_frame.clamp(1, total);
+
+        let mut rng = Pcg32::new(split_mix64(self.update_index), 0);
+        let rotation = random_rotation(&mut rng);
+
+        let indices = (0..count)
+            .map(|i| (self.next_probe + i) % total)
+            .collect::<Vec<_>>();
+        let new_probes = indices
+            .par_iter()
+            .map(|&index| self.trace_probe(index, scene, settings, &rotation))
+            .collect::<Vec<_>>();
+        for (index, new_probe) in indices.into_iter().zip(new_probes) {
+            self.probes[index].blend(&new_probe, settings.hysteresis);
+        }
+
+        let swept = self.next_probe + count >= total;
+        self.next_probe = (self.next_probe + count) % total;
+        self.update_index += 1;
+        swept
+    }
+
+    fn texture_kind(&self) -> GpuTextureKind {
+        GpuTextureKind::Volume {
+            width: self.count.x,
+            height: self.count.y,
+            depth: self.count.z * SLICES_PER_PROBE,
+        }
+    }
+
+    /// Writes the probes in `RGBA32F` format. Every layer of the probes occupies [`SLICES_PER_PROBE`]
+    /// slices of the texture: six slices of irradiance (alpha is set for valid probes) and three
+    /// slices of distances - `(mean+, squared mean+, mean-, squared mean-)` for each axis.
+    pub(crate) fn write_texture_data(&self, data: &mut Vec<f32>) {
+        let (cx, cy) = (self.count.x, self.count.y);
+        data.clear();
+        data.resize(cx * cy * self.count.z * SLICES_PER_PROBE * 4, 0.0);
+        for (index, probe) in self.probes.iter().enumerate() {
+            let c = self.coordinates(index);
+            let mut write = |slice: usize, texel: [f32; 4]| {
+                let offset = (((c.z * SLICES_PER_PROBE + slice) * cy + c.y) * cx + c.x) * 4;
+                data[offset..offset + 4].copy_from_slice(&texel);
+            };
+            let valid = if probe.valid { 1.0 } else { 0.0 };
+            for (face, irradiance) in probe.irradiance.iter().enumerate() {
+                write(face, [irradiance.x, irradiance.y, irradiance.z, valid]);
+            }
+            for axis in 0..3 {
+                let positive = probe.depth[axis * 2];
+                let negative = probe.depth[axis * 2 + 1];
+                write(6 + axis, [positive.x, positive.y, negative.x, negative.y]);
+            }
+        }
+    }
+}
+
+struct ProbeVolumeShader {
+    program: GpuProgram,
+    depth_texture: UniformLocation,
+    color_texture: UniformLocation,
+    normal_texture: UniformLocation,
+    ao_sampler: UniformLocation,
+    probe_texture: UniformLocation,
+    inv_view_proj: UniformLocation,
+    volume_transform: UniformLocation,
+    inv_volume_transform: UniformLocation,
+    probe_count: UniformLocation,
+    intensity: UniformLocation,
+    normal_bias: UniformLocation,
+}
+
+impl ProbeVolumeShader {
+    fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/probe_volume_fs.glsl");
+        let program = GpuProgram::from_source(
+            state,
+            "ProbeVolumeShader",
+            FULLSCREEN_VERTEX_SHADER,
+            fragment_source,
+        )?;
+        Ok(Self {
+            depth_texture: program
+                .uniform_location(state, &ImmutableString::new("depthTexture"))?,
+            color_texture: program
+                .uniform_location(state, &ImmutableString::new("colorTexture"))?,
+            normal_texture: program
+                .uniform_location(state, &ImmutableString::new("normalTexture"))?,
+            ao_sampler: program.uniform_location(state, &ImmutableString::new("aoSampler"))?,
+            probe_texture: program
+                .uniform_location(state, &ImmutableString::new("probeTexture"))?,
+            inv_view_proj: program.uniform_location(state, &ImmutableString::new("invViewProj"))?,
+            volume_transform: program
+                .uniform_location(state, &ImmutableString::new("volumeTransform"))?,
+            inv_volume_transform: program
+                .uniform_location(state, &ImmutableString::new("invVolumeTransform"))?,
+            probe_count: program.uniform_location(state, &ImmutableString::new("probeCount"))?,
+            intensity: program.uniform_location(state, &ImmutableString::new("intensity"))?,
+            normal_bias: program.uniform_location(state, &ImmutableString::new("normalBias"))?,
+            program,
+        })
+    }
+}
+
+struct ProbeVolumeInstance {
+    grid: ProbeGrid,
+    tracing_scene: Option<TracingScene>,
+    texture: Rc<RefCell<GpuTexture>>,
+    data: Vec<f32>,
+    alive: bool,
+}
+
+impl ProbeVolumeInstance {
+    fn new(state: &PipelineState, count: Vector3<usize>) -> Result<Self, FrameworkError> {
+        let grid = ProbeGrid::new(count);
+        let mut data = Vec::new();
+        grid.write_texture_data(&mut data);
+        let texture = GpuTexture::new(
+            state,
+            grid.texture_kind(),
+            PixelKind::RGBA32F,
+            MinificationFilter::Nearest,
+            MagnificationFilter::Nearest,
+            1,
+            Some(crate::core::array_as_u8_slice(&data)),
+        )?;
+        Ok(Self {
+            grid,
+            tracing_scene: None,
+            texture: Rc::new(RefCell::new(texture)),
+            data,
+            alive: true,
+        })
+    }
+}
+
+pub struct ProbeVolumeRenderer {
+    shader: ProbeVolumeShader,
+    instances: FxHashMap<(Handle<Scene>, Handle<Node>), ProbeVolumeInstance>,
+}
+
+impl ProbeVolumeRenderer {
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: ProbeVolumeShader::new(state)?,
+            instances: Default::default(),
+        })
+    }
+
+    /// Updates a portion of the probes of every probe volume of the scene. Must be called once per
+    /// frame.
+    pub(crate) fn update(
+        &mut self,
+        state: &PipelineState,
+        scene_handle: Handle<Scene>,
+        scene: &Scene,
+    ) -> Result<(), FrameworkError> {
+        scope_profile!();
+
+        for (key, instance) in self.instances.iter_mut() {
+            if key.0 == scene_handle {
+                instance.alive = false;
+            }
+        }
+
+        for (handle, node) in scene.graph.pair_iter() {
+            let Some(volume) = node.cast::<ProbeVolume>() else {
+                continue;
+            };
+            if !volume.is_globally_enabled() {
+                continue;
+            }
+
+            let count = volume.probe_count().map(|n| n as usize);
+            let instance = match self.instances.entry((scene_handle, handle)) {
+                std::collections::hash_map::Entry::Occupied(entry) => {
+                    let instance = entry.into_mut();
+                    if instance.grid.count() != count {
+                        *instance = ProbeVolumeInstance::new(state, count)?;
+                    }
+                    instance
+                }
+                std::collections::hash_map::Entry::Vacant(entry) => {
+                    entry.insert(ProbeVolumeInstance::new(state, count)?)
+                }
+            };
+            instance.alive = true;
+
+            if instance.tracing_scene.is_none() {
+                // Meshes with invalid data cannot be traced, such scenes get no indirect light.
+                instance.tracing_scene = TracingScene::from_scene(scene).ok();
+            }
+            let Some(tracing_scene) = instance.tracing_scene.as_ref() else {
+                continue;
+            };
+
+            let settings = ProbeUpdateSettings::from_volume(volume);
+            if instance
+                .grid
+                .update(tracing_scene, &settings, volume.global_transform())
+            {
+                // Every probe has seen the current snapshot, take a new one to catch up with the
+                // changes in the scene.
+                instance.tracing_scene = None;
+            }
+
+            instance.grid.write_texture_data(&mut instance.data);
+            instance.texture.borrow_mut().bind_mut(state, 0).set_data(
+                instance.grid.texture_kind(),
+                PixelKind::RGBA32F,
+                1,
+                Some(crate::core::array_as_u8_slice(&instance.data)),
+            )?;
+        }
+
+        self.instances.retain(|_, instance| instance.alive);
+
+        Ok(())
+    }
+
+    /// Removes probe volumes of the scenes, that do not satisfy the given predicate.
+    pub(crate) fn retain_scenes(&mut self, mut predicate: impl FnMut(Handle<Scene>) -> bool) {
+        self.instances.retain(|(scene, _), _| predicate(*scene));
+    }
+
+    /// Adds indirect light of every probe volume of the scene to the frame buffer.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn render(
+        &self,
+        state: &PipelineState,
+        quad: &GeometryBuffer,
+        frame_buffer: &mut FrameBuffer,
+        viewport: Rect<i32>,
+        gbuffer: &GBuffer,
+        ao_map: &Rc<RefCell<GpuTexture>>,
+        camera: &Camera,
+        scene_handle: Handle<Scene>,
+        graph: &Graph,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!();
+
+        let mut stats = RenderPassStatistics::default();
+
+        let pass = FullscreenPass::new(state, quad);
+        let inv_view_projection = camera
+            .view_projection_matrix()
+            .try_inverse()
+            .unwrap_or_default();
+        let depth = gbuffer.depth();
+        let diffuse = gbuffer.diffuse_texture();
+        let normal = gbuffer.normal_texture();
+
+        let shader = &self.shader;
+        for (key, instance) in self.instances.iter() {
+            if key.0 != scene_handle {
+                continue;
+            }
+            let Some(volume) = graph.try_get(key.1).and_then(|n| n.cast::<ProbeVolume>()) else {
+                continue;
+            };
+
+            let volume_transform = volume.global_transform();
+            let inv_volume_transform = volume_transform.try_inverse().unwrap_or_default();
+            let probe_count = instance.grid.count().cast::<f32>();
+
+            stats += pass.draw(
+                frame_buffer,
+                viewport,
+                &shader.program,
+                Some(BlendParameters {
+                    func: BlendFunc::new(BlendFactor::One, BlendFactor::One),
+                    ..Default::default()
+                }),
+                |program_binding| {
+                    program_binding
+                        .set_texture(&shader.depth_texture, &depth)
+                        .set_texture(&shader.color_texture, &diffuse)
+                        .set_texture(&shader.normal_texture, &normal)
+                        .set_texture(&shader.ao_sampler, ao_map)
+                        .set_texture(&shader.probe_texture, &instance.texture)
+                        .set_matrix4(&shader.inv_view_proj, &inv_view_projection)
+                        .set_matrix4(&shader.volume_transform, &volume_transform)
+                        .set_matrix4(&shader.inv_volume_transform, &inv_volume_transform)
+                        .set_vector3(&shader.probe_count, &probe_count)
+                        .set_f32(&shader.intensity, volume.intensity())
+                        .set_f32(&shader.normal_bias, volume.normal_bias());
+                },
+            )?;
+        }
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Matrix4, Vector2, Vector3},
+        renderer::probe_volume::{
+            ambient_cube_weights, fibonacci_direction, ProbeGrid, ProbeUpdateSettings,
+        },
+        scene::Scene,
+        utils::path_tracer::TracingScene,
+    };
+
+    #[test]
+    fn test_ambient_cube_weights() {
+        for i in 0..32 {
+            let direction = fibonacci_direction(i, 32);
+            assert!((direction.norm() - 1.0).abs() < 1.0e-5);
+            let sum = ambient_cube_weights(&direction).iter().sum::<f32>();
+            assert!((sum - 1.0).abs() < 1.0e-5);
+        }
+    }
+
+    #[test]
+    fn test_probe_grid_sky_lighting() {
+        let mut scene = Scene::new();
+        scene
+            .graph
+            .update(Vector2::new(1.0, 1.0), 0.0, Default::default());
+        let tracing_scene = TracingScene::from_scene(&scene).unwrap();
+
+        let mut grid = ProbeGrid::new(Vector3::new(2, 1, 2));
+        let settings = ProbeUpdateSettings {
+            rays_per_probe: 128,
+            probes_per_frame: 2,
+            hysteresis: 0.0,
+            sky: Vector3::repeat(1.0),
+        };
+        let transform = Matrix4::new_scaling(4.0);
+        assert!(!grid.update(&tracing_scene, &settings, transform));
+        assert!(grid.update(&tracing_scene, &settings, transform));
+
+        // Uniform white sky gives unit irradiance (divided by PI) in every direction.
+        let irradiance = grid.sample_irradiance(Vector3::default(), Vector3::y());
+        assert!((irradiance - Vector3::repeat(1.0)).norm() < 0.05);
+
+        // Outside of the volume there's no light.
+        let outside = grid.sample_irradiance(Vector3::new(10.0, 0.0, 0.0), Vector3::y());
+        assert_eq!(outside, Vector3::default());
+
+        let mut data = Vec::new();
+        grid.write_texture_data(&mut data);
+        assert_eq!(data.len(), 2 * 2 * 9 * 4);
+    }
+}
@@ -0,0 +1,496 @@
+//! Screen transitions hide scene switches. The last rendered frame is captured and shown (with
+//! optional blur and darkening) while a new scene loads, then it crossfades to the new scene. See
+//! [`ScreenTransition`] docs for more info.
+
+use crate::{
+    core::{algebra::Vector2, math::Rect, scope_profile, sstorage::ImmutableString},
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{
+                Attachment, AttachmentKind, BlendParameters, DrawParameters, FrameBuffer,
+            },
+            geometry_buffer::{ElementRange, GeometryBuffer, GeometryBufferKind},
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::{
+                Coordinate, GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter,
+                PixelKind, WrapMode,
+            },
+            state::{BlendFactor, BlendFunc, PipelineState},
+        },
+        make_viewport_matrix, RenderPassStatistics,
+    },
+    scene::mesh::surface::SurfaceData,
+};
+use std::{cell::RefCell, rc::Rc};
+
+/// Parameters of a screen transition. The transition starts by capturing the last rendered frame
+/// (without user interfaces), which is then shown instead of scenes. The effects (blur and
+/// darkening) are smoothly applied to the captured frame during [`Self::effect_duration`], so the
+/// player sees that the game is switching to something else. When the transition ends, the captured
+/// frame crossfades to the current frame during [`Self::fade_duration`].
+///
+/// User interfaces are drawn on top of the captured frame, so a loading screen (a progress bar,
+/// tips, etc.) could be shown meanwhile.
+///
+/// Transitions could be started manually (see [`crate::renderer::Renderer::begin_screen_transition`])
+/// or automatically by the engine when a scene is loading (see
+/// [`crate::engine::AsyncSceneLoader::set_transition`]).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScreenTransition {
+    /// Radius of the blur (in pixels) at full strength of the effects. Zero disables blur.
+    pub blur_radius: f32,
+    /// Brightness of the captured frame at full strength of the effects. `1.0` - unchanged,
+    /// `0.0` - black.
+    pub brightness: f32,
+    /// Time (in seconds) that is needed to reach full strength of the effects.
+    pub effect_duration: f32,
+    /// Duration (in seconds) of the final crossfade.
+    pub fade_duration: f32,
+}
+
+impl Default for ScreenTransition {
+    fn default() -> Self {
+        Self {
+            blur_radius: 8.0,
+            brightness: 0.6,
+            effect_duration: 0.3,
+            fade_duration: 0.5,
+        }
+    }
+}
+
+impl ScreenTransition {
+    /// Creates a transition, that only crossfades the captured frame to the new one without any
+    /// effects.
+    pub fn crossfade(fade_duration: f32) -> Self {
+        Self {
+            blur_radius: 0.0,
+            brightness: 1.0,
+            effect_duration: 0.0,
+            fade_duration,
+        }
+    }
+
+    /// Sets desired blur radius. See [`Self::blur_radius`].
+    pub fn with_blur_radius(mut self, blur_radius: f32) -> Self {
+        self.blur_radius = blur_radius.max(0.0);
+        self
+    }
+
+    /// Sets desired brightness. See [`Self::brightness`].
+    pub fn with_brightness(mut self, brightness: f32) -> Self {
+        self.brightness = brightness.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets desired effect duration. See [`Self::effect_duration`].
+    pub fn with_effect_duration(mut self, duration: f32) -> Self {
+        self.effect_duration = duration.max(0.0);
+        self
+    }
+
+    /// Sets desired crossfade duration. See [`Self::fade_duration`].
+    pub fn with_fade_duration(mut self, duration: f32) -> Self {
+        self.fade_duration = duration.max(0.0);
+        self
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Phase {
+    Idle,
+    Capture { end_requested: bool },
+    Hold { elapsed: f32 },
+    FadeOut { elapsed: f32, strength: f32 },
+}
+
+/// Appearance of the captured frame in the current frame.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Overlay {
+    strength: f32,
+    opacity: f32,
+}
+
+/// Time-dependent part of a transition, it does not use GPU.
+struct Timeline {
+    params: ScreenTransition,
+    phase: Phase,
+}
+
+impl Timeline {
+    fn strength(&self, elapsed: f32) -> f32 {
+        if self.params.effect_duration > 0.0 {
+            (elapsed / self.params.effect_duration).min(1.0)
+        } else {
+            1.0
+        }
+    }
+
+    fn begin(&mut self, params: ScreenTransition) {
+        self.params = params;
+        // The captured frame is already shown, capturing it again would capture the frame of a
+        // new scene instead.
+        if !matches!(self.phase, Phase::Capture { .. } | Phase::Hold { .. }) {
+            self.phase = Phase::Capture {
+                end_requested: false,
+            };
+        }
+    }
+
+    fn end(&mut self) {
+        match self.phase {
+            Phase::Capture { .. } => {
+                self.phase = Phase::Capture {
+                    end_requested: true,
+                }
+            }
+            Phase::Hold { elapsed } => {
+                self.phase = Phase::FadeOut {
+                    elapsed: 0.0,
+                    strength: self.strength(elapsed),
+                }
+            }
+            Phase::Idle | Phase::FadeOut { .. } => (),
+        }
+    }
+
+    // Must be called once the frame is captured.
+    fn on_captured(&mut self) {
+        if let Phase::Capture { end_requested } = self.phase {
+            self.phase = if end_requested {
+                Phase::FadeOut {
+                    elapsed: 0.0,
+                    strength: 0.0,
+                }
+            } else {
+                Phase::Hold { elapsed: 0.0 }
+            };
+        }
+    }
+
+    // Advances the transition and returns the appearance of the captured frame.
+    fn advance(&mut self, dt: f32) -> Option<Overlay> {
+        match self.phase {
+            Phase::Idle | Phase::Capture { .. } => None,
+            Phase::Hold { elapsed } => {
+                self.phase = Phase::Hold {
+                    elapsed: elapsed + dt,
+                };
+                Some(Overlay {
+                    strength: self.strength(elapsed),
+                    opacity: 1.0,
+                })
+            }
+            Phase::FadeOut { elapsed, strength } => {
+                let opacity = if self.params.fade_duration > 0.0 {
+                    1.0 - elapsed / self.params.fade_duration
+                } else {
+                    0.0
+                };
+                if opacity <= 0.0 {
+                    self.phase = Phase::Idle;
+                    None
+                } else {
+                    self.phase = Phase::FadeOut {
+                        elapsed: elapsed + dt,
+                        strength,
+                    };
+                    Some(Overlay { strength, opacity })
+                }
+            }
+        }
+    }
+}
+
+struct ScreenTransitionShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    frame_texture: UniformLocation,
+    inverse_frame_size: UniformLocation,
+    blur_radius: UniformLocation,
+    brightness: UniformLocation,
+    opacity: UniformLocation,
+}
+
+impl ScreenTransitionShader {
+    fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/screen_transition_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program = GpuProgram::from_source(
+            state,
+            "ScreenTransitionShader",
+            vertex_source,
+            fragment_source,
+        )?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            frame_texture: program
+                .uniform_location(state, &ImmutableString::new("frameTexture"))?,
+            inverse_frame_size: program
+                .uniform_location(state, &ImmutableString::new("inverseFrameSize"))?,
+            blur_radius: program.uniform_location(state, &ImmutableString::new("blurRadius"))?,
+            brightness: program.uniform_location(state, &ImmutableString::new("brightness"))?,
+            opacity: program.uniform_location(state, &ImmutableString::new("opacity"))?,
+            program,
+        })
+    }
+}
+
+struct CapturedFrame {
+    framebuffer: FrameBuffer,
+    width: i32,
+    height: i32,
+}
+
+fn make_capture_framebuffer(
+    state: &PipelineState,
+    width: usize,
+    height: usize,
+) -> Result<FrameBuffer, FrameworkError> {
+    let mut texture = GpuTexture::new(
+        state,
+        GpuTextureKind::Rectangle { width, height },
+        PixelKind::RGBA8,
+        MinificationFilter::Linear,
+        MagnificationFilter::Linear,
+        1,
+        None,
+    )?;
+    texture
+        .bind_mut(state, 0)
+        .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+        .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
+    FrameBuffer::new(
+        state,
+        None,
+        vec![Attachment {
+            kind: AttachmentKind::Color,
+            texture: Rc::new(RefCell::new(texture)),
+        }],
+    )
+}
+
+pub struct ScreenTransitionRenderer {
+    shader: ScreenTransitionShader,
+    quad: GeometryBuffer,
+    timeline: Timeline,
+    captured_frame: Option<CapturedFrame>,
+}
+
+impl ScreenTransitionRenderer {
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: ScreenTransitionShader::new(state)?,
+            quad: GeometryBuffer::from_surface_data(
+                &SurfaceData::make_unit_xy_quad(),
+                GeometryBufferKind::StaticDraw,
+                state,
+            )?,
+            timeline: Timeline {
+                params: Default::default(),
+                phase: Phase::Idle,
+            },
+            captured_frame: None,
+        })
+    }
+
+    pub fn begin(&mut self, params: ScreenTransition) {
+        self.timeline.begin(params);
+    }
+
+    pub fn end(&mut self) {
+        self.timeline.end();
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.timeline.phase != Phase::Idle
+    }
+
+    /// Draws the captured frame on top of the given frame buffer, and then captures the frame if
+    /// it was requested. It must be called after scenes are rendered, but before user interfaces.
+    pub(crate) fn render(
+        &mut self,
+        state: &PipelineState,
+        viewport: Rect<i32>,
+        frame_buffer: &mut FrameBuffer,
+        dt: f32,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!();
+
+        let mut statistics = RenderPassStatistics::default();
+
+        if let (Some(overlay), Some(captured_frame)) =
+            (self.timeline.advance(dt), self.captured_frame.as_ref())
+        {
+            let params = &self.timeline.params;
+            let blur_radius = params.blur_radius * overlay.strength;
+            let brightness = 1.0 + (params.brightness - 1.0) * overlay.strength;
+            let inverse_frame_size = Vector2::new(
+                1.0 / captured_frame.width as f32,
+                1.0 / captured_frame.height as f32,
+            );
+            let frame_texture = captured_frame.framebuffer.color_attachments()[0]
+                .texture
+                .clone();
+
+            statistics += frame_buffer.draw(
+                &self.quad,
+                state,
+                viewport,
+                &self.shader.program,
+                &DrawParameters {
+                    cull_face: None,
+                    color_write: Default::default(),
+                    depth_write: false,
+                    stencil_test: None,
+                    depth_test: false,
+                    blend: Some(BlendParameters {
+                        func: BlendFunc::new(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
+                        ..Default::default()
+                    }),
+                    stencil_op: Default::default(),
+                },
+                ElementRange::Full,
+                |mut program_binding| {
+                    program_binding
+                        .set_matrix4(&self.shader.wvp_matrix, &make_viewport_matrix(viewport))
+                        .set_texture(&self.shader.frame_texture, &frame_texture)
+                        .set_vector2(&self.shader.inverse_frame_size, &inverse_frame_size)
+                        .set_f32(&self.shader.blur_radius, blur_radius)
+                        .set_f32(&self.shader.brightness, brightness)
+                        .set_f32(&self.shader.opacity, overlay.opacity);
+                },
+            )?;
+        }
+
+        if matches!(self.timeline.phase, Phase::Capture { .. }) {
+            let (width, height) = (viewport.w(), viewport.h());
+            if self
+                .captured_frame
+                .as_ref()
+                .map_or(true, |f| f.width != width || f.height != height)
+            {
+                self.captured_frame = Some(CapturedFrame {
+                    framebuffer: make_capture_framebuffer(state, width as usize, height as usize)?,
+                    width,
+                    height,
+                });
+            }
+
+            if let Some(captured_frame) = self.captured_frame.as_ref() {
+                state.blit_framebuffer(
+                    frame_buffer.id(),
+                    captured_frame.framebuffer.id(),
+                    viewport.x(),
+                    viewport.y(),
+                    viewport.x() + width,
+                    viewport.y() + height,
+                    0,
+                    0,
+                    width,
+                    height,
+                    true,
+                    false,
+                    false,
+                );
+            }
+
+            self.timeline.on_captured();
+        } else if self.timeline.phase == Phase::Idle {
+            // Free video memory, the frame will be captured again by the next transition.
+            self.captured_frame = None;
+        }
+
+        Ok(statistics)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn timeline() -> Timeline {
+        Timeline {
+            params: ScreenTransition {
+                blur_radius: 4.0,
+                brightness: 0.5,
+                effect_duration: 1.0,
+                fade_duration: 1.0,
+            },
+            phase: Phase::Idle,
+        }
+    }
+
+    #[test]
+    fn test_timeline() {
+        let mut timeline = timeline();
+        let params = timeline.params.clone();
+        assert_eq!(timeline.advance(0.5), None);
+
+        timeline.begin(params.clone());
+        // Nothing is shown until the frame is captured.
+        assert_eq!(timeline.advance(0.5), None);
+        timeline.on_captured();
+        assert_eq!(
+            timeline.advance(0.5),
+            Some(Overlay {
+                strength: 0.0,
+                opacity: 1.0
+            })
+        );
+        assert_eq!(
+            timeline.advance(0.5),
+            Some(Overlay {
+                strength: 0.5,
+                opacity: 1.0
+            })
+        );
+
+        // Repeated begin must not capture the frame again.
+        timeline.begin(params);
+        assert_eq!(timeline.phase, Phase::Hold { elapsed: 1.0 });
+
+        timeline.end();
+        assert_eq!(
+            timeline.advance(0.5),
+            Some(Overlay {
+                strength: 1.0,
+                opacity: 1.0
+            })
+        );
+        assert_eq!(
+            timeline.advance(0.5),
+            Some(Overlay {
+                strength: 1.0,
+                opacity: 0.5
+            })
+        );
+        assert_eq!(timeline.advance(0.5), None);
+        assert_eq!(timeline.phase, Phase::Idle);
+    }
+
+    #[test]
+    fn test_end_before_capture() {
+        let mut timeline = timeline();
+        timeline.begin(ScreenTransition::crossfade(1.0));
+        timeline.end();
+        timeline.on_captured();
+        assert_eq!(
+            timeline.advance(0.25),
+            Some(Overlay {
+                strength: 0.0,
+                opacity: 1.0
+            })
+        );
+        assert_eq!(
+            timeline.advance(0.25),
+            Some(Overlay {
+                strength: 0.0,
+                opacity: 0.75
+            })
+        );
+    }
+}
@@ -0,0 +1,53 @@
+// Darkens ambient lighting by the baked occlusion of an ambient occlusion volume. See
+// `utils/voxel_ao.rs` for the layout of the texture.
+
+uniform sampler2D depthTexture;
+uniform sampler2D normalTexture;
+uniform sampler3D aoTexture;
+
+uniform mat4 invViewProj;
+uniform mat4 invVolumeTransform;
+uniform float intensity;
+uniform float normalBias;
+
+in vec2 texCoord;
+out vec4 FragColor;
+
+void main()
+{
+    float depth = texture(depthTexture, texCoord).r;
+    if (depth >= 1.0) {
+        discard;
+    }
+
+    vec3 fragmentPosition = S_UnProject(vec3(texCoord, depth), invViewProj);
+    vec3 normal = normalize(texture(normalTexture, texCoord).xyz * 2.0 - 1.0);
+
+    // Surfaces are inside of occupied voxels, so the sample point is moved to the empty space.
+    vec3 local = (invVolumeTransform * vec4(fragmentPosition + normal * normalBias, 1.0)).xyz;
+    vec3 edgeDistance = vec3(0.5) - abs(local);
+    if (any(lessThan(edgeDistance, vec3(0.0)))) {
+        discard;
+    }
+
+    vec3 resolution = vec3(textureSize(aoTexture, 0));
+
+    // Fade out the occlusion near the borders of the volume to hide the seams.
+    vec3 fadeDistance = clamp(edgeDistance * resolution * 2.0, 0.0, 1.0);
+    float fade = fadeDistance.x * fadeDistance.y * fadeDistance.z;
+
+    vec3 halfTexel = 0.5 / resolution;
+    vec4 data = texture(aoTexture, clamp(local + 0.5, halfTexel, 1.0 - halfTexel));
+
+    // Surfaces facing away from the unoccluded directions receive less ambient light. Bent normals
+    // are short when the unoccluded directions are spread widely, in this case the direction does
+    // not matter much.
+    vec3 bentNormal = data.xyz * 2.0 - 1.0;
+    float bentLength = length(bentNormal);
+    float directional = bentLength > 0.0 ? clamp(dot(bentNormal / bentLength, normal) * 0.5 + 0.5, 0.0, 1.0) : 1.0;
+    directional = mix(1.0, directional, clamp(bentLength * 2.0, 0.0, 1.0));
+
+    float occlusion = mix(1.0, data.a * directional, intensity * fade);
+
+    FragColor = vec4(vec3(occlusion), 1.0);
+}
@@ -0,0 +1,11 @@
+uniform vec4 topColor;
+uniform vec4 bottomColor;
+
+in vec2 texCoord;
+
+out vec4 FragColor;
+
+void main()
+{
+    FragColor = mix(bottomColor, topColor, texCoord.y);
+}
@@ -0,0 +1,68 @@
+// 13-tap downsampling filter from "Next Generation Post Processing in Call of Duty: Advanced Warfare"
+// by Jorge Jimenez, that also extracts bright parts of the frame. It is used only for the first mip
+// of the bloom chain, the rest are built using the generic downsampling.
+
+uniform sampler2D srcSampler;
+uniform vec2 srcTexelSize;
+uniform float threshold;
+uniform float softKnee;
+
+in vec2 texCoord;
+
+out vec4 outColor;
+
+vec3 Sample(vec2 offset) {
+    return texture(srcSampler, texCoord + offset * srcTexelSize).rgb;
+}
+
+// Quadratic curve around the threshold, that gives smooth transition between bright and dark pixels.
+vec3 Threshold(vec3 color) {
+    float brightness = max(color.r, max(color.g, color.b));
+    float knee = threshold * softKnee + 1.0e-5;
+    float soft = clamp(brightness - threshold + knee, 0.0, 2.0 * knee);
+    soft = soft * soft / (4.0 * knee);
+    float contribution = max(soft, brightness - threshold) / max(brightness, 1.0e-5);
+    return color * contribution;
+}
+
+// Karis average suppresses "fireflies" - very bright sub-pixel details, that flicker when moving.
+float KarisWeight(vec3 color) {
+    return 1.0 / (1.0 + S_Luminance(color));
+}
+
+void main() {
+    vec3 a = Sample(vec2(-2.0, 2.0));
+    vec3 b = Sample(vec2(0.0, 2.0));
+    vec3 c = Sample(vec2(2.0, 2.0));
+    vec3 d = Sample(vec2(-2.0, 0.0));
+    vec3 e = Sample(vec2(0.0, 0.0));
+    vec3 f = Sample(vec2(2.0, 0.0));
+    vec3 g = Sample(vec2(-2.0, -2.0));
+    vec3 h = Sample(vec2(0.0, -2.0));
+    vec3 i = Sample(vec2(2.0, -2.0));
+    vec3 j = Sample(vec2(-1.0, 1.0));
+    vec3 k = Sample(vec2(1.0, 1.0));
+    vec3 l = Sample(vec2(-1.0, -1.0));
+    vec3 m = Sample(vec2(1.0, -1.0));
+
+    vec3 groups[5] = vec3[] (
+        (j + k + l + m) * 0.25,
+        (a + b + d + e) * 0.25,
+        (b + c + e + f) * 0.25,
+        (d + e + g + h) * 0.25,
+        (e + f + h + i) * 0.25
+    );
+    const float weights[5] = float[] (0.5, 0.125, 0.125, 0.125, 0.125);
+
+    vec3 result = vec3(0.0);
+    float totalWeight = 0.0;
+    for (int n = 0; n < 5; ++n) {
+        vec3 group = Threshold(groups[n]);
+        float weight = weights[n] * KarisWeight(group);
+        result += group * weight;
+        totalWeight += weight;
+    }
+    result /= max(totalWeight, 1.0e-5);
+
+    outColor = vec4(max(result, vec3(0.0)), 1.0);
+}
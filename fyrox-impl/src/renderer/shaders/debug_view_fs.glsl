@@ -0,0 +1,23 @@
+uniform sampler2D depthTexture;
+uniform sampler2D normalTexture;
+uniform sampler2D materialTexture;
+uniform int mode;
+
+in vec2 texCoord;
+out vec4 FragColor;
+
+void main()
+{
+    // Skip pixels without any geometry.
+    if (texture(depthTexture, texCoord).r >= 1.0) {
+        discard;
+    }
+
+    if (mode == 0) {
+        // Normals are already packed in [0; 1] range.
+        FragColor = vec4(texture(normalTexture, texCoord).rgb, 1.0);
+    } else {
+        // Metallic (r) + Roughness (g) + Ambient Occlusion (b).
+        FragColor = vec4(texture(materialTexture, texCoord).rgb, 1.0);
+    }
+}
@@ -0,0 +1,27 @@
+uniform bool showMipLevel;
+uniform vec4 color;
+uniform vec2 textureSize;
+
+in vec2 texCoord;
+out vec4 FragColor;
+
+void main()
+{
+    if (showMipLevel) {
+        vec2 texel = texCoord * textureSize;
+        vec2 dx = dFdx(texel);
+        vec2 dy = dFdy(texel);
+        float level = 0.5 * log2(max(max(dot(dx, dx), dot(dy, dy)), 1.0e-8));
+
+        // Green - one texel per pixel, blue - texture is magnified (too low resolution),
+        // red - texture is minified (too high resolution).
+        vec3 ideal = vec3(0.0, 1.0, 0.0);
+        if (level < 0.0) {
+            FragColor = vec4(mix(ideal, vec3(0.0, 0.0, 1.0), clamp(-level / 3.0, 0.0, 1.0)), 1.0);
+        } else {
+            FragColor = vec4(mix(ideal, vec3(1.0, 0.0, 0.0), clamp(level / 3.0, 0.0, 1.0)), 1.0);
+        }
+    } else {
+        FragColor = color;
+    }
+}
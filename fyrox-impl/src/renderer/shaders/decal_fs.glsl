@@ -1,15 +1,24 @@
 uniform sampler2D sceneDepth;
 uniform sampler2D diffuseTexture;
 uniform sampler2D normalTexture;
+uniform sampler2D materialTexture;
 uniform usampler2D decalMask;
 uniform mat4 invViewProj;
 uniform mat4 invWorldDecal;
 uniform vec2 resolution;
 uniform vec4 color;
 uniform uint layerIndex;
+uniform float metallic;
+uniform float roughness;
+uniform float normalStrength;
+uniform float materialStrength;
+uniform vec3 projectionAxis;
+// Cosines of the angles at which the decal starts to fade (x) and completely fades out (y).
+uniform vec2 angleFade;
 
 layout(location = 0) out vec4 outDiffuseMap;
 layout(location = 1) out vec4 outNormalMap;
+layout(location = 2) out vec4 outMaterialMap;
 
 in vec4 clipSpacePosition;
 
@@ -43,12 +52,26 @@ void main()
 
     vec2 decalTexCoord = decalSpacePosition.xz + 0.5;
 
-    outDiffuseMap = color * texture(diffuseTexture, decalTexCoord);
-
     vec3 fragmentTangent = dFdx(sceneWorldPosition);
     vec3 fragmentBinormal = dFdy(sceneWorldPosition);
     vec3 fragmentNormal = cross(fragmentTangent, fragmentBinormal);
 
+    // Make sure that the normal faces the camera.
+    vec3 nearPlanePosition = S_UnProject(vec3(texCoord, 0.0), invViewProj);
+    if (dot(fragmentNormal, nearPlanePosition - sceneWorldPosition) < 0.0) {
+        fragmentNormal = -fragmentNormal;
+    }
+
+    // Fade out on surfaces, that are almost parallel to the projection axis.
+    float cosAngle = dot(normalize(fragmentNormal), projectionAxis);
+    float fadeRange = angleFade.x - angleFade.y;
+    float angleFadeFactor = fadeRange > 0.0001
+        ? clamp((cosAngle - angleFade.y) / fadeRange, 0.0, 1.0)
+        : step(angleFade.y, cosAngle);
+
+    outDiffuseMap = color * texture(diffuseTexture, decalTexCoord);
+    outDiffuseMap.a *= angleFadeFactor;
+
     mat3 tangentToWorld;
     tangentToWorld[0] = normalize(fragmentTangent); // Tangent
     tangentToWorld[1] = normalize(fragmentBinormal); // Binormal
@@ -56,5 +79,13 @@ void main()
 
     vec3 rawNormal = (texture(normalTexture, decalTexCoord) * 2.0 - 1.0).xyz;
     vec3 worldSpaceNormal = tangentToWorld * rawNormal;
-    outNormalMap = vec4(worldSpaceNormal * 0.5 + 0.5, outDiffuseMap.a);
-}
\ No newline at end of file
+    outNormalMap = vec4(normalize(worldSpaceNormal) * 0.5 + 0.5, outDiffuseMap.a * normalStrength);
+
+    vec3 material = texture(materialTexture, decalTexCoord).rgb;
+    outMaterialMap = vec4(
+        metallic * material.r,
+        roughness * material.g,
+        material.b,
+        outDiffuseMap.a * materialStrength
+    );
+}
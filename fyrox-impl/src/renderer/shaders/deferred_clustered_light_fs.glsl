@@ -0,0 +1,43 @@
+uniform sampler2D depthTexture;
+uniform sampler2D colorTexture;
+uniform sampler2D normalTexture;
+uniform sampler2D materialTexture;
+uniform sampler2D clusterGrid;
+uniform sampler2D clusterLights;
+uniform sampler2D clusterLightIndices;
+
+uniform mat4 invViewProj;
+uniform vec3 cameraPosition;
+uniform vec4 clusterParameters;
+uniform vec4 clusterDepthPlane;
+
+in vec2 texCoord;
+out vec4 FragColor;
+
+void main()
+{
+    float depth = texture(depthTexture, texCoord).r;
+    if (depth >= 1.0) {
+        discard;
+    }
+
+    vec3 material = texture(materialTexture, texCoord).rgb;
+    vec3 fragmentPosition = S_UnProject(vec3(texCoord, depth), invViewProj);
+    vec4 diffuseColor = texture(colorTexture, texCoord);
+
+    TPBRContext ctx;
+    ctx.albedo = S_SRGBToLinear(diffuseColor).rgb;
+    ctx.fragmentNormal = normalize(texture(normalTexture, texCoord).xyz * 2.0 - 1.0);
+    ctx.metallic = material.x;
+    ctx.roughness = material.y;
+    ctx.viewVector = normalize(cameraPosition - fragmentPosition);
+
+    float viewDepth = dot(clusterDepthPlane, vec4(fragmentPosition, 1.0));
+
+    // Lights with shadows (or cookies) are rendered separately.
+    vec3 lighting = S_ClusteredLighting(
+        clusterGrid, clusterLights, clusterLightIndices, clusterParameters,
+        texCoord, viewDepth, fragmentPosition, true, ctx);
+
+    FragColor = vec4(lighting, diffuseColor.a);
+}
@@ -22,13 +22,14 @@ uniform sampler2D shadowCascade2;
 uniform bool shadowsEnabled;
 uniform float shadowBias;
 uniform bool softShadows;
-uniform float shadowMapInvSize;
+uniform float shadowMapInvSizes[NUM_CASCADES];
+uniform bool showCascades;
 
 in vec2 texCoord;
 out vec4 FragColor;
 
 // Returns **inverted** shadow factor where 1 - fully bright, 0 - fully in shadow.
-float CsmGetShadow(in sampler2D sampler, in vec3 fragmentPosition, in mat4 lightViewProjMatrix)
+float CsmGetShadow(in sampler2D sampler, in vec3 fragmentPosition, in mat4 lightViewProjMatrix, in float shadowMapInvSize)
 {
     return S_SpotShadowFactor(shadowsEnabled, softShadows, shadowBias, fragmentPosition, lightViewProjMatrix, shadowMapInvSize, sampler);
 }
@@ -54,12 +55,20 @@ void main()
     float fragmentZViewSpace = abs((viewMatrix * vec4(fragmentPosition, 1.0)).z);
 
     float shadow = 1.0;
+    vec3 cascadeColor = vec3(1.0);
     if (fragmentZViewSpace <= cascadeDistances[0]) {
-        shadow = CsmGetShadow(shadowCascade0, fragmentPosition, lightViewProjMatrices[0]);
+        shadow = CsmGetShadow(shadowCascade0, fragmentPosition, lightViewProjMatrices[0], shadowMapInvSizes[0]);
+        cascadeColor = vec3(1.0, 0.25, 0.25);
     } else if (fragmentZViewSpace <= cascadeDistances[1]) {
-        shadow = CsmGetShadow(shadowCascade1, fragmentPosition, lightViewProjMatrices[1]);
+        shadow = CsmGetShadow(shadowCascade1, fragmentPosition, lightViewProjMatrices[1], shadowMapInvSizes[1]);
+        cascadeColor = vec3(0.25, 1.0, 0.25);
     } else if (fragmentZViewSpace <= cascadeDistances[2]) {
-        shadow = CsmGetShadow(shadowCascade2, fragmentPosition, lightViewProjMatrices[2]);
+        shadow = CsmGetShadow(shadowCascade2, fragmentPosition, lightViewProjMatrices[2], shadowMapInvSizes[2]);
+        cascadeColor = vec3(0.25, 0.25, 1.0);
+    }
+
+    if (showCascades) {
+        lighting *= cascadeColor;
     }
 
     FragColor = shadow * vec4(lightIntensity * lighting, diffuseColor.a);
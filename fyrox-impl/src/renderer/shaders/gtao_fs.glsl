@@ -0,0 +1,111 @@
+// Ground-truth ambient occlusion, based on "Practical Realtime Strategies for Accurate Indirect
+// Occlusion" by Jimenez et al.
+
+#define SLICE_COUNT 2
+#define STEP_COUNT 4
+#define PI 3.14159265
+#define HALF_PI 1.57079632
+
+uniform sampler2D depthSampler;
+uniform sampler2D normalSampler;
+
+uniform float radius;
+uniform mat4 inverseProjectionMatrix;
+uniform mat4 projectionMatrix;
+uniform mat3 viewMatrix;
+uniform int frameIndex;
+
+out float finalOcclusion;
+
+in vec2 texCoord;
+
+vec3 GetViewSpacePosition(vec2 screenCoord) {
+    return S_UnProject(vec3(screenCoord, texture(depthSampler, screenCoord).r), inverseProjectionMatrix);
+}
+
+float InterleavedGradientNoise(vec2 pixel) {
+    return fract(52.9829189 * fract(dot(pixel, vec2(0.06711056, 0.00583715))));
+}
+
+void main() {
+    vec3 position = GetViewSpacePosition(texCoord);
+    vec3 worldSpaceNormal = texture(normalSampler, texCoord).xyz * 2.0 - 1.0;
+    vec3 normal = normalize(viewMatrix * worldSpaceNormal);
+    vec3 viewDir = normalize(-position);
+
+    // Size of the sampling radius in texture coordinates.
+    vec4 center = projectionMatrix * vec4(position, 1.0);
+    vec4 edge = projectionMatrix * vec4(position + vec3(radius, 0.0, 0.0), 1.0);
+    float screenRadius = 0.5 * abs(edge.x / edge.w - center.x / center.w);
+
+    vec2 texelSize = 1.0 / vec2(textureSize(depthSampler, 0));
+    if (screenRadius < texelSize.x) {
+        finalOcclusion = 1.0;
+        return;
+    }
+
+    // Noise is shifted each frame, so temporal accumulation could gather more directions.
+    vec2 pixel = gl_FragCoord.xy + 5.588238 * float(frameIndex);
+    float sliceNoise = InterleavedGradientNoise(pixel);
+    float stepNoise = InterleavedGradientNoise(pixel.yx + vec2(17.0, 31.0));
+
+    float falloffRange = 0.615 * radius;
+    float falloffMul = -1.0 / falloffRange;
+    float falloffAdd = (radius - falloffRange) / falloffRange + 1.0;
+
+    float visibility = 0.0;
+    for (int slice = 0; slice < SLICE_COUNT; ++slice) {
+        float phi = (float(slice) + sliceNoise) * PI / float(SLICE_COUNT);
+        vec3 direction = vec3(cos(phi), sin(phi), 0.0);
+
+        vec3 orthoDirection = direction - dot(direction, viewDir) * viewDir;
+        vec3 axis = normalize(cross(orthoDirection, viewDir));
+        vec3 projectedNormal = normal - axis * dot(normal, axis);
+        float projectedNormalLength = length(projectedNormal);
+        if (projectedNormalLength < 0.0001) {
+            visibility += 1.0;
+            continue;
+        }
+
+        float signNormal = sign(dot(orthoDirection, projectedNormal));
+        float cosNormal = clamp(dot(projectedNormal, viewDir) / projectedNormalLength, 0.0, 1.0);
+        float n = signNormal * acos(cosNormal);
+
+        float lowHorizonCos0 = cos(n + HALF_PI);
+        float lowHorizonCos1 = cos(n - HALF_PI);
+        float horizonCos0 = lowHorizonCos0;
+        float horizonCos1 = lowHorizonCos1;
+
+        for (int i = 0; i < STEP_COUNT; ++i) {
+            float s = (float(i) + stepNoise) / float(STEP_COUNT);
+            // Put more samples close to the center.
+            vec2 offset = direction.xy * (s * s * screenRadius);
+
+            vec3 delta0 = GetViewSpacePosition(texCoord + offset) - position;
+            vec3 delta1 = GetViewSpacePosition(texCoord - offset) - position;
+            float distance0 = length(delta0);
+            float distance1 = length(delta1);
+
+            float weight0 = clamp(distance0 * falloffMul + falloffAdd, 0.0, 1.0);
+            float weight1 = clamp(distance1 * falloffMul + falloffAdd, 0.0, 1.0);
+
+            float sampleCos0 = mix(lowHorizonCos0, dot(delta0, viewDir) / max(distance0, 0.0001), weight0);
+            float sampleCos1 = mix(lowHorizonCos1, dot(delta1, viewDir) / max(distance1, 0.0001), weight1);
+
+            horizonCos0 = max(horizonCos0, sampleCos0);
+            horizonCos1 = max(horizonCos1, sampleCos1);
+        }
+
+        float h0 = -acos(clamp(horizonCos1, -1.0, 1.0));
+        float h1 = acos(clamp(horizonCos0, -1.0, 1.0));
+        h0 = n + clamp(h0 - n, -HALF_PI, HALF_PI);
+        h1 = n + clamp(h1 - n, -HALF_PI, HALF_PI);
+
+        float sinNormal = sin(n);
+        float arc0 = cosNormal + 2.0 * h0 * sinNormal - cos(2.0 * h0 - n);
+        float arc1 = cosNormal + 2.0 * h1 * sinNormal - cos(2.0 * h1 - n);
+        visibility += projectedNormalLength * 0.25 * (arc0 + arc1);
+    }
+
+    finalOcclusion = clamp(visibility / float(SLICE_COUNT), 0.0, 1.0);
+}
@@ -1,11 +1,13 @@
 uniform sampler2D oldLumSampler;
 uniform sampler2D newLumSampler;
-uniform float speed;
+uniform float speedUp;
+uniform float speedDown;
 
 out float outLum;
 
 void main() {
     float oldLum = texture(oldLumSampler, vec2(0.5, 0.5)).r;
     float newLum = texture(newLumSampler, vec2(0.5, 0.5)).r;
-    outLum = clamp(oldLum + (newLum - oldLum) * speed, 0.0, newLum);
+    float speed = newLum > oldLum ? speedUp : speedDown;
+    outLum = max(oldLum + (newLum - oldLum) * clamp(speed, 0.0, 1.0), 0.0);
 }
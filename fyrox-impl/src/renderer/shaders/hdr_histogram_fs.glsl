@@ -0,0 +1,6 @@
+out float outCount;
+
+void main()
+{
+    outCount = 1.0;
+}
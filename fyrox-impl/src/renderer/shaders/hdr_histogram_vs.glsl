@@ -0,0 +1,23 @@
+layout(location = 0) in vec3 vertexPosition;
+
+uniform sampler2D lumSampler;
+uniform float minLogLum;
+uniform float invLogLumRange;
+
+// Must be in sync with the size of the histogram texture.
+const float binCount = 64.0;
+
+void main()
+{
+    ivec2 lumSize = textureSize(lumSampler, 0);
+    ivec2 pixel = ivec2(gl_InstanceID % lumSize.x, gl_InstanceID / lumSize.x);
+    float lum = texelFetch(lumSampler, pixel, 0).r;
+
+    // Pixels outside of the range are accumulated in the edge bins.
+    float k = clamp((log2(max(lum, 1.0e-6)) - minLogLum) * invLogLumRange, 0.0, 1.0);
+    float bin = min(floor(k * binCount), binCount - 1.0);
+
+    // Each instance is a quad, that covers exactly one texel (bin) of the histogram.
+    float x = (bin + vertexPosition.x) / binCount * 2.0 - 1.0;
+    gl_Position = vec4(x, vertexPosition.y * 2.0 - 1.0, 0.0, 1.0);
+}
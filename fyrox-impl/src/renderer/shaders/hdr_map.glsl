@@ -1,6 +1,7 @@
 uniform sampler2D hdrSampler;
 uniform sampler2D lumSampler;
 uniform sampler2D bloomSampler;
+uniform float bloomIntensity;
 uniform sampler3D colorMapSampler;
 uniform bool useColorGrading;
 uniform float keyValue;
@@ -8,6 +9,10 @@ uniform float minLuminance;
 uniform float maxLuminance;
 uniform bool autoExposure;
 uniform float fixedExposure;
+uniform bool physicalExposure;
+uniform float minEv;
+uniform float maxEv;
+uniform float exposureCompensation;
 
 in vec2 texCoord;
 
@@ -25,13 +30,19 @@ vec3 ColorGrading(vec3 color) {
 void main() {
     vec4 hdrColor = texture(hdrSampler, texCoord);
 
-    hdrColor += texture(bloomSampler, texCoord);
+    hdrColor.rgb += texture(bloomSampler, texCoord).rgb * bloomIntensity;
 
     float luminance = texture(lumSampler, vec2(0.5, 0.5)).r;
 
     float exposure;
     if (autoExposure) {
-        exposure = keyValue / clamp(luminance, minLuminance, maxLuminance);
+        if (physicalExposure) {
+            // Saturation-based exposure for ISO 100 and calibration constant K = 12.5.
+            float ev100 = clamp(log2(max(luminance, 1.0e-6) * 100.0 / 12.5), minEv, maxEv) - exposureCompensation;
+            exposure = 1.0 / (1.2 * exp2(ev100));
+        } else {
+            exposure = keyValue / clamp(luminance, minLuminance, maxLuminance);
+        }
     } else {
         exposure = fixedExposure;
     }
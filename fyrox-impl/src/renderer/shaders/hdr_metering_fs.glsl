@@ -0,0 +1,37 @@
+uniform sampler2D histogramSampler;
+uniform float minLogLum;
+uniform float logLumRange;
+uniform float lowPercentile;
+uniform float highPercentile;
+
+// Must be in sync with the size of the histogram texture.
+const int binCount = 64;
+
+out float outLum;
+
+void main()
+{
+    float total = 0.0;
+    for (int i = 0; i < binCount; ++i) {
+        total += texelFetch(histogramSampler, ivec2(i, 0), 0).r;
+    }
+
+    float low = total * lowPercentile;
+    float high = total * max(highPercentile, lowPercentile);
+
+    // Average log-luminance of the pixels between the percentiles.
+    float cumulative = 0.0;
+    float sum = 0.0;
+    float weight = 0.0;
+    for (int i = 0; i < binCount; ++i) {
+        float count = texelFetch(histogramSampler, ivec2(i, 0), 0).r;
+        float inRange = clamp(cumulative + count, low, high) - clamp(cumulative, low, high);
+        cumulative += count;
+
+        float logLum = minLogLum + (float(i) + 0.5) / float(binCount) * logLumRange;
+        sum += logLum * inRange;
+        weight += inRange;
+    }
+
+    outLum = weight > 0.0 ? exp2(sum / weight) : exp2(minLogLum);
+}
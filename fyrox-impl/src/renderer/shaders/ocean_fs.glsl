@@ -0,0 +1,66 @@
+// Shading of the ocean surface: Fresnel reflection of the sky, specular highlight of the sun, mix of
+// deep and shallow colors depending on the thickness of the water, and foam on the crests of the waves.
+
+uniform sampler2D sceneDepth;
+uniform mat4 invViewProjection;
+uniform vec3 cameraPosition;
+uniform float time;
+uniform int waveCount;
+uniform vec4 waveDirections[8];
+uniform vec4 waveAmplitudes[8];
+uniform vec4 deepColor;
+uniform vec4 shallowColor;
+uniform vec4 skyColor;
+uniform vec4 foamColor;
+uniform float transparencyDepth;
+uniform vec3 sunDirection;
+uniform vec4 sunColor;
+
+in vec3 worldPosition;
+in vec2 surfacePosition;
+
+out vec4 FragColor;
+
+void main()
+{
+    // Normal is calculated per-pixel, it gives much more detail than the grid could provide. Jacobian
+    // of the horizontal displacement shows where the surface is compressed (at the crests).
+    vec3 normal = vec3(0.0, 1.0, 0.0);
+    float jacobian = 1.0;
+    for (int i = 0; i < waveCount; ++i) {
+        vec4 d = waveDirections[i];
+        vec4 a = waveAmplitudes[i];
+        float theta = d.z * dot(d.xy, surfacePosition) - d.w * time + a.z;
+        float slope = d.z * a.x * cos(theta);
+        normal.xz -= d.xy * slope;
+        float compression = d.z * a.y * sin(theta);
+        normal.y -= compression;
+        jacobian -= compression;
+    }
+    normal = normalize(normal);
+
+    vec3 toCamera = normalize(cameraPosition - worldPosition);
+    // The surface could be seen from below.
+    if (dot(normal, toCamera) < 0.0) {
+        normal = -normal;
+    }
+
+    float fresnel = 0.02 + 0.98 * pow(1.0 - max(dot(normal, toCamera), 0.0), 5.0);
+
+    // Thickness of the water between the surface and the underwater geometry.
+    vec2 screenUv = gl_FragCoord.xy / vec2(textureSize(sceneDepth, 0));
+    float depth = texture(sceneDepth, screenUv).r;
+    vec4 sceneWorld = invViewProjection * vec4(vec3(screenUv, depth) * 2.0 - 1.0, 1.0);
+    float thickness = distance(sceneWorld.xyz / sceneWorld.w, worldPosition);
+    float opacity = clamp(thickness / max(transparencyDepth, 1.0e-3), 0.0, 1.0);
+
+    vec3 color = mix(mix(shallowColor.rgb, deepColor.rgb, opacity), skyColor.rgb, fresnel);
+
+    vec3 halfway = normalize(sunDirection + toCamera);
+    color += sunColor.rgb * pow(max(dot(normal, halfway), 0.0), 256.0);
+
+    float foam = foamColor.a * (1.0 - smoothstep(0.1, 0.5, jacobian));
+    color = mix(color, foamColor.rgb, foam);
+
+    FragColor = vec4(color, max(max(opacity, fresnel), foam));
+}
@@ -0,0 +1,57 @@
+// Projected grid: vertices of a screen-space grid are projected onto the plane of the ocean and then
+// displaced by Gerstner waves. Waves must be in sync with `sample_waves` of the ocean node.
+
+layout(location = 0) in vec3 vertexPosition;
+
+uniform mat4 viewProjection;
+uniform mat4 invViewProjection;
+uniform float oceanLevel;
+uniform float maxDistance;
+uniform float time;
+uniform int waveCount;
+// xy - direction, z - wave number, w - angular frequency.
+uniform vec4 waveDirections[8];
+// x - amplitude, y - horizontal amplitude, z - phase.
+uniform vec4 waveAmplitudes[8];
+
+out vec3 worldPosition;
+out vec2 surfacePosition;
+
+vec3 Unproject(vec2 ndc, float z)
+{
+    vec4 p = invViewProjection * vec4(ndc, z, 1.0);
+    return p.xyz / p.w;
+}
+
+void main()
+{
+    // The grid is slightly larger than the screen, so displaced edges of the surface do not reveal
+    // gaps.
+    vec2 ndc = (vertexPosition.xy * 2.0 - 1.0) * 1.2;
+    vec3 nearPoint = Unproject(ndc, -1.0);
+    vec3 dir = normalize(Unproject(ndc, 1.0) - nearPoint);
+
+    float t = maxDistance;
+    if (abs(dir.y) > 1.0e-5) {
+        float hit = (oceanLevel - nearPoint.y) / dir.y;
+        if (hit > 0.0) {
+            t = min(hit, maxDistance);
+        }
+    }
+
+    // Rays, that miss the plane, are flattened onto it, which gives the horizon.
+    vec2 origin = (nearPoint + dir * t).xz;
+
+    vec3 position = vec3(origin.x, oceanLevel, origin.y);
+    for (int i = 0; i < waveCount; ++i) {
+        vec4 d = waveDirections[i];
+        vec4 a = waveAmplitudes[i];
+        float theta = d.z * dot(d.xy, origin) - d.w * time + a.z;
+        position.xz += d.xy * (a.y * cos(theta));
+        position.y += a.x * sin(theta);
+    }
+
+    worldPosition = position;
+    surfacePosition = origin;
+    gl_Position = viewProjection * vec4(position, 1.0);
+}
@@ -0,0 +1,25 @@
+// Composites weighted blended order-independent transparency targets onto the frame. Both targets have
+// the same size as the frame, so they're fetched directly using fragment coordinates.
+
+uniform sampler2D accumulationTexture;
+uniform sampler2D weightTexture;
+
+out vec4 outColor;
+
+void main() {
+    ivec2 fragCoord = ivec2(gl_FragCoord.xy);
+
+    vec4 accumulation = texelFetch(accumulationTexture, fragCoord, 0);
+
+    // Revealage is stored in alpha, 1.0 means that there's nothing transparent in this pixel.
+    float revealage = accumulation.a;
+    if (revealage >= 1.0) {
+        discard;
+    }
+
+    float weight = texelFetch(weightTexture, fragCoord, 0).r;
+
+    vec3 averageColor = accumulation.rgb / max(weight, 1.0e-5);
+
+    outColor = vec4(averageColor, 1.0 - revealage);
+}
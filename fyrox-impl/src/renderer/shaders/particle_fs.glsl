@@ -0,0 +1,19 @@
+uniform sampler2D diffuseTexture;
+uniform sampler2D sceneDepth;
+uniform float softBoundarySharpnessFactor;
+uniform float zNear;
+uniform float zFar;
+
+in vec2 texCoord;
+in vec4 color;
+flat in float softDistance;
+
+out vec4 FragColor;
+
+void main()
+{
+    // The same as the standard particle system shader.
+    float fadeDistance = softDistance > 0.0 ? softDistance : 1.0 / max(softBoundarySharpnessFactor, 0.000001);
+    FragColor = color * S_SRGBToLinear(texture(diffuseTexture, texCoord)).r;
+    FragColor.a *= S_SoftParticleFade(sceneDepth, gl_FragCoord, zNear, zFar, fadeDistance);
+}
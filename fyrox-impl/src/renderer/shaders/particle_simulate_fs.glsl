@@ -0,0 +1,44 @@
+// Advances GPU-simulated particles by one step. Every texel of the state textures is a particle
+// slot, dead particles have zero lifetime.
+
+uniform sampler2D positionAgeSampler;
+uniform sampler2D velocityLifetimeSampler;
+uniform sampler2D sizeRotationSampler;
+uniform sampler2D parametersSampler;
+uniform vec3 acceleration;
+uniform float dt;
+
+layout(location = 0) out vec4 outPositionAge;
+layout(location = 1) out vec4 outVelocityLifetime;
+layout(location = 2) out vec4 outSizeRotation;
+layout(location = 3) out vec4 outParameters;
+
+void main() {
+    ivec2 texel = ivec2(gl_FragCoord.xy);
+    vec4 positionAge = texelFetch(positionAgeSampler, texel, 0);
+    vec4 velocityLifetime = texelFetch(velocityLifetimeSampler, texel, 0);
+    vec4 sizeRotation = texelFetch(sizeRotationSampler, texel, 0);
+    vec4 parameters = texelFetch(parametersSampler, texel, 0);
+
+    // Particles, that were spawned in the middle of the step, are simulated only for the rest of it.
+    float stepDt = max(dt - parameters.y, 0.0);
+    parameters.y = 0.0;
+
+    if (velocityLifetime.w > 0.0 && stepDt > 0.0) {
+        positionAge.w += stepDt;
+        if (positionAge.w >= velocityLifetime.w) {
+            velocityLifetime.w = 0.0;
+        } else {
+            // The same integration as for CPU particles.
+            velocityLifetime.xyz += acceleration * stepDt * stepDt;
+            positionAge.xyz += velocityLifetime.xyz;
+            sizeRotation.x = max(sizeRotation.x + sizeRotation.y * stepDt, 0.0);
+            sizeRotation.z += sizeRotation.w * stepDt;
+        }
+    }
+
+    outPositionAge = positionAge;
+    outVelocityLifetime = velocityLifetime;
+    outSizeRotation = sizeRotation;
+    outParameters = parameters;
+}
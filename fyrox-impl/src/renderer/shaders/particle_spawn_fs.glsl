@@ -0,0 +1,16 @@
+flat in vec4 positionAge;
+flat in vec4 velocityLifetime;
+flat in vec4 sizeRotation;
+flat in vec4 parameters;
+
+layout(location = 0) out vec4 outPositionAge;
+layout(location = 1) out vec4 outVelocityLifetime;
+layout(location = 2) out vec4 outSizeRotation;
+layout(location = 3) out vec4 outParameters;
+
+void main() {
+    outPositionAge = positionAge;
+    outVelocityLifetime = velocityLifetime;
+    outSizeRotation = sizeRotation;
+    outParameters = parameters;
+}
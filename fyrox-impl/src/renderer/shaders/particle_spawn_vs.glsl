@@ -0,0 +1,26 @@
+// Writes new particles into their slots. Every spawned particle is a point, that covers exactly one
+// texel of the state textures.
+
+layout(location = 0) in vec2 slotPosition;
+layout(location = 1) in vec4 spawnPositionAge;
+layout(location = 2) in vec4 spawnVelocityLifetime;
+layout(location = 3) in vec4 spawnSizeRotation;
+layout(location = 4) in vec4 spawnParameters;
+
+uniform vec2 stateSize;
+
+flat out vec4 positionAge;
+flat out vec4 velocityLifetime;
+flat out vec4 sizeRotation;
+flat out vec4 parameters;
+
+void main()
+{
+    positionAge = spawnPositionAge;
+    velocityLifetime = spawnVelocityLifetime;
+    sizeRotation = spawnSizeRotation;
+    parameters = spawnParameters;
+
+    gl_PointSize = 1.0;
+    gl_Position = vec4((slotPosition + 0.5) / stateSize * 2.0 - 1.0, 0.0, 1.0);
+}
@@ -0,0 +1,76 @@
+// Renders GPU-simulated particles. Every instance is a particle slot, dead particles are moved out
+// of the view volume.
+
+layout(location = 0) in vec3 vertexPosition;
+
+uniform sampler2D positionAgeSampler;
+uniform sampler2D velocityLifetimeSampler;
+uniform sampler2D sizeRotationSampler;
+uniform sampler2D parametersSampler;
+uniform sampler2D colorOverLifetime;
+uniform int stateWidth;
+uniform mat4 viewProjection;
+uniform mat4 worldMatrix;
+uniform vec3 cameraPosition;
+uniform vec3 cameraUpVector;
+uniform vec3 cameraSideVector;
+// xyz - axis, w - billboard mode: 0 - face camera, 1 - velocity aligned, 2 - axis locked.
+uniform vec4 emitterAxes[16];
+uniform float emitterSoftDistances[16];
+
+out vec2 texCoord;
+out vec4 color;
+flat out float softDistance;
+
+vec2 rotateVec2(vec2 v, float angle)
+{
+    float c = cos(angle);
+    float s = sin(angle);
+    mat2 m = mat2(c, -s, s, c);
+    return m * v;
+}
+
+void main()
+{
+    ivec2 texel = ivec2(gl_InstanceID % stateWidth, gl_InstanceID / stateWidth);
+    vec4 velocityLifetime = texelFetch(velocityLifetimeSampler, texel, 0);
+
+    texCoord = vertexPosition.xy;
+
+    if (velocityLifetime.w <= 0.0) {
+        color = vec4(0.0);
+        softDistance = 0.0;
+        gl_Position = vec4(2.0, 2.0, 2.0, 1.0);
+        return;
+    }
+
+    vec4 positionAge = texelFetch(positionAgeSampler, texel, 0);
+    vec4 sizeRotation = texelFetch(sizeRotationSampler, texel, 0);
+    vec4 parameters = texelFetch(parametersSampler, texel, 0);
+
+    int emitterIndex = clamp(int(parameters.x), 0, 15);
+    vec4 emitterAxis = emitterAxes[emitterIndex];
+    vec3 axis = vec3(0.0);
+    if (emitterAxis.w > 1.5) {
+        axis = emitterAxis.xyz;
+    } else if (emitterAxis.w > 0.5) {
+        axis = velocityLifetime.xyz;
+    }
+    axis = mat3(worldMatrix) * axis;
+    softDistance = emitterSoftDistances[emitterIndex];
+
+    float k = clamp(positionAge.w / velocityLifetime.w, 0.0, 1.0);
+    color = S_SRGBToLinear(textureLod(colorOverLifetime, vec2(k, 0.5), 0.0));
+
+    vec2 vertexOffset = rotateVec2(vertexPosition.xy * 2.0 - 1.0, sizeRotation.z);
+    vec4 worldPosition = worldMatrix * vec4(positionAge.xyz, 1.0);
+    vec3 offset = S_BillboardOffset(
+        vertexOffset,
+        worldPosition.xyz,
+        axis,
+        cameraPosition,
+        cameraSideVector,
+        cameraUpVector
+    ) * sizeRotation.x;
+    gl_Position = viewProjection * vec4(worldPosition.xyz + offset, 1.0);
+}
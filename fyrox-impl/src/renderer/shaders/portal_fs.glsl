@@ -0,0 +1,20 @@
+// Fills the pixels of a portal (marked in the stencil buffer) with the frame rendered from the virtual
+// camera of the portal. The frame has the same size as the current one, so it is fetched directly using
+// fragment coordinates.
+
+uniform sampler2D viewTexture;
+uniform bool useView;
+uniform vec4 fallbackColor;
+
+out vec4 FragColor;
+
+in vec2 texCoord;
+
+void main()
+{
+    if (useView) {
+        FragColor = vec4(texelFetch(viewTexture, ivec2(gl_FragCoord.xy), 0).rgb, 1.0);
+    } else {
+        FragColor = fallbackColor;
+    }
+}
@@ -0,0 +1,27 @@
+uniform vec4 color;
+uniform bool snow;
+
+in vec2 texCoord;
+in float alpha;
+in float splash;
+
+out vec4 FragColor;
+
+void main()
+{
+    vec2 p = texCoord * 2.0 - 1.0;
+
+    float shape;
+    if (snow || splash > 0.5) {
+        shape = 1.0 - smoothstep(0.5, 1.0, length(p));
+    } else {
+        shape = (1.0 - abs(p.x)) * (1.0 - smoothstep(0.7, 1.0, abs(p.y)));
+    }
+
+    float a = color.a * alpha * shape;
+    if (a < 0.004) {
+        discard;
+    }
+
+    FragColor = vec4(color.rgb, a);
+}
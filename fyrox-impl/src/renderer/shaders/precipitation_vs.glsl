@@ -0,0 +1,82 @@
+layout(location = 0) in vec3 vertexPosition;
+layout(location = 1) in vec2 vertexTexCoord;
+
+uniform mat4 viewProjection;
+uniform mat4 invViewProjection;
+uniform sampler2D sceneDepth;
+uniform vec3 cameraPosition;
+uniform vec3 cameraUpVector;
+uniform vec3 cameraSideVector;
+uniform vec3 windVelocity;
+uniform float time;
+uniform float areaSize;
+uniform float fallSpeed;
+uniform vec2 particleSize;
+uniform float collisionThickness;
+uniform bool snow;
+
+out vec2 texCoord;
+out float alpha;
+out float splash;
+
+float Hash(float n)
+{
+    return fract(sin(n) * 43758.5453123);
+}
+
+void main()
+{
+    float id = float(gl_InstanceID);
+
+    // Particles are stateless: position of each particle is defined by its id and time only.
+    vec3 seed = vec3(Hash(id * 1.31), Hash(id * 7.17 + 3.1), Hash(id * 3.73 + 11.7));
+    vec3 velocity = vec3(0.0, -fallSpeed * mix(0.8, 1.2, Hash(id * 5.3 + 1.9)), 0.0) + windVelocity;
+    vec3 offset = seed * areaSize + velocity * time;
+    if (snow) {
+        float phase = time + id;
+        offset.xz += vec2(sin(phase * 1.7), cos(phase * 1.3)) * 0.3;
+    }
+
+    // Wrap the particle in a box around the camera.
+    vec3 boxOrigin = cameraPosition - vec3(0.5 * areaSize);
+    vec3 center = boxOrigin + mod(offset - boxOrigin, areaSize);
+
+    // Fade out particles near the edges of the box to hide wrapping.
+    vec3 edge = abs(center - cameraPosition) / (0.5 * areaSize);
+    alpha = 1.0 - smoothstep(0.7, 1.0, max(edge.x, max(edge.y, edge.z)));
+    splash = 0.0;
+
+    // Collision with the depth buffer. A particle, that is behind visible geometry, is either
+    // turned into a splash on the surface or hidden, if it is too deep.
+    vec4 clipPosition = viewProjection * vec4(center, 1.0);
+    vec2 screenPos = clipPosition.xy / clipPosition.w * 0.5 + 0.5;
+    if (clipPosition.w > 0.0 && all(greaterThanEqual(screenPos, vec2(0.0))) && all(lessThanEqual(screenPos, vec2(1.0)))) {
+        float depth = textureLod(sceneDepth, screenPos, 0.0).r;
+        vec3 surface = S_UnProject(vec3(screenPos, depth), invViewProjection);
+        vec3 toCamera = cameraPosition - surface;
+        float penetration = distance(center, cameraPosition) - length(toCamera);
+        if (penetration > collisionThickness) {
+            alpha = 0.0;
+        } else if (penetration > 0.0) {
+            splash = 1.0;
+            // Slightly move the splash towards the camera to prevent z-fighting.
+            center = surface + normalize(toCamera) * 0.05;
+        }
+    }
+
+    vec2 corner = vertexPosition.xy - 0.5;
+    vec3 worldPosition;
+    if (splash > 0.5) {
+        worldPosition = center + (cameraSideVector * corner.x + cameraUpVector * corner.y) * particleSize.y * 0.25;
+    } else if (snow) {
+        worldPosition = center + (cameraSideVector * corner.x + cameraUpVector * corner.y) * particleSize.x;
+    } else {
+        // Rain drops are streaks stretched along their velocity.
+        vec3 direction = normalize(velocity);
+        vec3 side = normalize(cross(direction, center - cameraPosition));
+        worldPosition = center + side * corner.x * particleSize.x + direction * corner.y * particleSize.y;
+    }
+
+    texCoord = vertexTexCoord;
+    gl_Position = alpha > 0.0 ? viewProjection * vec4(worldPosition, 1.0) : vec4(2.0, 2.0, 2.0, 1.0);
+}
@@ -0,0 +1,127 @@
+// Adds diffuse indirect light of a probe volume. See `renderer/probe_volume.rs` for the layout of
+// the probe texture.
+
+uniform sampler2D depthTexture;
+uniform sampler2D colorTexture;
+uniform sampler2D normalTexture;
+uniform sampler2D aoSampler;
+uniform sampler3D probeTexture;
+
+uniform mat4 invViewProj;
+uniform mat4 volumeTransform;
+uniform mat4 invVolumeTransform;
+uniform vec3 probeCount;
+uniform float intensity;
+uniform float normalBias;
+
+in vec2 texCoord;
+out vec4 FragColor;
+
+const int SLICES_PER_PROBE = 9;
+
+vec4 FetchProbe(ivec3 probe, int slice)
+{
+    return texelFetch(probeTexture, ivec3(probe.x, probe.y, probe.z * SLICES_PER_PROBE + slice), 0);
+}
+
+vec3 ProbeIrradiance(ivec3 probe, vec3 n)
+{
+    vec3 sq = n * n;
+    ivec3 faces = ivec3(n.x < 0.0 ? 1 : 0, n.y < 0.0 ? 3 : 2, n.z < 0.0 ? 5 : 4);
+    return sq.x * FetchProbe(probe, faces.x).rgb +
+           sq.y * FetchProbe(probe, faces.y).rgb +
+           sq.z * FetchProbe(probe, faces.z).rgb;
+}
+
+// Mean distance and mean squared distance from the probe in the given direction.
+vec2 ProbeDepth(ivec3 probe, vec3 dir)
+{
+    vec3 sq = dir * dir;
+    vec4 x = FetchProbe(probe, 6);
+    vec4 y = FetchProbe(probe, 7);
+    vec4 z = FetchProbe(probe, 8);
+    return sq.x * (dir.x >= 0.0 ? x.xy : x.zw) +
+           sq.y * (dir.y >= 0.0 ? y.xy : y.zw) +
+           sq.z * (dir.z >= 0.0 ? z.xy : z.zw);
+}
+
+void main()
+{
+    float depth = texture(depthTexture, texCoord).r;
+    if (depth >= 1.0) {
+        discard;
+    }
+
+    vec3 fragmentPosition = S_UnProject(vec3(texCoord, depth), invViewProj);
+    vec3 normal = normalize(texture(normalTexture, texCoord).xyz * 2.0 - 1.0);
+    vec3 samplePosition = fragmentPosition + normal * normalBias;
+
+    vec3 local = (invVolumeTransform * vec4(samplePosition, 1.0)).xyz;
+    vec3 edgeDistance = vec3(0.5) - abs(local);
+    if (any(lessThan(edgeDistance, vec3(0.0)))) {
+        discard;
+    }
+
+    // Fade out the light near the borders of the volume to hide the seams.
+    vec3 fadeDistance = clamp(edgeDistance * probeCount * 2.0, 0.0, 1.0);
+    float fade = fadeDistance.x * fadeDistance.y * fadeDistance.z;
+
+    vec3 grid = (local + 0.5) * probeCount - 0.5;
+    ivec3 maxProbe = ivec3(probeCount) - 1;
+    ivec3 baseProbe = clamp(ivec3(floor(grid)), ivec3(0), maxProbe);
+    vec3 alpha = clamp(grid - vec3(baseProbe), 0.0, 1.0);
+
+    vec3 irradiance = vec3(0.0);
+    float weightSum = 0.0;
+    for (int i = 0; i < 8; ++i) {
+        ivec3 offset = ivec3(i & 1, (i >> 1) & 1, (i >> 2) & 1);
+        ivec3 probe = min(baseProbe + offset, maxProbe);
+
+        // Probes, that were not updated yet, are skipped.
+        if (FetchProbe(probe, 0).a < 0.5) {
+            continue;
+        }
+
+        vec3 probeLocal = (vec3(probe) + 0.5) / probeCount - 0.5;
+        vec3 probePosition = (volumeTransform * vec4(probeLocal, 1.0)).xyz;
+
+        vec3 trilinear = mix(1.0 - alpha, alpha, vec3(offset));
+        float weight = trilinear.x * trilinear.y * trilinear.z;
+
+        // Probes behind the surface contribute less.
+        vec3 toProbe = probePosition - fragmentPosition;
+        float toProbeLength = length(toProbe);
+        vec3 toProbeDir = toProbeLength > 0.0 ? toProbe / toProbeLength : normal;
+        float backface = (dot(toProbeDir, normal) + 1.0) * 0.5;
+        weight *= backface * backface + 0.2;
+
+        // Chebyshev visibility test, it prevents light leaking through thin walls.
+        vec3 probeToPoint = samplePosition - probePosition;
+        float distanceToPoint = length(probeToPoint);
+        if (distanceToPoint > 0.0) {
+            vec2 moments = ProbeDepth(probe, probeToPoint / distanceToPoint);
+            if (distanceToPoint > moments.x) {
+                float variance = abs(moments.y - moments.x * moments.x);
+                float delta = distanceToPoint - moments.x;
+                float chebyshev = variance / (variance + delta * delta);
+                weight *= max(chebyshev * chebyshev * chebyshev, 0.0);
+            }
+        }
+
+        weight = max(weight, 0.0001);
+
+        irradiance += ProbeIrradiance(probe, normal) * weight;
+        weightSum += weight;
+    }
+
+    if (weightSum <= 0.0) {
+        discard;
+    }
+
+    irradiance /= weightSum;
+
+    vec3 albedo = S_SRGBToLinear(texture(colorTexture, texCoord)).rgb;
+    float ambientOcclusion = texture(aoSampler, texCoord).r;
+
+    FragColor = vec4(albedo * irradiance * intensity * ambientOcclusion * fade, 0.0);
+}
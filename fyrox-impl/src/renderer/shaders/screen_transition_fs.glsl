@@ -0,0 +1,29 @@
+uniform sampler2D frameTexture;
+uniform vec2 inverseFrameSize;
+uniform float blurRadius;
+uniform float brightness;
+uniform float opacity;
+
+in vec2 texCoord;
+out vec4 fragColor;
+
+const int HALF_KERNEL_SIZE = 3;
+
+void main()
+{
+    vec3 color = vec3(0.0);
+    float totalWeight = 0.0;
+
+    // Gaussian-like blur with fixed amount of taps, distance between taps grows with the radius.
+    for (int y = -HALF_KERNEL_SIZE; y <= HALF_KERNEL_SIZE; ++y) {
+        for (int x = -HALF_KERNEL_SIZE; x <= HALF_KERNEL_SIZE; ++x) {
+            vec2 offset = vec2(float(x), float(y)) / float(HALF_KERNEL_SIZE);
+            float weight = exp(-2.0 * dot(offset, offset));
+            vec2 uv = texCoord + offset * blurRadius * inverseFrameSize;
+            color += weight * texture(frameTexture, uv).rgb;
+            totalWeight += weight;
+        }
+    }
+
+    fragColor = vec4(brightness * color / totalWeight, opacity);
+}
@@ -0,0 +1,42 @@
+// Moves a field of the smoke simulation along the velocity field (semi-Lagrangian advection),
+// then adds the source and, for velocity field, the forces.
+
+uniform sampler2D velocitySampler;
+uniform sampler2D fieldSampler;
+uniform sampler2D scalarSampler;
+uniform int gridSize;
+uniform float dt;
+uniform vec4 decay;
+uniform vec3 sourcePosition;
+uniform float sourceRadius;
+uniform vec4 sourceAmount;
+uniform bool isVelocity;
+uniform float buoyancy;
+uniform float weight;
+
+out vec4 outValue;
+
+void main() {
+    ivec3 cell = S_VolumeAtlasCell(gl_FragCoord.xy, gridSize);
+    float size = float(gridSize);
+    vec3 position = vec3(cell) + 0.5;
+
+    vec3 velocity = S_FetchVolumeAtlas(velocitySampler, cell, gridSize).xyz;
+    vec4 value = S_SampleVolumeAtlas(fieldSampler, (position - velocity * dt) / size, size) * decay;
+
+    float w = 0.0;
+    if (sourceRadius > 0.0) {
+        w = clamp(1.0 - length(position - sourcePosition) / sourceRadius, 0.0, 1.0);
+    }
+
+    if (isVelocity) {
+        // Hot smoke rises, dense smoke sinks.
+        vec4 scalars = S_FetchVolumeAtlas(scalarSampler, cell, gridSize);
+        value.y += (buoyancy * scalars.y - weight * scalars.x) * size * dt;
+        value.xyz = mix(value.xyz, sourceAmount.xyz, w);
+    } else {
+        value += sourceAmount * w * dt;
+    }
+
+    outValue = value;
+}
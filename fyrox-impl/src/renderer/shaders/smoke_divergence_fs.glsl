@@ -0,0 +1,24 @@
+uniform sampler2D velocitySampler;
+uniform int gridSize;
+
+out vec4 outDivergence;
+
+float Velocity(ivec3 cell, int axis) {
+    // Walls of the grid are solid, so there's no velocity outside.
+    if (any(lessThan(cell, ivec3(0))) || any(greaterThanEqual(cell, ivec3(gridSize)))) {
+        return 0.0;
+    }
+    return S_FetchVolumeAtlas(velocitySampler, cell, gridSize)[axis];
+}
+
+void main() {
+    ivec3 cell = S_VolumeAtlasCell(gl_FragCoord.xy, gridSize);
+
+    float divergence = 0.5 * (
+        Velocity(cell + ivec3(1, 0, 0), 0) - Velocity(cell - ivec3(1, 0, 0), 0) +
+        Velocity(cell + ivec3(0, 1, 0), 1) - Velocity(cell - ivec3(0, 1, 0), 1) +
+        Velocity(cell + ivec3(0, 0, 1), 2) - Velocity(cell - ivec3(0, 0, 1), 2)
+    );
+
+    outDivergence = vec4(divergence, 0.0, 0.0, 0.0);
+}
@@ -0,0 +1,71 @@
+// Ray marching of the smoke volume. The volume is drawn using its back faces, so it is visible even
+// if the camera is inside of it.
+
+uniform sampler2D densitySampler;
+uniform sampler2D sceneDepth;
+uniform mat4 invViewProjection;
+uniform mat4 invWorldMatrix;
+uniform vec3 cameraPosition;
+uniform float gridSize;
+uniform int stepCount;
+uniform vec4 smokeColor;
+uniform vec4 fireColor;
+uniform float fireIntensity;
+uniform float absorption;
+
+in vec3 localPosition;
+
+out vec4 FragColor;
+
+void main()
+{
+    // Everything is done in local space of the volume, the back face is at t = 1.
+    vec3 origin = (invWorldMatrix * vec4(cameraPosition, 1.0)).xyz;
+    vec3 dir = localPosition - origin;
+
+    vec3 t0 = (vec3(-0.5) - origin) / dir;
+    vec3 t1 = (vec3(0.5) - origin) / dir;
+    vec3 tMin = min(t0, t1);
+    float tNear = max(max(max(tMin.x, tMin.y), tMin.z), 0.0);
+
+    // Stop at the visible geometry.
+    vec2 screenUv = gl_FragCoord.xy / vec2(textureSize(sceneDepth, 0));
+    float depth = texture(sceneDepth, screenUv).r;
+    vec4 sceneWorld = invViewProjection * vec4(vec3(screenUv, depth) * 2.0 - 1.0, 1.0);
+    vec3 sceneLocal = (invWorldMatrix * vec4(sceneWorld.xyz / sceneWorld.w, 1.0)).xyz;
+    float tFar = min(1.0, dot(sceneLocal - origin, dir) / dot(dir, dir));
+
+    if (tFar <= tNear) {
+        discard;
+    }
+
+    // Amount of steps is proportional to the length of the ray inside the volume, the diagonal of
+    // the volume gets all the steps.
+    float segment = (tFar - tNear) * length(dir);
+    int steps = clamp(int(ceil(float(stepCount) * segment / 1.7320508)), 1, stepCount);
+    float stepLength = segment / float(steps);
+    float dt = (tFar - tNear) / float(steps);
+
+    float transmittance = 1.0;
+    vec3 color = vec3(0.0);
+    float t = tNear + 0.5 * dt;
+    for (int i = 0; i < steps; ++i) {
+        vec4 cell = S_SampleVolumeAtlas(densitySampler, origin + dir * t + 0.5, gridSize);
+        float density = max(cell.x, 0.0);
+        float temperature = max(cell.y, 0.0);
+
+        float stepTransmittance = exp(-density * absorption * stepLength);
+        vec3 emission = fireColor.rgb * fireIntensity * temperature * stepLength;
+        color += transmittance * (smokeColor.rgb * (1.0 - stepTransmittance) + emission);
+        transmittance *= stepTransmittance;
+
+        if (transmittance < 0.01) {
+            break;
+        }
+
+        t += dt;
+    }
+
+    // Premultiplied alpha.
+    FragColor = vec4(color, 1.0 - transmittance);
+}
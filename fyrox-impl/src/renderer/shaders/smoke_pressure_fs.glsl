@@ -0,0 +1,24 @@
+// A single Jacobi iteration of the pressure solver.
+
+uniform sampler2D pressureSampler;
+uniform sampler2D divergenceSampler;
+uniform int gridSize;
+
+out vec4 outPressure;
+
+float Pressure(ivec3 cell) {
+    return S_FetchVolumeAtlas(pressureSampler, cell, gridSize).x;
+}
+
+void main() {
+    ivec3 cell = S_VolumeAtlasCell(gl_FragCoord.xy, gridSize);
+
+    float sum =
+        Pressure(cell + ivec3(1, 0, 0)) + Pressure(cell - ivec3(1, 0, 0)) +
+        Pressure(cell + ivec3(0, 1, 0)) + Pressure(cell - ivec3(0, 1, 0)) +
+        Pressure(cell + ivec3(0, 0, 1)) + Pressure(cell - ivec3(0, 0, 1));
+
+    float divergence = S_FetchVolumeAtlas(divergenceSampler, cell, gridSize).x;
+
+    outPressure = vec4((sum - divergence) / 6.0, 0.0, 0.0, 0.0);
+}
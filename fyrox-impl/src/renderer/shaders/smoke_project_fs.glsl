@@ -0,0 +1,25 @@
+// Subtracts pressure gradient from the velocity, which makes the velocity field divergence-free.
+
+uniform sampler2D velocitySampler;
+uniform sampler2D pressureSampler;
+uniform int gridSize;
+
+out vec4 outVelocity;
+
+float Pressure(ivec3 cell) {
+    return S_FetchVolumeAtlas(pressureSampler, cell, gridSize).x;
+}
+
+void main() {
+    ivec3 cell = S_VolumeAtlasCell(gl_FragCoord.xy, gridSize);
+
+    vec3 gradient = 0.5 * vec3(
+        Pressure(cell + ivec3(1, 0, 0)) - Pressure(cell - ivec3(1, 0, 0)),
+        Pressure(cell + ivec3(0, 1, 0)) - Pressure(cell - ivec3(0, 1, 0)),
+        Pressure(cell + ivec3(0, 0, 1)) - Pressure(cell - ivec3(0, 0, 1))
+    );
+
+    vec4 velocity = S_FetchVolumeAtlas(velocitySampler, cell, gridSize);
+
+    outVelocity = vec4(velocity.xyz - gradient, 0.0);
+}
@@ -0,0 +1,11 @@
+layout(location = 0) in vec3 vertexPosition;
+
+uniform mat4 worldViewProjection;
+
+out vec3 localPosition;
+
+void main()
+{
+    localPosition = vertexPosition;
+    gl_Position = worldViewProjection * vec4(vertexPosition, 1.0);
+}
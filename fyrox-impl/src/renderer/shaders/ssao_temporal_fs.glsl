@@ -0,0 +1,44 @@
+// Accumulates ambient occlusion over multiple frames. History is reprojected using the camera
+// matrices of the previous frame and clamped to the neighbourhood of the current pixel to reject
+// stale values on disocclusions.
+
+uniform sampler2D currentSampler;
+uniform sampler2D historySampler;
+uniform sampler2D depthSampler;
+
+uniform mat4 inverseViewProjection;
+uniform mat4 previousViewProjection;
+uniform float historyWeight;
+
+out float finalOcclusion;
+
+in vec2 texCoord;
+
+void main() {
+    vec2 texelSize = 1.0 / vec2(textureSize(currentSampler, 0));
+
+    float current = texture(currentSampler, texCoord).r;
+    float minOcclusion = current;
+    float maxOcclusion = current;
+    for (int y = -1; y <= 1; ++y) {
+        for (int x = -1; x <= 1; ++x) {
+            float neighbour = texture(currentSampler, texCoord + vec2(float(x), float(y)) * texelSize).r;
+            minOcclusion = min(minOcclusion, neighbour);
+            maxOcclusion = max(maxOcclusion, neighbour);
+        }
+    }
+
+    float depth = texture(depthSampler, texCoord).r;
+    vec3 worldPosition = S_UnProject(vec3(texCoord, depth), inverseViewProjection);
+    vec4 previousPosition = previousViewProjection * vec4(worldPosition, 1.0);
+    vec2 previousTexCoord = previousPosition.xy / previousPosition.w * 0.5 + 0.5;
+
+    float weight = historyWeight;
+    if (any(lessThan(previousTexCoord, vec2(0.0))) || any(greaterThan(previousTexCoord, vec2(1.0)))) {
+        weight = 0.0;
+    }
+
+    float history = clamp(texture(historySampler, previousTexCoord).r, minOcclusion, maxOcclusion);
+
+    finalOcclusion = mix(current, history, weight);
+}
@@ -0,0 +1,42 @@
+// Depth-aware (bilateral) upsampling of half resolution ambient occlusion. Each of the four
+// nearest low resolution texels is weighted by the similarity of its depth with the depth of
+// the current pixel, so the occlusion does not leak over edges of objects.
+
+uniform sampler2D inputTexture;
+uniform sampler2D depthSampler;
+uniform mat4 inverseProjectionMatrix;
+
+out float finalOcclusion;
+
+in vec2 texCoord;
+
+float GetViewSpaceDepth(vec2 screenCoord) {
+    return S_UnProject(vec3(screenCoord, texture(depthSampler, screenCoord).r), inverseProjectionMatrix).z;
+}
+
+void main() {
+    ivec2 inputSize = textureSize(inputTexture, 0);
+    vec2 inputCoord = texCoord * vec2(inputSize) - 0.5;
+    vec2 base = floor(inputCoord);
+    vec2 f = inputCoord - base;
+
+    float depth = GetViewSpaceDepth(texCoord);
+
+    float result = 0.0;
+    float totalWeight = 0.0;
+    for (int y = 0; y <= 1; ++y) {
+        for (int x = 0; x <= 1; ++x) {
+            ivec2 texel = clamp(ivec2(base) + ivec2(x, y), ivec2(0), inputSize - 1);
+            vec2 texelCoord = (vec2(texel) + 0.5) / vec2(inputSize);
+
+            float bilinearWeight = (x == 0 ? 1.0 - f.x : f.x) * (y == 0 ? 1.0 - f.y : f.y);
+            float depthWeight = 1.0 / (0.0001 + abs(GetViewSpaceDepth(texelCoord) - depth));
+            float weight = bilinearWeight * depthWeight;
+
+            result += texelFetch(inputTexture, texel, 0).r * weight;
+            totalWeight += weight;
+        }
+    }
+
+    finalOcclusion = totalWeight > 0.0 ? result / totalWeight : texture(inputTexture, texCoord).r;
+}
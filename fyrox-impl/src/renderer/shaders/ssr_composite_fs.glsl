@@ -0,0 +1,45 @@
+// Adds reflections to the lit frame. Screen-space reflections are used where they're available, the rest
+// is taken from the environment map. Reflections are weighted by Fresnel term and glossiness of surfaces.
+
+uniform sampler2D reflectionSampler;
+uniform sampler2D depthSampler;
+uniform sampler2D normalSampler;
+uniform sampler2D materialSampler;
+uniform sampler2D diffuseSampler;
+uniform samplerCube environmentMap;
+
+uniform mat4 invViewProjection;
+uniform vec3 cameraPosition;
+
+in vec2 texCoord;
+
+out vec4 FragColor;
+
+void main()
+{
+    float depth = texture(depthSampler, texCoord).r;
+    if (depth >= 1.0) {
+        discard;
+    }
+
+    vec4 material = texture(materialSampler, texCoord);
+    float metallic = material.x;
+    float roughness = material.y;
+
+    vec3 position = S_UnProject(vec3(texCoord, depth), invViewProjection);
+    vec3 normal = normalize(texture(normalSampler, texCoord).xyz * 2.0 - 1.0);
+    vec3 viewVector = normalize(cameraPosition - position);
+
+    vec3 albedo = S_SRGBToLinear(texture(diffuseSampler, texCoord)).rgb;
+    vec3 F0 = mix(vec3(0.04), albedo, metallic);
+    vec3 fresnel = S_FresnelSchlick(max(dot(normal, viewVector), 0.0), F0);
+
+    // Rough surfaces scatter reflected light in all directions, so reflections are barely visible on them.
+    float glossiness = (1.0 - roughness) * (1.0 - roughness);
+
+    vec3 fallback = S_SRGBToLinear(texture(environmentMap, reflect(-viewVector, normal))).rgb;
+    vec4 screenSpace = texture(reflectionSampler, texCoord);
+    vec3 reflection = mix(fallback, screenSpace.rgb, screenSpace.a);
+
+    FragColor = vec4(reflection * fresnel * glossiness, 1.0);
+}
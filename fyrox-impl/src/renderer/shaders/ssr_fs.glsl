@@ -0,0 +1,94 @@
+// Traces reflected rays in view space against the depth buffer. A ray is marched with a fixed step and
+// then the hit is refined using binary search. Output is the color of the lit frame at the hit point, alpha
+// contains confidence of the hit, so the reflection could be blended with the fallback.
+
+uniform sampler2D depthSampler;
+uniform sampler2D normalSampler;
+uniform sampler2D materialSampler;
+uniform sampler2D frameSampler;
+
+uniform mat4 projectionMatrix;
+uniform mat4 invProjectionMatrix;
+uniform mat3 viewMatrix;
+uniform int maxSteps;
+uniform float maxDistance;
+uniform float thickness;
+uniform float edgeFade;
+uniform float maxRoughness;
+
+in vec2 texCoord;
+
+out vec4 FragColor;
+
+const int REFINEMENT_STEPS = 8;
+
+float SceneDepth(vec2 uv)
+{
+    return S_UnProject(vec3(uv, texture(depthSampler, uv).r), invProjectionMatrix).z;
+}
+
+void main()
+{
+    FragColor = vec4(0.0);
+
+    float depth = texture(depthSampler, texCoord).r;
+    float roughness = texture(materialSampler, texCoord).y;
+    if (depth >= 1.0 || roughness > maxRoughness) {
+        return;
+    }
+
+    vec3 position = S_UnProject(vec3(texCoord, depth), invProjectionMatrix);
+    vec3 normal = normalize(viewMatrix * (texture(normalSampler, texCoord).xyz * 2.0 - 1.0));
+    vec3 direction = normalize(reflect(normalize(position), normal));
+
+    vec3 rayStep = direction * (maxDistance / float(maxSteps));
+    vec3 previous = position;
+    vec3 current = position;
+    bool hit = false;
+
+    for (int i = 0; i < maxSteps; ++i) {
+        current += rayStep;
+
+        // The ray went behind the camera.
+        if (current.z > -0.01) {
+            break;
+        }
+
+        vec2 uv = S_Project(current, projectionMatrix).xy;
+        if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+            break;
+        }
+
+        // Positive difference means that the ray is behind the surface.
+        float difference = SceneDepth(uv) - current.z;
+        if (difference > 0.0 && difference < thickness) {
+            hit = true;
+            break;
+        }
+
+        previous = current;
+    }
+
+    if (!hit) {
+        return;
+    }
+
+    for (int i = 0; i < REFINEMENT_STEPS; ++i) {
+        vec3 middle = (previous + current) * 0.5;
+        if (SceneDepth(S_Project(middle, projectionMatrix).xy) - middle.z > 0.0) {
+            current = middle;
+        } else {
+            previous = middle;
+        }
+    }
+
+    vec2 hitCoord = S_Project(current, projectionMatrix).xy;
+
+    vec2 edge = smoothstep(vec2(0.0), vec2(edgeFade), hitCoord) *
+        (vec2(1.0) - smoothstep(vec2(1.0 - edgeFade), vec2(1.0), hitCoord));
+    float screenFade = edge.x * edge.y;
+    float distanceFade = 1.0 - clamp(distance(current, position) / maxDistance, 0.0, 1.0);
+    float roughnessFade = 1.0 - roughness / maxRoughness;
+
+    FragColor = vec4(texture(frameSampler, hitCoord).rgb, screenFade * distanceFade * roughnessFade);
+}
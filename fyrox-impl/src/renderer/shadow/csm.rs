@@ -2,7 +2,9 @@ use crate::{
     core::{
         algebra::{Matrix4, Point3, Vector2, Vector3},
         math::{aabb::AxisAlignedBoundingBox, frustum::Frustum, Rect},
+        pool::Handle,
     },
+    material::GlobalMaterialProperties,
     renderer::{
         apply_material,
         bundle::{ObserverInfo, RenderDataBundleStorage},
@@ -16,15 +18,19 @@ use crate::{
             },
             state::{ColorMask, PipelineState},
         },
+        shadow::occlusion::ShadowCasterCuller,
         storage::MatrixStorageCache,
-        MaterialContext, RenderPassStatistics, ShadowMapPrecision, DIRECTIONAL_SHADOW_PASS_NAME,
+        CsmSettings, MaterialContext, RenderPassStatistics, ShadowMapPrecision,
+        DIRECTIONAL_SHADOW_PASS_NAME,
     },
     scene::{
         camera::Camera,
         graph::Graph,
-        light::directional::{DirectionalLight, FrustumSplitOptions, CSM_NUM_CASCADES},
+        light::directional::{DirectionalLight, CSM_NUM_CASCADES},
+        node::Node,
     },
 };
+use fxhash::FxHashSet;
 use fyrox_core::color::Color;
 use fyrox_core::math::Matrix4Ext;
 use std::{cell::RefCell, rc::Rc};
@@ -33,6 +39,7 @@ pub struct Cascade {
     pub frame_buffer: FrameBuffer,
     pub view_proj_matrix: Matrix4<f32>,
     pub z_far: f32,
+    pub size: usize,
 }
 
 impl Cascade {
@@ -75,6 +82,7 @@ impl Cascade {
             )?,
             view_proj_matrix: Default::default(),
             z_far: 0.0,
+            size,
         })
     }
 
@@ -89,7 +97,7 @@ impl Cascade {
 
 pub struct CsmRenderer {
     cascades: [Cascade; CSM_NUM_CASCADES],
-    size: usize,
+    sizes: [usize; CSM_NUM_CASCADES],
     precision: ShadowMapPrecision,
 }
 
@@ -107,21 +115,25 @@ pub(crate) struct CsmRenderContext<'a, 'c> {
     pub black_dummy: Rc<RefCell<GpuTexture>>,
     pub volume_dummy: Rc<RefCell<GpuTexture>>,
     pub matrix_storage: &'a mut MatrixStorageCache,
+    pub global_properties: &'a GlobalMaterialProperties,
+    pub settings: &'a CsmSettings,
+    pub light_handle: Handle<Node>,
+    pub occlusion_culler: Option<&'a mut ShadowCasterCuller>,
 }
 
 impl CsmRenderer {
     pub fn new(
         state: &PipelineState,
-        size: usize,
+        sizes: [usize; CSM_NUM_CASCADES],
         precision: ShadowMapPrecision,
     ) -> Result<Self, FrameworkError> {
         Ok(Self {
             precision,
-            size,
+            sizes,
             cascades: [
-                Cascade::new(state, size, precision)?,
-                Cascade::new(state, size, precision)?,
-                Cascade::new(state, size, precision)?,
+                Cascade::new(state, sizes[0], precision)?,
+                Cascade::new(state, sizes[1], precision)?,
+                Cascade::new(state, sizes[2], precision)?,
             ],
         })
     }
@@ -130,8 +142,8 @@ impl CsmRenderer {
         self.precision
     }
 
-    pub fn size(&self) -> usize {
-        self.size
+    pub fn sizes(&self) -> [usize; CSM_NUM_CASCADES] {
+        self.sizes
     }
 
     pub fn cascades(&self) -> &[Cascade] {
@@ -158,6 +170,10 @@ impl CsmRenderer {
             black_dummy,
             volume_dummy,
             matrix_storage,
+            global_properties,
+            settings,
+            light_handle,
+            mut occlusion_culler,
         } = ctx;
 
         let light_direction = -light
@@ -170,20 +186,10 @@ impl CsmRenderer {
             .try_normalize(f32::EPSILON)
             .unwrap_or_else(Vector3::z);
 
-        let z_values = match light.csm_options.split_options {
-            FrustumSplitOptions::Absolute { far_planes } => [
-                camera.projection().z_near(),
-                far_planes[0],
-                far_planes[1],
-                far_planes[2],
-            ],
-            FrustumSplitOptions::Relative { fractions } => [
-                camera.projection().z_near(),
-                camera.projection().z_far() * fractions[0],
-                camera.projection().z_far() * fractions[1],
-                camera.projection().z_far() * fractions[2],
-            ],
-        };
+        let z_values = settings
+            .split_options
+            .unwrap_or(light.csm_options.split_options)
+            .split_distances(camera.projection().z_near(), camera.projection().z_far());
 
         for i in 0..CSM_NUM_CASCADES {
             let z_near = z_values[i];
@@ -204,6 +210,8 @@ impl CsmRenderer {
                 Frustum::from_view_projection_matrix(projection_matrix * camera.view_matrix())
                     .unwrap_or_default();
 
+            let size = self.sizes[i];
+
             let center = frustum.center();
             let observer_position = center + light_direction;
             let light_view_matrix = Matrix4::look_at_lh(
@@ -220,6 +228,25 @@ impl CsmRenderer {
                 aabb.add_point(light_space_corner);
             }
 
+            if settings.stabilize {
+                // Use bounding sphere of the sub-frustum to make the size of the cascade
+                // independent of the camera orientation. The radius is rounded to reduce
+                // fluctuations caused by precision issues.
+                let radius = frustum
+                    .corners()
+                    .iter()
+                    .map(|corner| (corner - center).norm())
+                    .fold(0.0f32, f32::max);
+                let radius = (radius * 16.0).ceil() / 16.0;
+                let light_space_center = light_view_matrix
+                    .transform_point(&Point3::from(center))
+                    .coords;
+                aabb.min.x = light_space_center.x - radius;
+                aabb.max.x = light_space_center.x + radius;
+                aabb.min.y = light_space_center.y - radius;
+                aabb.max.y = light_space_center.y + radius;
+            }
+
             // Make sure most of the objects outside of the frustum will cast shadows.
             let z_mult = 10.0;
             if aabb.min.z < 0.0 {
@@ -233,10 +260,24 @@ impl CsmRenderer {
                 aabb.max.z *= z_mult;
             }
 
-            let cascade_projection_matrix = Matrix4::new_orthographic(
+            let mut cascade_projection_matrix = Matrix4::new_orthographic(
                 aabb.min.x, aabb.max.x, aabb.min.y, aabb.max.y, aabb.min.z, aabb.max.z,
             );
 
+            if settings.stabilize {
+                // Snap the projection of the world origin to the texel grid of the shadow map,
+                // so the cascade moves only by whole texels and shadow edges won't shimmer.
+                let half_size = size as f32 * 0.5;
+                let origin = (cascade_projection_matrix * light_view_matrix)
+                    .transform_point(&Point3::origin())
+                    .coords
+                    .xy()
+                    * half_size;
+                let offset = (origin.map(|c| c.round()) - origin) / half_size;
+                cascade_projection_matrix[(0, 3)] += offset.x;
+                cascade_projection_matrix[(1, 3)] += offset.y;
+            }
+
             let inv_view = light_view_matrix.try_inverse().unwrap();
             let camera_up = inv_view.up();
             let camera_side = inv_view.side();
@@ -245,10 +286,16 @@ impl CsmRenderer {
             self.cascades[i].view_proj_matrix = light_view_projection;
             self.cascades[i].z_far = z_far;
 
-            let viewport = Rect::new(0, 0, self.size as i32, self.size as i32);
+            let viewport = Rect::new(0, 0, size as i32, size as i32);
             let framebuffer = &mut self.cascades[i].frame_buffer;
             framebuffer.clear(state, viewport, None, Some(1.0), None);
 
+            let occlusion_view = (light_handle, i);
+            if let Some(culler) = occlusion_culler.as_deref_mut() {
+                culler.begin_view(state, occlusion_view);
+            }
+            let mut casters = FxHashSet::default();
+
             let bundle_storage = RenderDataBundleStorage::from_graph(
                 graph,
                 ObserverInfo {
@@ -257,11 +304,17 @@ impl CsmRenderer {
                     z_far,
                     view_matrix: light_view_matrix,
                     projection_matrix: cascade_projection_matrix,
+                    render_mask: light.base_light_ref().shadow_mask(),
                 },
                 DIRECTIONAL_SHADOW_PASS_NAME.clone(),
             );
 
-            for bundle in bundle_storage.bundles.iter() {
+            // Hardware-instanced bundles are supported only by forward and deferred renderers.
+            for bundle in bundle_storage
+                .bundles
+                .iter()
+                .filter(|b| b.hardware_instance_count.is_none())
+            {
                 let mut material_state = bundle.material.state();
                 let Some(material) = material_state.data() else {
                     continue;
@@ -281,7 +334,7 @@ impl CsmRenderer {
 
                 let Some(render_pass) =
                     shader_cache
-                        .get(state, material.shader())
+                        .get_for_material(state, material)
                         .and_then(|shader_set| {
                             shader_set.render_passes.get(&DIRECTIONAL_SHADOW_PASS_NAME)
                         })
@@ -290,8 +343,15 @@ impl CsmRenderer {
                 };
 
                 for instance in bundle.instances.iter() {
+                    casters.insert(instance.node_handle);
+                    if let Some(culler) = occlusion_culler.as_deref_mut() {
+                        if !culler.is_visible(occlusion_view, instance.node_handle) {
+                            continue;
+                        }
+                    }
+
                     stats += framebuffer.draw(
-                        geometry,
+                        geometry.buffer,
                         state,
                         viewport,
                         &render_pass.program,
@@ -304,10 +364,11 @@ impl CsmRenderer {
                             blend: None,
                             stencil_op: Default::default(),
                         },
-                        instance.element_range,
+                        geometry.element_range(instance.element_range)?,
                         |mut program_binding| {
                             apply_material(MaterialContext {
                                 material,
+                                global_properties,
                                 program_binding: &mut program_binding,
                                 texture_cache,
                                 matrix_storage,
@@ -315,6 +376,8 @@ impl CsmRenderer {
                                 view_projection_matrix: &light_view_projection,
                                 wvp_matrix: &(light_view_projection * instance.world_transform),
                                 bone_matrices: &instance.bone_matrices,
+                                prev_world_matrix: &instance.prev_world_transform,
+                                prev_bone_matrices: &instance.prev_bone_matrices,
                                 use_skeletal_animation: bundle.is_skinned,
                                 camera_position: &camera.global_position(),
                                 camera_up_vector: &camera_up,
@@ -331,13 +394,28 @@ impl CsmRenderer {
                                 persistent_identifier: instance.persistent_identifier,
                                 light_data: None,            // TODO
                                 ambient_light: Color::WHITE, // TODO
+                                weather: Default::default(),
                                 scene_depth: None,
+                                light_clusters: None,
                                 z_far,
                             });
                         },
                     )?;
                 }
             }
+
+            if let Some(culler) = occlusion_culler.as_deref_mut() {
+                stats += culler.end_view(
+                    state,
+                    occlusion_view,
+                    framebuffer,
+                    viewport,
+                    &light_view_projection,
+                    None,
+                    graph,
+                    &casters,
+                )?;
+            }
         }
 
         Ok(stats)
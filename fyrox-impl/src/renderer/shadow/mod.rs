@@ -1,6 +1,9 @@
 #![warn(clippy::too_many_arguments)]
 
 pub mod csm;
+// Occlusion queries are not wrapped by the framework, so the culler calls GL directly.
+#[allow(unsafe_code)]
+pub(crate) mod occlusion;
 pub mod point;
 pub mod spot;
 
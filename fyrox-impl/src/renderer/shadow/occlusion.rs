@@ -0,0 +1,277 @@
+//! Occlusion culling of shadow casters. See [`ShadowCasterCuller`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector3},
+        log::Log,
+        math::{aabb::AxisAlignedBoundingBox, Rect},
+        pool::Handle,
+    },
+    graph::BaseSceneGraph,
+    renderer::{
+        flat_shader::FlatShader,
+        framework::{
+            error::FrameworkError,
+            framebuffer::{DrawParameters, FrameBuffer},
+            geometry_buffer::{ElementRange, GeometryBuffer, GeometryBufferKind},
+            state::{ColorMask, PipelineState},
+        },
+        RenderPassStatistics,
+    },
+    scene::{graph::Graph, mesh::surface::SurfaceData, node::Node},
+};
+use fxhash::{FxHashMap, FxHashSet};
+use glow::HasContext;
+use std::rc::Weak;
+
+/// Amount of frames, after which the results of a view, that is not rendered anymore, are
+/// discarded.
+const MAX_UNUSED_FRAMES: u64 = 60;
+
+/// A view of a shadow map - a light and an index of its sub-view (cube map face or cascade).
+pub(crate) type ShadowViewKey = (Handle<Node>, usize);
+
+#[derive(Default)]
+struct ShadowView {
+    // Casters, that were hidden by other casters during the last test.
+    occluded: FxHashSet<Handle<Node>>,
+    pending: Vec<(Handle<Node>, glow::Query)>,
+    last_frame: u64,
+}
+
+/// Skips shadow casters, that are hidden from the light by other casters. Such casters do not
+/// change the shadow map, but still cost a draw call and a lot of triangles (for example, furniture
+/// in a room, that is lit by a light outside of the room).
+///
+/// After a shadow map is rendered, the bounding box of every caster is tested against its depth
+/// using hardware occlusion queries. The results are read back asynchronously (without stalling
+/// the pipeline) and used the next time the same view of the same light is rendered. Occluded
+/// casters are tested as well, so they will be rendered again as soon as they become visible to
+/// the light, with a delay of one or a few frames.
+pub(crate) struct ShadowCasterCuller {
+    state: Weak<PipelineState>,
+    supported: bool,
+    cube: GeometryBuffer,
+    shader: FlatShader,
+    views: FxHashMap<ShadowViewKey, ShadowView>,
+    free_queries: Vec<glow::Query>,
+    frame: u64,
+    culled: usize,
+}
+
+impl ShadowCasterCuller {
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            state: state.weak(),
+            supported: true,
+            cube: GeometryBuffer::from_surface_data(
+                &SurfaceData::make_cube(Matrix4::identity()),
+                GeometryBufferKind::StaticDraw,
+                state,
+            )?,
+            shader: FlatShader::new(state)?,
+            views: Default::default(),
+            free_queries: Default::default(),
+            frame: 0,
+            culled: 0,
+        })
+    }
+
+    /// Must be called once per frame before rendering any shadow map.
+    pub fn begin_frame(&mut self, state: &PipelineState) {
+        self.frame += 1;
+        self.culled = 0;
+
+        let frame = self.frame;
+        let free_queries = &mut self.free_queries;
+        self.views.retain(|_, view| {
+            let keep = frame - view.last_frame <= MAX_UNUSED_FRAMES;
+            if !keep {
+                free_queries.extend(view.pending.drain(..).map(|(_, q)| q));
+            }
+            keep
+        });
+
+        // Delete excessive queries, that could be left after a lot of casters were removed.
+        let max_free = self.views.values().map(|v| v.pending.len()).sum::<usize>();
+        while self.free_queries.len() > max_free {
+            if let Some(query) = self.free_queries.pop() {
+                unsafe { state.gl.delete_query(query) };
+            }
+        }
+    }
+
+    /// Returns amount of casters, that were skipped in the current frame.
+    pub fn culled_count(&self) -> usize {
+        self.culled
+    }
+
+    /// Reads back the results of the previous test of the given view, if they're ready. Must be
+    /// called before [`Self::is_visible`].
+    pub fn begin_view(&mut self, state: &PipelineState, key: ShadowViewKey) {
+        if !self.supported {
+            return;
+        }
+
+        let view = self.views.entry(key).or_default();
+        view.last_frame = self.frame;
+
+        let ready = view.pending.iter().all(|(_, query)| unsafe {
+            state
+                .gl
+                .get_query_parameter_u32(*query, glow::QUERY_RESULT_AVAILABLE)
+                != 0
+        });
+
+        if ready && !view.pending.is_empty() {
+            view.occluded.clear();
+            for (node, query) in view.pending.drain(..) {
+                let passed = unsafe { state.gl.get_query_parameter_u32(query, glow::QUERY_RESULT) };
+                if passed == 0 {
+                    view.occluded.insert(node);
+                }
+                self.free_queries.push(query);
+            }
+        }
+    }
+
+    /// Returns `false` if the caster was hidden from the light during the last test of the view.
+    pub fn is_visible(&mut self, key: ShadowViewKey, node: Handle<Node>) -> bool {
+        let visible = self
+            .views
+            .get(&key)
+            .map_or(true, |view| !view.occluded.contains(&node));
+        if !visible {
+            self.culled += 1;
+        }
+        visible
+    }
+
+    fn alloc_query(&mut self, state: &PipelineState) -> Option<glow::Query> {
+        if let Some(query) = self.free_queries.pop() {
+            return Some(query);
+        }
+
+        match unsafe { state.gl.create_query() } {
+            Ok(query) => Some(query),
+            Err(err) => {
+                Log::err(format!(
+                    "Unable to create occlusion query. Occlusion culling of shadow casters \
+                    will be disabled. Reason: {err}"
+                ));
+                self.supported = false;
+                None
+            }
+        }
+    }
+
+    /// Tests the bounding boxes of the given casters against the depth of the shadow map, that was
+    /// just rendered into the given frame buffer. The test is skipped, if the results of the
+    /// previous test are not ready yet. `light_position` is used to keep the casters, that contain
+    /// the light, always visible (it should be `None` for directional lights).
+    #[allow(clippy::too_many_arguments)]
+    pub fn end_view(
+        &mut self,
+        state: &PipelineState,
+        key: ShadowViewKey,
+        framebuffer: &mut FrameBuffer,
+        viewport: Rect<i32>,
+        view_projection: &Matrix4<f32>,
+        light_position: Option<Vector3<f32>>,
+        graph: &Graph,
+        casters: &FxHashSet<Handle<Node>>,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut statistics = RenderPassStatistics::default();
+
+        if !self.supported
+            || self
+                .views
+                .get(&key)
+                .map_or(true, |view| !view.pending.is_empty())
+        {
+            return Ok(statistics);
+        }
+
+        let mut pending = Vec::with_capacity(casters.len());
+        for &node in casters {
+            let Some(node_ref) = graph.try_get(node) else {
+                continue;
+            };
+
+            let aabb = node_ref.world_bounding_box();
+            if !aabb.is_valid() {
+                continue;
+            }
+
+            // Inflate the box a bit, so its faces are never behind the surfaces of the caster.
+            let size = aabb.max - aabb.min;
+            let margin = size.scale(0.01).add_scalar(0.01);
+            let aabb = AxisAlignedBoundingBox::from_min_max(aabb.min - margin, aabb.max + margin);
+
+            if light_position.map_or(false, |p| aabb.is_contains_point(p)) {
+                // The box will be clipped by the near plane, and the caster is always visible
+                // anyway.
+                continue;
+            }
+
+            let Some(query) = self.alloc_query(state) else {
+                break;
+            };
+
+            let world_matrix = Matrix4::new_translation(&aabb.center())
+                * Matrix4::new_nonuniform_scaling(&(aabb.max - aabb.min));
+
+            unsafe {
+                state.gl.begin_query(glow::ANY_SAMPLES_PASSED, query);
+            }
+            let result = framebuffer.draw(
+                &self.cube,
+                state,
+                viewport,
+                &self.shader.program,
+                &DrawParameters {
+                    cull_face: None,
+                    color_write: ColorMask::all(false),
+                    depth_write: false,
+                    stencil_test: None,
+                    depth_test: true,
+                    blend: None,
+                    stencil_op: Default::default(),
+                },
+                ElementRange::Full,
+                |mut program_binding| {
+                    program_binding
+                        .set_matrix4(&self.shader.wvp_matrix, &(view_projection * world_matrix));
+                },
+            );
+            unsafe {
+                state.gl.end_query(glow::ANY_SAMPLES_PASSED);
+            }
+
+            pending.push((node, query));
+            statistics += result?;
+        }
+
+        if let Some(view) = self.views.get_mut(&key) {
+            view.pending = pending;
+        }
+
+        Ok(statistics)
+    }
+}
+
+impl Drop for ShadowCasterCuller {
+    fn drop(&mut self) {
+        if let Some(state) = self.state.upgrade() {
+            unsafe {
+                for query in self.free_queries.drain(..).chain(
+                    self.views
+                        .drain()
+                        .flat_map(|(_, view)| view.pending.into_iter().map(|(_, q)| q)),
+                ) {
+                    state.gl.delete_query(query);
+                }
+            }
+        }
+    }
+}
@@ -3,8 +3,10 @@ use crate::{
         algebra::{Matrix4, Point3, Vector3},
         color::Color,
         math::Rect,
+        pool::Handle,
         scope_profile,
     },
+    material::GlobalMaterialProperties,
     renderer::{
         apply_material,
         bundle::{ObserverInfo, RenderDataBundleStorage},
@@ -18,13 +20,14 @@ use crate::{
             },
             state::PipelineState,
         },
-        shadow::cascade_size,
+        shadow::{cascade_size, occlusion::ShadowCasterCuller},
         storage::MatrixStorageCache,
         GeometryCache, MaterialContext, RenderPassStatistics, ShadowMapPrecision,
         POINT_SHADOW_PASS_NAME,
     },
-    scene::graph::Graph,
+    scene::{collider::BitMask, graph::Graph, node::Node},
 };
+use fxhash::FxHashSet;
 use fyrox_core::math::Matrix4Ext;
 use std::{cell::RefCell, rc::Rc};
 
@@ -55,6 +58,10 @@ pub(crate) struct PointShadowMapRenderContext<'a> {
     pub black_dummy: Rc<RefCell<GpuTexture>>,
     pub volume_dummy: Rc<RefCell<GpuTexture>>,
     pub matrix_storage: &'a mut MatrixStorageCache,
+    pub global_properties: &'a GlobalMaterialProperties,
+    pub light_handle: Handle<Node>,
+    pub render_mask: BitMask,
+    pub occlusion_culler: Option<&'a mut ShadowCasterCuller>,
 }
 
 impl PointShadowMapRenderer {
@@ -208,6 +215,10 @@ impl PointShadowMapRenderer {
             black_dummy,
             volume_dummy,
             matrix_storage,
+            global_properties,
+            light_handle,
+            render_mask,
+            mut occlusion_culler,
         } = args;
 
         let framebuffer = &mut self.cascades[cascade];
@@ -220,7 +231,7 @@ impl PointShadowMapRenderer {
         let light_projection_matrix =
             Matrix4::new_perspective(1.0, std::f32::consts::FRAC_PI_2, z_near, z_far);
 
-        for face in self.faces.iter() {
+        for (face_index, face) in self.faces.iter().enumerate() {
             framebuffer.set_cubemap_face(state, 0, face.face).clear(
                 state,
                 viewport,
@@ -241,6 +252,12 @@ impl PointShadowMapRenderer {
             let camera_up = inv_view.up();
             let camera_side = inv_view.side();
 
+            let occlusion_view = (light_handle, face_index);
+            if let Some(culler) = occlusion_culler.as_deref_mut() {
+                culler.begin_view(state, occlusion_view);
+            }
+            let mut casters = FxHashSet::default();
+
             let bundle_storage = RenderDataBundleStorage::from_graph(
                 graph,
                 ObserverInfo {
@@ -249,11 +266,17 @@ impl PointShadowMapRenderer {
                     z_far,
                     view_matrix: light_view_matrix,
                     projection_matrix: light_projection_matrix,
+                    render_mask,
                 },
                 POINT_SHADOW_PASS_NAME.clone(),
             );
 
-            for bundle in bundle_storage.bundles.iter() {
+            // Hardware-instanced bundles are supported only by forward and deferred renderers.
+            for bundle in bundle_storage
+                .bundles
+                .iter()
+                .filter(|b| b.hardware_instance_count.is_none())
+            {
                 let mut material_state = bundle.material.state();
                 let Some(material) = material_state.data() else {
                     continue;
@@ -271,23 +294,31 @@ impl PointShadowMapRenderer {
                     .and_then(|c| c.blend_shape_storage.clone());
 
                 let Some(render_pass) = shader_cache
-                    .get(state, material.shader())
+                    .get_for_material(state, material)
                     .and_then(|shader_set| shader_set.render_passes.get(&POINT_SHADOW_PASS_NAME))
                 else {
                     continue;
                 };
 
                 for instance in bundle.instances.iter() {
+                    casters.insert(instance.node_handle);
+                    if let Some(culler) = occlusion_culler.as_deref_mut() {
+                        if !culler.is_visible(occlusion_view, instance.node_handle) {
+                            continue;
+                        }
+                    }
+
                     statistics += framebuffer.draw(
-                        geometry,
+                        geometry.buffer,
                         state,
                         viewport,
                         &render_pass.program,
                         &render_pass.draw_params,
-                        instance.element_range,
+                        geometry.element_range(instance.element_range)?,
                         |mut program_binding| {
                             apply_material(MaterialContext {
                                 material,
+                                global_properties,
                                 program_binding: &mut program_binding,
                                 texture_cache,
                                 matrix_storage,
@@ -296,6 +327,8 @@ impl PointShadowMapRenderer {
                                 wvp_matrix: &(light_view_projection_matrix
                                     * instance.world_transform),
                                 bone_matrices: &instance.bone_matrices,
+                                prev_world_matrix: &instance.prev_world_transform,
+                                prev_bone_matrices: &instance.prev_bone_matrices,
                                 use_skeletal_animation: bundle.is_skinned,
                                 camera_position: &Default::default(),
                                 camera_up_vector: &camera_up,
@@ -312,13 +345,28 @@ impl PointShadowMapRenderer {
                                 persistent_identifier: instance.persistent_identifier,
                                 light_data: None,            // TODO
                                 ambient_light: Color::WHITE, // TODO
+                                weather: Default::default(),
                                 scene_depth: None,
+                                light_clusters: None,
                                 z_far,
                             });
                         },
                     )?;
                 }
             }
+
+            if let Some(culler) = occlusion_culler.as_deref_mut() {
+                statistics += culler.end_view(
+                    state,
+                    occlusion_view,
+                    framebuffer,
+                    viewport,
+                    &light_view_projection_matrix,
+                    Some(light_pos),
+                    graph,
+                    &casters,
+                )?;
+            }
         }
 
         Ok(statistics)
@@ -3,8 +3,10 @@ use crate::{
         algebra::{Matrix4, Vector3},
         color::Color,
         math::Rect,
+        pool::Handle,
         scope_profile,
     },
+    material::GlobalMaterialProperties,
     renderer::{
         apply_material,
         bundle::{ObserverInfo, RenderDataBundleStorage},
@@ -18,13 +20,14 @@ use crate::{
             },
             state::{ColorMask, PipelineState},
         },
-        shadow::cascade_size,
+        shadow::{cascade_size, occlusion::ShadowCasterCuller},
         storage::MatrixStorageCache,
         GeometryCache, MaterialContext, RenderPassStatistics, ShadowMapPrecision,
         SPOT_SHADOW_PASS_NAME,
     },
-    scene::graph::Graph,
+    scene::{collider::BitMask, graph::Graph, node::Node},
 };
+use fxhash::FxHashSet;
 use fyrox_core::math::Matrix4Ext;
 use std::{cell::RefCell, rc::Rc};
 
@@ -134,6 +137,10 @@ impl SpotShadowMapRenderer {
         black_dummy: Rc<RefCell<GpuTexture>>,
         volume_dummy: Rc<RefCell<GpuTexture>>,
         matrix_storage: &mut MatrixStorageCache,
+        global_properties: &GlobalMaterialProperties,
+        light_handle: Handle<Node>,
+        render_mask: BitMask,
+        mut occlusion_culler: Option<&mut ShadowCasterCuller>,
     ) -> Result<RenderPassStatistics, FrameworkError> {
         scope_profile!();
 
@@ -155,6 +162,7 @@ impl SpotShadowMapRenderer {
                 z_far,
                 view_matrix: light_view_matrix,
                 projection_matrix: light_projection_matrix,
+                render_mask,
             },
             SPOT_SHADOW_PASS_NAME.clone(),
         );
@@ -163,7 +171,19 @@ impl SpotShadowMapRenderer {
         let camera_up = inv_view.up();
         let camera_side = inv_view.side();
 
-        for bundle in bundle_storage.bundles.iter() {
+        // Spot lights have only one view, the cascades differ only in resolution.
+        let occlusion_view = (light_handle, 0);
+        if let Some(culler) = occlusion_culler.as_deref_mut() {
+            culler.begin_view(state, occlusion_view);
+        }
+        let mut casters = FxHashSet::default();
+
+        // Hardware-instanced bundles are supported only by forward and deferred renderers.
+        for bundle in bundle_storage
+            .bundles
+            .iter()
+            .filter(|b| b.hardware_instance_count.is_none())
+        {
             let mut material_state = bundle.material.state();
             let Some(material) = material_state.data() else {
                 continue;
@@ -181,15 +201,22 @@ impl SpotShadowMapRenderer {
                 .and_then(|c| c.blend_shape_storage.clone());
 
             let Some(render_pass) = shader_cache
-                .get(state, material.shader())
+                .get_for_material(state, material)
                 .and_then(|shader_set| shader_set.render_passes.get(&SPOT_SHADOW_PASS_NAME))
             else {
                 continue;
             };
 
             for instance in bundle.instances.iter() {
+                casters.insert(instance.node_handle);
+                if let Some(culler) = occlusion_culler.as_deref_mut() {
+                    if !culler.is_visible(occlusion_view, instance.node_handle) {
+                        continue;
+                    }
+                }
+
                 statistics += framebuffer.draw(
-                    geometry,
+                    geometry.buffer,
                     state,
                     viewport,
                     &render_pass.program,
@@ -202,10 +229,11 @@ impl SpotShadowMapRenderer {
                         blend: None,
                         stencil_op: Default::default(),
                     },
-                    instance.element_range,
+                    geometry.element_range(instance.element_range)?,
                     |mut program_binding| {
                         apply_material(MaterialContext {
                             material,
+                            global_properties,
                             program_binding: &mut program_binding,
                             texture_cache,
                             matrix_storage,
@@ -213,6 +241,8 @@ impl SpotShadowMapRenderer {
                             view_projection_matrix: &light_view_projection,
                             wvp_matrix: &(light_view_projection * instance.world_transform),
                             bone_matrices: &instance.bone_matrices,
+                            prev_world_matrix: &instance.prev_world_transform,
+                            prev_bone_matrices: &instance.prev_bone_matrices,
                             use_skeletal_animation: bundle.is_skinned,
                             camera_position: &Default::default(),
                             camera_up_vector: &camera_up,
@@ -229,7 +259,9 @@ impl SpotShadowMapRenderer {
                             persistent_identifier: instance.persistent_identifier,
                             light_data: None,            // TODO
                             ambient_light: Color::WHITE, // TODO
+                            weather: Default::default(),
                             scene_depth: None,
+                            light_clusters: None,
                             z_far,
                         });
                     },
@@ -237,6 +269,19 @@ impl SpotShadowMapRenderer {
             }
         }
 
+        if let Some(culler) = occlusion_culler {
+            statistics += culler.end_view(
+                state,
+                occlusion_view,
+                framebuffer,
+                viewport,
+                &light_view_projection,
+                Some(light_position),
+                graph,
+                &casters,
+            )?;
+        }
+
         Ok(statistics)
     }
 }
@@ -0,0 +1,774 @@
+//! Simulates and renders smoke volumes (see [`crate::scene::smoke::Smoke`]). The simulation is done
+//! on GPU by ping-ponging a set of render targets, every 3D grid is stored in a 2D texture ("atlas"),
+//! where all the slices of the grid are placed in a row. If the graphics API does not support
+//! floating-point render targets, the simulation is done on CPU and the result is uploaded to the
+//! atlas every frame.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector3, Vector4},
+        color::Color,
+        log::Log,
+        math::{frustum::Frustum, Rect},
+        pool::Handle,
+        scope_profile,
+        sstorage::ImmutableString,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{
+                Attachment, AttachmentKind, BlendParameters, CullFace, DrawParameters, FrameBuffer,
+            },
+            geometry_buffer::{ElementRange, GeometryBuffer, GeometryBufferKind},
+            gpu_program::{GpuProgram, GpuProgramBinding, UniformLocation},
+            gpu_texture::{
+                Coordinate, GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter,
+                PixelKind, WrapMode,
+            },
+            state::{BlendFactor, BlendFunc, PipelineState},
+        },
+        make_viewport_matrix, RenderPassStatistics,
+    },
+    scene::{
+        camera::Camera,
+        graph::Graph,
+        mesh::surface::SurfaceData,
+        node::Node,
+        smoke::{grid::SmokeGrid, Smoke, SmokeSimulation, SmokeSimulationMode},
+        Scene,
+    },
+};
+use fxhash::FxHashMap;
+use half::f16;
+use std::{cell::RefCell, rc::Rc};
+
+// Simulation becomes unstable on large time steps, so they're clamped (the same as on CPU).
+const MAX_TIME_STEP: f32 = 1.0 / 30.0;
+
+struct AdvectShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    velocity_sampler: UniformLocation,
+    field_sampler: UniformLocation,
+    scalar_sampler: UniformLocation,
+    grid_size: UniformLocation,
+    dt: UniformLocation,
+    decay: UniformLocation,
+    source_position: UniformLocation,
+    source_radius: UniformLocation,
+    source_amount: UniformLocation,
+    is_velocity: UniformLocation,
+    buoyancy: UniformLocation,
+    weight: UniformLocation,
+}
+
+impl AdvectShader {
+    fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/smoke_advect_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "SmokeAdvectShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            velocity_sampler: program
+                .uniform_location(state, &ImmutableString::new("velocitySampler"))?,
+            field_sampler: program
+                .uniform_location(state, &ImmutableString::new("fieldSampler"))?,
+            scalar_sampler: program
+                .uniform_location(state, &ImmutableString::new("scalarSampler"))?,
+            grid_size: program.uniform_location(state, &ImmutableString::new("gridSize"))?,
+            dt: program.uniform_location(state, &ImmutableString::new("dt"))?,
+            decay: program.uniform_location(state, &ImmutableString::new("decay"))?,
+            source_position: program
+                .uniform_location(state, &ImmutableString::new("sourcePosition"))?,
+            source_radius: program
+                .uniform_location(state, &ImmutableString::new("sourceRadius"))?,
+            source_amount: program
+                .uniform_location(state, &ImmutableString::new("sourceAmount"))?,
+            is_velocity: program.uniform_location(state, &ImmutableString::new("isVelocity"))?,
+            buoyancy: program.uniform_location(state, &ImmutableString::new("buoyancy"))?,
+            weight: program.uniform_location(state, &ImmutableString::new("weight"))?,
+            program,
+        })
+    }
+}
+
+struct DivergenceShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    velocity_sampler: UniformLocation,
+    grid_size: UniformLocation,
+}
+
+impl DivergenceShader {
+    fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/smoke_divergence_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program = GpuProgram::from_source(
+            state,
+            "SmokeDivergenceShader",
+            vertex_source,
+            fragment_source,
+        )?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            velocity_sampler: program
+                .uniform_location(state, &ImmutableString::new("velocitySampler"))?,
+            grid_size: program.uniform_location(state, &ImmutableString::new("gridSize"))?,
+            program,
+        })
+    }
+}
+
+struct PressureShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    pressure_sampler: UniformLocation,
+    divergence_sampler: UniformLocation,
+    grid_size: UniformLocation,
+}
+
+impl PressureShader {
+    fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/smoke_pressure_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "SmokePressureShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            pressure_sampler: program
+                .uniform_location(state, &ImmutableString::new("pressureSampler"))?,
+            divergence_sampler: program
+                .uniform_location(state, &ImmutableString::new("divergenceSampler"))?,
+            grid_size: program.uniform_location(state, &ImmutableString::new("gridSize"))?,
+            program,
+        })
+    }
+}
+
+struct ProjectShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    velocity_sampler: UniformLocation,
+    pressure_sampler: UniformLocation,
+    grid_size: UniformLocation,
+}
+
+impl ProjectShader {
+    fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/smoke_project_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "SmokeProjectShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            velocity_sampler: program
+                .uniform_location(state, &ImmutableString::new("velocitySampler"))?,
+            pressure_sampler: program
+                .uniform_location(state, &ImmutableString::new("pressureSampler"))?,
+            grid_size: program.uniform_location(state, &ImmutableString::new("gridSize"))?,
+            program,
+        })
+    }
+}
+
+struct SmokeShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    density_sampler: UniformLocation,
+    scene_depth: UniformLocation,
+    inv_view_projection: UniformLocation,
+    inv_world_matrix: UniformLocation,
+    camera_position: UniformLocation,
+    grid_size: UniformLocation,
+    step_count: UniformLocation,
+    smoke_color: UniformLocation,
+    fire_color: UniformLocation,
+    fire_intensity: UniformLocation,
+    absorption: UniformLocation,
+}
+
+impl SmokeShader {
+    fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/smoke_fs.glsl");
+        let vertex_source = include_str!("shaders/smoke_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "SmokeShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            density_sampler: program
+                .uniform_location(state, &ImmutableString::new("densitySampler"))?,
+            scene_depth: program.uniform_location(state, &ImmutableString::new("sceneDepth"))?,
+            inv_view_projection: program
+                .uniform_location(state, &ImmutableString::new("invViewProjection"))?,
+            inv_world_matrix: program
+                .uniform_location(state, &ImmutableString::new("invWorldMatrix"))?,
+            camera_position: program
+                .uniform_location(state, &ImmutableString::new("cameraPosition"))?,
+            grid_size: program.uniform_location(state, &ImmutableString::new("gridSize"))?,
+            step_count: program.uniform_location(state, &ImmutableString::new("stepCount"))?,
+            smoke_color: program.uniform_location(state, &ImmutableString::new("smokeColor"))?,
+            fire_color: program.uniform_location(state, &ImmutableString::new("fireColor"))?,
+            fire_intensity: program
+                .uniform_location(state, &ImmutableString::new("fireIntensity"))?,
+            absorption: program.uniform_location(state, &ImmutableString::new("absorption"))?,
+            program,
+        })
+    }
+}
+
+struct SimulationShaders {
+    advect: AdvectShader,
+    divergence: DivergenceShader,
+    pressure: PressureShader,
+    project: ProjectShader,
+}
+
+fn atlas_kind(size: usize) -> GpuTextureKind {
+    GpuTextureKind::Rectangle {
+        width: size * size,
+        height: size,
+    }
+}
+
+fn create_atlas_texture(
+    state: &PipelineState,
+    size: usize,
+    pixel_kind: PixelKind,
+) -> Result<GpuTexture, FrameworkError> {
+    // Filtering must be linear for trilinear sampling of the grid, neighbour cells are fetched
+    // directly.
+    let mut texture = GpuTexture::new(
+        state,
+        atlas_kind(size),
+        pixel_kind,
+        MinificationFilter::Linear,
+        MagnificationFilter::Linear,
+        1,
+        None,
+    )?;
+    texture
+        .bind_mut(state, 0)
+        .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+        .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
+    Ok(texture)
+}
+
+fn create_atlas_framebuffer(
+    state: &PipelineState,
+    size: usize,
+    pixel_kind: PixelKind,
+) -> Result<FrameBuffer, FrameworkError> {
+    let mut framebuffer = FrameBuffer::new(
+        state,
+        None,
+        vec![Attachment {
+            kind: AttachmentKind::Color,
+            texture: Rc::new(RefCell::new(create_atlas_texture(state, size, pixel_kind)?)),
+        }],
+    )?;
+    framebuffer.clear(
+        state,
+        atlas_viewport(size),
+        Some(Color::TRANSPARENT),
+        None,
+        None,
+    );
+    Ok(framebuffer)
+}
+
+fn atlas_viewport(size: usize) -> Rect<i32> {
+    Rect::new(0, 0, (size * size) as i32, size as i32)
+}
+
+fn texture(framebuffer: &FrameBuffer) -> Rc<RefCell<GpuTexture>> {
+    framebuffer.color_attachments()[0].texture.clone()
+}
+
+// Draws a full-screen quad over the whole atlas.
+fn draw_atlas(
+    framebuffer: &mut FrameBuffer,
+    state: &PipelineState,
+    quad: &GeometryBuffer,
+    size: usize,
+    program: &GpuProgram,
+    wvp_matrix: &UniformLocation,
+    apply_uniforms: impl FnOnce(&mut GpuProgramBinding<'_, '_>),
+) -> Result<RenderPassStatistics, FrameworkError> {
+    let viewport = atlas_viewport(size);
+    let mut stats = RenderPassStatistics::default();
+    stats += framebuffer.draw(
+        quad,
+        state,
+        viewport,
+        program,
+        &DrawParameters {
+            cull_face: None,
+            color_write: Default::default(),
+            depth_write: false,
+            stencil_test: None,
+            depth_test: false,
+            blend: None,
+            stencil_op: Default::default(),
+        },
+        ElementRange::Full,
+        |mut program_binding| {
+            program_binding.set_matrix4(wvp_matrix, &make_viewport_matrix(viewport));
+            apply_uniforms(&mut program_binding);
+        },
+    )?;
+    Ok(stats)
+}
+
+struct GpuSmokeGrid {
+    size: usize,
+    velocity: FrameBuffer,
+    velocity_next: FrameBuffer,
+    scalars: FrameBuffer,
+    scalars_next: FrameBuffer,
+    pressure: FrameBuffer,
+    pressure_next: FrameBuffer,
+    divergence: FrameBuffer,
+}
+
+impl GpuSmokeGrid {
+    fn new(state: &PipelineState, size: usize) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            size,
+            velocity: create_atlas_framebuffer(state, size, PixelKind::RGBA16F)?,
+            velocity_next: create_atlas_framebuffer(state, size, PixelKind::RGBA16F)?,
+            scalars: create_atlas_framebuffer(state, size, PixelKind::RGBA16F)?,
+            scalars_next: create_atlas_framebuffer(state, size, PixelKind::RGBA16F)?,
+            pressure: create_atlas_framebuffer(state, size, PixelKind::R32F)?,
+            pressure_next: create_atlas_framebuffer(state, size, PixelKind::R32F)?,
+            divergence: create_atlas_framebuffer(state, size, PixelKind::R32F)?,
+        })
+    }
+
+    fn step(
+        &mut self,
+        state: &PipelineState,
+        quad: &GeometryBuffer,
+        shaders: &SimulationShaders,
+        simulation: &SmokeSimulation,
+        dt: f32,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut stats = RenderPassStatistics::default();
+
+        let size = self.size;
+        let grid_size = size as i32;
+        let source = simulation.grid_source(size);
+
+        // Advect densities and temperatures, then add new smoke from the source.
+        let shader = &shaders.advect;
+        let velocity = texture(&self.velocity);
+        let scalars = texture(&self.scalars);
+        stats += draw_atlas(
+            &mut self.scalars_next,
+            state,
+            quad,
+            size,
+            &shader.program,
+            &shader.wvp_matrix,
+            |program_binding| {
+                program_binding
+                    .set_texture(&shader.velocity_sampler, &velocity)
+                    .set_texture(&shader.field_sampler, &scalars)
+                    .set_texture(&shader.scalar_sampler, &scalars)
+                    .set_i32(&shader.grid_size, grid_size)
+                    .set_f32(&shader.dt, dt)
+                    .set_vector4(
+                        &shader.decay,
+                        &Vector4::new(
+                            (-simulation.density_dissipation * dt).exp(),
+                            (-simulation.temperature_dissipation * dt).exp(),
+                            0.0,
+                            0.0,
+                        ),
+                    )
+                    .set_vector3(&shader.source_position, &source.position)
+                    .set_f32(&shader.source_radius, source.radius)
+                    .set_vector4(
+                        &shader.source_amount,
+                        &Vector4::new(source.density, source.temperature, 0.0, 0.0),
+                    )
+                    .set_bool(&shader.is_velocity, false);
+            },
+        )?;
+        std::mem::swap(&mut self.scalars, &mut self.scalars_next);
+
+        // Advect velocity and apply forces.
+        let scalars = texture(&self.scalars);
+        stats += draw_atlas(
+            &mut self.velocity_next,
+            state,
+            quad,
+            size,
+            &shader.program,
+            &shader.wvp_matrix,
+            |program_binding| {
+                program_binding
+                    .set_texture(&shader.velocity_sampler, &velocity)
+                    .set_texture(&shader.field_sampler, &velocity)
+                    .set_texture(&shader.scalar_sampler, &scalars)
+                    .set_i32(&shader.grid_size, grid_size)
+                    .set_f32(&shader.dt, dt)
+                    .set_vector4(
+                        &shader.decay,
+                        &Vector4::repeat((-simulation.velocity_dissipation * dt).exp()),
+                    )
+                    .set_vector3(&shader.source_position, &source.position)
+                    .set_f32(&shader.source_radius, source.radius)
+                    .set_vector4(&shader.source_amount, &source.velocity.push(0.0))
+                    .set_bool(&shader.is_velocity, true)
+                    .set_f32(&shader.buoyancy, simulation.buoyancy)
+                    .set_f32(&shader.weight, simulation.weight);
+            },
+        )?;
+        std::mem::swap(&mut self.velocity, &mut self.velocity_next);
+
+        // Project the velocity.
+        let shader = &shaders.divergence;
+        let velocity = texture(&self.velocity);
+        stats += draw_atlas(
+            &mut self.divergence,
+            state,
+            quad,
+            size,
+            &shader.program,
+            &shader.wvp_matrix,
+            |program_binding| {
+                program_binding
+                    .set_texture(&shader.velocity_sampler, &velocity)
+                    .set_i32(&shader.grid_size, grid_size);
+            },
+        )?;
+
+        let shader = &shaders.pressure;
+        let divergence = texture(&self.divergence);
+        for _ in 0..simulation.pressure_iterations {
+            let pressure = texture(&self.pressure);
+            stats += draw_atlas(
+                &mut self.pressure_next,
+                state,
+                quad,
+                size,
+                &shader.program,
+                &shader.wvp_matrix,
+                |program_binding| {
+                    program_binding
+                        .set_texture(&shader.pressure_sampler, &pressure)
+                        .set_texture(&shader.divergence_sampler, &divergence)
+                        .set_i32(&shader.grid_size, grid_size);
+                },
+            )?;
+            std::mem::swap(&mut self.pressure, &mut self.pressure_next);
+        }
+
+        let shader = &shaders.project;
+        let pressure = texture(&self.pressure);
+        stats += draw_atlas(
+            &mut self.velocity_next,
+            state,
+            quad,
+            size,
+            &shader.program,
+            &shader.wvp_matrix,
+            |program_binding| {
+                program_binding
+                    .set_texture(&shader.velocity_sampler, &velocity)
+                    .set_texture(&shader.pressure_sampler, &pressure)
+                    .set_i32(&shader.grid_size, grid_size);
+            },
+        )?;
+        std::mem::swap(&mut self.velocity, &mut self.velocity_next);
+
+        Ok(stats)
+    }
+}
+
+struct CpuSmokeGrid {
+    grid: SmokeGrid,
+    atlas: Rc<RefCell<GpuTexture>>,
+    data: Vec<Vector4<f32>>,
+    pixels: Vec<[f16; 4]>,
+}
+
+impl CpuSmokeGrid {
+    fn new(state: &PipelineState, size: usize) -> Result<Self, FrameworkError> {
+        // Half-precision is used, because 32-bit float textures could not be filtered on some
+        // platforms.
+        Ok(Self {
+            grid: SmokeGrid::new(size),
+            atlas: Rc::new(RefCell::new(create_atlas_texture(
+                state,
+                size,
+                PixelKind::RGBA16F,
+            )?)),
+            data: Default::default(),
+            pixels: Default::default(),
+        })
+    }
+
+    fn step(
+        &mut self,
+        state: &PipelineState,
+        simulation: &SmokeSimulation,
+        dt: f32,
+    ) -> Result<(), FrameworkError> {
+        self.grid.step(simulation, dt);
+
+        self.grid.write_atlas(&mut self.data);
+        self.pixels.clear();
+        self.pixels.extend(
+            self.data
+                .iter()
+                .map(|v| -> [f16; 4] { v.map(f16::from_f32).into() }),
+        );
+
+        let size = self.grid.size();
+        self.atlas.borrow_mut().bind_mut(state, 0).set_data(
+            atlas_kind(size),
+            PixelKind::RGBA16F,
+            1,
+            Some(crate::core::array_as_u8_slice(&self.pixels)),
+        )?;
+        Ok(())
+    }
+}
+
+enum SmokeSimulationState {
+    Gpu(GpuSmokeGrid),
+    Cpu(CpuSmokeGrid),
+}
+
+struct SmokeInstance {
+    mode: SmokeSimulationMode,
+    size: usize,
+    state: SmokeSimulationState,
+    alive: bool,
+}
+
+impl SmokeInstance {
+    fn new(
+        state: &PipelineState,
+        mode: SmokeSimulationMode,
+        size: usize,
+    ) -> Result<Self, FrameworkError> {
+        let simulation_state = if mode == SmokeSimulationMode::Gpu
+            && state.supports_float_render_targets()
+        {
+            match GpuSmokeGrid::new(state, size) {
+                Ok(grid) => SmokeSimulationState::Gpu(grid),
+                Err(err) => {
+                    Log::warn(format!(
+                        "Unable to create GPU smoke simulation, falling back to CPU. Reason: {err:?}"
+                    ));
+                    SmokeSimulationState::Cpu(CpuSmokeGrid::new(state, size)?)
+                }
+            }
+        } else {
+            SmokeSimulationState::Cpu(CpuSmokeGrid::new(state, size)?)
+        };
+
+        Ok(Self {
+            mode,
+            size,
+            state: simulation_state,
+            alive: true,
+        })
+    }
+
+    fn density_texture(&self) -> Rc<RefCell<GpuTexture>> {
+        match self.state {
+            SmokeSimulationState::Gpu(ref grid) => texture(&grid.scalars),
+            SmokeSimulationState::Cpu(ref grid) => grid.atlas.clone(),
+        }
+    }
+}
+
+pub struct SmokeRenderer {
+    simulation_shaders: SimulationShaders,
+    smoke_shader: SmokeShader,
+    cube: GeometryBuffer,
+    instances: FxHashMap<(Handle<Scene>, Handle<Node>), SmokeInstance>,
+}
+
+impl SmokeRenderer {
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            simulation_shaders: SimulationShaders {
+                advect: AdvectShader::new(state)?,
+                divergence: DivergenceShader::new(state)?,
+                pressure: PressureShader::new(state)?,
+                project: ProjectShader::new(state)?,
+            },
+            smoke_shader: SmokeShader::new(state)?,
+            cube: GeometryBuffer::from_surface_data(
+                &SurfaceData::make_cube(Matrix4::identity()),
+                GeometryBufferKind::StaticDraw,
+                state,
+            )?,
+            instances: Default::default(),
+        })
+    }
+
+    /// Performs a simulation step for every smoke in the scene. Must be called once per frame.
+    pub(crate) fn update(
+        &mut self,
+        state: &PipelineState,
+        quad: &GeometryBuffer,
+        scene_handle: Handle<Scene>,
+        graph: &Graph,
+        dt: f32,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!();
+
+        let mut stats = RenderPassStatistics::default();
+
+        for (key, instance) in self.instances.iter_mut() {
+            if key.0 == scene_handle {
+                instance.alive = false;
+            }
+        }
+
+        for (handle, node) in graph.pair_iter() {
+            let Some(smoke) = node.cast::<Smoke>() else {
+                continue;
+            };
+            if !smoke.is_globally_enabled() {
+                continue;
+            }
+
+            let simulation = smoke.simulation();
+            let mode = smoke.simulation_mode();
+            let size = simulation.grid_size();
+
+            let instance = match self.instances.entry((scene_handle, handle)) {
+                std::collections::hash_map::Entry::Occupied(entry) => {
+                    let instance = entry.into_mut();
+                    if instance.mode != mode || instance.size != size {
+                        *instance = SmokeInstance::new(state, mode, size)?;
+                    }
+                    instance
+                }
+                std::collections::hash_map::Entry::Vacant(entry) => {
+                    entry.insert(SmokeInstance::new(state, mode, size)?)
+                }
+            };
+            instance.alive = true;
+
+            if dt * simulation.time_scale <= 0.0 {
+                continue;
+            }
+
+            match instance.state {
+                SmokeSimulationState::Gpu(ref mut grid) => {
+                    let dt = (dt * simulation.time_scale).min(MAX_TIME_STEP);
+                    stats += grid.step(state, quad, &self.simulation_shaders, simulation, dt)?;
+                }
+                SmokeSimulationState::Cpu(ref mut grid) => {
+                    // Time scale is applied by the grid itself.
+                    grid.step(state, simulation, dt)?;
+                }
+            }
+        }
+
+        self.instances.retain(|_, instance| instance.alive);
+
+        Ok(stats)
+    }
+
+    /// Removes simulations of the scenes, that do not satisfy the given predicate.
+    pub(crate) fn retain_scenes(&mut self, mut predicate: impl FnMut(Handle<Scene>) -> bool) {
+        self.instances.retain(|(scene, _), _| predicate(*scene));
+    }
+
+    pub(crate) fn render(
+        &self,
+        state: &PipelineState,
+        viewport: Rect<i32>,
+        frame_buffer: &mut FrameBuffer,
+        scene_depth: Rc<RefCell<GpuTexture>>,
+        camera: &Camera,
+        scene_handle: Handle<Scene>,
+        graph: &Graph,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!();
+
+        let mut stats = RenderPassStatistics::default();
+
+        let view_projection = camera.view_projection_matrix();
+        let inv_view_projection = view_projection.try_inverse().unwrap_or_default();
+        let frustum = Frustum::from_view_projection_matrix(view_projection).unwrap_or_default();
+        let camera_position: Vector3<f32> = camera.global_position();
+
+        let shader = &self.smoke_shader;
+        for (key, instance) in self.instances.iter() {
+            if key.0 != scene_handle {
+                continue;
+            }
+            let Some(smoke) = graph.try_get(key.1).and_then(|n| n.cast::<Smoke>()) else {
+                continue;
+            };
+            if !frustum.is_intersects_aabb(&smoke.world_bounding_box()) {
+                continue;
+            }
+
+            let world = smoke.global_transform();
+            let inv_world = world.try_inverse().unwrap_or_default();
+            let density_texture = instance.density_texture();
+
+            stats += frame_buffer.draw(
+                &self.cube,
+                state,
+                viewport,
+                &shader.program,
+                &DrawParameters {
+                    // Back faces are drawn, so the volume is visible from inside.
+                    cull_face: Some(CullFace::Front),
+                    color_write: Default::default(),
+                    depth_write: false,
+                    stencil_test: None,
+                    // Depth is tested in the shader.
+                    depth_test: false,
+                    blend: Some(BlendParameters {
+                        func: BlendFunc::new(BlendFactor::One, BlendFactor::OneMinusSrcAlpha),
+                        ..Default::default()
+                    }),
+                    stencil_op: Default::default(),
+                },
+                ElementRange::Full,
+                |mut program_binding| {
+                    program_binding
+                        .set_matrix4(&shader.wvp_matrix, &(view_projection * world))
+                        .set_texture(&shader.density_sampler, &density_texture)
+                        .set_texture(&shader.scene_depth, &scene_depth)
+                        .set_matrix4(&shader.inv_view_projection, &inv_view_projection)
+                        .set_matrix4(&shader.inv_world_matrix, &inv_world)
+                        .set_vector3(&shader.camera_position, &camera_position)
+                        .set_f32(&shader.grid_size, instance.size as f32)
+                        .set_i32(&shader.step_count, smoke.step_count().max(1) as i32)
+                        .set_srgb_color(&shader.smoke_color, &smoke.smoke_color())
+                        .set_srgb_color(&shader.fire_color, &smoke.fire_color())
+                        .set_f32(&shader.fire_intensity, smoke.fire_intensity())
+                        .set_f32(&shader.absorption, smoke.absorption());
+                },
+            )?;
+        }
+
+        Ok(stats)
+    }
+}
@@ -0,0 +1,81 @@
+use crate::{
+    core::sstorage::ImmutableString,
+    renderer::framework::{
+        error::FrameworkError,
+        gpu_program::{GpuProgram, UniformLocation},
+        state::PipelineState,
+    },
+};
+
+pub struct GtaoShader {
+    pub program: GpuProgram,
+    pub depth_sampler: UniformLocation,
+    pub normal_sampler: UniformLocation,
+    pub radius: UniformLocation,
+    pub projection_matrix: UniformLocation,
+    pub inv_proj_matrix: UniformLocation,
+    pub world_view_proj_matrix: UniformLocation,
+    pub view_matrix: UniformLocation,
+    pub frame_index: UniformLocation,
+}
+
+impl GtaoShader {
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("../shaders/gtao_fs.glsl");
+        let vertex_source = include_str!("../shaders/ssao_vs.glsl");
+        let program = GpuProgram::from_source(state, "GtaoShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            depth_sampler: program
+                .uniform_location(state, &ImmutableString::new("depthSampler"))?,
+            normal_sampler: program
+                .uniform_location(state, &ImmutableString::new("normalSampler"))?,
+            radius: program.uniform_location(state, &ImmutableString::new("radius"))?,
+            projection_matrix: program
+                .uniform_location(state, &ImmutableString::new("projectionMatrix"))?,
+            inv_proj_matrix: program
+                .uniform_location(state, &ImmutableString::new("inverseProjectionMatrix"))?,
+            world_view_proj_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            view_matrix: program.uniform_location(state, &ImmutableString::new("viewMatrix"))?,
+            frame_index: program.uniform_location(state, &ImmutableString::new("frameIndex"))?,
+            program,
+        })
+    }
+}
+
+pub struct TemporalShader {
+    pub program: GpuProgram,
+    pub current_sampler: UniformLocation,
+    pub history_sampler: UniformLocation,
+    pub depth_sampler: UniformLocation,
+    pub inv_view_projection: UniformLocation,
+    pub prev_view_projection: UniformLocation,
+    pub history_weight: UniformLocation,
+    pub world_view_proj_matrix: UniformLocation,
+}
+
+impl TemporalShader {
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("../shaders/ssao_temporal_fs.glsl");
+        let vertex_source = include_str!("../shaders/ssao_vs.glsl");
+        let program =
+            GpuProgram::from_source(state, "SsaoTemporalShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            current_sampler: program
+                .uniform_location(state, &ImmutableString::new("currentSampler"))?,
+            history_sampler: program
+                .uniform_location(state, &ImmutableString::new("historySampler"))?,
+            depth_sampler: program
+                .uniform_location(state, &ImmutableString::new("depthSampler"))?,
+            inv_view_projection: program
+                .uniform_location(state, &ImmutableString::new("inverseViewProjection"))?,
+            prev_view_projection: program
+                .uniform_location(state, &ImmutableString::new("previousViewProjection"))?,
+            history_weight: program
+                .uniform_location(state, &ImmutableString::new("historyWeight"))?,
+            world_view_proj_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            program,
+        })
+    }
+}
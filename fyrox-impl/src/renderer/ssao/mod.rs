@@ -1,4 +1,4 @@
-use crate::renderer::framework::geometry_buffer::ElementRange;
+use crate::renderer::framework::geometry_buffer::{DrawCallStatistics, ElementRange};
 use crate::{
     core::{
         algebra::{Matrix3, Matrix4, Vector2, Vector3},
@@ -21,14 +21,20 @@ use crate::{
             state::PipelineState,
         },
         gbuffer::GBuffer,
-        ssao::blur::Blur,
-        RenderPassStatistics,
+        ssao::{
+            blur::Blur,
+            gtao::{GtaoShader, TemporalShader},
+            upsample::BilateralUpsample,
+        },
+        AmbientOcclusionMode, QualitySettings, RenderPassStatistics,
     },
     scene::mesh::surface::SurfaceData,
 };
 use std::{cell::RefCell, rc::Rc};
 
 mod blur;
+mod gtao;
+mod upsample;
 
 // Keep in sync with shader define.
 const KERNEL_SIZE: usize = 32;
@@ -36,6 +42,38 @@ const KERNEL_SIZE: usize = 32;
 // Size of noise texture.
 const NOISE_SIZE: usize = 4;
 
+// Weight of the accumulated occlusion, the rest is taken from the current frame.
+const HISTORY_WEIGHT: f32 = 0.9;
+
+pub(crate) fn make_occlusion_framebuffer(
+    state: &PipelineState,
+    width: usize,
+    height: usize,
+) -> Result<FrameBuffer, FrameworkError> {
+    let kind = GpuTextureKind::Rectangle { width, height };
+    let mut texture = GpuTexture::new(
+        state,
+        kind,
+        PixelKind::R32F,
+        MinificationFilter::Nearest,
+        MagnificationFilter::Nearest,
+        1,
+        None,
+    )?;
+    texture
+        .bind_mut(state, 0)
+        .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+        .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
+    FrameBuffer::new(
+        state,
+        None,
+        vec![Attachment {
+            kind: AttachmentKind::Color,
+            texture: Rc::new(RefCell::new(texture)),
+        }],
+    )
+}
+
 struct Shader {
     program: GpuProgram,
     depth_sampler: UniformLocation,
@@ -80,13 +118,24 @@ impl Shader {
 pub struct ScreenSpaceAmbientOcclusionRenderer {
     blur: Blur,
     shader: Shader,
+    gtao_shader: GtaoShader,
+    temporal_shader: TemporalShader,
     framebuffer: FrameBuffer,
+    history: [FrameBuffer; 2],
+    history_index: usize,
+    prev_view_projection: Option<Matrix4<f32>>,
+    upsample: Option<BilateralUpsample>,
     quad: GeometryBuffer,
     width: i32,
     height: i32,
     noise: Rc<RefCell<GpuTexture>>,
     kernel: [Vector3<f32>; KERNEL_SIZE],
     radius: f32,
+    frame_width: usize,
+    frame_height: usize,
+    mode: AmbientOcclusionMode,
+    temporal_accumulation: bool,
+    frame_index: u32,
 }
 
 impl ScreenSpaceAmbientOcclusionRenderer {
@@ -94,28 +143,23 @@ impl ScreenSpaceAmbientOcclusionRenderer {
         state: &PipelineState,
         frame_width: usize,
         frame_height: usize,
+        half_resolution: bool,
     ) -> Result<Self, FrameworkError> {
         // It is good balance between quality and performance, no need to do SSAO in full resolution.
-        // This SSAO map size reduction was taken from DOOM (2016).
-        let width = (frame_width / 2).max(1);
-        let height = (frame_height / 2).max(1);
-
-        let occlusion = {
-            let kind = GpuTextureKind::Rectangle { width, height };
-            let mut texture = GpuTexture::new(
-                state,
-                kind,
-                PixelKind::R32F,
-                MinificationFilter::Nearest,
-                MagnificationFilter::Nearest,
-                1,
-                None,
-            )?;
-            texture
-                .bind_mut(state, 0)
-                .set_minification_filter(MinificationFilter::Nearest)
-                .set_magnification_filter(MagnificationFilter::Nearest);
-            texture
+        // This SSAO map size reduction was taken from DOOM (2016). Half resolution map is then
+        // upscaled with depth-aware filter to prevent occlusion from leaking over edges.
+        let (width, height, upsample) = if half_resolution {
+            (
+                (frame_width / 2).max(1),
+                (frame_height / 2).max(1),
+                Some(BilateralUpsample::new(
+                    state,
+                    frame_width.max(1),
+                    frame_height.max(1),
+                )?),
+            )
+        } else {
+            (frame_width.max(1), frame_height.max(1), None)
         };
 
         let mut rng = crate::rand::thread_rng();
@@ -123,14 +167,16 @@ impl ScreenSpaceAmbientOcclusionRenderer {
         Ok(Self {
             blur: Blur::new(state, width, height)?,
             shader: Shader::new(state)?,
-            framebuffer: FrameBuffer::new(
-                state,
-                None,
-                vec![Attachment {
-                    kind: AttachmentKind::Color,
-                    texture: Rc::new(RefCell::new(occlusion)),
-                }],
-            )?,
+            gtao_shader: GtaoShader::new(state)?,
+            temporal_shader: TemporalShader::new(state)?,
+            framebuffer: make_occlusion_framebuffer(state, width, height)?,
+            history: [
+                make_occlusion_framebuffer(state, width, height)?,
+                make_occlusion_framebuffer(state, width, height)?,
+            ],
+            history_index: 0,
+            prev_view_projection: None,
+            upsample,
             quad: GeometryBuffer::from_surface_data(
                 &SurfaceData::make_unit_xy_quad(),
                 GeometryBufferKind::StaticDraw,
@@ -184,11 +230,20 @@ impl ScreenSpaceAmbientOcclusionRenderer {
                 texture
             })),
             radius: 0.5,
+            frame_width,
+            frame_height,
+            mode: Default::default(),
+            temporal_accumulation: true,
+            frame_index: 0,
         })
     }
 
-    pub fn set_radius(&mut self, radius: f32) {
-        self.radius = radius.abs();
+    pub fn is_half_resolution(&self) -> bool {
+        self.upsample.is_some()
+    }
+
+    pub fn frame_size(&self) -> (usize, usize) {
+        (self.frame_width, self.frame_height)
     }
 
     fn raw_ao_map(&self) -> Rc<RefCell<GpuTexture>> {
@@ -196,18 +251,83 @@ impl ScreenSpaceAmbientOcclusionRenderer {
     }
 
     pub fn ao_map(&self) -> Rc<RefCell<GpuTexture>> {
-        self.blur.result()
+        match self.upsample {
+            Some(ref upsample) => upsample.result(),
+            None => self.blur.result(),
+        }
+    }
+
+    fn uses_temporal_accumulation(&self) -> bool {
+        self.temporal_accumulation && self.mode == AmbientOcclusionMode::Gtao
+    }
+
+    fn accumulate(
+        &mut self,
+        state: &PipelineState,
+        gbuffer: &GBuffer,
+        viewport: Rect<i32>,
+        frame_matrix: &Matrix4<f32>,
+        view_projection: Matrix4<f32>,
+    ) -> Result<DrawCallStatistics, FrameworkError> {
+        let current = self.raw_ao_map();
+        let history = self.history[1 - self.history_index].color_attachments()[0]
+            .texture
+            .clone();
+        // History is discarded on the first frame or after a reset.
+        let (prev_view_projection, history_weight) = match self.prev_view_projection {
+            Some(prev_view_projection) => (prev_view_projection, HISTORY_WEIGHT),
+            None => (view_projection, 0.0),
+        };
+        let inv_view_projection = view_projection.try_inverse().unwrap_or_default();
+
+        let shader = &self.temporal_shader;
+        self.history[self.history_index].draw(
+            &self.quad,
+            state,
+            viewport,
+            &shader.program,
+            &DrawParameters {
+                cull_face: None,
+                color_write: Default::default(),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: false,
+                blend: None,
+                stencil_op: Default::default(),
+            },
+            ElementRange::Full,
+            |mut program_binding| {
+                program_binding
+                    .set_texture(&shader.current_sampler, &current)
+                    .set_texture(&shader.history_sampler, &history)
+                    .set_texture(&shader.depth_sampler, &gbuffer.depth())
+                    .set_matrix4(&shader.inv_view_projection, &inv_view_projection)
+                    .set_matrix4(&shader.prev_view_projection, &prev_view_projection)
+                    .set_f32(&shader.history_weight, history_weight)
+                    .set_matrix4(&shader.world_view_proj_matrix, frame_matrix);
+            },
+        )
     }
 
     pub(crate) fn render(
         &mut self,
         state: &PipelineState,
         gbuffer: &GBuffer,
+        settings: &QualitySettings,
         projection_matrix: Matrix4<f32>,
         view_matrix: Matrix3<f32>,
+        view_projection: Matrix4<f32>,
     ) -> Result<RenderPassStatistics, FrameworkError> {
         scope_profile!();
 
+        self.radius = settings.ssao_radius.abs();
+        self.temporal_accumulation = settings.ssao_temporal_accumulation;
+        if self.mode != settings.ssao_mode {
+            self.mode = settings.ssao_mode;
+            // Accumulated occlusion of a different algorithm is useless.
+            self.prev_view_projection = None;
+        }
+
         let mut stats = RenderPassStatistics::default();
 
         let viewport = Rect::new(0, 0, self.width, self.height);
@@ -233,48 +353,110 @@ impl ScreenSpaceAmbientOcclusionRenderer {
             None,
         );
 
-        let shader = &self.shader;
-        let noise = &self.noise;
-        let kernel = &self.kernel;
-        let noise_scale = Vector2::new(
-            self.width as f32 / NOISE_SIZE as f32,
-            self.height as f32 / NOISE_SIZE as f32,
-        );
-        let radius = self.radius;
-        stats += self.framebuffer.draw(
-            &self.quad,
-            state,
-            viewport,
-            &shader.program,
-            &DrawParameters {
-                cull_face: None,
-                color_write: Default::default(),
-                depth_write: false,
-                stencil_test: None,
-                depth_test: false,
-                blend: None,
-                stencil_op: Default::default(),
-            },
-            ElementRange::Full,
-            |mut program_binding| {
-                program_binding
-                    .set_texture(&shader.depth_sampler, &gbuffer.depth())
-                    .set_texture(&shader.normal_sampler, &gbuffer.normal_texture())
-                    .set_texture(&shader.noise_sampler, noise)
-                    .set_vector3_slice(&shader.kernel, kernel)
-                    .set_vector2(&shader.noise_scale, &noise_scale)
-                    .set_f32(&shader.radius, radius)
-                    .set_matrix4(&shader.world_view_proj_matrix, &frame_matrix)
-                    .set_matrix4(&shader.projection_matrix, &projection_matrix)
-                    .set_matrix4(
-                        &shader.inv_proj_matrix,
-                        &projection_matrix.try_inverse().unwrap_or_default(),
-                    )
-                    .set_matrix3(&shader.view_matrix, &view_matrix);
-            },
-        )?;
+        match self.mode {
+            AmbientOcclusionMode::Ssao => {
+                let shader = &self.shader;
+                let noise = &self.noise;
+                let kernel = &self.kernel;
+                let noise_scale = Vector2::new(
+                    self.width as f32 / NOISE_SIZE as f32,
+                    self.height as f32 / NOISE_SIZE as f32,
+                );
+                let radius = self.radius;
+                stats += self.framebuffer.draw(
+                    &self.quad,
+                    state,
+                    viewport,
+                    &shader.program,
+                    &DrawParameters {
+                        cull_face: None,
+                        color_write: Default::default(),
+                        depth_write: false,
+                        stencil_test: None,
+                        depth_test: false,
+                        blend: None,
+                        stencil_op: Default::default(),
+                    },
+                    ElementRange::Full,
+                    |mut program_binding| {
+                        program_binding
+                            .set_texture(&shader.depth_sampler, &gbuffer.depth())
+                            .set_texture(&shader.normal_sampler, &gbuffer.normal_texture())
+                            .set_texture(&shader.noise_sampler, noise)
+                            .set_vector3_slice(&shader.kernel, kernel)
+                            .set_vector2(&shader.noise_scale, &noise_scale)
+                            .set_f32(&shader.radius, radius)
+                            .set_matrix4(&shader.world_view_proj_matrix, &frame_matrix)
+                            .set_matrix4(&shader.projection_matrix, &projection_matrix)
+                            .set_matrix4(
+                                &shader.inv_proj_matrix,
+                                &projection_matrix.try_inverse().unwrap_or_default(),
+                            )
+                            .set_matrix3(&shader.view_matrix, &view_matrix);
+                    },
+                )?;
+            }
+            AmbientOcclusionMode::Gtao => {
+                let shader = &self.gtao_shader;
+                let radius = self.radius;
+                let frame_index = (self.frame_index % 64) as i32;
+                stats += self.framebuffer.draw(
+                    &self.quad,
+                    state,
+                    viewport,
+                    &shader.program,
+                    &DrawParameters {
+                        cull_face: None,
+                        color_write: Default::default(),
+                        depth_write: false,
+                        stencil_test: None,
+                        depth_test: false,
+                        blend: None,
+                        stencil_op: Default::default(),
+                    },
+                    ElementRange::Full,
+                    |mut program_binding| {
+                        program_binding
+                            .set_texture(&shader.depth_sampler, &gbuffer.depth())
+                            .set_texture(&shader.normal_sampler, &gbuffer.normal_texture())
+                            .set_f32(&shader.radius, radius)
+                            .set_i32(&shader.frame_index, frame_index)
+                            .set_matrix4(&shader.world_view_proj_matrix, &frame_matrix)
+                            .set_matrix4(&shader.projection_matrix, &projection_matrix)
+                            .set_matrix4(
+                                &shader.inv_proj_matrix,
+                                &projection_matrix.try_inverse().unwrap_or_default(),
+                            )
+                            .set_matrix3(&shader.view_matrix, &view_matrix);
+                    },
+                )?;
+            }
+        }
+
+        let occlusion = if self.uses_temporal_accumulation() {
+            stats += self.accumulate(state, gbuffer, viewport, &frame_matrix, view_projection)?;
+            let result = self.history[self.history_index].color_attachments()[0]
+                .texture
+                .clone();
+            self.history_index = 1 - self.history_index;
+            self.prev_view_projection = Some(view_projection);
+            result
+        } else {
+            self.prev_view_projection = None;
+            self.raw_ao_map()
+        };
+        self.frame_index = self.frame_index.wrapping_add(1);
+
+        stats += self.blur.render(state, occlusion)?;
 
-        self.blur.render(state, self.raw_ao_map())?;
+        if let Some(upsample) = self.upsample.as_mut() {
+            stats += upsample.render(
+                state,
+                self.blur.result(),
+                gbuffer.depth(),
+                projection_matrix,
+            )?;
+        }
 
         Ok(stats)
     }
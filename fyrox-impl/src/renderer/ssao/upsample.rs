@@ -0,0 +1,119 @@
+use crate::renderer::framework::geometry_buffer::{DrawCallStatistics, ElementRange};
+use crate::{
+    core::{algebra::Matrix4, math::Rect, scope_profile, sstorage::ImmutableString},
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{DrawParameters, FrameBuffer},
+            geometry_buffer::{GeometryBuffer, GeometryBufferKind},
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::GpuTexture,
+            state::PipelineState,
+        },
+        make_viewport_matrix,
+        ssao::make_occlusion_framebuffer,
+    },
+    scene::mesh::surface::SurfaceData,
+};
+use std::{cell::RefCell, rc::Rc};
+
+struct Shader {
+    program: GpuProgram,
+    world_view_projection_matrix: UniformLocation,
+    input_texture: UniformLocation,
+    depth_sampler: UniformLocation,
+    inv_proj_matrix: UniformLocation,
+}
+
+impl Shader {
+    fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("../shaders/ssao_upsample_fs.glsl");
+        let vertex_source = include_str!("../shaders/ssao_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "SsaoUpsampleShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            world_view_projection_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            input_texture: program
+                .uniform_location(state, &ImmutableString::new("inputTexture"))?,
+            depth_sampler: program
+                .uniform_location(state, &ImmutableString::new("depthSampler"))?,
+            inv_proj_matrix: program
+                .uniform_location(state, &ImmutableString::new("inverseProjectionMatrix"))?,
+            program,
+        })
+    }
+}
+
+/// Upscales a half resolution occlusion map to the full resolution using depth-aware filtering.
+pub struct BilateralUpsample {
+    shader: Shader,
+    framebuffer: FrameBuffer,
+    quad: GeometryBuffer,
+    width: usize,
+    height: usize,
+}
+
+impl BilateralUpsample {
+    pub fn new(state: &PipelineState, width: usize, height: usize) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: Shader::new(state)?,
+            framebuffer: make_occlusion_framebuffer(state, width, height)?,
+            quad: GeometryBuffer::from_surface_data(
+                &SurfaceData::make_unit_xy_quad(),
+                GeometryBufferKind::StaticDraw,
+                state,
+            )?,
+            width,
+            height,
+        })
+    }
+
+    pub fn result(&self) -> Rc<RefCell<GpuTexture>> {
+        self.framebuffer.color_attachments()[0].texture.clone()
+    }
+
+    pub(crate) fn render(
+        &mut self,
+        state: &PipelineState,
+        input: Rc<RefCell<GpuTexture>>,
+        depth: Rc<RefCell<GpuTexture>>,
+        projection_matrix: Matrix4<f32>,
+    ) -> Result<DrawCallStatistics, FrameworkError> {
+        scope_profile!();
+
+        let viewport = Rect::new(0, 0, self.width as i32, self.height as i32);
+
+        let shader = &self.shader;
+        self.framebuffer.draw(
+            &self.quad,
+            state,
+            viewport,
+            &shader.program,
+            &DrawParameters {
+                cull_face: None,
+                color_write: Default::default(),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: false,
+                blend: None,
+                stencil_op: Default::default(),
+            },
+            ElementRange::Full,
+            |mut program_binding| {
+                program_binding
+                    .set_matrix4(
+                        &shader.world_view_projection_matrix,
+                        &(make_viewport_matrix(viewport)),
+                    )
+                    .set_texture(&shader.input_texture, &input)
+                    .set_texture(&shader.depth_sampler, &depth)
+                    .set_matrix4(
+                        &shader.inv_proj_matrix,
+                        &projection_matrix.try_inverse().unwrap_or_default(),
+                    );
+            },
+        )
+    }
+}
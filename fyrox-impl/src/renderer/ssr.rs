@@ -0,0 +1,304 @@
+//! Screen-space reflections (SSR). Reflected rays are traced in view space against the depth buffer,
+//! the lit frame is then sampled at the hit points. Reflections that could not be found on screen are
+//! taken from the environment map of the camera (or its skybox), so rays that leave the screen smoothly
+//! fade to the environment.
+
+use crate::{
+    core::{
+        algebra::{Matrix3, Vector3},
+        color::Color,
+        math::{Matrix4Ext, Rect},
+        scope_profile,
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{
+                Attachment, AttachmentKind, BlendParameters, DrawParameters, FrameBuffer,
+            },
+            geometry_buffer::{ElementRange, GeometryBuffer},
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::{
+                Coordinate, GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter,
+                PixelKind, WrapMode,
+            },
+            state::{BlendFactor, BlendFunc, PipelineState},
+        },
+        gbuffer::GBuffer,
+        make_viewport_matrix, RenderPassStatistics, SsrSettings,
+    },
+    scene::camera::Camera,
+};
+use std::{cell::RefCell, rc::Rc};
+
+struct TraceShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    depth_sampler: UniformLocation,
+    normal_sampler: UniformLocation,
+    material_sampler: UniformLocation,
+    frame_sampler: UniformLocation,
+    projection_matrix: UniformLocation,
+    inv_projection_matrix: UniformLocation,
+    view_matrix: UniformLocation,
+    max_steps: UniformLocation,
+    max_distance: UniformLocation,
+    thickness: UniformLocation,
+    edge_fade: UniformLocation,
+    max_roughness: UniformLocation,
+}
+
+impl TraceShader {
+    fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/ssr_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program = GpuProgram::from_source(state, "SsrShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            depth_sampler: program
+                .uniform_location(state, &ImmutableString::new("depthSampler"))?,
+            normal_sampler: program
+                .uniform_location(state, &ImmutableString::new("normalSampler"))?,
+            material_sampler: program
+                .uniform_location(state, &ImmutableString::new("materialSampler"))?,
+            frame_sampler: program
+                .uniform_location(state, &ImmutableString::new("frameSampler"))?,
+            projection_matrix: program
+                .uniform_location(state, &ImmutableString::new("projectionMatrix"))?,
+            inv_projection_matrix: program
+                .uniform_location(state, &ImmutableString::new("invProjectionMatrix"))?,
+            view_matrix: program.uniform_location(state, &ImmutableString::new("viewMatrix"))?,
+            max_steps: program.uniform_location(state, &ImmutableString::new("maxSteps"))?,
+            max_distance: program.uniform_location(state, &ImmutableString::new("maxDistance"))?,
+            thickness: program.uniform_location(state, &ImmutableString::new("thickness"))?,
+            edge_fade: program.uniform_location(state, &ImmutableString::new("edgeFade"))?,
+            max_roughness: program
+                .uniform_location(state, &ImmutableString::new("maxRoughness"))?,
+            program,
+        })
+    }
+}
+
+struct CompositeShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    reflection_sampler: UniformLocation,
+    depth_sampler: UniformLocation,
+    normal_sampler: UniformLocation,
+    material_sampler: UniformLocation,
+    diffuse_sampler: UniformLocation,
+    environment_map: UniformLocation,
+    inv_view_projection: UniformLocation,
+    camera_position: UniformLocation,
+}
+
+impl CompositeShader {
+    fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/ssr_composite_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "SsrCompositeShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            reflection_sampler: program
+                .uniform_location(state, &ImmutableString::new("reflectionSampler"))?,
+            depth_sampler: program
+                .uniform_location(state, &ImmutableString::new("depthSampler"))?,
+            normal_sampler: program
+                .uniform_location(state, &ImmutableString::new("normalSampler"))?,
+            material_sampler: program
+                .uniform_location(state, &ImmutableString::new("materialSampler"))?,
+            diffuse_sampler: program
+                .uniform_location(state, &ImmutableString::new("diffuseSampler"))?,
+            environment_map: program
+                .uniform_location(state, &ImmutableString::new("environmentMap"))?,
+            inv_view_projection: program
+                .uniform_location(state, &ImmutableString::new("invViewProjection"))?,
+            camera_position: program
+                .uniform_location(state, &ImmutableString::new("cameraPosition"))?,
+            program,
+        })
+    }
+}
+
+fn make_reflection_framebuffer(
+    state: &PipelineState,
+    width: usize,
+    height: usize,
+) -> Result<FrameBuffer, FrameworkError> {
+    let mut texture = GpuTexture::new(
+        state,
+        GpuTextureKind::Rectangle { width, height },
+        PixelKind::RGBA16F,
+        MinificationFilter::Linear,
+        MagnificationFilter::Linear,
+        1,
+        None,
+    )?;
+    texture
+        .bind_mut(state, 0)
+        .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+        .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
+    FrameBuffer::new(
+        state,
+        None,
+        vec![Attachment {
+            kind: AttachmentKind::Color,
+            texture: Rc::new(RefCell::new(texture)),
+        }],
+    )
+}
+
+struct ReflectionTarget {
+    framebuffer: FrameBuffer,
+    width: i32,
+    height: i32,
+}
+
+pub struct ScreenSpaceReflectionsRenderer {
+    trace_shader: TraceShader,
+    composite_shader: CompositeShader,
+    target: Option<ReflectionTarget>,
+}
+
+impl ScreenSpaceReflectionsRenderer {
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            trace_shader: TraceShader::new(state)?,
+            composite_shader: CompositeShader::new(state)?,
+            target: None,
+        })
+    }
+
+    /// Traces reflections and adds them to the given (lit) frame. The frame must have the same size
+    /// as the g-buffer. Environment map is used where screen-space reflections are not available.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn render(
+        &mut self,
+        state: &PipelineState,
+        gbuffer: &GBuffer,
+        frame_buffer: &mut FrameBuffer,
+        quad: &GeometryBuffer,
+        camera: &Camera,
+        settings: &SsrSettings,
+        environment_map: Rc<RefCell<GpuTexture>>,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!();
+
+        let mut stats = RenderPassStatistics::default();
+
+        let (width, height) = if settings.half_resolution {
+            ((gbuffer.width / 2).max(1), (gbuffer.height / 2).max(1))
+        } else {
+            (gbuffer.width.max(1), gbuffer.height.max(1))
+        };
+        let is_valid = self
+            .target
+            .as_ref()
+            .is_some_and(|target| target.width == width && target.height == height);
+        if !is_valid {
+            self.target = Some(ReflectionTarget {
+                framebuffer: make_reflection_framebuffer(state, width as usize, height as usize)?,
+                width,
+                height,
+            });
+        }
+        let Some(target) = self.target.as_mut() else {
+            return Ok(stats);
+        };
+
+        let frame_texture = frame_buffer.color_attachments()[0].texture.clone();
+        let projection_matrix = camera.projection_matrix();
+        let inv_projection_matrix = projection_matrix.try_inverse().unwrap_or_default();
+        let view_matrix: Matrix3<f32> = camera.view_matrix().basis();
+
+        let trace_viewport = Rect::new(0, 0, width, height);
+        target
+            .framebuffer
+            .clear(state, trace_viewport, Some(Color::TRANSPARENT), None, None);
+
+        let shader = &self.trace_shader;
+        stats += target.framebuffer.draw(
+            quad,
+            state,
+            trace_viewport,
+            &shader.program,
+            &DrawParameters {
+                cull_face: None,
+                color_write: Default::default(),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: false,
+                blend: None,
+                stencil_op: Default::default(),
+            },
+            ElementRange::Full,
+            |mut program_binding| {
+                program_binding
+                    .set_matrix4(&shader.wvp_matrix, &make_viewport_matrix(trace_viewport))
+                    .set_texture(&shader.depth_sampler, &gbuffer.depth())
+                    .set_texture(&shader.normal_sampler, &gbuffer.normal_texture())
+                    .set_texture(&shader.material_sampler, &gbuffer.material_texture())
+                    .set_texture(&shader.frame_sampler, &frame_texture)
+                    .set_matrix4(&shader.projection_matrix, &projection_matrix)
+                    .set_matrix4(&shader.inv_projection_matrix, &inv_projection_matrix)
+                    .set_matrix3(&shader.view_matrix, &view_matrix)
+                    .set_i32(&shader.max_steps, settings.max_steps.clamp(1, 256) as i32)
+                    .set_f32(&shader.max_distance, settings.max_distance.max(0.001))
+                    .set_f32(&shader.thickness, settings.thickness)
+                    .set_f32(&shader.edge_fade, settings.edge_fade.max(0.001))
+                    .set_f32(&shader.max_roughness, settings.max_roughness.max(0.001));
+            },
+        )?;
+
+        // Add reflections to the frame. The frame already contains diffuse and specular lighting,
+        // reflections are added on top of it.
+        let reflection = target.framebuffer.color_attachments()[0].texture.clone();
+        let viewport = Rect::new(0, 0, gbuffer.width, gbuffer.height);
+        let inv_view_projection = camera
+            .view_projection_matrix()
+            .try_inverse()
+            .unwrap_or_default();
+        let camera_position: Vector3<f32> = camera.global_position();
+
+        let shader = &self.composite_shader;
+        stats += frame_buffer.draw(
+            quad,
+            state,
+            viewport,
+            &shader.program,
+            &DrawParameters {
+                cull_face: None,
+                color_write: Default::default(),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: false,
+                blend: Some(BlendParameters {
+                    func: BlendFunc::new(BlendFactor::One, BlendFactor::One),
+                    ..Default::default()
+                }),
+                stencil_op: Default::default(),
+            },
+            ElementRange::Full,
+            |mut program_binding| {
+                program_binding
+                    .set_matrix4(&shader.wvp_matrix, &make_viewport_matrix(viewport))
+                    .set_texture(&shader.reflection_sampler, &reflection)
+                    .set_texture(&shader.depth_sampler, &gbuffer.depth())
+                    .set_texture(&shader.normal_sampler, &gbuffer.normal_texture())
+                    .set_texture(&shader.material_sampler, &gbuffer.material_texture())
+                    .set_texture(&shader.diffuse_sampler, &gbuffer.diffuse_texture())
+                    .set_texture(&shader.environment_map, &environment_map)
+                    .set_matrix4(&shader.inv_view_projection, &inv_view_projection)
+                    .set_vector3(&shader.camera_position, &camera_position);
+            },
+        )?;
+
+        Ok(stats)
+    }
+}
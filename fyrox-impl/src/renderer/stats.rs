@@ -83,6 +83,11 @@ pub struct LightingStatistics {
     pub spot_shadow_maps_rendered: usize,
     /// How many directional lights were rendered.
     pub directional_lights_rendered: usize,
+    /// How many lights were rendered using light clusters.
+    pub clustered_lights_rendered: usize,
+    /// How many shadow casters were skipped, because they were hidden from lights by other
+    /// shadow casters.
+    pub shadow_casters_culled: usize,
 }
 
 impl AddAssign for LightingStatistics {
@@ -93,6 +98,8 @@ impl AddAssign for LightingStatistics {
         self.spot_shadow_maps_rendered += rhs.spot_shadow_maps_rendered;
         self.directional_lights_rendered += rhs.directional_lights_rendered;
         self.csm_rendered += rhs.csm_rendered;
+        self.clustered_lights_rendered += rhs.clustered_lights_rendered;
+        self.shadow_casters_culled += rhs.shadow_casters_culled;
     }
 }
 
@@ -106,13 +113,17 @@ impl Display for LightingStatistics {
             \tDirectional Lights: {}\n\
             \tPoint Shadow Maps: {}\n\
             \tSpot Shadow Maps: {}\n\
-            \tSpot Shadow Maps: {}\n",
+            \tSpot Shadow Maps: {}\n\
+            \tClustered Lights: {}\n\
+            \tCulled Shadow Casters: {}\n",
             self.point_lights_rendered,
             self.spot_lights_rendered,
             self.directional_lights_rendered,
             self.point_shadow_maps_rendered,
             self.spot_shadow_maps_rendered,
-            self.csm_rendered
+            self.csm_rendered,
+            self.clustered_lights_rendered,
+            self.shadow_casters_culled
         )
     }
 }
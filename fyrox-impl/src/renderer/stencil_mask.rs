@@ -0,0 +1,243 @@
+//! Writes stencil masks (see [`crate::scene::stencil_mask::StencilMask`]) into the stencil buffer
+//! and restricts rendering of the masked nodes to the regions of the masks.
+
+use crate::{
+    core::{algebra::Matrix4, log::Log, math::Rect, pool::Handle, scope_profile},
+    graph::{BaseSceneGraph, SceneGraph},
+    renderer::{
+        cache::{geometry::GeometryCache, TimeToLive},
+        flat_shader::FlatShader,
+        framework::{
+            error::FrameworkError,
+            framebuffer::{DrawParameters, FrameBuffer},
+            geometry_buffer::ElementRange,
+            state::{ColorMask, CompareFunc, PipelineState, StencilAction, StencilFunc, StencilOp},
+        },
+        RenderPassStatistics,
+    },
+    scene::{
+        graph::Graph,
+        mesh::Mesh,
+        node::Node,
+        stencil_mask::{StencilMask, MAX_STENCIL_MASKS},
+    },
+};
+use fxhash::FxHashMap;
+use std::borrow::Cow;
+
+struct ActiveMask {
+    mesh: Handle<Node>,
+    bit: u32,
+    inverted: bool,
+}
+
+/// A set of active stencil masks of a graph. Every mask occupies its own bit of the stencil buffer.
+#[derive(Default)]
+pub(crate) struct StencilMasks {
+    masks: Vec<ActiveMask>,
+    masked_nodes: FxHashMap<Handle<Node>, usize>,
+}
+
+impl StencilMasks {
+    pub(crate) fn from_graph(graph: &Graph) -> Self {
+        scope_profile!();
+
+        let mut masks = Vec::new();
+        let mut masked_nodes = FxHashMap::default();
+
+        for stencil_mask in graph.linear_iter().filter_map(|n| n.cast::<StencilMask>()) {
+            if !stencil_mask.is_globally_enabled() || !graph.is_valid_handle(stencil_mask.mask()) {
+                continue;
+            }
+
+            if masks.len() == MAX_STENCIL_MASKS {
+                Log::warn(format!(
+                    "Stencil mask {} is ignored, there could be at most {} active masks.",
+                    stencil_mask.name(),
+                    MAX_STENCIL_MASKS
+                ));
+                continue;
+            }
+
+            let index = masks.len();
+            masks.push(ActiveMask {
+                mesh: stencil_mask.mask(),
+                bit: 1 << index,
+                inverted: stencil_mask.is_inverted(),
+            });
+
+            for &root in stencil_mask.masked_nodes() {
+                if graph.is_valid_handle(root) {
+                    for handle in graph.traverse_handle_iter(root) {
+                        masked_nodes.entry(handle).or_insert(index);
+                    }
+                }
+            }
+        }
+
+        Self {
+            masks,
+            masked_nodes,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.masks.is_empty()
+    }
+
+    /// Restricts the given draw parameters to the region of the mask of the node (if any). Stencil
+    /// parameters of the draw parameters are replaced.
+    pub(crate) fn draw_params<'a>(
+        &self,
+        node: Handle<Node>,
+        draw_params: &'a DrawParameters,
+    ) -> Cow<'a, DrawParameters> {
+        match self
+            .masked_nodes
+            .get(&node)
+            .and_then(|index| self.masks.get(*index))
+        {
+            Some(mask) => Cow::Owned(DrawParameters {
+                stencil_test: Some(StencilFunc {
+                    func: if mask.inverted {
+                        CompareFunc::NotEqual
+                    } else {
+                        CompareFunc::Equal
+                    },
+                    ref_value: mask.bit,
+                    mask: mask.bit,
+                }),
+                stencil_op: StencilOp {
+                    write_mask: 0,
+                    ..Default::default()
+                },
+                ..draw_params.clone()
+            }),
+            None => Cow::Borrowed(draw_params),
+        }
+    }
+
+    /// Writes the bits of every mask into the stencil buffer of the frame buffer. Masks are drawn
+    /// without depth test.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn write(
+        &self,
+        state: &PipelineState,
+        framebuffer: &mut FrameBuffer,
+        viewport: Rect<i32>,
+        graph: &Graph,
+        view_projection: &Matrix4<f32>,
+        geom_cache: &mut GeometryCache,
+        shader: &FlatShader,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!();
+
+        let mut stats = RenderPassStatistics::default();
+
+        for mask in self.masks.iter() {
+            let Some(mesh) = graph.try_get(mask.mesh).and_then(|n| n.cast::<Mesh>()) else {
+                continue;
+            };
+
+            let wvp = view_projection * mesh.global_transform();
+
+            for surface in mesh.surfaces() {
+                let Some(geometry) =
+                    geom_cache.get(state, surface.data_ref(), TimeToLive::default())
+                else {
+                    continue;
+                };
+
+                stats += framebuffer.draw(
+                    geometry.buffer,
+                    state,
+                    viewport,
+                    &shader.program,
+                    &DrawParameters {
+                        cull_face: None,
+                        color_write: ColorMask::all(false),
+                        depth_write: false,
+                        stencil_test: Some(StencilFunc {
+                            func: CompareFunc::Always,
+                            ref_value: mask.bit,
+                            mask: mask.bit,
+                        }),
+                        depth_test: false,
+                        blend: None,
+                        stencil_op: StencilOp {
+                            zpass: StencilAction::Replace,
+                            write_mask: mask.bit,
+                            ..Default::default()
+                        },
+                    },
+                    geometry.element_range(ElementRange::Full)?,
+                    |mut program_binding| {
+                        program_binding.set_matrix4(&shader.wvp_matrix, &wvp);
+                    },
+                )?;
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// Clears the stencil buffer of the frame buffer, so the bits of the masks won't interfere with
+    /// other users of the stencil buffer.
+    pub(crate) fn clear(
+        &self,
+        state: &PipelineState,
+        framebuffer: &mut FrameBuffer,
+        viewport: Rect<i32>,
+    ) {
+        if !self.is_empty() {
+            framebuffer.clear(state, viewport, None, None, Some(0));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::pool::Handle,
+        renderer::{
+            framework::{framebuffer::DrawParameters, state::CompareFunc},
+            stencil_mask::StencilMasks,
+        },
+        scene::{
+            base::BaseBuilder, graph::Graph, mesh::MeshBuilder, pivot::PivotBuilder,
+            stencil_mask::StencilMaskBuilder,
+        },
+    };
+
+    #[test]
+    fn test_stencil_masks() {
+        let mut graph = Graph::new();
+        let mask = MeshBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let child = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let masked =
+            PivotBuilder::new(BaseBuilder::new().with_children(&[child])).build(&mut graph);
+        let other = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        StencilMaskBuilder::new(BaseBuilder::new())
+            .with_mask(Handle::NONE)
+            .with_masked_nodes(vec![other])
+            .build(&mut graph);
+        StencilMaskBuilder::new(BaseBuilder::new())
+            .with_mask(mask)
+            .with_masked_nodes(vec![masked])
+            .with_inverted(true)
+            .build(&mut graph);
+
+        let masks = StencilMasks::from_graph(&graph);
+        assert!(!masks.is_empty());
+
+        let params = DrawParameters::default();
+        for node in [masked, child] {
+            let stencil_test = masks.draw_params(node, &params).stencil_test.unwrap();
+            assert_eq!(stencil_test.func, CompareFunc::NotEqual);
+            assert_eq!(stencil_test.ref_value, 1);
+            assert_eq!(stencil_test.mask, 1);
+        }
+        assert!(masks.draw_params(other, &params).stencil_test.is_none());
+    }
+}
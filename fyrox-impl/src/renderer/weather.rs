@@ -0,0 +1,167 @@
+//! Renders precipitation of the scene weather. Particles are fully stateless and their positions
+//! are computed in the vertex shader, so there's no per-particle data on CPU side.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector3},
+        math::{Matrix4Ext, Rect},
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{BlendParameters, DrawParameters, FrameBuffer},
+            geometry_buffer::{ElementRange, GeometryBuffer, GeometryBufferKind},
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::GpuTexture,
+            state::{BlendFactor, BlendFunc, PipelineState},
+        },
+        RenderPassStatistics,
+    },
+    scene::{
+        camera::Camera,
+        mesh::surface::SurfaceData,
+        weather::{PrecipitationKind, Weather},
+    },
+};
+use std::{cell::RefCell, rc::Rc};
+
+struct PrecipitationShader {
+    program: GpuProgram,
+    view_projection: UniformLocation,
+    inv_view_projection: UniformLocation,
+    scene_depth: UniformLocation,
+    camera_position: UniformLocation,
+    camera_up_vector: UniformLocation,
+    camera_side_vector: UniformLocation,
+    wind_velocity: UniformLocation,
+    time: UniformLocation,
+    area_size: UniformLocation,
+    fall_speed: UniformLocation,
+    particle_size: UniformLocation,
+    collision_thickness: UniformLocation,
+    snow: UniformLocation,
+    color: UniformLocation,
+}
+
+impl PrecipitationShader {
+    fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/precipitation_fs.glsl");
+        let vertex_source = include_str!("shaders/precipitation_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "PrecipitationShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            view_projection: program
+                .uniform_location(state, &ImmutableString::new("viewProjection"))?,
+            inv_view_projection: program
+                .uniform_location(state, &ImmutableString::new("invViewProjection"))?,
+            scene_depth: program.uniform_location(state, &ImmutableString::new("sceneDepth"))?,
+            camera_position: program
+                .uniform_location(state, &ImmutableString::new("cameraPosition"))?,
+            camera_up_vector: program
+                .uniform_location(state, &ImmutableString::new("cameraUpVector"))?,
+            camera_side_vector: program
+                .uniform_location(state, &ImmutableString::new("cameraSideVector"))?,
+            wind_velocity: program
+                .uniform_location(state, &ImmutableString::new("windVelocity"))?,
+            time: program.uniform_location(state, &ImmutableString::new("time"))?,
+            area_size: program.uniform_location(state, &ImmutableString::new("areaSize"))?,
+            fall_speed: program.uniform_location(state, &ImmutableString::new("fallSpeed"))?,
+            particle_size: program
+                .uniform_location(state, &ImmutableString::new("particleSize"))?,
+            collision_thickness: program
+                .uniform_location(state, &ImmutableString::new("collisionThickness"))?,
+            snow: program.uniform_location(state, &ImmutableString::new("snow"))?,
+            color: program.uniform_location(state, &ImmutableString::new("color"))?,
+            program,
+        })
+    }
+}
+
+pub struct PrecipitationRenderer {
+    shader: PrecipitationShader,
+    quad: GeometryBuffer,
+}
+
+impl PrecipitationRenderer {
+    pub fn new(state: &PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: PrecipitationShader::new(state)?,
+            quad: GeometryBuffer::from_surface_data(
+                &SurfaceData::make_unit_xy_quad(),
+                GeometryBufferKind::StaticDraw,
+                state,
+            )?,
+        })
+    }
+
+    pub(crate) fn render(
+        &self,
+        state: &PipelineState,
+        viewport: Rect<i32>,
+        frame_buffer: &mut FrameBuffer,
+        scene_depth: Rc<RefCell<GpuTexture>>,
+        camera: &Camera,
+        weather: &Weather,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut statistics = RenderPassStatistics::default();
+
+        let precipitation = &weather.precipitation;
+        let count = precipitation.particle_count();
+        if !weather.enabled || count == 0 {
+            return Ok(statistics);
+        }
+
+        let view_projection = camera.view_projection_matrix();
+        let inv_view_projection = view_projection.try_inverse().unwrap_or_default();
+        let inv_view = camera.inv_view_matrix().unwrap_or_else(Matrix4::identity);
+        let wind_velocity: Vector3<f32> = weather.wind_velocity();
+
+        statistics += frame_buffer.draw_instances(
+            count as usize,
+            &self.quad,
+            state,
+            viewport,
+            &self.shader.program,
+            &DrawParameters {
+                cull_face: None,
+                color_write: Default::default(),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: true,
+                blend: Some(BlendParameters {
+                    func: BlendFunc::new(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
+                    ..Default::default()
+                }),
+                stencil_op: Default::default(),
+            },
+            ElementRange::Full,
+            |mut program_binding| {
+                program_binding
+                    .set_matrix4(&self.shader.view_projection, &view_projection)
+                    .set_matrix4(&self.shader.inv_view_projection, &inv_view_projection)
+                    .set_texture(&self.shader.scene_depth, &scene_depth)
+                    .set_vector3(&self.shader.camera_position, &camera.global_position())
+                    .set_vector3(&self.shader.camera_up_vector, &inv_view.up())
+                    .set_vector3(&self.shader.camera_side_vector, &inv_view.side())
+                    .set_vector3(&self.shader.wind_velocity, &wind_velocity)
+                    .set_f32(&self.shader.time, weather.time())
+                    .set_f32(&self.shader.area_size, precipitation.area_size.max(1.0))
+                    .set_f32(&self.shader.fall_speed, precipitation.fall_speed)
+                    .set_vector2(&self.shader.particle_size, &precipitation.particle_size)
+                    .set_f32(
+                        &self.shader.collision_thickness,
+                        precipitation.collision_thickness,
+                    )
+                    .set_bool(
+                        &self.shader.snow,
+                        precipitation.kind == PrecipitationKind::Snow,
+                    )
+                    .set_srgb_color(&self.shader.color, &precipitation.color);
+            },
+        )?;
+
+        Ok(statistics)
+    }
+}
@@ -0,0 +1,34 @@
+//! Dialogue loader.
+
+use crate::{
+    asset::{
+        io::ResourceIo,
+        loader::{BoxedLoaderFuture, LoaderPayload, ResourceLoader},
+    },
+    core::{uuid::Uuid, TypeUuidProvider},
+    resource::dialogue::DialogueResourceState,
+};
+use fyrox_resource::state::LoadError;
+use std::{path::PathBuf, sync::Arc};
+
+/// Default implementation for dialogue loading.
+pub struct DialogueLoader;
+
+impl ResourceLoader for DialogueLoader {
+    fn extensions(&self) -> &[&str] {
+        &["dialogue"]
+    }
+
+    fn data_type_uuid(&self) -> Uuid {
+        DialogueResourceState::type_uuid()
+    }
+
+    fn load(&self, path: PathBuf, io: Arc<dyn ResourceIo>) -> BoxedLoaderFuture {
+        Box::pin(async move {
+            let dialogue_state = DialogueResourceState::from_file(&path, io.as_ref())
+                .await
+                .map_err(LoadError::new)?;
+            Ok(LoaderPayload::new(dialogue_state))
+        })
+    }
+}
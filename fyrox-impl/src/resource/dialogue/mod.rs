@@ -0,0 +1,325 @@
+//! Dialogue resource holds a [`Dialogue`] - a graph of lines and choices, that could be played by
+//! [`runner::DialogueRunner`].
+
+use crate::{
+    asset::{io::ResourceIo, Resource, ResourceData, DIALOGUE_RESOURCE_UUID},
+    core::{
+        io::FileLoadError, reflect::prelude::*, uuid::Uuid, uuid_provider, visitor::prelude::*,
+        TypeUuidProvider,
+    },
+};
+use fxhash::FxHashMap;
+use std::error::Error;
+use std::{
+    any::Any,
+    fmt::{Display, Formatter},
+    path::Path,
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+pub mod loader;
+pub mod runner;
+
+/// A value of a dialogue variable.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum DialogueValue {
+    /// A boolean value.
+    Bool(bool),
+    /// A number.
+    Number(f32),
+    /// A string.
+    String(String),
+}
+
+uuid_provider!(DialogueValue = "8e4c2a7f-3b91-4d5e-a6f0-c7d9b1e3f528");
+
+impl Default for DialogueValue {
+    fn default() -> Self {
+        Self::Bool(false)
+    }
+}
+
+/// A set of named variables (a blackboard), that is used to check conditions of a dialogue. The
+/// variables are owned by the game, usually they're changed by scripts in response to
+/// [`runner::DialogueEvent`]s.
+pub type DialogueVariables = FxHashMap<String, DialogueValue>;
+
+/// An operator, that is used to compare a variable with a value.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum ComparisonOperator {
+    /// `variable == value`
+    #[default]
+    Equal,
+    /// `variable != value`
+    NotEqual,
+    /// `variable < value`, numbers only.
+    Less,
+    /// `variable <= value`, numbers only.
+    LessOrEqual,
+    /// `variable > value`, numbers only.
+    Greater,
+    /// `variable >= value`, numbers only.
+    GreaterOrEqual,
+}
+
+uuid_provider!(ComparisonOperator = "1d7b3f95-c248-4a6e-9b0c-5e2f8a4d7c13");
+
+/// A condition, that compares a dialogue variable with a value. Missing variables do not satisfy
+/// any condition, as well as variables of a type that differs from the type of the value.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct DialogueCondition {
+    /// Name of a variable.
+    pub variable: String,
+    /// Comparison operator.
+    pub operator: ComparisonOperator,
+    /// A value to compare the variable with.
+    pub value: DialogueValue,
+}
+
+uuid_provider!(DialogueCondition = "b5a09e2d-7f64-4c31-8d1b-2e6c9f0a4b87");
+
+impl DialogueCondition {
+    /// Checks whether the condition is satisfied for the given set of variables or not.
+    pub fn is_satisfied(&self, variables: &DialogueVariables) -> bool {
+        let Some(variable) = variables.get(&self.variable) else {
+            return false;
+        };
+
+        let ordering = match (variable, &self.value) {
+            (DialogueValue::Number(a), DialogueValue::Number(b)) => a.partial_cmp(b),
+            (DialogueValue::Bool(a), DialogueValue::Bool(b)) if a == b => {
+                Some(std::cmp::Ordering::Equal)
+            }
+            (DialogueValue::String(a), DialogueValue::String(b)) if a == b => {
+                Some(std::cmp::Ordering::Equal)
+            }
+            (DialogueValue::Bool(_), DialogueValue::Bool(_))
+            | (DialogueValue::String(_), DialogueValue::String(_)) => {
+                return self.operator == ComparisonOperator::NotEqual
+            }
+            _ => return false,
+        };
+
+        let Some(ordering) = ordering else {
+            return false;
+        };
+
+        match self.operator {
+            ComparisonOperator::Equal => ordering.is_eq(),
+            ComparisonOperator::NotEqual => ordering.is_ne(),
+            ComparisonOperator::Less => ordering.is_lt(),
+            ComparisonOperator::LessOrEqual => ordering.is_le(),
+            ComparisonOperator::Greater => ordering.is_gt(),
+            ComparisonOperator::GreaterOrEqual => ordering.is_ge(),
+        }
+    }
+}
+
+fn are_satisfied(conditions: &[DialogueCondition], variables: &DialogueVariables) -> bool {
+    conditions.iter().all(|c| c.is_satisfied(variables))
+}
+
+/// A choice of a player at a dialogue node.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct DialogueChoice {
+    /// Localization key of the text of the choice.
+    pub text_key: String,
+    /// A set of conditions, that must be satisfied to make the choice available.
+    pub conditions: Vec<DialogueCondition>,
+    /// Names of the nodes, that could follow the choice. The first node with satisfied conditions
+    /// is used. The dialogue ends if there's no such node.
+    pub next: Vec<String>,
+}
+
+uuid_provider!(DialogueChoice = "4f82d6c0-a1e9-4b73-85d2-9c0e7b3a6f14");
+
+impl DialogueChoice {
+    /// Returns `true` if the choice is available for the given set of variables.
+    pub fn is_available(&self, variables: &DialogueVariables) -> bool {
+        are_satisfied(&self.conditions, variables)
+    }
+}
+
+/// A single line of a dialogue.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct DialogueNode {
+    /// Unique name of the node, it is used to link nodes with each other.
+    pub name: String,
+    /// Name of the speaker.
+    pub speaker: String,
+    /// Localization key of the text of the line.
+    pub text_key: String,
+    /// A set of conditions, that must be satisfied to enter the node.
+    pub conditions: Vec<DialogueCondition>,
+    /// Names of events, that will be emitted when the node is entered. See
+    /// [`runner::DialogueEvent`] docs for more info.
+    pub events: Vec<String>,
+    /// A list of choices. If there are no available choices, the dialogue continues with
+    /// [`Self::next`] nodes.
+    pub choices: Vec<DialogueChoice>,
+    /// Names of the nodes, that could follow this node if it has no available choices. The first
+    /// node with satisfied conditions is used. The dialogue ends if there's no such node.
+    pub next: Vec<String>,
+}
+
+uuid_provider!(DialogueNode = "e29c7b4a-5d03-4f18-b6a7-0d3e8c1f9a65");
+
+impl DialogueNode {
+    /// Returns `true` if the node could be entered with the given set of variables.
+    pub fn can_enter(&self, variables: &DialogueVariables) -> bool {
+        are_satisfied(&self.conditions, variables)
+    }
+}
+
+/// Dialogue is a graph of nodes, where each node is a line of a speaker, optionally followed by a
+/// set of choices. Transitions between nodes and availability of choices could be controlled by
+/// conditions on [`DialogueVariables`].
+///
+/// Text of the nodes and choices is stored as localization keys, use [`TextProvider`] to get the
+/// actual text.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct Dialogue {
+    /// Names of the nodes, that could start the dialogue. The first node with satisfied conditions
+    /// is used.
+    pub start: Vec<String>,
+    /// A list of nodes of the dialogue.
+    pub nodes: Vec<DialogueNode>,
+}
+
+impl Dialogue {
+    /// Tries to find a node with the given name and returns its index.
+    pub fn find_node(&self, name: &str) -> Option<usize> {
+        self.nodes.iter().position(|n| n.name == name)
+    }
+
+    /// Returns the index of the first node in the given list, that exists and could be entered
+    /// with the given set of variables.
+    pub fn first_available(
+        &self,
+        names: &[String],
+        variables: &DialogueVariables,
+    ) -> Option<usize> {
+        names.iter().find_map(|name| {
+            self.find_node(name)
+                .filter(|i| self.nodes[*i].can_enter(variables))
+        })
+    }
+}
+
+/// Text provider resolves localization keys of a dialogue into actual text.
+pub trait TextProvider {
+    /// Returns a text for the given localization key, if any.
+    fn text(&self, key: &str) -> Option<String>;
+}
+
+impl TextProvider for FxHashMap<String, String> {
+    fn text(&self, key: &str) -> Option<String> {
+        self.get(key).cloned()
+    }
+}
+
+/// An error that may occur during dialogue resource loading.
+#[derive(Debug)]
+pub enum DialogueResourceError {
+    /// An i/o error has occurred.
+    Io(FileLoadError),
+
+    /// An error that may occur due to version incompatibilities.
+    Visit(VisitError),
+}
+
+impl Display for DialogueResourceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DialogueResourceError::Io(v) => {
+                write!(f, "A file load error has occurred {v:?}")
+            }
+            DialogueResourceError::Visit(v) => {
+                write!(
+                    f,
+                    "An error that may occur due to version incompatibilities. {v:?}"
+                )
+            }
+        }
+    }
+}
+
+impl From<FileLoadError> for DialogueResourceError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<VisitError> for DialogueResourceError {
+    fn from(e: VisitError) -> Self {
+        Self::Visit(e)
+    }
+}
+
+/// State of the [`DialogueResource`]
+#[derive(Debug, Visit, Default, Reflect)]
+pub struct DialogueResourceState {
+    /// Actual dialogue.
+    pub dialogue: Dialogue,
+}
+
+impl ResourceData for DialogueResourceState {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+
+    fn save(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut visitor = Visitor::new();
+        self.dialogue.visit("Dialogue", &mut visitor)?;
+        visitor.save_binary(path)?;
+        Ok(())
+    }
+
+    fn can_be_saved(&self) -> bool {
+        true
+    }
+}
+
+impl TypeUuidProvider for DialogueResourceState {
+    fn type_uuid() -> Uuid {
+        DIALOGUE_RESOURCE_UUID
+    }
+}
+
+impl DialogueResourceState {
+    /// Load a dialogue resource from the specific file path.
+    pub async fn from_file(
+        path: &Path,
+        io: &dyn ResourceIo,
+    ) -> Result<Self, DialogueResourceError> {
+        let bytes = io.load_file(path).await?;
+        let mut visitor = Visitor::load_from_memory(&bytes)?;
+        let mut dialogue = Dialogue::default();
+        dialogue.visit("Dialogue", &mut visitor)?;
+        Ok(Self { dialogue })
+    }
+}
+
+/// Type alias for dialogue resources.
+pub type DialogueResource = Resource<DialogueResourceState>;
@@ -0,0 +1,348 @@
+//! Runtime part of dialogues. See [`DialogueRunner`] docs for more info.
+
+use crate::resource::dialogue::{Dialogue, DialogueResource, DialogueVariables, TextProvider};
+use std::{
+    collections::VecDeque,
+    fmt::{Display, Formatter},
+};
+
+/// An error that may occur during dialogue playback.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DialogueError {
+    /// Dialogue resource is not loaded (yet) or failed to load.
+    NotLoaded,
+    /// The dialogue was not started or it has already ended.
+    NotRunning,
+    /// Current node has available choices, so the dialogue cannot be advanced without a choice.
+    ChoiceRequired,
+    /// A choice with the given index does not exist or it is not available.
+    InvalidChoice(usize),
+}
+
+impl Display for DialogueError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DialogueError::NotLoaded => write!(f, "Dialogue resource is not loaded."),
+            DialogueError::NotRunning => write!(f, "Dialogue is not running."),
+            DialogueError::ChoiceRequired => {
+                write!(f, "Current dialogue node requires a choice.")
+            }
+            DialogueError::InvalidChoice(index) => {
+                write!(f, "Choice {index} does not exist or it is not available.")
+            }
+        }
+    }
+}
+
+/// An event, that is emitted when a dialogue node is entered. Events are used to run game logic
+/// (give an item to the player, start a quest, change dialogue variables, etc.) from dialogues.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DialogueEvent {
+    /// Name of the node, that emitted the event.
+    pub node: String,
+    /// Name of the event.
+    pub name: String,
+}
+
+/// An available choice of a [`DialogueLine`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DialogueLineChoice {
+    /// Index of the choice, it should be passed to [`DialogueRunner::choose`].
+    pub index: usize,
+    /// Localization key of the text of the choice.
+    pub text_key: String,
+}
+
+impl DialogueLineChoice {
+    /// Returns a localized text of the choice or its localization key, if there's no text for it.
+    pub fn text(&self, provider: &dyn TextProvider) -> String {
+        provider
+            .text(&self.text_key)
+            .unwrap_or_else(|| self.text_key.clone())
+    }
+}
+
+/// A line of a dialogue, that should be shown to the player.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DialogueLine {
+    /// Name of the node of the line.
+    pub node: String,
+    /// Name of the speaker.
+    pub speaker: String,
+    /// Localization key of the text of the line.
+    pub text_key: String,
+    /// A list of available choices. Choices, that have unsatisfied conditions, are not included.
+    pub choices: Vec<DialogueLineChoice>,
+}
+
+impl DialogueLine {
+    /// Returns a localized text of the line or its localization key, if there's no text for it.
+    pub fn text(&self, provider: &dyn TextProvider) -> String {
+        provider
+            .text(&self.text_key)
+            .unwrap_or_else(|| self.text_key.clone())
+    }
+}
+
+/// Dialogue runner plays a dialogue from a [`DialogueResource`]. It keeps the current node of the
+/// dialogue and produces [`DialogueLine`]s, that should be shown to the player.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::resource::dialogue::{
+/// #     runner::{DialogueLine, DialogueRunner},
+/// #     DialogueVariables,
+/// # };
+/// fn show(line: &DialogueLine) {
+///     println!("{}: {}", line.speaker, line.text_key);
+///     for choice in line.choices.iter() {
+///         println!("{}. {}", choice.index, choice.text_key);
+///     }
+/// }
+///
+/// fn talk(runner: &mut DialogueRunner, variables: &DialogueVariables) {
+///     if let Ok(Some(line)) = runner.start(variables) {
+///         show(&line);
+///     }
+///
+///     // Show every line until the player has to make a choice.
+///     for line in runner.lines(variables) {
+///         show(&line);
+///     }
+///
+///     while let Some(event) = runner.pop_event() {
+///         println!("Event {} from {}", event.name, event.node);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DialogueRunner {
+    dialogue: DialogueResource,
+    current: Option<usize>,
+    events: VecDeque<DialogueEvent>,
+}
+
+impl DialogueRunner {
+    /// Creates a new dialogue runner for the given dialogue. The dialogue must be started using
+    /// [`Self::start`].
+    pub fn new(dialogue: DialogueResource) -> Self {
+        Self {
+            dialogue,
+            current: None,
+            events: Default::default(),
+        }
+    }
+
+    /// Returns a reference to the dialogue resource.
+    pub fn dialogue(&self) -> &DialogueResource {
+        &self.dialogue
+    }
+
+    /// Returns `true` if the dialogue is not started or it has ended.
+    pub fn is_finished(&self) -> bool {
+        self.current.is_none()
+    }
+
+    /// Takes the oldest event from the internal queue.
+    pub fn pop_event(&mut self) -> Option<DialogueEvent> {
+        self.events.pop_front()
+    }
+
+    fn make_line(
+        dialogue: &Dialogue,
+        index: usize,
+        variables: &DialogueVariables,
+    ) -> Option<DialogueLine> {
+        let node = dialogue.nodes.get(index)?;
+        Some(DialogueLine {
+            node: node.name.clone(),
+            speaker: node.speaker.clone(),
+            text_key: node.text_key.clone(),
+            choices: node
+                .choices
+                .iter()
+                .enumerate()
+                .filter(|(_, choice)| choice.is_available(variables))
+                .map(|(index, choice)| DialogueLineChoice {
+                    index,
+                    text_key: choice.text_key.clone(),
+                })
+                .collect(),
+        })
+    }
+
+    fn enter(
+        &mut self,
+        dialogue: &Dialogue,
+        index: Option<usize>,
+        variables: &DialogueVariables,
+    ) -> Option<DialogueLine> {
+        self.current = index;
+        let index = index?;
+        let node = &dialogue.nodes[index];
+        self.events
+            .extend(node.events.iter().map(|name| DialogueEvent {
+                node: node.name.clone(),
+                name: name.clone(),
+            }));
+        Self::make_line(dialogue, index, variables)
+    }
+
+    /// Starts the dialogue from the first available start node and returns its line. `None` means
+    /// that there's no available start node.
+    pub fn start(
+        &mut self,
+        variables: &DialogueVariables,
+    ) -> Result<Option<DialogueLine>, DialogueError> {
+        let resource = self.dialogue.clone();
+        let mut state = resource.state();
+        let dialogue = &state.data().ok_or(DialogueError::NotLoaded)?.dialogue;
+        let index = dialogue.first_available(&dialogue.start, variables);
+        Ok(self.enter(dialogue, index, variables))
+    }
+
+    /// Returns the line of the current node. Availability of choices is checked against the given
+    /// set of variables.
+    pub fn current_line(&self, variables: &DialogueVariables) -> Option<DialogueLine> {
+        let mut state = self.dialogue.state();
+        let dialogue = &state.data()?.dialogue;
+        Self::make_line(dialogue, self.current?, variables)
+    }
+
+    /// Moves the dialogue to the next node and returns its line. It is possible only if the
+    /// current node has no available choices, otherwise [`DialogueError::ChoiceRequired`] is
+    /// returned. `Ok(None)` means that the dialogue has ended.
+    pub fn advance(
+        &mut self,
+        variables: &DialogueVariables,
+    ) -> Result<Option<DialogueLine>, DialogueError> {
+        let resource = self.dialogue.clone();
+        let mut state = resource.state();
+        let dialogue = &state.data().ok_or(DialogueError::NotLoaded)?.dialogue;
+        let node = self
+            .current
+            .and_then(|i| dialogue.nodes.get(i))
+            .ok_or(DialogueError::NotRunning)?;
+        if node.choices.iter().any(|c| c.is_available(variables)) {
+            return Err(DialogueError::ChoiceRequired);
+        }
+        let next = dialogue.first_available(&node.next, variables);
+        Ok(self.enter(dialogue, next, variables))
+    }
+
+    /// Makes a choice with the given index (see [`DialogueLineChoice::index`]) and returns the
+    /// line of the next node. `Ok(None)` means that the dialogue has ended.
+    pub fn choose(
+        &mut self,
+        index: usize,
+        variables: &DialogueVariables,
+    ) -> Result<Option<DialogueLine>, DialogueError> {
+        let resource = self.dialogue.clone();
+        let mut state = resource.state();
+        let dialogue = &state.data().ok_or(DialogueError::NotLoaded)?.dialogue;
+        let node = self
+            .current
+            .and_then(|i| dialogue.nodes.get(i))
+            .ok_or(DialogueError::NotRunning)?;
+        let choice = node
+            .choices
+            .get(index)
+            .filter(|c| c.is_available(variables))
+            .ok_or(DialogueError::InvalidChoice(index))?;
+        let next = dialogue.first_available(&choice.next, variables);
+        Ok(self.enter(dialogue, next, variables))
+    }
+
+    /// Returns an iterator, that advances the dialogue and yields lines until a choice is required
+    /// or the dialogue ends.
+    pub fn lines<'a>(
+        &'a mut self,
+        variables: &'a DialogueVariables,
+    ) -> impl Iterator<Item = DialogueLine> + 'a {
+        std::iter::from_fn(move || self.advance(variables).ok().flatten())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::{untyped::ResourceKind, Resource},
+        resource::dialogue::{
+            runner::{DialogueError, DialogueEvent, DialogueRunner},
+            ComparisonOperator, Dialogue, DialogueChoice, DialogueCondition, DialogueNode,
+            DialogueResourceState, DialogueValue, DialogueVariables,
+        },
+    };
+
+    fn node(name: &str, next: &[&str]) -> DialogueNode {
+        DialogueNode {
+            name: name.to_string(),
+            text_key: format!("{name}_text"),
+            next: next.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_dialogue_runner() {
+        let mut greeting = node("Greeting", &[]);
+        greeting.choices = vec![
+            DialogueChoice {
+                text_key: "Bye".to_string(),
+                next: vec!["Farewell".to_string()],
+                ..Default::default()
+            },
+            DialogueChoice {
+                text_key: "Gold".to_string(),
+                conditions: vec![DialogueCondition {
+                    variable: "gold".to_string(),
+                    operator: ComparisonOperator::GreaterOrEqual,
+                    value: DialogueValue::Number(10.0),
+                }],
+                next: vec!["Trade".to_string()],
+            },
+        ];
+        let mut trade = node("Trade", &["Farewell"]);
+        trade.events = vec!["OpenShop".to_string()];
+
+        let dialogue = Dialogue {
+            start: vec!["Greeting".to_string()],
+            nodes: vec![greeting, trade, node("Farewell", &[])],
+        };
+        let mut runner = DialogueRunner::new(Resource::new_ok(
+            ResourceKind::Embedded,
+            DialogueResourceState { dialogue },
+        ));
+
+        let mut variables = DialogueVariables::default();
+        let line = runner.start(&variables).unwrap().unwrap();
+        assert_eq!(line.node, "Greeting");
+        // Not enough gold.
+        assert_eq!(line.choices.len(), 1);
+        assert_eq!(
+            runner.choose(1, &variables),
+            Err(DialogueError::InvalidChoice(1))
+        );
+        assert_eq!(
+            runner.advance(&variables),
+            Err(DialogueError::ChoiceRequired)
+        );
+
+        variables.insert("gold".to_string(), DialogueValue::Number(15.0));
+        assert_eq!(runner.current_line(&variables).unwrap().choices.len(), 2);
+        let line = runner.choose(1, &variables).unwrap().unwrap();
+        assert_eq!(line.node, "Trade");
+        assert_eq!(
+            runner.pop_event(),
+            Some(DialogueEvent {
+                node: "Trade".to_string(),
+                name: "OpenShop".to_string()
+            })
+        );
+
+        let lines = runner.lines(&variables).map(|l| l.node).collect::<Vec<_>>();
+        assert_eq!(lines, ["Farewell"]);
+        assert!(runner.is_finished());
+    }
+}
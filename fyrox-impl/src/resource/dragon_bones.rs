@@ -0,0 +1,799 @@
+//! Importer for 2D skeletons in DragonBones JSON format (version 5.x). See [`import`] docs for
+//! more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector2, Vector3},
+        log::Log,
+        math::{
+            curve::{Curve, CurveKey, CurveKeyKind},
+            TriangleDefinition,
+        },
+        pool::Handle,
+        sstorage::ImmutableString,
+    },
+    generic_animation::{
+        container::{InterpolationMode, TrackDataContainer, TrackValueKind},
+        track::Track,
+        value::ValueBinding,
+    },
+    material::{shader::SamplerFallback, Material, MaterialResource, PropertyValue},
+    resource::texture::TextureResource,
+    scene::{
+        animation::{Animation, AnimationContainer, AnimationPlayerBuilder},
+        base::BaseBuilder,
+        dim2::skinned_sprite::{SkinnedSpriteBuilder, SkinnedSpriteVertex},
+        graph::Graph,
+        node::Node,
+        pivot::PivotBuilder,
+        transform::TransformBuilder,
+    },
+};
+use fxhash::FxHashMap;
+use fyrox_graph::BaseSceneGraph;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+
+/// Distance along Z axis (in pixels) between the slots of a skeleton, it defines the draw order.
+const SLOT_DEPTH_STEP: f32 = 0.1;
+
+/// An error that may occur during DragonBones skeleton import.
+#[derive(Debug)]
+pub enum DragonBonesError {
+    /// The data is not a valid JSON or it does not match DragonBones format.
+    Json(serde_json::Error),
+    /// The skeleton does not have an armature with the requested name.
+    NoSuchArmature,
+    /// A bone refers to a parent, that does not exist or is defined after the bone.
+    InvalidBoneParent(String),
+}
+
+impl Display for DragonBonesError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DragonBonesError::Json(v) => {
+                write!(f, "Unable to parse DragonBones data. Reason: {v}")
+            }
+            DragonBonesError::NoSuchArmature => {
+                write!(f, "There is no such armature in the skeleton.")
+            }
+            DragonBonesError::InvalidBoneParent(v) => {
+                write!(f, "Bone {v} has invalid parent.")
+            }
+        }
+    }
+}
+
+impl From<serde_json::Error> for DragonBonesError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(default)]
+struct DbTransform {
+    x: f32,
+    y: f32,
+    #[serde(rename = "skY")]
+    rotation: f32,
+    #[serde(rename = "scX")]
+    scale_x: Option<f32>,
+    #[serde(rename = "scY")]
+    scale_y: Option<f32>,
+}
+
+impl DbTransform {
+    fn scale(&self) -> Vector2<f32> {
+        Vector2::new(self.scale_x.unwrap_or(1.0), self.scale_y.unwrap_or(1.0))
+    }
+
+    fn matrix(&self) -> Matrix4<f32> {
+        let scale = self.scale();
+        Matrix4::new_translation(&Vector3::new(self.x, self.y, 0.0))
+            * Matrix4::new_rotation(Vector3::z() * self.rotation.to_radians())
+            * Matrix4::new_nonuniform_scaling(&Vector3::new(scale.x, scale.y, 1.0))
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct DbBone {
+    name: String,
+    parent: Option<String>,
+    transform: DbTransform,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct DbSlot {
+    name: String,
+    parent: String,
+    #[serde(rename = "displayIndex")]
+    display_index: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct DbPivot {
+    x: f32,
+    y: f32,
+}
+
+impl Default for DbPivot {
+    fn default() -> Self {
+        Self { x: 0.5, y: 0.5 }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct DbDisplay {
+    name: String,
+    path: Option<String>,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    transform: DbTransform,
+    pivot: DbPivot,
+    vertices: Vec<f32>,
+    uvs: Vec<f32>,
+    triangles: Vec<u32>,
+    weights: Vec<f32>,
+    #[serde(rename = "bonePose")]
+    bone_pose: Vec<f32>,
+}
+
+impl DbDisplay {
+    fn texture_name(&self) -> &str {
+        self.path.as_deref().unwrap_or(&self.name)
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct DbSkinSlot {
+    name: String,
+    display: Vec<DbDisplay>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct DbSkin {
+    slot: Vec<DbSkinSlot>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct DbFrame {
+    duration: u32,
+    #[serde(rename = "tweenEasing")]
+    tween_easing: Option<f32>,
+    x: Option<f32>,
+    y: Option<f32>,
+    rotate: f32,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct DbBoneTimeline {
+    name: String,
+    #[serde(rename = "translateFrame")]
+    translate_frame: Vec<DbFrame>,
+    #[serde(rename = "rotateFrame")]
+    rotate_frame: Vec<DbFrame>,
+    #[serde(rename = "scaleFrame")]
+    scale_frame: Vec<DbFrame>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct DbAnimation {
+    name: String,
+    duration: u32,
+    #[serde(rename = "playTimes")]
+    play_times: u32,
+    bone: Vec<DbBoneTimeline>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct DbArmature {
+    name: String,
+    #[serde(rename = "frameRate")]
+    frame_rate: Option<f32>,
+    bone: Vec<DbBone>,
+    slot: Vec<DbSlot>,
+    skin: Vec<DbSkin>,
+    animation: Vec<DbAnimation>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct DbSkeleton {
+    #[serde(rename = "frameRate")]
+    frame_rate: Option<f32>,
+    armature: Vec<DbArmature>,
+}
+
+#[derive(Deserialize, Default, Clone)]
+#[serde(default)]
+struct DbSubTexture {
+    name: String,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    #[serde(rename = "frameX")]
+    frame_x: f32,
+    #[serde(rename = "frameY")]
+    frame_y: f32,
+    #[serde(rename = "frameWidth")]
+    frame_width: Option<f32>,
+    #[serde(rename = "frameHeight")]
+    frame_height: Option<f32>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct DbAtlas {
+    width: Option<f32>,
+    height: Option<f32>,
+    #[serde(rename = "SubTexture")]
+    sub_texture: Vec<DbSubTexture>,
+}
+
+/// Options of DragonBones skeleton import.
+#[derive(Clone, Debug)]
+pub struct DragonBonesImportOptions {
+    /// Name of the armature to import, the first armature of the skeleton is imported if `None`.
+    pub armature: Option<String>,
+    /// Amount of pixels of the skeleton per one unit of the scene.
+    pub pixels_per_unit: f32,
+    /// A texture atlas with the parts of the skeleton.
+    pub texture: Option<TextureResource>,
+}
+
+impl Default for DragonBonesImportOptions {
+    fn default() -> Self {
+        Self {
+            armature: None,
+            pixels_per_unit: 100.0,
+            texture: None,
+        }
+    }
+}
+
+struct Atlas {
+    size: Vector2<f32>,
+    regions: FxHashMap<String, DbSubTexture>,
+}
+
+impl Atlas {
+    fn uv(&self, region: &DbSubTexture, local_uv: Vector2<f32>) -> Vector2<f32> {
+        Vector2::new(
+            (region.x + local_uv.x * region.width) / self.size.x,
+            (region.y + local_uv.y * region.height) / self.size.y,
+        )
+    }
+}
+
+/// Reads bone weights of a weighted mesh: for every vertex there is a count of bones followed by
+/// pairs of bone index (in the armature) and weight.
+fn read_weights(weights: &[f32], vertex_count: usize) -> Option<Vec<Vec<(usize, f32)>>> {
+    let mut result = Vec::with_capacity(vertex_count);
+    let mut cursor = 0;
+    for _ in 0..vertex_count {
+        let count = *weights.get(cursor)? as usize;
+        cursor += 1;
+        let mut bones = Vec::with_capacity(count);
+        for _ in 0..count {
+            bones.push((*weights.get(cursor)? as usize, *weights.get(cursor + 1)?));
+            cursor += 2;
+        }
+        result.push(bones);
+    }
+    Some(result)
+}
+
+fn make_track(
+    target: Handle<Node>,
+    binding: ValueBinding,
+    kind: TrackValueKind,
+    curves: Vec<Vec<CurveKey>>,
+) -> Track<Handle<Node>> {
+    let mut data = TrackDataContainer::new(kind);
+    if let TrackValueKind::UnitQuaternion = kind {
+        data.mode = InterpolationMode::ShortPath;
+    }
+    for (i, keys) in curves.into_iter().enumerate() {
+        data.curves_mut()[i] = Curve::from(keys);
+    }
+    let mut track = Track::new(data, binding);
+    track.set_target(target);
+    track
+}
+
+/// Converts a list of DragonBones frames into a set of curves. The given closure maps a frame to
+/// the values of the curves.
+fn frames_to_curves<const N: usize>(
+    frames: &[DbFrame],
+    frame_rate: f32,
+    mut value: impl FnMut(&DbFrame) -> [f32; N],
+) -> Vec<Vec<CurveKey>> {
+    let mut curves = vec![Vec::new(); N];
+    let mut time = 0.0;
+    for frame in frames {
+        let kind = if frame.tween_easing.is_some() {
+            CurveKeyKind::Linear
+        } else {
+            CurveKeyKind::Constant
+        };
+        for (curve, value) in curves.iter_mut().zip(value(frame)) {
+            curve.push(CurveKey::new(time, value, kind.clone()));
+        }
+        time += frame.duration as f32 / frame_rate;
+    }
+    curves
+}
+
+/// Imports a 2D skeleton in DragonBones JSON format (`*_ske.json`) into the graph. Every bone of
+/// the skeleton becomes a pivot node, every visible slot becomes a
+/// [`crate::scene::dim2::skinned_sprite::SkinnedSprite`] node, animations of the bones are put
+/// into an animation player. All the nodes are put under a single root node, which handle is
+/// returned. The root node flips Y axis of the skeleton (DragonBones uses Y-down coordinates) and
+/// scales the skeleton according to [`DragonBonesImportOptions::pixels_per_unit`].
+///
+/// The texture atlas description (`*_tex.json`) is required for image displays and to map texture
+/// coordinates of the meshes to the atlas texture (see [`DragonBonesImportOptions::texture`]).
+///
+/// # Limitations
+///
+/// Only the first display of each slot of the default skin is imported. Skew, slot animations,
+/// mesh deformation (FFD) animations, inverse kinematics constraints and easing curves are not
+/// supported, tweened frames are interpolated linearly.
+pub fn import(
+    graph: &mut Graph,
+    skeleton_json: &str,
+    atlas_json: Option<&str>,
+    options: &DragonBonesImportOptions,
+) -> Result<Handle<Node>, DragonBonesError> {
+    let skeleton: DbSkeleton = serde_json::from_str(skeleton_json)?;
+    let atlas = match atlas_json {
+        Some(atlas_json) => {
+            let atlas: DbAtlas = serde_json::from_str(atlas_json)?;
+            let texture_size = options
+                .texture
+                .as_ref()
+                .and_then(|t| t.state().data().and_then(|t| t.kind().rectangle_size()));
+            let size = match (atlas.width, atlas.height) {
+                (Some(width), Some(height)) => Vector2::new(width, height),
+                _ => texture_size.map_or(Vector2::new(1.0, 1.0), |s| s.cast::<f32>()),
+            };
+            Some(Atlas {
+                size,
+                regions: atlas
+                    .sub_texture
+                    .into_iter()
+                    .map(|t| (t.name.clone(), t))
+                    .collect(),
+            })
+        }
+        None => None,
+    };
+
+    let armature = match options.armature.as_ref() {
+        Some(name) => skeleton.armature.iter().find(|a| &a.name == name),
+        None => skeleton.armature.first(),
+    }
+    .ok_or(DragonBonesError::NoSuchArmature)?;
+    let frame_rate = armature
+        .frame_rate
+        .or(skeleton.frame_rate)
+        .unwrap_or(24.0)
+        .max(1.0);
+
+    let scale = 1.0 / options.pixels_per_unit.max(f32::EPSILON);
+    let root_transform = Matrix4::new_nonuniform_scaling(&Vector3::new(scale, -scale, scale));
+    let root = PivotBuilder::new(
+        BaseBuilder::new()
+            .with_name(armature.name.clone())
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_scale(Vector3::new(scale, -scale, scale))
+                    .build(),
+            ),
+    )
+    .build(graph);
+
+    // Bones. Parents are always defined before their children. The matrices of the bones are in
+    // the space of the armature.
+    let mut bone_handles = Vec::with_capacity(armature.bone.len());
+    let mut bone_matrices: Vec<Matrix4<f32>> = Vec::with_capacity(armature.bone.len());
+    let mut bone_indices = FxHashMap::default();
+    for (index, bone) in armature.bone.iter().enumerate() {
+        let (parent_handle, parent_matrix) = match bone.parent.as_ref() {
+            Some(parent) => {
+                let parent_index = *bone_indices
+                    .get(parent.as_str())
+                    .ok_or_else(|| DragonBonesError::InvalidBoneParent(bone.name.clone()))?;
+                (bone_handles[parent_index], bone_matrices[parent_index])
+            }
+            None => (root, Matrix4::identity()),
+        };
+
+        let matrix = parent_matrix * bone.transform.matrix();
+        let bind_pose: Matrix4<f32> = root_transform * matrix;
+        let scale = bone.transform.scale();
+        let handle = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name(bone.name.clone())
+                .with_inv_bind_pose_transform(bind_pose.try_inverse().unwrap_or_default())
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(bone.transform.x, bone.transform.y, 0.0))
+                        .with_local_rotation(UnitQuaternion::from_axis_angle(
+                            &Vector3::z_axis(),
+                            bone.transform.rotation.to_radians(),
+                        ))
+                        .with_local_scale(Vector3::new(scale.x, scale.y, 1.0))
+                        .build(),
+                ),
+        )
+        .build(graph);
+        graph.link_nodes(handle, parent_handle);
+
+        bone_handles.push(handle);
+        bone_matrices.push(matrix);
+        bone_indices.insert(bone.name.as_str(), index);
+    }
+
+    // Every part of the skeleton shares the same material, so the parts are batched together.
+    let mut material = Material::standard_2d();
+    if let Err(e) = material.set_property(
+        &ImmutableString::new("diffuseTexture"),
+        PropertyValue::Sampler {
+            value: options.texture.clone(),
+            fallback: SamplerFallback::White,
+        },
+    ) {
+        Log::err(format!("Unable to set skeleton texture: {e}"));
+    }
+    let material = MaterialResource::new_ok(Default::default(), material);
+
+    // Slots.
+    let skin = armature.skin.first();
+    for (slot_index, slot) in armature.slot.iter().enumerate() {
+        let display_index = slot.display_index.unwrap_or(0);
+        let Some(display) = skin
+            .and_then(|skin| skin.slot.iter().find(|s| s.name == slot.name))
+            .and_then(|s| {
+                usize::try_from(display_index)
+                    .ok()
+                    .and_then(|i| s.display.get(i))
+            })
+        else {
+            continue;
+        };
+        let Some(&slot_bone) = bone_indices.get(slot.parent.as_str()) else {
+            Log::warn(format!("Slot {} has invalid parent bone.", slot.name));
+            continue;
+        };
+
+        let region = atlas
+            .as_ref()
+            .and_then(|atlas| atlas.regions.get(display.texture_name()));
+        let uv = |local_uv: Vector2<f32>| match (atlas.as_ref(), region) {
+            (Some(atlas), Some(region)) => atlas.uv(region, local_uv),
+            _ => local_uv,
+        };
+        // Later slots are closer to the camera.
+        let depth = -(slot_index as f32) * SLOT_DEPTH_STEP;
+        let to_bind_space = |position: Vector3<f32>| {
+            root_transform
+                .transform_point(&Vector3::new(position.x, position.y, depth).into())
+                .coords
+        };
+
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+        match display.kind.as_deref() {
+            Some("mesh") => {
+                let vertex_count = display.vertices.len() / 2;
+                let weights = if display.weights.is_empty() {
+                    None
+                } else {
+                    read_weights(&display.weights, vertex_count)
+                };
+                // Bone matrices of weighted meshes at the moment of binding, they may differ from
+                // the setup pose of the bones.
+                let bone_pose = display
+                    .bone_pose
+                    .chunks_exact(7)
+                    .map(|p| {
+                        (
+                            p[0] as usize,
+                            Matrix4::new(
+                                p[1], p[3], 0.0, p[5], //
+                                p[2], p[4], 0.0, p[6], //
+                                0.0, 0.0, 1.0, 0.0, //
+                                0.0, 0.0, 0.0, 1.0,
+                            ),
+                        )
+                    })
+                    .collect::<FxHashMap<_, _>>();
+
+                for i in 0..vertex_count {
+                    let position =
+                        Vector3::new(display.vertices[2 * i], display.vertices[2 * i + 1], 0.0);
+                    let tex_coord = uv(Vector2::new(
+                        display.uvs.get(2 * i).cloned().unwrap_or_default(),
+                        display.uvs.get(2 * i + 1).cloned().unwrap_or_default(),
+                    ));
+                    let vertex = match weights.as_ref().and_then(|w| w.get(i)) {
+                        Some(bones) => {
+                            // Move the vertex from the binding pose to the setup pose.
+                            let mut setup_position = Vector3::default();
+                            for (bone, weight) in bones {
+                                let setup = bone_matrices.get(*bone).cloned().unwrap_or_default();
+                                let pose = bone_pose.get(bone).cloned().unwrap_or(setup);
+                                let local = pose
+                                    .try_inverse()
+                                    .unwrap_or_default()
+                                    .transform_point(&position.into());
+                                setup_position +=
+                                    setup.transform_point(&local).coords.scale(*weight);
+                            }
+                            SkinnedSpriteVertex::new(to_bind_space(setup_position), tex_coord)
+                                .with_bones(
+                                    &bones
+                                        .iter()
+                                        .map(|(bone, weight)| (*bone as u32, *weight))
+                                        .collect::<Vec<_>>(),
+                                )
+                        }
+                        None => {
+                            let local =
+                                display.transform.matrix().transform_point(&position.into());
+                            let armature_position =
+                                bone_matrices[slot_bone].transform_point(&local);
+                            SkinnedSpriteVertex::new(
+                                to_bind_space(armature_position.coords),
+                                tex_coord,
+                            )
+                            .with_bones(&[(slot_bone as u32, 1.0)])
+                        }
+                    };
+                    vertices.push(vertex);
+                }
+                triangles.extend(
+                    display
+                        .triangles
+                        .chunks_exact(3)
+                        .map(|t| TriangleDefinition([t[0], t[1], t[2]])),
+                );
+            }
+            None | Some("image") => {
+                let Some(region) = region else {
+                    Log::warn(format!(
+                        "Unable to find region {} in the atlas.",
+                        display.texture_name()
+                    ));
+                    continue;
+                };
+                let frame_size = Vector2::new(
+                    region.frame_width.unwrap_or(region.width),
+                    region.frame_height.unwrap_or(region.height),
+                );
+                let origin = Vector2::new(
+                    -display.pivot.x * frame_size.x - region.frame_x,
+                    -display.pivot.y * frame_size.y - region.frame_y,
+                );
+                let local_to_armature = bone_matrices[slot_bone] * display.transform.matrix();
+                for corner in [
+                    Vector2::new(0.0, 0.0),
+                    Vector2::new(1.0, 0.0),
+                    Vector2::new(1.0, 1.0),
+                    Vector2::new(0.0, 1.0),
+                ] {
+                    let position = Vector3::new(
+                        origin.x + corner.x * region.width,
+                        origin.y + corner.y * region.height,
+                        0.0,
+                    );
+                    let armature_position = local_to_armature.transform_point(&position.into());
+                    vertices.push(
+                        SkinnedSpriteVertex::new(
+                            to_bind_space(armature_position.coords),
+                            uv(corner),
+                        )
+                        .with_bones(&[(slot_bone as u32, 1.0)]),
+                    );
+                }
+                triangles.extend([TriangleDefinition([0, 1, 2]), TriangleDefinition([2, 3, 0])]);
+            }
+            Some(kind) => {
+                Log::warn(format!(
+                    "Display {} of type {kind} is not supported.",
+                    display.name
+                ));
+                continue;
+            }
+        }
+
+        let sprite = SkinnedSpriteBuilder::new(BaseBuilder::new().with_name(slot.name.clone()))
+            .with_material(material.clone())
+            .with_vertices(vertices)
+            .with_triangles(triangles)
+            .with_bones(bone_handles.clone())
+            .build(graph);
+        graph.link_nodes(sprite, root);
+    }
+
+    // Animations. Frame values are relative to the setup pose of the bones.
+    let mut animations = AnimationContainer::new();
+    for source in armature.animation.iter() {
+        let mut animation = Animation::default();
+        animation.set_name(&source.name);
+        animation.set_time_slice(0.0..(source.duration as f32 / frame_rate).max(f32::EPSILON));
+        animation.set_loop(source.play_times == 0);
+
+        for timeline in source.bone.iter() {
+            let Some(&bone_index) = bone_indices.get(timeline.name.as_str()) else {
+                continue;
+            };
+            let setup = &armature.bone[bone_index].transform;
+            let target = bone_handles[bone_index];
+
+            if !timeline.translate_frame.is_empty() {
+                let curves = frames_to_curves(&timeline.translate_frame, frame_rate, |f| {
+                    [
+                        setup.x + f.x.unwrap_or(0.0),
+                        setup.y + f.y.unwrap_or(0.0),
+                        0.0,
+                    ]
+                });
+                animation.add_track(make_track(
+                    target,
+                    ValueBinding::Position,
+                    TrackValueKind::Vector3,
+                    curves,
+                ));
+            }
+            if !timeline.rotate_frame.is_empty() {
+                let curves = frames_to_curves(&timeline.rotate_frame, frame_rate, |f| {
+                    [0.0, 0.0, (setup.rotation + f.rotate).to_radians()]
+                });
+                animation.add_track(make_track(
+                    target,
+                    ValueBinding::Rotation,
+                    TrackValueKind::UnitQuaternion,
+                    curves,
+                ));
+            }
+            if !timeline.scale_frame.is_empty() {
+                let scale = setup.scale();
+                let curves = frames_to_curves(&timeline.scale_frame, frame_rate, |f| {
+                    [
+                        scale.x * f.x.unwrap_or(1.0),
+                        scale.y * f.y.unwrap_or(1.0),
+                        1.0,
+                    ]
+                });
+                animation.add_track(make_track(
+                    target,
+                    ValueBinding::Scale,
+                    TrackValueKind::Vector3,
+                    curves,
+                ));
+            }
+        }
+
+        animations.add(animation);
+    }
+
+    if !armature.animation.is_empty() {
+        let player = AnimationPlayerBuilder::new(BaseBuilder::new().with_name("AnimationPlayer"))
+            .with_animations(animations)
+            .build(graph);
+        graph.link_nodes(player, root);
+    }
+
+    Ok(root)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        resource::dragon_bones::{import, DragonBonesImportOptions},
+        scene::{animation::AnimationPlayer, dim2::skinned_sprite::SkinnedSprite, graph::Graph},
+    };
+    use fyrox_graph::SceneGraph;
+
+    const SKELETON: &str = r#"{
+        "frameRate": 10,
+        "armature": [{
+            "name": "Armature",
+            "bone": [
+                { "name": "root" },
+                { "name": "arm", "parent": "root", "transform": { "x": 100, "skY": 90 } }
+            ],
+            "slot": [{ "name": "hand", "parent": "arm" }],
+            "skin": [{ "slot": [{ "name": "hand", "display": [{ "name": "hand" }] }] }],
+            "animation": [{
+                "name": "wave",
+                "duration": 10,
+                "playTimes": 0,
+                "bone": [{
+                    "name": "arm",
+                    "translateFrame": [{ "duration": 10, "tweenEasing": 0 }, { "x": 100 }]
+                }]
+            }]
+        }]
+    }"#;
+
+    const ATLAS: &str = r#"{
+        "width": 64,
+        "height": 64,
+        "SubTexture": [{ "name": "hand", "x": 0, "y": 0, "width": 20, "height": 10 }]
+    }"#;
+
+    fn bounds(positions: &[Vector3<f32>]) -> (Vector3<f32>, Vector3<f32>) {
+        positions.iter().fold(
+            (Vector3::repeat(f32::MAX), Vector3::repeat(-f32::MAX)),
+            |(min, max), p| (min.inf(p), max.sup(p)),
+        )
+    }
+
+    #[test]
+    fn test_dragon_bones_import() {
+        let mut graph = Graph::new();
+        let root = import(
+            &mut graph,
+            SKELETON,
+            Some(ATLAS),
+            &DragonBonesImportOptions::default(),
+        )
+        .unwrap();
+        graph.update_hierarchical_data();
+
+        let (sprite, _) = graph
+            .find_by_name(root, "hand")
+            .expect("Slot must be imported");
+        let (arm, _) = graph.find_by_name(root, "arm").unwrap();
+
+        // The image is centered at the arm bone and rotated by 90 degrees, Y axis is flipped.
+        let positions = graph[sprite]
+            .cast::<SkinnedSprite>()
+            .unwrap()
+            .skinned_positions(&graph);
+        assert_eq!(positions.len(), 4);
+        let (min, max) = bounds(&positions);
+        assert!((min - Vector3::new(0.95, -0.1, 0.0)).norm() < 1.0e-5);
+        assert!((max - Vector3::new(1.05, 0.1, 0.0)).norm() < 1.0e-5);
+
+        // Moving the bone must move the sprite.
+        graph[arm]
+            .local_transform_mut()
+            .set_position(Vector3::new(200.0, 0.0, 0.0));
+        graph.update_hierarchical_data();
+        let positions = graph[sprite]
+            .cast::<SkinnedSprite>()
+            .unwrap()
+            .skinned_positions(&graph);
+        let (min, max) = bounds(&positions);
+        assert!(((min + max).scale(0.5) - Vector3::new(2.0, 0.0, 0.0)).norm() < 1.0e-5);
+
+        let (player, _) = graph
+            .find_by_name(root, "AnimationPlayer")
+            .expect("Animation player must be created");
+        let animations = graph[player]
+            .cast::<AnimationPlayer>()
+            .unwrap()
+            .animations();
+        let animation = animations.iter().next().unwrap();
+        assert_eq!(animation.name(), "wave");
+        assert_eq!(animation.tracks().len(), 1);
+        assert!(animation.is_loop());
+        assert_eq!(animation.time_slice(), 0.0..1.0);
+    }
+}
@@ -23,7 +23,7 @@ use crate::{
         sstorage::ImmutableString,
     },
     graph::BaseSceneGraph,
-    material::{shader::SamplerFallback, PropertyValue},
+    material::{shader::SamplerFallback, Material, PropertyValue},
     resource::{
         fbx::{
             document::FbxDocument,
@@ -35,7 +35,7 @@ use crate::{
                 FbxComponent, FbxMapping, FbxScene,
             },
         },
-        model::{MaterialSearchOptions, ModelImportOptions},
+        model::{MaterialSearchOptions, ModelImportOptions, TangentGenerationMethod},
         texture::Texture,
     },
     scene::{
@@ -296,6 +296,17 @@ async fn create_surfaces(
             ));
             surface.vertex_weights = data.skin_data;
             let material = fbx_scene.get(material_handle).as_material()?;
+
+            if let Some(remap) = model_import_options
+                .material_remap
+                .iter()
+                .find(|remap| remap.source == material.name)
+            {
+                surface.set_material(resource_manager.request::<Material>(&remap.material));
+                surfaces.push(surface);
+                continue;
+            }
+
             if let Err(e) = surface.material().data_ref().set_property(
                 &ImmutableString::new("diffuseColor"),
                 PropertyValue::Color(material.diffuse_color),
@@ -600,7 +611,10 @@ async fn convert_mesh(
         )
         .await?;
 
-        if geom.tangents.is_none() {
+        // Tangents of all meshes are recalculated after conversion, if requested.
+        if geom.tangents.is_none()
+            && model_import_options.tangent_generation == TangentGenerationMethod::Auto
+        {
             for surface in surfaces.iter_mut() {
                 surface.data().data_ref().calculate_tangents().unwrap();
             }
@@ -647,8 +661,8 @@ async fn convert_model(
 ) -> Result<Handle<Node>, FbxError> {
     let base = convert_model_to_base(model);
 
-    // Create node with correct kind.
-    let node_handle = if !model.geoms.is_empty() {
+    // Create node with correct kind. Geometry is not needed, if only animations are imported.
+    let node_handle = if !model.geoms.is_empty() && !model_import_options.animation_only {
         convert_mesh(
             base,
             fbx_scene,
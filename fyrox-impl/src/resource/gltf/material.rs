@@ -8,7 +8,7 @@ use crate::{
         Material, MaterialResource, PropertyValue,
     },
     resource::{
-        model::MaterialSearchOptions,
+        model::{MaterialRemap, MaterialSearchOptions},
         texture::{Texture, TextureError, TextureImportOptions, TextureResource},
     },
 };
@@ -122,9 +122,17 @@ pub async fn import_materials(
     gltf: &Document,
     textures: &[TextureResource],
     resource_manager: &ResourceManager,
+    material_remap: &[MaterialRemap],
 ) -> Result<Vec<MaterialResource>> {
     let mut result: Vec<MaterialResource> = Vec::with_capacity(gltf.materials().len());
     for mat in gltf.materials() {
+        if let Some(remap) = material_remap
+            .iter()
+            .find(|remap| Some(remap.source.as_str()) == mat.name())
+        {
+            result.push(resource_manager.request::<Material>(&remap.material));
+            continue;
+        }
         match import_material(mat, textures, resource_manager).await {
             Ok(res) => result.push(res),
             Err(err) => {
@@ -19,7 +19,9 @@ use crate::graph::BaseSceneGraph;
 use crate::graph::NodeMapping;
 use crate::gui::core::io::FileLoadError;
 use crate::material::MaterialResource;
-use crate::resource::model::{MaterialSearchOptions, Model, ModelImportOptions};
+use crate::resource::model::{
+    self, MaterialRemap, MaterialSearchOptions, Model, ModelImportOptions,
+};
 use crate::resource::texture::{TextureError, TextureResource};
 use crate::scene::animation::{AnimationContainer, AnimationPlayerBuilder};
 use crate::scene::base::BaseBuilder;
@@ -204,6 +206,7 @@ struct ImportContext {
     resource_manager: ResourceManager,
     model_path: PathBuf,
     search_options: MaterialSearchOptions,
+    material_remap: Vec<MaterialRemap>,
 }
 
 impl ImportContext {
@@ -302,7 +305,8 @@ async fn load(
         io,
         resource_manager,
         model_path: path.clone(),
-        search_options: options.material_search_options,
+        search_options: options.material_search_options.clone(),
+        material_remap: options.material_remap.clone(),
     };
     let root_name = path
         .file_name()
@@ -312,7 +316,11 @@ async fn load(
     scene.graph[root].set_name(root_name.clone());
     import_from_path(&mut scene.graph, &context).await?;
     node_names::resolve_name_conflicts(context.model_path.as_path(), &mut scene.graph);
-    Ok(Model::new(NodeMapping::UseNames, scene))
+    let shared_skeleton =
+        model::apply_import_options(&mut scene, &path, &context.resource_manager, &options).await;
+    let mut model = Model::new(NodeMapping::UseNames, scene);
+    model.shared_skeleton = shared_skeleton;
+    Ok(model)
 }
 
 async fn import_from_path(graph: &mut Graph, context: &ImportContext) -> Result<()> {
@@ -333,7 +341,15 @@ async fn import_from_slice(slice: &[u8], graph: &mut Graph, context: &ImportCont
     imports.textures =
         Some(import_textures(&doc, images.as_slice(), context.as_texture_context()).await?);
     let textures = imports.textures.as_ref().unwrap().as_slice();
-    imports.materials = Some(import_materials(&doc, textures, &context.resource_manager).await?);
+    imports.materials = Some(
+        import_materials(
+            &doc,
+            textures,
+            &context.resource_manager,
+            &context.material_remap,
+        )
+        .await?,
+    );
     let materials = imports.materials.as_ref().unwrap().as_slice();
     imports.skins = Some(import_skins(&doc, &imports)?);
     imports.meshes = Some(import_meshes(
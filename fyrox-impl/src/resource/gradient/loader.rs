@@ -0,0 +1,34 @@
+//! Color gradient loader.
+
+use crate::{
+    asset::{
+        io::ResourceIo,
+        loader::{BoxedLoaderFuture, LoaderPayload, ResourceLoader},
+    },
+    core::{uuid::Uuid, TypeUuidProvider},
+    resource::gradient::ColorGradientResourceState,
+};
+use fyrox_resource::state::LoadError;
+use std::{path::PathBuf, sync::Arc};
+
+/// Default implementation for color gradient loading.
+pub struct ColorGradientLoader;
+
+impl ResourceLoader for ColorGradientLoader {
+    fn extensions(&self) -> &[&str] {
+        &["gradient"]
+    }
+
+    fn data_type_uuid(&self) -> Uuid {
+        ColorGradientResourceState::type_uuid()
+    }
+
+    fn load(&self, path: PathBuf, io: Arc<dyn ResourceIo>) -> BoxedLoaderFuture {
+        Box::pin(async move {
+            let gradient_state = ColorGradientResourceState::from_file(&path, io.as_ref())
+                .await
+                .map_err(LoadError::new)?;
+            Ok(LoaderPayload::new(gradient_state))
+        })
+    }
+}
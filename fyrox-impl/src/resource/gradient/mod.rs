@@ -0,0 +1,111 @@
+//! Color gradient resource holds a [`ColorGradient`], that could be shared across multiple
+//! particle systems, UI themes, debug visualizations, etc.
+
+use crate::{
+    asset::{io::ResourceIo, Resource, ResourceData, COLOR_GRADIENT_RESOURCE_UUID},
+    core::{
+        color_gradient::ColorGradient, io::FileLoadError, reflect::prelude::*, uuid::Uuid,
+        visitor::prelude::*, TypeUuidProvider,
+    },
+};
+use std::error::Error;
+use std::{
+    any::Any,
+    fmt::{Display, Formatter},
+    path::Path,
+};
+
+pub mod loader;
+
+/// An error that may occur during color gradient resource loading.
+#[derive(Debug)]
+pub enum ColorGradientResourceError {
+    /// An i/o error has occurred.
+    Io(FileLoadError),
+
+    /// An error that may occur due to version incompatibilities.
+    Visit(VisitError),
+}
+
+impl Display for ColorGradientResourceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColorGradientResourceError::Io(v) => {
+                write!(f, "A file load error has occurred {v:?}")
+            }
+            ColorGradientResourceError::Visit(v) => {
+                write!(
+                    f,
+                    "An error that may occur due to version incompatibilities. {v:?}"
+                )
+            }
+        }
+    }
+}
+
+impl From<FileLoadError> for ColorGradientResourceError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<VisitError> for ColorGradientResourceError {
+    fn from(e: VisitError) -> Self {
+        Self::Visit(e)
+    }
+}
+
+/// State of the [`ColorGradientResource`]
+#[derive(Debug, Visit, Default, Reflect)]
+pub struct ColorGradientResourceState {
+    /// Actual gradient.
+    pub gradient: ColorGradient,
+}
+
+impl ResourceData for ColorGradientResourceState {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+
+    fn save(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut visitor = Visitor::new();
+        self.gradient.visit("Gradient", &mut visitor)?;
+        visitor.save_binary(path)?;
+        Ok(())
+    }
+
+    fn can_be_saved(&self) -> bool {
+        true
+    }
+}
+
+impl TypeUuidProvider for ColorGradientResourceState {
+    fn type_uuid() -> Uuid {
+        COLOR_GRADIENT_RESOURCE_UUID
+    }
+}
+
+impl ColorGradientResourceState {
+    /// Load a color gradient resource from the specific file path.
+    pub async fn from_file(
+        path: &Path,
+        io: &dyn ResourceIo,
+    ) -> Result<Self, ColorGradientResourceError> {
+        let bytes = io.load_file(path).await?;
+        let mut visitor = Visitor::load_from_memory(&bytes)?;
+        let mut gradient = ColorGradient::default();
+        gradient.visit("Gradient", &mut visitor)?;
+        Ok(Self { gradient })
+    }
+}
+
+/// Type alias for color gradient resources.
+pub type ColorGradientResource = Resource<ColorGradientResourceState>;
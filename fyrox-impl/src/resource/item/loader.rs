@@ -0,0 +1,34 @@
+//! Item definition loader.
+
+use crate::{
+    asset::{
+        io::ResourceIo,
+        loader::{BoxedLoaderFuture, LoaderPayload, ResourceLoader},
+    },
+    core::{uuid::Uuid, TypeUuidProvider},
+    resource::item::ItemResourceState,
+};
+use fyrox_resource::state::LoadError;
+use std::{path::PathBuf, sync::Arc};
+
+/// Default implementation for item definition loading.
+pub struct ItemLoader;
+
+impl ResourceLoader for ItemLoader {
+    fn extensions(&self) -> &[&str] {
+        &["item"]
+    }
+
+    fn data_type_uuid(&self) -> Uuid {
+        ItemResourceState::type_uuid()
+    }
+
+    fn load(&self, path: PathBuf, io: Arc<dyn ResourceIo>) -> BoxedLoaderFuture {
+        Box::pin(async move {
+            let item_state = ItemResourceState::from_file(&path, io.as_ref())
+                .await
+                .map_err(LoadError::new)?;
+            Ok(LoaderPayload::new(item_state))
+        })
+    }
+}
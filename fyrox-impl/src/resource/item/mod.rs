@@ -0,0 +1,173 @@
+//! Item resource holds an [`ItemDefinition`] - a description of an item, that could be stored in
+//! an [`crate::scene::inventory::Inventory`].
+
+use crate::{
+    asset::{io::ResourceIo, Resource, ResourceData, ITEM_RESOURCE_UUID},
+    core::{
+        io::FileLoadError, reflect::prelude::*, uuid::Uuid, visitor::prelude::*, TypeUuidProvider,
+    },
+    resource::texture::TextureResource,
+    scene::base::{Property, PropertyValue},
+};
+use std::error::Error;
+use std::{
+    any::Any,
+    fmt::{Display, Formatter},
+    path::Path,
+};
+
+pub mod loader;
+
+/// Item definition describes a kind of items - its name, icon, stacking rules and arbitrary
+/// properties (damage of a weapon, price, weight, etc.). Inventories store references to item
+/// resources, so every item of the same kind shares the same definition.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct ItemDefinition {
+    /// Unique identifier of the item kind. It could be used to find items in game logic, for
+    /// example to check whether the player has a specific key.
+    pub id: String,
+    /// Display name (or its localization key) of the item.
+    pub name: String,
+    /// Description (or its localization key) of the item.
+    pub description: String,
+    /// An icon of the item, that could be shown in inventory UI.
+    pub icon: Option<TextureResource>,
+    /// Maximum amount of items in a single inventory slot. `1` means that the items cannot be
+    /// stacked.
+    #[reflect(min_value = 1.0)]
+    pub max_stack_size: u32,
+    /// Custom properties of the item.
+    pub properties: Vec<Property>,
+}
+
+impl Default for ItemDefinition {
+    fn default() -> Self {
+        Self {
+            id: Default::default(),
+            name: Default::default(),
+            description: Default::default(),
+            icon: None,
+            max_stack_size: 1,
+            properties: Default::default(),
+        }
+    }
+}
+
+impl ItemDefinition {
+    /// Returns `true` if multiple items could be stored in a single slot.
+    pub fn is_stackable(&self) -> bool {
+        self.max_stack_size > 1
+    }
+
+    /// Tries to find a property with the given name.
+    pub fn find_property(&self, name: &str) -> Option<&PropertyValue> {
+        self.properties
+            .iter()
+            .find_map(|p| (p.name == name).then_some(&p.value))
+    }
+}
+
+/// An error that may occur during item resource loading.
+#[derive(Debug)]
+pub enum ItemResourceError {
+    /// An i/o error has occurred.
+    Io(FileLoadError),
+
+    /// An error that may occur due to version incompatibilities.
+    Visit(VisitError),
+}
+
+impl Display for ItemResourceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ItemResourceError::Io(v) => {
+                write!(f, "A file load error has occurred {v:?}")
+            }
+            ItemResourceError::Visit(v) => {
+                write!(
+                    f,
+                    "An error that may occur due to version incompatibilities. {v:?}"
+                )
+            }
+        }
+    }
+}
+
+impl From<FileLoadError> for ItemResourceError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<VisitError> for ItemResourceError {
+    fn from(e: VisitError) -> Self {
+        Self::Visit(e)
+    }
+}
+
+/// State of the [`ItemResource`]
+#[derive(Debug, Visit, Default, Reflect)]
+pub struct ItemResourceState {
+    /// Actual item definition.
+    pub definition: ItemDefinition,
+}
+
+impl ResourceData for ItemResourceState {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+
+    fn save(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut visitor = Visitor::new();
+        self.definition.visit("Definition", &mut visitor)?;
+        visitor.save_binary(path)?;
+        Ok(())
+    }
+
+    fn can_be_saved(&self) -> bool {
+        true
+    }
+}
+
+impl TypeUuidProvider for ItemResourceState {
+    fn type_uuid() -> Uuid {
+        ITEM_RESOURCE_UUID
+    }
+}
+
+impl ItemResourceState {
+    /// Load a item resource from the specific file path.
+    pub async fn from_file(path: &Path, io: &dyn ResourceIo) -> Result<Self, ItemResourceError> {
+        let bytes = io.load_file(path).await?;
+        let mut visitor = Visitor::load_from_memory(&bytes)?;
+        let mut definition = ItemDefinition::default();
+        definition.visit("Definition", &mut visitor)?;
+        Ok(Self { definition })
+    }
+}
+
+/// Type alias for item resources.
+pub type ItemResource = Resource<ItemResourceState>;
+
+/// Extension trait for [`ItemResource`].
+pub trait ItemResourceExtension {
+    /// Returns maximum amount of items in a single inventory slot. Returns `1` if the resource is
+    /// not loaded.
+    fn max_stack_size(&self) -> u32;
+}
+
+impl ItemResourceExtension for ItemResource {
+    fn max_stack_size(&self) -> u32 {
+        self.state()
+            .data()
+            .map_or(1, |state| state.definition.max_stack_size.max(1))
+    }
+}
@@ -3,8 +3,14 @@
 #![warn(missing_docs)]
 
 pub mod curve;
+pub mod dialogue;
+pub mod dragon_bones;
 pub mod fbx;
 #[cfg(feature = "gltf")]
 pub mod gltf;
+pub mod gradient;
+pub mod item;
 pub mod model;
+pub mod svg;
 pub mod texture;
+pub mod timeline;
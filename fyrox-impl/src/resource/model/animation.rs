@@ -0,0 +1,124 @@
+//! Animation-related import options processing, that is shared across all supported model formats.
+
+use crate::{
+    core::{log::Log, pool::Handle},
+    graph::{BaseSceneGraph, SceneGraph},
+    resource::model::{ModelImportOptions, ModelResource},
+    scene::{
+        animation::{AnimationContainer, AnimationPlayer},
+        graph::Graph,
+        mesh::Mesh,
+        node::Node,
+        Scene,
+    },
+};
+use std::path::Path;
+
+/// Applies animation-related import options to a freshly imported scene of a model resource.
+pub(crate) fn apply_import_options(
+    scene: &mut Scene,
+    path: &Path,
+    skeleton: Option<&ModelResource>,
+    options: &ModelImportOptions,
+) {
+    if options.animation_only {
+        for node in scene.graph.linear_iter_mut() {
+            if let Some(mesh) = node.cast_mut::<Mesh>() {
+                mesh.clear_surfaces();
+            }
+        }
+    }
+
+    if let Some(skeleton) = skeleton {
+        let skeleton = skeleton.data_ref();
+        remove_unmatched_tracks(&mut scene.graph, &skeleton.scene.graph, path);
+    }
+
+    if !options.animation_clips.is_empty() {
+        split_clips(&mut scene.graph, path, options);
+    }
+}
+
+fn animation_players(graph: &Graph) -> Vec<Handle<Node>> {
+    graph
+        .pair_iter()
+        .filter_map(|(handle, node)| node.cast::<AnimationPlayer>().map(|_| handle))
+        .collect()
+}
+
+fn remove_unmatched_tracks(graph: &mut Graph, skeleton: &Graph, path: &Path) {
+    for player_handle in animation_players(graph) {
+        let mut animations = graph[player_handle]
+            .cast::<AnimationPlayer>()
+            .unwrap()
+            .animations()
+            .clone_inner();
+
+        let mut removed = 0;
+        for animation in animations.iter_mut() {
+            let tracks_count = animation.tracks().len();
+            animation.retain_tracks(|track| {
+                graph.try_get(track.target()).map_or(false, |node| {
+                    skeleton.find_by_name_from_root(node.name()).is_some()
+                })
+            });
+            removed += tracks_count - animation.tracks().len();
+        }
+
+        if removed > 0 {
+            Log::warn(format!(
+                "{removed} animation tracks of model {} were removed, because they animate \
+                nodes that are missing in the skeleton.",
+                path.display()
+            ));
+        }
+
+        graph[player_handle]
+            .cast_mut::<AnimationPlayer>()
+            .unwrap()
+            .set_animations(animations);
+    }
+}
+
+fn split_clips(graph: &mut Graph, path: &Path, options: &ModelImportOptions) {
+    let frame_rate = options.animation_frame_rate.max(1) as f32;
+
+    for player_handle in animation_players(graph) {
+        let player = graph[player_handle].cast_mut::<AnimationPlayer>().unwrap();
+        let source_animations = player.animations().clone_inner();
+
+        let mut clips = AnimationContainer::new();
+        for clip in options.animation_clips.iter() {
+            let source = if clip.source.is_empty() {
+                source_animations.iter().next()
+            } else {
+                source_animations
+                    .find_by_name_ref(&clip.source)
+                    .map(|(_, animation)| animation)
+            };
+
+            let Some(source) = source else {
+                Log::err(format!(
+                    "Unable to create animation clip {} of model {}, because there's no source \
+                    animation {}.",
+                    clip.name,
+                    path.display(),
+                    clip.source
+                ));
+                continue;
+            };
+
+            let start = clip.start_frame as f32 / frame_rate;
+            let end = clip.end_frame.max(clip.start_frame) as f32 / frame_rate;
+
+            let mut animation = source.clone();
+            animation.set_name(&clip.name);
+            animation.set_time_slice(start..end);
+            animation.set_loop(clip.looped);
+            animation.rewind();
+            clips.add(animation);
+        }
+
+        player.set_animations(clips);
+    }
+}
@@ -0,0 +1,329 @@
+//! Geometry-related import options processing, that is shared across all supported model formats.
+
+use crate::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        log::Log,
+        pool::Handle,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    material::MaterialResource,
+    resource::model::{ModelImportOptions, ModelUpAxis, TangentGenerationMethod},
+    scene::{
+        animation::AnimationPlayer,
+        base::BaseBuilder,
+        graph::Graph,
+        mesh::{
+            buffer::{TriangleBuffer, VertexReadTrait},
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            transform_vertex, Mesh, MeshBuilder,
+        },
+        node::Node,
+        pivot::PivotBuilder,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use fxhash::{FxHashMap, FxHashSet, FxHasher};
+use std::{
+    hash::{Hash, Hasher},
+    path::Path,
+};
+
+/// Applies geometry-related import options to a freshly imported scene of a model resource.
+pub(crate) fn apply_import_options(scene: &mut Scene, path: &Path, options: &ModelImportOptions) {
+    convert_axes_and_units(&mut scene.graph, options);
+
+    if options.tangent_generation == TangentGenerationMethod::Recalculate {
+        recalculate_tangents(&scene.graph, path);
+    }
+
+    if options.merge_meshes_by_material {
+        merge_meshes_by_material(&mut scene.graph, path);
+    }
+
+    if options.weld_vertices {
+        weld_vertices(&scene.graph, path, options.weld_tolerance);
+    }
+
+    if options.deduplicate_surfaces {
+        deduplicate_surfaces(&mut scene.graph, path);
+    }
+
+    if options.optimize_vertex_cache || options.optimize_overdraw {
+        optimize_surfaces(&scene.graph, path, options);
+    }
+}
+
+/// Returns every unique surface resource of the graph.
+fn unique_surface_resources(graph: &Graph) -> Vec<SurfaceResource> {
+    let mut visited = FxHashSet::default();
+    let mut resources = Vec::new();
+    for node in graph.linear_iter() {
+        if let Some(mesh) = node.cast::<Mesh>() {
+            for surface in mesh.surfaces() {
+                let data = surface.data();
+                if visited.insert(data.key()) {
+                    resources.push(data);
+                }
+            }
+        }
+    }
+    resources
+}
+
+fn weld_vertices(graph: &Graph, path: &Path, tolerance: f32) {
+    let mut removed = 0;
+    for resource in unique_surface_resources(graph) {
+        removed += resource.data_ref().weld_vertices(tolerance);
+    }
+
+    Log::info(format!(
+        "{removed} vertices were welded in model {}.",
+        path.display()
+    ));
+}
+
+fn is_same_content(a: &SurfaceData, b: &SurfaceData) -> bool {
+    a.vertex_buffer.raw_data() == b.vertex_buffer.raw_data()
+        && a.geometry_buffer.triangles_ref() == b.geometry_buffer.triangles_ref()
+        && a.vertex_buffer.layout_hash() == b.vertex_buffer.layout_hash()
+}
+
+fn deduplicate_surfaces(graph: &mut Graph, path: &Path) {
+    let mut unique = FxHashMap::<u64, Vec<SurfaceResource>>::default();
+    let mut replacements = FxHashMap::default();
+    for resource in unique_surface_resources(graph) {
+        let data = resource.data_ref();
+        if data.blend_shapes_container.is_some() {
+            continue;
+        }
+
+        let candidates = unique.entry(data.content_hash()).or_default();
+        match candidates
+            .iter()
+            .find(|candidate| is_same_content(&candidate.data_ref(), &data))
+        {
+            Some(original) => {
+                replacements.insert(resource.key(), original.clone());
+            }
+            None => {
+                drop(data);
+                candidates.push(resource);
+            }
+        }
+    }
+
+    if replacements.is_empty() {
+        return;
+    }
+
+    for node in graph.linear_iter_mut() {
+        if let Some(mesh) = node.cast_mut::<Mesh>() {
+            for surface in mesh.surfaces_mut() {
+                if let Some(original) = replacements.get(&surface.data_ref().key()) {
+                    surface.data.set_value_and_mark_modified(original.clone());
+                }
+            }
+        }
+    }
+
+    Log::info(format!(
+        "{} duplicate surfaces were removed from model {}.",
+        replacements.len(),
+        path.display()
+    ));
+}
+
+fn optimize_surfaces(graph: &Graph, path: &Path, options: &ModelImportOptions) {
+    for resource in unique_surface_resources(graph) {
+        let mut data = resource.data_ref();
+
+        if options.optimize_vertex_cache {
+            data.optimize_vertex_cache();
+        }
+
+        if options.optimize_overdraw {
+            if let Err(err) = data.optimize_overdraw() {
+                Log::err(format!(
+                    "Unable to optimize overdraw of a surface of model {}. Reason: {:?}",
+                    path.display(),
+                    err
+                ));
+            }
+        }
+    }
+}
+
+fn convert_axes_and_units(graph: &mut Graph, options: &ModelImportOptions) {
+    let rotation = match options.up_axis {
+        ModelUpAxis::X => {
+            UnitQuaternion::from_axis_angle(&Vector3::z_axis(), std::f32::consts::FRAC_PI_2)
+        }
+        ModelUpAxis::Y => UnitQuaternion::identity(),
+        ModelUpAxis::Z => {
+            UnitQuaternion::from_axis_angle(&Vector3::x_axis(), -std::f32::consts::FRAC_PI_2)
+        }
+    };
+
+    if options.up_axis == ModelUpAxis::Y && options.unit_scale == 1.0 {
+        return;
+    }
+
+    // The conversion is applied using an intermediate node, this way it won't be overwritten by
+    // animations of the top-level nodes of the model.
+    let root = graph.get_root();
+    let children = graph[root].children().to_vec();
+    let pivot = PivotBuilder::new(
+        BaseBuilder::new()
+            .with_name("ImportConversion")
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_rotation(rotation)
+                    .with_local_scale(Vector3::repeat(options.unit_scale))
+                    .build(),
+            ),
+    )
+    .build(graph);
+    for child in children {
+        graph.link_nodes(child, pivot);
+    }
+
+    graph.update_hierarchical_data();
+}
+
+fn recalculate_tangents(graph: &Graph, path: &Path) {
+    for node in graph.linear_iter() {
+        if let Some(mesh) = node.cast::<Mesh>() {
+            for surface in mesh.surfaces() {
+                if let Err(err) = surface.data().data_ref().calculate_tangents() {
+                    Log::err(format!(
+                        "Unable to calculate tangents for mesh {} of model {}. Reason: {:?}",
+                        mesh.name(),
+                        path.display(),
+                        err
+                    ));
+                }
+            }
+        }
+    }
+}
+
+fn is_animated(
+    graph: &Graph,
+    mut handle: Handle<Node>,
+    animated: &FxHashSet<Handle<Node>>,
+) -> bool {
+    while let Some(node) = graph.try_get(handle) {
+        if animated.contains(&handle) {
+            return true;
+        }
+        handle = node.parent();
+    }
+    false
+}
+
+struct MergedSurface {
+    data: SurfaceData,
+    material: MaterialResource,
+}
+
+fn merge_meshes_by_material(graph: &mut Graph, path: &Path) {
+    let mut animated = FxHashSet::default();
+    for node in graph.linear_iter() {
+        if let Some(player) = node.cast::<AnimationPlayer>() {
+            for animation in player.animations().iter() {
+                animated.extend(animation.tracks().iter().map(|track| track.target()));
+            }
+        }
+    }
+
+    let mut merged = FxHashMap::<u64, MergedSurface>::default();
+    let mut sources = Vec::new();
+    for (handle, node) in graph.pair_iter() {
+        let Some(mesh) = node.cast::<Mesh>() else {
+            continue;
+        };
+
+        if mesh.surfaces().is_empty()
+            || !mesh.blend_shapes().is_empty()
+            || mesh.surfaces().iter().any(|s| !s.bones().is_empty())
+            || is_animated(graph, handle, &animated)
+        {
+            continue;
+        }
+
+        let world_transform = mesh.global_transform();
+        for surface in mesh.surfaces() {
+            let data = surface.data();
+            let data = data.data_ref();
+
+            let mut hasher = FxHasher::default();
+            data.vertex_buffer.layout().hash(&mut hasher);
+            hasher.write_u64(surface.material_id());
+
+            let merged_surface = merged
+                .entry(hasher.finish())
+                .or_insert_with(|| MergedSurface {
+                    data: SurfaceData::new(
+                        data.vertex_buffer.clone_empty(4096),
+                        TriangleBuffer::new(Vec::with_capacity(4096)),
+                    ),
+                    material: surface.material().clone(),
+                });
+
+            let start_vertex_index = merged_surface.data.vertex_buffer.vertex_count();
+            let mut vertex_buffer = merged_surface.data.vertex_buffer.modify();
+            for vertex in data.vertex_buffer.iter() {
+                vertex_buffer
+                    .push_vertex_raw(
+                        &vertex.transform(&mut |vertex| transform_vertex(vertex, &world_transform)),
+                    )
+                    .expect("Vertex size must match!");
+            }
+            drop(vertex_buffer);
+
+            merged_surface
+                .data
+                .geometry_buffer
+                .modify()
+                .push_triangles_with_offset(
+                    start_vertex_index,
+                    data.geometry_buffer.triangles_ref(),
+                );
+        }
+
+        sources.push(handle);
+    }
+
+    // Merging of a single mesh gives nothing.
+    if sources.len() < 2 {
+        return;
+    }
+
+    for source in sources {
+        if let Some(mesh) = graph[source].cast_mut::<Mesh>() {
+            mesh.clear_surfaces();
+        }
+    }
+
+    let surfaces = merged
+        .into_values()
+        .map(|merged_surface| {
+            SurfaceBuilder::new(SurfaceResource::new_ok(
+                ResourceKind::External(path.to_path_buf()),
+                merged_surface.data,
+            ))
+            .with_material(merged_surface.material)
+            .build()
+        })
+        .collect::<Vec<_>>();
+
+    // Vertices are in world space already.
+    MeshBuilder::new(BaseBuilder::new().with_name("MergedMesh"))
+        .with_surfaces(surfaces)
+        .build(graph);
+
+    graph.update_hierarchical_data();
+}
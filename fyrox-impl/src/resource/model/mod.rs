@@ -37,7 +37,10 @@ use crate::{
     engine::SerializationContext,
     generic_animation::AnimationContainer,
     graph::{BaseSceneGraph, NodeHandleMap, NodeMapping, PrefabData, SceneGraph, SceneGraphNode},
-    resource::fbx::{self, error::FbxError},
+    resource::{
+        fbx::{self, error::FbxError},
+        model::skeleton::{SharedSkeleton, SkeletonCompatibilityError},
+    },
     scene::{
         animation::Animation, base::SceneNodeId, graph::Graph, node::Node, transform::Transform,
         Scene, SceneLoader,
@@ -55,7 +58,10 @@ use std::{
 };
 use strum_macros::{AsRefStr, EnumString, VariantNames};
 
+pub(crate) mod animation;
+pub(crate) mod conversion;
 pub mod loader;
+pub mod skeleton;
 
 /// See module docs.
 #[derive(Debug, Visit, Reflect)]
@@ -64,6 +70,9 @@ pub struct Model {
     pub(crate) mapping: NodeMapping,
     #[visit(skip)]
     pub(crate) scene: Scene,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub(crate) shared_skeleton: Option<SharedSkeleton>,
 }
 
 impl PrefabData for Model {
@@ -347,6 +356,17 @@ pub trait ModelResourceExtension: Sized {
         orientation: UnitQuaternion<f32>,
     ) -> Handle<Node>;
 
+    /// Instantiates a model, that shares its skeleton with other models, and binds its skinned
+    /// meshes to the bones of the given instance of the skeleton. The instance of the model will be
+    /// attached to the skeleton root. Skinned meshes of models without shared skeleton are rebound
+    /// to the bones of the skeleton with the same names and their own bones are removed. See
+    /// [`SharedSkeleton`] docs for more info.
+    fn instantiate_with_skeleton(
+        &self,
+        scene: &mut Scene,
+        skeleton_root: Handle<Node>,
+    ) -> Handle<Node>;
+
     /// Tries to retarget animations from given model resource to a node hierarchy starting
     /// from `root` on a given scene.
     ///
@@ -473,6 +493,25 @@ impl ModelResourceExtension for ModelResource {
             .finish()
     }
 
+    fn instantiate_with_skeleton(
+        &self,
+        scene: &mut Scene,
+        skeleton_root: Handle<Node>,
+    ) -> Handle<Node> {
+        let instance = self.instantiate(scene);
+
+        // Bind before linking, so the search of the bones won't find the nodes of the instance.
+        if let Some(shared_skeleton) = self.data_ref().shared_skeleton.as_ref() {
+            skeleton::bind_instance(shared_skeleton, &mut scene.graph, instance, skeleton_root);
+        } else {
+            skeleton::rebind_instance(&mut scene.graph, instance, skeleton_root);
+        }
+
+        scene.graph.link_nodes(instance, skeleton_root);
+
+        instance
+    }
+
     fn retarget_animations_directly(&self, root: Handle<Node>, graph: &Graph) -> Vec<Animation> {
         let mut header = self.state();
         let self_kind = header.kind().clone();
@@ -548,6 +587,7 @@ impl Default for Model {
         Self {
             mapping: NodeMapping::UseNames,
             scene: Scene::new(),
+            shared_skeleton: None,
         }
     }
 }
@@ -627,6 +667,94 @@ impl MaterialSearchOptions {
     }
 }
 
+/// Defines which axis of a model resource points up. The engine uses Y axis as up axis, so the
+/// models with other up axes will be rotated on import.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum ModelUpAxis {
+    /// X axis points up.
+    X,
+    /// Y axis points up. This is **default** option, no conversion is needed.
+    #[default]
+    Y,
+    /// Z axis points up. It is used by Blender and 3ds Max, for example.
+    Z,
+}
+
+uuid_provider!(ModelUpAxis = "6c0b84c6-9d0f-4b7c-8d3a-2d42e9b6f1a5");
+
+/// Defines how tangents of meshes are obtained on import.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum TangentGenerationMethod {
+    /// Tangents stored in the source file are used as is, missing tangents are calculated. This
+    /// is **default** option.
+    #[default]
+    Auto,
+    /// Tangents are always calculated, regardless of the tangents stored in the source file. It
+    /// could be useful to fix invalid tangents produced by some exporters.
+    Recalculate,
+}
+
+uuid_provider!(TangentGenerationMethod = "0f5d1f4e-3b7a-4f0c-b7f2-4b8f4f2e9d13");
+
+/// Replaces a material of a model resource with an existing material resource on import. See
+/// [`ModelImportOptions::material_remap`] docs for more info.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default, Reflect, Eq)]
+pub struct MaterialRemap {
+    /// Name of the material in the source file.
+    pub source: String,
+    /// Path to a material resource, that will be used instead of the source material.
+    pub material: PathBuf,
+}
+
+uuid_provider!(MaterialRemap = "2c8e6b8a-7f6d-4e1b-9a1a-3f2d6e5c4b70");
+
+/// Defines a named animation clip, that will be cut from an animation of a model resource by a
+/// range of frames. See [`ModelImportOptions::animation_clips`] docs for more info.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default, Reflect, Eq)]
+pub struct AnimationClipImportOptions {
+    /// Name of the resulting animation.
+    pub name: String,
+    /// Name of the animation in the source file, the clip will be cut from. Empty name means the
+    /// first animation of the source file. FBX files always have a single animation.
+    #[serde(default)]
+    pub source: String,
+    /// Index of the first frame of the clip.
+    pub start_frame: u32,
+    /// Index of the last frame of the clip (inclusive).
+    pub end_frame: u32,
+    /// Whether the clip should be looped or not.
+    #[serde(default)]
+    pub looped: bool,
+}
+
+uuid_provider!(AnimationClipImportOptions = "a2b6f0c2-57c1-4a8e-9e3b-1f0d3b6c7a41");
+
 /// A set of options that will be applied to a model resource when loading it from external source.
 ///
 /// # Details
@@ -641,16 +769,161 @@ impl MaterialSearchOptions {
 /// )
 /// ```
 ///
+/// Animation-only file with a few clips, that is used with a character skeleton could have options
+/// like this:
+///
+/// ```text
+/// (
+///     animation_only: true,
+///     skeleton: "data/characters/paladin.fbx",
+///     animation_frame_rate: 30,
+///     animation_clips: [
+///         (name: "Idle", start_frame: 0, end_frame: 59, looped: true),
+///         (name: "Attack", start_frame: 60, end_frame: 95),
+///     ],
+/// )
+/// ```
+///
 /// Check documentation of the field of the structure for more info about each parameter.
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default, Reflect, Eq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Reflect)]
 pub struct ModelImportOptions {
     /// See [`MaterialSearchOptions`] docs for more info.
     #[serde(default)]
     pub material_search_options: MaterialSearchOptions,
+
+    /// If set, only the node hierarchy and animations will be imported, all meshes will be
+    /// imported as empty nodes (without surfaces). It is useful for files, that contain only
+    /// animations for some existing character, the animations could be then retargeted to an
+    /// instance of the character using [`ModelResourceExtension::retarget_animations`].
+    #[serde(default)]
+    pub animation_only: bool,
+
+    /// Path to a model resource with a skeleton, that the animations of the model will be used
+    /// with. If set, all animation tracks, that animate nodes missing in the skeleton, will be
+    /// removed on import (with a warning). Empty path means that there's no such skeleton.
+    #[serde(default)]
+    pub skeleton: PathBuf,
+
+    /// If set together with [`Self::skeleton`], the bones of skinned meshes will be removed from
+    /// the model on import and the meshes will use the bones of the skeleton instead. Such model
+    /// must be instantiated using [`ModelResourceExtension::instantiate_with_skeleton`]. See
+    /// [`SharedSkeleton`] docs for more info.
+    #[serde(default)]
+    pub share_skeleton: bool,
+
+    /// Frame rate, that is used to convert frame indices of [`Self::animation_clips`] to time.
+    #[serde(default = "default_animation_frame_rate")]
+    pub animation_frame_rate: u32,
+
+    /// A set of animation clips, that will be cut from the animations of the source file. It
+    /// allows you to store multiple animations in a single file, one after another. If the list
+    /// is not empty, the source animations will be replaced with the clips.
+    #[serde(default)]
+    pub animation_clips: Vec<AnimationClipImportOptions>,
+
+    /// Up axis of the source file. Models with up axis other than Y will be rotated, so their up
+    /// axis will match the up axis of the engine.
+    #[serde(default)]
+    pub up_axis: ModelUpAxis,
+
+    /// Scale, that will be applied to the model, for example `0.01` for models that use
+    /// centimeters as units. The engine uses meters as units.
+    #[serde(default = "default_unit_scale")]
+    pub unit_scale: f32,
+
+    /// See [`TangentGenerationMethod`] docs for more info.
+    #[serde(default)]
+    pub tangent_generation: TangentGenerationMethod,
+
+    /// A table, that allows you to replace materials of the source file with existing material
+    /// resources. Materials are matched by their names in the source file.
+    #[serde(default)]
+    pub material_remap: Vec<MaterialRemap>,
+
+    /// If set, static meshes, that use the same material, will be merged into a single mesh. It
+    /// reduces the amount of draw calls, but individual meshes could not be moved anymore. Skinned,
+    /// animated and meshes with blend shapes are never merged.
+    #[serde(default)]
+    pub merge_meshes_by_material: bool,
+
+    /// If set, vertices with the same attributes (within [`Self::weld_tolerance`]) will be merged
+    /// into one. Source files usually store a separate vertex for each corner of each face, welding
+    /// significantly reduces the amount of vertices. Surfaces with blend shapes are never welded.
+    #[serde(default)]
+    pub weld_vertices: bool,
+
+    /// Maximum difference between attributes of vertices, at which the vertices are considered
+    /// equal. See [`Self::weld_vertices`].
+    #[serde(default = "default_weld_tolerance")]
+    pub weld_tolerance: f32,
+
+    /// If set, surfaces with the same content will share the same data, which reduces memory usage
+    /// and allows the renderer to batch such surfaces. Surfaces with blend shapes are never
+    /// deduplicated.
+    #[serde(default)]
+    pub deduplicate_surfaces: bool,
+
+    /// If set, triangles of every surface will be reordered to improve utilization of the vertex
+    /// cache of a GPU and vertices will be reordered in the order of their use.
+    #[serde(default)]
+    pub optimize_vertex_cache: bool,
+
+    /// If set, triangles of every surface will be reordered to reduce overdraw. Works best together
+    /// with [`Self::optimize_vertex_cache`].
+    #[serde(default)]
+    pub optimize_overdraw: bool,
+}
+
+fn default_unit_scale() -> f32 {
+    1.0
+}
+
+fn default_animation_frame_rate() -> u32 {
+    30
+}
+
+fn default_weld_tolerance() -> f32 {
+    1.0e-5
+}
+
+impl Default for ModelImportOptions {
+    fn default() -> Self {
+        Self {
+            material_search_options: Default::default(),
+            animation_only: false,
+            skeleton: Default::default(),
+            share_skeleton: false,
+            animation_frame_rate: default_animation_frame_rate(),
+            animation_clips: Default::default(),
+            up_axis: Default::default(),
+            unit_scale: default_unit_scale(),
+            tangent_generation: Default::default(),
+            material_remap: Default::default(),
+            merge_meshes_by_material: false,
+            weld_vertices: false,
+            weld_tolerance: default_weld_tolerance(),
+            deduplicate_surfaces: false,
+            optimize_vertex_cache: false,
+            optimize_overdraw: false,
+        }
+    }
 }
 
 impl ImportOptions for ModelImportOptions {}
 
+/// Applies format-independent import options to a freshly imported scene of a model resource.
+pub(crate) async fn apply_import_options(
+    scene: &mut Scene,
+    path: &Path,
+    resource_manager: &ResourceManager,
+    options: &ModelImportOptions,
+) -> Option<SharedSkeleton> {
+    conversion::apply_import_options(scene, path, options);
+    let skeleton = skeleton::load_skeleton(path, resource_manager, options).await;
+    animation::apply_import_options(scene, path, skeleton.as_ref(), options);
+    skeleton::apply_import_options(scene, path, skeleton.as_ref(), options)
+}
+
 /// All possible errors that may occur while trying to load model from some
 /// data source.
 #[derive(Debug)]
@@ -693,7 +966,11 @@ impl Model {
     /// Creates a new Model instance using the given node mapping and the given scene. It could be
     /// used to create your own Model resources.
     pub fn new(mapping: NodeMapping, scene: Scene) -> Self {
-        Self { mapping, scene }
+        Self {
+            mapping,
+            scene,
+            shared_skeleton: None,
+        }
     }
 
     pub(crate) async fn load<P: AsRef<Path>>(
@@ -710,6 +987,7 @@ impl Model {
             .to_string_lossy()
             .as_ref()
             .to_lowercase();
+        let mut shared_skeleton = None;
         let (scene, mapping) = match extension.as_ref() {
             "fbx" => {
                 let mut scene = Scene::new();
@@ -719,12 +997,19 @@ impl Model {
                 }
                 fbx::load_to_scene(
                     &mut scene,
-                    resource_manager,
+                    resource_manager.clone(),
                     io,
                     path.as_ref(),
                     &model_import_options,
                 )
                 .await?;
+                shared_skeleton = apply_import_options(
+                    &mut scene,
+                    path.as_ref(),
+                    &resource_manager,
+                    &model_import_options,
+                )
+                .await;
                 // Set NodeMapping::UseNames as mapping here because FBX does not have
                 // any persistent unique ids, and we have to use names.
                 (scene, NodeMapping::UseNames)
@@ -753,7 +1038,27 @@ impl Model {
             }
         };
 
-        Ok(Self { scene, mapping })
+        Ok(Self {
+            scene,
+            mapping,
+            shared_skeleton,
+        })
+    }
+
+    /// Returns the skeleton, that is shared with this model. See [`SharedSkeleton`] docs for more
+    /// info.
+    pub fn shared_skeleton(&self) -> Option<&SharedSkeleton> {
+        self.shared_skeleton.as_ref()
+    }
+
+    /// Checks whether skinned meshes of the model could be bound to the bones of the given skeleton
+    /// or not. Returns a list of incompatibilities, empty list means that the model is fully
+    /// compatible with the skeleton.
+    pub fn check_skeleton_compatibility(
+        &self,
+        skeleton: &Model,
+    ) -> Vec<SkeletonCompatibilityError> {
+        skeleton::check_compatibility(&self.scene.graph, &skeleton.scene.graph)
     }
 
     /// Returns shared reference to internal scene, there is no way to obtain
@@ -0,0 +1,376 @@
+//! Skeleton sharing between model resources. See [`SharedSkeleton`] docs for more info.
+
+use crate::{
+    asset::manager::ResourceManager,
+    core::{log::Log, pool::Handle},
+    graph::{BaseSceneGraph, SceneGraph},
+    resource::model::{Model, ModelImportOptions, ModelResource},
+    scene::{animation::AnimationPlayer, graph::Graph, mesh::Mesh, node::Node, Scene},
+};
+use fxhash::{FxHashMap, FxHashSet};
+use std::{
+    fmt::{Display, Formatter},
+    path::Path,
+};
+
+/// Maximum difference between elements of inverse bind pose matrices of a bone in a model and in
+/// a skeleton, at which the bind poses are considered equal.
+const BIND_POSE_TOLERANCE: f32 = 1.0e-3;
+
+/// A skeleton, that is shared across multiple model resources. Modular characters usually consist
+/// of multiple parts (body, armor pieces, hair, etc.) that are stored in separate files, but they
+/// are all skinned to the same skeleton. Each part has its own copy of the bones, which is
+/// useless, since every part must follow the bones of the character.
+///
+/// If [`ModelImportOptions::share_skeleton`] is set, the bones of skinned meshes are removed from
+/// the model on import and the skinned meshes remember the names of their bones. Such model then
+/// should be instantiated using [`super::ModelResourceExtension::instantiate_with_skeleton`], which
+/// binds the skinned meshes to the bones of an existing instance of the skeleton.
+#[derive(Debug, Clone)]
+pub struct SharedSkeleton {
+    /// A model resource with the skeleton.
+    pub resource: ModelResource,
+    // Mesh handle -> bone names of each surface of the mesh.
+    pub(crate) bindings: FxHashMap<Handle<Node>, Vec<Vec<String>>>,
+}
+
+/// An incompatibility between skinned meshes of a model and a skeleton.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SkeletonCompatibilityError {
+    /// A bone of a mesh does not exist in the skeleton.
+    MissingBone {
+        /// Name of the mesh.
+        mesh: String,
+        /// Name of the bone.
+        bone: String,
+    },
+    /// Bind pose of a bone differs from the bind pose of the respective bone of the skeleton, the
+    /// mesh will be deformed incorrectly.
+    BindPoseMismatch {
+        /// Name of the mesh.
+        mesh: String,
+        /// Name of the bone.
+        bone: String,
+    },
+}
+
+impl Display for SkeletonCompatibilityError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkeletonCompatibilityError::MissingBone { mesh, bone } => {
+                write!(
+                    f,
+                    "Bone {bone} of mesh {mesh} does not exist in the skeleton."
+                )
+            }
+            SkeletonCompatibilityError::BindPoseMismatch { mesh, bone } => {
+                write!(
+                    f,
+                    "Bind pose of bone {bone} of mesh {mesh} does not match the bind pose of the \
+                    skeleton."
+                )
+            }
+        }
+    }
+}
+
+/// Checks whether skinned meshes of the given graph could be bound to the bones of the given
+/// skeleton or not.
+pub(crate) fn check_compatibility(
+    graph: &Graph,
+    skeleton: &Graph,
+) -> Vec<SkeletonCompatibilityError> {
+    let mut errors = Vec::new();
+    let mut checked = FxHashSet::default();
+    for (mesh_handle, node) in graph.pair_iter() {
+        let Some(mesh) = node.cast::<Mesh>() else {
+            continue;
+        };
+
+        for &bone_handle in mesh.surfaces().iter().flat_map(|s| s.bones().iter()) {
+            let Some(bone) = graph.try_get(bone_handle) else {
+                continue;
+            };
+
+            if !checked.insert((mesh_handle, bone_handle)) {
+                continue;
+            }
+
+            match skeleton.find_by_name_from_root(bone.name()) {
+                Some((_, skeleton_bone)) => {
+                    let difference =
+                        bone.inv_bind_pose_transform() - skeleton_bone.inv_bind_pose_transform();
+                    if difference.amax() > BIND_POSE_TOLERANCE {
+                        errors.push(SkeletonCompatibilityError::BindPoseMismatch {
+                            mesh: mesh.name_owned(),
+                            bone: bone.name_owned(),
+                        });
+                    }
+                }
+                None => errors.push(SkeletonCompatibilityError::MissingBone {
+                    mesh: mesh.name_owned(),
+                    bone: bone.name_owned(),
+                }),
+            }
+        }
+    }
+    errors
+}
+
+/// Loads the skeleton specified in the import options, if any.
+pub(crate) async fn load_skeleton(
+    path: &Path,
+    resource_manager: &ResourceManager,
+    options: &ModelImportOptions,
+) -> Option<ModelResource> {
+    if options.skeleton == Path::new("") {
+        return None;
+    }
+
+    // Prevent a model from waiting for itself.
+    if options.skeleton == path {
+        Log::warn(format!(
+            "Model {} uses itself as a skeleton, skeleton import option is ignored.",
+            path.display()
+        ));
+        return None;
+    }
+
+    match resource_manager.request::<Model>(&options.skeleton).await {
+        Ok(skeleton) => Some(skeleton),
+        Err(err) => {
+            Log::err(format!(
+                "Unable to load skeleton {} for model {}. Reason: {:?}",
+                options.skeleton.display(),
+                path.display(),
+                err
+            ));
+            None
+        }
+    }
+}
+
+/// Removes the top-most bones of the given set from the graph, unless there are meshes in their
+/// hierarchy. Returns handles of every removed node.
+fn remove_bones(graph: &mut Graph, bones: &FxHashSet<Handle<Node>>) -> FxHashSet<Handle<Node>> {
+    let mut removed = FxHashSet::default();
+    for &bone in bones.iter() {
+        let Some(bone_ref) = graph.try_get(bone) else {
+            continue;
+        };
+
+        if bones.contains(&bone_ref.parent()) {
+            continue;
+        }
+
+        if graph
+            .traverse_iter(bone)
+            .all(|node| node.cast::<Mesh>().is_none())
+        {
+            removed.extend(graph.traverse_handle_iter(bone));
+            graph.remove_node(bone);
+        }
+    }
+    removed
+}
+
+/// Validates skinned meshes of a freshly imported model against the skeleton and removes the
+/// bones from the model, if [`ModelImportOptions::share_skeleton`] is set.
+pub(crate) fn apply_import_options(
+    scene: &mut Scene,
+    path: &Path,
+    skeleton: Option<&ModelResource>,
+    options: &ModelImportOptions,
+) -> Option<SharedSkeleton> {
+    let skeleton = skeleton?;
+
+    for error in check_compatibility(&scene.graph, &skeleton.data_ref().scene.graph) {
+        Log::err(format!(
+            "Model {} is incompatible with skeleton {}: {error}",
+            path.display(),
+            options.skeleton.display()
+        ));
+    }
+
+    if !options.share_skeleton {
+        return None;
+    }
+
+    let graph = &mut scene.graph;
+
+    let mut bindings = FxHashMap::default();
+    let mut bones = FxHashSet::default();
+    for (handle, node) in graph.pair_iter_mut() {
+        if let Some(mesh) = node.cast_mut::<Mesh>() {
+            if mesh.surfaces().iter().all(|s| s.bones().is_empty()) {
+                continue;
+            }
+
+            let mut surface_bones = Vec::new();
+            for surface in mesh.surfaces_mut() {
+                bones.extend(surface.bones().iter().copied());
+                surface_bones.push(surface.bones.set_value_and_mark_modified(Vec::new()));
+            }
+            bindings.insert(handle, surface_bones);
+        }
+    }
+
+    let bindings = bindings
+        .into_iter()
+        .map(|(mesh, surface_bones)| {
+            let names = surface_bones
+                .into_iter()
+                .map(|bones| {
+                    bones
+                        .into_iter()
+                        .map(|bone| {
+                            graph
+                                .try_get(bone)
+                                .map(|bone| bone.name_owned())
+                                .unwrap_or_default()
+                        })
+                        .collect()
+                })
+                .collect();
+            (mesh, names)
+        })
+        .collect();
+
+    let removed = remove_bones(graph, &bones);
+
+    // Animations of the removed bones are useless now.
+    for node in graph.linear_iter_mut() {
+        if let Some(player) = node.cast_mut::<AnimationPlayer>() {
+            let mut animations = player.animations().clone_inner();
+            for animation in animations.iter_mut() {
+                animation.retain_tracks(|track| !removed.contains(&track.target()));
+            }
+            player.set_animations(animations);
+        }
+    }
+
+    Log::info(format!(
+        "{} nodes were removed from model {}, because it shares skeleton {}.",
+        removed.len(),
+        path.display(),
+        options.skeleton.display()
+    ));
+
+    Some(SharedSkeleton {
+        resource: skeleton.clone(),
+        bindings,
+    })
+}
+
+/// Binds skinned meshes of an instance of a model with shared skeleton to the bones of the given
+/// skeleton instance.
+pub(crate) fn bind_instance(
+    shared_skeleton: &SharedSkeleton,
+    graph: &mut Graph,
+    instance_root: Handle<Node>,
+    skeleton_root: Handle<Node>,
+) {
+    let meshes = graph
+        .traverse_handle_iter(instance_root)
+        .filter(|&h| graph[h].cast::<Mesh>().is_some())
+        .collect::<Vec<_>>();
+
+    for mesh_handle in meshes {
+        let Some(surface_bones) = shared_skeleton
+            .bindings
+            .get(&graph[mesh_handle].original_handle_in_resource())
+        else {
+            continue;
+        };
+
+        let surface_bones = surface_bones
+            .iter()
+            .map(|names| {
+                names
+                    .iter()
+                    .map(|name| match graph.find_by_name(skeleton_root, name) {
+                        Some((bone, _)) => bone,
+                        None => {
+                            Log::warn(format!(
+                                "Unable to find bone {name} in the skeleton instance for mesh {}.",
+                                graph[mesh_handle].name()
+                            ));
+                            Handle::NONE
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mesh = graph[mesh_handle].cast_mut::<Mesh>().unwrap();
+        let mut surfaces = mesh.surfaces().to_vec();
+        for (surface, bones) in surfaces.iter_mut().zip(surface_bones) {
+            surface.bones.set_value_and_mark_modified(bones);
+        }
+        mesh.set_surfaces(surfaces);
+    }
+}
+
+/// Binds skinned meshes of an instance of a model without shared skeleton to the bones of the
+/// given skeleton instance. Bones are matched by their names, the bones of the instance that were
+/// replaced by the bones of the skeleton are removed. The instance must not be a descendant of the
+/// skeleton instance, otherwise its own bones could be found instead of the bones of the skeleton.
+pub(crate) fn rebind_instance(
+    graph: &mut Graph,
+    instance_root: Handle<Node>,
+    skeleton_root: Handle<Node>,
+) {
+    let meshes = graph
+        .traverse_handle_iter(instance_root)
+        .filter(|&h| graph[h].cast::<Mesh>().is_some())
+        .collect::<Vec<_>>();
+
+    let mut replaced = FxHashSet::default();
+    for mesh_handle in meshes {
+        let surface_bones = graph[mesh_handle]
+            .cast::<Mesh>()
+            .unwrap()
+            .surfaces()
+            .iter()
+            .map(|surface| {
+                surface
+                    .bones()
+                    .iter()
+                    .map(|&bone| {
+                        let skeleton_bone = graph
+                            .try_get(bone)
+                            .and_then(|b| graph.find_by_name(skeleton_root, b.name()));
+                        match skeleton_bone {
+                            Some((skeleton_bone, _)) => {
+                                replaced.insert(bone);
+                                skeleton_bone
+                            }
+                            None => {
+                                Log::warn(format!(
+                                    "Unable to find bone {} in the skeleton instance for mesh {}.",
+                                    graph.try_get(bone).map(|b| b.name()).unwrap_or_default(),
+                                    graph[mesh_handle].name()
+                                ));
+                                bone
+                            }
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        if surface_bones.iter().all(|bones| bones.is_empty()) {
+            continue;
+        }
+
+        let mesh = graph[mesh_handle].cast_mut::<Mesh>().unwrap();
+        let mut surfaces = mesh.surfaces().to_vec();
+        for (surface, bones) in surfaces.iter_mut().zip(surface_bones) {
+            surface.bones.set_value_and_mark_modified(bones);
+        }
+        mesh.set_surfaces(surfaces);
+    }
+
+    replaced.remove(&instance_root);
+    remove_bones(graph, &replaced);
+}
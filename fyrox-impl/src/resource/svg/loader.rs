@@ -0,0 +1,34 @@
+//! SVG image loader.
+
+use crate::{
+    asset::{
+        io::ResourceIo,
+        loader::{BoxedLoaderFuture, LoaderPayload, ResourceLoader},
+    },
+    core::{uuid::Uuid, TypeUuidProvider},
+    resource::svg::SvgImage,
+};
+use fyrox_resource::state::LoadError;
+use std::{path::PathBuf, sync::Arc};
+
+/// Default implementation for SVG image loading.
+pub struct SvgImageLoader;
+
+impl ResourceLoader for SvgImageLoader {
+    fn extensions(&self) -> &[&str] {
+        &["svg"]
+    }
+
+    fn data_type_uuid(&self) -> Uuid {
+        SvgImage::type_uuid()
+    }
+
+    fn load(&self, path: PathBuf, io: Arc<dyn ResourceIo>) -> BoxedLoaderFuture {
+        Box::pin(async move {
+            let image = SvgImage::from_file(&path, io.as_ref())
+                .await
+                .map_err(LoadError::new)?;
+            Ok(LoaderPayload::new(image))
+        })
+    }
+}
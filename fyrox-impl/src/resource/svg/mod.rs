@@ -0,0 +1,339 @@
+//! SVG image resource is a vector image, that could be rasterized into a texture of any size. It is
+//! mostly used for UI (HUD icons, buttons, etc.), because a single SVG file could be used on every
+//! screen resolution instead of shipping multiple PNG files of different sizes. See [`SvgImage`] docs
+//! for more info.
+
+use crate::{
+    asset::{io::ResourceIo, Resource, ResourceData, SVG_IMAGE_RESOURCE_UUID},
+    core::{
+        algebra::Vector2, io::FileLoadError, parking_lot::Mutex, reflect::prelude::*, uuid::Uuid,
+        visitor::prelude::*, TypeUuidProvider,
+    },
+    resource::texture::{
+        TextureKind, TextureMagnificationFilter, TextureMinificationFilter, TexturePixelKind,
+        TextureResource, TextureResourceExtension, TextureWrapMode,
+    },
+    scene::vector_shape::svg::{self, SvgDocument, SvgError},
+};
+use fxhash::FxHashMap;
+use fyrox_resource::untyped::ResourceKind;
+use std::{
+    any::Any,
+    error::Error,
+    fmt::{Display, Formatter},
+    path::Path,
+};
+
+pub mod loader;
+
+/// Max size (in pixels) of a rasterized image along any axis.
+pub const MAX_RASTER_SIZE: u32 = 4096;
+
+/// An error that may occur during SVG image loading.
+#[derive(Debug)]
+pub enum SvgImageError {
+    /// An i/o error has occurred.
+    Io(FileLoadError),
+
+    /// The file is not a valid UTF-8 text.
+    InvalidUtf8,
+
+    /// The document could not be parsed.
+    Svg(SvgError),
+}
+
+impl Display for SvgImageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SvgImageError::Io(v) => {
+                write!(f, "A file load error has occurred {v:?}")
+            }
+            SvgImageError::InvalidUtf8 => {
+                write!(f, "SVG file is not a valid UTF-8 text")
+            }
+            SvgImageError::Svg(v) => {
+                write!(f, "Unable to parse SVG document. Reason: {v}")
+            }
+        }
+    }
+}
+
+impl From<FileLoadError> for SvgImageError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<SvgError> for SvgImageError {
+    fn from(e: SvgError) -> Self {
+        Self::Svg(e)
+    }
+}
+
+/// SVG image is a vector image, that is rasterized into textures on demand. Every rasterized size
+/// is cached, so requesting a texture of the same size again is cheap. See [`svg`] module docs
+/// for the list of supported SVG features.
+///
+/// # DPI scaling
+///
+/// Use [`SvgImage::texture`] with the scale factor of the window (multiplied by the scale of the
+/// UI, if any) to get a texture, that is pixel-perfect on the current screen. The size of the
+/// texture is the size of the document (`width` and `height` attributes of the root element)
+/// multiplied by the scale. If the scale factor has changed (for example, the window was moved to
+/// another monitor), just request the texture again.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     asset::manager::ResourceManager,
+/// #     core::pool::Handle,
+/// #     gui::{image::ImageBuilder, widget::WidgetBuilder, BuildContext, UiNode},
+/// #     resource::svg::SvgImage,
+/// # };
+/// async fn create_icon(
+///     resource_manager: ResourceManager,
+///     scale_factor: f32,
+///     ctx: &mut BuildContext<'_>,
+/// ) -> Handle<UiNode> {
+///     let svg = resource_manager
+///         .request::<SvgImage>("data/icons/health.svg")
+///         .await
+///         .unwrap();
+///     let texture = svg.data_ref().texture(scale_factor);
+///     ImageBuilder::new(WidgetBuilder::new().with_width(32.0).with_height(32.0))
+///         .with_texture(texture.into_untyped())
+///         .build(ctx)
+/// }
+/// ```
+#[derive(Debug, Default, Visit, Reflect)]
+pub struct SvgImage {
+    #[reflect(hidden)]
+    #[visit(skip)]
+    source: String,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    document: SvgDocument,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    textures: Mutex<FxHashMap<(u32, u32), TextureResource>>,
+}
+
+impl ResourceData for SvgImage {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+
+    fn save(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        std::fs::write(path, self.source.as_bytes())?;
+        Ok(())
+    }
+
+    fn can_be_saved(&self) -> bool {
+        true
+    }
+}
+
+impl TypeUuidProvider for SvgImage {
+    fn type_uuid() -> Uuid {
+        SVG_IMAGE_RESOURCE_UUID
+    }
+}
+
+impl SvgImage {
+    /// Creates an SVG image from the given SVG document.
+    pub fn from_svg(source: &str) -> Result<Self, SvgError> {
+        Ok(Self {
+            source: source.to_string(),
+            document: svg::parse_document(source)?,
+            textures: Default::default(),
+        })
+    }
+
+    /// Loads an SVG image from the specific file path.
+    pub async fn from_file(path: &Path, io: &dyn ResourceIo) -> Result<Self, SvgImageError> {
+        let bytes = io.load_file(path).await?;
+        let source = String::from_utf8(bytes).map_err(|_| SvgImageError::InvalidUtf8)?;
+        Ok(Self::from_svg(&source)?)
+    }
+
+    /// Returns parsed SVG document.
+    pub fn document(&self) -> &SvgDocument {
+        &self.document
+    }
+
+    /// Returns the size of the document in pixels (at scale 1.0).
+    pub fn size(&self) -> Vector2<f32> {
+        self.document.size
+    }
+
+    /// Returns a texture with the image rasterized at the given scale. See [`Self::texture_with_size`]
+    /// for more info.
+    pub fn texture(&self, scale: f32) -> TextureResource {
+        let size = self.document.size * scale.max(0.0);
+        self.texture_with_size(size.x.ceil() as u32, size.y.ceil() as u32)
+    }
+
+    /// Returns a texture with the image rasterized to fit the given size in pixels. The size is
+    /// clamped to `1..=MAX_RASTER_SIZE`. Textures are cached, so every size is rasterized only
+    /// once.
+    pub fn texture_with_size(&self, width: u32, height: u32) -> TextureResource {
+        let width = width.clamp(1, MAX_RASTER_SIZE);
+        let height = height.clamp(1, MAX_RASTER_SIZE);
+
+        self.textures
+            .lock()
+            .entry((width, height))
+            .or_insert_with(|| {
+                let texture = TextureResource::from_bytes(
+                    TextureKind::Rectangle { width, height },
+                    TexturePixelKind::RGBA8,
+                    self.rasterize(width, height),
+                    ResourceKind::Embedded,
+                )
+                .expect("Rasterized image must have correct size!");
+
+                let mut data_ref = texture.data_ref();
+                data_ref.set_s_wrap_mode(TextureWrapMode::ClampToEdge);
+                data_ref.set_t_wrap_mode(TextureWrapMode::ClampToEdge);
+                data_ref.set_minification_filter(TextureMinificationFilter::Linear);
+                data_ref.set_magnification_filter(TextureMagnificationFilter::Linear);
+                drop(data_ref);
+
+                texture
+            })
+            .clone()
+    }
+
+    /// Removes every cached texture.
+    pub fn clear_cache(&self) {
+        self.textures.lock().clear();
+    }
+
+    /// Returns amount of cached textures.
+    pub fn cached_texture_count(&self) -> usize {
+        self.textures.lock().len()
+    }
+
+    /// Rasterizes the image into RGBA8 pixels (non-premultiplied alpha) of the given size. The view
+    /// box of the document is stretched over the whole image. Edges are anti-aliased using
+    /// distances to the contours of the shapes.
+    pub fn rasterize(&self, width: u32, height: u32) -> Vec<u8> {
+        let width = width as usize;
+        let height = height as usize;
+        let view_box = self.document.view_box;
+
+        let units_per_pixel =
+            Vector2::new(view_box.w() / width as f32, view_box.h() / height as f32);
+        let pixel_size = units_per_pixel.x.max(units_per_pixel.y).max(f32::EPSILON);
+
+        // Premultiplied colors.
+        let mut pixels = vec![[0.0f32; 4]; width * height];
+
+        for shape in self.document.shapes.iter() {
+            let (Some(fill), Some(bounds)) = (shape.fill, shape.path.bounds()) else {
+                continue;
+            };
+            let fill = fill.as_frgba();
+            if fill.w <= 0.0 {
+                continue;
+            }
+
+            // Only pixels within the bounds of the shape (plus one pixel of the edge) could be
+            // covered.
+            let to_pixel = |value: f32, origin: f32, units_per_pixel: f32, max: usize| {
+                ((value - origin) / units_per_pixel).clamp(0.0, max as f32) as usize
+            };
+            let x_range = to_pixel(bounds.x(), view_box.x(), units_per_pixel.x, width)
+                .saturating_sub(1)
+                ..(to_pixel(
+                    bounds.x() + bounds.w(),
+                    view_box.x(),
+                    units_per_pixel.x,
+                    width,
+                ) + 1)
+                    .min(width);
+            let y_range = to_pixel(bounds.y(), view_box.y(), units_per_pixel.y, height)
+                .saturating_sub(1)
+                ..(to_pixel(
+                    bounds.y() + bounds.h(),
+                    view_box.y(),
+                    units_per_pixel.y,
+                    height,
+                ) + 1)
+                    .min(height);
+
+            for y in y_range {
+                for x in x_range.clone() {
+                    let point = Vector2::new(
+                        view_box.x() + (x as f32 + 0.5) * units_per_pixel.x,
+                        view_box.y() + (y as f32 + 0.5) * units_per_pixel.y,
+                    );
+                    let distance = shape.path.signed_distance(point) / pixel_size;
+                    let alpha = (0.5 - distance).clamp(0.0, 1.0) * fill.w;
+                    if alpha <= 0.0 {
+                        continue;
+                    }
+
+                    let pixel = &mut pixels[y * width + x];
+                    let inv_alpha = 1.0 - alpha;
+                    pixel[0] = fill.x * alpha + pixel[0] * inv_alpha;
+                    pixel[1] = fill.y * alpha + pixel[1] * inv_alpha;
+                    pixel[2] = fill.z * alpha + pixel[2] * inv_alpha;
+                    pixel[3] = alpha + pixel[3] * inv_alpha;
+                }
+            }
+        }
+
+        let mut bytes = Vec::with_capacity(width * height * 4);
+        for [r, g, b, a] in pixels {
+            let k = if a > 0.0 { 255.0 / a } else { 0.0 };
+            bytes.extend_from_slice(&[
+                (r * k).round().clamp(0.0, 255.0) as u8,
+                (g * k).round().clamp(0.0, 255.0) as u8,
+                (b * k).round().clamp(0.0, 255.0) as u8,
+                (a * 255.0).round().clamp(0.0, 255.0) as u8,
+            ]);
+        }
+        bytes
+    }
+}
+
+/// Type alias for SVG image resources.
+pub type SvgImageResource = Resource<SvgImage>;
+
+#[cfg(test)]
+mod test {
+    use crate::resource::svg::SvgImage;
+
+    #[test]
+    fn test_svg_rasterization() {
+        let image = SvgImage::from_svg(
+            r##"<svg width="4" height="2" viewBox="0 0 8 4">
+                <rect width="4" height="4" fill="#ff0000"/>
+            </svg>"##,
+        )
+        .unwrap();
+
+        let bytes = image.rasterize(4, 2);
+        assert_eq!(bytes.len(), 4 * 2 * 4);
+        // Left half is red, right half is empty.
+        assert_eq!(&bytes[0..4], &[255, 0, 0, 255]);
+        assert_eq!(&bytes[12..16], &[0, 0, 0, 0]);
+
+        // Every size is rasterized only once.
+        let a = image.texture(2.0);
+        let b = image.texture_with_size(8, 4);
+        assert_eq!(a, b);
+        image.texture(1.0);
+        assert_eq!(image.cached_texture_count(), 2);
+    }
+}
@@ -26,6 +26,7 @@ use crate::{
         algebra::{Vector2, Vector3},
         futures::io::Error,
         io::FileLoadError,
+        noise::{self, Noise},
         reflect::prelude::*,
         uuid::Uuid,
         visitor::{PodVecView, Visit, VisitError, VisitResult, Visitor},
@@ -88,6 +89,17 @@ pub enum TextureKind {
         /// Depth of the volume.
         depth: u32,
     },
+    /// Array of 2D textures of the same size. Layers are sampled separately, there is no filtering
+    /// between adjacent layers. Layers are stored one after another, mips of every layer follow
+    /// the layers of the previous mip level.
+    Array {
+        /// Width of a layer.
+        width: u32,
+        /// Height of a layer.
+        height: u32,
+        /// Amount of layers in the array.
+        layers: u32,
+    },
 }
 
 impl TextureKind {
@@ -135,6 +147,21 @@ impl TextureKind {
             None
         }
     }
+
+    /// Tries to fetch [`TextureKind::Array`]'s width (x), height (y) and layer count (z).
+    #[inline]
+    pub fn array_size(&self) -> Option<Vector3<u32>> {
+        if let Self::Array {
+            width,
+            height,
+            layers,
+        } = self
+        {
+            Some(Vector3::new(*width, *height, *layers))
+        } else {
+            None
+        }
+    }
 }
 
 impl Default for TextureKind {
@@ -155,6 +182,7 @@ impl Visit for TextureKind {
             TextureKind::Rectangle { .. } => 1,
             TextureKind::Cube { .. } => 2,
             TextureKind::Volume { .. } => 3,
+            TextureKind::Array { .. } => 4,
         };
         id.visit("Id", &mut region)?;
         if region.is_reading() {
@@ -173,6 +201,11 @@ impl Visit for TextureKind {
                     height: 0,
                     depth: 0,
                 },
+                4 => TextureKind::Array {
+                    width: 0,
+                    height: 0,
+                    layers: 0,
+                },
                 _ => {
                     return VisitResult::Err(VisitError::User(format!(
                         "Invalid texture kind {}!",
@@ -202,6 +235,15 @@ impl Visit for TextureKind {
                 height.visit("Height", &mut region)?;
                 depth.visit("Depth", &mut region)?;
             }
+            TextureKind::Array {
+                width,
+                height,
+                layers,
+            } => {
+                width.visit("Width", &mut region)?;
+                height.visit("Height", &mut region)?;
+                layers.visit("Layers", &mut region)?;
+            }
         }
 
         Ok(())
@@ -1187,6 +1229,11 @@ fn bytes_in_mip_level(kind: TextureKind, pixel_kind: TexturePixelKind, mip: usiz
             height,
             depth,
         } => width.shr(mip) * height.shr(mip) * depth.shr(mip),
+        TextureKind::Array {
+            width,
+            height,
+            layers,
+        } => layers * width.shr(mip) * height.shr(mip),
     };
     match pixel_kind {
         // Uncompressed formats.
@@ -1236,6 +1283,11 @@ fn bytes_in_mip_level(kind: TextureKind, pixel_kind: TexturePixelKind, mip: usiz
                     height,
                     depth,
                 } => ceil_div_4(width) * ceil_div_4(height) * ceil_div_4(depth) * block_size,
+                TextureKind::Array {
+                    width,
+                    height,
+                    layers,
+                } => layers * ceil_div_4(width) * ceil_div_4(height) * block_size,
             }
         }
     }
@@ -1565,6 +1617,24 @@ impl Texture {
         }
     }
 
+    /// Bakes the given noise into a single-channel 8-bit rectangle texture of the given size. The
+    /// texture covers `[0; noise_size]` region of the noise space. It could be used to create
+    /// procedural masks, clouds, detail maps and so on.
+    pub fn from_noise<N: Noise + ?Sized>(
+        noise: &N,
+        width: u32,
+        height: u32,
+        noise_size: Vector2<f32>,
+    ) -> Self {
+        let bytes = noise::bake_2d_u8(noise, width as usize, height as usize, noise_size);
+        Self::from_bytes(
+            TextureKind::Rectangle { width, height },
+            TexturePixelKind::R8,
+            bytes,
+        )
+        .unwrap()
+    }
+
     /// Sets new minification filter. It is used when texture becomes smaller.
     pub fn set_minification_filter(&mut self, filter: TextureMinificationFilter) {
         self.minification_filter = filter;
@@ -0,0 +1,111 @@
+//! Timeline resource holds a [`Timeline`] (a cutscene, for example), that could be played by
+//! [`crate::scene::timeline::TimelinePlayer`] nodes.
+
+use crate::{
+    asset::{io::ResourceIo, Resource, ResourceData, TIMELINE_RESOURCE_UUID},
+    core::{
+        io::FileLoadError, reflect::prelude::*, uuid::Uuid, visitor::prelude::*, TypeUuidProvider,
+    },
+    scene::timeline::Timeline,
+};
+use std::error::Error;
+use std::{
+    any::Any,
+    fmt::{Display, Formatter},
+    path::Path,
+};
+
+pub mod loader;
+
+/// An error that may occur during timeline resource loading.
+#[derive(Debug)]
+pub enum TimelineResourceError {
+    /// An i/o error has occurred.
+    Io(FileLoadError),
+
+    /// An error that may occur due to version incompatibilities.
+    Visit(VisitError),
+}
+
+impl Display for TimelineResourceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimelineResourceError::Io(v) => {
+                write!(f, "A file load error has occurred {v:?}")
+            }
+            TimelineResourceError::Visit(v) => {
+                write!(
+                    f,
+                    "An error that may occur due to version incompatibilities. {v:?}"
+                )
+            }
+        }
+    }
+}
+
+impl From<FileLoadError> for TimelineResourceError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<VisitError> for TimelineResourceError {
+    fn from(e: VisitError) -> Self {
+        Self::Visit(e)
+    }
+}
+
+/// State of the [`TimelineResource`]
+#[derive(Debug, Visit, Default, Reflect)]
+pub struct TimelineResourceState {
+    /// Actual timeline.
+    pub timeline: Timeline,
+}
+
+impl ResourceData for TimelineResourceState {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+
+    fn save(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut visitor = Visitor::new();
+        self.timeline.visit("Timeline", &mut visitor)?;
+        visitor.save_binary(path)?;
+        Ok(())
+    }
+
+    fn can_be_saved(&self) -> bool {
+        true
+    }
+}
+
+impl TypeUuidProvider for TimelineResourceState {
+    fn type_uuid() -> Uuid {
+        TIMELINE_RESOURCE_UUID
+    }
+}
+
+impl TimelineResourceState {
+    /// Load a timeline resource from the specific file path.
+    pub async fn from_file(
+        path: &Path,
+        io: &dyn ResourceIo,
+    ) -> Result<Self, TimelineResourceError> {
+        let bytes = io.load_file(path).await?;
+        let mut visitor = Visitor::load_from_memory(&bytes)?;
+        let mut timeline = Timeline::default();
+        timeline.visit("Timeline", &mut visitor)?;
+        Ok(Self { timeline })
+    }
+}
+
+/// Type alias for timeline resources.
+pub type TimelineResource = Resource<TimelineResourceState>;
@@ -3,6 +3,7 @@
 
 use crate::{
     core::{
+        log::Log,
         math::aabb::AxisAlignedBoundingBox,
         pool::Handle,
         reflect::prelude::*,
@@ -13,11 +14,13 @@ use crate::{
     },
     scene::{
         animation::prelude::*,
-        base::{Base, BaseBuilder},
-        graph::Graph,
+        base::{Base, BaseBuilder, NodeScriptMessage},
+        graph::{Graph, NodePool},
         node::{Node, NodeTrait, UpdateContext},
+        sound::Sound,
         Scene,
     },
+    script::{RoutingStrategy, ScriptMessage, ScriptMessageKind},
 };
 use fyrox_graph::{BaseSceneGraph, SceneGraph};
 use std::ops::{Deref, DerefMut};
@@ -63,6 +66,9 @@ pub type StateAction = crate::generic_animation::machine::state::StateAction<Han
 /// Scene specific animation blending state machine state action wrapper.
 pub type StateActionWrapper =
     crate::generic_animation::machine::state::StateActionWrapper<Handle<Node>>;
+/// Scene specific animation blending state machine state action request.
+pub type StateActionRequest =
+    crate::generic_animation::machine::state::StateActionRequest<Handle<Node>>;
 /// Scene specific animation blending state machine logic node.
 pub type LogicNode = crate::generic_animation::machine::transition::LogicNode<Handle<Node>>;
 /// Scene specific animation blending state machine And logic node.
@@ -87,11 +93,14 @@ pub mod prelude {
         AnimationEventsSource, BasePoseNode, BlendAnimations, BlendAnimationsByIndex, BlendPose,
         BlendSpace, BlendSpacePoint, Event, IndexedBlendInput, LayerAnimationEventsCollection,
         LayerMask, LogicNode, Machine, MachineLayer, NotNode, OrNode, PlayAnimation, PoseNode,
-        RootMotionSettings, State, StateAction, StateActionWrapper, Transition, XorNode,
+        RootMotionSettings, State, StateAction, StateActionRequest, StateActionWrapper,
+        StateMessage, Transition, XorNode,
     };
     pub use crate::generic_animation::machine::{
         node::AnimationEventCollectionStrategy,
-        parameter::{Parameter, ParameterContainer, ParameterDefinition, PoseWeight},
+        parameter::{
+            CurveParameter, Parameter, ParameterContainer, ParameterDefinition, PoseWeight,
+        },
     };
 }
 
@@ -109,6 +118,20 @@ impl LayerMaskExt for LayerMask {
     }
 }
 
+/// A script message, that is sent by [`StateAction::SendMessage`] action of a state. The message is
+/// delivered to the scripts of the state machine node and every its ancestor (see
+/// [`crate::script::RoutingStrategy::Up`]), that are subscribed to this message type. This way a
+/// script of a character could react to the changes of its state machine without polling its events.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateMessage {
+    /// A handle of the state machine node, that sent the message.
+    pub absm: Handle<Node>,
+    /// Name of the state, that sent the message.
+    pub state: String,
+    /// The message.
+    pub message: String,
+}
+
 /// Animation blending state machine (ABSM) is a node that takes multiple animations from an animation player and
 /// mixes them in arbitrary way into one animation. Usually, ABSMs are used to animate humanoid characters in games,
 /// by blending multiple states with one or more animations. More info about state machines can be found in
@@ -224,6 +247,40 @@ impl AnimationBlendingStateMachine {
     pub fn animation_player(&self) -> Handle<Node> {
         *self.animation_player
     }
+
+    /// Handles actions of the state machine, that cannot be handled by the machine itself (sound
+    /// playback and messages).
+    pub(crate) fn process_actions(&mut self, nodes: &mut NodePool) {
+        let machine = self.machine.get_value_mut_silent();
+        while let Some(request) = machine.pop_action() {
+            match request.action {
+                StateAction::PlaySound(sound) => {
+                    if let Some(sound) = nodes
+                        .try_borrow_mut(sound)
+                        .and_then(|n| n.cast_mut::<Sound>())
+                    {
+                        sound.play();
+                    }
+                }
+                StateAction::SendMessage(message) => {
+                    if let Some(sender) = self.base.script_message_sender.as_ref() {
+                        Log::verify(sender.send(NodeScriptMessage::SendMessage(ScriptMessage {
+                            payload: Box::new(StateMessage {
+                                absm: self.base.self_handle,
+                                state: request.state_name,
+                                message,
+                            }),
+                            kind: ScriptMessageKind::Hierarchical {
+                                root: self.base.self_handle,
+                                routing: RoutingStrategy::Up,
+                            },
+                        })));
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
 }
 
 impl TypeUuidProvider for AnimationBlendingStateMachine {
@@ -281,6 +338,8 @@ impl NodeTrait for AnimationBlendingStateMachine {
 
             pose.apply_internal(context.nodes);
         }
+
+        self.process_actions(context.nodes);
     }
 
     fn validate(&self, scene: &Scene) -> Result<(), String> {
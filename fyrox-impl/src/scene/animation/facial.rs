@@ -0,0 +1,761 @@
+//! Facial animation driven by visemes and emotions. See [`FacialAnimator`] docs for more info.
+
+use crate::{
+    core::{
+        math::{aabb::AxisAlignedBoundingBox, curve::Curve, lerpf},
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::{Graph, NodePool},
+        mesh::Mesh,
+        node::{Node, NodeTrait, UpdateContext},
+        sound::{context::SoundContext, Sound},
+    },
+};
+use fxhash::FxHashMap;
+use fyrox_graph::BaseSceneGraph;
+use std::{
+    ops::{Deref, DerefMut},
+    time::Duration,
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Length of the time window, that is used to calculate amplitude of a sound.
+const AMPLITUDE_WINDOW: Duration = Duration::from_millis(50);
+
+/// A blend shape, that is driven by a facial channel.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "5d1c1f8e-2b9a-4f6e-8f43-0a7c9b1e6d24")]
+pub struct BlendShapeTarget {
+    /// Name of the blend shape of the mesh.
+    pub blend_shape: String,
+    /// Weight of the blend shape, when the channel is fully active. Weights of blend shapes are
+    /// in `0..100` range.
+    #[reflect(min_value = 0.0, max_value = 100.0, step = 1.0)]
+    pub weight: f32,
+}
+
+impl Default for BlendShapeTarget {
+    fn default() -> Self {
+        Self {
+            blend_shape: Default::default(),
+            weight: 100.0,
+        }
+    }
+}
+
+/// A named facial channel (a viseme, like `AA` or `OU`, or an emotion, like `Smile`), that maps its
+/// value in `0..1` range onto weights of one or more blend shapes.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "a3f2e6d0-7c41-4b8e-9a15-3e9d2c6b8f71")]
+pub struct FacialChannel {
+    /// Name of the channel.
+    pub name: String,
+    /// Blend shapes, that are driven by the channel.
+    pub targets: Vec<BlendShapeTarget>,
+}
+
+/// Value of a facial channel.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "0e6b7f4d-3a28-4c9e-b5d1-8f2a6c4e9b03")]
+pub struct ChannelValue {
+    /// Name of the channel.
+    pub channel: String,
+    /// Value of the channel in `0..1` range.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub value: f32,
+}
+
+/// A key of a viseme track, that activates a channel at the given time.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "c7d94b2e-61f0-4a3d-8e5b-2f7a1c9d4e68")]
+pub struct VisemeKey {
+    /// Time (in seconds) at which the channel becomes active.
+    #[reflect(min_value = 0.0, step = 0.01)]
+    pub time: f32,
+    /// Name of the channel.
+    pub channel: String,
+    /// Duration (in seconds) of the key. Zero duration means that the channel is active until the
+    /// next key.
+    #[reflect(min_value = 0.0, step = 0.01)]
+    pub duration: f32,
+}
+
+/// A curve, that defines a value of a channel over time.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "8b1e3d6f-4c2a-49e7-a0f5-6d3b9e2c7a14")]
+pub struct ChannelCurve {
+    /// Name of the channel.
+    pub channel: String,
+    /// Value of the channel over time.
+    pub curve: Curve,
+}
+
+/// Defines where a facial layer takes values of its channels from.
+#[derive(
+    Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames, TypeUuidProvider,
+)]
+#[type_uuid(id = "f1a6c8e3-9d27-4b5e-8c04-7e2d5b3a9f16")]
+pub enum FacialLayerSource {
+    /// Values are set manually (from code, or in the editor), see
+    /// [`FacialAnimator::set_channel_value`].
+    Manual {
+        /// Values of the channels.
+        values: Vec<ChannelValue>,
+    },
+    /// A track of visemes (for example, produced by a phoneme extraction tool). Every channel of
+    /// the track is either fully active or inactive, smooth transitions are done by the smoothing
+    /// of the animator.
+    Track {
+        /// Keys of the track, they must be sorted by time.
+        keys: Vec<VisemeKey>,
+        /// A sound node, that plays the speech. If set, the time of the track is synchronized with
+        /// the playback time of the sound.
+        sound: Handle<Node>,
+    },
+    /// Values of the channels are defined by curves.
+    Curves {
+        /// Curves of the channels.
+        curves: Vec<ChannelCurve>,
+        /// A sound node, that plays the speech. If set, the time of the curves is synchronized with
+        /// the playback time of the sound.
+        sound: Handle<Node>,
+        /// Whether the curves are looped or not.
+        looped: bool,
+    },
+    /// Value of a single channel (usually the one, that opens the jaw) follows the amplitude of the
+    /// sound. This is the simplest form of lip-sync, which does not require any preprocessing of
+    /// the speech.
+    Amplitude {
+        /// A sound node, that plays the speech.
+        sound: Handle<Node>,
+        /// Name of the channel.
+        channel: String,
+        /// Amplitude multiplier.
+        #[reflect(min_value = 0.0, step = 0.1)]
+        gain: f32,
+        /// Amplitude, below which the sound is considered silent.
+        #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+        noise_floor: f32,
+    },
+}
+
+impl Default for FacialLayerSource {
+    fn default() -> Self {
+        Self::Manual {
+            values: Default::default(),
+        }
+    }
+}
+
+/// A layer of facial animation. Layers are applied in the order of their priorities, a layer with
+/// higher priority overrides the values of the channels it drives. For example, a lip-sync layer
+/// with high priority could override mouth channels of an emotion layer, while keeping the rest of
+/// the channels (eyebrows, eyes) untouched.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "2c8e5a1b-7f3d-4e96-b2a0-9d6f1e4c8b37")]
+pub struct FacialLayer {
+    /// Name of the layer.
+    pub name: String,
+    /// Priority of the layer. Layers with higher priority are applied on top of the layers with
+    /// lower priority.
+    pub priority: i32,
+    /// Influence of the layer in `0..1` range.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub weight: f32,
+    /// Whether the layer is enabled or not.
+    pub enabled: bool,
+    /// Source of the values of the channels.
+    pub source: FacialLayerSource,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    time: f32,
+}
+
+impl Default for FacialLayer {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            priority: 0,
+            weight: 1.0,
+            enabled: true,
+            source: Default::default(),
+            time: 0.0,
+        }
+    }
+}
+
+fn sound_ref(nodes: &NodePool, sound: Handle<Node>) -> Option<&Sound> {
+    nodes.try_borrow(sound).and_then(|n| n.cast::<Sound>())
+}
+
+impl FacialLayer {
+    /// Creates a new layer with the given name, priority and source.
+    pub fn new(name: impl Into<String>, priority: i32, source: FacialLayerSource) -> Self {
+        Self {
+            name: name.into(),
+            priority,
+            source,
+            ..Default::default()
+        }
+    }
+
+    /// Returns current time (in seconds) of the layer. Layers, that are synchronized with a sound,
+    /// use the playback time of the sound instead.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Sets current time (in seconds) of the layer. It could be used to restart a track.
+    pub fn set_time(&mut self, time: f32) {
+        self.time = time.max(0.0);
+    }
+
+    fn advance(&mut self, nodes: &NodePool, dt: f32) {
+        let (sound, loop_duration) = match self.source {
+            FacialLayerSource::Track { sound, .. } => (sound, 0.0),
+            FacialLayerSource::Curves {
+                ref curves,
+                sound,
+                looped,
+            } => {
+                let duration = if looped {
+                    curves
+                        .iter()
+                        .map(|c| c.curve.max_location())
+                        .fold(0.0, f32::max)
+                } else {
+                    0.0
+                };
+                (sound, duration)
+            }
+            _ => return,
+        };
+
+        if let Some(sound) = sound_ref(nodes, sound) {
+            self.time = sound.playback_time();
+        } else {
+            self.time += dt;
+            if loop_duration > 0.0 {
+                self.time %= loop_duration;
+            }
+        }
+    }
+
+    fn collect_values(
+        &self,
+        nodes: &NodePool,
+        sound_context: &SoundContext,
+        values: &mut Vec<(String, f32)>,
+    ) {
+        match self.source {
+            FacialLayerSource::Manual { values: ref manual } => {
+                values.extend(manual.iter().map(|v| (v.channel.clone(), v.value)));
+            }
+            FacialLayerSource::Track { ref keys, .. } => {
+                let active = keys
+                    .iter()
+                    .rev()
+                    .find(|k| k.time <= self.time)
+                    .filter(|k| k.duration <= 0.0 || self.time <= k.time + k.duration);
+                for key in keys.iter() {
+                    if !values.iter().any(|(channel, _)| *channel == key.channel) {
+                        values.push((key.channel.clone(), 0.0));
+                    }
+                }
+                if let Some(active) = active {
+                    if let Some((_, value)) = values.iter_mut().find(|(c, _)| *c == active.channel)
+                    {
+                        *value = 1.0;
+                    }
+                }
+            }
+            FacialLayerSource::Curves { ref curves, .. } => {
+                values.extend(
+                    curves
+                        .iter()
+                        .map(|c| (c.channel.clone(), c.curve.value_at(self.time))),
+                );
+            }
+            FacialLayerSource::Amplitude {
+                sound,
+                ref channel,
+                gain,
+                noise_floor,
+            } => {
+                let amplitude = sound_ref(nodes, sound).map_or(0.0, |sound| {
+                    sound_context.sound_amplitude(sound, AMPLITUDE_WINDOW)
+                });
+                let value = ((amplitude - noise_floor).max(0.0) * gain).clamp(0.0, 1.0);
+                values.push((channel.clone(), value));
+            }
+        }
+    }
+}
+
+/// Facial animator maps values of facial channels (visemes and emotions) onto weights of blend
+/// shapes (morph targets) of one or more meshes. Values of the channels are produced by a stack of
+/// [`FacialLayer`]s, that could be driven by code, by curves, by viseme tracks, or by amplitude of
+/// a sound (basic lip-sync).
+///
+/// # Channels
+///
+/// Every [`FacialChannel`] defines a set of blend shapes, that it drives. For example, `AA` viseme
+/// could open the jaw and stretch the lips a bit. Blend shapes are matched by their names, weight
+/// of a blend shape is a sum of the contributions of every channel. Blend shapes, that are not
+/// driven by any channel, are not changed by the animator.
+///
+/// # Layers
+///
+/// Layers are applied in the order of their priorities. A layer overrides the values of the
+/// channels it drives (blended by the weight of the layer), other channels are not affected. This
+/// allows you to mix emotions with lip-sync, for example.
+///
+/// # Smoothing
+///
+/// Values of the channels do not change instantly, they approach their target values with
+/// exponential smoothing. It removes jitter of amplitude-driven lip-sync and makes transitions
+/// between visemes of a track smooth.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     scene::{
+/// #         animation::facial::{
+/// #             BlendShapeTarget, FacialAnimatorBuilder, FacialChannel, FacialLayer,
+/// #             FacialLayerSource,
+/// #         },
+/// #         base::BaseBuilder,
+/// #         graph::Graph,
+/// #         node::Node,
+/// #     },
+/// # };
+/// fn create_lip_sync(face: Handle<Node>, speech: Handle<Node>, graph: &mut Graph) -> Handle<Node> {
+///     FacialAnimatorBuilder::new(BaseBuilder::new())
+///         .with_meshes(vec![face])
+///         .with_channels(vec![FacialChannel {
+///             name: "JawOpen".to_string(),
+///             targets: vec![BlendShapeTarget {
+///                 blend_shape: "mouthOpen".to_string(),
+///                 weight: 100.0,
+///             }],
+///         }])
+///         .with_layers(vec![FacialLayer::new(
+///             "LipSync",
+///             0,
+///             FacialLayerSource::Amplitude {
+///                 sound: speech,
+///                 channel: "JawOpen".to_string(),
+///                 gain: 4.0,
+///                 noise_floor: 0.02,
+///             },
+///         )])
+///         .build(graph)
+/// }
+/// ```
+#[derive(Clone, Debug, Visit, Reflect)]
+pub struct FacialAnimator {
+    base: Base,
+
+    #[reflect(setter = "set_meshes")]
+    meshes: InheritableVariable<Vec<Handle<Node>>>,
+
+    #[reflect(setter = "set_channels")]
+    channels: InheritableVariable<Vec<FacialChannel>>,
+
+    #[reflect(setter = "set_layers")]
+    layers: InheritableVariable<Vec<FacialLayer>>,
+
+    #[reflect(min_value = 0.0, step = 0.01)]
+    #[reflect(setter = "set_smoothing_time")]
+    smoothing_time: InheritableVariable<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    values: FxHashMap<String, f32>,
+}
+
+impl Deref for FacialAnimator {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for FacialAnimator {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl Default for FacialAnimator {
+    fn default() -> Self {
+        FacialAnimatorBuilder::new(BaseBuilder::new()).build_facial_animator()
+    }
+}
+
+impl TypeUuidProvider for FacialAnimator {
+    fn type_uuid() -> Uuid {
+        uuid!("6e4b2d9a-1f7c-4a38-b5e0-c3d8f2a61e95")
+    }
+}
+
+impl FacialAnimator {
+    /// Sets meshes, which blend shapes are driven by the animator.
+    pub fn set_meshes(&mut self, meshes: Vec<Handle<Node>>) -> Vec<Handle<Node>> {
+        self.meshes.set_value_and_mark_modified(meshes)
+    }
+
+    /// Returns meshes, which blend shapes are driven by the animator.
+    pub fn meshes(&self) -> &[Handle<Node>] {
+        &self.meshes
+    }
+
+    /// Sets facial channels of the animator.
+    pub fn set_channels(&mut self, channels: Vec<FacialChannel>) -> Vec<FacialChannel> {
+        self.channels.set_value_and_mark_modified(channels)
+    }
+
+    /// Returns facial channels of the animator.
+    pub fn channels(&self) -> &[FacialChannel] {
+        &self.channels
+    }
+
+    /// Sets layers of the animator.
+    pub fn set_layers(&mut self, layers: Vec<FacialLayer>) -> Vec<FacialLayer> {
+        self.layers.set_value_and_mark_modified(layers)
+    }
+
+    /// Returns layers of the animator.
+    pub fn layers(&self) -> &[FacialLayer] {
+        &self.layers
+    }
+
+    /// Returns a reference to a layer with the given name.
+    pub fn layer(&self, name: &str) -> Option<&FacialLayer> {
+        self.layers.iter().find(|l| l.name == name)
+    }
+
+    /// Returns a reference to a layer with the given name.
+    pub fn layer_mut(&mut self, name: &str) -> Option<&mut FacialLayer> {
+        self.layers
+            .get_value_mut_and_mark_modified()
+            .iter_mut()
+            .find(|l| l.name == name)
+    }
+
+    /// Sets a value of a channel in a manual layer with the given name. Returns `false` if there's
+    /// no such layer or the layer is not manual.
+    pub fn set_channel_value(&mut self, layer: &str, channel: &str, value: f32) -> bool {
+        let Some(FacialLayerSource::Manual { values }) =
+            self.layer_mut(layer).map(|l| &mut l.source)
+        else {
+            return false;
+        };
+
+        let value = value.clamp(0.0, 1.0);
+        match values.iter_mut().find(|v| v.channel == channel) {
+            Some(channel_value) => channel_value.value = value,
+            None => values.push(ChannelValue {
+                channel: channel.to_string(),
+                value,
+            }),
+        }
+        true
+    }
+
+    /// Sets smoothing time (in seconds) of the channels. Zero means no smoothing.
+    pub fn set_smoothing_time(&mut self, smoothing_time: f32) -> f32 {
+        self.smoothing_time
+            .set_value_and_mark_modified(smoothing_time.max(0.0))
+    }
+
+    /// Returns smoothing time (in seconds) of the channels.
+    pub fn smoothing_time(&self) -> f32 {
+        *self.smoothing_time
+    }
+
+    /// Returns current (smoothed) value of a channel.
+    pub fn channel_value(&self, channel: &str) -> f32 {
+        self.values.get(channel).copied().unwrap_or_default()
+    }
+
+    fn target_values(&self, nodes: &NodePool, sound_context: &SoundContext) -> Vec<(String, f32)> {
+        let mut layers = self.layers.iter().filter(|l| l.enabled).collect::<Vec<_>>();
+        layers.sort_by_key(|l| l.priority);
+
+        let mut targets = Vec::<(String, f32)>::new();
+        let mut layer_values = Vec::new();
+        for layer in layers {
+            layer_values.clear();
+            layer.collect_values(nodes, sound_context, &mut layer_values);
+            for (channel, value) in layer_values.drain(..) {
+                let weight = layer.weight.clamp(0.0, 1.0);
+                match targets.iter_mut().find(|(c, _)| *c == channel) {
+                    Some((_, target)) => *target = lerpf(*target, value, weight),
+                    None => targets.push((channel, value * weight)),
+                }
+            }
+        }
+        targets
+    }
+
+    fn apply(&self, nodes: &mut NodePool) {
+        let mut weights = FxHashMap::<&str, f32>::default();
+        for channel in self.channels.iter() {
+            let value = self.channel_value(&channel.name);
+            for target in channel.targets.iter() {
+                *weights.entry(target.blend_shape.as_str()).or_default() += value * target.weight;
+            }
+        }
+
+        for &mesh in self.meshes.iter() {
+            let Some(mesh) = nodes
+                .try_borrow_mut(mesh)
+                .and_then(|n| n.cast_mut::<Mesh>())
+            else {
+                continue;
+            };
+
+            for blend_shape in mesh.blend_shapes_mut() {
+                if let Some(weight) = weights.get(blend_shape.name.as_str()) {
+                    blend_shape.weight = weight.clamp(0.0, 100.0);
+                }
+            }
+        }
+    }
+}
+
+impl NodeTrait for FacialAnimator {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        let dt = context.dt;
+
+        for layer in self.layers.get_value_mut_silent().iter_mut() {
+            layer.advance(context.nodes, dt);
+        }
+
+        let targets = self.target_values(context.nodes, context.sound_context);
+
+        let k = if *self.smoothing_time > 0.0 {
+            1.0 - (-dt / *self.smoothing_time).exp()
+        } else {
+            1.0
+        };
+        let channels = self
+            .channels
+            .iter()
+            .map(|c| c.name.clone())
+            .collect::<Vec<_>>();
+        for channel in channels {
+            let target = targets
+                .iter()
+                .find(|(c, _)| *c == channel)
+                .map_or(0.0, |(_, v)| *v);
+            let value = self.values.entry(channel).or_default();
+            *value = lerpf(*value, target, k);
+        }
+
+        self.apply(context.nodes);
+    }
+}
+
+/// Allows you to create [`FacialAnimator`] nodes in a declarative manner.
+pub struct FacialAnimatorBuilder {
+    base_builder: BaseBuilder,
+    meshes: Vec<Handle<Node>>,
+    channels: Vec<FacialChannel>,
+    layers: Vec<FacialLayer>,
+    smoothing_time: f32,
+}
+
+impl FacialAnimatorBuilder {
+    /// Creates a new builder instance.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            meshes: Default::default(),
+            channels: Default::default(),
+            layers: Default::default(),
+            smoothing_time: 0.08,
+        }
+    }
+
+    /// Sets meshes, which blend shapes will be driven by the animator.
+    pub fn with_meshes(mut self, meshes: Vec<Handle<Node>>) -> Self {
+        self.meshes = meshes;
+        self
+    }
+
+    /// Sets facial channels of the animator.
+    pub fn with_channels(mut self, channels: Vec<FacialChannel>) -> Self {
+        self.channels = channels;
+        self
+    }
+
+    /// Sets layers of the animator.
+    pub fn with_layers(mut self, layers: Vec<FacialLayer>) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    /// Sets smoothing time (in seconds) of the channels.
+    pub fn with_smoothing_time(mut self, smoothing_time: f32) -> Self {
+        self.smoothing_time = smoothing_time;
+        self
+    }
+
+    /// Creates new [`FacialAnimator`] instance.
+    pub fn build_facial_animator(self) -> FacialAnimator {
+        FacialAnimator {
+            base: self.base_builder.build_base(),
+            meshes: self.meshes.into(),
+            channels: self.channels.into(),
+            layers: self.layers.into(),
+            smoothing_time: self.smoothing_time.max(0.0).into(),
+            values: Default::default(),
+        }
+    }
+
+    /// Creates new [`FacialAnimator`] node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_facial_animator())
+    }
+
+    /// Creates new [`FacialAnimator`] node and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::untyped::ResourceKind,
+        core::{algebra::Matrix4, pool::Handle},
+        scene::{
+            animation::facial::{
+                BlendShapeTarget, FacialAnimatorBuilder, FacialChannel, FacialLayer,
+                FacialLayerSource, VisemeKey,
+            },
+            base::BaseBuilder,
+            graph::Graph,
+            mesh::{
+                surface::{BlendShape, SurfaceBuilder, SurfaceData, SurfaceResource},
+                Mesh, MeshBuilder,
+            },
+            node::Node,
+        },
+    };
+
+    fn channel(name: &str, blend_shape: &str) -> FacialChannel {
+        FacialChannel {
+            name: name.to_string(),
+            targets: vec![BlendShapeTarget {
+                blend_shape: blend_shape.to_string(),
+                weight: 100.0,
+            }],
+        }
+    }
+
+    fn weights(graph: &Graph, mesh: Handle<Node>) -> Vec<f32> {
+        graph[mesh]
+            .cast::<Mesh>()
+            .unwrap()
+            .blend_shapes()
+            .iter()
+            .map(|s| s.weight)
+            .collect()
+    }
+
+    #[test]
+    fn test_facial_layers() {
+        let mut graph = Graph::new();
+        let mesh = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+                ResourceKind::Embedded,
+                SurfaceData::make_cube(Matrix4::identity()),
+            ))
+            .build()])
+            .with_blend_shapes(
+                ["open", "round", "smile"]
+                    .into_iter()
+                    .map(|name| BlendShape {
+                        weight: 0.0,
+                        name: name.to_string(),
+                    })
+                    .collect(),
+            )
+            .build(&mut graph);
+
+        let emotion = FacialLayer::new("Emotion", 0, Default::default());
+        let visemes = FacialLayer::new(
+            "Visemes",
+            1,
+            FacialLayerSource::Track {
+                keys: vec![
+                    VisemeKey {
+                        time: 0.0,
+                        channel: "AA".to_string(),
+                        duration: 0.0,
+                    },
+                    VisemeKey {
+                        time: 0.5,
+                        channel: "OU".to_string(),
+                        duration: 0.25,
+                    },
+                ],
+                sound: Handle::NONE,
+            },
+        );
+
+        let animator = FacialAnimatorBuilder::new(BaseBuilder::new())
+            .with_meshes(vec![mesh])
+            .with_channels(vec![
+                channel("AA", "open"),
+                channel("OU", "round"),
+                channel("Smile", "smile"),
+            ])
+            .with_layers(vec![emotion, visemes])
+            .with_smoothing_time(0.0)
+            .build(&mut graph);
+
+        let animator_ref = graph[animator].cast_mut::<super::FacialAnimator>().unwrap();
+        assert!(animator_ref.set_channel_value("Emotion", "Smile", 0.5));
+        // The track overrides the emotion layer for its own channels only.
+        assert!(animator_ref.set_channel_value("Emotion", "AA", 1.0));
+        assert!(!animator_ref.set_channel_value("Visemes", "AA", 1.0));
+
+        graph.update(Default::default(), 0.1, Default::default());
+        assert_eq!(weights(&graph, mesh), vec![100.0, 0.0, 50.0]);
+
+        graph.update(Default::default(), 0.5, Default::default());
+        assert_eq!(weights(&graph, mesh), vec![0.0, 100.0, 50.0]);
+
+        // The key of OU is over.
+        graph.update(Default::default(), 0.5, Default::default());
+        assert_eq!(weights(&graph, mesh), vec![0.0, 0.0, 50.0]);
+    }
+}
@@ -23,6 +23,8 @@ use fyrox_graph::BaseSceneGraph;
 use std::ops::{Deref, DerefMut};
 
 pub mod absm;
+pub mod facial;
+pub(crate) mod parallel;
 pub mod spritesheet;
 
 /// Scene specific animation.
@@ -0,0 +1,233 @@
+//! Parallel animation update stage of the graph. Animation players and animation blending state
+//! machines are grouped by animation player, every group is evaluated on a thread pool and then the
+//! output poses are applied to the scene nodes on the calling thread.
+//!
+//! Evaluation of a group does not touch the graph at all (animations and machines are temporarily
+//! moved out of their nodes), so groups are independent even if they animate the same nodes. Poses
+//! are applied in the same order as the sequential update would apply them for groups: animations of
+//! a player first, then the poses of the machines that use the player.
+
+use crate::{
+    core::pool::Handle,
+    scene::{
+        animation::{
+            absm::{AnimationBlendingStateMachine, Machine},
+            AnimationContainer, AnimationPlayer, AnimationPoseExt,
+        },
+        graph::NodePool,
+        node::Node,
+    },
+};
+use fxhash::{FxHashMap, FxHashSet};
+use rayon::prelude::*;
+
+/// An animation player and every state machine, that uses the player.
+struct AnimationGroup {
+    player: Handle<Node>,
+    // `true` if the player itself must be updated (it is enabled and not filtered out).
+    tick_player: bool,
+    auto_apply: bool,
+    animations: AnimationContainer,
+    machines: Vec<(Handle<Node>, Machine)>,
+}
+
+impl AnimationGroup {
+    fn evaluate(&mut self, dt: f32) {
+        if self.tick_player {
+            // Poses are applied later on the calling thread.
+            for animation in self.animations.iter_mut().filter(|a| a.is_enabled()) {
+                animation.tick(dt);
+            }
+        }
+        for (_, machine) in self.machines.iter_mut() {
+            machine.evaluate_pose(&mut self.animations, dt);
+        }
+    }
+
+    fn apply(&self, nodes: &mut NodePool) {
+        if self.tick_player && self.auto_apply {
+            for animation in self.animations.iter().filter(|a| a.is_enabled()) {
+                animation.pose().apply_internal(nodes);
+            }
+        }
+        for (_, machine) in self.machines.iter() {
+            machine.pose().apply_internal(nodes);
+        }
+    }
+}
+
+/// Updates every animation player and animation blending state machine in the pool using the thread
+/// pool. `filter` decides which nodes should be updated. Handles of the updated nodes are added to
+/// `updated`, these nodes must not be updated again in this frame.
+pub(crate) fn update_animations(
+    nodes: &mut NodePool,
+    dt: f32,
+    mut filter: impl FnMut(Handle<Node>) -> bool,
+    updated: &mut FxHashSet<Handle<Node>>,
+) {
+    let mut group_indices = FxHashMap::<Handle<Node>, usize>::default();
+    let mut groups = Vec::new();
+
+    let mut group_for = |player: Handle<Node>, groups: &mut Vec<AnimationGroup>| -> usize {
+        *group_indices.entry(player).or_insert_with(|| {
+            groups.push(AnimationGroup {
+                player,
+                tick_player: false,
+                auto_apply: false,
+                animations: Default::default(),
+                machines: Default::default(),
+            });
+            groups.len() - 1
+        })
+    };
+
+    for (handle, node) in nodes.pair_iter() {
+        if !node.is_globally_enabled() || !filter(handle) {
+            continue;
+        }
+
+        // Only exact animation players are updated here, other nodes with animation player component
+        // may do something else in their update.
+        if node.cast::<AnimationPlayer>().is_some() {
+            let index = group_for(handle, &mut groups);
+            groups[index].tick_player = true;
+        } else if let Some(absm) = node.cast::<AnimationBlendingStateMachine>() {
+            let player = absm.animation_player();
+            if nodes
+                .try_borrow(player)
+                .and_then(|n| n.query_component_ref::<AnimationPlayer>())
+                .is_some()
+            {
+                let index = group_for(player, &mut groups);
+                groups[index].machines.push((handle, Machine::default()));
+            }
+        }
+    }
+
+    if groups.is_empty() {
+        return;
+    }
+
+    // Move the animations and the machines out of their nodes.
+    for group in groups.iter_mut() {
+        let player = nodes[group.player]
+            .query_component_mut::<AnimationPlayer>()
+            .unwrap();
+        if !group.machines.is_empty() {
+            // The same as the machine does in its own update.
+            player.set_auto_apply(false);
+        }
+        group.auto_apply = player.is_auto_apply();
+        group.animations = std::mem::take(player.animations.get_value_mut_silent());
+
+        for (handle, machine) in group.machines.iter_mut() {
+            let absm = nodes[*handle]
+                .cast_mut::<AnimationBlendingStateMachine>()
+                .unwrap();
+            *machine = std::mem::take(absm.machine_mut().get_value_mut_silent());
+        }
+    }
+
+    groups.par_iter_mut().for_each(|group| group.evaluate(dt));
+
+    for group in groups {
+        group.apply(nodes);
+
+        let player = nodes[group.player]
+            .query_component_mut::<AnimationPlayer>()
+            .unwrap();
+        *player.animations.get_value_mut_silent() = group.animations;
+        if group.tick_player {
+            updated.insert(group.player);
+        }
+
+        for (handle, machine) in group.machines {
+            if let Some((ticket, mut node)) = nodes.try_take_reserve(handle) {
+                if let Some(absm) = node.cast_mut::<AnimationBlendingStateMachine>() {
+                    *absm.machine_mut().get_value_mut_silent() = machine;
+                    absm.process_actions(nodes);
+                }
+                nodes.put_back(ticket, node);
+            }
+            updated.insert(handle);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::Vector3,
+            math::curve::{Curve, CurveKey, CurveKeyKind},
+            pool::Handle,
+        },
+        generic_animation::container::{TrackDataContainer, TrackValueKind},
+        scene::{
+            animation::{prelude::*, AnimationPlayerBuilder},
+            base::BaseBuilder,
+            graph::{Graph, GraphUpdateSwitches},
+            pivot::PivotBuilder,
+        },
+    };
+
+    fn make_animated_node(graph: &mut Graph) -> Handle<crate::scene::node::Node> {
+        let node = PivotBuilder::new(BaseBuilder::new()).build(graph);
+
+        let mut container = TrackDataContainer::new(TrackValueKind::Vector3);
+        let mut curve = Curve::default();
+        curve.add_key(CurveKey::new(0.0, 0.0, CurveKeyKind::Linear));
+        curve.add_key(CurveKey::new(1.0, 1.0, CurveKeyKind::Linear));
+        container.curves_mut()[0] = curve;
+
+        let mut track = Track::new_position().with_target(node);
+        track.set_data_container(container);
+
+        let mut animation = Animation::default();
+        animation.add_track(track);
+        animation.set_time_slice(0.0..1.0);
+
+        let mut animations = AnimationContainer::new();
+        animations.add(animation);
+        AnimationPlayerBuilder::new(BaseBuilder::new())
+            .with_animations(animations)
+            .build(graph);
+
+        node
+    }
+
+    #[test]
+    fn test_parallel_animation_matches_sequential() {
+        let mut positions = Vec::new();
+        for parallel_animation in [false, true] {
+            let mut graph = Graph::new();
+            let nodes = (0..8)
+                .map(|_| make_animated_node(&mut graph))
+                .collect::<Vec<_>>();
+
+            for _ in 0..3 {
+                graph.update(
+                    Default::default(),
+                    0.25,
+                    GraphUpdateSwitches {
+                        parallel_animation,
+                        ..Default::default()
+                    },
+                );
+            }
+
+            positions.push(
+                nodes
+                    .iter()
+                    .map(|n| **graph[*n].local_transform().position())
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        assert_eq!(positions[0], positions[1]);
+        // `Animation::tick` samples the pose before it advances the time position, so after three
+        // updates the time position is 0.75, but the applied pose is the one at 0.5 seconds. Both
+        // update modes must behave the same.
+        assert_eq!(positions[1][0], Vector3::new(0.5, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,323 @@
+//! Ambient occlusion volume is a box with baked large-scale ambient occlusion and bent normals of
+//! static geometry inside of it.
+//!
+//! For more info see [`AoVolume`]
+
+use crate::{
+    core::{
+        algebra::Vector3,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    resource::texture::TextureResource,
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{Node, NodeTrait},
+        Scene,
+    },
+    utils::voxel_ao::{self, VoxelAoError, VoxelAoSettings, MAX_VOXELS_PER_AXIS},
+};
+use fyrox_graph::{BaseSceneGraph, SceneGraph};
+use std::ops::{Deref, DerefMut};
+
+/// Max amount of rays, that are cast from each voxel during baking.
+pub const MAX_RAYS_PER_VOXEL: u32 = 1024;
+
+/// Ambient occlusion volume is a box, that contains baked ambient occlusion and bent normals of the
+/// static geometry inside of it. The baked data is used in the deferred lighting pass to darken the
+/// ambient lighting of the surfaces, that are surrounded by other geometry. Unlike screen-space
+/// ambient occlusion, which knows only about the geometry visible on screen, the baked occlusion
+/// takes into account everything inside the volume, so large occluders (walls, ceilings, etc.)
+/// give proper occlusion even when they are off-screen. Both techniques could be used together,
+/// screen-space ambient occlusion adds small-scale details. The volume is a unit cube in local
+/// coordinates of the node, use the scale of the node to define its size.
+///
+/// # How it works
+///
+/// Static geometry inside the volume is voxelized into a regular grid of voxels (see
+/// [`AoVolume::set_resolution`]), then a number of rays is marched from every empty voxel to
+/// find out how much of the surrounding space is unoccluded and in which direction (bent normal).
+/// The result is stored in a volume texture, which is filtered when sampled, so the resolution
+/// could be quite coarse. Shaded surfaces, that face away from the unoccluded directions, are
+/// darkened more.
+///
+/// The baking is offline, call [`AoVolume::bake`] every time the static geometry changes. The
+/// baked texture is saved together with the scene.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::Vector3, pool::Handle},
+/// #     scene::{
+/// #         ao_volume::{AoVolume, AoVolumeBuilder},
+/// #         base::BaseBuilder, node::Node, transform::TransformBuilder, Scene,
+/// #     },
+/// # };
+/// fn create_ao_volume(scene: &mut Scene) -> Handle<Node> {
+///     let volume = AoVolumeBuilder::new(
+///         BaseBuilder::new().with_local_transform(
+///             TransformBuilder::new()
+///                 .with_local_scale(Vector3::new(20.0, 5.0, 20.0))
+///                 .build(),
+///         ),
+///     )
+///     .with_resolution(Vector3::new(40, 10, 40))
+///     .build(&mut scene.graph);
+///
+///     scene.graph.update_hierarchical_data();
+///
+///     // Bake occlusion of every mesh in the scene.
+///     AoVolume::bake(scene, volume, |_, _| true).unwrap();
+///
+///     volume
+/// }
+/// ```
+#[derive(Debug, Visit, Clone, Reflect)]
+pub struct AoVolume {
+    base: Base,
+
+    #[reflect(setter = "set_resolution")]
+    resolution: InheritableVariable<Vector3<u32>>,
+
+    #[reflect(min_value = 1.0, max_value = 1024.0)]
+    #[reflect(setter = "set_ray_count")]
+    ray_count: InheritableVariable<u32>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_max_distance")]
+    max_distance: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    #[reflect(setter = "set_intensity")]
+    intensity: InheritableVariable<f32>,
+
+    #[reflect(read_only)]
+    baked: InheritableVariable<Option<TextureResource>>,
+}
+
+impl Default for AoVolume {
+    fn default() -> Self {
+        AoVolumeBuilder::new(BaseBuilder::new()).build_ao_volume()
+    }
+}
+
+impl Deref for AoVolume {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for AoVolume {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for AoVolume {
+    fn type_uuid() -> Uuid {
+        uuid!("6d3b9e27-1c84-4f5a-a0e2-8b47f5c1d936")
+    }
+}
+
+fn clamp_resolution(resolution: Vector3<u32>) -> Vector3<u32> {
+    resolution.map(|n| n.clamp(1, MAX_VOXELS_PER_AXIS))
+}
+
+impl AoVolume {
+    /// Sets the amount of voxels along each axis of the volume. Every component is clamped to
+    /// `[1; MAX_VOXELS_PER_AXIS]` range. The volume must be baked again to apply the changes.
+    pub fn set_resolution(&mut self, resolution: Vector3<u32>) -> Vector3<u32> {
+        self.resolution
+            .set_value_and_mark_modified(clamp_resolution(resolution))
+    }
+
+    /// Returns the amount of voxels along each axis of the volume.
+    pub fn resolution(&self) -> Vector3<u32> {
+        clamp_resolution(*self.resolution)
+    }
+
+    /// Sets the amount of rays, that are cast from each voxel during baking. More rays give
+    /// smoother occlusion. The value is clamped to `[1; MAX_RAYS_PER_VOXEL]` range.
+    pub fn set_ray_count(&mut self, count: u32) -> u32 {
+        self.ray_count
+            .set_value_and_mark_modified(count.clamp(1, MAX_RAYS_PER_VOXEL))
+    }
+
+    /// Returns the amount of rays, that are cast from each voxel during baking.
+    pub fn ray_count(&self) -> u32 {
+        (*self.ray_count).clamp(1, MAX_RAYS_PER_VOXEL)
+    }
+
+    /// Sets max distance (in world units) at which the geometry occludes a voxel.
+    pub fn set_max_distance(&mut self, distance: f32) -> f32 {
+        self.max_distance
+            .set_value_and_mark_modified(distance.max(0.0))
+    }
+
+    /// Returns max distance at which the geometry occludes a voxel.
+    pub fn max_distance(&self) -> f32 {
+        *self.max_distance
+    }
+
+    /// Sets how much the baked occlusion darkens the ambient lighting, zero disables the volume.
+    /// The value is clamped to `[0.0; 1.0]` range.
+    pub fn set_intensity(&mut self, intensity: f32) -> f32 {
+        self.intensity
+            .set_value_and_mark_modified(intensity.clamp(0.0, 1.0))
+    }
+
+    /// Returns how much the baked occlusion darkens the ambient lighting.
+    pub fn intensity(&self) -> f32 {
+        *self.intensity
+    }
+
+    /// Returns the size of a voxel along each axis in world units.
+    pub fn voxel_size(&self) -> Vector3<f32> {
+        let transform = self.global_transform();
+        Vector3::new(
+            transform.column(0).xyz().norm(),
+            transform.column(1).xyz().norm(),
+            transform.column(2).xyz().norm(),
+        )
+        .component_div(&self.resolution().cast::<f32>())
+    }
+
+    /// Returns the baked volume texture (if any). Bent normals are stored in `RGB` channels
+    /// (remapped to `[0; 1]` range), ambient occlusion is stored in alpha channel.
+    pub fn baked(&self) -> Option<&TextureResource> {
+        self.baked.as_ref()
+    }
+
+    /// Discards the baked data.
+    pub fn clear_baked(&mut self) {
+        self.baked.set_value_and_mark_modified(None);
+    }
+
+    /// Returns baking settings of the volume.
+    pub fn settings(&self) -> VoxelAoSettings {
+        VoxelAoSettings {
+            resolution: self.resolution(),
+            ray_count: self.ray_count(),
+            max_distance: self.max_distance(),
+        }
+    }
+
+    /// Bakes ambient occlusion of the meshes inside the volume with the given handle. Only the
+    /// meshes, that pass the given filter, are taken into account, use it to skip dynamic
+    /// objects. Global transforms of the scene nodes must be up-to-date. The method does nothing
+    /// if the handle does not point to an ambient occlusion volume. See
+    /// [`voxel_ao::bake_voxel_ao`] for more info.
+    pub fn bake<F>(scene: &mut Scene, handle: Handle<Node>, filter: F) -> Result<(), VoxelAoError>
+    where
+        F: FnMut(Handle<Node>, &Node) -> bool,
+    {
+        let Some(volume) = scene.graph.try_get_of_type::<AoVolume>(handle) else {
+            return Ok(());
+        };
+        let transform = volume.global_transform();
+        let settings = volume.settings();
+
+        let texture = voxel_ao::bake_voxel_ao(scene, transform, &settings, filter)?;
+
+        if let Some(volume) = scene.graph.try_get_mut_of_type::<AoVolume>(handle) {
+            volume.baked.set_value_and_mark_modified(Some(texture));
+        }
+
+        Ok(())
+    }
+}
+
+impl NodeTrait for AoVolume {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox::unit()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Allows you to create ambient occlusion volumes in declarative manner.
+pub struct AoVolumeBuilder {
+    base_builder: BaseBuilder,
+    resolution: Vector3<u32>,
+    ray_count: u32,
+    max_distance: f32,
+    intensity: f32,
+}
+
+impl AoVolumeBuilder {
+    /// Creates new ambient occlusion volume builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        let settings = VoxelAoSettings::default();
+        Self {
+            base_builder,
+            resolution: settings.resolution,
+            ray_count: settings.ray_count,
+            max_distance: settings.max_distance,
+            intensity: 1.0,
+        }
+    }
+
+    /// Sets desired amount of voxels along each axis. See [`AoVolume::set_resolution`].
+    pub fn with_resolution(mut self, resolution: Vector3<u32>) -> Self {
+        self.resolution = clamp_resolution(resolution);
+        self
+    }
+
+    /// Sets desired amount of rays per voxel. See [`AoVolume::set_ray_count`].
+    pub fn with_ray_count(mut self, count: u32) -> Self {
+        self.ray_count = count.clamp(1, MAX_RAYS_PER_VOXEL);
+        self
+    }
+
+    /// Sets desired max occlusion distance. See [`AoVolume::set_max_distance`].
+    pub fn with_max_distance(mut self, distance: f32) -> Self {
+        self.max_distance = distance.max(0.0);
+        self
+    }
+
+    /// Sets desired intensity of the occlusion. See [`AoVolume::set_intensity`].
+    pub fn with_intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Creates new ambient occlusion volume.
+    pub fn build_ao_volume(self) -> AoVolume {
+        AoVolume {
+            base: self.base_builder.build_base(),
+            resolution: self.resolution.into(),
+            ray_count: self.ray_count.into(),
+            max_distance: self.max_distance.into(),
+            intensity: self.intensity.into(),
+            baked: None.into(),
+        }
+    }
+
+    /// Creates new ambient occlusion volume node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_ao_volume())
+    }
+
+    /// Creates new ambient occlusion volume and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
@@ -17,8 +17,8 @@ use crate::{
     engine::SerializationContext,
     graph::BaseSceneGraph,
     resource::model::ModelResource,
-    scene::{node::Node, transform::Transform},
-    script::{Script, ScriptTrait},
+    scene::{collider::BitMask, node::Node, transform::Transform},
+    script::{Script, ScriptMessage, ScriptTrait},
 };
 use serde::{Deserialize, Serialize};
 use std::{
@@ -179,6 +179,35 @@ pub enum Mobility {
     Dynamic = 2,
 }
 
+/// Defines what happens with a node when its lifetime ends. See [`Base::set_lifetime`] docs for
+/// more info.
+#[derive(
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Debug,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+)]
+#[type_uuid(id = "a3bc5a1d-3bd1-4b4e-9f61-2e3c0c6d8a57")]
+#[repr(u32)]
+pub enum LifetimePolicy {
+    /// The node will be removed from the graph (including all its descendants).
+    #[default]
+    Remove = 0,
+
+    /// The node will be disabled, but kept in the graph. It is useful for pooled objects, that
+    /// are recycled instead of being re-created every time. See
+    /// [`crate::scene::prefab_pool::PrefabPool`] for more info.
+    Disable = 1,
+}
+
 /// A property value.
 #[derive(
     Debug, Visit, Reflect, PartialEq, Clone, AsRefStr, EnumString, VariantNames, TypeUuidProvider,
@@ -248,6 +277,14 @@ pub enum NodeScriptMessage {
         /// Index of the script.
         script_index: usize,
     },
+    /// A node was recycled (see [`crate::scene::prefab_pool::PrefabPool`]) and its script must
+    /// reset its state.
+    RecycleScript {
+        /// Node handle.
+        handle: Handle<Node>,
+        /// Index of the script.
+        script_index: usize,
+    },
     /// A node script must be destroyed. It can happen if the script was replaced with some other
     /// or a node was destroyed.
     DestroyScript {
@@ -258,6 +295,9 @@ pub enum NodeScriptMessage {
         /// Index of the script.
         script_index: usize,
     },
+    /// A node sends a script message. It is used by nodes, that have no access to
+    /// [`crate::script::ScriptMessageSender`] in their update methods.
+    SendMessage(ScriptMessage),
 }
 
 /// Unique id of a node, that could be used as a reliable "index" of the node. This id is mostly
@@ -369,6 +409,12 @@ pub struct Base {
     )]
     pub(crate) lifetime: InheritableVariable<Option<f32>>,
 
+    #[reflect(
+        description = "Defines what happens with the node when its lifetime ends.",
+        setter = "set_lifetime_policy"
+    )]
+    lifetime_policy: InheritableVariable<LifetimePolicy>,
+
     #[reflect(min_value = 0.0, max_value = 1.0, step = 0.1)]
     #[reflect(setter = "set_depth_offset_factor")]
     depth_offset: InheritableVariable<f32>,
@@ -385,6 +431,9 @@ pub struct Base {
     #[reflect(setter = "set_cast_shadows")]
     cast_shadows: InheritableVariable<bool>,
 
+    #[reflect(setter = "set_render_mask")]
+    render_mask: InheritableVariable<BitMask>,
+
     /// A set of custom properties that can hold almost any data. It can be used to set additional
     /// properties to scene nodes.
 
@@ -542,6 +591,19 @@ impl Base {
         *self.lifetime
     }
 
+    /// Sets a new lifetime policy, that defines what happens with the node when its lifetime
+    /// ends. See [`LifetimePolicy`] docs for more info.
+    #[inline]
+    pub fn set_lifetime_policy(&mut self, policy: LifetimePolicy) -> LifetimePolicy {
+        self.lifetime_policy.set_value_and_mark_modified(policy)
+    }
+
+    /// Returns current lifetime policy of the node.
+    #[inline]
+    pub fn lifetime_policy(&self) -> LifetimePolicy {
+        *self.lifetime_policy
+    }
+
     /// Returns handle of parent node.
     #[inline]
     pub fn parent(&self) -> Handle<Node> {
@@ -740,6 +802,22 @@ impl Base {
             .set_value_and_mark_modified(frustum_culling)
     }
 
+    /// Returns a set of render layers, that the node belongs to. See [`Self::set_render_mask`] for
+    /// more info.
+    #[inline]
+    pub fn render_mask(&self) -> BitMask {
+        *self.render_mask
+    }
+
+    /// Sets a set of render layers, that the node belongs to. The node is rendered by a camera
+    /// only if the render mask of the camera has at least one common bit with the render mask of
+    /// the node. By default, a node belongs to all layers. The mask affects only the node itself,
+    /// not its descendants.
+    #[inline]
+    pub fn set_render_mask(&mut self, mask: BitMask) -> BitMask {
+        self.render_mask.set_value_and_mark_modified(mask)
+    }
+
     /// Returns true if the node should cast shadows, false - otherwise.
     #[inline]
     pub fn cast_shadows(&self) -> bool {
@@ -1094,8 +1172,10 @@ impl Visit for Base {
         let _ = self.properties.visit("Properties", &mut region);
         let _ = self.frustum_culling.visit("FrustumCulling", &mut region);
         let _ = self.cast_shadows.visit("CastShadows", &mut region);
+        let _ = self.render_mask.visit("RenderMask", &mut region);
         let _ = self.instance_id.visit("InstanceId", &mut region);
         let _ = self.enabled.visit("Enabled", &mut region);
+        let _ = self.lifetime_policy.visit("LifetimePolicy", &mut region);
 
         // Script visiting may fail for various reasons:
         //
@@ -1128,6 +1208,7 @@ pub struct BaseBuilder {
     local_transform: Transform,
     children: Vec<Handle<Node>>,
     lifetime: Option<f32>,
+    lifetime_policy: LifetimePolicy,
     depth_offset: f32,
     lod_group: Option<LodGroup>,
     mobility: Mobility,
@@ -1135,6 +1216,7 @@ pub struct BaseBuilder {
     tag: String,
     frustum_culling: bool,
     cast_shadows: bool,
+    render_mask: BitMask,
     scripts: Vec<ScriptRecord>,
     instance_id: SceneNodeId,
     enabled: bool,
@@ -1156,6 +1238,7 @@ impl BaseBuilder {
             local_transform: Default::default(),
             children: Default::default(),
             lifetime: None,
+            lifetime_policy: Default::default(),
             depth_offset: 0.0,
             lod_group: None,
             mobility: Default::default(),
@@ -1163,12 +1246,20 @@ impl BaseBuilder {
             tag: Default::default(),
             frustum_culling: true,
             cast_shadows: true,
+            render_mask: BitMask(u32::MAX),
             scripts: vec![],
             instance_id: SceneNodeId(Uuid::new_v4()),
             enabled: true,
         }
     }
 
+    /// Sets desired lifetime policy.
+    #[inline]
+    pub fn with_lifetime_policy(mut self, policy: LifetimePolicy) -> Self {
+        self.lifetime_policy = policy;
+        self
+    }
+
     /// Sets desired mobility.
     #[inline]
     pub fn with_mobility(mut self, mobility: Mobility) -> Self {
@@ -1266,6 +1357,13 @@ impl BaseBuilder {
         self
     }
 
+    /// Sets desired render mask. See [`Base::set_render_mask`] for more info.
+    #[inline]
+    pub fn with_render_mask(mut self, mask: BitMask) -> Self {
+        self.render_mask = mask;
+        self
+    }
+
     /// Sets script of the node.
     #[inline]
     pub fn with_script<T>(mut self, script: T) -> Self
@@ -1292,6 +1390,7 @@ impl BaseBuilder {
             children: self.children,
             local_transform: self.local_transform,
             lifetime: self.lifetime.into(),
+            lifetime_policy: self.lifetime_policy.into(),
             visibility: self.visibility.into(),
             global_visibility: Cell::new(true),
             parent: Handle::NONE,
@@ -1308,6 +1407,7 @@ impl BaseBuilder {
             transform_modified: Cell::new(false),
             frustum_culling: self.frustum_culling.into(),
             cast_shadows: self.cast_shadows.into(),
+            render_mask: self.render_mask.into(),
             scripts: self.scripts,
             instance_id: SceneNodeId(Uuid::new_v4()),
             enabled: self.enabled.into(),
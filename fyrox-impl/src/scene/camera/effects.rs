@@ -0,0 +1,480 @@
+//! Camera effects are procedural modifications of camera transform and field of view - camera shake,
+//! recoil kicks, field of view kicks, smooth follow. See [`CameraEffect`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, UnitQuaternion, Vector2, Vector3},
+        noise::{Noise, Perlin},
+        pool::Handle,
+        reflect::prelude::*,
+        uuid_provider,
+        visitor::prelude::*,
+    },
+    scene::{
+        collider::InteractionGroups,
+        graph::{
+            physics::{PhysicsWorld, RayCastOptions},
+            NodePool,
+        },
+        node::Node,
+    },
+};
+use lazy_static::lazy_static;
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+lazy_static! {
+    static ref SHAKE_NOISE: Perlin = Perlin::new(0);
+}
+
+/// Trauma-based camera shake. Trauma is a value in `[0; 1]` range, that is increased by game events
+/// (explosions, hits, etc.) and decreases linearly over time. Actual shake intensity is `trauma^exponent`,
+/// which makes small amounts of trauma almost unnoticeable and large amounts - very intense. Offsets
+/// are sampled from a coherent noise, which gives smooth and natural motion (unlike random offsets
+/// every frame, which depend on frame rate and look jittery).
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct TraumaShake {
+    /// Maximum translation offset (in local coordinates of the camera) at full trauma.
+    pub max_offset: Vector3<f32>,
+    /// Maximum rotation angles (in radians) around local X, Y, Z axes at full trauma.
+    pub max_angles: Vector3<f32>,
+    /// Frequency of the shake (how many oscillations per second, approximately).
+    #[reflect(min_value = 0.0)]
+    pub frequency: f32,
+    /// Amount of trauma removed per second.
+    #[reflect(min_value = 0.0)]
+    pub decay: f32,
+    /// Exponent that is applied to trauma to get shake intensity. Values in `[2; 3]` range give good
+    /// results.
+    #[reflect(min_value = 1.0)]
+    pub exponent: f32,
+    /// Seed of the noise. Use different seeds for different cameras to make their shakes different.
+    pub seed: u32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    trauma: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    time: f32,
+}
+
+impl Default for TraumaShake {
+    fn default() -> Self {
+        Self {
+            max_offset: Vector3::new(0.1, 0.1, 0.0),
+            max_angles: Vector3::new(
+                3.0f32.to_radians(),
+                3.0f32.to_radians(),
+                5.0f32.to_radians(),
+            ),
+            frequency: 15.0,
+            decay: 0.8,
+            exponent: 2.0,
+            seed: 0,
+            trauma: 0.0,
+            time: 0.0,
+        }
+    }
+}
+
+impl TraumaShake {
+    /// Adds the given amount of trauma. Resulting trauma is clamped to `[0; 1]` range.
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    /// Returns current amount of trauma.
+    pub fn trauma(&self) -> f32 {
+        self.trauma
+    }
+
+    fn update(&mut self, dt: f32, output: &mut CameraEffectsOutput) {
+        self.time += dt;
+
+        let intensity = self.trauma.powf(self.exponent);
+        if intensity > 0.0 {
+            let t = self.time * self.frequency;
+            // Each channel uses its own row of the noise. Half-unit shift prevents sampling at
+            // lattice points, where Perlin noise is always zero.
+            let row = self.seed as f32 * 7.0 + 0.5;
+            let sample = |channel: f32| {
+                intensity * SHAKE_NOISE.sample_2d(Vector2::new(t, row + channel * 13.0))
+            };
+
+            output.position_offset += Vector3::new(
+                self.max_offset.x * sample(0.0),
+                self.max_offset.y * sample(1.0),
+                self.max_offset.z * sample(2.0),
+            );
+            output.rotation_offset += Vector3::new(
+                self.max_angles.x * sample(3.0),
+                self.max_angles.y * sample(4.0),
+                self.max_angles.z * sample(5.0),
+            );
+        }
+
+        self.trauma = (self.trauma - self.decay * dt).max(0.0);
+    }
+}
+
+/// Maximum time step of spring simulation, larger time steps are split into multiple steps to keep
+/// the simulation stable.
+const MAX_SPRING_STEP: f32 = 1.0 / 120.0;
+
+/// Kick (recoil) effect. Kicks are impulses that push the camera away from its rest position, then a
+/// damped spring returns the camera back. It is useful for weapon recoil, landing after a jump, etc.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct Kick {
+    /// Stiffness of the spring that returns the camera to its rest position.
+    #[reflect(min_value = 0.0)]
+    pub stiffness: f32,
+    /// Damping of the spring. `2 * sqrt(stiffness)` gives critically damped spring (fastest return
+    /// without oscillations), smaller values make the camera oscillate around its rest position.
+    #[reflect(min_value = 0.0)]
+    pub damping: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    linear_offset: Vector3<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    linear_velocity: Vector3<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    angular_offset: Vector3<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    angular_velocity: Vector3<f32>,
+}
+
+impl Default for Kick {
+    fn default() -> Self {
+        Self {
+            stiffness: 200.0,
+            damping: 2.0 * 200.0f32.sqrt(),
+            linear_offset: Default::default(),
+            linear_velocity: Default::default(),
+            angular_offset: Default::default(),
+            angular_velocity: Default::default(),
+        }
+    }
+}
+
+impl Kick {
+    /// Adds an impulse to the camera. `linear` is a velocity change (in local coordinates of the camera),
+    /// `angular` is an angular velocity change around local X, Y, Z axes (in radians per second).
+    pub fn add_impulse(&mut self, linear: Vector3<f32>, angular: Vector3<f32>) {
+        self.linear_velocity += linear;
+        self.angular_velocity += angular;
+    }
+
+    fn update(&mut self, dt: f32, output: &mut CameraEffectsOutput) {
+        let steps = (dt / MAX_SPRING_STEP).ceil().max(1.0);
+        let h = dt / steps;
+        for _ in 0..steps as usize {
+            // Semi-implicit Euler integration.
+            self.linear_velocity += (-self.linear_offset.scale(self.stiffness)
+                - self.linear_velocity.scale(self.damping))
+            .scale(h);
+            self.linear_offset += self.linear_velocity.scale(h);
+
+            self.angular_velocity += (-self.angular_offset.scale(self.stiffness)
+                - self.angular_velocity.scale(self.damping))
+            .scale(h);
+            self.angular_offset += self.angular_velocity.scale(h);
+        }
+
+        output.position_offset += self.linear_offset;
+        output.rotation_offset += self.angular_offset;
+    }
+}
+
+/// Field of view kick. Temporarily changes field of view of the camera, the change exponentially
+/// decays over time. It is useful to emphasize acceleration, impacts, etc. Works only with perspective
+/// projection.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct FovKick {
+    /// Speed of the recovery, larger values make the field of view return to its normal value
+    /// faster.
+    #[reflect(min_value = 0.0)]
+    pub recovery_speed: f32,
+    /// Maximum absolute change of the field of view (in radians).
+    #[reflect(min_value = 0.0)]
+    pub max_offset: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    offset: f32,
+}
+
+impl Default for FovKick {
+    fn default() -> Self {
+        Self {
+            recovery_speed: 5.0,
+            max_offset: 20.0f32.to_radians(),
+            offset: 0.0,
+        }
+    }
+}
+
+impl FovKick {
+    /// Changes field of view by the given amount (in radians).
+    pub fn add_kick(&mut self, amount: f32) {
+        self.offset = (self.offset + amount).clamp(-self.max_offset, self.max_offset);
+    }
+
+    fn update(&mut self, dt: f32, output: &mut CameraEffectsOutput) {
+        output.fov_offset += self.offset;
+        // Exponential decay does not depend on frame rate.
+        self.offset *= (-self.recovery_speed * dt).exp();
+    }
+}
+
+/// Smooth follow makes the camera follow a target node at some offset and look at it. The camera is
+/// pulled towards the target if there is an obstacle between them, so the target is always visible.
+/// This effect overrides global transform of the camera, any other effects are applied on top of it.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct SmoothFollow {
+    /// A node to follow.
+    pub target: Handle<Node>,
+    /// Desired position of the camera in local coordinates of the target.
+    pub offset: Vector3<f32>,
+    /// A point (in local coordinates of the target) to look at.
+    pub look_at_offset: Vector3<f32>,
+    /// Speed of the following, larger values make the camera follow the target faster.
+    #[reflect(min_value = 0.0)]
+    pub smoothing: f32,
+    /// Distance between the camera and an obstacle, that the camera keeps.
+    #[reflect(min_value = 0.0)]
+    pub collision_margin: f32,
+    /// Collision groups of obstacles. Colliders that belong to the hierarchy of the target are always
+    /// ignored.
+    pub collision_groups: InteractionGroups,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    position: Option<Vector3<f32>>,
+}
+
+impl Default for SmoothFollow {
+    fn default() -> Self {
+        Self {
+            target: Default::default(),
+            offset: Vector3::new(0.0, 2.0, -5.0),
+            look_at_offset: Vector3::new(0.0, 1.5, 0.0),
+            smoothing: 8.0,
+            collision_margin: 0.2,
+            collision_groups: Default::default(),
+            position: None,
+        }
+    }
+}
+
+fn is_in_hierarchy(nodes: &NodePool, mut handle: Handle<Node>, root: Handle<Node>) -> bool {
+    while let Some(node) = nodes.try_borrow(handle) {
+        if handle == root {
+            return true;
+        }
+        handle = node.parent();
+    }
+    false
+}
+
+impl SmoothFollow {
+    fn update(
+        &mut self,
+        dt: f32,
+        nodes: &NodePool,
+        physics: &PhysicsWorld,
+        output: &mut CameraEffectsOutput,
+    ) {
+        let Some(target) = nodes.try_borrow(self.target) else {
+            return;
+        };
+
+        let target_transform = target.global_transform();
+        let pivot = target_transform
+            .transform_point(&Point3::from(self.look_at_offset))
+            .coords;
+        let desired = target_transform
+            .transform_point(&Point3::from(self.offset))
+            .coords;
+
+        let position = match self.position {
+            Some(position) => position.lerp(&desired, 1.0 - (-self.smoothing * dt).exp()),
+            None => desired,
+        };
+        self.position = Some(position);
+
+        // Obstacles are checked after smoothing, otherwise the camera could be behind an obstacle
+        // for some time.
+        let mut final_position = position;
+        let direction = position - pivot;
+        let distance = direction.norm();
+        if distance > f32::EPSILON {
+            let mut intersections = Vec::new();
+            physics.cast_ray(
+                RayCastOptions {
+                    ray_origin: Point3::from(pivot),
+                    ray_direction: direction,
+                    max_len: distance + self.collision_margin,
+                    groups: self.collision_groups,
+                    sort_results: true,
+                },
+                &mut intersections,
+            );
+
+            if let Some(intersection) = intersections
+                .iter()
+                .find(|i| !is_in_hierarchy(nodes, i.collider, self.target))
+            {
+                let allowed_distance = (intersection.toi - self.collision_margin).max(0.0);
+                if allowed_distance < distance {
+                    final_position = pivot + direction.scale(allowed_distance / distance);
+                }
+            }
+        }
+
+        let rotation = (pivot - final_position)
+            .try_normalize(f32::EPSILON)
+            .map(|look| UnitQuaternion::face_towards(&look, &Vector3::y()))
+            .unwrap_or_else(UnitQuaternion::identity);
+
+        output.transform =
+            Some(Matrix4::new_translation(&final_position) * rotation.to_homogeneous());
+    }
+}
+
+/// Camera effect is a procedural modification of camera transform and field of view. Effects are
+/// evaluated one after another in the order they're stored in the camera (see
+/// [`super::Camera::set_effects`]), after the scripts were updated. Effects do not change the local
+/// transform of the camera, instead they modify its global transform, which is then used for
+/// rendering. This means that the effects can be freely combined with any other code that moves the
+/// camera.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::scene::camera::{effects::{CameraEffect, TraumaShake}, Camera};
+/// fn setup(camera: &mut Camera) {
+///     camera.set_effects(vec![CameraEffect::Shake(TraumaShake::default())]);
+/// }
+///
+/// fn on_explosion(camera: &mut Camera) {
+///     camera.add_trauma(0.5);
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum CameraEffect {
+    /// See [`TraumaShake`] docs.
+    Shake(TraumaShake),
+    /// See [`Kick`] docs.
+    Kick(Kick),
+    /// See [`FovKick`] docs.
+    FovKick(FovKick),
+    /// See [`SmoothFollow`] docs.
+    Follow(SmoothFollow),
+}
+
+uuid_provider!(CameraEffect = "3f1d8c52-7b0e-4d3a-a6c9-52e7f0b4a91d");
+
+impl Default for CameraEffect {
+    fn default() -> Self {
+        Self::Shake(Default::default())
+    }
+}
+
+/// Combined result of camera effects.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CameraEffectsOutput {
+    /// New global transform of the camera, if any.
+    pub transform: Option<Matrix4<f32>>,
+    /// Translation offset in local coordinates of the camera.
+    pub position_offset: Vector3<f32>,
+    /// Rotation angles (in radians) around local X, Y, Z axes of the camera.
+    pub rotation_offset: Vector3<f32>,
+    /// Change of the field of view (in radians).
+    pub fov_offset: f32,
+}
+
+impl CameraEffectsOutput {
+    /// Applies the output to the given global transform of a camera.
+    pub fn apply(&self, global_transform: Matrix4<f32>) -> Matrix4<f32> {
+        let base = self.transform.unwrap_or(global_transform);
+        let rotation = UnitQuaternion::from_euler_angles(
+            self.rotation_offset.x,
+            self.rotation_offset.y,
+            self.rotation_offset.z,
+        );
+        base * Matrix4::new_translation(&self.position_offset) * rotation.to_homogeneous()
+    }
+}
+
+/// Evaluates the given set of effects and returns their combined output.
+pub fn evaluate_effects(
+    effects: &mut [CameraEffect],
+    dt: f32,
+    nodes: &NodePool,
+    physics: &PhysicsWorld,
+) -> CameraEffectsOutput {
+    let mut output = CameraEffectsOutput::default();
+    for effect in effects {
+        match effect {
+            CameraEffect::Shake(shake) => shake.update(dt, &mut output),
+            CameraEffect::Kick(kick) => kick.update(dt, &mut output),
+            CameraEffect::FovKick(fov_kick) => fov_kick.update(dt, &mut output),
+            CameraEffect::Follow(follow) => follow.update(dt, nodes, physics, &mut output),
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        scene::camera::effects::{CameraEffectsOutput, FovKick, Kick, TraumaShake},
+    };
+
+    #[test]
+    fn test_trauma_decay() {
+        let mut shake = TraumaShake::default();
+        shake.add_trauma(2.0);
+        assert_eq!(shake.trauma(), 1.0);
+
+        let mut output = CameraEffectsOutput::default();
+        for _ in 0..100 {
+            shake.update(0.1, &mut output);
+        }
+        assert_eq!(shake.trauma(), 0.0);
+    }
+
+    #[test]
+    fn test_kick_returns_to_rest() {
+        let mut kick = Kick::default();
+        kick.add_impulse(Vector3::new(0.0, 1.0, 0.0), Vector3::new(2.0, 0.0, 0.0));
+        let mut output = CameraEffectsOutput::default();
+        kick.update(0.05, &mut output);
+        assert!(output.position_offset.y > 0.0);
+        assert!(output.rotation_offset.x > 0.0);
+
+        for _ in 0..100 {
+            output = Default::default();
+            // Large time step must not break the simulation.
+            kick.update(0.25, &mut output);
+        }
+        assert!(output.position_offset.norm() < 1.0e-3);
+        assert!(output.rotation_offset.norm() < 1.0e-3);
+    }
+
+    #[test]
+    fn test_fov_kick_is_frame_rate_independent() {
+        let mut a = FovKick::default();
+        let mut b = FovKick::default();
+        a.add_kick(0.2);
+        b.add_kick(0.2);
+
+        let mut output = CameraEffectsOutput::default();
+        a.update(0.5, &mut output);
+        for _ in 0..50 {
+            b.update(0.01, &mut output);
+        }
+        assert!((a.offset - b.offset).abs() < 1.0e-4);
+    }
+}
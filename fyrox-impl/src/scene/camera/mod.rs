@@ -1,5 +1,8 @@
 //! Contains all methods and structures to create and manage cameras. See [`Camera`] docs for more info.
 
+pub mod effects;
+pub mod post_effect;
+
 use crate::resource::texture::{
     CompressionOptions, TextureImportOptions, TextureMinificationFilter,
 };
@@ -21,6 +24,11 @@ use crate::{
     },
     scene::{
         base::{Base, BaseBuilder},
+        camera::{
+            effects::{evaluate_effects, CameraEffect},
+            post_effect::PostEffect,
+        },
+        collider::BitMask,
         debug::SceneDrawingContext,
         graph::Graph,
         node::{Node, NodeTrait, UpdateContext},
@@ -272,6 +280,43 @@ pub enum Exposure {
 
     /// Specific exposure level. To "disable" any HDR effects use [`std::f32::consts::E`] as a value.
     Manual(f32),
+
+    /// Physically-based automatic exposure, that is metered using a histogram of log-luminance of
+    /// the frame. Unlike [`Exposure::Auto`], it ignores the darkest and the brightest parts of the
+    /// frame (defined by the percentiles), so small bright areas (skyboxes, light sources) do not
+    /// blow out the adaptation.
+    ///
+    /// # Equation
+    ///
+    /// `ev100 = clamp(log2(avg_luminance * 100 / 12.5), min_ev, max_ev) - compensation`
+    ///
+    /// `exposure = 1 / (1.2 * 2 ^ ev100)`
+    Histogram {
+        /// Minimal exposure value (EV100), it also defines the lower bound of the histogram.
+        /// Default is -4.0.
+        #[reflect(min_value = -16.0, max_value = 32.0, step = 0.5)]
+        min_ev: f32,
+        /// Maximal exposure value (EV100), it also defines the upper bound of the histogram.
+        /// Default is 16.0.
+        #[reflect(min_value = -16.0, max_value = 32.0, step = 0.5)]
+        max_ev: f32,
+        /// Fraction of the darkest pixels, that will be ignored by metering. Default is 0.5.
+        #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+        low_percentile: f32,
+        /// Fraction of the pixels (starting from the darkest), that will be used by metering.
+        /// Everything brighter will be ignored. Default is 0.95.
+        #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+        high_percentile: f32,
+        /// Adaptation speed (in 1/s), when the frame becomes brighter. Default is 3.0.
+        #[reflect(min_value = 0.0, step = 0.1)]
+        speed_up: f32,
+        /// Adaptation speed (in 1/s), when the frame becomes darker. Default is 1.0.
+        #[reflect(min_value = 0.0, step = 0.1)]
+        speed_down: f32,
+        /// Exposure compensation in EV. Positive values make the frame brighter. Default is 0.0.
+        #[reflect(step = 0.1)]
+        compensation: f32,
+    },
 }
 
 uuid_provider!(Exposure = "0e35ee3d-8baa-4b0c-b3dd-6c31a08c121e");
@@ -286,6 +331,96 @@ impl Default for Exposure {
     }
 }
 
+impl Exposure {
+    /// Creates histogram-based exposure with default parameters. See [`Exposure::Histogram`]
+    /// docs for more info.
+    pub fn histogram() -> Self {
+        Self::Histogram {
+            min_ev: -4.0,
+            max_ev: 16.0,
+            low_percentile: 0.5,
+            high_percentile: 0.95,
+            speed_up: 3.0,
+            speed_down: 1.0,
+            compensation: 0.0,
+        }
+    }
+}
+
+/// Bloom settings of a camera. Bloom makes bright parts of the frame "bleed" into their surroundings.
+/// Bright pixels are extracted from the frame using the threshold with a soft knee, then the result is
+/// progressively downsampled into a chain of mips and upsampled back with a tent filter, accumulating
+/// every mip on the way. Wide and stable glow is produced at a fraction of cost of a full-resolution
+/// blur.
+#[derive(Visit, Copy, Clone, PartialEq, Debug, Reflect)]
+pub struct BloomSettings {
+    /// Whether the bloom is enabled or not. Default is `true`.
+    pub enabled: bool,
+    /// Brightness of a pixel (in linear HDR units), starting from which it contributes to the bloom.
+    /// Default is 1.0.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub threshold: f32,
+    /// Relative width of a smooth transition around the threshold in `[0; 1]` range. Zero
+    /// gives a hard cut-off, which could cause flickering on small bright details. Default
+    /// is 0.5.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub soft_knee: f32,
+    /// A multiplier of the bloom, that is added to the frame. Default is 1.0.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub intensity: f32,
+    /// Amount of mips in the downsample chain. Every next mip has half size of the previous, so
+    /// more mips gives wider glow. Actual amount is limited by the size of the frame. Default is 6.
+    #[reflect(min_value = 1.0, max_value = 8.0, step = 1.0)]
+    pub mip_count: u32,
+    /// Radius (in texels) of the upsampling filter. Larger values make glow more smooth, but too
+    /// large values cause visible artifacts. Default is 1.0.
+    #[reflect(min_value = 0.0, max_value = 4.0, step = 0.1)]
+    pub radius: f32,
+}
+
+uuid_provider!(BloomSettings = "b7a3e2d4-6c1f-4f0a-9e58-3d2b7c4a1f96");
+
+impl Default for BloomSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 1.0,
+            soft_knee: 0.5,
+            intensity: 1.0,
+            mip_count: 6,
+            radius: 1.0,
+        }
+    }
+}
+
+/// Defines what is rendered behind the objects of a scene, that is seen by a camera. Background is
+/// applied only within the viewport of the camera, everything outside of it stays untouched.
+#[derive(
+    Visit, Copy, Clone, PartialEq, Debug, Default, Reflect, AsRefStr, EnumString, VariantNames,
+)]
+pub enum CameraBackground {
+    /// The frame is cleared with the clear color of the scene (see
+    /// [`crate::scene::SceneRenderingOptions::clear_color`]) and the skybox of the camera (if any) is
+    /// drawn on top of it.
+    #[default]
+    Skybox,
+    /// The frame is cleared with the given color, skybox is not drawn.
+    SolidColor(Color),
+    /// Vertical gradient from the bottom color to the top color, skybox is not drawn.
+    Gradient {
+        /// Color at the top of the viewport.
+        top: Color,
+        /// Color at the bottom of the viewport.
+        bottom: Color,
+    },
+    /// The frame is not cleared at all, so everything that was rendered by previous cameras stays
+    /// visible behind the objects. Useful for overlay cameras (for example, to render first-person
+    /// weapons or in-game HUD on top of the main view).
+    None,
+}
+
+uuid_provider!(CameraBackground = "9d4b2e71-3f6a-4c58-8e0d-b7a1c5f2e396");
+
 /// Camera allows you to see world from specific point in world. You must have at least one camera in
 /// your scene to see anything.
 ///
@@ -304,6 +439,18 @@ impl Default for Exposure {
 ///
 /// Fyrox supports multiple cameras per scene, it means that you can create split screen games, make
 /// picture-in-picture insertions in your main camera view and any other combinations you need.
+/// Cameras are rendered in ascending order of their render order (see [`Camera::set_render_order`]),
+/// cameras with the same order are rendered in the order of their appearance in the scene graph.
+///
+/// Cameras could be stacked on top of each other - for example, a first-person weapon could be
+/// rendered by a separate camera with its own field of view without a separate scene:
+///
+/// - Use [`CameraBackground::None`] to keep everything that was rendered by the previous cameras.
+/// - Use [`Camera::set_clear_depth`] to decide whether the objects of the camera should be drawn on
+/// top of everything (weapon view models) or should be occluded by the objects of the previous
+/// cameras.
+/// - Use render masks (see [`Camera::set_render_mask`] and [`Base::set_render_mask`]) to decide
+/// which objects are rendered by which camera.
 ///
 /// ## Performance
 ///
@@ -337,6 +484,38 @@ pub struct Camera {
     #[reflect(setter = "set_color_grading_enabled")]
     color_grading_enabled: InheritableVariable<bool>,
 
+    #[visit(optional)]
+    #[reflect(setter = "set_bloom_settings")]
+    bloom_settings: InheritableVariable<BloomSettings>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_effects")]
+    effects: InheritableVariable<Vec<CameraEffect>>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_background")]
+    background: InheritableVariable<CameraBackground>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_render_order")]
+    render_order: InheritableVariable<i32>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_render_mask")]
+    render_mask: InheritableVariable<BitMask>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_clear_depth")]
+    clear_depth: InheritableVariable<bool>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_post_effects")]
+    post_effects: InheritableVariable<Vec<PostEffect>>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    fov_offset: f32,
+
     #[visit(skip)]
     #[reflect(hidden)]
     view_matrix: Matrix4<f32>,
@@ -404,7 +583,17 @@ impl Camera {
         let up = self.base.up_vector();
 
         self.view_matrix = Matrix4::look_at_rh(&Point3::from(pos), &Point3::from(pos + look), &up);
-        self.projection_matrix = self.projection.matrix(frame_size);
+        self.projection_matrix = match self.projection.deref() {
+            Projection::Perspective(perspective) if self.fov_offset != 0.0 => {
+                PerspectiveProjection {
+                    fov: (perspective.fov + self.fov_offset)
+                        .clamp(1.0f32.to_radians(), 179.0f32.to_radians()),
+                    ..perspective.clone()
+                }
+                .matrix(frame_size)
+            }
+            projection => projection.matrix(frame_size),
+        };
     }
 
     /// Sets new viewport in resolution-independent format. In other words
@@ -470,6 +659,39 @@ impl Camera {
         self.view_matrix.try_inverse()
     }
 
+    /// Creates a virtual camera, that has the same settings as this camera, but uses the given view and
+    /// projection matrices. Such cameras are used to render the scene through portals.
+    pub(crate) fn make_virtual(
+        &self,
+        view_matrix: Matrix4<f32>,
+        projection_matrix: Matrix4<f32>,
+    ) -> Camera {
+        let base = BaseBuilder::new().build_base();
+        base.global_transform
+            .set(view_matrix.try_inverse().unwrap_or_else(Matrix4::identity));
+        Camera {
+            base,
+            projection: self.projection.clone(),
+            viewport: self.viewport.clone(),
+            enabled: self.enabled.clone(),
+            sky_box: self.sky_box.clone(),
+            environment: self.environment.clone(),
+            exposure: self.exposure.clone(),
+            color_grading_lut: self.color_grading_lut.clone(),
+            color_grading_enabled: self.color_grading_enabled.clone(),
+            bloom_settings: self.bloom_settings.clone(),
+            effects: Default::default(),
+            background: self.background.clone(),
+            render_order: self.render_order.clone(),
+            render_mask: self.render_mask.clone(),
+            clear_depth: self.clear_depth.clone(),
+            post_effects: self.post_effects.clone(),
+            fov_offset: 0.0,
+            view_matrix,
+            projection_matrix,
+        }
+    }
+
     /// Returns current projection mode.
     #[inline]
     pub fn projection(&self) -> &Projection {
@@ -680,6 +902,16 @@ impl Camera {
         *self.color_grading_enabled
     }
 
+    /// Sets new bloom settings. See [`BloomSettings`] docs for more info.
+    pub fn set_bloom_settings(&mut self, settings: BloomSettings) -> BloomSettings {
+        self.bloom_settings.set_value_and_mark_modified(settings)
+    }
+
+    /// Returns current bloom settings.
+    pub fn bloom_settings(&self) -> BloomSettings {
+        *self.bloom_settings
+    }
+
     /// Sets new exposure. See `Exposure` struct docs for more info.
     pub fn set_exposure(&mut self, exposure: Exposure) -> Exposure {
         self.exposure.set_value_and_mark_modified(exposure)
@@ -689,6 +921,137 @@ impl Camera {
     pub fn exposure(&self) -> Exposure {
         *self.exposure
     }
+
+    /// Sets new background of the camera. See [`CameraBackground`] docs for more info.
+    pub fn set_background(&mut self, background: CameraBackground) -> CameraBackground {
+        self.background.set_value_and_mark_modified(background)
+    }
+
+    /// Returns current background of the camera.
+    pub fn background(&self) -> CameraBackground {
+        *self.background
+    }
+
+    /// Sets new render order of the camera. Cameras with lower order are rendered first, so the
+    /// cameras with higher order are drawn on top of them.
+    pub fn set_render_order(&mut self, order: i32) -> i32 {
+        self.render_order.set_value_and_mark_modified(order)
+    }
+
+    /// Returns current render order of the camera.
+    pub fn render_order(&self) -> i32 {
+        *self.render_order
+    }
+
+    /// Sets new render mask of the camera. The camera renders only the nodes, whose render mask
+    /// (see [`Base::set_render_mask`]) has at least one common bit with this mask. By default,
+    /// a camera renders all layers.
+    pub fn set_render_mask(&mut self, mask: BitMask) -> BitMask {
+        self.render_mask.set_value_and_mark_modified(mask)
+    }
+
+    /// Returns current render mask of the camera.
+    pub fn render_mask(&self) -> BitMask {
+        *self.render_mask
+    }
+
+    /// Defines whether the depth buffer should be cleared before rendering the camera or not.
+    /// When the depth is kept, the objects of the camera are occluded by the objects, that were
+    /// rendered by the previous cameras (it makes sense only if the cameras have the same
+    /// projection planes). The first rendered camera of a scene always clears the depth. Default
+    /// is `true`.
+    pub fn set_clear_depth(&mut self, clear_depth: bool) -> bool {
+        self.clear_depth.set_value_and_mark_modified(clear_depth)
+    }
+
+    /// Returns `true` if the depth buffer is cleared before rendering the camera, `false` -
+    /// otherwise.
+    pub fn clear_depth(&self) -> bool {
+        *self.clear_depth
+    }
+
+    /// Sets new set of camera effects. See [`CameraEffect`] docs for more info.
+    pub fn set_effects(&mut self, effects: Vec<CameraEffect>) -> Vec<CameraEffect> {
+        self.effects.set_value_and_mark_modified(effects)
+    }
+
+    /// Returns current set of camera effects.
+    pub fn effects(&self) -> &[CameraEffect] {
+        &self.effects
+    }
+
+    /// Returns current set of camera effects. Any change made via the returned reference will mark the
+    /// effects as modified, use [`Self::add_trauma`], [`Self::kick`], [`Self::fov_kick`] to trigger
+    /// the effects.
+    pub fn effects_mut(&mut self) -> &mut Vec<CameraEffect> {
+        self.effects.get_value_mut_and_mark_modified()
+    }
+
+    /// Sets new stack of post-processing effects. The effects are applied in the order of the stack.
+    /// See [`PostEffect`] docs for more info.
+    pub fn set_post_effects(&mut self, effects: Vec<PostEffect>) -> Vec<PostEffect> {
+        self.post_effects.set_value_and_mark_modified(effects)
+    }
+
+    /// Returns current stack of post-processing effects.
+    pub fn post_effects(&self) -> &[PostEffect] {
+        &self.post_effects
+    }
+
+    /// Returns current stack of post-processing effects as mutable reference. It could be used to
+    /// reorder, enable or disable the effects.
+    pub fn post_effects_mut(&mut self) -> &mut Vec<PostEffect> {
+        self.post_effects.get_value_mut_and_mark_modified()
+    }
+
+    /// Adds the given amount of trauma to every shake effect of the camera. See
+    /// [`effects::TraumaShake`] docs for more info.
+    pub fn add_trauma(&mut self, amount: f32) {
+        for effect in self.effects.get_value_mut_silent() {
+            if let CameraEffect::Shake(shake) = effect {
+                shake.add_trauma(amount);
+            }
+        }
+    }
+
+    /// Adds the given impulses to every kick effect of the camera. See [`effects::Kick`] docs for
+    /// more info.
+    pub fn kick(&mut self, linear: Vector3<f32>, angular: Vector3<f32>) {
+        for effect in self.effects.get_value_mut_silent() {
+            if let CameraEffect::Kick(kick) = effect {
+                kick.add_impulse(linear, angular);
+            }
+        }
+    }
+
+    /// Changes field of view using every field of view kick effect of the camera. See
+    /// [`effects::FovKick`] docs for more info.
+    pub fn fov_kick(&mut self, amount: f32) {
+        for effect in self.effects.get_value_mut_silent() {
+            if let CameraEffect::FovKick(fov_kick) = effect {
+                fov_kick.add_kick(amount);
+            }
+        }
+    }
+
+    fn update_effects(&mut self, context: &UpdateContext) {
+        if self.effects.is_empty() {
+            self.fov_offset = 0.0;
+            return;
+        }
+
+        let output = evaluate_effects(
+            self.effects.get_value_mut_silent(),
+            context.dt,
+            context.nodes,
+            context.physics,
+        );
+
+        self.fov_offset = output.fov_offset;
+        self.base
+            .global_transform
+            .set(output.apply(self.base.global_transform()));
+    }
 }
 
 impl NodeTrait for Camera {
@@ -711,6 +1074,7 @@ impl NodeTrait for Camera {
     }
 
     fn update(&mut self, context: &mut UpdateContext) {
+        self.update_effects(context);
         self.calculate_matrices(context.frame_size);
     }
 
@@ -941,21 +1305,25 @@ fn load_texture(data: &[u8], id: &str) -> TextureResource {
 
 lazy_static! {
     static ref BUILT_IN_SKYBOX_FRONT: TextureResource = load_texture(
-        include_bytes!("skybox/front.png"),
+        include_bytes!("../skybox/front.png"),
         "__BUILT_IN_SKYBOX_FRONT",
     );
-    static ref BUILT_IN_SKYBOX_BACK: TextureResource =
-        load_texture(include_bytes!("skybox/back.png"), "__BUILT_IN_SKYBOX_BACK",);
+    static ref BUILT_IN_SKYBOX_BACK: TextureResource = load_texture(
+        include_bytes!("../skybox/back.png"),
+        "__BUILT_IN_SKYBOX_BACK",
+    );
     static ref BUILT_IN_SKYBOX_TOP: TextureResource =
-        load_texture(include_bytes!("skybox/top.png"), "__BUILT_IN_SKYBOX_TOP",);
+        load_texture(include_bytes!("../skybox/top.png"), "__BUILT_IN_SKYBOX_TOP",);
     static ref BUILT_IN_SKYBOX_BOTTOM: TextureResource = load_texture(
-        include_bytes!("skybox/bottom.png"),
+        include_bytes!("../skybox/bottom.png"),
         "__BUILT_IN_SKYBOX_BOTTOM",
     );
-    static ref BUILT_IN_SKYBOX_LEFT: TextureResource =
-        load_texture(include_bytes!("skybox/left.png"), "__BUILT_IN_SKYBOX_LEFT",);
+    static ref BUILT_IN_SKYBOX_LEFT: TextureResource = load_texture(
+        include_bytes!("../skybox/left.png"),
+        "__BUILT_IN_SKYBOX_LEFT",
+    );
     static ref BUILT_IN_SKYBOX_RIGHT: TextureResource = load_texture(
-        include_bytes!("skybox/right.png"),
+        include_bytes!("../skybox/right.png"),
         "__BUILT_IN_SKYBOX_RIGHT",
     );
     static ref BUILT_IN_SKYBOX: SkyBox = SkyBoxKind::make_built_in_skybox();
@@ -1015,7 +1383,14 @@ pub struct CameraBuilder {
     exposure: Exposure,
     color_grading_lut: Option<ColorGradingLut>,
     color_grading_enabled: bool,
+    bloom_settings: BloomSettings,
     projection: Projection,
+    effects: Vec<CameraEffect>,
+    background: CameraBackground,
+    render_order: i32,
+    render_mask: BitMask,
+    clear_depth: bool,
+    post_effects: Vec<PostEffect>,
 }
 
 impl CameraBuilder {
@@ -1033,7 +1408,14 @@ impl CameraBuilder {
             exposure: Exposure::Manual(std::f32::consts::E),
             color_grading_lut: None,
             color_grading_enabled: false,
+            bloom_settings: Default::default(),
             projection: Projection::default(),
+            effects: Default::default(),
+            background: Default::default(),
+            render_order: 0,
+            render_mask: BitMask(u32::MAX),
+            clear_depth: true,
+            post_effects: Default::default(),
         }
     }
 
@@ -1097,6 +1479,12 @@ impl CameraBuilder {
         self
     }
 
+    /// Sets desired bloom settings.
+    pub fn with_bloom_settings(mut self, settings: BloomSettings) -> Self {
+        self.bloom_settings = settings;
+        self
+    }
+
     /// Sets desired exposure options.
     pub fn with_exposure(mut self, exposure: Exposure) -> Self {
         self.exposure = exposure;
@@ -1109,6 +1497,43 @@ impl CameraBuilder {
         self
     }
 
+    /// Sets desired set of camera effects. See [`CameraEffect`] docs for more info.
+    pub fn with_effects(mut self, effects: Vec<CameraEffect>) -> Self {
+        self.effects = effects;
+        self
+    }
+
+    /// Sets desired background of the camera. See [`CameraBackground`] docs for more info.
+    pub fn with_background(mut self, background: CameraBackground) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Sets desired render order of the camera. See [`Camera::set_render_order`] for more info.
+    pub fn with_render_order(mut self, order: i32) -> Self {
+        self.render_order = order;
+        self
+    }
+
+    /// Sets desired render mask of the camera. See [`Camera::set_render_mask`] for more info.
+    pub fn with_render_mask(mut self, mask: BitMask) -> Self {
+        self.render_mask = mask;
+        self
+    }
+
+    /// Defines whether the camera should clear the depth buffer or not. See
+    /// [`Camera::set_clear_depth`] for more info.
+    pub fn with_clear_depth(mut self, clear_depth: bool) -> Self {
+        self.clear_depth = clear_depth;
+        self
+    }
+
+    /// Sets desired stack of post-processing effects. See [`PostEffect`] docs for more info.
+    pub fn with_post_effects(mut self, effects: Vec<PostEffect>) -> Self {
+        self.post_effects = effects;
+        self
+    }
+
     /// Creates new instance of camera.
     pub fn build_camera(self) -> Camera {
         Camera {
@@ -1129,6 +1554,14 @@ impl CameraBuilder {
             exposure: self.exposure.into(),
             color_grading_lut: self.color_grading_lut.into(),
             color_grading_enabled: self.color_grading_enabled.into(),
+            bloom_settings: self.bloom_settings.into(),
+            effects: self.effects.into(),
+            background: self.background.into(),
+            render_order: self.render_order.into(),
+            render_mask: self.render_mask.into(),
+            clear_depth: self.clear_depth.into(),
+            post_effects: self.post_effects.into(),
+            fov_offset: 0.0,
         }
     }
 
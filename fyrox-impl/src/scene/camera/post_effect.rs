@@ -0,0 +1,105 @@
+//! Custom post-processing effects of a camera. See [`PostEffect`] docs for more info.
+
+use crate::{
+    asset::untyped::ResourceKind,
+    core::{reflect::prelude::*, uuid_provider, visitor::prelude::*},
+    material::{Material, MaterialResource},
+};
+
+/// Post effect is a user-defined full-screen pass, that is applied to the final (tone mapped and
+/// anti-aliased) frame of a camera. Every camera has its own stack of post effects (see
+/// [`super::Camera::set_post_effects`]), the effects are applied one after another in the order
+/// of the stack: the output of an effect is the input of the next one. It allows you to add custom
+/// vignette, distortion, underwater and similar effects without modifying the renderer.
+///
+/// An effect is defined by a material, its shader must have a render pass called `PostEffect`,
+/// materials without such pass are ignored. The pass is used to draw a full-screen quad, the
+/// following built-in uniforms are available in addition to the properties of the material:
+///
+/// - `fyrox_inputColor` - `sampler2D` with the output of the previous stage.
+/// - `fyrox_inputSize` - `vec2` with the size of the input in pixels.
+/// - `fyrox_sceneDepth` - `sampler2D` with the depth buffer of the scene.
+/// - `fyrox_worldViewProjection` - `mat4` that projects the quad to the viewport of the camera.
+///
+/// ## Example
+///
+/// A simple vignette effect could be defined like so:
+///
+/// ```text
+/// (
+///     name: "Vignette",
+///     properties: [
+///         (
+///             name: "strength",
+///             kind: Float(0.5),
+///         ),
+///     ],
+///     passes: [
+///         (
+///             name: "PostEffect",
+///             draw_parameters: DrawParameters(
+///                 cull_face: None,
+///                 color_write: ColorMask(red: true, green: true, blue: true, alpha: true),
+///                 depth_write: false,
+///                 stencil_test: None,
+///                 depth_test: false,
+///                 blend: None,
+///                 stencil_op: StencilOp(fail: Keep, zfail: Keep, zpass: Keep, write_mask: 0xFFFF_FFFF),
+///             ),
+///             vertex_shader: r#"
+///                 layout(location = 0) in vec3 vertexPosition;
+///                 layout(location = 1) in vec2 vertexTexCoord;
+///                 uniform mat4 fyrox_worldViewProjection;
+///                 out vec2 texCoord;
+///                 void main() {
+///                     texCoord = vertexTexCoord;
+///                     gl_Position = fyrox_worldViewProjection * vec4(vertexPosition, 1.0);
+///                 }
+///             "#,
+///             fragment_shader: r#"
+///                 uniform sampler2D fyrox_inputColor;
+///                 uniform float strength;
+///                 in vec2 texCoord;
+///                 out vec4 FragColor;
+///                 void main() {
+///                     vec4 color = texture(fyrox_inputColor, texCoord);
+///                     float vignette = 1.0 - strength * length(texCoord - vec2(0.5));
+///                     FragColor = vec4(color.rgb * vignette, color.a);
+///                 }
+///             "#,
+///         )
+///     ],
+/// )
+/// ```
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct PostEffect {
+    /// Name of the effect. It is used only for identification purposes.
+    pub name: String,
+    /// Disabled effects are skipped when rendering.
+    pub enabled: bool,
+    /// Material of the effect, see [`PostEffect`] docs for the requirements for its shader.
+    pub material: MaterialResource,
+}
+
+uuid_provider!(PostEffect = "5b3c6a4e-0f8d-4d6b-9a0c-6f7d0e2b9c31");
+
+impl Default for PostEffect {
+    fn default() -> Self {
+        Self {
+            name: "PostEffect".to_string(),
+            enabled: true,
+            material: MaterialResource::new_ok(ResourceKind::Embedded, Material::default()),
+        }
+    }
+}
+
+impl PostEffect {
+    /// Creates a new enabled post effect with the given name and material.
+    pub fn new(name: impl Into<String>, material: MaterialResource) -> Self {
+        Self {
+            name: name.into(),
+            enabled: true,
+            material,
+        }
+    }
+}
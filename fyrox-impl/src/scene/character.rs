@@ -0,0 +1,296 @@
+//! Modular characters assembled from multiple models. See [`CharacterAssembly`] docs for more info.
+
+use crate::{
+    core::pool::Handle,
+    graph::{BaseSceneGraph, SceneGraph},
+    resource::model::{ModelResource, ModelResourceExtension},
+    scene::{graph::Graph, mesh::Mesh, node::Node, Scene},
+};
+use fxhash::FxHashSet;
+
+/// A named set of meshes of a character. Parts of a character could hide visibility sets, for
+/// example an armor could hide the torso of the body under it to prevent the body from poking
+/// through the armor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VisibilitySet {
+    /// Name of the set.
+    pub name: String,
+    /// Names of the meshes of the set.
+    pub meshes: Vec<String>,
+}
+
+/// A part of a character, attached using [`CharacterAssembly::attach`].
+#[derive(Debug, Clone)]
+pub struct CharacterPart {
+    resource: ModelResource,
+    root: Handle<Node>,
+    hidden_sets: Vec<String>,
+}
+
+impl CharacterPart {
+    /// Returns the model resource of the part.
+    pub fn resource(&self) -> &ModelResource {
+        &self.resource
+    }
+
+    /// Returns a handle of the root node of the instance of the part.
+    pub fn root(&self) -> Handle<Node> {
+        self.root
+    }
+
+    /// Returns names of the visibility sets, that are hidden by the part.
+    pub fn hidden_sets(&self) -> &[String] {
+        &self.hidden_sets
+    }
+}
+
+/// Character assembly allows you to build a character from multiple models (body, armor pieces,
+/// hair, etc.) that are skinned to the same skeleton. Every attached part is bound to the bones of
+/// a single skeleton instance, so all the parts follow the animations of the skeleton.
+///
+/// Parts could be imported with [`crate::resource::model::ModelImportOptions::share_skeleton`]
+/// option, in this case they don't have bones at all. Parts without shared skeleton are bound to
+/// the bones of the skeleton with the same names, their own bones are removed.
+///
+/// # Visibility sets
+///
+/// Parts could hide meshes of other parts (or meshes of the skeleton instance itself) using
+/// [`VisibilitySet`]s. A mesh is hidden if it belongs to at least one visibility set, that is
+/// hidden by at least one attached part. Visibility of meshes, that do not belong to any set, is
+/// never changed.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     resource::model::ModelResource,
+/// #     scene::{character::CharacterAssembly, node::Node, Scene},
+/// # };
+/// fn equip_armor(
+///     character: &mut CharacterAssembly,
+///     armor: &ModelResource,
+///     scene: &mut Scene,
+/// ) -> Handle<Node> {
+///     character.define_visibility_set(
+///         &mut scene.graph,
+///         "Torso",
+///         vec!["Body_Torso".to_string(), "Body_Arms".to_string()],
+///     );
+///     let part = character.attach(scene, armor);
+///     character.set_hidden_sets(&mut scene.graph, part, vec!["Torso".to_string()]);
+///     part
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CharacterAssembly {
+    skeleton_root: Handle<Node>,
+    parts: Vec<CharacterPart>,
+    visibility_sets: Vec<VisibilitySet>,
+}
+
+impl CharacterAssembly {
+    /// Creates a new assembly for the given skeleton instance.
+    pub fn new(skeleton_root: Handle<Node>) -> Self {
+        Self {
+            skeleton_root,
+            parts: Default::default(),
+            visibility_sets: Default::default(),
+        }
+    }
+
+    /// Returns a handle of the root node of the skeleton instance.
+    pub fn skeleton_root(&self) -> Handle<Node> {
+        self.skeleton_root
+    }
+
+    /// Returns a list of attached parts.
+    pub fn parts(&self) -> &[CharacterPart] {
+        &self.parts
+    }
+
+    /// Returns a list of defined visibility sets.
+    pub fn visibility_sets(&self) -> &[VisibilitySet] {
+        &self.visibility_sets
+    }
+
+    /// Instantiates the given model, binds its skinned meshes to the bones of the skeleton and
+    /// attaches the instance to the skeleton root. Returns a handle of the root node of the
+    /// instance, that could be used later to detach the part.
+    pub fn attach(&mut self, scene: &mut Scene, resource: &ModelResource) -> Handle<Node> {
+        let root = resource.instantiate_with_skeleton(scene, self.skeleton_root);
+
+        self.parts.push(CharacterPart {
+            resource: resource.clone(),
+            root,
+            hidden_sets: Default::default(),
+        });
+
+        self.update_visibility(&mut scene.graph);
+
+        root
+    }
+
+    /// Removes the given part from the graph. Meshes, that were hidden by the part, become visible
+    /// again. Returns `false` if the part does not belong to the assembly.
+    pub fn detach(&mut self, graph: &mut Graph, part: Handle<Node>) -> bool {
+        let Some(position) = self.parts.iter().position(|p| p.root == part) else {
+            return false;
+        };
+
+        self.parts.remove(position);
+
+        if graph.is_valid_handle(part) {
+            graph.remove_node(part);
+        }
+
+        self.update_visibility(graph);
+
+        true
+    }
+
+    /// Defines a new visibility set or replaces the meshes of an existing one.
+    pub fn define_visibility_set(
+        &mut self,
+        graph: &mut Graph,
+        name: impl Into<String>,
+        meshes: Vec<String>,
+    ) {
+        let name = name.into();
+        match self.visibility_sets.iter_mut().find(|s| s.name == name) {
+            Some(set) => set.meshes = meshes,
+            None => self.visibility_sets.push(VisibilitySet { name, meshes }),
+        }
+        self.update_visibility(graph);
+    }
+
+    /// Sets the visibility sets, that are hidden by the given part. Returns `false` if the part
+    /// does not belong to the assembly.
+    pub fn set_hidden_sets(
+        &mut self,
+        graph: &mut Graph,
+        part: Handle<Node>,
+        hidden_sets: Vec<String>,
+    ) -> bool {
+        let Some(part) = self.parts.iter_mut().find(|p| p.root == part) else {
+            return false;
+        };
+
+        part.hidden_sets = hidden_sets;
+
+        self.update_visibility(graph);
+
+        true
+    }
+
+    /// Updates visibility of the meshes of the character. It is called automatically when the
+    /// assembly changes, but it should be called manually if meshes were added to the character by
+    /// other means.
+    pub fn update_visibility(&self, graph: &mut Graph) {
+        if !graph.is_valid_handle(self.skeleton_root) {
+            return;
+        }
+
+        let hidden_sets = self
+            .parts
+            .iter()
+            .flat_map(|p| p.hidden_sets.iter().map(|s| s.as_str()))
+            .collect::<FxHashSet<_>>();
+
+        let mut managed = FxHashSet::default();
+        let mut hidden = FxHashSet::default();
+        for set in self.visibility_sets.iter() {
+            managed.extend(set.meshes.iter().map(|m| m.as_str()));
+            if hidden_sets.contains(set.name.as_str()) {
+                hidden.extend(set.meshes.iter().map(|m| m.as_str()));
+            }
+        }
+
+        let meshes = graph
+            .traverse_handle_iter(self.skeleton_root)
+            .filter(|&h| {
+                let node = &graph[h];
+                node.cast::<Mesh>().is_some() && managed.contains(node.name())
+            })
+            .collect::<Vec<_>>();
+
+        for mesh in meshes {
+            let node = &mut graph[mesh];
+            let visibility = !hidden.contains(node.name());
+            node.set_visibility(visibility);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::untyped::ResourceKind,
+        core::{algebra::Matrix4, pool::Handle},
+        graph::{BaseSceneGraph, SceneGraph},
+        scene::{
+            base::BaseBuilder,
+            character::CharacterAssembly,
+            graph::Graph,
+            mesh::{
+                surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+                Mesh, MeshBuilder,
+            },
+            node::Node,
+            pivot::PivotBuilder,
+            Scene,
+        },
+    };
+
+    fn make_skinned_model(graph: &mut Graph, mesh_name: &str) -> (Handle<Node>, Handle<Node>) {
+        let hand = PivotBuilder::new(BaseBuilder::new().with_name("Hand")).build(graph);
+        let arm = PivotBuilder::new(BaseBuilder::new().with_name("Arm").with_children(&[hand]))
+            .build(graph);
+        let mesh = MeshBuilder::new(BaseBuilder::new().with_name(mesh_name))
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+                ResourceKind::Embedded,
+                SurfaceData::make_cube(Matrix4::identity()),
+            ))
+            .with_bones(vec![arm, hand])
+            .build()])
+            .build(graph);
+        let root = PivotBuilder::new(BaseBuilder::new().with_children(&[arm, mesh])).build(graph);
+        (root, mesh)
+    }
+
+    fn bones(graph: &Graph, mesh: Handle<Node>) -> Vec<Handle<Node>> {
+        graph[mesh].cast::<Mesh>().unwrap().surfaces()[0]
+            .bones()
+            .to_vec()
+    }
+
+    #[test]
+    fn test_character_assembly() {
+        let mut scene = Scene::new();
+        let (skeleton_root, body) = make_skinned_model(&mut scene.graph, "Body");
+        let skeleton_bones = bones(&scene.graph, body);
+
+        let mut prefab_graph = Graph::new();
+        let (armor_root, _) = make_skinned_model(&mut prefab_graph, "Armor");
+        let armor = prefab_graph.extract_as_prefab(armor_root);
+
+        let mut character = CharacterAssembly::new(skeleton_root);
+        character.define_visibility_set(&mut scene.graph, "Torso", vec!["Body".to_string()]);
+
+        let part = character.attach(&mut scene, &armor);
+        assert_eq!(scene.graph[part].parent(), skeleton_root);
+        let (armor_mesh, _) = scene.graph.find_by_name(part, "Armor").unwrap();
+        assert_eq!(bones(&scene.graph, armor_mesh), skeleton_bones);
+        // Bones of the armor must be removed.
+        assert!(scene.graph.find_by_name(part, "Arm").is_none());
+        assert!(scene.graph[body].visibility());
+
+        assert!(character.set_hidden_sets(&mut scene.graph, part, vec!["Torso".to_string()]));
+        assert!(!scene.graph[body].visibility());
+
+        assert!(character.detach(&mut scene.graph, part));
+        assert!(!character.detach(&mut scene.graph, part));
+        assert!(!scene.graph.is_valid_handle(armor_mesh));
+        assert!(scene.graph[body].visibility());
+    }
+}
@@ -189,6 +189,23 @@ pub struct TrimeshShape {
 pub struct HeightfieldShape {
     /// A handle to terrain scene node.
     pub geometry_source: GeometrySource,
+    /// Level of detail of the shape. Each level halves the resolution of the height map of the
+    /// terrain, which significantly reduces memory usage and time that is needed to build the
+    /// shape. Zero means full resolution.
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, max_value = 8.0)]
+    pub lod: u32,
+    /// Whether to build a separate height field for each chunk of the terrain or a single one
+    /// for the entire terrain. Separate height fields follow the chunks exactly, without
+    /// distortions on chunk borders.
+    #[visit(optional)]
+    pub per_chunk: bool,
+    /// Whether to build the shape on a background thread or not. A coarse approximation of the
+    /// terrain is used until the first shape is built, then it is swapped in. When the terrain is
+    /// modified later, the previous shape is used until the new one is built. See
+    /// [`crate::scene::graph::physics::PhysicsWorld::is_cooking_heightfield`].
+    #[visit(optional)]
+    pub async_cooking: bool,
 }
 
 /// Arbitrary convex polyhedron shape.
@@ -519,7 +536,10 @@ impl ColliderShape {
 
     /// Initializes a heightfield shape defined by a handle to terrain node.
     pub fn heightfield(geometry_source: GeometrySource) -> Self {
-        Self::Heightfield(HeightfieldShape { geometry_source })
+        Self::Heightfield(HeightfieldShape {
+            geometry_source,
+            ..Default::default()
+        })
     }
 }
 
@@ -559,6 +579,12 @@ pub struct Collider {
     #[visit(skip)]
     #[reflect(hidden)]
     pub(crate) native: Cell<ColliderHandle>,
+
+    /// Version of the geometry source (height map version of a terrain), that was used to build
+    /// the native shape.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub(crate) geometry_source_version: Cell<u64>,
 }
 
 impl Default for Collider {
@@ -575,6 +601,7 @@ impl Default for Collider {
             friction_combine_rule: Default::default(),
             restitution_combine_rule: Default::default(),
             native: Cell::new(ColliderHandle::invalid()),
+            geometry_source_version: Cell::new(0),
         }
     }
 }
@@ -608,6 +635,7 @@ impl Clone for Collider {
             restitution_combine_rule: self.restitution_combine_rule.clone(),
             // Do not copy. The copy will have its own native representation (for example - Rapier's collider)
             native: Cell::new(ColliderHandle::invalid()),
+            geometry_source_version: Cell::new(0),
         }
     }
 }
@@ -984,6 +1012,7 @@ impl ColliderBuilder {
             friction_combine_rule: self.friction_combine_rule.into(),
             restitution_combine_rule: self.restitution_combine_rule.into(),
             native: Cell::new(ColliderHandle::invalid()),
+            geometry_source_version: Cell::new(0),
         }
     }
 
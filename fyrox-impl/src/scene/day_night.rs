@@ -0,0 +1,389 @@
+//! Time of day of a scene - sun and moon movement, lighting and sky color. See [`DayNightCycle`]
+//! docs for more info.
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        color::Color,
+        color_gradient::{ColorGradient, ColorGradientBuilder, GradientPoint},
+        math::curve::{Curve, CurveKey, CurveKeyKind},
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    scene::{graph::Graph, light::BaseLight, node::Node, SceneRenderingOptions},
+};
+use std::collections::VecDeque;
+
+const HOURS_IN_DAY: f32 = 24.0;
+
+/// An event, that is emitted by [`DayNightCycle`] when the time of day passes a notable point.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DayNightEvent {
+    /// The sun has risen above the horizon.
+    Sunrise,
+    /// The sun has set below the horizon.
+    Sunset,
+    /// Lighting has changed significantly since the last refresh, so baked environment data
+    /// (reflection probes, environment maps, etc.) should be refreshed. See
+    /// [`DayNightCycle::environment_refresh_interval`].
+    RefreshEnvironment,
+}
+
+fn gradient(points: &[(f32, Color)]) -> ColorGradient {
+    points
+        .iter()
+        .fold(ColorGradientBuilder::new(), |builder, (location, color)| {
+            builder.with_point(GradientPoint::new(*location, *color))
+        })
+        .build()
+}
+
+fn curve(keys: &[(f32, f32)]) -> Curve {
+    Curve::from(
+        keys.iter()
+            .map(|(location, value)| CurveKey::new(*location, *value, CurveKeyKind::Linear))
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Day/night cycle drives sun and moon directional lights, ambient lighting and sky color of a
+/// scene from a single parameter - time of day.
+///
+/// Every color gradient and intensity curve is sampled using a fraction of a day in `[0; 1]`
+/// range, where `0.0` is midnight, `0.25` is sunrise (06:00), `0.5` is noon and `0.75` is
+/// sunset (18:00).
+///
+/// Sun and moon could be any light sources, but directional lights are the most suitable. Their
+/// rotation is overwritten every frame, so they should not be rotated by anything else and their
+/// parents should not be rotated either.
+///
+/// The cycle is applied to the scene even if the scene is paused or edited in the editor, only
+/// the time does not flow in this case. This allows you to preview any time of day by changing
+/// [`Self::time_of_day`].
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     scene::{node::Node, Scene},
+/// # };
+/// fn setup_day_night(scene: &mut Scene, sun: Handle<Node>, moon: Handle<Node>) {
+///     let cycle = &mut scene.day_night;
+///     cycle.enabled = true;
+///     cycle.sun = sun;
+///     cycle.moon = moon;
+///     // Full day in 20 minutes.
+///     cycle.day_length = 20.0 * 60.0;
+///     cycle.set_time_of_day(7.5);
+/// }
+/// ```
+#[derive(Clone, Debug, Visit, Reflect)]
+pub struct DayNightCycle {
+    /// Whether the cycle is enabled or not. Disabled cycle does not modify the scene.
+    pub enabled: bool,
+    /// Current time of day in hours, `[0; 24)` range.
+    #[reflect(min_value = 0.0, max_value = 24.0, step = 0.1)]
+    pub time_of_day: f32,
+    /// Length of a full day in seconds of real time. Zero means that the time is frozen.
+    #[reflect(min_value = 0.0)]
+    pub day_length: f32,
+    /// A light source, that is used as the sun.
+    pub sun: Handle<Node>,
+    /// A light source, that is used as the moon. It is always on the opposite side of the sky.
+    pub moon: Handle<Node>,
+    /// Tilt of the path of the sun across the sky (in degrees). Zero means that the sun passes
+    /// exactly overhead at noon.
+    #[reflect(min_value = -90.0, max_value = 90.0)]
+    pub tilt: f32,
+    /// Rotation of the path of the sun around the vertical axis (in degrees). Zero means that the
+    /// sun rises at +X direction.
+    pub azimuth: f32,
+    /// Color of the sun during a day.
+    pub sun_color: ColorGradient,
+    /// Intensity of the sun during a day.
+    pub sun_intensity: Curve,
+    /// Color of the moon during a day.
+    pub moon_color: ColorGradient,
+    /// Intensity of the moon during a day.
+    pub moon_intensity: Curve,
+    /// Ambient lighting color of the scene during a day. See
+    /// [`SceneRenderingOptions::ambient_lighting_color`].
+    pub ambient_color: ColorGradient,
+    /// Whether the cycle should drive the sky color ([`SceneRenderingOptions::clear_color`]) or
+    /// not.
+    pub drive_sky_color: bool,
+    /// Sky color during a day.
+    pub sky_color: ColorGradient,
+    /// Amount of hours of in-game time, after which [`DayNightEvent::RefreshEnvironment`] is
+    /// emitted. It should be used to schedule refresh of baked environment data. Zero disables
+    /// the event.
+    #[reflect(min_value = 0.0)]
+    pub environment_refresh_interval: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    last_time_of_day: Option<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    hours_since_refresh: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    events: VecDeque<DayNightEvent>,
+}
+
+impl Default for DayNightCycle {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            time_of_day: 12.0,
+            day_length: 0.0,
+            sun: Default::default(),
+            moon: Default::default(),
+            tilt: 30.0,
+            azimuth: 0.0,
+            sun_color: gradient(&[
+                (0.0, Color::opaque(255, 120, 60)),
+                (0.25, Color::opaque(255, 140, 70)),
+                (0.35, Color::opaque(255, 240, 220)),
+                (0.65, Color::opaque(255, 240, 220)),
+                (0.75, Color::opaque(255, 130, 60)),
+                (1.0, Color::opaque(255, 120, 60)),
+            ]),
+            sun_intensity: curve(&[
+                (0.0, 0.0),
+                (0.24, 0.0),
+                (0.35, 1.0),
+                (0.65, 1.0),
+                (0.76, 0.0),
+                (1.0, 0.0),
+            ]),
+            moon_color: gradient(&[
+                (0.0, Color::opaque(140, 160, 220)),
+                (1.0, Color::opaque(140, 160, 220)),
+            ]),
+            moon_intensity: curve(&[
+                (0.0, 0.15),
+                (0.22, 0.15),
+                (0.27, 0.0),
+                (0.73, 0.0),
+                (0.78, 0.15),
+                (1.0, 0.15),
+            ]),
+            ambient_color: gradient(&[
+                (0.0, Color::opaque(15, 20, 35)),
+                (0.25, Color::opaque(60, 55, 60)),
+                (0.35, Color::opaque(100, 100, 100)),
+                (0.65, Color::opaque(100, 100, 100)),
+                (0.75, Color::opaque(60, 50, 50)),
+                (1.0, Color::opaque(15, 20, 35)),
+            ]),
+            drive_sky_color: true,
+            sky_color: gradient(&[
+                (0.0, Color::opaque(5, 8, 20)),
+                (0.22, Color::opaque(20, 25, 50)),
+                (0.27, Color::opaque(240, 150, 100)),
+                (0.35, Color::opaque(120, 170, 230)),
+                (0.65, Color::opaque(120, 170, 230)),
+                (0.73, Color::opaque(240, 130, 80)),
+                (0.78, Color::opaque(20, 25, 50)),
+                (1.0, Color::opaque(5, 8, 20)),
+            ]),
+            environment_refresh_interval: 1.0,
+            last_time_of_day: None,
+            hours_since_refresh: 0.0,
+            events: Default::default(),
+        }
+    }
+}
+
+impl DayNightCycle {
+    /// Sets new time of day in hours. The value will be wrapped in `[0; 24)` range.
+    pub fn set_time_of_day(&mut self, hours: f32) {
+        self.time_of_day = hours.rem_euclid(HOURS_IN_DAY);
+    }
+
+    /// Returns fraction of a day in `[0; 1)` range, that is used to sample curves and gradients.
+    pub fn day_fraction(&self) -> f32 {
+        self.time_of_day.rem_euclid(HOURS_IN_DAY) / HOURS_IN_DAY
+    }
+
+    fn orbit_rotation(&self) -> UnitQuaternion<f32> {
+        UnitQuaternion::from_axis_angle(&Vector3::y_axis(), self.azimuth.to_radians())
+            * UnitQuaternion::from_axis_angle(&Vector3::x_axis(), self.tilt.to_radians())
+    }
+
+    fn sun_direction_at(&self, fraction: f32) -> Vector3<f32> {
+        let angle = (fraction - 0.25) * std::f32::consts::TAU;
+        self.orbit_rotation() * Vector3::new(angle.cos(), angle.sin(), 0.0)
+    }
+
+    /// Returns a normalized direction from the scene to the sun.
+    pub fn sun_direction(&self) -> Vector3<f32> {
+        self.sun_direction_at(self.day_fraction())
+    }
+
+    /// Returns `true` if the sun is above the horizon.
+    pub fn is_day(&self) -> bool {
+        self.sun_direction().y > 0.0
+    }
+
+    /// Takes the oldest event from the internal queue.
+    pub fn pop_event(&mut self) -> Option<DayNightEvent> {
+        self.events.pop_front()
+    }
+
+    /// Advances the time of day and applies the cycle to the scene.
+    pub fn update(
+        &mut self,
+        dt: f32,
+        graph: &mut Graph,
+        rendering_options: &mut SceneRenderingOptions,
+    ) {
+        if !self.enabled {
+            return;
+        }
+
+        if self.day_length > 0.0 {
+            self.set_time_of_day(self.time_of_day + dt * HOURS_IN_DAY / self.day_length);
+        }
+
+        self.apply(graph, rendering_options);
+    }
+
+    /// Applies the current time of day to the lights and rendering options of the scene, without
+    /// advancing the time.
+    pub fn apply(&mut self, graph: &mut Graph, rendering_options: &mut SceneRenderingOptions) {
+        if !self.enabled {
+            return;
+        }
+
+        let fraction = self.day_fraction();
+        let sun_direction = self.sun_direction_at(fraction);
+
+        if let Some(last) = self.last_time_of_day {
+            // Shortest path around the clock, so jumps over midnight are handled correctly.
+            let passed = (self.time_of_day - last + HOURS_IN_DAY * 1.5).rem_euclid(HOURS_IN_DAY)
+                - HOURS_IN_DAY * 0.5;
+            self.hours_since_refresh += passed.abs();
+
+            let was_day = self.sun_direction_at(last / HOURS_IN_DAY).y > 0.0;
+            let is_day = sun_direction.y > 0.0;
+            if !was_day && is_day {
+                self.events.push_back(DayNightEvent::Sunrise);
+            } else if was_day && !is_day {
+                self.events.push_back(DayNightEvent::Sunset);
+            }
+        } else {
+            // Always refresh the environment on the first frame.
+            self.hours_since_refresh = f32::MAX;
+        }
+        self.last_time_of_day = Some(self.time_of_day);
+
+        if self.environment_refresh_interval > 0.0
+            && self.hours_since_refresh >= self.environment_refresh_interval
+        {
+            self.hours_since_refresh = 0.0;
+            self.events.push_back(DayNightEvent::RefreshEnvironment);
+        }
+
+        let orbit_axis = self.orbit_rotation() * Vector3::z();
+        for (handle, direction, color, intensity) in [
+            (
+                self.sun,
+                sun_direction,
+                &self.sun_color,
+                &self.sun_intensity,
+            ),
+            (
+                self.moon,
+                -sun_direction,
+                &self.moon_color,
+                &self.moon_intensity,
+            ),
+        ] {
+            let Some(node) = graph.try_get_mut(handle) else {
+                continue;
+            };
+
+            // Light shines along its look vector, so it must look away from the sky.
+            node.local_transform_mut()
+                .set_rotation(UnitQuaternion::face_towards(&-direction, &orbit_axis));
+
+            let intensity = intensity.value_at(fraction).max(0.0);
+            if let Some(light) = node.query_component_mut::<BaseLight>() {
+                light.set_color(color.get_color(fraction));
+                light.set_intensity(intensity);
+            }
+            node.set_visibility(intensity > 0.0);
+        }
+
+        rendering_options.ambient_lighting_color = self.ambient_color.get_color(fraction);
+        if self.drive_sky_color {
+            rendering_options.clear_color = Some(self.sky_color.get_color(fraction));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        scene::{
+            base::BaseBuilder,
+            day_night::{DayNightCycle, DayNightEvent},
+            graph::Graph,
+            light::{directional::DirectionalLightBuilder, BaseLight, BaseLightBuilder},
+            node::Node,
+            SceneRenderingOptions,
+        },
+    };
+
+    #[test]
+    fn test_day_night_cycle() {
+        let mut graph = Graph::new();
+        let sun = DirectionalLightBuilder::new(BaseLightBuilder::new(BaseBuilder::new()))
+            .build(&mut graph);
+        let mut options = SceneRenderingOptions::default();
+
+        let mut cycle = DayNightCycle {
+            enabled: true,
+            sun,
+            tilt: 0.0,
+            day_length: 24.0,
+            ..Default::default()
+        };
+        cycle.set_time_of_day(5.0);
+        cycle.apply(&mut graph, &mut options);
+        assert_eq!(cycle.pop_event(), Some(DayNightEvent::RefreshEnvironment));
+        assert!(!cycle.is_day());
+        assert!(!graph[sun].visibility());
+
+        // One second of real time is one hour.
+        cycle.update(7.0, &mut graph, &mut options);
+        assert_eq!(cycle.time_of_day, 12.0);
+        assert!(cycle.is_day());
+        assert!((cycle.sun_direction() - Vector3::y()).norm() < 1.0e-5);
+        assert_eq!(cycle.pop_event(), Some(DayNightEvent::Sunrise));
+        assert_eq!(cycle.pop_event(), Some(DayNightEvent::RefreshEnvironment));
+
+        let node: &Node = &graph[sun];
+        assert!(node.visibility());
+        assert_eq!(
+            node.query_component_ref::<BaseLight>().unwrap().intensity(),
+            1.0
+        );
+        // Light looks down at noon.
+        let look = node
+            .local_transform()
+            .rotation()
+            .transform_vector(&Vector3::z());
+        assert!((look + Vector3::y()).norm() < 1.0e-5);
+        assert_eq!(options.clear_color, Some(cycle.sky_color.get_color(0.5)));
+
+        // Wrap around midnight.
+        cycle.update(13.0, &mut graph, &mut options);
+        assert_eq!(cycle.time_of_day, 1.0);
+        assert_eq!(cycle.pop_event(), Some(DayNightEvent::Sunset));
+    }
+}
@@ -42,8 +42,23 @@ use std::ops::{Deref, DerefMut};
 ///
 /// # Supported maps
 ///
-/// Currently, only diffuse and normal maps are supported. Diffuse and normal maps will be automatically projected
-/// on the data stored in G-Buffer.
+/// Diffuse, normal and material (metallic + roughness + ambient occlusion) maps are supported. All of them will be
+/// automatically projected on the data stored in G-Buffer, so decals are correctly affected by lighting. Influence
+/// of the decal on normals and material properties of a surface could be controlled by
+/// [`Decal::set_normal_strength`] and [`Decal::set_material_strength`] respectively.
+///
+/// # Angle fade
+///
+/// Decal is projected along its local Y axis. Surfaces, that are almost parallel to the projection direction
+/// usually look bad because of stretched texture, so decal smoothly fades out on such surfaces. The fade is
+/// defined by a range of angles between the surface normal and the projection axis, see
+/// [`Decal::set_angle_fade_start`] and [`Decal::set_angle_fade_end`].
+///
+/// # Sorting
+///
+/// Overlapping decals are blended in the order defined by their sorting order (see [`Decal::set_sorting_order`]).
+/// Decals with lower sorting order are drawn first, decals with equal sorting order are drawn in the order of
+/// their appearance in the scene graph.
 ///
 /// # Limitations
 ///
@@ -84,7 +99,7 @@ use std::ops::{Deref, DerefMut};
 ///         .build(graph)
 /// }
 /// ```
-#[derive(Debug, Visit, Default, Clone, Reflect)]
+#[derive(Debug, Visit, Clone, Reflect)]
 pub struct Decal {
     base: Base,
 
@@ -100,6 +115,50 @@ pub struct Decal {
     #[reflect(min_value = 0.0)]
     #[reflect(setter = "set_layer")]
     layer: InheritableVariable<u8>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_material_texture")]
+    material_texture: InheritableVariable<Option<TextureResource>>,
+
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    #[reflect(setter = "set_metallic")]
+    metallic: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    #[reflect(setter = "set_roughness")]
+    roughness: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    #[reflect(setter = "set_normal_strength")]
+    normal_strength: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    #[reflect(setter = "set_material_strength")]
+    material_strength: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, max_value = 3.1416, step = 0.01)]
+    #[reflect(setter = "set_angle_fade_start")]
+    angle_fade_start: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, max_value = 3.1416, step = 0.01)]
+    #[reflect(setter = "set_angle_fade_end")]
+    angle_fade_end: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_sorting_order")]
+    sorting_order: InheritableVariable<i32>,
+}
+
+impl Default for Decal {
+    fn default() -> Self {
+        DecalBuilder::new(BaseBuilder::new()).build_decal()
+    }
 }
 
 impl Deref for Decal {
@@ -188,6 +247,111 @@ impl Decal {
     pub fn layer(&self) -> u8 {
         *self.layer
     }
+
+    /// Sets new material texture. Channels of the texture are used as follows: R - metallic, G - roughness,
+    /// B - ambient occlusion. Metallic and roughness are multiplied with respective values of the decal.
+    pub fn set_material_texture(
+        &mut self,
+        material_texture: Option<TextureResource>,
+    ) -> Option<TextureResource> {
+        std::mem::replace(
+            self.material_texture.get_value_mut_and_mark_modified(),
+            material_texture,
+        )
+    }
+
+    /// Returns current material texture.
+    pub fn material_texture(&self) -> Option<&TextureResource> {
+        self.material_texture.as_ref()
+    }
+
+    /// Returns current material texture.
+    pub fn material_texture_value(&self) -> Option<TextureResource> {
+        (*self.material_texture).clone()
+    }
+
+    /// Sets metallic value of the decal, it is clamped to `[0; 1]` range.
+    pub fn set_metallic(&mut self, metallic: f32) -> f32 {
+        self.metallic
+            .set_value_and_mark_modified(metallic.clamp(0.0, 1.0))
+    }
+
+    /// Returns current metallic value of the decal.
+    pub fn metallic(&self) -> f32 {
+        *self.metallic
+    }
+
+    /// Sets roughness value of the decal, it is clamped to `[0; 1]` range.
+    pub fn set_roughness(&mut self, roughness: f32) -> f32 {
+        self.roughness
+            .set_value_and_mark_modified(roughness.clamp(0.0, 1.0))
+    }
+
+    /// Returns current roughness value of the decal.
+    pub fn roughness(&self) -> f32 {
+        *self.roughness
+    }
+
+    /// Sets how much the decal affects normals of a surface. 0.0 - normals of the surface are
+    /// kept intact, 1.0 - normals are fully replaced with the normals of the decal (taking alpha of
+    /// the decal into account).
+    pub fn set_normal_strength(&mut self, strength: f32) -> f32 {
+        self.normal_strength
+            .set_value_and_mark_modified(strength.clamp(0.0, 1.0))
+    }
+
+    /// Returns current normal strength of the decal.
+    pub fn normal_strength(&self) -> f32 {
+        *self.normal_strength
+    }
+
+    /// Sets how much the decal affects material properties (metallic, roughness, ambient occlusion)
+    /// of a surface. 0.0 - material of the surface is kept intact, 1.0 - material is fully replaced
+    /// with the material of the decal (taking alpha of the decal into account).
+    pub fn set_material_strength(&mut self, strength: f32) -> f32 {
+        self.material_strength
+            .set_value_and_mark_modified(strength.clamp(0.0, 1.0))
+    }
+
+    /// Returns current material strength of the decal.
+    pub fn material_strength(&self) -> f32 {
+        *self.material_strength
+    }
+
+    /// Sets an angle (in radians) between the surface normal and the projection axis of the decal
+    /// at which the decal starts to fade out.
+    pub fn set_angle_fade_start(&mut self, angle: f32) -> f32 {
+        self.angle_fade_start
+            .set_value_and_mark_modified(angle.clamp(0.0, std::f32::consts::PI))
+    }
+
+    /// Returns an angle (in radians) at which the decal starts to fade out.
+    pub fn angle_fade_start(&self) -> f32 {
+        *self.angle_fade_start
+    }
+
+    /// Sets an angle (in radians) between the surface normal and the projection axis of the decal
+    /// at which the decal becomes fully transparent.
+    pub fn set_angle_fade_end(&mut self, angle: f32) -> f32 {
+        self.angle_fade_end
+            .set_value_and_mark_modified(angle.clamp(0.0, std::f32::consts::PI))
+    }
+
+    /// Returns an angle (in radians) at which the decal becomes fully transparent.
+    pub fn angle_fade_end(&self) -> f32 {
+        *self.angle_fade_end
+    }
+
+    /// Sets sorting order of the decal. Decals with lower sorting order are drawn first, so decals
+    /// with higher order are drawn on top of them.
+    pub fn set_sorting_order(&mut self, order: i32) -> i32 {
+        self.sorting_order.set_value_and_mark_modified(order)
+    }
+
+    /// Returns current sorting order of the decal.
+    pub fn sorting_order(&self) -> i32 {
+        *self.sorting_order
+    }
 }
 
 impl NodeTrait for Decal {
@@ -217,6 +381,14 @@ pub struct DecalBuilder {
     normal_texture: Option<TextureResource>,
     color: Color,
     layer: u8,
+    material_texture: Option<TextureResource>,
+    metallic: f32,
+    roughness: f32,
+    normal_strength: f32,
+    material_strength: f32,
+    angle_fade_start: f32,
+    angle_fade_end: f32,
+    sorting_order: i32,
 }
 
 impl DecalBuilder {
@@ -228,6 +400,14 @@ impl DecalBuilder {
             normal_texture: None,
             color: Color::opaque(255, 255, 255),
             layer: 0,
+            material_texture: None,
+            metallic: 0.0,
+            roughness: 1.0,
+            normal_strength: 1.0,
+            material_strength: 0.0,
+            angle_fade_start: std::f32::consts::PI,
+            angle_fade_end: std::f32::consts::PI,
+            sorting_order: 0,
         }
     }
 
@@ -255,6 +435,50 @@ impl DecalBuilder {
         self
     }
 
+    /// Sets desired material texture. See [`Decal::set_material_texture`] for more info.
+    pub fn with_material_texture(mut self, material_texture: TextureResource) -> Self {
+        self.material_texture = Some(material_texture);
+        self
+    }
+
+    /// Sets desired metallic value.
+    pub fn with_metallic(mut self, metallic: f32) -> Self {
+        self.metallic = metallic.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets desired roughness value.
+    pub fn with_roughness(mut self, roughness: f32) -> Self {
+        self.roughness = roughness.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets desired normal strength. See [`Decal::set_normal_strength`] for more info.
+    pub fn with_normal_strength(mut self, strength: f32) -> Self {
+        self.normal_strength = strength.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets desired material strength. See [`Decal::set_material_strength`] for more info.
+    pub fn with_material_strength(mut self, strength: f32) -> Self {
+        self.material_strength = strength.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets desired range of angles (in radians) at which the decal fades out. See
+    /// [`Decal::set_angle_fade_start`] and [`Decal::set_angle_fade_end`] for more info.
+    pub fn with_angle_fade(mut self, start: f32, end: f32) -> Self {
+        self.angle_fade_start = start.clamp(0.0, std::f32::consts::PI);
+        self.angle_fade_end = end.clamp(0.0, std::f32::consts::PI);
+        self
+    }
+
+    /// Sets desired sorting order.
+    pub fn with_sorting_order(mut self, order: i32) -> Self {
+        self.sorting_order = order;
+        self
+    }
+
     /// Creates new Decal node.
     pub fn build_decal(self) -> Decal {
         Decal {
@@ -263,6 +487,14 @@ impl DecalBuilder {
             normal_texture: self.normal_texture.into(),
             color: self.color.into(),
             layer: self.layer.into(),
+            material_texture: self.material_texture.into(),
+            metallic: self.metallic.into(),
+            roughness: self.roughness.into(),
+            normal_strength: self.normal_strength.into(),
+            material_strength: self.material_strength.into(),
+            angle_fade_start: self.angle_fade_start.into(),
+            angle_fade_end: self.angle_fade_end.into(),
+            sorting_order: self.sorting_order.into(),
         }
     }
 
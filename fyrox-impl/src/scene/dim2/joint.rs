@@ -29,6 +29,43 @@ use std::{
 };
 use strum_macros::{AsRefStr, EnumString, VariantNames};
 
+/// Motor of a joint drives relative motion of the bodies along (or around) a free axis of the joint.
+/// It acts as a spring, that pulls the bodies to the target position with the given stiffness, and
+/// as a damper, that pulls the relative velocity of the bodies to the target velocity. For example,
+/// a velocity-driven motor has zero stiffness and non-zero damping, while a position-driven motor has
+/// non-zero stiffness.
+#[derive(Clone, Debug, Visit, PartialEq, Reflect)]
+pub struct JointMotor {
+    /// Whether the motor is enabled or not. Default is `false`.
+    pub enabled: bool,
+    /// Target relative velocity of the bodies (in units per second or radians per second).
+    pub target_velocity: f32,
+    /// Target relative position of the bodies (in units or radians).
+    pub target_position: f32,
+    /// Stiffness of the spring, that pulls the bodies to the target position.
+    #[reflect(min_value = 0.0)]
+    pub stiffness: f32,
+    /// Damping of the motor, that pulls the bodies to the target velocity.
+    #[reflect(min_value = 0.0)]
+    pub damping: f32,
+    /// Maximum force (or torque), that the motor could apply.
+    #[reflect(min_value = 0.0)]
+    pub max_force: f32,
+}
+
+impl Default for JointMotor {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target_velocity: 0.0,
+            target_position: 0.0,
+            stiffness: 0.0,
+            damping: 1.0,
+            max_force: f32::MAX,
+        }
+    }
+}
+
 /// Ball joint locks any translational moves between two objects on the axis between objects, but
 /// allows rigid bodies to perform relative rotations. The real world example is a human shoulder,
 /// pendulum, etc.
@@ -74,6 +111,11 @@ pub struct PrismaticJoint {
     #[reflect(description = "Allowed linear distance range along local X axis of the joint.")]
     #[visit(optional)] // Backward compatibility
     pub limits: Range<f32>,
+
+    /// Motor, that drives the bodies along local X axis of the joint.
+    #[reflect(description = "Motor, that drives the bodies along local X axis of the joint.")]
+    #[visit(optional)]
+    pub motor: JointMotor,
 }
 
 impl Default for PrismaticJoint {
@@ -81,6 +123,46 @@ impl Default for PrismaticJoint {
         Self {
             limits_enabled: false,
             limits: -std::f32::consts::PI..std::f32::consts::PI,
+            motor: Default::default(),
+        }
+    }
+}
+
+/// Wheel joint attaches a wheel (the second body) to a vehicle (the first body). The wheel could
+/// freely rotate and move along local Y axis of the joint (suspension), which is simulated by a
+/// spring. The rotation of the wheel could be driven by a motor. The real world example is a wheel
+/// of a car, a bike, etc.
+#[derive(Clone, Debug, Visit, PartialEq, Reflect)]
+pub struct WheelJoint {
+    /// Stiffness of the suspension spring.
+    #[reflect(description = "Stiffness of the suspension spring.", min_value = 0.0)]
+    pub suspension_stiffness: f32,
+
+    /// Damping of the suspension spring.
+    #[reflect(description = "Damping of the suspension spring.", min_value = 0.0)]
+    pub suspension_damping: f32,
+
+    /// Whether the travel of the suspension is limited or not.
+    #[reflect(description = "Whether the travel of the suspension is limited or not.")]
+    pub suspension_limits_enabled: bool,
+
+    /// Allowed travel of the suspension along local Y axis of the joint.
+    #[reflect(description = "Allowed travel of the suspension along local Y axis of the joint.")]
+    pub suspension_limits: Range<f32>,
+
+    /// Motor, that rotates the wheel.
+    #[reflect(description = "Motor, that rotates the wheel.")]
+    pub motor: JointMotor,
+}
+
+impl Default for WheelJoint {
+    fn default() -> Self {
+        Self {
+            suspension_stiffness: 50.0,
+            suspension_damping: 5.0,
+            suspension_limits_enabled: true,
+            suspension_limits: -0.25..0.25,
+            motor: Default::default(),
         }
     }
 }
@@ -94,6 +176,8 @@ pub enum JointParams {
     FixedJoint(FixedJoint),
     /// See [`PrismaticJoint`] for more info.
     PrismaticJoint(PrismaticJoint),
+    /// See [`WheelJoint`] for more info.
+    WheelJoint(WheelJoint),
 }
 
 uuid_provider!(JointParams = "e1fa2015-3ea3-47bb-8ad3-d408559c9643");
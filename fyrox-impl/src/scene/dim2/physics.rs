@@ -266,6 +266,24 @@ where
     map: BiDirHashMap<A, Handle<Node>>,
 }
 
+fn apply_joint_motor(
+    joint: &mut GenericJoint,
+    axis: JointAxis,
+    motor: &scene::dim2::joint::JointMotor,
+) {
+    if motor.enabled {
+        joint
+            .set_motor(
+                axis,
+                motor.target_position,
+                motor.target_velocity,
+                motor.stiffness,
+                motor.damping,
+            )
+            .set_motor_max_force(axis, motor.max_force);
+    }
+}
+
 fn convert_joint_params(
     params: scene::dim2::joint::JointParams,
     local_frame1: Isometry2<f32>,
@@ -275,6 +293,8 @@ fn convert_joint_params(
         JointParams::BallJoint(_) => JointAxesMask::LOCKED_REVOLUTE_AXES,
         JointParams::FixedJoint(_) => JointAxesMask::LOCKED_FIXED_AXES,
         JointParams::PrismaticJoint(_) => JointAxesMask::LOCKED_PRISMATIC_AXES,
+        // The wheel moves along the suspension axis (local Y) and rotates freely.
+        JointParams::WheelJoint(_) => JointAxesMask::LIN_X,
     };
 
     let mut joint = GenericJointBuilder::new(locked_axis)
@@ -296,6 +316,22 @@ fn convert_joint_params(
             if v.limits_enabled {
                 joint.set_limits(JointAxis::LinX, [v.limits.start, v.limits.end]);
             }
+            apply_joint_motor(&mut joint, JointAxis::LinX, &v.motor);
+        }
+        scene::dim2::joint::JointParams::WheelJoint(v) => {
+            joint.set_motor_position(
+                JointAxis::LinY,
+                0.0,
+                v.suspension_stiffness,
+                v.suspension_damping,
+            );
+            if v.suspension_limits_enabled {
+                joint.set_limits(
+                    JointAxis::LinY,
+                    [v.suspension_limits.start, v.suspension_limits.end],
+                );
+            }
+            apply_joint_motor(&mut joint, JointAxis::AngX, &v.motor);
         }
     }
 
@@ -408,6 +444,9 @@ pub struct PhysicsWorld {
     #[visit(skip)]
     #[reflect(hidden)]
     debug_render_pipeline: Mutex<DebugRenderPipeline>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    active: bool,
 }
 
 fn isometry_from_global_transform(transform: &Matrix4<f32>) -> Isometry2<f32> {
@@ -506,13 +545,24 @@ impl PhysicsWorld {
             query: RefCell::new(Default::default()),
             performance_statistics: Default::default(),
             debug_render_pipeline: Default::default(),
+            active: true,
         }
     }
 
+    /// Returns `true` if the 2D physics world is active in its scene. An inactive world is never
+    /// stepped and does not hold any native objects. See [`crate::scene::graph::PhysicsMode`].
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub(crate) fn set_active(&mut self, active: bool) {
+        self.active = active;
+    }
+
     pub(crate) fn update(&mut self, dt: f32) {
         let time = instant::Instant::now();
 
-        if *self.enabled {
+        if *self.enabled && self.active {
             let integration_parameters = rapier2d::dynamics::IntegrationParameters {
                 dt: self.integration_parameters.dt.unwrap_or(dt),
                 min_ccd_dt: self.integration_parameters.min_ccd_dt,
@@ -842,7 +892,7 @@ impl PhysicsWorld {
         handle: Handle<Node>,
         rigid_body_node: &scene::dim2::rigidbody::RigidBody,
     ) {
-        if !rigid_body_node.is_globally_enabled() {
+        if !self.active || !rigid_body_node.is_globally_enabled() {
             self.remove_body(rigid_body_node.native.get());
             rigid_body_node.native.set(Default::default());
             return;
@@ -984,7 +1034,7 @@ impl PhysicsWorld {
         handle: Handle<Node>,
         collider_node: &scene::dim2::collider::Collider,
     ) {
-        if !collider_node.is_globally_enabled() {
+        if !self.active || !collider_node.is_globally_enabled() {
             self.remove_collider(collider_node.native.get());
             collider_node.native.set(Default::default());
             return;
@@ -1103,7 +1153,7 @@ impl PhysicsWorld {
         handle: Handle<Node>,
         joint: &scene::dim2::joint::Joint,
     ) {
-        if !joint.is_globally_enabled() {
+        if !self.active || !joint.is_globally_enabled() {
             self.remove_joint(joint.native.get());
             joint.native.set(ImpulseJointHandle(Default::default()));
             return;
@@ -0,0 +1,501 @@
+//! Skinned sprite is a 2D mesh, that is deformed by a hierarchy of bones. It is used for 2D
+//! skeletal (cut-out) character animation.
+//!
+//! See [`SkinnedSprite`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, Vector2, Vector3},
+        color::Color,
+        math::{aabb::AxisAlignedBoundingBox, TriangleDefinition},
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        value_as_u8_slice,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    graph::BaseSceneGraph,
+    material::{Material, MaterialResource},
+    renderer::{self, bundle::RenderContext},
+    scene::{
+        base::{Base, BaseBuilder},
+        dim2::rectangle::RectangleVertex,
+        graph::Graph,
+        mesh::{buffer::VertexTrait, RenderPath},
+        node::{Node, NodeTrait, RdcControlFlow, SyncContext},
+    },
+};
+use std::{
+    cell::Cell,
+    ops::{Deref, DerefMut},
+};
+
+/// Max amount of bones, that could affect a single vertex.
+pub const MAX_BONES_PER_VERTEX: usize = 4;
+
+/// A vertex of a skinned sprite.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct SkinnedSpriteVertex {
+    /// Position of the vertex in bind pose. For skinned sprites it is the world-space position of
+    /// the vertex at the moment, when the inverse bind pose matrices of the bones were calculated.
+    /// For sprites without bones it is the position in local coordinates of the sprite.
+    pub position: Vector3<f32>,
+    /// Texture coordinates.
+    pub tex_coord: Vector2<f32>,
+    /// Indices of the bones (in [`SkinnedSprite::bones`] list), that affect the vertex.
+    pub bone_indices: [u32; MAX_BONES_PER_VERTEX],
+    /// Weights of the bones, that affect the vertex. The sum of the weights must be equal to one,
+    /// unused bones must have zero weight.
+    pub bone_weights: [f32; MAX_BONES_PER_VERTEX],
+}
+
+impl SkinnedSpriteVertex {
+    /// Creates a new vertex, that is not affected by any bone.
+    pub fn new(position: Vector3<f32>, tex_coord: Vector2<f32>) -> Self {
+        Self {
+            position,
+            tex_coord,
+            ..Default::default()
+        }
+    }
+
+    /// Sets bones (pairs of bone index and weight) that affect the vertex. Only the first
+    /// [`MAX_BONES_PER_VERTEX`] bones with the largest weights are used, the weights are
+    /// normalized.
+    pub fn with_bones(mut self, bones: &[(u32, f32)]) -> Self {
+        let mut bones = bones.to_vec();
+        bones.sort_by(|a, b| b.1.total_cmp(&a.1));
+        bones.truncate(MAX_BONES_PER_VERTEX);
+
+        let sum = bones.iter().map(|(_, weight)| *weight).sum::<f32>();
+        self.bone_indices = Default::default();
+        self.bone_weights = Default::default();
+        for (i, (index, weight)) in bones.into_iter().enumerate() {
+            self.bone_indices[i] = index;
+            self.bone_weights[i] = if sum > 0.0 { weight / sum } else { 0.0 };
+        }
+        self
+    }
+}
+
+/// Skinned sprite is a 2D mesh (a set of textured triangles), which vertices are bound to a
+/// hierarchy of bones. Bones are ordinary scene nodes, so they could be animated by animation
+/// players (and animation blending state machines) the same way as the bones of 3D skinned
+/// meshes. It allows you to animate 2D characters made of deformable parts, like in Spine or
+/// DragonBones. Use [`crate::resource::dragon_bones`] to import a skeleton from the DragonBones
+/// format.
+///
+/// ## Skinning
+///
+/// The final position of each vertex is a weighted sum of its bind pose position transformed by
+/// the matrices of the bones, the matrix of a bone is its global transform multiplied by its inverse
+/// bind pose transform (see [`crate::scene::base::BaseBuilder::with_inv_bind_pose_transform`]).
+/// The transform of the sprite itself is ignored when the sprite has bones. Skinning is done on
+/// CPU, which is fine for the amount of vertices in typical 2D characters, and it allows skinned
+/// sprites to be batched together with other 2D nodes that use the same material.
+///
+/// ## Material
+///
+/// By default, skinned sprites use the standard 2D material, the same as
+/// [`super::rectangle::Rectangle`], its `diffuseTexture` property defines the texture (usually an
+/// atlas with all the parts of a character).
+///
+/// ## Draw order
+///
+/// Skinned sprites are sorted by the distance to the camera, use Z coordinate of the vertices to
+/// define the draw order of the parts of a character.
+#[derive(Reflect, Debug, Clone, Visit)]
+pub struct SkinnedSprite {
+    base: Base,
+
+    #[reflect(setter = "set_color")]
+    color: InheritableVariable<Color>,
+
+    material: InheritableVariable<MaterialResource>,
+
+    #[reflect(setter = "set_vertices")]
+    vertices: InheritableVariable<Vec<SkinnedSpriteVertex>>,
+
+    #[reflect(setter = "set_triangles")]
+    triangles: InheritableVariable<Vec<TriangleDefinition>>,
+
+    #[reflect(setter = "set_bones")]
+    bones: InheritableVariable<Vec<Handle<Node>>>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    world_bounding_box: Cell<AxisAlignedBoundingBox>,
+}
+
+impl Default for SkinnedSprite {
+    fn default() -> Self {
+        SkinnedSpriteBuilder::new(BaseBuilder::new()).build_skinned_sprite()
+    }
+}
+
+impl Deref for SkinnedSprite {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for SkinnedSprite {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for SkinnedSprite {
+    fn type_uuid() -> Uuid {
+        uuid!("c1a9e3f2-5b7d-4e60-9f84-2d6b0a7c3e15")
+    }
+}
+
+/// Applies bone matrices to a vertex.
+fn skin_vertex(vertex: &SkinnedSpriteVertex, bone_matrices: &[Matrix4<f32>]) -> Vector3<f32> {
+    let position = Point3::from(vertex.position);
+    let mut result = Vector3::default();
+    let mut total_weight = 0.0;
+    for (index, weight) in vertex.bone_indices.iter().zip(vertex.bone_weights.iter()) {
+        if *weight > 0.0 {
+            if let Some(matrix) = bone_matrices.get(*index as usize) {
+                result += matrix.transform_point(&position).coords.scale(*weight);
+                total_weight += *weight;
+            }
+        }
+    }
+    if total_weight > 0.0 {
+        result / total_weight
+    } else {
+        vertex.position
+    }
+}
+
+impl SkinnedSprite {
+    /// Returns current color of the sprite.
+    pub fn color(&self) -> Color {
+        *self.color
+    }
+
+    /// Sets color of the sprite, it is multiplied with the color of the texture.
+    pub fn set_color(&mut self, color: Color) -> Color {
+        self.color.set_value_and_mark_modified(color)
+    }
+
+    /// Returns a reference to the current material used by the sprite.
+    pub fn material(&self) -> &InheritableVariable<MaterialResource> {
+        &self.material
+    }
+
+    /// Returns a reference to the current material used by the sprite.
+    pub fn material_mut(&mut self) -> &mut InheritableVariable<MaterialResource> {
+        &mut self.material
+    }
+
+    /// Returns vertices of the sprite.
+    pub fn vertices(&self) -> &[SkinnedSpriteVertex] {
+        &self.vertices
+    }
+
+    /// Sets new vertices of the sprite.
+    pub fn set_vertices(&mut self, vertices: Vec<SkinnedSpriteVertex>) -> Vec<SkinnedSpriteVertex> {
+        self.vertices.set_value_and_mark_modified(vertices)
+    }
+
+    /// Returns triangles of the sprite.
+    pub fn triangles(&self) -> &[TriangleDefinition] {
+        &self.triangles
+    }
+
+    /// Sets new triangles of the sprite. Every index must point to an existing vertex.
+    pub fn set_triangles(&mut self, triangles: Vec<TriangleDefinition>) -> Vec<TriangleDefinition> {
+        self.triangles.set_value_and_mark_modified(triangles)
+    }
+
+    /// Returns handles of the bones of the sprite.
+    pub fn bones(&self) -> &[Handle<Node>] {
+        &self.bones
+    }
+
+    /// Sets new bones of the sprite. Bone indices of the vertices refer to this list.
+    pub fn set_bones(&mut self, bones: Vec<Handle<Node>>) -> Vec<Handle<Node>> {
+        self.bones.set_value_and_mark_modified(bones)
+    }
+
+    fn bone_matrices<'a>(
+        &self,
+        mut fetch: impl FnMut(Handle<Node>) -> Option<&'a Node>,
+    ) -> Vec<Matrix4<f32>> {
+        self.bones
+            .iter()
+            .map(|bone| {
+                fetch(*bone)
+                    .map(|bone| bone.global_transform() * bone.inv_bind_pose_transform())
+                    .unwrap_or_else(Matrix4::identity)
+            })
+            .collect()
+    }
+
+    fn skinned_positions_with(
+        &self,
+        global_transform: &Matrix4<f32>,
+        bone_matrices: &[Matrix4<f32>],
+    ) -> Vec<Vector3<f32>> {
+        if self.bones.is_empty() {
+            self.vertices
+                .iter()
+                .map(|v| {
+                    global_transform
+                        .transform_point(&Point3::from(v.position))
+                        .coords
+                })
+                .collect()
+        } else {
+            self.vertices
+                .iter()
+                .map(|v| skin_vertex(v, bone_matrices))
+                .collect()
+        }
+    }
+
+    /// Returns world-space positions of the vertices of the sprite deformed by the current pose of
+    /// the bones.
+    pub fn skinned_positions(&self, graph: &Graph) -> Vec<Vector3<f32>> {
+        let bone_matrices = self.bone_matrices(|bone| graph.try_get(bone));
+        self.skinned_positions_with(&self.global_transform(), &bone_matrices)
+    }
+}
+
+impl NodeTrait for SkinnedSprite {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox::from_points(
+            &self.vertices.iter().map(|v| v.position).collect::<Vec<_>>(),
+        )
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.world_bounding_box.get()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn sync_transform(&self, new_global_transform: &Matrix4<f32>, context: &mut SyncContext) {
+        let bone_matrices = self.bone_matrices(|bone| context.nodes.try_borrow(bone));
+        let positions = self.skinned_positions_with(new_global_transform, &bone_matrices);
+        self.world_bounding_box
+            .set(AxisAlignedBoundingBox::from_points(&positions));
+    }
+
+    fn collect_render_data(&self, ctx: &mut RenderContext) -> RdcControlFlow {
+        if !self.global_visibility()
+            || !self.is_globally_enabled()
+            || (self.frustum_culling()
+                && !ctx
+                    .frustum
+                    .map_or(true, |f| f.is_intersects_aabb(&self.world_bounding_box())))
+        {
+            return RdcControlFlow::Continue;
+        }
+
+        if renderer::is_shadow_pass(ctx.render_pass_name) {
+            return RdcControlFlow::Continue;
+        }
+
+        let bone_matrices = self.bone_matrices(|bone| ctx.graph.try_get(bone));
+        let positions = self.skinned_positions_with(&self.global_transform(), &bone_matrices);
+        if positions.is_empty() {
+            return RdcControlFlow::Continue;
+        }
+
+        let vertex_count = positions.len() as u32;
+        let color = *self.color;
+        let vertices = positions
+            .iter()
+            .zip(self.vertices.iter())
+            .map(|(position, vertex)| RectangleVertex {
+                position: *position,
+                tex_coord: vertex.tex_coord,
+                color,
+            })
+            .collect::<Vec<_>>();
+        let triangles = self
+            .triangles
+            .iter()
+            .filter(|t| t.0.iter().all(|i| *i < vertex_count))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let center = AxisAlignedBoundingBox::from_points(&positions).center();
+        let sort_index = ctx.calculate_sorting_index(center);
+
+        ctx.storage.push_triangles(
+            RectangleVertex::layout(),
+            &self.material,
+            RenderPath::Forward,
+            0,
+            sort_index,
+            false,
+            self.self_handle,
+            &mut move |mut vertex_buffer, mut triangle_buffer| {
+                let start_vertex_index = vertex_buffer.vertex_count();
+
+                for vertex in vertices.iter() {
+                    vertex_buffer
+                        .push_vertex_raw(value_as_u8_slice(vertex))
+                        .unwrap();
+                }
+
+                triangle_buffer
+                    .push_triangles_iter_with_offset(start_vertex_index, triangles.iter().cloned());
+            },
+        );
+
+        RdcControlFlow::Continue
+    }
+}
+
+/// Allows you to create skinned sprites in declarative manner.
+pub struct SkinnedSpriteBuilder {
+    base_builder: BaseBuilder,
+    color: Color,
+    material: MaterialResource,
+    vertices: Vec<SkinnedSpriteVertex>,
+    triangles: Vec<TriangleDefinition>,
+    bones: Vec<Handle<Node>>,
+}
+
+impl SkinnedSpriteBuilder {
+    /// Creates new skinned sprite builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            color: Color::WHITE,
+            material: MaterialResource::new_ok(Default::default(), Material::standard_2d()),
+            vertices: Default::default(),
+            triangles: Default::default(),
+            bones: Default::default(),
+        }
+    }
+
+    /// Sets desired color of the sprite.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the desired material of the sprite.
+    pub fn with_material(mut self, material: MaterialResource) -> Self {
+        self.material = material;
+        self
+    }
+
+    /// Sets desired vertices of the sprite.
+    pub fn with_vertices(mut self, vertices: Vec<SkinnedSpriteVertex>) -> Self {
+        self.vertices = vertices;
+        self
+    }
+
+    /// Sets desired triangles of the sprite.
+    pub fn with_triangles(mut self, triangles: Vec<TriangleDefinition>) -> Self {
+        self.triangles = triangles;
+        self
+    }
+
+    /// Sets desired bones of the sprite.
+    pub fn with_bones(mut self, bones: Vec<Handle<Node>>) -> Self {
+        self.bones = bones;
+        self
+    }
+
+    /// Creates new [`SkinnedSprite`] instance.
+    pub fn build_skinned_sprite(self) -> SkinnedSprite {
+        SkinnedSprite {
+            base: self.base_builder.build_base(),
+            color: self.color.into(),
+            material: self.material.into(),
+            vertices: self.vertices.into(),
+            triangles: self.triangles.into(),
+            bones: self.bones.into(),
+            world_bounding_box: Default::default(),
+        }
+    }
+
+    /// Creates new [`SkinnedSprite`] node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_skinned_sprite())
+    }
+
+    /// Creates new [`SkinnedSprite`] instance and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Matrix4, Vector2, Vector3},
+            math::TriangleDefinition,
+        },
+        scene::{
+            base::BaseBuilder,
+            dim2::skinned_sprite::{SkinnedSprite, SkinnedSpriteBuilder, SkinnedSpriteVertex},
+            graph::Graph,
+            pivot::PivotBuilder,
+            transform::TransformBuilder,
+        },
+    };
+
+    #[test]
+    fn test_skinned_sprite_deformation() {
+        let mut graph = Graph::new();
+
+        // The bone is at (1, 0) in bind pose, then it is moved up by one unit.
+        let bone = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_inv_bind_pose_transform(Matrix4::new_translation(&Vector3::new(
+                    -1.0, 0.0, 0.0,
+                )))
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(1.0, 1.0, 0.0))
+                        .build(),
+                ),
+        )
+        .build(&mut graph);
+
+        let sprite = SkinnedSpriteBuilder::new(BaseBuilder::new())
+            .with_vertices(vec![
+                // Fixed vertex.
+                SkinnedSpriteVertex::new(Vector3::new(0.0, 0.0, 0.0), Vector2::new(0.0, 0.0)),
+                // Fully attached to the bone.
+                SkinnedSpriteVertex::new(Vector3::new(2.0, 0.0, 0.0), Vector2::new(1.0, 0.0))
+                    .with_bones(&[(0, 2.0)]),
+                // Half attached to the bone, the other half is attached to a missing bone.
+                SkinnedSpriteVertex::new(Vector3::new(2.0, 2.0, 0.0), Vector2::new(1.0, 1.0))
+                    .with_bones(&[(0, 1.0), (5, 1.0)]),
+            ])
+            .with_triangles(vec![TriangleDefinition([0, 1, 2])])
+            .with_bones(vec![bone])
+            .build(&mut graph);
+
+        graph.update_hierarchical_data();
+
+        let sprite = graph[sprite].cast::<SkinnedSprite>().unwrap();
+        assert_eq!(sprite.vertices()[1].bone_weights, [1.0, 0.0, 0.0, 0.0]);
+
+        let positions = sprite.skinned_positions(&graph);
+        assert_eq!(positions[0], Vector3::new(0.0, 0.0, 0.0));
+        assert_eq!(positions[1], Vector3::new(2.0, 1.0, 0.0));
+        assert_eq!(positions[2], Vector3::new(2.0, 3.0, 0.0));
+    }
+}
@@ -23,34 +23,45 @@
 //! is used in skinning (animating 3d model by set of bones).
 
 use crate::{
-    asset::{manager::ResourceManager, untyped::UntypedResource},
+    asset::{
+        manager::ResourceManager,
+        untyped::{ResourceKind, UntypedResource},
+    },
     core::{
         algebra::{Matrix4, Rotation3, UnitQuaternion, Vector2, Vector3},
         instant,
         log::{Log, MessageKind},
-        math::{aabb::AxisAlignedBoundingBox, Matrix4Ext},
+        math::{aabb::AxisAlignedBoundingBox, ray::Ray, Matrix4Ext},
+        memory::{self, MemoryTag},
         pool::{ErasedHandle, Handle, MultiBorrowContext, Pool, Ticket},
         reflect::prelude::*,
         sstorage::ImmutableString,
+        uuid_provider,
         visitor::{Visit, VisitResult, Visitor},
     },
-    graph::{AbstractSceneGraph, AbstractSceneNode, BaseSceneGraph, NodeHandleMap, SceneGraph},
-    material::{shader::SamplerFallback, MaterialResource, PropertyValue},
+    graph::{
+        AbstractSceneGraph, AbstractSceneNode, BaseSceneGraph, NodeHandleMap, NodeMapping,
+        SceneGraph,
+    },
+    material::{shader::SamplerFallback, Material, MaterialResource, PropertyValue},
     resource::model::{Model, ModelResource, ModelResourceExtension},
     scene::{
-        base::{NodeScriptMessage, SceneNodeId},
+        animation,
+        base::{LifetimePolicy, NodeScriptMessage, SceneNodeId},
         camera::Camera,
         dim2::{self},
         graph::{
             event::{GraphEvent, GraphEventBroadcaster},
             physics::{PhysicsPerformanceStatistics, PhysicsWorld},
+            query::{NodeIndex, SpatialIndex, SpatialRayHit},
         },
         mesh::Mesh,
         navmesh,
         node::{container::NodeContainer, Node, NodeTrait, SyncContext, UpdateContext},
         pivot::Pivot,
-        sound::context::SoundContext,
+        sound::{context::SoundContext, occlusion, reverb_zone},
         transform::TransformBuilder,
+        Scene,
     },
     script::ScriptTrait,
     utils::lightmap::{self, Lightmap},
@@ -63,9 +74,11 @@ use std::{
     sync::mpsc::{channel, Receiver, Sender},
     time::Duration,
 };
+use strum_macros::{AsRefStr, EnumString, VariantNames};
 
 pub mod event;
 pub mod physics;
+pub mod query;
 
 /// Graph performance statistics. Allows you to find out "hot" parts of the scene graph, which
 /// parts takes the most time to update.
@@ -87,6 +100,10 @@ pub struct GraphPerformanceStatistics {
 
     /// A time which was required to render sounds.
     pub sound_update_time: Duration,
+
+    /// Amount of time that was needed to update animation players and animation blending state
+    /// machines in the parallel animation stage. See [`GraphUpdateSwitches::parallel_animation`].
+    pub animation_time: Duration,
 }
 
 impl GraphPerformanceStatistics {
@@ -97,6 +114,48 @@ impl GraphPerformanceStatistics {
             + self.physics.total()
             + self.physics2d.total()
             + self.sound_update_time
+            + self.animation_time
+    }
+}
+
+/// Defines which physics worlds are simulated in a graph. Scenes that use only one kind of physics
+/// could switch off the other world entirely - it won't be stepped and none of its native rigid
+/// bodies, colliders or joints will be allocated.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum PhysicsMode {
+    /// Both 3D and 2D physics worlds are simulated.
+    #[default]
+    Full,
+    /// Only 2D physics world is simulated, 3D physics nodes will be ignored.
+    Only2D,
+    /// Only 3D physics world is simulated, 2D physics nodes will be ignored.
+    Only3D,
+}
+
+uuid_provider!(PhysicsMode = "1f6b3e1a-2f7c-4f0e-9b63-8d0a4b2d5c17");
+
+impl PhysicsMode {
+    /// Returns `true` if 3D physics world is simulated in this mode.
+    pub fn is_3d_enabled(self) -> bool {
+        matches!(self, Self::Full | Self::Only3D)
+    }
+
+    /// Returns `true` if 2D physics world is simulated in this mode.
+    pub fn is_2d_enabled(self) -> bool {
+        matches!(self, Self::Full | Self::Only2D)
     }
 }
 
@@ -120,6 +179,10 @@ pub struct Graph {
     /// Backing 2D physics "world". It is responsible for the 2D physics simulation.
     pub physics2d: dim2::physics::PhysicsWorld,
 
+    /// Defines which physics worlds are simulated. See [`PhysicsMode`] docs for more info.
+    #[reflect(setter = "set_physics_mode")]
+    physics_mode: PhysicsMode,
+
     /// Backing sound context. It is responsible for sound rendering.
     #[reflect(hidden)]
     pub sound_context: SoundContext,
@@ -141,6 +204,12 @@ pub struct Graph {
     pub(crate) script_message_receiver: Receiver<NodeScriptMessage>,
 
     instance_id_map: FxHashMap<SceneNodeId, Handle<Node>>,
+
+    #[reflect(hidden)]
+    spatial_index: SpatialIndex,
+
+    #[reflect(hidden)]
+    node_index: NodeIndex,
 }
 
 impl Default for Graph {
@@ -150,6 +219,7 @@ impl Default for Graph {
         Self {
             physics: PhysicsWorld::new(),
             physics2d: dim2::physics::PhysicsWorld::new(),
+            physics_mode: Default::default(),
             root: Handle::NONE,
             pool: Pool::new(),
             stack: Vec::new(),
@@ -160,6 +230,8 @@ impl Default for Graph {
             script_message_sender: tx,
             lightmap: None,
             instance_id_map: Default::default(),
+            spatial_index: Default::default(),
+            node_index: Default::default(),
         }
     }
 }
@@ -237,6 +309,12 @@ pub struct GraphUpdateSwitches {
     /// Whether the graph update is paused or not. Paused graphs won't be updated and their sound content will be also paused
     /// so it won't emit any sounds.
     pub paused: bool,
+    /// Enables or disables parallel animation update. When enabled, animation players and animation blending state machines
+    /// are evaluated on a thread pool before every other node is updated and then their poses are applied to the graph.
+    /// Every animation player is evaluated together with the state machines that use it, so a scene with many animated
+    /// characters is updated much faster on multicore CPUs. When disabled, animations are updated one-by-one as any other
+    /// node. Disabled by default, because custom nodes and scripts could rely on the order of the sequential update.
+    pub parallel_animation: bool,
 }
 
 impl Default for GraphUpdateSwitches {
@@ -247,6 +325,7 @@ impl Default for GraphUpdateSwitches {
             node_overrides: Default::default(),
             delete_dead_nodes: true,
             paused: false,
+            parallel_animation: false,
         }
     }
 }
@@ -270,12 +349,16 @@ impl Graph {
 
         let instance_id_map = FxHashMap::from_iter([(instance_id, root)]);
 
+        let mut node_index = NodeIndex::default();
+        node_index.insert(root, &pool[root]);
+
         Self {
             physics: Default::default(),
             stack: Vec::new(),
             root,
             pool,
             physics2d: Default::default(),
+            physics_mode: Default::default(),
             sound_context: SoundContext::new(),
             performance_statistics: Default::default(),
             event_broadcaster: Default::default(),
@@ -283,6 +366,8 @@ impl Graph {
             script_message_sender: tx,
             lightmap: None,
             instance_id_map,
+            spatial_index: Default::default(),
+            node_index,
         }
     }
 
@@ -550,6 +635,51 @@ impl Graph {
         (root_handle, old_new_mapping)
     }
 
+    /// Creates a deep copy of a node with all its descendants in the given graph. It is a shortcut
+    /// for [`Self::copy_node`] without any filtering. It could be used to copy a part of a scene to
+    /// some other scene at runtime. The copy keeps links to the prefabs of the copied nodes (if any),
+    /// so all the property overrides of the prefab instances are preserved.
+    ///
+    /// Returns a handle of the copy of the root node and old-to-new mapping of the copied nodes.
+    #[inline]
+    pub fn copy_subtree_to(
+        &self,
+        node_handle: Handle<Node>,
+        dest_graph: &mut Graph,
+    ) -> (Handle<Node>, NodeHandleMap<Node>) {
+        self.copy_node(
+            node_handle,
+            dest_graph,
+            &mut |_, _| true,
+            &mut |_, _| {},
+            &mut |_, _, _| {},
+        )
+    }
+
+    /// Extracts a copy of a node with all its descendants as a standalone prefab. The prefab
+    /// could be instantiated as any other model resource (see [`ModelResourceExtension`]) or saved
+    /// to a file using [`crate::asset::ResourceData::save`]. This is useful to save player-created
+    /// structures at runtime, for example.
+    ///
+    /// The copy of the given node becomes the root of the prefab. The returned resource is
+    /// embedded, it is up to the caller to save it somewhere.
+    pub fn extract_as_prefab(&self, node_handle: Handle<Node>) -> ModelResource {
+        let mut scene = Scene::new();
+        let (root, _) = self.copy_subtree_to(node_handle, &mut scene.graph);
+
+        // Make the copied node the root of the prefab, so its instances will have the same
+        // hierarchy as the source.
+        let default_root = scene.graph.get_root();
+        scene.graph.isolate_node(root);
+        scene.graph.set_root(root);
+        scene.graph.remove_node(default_root);
+
+        ModelResource::new_ok(
+            ResourceKind::Embedded,
+            Model::new(NodeMapping::UseHandles, scene),
+        )
+    }
+
     /// Creates copy of a node and breaks all connections with other nodes. Keep in mind that
     /// this method may give unexpected results when the node has connections with other nodes.
     /// For example if you'll try to copy a skinned mesh, its copy won't be skinned anymore -
@@ -653,6 +783,23 @@ impl Graph {
         }
 
         // Sync materials with shaders.
+        self.sync_materials_to_shaders(resource_manager, |_| true);
+
+        self.apply_lightmap();
+
+        Log::writeln(MessageKind::Information, "Graph resolved successfully!");
+    }
+
+    /// Syncs every material of the graph, that passes the given filter, with its shader. See
+    /// [`Material::sync_to_shader`] for more info. It is used to propagate
+    /// hot-reloading of shaders to the materials.
+    pub fn sync_materials_to_shaders<F>(
+        &mut self,
+        resource_manager: &ResourceManager,
+        mut filter: F,
+    ) where
+        F: FnMut(&Material) -> bool,
+    {
         let mut materials = FxHashSet::default();
         for node in self.linear_iter_mut() {
             (node as &mut dyn Reflect).enumerate_fields_recursively(
@@ -670,13 +817,11 @@ impl Graph {
         for material in materials {
             let mut material_state = material.state();
             if let Some(material) = material_state.data() {
-                material.sync_to_shader(resource_manager);
+                if filter(material) {
+                    material.sync_to_shader(resource_manager);
+                }
             }
         }
-
-        self.apply_lightmap();
-
-        Log::writeln(MessageKind::Information, "Graph resolved successfully!");
     }
 
     /// Tries to set new lightmap to scene.
@@ -923,6 +1068,7 @@ impl Graph {
         frame_size: Vector2<f32>,
         dt: f32,
         delete_dead_nodes: bool,
+        animated: &FxHashSet<Handle<Node>>,
     ) {
         if let Some((ticket, mut node)) = self.pool.try_take_reserve(handle) {
             node.transform_modified.set(false);
@@ -930,14 +1076,18 @@ impl Graph {
             let mut is_alive = node.is_alive();
 
             if node.is_globally_enabled() {
-                node.update(&mut UpdateContext {
-                    frame_size,
-                    dt,
-                    nodes: &mut self.pool,
-                    physics: &mut self.physics,
-                    physics2d: &mut self.physics2d,
-                    sound_context: &mut self.sound_context,
-                });
+                // Nodes, that were updated by the parallel animation stage, must not be updated
+                // twice.
+                if !animated.contains(&handle) {
+                    node.update(&mut UpdateContext {
+                        frame_size,
+                        dt,
+                        nodes: &mut self.pool,
+                        physics: &mut self.physics,
+                        physics2d: &mut self.physics2d,
+                        sound_context: &mut self.sound_context,
+                    });
+                }
 
                 if delete_dead_nodes {
                     if let Some(lifetime) = node.lifetime.get_value_mut_silent().as_mut() {
@@ -949,6 +1099,12 @@ impl Graph {
                 }
             }
 
+            if !is_alive && delete_dead_nodes && node.lifetime_policy() == LifetimePolicy::Disable {
+                // The node is kept for further reuse, so it must not be removed.
+                node.set_enabled(false);
+                is_alive = true;
+            }
+
             self.pool.put_back(ticket, node);
 
             if !is_alive && delete_dead_nodes {
@@ -972,31 +1128,60 @@ impl Graph {
 
         let last_time = instant::Instant::now();
         self.update_hierarchical_data();
+        self.update_query_index();
         self.performance_statistics.hierarchical_properties_time =
             instant::Instant::now() - last_time;
 
         let last_time = instant::Instant::now();
         self.sync_native(&switches);
+        reverb_zone::update_reverb_zones(&self.pool, &mut self.sound_context);
         self.performance_statistics.sync_time = instant::Instant::now() - last_time;
 
-        if switches.physics {
+        let physics_scope = memory::scope(MemoryTag::Physics);
+        if switches.physics && self.physics.is_active() {
             self.physics.performance_statistics.reset();
             self.physics.update(dt);
             self.performance_statistics.physics = self.physics.performance_statistics.clone();
         }
 
-        if switches.physics2d {
+        if switches.physics2d && self.physics2d.is_active() {
             self.physics2d.performance_statistics.reset();
             self.physics2d.update(dt);
             self.performance_statistics.physics2d = self.physics2d.performance_statistics.clone();
         }
+        drop(physics_scope);
+
+        occlusion::update_occlusion(&self.pool, &self.physics, &mut self.sound_context, dt);
 
         self.performance_statistics.sound_update_time =
             self.sound_context.state().full_render_duration();
 
+        let mut animated = FxHashSet::default();
+        if switches.parallel_animation {
+            let last_time = instant::Instant::now();
+            animation::parallel::update_animations(
+                &mut self.pool,
+                dt,
+                |handle| {
+                    switches
+                        .node_overrides
+                        .as_ref()
+                        .map_or(true, |overrides| overrides.contains(&handle))
+                },
+                &mut animated,
+            );
+            self.performance_statistics.animation_time = instant::Instant::now() - last_time;
+        }
+
         if let Some(overrides) = switches.node_overrides.as_ref() {
             for handle in overrides {
-                self.update_node(*handle, frame_size, dt, switches.delete_dead_nodes);
+                self.update_node(
+                    *handle,
+                    frame_size,
+                    dt,
+                    switches.delete_dead_nodes,
+                    &animated,
+                );
             }
         } else {
             for i in 0..self.pool.get_capacity() {
@@ -1005,11 +1190,83 @@ impl Graph {
                     frame_size,
                     dt,
                     switches.delete_dead_nodes,
+                    &animated,
                 );
             }
         }
     }
 
+    /// Synchronizes the spatial index and the tag index of the graph with the current state of the
+    /// nodes. It is called automatically by [`Self::update`] right after global transforms are
+    /// calculated, so there's no need to call it manually unless you need to query a freshly
+    /// created or moved nodes before the next update (call [`Self::update_hierarchical_data`]
+    /// first in this case).
+    pub fn update_query_index(&mut self) {
+        self.spatial_index.sync(&self.pool, self.root);
+        self.node_index.sync(&self.pool);
+    }
+
+    /// Returns a reference to the spatial index of the graph. See [`SpatialIndex`] docs for more
+    /// info.
+    pub fn spatial_index(&self) -> &SpatialIndex {
+        &self.spatial_index
+    }
+
+    /// Returns an iterator over every node of the given type in no particular order. Unlike
+    /// iteration over every node of the graph, it visits only the nodes of the given type.
+    ///
+    /// ```rust
+    /// # use fyrox_impl::scene::{graph::Graph, mesh::Mesh, node::Node};
+    /// # use fyrox_impl::core::pool::Handle;
+    /// fn hide_meshes(graph: &mut Graph) {
+    ///     let meshes = graph
+    ///         .typed_iter::<Mesh>()
+    ///         .map(|(handle, _)| handle)
+    ///         .collect::<Vec<Handle<Node>>>();
+    ///     for mesh in meshes {
+    ///         graph[mesh].set_visibility(false);
+    ///     }
+    /// }
+    /// ```
+    pub fn typed_iter<T: NodeTrait>(&self) -> impl Iterator<Item = (Handle<Node>, &T)> {
+        self.node_index
+            .of_type(TypeId::of::<T>())
+            .filter_map(|handle| {
+                self.pool
+                    .try_borrow(handle)
+                    .and_then(|node| node.cast::<T>())
+                    .map(|node| (handle, node))
+            })
+    }
+
+    /// Returns an iterator over every node with the given tag in no particular order. Tag changes
+    /// are tracked by [`Self::update_query_index`], nodes added to the graph are indexed
+    /// immediately.
+    pub fn find_by_tag<'a>(
+        &'a self,
+        tag: &'a str,
+    ) -> impl Iterator<Item = (Handle<Node>, &'a Node)> + 'a {
+        self.node_index.with_tag(tag).filter_map(move |handle| {
+            self.pool
+                .try_borrow(handle)
+                .filter(|node| node.tag() == tag)
+                .map(|node| (handle, node))
+        })
+    }
+
+    /// Finds every node, whose world-space bounding box intersects the given bounding box. The
+    /// results are appended to the given buffer. See [`SpatialIndex`] docs for more info.
+    pub fn query_aabb(&self, aabb: &AxisAlignedBoundingBox, results: &mut Vec<Handle<Node>>) {
+        self.spatial_index.query_aabb(aabb, results)
+    }
+
+    /// Finds every node, whose world-space bounding box is intersected by the given ray. The
+    /// results are sorted by distance and appended to the given buffer. See [`SpatialIndex`] docs
+    /// for more info.
+    pub fn query_ray(&self, ray: &Ray, results: &mut Vec<SpatialRayHit>) {
+        self.spatial_index.query_ray(ray, results)
+    }
+
     /// Returns capacity of internal pool. Can be used to iterate over all **potentially**
     /// available indices and try to convert them to handles.
     ///
@@ -1174,6 +1431,19 @@ impl Graph {
         self.pool.alive_count()
     }
 
+    /// Sets a new physics mode of the graph and returns the previous one. Physics worlds that are
+    /// switched off release all their native objects on the next update.
+    pub fn set_physics_mode(&mut self, mode: PhysicsMode) -> PhysicsMode {
+        self.physics.set_active(mode.is_3d_enabled());
+        self.physics2d.set_active(mode.is_2d_enabled());
+        std::mem::replace(&mut self.physics_mode, mode)
+    }
+
+    /// Returns current physics mode of the graph.
+    pub fn physics_mode(&self) -> PhysicsMode {
+        self.physics_mode
+    }
+
     /// Creates deep copy of graph. Allows filtering while copying, returns copy and
     /// old-to-new node mapping.
     #[inline]
@@ -1193,6 +1463,7 @@ impl Graph {
             sound_context: self.sound_context.deep_clone(),
             ..Default::default()
         };
+        copy.set_physics_mode(self.physics_mode);
 
         let (copy_root, old_new_map) = self.copy_node(
             root,
@@ -1480,9 +1751,16 @@ impl Visit for Graph {
 
         self.root.visit("Root", &mut region)?;
         self.pool.visit("Pool", &mut region)?;
+        if region.is_reading() {
+            self.node_index.sync(&self.pool);
+        }
         self.sound_context.visit("SoundContext", &mut region)?;
         self.physics.visit("PhysicsWorld", &mut region)?;
         self.physics2d.visit("PhysicsWorld2D", &mut region)?;
+        let _ = self.physics_mode.visit("PhysicsMode", &mut region);
+        if region.is_reading() {
+            self.set_physics_mode(self.physics_mode);
+        }
         let _ = self.lightmap.visit("Lightmap", &mut region);
 
         Ok(())
@@ -1558,6 +1836,7 @@ impl BaseSceneGraph for Graph {
         node.script_message_sender = Some(sender);
 
         self.instance_id_map.insert(node.instance_id, handle);
+        self.node_index.insert(handle, node);
 
         handle
     }
@@ -1576,6 +1855,8 @@ impl BaseSceneGraph for Graph {
             // Remove associated entities.
             let mut node = self.pool.free(handle);
             self.instance_id_map.remove(&node.instance_id);
+            self.node_index.remove(handle);
+            self.spatial_index.remove(handle);
             node.on_removed_from_graph(self);
 
             self.event_broadcaster
@@ -1649,8 +1930,9 @@ mod test {
     use crate::{
         asset::{io::FsResourceIo, manager::ResourceManager},
         core::{
-            algebra::{Matrix4, Vector3},
+            algebra::{Matrix4, Vector2, Vector3},
             futures::executor::block_on,
+            math::{aabb::AxisAlignedBoundingBox, ray::Ray},
             pool::Handle,
             reflect::prelude::*,
             type_traits::prelude::*,
@@ -1664,7 +1946,7 @@ mod test {
             graph::Graph,
             mesh::{
                 surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
-                MeshBuilder,
+                Mesh, MeshBuilder,
             },
             node::Node,
             pivot::{Pivot, PivotBuilder},
@@ -1877,6 +2159,34 @@ mod test {
         scene
     }
 
+    #[test]
+    fn test_extract_as_prefab() {
+        let mut graph = Graph::new();
+        let child = PivotBuilder::new(BaseBuilder::new().with_name("Child")).build(&mut graph);
+        let parent = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("Parent")
+                .with_children(&[child]),
+        )
+        .build(&mut graph);
+
+        let mut other_graph = Graph::new();
+        let (copy, mapping) = graph.copy_subtree_to(parent, &mut other_graph);
+        assert_eq!(other_graph[copy].name(), "Parent");
+        assert_eq!(other_graph[copy].parent(), other_graph.get_root());
+        let mut child_copy = child;
+        assert!(mapping.try_map(&mut child_copy));
+        assert_eq!(other_graph[child_copy].parent(), copy);
+
+        let prefab = graph.extract_as_prefab(parent);
+        assert_eq!(prefab.data_ref().get_scene().graph.node_count(), 2);
+
+        let mut scene = Scene::new();
+        let instance = prefab.instantiate(&mut scene);
+        assert_eq!(scene.graph[instance].name(), "Parent");
+        assert_eq!(scene.graph[instance].children().len(), 1);
+    }
+
     fn save_scene(scene: &mut Scene, path: &Path) {
         let mut visitor = Visitor::new();
         scene.save("Scene", &mut visitor).unwrap();
@@ -1987,4 +2297,151 @@ mod test {
                 .unwrap();
         }
     }
+
+    #[test]
+    fn test_graph_queries() {
+        let mut graph = Graph::new();
+        let pivot_at = |graph: &mut Graph, x: f32, tag: &str| {
+            PivotBuilder::new(
+                BaseBuilder::new()
+                    .with_tag(tag.to_string())
+                    .with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position(Vector3::new(x, 0.0, 0.0))
+                            .build(),
+                    ),
+            )
+            .build(graph)
+        };
+        let a = pivot_at(&mut graph, 0.0, "enemy");
+        let b = pivot_at(&mut graph, 20.0, "enemy");
+        let c = pivot_at(&mut graph, 5.0, "");
+        let mesh = MeshBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(10.0, 0.0, 0.0))
+                    .build(),
+            ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            SurfaceData::make_cube(Matrix4::identity()),
+        ))
+        .build()])
+        .build(&mut graph);
+
+        let mut enemies = graph
+            .find_by_tag("enemy")
+            .map(|(h, _)| h)
+            .collect::<Vec<_>>();
+        enemies.sort();
+        assert_eq!(enemies, [a, b]);
+        assert_eq!(
+            graph
+                .typed_iter::<Mesh>()
+                .map(|(h, _)| h)
+                .collect::<Vec<_>>(),
+            [mesh]
+        );
+        assert_eq!(graph.typed_iter::<Pivot>().count(), 4);
+
+        graph.update_hierarchical_data();
+        graph.update_query_index();
+
+        let mut results = Vec::new();
+        graph.query_aabb(
+            &AxisAlignedBoundingBox::from_min_max(
+                Vector3::new(8.0, -1.0, -1.0),
+                Vector3::new(12.0, 1.0, 1.0),
+            ),
+            &mut results,
+        );
+        assert_eq!(results, [mesh]);
+
+        let mut hits = Vec::new();
+        graph.query_ray(
+            &Ray::from_two_points(Vector3::new(30.0, 0.0, 0.0), Vector3::new(-30.0, 0.0, 0.0)),
+            &mut hits,
+        );
+        assert_eq!(
+            hits.iter().map(|hit| hit.node).collect::<Vec<_>>(),
+            [b, mesh, c, a]
+        );
+
+        // Move the mesh away and change tags.
+        graph[mesh]
+            .local_transform_mut()
+            .set_position(Vector3::new(100.0, 0.0, 0.0));
+        graph[c].set_tag("enemy".to_string());
+        graph.remove_node(b);
+        graph.update_hierarchical_data();
+        graph.update_query_index();
+
+        results.clear();
+        graph.query_aabb(
+            &AxisAlignedBoundingBox::from_min_max(
+                Vector3::new(-1.0, -1.0, -1.0),
+                Vector3::new(150.0, 1.0, 1.0),
+            ),
+            &mut results,
+        );
+        results.sort();
+        let mut expected = vec![a, c, mesh];
+        expected.sort();
+        assert_eq!(results, expected);
+        assert_eq!(graph.spatial_index().len(), 3);
+
+        let mut enemies = graph
+            .find_by_tag("enemy")
+            .map(|(h, _)| h)
+            .collect::<Vec<_>>();
+        enemies.sort();
+        assert_eq!(enemies, [a, c]);
+    }
+
+    #[test]
+    fn test_physics_mode() {
+        use crate::scene::{
+            dim2::rigidbody::{RigidBody as RigidBody2D, RigidBodyBuilder as RigidBody2DBuilder},
+            graph::PhysicsMode,
+            rigidbody::{RigidBody, RigidBodyBuilder},
+        };
+        use rapier3d::dynamics::RigidBodyHandle;
+
+        let mut graph = Graph::new();
+        let body = RigidBodyBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let body2d = RigidBody2DBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        let natives = |graph: &Graph| {
+            (
+                graph[body].cast::<RigidBody>().unwrap().native.get(),
+                graph[body2d].cast::<RigidBody2D>().unwrap().native.get(),
+            )
+        };
+
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        let (native, native2d) = natives(&graph);
+        assert_ne!(native, RigidBodyHandle::invalid());
+        assert_ne!(native2d, rapier2d::dynamics::RigidBodyHandle::invalid());
+
+        assert_eq!(
+            graph.set_physics_mode(PhysicsMode::Only2D),
+            PhysicsMode::Full
+        );
+        assert!(!graph.physics.is_active());
+        assert!(graph.physics2d.is_active());
+
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        let (native, native2d) = natives(&graph);
+        assert_eq!(native, RigidBodyHandle::invalid());
+        assert_ne!(native2d, rapier2d::dynamics::RigidBodyHandle::invalid());
+
+        let (copy, _) = graph.clone(
+            graph.get_root(),
+            &mut |_, _| true,
+            &mut |_, _| {},
+            &mut |_, _, _| {},
+        );
+        assert_eq!(copy.physics_mode(), PhysicsMode::Only2D);
+    }
 }
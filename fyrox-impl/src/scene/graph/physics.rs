@@ -3,8 +3,8 @@
 use crate::{
     core::{
         algebra::{
-            DMatrix, Dyn, Isometry3, Matrix4, Point3, Translation, Translation3, UnitQuaternion,
-            UnitVector3, VecStorage, Vector2, Vector3,
+            DMatrix, Isometry3, Matrix4, Point3, Translation, Translation3, UnitQuaternion,
+            UnitVector3, Vector2, Vector3,
         },
         arrayvec::ArrayVec,
         instant,
@@ -56,11 +56,15 @@ use std::{
     fmt::{Debug, Formatter},
     hash::Hash,
     num::NonZeroUsize,
-    sync::Arc,
+    sync::{
+        mpsc::{channel, Receiver, TryRecvError},
+        Arc,
+    },
     time::Duration,
 };
 use strum_macros::{AsRefStr, EnumString, VariantNames};
 
+use fxhash::FxHashMap;
 use fyrox_graph::{BaseSceneGraph, SceneGraphNode};
 pub use rapier3d::geometry::shape::*;
 
@@ -619,61 +623,258 @@ fn make_polyhedron_shape(owner_inv_transform: Matrix4<f32>, mesh: &Mesh) -> Shar
     SharedShape::convex_decomposition(&vertices, &indices)
 }
 
-/// Creates height field shape from given terrain.
-fn make_heightfield(terrain: &Terrain) -> SharedShape {
-    assert!(!terrain.chunks_ref().is_empty());
-
-    // HACK: Temporary solution for https://github.com/FyroxEngine/Fyrox/issues/365
-    let scale = terrain.local_transform().scale();
-
-    // Count rows and columns.
-    let height_map_size = terrain.height_map_size();
-    let nrows = height_map_size.y * terrain.length_chunks().len() as u32;
-    let ncols = height_map_size.x * terrain.width_chunks().len() as u32;
-
-    // Combine height map of each chunk into bigger one.
-    let mut ox = 0;
-    let mut oz = 0;
-    let mut data = vec![0.0; (nrows * ncols) as usize];
-    for cz in 0..terrain.length_chunks().len() {
-        for cx in 0..terrain.width_chunks().len() {
-            let chunk = &terrain.chunks_ref()[cz * terrain.width_chunks().len() + cx];
-            let texture = chunk.heightmap().data_ref();
-            let height_map = texture.data_of_type::<f32>().unwrap();
-            for iy in 0..height_map_size.y {
-                for ix in 0..height_map_size.x {
-                    let value = height_map[(iy * height_map_size.x + ix) as usize] * scale.y;
-                    data[((ox + ix) * nrows + oz + iy) as usize] = value;
+/// Resamples the given height map, so its resolution is reduced `2^lod` times. Samples on the
+/// edges of the height map are preserved, so the neighbour chunks stay seamless.
+fn downsample_height_map(
+    height_map: &[f32],
+    size: Vector2<u32>,
+    lod: u32,
+) -> (Vec<f32>, Vector2<u32>) {
+    if lod == 0 {
+        return (height_map.to_vec(), size);
+    }
+
+    let last = size.map(|s| s.saturating_sub(1));
+    let new_size = last.map(|s| (s >> lod.min(31)).max(1) + 1);
+    let pixel = |x: u32, y: u32| {
+        height_map
+            .get((y * size.x + x) as usize)
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    let mut data = Vec::with_capacity((new_size.x * new_size.y) as usize);
+    for iy in 0..new_size.y {
+        let y = iy as f32 * last.y as f32 / (new_size.y - 1) as f32;
+        let (y0, ty) = (y.floor() as u32, y.fract());
+        let y1 = (y0 + 1).min(last.y);
+        for ix in 0..new_size.x {
+            let x = ix as f32 * last.x as f32 / (new_size.x - 1) as f32;
+            let (x0, tx) = (x.floor() as u32, x.fract());
+            let x1 = (x0 + 1).min(last.x);
+            let top = pixel(x0, y0) + (pixel(x1, y0) - pixel(x0, y0)) * tx;
+            let bottom = pixel(x0, y1) + (pixel(x1, y1) - pixel(x0, y1)) * tx;
+            data.push(top + (bottom - top) * ty);
+        }
+    }
+
+    (data, new_size)
+}
+
+/// Returns the lowest level of detail, at which a chunk has at most 9x9 samples. It is used for
+/// coarse approximation of a terrain while the actual shape is being built.
+fn coarse_heightfield_lod(height_map_size: Vector2<u32>) -> u32 {
+    let mut lod = 0;
+    while (height_map_size.x.max(height_map_size.y).saturating_sub(1) >> lod) > 8 {
+        lod += 1;
+    }
+    lod
+}
+
+struct HeightfieldPart {
+    heights: DMatrix<f32>,
+    scale: Vector3<f32>,
+    translation: Vector3<f32>,
+}
+
+/// Everything, that is needed to build a height field shape of a terrain. The data is copied from
+/// the terrain, so the shape could be built on another thread.
+struct HeightfieldData {
+    parts: Vec<HeightfieldPart>,
+}
+
+impl HeightfieldData {
+    fn new(terrain: &Terrain, per_chunk: bool, lod: u32) -> Option<Self> {
+        if terrain.chunks_ref().is_empty() {
+            return None;
+        }
+
+        // HACK: Temporary solution for https://github.com/FyroxEngine/Fyrox/issues/365
+        let scale = terrain.local_transform().scale();
+
+        let chunk_size = terrain.chunk_size();
+        let width_chunks = terrain.width_chunks();
+        let length_chunks = terrain.length_chunks();
+        let width = width_chunks.len();
+        let length = length_chunks.len();
+
+        let chunks = terrain
+            .chunks_ref()
+            .iter()
+            .map(|chunk| {
+                let texture = chunk.heightmap().data_ref();
+                let height_map = texture.data_of_type::<f32>().unwrap_or_default();
+                let (mut data, size) =
+                    downsample_height_map(height_map, chunk.height_map_size(), lod);
+                for height in data.iter_mut() {
+                    *height *= scale.y;
                 }
-            }
+                (data, size)
+            })
+            .collect::<Vec<_>>();
+
+        let parts = if per_chunk {
+            let total_size =
+                Vector2::new(chunk_size.x * width as f32, chunk_size.y * length as f32);
+            let center = Vector2::new(
+                width_chunks.start as f32 * chunk_size.x,
+                length_chunks.start as f32 * chunk_size.y,
+            ) + total_size.scale(0.5);
+
+            terrain
+                .chunks_ref()
+                .iter()
+                .zip(chunks)
+                .map(|(chunk, (data, size))| {
+                    let offset = chunk.local_position() + chunk_size.scale(0.5) - center;
+                    HeightfieldPart {
+                        heights: DMatrix::from_fn(size.y as usize, size.x as usize, |row, col| {
+                            data[row * size.x as usize + col]
+                        }),
+                        scale: Vector3::new(chunk_size.x * scale.x, 1.0, chunk_size.y * scale.z),
+                        translation: Vector3::new(offset.x * scale.x, 0.0, offset.y * scale.z),
+                    }
+                })
+                .collect()
+        } else {
+            // Combine height map of each chunk into bigger one. Chunks could have height maps of
+            // different sizes (impostors of streamed chunks), smaller ones are upsampled.
+            let size = chunks
+                .iter()
+                .fold(Vector2::repeat(1), |size, (_, s)| size.sup(s))
+                .map(|s| s as usize);
+            let remap = |i: usize, from: usize, to: usize| {
+                if from > 1 && to > 1 {
+                    (i as f32 * (to - 1) as f32 / (from - 1) as f32).round() as usize
+                } else {
+                    0
+                }
+            };
+            vec![HeightfieldPart {
+                heights: DMatrix::from_fn(size.y * length, size.x * width, |row, col| {
+                    let (cz, iy) = (row / size.y, row % size.y);
+                    let (cx, ix) = (col / size.x, col % size.x);
+                    chunks
+                        .get(cz * width + cx)
+                        .and_then(|(data, chunk_size)| {
+                            let iy = remap(iy, size.y, chunk_size.y as usize);
+                            let ix = remap(ix, size.x, chunk_size.x as usize);
+                            data.get(iy * chunk_size.x as usize + ix)
+                        })
+                        .cloned()
+                        .unwrap_or_default()
+                }),
+                scale: Vector3::new(
+                    chunk_size.x * scale.x * width as f32,
+                    1.0,
+                    chunk_size.y * scale.z * length as f32,
+                ),
+                translation: Default::default(),
+            }]
+        };
+
+        Some(Self { parts })
+    }
+
+    /// Builds the shape. This could be a heavy operation for large terrains.
+    fn cook(self) -> SharedShape {
+        let mut shapes = self
+            .parts
+            .into_iter()
+            .map(|part| {
+                (
+                    Isometry3::translation(
+                        part.translation.x,
+                        part.translation.y,
+                        part.translation.z,
+                    ),
+                    SharedShape::heightfield(part.heights, part.scale),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if shapes.len() == 1 && shapes[0].0 == Isometry3::identity() {
+            shapes.pop().unwrap().1
+        } else {
+            SharedShape::compound(shapes)
+        }
+    }
+}
+
+/// Builds height field shapes on the worker threads of the global thread pool. The shapes are
+/// swapped in by the physics world as soon as they are ready.
+#[derive(Default)]
+struct HeightfieldCooker {
+    pending: FxHashMap<Handle<Node>, Receiver<SharedShape>>,
+}
+
+impl HeightfieldCooker {
+    fn cook(&mut self, collider: Handle<Node>, data: HeightfieldData) {
+        let (sender, receiver) = channel();
+        let task = move || {
+            // The receiver could be dropped if a newer shape was requested.
+            let _ = sender.send(data.cook());
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            rayon::spawn(task);
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            task();
+        }
+
+        // A newer request replaces the old one, so an outdated shape will never be swapped in.
+        self.pending.insert(collider, receiver);
+    }
 
-            ox += height_map_size.x;
+    fn poll(&mut self, collider: Handle<Node>) -> Option<SharedShape> {
+        let result = self.pending.get(&collider)?.try_recv();
+        match result {
+            Ok(shape) => {
+                self.pending.remove(&collider);
+                Some(shape)
+            }
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                self.pending.remove(&collider);
+                None
+            }
         }
+    }
 
-        ox = 0;
-        oz += height_map_size.y;
+    fn cancel(&mut self, collider: Handle<Node>) {
+        self.pending.remove(&collider);
     }
 
-    SharedShape::heightfield(
-        DMatrix::from_data(VecStorage::new(
-            Dyn(nrows as usize),
-            Dyn(ncols as usize),
-            data,
-        )),
-        Vector3::new(
-            terrain.chunk_size().x * scale.x * terrain.width_chunks().len() as f32,
-            1.0,
-            terrain.chunk_size().y * scale.z * terrain.length_chunks().len() as f32,
-        ),
-    )
+    fn is_cooking(&self, collider: Handle<Node>) -> bool {
+        self.pending.contains_key(&collider)
+    }
 }
 
-// Converts descriptor in a shared shape.
+/// Returns version of the geometry source of the given shape. Only heightfields have versioned
+/// geometry sources at the moment.
+fn geometry_source_version(nodes: &NodePool, shape: &ColliderShape) -> u64 {
+    match shape {
+        ColliderShape::Heightfield(heightfield) => nodes
+            .try_borrow(heightfield.geometry_source.0)
+            .and_then(|n| n.cast::<Terrain>())
+            .map_or(0, |terrain| terrain.height_map_version()),
+        _ => 0,
+    }
+}
+
+// Converts descriptor in a shared shape. `has_native_shape` tells whether the native collider
+// already has a shape, that could be used until an asynchronously cooked shape is ready.
 fn collider_shape_into_native_shape(
     shape: &ColliderShape,
     owner_inv_global_transform: Matrix4<f32>,
     owner_collider: Handle<Node>,
     pool: &NodePool,
+    cooker: &mut HeightfieldCooker,
+    has_native_shape: bool,
 ) -> Option<SharedShape> {
     match shape {
         ColliderShape::Ball(ball) => Some(SharedShape::ball(ball.radius)),
@@ -711,10 +912,29 @@ fn collider_shape_into_native_shape(
                 ))
             }
         }
-        ColliderShape::Heightfield(heightfield) => pool
-            .try_borrow(heightfield.geometry_source.0)
-            .and_then(|n| n.cast::<Terrain>())
-            .map(make_heightfield),
+        ColliderShape::Heightfield(heightfield) => {
+            let terrain = pool
+                .try_borrow(heightfield.geometry_source.0)
+                .and_then(|n| n.cast::<Terrain>())?;
+            let data = HeightfieldData::new(terrain, heightfield.per_chunk, heightfield.lod)?;
+            if heightfield.async_cooking {
+                cooker.cook(owner_collider, data);
+                if has_native_shape {
+                    // Keep the current shape until the actual one is built, otherwise every
+                    // modification of the terrain would drop the collider to a low resolution.
+                    return None;
+                }
+                // Use coarse approximation of the terrain until the actual shape is built.
+                let coarse_lod = heightfield
+                    .lod
+                    .max(coarse_heightfield_lod(terrain.height_map_size()));
+                HeightfieldData::new(terrain, heightfield.per_chunk, coarse_lod)
+                    .map(HeightfieldData::cook)
+            } else {
+                cooker.cancel(owner_collider);
+                Some(data.cook())
+            }
+        }
         ColliderShape::Polyhedron(polyhedron) => pool
             .try_borrow(polyhedron.geometry_source.0)
             .and_then(|n| n.cast::<Mesh>())
@@ -961,6 +1181,12 @@ pub struct PhysicsWorld {
     #[visit(skip)]
     #[reflect(hidden)]
     debug_render_pipeline: Mutex<DebugRenderPipeline>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    active: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    heightfield_cooker: HeightfieldCooker,
 }
 
 fn isometry_from_global_transform(transform: &Matrix4<f32>) -> Isometry3<f32> {
@@ -1060,13 +1286,31 @@ impl PhysicsWorld {
             query: RefCell::new(Default::default()),
             performance_statistics: Default::default(),
             debug_render_pipeline: Default::default(),
+            active: true,
+            heightfield_cooker: Default::default(),
         }
     }
 
+    /// Returns `true` if a height field shape of the given collider is being built on a background
+    /// thread. See [`collider::HeightfieldShape::async_cooking`] for more info.
+    pub fn is_cooking_heightfield(&self, collider: Handle<Node>) -> bool {
+        self.heightfield_cooker.is_cooking(collider)
+    }
+
+    /// Returns `true` if the 3D physics world is active in its scene. An inactive world is never
+    /// stepped and does not hold any native objects. See [`crate::scene::graph::PhysicsMode`].
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub(crate) fn set_active(&mut self, active: bool) {
+        self.active = active;
+    }
+
     pub(super) fn update(&mut self, dt: f32) {
         let time = instant::Instant::now();
 
-        if *self.enabled {
+        if *self.enabled && self.active {
             let integration_parameters = rapier3d::dynamics::IntegrationParameters {
                 dt: self.integration_parameters.dt.unwrap_or(dt),
                 min_ccd_dt: self.integration_parameters.min_ccd_dt,
@@ -1398,7 +1642,7 @@ impl PhysicsWorld {
         handle: Handle<Node>,
         rigid_body_node: &scene::rigidbody::RigidBody,
     ) {
-        if !rigid_body_node.is_globally_enabled() {
+        if !self.active || !rigid_body_node.is_globally_enabled() {
             self.remove_body(rigid_body_node.native.get());
             rigid_body_node.native.set(Default::default());
             return;
@@ -1559,14 +1803,30 @@ impl PhysicsWorld {
         handle: Handle<Node>,
         collider_node: &scene::collider::Collider,
     ) {
-        if !collider_node.is_globally_enabled() {
+        if !self.active || !collider_node.is_globally_enabled() {
             self.remove_collider(collider_node.native.get());
             collider_node.native.set(Default::default());
+            // Nobody will consume the heightfield, that is being cooked for the removed collider.
+            self.heightfield_cooker.cancel(handle);
             return;
         }
 
-        let anything_changed =
-            collider_node.transform_modified.get() || collider_node.needs_sync_model();
+        // Swap in a height field shape, that was built on a background thread.
+        if let Some(shape) = self.heightfield_cooker.poll(handle) {
+            if let Some(native) = self.colliders.get_mut(collider_node.native.get()) {
+                native.set_shape(shape);
+            }
+        }
+
+        // Terrains could be modified at runtime, so heightfield shapes must be rebuilt when the
+        // height map of the source terrain changes.
+        let geometry_source_version = geometry_source_version(nodes, &collider_node.shape);
+        let geometry_source_changed =
+            collider_node.geometry_source_version.get() != geometry_source_version;
+
+        let anything_changed = collider_node.transform_modified.get()
+            || collider_node.needs_sync_model()
+            || geometry_source_changed;
 
         // Important notes!
         // 1) The collider node may lack backing native physics collider in case if it
@@ -1585,6 +1845,25 @@ impl PhysicsWorld {
                         });
                     }
 
+                    if geometry_source_changed && !collider_node.shape.need_sync() {
+                        let inv_global_transform = isometric_global_transform(nodes, handle)
+                            .try_inverse()
+                            .unwrap();
+                        if let Some(shape) = collider_shape_into_native_shape(
+                            &collider_node.shape,
+                            inv_global_transform,
+                            handle,
+                            nodes,
+                            &mut self.heightfield_cooker,
+                            true,
+                        ) {
+                            native.set_shape(shape);
+                        }
+                    }
+                    collider_node
+                        .geometry_source_version
+                        .set(geometry_source_version);
+
                     collider_node.shape.try_sync_model(|v| {
                         let inv_global_transform = isometric_global_transform(nodes, handle)
                             .try_inverse()
@@ -1594,6 +1873,8 @@ impl PhysicsWorld {
                             inv_global_transform,
                             handle,
                             nodes,
+                            &mut self.heightfield_cooker,
+                            true,
                         ) {
                             native.set_shape(shape);
                         }
@@ -1641,6 +1922,8 @@ impl PhysicsWorld {
                     inv_global_transform,
                     handle,
                     nodes,
+                    &mut self.heightfield_cooker,
+                    false,
                 ) {
                     let mut builder = ColliderBuilder::new(shape)
                         .position(Isometry3 {
@@ -1671,6 +1954,9 @@ impl PhysicsWorld {
                         self.add_collider(handle, rigid_body_native, builder.build());
 
                     collider_node.native.set(native_handle);
+                    collider_node
+                        .geometry_source_version
+                        .set(geometry_source_version);
 
                     Log::writeln(
                         MessageKind::Information,
@@ -1690,7 +1976,7 @@ impl PhysicsWorld {
         handle: Handle<Node>,
         joint: &scene::joint::Joint,
     ) {
-        if !joint.is_globally_enabled() {
+        if !self.active || !joint.is_globally_enabled() {
             self.remove_joint(joint.native.get());
             joint.native.set(ImpulseJointHandle(Default::default()));
             return;
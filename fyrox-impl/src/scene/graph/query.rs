@@ -0,0 +1,398 @@
+//! Acceleration structures for scene graph queries. See [`SpatialIndex`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::Vector3,
+        math::{aabb::AxisAlignedBoundingBox, ray::Ray},
+        pool::{Handle, Pool},
+    },
+    scene::{graph::NodePool, node::Node},
+};
+use fxhash::{FxHashMap, FxHashSet};
+use std::any::TypeId;
+
+/// Bounding boxes of the leaves of the tree are inflated by this value, this way small movements
+/// of the nodes won't cause re-insertion.
+const BOUNDS_MARGIN: f32 = 0.1;
+
+#[derive(Clone, Debug)]
+enum BvhNodeKind {
+    Leaf(Handle<Node>),
+    Branch([Handle<BvhNode>; 2]),
+}
+
+#[derive(Clone, Debug)]
+struct BvhNode {
+    bounds: AxisAlignedBoundingBox,
+    parent: Handle<BvhNode>,
+    kind: BvhNodeKind,
+}
+
+/// A result of [`SpatialIndex::query_ray`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpatialRayHit {
+    /// A handle of the node, whose bounding box was intersected by the ray.
+    pub node: Handle<Node>,
+    /// A parameter of the ray equation at the entry point to the bounding box of the node. It is
+    /// in `0.0..1.0` range, the actual point could be calculated using [`Ray::get_point`].
+    pub toi: f32,
+}
+
+fn surface_area(aabb: &AxisAlignedBoundingBox) -> f32 {
+    let size = aabb.max - aabb.min;
+    2.0 * (size.x * size.y + size.y * size.z + size.z * size.x)
+}
+
+fn union(a: &AxisAlignedBoundingBox, b: &AxisAlignedBoundingBox) -> AxisAlignedBoundingBox {
+    AxisAlignedBoundingBox::from_min_max(a.min.inf(&b.min), a.max.sup(&b.max))
+}
+
+fn contains(outer: &AxisAlignedBoundingBox, inner: &AxisAlignedBoundingBox) -> bool {
+    outer.min <= inner.min && outer.max >= inner.max
+}
+
+/// Spatial index is a persistent bounding volume hierarchy (dynamic AABB tree) of world-space
+/// bounding boxes of scene nodes. It allows you to find nodes in an area or along a ray without
+/// iterating over every node of a graph.
+///
+/// The index is updated incrementally by [`super::Graph::update`] right after global transforms
+/// of the nodes are calculated. Every leaf of the tree stores a slightly inflated bounding box of
+/// a node, so only the nodes that moved out of their inflated bounding boxes are re-inserted.
+/// This means that query results could contain a few nodes, whose actual bounding boxes are
+/// slightly off the query area. Bounding boxes of the nodes are calculated by transforming their
+/// local bounding boxes, nodes without bounding box (pivots, for example) are represented by their
+/// global position.
+#[derive(Default, Clone, Debug)]
+pub struct SpatialIndex {
+    nodes: Pool<BvhNode>,
+    root: Handle<BvhNode>,
+    leaves: FxHashMap<Handle<Node>, Handle<BvhNode>>,
+}
+
+impl SpatialIndex {
+    /// Returns the amount of scene nodes in the index.
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Returns `true` if there's no scene nodes in the index.
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Returns `true` if the given scene node is in the index.
+    pub fn contains(&self, node: Handle<Node>) -> bool {
+        self.leaves.contains_key(&node)
+    }
+
+    /// Returns the inflated bounding box, that is stored in the index for the given scene node.
+    pub fn fat_bounds(&self, node: Handle<Node>) -> Option<AxisAlignedBoundingBox> {
+        self.leaves.get(&node).map(|leaf| self.nodes[*leaf].bounds)
+    }
+
+    /// Removes every scene node from the index.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.leaves.clear();
+        self.root = Handle::NONE;
+    }
+
+    /// Inserts a scene node with the given world-space bounding box into the index or updates the
+    /// bounding box of an existing node. Returns `true` if the tree was modified.
+    pub fn update(&mut self, node: Handle<Node>, bounds: AxisAlignedBoundingBox) -> bool {
+        if let Some(&leaf) = self.leaves.get(&node) {
+            if contains(&self.nodes[leaf].bounds, &bounds) {
+                return false;
+            }
+            self.remove_leaf(leaf);
+            self.nodes.free(leaf);
+        }
+
+        let mut fat_bounds = bounds;
+        fat_bounds.inflate(Vector3::repeat(2.0 * BOUNDS_MARGIN));
+        let leaf = self.nodes.spawn(BvhNode {
+            bounds: fat_bounds,
+            parent: Handle::NONE,
+            kind: BvhNodeKind::Leaf(node),
+        });
+        self.leaves.insert(node, leaf);
+        self.insert_leaf(leaf);
+        true
+    }
+
+    /// Removes the given scene node from the index. Returns `true` if the node was in the index.
+    pub fn remove(&mut self, node: Handle<Node>) -> bool {
+        match self.leaves.remove(&node) {
+            Some(leaf) => {
+                self.remove_leaf(leaf);
+                self.nodes.free(leaf);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn insert_leaf(&mut self, leaf: Handle<BvhNode>) {
+        if self.root.is_none() {
+            self.root = leaf;
+            return;
+        }
+
+        // Find the best sibling for the new leaf using the surface area heuristic.
+        let leaf_bounds = self.nodes[leaf].bounds;
+        let mut sibling = self.root;
+        while let BvhNodeKind::Branch(children) = self.nodes[sibling].kind {
+            let bounds = self.nodes[sibling].bounds;
+            let area = surface_area(&bounds);
+            let combined_area = surface_area(&union(&bounds, &leaf_bounds));
+
+            // Cost of creating a new parent for this node and the new leaf.
+            let cost = 2.0 * combined_area;
+            // Minimum cost of pushing the leaf further down the tree.
+            let inheritance_cost = 2.0 * (combined_area - area);
+
+            let child_cost = |child: Handle<BvhNode>| {
+                let child = &self.nodes[child];
+                let combined = surface_area(&union(&child.bounds, &leaf_bounds));
+                match child.kind {
+                    BvhNodeKind::Leaf(_) => combined + inheritance_cost,
+                    BvhNodeKind::Branch(_) => {
+                        combined - surface_area(&child.bounds) + inheritance_cost
+                    }
+                }
+            };
+
+            let cost_a = child_cost(children[0]);
+            let cost_b = child_cost(children[1]);
+
+            if cost < cost_a && cost < cost_b {
+                break;
+            }
+
+            sibling = if cost_a < cost_b {
+                children[0]
+            } else {
+                children[1]
+            };
+        }
+
+        let old_parent = self.nodes[sibling].parent;
+        let new_parent = self.nodes.spawn(BvhNode {
+            bounds: union(&self.nodes[sibling].bounds, &leaf_bounds),
+            parent: old_parent,
+            kind: BvhNodeKind::Branch([sibling, leaf]),
+        });
+
+        if old_parent.is_some() {
+            self.replace_child(old_parent, sibling, new_parent);
+        } else {
+            self.root = new_parent;
+        }
+        self.nodes[sibling].parent = new_parent;
+        self.nodes[leaf].parent = new_parent;
+
+        self.refit(old_parent);
+    }
+
+    fn remove_leaf(&mut self, leaf: Handle<BvhNode>) {
+        if leaf == self.root {
+            self.root = Handle::NONE;
+            return;
+        }
+
+        let parent = self.nodes[leaf].parent;
+        let grand_parent = self.nodes[parent].parent;
+        let BvhNodeKind::Branch(children) = self.nodes[parent].kind else {
+            unreachable!("Parent of a leaf must be a branch!")
+        };
+        let sibling = if children[0] == leaf {
+            children[1]
+        } else {
+            children[0]
+        };
+
+        self.nodes.free(parent);
+        self.nodes[sibling].parent = grand_parent;
+        if grand_parent.is_some() {
+            self.replace_child(grand_parent, parent, sibling);
+            self.refit(grand_parent);
+        } else {
+            self.root = sibling;
+        }
+    }
+
+    fn replace_child(
+        &mut self,
+        branch: Handle<BvhNode>,
+        old_child: Handle<BvhNode>,
+        new_child: Handle<BvhNode>,
+    ) {
+        if let BvhNodeKind::Branch(ref mut children) = self.nodes[branch].kind {
+            for child in children.iter_mut() {
+                if *child == old_child {
+                    *child = new_child;
+                }
+            }
+        }
+    }
+
+    // Recalculates bounds of the branches starting from the given one up to the root.
+    fn refit(&mut self, mut branch: Handle<BvhNode>) {
+        while branch.is_some() {
+            if let BvhNodeKind::Branch([a, b]) = self.nodes[branch].kind {
+                self.nodes[branch].bounds = union(&self.nodes[a].bounds, &self.nodes[b].bounds);
+            }
+            branch = self.nodes[branch].parent;
+        }
+    }
+
+    fn visit_overlapping(
+        &self,
+        mut overlaps: impl FnMut(&AxisAlignedBoundingBox) -> bool,
+        mut on_leaf: impl FnMut(Handle<Node>, &AxisAlignedBoundingBox),
+    ) {
+        if self.root.is_none() {
+            return;
+        }
+
+        let mut stack = vec![self.root];
+        while let Some(handle) = stack.pop() {
+            let node = &self.nodes[handle];
+            if !overlaps(&node.bounds) {
+                continue;
+            }
+            match node.kind {
+                BvhNodeKind::Leaf(scene_node) => on_leaf(scene_node, &node.bounds),
+                BvhNodeKind::Branch(children) => stack.extend_from_slice(&children),
+            }
+        }
+    }
+
+    /// Finds every scene node, whose bounding box intersects the given world-space bounding box.
+    /// The results are appended to the given buffer.
+    pub fn query_aabb(&self, aabb: &AxisAlignedBoundingBox, results: &mut Vec<Handle<Node>>) {
+        self.visit_overlapping(
+            |bounds| bounds.is_intersects_aabb(aabb),
+            |node, _| results.push(node),
+        );
+    }
+
+    /// Finds every scene node, whose bounding box is intersected by the given ray. The ray is
+    /// treated as a segment from `ray.origin` to `ray.origin + ray.dir`. The results are sorted
+    /// by the distance from the origin of the ray and appended to the given buffer.
+    pub fn query_ray(&self, ray: &Ray, results: &mut Vec<SpatialRayHit>) {
+        let start = results.len();
+        self.visit_overlapping(
+            |bounds| ray.aabb_intersection(bounds).is_some(),
+            |node, bounds| {
+                if let Some(intersection) = ray.aabb_intersection(bounds) {
+                    results.push(SpatialRayHit {
+                        node,
+                        toi: intersection.min.max(0.0),
+                    });
+                }
+            },
+        );
+        results[start..].sort_by(|a, b| a.toi.total_cmp(&b.toi));
+    }
+
+    /// Synchronizes the index with the world-space bounding boxes of the given nodes. Nodes, that
+    /// are no longer exist, are removed from the index.
+    pub(crate) fn sync(&mut self, pool: &NodePool, root: Handle<Node>) {
+        let stale = self
+            .leaves
+            .keys()
+            .filter(|&&handle| !pool.is_valid_handle(handle))
+            .cloned()
+            .collect::<Vec<_>>();
+        for handle in stale {
+            self.remove(handle);
+        }
+
+        for (handle, node) in pool.pair_iter() {
+            if handle == root {
+                continue;
+            }
+
+            let local_bounds = node.local_bounding_box();
+            // Cached world-space bounding boxes of some nodes lag one frame behind their global
+            // transforms, so the bounding box is calculated here.
+            let bounds = if local_bounds.is_valid() {
+                local_bounds.transform(&node.global_transform())
+            } else {
+                AxisAlignedBoundingBox::from_point(node.global_position())
+            };
+            self.update(handle, bounds);
+        }
+    }
+}
+
+/// An index of scene nodes by their types and tags.
+#[derive(Default, Debug)]
+pub(crate) struct NodeIndex {
+    by_type: FxHashMap<TypeId, FxHashSet<Handle<Node>>>,
+    by_tag: FxHashMap<String, FxHashSet<Handle<Node>>>,
+    entries: FxHashMap<Handle<Node>, (TypeId, String)>,
+}
+
+impl NodeIndex {
+    pub(crate) fn insert(&mut self, handle: Handle<Node>, node: &Node) {
+        let type_id = node.as_any_ref().type_id();
+        let tag = node.tag_owned();
+        self.by_type.entry(type_id).or_default().insert(handle);
+        self.by_tag.entry(tag.clone()).or_default().insert(handle);
+        self.entries.insert(handle, (type_id, tag));
+    }
+
+    pub(crate) fn remove(&mut self, handle: Handle<Node>) {
+        if let Some((type_id, tag)) = self.entries.remove(&handle) {
+            if let Some(set) = self.by_type.get_mut(&type_id) {
+                set.remove(&handle);
+            }
+            if let Some(set) = self.by_tag.get_mut(&tag) {
+                set.remove(&handle);
+                if set.is_empty() {
+                    self.by_tag.remove(&tag);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn of_type(&self, type_id: TypeId) -> impl Iterator<Item = Handle<Node>> + '_ {
+        self.by_type
+            .get(&type_id)
+            .into_iter()
+            .flat_map(|set| set.iter().cloned())
+    }
+
+    pub(crate) fn with_tag(&self, tag: &str) -> impl Iterator<Item = Handle<Node>> + '_ {
+        self.by_tag
+            .get(tag)
+            .into_iter()
+            .flat_map(|set| set.iter().cloned())
+    }
+
+    /// Registers new nodes, removes deleted ones and updates tags of the nodes, that were changed.
+    pub(crate) fn sync(&mut self, pool: &NodePool) {
+        let stale = self
+            .entries
+            .keys()
+            .filter(|&&handle| !pool.is_valid_handle(handle))
+            .cloned()
+            .collect::<Vec<_>>();
+        for handle in stale {
+            self.remove(handle);
+        }
+
+        for (handle, node) in pool.pair_iter() {
+            match self.entries.get(&handle) {
+                Some((_, tag)) if tag == node.tag() => (),
+                Some(_) => {
+                    self.remove(handle);
+                    self.insert(handle, node);
+                }
+                None => self.insert(handle, node),
+            }
+        }
+    }
+}
@@ -0,0 +1,380 @@
+//! Slot-based container of items. See [`Inventory`] docs for more info.
+
+use crate::{
+    core::{reflect::prelude::*, uuid_provider, visitor::prelude::*},
+    resource::item::{ItemResource, ItemResourceExtension},
+};
+use std::{
+    collections::VecDeque,
+    fmt::{Display, Formatter},
+};
+
+/// A stack of items of the same kind in an inventory slot.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct ItemStack {
+    /// Kind of the items in the stack. `None` means that the slot is empty.
+    pub item: Option<ItemResource>,
+    /// Amount of items in the stack.
+    pub count: u32,
+}
+
+uuid_provider!(ItemStack = "d3b87c15-0e6a-4f29-9c4d-71a5e8f2b603");
+
+impl ItemStack {
+    /// Creates a new stack of items.
+    pub fn new(item: ItemResource, count: u32) -> Self {
+        Self {
+            item: Some(item),
+            count,
+        }
+    }
+
+    /// Returns `true` if the stack contains no items.
+    pub fn is_empty(&self) -> bool {
+        self.item.is_none() || self.count == 0
+    }
+
+    /// Returns `true` if the stack contains items of the given kind.
+    pub fn is_of(&self, item: &ItemResource) -> bool {
+        !self.is_empty() && self.item.as_ref() == Some(item)
+    }
+}
+
+/// An event, that is emitted when the content of an inventory changes. It could be used to
+/// update UI, that shows the inventory.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InventoryEvent {
+    /// Content of the slot with the given index has changed.
+    SlotChanged(usize),
+    /// Amount of slots of the inventory has changed.
+    CapacityChanged,
+}
+
+/// An error that may occur during inventory modification.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InventoryError {
+    /// There's no slot with the given index.
+    InvalidSlot(usize),
+    /// The slot has items of a different kind.
+    SlotOccupied(usize),
+}
+
+impl Display for InventoryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InventoryError::InvalidSlot(index) => write!(f, "There's no slot {index}."),
+            InventoryError::SlotOccupied(index) => {
+                write!(f, "Slot {index} contains items of a different kind.")
+            }
+        }
+    }
+}
+
+/// Inventory is a container with fixed amount of slots, where each slot could store a stack of
+/// items of the same kind. Maximum size of a stack is defined by
+/// [`crate::resource::item::ItemDefinition::max_stack_size`].
+///
+/// Inventory could be used as a field of a script, it is serialized and could be edited in the
+/// editor. Every change of the content produces an [`InventoryEvent`], that could be fetched by
+/// [`Inventory::pop_event`] to update UI.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{resource::item::ItemResource, scene::inventory::Inventory};
+/// fn pick_up(inventory: &mut Inventory, item: &ItemResource, count: u32) -> bool {
+///     // Everything that did not fit stays on the ground.
+///     let leftover = inventory.add(item, count);
+///     leftover == 0
+/// }
+/// ```
+#[derive(Clone, Debug, Default, Visit, Reflect)]
+pub struct Inventory {
+    slots: Vec<ItemStack>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    events: VecDeque<InventoryEvent>,
+}
+
+impl Inventory {
+    /// Creates a new inventory with the given amount of empty slots.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            slots: vec![Default::default(); capacity],
+            events: Default::default(),
+        }
+    }
+
+    /// Returns amount of slots of the inventory.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Changes amount of slots of the inventory. Returns non-empty stacks of the removed slots,
+    /// if the new capacity is less than the old one.
+    pub fn set_capacity(&mut self, capacity: usize) -> Vec<ItemStack> {
+        let removed = if capacity < self.slots.len() {
+            self.slots
+                .drain(capacity..)
+                .filter(|s| !s.is_empty())
+                .collect()
+        } else {
+            self.slots.resize(capacity, Default::default());
+            Vec::new()
+        };
+        self.events.push_back(InventoryEvent::CapacityChanged);
+        removed
+    }
+
+    /// Returns a reference to the slots of the inventory.
+    pub fn slots(&self) -> &[ItemStack] {
+        &self.slots
+    }
+
+    /// Returns a reference to a slot with the given index.
+    pub fn slot(&self, index: usize) -> Option<&ItemStack> {
+        self.slots.get(index)
+    }
+
+    /// Takes the oldest event from the internal queue.
+    pub fn pop_event(&mut self) -> Option<InventoryEvent> {
+        self.events.pop_front()
+    }
+
+    /// Returns total amount of items of the given kind in the inventory.
+    pub fn count(&self, item: &ItemResource) -> u32 {
+        self.slots
+            .iter()
+            .filter(|s| s.is_of(item))
+            .map(|s| s.count)
+            .sum()
+    }
+
+    /// Returns `true` if the inventory contains at least one item of the given kind.
+    pub fn contains(&self, item: &ItemResource) -> bool {
+        self.slots.iter().any(|s| s.is_of(item))
+    }
+
+    /// Tries to find a slot with items, which definition has the given id.
+    pub fn find_by_id(&self, id: &str) -> Option<usize> {
+        self.slots.iter().position(|s| {
+            !s.is_empty()
+                && s.item
+                    .as_ref()
+                    .and_then(|item| item.state().data().map(|d| d.definition.id == id))
+                    .unwrap_or_default()
+        })
+    }
+
+    fn put(&mut self, index: usize, item: &ItemResource, count: u32, max: u32) -> u32 {
+        let slot = &mut self.slots[index];
+        if slot.is_empty() {
+            *slot = ItemStack::new(item.clone(), 0);
+        } else if !slot.is_of(item) {
+            return count;
+        }
+
+        let amount = count.min(max.saturating_sub(slot.count));
+        if amount > 0 {
+            slot.count += amount;
+            self.events.push_back(InventoryEvent::SlotChanged(index));
+        } else if slot.count == 0 {
+            *slot = Default::default();
+        }
+        count - amount
+    }
+
+    /// Adds the given amount of items to the inventory. Existing stacks of the same items are
+    /// filled first, then the rest of the items are put in empty slots. Returns amount of items,
+    /// that did not fit in the inventory.
+    pub fn add(&mut self, item: &ItemResource, count: u32) -> u32 {
+        let max = item.max_stack_size();
+        let mut count = count;
+
+        for index in 0..self.slots.len() {
+            if count == 0 {
+                break;
+            }
+            if self.slots[index].is_of(item) {
+                count = self.put(index, item, count, max);
+            }
+        }
+
+        for index in 0..self.slots.len() {
+            if count == 0 {
+                break;
+            }
+            if self.slots[index].is_empty() {
+                count = self.put(index, item, count, max);
+            }
+        }
+
+        count
+    }
+
+    /// Adds the given amount of items to a slot with the given index. Returns amount of items,
+    /// that did not fit in the slot.
+    pub fn add_to_slot(
+        &mut self,
+        index: usize,
+        item: &ItemResource,
+        count: u32,
+    ) -> Result<u32, InventoryError> {
+        let slot = self
+            .slots
+            .get(index)
+            .ok_or(InventoryError::InvalidSlot(index))?;
+        if !slot.is_empty() && !slot.is_of(item) {
+            return Err(InventoryError::SlotOccupied(index));
+        }
+        Ok(self.put(index, item, count, item.max_stack_size()))
+    }
+
+    /// Removes the given amount of items of the given kind from the inventory. Returns amount of
+    /// items, that were actually removed.
+    pub fn remove(&mut self, item: &ItemResource, count: u32) -> u32 {
+        let mut removed = 0;
+        for index in (0..self.slots.len()).rev() {
+            if removed == count {
+                break;
+            }
+            if self.slots[index].is_of(item) {
+                removed += self.take_from(index, count - removed).count;
+            }
+        }
+        removed
+    }
+
+    fn take_from(&mut self, index: usize, count: u32) -> ItemStack {
+        let slot = &mut self.slots[index];
+        if slot.is_empty() || count == 0 {
+            return Default::default();
+        }
+
+        let amount = count.min(slot.count);
+        let taken = ItemStack {
+            item: slot.item.clone(),
+            count: amount,
+        };
+        slot.count -= amount;
+        if slot.count == 0 {
+            *slot = Default::default();
+        }
+        self.events.push_back(InventoryEvent::SlotChanged(index));
+        taken
+    }
+
+    /// Takes the given amount of items from a slot with the given index.
+    pub fn take(&mut self, index: usize, count: u32) -> Result<ItemStack, InventoryError> {
+        if index >= self.slots.len() {
+            return Err(InventoryError::InvalidSlot(index));
+        }
+        Ok(self.take_from(index, count))
+    }
+
+    /// Moves a stack of items from one slot to another. If the destination slot contains items of
+    /// the same kind, the stacks are merged (everything that did not fit stays in the source slot),
+    /// otherwise the content of the slots is swapped.
+    pub fn move_stack(&mut self, from: usize, to: usize) -> Result<(), InventoryError> {
+        for index in [from, to] {
+            if index >= self.slots.len() {
+                return Err(InventoryError::InvalidSlot(index));
+            }
+        }
+
+        if from == to || self.slots[from].is_empty() {
+            return Ok(());
+        }
+
+        let source = self.slots[from].clone();
+        if let Some(item) = source
+            .item
+            .as_ref()
+            .filter(|item| self.slots[to].is_of(item))
+        {
+            let leftover = self.put(to, item, source.count, item.max_stack_size());
+            self.take_from(from, source.count - leftover);
+        } else {
+            self.slots.swap(from, to);
+            self.events.push_back(InventoryEvent::SlotChanged(from));
+            self.events.push_back(InventoryEvent::SlotChanged(to));
+        }
+
+        Ok(())
+    }
+
+    /// Removes every item from the inventory.
+    pub fn clear(&mut self) {
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if !slot.is_empty() {
+                *slot = Default::default();
+                self.events.push_back(InventoryEvent::SlotChanged(index));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::{untyped::ResourceKind, Resource},
+        resource::item::{ItemDefinition, ItemResource, ItemResourceState},
+        scene::inventory::{Inventory, InventoryError, InventoryEvent},
+    };
+
+    fn item(id: &str, max_stack_size: u32) -> ItemResource {
+        Resource::new_ok(
+            ResourceKind::Embedded,
+            ItemResourceState {
+                definition: ItemDefinition {
+                    id: id.to_string(),
+                    max_stack_size,
+                    ..Default::default()
+                },
+            },
+        )
+    }
+
+    #[test]
+    fn test_inventory() {
+        let arrow = item("Arrow", 10);
+        let sword = item("Sword", 1);
+
+        let mut inventory = Inventory::new(3);
+        assert_eq!(inventory.add(&arrow, 15), 0);
+        assert_eq!(inventory.slot(0).unwrap().count, 10);
+        assert_eq!(inventory.slot(1).unwrap().count, 5);
+        assert_eq!(inventory.pop_event(), Some(InventoryEvent::SlotChanged(0)));
+        assert_eq!(inventory.pop_event(), Some(InventoryEvent::SlotChanged(1)));
+        assert_eq!(inventory.pop_event(), None);
+
+        // Only one sword fits.
+        assert_eq!(inventory.add(&sword, 2), 1);
+        assert_eq!(inventory.find_by_id("Sword"), Some(2));
+        assert_eq!(
+            inventory.add_to_slot(2, &arrow, 1),
+            Err(InventoryError::SlotOccupied(2))
+        );
+
+        // Removal starts from the last slots.
+        assert_eq!(inventory.remove(&arrow, 7), 7);
+        assert_eq!(inventory.count(&arrow), 8);
+        assert!(inventory.slot(1).unwrap().is_empty());
+
+        // Move to an empty slot.
+        inventory.move_stack(0, 1).unwrap();
+        assert!(inventory.slot(0).unwrap().is_empty());
+        assert_eq!(inventory.take(1, 3).unwrap().count, 3);
+
+        // Merge of stacks of the same kind.
+        assert_eq!(inventory.add_to_slot(0, &arrow, 4), Ok(0));
+        inventory.move_stack(0, 1).unwrap();
+        assert!(inventory.slot(0).unwrap().is_empty());
+        assert_eq!(inventory.slot(1).unwrap().count, 9);
+
+        // Swap of stacks of different kinds.
+        inventory.move_stack(1, 2).unwrap();
+        assert_eq!(inventory.find_by_id("Sword"), Some(1));
+        assert_eq!(inventory.slot(2).unwrap().count, 9);
+    }
+}
@@ -28,6 +28,7 @@ use crate::{
 };
 use fyrox_core::uuid_provider;
 use fyrox_graph::BaseSceneGraph;
+use serde::{Deserialize, Serialize};
 use std::ops::{Deref, DerefMut};
 use strum_macros::{AsRefStr, EnumString, VariantNames};
 
@@ -35,7 +36,19 @@ use strum_macros::{AsRefStr, EnumString, VariantNames};
 pub const CSM_NUM_CASCADES: usize = 3;
 
 /// Frustum split options defines how to split camera's frustum to generate cascades.
-#[derive(Reflect, Clone, Visit, Debug, PartialEq, AsRefStr, EnumString, VariantNames)]
+#[derive(
+    Reflect,
+    Copy,
+    Clone,
+    Visit,
+    Debug,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
 pub enum FrustumSplitOptions {
     /// Camera frustum will be split into a [`CSM_NUM_CASCADES`] splits where each sub-frustum
     /// will have fixed far plane location.
@@ -59,6 +72,39 @@ pub enum FrustumSplitOptions {
         /// sub-frustum will be relative to camera's frustum.
         fractions: [f32; CSM_NUM_CASCADES],
     },
+    /// Camera frustum will be split into a [`CSM_NUM_CASCADES`] splits using so called "practical"
+    /// split scheme, which is a mix of logarithmic and uniform split schemes.
+    ///
+    /// This option gives good distribution of shadow map resolution for any range of the camera,
+    /// and it does not require any precise tweaking as well.
+    Logarithmic {
+        /// A blend factor in `[0; 1]` range between uniform (0.0) and logarithmic (1.0) splits.
+        /// Logarithmic splits give more resolution to the cascades that are close to the camera.
+        #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+        lambda: f32,
+    },
+}
+
+impl FrustumSplitOptions {
+    /// Calculates distances of the planes that split the camera frustum with the given near and
+    /// far planes. The first distance is always `z_near`, the rest are far planes of each cascade.
+    pub fn split_distances(&self, z_near: f32, z_far: f32) -> [f32; CSM_NUM_CASCADES + 1] {
+        let mut distances = [z_near; CSM_NUM_CASCADES + 1];
+        for (i, distance) in distances.iter_mut().enumerate().skip(1) {
+            *distance = match self {
+                Self::Absolute { far_planes } => far_planes[i - 1],
+                Self::Relative { fractions } => z_far * fractions[i - 1],
+                Self::Logarithmic { lambda } => {
+                    let fraction = i as f32 / CSM_NUM_CASCADES as f32;
+                    let z_near = z_near.max(f32::EPSILON);
+                    let logarithmic = z_near * (z_far / z_near).powf(fraction);
+                    let uniform = z_near + (z_far - z_near) * fraction;
+                    uniform + (logarithmic - uniform) * lambda.clamp(0.0, 1.0)
+                }
+            };
+        }
+        distances
+    }
 }
 
 uuid_provider!(FrustumSplitOptions = "b2ed128a-b7da-4d34-b027-a0af19c2f563");
@@ -221,3 +267,36 @@ impl DirectionalLightBuilder {
         graph.add_node(self.build_node())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::scene::light::directional::FrustumSplitOptions;
+
+    #[test]
+    fn test_split_distances() {
+        assert_eq!(
+            FrustumSplitOptions::Absolute {
+                far_planes: [5.0, 25.0, 64.0]
+            }
+            .split_distances(0.1, 100.0),
+            [0.1, 5.0, 25.0, 64.0]
+        );
+        assert_eq!(
+            FrustumSplitOptions::Relative {
+                fractions: [0.25, 0.5, 1.0]
+            }
+            .split_distances(0.1, 100.0),
+            [0.1, 25.0, 50.0, 100.0]
+        );
+
+        for (lambda, expected) in [
+            (0.0, [1.0, 22.0, 43.0, 64.0]),
+            (1.0, [1.0, 4.0, 16.0, 64.0]),
+        ] {
+            let distances = FrustumSplitOptions::Logarithmic { lambda }.split_distances(1.0, 64.0);
+            for (distance, expected) in distances.iter().zip(expected) {
+                assert!((distance - expected).abs() < 0.001);
+            }
+        }
+    }
+}
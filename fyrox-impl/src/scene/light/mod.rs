@@ -24,7 +24,10 @@ use crate::{
         variable::InheritableVariable,
         visitor::{Visit, VisitResult, Visitor},
     },
-    scene::base::{Base, BaseBuilder},
+    scene::{
+        base::{Base, BaseBuilder},
+        collider::BitMask,
+    },
 };
 use std::ops::{Deref, DerefMut};
 
@@ -67,6 +70,10 @@ pub struct BaseLight {
     #[reflect(min_value = 0.0, step = 0.1)]
     #[reflect(setter = "set_intensity")]
     intensity: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_shadow_mask")]
+    shadow_mask: InheritableVariable<BitMask>,
 }
 
 impl Deref for BaseLight {
@@ -96,6 +103,7 @@ impl Default for BaseLight {
             )),
             scatter_enabled: InheritableVariable::new_modified(true),
             intensity: InheritableVariable::new_modified(1.0),
+            shadow_mask: InheritableVariable::new_modified(BitMask(u32::MAX)),
         }
     }
 }
@@ -175,6 +183,24 @@ impl BaseLight {
     pub fn is_scatter_enabled(&self) -> bool {
         *self.scatter_enabled
     }
+
+    /// Sets a set of render layers, that cast shadows from this light. A node casts shadows only if
+    /// its render mask (see [`Base::set_render_mask`]) has at least one common bit with this mask.
+    /// It could be used, for example, to exclude first-person arms from shadows. By default, all
+    /// layers cast shadows.
+    ///
+    /// Keep in mind, that the light itself is visible only for cameras, which render mask has at
+    /// least one common bit with the render mask of the light node.
+    #[inline]
+    pub fn set_shadow_mask(&mut self, mask: BitMask) -> BitMask {
+        self.shadow_mask.set_value_and_mark_modified(mask)
+    }
+
+    /// Returns a set of render layers, that cast shadows from this light.
+    #[inline]
+    pub fn shadow_mask(&self) -> BitMask {
+        *self.shadow_mask
+    }
 }
 
 /// Light scene node builder. Provides easy declarative way of creating light scene
@@ -186,6 +212,7 @@ pub struct BaseLightBuilder {
     scatter_factor: Vector3<f32>,
     scatter_enabled: bool,
     intensity: f32,
+    shadow_mask: BitMask,
 }
 
 impl BaseLightBuilder {
@@ -201,6 +228,7 @@ impl BaseLightBuilder {
             scatter_factor: Vector3::new(DEFAULT_SCATTER_R, DEFAULT_SCATTER_G, DEFAULT_SCATTER_B),
             scatter_enabled: true,
             intensity: 1.0,
+            shadow_mask: BitMask(u32::MAX),
         }
     }
 
@@ -234,6 +262,12 @@ impl BaseLightBuilder {
         self
     }
 
+    /// Sets desired shadow mask. See [`BaseLight::set_shadow_mask`] for more info.
+    pub fn with_shadow_mask(mut self, mask: BitMask) -> Self {
+        self.shadow_mask = mask;
+        self
+    }
+
     /// Creates new instance of base light.
     pub fn build(self) -> BaseLight {
         BaseLight {
@@ -243,6 +277,7 @@ impl BaseLightBuilder {
             scatter: self.scatter_factor.into(),
             scatter_enabled: self.scatter_enabled.into(),
             intensity: self.intensity.into(),
+            shadow_mask: self.shadow_mask.into(),
         }
     }
 }
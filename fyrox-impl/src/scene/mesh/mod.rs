@@ -48,6 +48,8 @@ use std::{
 use strum_macros::{AsRefStr, EnumString, VariantNames};
 
 pub mod buffer;
+pub mod optimization;
+pub mod primitives;
 pub mod surface;
 pub mod vertex;
 
@@ -80,9 +82,16 @@ pub enum RenderPath {
     /// Forward rendering path supports translucency and custom blending. However current support
     /// of forward rendering is very little. It is ideal for transparent objects like glass.
     Forward = 1,
+
+    /// Order-independent transparency path, that uses weighted blended OIT technique. Unlike [`Self::Forward`],
+    /// it does not require transparent objects to be sorted and produces stable results for intersecting
+    /// transparent surfaces (glass, liquids, etc.). The cost is an approximate (weighted) blending order.
+    /// Material of a mesh must have `OrderIndependentTransparency` render pass, otherwise the mesh won't
+    /// be rendered.
+    OrderIndependent = 2,
 }
 
-fn transform_vertex(mut vertex: VertexViewMut, world: &Matrix4<f32>) {
+pub(crate) fn transform_vertex(mut vertex: VertexViewMut, world: &Matrix4<f32>) {
     if let Ok(position) = vertex.cast_attribute::<Vector3<f32>>(VertexAttributeUsage::Position) {
         *position = world.transform_point(&(*position).into()).coords;
     }
@@ -260,6 +269,18 @@ impl RenderDataBundleStorageTrait for BatchContainer {
             src_data.geometry_buffer.triangles_ref(),
         );
     }
+
+    fn push_instanced(
+        &mut self,
+        _data: &SurfaceResource,
+        _material: &MaterialResource,
+        _render_path: RenderPath,
+        _sort_index: u64,
+        _hardware_instance_count: usize,
+        _instance_data: SurfaceInstanceData,
+    ) {
+        // Per-instance data is interpreted by shaders only, so it cannot be baked into a batch.
+    }
 }
 
 /// Mesh is a 3D model, each mesh split into multiple surfaces, each surface represents a patch of the mesh with a single material
@@ -631,6 +652,8 @@ impl NodeTrait for Mesh {
                     SurfaceInstanceData {
                         world_transform: Matrix4::identity(),
                         bone_matrices: Default::default(),
+                        prev_world_transform: Matrix4::identity(),
+                        prev_bone_matrices: Default::default(),
                         depth_offset: self.depth_offset_factor(),
                         blend_shapes_weights: Default::default(),
                         element_range: ElementRange::Full,
@@ -681,6 +704,8 @@ impl NodeTrait for Mesh {
                             surface.material().key(),
                             SurfaceInstanceData {
                                 world_transform: world,
+                                prev_world_transform: world,
+                                prev_bone_matrices: Default::default(),
                                 bone_matrices: surface
                                     .bones
                                     .iter()
@@ -0,0 +1,420 @@
+//! Mesh optimization algorithms, that are mostly used at import time: vertex welding, vertex cache
+//! optimization, overdraw optimization and vertex fetch optimization. See [`super::surface::SurfaceData`]
+//! methods for high-level access to these algorithms.
+
+use crate::{
+    core::{algebra::Vector3, math::TriangleDefinition},
+    scene::mesh::buffer::{VertexAttributeDataType, VertexBuffer},
+};
+use fxhash::FxHashMap;
+
+/// Size of the simulated post-transform vertex cache. Modern GPUs do not have a classic FIFO
+/// cache anymore, but the value is a good approximation for most of them.
+pub const VERTEX_CACHE_SIZE: usize = 32;
+
+const CACHE_DECAY_POWER: f32 = 1.5;
+const LAST_TRIANGLE_SCORE: f32 = 0.75;
+const VALENCE_BOOST_SCALE: f32 = 2.0;
+const VALENCE_BOOST_POWER: f32 = 0.5;
+
+/// Marks a vertex, that is not referenced by any triangle, in remap tables.
+const UNUSED: u32 = u32::MAX;
+
+fn vertex_score(cache_position: Option<usize>, remaining_triangles: usize) -> f32 {
+    if remaining_triangles == 0 {
+        // The vertex is not used anymore, no need to prioritize it.
+        return -1.0;
+    }
+
+    let mut score = match cache_position {
+        None => 0.0,
+        // The vertices of the last triangle are used with a fixed score, this way the next
+        // triangle won't be a direct neighbour of the last one, which results in long strips
+        // with poor cache utilization.
+        Some(position) if position < 3 => LAST_TRIANGLE_SCORE,
+        Some(position) => {
+            let scale = 1.0 / (VERTEX_CACHE_SIZE - 3) as f32;
+            (1.0 - (position - 3) as f32 * scale).powf(CACHE_DECAY_POWER)
+        }
+    };
+
+    // Boost vertices with few remaining triangles, this way lone triangles won't be left behind.
+    score += VALENCE_BOOST_SCALE * (remaining_triangles as f32).powf(-VALENCE_BOOST_POWER);
+
+    score
+}
+
+/// Reorders the triangles to improve utilization of the post-transform vertex cache of a GPU. It
+/// uses the algorithm described in "Linear-Speed Vertex Cache Optimisation" by Tom Forsyth. The
+/// set of triangles is preserved, only their order is changed.
+pub fn optimize_vertex_cache(
+    triangles: &[TriangleDefinition],
+    vertex_count: usize,
+) -> Vec<TriangleDefinition> {
+    let mut adjacency = vec![Vec::new(); vertex_count];
+    for (index, triangle) in triangles.iter().enumerate() {
+        for &vertex in triangle.0.iter() {
+            adjacency[vertex as usize].push(index as u32);
+        }
+    }
+
+    let mut vertex_scores = adjacency
+        .iter()
+        .map(|triangles| vertex_score(None, triangles.len()))
+        .collect::<Vec<_>>();
+    let mut emitted = vec![false; triangles.len()];
+
+    let mut result = Vec::with_capacity(triangles.len());
+    let mut cache = Vec::<u32>::with_capacity(VERTEX_CACHE_SIZE + 3);
+    let mut next_candidate = 0;
+    let mut best_triangle = None;
+
+    while result.len() < triangles.len() {
+        let current = match best_triangle {
+            Some(best) => best,
+            None => {
+                // There's no triangles in the cache, pick the next unprocessed one.
+                while emitted[next_candidate] {
+                    next_candidate += 1;
+                }
+                next_candidate
+            }
+        };
+
+        emitted[current] = true;
+        let triangle = triangles[current];
+        result.push(triangle);
+
+        for &vertex in triangle.0.iter() {
+            let vertex_triangles = &mut adjacency[vertex as usize];
+            if let Some(position) = vertex_triangles.iter().position(|&t| t == current as u32) {
+                vertex_triangles.swap_remove(position);
+            }
+        }
+
+        // Move the vertices of the triangle to the front of the cache.
+        let mut new_cache = Vec::with_capacity(VERTEX_CACHE_SIZE + 3);
+        new_cache.extend_from_slice(&triangle.0);
+        new_cache.extend(cache.iter().filter(|v| !triangle.0.contains(v)));
+        let evicted = new_cache.split_off(new_cache.len().min(VERTEX_CACHE_SIZE));
+        cache = new_cache;
+
+        for &vertex in evicted.iter() {
+            vertex_scores[vertex as usize] = vertex_score(None, adjacency[vertex as usize].len());
+        }
+        for (position, &vertex) in cache.iter().enumerate() {
+            vertex_scores[vertex as usize] =
+                vertex_score(Some(position), adjacency[vertex as usize].len());
+        }
+
+        // Only the triangles, that use the vertices of the cache, could change their score.
+        best_triangle = None;
+        let mut best_score = f32::MIN;
+        for &vertex in cache.iter().chain(evicted.iter()) {
+            for &triangle_index in adjacency[vertex as usize].iter() {
+                let score = triangles[triangle_index as usize]
+                    .0
+                    .iter()
+                    .map(|&v| vertex_scores[v as usize])
+                    .sum::<f32>();
+                if score > best_score {
+                    best_score = score;
+                    best_triangle = Some(triangle_index as usize);
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// Simulates a FIFO vertex cache, returns `true` if the vertex was not in the cache.
+fn fifo_cache_miss(cache: &mut Vec<u32>, vertex: u32) -> bool {
+    if cache.contains(&vertex) {
+        return false;
+    }
+    if cache.len() == VERTEX_CACHE_SIZE {
+        cache.remove(0);
+    }
+    cache.push(vertex);
+    true
+}
+
+/// Reorders the triangles to reduce overdraw. The triangles are split into clusters at the places
+/// where the vertex cache is fully flushed and the clusters are sorted so the ones that face away
+/// from the center of the mesh are drawn first, they're more likely to occlude other clusters. The
+/// order of triangles in each cluster is preserved, so the function should be used after
+/// [`optimize_vertex_cache`] to keep the cache efficiency.
+pub fn optimize_overdraw(
+    triangles: &[TriangleDefinition],
+    positions: &[Vector3<f32>],
+) -> Vec<TriangleDefinition> {
+    if triangles.is_empty() {
+        return Vec::new();
+    }
+
+    // Split the triangles into clusters, using a simulated FIFO vertex cache.
+    let mut clusters = Vec::new();
+    let mut cluster_start = 0;
+    let mut cache = Vec::<u32>::with_capacity(VERTEX_CACHE_SIZE);
+    for (index, triangle) in triangles.iter().enumerate() {
+        let misses = triangle
+            .0
+            .iter()
+            .filter(|&&vertex| fifo_cache_miss(&mut cache, vertex))
+            .count();
+
+        if misses == 3 && index != cluster_start {
+            clusters.push(cluster_start..index);
+            cluster_start = index;
+        }
+    }
+    clusters.push(cluster_start..triangles.len());
+
+    let triangle_center = |triangle: &TriangleDefinition| {
+        triangle
+            .0
+            .iter()
+            .map(|&v| positions[v as usize])
+            .sum::<Vector3<f32>>()
+            .scale(1.0 / 3.0)
+    };
+
+    let mesh_center = triangles
+        .iter()
+        .map(triangle_center)
+        .sum::<Vector3<f32>>()
+        .scale(1.0 / triangles.len() as f32);
+
+    let mut sorted_clusters = clusters
+        .into_iter()
+        .map(|cluster| {
+            let mut center = Vector3::default();
+            let mut normal = Vector3::default();
+            for triangle in triangles[cluster.clone()].iter() {
+                let [a, b, c] = triangle.0.map(|v| positions[v as usize]);
+                // Non-normalized cross product, so larger triangles have bigger influence.
+                normal += (b - a).cross(&(c - a));
+                center += triangle_center(triangle);
+            }
+            center /= cluster.len() as f32;
+            let sort_key =
+                (center - mesh_center).dot(&normal.try_normalize(f32::EPSILON).unwrap_or_default());
+            (sort_key, cluster)
+        })
+        .collect::<Vec<_>>();
+
+    sorted_clusters.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+    sorted_clusters
+        .into_iter()
+        .flat_map(|(_, cluster)| triangles[cluster].iter().cloned())
+        .collect()
+}
+
+/// Rearranges the vertices of the given buffer using the given remap table, where
+/// `remap[old_index]` is a new index of the vertex or `u32::MAX` if the vertex should be discarded.
+/// Vertices, that map to the same new index, are assumed to be equal and the first of them is used.
+fn remap_vertex_buffer(vertex_buffer: &mut VertexBuffer, remap: &[u32], new_vertex_count: usize) {
+    let mut sources = vec![UNUSED; new_vertex_count];
+    for (old_index, &new_index) in remap.iter().enumerate() {
+        if new_index != UNUSED && sources[new_index as usize] == UNUSED {
+            sources[new_index as usize] = old_index as u32;
+        }
+    }
+
+    let vertex_size = vertex_buffer.vertex_size() as usize;
+    let data = vertex_buffer.raw_data().to_vec();
+    let mut vertex_buffer_mut = vertex_buffer.modify();
+    vertex_buffer_mut.clear();
+    for source in sources {
+        let offset = source as usize * vertex_size;
+        vertex_buffer_mut
+            .push_vertex_raw(&data[offset..(offset + vertex_size)])
+            .expect("Vertex size must match!");
+    }
+}
+
+fn remap_triangles(triangles: &mut [TriangleDefinition], remap: &[u32]) {
+    for triangle in triangles.iter_mut() {
+        for index in triangle.0.iter_mut() {
+            *index = remap[*index as usize];
+        }
+    }
+}
+
+/// Merges the vertices, that have the same attributes within the given tolerance. Floating-point
+/// attributes are snapped to a grid with the cell size equal to the tolerance, all other attributes
+/// must match exactly. Indices of the triangles are modified accordingly, vertices that are not
+/// used by any triangle are discarded. Returns the amount of removed vertices.
+pub fn weld_vertices(
+    vertex_buffer: &mut VertexBuffer,
+    triangles: &mut [TriangleDefinition],
+    tolerance: f32,
+) -> usize {
+    let tolerance = tolerance.max(f32::EPSILON);
+    let vertex_size = vertex_buffer.vertex_size() as usize;
+    let layout = vertex_buffer.layout();
+    let data = vertex_buffer.raw_data();
+
+    let mut used = vec![false; vertex_buffer.vertex_count() as usize];
+    for triangle in triangles.iter() {
+        for &index in triangle.0.iter() {
+            used[index as usize] = true;
+        }
+    }
+
+    let mut unique = FxHashMap::<Vec<i64>, u32>::default();
+    let mut remap = vec![UNUSED; used.len()];
+    for (index, vertex) in data.chunks_exact(vertex_size).enumerate() {
+        if !used[index] {
+            continue;
+        }
+
+        let mut key = Vec::new();
+        for attribute in layout {
+            let component_size = attribute.data_type.size() as usize;
+            let start = attribute.offset as usize;
+            let end = start + attribute.size as usize * component_size;
+            for component in vertex[start..end].chunks_exact(component_size) {
+                key.push(match attribute.data_type {
+                    VertexAttributeDataType::F32 => {
+                        let value = f32::from_ne_bytes(component.try_into().unwrap());
+                        (value / tolerance).round() as i64
+                    }
+                    VertexAttributeDataType::U32 => {
+                        u32::from_ne_bytes(component.try_into().unwrap()) as i64
+                    }
+                    VertexAttributeDataType::U16 => {
+                        u16::from_ne_bytes(component.try_into().unwrap()) as i64
+                    }
+                    VertexAttributeDataType::U8 => component[0] as i64,
+                });
+            }
+        }
+
+        let next_index = unique.len() as u32;
+        remap[index] = *unique.entry(key).or_insert(next_index);
+    }
+
+    let removed = remap.len() - unique.len();
+    remap_triangles(triangles, &remap);
+    remap_vertex_buffer(vertex_buffer, &remap, unique.len());
+    removed
+}
+
+/// Reorders the vertices in the order of their first use by the triangles, which improves memory
+/// access locality when the GPU fetches the vertices. Indices of the triangles are modified
+/// accordingly, vertices that are not used by any triangle are discarded.
+pub fn optimize_vertex_fetch(
+    vertex_buffer: &mut VertexBuffer,
+    triangles: &mut [TriangleDefinition],
+) {
+    let mut remap = vec![UNUSED; vertex_buffer.vertex_count() as usize];
+    let mut next_index = 0;
+    for triangle in triangles.iter() {
+        for &index in triangle.0.iter() {
+            if remap[index as usize] == UNUSED {
+                remap[index as usize] = next_index;
+                next_index += 1;
+            }
+        }
+    }
+
+    remap_triangles(triangles, &remap);
+    remap_vertex_buffer(vertex_buffer, &remap, next_index as usize);
+}
+
+/// Calculates average cache miss ratio (ACMR) of the given triangles, which is an average amount
+/// of vertex shader invocations per triangle. The value is in `0.5..3.0` range, lower is better.
+pub fn average_cache_miss_ratio(triangles: &[TriangleDefinition]) -> f32 {
+    if triangles.is_empty() {
+        return 0.0;
+    }
+
+    let mut cache = Vec::<u32>::with_capacity(VERTEX_CACHE_SIZE);
+    let misses = triangles
+        .iter()
+        .flat_map(|triangle| triangle.0.iter())
+        .filter(|&&vertex| fifo_cache_miss(&mut cache, vertex))
+        .count();
+    misses as f32 / triangles.len() as f32
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Matrix4, math::TriangleDefinition},
+        scene::mesh::{
+            buffer::{TriangleBuffer, VertexAttributeUsage, VertexReadTrait},
+            optimization::average_cache_miss_ratio,
+            surface::SurfaceData,
+        },
+    };
+
+    // Sorted list of triangles, where each triangle is represented by the positions of its vertices.
+    fn triangle_set(data: &SurfaceData) -> Vec<[[u32; 3]; 3]> {
+        let mut triangles = data
+            .geometry_buffer
+            .iter()
+            .map(|triangle| {
+                triangle.0.map(|i| {
+                    data.vertex_buffer
+                        .get(i as usize)
+                        .unwrap()
+                        .read_3_f32(VertexAttributeUsage::Position)
+                        .unwrap()
+                        .map(|c| c.to_bits())
+                        .into()
+                })
+            })
+            .collect::<Vec<_>>();
+        triangles.sort();
+        triangles
+    }
+
+    // Makes a copy of the surface, where each triangle has its own vertices.
+    fn unweld(data: &SurfaceData) -> SurfaceData {
+        let vertex_size = data.vertex_buffer.vertex_size() as usize;
+        let mut vertex_buffer = data.vertex_buffer.clone_empty(0);
+        let mut vertex_buffer_mut = vertex_buffer.modify();
+        let mut triangles = Vec::new();
+        for (index, triangle) in data.geometry_buffer.iter().enumerate() {
+            for &vertex in triangle.0.iter() {
+                let offset = vertex as usize * vertex_size;
+                vertex_buffer_mut
+                    .push_vertex_raw(&data.vertex_buffer.raw_data()[offset..offset + vertex_size])
+                    .unwrap();
+            }
+            let first = index as u32 * 3;
+            triangles.push(TriangleDefinition([first, first + 1, first + 2]));
+        }
+        drop(vertex_buffer_mut);
+        SurfaceData::new(vertex_buffer, TriangleBuffer::new(triangles))
+    }
+
+    #[test]
+    fn test_mesh_optimization() {
+        let sphere = SurfaceData::make_sphere(16, 16, 1.0, &Matrix4::identity());
+        let reference = triangle_set(&sphere);
+
+        let mut data = unweld(&sphere);
+        assert_eq!(
+            data.vertex_buffer.vertex_count() as usize,
+            sphere.geometry_buffer.len() * 3
+        );
+
+        let removed = data.weld_vertices(1.0e-5);
+        assert!(removed > 0);
+        assert!(data.vertex_buffer.vertex_count() <= sphere.vertex_buffer.vertex_count());
+        assert_eq!(triangle_set(&data), reference);
+
+        let acmr = average_cache_miss_ratio(data.geometry_buffer.triangles_ref());
+        data.optimize_vertex_cache();
+        assert_eq!(triangle_set(&data), reference);
+        assert!(average_cache_miss_ratio(data.geometry_buffer.triangles_ref()) <= acmr);
+
+        data.optimize_overdraw().unwrap();
+        assert_eq!(triangle_set(&data), reference);
+    }
+}
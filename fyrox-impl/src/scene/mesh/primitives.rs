@@ -0,0 +1,707 @@
+//! Parametric primitives for level blockouts (greyboxing) - stairs, ramps, arches, pipes and walls
+//! with openings. See [`SurfaceData::make_stairs`] and other methods of this module for more info.
+//!
+//! Every primitive is centered on `X` and `Z` axes and lies on `oXZ` plane (its lowest point has
+//! zero `Y` coordinate), so it could be placed on a floor without any adjustments. Primitives are
+//! flat-shaded.
+
+use crate::{
+    core::algebra::{Matrix4, Vector2, Vector3},
+    scene::mesh::{surface::SurfaceData, vertex::StaticVertex},
+    utils::raw_mesh::RawMeshBuilder,
+};
+
+/// Defines how texture coordinates of procedural primitives are generated.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum UvMapping {
+    /// Every face is stretched over the whole texture.
+    Stretch,
+    /// Texture coordinates are projected from local positions of vertices on one of the three
+    /// axial planes (the one that is the closest to the face) and multiplied by the given scale.
+    /// Texture density is the same on every face of every primitive, which makes this mode the
+    /// best choice for blockouts with grid textures.
+    Box {
+        /// Amount of texture repetitions per one unit of length.
+        scale: f32,
+    },
+}
+
+impl Default for UvMapping {
+    fn default() -> Self {
+        Self::Box { scale: 1.0 }
+    }
+}
+
+/// An opening (a door, a window, etc.) in a wall. See [`SurfaceData::make_wall`] docs for more
+/// info.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct WallOpening {
+    /// Position of the bottom-left corner of the opening relative to the bottom-left corner of the
+    /// wall (when looking at the front side of the wall).
+    pub position: Vector2<f32>,
+    /// Width and height of the opening.
+    pub size: Vector2<f32>,
+}
+
+struct PrimitiveBuilder {
+    builder: RawMeshBuilder<StaticVertex>,
+    uv_mapping: UvMapping,
+}
+
+impl PrimitiveBuilder {
+    fn new(uv_mapping: UvMapping) -> Self {
+        Self {
+            builder: RawMeshBuilder::new(64, 64),
+            uv_mapping,
+        }
+    }
+
+    fn tex_coord(
+        &self,
+        point: Vector3<f32>,
+        normal: Vector3<f32>,
+        basis: &FaceBasis,
+    ) -> Vector2<f32> {
+        match self.uv_mapping {
+            UvMapping::Stretch => basis.project(point),
+            UvMapping::Box { scale } => {
+                let n = normal.abs();
+                let uv = if n.x >= n.y && n.x >= n.z {
+                    Vector2::new(point.z, point.y)
+                } else if n.y >= n.z {
+                    Vector2::new(point.x, point.z)
+                } else {
+                    Vector2::new(point.x, point.y)
+                };
+                uv.scale(scale)
+            }
+        }
+    }
+
+    /// Adds a planar convex face. Order of the points does not matter, it is reversed if the face
+    /// is not facing in the `outward` direction.
+    fn face(&mut self, points: &[Vector3<f32>], outward: Vector3<f32>) {
+        // Newell's method works for any planar polygon, even with collinear first points.
+        let mut normal = Vector3::default();
+        for (i, a) in points.iter().enumerate() {
+            let b = points[(i + 1) % points.len()];
+            normal.x += (a.y - b.y) * (a.z + b.z);
+            normal.y += (a.z - b.z) * (a.x + b.x);
+            normal.z += (a.x - b.x) * (a.y + b.y);
+        }
+        let Some(mut normal) = normal.try_normalize(f32::EPSILON) else {
+            // Degenerated face.
+            return;
+        };
+
+        let mut points = points.to_vec();
+        if normal.dot(&outward) < 0.0 {
+            points.reverse();
+            normal = -normal;
+        }
+
+        let basis = FaceBasis::new(&points, normal);
+        for i in 1..points.len() - 1 {
+            for point in [points[0], points[i], points[i + 1]] {
+                let tex_coord = self.tex_coord(point, normal, &basis);
+                self.builder
+                    .insert(StaticVertex::from_pos_uv_normal(point, tex_coord, normal));
+            }
+        }
+    }
+
+    fn build(self, transform: &Matrix4<f32>) -> SurfaceData {
+        let mut data = SurfaceData::from_raw_mesh(self.builder.build());
+        data.calculate_tangents().unwrap();
+        data.transform_geometry(transform).unwrap();
+        data
+    }
+}
+
+/// Local 2D coordinate system of a face, that is used to stretch the face over the whole texture.
+struct FaceBasis {
+    origin: Vector3<f32>,
+    u: Vector3<f32>,
+    v: Vector3<f32>,
+    min: Vector2<f32>,
+    size: Vector2<f32>,
+}
+
+impl FaceBasis {
+    fn new(points: &[Vector3<f32>], normal: Vector3<f32>) -> Self {
+        let origin = points[0];
+        let u = points
+            .iter()
+            .find_map(|p| (p - origin).try_normalize(f32::EPSILON))
+            .unwrap_or_else(Vector3::x);
+        let v = normal.cross(&u);
+
+        let mut min = Vector2::repeat(f32::MAX);
+        let mut max = Vector2::repeat(-f32::MAX);
+        for point in points {
+            let local = Vector2::new((point - origin).dot(&u), (point - origin).dot(&v));
+            min = min.inf(&local);
+            max = max.sup(&local);
+        }
+
+        Self {
+            origin,
+            u,
+            v,
+            min,
+            size: max - min,
+        }
+    }
+
+    fn project(&self, point: Vector3<f32>) -> Vector2<f32> {
+        let local = Vector2::new(
+            (point - self.origin).dot(&self.u),
+            (point - self.origin).dot(&self.v),
+        ) - self.min;
+        Vector2::new(
+            if self.size.x > 0.0 {
+                local.x / self.size.x
+            } else {
+                0.0
+            },
+            if self.size.y > 0.0 {
+                local.y / self.size.y
+            } else {
+                0.0
+            },
+        )
+    }
+}
+
+fn sorted_cuts(mut cuts: Vec<f32>) -> Vec<f32> {
+    cuts.sort_by(|a, b| a.total_cmp(b));
+    cuts.dedup_by(|a, b| (*a - *b).abs() <= f32::EPSILON);
+    cuts
+}
+
+impl SurfaceData {
+    /// Creates solid stairs with the given width, height, depth and amount of steps. Stairs rise
+    /// along `Z` axis. Faces of the stairs are split so that the mesh has no T-junctions. The
+    /// stairs are transformed using the given transformation matrix, which could be
+    /// [`Matrix4::identity`] to not modify the stairs at all.
+    pub fn make_stairs(
+        width: f32,
+        height: f32,
+        depth: f32,
+        steps: usize,
+        uv_mapping: UvMapping,
+        transform: &Matrix4<f32>,
+    ) -> Self {
+        let steps = steps.max(1);
+        let mut builder = PrimitiveBuilder::new(uv_mapping);
+
+        let hw = width * 0.5;
+        let step_height = height / steps as f32;
+        let step_depth = depth / steps as f32;
+        let y = |i: usize| i as f32 * step_height;
+        let z = |i: usize| i as f32 * step_depth - depth * 0.5;
+
+        for column in 0..steps {
+            let (z0, z1) = (z(column), z(column + 1));
+            let (y0, y1) = (y(column), y(column + 1));
+
+            // Tread.
+            builder.face(
+                &[
+                    Vector3::new(-hw, y1, z0),
+                    Vector3::new(hw, y1, z0),
+                    Vector3::new(hw, y1, z1),
+                    Vector3::new(-hw, y1, z1),
+                ],
+                Vector3::y(),
+            );
+            // Riser.
+            builder.face(
+                &[
+                    Vector3::new(-hw, y0, z0),
+                    Vector3::new(hw, y0, z0),
+                    Vector3::new(hw, y1, z0),
+                    Vector3::new(-hw, y1, z0),
+                ],
+                -Vector3::z(),
+            );
+            // Bottom.
+            builder.face(
+                &[
+                    Vector3::new(-hw, 0.0, z0),
+                    Vector3::new(hw, 0.0, z0),
+                    Vector3::new(hw, 0.0, z1),
+                    Vector3::new(-hw, 0.0, z1),
+                ],
+                -Vector3::y(),
+            );
+            // Back.
+            builder.face(
+                &[
+                    Vector3::new(-hw, y0, depth * 0.5),
+                    Vector3::new(hw, y0, depth * 0.5),
+                    Vector3::new(hw, y1, depth * 0.5),
+                    Vector3::new(-hw, y1, depth * 0.5),
+                ],
+                Vector3::z(),
+            );
+            // Sides.
+            for row in 0..=column {
+                let (y0, y1) = (y(row), y(row + 1));
+                for (x, outward) in [(-hw, -Vector3::x()), (hw, Vector3::x())] {
+                    builder.face(
+                        &[
+                            Vector3::new(x, y0, z0),
+                            Vector3::new(x, y0, z1),
+                            Vector3::new(x, y1, z1),
+                            Vector3::new(x, y1, z0),
+                        ],
+                        outward,
+                    );
+                }
+            }
+        }
+
+        builder.build(transform)
+    }
+
+    /// Creates a ramp (a wedge) with the given width, height and depth. The ramp rises along `Z`
+    /// axis. The ramp is transformed using the given transformation matrix, which could be
+    /// [`Matrix4::identity`] to not modify the ramp at all.
+    pub fn make_ramp(
+        width: f32,
+        height: f32,
+        depth: f32,
+        uv_mapping: UvMapping,
+        transform: &Matrix4<f32>,
+    ) -> Self {
+        let mut builder = PrimitiveBuilder::new(uv_mapping);
+
+        let hw = width * 0.5;
+        let hd = depth * 0.5;
+
+        // Slope.
+        builder.face(
+            &[
+                Vector3::new(-hw, 0.0, -hd),
+                Vector3::new(hw, 0.0, -hd),
+                Vector3::new(hw, height, hd),
+                Vector3::new(-hw, height, hd),
+            ],
+            Vector3::new(0.0, depth, -height),
+        );
+        // Bottom.
+        builder.face(
+            &[
+                Vector3::new(-hw, 0.0, -hd),
+                Vector3::new(hw, 0.0, -hd),
+                Vector3::new(hw, 0.0, hd),
+                Vector3::new(-hw, 0.0, hd),
+            ],
+            -Vector3::y(),
+        );
+        // Back.
+        builder.face(
+            &[
+                Vector3::new(-hw, 0.0, hd),
+                Vector3::new(hw, 0.0, hd),
+                Vector3::new(hw, height, hd),
+                Vector3::new(-hw, height, hd),
+            ],
+            Vector3::z(),
+        );
+        // Sides.
+        for (x, outward) in [(-hw, -Vector3::x()), (hw, Vector3::x())] {
+            builder.face(
+                &[
+                    Vector3::new(x, 0.0, -hd),
+                    Vector3::new(x, 0.0, hd),
+                    Vector3::new(x, height, hd),
+                ],
+                outward,
+            );
+        }
+
+        builder.build(transform)
+    }
+
+    /// Creates a round arch with the given outer width, height, thickness (of the arch itself)
+    /// and depth. The arch lies on `oXY` plane. `segments` defines smoothness of the rounded part
+    /// of the arch, typical values are [8..32]. If the height is less than half of the width, the
+    /// arch will have no vertical legs. The arch is transformed using the given transformation
+    /// matrix, which could be [`Matrix4::identity`] to not modify the arch at all.
+    pub fn make_arch(
+        width: f32,
+        height: f32,
+        thickness: f32,
+        depth: f32,
+        segments: usize,
+        uv_mapping: UvMapping,
+        transform: &Matrix4<f32>,
+    ) -> Self {
+        let segments = segments.max(1);
+        let mut builder = PrimitiveBuilder::new(uv_mapping);
+
+        let outer_radius = width * 0.5;
+        let inner_radius = (outer_radius - thickness).max(0.0);
+        let spring = (height - outer_radius).max(0.0);
+        let hd = depth * 0.5;
+
+        // A profile of the arch - pairs of inner and outer points, from the left leg to the right.
+        let mut profile = vec![(
+            Vector2::new(-inner_radius, 0.0),
+            Vector2::new(-outer_radius, 0.0),
+        )];
+        for i in 0..=segments {
+            let angle = std::f32::consts::PI * (1.0 - i as f32 / segments as f32);
+            let (sin, cos) = angle.sin_cos();
+            profile.push((
+                Vector2::new(inner_radius * cos, spring + inner_radius * sin),
+                Vector2::new(outer_radius * cos, spring + outer_radius * sin),
+            ));
+        }
+        profile.push((
+            Vector2::new(inner_radius, 0.0),
+            Vector2::new(outer_radius, 0.0),
+        ));
+
+        let point = |p: Vector2<f32>, z: f32| Vector3::new(p.x, p.y, z);
+
+        for pair in profile.windows(2) {
+            let (inner0, outer0) = pair[0];
+            let (inner1, outer1) = pair[1];
+
+            // Front and back.
+            for (z, outward) in [(hd, Vector3::z()), (-hd, -Vector3::z())] {
+                builder.face(
+                    &[
+                        point(inner0, z),
+                        point(outer0, z),
+                        point(outer1, z),
+                        point(inner1, z),
+                    ],
+                    outward,
+                );
+            }
+
+            let direction = (outer0 + outer1) - (inner0 + inner1);
+            let direction = Vector3::new(direction.x, direction.y, 0.0);
+
+            // Outer surface.
+            builder.face(
+                &[
+                    point(outer0, -hd),
+                    point(outer1, -hd),
+                    point(outer1, hd),
+                    point(outer0, hd),
+                ],
+                direction,
+            );
+            // Inner surface.
+            builder.face(
+                &[
+                    point(inner0, -hd),
+                    point(inner1, -hd),
+                    point(inner1, hd),
+                    point(inner0, hd),
+                ],
+                -direction,
+            );
+        }
+
+        // Feet.
+        for (inner, outer) in [profile[0], profile[profile.len() - 1]] {
+            builder.face(
+                &[
+                    point(inner, -hd),
+                    point(outer, -hd),
+                    point(outer, hd),
+                    point(inner, hd),
+                ],
+                -Vector3::y(),
+            );
+        }
+
+        builder.build(transform)
+    }
+
+    /// Creates a vertical pipe with the given amount of sides, outer and inner radii and length.
+    /// Zero inner radius produces a solid cylinder. The pipe is transformed using the given
+    /// transformation matrix, which could be [`Matrix4::identity`] to not modify the pipe at all.
+    pub fn make_pipe(
+        sides: usize,
+        outer_radius: f32,
+        inner_radius: f32,
+        length: f32,
+        uv_mapping: UvMapping,
+        transform: &Matrix4<f32>,
+    ) -> Self {
+        let sides = sides.max(3);
+        let inner_radius = inner_radius.clamp(0.0, outer_radius);
+        let mut builder = PrimitiveBuilder::new(uv_mapping);
+
+        let direction = |i: usize| {
+            let (sin, cos) = (std::f32::consts::TAU * i as f32 / sides as f32).sin_cos();
+            Vector3::new(cos, 0.0, sin)
+        };
+
+        for i in 0..sides {
+            let (d0, d1) = (direction(i), direction(i + 1));
+            let outward = d0 + d1;
+
+            let (outer0, outer1) = (d0.scale(outer_radius), d1.scale(outer_radius));
+            let (inner0, inner1) = (d0.scale(inner_radius), d1.scale(inner_radius));
+            let up = Vector3::new(0.0, length, 0.0);
+
+            builder.face(&[outer0, outer1, outer1 + up, outer0 + up], outward);
+
+            if inner_radius > 0.0 {
+                builder.face(&[inner0, inner1, inner1 + up, inner0 + up], -outward);
+                builder.face(&[inner0, outer0, outer1, inner1], -Vector3::y());
+                builder.face(
+                    &[inner0 + up, outer0 + up, outer1 + up, inner1 + up],
+                    Vector3::y(),
+                );
+            } else {
+                builder.face(&[Vector3::default(), outer0, outer1], -Vector3::y());
+                builder.face(&[up, outer0 + up, outer1 + up], Vector3::y());
+            }
+        }
+
+        builder.build(transform)
+    }
+
+    /// Creates a wall with the given width, height, thickness and a set of openings (doors,
+    /// windows, etc.). The wall lies on `oXY` plane, its front side faces `+Z` direction. Openings
+    /// are clipped by the bounds of the wall, they could overlap each other. An opening that
+    /// touches the bottom (or any other) edge of the wall cuts through it, which is useful for
+    /// doors. The wall is transformed using the given transformation matrix, which could be
+    /// [`Matrix4::identity`] to not modify the wall at all.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use fyrox_impl::{
+    /// #     core::algebra::{Matrix4, Vector2},
+    /// #     scene::mesh::{
+    /// #         primitives::{UvMapping, WallOpening},
+    /// #         surface::SurfaceData,
+    /// #     },
+    /// # };
+    /// let wall = SurfaceData::make_wall(
+    ///     4.0,
+    ///     3.0,
+    ///     0.2,
+    ///     &[
+    ///         // Door.
+    ///         WallOpening {
+    ///             position: Vector2::new(0.5, 0.0),
+    ///             size: Vector2::new(1.0, 2.0),
+    ///         },
+    ///         // Window.
+    ///         WallOpening {
+    ///             position: Vector2::new(2.25, 1.0),
+    ///             size: Vector2::new(1.25, 1.0),
+    ///         },
+    ///     ],
+    ///     UvMapping::default(),
+    ///     &Matrix4::identity(),
+    /// );
+    /// ```
+    pub fn make_wall(
+        width: f32,
+        height: f32,
+        thickness: f32,
+        openings: &[WallOpening],
+        uv_mapping: UvMapping,
+        transform: &Matrix4<f32>,
+    ) -> Self {
+        let mut builder = PrimitiveBuilder::new(uv_mapping);
+
+        // Split the wall into a grid of cells using the edges of the openings. Every cell is
+        // either completely solid or completely empty.
+        let mut xs = vec![0.0, width];
+        let mut ys = vec![0.0, height];
+        for opening in openings {
+            let min = opening.position;
+            let max = opening.position + opening.size;
+            xs.extend([min.x.clamp(0.0, width), max.x.clamp(0.0, width)]);
+            ys.extend([min.y.clamp(0.0, height), max.y.clamp(0.0, height)]);
+        }
+        let xs = sorted_cuts(xs);
+        let ys = sorted_cuts(ys);
+
+        let columns = xs.len() - 1;
+        let rows = ys.len() - 1;
+        let mut solid = vec![true; columns * rows];
+        for row in 0..rows {
+            for column in 0..columns {
+                let center = Vector2::new(
+                    (xs[column] + xs[column + 1]) * 0.5,
+                    (ys[row] + ys[row + 1]) * 0.5,
+                );
+                solid[row * columns + column] = !openings.iter().any(|o| {
+                    let max = o.position + o.size;
+                    center.x > o.position.x
+                        && center.x < max.x
+                        && center.y > o.position.y
+                        && center.y < max.y
+                });
+            }
+        }
+        let is_solid = |column: isize, row: isize| {
+            column >= 0
+                && row >= 0
+                && (column as usize) < columns
+                && (row as usize) < rows
+                && solid[row as usize * columns + column as usize]
+        };
+
+        let hw = width * 0.5;
+        let ht = thickness * 0.5;
+        let point = |column: usize, row: usize, z: f32| Vector3::new(xs[column] - hw, ys[row], z);
+
+        for row in 0..rows {
+            for column in 0..columns {
+                if !is_solid(column as isize, row as isize) {
+                    continue;
+                }
+
+                let (c, r) = (column as isize, row as isize);
+
+                // Front and back.
+                for (z, outward) in [(ht, Vector3::z()), (-ht, -Vector3::z())] {
+                    builder.face(
+                        &[
+                            point(column, row, z),
+                            point(column + 1, row, z),
+                            point(column + 1, row + 1, z),
+                            point(column, row + 1, z),
+                        ],
+                        outward,
+                    );
+                }
+
+                // Sides, that face empty cells or the outside of the wall.
+                let sides = [
+                    (c - 1, r, (column, row), (column, row + 1), -Vector3::x()),
+                    (
+                        c + 1,
+                        r,
+                        (column + 1, row),
+                        (column + 1, row + 1),
+                        Vector3::x(),
+                    ),
+                    (c, r - 1, (column, row), (column + 1, row), -Vector3::y()),
+                    (
+                        c,
+                        r + 1,
+                        (column, row + 1),
+                        (column + 1, row + 1),
+                        Vector3::y(),
+                    ),
+                ];
+                for (neighbour_column, neighbour_row, a, b, outward) in sides {
+                    if !is_solid(neighbour_column, neighbour_row) {
+                        builder.face(
+                            &[
+                                point(a.0, a.1, -ht),
+                                point(b.0, b.1, -ht),
+                                point(b.0, b.1, ht),
+                                point(a.0, a.1, ht),
+                            ],
+                            outward,
+                        );
+                    }
+                }
+            }
+        }
+
+        builder.build(transform)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Matrix4, Vector2, Vector3},
+        scene::mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait},
+            primitives::{UvMapping, WallOpening},
+            surface::SurfaceData,
+        },
+    };
+
+    fn positions(data: &SurfaceData) -> Vec<Vector3<f32>> {
+        data.vertex_buffer
+            .iter()
+            .map(|v| v.read_3_f32(VertexAttributeUsage::Position).unwrap())
+            .collect()
+    }
+
+    fn bounds(data: &SurfaceData) -> (Vector3<f32>, Vector3<f32>) {
+        positions(data).into_iter().fold(
+            (Vector3::repeat(f32::MAX), Vector3::repeat(-f32::MAX)),
+            |(min, max), p| (min.inf(&p), max.sup(&p)),
+        )
+    }
+
+    // Every face of a convex primitive must face away from its center.
+    fn assert_faces_outward(data: &SurfaceData, center: Vector3<f32>) {
+        let positions = positions(data);
+        for triangle in data.geometry_buffer.iter() {
+            let [a, b, c] = triangle.0.map(|i| positions[i as usize]);
+            let normal = (b - a).cross(&(c - a));
+            assert!(normal.dot(&(a - center)) > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_primitives() {
+        let identity = Matrix4::identity();
+
+        let ramp = SurfaceData::make_ramp(1.0, 2.0, 3.0, UvMapping::Stretch, &identity);
+        assert_eq!(
+            bounds(&ramp),
+            (Vector3::new(-0.5, 0.0, -1.5), Vector3::new(0.5, 2.0, 1.5))
+        );
+        assert_faces_outward(&ramp, Vector3::new(0.0, 0.5, 0.5));
+
+        let pipe = SurfaceData::make_pipe(8, 1.0, 0.0, 2.0, UvMapping::default(), &identity);
+        assert_faces_outward(&pipe, Vector3::new(0.0, 1.0, 0.0));
+
+        let stairs = SurfaceData::make_stairs(1.0, 1.0, 2.0, 4, UvMapping::default(), &identity);
+        assert_eq!(
+            bounds(&stairs),
+            (Vector3::new(-0.5, 0.0, -1.0), Vector3::new(0.5, 1.0, 1.0))
+        );
+
+        let arch = SurfaceData::make_arch(2.0, 3.0, 0.5, 1.0, 8, UvMapping::default(), &identity);
+        let (min, max) = bounds(&arch);
+        assert!((max.y - 3.0).abs() < 1.0e-5);
+        assert!((min.x + 1.0).abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_wall_openings() {
+        let identity = Matrix4::identity();
+
+        let solid = SurfaceData::make_wall(4.0, 3.0, 0.2, &[], UvMapping::default(), &identity);
+        // Just a box.
+        assert_eq!(solid.geometry_buffer.len(), 12);
+
+        let door = WallOpening {
+            position: Vector2::new(1.0, 0.0),
+            size: Vector2::new(1.0, 2.0),
+        };
+        let wall = SurfaceData::make_wall(4.0, 3.0, 0.2, &[door], UvMapping::default(), &identity);
+        assert_eq!(
+            bounds(&wall),
+            (Vector3::new(-2.0, 0.0, -0.1), Vector3::new(2.0, 3.0, 0.1))
+        );
+        // There must be no geometry inside the door.
+        assert!(positions(&wall)
+            .iter()
+            .all(|p| !(p.x > -1.0 && p.x < 0.0 && p.y < 2.0)));
+    }
+}
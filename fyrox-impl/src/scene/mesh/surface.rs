@@ -31,6 +31,7 @@ use crate::{
                 TriangleBuffer, VertexAttributeUsage, VertexBuffer, VertexFetchError,
                 VertexReadTrait, VertexTrait, VertexWriteTrait,
             },
+            optimization,
             vertex::StaticVertex,
         },
         node::Node,
@@ -1036,6 +1037,50 @@ impl SurfaceData {
         )
     }
 
+    /// Merges the vertices, that have the same attributes within the given tolerance, and discards
+    /// unused vertices. Returns the amount of removed vertices. Surfaces with blend shapes are left
+    /// unchanged, because blend shapes are bound to vertex indices. See
+    /// [`optimization::weld_vertices`] for more info.
+    pub fn weld_vertices(&mut self, tolerance: f32) -> usize {
+        if self.blend_shapes_container.is_some() {
+            return 0;
+        }
+
+        let mut triangles = self.geometry_buffer.triangles_ref().to_vec();
+        let removed =
+            optimization::weld_vertices(&mut self.vertex_buffer, &mut triangles, tolerance);
+        self.geometry_buffer.set_triangles(triangles);
+        removed
+    }
+
+    /// Reorders the triangles to improve utilization of the post-transform vertex cache of a GPU
+    /// and then reorders the vertices in the order of their use. Vertices of surfaces with blend
+    /// shapes are never reordered. See [`optimization::optimize_vertex_cache`] for more info.
+    pub fn optimize_vertex_cache(&mut self) {
+        let mut triangles = optimization::optimize_vertex_cache(
+            self.geometry_buffer.triangles_ref(),
+            self.vertex_buffer.vertex_count() as usize,
+        );
+        if self.blend_shapes_container.is_none() {
+            optimization::optimize_vertex_fetch(&mut self.vertex_buffer, &mut triangles);
+        }
+        self.geometry_buffer.set_triangles(triangles);
+    }
+
+    /// Reorders the triangles to reduce overdraw, it should be used after
+    /// [`Self::optimize_vertex_cache`]. See [`optimization::optimize_overdraw`] for more info.
+    pub fn optimize_overdraw(&mut self) -> Result<(), VertexFetchError> {
+        let positions = self
+            .vertex_buffer
+            .iter()
+            .map(|v| v.read_3_f32(VertexAttributeUsage::Position))
+            .collect::<Result<Vec<_>, _>>()?;
+        let triangles =
+            optimization::optimize_overdraw(self.geometry_buffer.triangles_ref(), &positions);
+        self.geometry_buffer.set_triangles(triangles);
+        Ok(())
+    }
+
     /// Clears both vertex and index buffers.
     pub fn clear(&mut self) {
         self.geometry_buffer.modify().clear();
@@ -6,27 +6,40 @@
 
 pub mod accel;
 pub mod animation;
+pub mod ao_volume;
 pub mod base;
 pub mod camera;
+pub mod character;
 pub mod collider;
+pub mod day_night;
 pub mod debug;
 pub mod decal;
 pub mod dim2;
 pub mod graph;
+pub mod inventory;
 pub mod joint;
 pub mod light;
 pub mod mesh;
 pub mod navmesh;
 pub mod node;
+pub mod ocean;
 pub mod particle_system;
 pub mod pivot;
+pub mod portal;
+pub mod prefab_pool;
+pub mod probe_volume;
 pub mod ragdoll;
 pub mod rigidbody;
+pub mod smoke;
 pub mod sound;
 pub mod sprite;
+pub mod stencil_mask;
 pub mod terrain;
 pub mod tilemap;
+pub mod timeline;
 pub mod transform;
+pub mod vector_shape;
+pub mod weather;
 
 use crate::{
     asset::{self, manager::ResourceManager, untyped::UntypedResource},
@@ -37,33 +50,41 @@ use crate::{
         log::{Log, MessageKind},
         pool::{Handle, Pool, Ticket},
         reflect::prelude::*,
+        rng::RngStreams,
+        uuid::Uuid,
+        uuid_provider,
         visitor::{Visit, VisitError, VisitResult, Visitor},
     },
     engine::SerializationContext,
     graph::NodeHandleMap,
-    renderer::framework::state::PolygonFillMode,
+    renderer::{framework::state::PolygonFillMode, QualitySettings},
     resource::texture::TextureResource,
     scene::{
         base::BaseBuilder,
         camera::Camera,
+        day_night::DayNightCycle,
         debug::SceneDrawingContext,
         graph::{Graph, GraphPerformanceStatistics, GraphUpdateSwitches},
         navmesh::NavigationalMeshBuilder,
         node::Node,
         sound::SoundEngine,
+        weather::Weather,
     },
     utils::navmesh::Navmesh,
 };
 use asset::io::ResourceIo;
 use fxhash::FxHashSet;
 use fyrox_core::variable::InheritableVariable;
+use fyrox_graph::BaseSceneGraph;
 use std::{
+    cell::Cell,
     fmt::{Display, Formatter},
     ops::{Index, IndexMut},
     path::Path,
     path::PathBuf,
     sync::Arc,
 };
+use strum_macros::{AsRefStr, EnumString, VariantNames};
 
 /// A container for navigational meshes.
 #[derive(Default, Clone, Debug, Visit)]
@@ -142,6 +163,82 @@ impl IndexMut<Handle<Navmesh>> for NavMeshContainer {
     }
 }
 
+/// Defines how often a scene with a render target is rendered. Scenes, that render directly on
+/// screen, are always rendered every frame.
+#[derive(
+    Debug, Visit, Reflect, PartialEq, Clone, Copy, Default, AsRefStr, EnumString, VariantNames,
+)]
+pub enum RenderTargetUpdateMode {
+    /// The scene is rendered every frame.
+    #[default]
+    EveryFrame,
+    /// The scene is rendered once per the given amount of frames. It is useful for render targets,
+    /// that do not need to be updated every frame, such as minimaps or security monitors.
+    Interval {
+        /// Amount of frames between two subsequent renders of the scene.
+        frames: u32,
+    },
+    /// The scene is rendered only once after creation and then only when requested using
+    /// [`Scene::request_render`]. It is useful for render targets, that change
+    /// rarely, such as pictograms of characters.
+    OnDemand,
+}
+
+uuid_provider!(RenderTargetUpdateMode = "0d9b3c57-6a1e-4f2b-9c84-3e7a51d2f6b0");
+
+/// Quality settings of a scene with a render target. Render targets are usually small and do not
+/// need every effect of the main scene, these settings allow you to disable some of the effects
+/// for a particular render target. The settings can only lower the quality defined by the quality
+/// settings of the renderer, an effect that is disabled in the renderer stays disabled.
+#[derive(Debug, Visit, Reflect, PartialEq, Clone, Copy)]
+pub struct RenderTargetQuality {
+    /// Whether the shadows of every kind of light sources are enabled or not.
+    pub shadows: bool,
+    /// Whether screen-space ambient occlusion is enabled or not.
+    pub ssao: bool,
+    /// Whether screen-space reflections are enabled or not.
+    pub ssr: bool,
+    /// Whether light scattering is enabled or not.
+    pub light_scattering: bool,
+    /// Whether bloom is enabled or not.
+    pub bloom: bool,
+    /// Whether FXAA is enabled or not.
+    pub fxaa: bool,
+    /// Whether parallax mapping is enabled or not.
+    pub parallax_mapping: bool,
+}
+
+impl Default for RenderTargetQuality {
+    fn default() -> Self {
+        Self {
+            shadows: true,
+            ssao: true,
+            ssr: true,
+            light_scattering: true,
+            bloom: true,
+            fxaa: true,
+            parallax_mapping: true,
+        }
+    }
+}
+
+impl RenderTargetQuality {
+    /// Applies the settings to the given quality settings of the renderer.
+    pub fn apply(&self, settings: &QualitySettings) -> QualitySettings {
+        let mut settings = *settings;
+        settings.point_shadows_enabled &= self.shadows;
+        settings.spot_shadows_enabled &= self.shadows;
+        settings.csm_settings.enabled &= self.shadows;
+        settings.use_ssao &= self.ssao;
+        settings.ssr_settings.enabled &= self.ssr;
+        settings.light_scatter_enabled &= self.light_scattering;
+        settings.use_bloom &= self.bloom;
+        settings.fxaa &= self.fxaa;
+        settings.use_parallax_mapping &= self.parallax_mapping;
+        settings
+    }
+}
+
 /// Rendering options of a scene. It allows you to specify a render target to render the scene to, change its clear color, etc.
 #[derive(Debug, Visit, Reflect, PartialEq)]
 pub struct SceneRenderingOptions {
@@ -162,6 +259,16 @@ pub struct SceneRenderingOptions {
 
     /// Color of the ambient lighting.
     pub ambient_lighting_color: Color,
+
+    /// Defines how often the scene is rendered into its render target. It has no effect if there's
+    /// no render target. See [`RenderTargetUpdateMode`] docs for more info.
+    #[visit(optional)]
+    pub render_target_update_mode: RenderTargetUpdateMode,
+
+    /// Quality settings of the scene. It has no effect if there's no render target. See
+    /// [`RenderTargetQuality`] docs for more info.
+    #[visit(optional)]
+    pub render_target_quality: RenderTargetQuality,
 }
 
 impl Default for SceneRenderingOptions {
@@ -171,6 +278,8 @@ impl Default for SceneRenderingOptions {
             clear_color: None,
             polygon_rasterization_mode: Default::default(),
             ambient_lighting_color: Color::opaque(100, 100, 100),
+            render_target_update_mode: Default::default(),
+            render_target_quality: Default::default(),
         }
     }
 }
@@ -182,6 +291,49 @@ impl Clone for SceneRenderingOptions {
             clear_color: self.clear_color,
             polygon_rasterization_mode: self.polygon_rasterization_mode,
             ambient_lighting_color: self.ambient_lighting_color,
+            render_target_update_mode: self.render_target_update_mode,
+            render_target_quality: self.render_target_quality,
+        }
+    }
+}
+
+/// Defines how a scene is composed with other scenes of the engine. It allows you to build explicit
+/// multi-scene workflows - for example, a background scene, a gameplay scene and a 3D UI scene on top
+/// of them:
+///
+/// - Scenes are updated in ascending order of [`Self::update_order`] and rendered into the back
+///   buffer in ascending order of [`Self::render_order`]. Scenes with the same order keep their
+///   order in the [`SceneContainer`].
+/// - Every scene has its own time scale, that affects the graph (physics, animations, etc.) and the
+///   scripts of the scene. It could be used to slow down the gameplay, while the UI scene runs in
+///   real time.
+/// - Scenes could be blended over the previously rendered scenes using the alpha of their frame
+///   (see [`SceneRenderingOptions::clear_color`]).
+///
+/// Use [`CrossSceneHandle`] to reference nodes of other scenes.
+#[derive(Debug, Clone, Copy, PartialEq, Visit, Reflect)]
+pub struct SceneComposition {
+    /// Scenes with lower update order are updated first.
+    pub update_order: i32,
+    /// Scenes with lower render order are rendered into the back buffer first, so the scenes with
+    /// higher order are drawn on top of them. It has no effect for scenes with render target.
+    pub render_order: i32,
+    /// A multiplier for the time step of the scene. `0.0` freezes the scene, `1.0` - normal speed.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub time_scale: f32,
+    /// If `true`, the frame of the scene is blended with the content of the back buffer using its
+    /// alpha channel. Otherwise, the frame replaces the content of the back buffer. Set the clear
+    /// color of the scene to a transparent color to see the previous scenes through it.
+    pub alpha_blend: bool,
+}
+
+impl Default for SceneComposition {
+    fn default() -> Self {
+        Self {
+            update_order: 0,
+            render_order: 0,
+            time_scale: 1.0,
+            alpha_blend: false,
         }
     }
 }
@@ -213,6 +365,30 @@ pub struct Scene {
     /// to false for menu's scene and when you need to open a menu - set it to true and
     /// set `enabled` flag to false for level's scene.
     pub enabled: InheritableVariable<bool>,
+
+    /// Deterministic random numbers streams of the scene. Use them instead of `thread_rng` for any
+    /// randomness that affects the simulation (gameplay, ai, procedural generation, etc.), so replays
+    /// and networked simulations do not diverge. See [`RngStreams`] docs for more info.
+    #[reflect(hidden)]
+    pub rng: RngStreams,
+
+    /// Weather of the scene - precipitation, wetness and snow coverage of the surfaces, wind. See
+    /// [`Weather`] docs for more info.
+    pub weather: Weather,
+
+    /// Day/night cycle of the scene - time of day, that drives sun and moon lights, ambient
+    /// lighting and sky color. See [`DayNightCycle`] docs for more info.
+    pub day_night: DayNightCycle,
+
+    /// Defines how the scene is composed with other scenes (update and render order, time scale,
+    /// etc.). See [`SceneComposition`] docs for more info.
+    pub composition: SceneComposition,
+
+    #[reflect(hidden)]
+    id: SceneId,
+
+    #[reflect(hidden)]
+    render_requested: Cell<bool>,
 }
 
 impl Default for Scene {
@@ -223,6 +399,12 @@ impl Default for Scene {
             drawing_context: Default::default(),
             performance_statistics: Default::default(),
             enabled: true.into(),
+            rng: Default::default(),
+            weather: Default::default(),
+            day_night: Default::default(),
+            composition: Default::default(),
+            id: SceneId(Uuid::new_v4()),
+            render_requested: Cell::new(false),
         }
     }
 }
@@ -241,6 +423,7 @@ impl Display for PerformanceStatistics {
             "Graph: {:?}\n\
             \tSync Time: {:?}\n\
             \tSound: {:?}\n\
+            \tAnimation: {:?}\n\
             \tPhysics: {:?}\n\
             \t\tSimulation: {:?}\n\
             \t\tRay cast: {:?}\n\
@@ -251,6 +434,7 @@ impl Display for PerformanceStatistics {
             self.graph.total(),
             self.graph.sync_time,
             self.graph.sound_update_time,
+            self.graph.animation_time,
             self.graph.physics.total(),
             self.graph.physics.step_time,
             self.graph.physics.total_ray_cast_time.get(),
@@ -384,9 +568,33 @@ impl Scene {
             drawing_context: Default::default(),
             performance_statistics: Default::default(),
             enabled: true.into(),
+            rng: Default::default(),
+            weather: Default::default(),
+            day_night: Default::default(),
+            composition: Default::default(),
+            id: SceneId(Uuid::new_v4()),
+            render_requested: Cell::new(false),
         }
     }
 
+    /// Returns the unique id of the scene. See [`SceneId`] docs for more info.
+    pub fn id(&self) -> SceneId {
+        self.id
+    }
+
+    /// Requests the renderer to render the scene into its render target on the next frame. It is
+    /// used together with [`RenderTargetUpdateMode::OnDemand`], but also forces rendering of a
+    /// scene with [`RenderTargetUpdateMode::Interval`]. It has no effect if the scene has no render
+    /// target.
+    pub fn request_render(&self) {
+        self.render_requested.set(true);
+    }
+
+    /// Returns `true` if the scene was requested to be rendered and resets the request.
+    pub(crate) fn take_render_request(&self) -> bool {
+        self.render_requested.replace(false)
+    }
+
     /// Synchronizes the state of the scene with external resources.
     pub fn resolve(&mut self, resource_manager: &ResourceManager) {
         Log::writeln(MessageKind::Information, "Starting resolve...");
@@ -408,6 +616,17 @@ impl Scene {
     /// it updates physics, animations, and each graph node. In most cases there is
     /// no need to call it directly, engine automatically updates all available scenes.
     pub fn update(&mut self, frame_size: Vector2<f32>, dt: f32, switches: GraphUpdateSwitches) {
+        if !switches.paused {
+            self.weather.update(dt, &mut self.graph);
+        }
+        // Time does not flow in paused scenes and in scenes with node overrides (the editor), but
+        // the cycle is still applied, so any time of day could be previewed.
+        let time_flows = !switches.paused && switches.node_overrides.is_none();
+        self.day_night.update(
+            if time_flows { dt } else { 0.0 },
+            &mut self.graph,
+            self.rendering_options.get_value_mut_silent(),
+        );
         self.graph.update(frame_size, dt, switches);
         self.performance_statistics.graph = self.graph.performance_statistics.clone();
     }
@@ -430,6 +649,19 @@ impl Scene {
             self.graph
                 .clone(root, filter, pre_process_callback, post_process_callback);
 
+        let mut weather = self.weather.clone();
+        let mut day_night = self.day_night.clone();
+        for handle in [
+            &mut weather.rain_sound,
+            &mut weather.wind_sound,
+            &mut day_night.sun,
+            &mut day_night.moon,
+        ] {
+            if !old_new_map.try_map(handle) {
+                *handle = Handle::NONE;
+            }
+        }
+
         (
             Self {
                 graph,
@@ -437,6 +669,12 @@ impl Scene {
                 drawing_context: self.drawing_context.clone(),
                 performance_statistics: Default::default(),
                 enabled: self.enabled.clone(),
+                rng: self.rng.clone(),
+                weather,
+                day_night,
+                composition: self.composition,
+                id: self.id,
+                render_requested: Cell::new(false),
             },
             old_new_map,
         )
@@ -461,6 +699,11 @@ impl Scene {
         let _ = self
             .rendering_options
             .visit("RenderingOptions", &mut region);
+        let _ = self.rng.visit("Rng", &mut region);
+        let _ = self.weather.visit("Weather", &mut region);
+        let _ = self.day_night.visit("DayNight", &mut region);
+        let _ = self.composition.visit("Composition", &mut region);
+        let _ = self.id.visit("Id", &mut region);
 
         // Backward compatibility.
         let mut navmeshes = NavMeshContainer::default();
@@ -524,6 +767,44 @@ impl Scene {
     }
 }
 
+/// Unique id of a scene. Unlike the handle of the scene in the [`SceneContainer`], the id is saved
+/// together with the scene, so it stays the same when the scene is saved and loaded back. Keep in
+/// mind, that every scene loaded from the same file has the same id.
+#[derive(Clone, Copy, Eq, Hash, PartialEq, Default, Debug, Reflect)]
+#[repr(transparent)]
+#[reflect(hide_all)]
+pub struct SceneId(pub Uuid);
+
+impl Visit for SceneId {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        self.0.visit(name, visitor)
+    }
+}
+
+/// A reference to a node of a specific scene. It could be used to safely reference nodes of other
+/// scenes (for example, a 3D UI scene could follow a node of a gameplay scene). The scene is
+/// referenced by its [`SceneId`], so the reference could be saved (in a script, for example) and
+/// stays valid after the scenes are loaded back in any order. The node handle is generational, so
+/// the reference never resolves to a wrong node, even if the node was destroyed and its place was
+/// taken by another one. Use [`SceneContainer::try_get_node`] to resolve the reference.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Visit, Reflect)]
+pub struct CrossSceneHandle {
+    /// An id of the scene.
+    pub scene: SceneId,
+    /// A handle of the node in the scene.
+    pub node: Handle<Node>,
+}
+
+impl CrossSceneHandle {
+    /// Creates new reference to a node of the given scene.
+    pub fn new(scene: &Scene, node: Handle<Node>) -> Self {
+        Self {
+            scene: scene.id,
+            node,
+        }
+    }
+}
+
 /// Container for scenes in the engine.
 pub struct SceneContainer {
     pool: Pool<Scene>,
@@ -565,6 +846,53 @@ impl SceneContainer {
         self.pool.try_borrow_mut(handle)
     }
 
+    /// Searches for a scene with the given id and returns its handle. If there are multiple scenes
+    /// with the same id, the first one is returned.
+    pub fn find_by_id(&self, id: SceneId) -> Handle<Scene> {
+        self.pool
+            .pair_iter()
+            .find_map(|(handle, scene)| (scene.id == id).then_some(handle))
+            .unwrap_or_default()
+    }
+
+    /// Tries to borrow a node of a scene using its cross-scene handle. Returns `None` if either the
+    /// scene or the node does not exist anymore.
+    pub fn try_get_node(&self, handle: CrossSceneHandle) -> Option<&Node> {
+        self.try_get(self.find_by_id(handle.scene))
+            .and_then(|scene| scene.graph.try_get(handle.node))
+    }
+
+    /// Tries to borrow a node of a scene using its cross-scene handle. Returns `None` if either the
+    /// scene or the node does not exist anymore.
+    pub fn try_get_node_mut(&mut self, handle: CrossSceneHandle) -> Option<&mut Node> {
+        let scene = self.find_by_id(handle.scene);
+        self.try_get_mut(scene)
+            .and_then(|scene| scene.graph.try_get_mut(handle.node))
+    }
+
+    fn ordered_handles(&self, order: impl Fn(&SceneComposition) -> i32) -> Vec<Handle<Scene>> {
+        let mut handles = self
+            .pool
+            .pair_iter()
+            .map(|(handle, scene)| (handle, order(&scene.composition)))
+            .collect::<Vec<_>>();
+        // The sort is stable, so the scenes with the same order keep their order in the pool.
+        handles.sort_by_key(|(_, order)| *order);
+        handles.into_iter().map(|(handle, _)| handle).collect()
+    }
+
+    /// Returns handles of all scenes in the order of their update. See [`SceneComposition`] docs
+    /// for more info.
+    pub fn update_order(&self) -> Vec<Handle<Scene>> {
+        self.ordered_handles(|composition| composition.update_order)
+    }
+
+    /// Returns handles of all scenes in the order of their rendering. See [`SceneComposition`] docs
+    /// for more info.
+    pub fn render_order(&self) -> Vec<Handle<Scene>> {
+        self.ordered_handles(|composition| composition.render_order)
+    }
+
     /// Creates new iterator over scenes in container.
     #[inline]
     pub fn iter(&self) -> impl Iterator<Item = &Scene> {
@@ -634,3 +962,61 @@ impl IndexMut<Handle<Scene>> for SceneContainer {
         &mut self.pool[index]
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::asset::manager::ResourceManager;
+    use crate::core::visitor::Visitor;
+    use crate::engine::SerializationContext;
+    use crate::graph::BaseSceneGraph;
+    use crate::scene::{
+        base::BaseBuilder, pivot::PivotBuilder, sound::SoundEngine, CrossSceneHandle, Scene,
+        SceneContainer, SceneLoader,
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn test_scene_composition() {
+        let mut container = SceneContainer::new(SoundEngine::without_device());
+
+        let mut background = Scene::new();
+        background.composition.render_order = -1;
+        background.composition.update_order = 1;
+        let background = container.add(background);
+
+        let mut gameplay = Scene::new();
+        let node = PivotBuilder::new(BaseBuilder::new()).build(&mut gameplay.graph);
+        let gameplay = container.add(gameplay);
+
+        let ui = container.add(Scene::new());
+
+        assert_eq!(container.render_order(), vec![background, gameplay, ui]);
+        assert_eq!(container.update_order(), vec![gameplay, ui, background]);
+
+        let reference = CrossSceneHandle::new(&container[gameplay], node);
+        assert!(container.try_get_node(reference).is_some());
+
+        // The scene is found by its id, even if it was saved and loaded back to some other place.
+        let mut visitor = Visitor::new();
+        container[gameplay].save("Scene", &mut visitor).unwrap();
+        let mut visitor =
+            Visitor::load_from_memory(&visitor.save_binary_to_vec().unwrap()).unwrap();
+        let loader = SceneLoader::load(
+            "Scene",
+            Arc::new(SerializationContext::new()),
+            ResourceManager::new(Arc::new(Default::default())),
+            &mut visitor,
+            None,
+        )
+        .unwrap();
+        container.remove(gameplay);
+        let gameplay = container.add(loader.scene);
+        assert!(container.try_get_node(reference).is_some());
+
+        container[gameplay].graph.remove_node(node);
+        assert!(container.try_get_node(reference).is_none());
+
+        container.remove(gameplay);
+        assert!(container.try_get_node(reference).is_none());
+    }
+}
@@ -9,6 +9,7 @@ use crate::{
         pool::Handle,
         reflect::prelude::*,
         uuid::{uuid, Uuid},
+        uuid_provider,
         variable::InheritableVariable,
         visitor::prelude::*,
         TypeUuidProvider,
@@ -17,10 +18,17 @@ use crate::{
         base::{Base, BaseBuilder},
         debug::{Line, SceneDrawingContext},
         graph::Graph,
-        node::{Node, NodeTrait},
+        node::{Node, NodeTrait, UpdateContext},
+    },
+    utils::navmesh::{
+        bake::{
+            collect_triangles, NavmeshBakeSettings, NavmeshObstacleShape, NavmeshObstacleVolume,
+            TiledNavmeshBaker,
+        },
+        Navmesh,
     },
-    utils::navmesh::Navmesh,
 };
+use fxhash::FxHashSet;
 use fyrox_core::parking_lot::{RwLockReadGuard, RwLockWriteGuard};
 use fyrox_graph::BaseSceneGraph;
 use std::{
@@ -43,6 +51,43 @@ impl Visit for Container {
     }
 }
 
+/// An obstacle, that carves its volume out of a dynamic navigational mesh. The obstacle follows the
+/// global transform of its node, so doors, movable crates, destroyed bridges, etc. could change the
+/// navmesh at runtime. See [`NavigationalMesh`] docs for more info.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct NavmeshObstacle {
+    /// A node, that defines the position, rotation and scale of the obstacle. Geometry of the node
+    /// (and its descendants) is excluded from the static geometry of the navmesh.
+    pub node: Handle<Node>,
+    /// Shape of the obstacle in local coordinates of the node.
+    pub shape: NavmeshObstacleShape,
+    /// Disabled obstacles (as well as obstacles with disabled nodes) do not affect the navmesh.
+    pub enabled: bool,
+}
+
+uuid_provider!(NavmeshObstacle = "53b573c4-457c-44e7-afbe-58eb432460ea");
+
+impl Default for NavmeshObstacle {
+    fn default() -> Self {
+        Self {
+            node: Default::default(),
+            shape: Default::default(),
+            enabled: true,
+        }
+    }
+}
+
+// Runtime state of the dynamic baking, it is not saved and not cloned - a copy of the node bakes
+// its own navmesh on the first update.
+#[derive(Default, Debug)]
+struct BakerState(Option<Box<TiledNavmeshBaker>>);
+
+impl Clone for BakerState {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
 /// Navigational mesh (navmesh for short) is a surface which can be used for path finding. Unlike [A* Pathfinder](crate::utils::astar),
 /// it can build arbitrary paths on a surface of large polygons, making a path from point A to point B linear (standard pathfinder builds
 /// path only from vertex to vertex). Navmeshes should be used when you have an arbitrary "walkable" surface, for example, a game level
@@ -130,11 +175,50 @@ impl Visit for Container {
 ///     scene.graph[handle].as_navigational_mesh_mut()
 /// }
 /// ```
+///
+/// ## Dynamic obstacles
+///
+/// When dynamic baking is enabled (see [`NavigationalMesh::set_dynamic_baking`]), the navmesh is
+/// baked from the scene geometry (using [`NavmeshBakeSettings`]) on the first update and then every
+/// obstacle (see [`NavmeshObstacle`]) carves its volume out of it. The scene is split into tiles, and
+/// only the tiles touched by added, removed or moved obstacles are re-baked, so opening a door or
+/// pushing a crate is cheap. Changes of the static geometry are not tracked, call
+/// [`NavigationalMesh::rebake`] if it has changed.
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     scene::{
+/// #         base::BaseBuilder,
+/// #         graph::Graph,
+/// #         navmesh::{NavigationalMeshBuilder, NavmeshObstacle},
+/// #         node::Node,
+/// #     },
+/// # };
+/// fn create_dynamic_navmesh(graph: &mut Graph, door: Handle<Node>) -> Handle<Node> {
+///     NavigationalMeshBuilder::new(BaseBuilder::new())
+///         .with_dynamic_baking(true)
+///         .with_obstacles(vec![NavmeshObstacle {
+///             node: door,
+///             ..Default::default()
+///         }])
+///         .build(graph)
+/// }
+/// ```
 #[derive(Debug, Clone, Visit, Reflect, Default)]
 pub struct NavigationalMesh {
     base: Base,
     #[reflect(read_only)]
     navmesh: InheritableVariable<Container>,
+    #[visit(optional)]
+    dynamic_baking: InheritableVariable<bool>,
+    #[visit(optional)]
+    bake_settings: InheritableVariable<NavmeshBakeSettings>,
+    #[visit(optional)]
+    obstacles: InheritableVariable<Vec<NavmeshObstacle>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    baker: BakerState,
 }
 
 impl TypeUuidProvider for NavigationalMesh {
@@ -189,6 +273,51 @@ impl NodeTrait for NavigationalMesh {
             }
         }
     }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        if !*self.dynamic_baking {
+            self.baker.0 = None;
+            return;
+        }
+
+        let nodes = &*context.nodes;
+
+        let mut baked = false;
+        let baker = self.baker.0.get_or_insert_with(|| {
+            let excluded = self
+                .obstacles
+                .iter()
+                .map(|o| o.node)
+                .collect::<FxHashSet<_>>();
+            let triangles = collect_triangles(
+                |handle| nodes.try_borrow(handle),
+                nodes.pair_iter(),
+                &excluded,
+                &self.bake_settings,
+            );
+            baked = true;
+            Box::new(TiledNavmeshBaker::new(&triangles, &self.bake_settings))
+        });
+
+        let volumes = self
+            .obstacles
+            .iter()
+            .filter(|o| o.enabled)
+            .filter_map(|o| {
+                nodes
+                    .try_borrow(o.node)
+                    .filter(|n| n.is_globally_enabled())
+                    .map(|n| NavmeshObstacleVolume {
+                        transform: n.global_transform(),
+                        shape: o.shape,
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        if baker.set_obstacles(volumes) || baked {
+            *self.navmesh.0.write() = baker.navmesh();
+        }
+    }
 }
 
 impl NavigationalMesh {
@@ -207,12 +336,70 @@ impl NavigationalMesh {
     pub fn navmesh(&self) -> Arc<RwLock<Navmesh>> {
         self.navmesh.0.clone()
     }
+
+    /// Enables or disables dynamic baking. See [`NavigationalMesh`] docs for more info. Returns the
+    /// previous value.
+    pub fn set_dynamic_baking(&mut self, enabled: bool) -> bool {
+        self.dynamic_baking.set_value_and_mark_modified(enabled)
+    }
+
+    /// Returns `true` if the navmesh is baked from the scene geometry and updated by obstacles.
+    pub fn is_dynamic_baking(&self) -> bool {
+        *self.dynamic_baking
+    }
+
+    /// Sets new settings for dynamic baking. The navmesh will be fully re-baked on the next update.
+    /// Returns the previous settings.
+    pub fn set_bake_settings(&mut self, settings: NavmeshBakeSettings) -> NavmeshBakeSettings {
+        self.rebake();
+        self.bake_settings.set_value_and_mark_modified(settings)
+    }
+
+    /// Returns current settings of dynamic baking.
+    pub fn bake_settings(&self) -> &NavmeshBakeSettings {
+        &self.bake_settings
+    }
+
+    /// Sets new obstacles. The navmesh will be fully re-baked on the next update, because the nodes
+    /// of the obstacles are excluded from the static geometry. Move, enable or disable existing
+    /// obstacles (or their nodes) for cheap partial updates. Returns the previous obstacles.
+    pub fn set_obstacles(&mut self, obstacles: Vec<NavmeshObstacle>) -> Vec<NavmeshObstacle> {
+        self.rebake();
+        self.obstacles.set_value_and_mark_modified(obstacles)
+    }
+
+    /// Returns current obstacles.
+    pub fn obstacles(&self) -> &[NavmeshObstacle] {
+        &self.obstacles
+    }
+
+    /// Returns a mutable reference to an obstacle with the given index. Changes of the shape and
+    /// of the enabled flag are applied on the next update.
+    pub fn obstacle_mut(&mut self, index: usize) -> Option<&mut NavmeshObstacle> {
+        self.obstacles
+            .get_value_mut_and_mark_modified()
+            .get_mut(index)
+    }
+
+    /// Forces the dynamic navmesh to be fully re-baked on the next update. It should be called when
+    /// the static geometry of the scene has changed.
+    pub fn rebake(&mut self) {
+        self.baker.0 = None;
+    }
+
+    /// Returns the baker of the dynamic navmesh, if the navmesh was baked.
+    pub fn baker(&self) -> Option<&TiledNavmeshBaker> {
+        self.baker.0.as_deref()
+    }
 }
 
 /// Creates navigational meshes and adds them to a scene graph.
 pub struct NavigationalMeshBuilder {
     base_builder: BaseBuilder,
     navmesh: Navmesh,
+    dynamic_baking: bool,
+    bake_settings: NavmeshBakeSettings,
+    obstacles: Vec<NavmeshObstacle>,
 }
 
 impl NavigationalMeshBuilder {
@@ -221,6 +408,9 @@ impl NavigationalMeshBuilder {
         Self {
             base_builder,
             navmesh: Default::default(),
+            dynamic_baking: false,
+            bake_settings: Default::default(),
+            obstacles: Default::default(),
         }
     }
 
@@ -230,12 +420,34 @@ impl NavigationalMeshBuilder {
         self
     }
 
+    /// Enables or disables dynamic baking. See [`NavigationalMesh`] docs for more info.
+    pub fn with_dynamic_baking(mut self, enabled: bool) -> Self {
+        self.dynamic_baking = enabled;
+        self
+    }
+
+    /// Sets the settings of dynamic baking.
+    pub fn with_bake_settings(mut self, settings: NavmeshBakeSettings) -> Self {
+        self.bake_settings = settings;
+        self
+    }
+
+    /// Sets the obstacles of the dynamic navmesh.
+    pub fn with_obstacles(mut self, obstacles: Vec<NavmeshObstacle>) -> Self {
+        self.obstacles = obstacles;
+        self
+    }
+
     fn build_navigational_mesh(self) -> NavigationalMesh {
         NavigationalMesh {
             base: self.base_builder.build_base(),
             navmesh: InheritableVariable::new_modified(Container(Arc::new(RwLock::new(
                 self.navmesh,
             )))),
+            dynamic_baking: self.dynamic_baking.into(),
+            bake_settings: self.bake_settings.into(),
+            obstacles: self.obstacles.into(),
+            baker: Default::default(),
         }
     }
 
@@ -9,7 +9,8 @@ use crate::{
     },
     scene::{
         self,
-        animation::{absm::AnimationBlendingStateMachine, AnimationPlayer},
+        animation::{absm::AnimationBlendingStateMachine, facial::FacialAnimator, AnimationPlayer},
+        ao_volume::AoVolume,
         camera::Camera,
         decal::Decal,
         dim2::{self, rectangle::Rectangle},
@@ -17,12 +18,19 @@ use crate::{
         mesh::Mesh,
         navmesh::NavigationalMesh,
         node::{Node, NodeTrait},
+        ocean::{buoyancy::Buoyancy, Ocean},
         particle_system::ParticleSystem,
         pivot::Pivot,
+        portal::Portal,
+        probe_volume::ProbeVolume,
         ragdoll::Ragdoll,
-        sound::{listener::Listener, Sound},
+        smoke::Smoke,
+        sound::{listener::Listener, reverb_zone::ReverbZone, Sound},
         sprite::Sprite,
+        stencil_mask::StencilMask,
         terrain::Terrain,
+        timeline::TimelinePlayer,
+        vector_shape::VectorShape,
     },
 };
 use fxhash::FxHashMap;
@@ -51,6 +59,7 @@ impl NodeConstructorContainer {
         container.add::<dim2::joint::Joint>();
         container.add::<Rectangle>();
         container.add::<dim2::rigidbody::RigidBody>();
+        container.add::<dim2::skinned_sprite::SkinnedSprite>();
         container.add::<DirectionalLight>();
         container.add::<PointLight>();
         container.add::<SpotLight>();
@@ -58,6 +67,7 @@ impl NodeConstructorContainer {
         container.add::<ParticleSystem>();
         container.add::<Sound>();
         container.add::<Listener>();
+        container.add::<ReverbZone>();
         container.add::<Camera>();
         container.add::<scene::collider::Collider>();
         container.add::<Decal>();
@@ -68,9 +78,19 @@ impl NodeConstructorContainer {
         container.add::<Terrain>();
         container.add::<AnimationPlayer>();
         container.add::<AnimationBlendingStateMachine>();
+        container.add::<FacialAnimator>();
         container.add::<NavigationalMesh>();
         container.add::<Ragdoll>();
         container.add::<TileMap>();
+        container.add::<TimelinePlayer>();
+        container.add::<Smoke>();
+        container.add::<Ocean>();
+        container.add::<Buoyancy>();
+        container.add::<Portal>();
+        container.add::<VectorShape>();
+        container.add::<ProbeVolume>();
+        container.add::<AoVolume>();
+        container.add::<StencilMask>();
 
         container
     }
@@ -0,0 +1,511 @@
+//! Buoyancy makes rigid bodies float on the surface of an [`Ocean`]. See [`Buoyancy`] docs for more
+//! info.
+
+use crate::{
+    core::{
+        algebra::{Vector2, Vector3},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{Node, NodeTrait, UpdateContext},
+        ocean::Ocean,
+        rigidbody::RigidBody,
+    },
+};
+use fyrox_graph::BaseSceneGraph;
+use std::{
+    collections::VecDeque,
+    ops::{Deref, DerefMut},
+};
+
+/// An event, that is produced when a sample point of a [`Buoyancy`] crosses the surface of the
+/// ocean. Events could be used to spawn splash effects or play sounds.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BuoyancyEvent {
+    /// A sample point has entered the water fast enough.
+    Splash {
+        /// Index of the sample point.
+        point: usize,
+        /// World-space position of the sample point.
+        position: Vector3<f32>,
+        /// Speed of the sample point relative to the water, along the normal of the surface.
+        speed: f32,
+    },
+    /// A sample point has left the water.
+    Emerged {
+        /// Index of the sample point.
+        point: usize,
+        /// World-space position of the sample point.
+        position: Vector3<f32>,
+        /// Speed of the sample point relative to the water, along the normal of the surface.
+        speed: f32,
+    },
+}
+
+/// Parameters of the water, that are used to calculate forces applied to a single sample point.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PointForceParameters {
+    /// Gravity vector of the physics world.
+    pub gravity: Vector3<f32>,
+    /// Density of the water.
+    pub density: f32,
+    /// Volume of the water, that is displaced by the point when it is fully submerged.
+    pub volume: f32,
+    /// Linear drag coefficient of the point.
+    pub drag: f32,
+}
+
+/// Calculates a force applied to a sample point, that is submerged by the given fraction (`[0; 1]`
+/// range) and moves with the given velocity relative to the water. The force consists of Archimedes'
+/// buoyancy and linear drag.
+pub fn point_force(
+    parameters: &PointForceParameters,
+    submersion: f32,
+    relative_velocity: Vector3<f32>,
+) -> Vector3<f32> {
+    let submersion = submersion.clamp(0.0, 1.0);
+    let buoyancy = -parameters.gravity * (parameters.density * parameters.volume * submersion);
+    let drag = -relative_velocity * (parameters.drag * submersion);
+    buoyancy + drag
+}
+
+/// Buoyancy makes its parent rigid body float on the surface of an [`Ocean`]. The surface is sampled
+/// at a set of points, every point applies buoyancy and drag forces to the body, depending on how deep
+/// it is submerged. Use more points for large bodies (boats, rafts) to get realistic rolling on the
+/// waves, a single point is enough for small objects (barrels, crates).
+///
+/// # Setup
+///
+/// Buoyancy node must be a direct child of a dynamic rigid body, sample points are defined in local
+/// coordinates of the buoyancy node. Total volume of the body is divided equally between the points,
+/// so a body floats if its density is less than density of the water (`mass < volume * density`).
+///
+/// # Events
+///
+/// Every time a sample point crosses the surface, a [`BuoyancyEvent`] is produced. Splash events are
+/// produced only if the speed of the point is higher than [`Buoyancy::splash_threshold`]. Events
+/// should be fetched every frame using [`Buoyancy::pop_event`], otherwise they'll accumulate.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::Vector3, pool::Handle},
+/// #     scene::{
+/// #         base::BaseBuilder, graph::Graph, node::Node, ocean::buoyancy::BuoyancyBuilder,
+/// #         rigidbody::RigidBodyBuilder,
+/// #     },
+/// # };
+/// fn create_raft(graph: &mut Graph, ocean: Handle<Node>) -> Handle<Node> {
+///     let buoyancy = BuoyancyBuilder::new(BaseBuilder::new())
+///         .with_ocean(ocean)
+///         .with_points(vec![
+///             Vector3::new(-1.0, 0.0, -1.0),
+///             Vector3::new(1.0, 0.0, -1.0),
+///             Vector3::new(1.0, 0.0, 1.0),
+///             Vector3::new(-1.0, 0.0, 1.0),
+///         ])
+///         .with_volume(2.0)
+///         .build(graph);
+///
+///     RigidBodyBuilder::new(BaseBuilder::new().with_children(&[buoyancy]))
+///         .with_mass(500.0)
+///         .build(graph)
+/// }
+/// ```
+#[derive(Debug, Visit, Clone, Reflect)]
+pub struct Buoyancy {
+    base: Base,
+
+    #[reflect(setter = "set_ocean")]
+    ocean: InheritableVariable<Handle<Node>>,
+
+    #[reflect(setter = "set_points")]
+    points: InheritableVariable<Vec<Vector3<f32>>>,
+
+    #[reflect(min_value = 0.0)]
+    #[reflect(setter = "set_volume")]
+    volume: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.001)]
+    #[reflect(setter = "set_point_radius")]
+    point_radius: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0)]
+    #[reflect(setter = "set_linear_drag")]
+    linear_drag: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0)]
+    #[reflect(setter = "set_angular_drag")]
+    angular_drag: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0)]
+    #[reflect(setter = "set_splash_threshold")]
+    splash_threshold: InheritableVariable<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    submerged: Vec<bool>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    submersion: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    events: VecDeque<BuoyancyEvent>,
+}
+
+impl Deref for Buoyancy {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Buoyancy {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl Default for Buoyancy {
+    fn default() -> Self {
+        BuoyancyBuilder::new(BaseBuilder::new()).build_buoyancy()
+    }
+}
+
+impl TypeUuidProvider for Buoyancy {
+    fn type_uuid() -> Uuid {
+        uuid!("a83d5f27-1e6c-4b9a-8d42-f07c3e9b5a18")
+    }
+}
+
+impl Buoyancy {
+    /// Sets a handle of an [`Ocean`] node, on which the body should float.
+    pub fn set_ocean(&mut self, ocean: Handle<Node>) -> Handle<Node> {
+        self.ocean.set_value_and_mark_modified(ocean)
+    }
+
+    /// Returns a handle of the ocean node.
+    pub fn ocean(&self) -> Handle<Node> {
+        *self.ocean
+    }
+
+    /// Sets new sample points in local coordinates of the node.
+    pub fn set_points(&mut self, points: Vec<Vector3<f32>>) -> Vec<Vector3<f32>> {
+        self.points.set_value_and_mark_modified(points)
+    }
+
+    /// Returns sample points in local coordinates of the node.
+    pub fn points(&self) -> &[Vector3<f32>] {
+        &self.points
+    }
+
+    /// Sets total volume (in m³) of the water, that is displaced by the body when it is fully
+    /// submerged.
+    pub fn set_volume(&mut self, volume: f32) -> f32 {
+        self.volume.set_value_and_mark_modified(volume)
+    }
+
+    /// Returns total volume of the displaced water.
+    pub fn volume(&self) -> f32 {
+        *self.volume
+    }
+
+    /// Sets radius (in meters) of every sample point. A point is partially submerged while it is
+    /// closer than the radius to the surface, this gives smooth transition of the buoyancy force.
+    pub fn set_point_radius(&mut self, radius: f32) -> f32 {
+        self.point_radius.set_value_and_mark_modified(radius)
+    }
+
+    /// Returns radius of every sample point.
+    pub fn point_radius(&self) -> f32 {
+        *self.point_radius
+    }
+
+    /// Sets linear drag coefficient of every sample point. Drag slows down motion of the body
+    /// relative to the water.
+    pub fn set_linear_drag(&mut self, drag: f32) -> f32 {
+        self.linear_drag.set_value_and_mark_modified(drag)
+    }
+
+    /// Returns linear drag coefficient of every sample point.
+    pub fn linear_drag(&self) -> f32 {
+        *self.linear_drag
+    }
+
+    /// Sets angular drag coefficient of the body. It is scaled by the submerged fraction of the body.
+    pub fn set_angular_drag(&mut self, drag: f32) -> f32 {
+        self.angular_drag.set_value_and_mark_modified(drag)
+    }
+
+    /// Returns angular drag coefficient of the body.
+    pub fn angular_drag(&self) -> f32 {
+        *self.angular_drag
+    }
+
+    /// Sets min speed (in m/s) of a sample point, that enters the water, to produce
+    /// [`BuoyancyEvent::Splash`].
+    pub fn set_splash_threshold(&mut self, threshold: f32) -> f32 {
+        self.splash_threshold.set_value_and_mark_modified(threshold)
+    }
+
+    /// Returns min speed of a sample point, that enters the water, to produce a splash.
+    pub fn splash_threshold(&self) -> f32 {
+        *self.splash_threshold
+    }
+
+    /// Returns fraction (`[0; 1]` range) of the body, that is submerged.
+    pub fn submersion(&self) -> f32 {
+        self.submersion
+    }
+
+    /// Returns `true` if the sample point with the given index is under the water.
+    pub fn is_point_submerged(&self, index: usize) -> bool {
+        self.submerged.get(index).cloned().unwrap_or_default()
+    }
+
+    /// Pops the oldest event from the queue.
+    pub fn pop_event(&mut self) -> Option<BuoyancyEvent> {
+        self.events.pop_front()
+    }
+}
+
+impl NodeTrait for Buoyancy {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, ctx: &mut UpdateContext) {
+        let Some(ocean) = ctx
+            .nodes
+            .try_borrow(*self.ocean)
+            .and_then(|n| n.cast::<Ocean>())
+        else {
+            return;
+        };
+
+        let waves = ocean.wave_parameters();
+        let level = ocean.level();
+        let time = ocean.time();
+        let parameters = PointForceParameters {
+            gravity: *ctx.physics.gravity,
+            density: ocean.density(),
+            volume: *self.volume / self.points.len().max(1) as f32,
+            drag: *self.linear_drag,
+        };
+
+        let Some(body) = ctx
+            .nodes
+            .try_borrow_mut(self.parent())
+            .and_then(|n| n.query_component_mut::<RigidBody>())
+        else {
+            return;
+        };
+
+        let center = body.global_position();
+        let lin_vel = body.lin_vel();
+        let ang_vel = body.ang_vel();
+        let transform = self.global_transform();
+        let radius = self.point_radius.max(0.001);
+
+        self.submerged.resize(self.points.len(), false);
+
+        let mut total_submersion = 0.0;
+        for (index, local_point) in self.points.iter().enumerate() {
+            let point = transform.transform_point(&(*local_point).into()).coords;
+            let sample = super::sample_waves(&waves, level, time, Vector2::new(point.x, point.z));
+
+            let depth = sample.height - point.y;
+            let submersion = ((depth + radius) / (2.0 * radius)).clamp(0.0, 1.0);
+            total_submersion += submersion;
+
+            let point_velocity = lin_vel + ang_vel.cross(&(point - center));
+            let relative_velocity = point_velocity - sample.velocity;
+
+            if submersion > 0.0 {
+                body.apply_force_at_point(
+                    point_force(&parameters, submersion, relative_velocity),
+                    point,
+                );
+            }
+
+            let is_submerged = depth > 0.0;
+            let was_submerged = std::mem::replace(&mut self.submerged[index], is_submerged);
+            let speed = relative_velocity.dot(&sample.normal).abs();
+            if is_submerged && !was_submerged {
+                if speed >= *self.splash_threshold {
+                    self.events.push_back(BuoyancyEvent::Splash {
+                        point: index,
+                        position: point,
+                        speed,
+                    });
+                }
+            } else if !is_submerged && was_submerged {
+                self.events.push_back(BuoyancyEvent::Emerged {
+                    point: index,
+                    position: point,
+                    speed,
+                });
+            }
+        }
+
+        self.submersion = total_submersion / self.points.len().max(1) as f32;
+
+        if self.submersion > 0.0 {
+            body.apply_torque(-ang_vel * (*self.angular_drag * self.submersion));
+            // Waves must move the body even if it fell asleep.
+            body.wake_up();
+        }
+    }
+}
+
+/// Allows you to create a buoyancy in a declarative manner.
+pub struct BuoyancyBuilder {
+    base_builder: BaseBuilder,
+    ocean: Handle<Node>,
+    points: Vec<Vector3<f32>>,
+    volume: f32,
+    point_radius: f32,
+    linear_drag: f32,
+    angular_drag: f32,
+    splash_threshold: f32,
+}
+
+impl BuoyancyBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            ocean: Default::default(),
+            points: vec![Vector3::zeros()],
+            volume: 1.0,
+            point_radius: 0.5,
+            linear_drag: 100.0,
+            angular_drag: 50.0,
+            splash_threshold: 1.0,
+        }
+    }
+
+    /// Sets desired ocean node.
+    pub fn with_ocean(mut self, ocean: Handle<Node>) -> Self {
+        self.ocean = ocean;
+        self
+    }
+
+    /// Sets desired sample points.
+    pub fn with_points(mut self, points: Vec<Vector3<f32>>) -> Self {
+        self.points = points;
+        self
+    }
+
+    /// Sets desired total volume of the displaced water.
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    /// Sets desired radius of every sample point.
+    pub fn with_point_radius(mut self, radius: f32) -> Self {
+        self.point_radius = radius;
+        self
+    }
+
+    /// Sets desired linear drag coefficient.
+    pub fn with_linear_drag(mut self, drag: f32) -> Self {
+        self.linear_drag = drag;
+        self
+    }
+
+    /// Sets desired angular drag coefficient.
+    pub fn with_angular_drag(mut self, drag: f32) -> Self {
+        self.angular_drag = drag;
+        self
+    }
+
+    /// Sets desired min speed of splashes.
+    pub fn with_splash_threshold(mut self, threshold: f32) -> Self {
+        self.splash_threshold = threshold;
+        self
+    }
+
+    /// Creates new buoyancy instance.
+    pub fn build_buoyancy(self) -> Buoyancy {
+        Buoyancy {
+            base: self.base_builder.build_base(),
+            ocean: self.ocean.into(),
+            points: self.points.into(),
+            volume: self.volume.into(),
+            point_radius: self.point_radius.into(),
+            linear_drag: self.linear_drag.into(),
+            angular_drag: self.angular_drag.into(),
+            splash_threshold: self.splash_threshold.into(),
+            submerged: Default::default(),
+            submersion: 0.0,
+            events: Default::default(),
+        }
+    }
+
+    /// Creates new buoyancy node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_buoyancy())
+    }
+
+    /// Creates new instance of buoyancy node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        scene::ocean::buoyancy::{point_force, PointForceParameters},
+    };
+
+    #[test]
+    fn test_point_force() {
+        let parameters = PointForceParameters {
+            gravity: Vector3::new(0.0, -9.81, 0.0),
+            density: 1000.0,
+            volume: 0.5,
+            drag: 10.0,
+        };
+
+        // Out of the water.
+        assert_eq!(
+            point_force(&parameters, 0.0, Vector3::y()),
+            Vector3::zeros()
+        );
+
+        // Fully submerged and still - Archimedes' force only.
+        let force = point_force(&parameters, 1.0, Vector3::zeros());
+        assert!((force - Vector3::new(0.0, 9.81 * 1000.0 * 0.5, 0.0)).norm() < 1.0e-3);
+
+        // Drag opposes the motion and scales with submersion.
+        let force = point_force(&parameters, 0.5, Vector3::new(2.0, 0.0, 0.0));
+        assert!((force.x + 10.0).abs() < 1.0e-5);
+        assert!((force.y - 9.81 * 1000.0 * 0.25).abs() < 1.0e-2);
+    }
+}
@@ -0,0 +1,622 @@
+//! Ocean is an infinite water surface with large-scale waves, that is rendered using a projected grid.
+//! Rigid bodies could float on it using [`buoyancy::Buoyancy`] node.
+//!
+//! For more info see [`Ocean`]
+
+use crate::{
+    core::{
+        algebra::{Vector2, Vector3},
+        color::Color,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{Node, NodeTrait, UpdateContext},
+    },
+};
+use fyrox_graph::BaseSceneGraph;
+use std::ops::{Deref, DerefMut};
+
+pub mod buoyancy;
+
+/// Max amount of waves of an ocean, the rest of the waves are ignored.
+pub const MAX_OCEAN_WAVES: usize = 8;
+
+// Gravitational acceleration used in the dispersion relation of the waves.
+const GRAVITY: f32 = 9.81;
+
+// Amount of iterations used to find the undisplaced position of a point on the surface.
+const SAMPLE_ITERATIONS: usize = 8;
+
+/// A single Gerstner (trochoidal) wave. Unlike simple sine waves, Gerstner waves move the points of the
+/// surface horizontally as well, which gives sharp crests and flat troughs.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct OceanWave {
+    /// Direction of the wave in XZ plane. It does not need to be normalized.
+    pub direction: Vector2<f32>,
+    /// Distance between two crests of the wave (in meters).
+    #[reflect(min_value = 0.01)]
+    pub wavelength: f32,
+    /// Height of the crests of the wave above the level of the ocean (in meters).
+    #[reflect(min_value = 0.0)]
+    pub amplitude: f32,
+    /// Sharpness of the crests. Zero gives a sine wave, one gives the sharpest crests. Steepness of
+    /// the waves is divided between all the waves of the ocean, so the surface never loops over.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub steepness: f32,
+    /// Initial phase of the wave (in radians).
+    pub phase: f32,
+}
+
+uuid_provider!(OceanWave = "2f6b9d14-7c3e-4a85-b1d0-e84a5c7f3b26");
+
+impl Default for OceanWave {
+    fn default() -> Self {
+        Self {
+            direction: Vector2::new(1.0, 0.0),
+            wavelength: 20.0,
+            amplitude: 0.5,
+            steepness: 0.5,
+            phase: 0.0,
+        }
+    }
+}
+
+/// Parameters of a wave, prepared for evaluation. They're shared with the renderer, so the rendered
+/// surface exactly matches the one used for buoyancy.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WaveParameters {
+    /// Normalized direction of the wave.
+    pub direction: Vector2<f32>,
+    /// Wave number (`2 * pi / wavelength`).
+    pub wave_number: f32,
+    /// Angular frequency, that is defined by the deep water dispersion relation.
+    pub angular_frequency: f32,
+    /// Vertical amplitude.
+    pub amplitude: f32,
+    /// Horizontal amplitude. It is defined by steepness, so the surface never loops over.
+    pub horizontal_amplitude: f32,
+    /// Initial phase.
+    pub phase: f32,
+}
+
+/// State of the ocean surface at some point.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OceanSample {
+    /// World-space height of the surface.
+    pub height: f32,
+    /// World-space normal of the surface.
+    pub normal: Vector3<f32>,
+    /// Velocity of the water at the surface (orbital velocity of the waves).
+    pub velocity: Vector3<f32>,
+}
+
+/// Ocean is an infinite water surface at the height of the node, with a set of Gerstner waves on
+/// it. It is intended for large bodies of water - seas, oceans, large lakes.
+///
+/// # Rendering
+///
+/// The ocean is rendered using a projected grid - a screen-space grid, which vertices are projected
+/// onto the plane of the ocean. It gives uniform detail on screen at any distance up to
+/// [`Ocean::max_distance`]. The surface is shaded using a simple model: Fresnel reflection of the sky,
+/// a specular highlight of the first directional light of the scene, and a mix of deep and shallow
+/// colors, that depends on the distance to the underwater geometry. Crests of the waves are covered
+/// with foam.
+///
+/// # Physics
+///
+/// The surface of the ocean could be sampled at any point using [`Ocean::sample`]. Rigid bodies could
+/// float on the surface using [`buoyancy::Buoyancy`] node, which applies buoyancy and drag forces and
+/// produces splash events.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::Vector2, pool::Handle},
+/// #     scene::{
+/// #         base::BaseBuilder, graph::Graph, node::Node,
+/// #         ocean::{OceanBuilder, OceanWave},
+/// #     },
+/// # };
+/// fn create_ocean(graph: &mut Graph) -> Handle<Node> {
+///     OceanBuilder::new(BaseBuilder::new())
+///         .with_waves(vec![
+///             OceanWave {
+///                 direction: Vector2::new(1.0, 0.3),
+///                 wavelength: 40.0,
+///                 amplitude: 0.8,
+///                 steepness: 0.6,
+///                 phase: 0.0,
+///             },
+///             OceanWave {
+///                 direction: Vector2::new(0.2, 1.0),
+///                 wavelength: 12.0,
+///                 amplitude: 0.2,
+///                 steepness: 0.4,
+///                 phase: 1.0,
+///             },
+///         ])
+///         .build(graph)
+/// }
+/// ```
+#[derive(Debug, Visit, Clone, Reflect)]
+pub struct Ocean {
+    base: Base,
+
+    #[reflect(setter = "set_waves")]
+    waves: InheritableVariable<Vec<OceanWave>>,
+
+    #[reflect(min_value = 0.0)]
+    #[reflect(setter = "set_density")]
+    density: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_deep_color")]
+    deep_color: InheritableVariable<Color>,
+
+    #[reflect(setter = "set_shallow_color")]
+    shallow_color: InheritableVariable<Color>,
+
+    #[reflect(setter = "set_sky_color")]
+    sky_color: InheritableVariable<Color>,
+
+    #[reflect(setter = "set_foam_color")]
+    foam_color: InheritableVariable<Color>,
+
+    #[reflect(min_value = 0.0)]
+    #[reflect(setter = "set_transparency_depth")]
+    transparency_depth: InheritableVariable<f32>,
+
+    #[reflect(min_value = 16.0, max_value = 512.0, step = 1.0)]
+    #[reflect(setter = "set_grid_resolution")]
+    grid_resolution: InheritableVariable<u32>,
+
+    #[reflect(min_value = 0.0)]
+    #[reflect(setter = "set_max_distance")]
+    max_distance: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_time_scale")]
+    time_scale: InheritableVariable<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    time: f32,
+}
+
+impl Deref for Ocean {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Ocean {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl Default for Ocean {
+    fn default() -> Self {
+        OceanBuilder::new(BaseBuilder::new()).build_ocean()
+    }
+}
+
+impl TypeUuidProvider for Ocean {
+    fn type_uuid() -> Uuid {
+        uuid!("6c1e8f3a-94d2-4b7e-a05c-3d8b2f7e1a49")
+    }
+}
+
+impl Ocean {
+    /// Sets new set of waves. Only first [`MAX_OCEAN_WAVES`] waves are used.
+    pub fn set_waves(&mut self, waves: Vec<OceanWave>) -> Vec<OceanWave> {
+        self.waves.set_value_and_mark_modified(waves)
+    }
+
+    /// Returns current set of waves.
+    pub fn waves(&self) -> &[OceanWave] {
+        &self.waves
+    }
+
+    /// Sets density of the water (in kg/m³), it defines the strength of buoyancy. Default is 1025
+    /// (sea water).
+    pub fn set_density(&mut self, density: f32) -> f32 {
+        self.density.set_value_and_mark_modified(density)
+    }
+
+    /// Returns density of the water.
+    pub fn density(&self) -> f32 {
+        *self.density
+    }
+
+    /// Sets color of deep water.
+    pub fn set_deep_color(&mut self, color: Color) -> Color {
+        self.deep_color.set_value_and_mark_modified(color)
+    }
+
+    /// Returns color of deep water.
+    pub fn deep_color(&self) -> Color {
+        *self.deep_color
+    }
+
+    /// Sets color of shallow water.
+    pub fn set_shallow_color(&mut self, color: Color) -> Color {
+        self.shallow_color.set_value_and_mark_modified(color)
+    }
+
+    /// Returns color of shallow water.
+    pub fn shallow_color(&self) -> Color {
+        *self.shallow_color
+    }
+
+    /// Sets color of the sky, that is reflected by the surface.
+    pub fn set_sky_color(&mut self, color: Color) -> Color {
+        self.sky_color.set_value_and_mark_modified(color)
+    }
+
+    /// Returns color of the sky, that is reflected by the surface.
+    pub fn sky_color(&self) -> Color {
+        *self.sky_color
+    }
+
+    /// Sets color of the foam on the crests of the waves. Alpha channel defines intensity of the
+    /// foam.
+    pub fn set_foam_color(&mut self, color: Color) -> Color {
+        self.foam_color.set_value_and_mark_modified(color)
+    }
+
+    /// Returns color of the foam.
+    pub fn foam_color(&self) -> Color {
+        *self.foam_color
+    }
+
+    /// Sets depth of the water (in meters), at which underwater geometry becomes fully invisible.
+    pub fn set_transparency_depth(&mut self, depth: f32) -> f32 {
+        self.transparency_depth.set_value_and_mark_modified(depth)
+    }
+
+    /// Returns depth of the water, at which underwater geometry becomes fully invisible.
+    pub fn transparency_depth(&self) -> f32 {
+        *self.transparency_depth
+    }
+
+    /// Sets amount of cells of the projected grid along each axis of the screen. Higher values give
+    /// smoother waves at the cost of performance.
+    pub fn set_grid_resolution(&mut self, resolution: u32) -> u32 {
+        self.grid_resolution.set_value_and_mark_modified(resolution)
+    }
+
+    /// Returns amount of cells of the projected grid along each axis of the screen.
+    pub fn grid_resolution(&self) -> u32 {
+        *self.grid_resolution
+    }
+
+    /// Sets max distance from the camera (in meters), at which the surface is rendered.
+    pub fn set_max_distance(&mut self, distance: f32) -> f32 {
+        self.max_distance.set_value_and_mark_modified(distance)
+    }
+
+    /// Returns max distance from the camera, at which the surface is rendered.
+    pub fn max_distance(&self) -> f32 {
+        *self.max_distance
+    }
+
+    /// Sets speed of the waves.
+    pub fn set_time_scale(&mut self, time_scale: f32) -> f32 {
+        self.time_scale.set_value_and_mark_modified(time_scale)
+    }
+
+    /// Returns speed of the waves.
+    pub fn time_scale(&self) -> f32 {
+        *self.time_scale
+    }
+
+    /// Returns current time of the wave animation (in seconds).
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Sets current time of the wave animation. Could be used to synchronize the waves over network.
+    pub fn set_time(&mut self, time: f32) {
+        self.time = time;
+    }
+
+    /// Returns world-space height of the ocean level (height of the calm surface).
+    pub fn level(&self) -> f32 {
+        self.global_position().y
+    }
+
+    /// Returns evaluation parameters of the waves. Steepness is normalized by the amount of waves, so
+    /// the sum of the waves never loops over.
+    pub fn wave_parameters(&self) -> Vec<WaveParameters> {
+        let count = self.waves.len().clamp(1, MAX_OCEAN_WAVES) as f32;
+        self.waves
+            .iter()
+            .take(MAX_OCEAN_WAVES)
+            .filter_map(|wave| {
+                let direction = wave.direction.try_normalize(f32::EPSILON)?;
+                let wave_number = std::f32::consts::TAU / wave.wavelength.max(0.01);
+                Some(WaveParameters {
+                    direction,
+                    wave_number,
+                    angular_frequency: (GRAVITY * wave_number).sqrt(),
+                    amplitude: wave.amplitude,
+                    horizontal_amplitude: wave.steepness.clamp(0.0, 1.0) / (wave_number * count),
+                    phase: wave.phase,
+                })
+            })
+            .collect()
+    }
+
+    /// Samples the surface of the ocean at the given world-space point of XZ plane.
+    pub fn sample(&self, position: Vector2<f32>) -> OceanSample {
+        sample_waves(&self.wave_parameters(), self.level(), self.time, position)
+    }
+}
+
+// Gerstner waves move the points of the surface horizontally, so the point of the undisplaced surface,
+// that ends up at the given position, is found using a few fixed-point iterations.
+pub(crate) fn sample_waves(
+    waves: &[WaveParameters],
+    level: f32,
+    time: f32,
+    position: Vector2<f32>,
+) -> OceanSample {
+    let mut origin = position;
+    for _ in 0..SAMPLE_ITERATIONS {
+        let mut offset = Vector2::zeros();
+        for wave in waves {
+            let theta = wave.wave_number * wave.direction.dot(&origin)
+                - wave.angular_frequency * time
+                + wave.phase;
+            offset += wave.direction * (wave.horizontal_amplitude * theta.cos());
+        }
+        origin = position - offset;
+    }
+
+    let mut height = level;
+    let mut normal = Vector3::new(0.0, 1.0, 0.0);
+    let mut velocity = Vector3::zeros();
+    for wave in waves {
+        let theta = wave.wave_number * wave.direction.dot(&origin) - wave.angular_frequency * time
+            + wave.phase;
+        let (sin, cos) = theta.sin_cos();
+
+        height += wave.amplitude * sin;
+
+        let slope = wave.wave_number * wave.amplitude * cos;
+        normal.x -= wave.direction.x * slope;
+        normal.z -= wave.direction.y * slope;
+        normal.y -= wave.wave_number * wave.horizontal_amplitude * sin;
+
+        let horizontal_speed = wave.angular_frequency * wave.horizontal_amplitude * sin;
+        velocity.x += wave.direction.x * horizontal_speed;
+        velocity.z += wave.direction.y * horizontal_speed;
+        velocity.y -= wave.angular_frequency * wave.amplitude * cos;
+    }
+
+    OceanSample {
+        height,
+        normal: normal
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::y),
+        velocity,
+    }
+}
+
+impl NodeTrait for Ocean {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        self.time += context.dt * *self.time_scale;
+    }
+}
+
+/// Allows you to create an ocean in a declarative manner.
+pub struct OceanBuilder {
+    base_builder: BaseBuilder,
+    waves: Vec<OceanWave>,
+    density: f32,
+    deep_color: Color,
+    shallow_color: Color,
+    sky_color: Color,
+    foam_color: Color,
+    transparency_depth: f32,
+    grid_resolution: u32,
+    max_distance: f32,
+    time_scale: f32,
+}
+
+impl OceanBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            waves: vec![
+                OceanWave::default(),
+                OceanWave {
+                    direction: Vector2::new(0.3, 1.0),
+                    wavelength: 8.0,
+                    amplitude: 0.15,
+                    steepness: 0.4,
+                    phase: 1.3,
+                },
+            ],
+            density: 1025.0,
+            deep_color: Color::opaque(5, 30, 50),
+            shallow_color: Color::opaque(20, 110, 120),
+            sky_color: Color::opaque(150, 190, 230),
+            foam_color: Color::from_rgba(240, 245, 250, 200),
+            transparency_depth: 4.0,
+            grid_resolution: 128,
+            max_distance: 1000.0,
+            time_scale: 1.0,
+        }
+    }
+
+    /// Sets desired set of waves.
+    pub fn with_waves(mut self, waves: Vec<OceanWave>) -> Self {
+        self.waves = waves;
+        self
+    }
+
+    /// Sets desired density of the water.
+    pub fn with_density(mut self, density: f32) -> Self {
+        self.density = density;
+        self
+    }
+
+    /// Sets desired color of deep water.
+    pub fn with_deep_color(mut self, color: Color) -> Self {
+        self.deep_color = color;
+        self
+    }
+
+    /// Sets desired color of shallow water.
+    pub fn with_shallow_color(mut self, color: Color) -> Self {
+        self.shallow_color = color;
+        self
+    }
+
+    /// Sets desired color of the sky.
+    pub fn with_sky_color(mut self, color: Color) -> Self {
+        self.sky_color = color;
+        self
+    }
+
+    /// Sets desired color of the foam.
+    pub fn with_foam_color(mut self, color: Color) -> Self {
+        self.foam_color = color;
+        self
+    }
+
+    /// Sets desired depth, at which underwater geometry becomes fully invisible.
+    pub fn with_transparency_depth(mut self, depth: f32) -> Self {
+        self.transparency_depth = depth;
+        self
+    }
+
+    /// Sets desired resolution of the projected grid.
+    pub fn with_grid_resolution(mut self, resolution: u32) -> Self {
+        self.grid_resolution = resolution;
+        self
+    }
+
+    /// Sets desired max distance of the surface from the camera.
+    pub fn with_max_distance(mut self, distance: f32) -> Self {
+        self.max_distance = distance;
+        self
+    }
+
+    /// Sets desired speed of the waves.
+    pub fn with_time_scale(mut self, time_scale: f32) -> Self {
+        self.time_scale = time_scale;
+        self
+    }
+
+    /// Creates new ocean instance.
+    pub fn build_ocean(self) -> Ocean {
+        Ocean {
+            base: self.base_builder.build_base(),
+            waves: self.waves.into(),
+            density: self.density.into(),
+            deep_color: self.deep_color.into(),
+            shallow_color: self.shallow_color.into(),
+            sky_color: self.sky_color.into(),
+            foam_color: self.foam_color.into(),
+            transparency_depth: self.transparency_depth.into(),
+            grid_resolution: self.grid_resolution.into(),
+            max_distance: self.max_distance.into(),
+            time_scale: self.time_scale.into(),
+            time: 0.0,
+        }
+    }
+
+    /// Creates new ocean node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_ocean())
+    }
+
+    /// Creates new instance of ocean node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector2,
+        scene::{
+            base::BaseBuilder,
+            ocean::{OceanBuilder, OceanWave},
+        },
+    };
+
+    #[test]
+    fn test_calm_ocean() {
+        let ocean = OceanBuilder::new(BaseBuilder::new())
+            .with_waves(vec![])
+            .build_ocean();
+
+        let sample = ocean.sample(Vector2::new(12.0, -3.0));
+        assert_eq!(sample.height, 0.0);
+        assert_eq!(sample.normal, crate::core::algebra::Vector3::y());
+        assert_eq!(sample.velocity.norm(), 0.0);
+    }
+
+    #[test]
+    fn test_sample_matches_displaced_surface() {
+        let mut ocean = OceanBuilder::new(BaseBuilder::new())
+            .with_waves(vec![OceanWave {
+                direction: Vector2::new(1.0, 0.0),
+                wavelength: 10.0,
+                amplitude: 0.5,
+                steepness: 0.5,
+                phase: 0.0,
+            }])
+            .build_ocean();
+        ocean.set_time(1.7);
+
+        let waves = ocean.wave_parameters();
+        let wave = waves[0];
+
+        // Displace a point of the calm surface and check that sampling at the displaced position
+        // gives the same height.
+        for i in 0..20 {
+            let origin = Vector2::new(i as f32 * 0.37, 2.0);
+            let theta = wave.wave_number * wave.direction.dot(&origin)
+                - wave.angular_frequency * ocean.time()
+                + wave.phase;
+            let displaced = origin + wave.direction * (wave.horizontal_amplitude * theta.cos());
+            let expected_height = wave.amplitude * theta.sin();
+
+            let sample = ocean.sample(displaced);
+            assert!((sample.height - expected_height).abs() < 1.0e-2);
+            assert!(sample.normal.y > 0.0);
+        }
+    }
+}
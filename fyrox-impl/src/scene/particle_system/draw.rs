@@ -17,6 +17,10 @@ pub struct Vertex {
     pub size: f32,
     pub rotation: f32,
     pub color: Color,
+    /// World-space axis of a billboard, zero means that the billboard faces the camera.
+    pub axis: Vector3<f32>,
+    /// Soft particle fade distance, zero means that the sharpness factor of the material is used.
+    pub soft_distance: f32,
 }
 
 impl VertexTrait for Vertex {
@@ -62,6 +66,22 @@ impl VertexTrait for Vertex {
                 shader_location: 4,
                 normalized: true,
             },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Custom2,
+                data_type: VertexAttributeDataType::F32,
+                size: 3,
+                divisor: 0,
+                shader_location: 5,
+                normalized: false,
+            },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Custom3,
+                data_type: VertexAttributeDataType::F32,
+                size: 1,
+                divisor: 0,
+                shader_location: 6,
+                normalized: false,
+            },
         ]
     }
 }
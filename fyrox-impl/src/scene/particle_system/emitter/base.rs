@@ -5,7 +5,10 @@ use crate::{
         algebra::Vector3, color::Color, numeric_range::RangeExt, reflect::prelude::*,
         visitor::prelude::*,
     },
-    scene::particle_system::{Particle, ParticleSystemRng},
+    scene::{
+        particle_system::{Particle, ParticleSystemRng},
+        sprite::BillboardMode,
+    },
 };
 use std::ops::Range;
 
@@ -49,6 +52,14 @@ pub struct BaseEmitter {
     resurrect_particles: bool,
     #[reflect(hidden)]
     pub(crate) spawned_particles: u64,
+    /// Orientation of particles relative to the camera.
+    #[visit(optional)]
+    billboard_mode: BillboardMode,
+    /// Distance over which particles fade out when they approach opaque geometry. Zero means that
+    /// the sharpness factor of the material is used.
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, step = 0.05)]
+    soft_particle_distance: f32,
 }
 
 /// Emitter builder allows you to construct emitter in declarative manner.
@@ -66,6 +77,8 @@ pub struct BaseEmitterBuilder {
     rotation_speed: Range<f32>,
     rotation: Range<f32>,
     resurrect_particles: bool,
+    billboard_mode: BillboardMode,
+    soft_particle_distance: f32,
 }
 
 impl Default for BaseEmitterBuilder {
@@ -90,6 +103,8 @@ impl BaseEmitterBuilder {
             rotation_speed: -0.02..0.02,
             rotation: -std::f32::consts::PI..std::f32::consts::PI,
             resurrect_particles: true,
+            billboard_mode: Default::default(),
+            soft_particle_distance: 0.0,
         }
     }
 
@@ -165,6 +180,19 @@ impl BaseEmitterBuilder {
         self
     }
 
+    /// Sets desired billboard mode of particles. See [`BillboardMode`] docs for more info.
+    pub fn with_billboard_mode(mut self, mode: BillboardMode) -> Self {
+        self.billboard_mode = mode;
+        self
+    }
+
+    /// Sets desired soft particle distance. See [`BaseEmitter::set_soft_particle_distance`] for
+    /// more info.
+    pub fn with_soft_particle_distance(mut self, distance: f32) -> Self {
+        self.soft_particle_distance = distance.max(0.0);
+        self
+    }
+
     /// Creates new instance of emitter.
     pub fn build(self) -> BaseEmitter {
         BaseEmitter {
@@ -184,6 +212,8 @@ impl BaseEmitterBuilder {
             particles_to_spawn: 0,
             resurrect_particles: self.resurrect_particles,
             spawned_particles: 0,
+            billboard_mode: self.billboard_mode,
+            soft_particle_distance: self.soft_particle_distance,
         }
     }
 }
@@ -377,6 +407,33 @@ impl BaseEmitter {
     pub fn spawned_particles(&self) -> u64 {
         self.spawned_particles
     }
+
+    /// Sets new billboard mode of particles. [`BillboardMode::VelocityAligned`] aligns particles along
+    /// their velocity, [`BillboardMode::AxisLocked`] axis is defined in local coordinates of the
+    /// particle system.
+    pub fn set_billboard_mode(&mut self, mode: BillboardMode) -> &mut Self {
+        self.billboard_mode = mode;
+        self
+    }
+
+    /// Returns current billboard mode of particles.
+    pub fn billboard_mode(&self) -> BillboardMode {
+        self.billboard_mode
+    }
+
+    /// Sets a distance (in world units) over which particles fade out when they approach opaque
+    /// geometry behind them (so called "soft particles"). It hides hard intersection lines with the
+    /// geometry. Zero means that `softBoundarySharpnessFactor` property of the material is used
+    /// instead.
+    pub fn set_soft_particle_distance(&mut self, distance: f32) -> &mut Self {
+        self.soft_particle_distance = distance.max(0.0);
+        self
+    }
+
+    /// Returns current soft particle distance.
+    pub fn soft_particle_distance(&self) -> f32 {
+        self.soft_particle_distance
+    }
 }
 
 impl Clone for BaseEmitter {
@@ -398,6 +455,8 @@ impl Clone for BaseEmitter {
             particles_to_spawn: 0,
             resurrect_particles: self.resurrect_particles,
             spawned_particles: self.spawned_particles,
+            billboard_mode: self.billboard_mode,
+            soft_particle_distance: self.soft_particle_distance,
         }
     }
 }
@@ -421,6 +480,8 @@ impl Default for BaseEmitter {
             particles_to_spawn: 0,
             resurrect_particles: true,
             spawned_particles: 0,
+            billboard_mode: Default::default(),
+            soft_particle_distance: 0.0,
         }
     }
 }
@@ -0,0 +1,220 @@
+//! CPU-side state of GPU-simulated particles. See [`super::ParticleSimulationMode::Gpu`] docs for
+//! more info.
+//!
+//! Emitters still spawn particles on CPU (so they keep their API and determinism), but every spawned
+//! particle is only written into a slot of GPU particle storage and then the renderer simulates it
+//! until it dies. CPU tracks lifetimes of the particles to know which slots are free and how many
+//! particles every emitter has, it is much cheaper than the full simulation.
+
+use crate::{
+    core::{algebra::Vector3, parking_lot::Mutex},
+    scene::particle_system::particle::Particle,
+};
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+/// Initial state of a particle, that must be written to its slot in GPU storage.
+#[derive(Clone, Debug)]
+pub struct GpuParticleSpawn {
+    /// Index of the slot in GPU storage.
+    pub slot: u32,
+    /// Position of the particle in local coordinates of the particle system.
+    pub position: Vector3<f32>,
+    /// Velocity of the particle (per update tick, the same as for CPU particles).
+    pub velocity: Vector3<f32>,
+    /// Initial size of the particle.
+    pub size: f32,
+    /// Size change per second.
+    pub size_modifier: f32,
+    /// Initial rotation of the particle.
+    pub rotation: f32,
+    /// Rotation speed in radians per second.
+    pub rotation_speed: f32,
+    /// Total lifetime of the particle.
+    pub lifetime: f32,
+    /// Index of the emitter, that spawned the particle.
+    pub emitter_index: u32,
+    /// Amount of time (in seconds) of the batch, that passed before the particle was spawned. The
+    /// particle must not be simulated during this time.
+    pub delay: f32,
+}
+
+/// A set of changes since the last simulation step on GPU.
+#[derive(Default, Debug)]
+pub struct GpuParticleBatch {
+    /// `true` if every particle must be removed before applying the batch.
+    pub reset: bool,
+    /// Amount of time to simulate.
+    pub dt: f32,
+    /// New particles.
+    pub spawns: Vec<GpuParticleSpawn>,
+}
+
+#[derive(Debug)]
+struct Death {
+    time: f64,
+    slot: u32,
+    emitter_index: u32,
+}
+
+impl PartialEq for Death {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Death {}
+
+impl PartialOrd for Death {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Death {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, so the binary heap will give the earliest death first.
+        other.time.total_cmp(&self.time)
+    }
+}
+
+/// CPU-side state of GPU-simulated particles.
+#[derive(Default, Debug)]
+pub(crate) struct GpuParticles {
+    capacity: u32,
+    free_slots: Vec<u32>,
+    used_slots: u32,
+    deaths: BinaryHeap<Death>,
+    time: f64,
+    alive: u32,
+    batch: Mutex<GpuParticleBatch>,
+}
+
+impl Clone for GpuParticles {
+    fn clone(&self) -> Self {
+        // GPU storage could not be shared, so the copy starts from scratch.
+        Self::default()
+    }
+}
+
+impl GpuParticles {
+    pub(crate) fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    pub(crate) fn alive_count(&self) -> u32 {
+        self.alive
+    }
+
+    /// Removes every particle and sets new capacity.
+    pub(crate) fn reset(&mut self, capacity: u32) {
+        self.capacity = capacity;
+        self.free_slots.clear();
+        self.used_slots = 0;
+        self.deaths.clear();
+        self.time = 0.0;
+        self.alive = 0;
+        *self.batch.get_mut() = GpuParticleBatch {
+            reset: true,
+            dt: 0.0,
+            spawns: Default::default(),
+        };
+    }
+
+    /// Puts the particle into a free slot. Returns `false` if there's no free slots.
+    pub(crate) fn spawn(&mut self, particle: &Particle) -> bool {
+        let slot = if let Some(slot) = self.free_slots.pop() {
+            slot
+        } else if self.used_slots < self.capacity {
+            self.used_slots += 1;
+            self.used_slots - 1
+        } else {
+            return false;
+        };
+
+        self.alive += 1;
+        self.deaths.push(Death {
+            time: self.time + particle.initial_lifetime as f64,
+            slot,
+            emitter_index: particle.emitter_index,
+        });
+
+        let batch = self.batch.get_mut();
+        batch.spawns.push(GpuParticleSpawn {
+            slot,
+            position: particle.position,
+            velocity: particle.velocity,
+            size: particle.size,
+            size_modifier: particle.size_modifier,
+            rotation: particle.rotation,
+            rotation_speed: particle.rotation_speed,
+            lifetime: particle.initial_lifetime,
+            emitter_index: particle.emitter_index,
+            delay: batch.dt,
+        });
+
+        // Nobody takes the batches (there's no renderer, for example), keep only the spawns that
+        // could be alive.
+        if batch.spawns.len() > 2 * self.capacity as usize {
+            let excess = batch.spawns.len() - self.capacity as usize;
+            batch.spawns.drain(..excess);
+        }
+
+        true
+    }
+
+    /// Advances the time and frees the slots of dead particles. `on_death` is called with the index
+    /// of the emitter of every dead particle.
+    pub(crate) fn advance(&mut self, dt: f32, mut on_death: impl FnMut(u32)) {
+        self.time += dt as f64;
+        self.batch.get_mut().dt += dt;
+
+        while self
+            .deaths
+            .peek()
+            .is_some_and(|death| death.time <= self.time)
+        {
+            let death = self.deaths.pop().unwrap();
+            self.free_slots.push(death.slot);
+            self.alive = self.alive.saturating_sub(1);
+            on_death(death.emitter_index);
+        }
+    }
+
+    /// Takes every change since the last call.
+    pub(crate) fn take_batch(&self) -> GpuParticleBatch {
+        std::mem::take(&mut *self.batch.lock())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::scene::particle_system::{gpu::GpuParticles, particle::Particle};
+
+    #[test]
+    fn test_gpu_particles_bookkeeping() {
+        let mut particles = GpuParticles::default();
+        particles.reset(2);
+        assert!(particles.take_batch().reset);
+
+        let particle = Particle::default().with_initial_lifetime(1.0);
+        assert!(particles.spawn(&particle));
+        assert!(particles.spawn(&particle.clone().with_initial_lifetime(2.0)));
+        // No free slots.
+        assert!(!particles.spawn(&particle));
+        assert_eq!(particles.alive_count(), 2);
+
+        let mut deaths = 0;
+        particles.advance(1.5, |_| deaths += 1);
+        assert_eq!(deaths, 1);
+        assert_eq!(particles.alive_count(), 1);
+
+        // The slot of the dead particle is reused.
+        assert!(particles.spawn(&particle));
+        let batch = particles.take_batch();
+        assert!(!batch.reset);
+        assert_eq!(batch.spawns.len(), 3);
+        assert_eq!(batch.spawns[2].slot, 0);
+        assert_eq!(batch.spawns[2].delay, 1.5);
+        assert_eq!(batch.dt, 1.5);
+    }
+}
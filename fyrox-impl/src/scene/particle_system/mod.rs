@@ -6,11 +6,13 @@ use crate::scene::node::RdcControlFlow;
 use crate::{
     core::{
         algebra::{Point3, Vector2, Vector3},
+        color::Color,
         color_gradient::ColorGradient,
         log::Log,
         math::{aabb::AxisAlignedBoundingBox, TriangleDefinition},
         pool::Handle,
         reflect::prelude::*,
+        rng::Pcg32,
         sstorage::ImmutableString,
         uuid::{uuid, Uuid},
         variable::InheritableVariable,
@@ -18,8 +20,9 @@ use crate::{
         TypeUuidProvider,
     },
     material::{self, Material, MaterialResource, PropertyValue},
-    rand::{prelude::StdRng, Error, RngCore, SeedableRng},
+    rand::{Error, RngCore, SeedableRng},
     renderer::{self, bundle::RenderContext},
+    resource::gradient::ColorGradientResource,
     scene::{
         base::{Base, BaseBuilder},
         graph::Graph,
@@ -28,29 +31,75 @@ use crate::{
         particle_system::{
             draw::Vertex,
             emitter::{Emit, Emitter},
+            gpu::{GpuParticleBatch, GpuParticles},
             particle::Particle,
         },
     },
 };
-use fyrox_core::value_as_u8_slice;
+use fyrox_core::{uuid_provider, value_as_u8_slice};
 use fyrox_graph::BaseSceneGraph;
 use std::{
     cmp::Ordering,
     fmt::Debug,
     ops::{Deref, DerefMut},
 };
+use strum_macros::{AsRefStr, EnumString, VariantNames};
 
 pub(crate) mod draw;
 pub mod emitter;
+pub mod gpu;
 pub mod particle;
 
-/// Pseudo-random numbers generator for particle systems.
+/// Maximum amount of GPU-simulated particles of a single particle system.
+pub const MAX_GPU_PARTICLES: u32 = 1 << 20;
+
+/// Defines where particles are simulated.
+#[derive(
+    Copy,
+    Clone,
+    Default,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum ParticleSimulationMode {
+    /// Particles are simulated on CPU, sorted back-to-front and rendered using the material of the
+    /// particle system. It is the most flexible mode, but it struggles with tens of thousands of
+    /// particles.
+    #[default]
+    Cpu,
+    /// Particles are spawned by the emitters on CPU, but simulated on GPU in a set of floating-point
+    /// textures and rendered using instancing, so hundreds of thousands of particles are feasible.
+    /// There are some limitations:
+    ///
+    /// - Particles are not sorted, so it is best suited for additive or soft effects (sparks, dust,
+    /// magic, etc.).
+    /// - Only `diffuseTexture` and `softBoundarySharpnessFactor` properties of the material are used,
+    /// the shader of the material is ignored.
+    /// - Particles do not cast shadows and could not be accessed (or set) from code.
+    /// - There's a limited amount of particles, see [`ParticleSystem::set_gpu_capacity`].
+    ///
+    /// If the current graphics API does not support floating-point render targets, the renderer
+    /// falls back to simulation on CPU, but particles are still rendered using instancing.
+    Gpu,
+}
+
+uuid_provider!(ParticleSimulationMode = "2f6d8b1a-7c3e-4a95-b0d4-e1c9a6f37b82");
+
+/// Pseudo-random numbers generator for particle systems. It uses [`Pcg32`] internally, so the same
+/// seed gives the same particles on every platform.
 #[derive(Debug, Clone, Reflect)]
 pub struct ParticleSystemRng {
     rng_seed: u64,
 
     #[reflect(hidden)]
-    rng: StdRng,
+    rng: Pcg32,
 }
 
 impl Default for ParticleSystemRng {
@@ -65,14 +114,14 @@ impl ParticleSystemRng {
     pub fn new(seed: u64) -> Self {
         Self {
             rng_seed: seed,
-            rng: StdRng::seed_from_u64(seed),
+            rng: Pcg32::seed_from_u64(seed),
         }
     }
 
     /// Resets the state of PRNG.
     #[inline]
     pub fn reset(&mut self) {
-        self.rng = StdRng::seed_from_u64(self.rng_seed);
+        self.rng = Pcg32::seed_from_u64(self.rng_seed);
     }
 }
 
@@ -106,7 +155,7 @@ impl Visit for ParticleSystemRng {
 
         // Re-initialize the RNG to keep determinism.
         if guard.is_reading() {
-            self.rng = StdRng::seed_from_u64(self.rng_seed);
+            self.rng = Pcg32::seed_from_u64(self.rng_seed);
         }
 
         Ok(())
@@ -133,6 +182,12 @@ impl Visit for ParticleSystemRng {
 /// enough, alternatively amount of particles can be defined by some coefficient based on
 /// graphics quality settings.
 ///
+/// # GPU simulation
+///
+/// Particle system could be simulated on GPU, which allows you to have hundreds of thousands of
+/// particles with the same emitters. See [`ParticleSimulationMode`] docs for more info and
+/// limitations.
+///
 /// # Example
 ///
 /// Simple smoke effect can be create like so:
@@ -225,9 +280,24 @@ pub struct ParticleSystem {
     #[reflect(setter = "set_color_over_lifetime_gradient")]
     color_over_lifetime: InheritableVariable<ColorGradient>,
 
+    /// Optional shared color gradient resource. If set and loaded, it is used instead of the
+    /// [`Self::set_color_over_lifetime_gradient`] gradient.
+    #[reflect(setter = "set_color_over_lifetime_resource")]
+    color_over_lifetime_resource: InheritableVariable<Option<ColorGradientResource>>,
+
     #[reflect(setter = "play")]
     is_playing: InheritableVariable<bool>,
 
+    #[reflect(setter = "set_simulation_mode")]
+    simulation_mode: InheritableVariable<ParticleSimulationMode>,
+
+    #[reflect(min_value = 1.0, max_value = 1048576.0, step = 1.0)]
+    #[reflect(setter = "set_gpu_capacity")]
+    gpu_capacity: InheritableVariable<u32>,
+
+    #[reflect(hidden)]
+    gpu_particles: GpuParticles,
+
     #[reflect(hidden)]
     particles: Vec<Particle>,
 
@@ -246,10 +316,15 @@ impl Visit for ParticleSystem {
         self.acceleration.visit("Acceleration", &mut region)?;
         self.color_over_lifetime
             .visit("ColorGradient", &mut region)?;
+        let _ = self
+            .color_over_lifetime_resource
+            .visit("ColorGradientResource", &mut region);
         self.is_playing.visit("Enabled", &mut region)?;
         self.particles.visit("Particles", &mut region)?;
         self.free_particles.visit("FreeParticles", &mut region)?;
         let _ = self.rng.visit("Rng", &mut region);
+        let _ = self.simulation_mode.visit("SimulationMode", &mut region);
+        let _ = self.gpu_capacity.visit("GpuCapacity", &mut region);
 
         // Backward compatibility.
         if region.is_reading() {
@@ -318,6 +393,22 @@ impl ParticleSystem {
             .set_value_and_mark_modified(gradient)
     }
 
+    /// Sets new shared color gradient resource that will evaluate color over lifetime. It takes
+    /// precedence over the gradient set by [`Self::set_color_over_lifetime_gradient`], which is
+    /// used as a fallback while the resource is loading (or if it failed to load).
+    pub fn set_color_over_lifetime_resource(
+        &mut self,
+        resource: Option<ColorGradientResource>,
+    ) -> Option<ColorGradientResource> {
+        self.color_over_lifetime_resource
+            .set_value_and_mark_modified(resource)
+    }
+
+    /// Returns current shared color gradient resource, if any.
+    pub fn color_over_lifetime_resource(&self) -> Option<ColorGradientResource> {
+        (*self.color_over_lifetime_resource).clone()
+    }
+
     /// Plays or pauses the particle system. Paused particle system remains in "frozen" state
     /// until played again again. You can manually reset state of the system by calling [`Self::clear_particles`].
     pub fn play(&mut self, is_playing: bool) -> bool {
@@ -337,14 +428,22 @@ impl ParticleSystem {
     }
 
     /// Returns a reference to a slice to the current set of particles, generated by the particle system.
+    /// It is always empty if particles are simulated on GPU.
     pub fn particles(&self) -> &[Particle] {
         &self.particles
     }
 
+    /// Sets new pseudo-random numbers generator. It could be used to make the particle system behave
+    /// deterministically (in tests, for example).
+    pub fn set_rng(&mut self, rng: ParticleSystemRng) {
+        self.rng = rng;
+    }
+
     /// Removes all generated particles.
     pub fn clear_particles(&mut self) {
         self.particles.clear();
         self.free_particles.clear();
+        self.gpu_particles.reset(*self.gpu_capacity);
         for emitter in self.emitters.get_value_mut_silent().iter_mut() {
             emitter.alive_particles = 0;
             emitter.spawned_particles = 0;
@@ -366,11 +465,99 @@ impl ParticleSystem {
         &self.material
     }
 
+    /// Sets new simulation mode. Changing the mode removes every particle. See
+    /// [`ParticleSimulationMode`] docs for more info.
+    pub fn set_simulation_mode(&mut self, mode: ParticleSimulationMode) -> ParticleSimulationMode {
+        let prev = self.simulation_mode.set_value_and_mark_modified(mode);
+        if prev != mode {
+            self.clear_particles();
+        }
+        prev
+    }
+
+    /// Returns current simulation mode.
+    pub fn simulation_mode(&self) -> ParticleSimulationMode {
+        *self.simulation_mode
+    }
+
+    /// Sets max amount of particles, that could be simulated on GPU at the same time. Emitters do
+    /// not spawn new particles when the limit is reached. Changing the capacity removes every GPU
+    /// particle.
+    pub fn set_gpu_capacity(&mut self, capacity: u32) -> u32 {
+        self.gpu_capacity
+            .set_value_and_mark_modified(capacity.clamp(1, MAX_GPU_PARTICLES))
+    }
+
+    /// Returns max amount of particles, that could be simulated on GPU at the same time.
+    pub fn gpu_capacity(&self) -> u32 {
+        *self.gpu_capacity
+    }
+
+    /// Returns amount of alive particles, that are simulated on GPU.
+    pub fn gpu_particle_count(&self) -> u32 {
+        self.gpu_particles.alive_count()
+    }
+
+    /// Takes every change of GPU particles since the last call. It is used by the renderer.
+    pub(crate) fn take_gpu_batch(&self) -> GpuParticleBatch {
+        self.gpu_particles.take_batch()
+    }
+
+    /// Samples current color-over-lifetime gradient uniformly into the given buffer.
+    pub(crate) fn sample_color_over_lifetime(&self, samples: &mut [Color]) {
+        let mut gradient_state = self
+            .color_over_lifetime_resource
+            .as_ref()
+            .map(|resource| resource.state());
+        let color_over_lifetime = gradient_state
+            .as_mut()
+            .and_then(|state| state.data())
+            .map(|data| &data.gradient)
+            .unwrap_or(&*self.color_over_lifetime);
+
+        let last = samples.len().saturating_sub(1).max(1) as f32;
+        for (i, sample) in samples.iter_mut().enumerate() {
+            *sample = color_over_lifetime.get_color(i as f32 / last);
+        }
+    }
+
+    fn tick_gpu(&mut self, dt: f32) {
+        if self.gpu_particles.capacity() != *self.gpu_capacity {
+            // The capacity was changed or the particle system was just created (copied, loaded).
+            self.clear_particles();
+        }
+
+        for (i, emitter) in self.emitters.get_value_mut_silent().iter_mut().enumerate() {
+            for _ in 0..emitter.particles_to_spawn {
+                let mut particle = Particle {
+                    emitter_index: i as u32,
+                    ..Particle::default()
+                };
+                emitter.emit(&mut particle, &mut self.rng);
+                if self.gpu_particles.spawn(&particle) {
+                    emitter.alive_particles += 1;
+                }
+            }
+        }
+
+        let emitters = self.emitters.get_value_mut_silent();
+        self.gpu_particles.advance(dt, |emitter_index| {
+            if let Some(emitter) = emitters.get_mut(emitter_index as usize) {
+                emitter.alive_particles = emitter.alive_particles.saturating_sub(1);
+            }
+        });
+    }
+
     fn tick(&mut self, dt: f32) {
         for emitter in self.emitters.get_value_mut_silent().iter_mut() {
             emitter.tick(dt);
         }
 
+        if *self.simulation_mode == ParticleSimulationMode::Gpu {
+            self.tick_gpu(dt);
+            return;
+        }
+
         for (i, emitter) in self.emitters.get_value_mut_silent().iter_mut().enumerate() {
             for _ in 0..emitter.particles_to_spawn {
                 let mut particle = Particle {
@@ -389,6 +576,16 @@ impl ParticleSystem {
 
         let acceleration_offset = self.acceleration.scale(dt * dt);
 
+        let mut gradient_state = self
+            .color_over_lifetime_resource
+            .as_ref()
+            .map(|resource| resource.state());
+        let color_over_lifetime = gradient_state
+            .as_mut()
+            .and_then(|state| state.data())
+            .map(|data| &data.gradient)
+            .unwrap_or(&*self.color_over_lifetime);
+
         for (i, particle) in self.particles.iter_mut().enumerate() {
             if particle.alive {
                 particle.lifetime += dt;
@@ -413,7 +610,7 @@ impl ParticleSystem {
                     particle.rotation += particle.rotation_speed * dt;
 
                     let k = particle.lifetime / particle.initial_lifetime;
-                    particle.color = self.color_over_lifetime.get_color(k);
+                    particle.color = color_over_lifetime.get_color(k);
                 }
             }
         }
@@ -479,6 +676,11 @@ impl NodeTrait for ParticleSystem {
             return RdcControlFlow::Continue;
         }
 
+        if *self.simulation_mode == ParticleSimulationMode::Gpu {
+            // GPU particles are rendered by the renderer itself.
+            return RdcControlFlow::Continue;
+        }
+
         let mut sorted_particles = Vec::new();
         for (i, particle) in self.particles.iter().enumerate() {
             if particle.alive {
@@ -525,6 +727,19 @@ impl NodeTrait for ParticleSystem {
                         .transform_point(&Point3::from(particle.position))
                         .coords;
 
+                    let (axis, soft_distance) = self
+                        .emitters
+                        .get(particle.emitter_index as usize)
+                        .map(|emitter| {
+                            (
+                                global_transform.transform_vector(
+                                    &emitter.billboard_mode().axis(particle.velocity),
+                                ),
+                                emitter.soft_particle_distance(),
+                            )
+                        })
+                        .unwrap_or_default();
+
                     [
                         Vertex {
                             position,
@@ -532,6 +747,8 @@ impl NodeTrait for ParticleSystem {
                             size: particle.size,
                             rotation: particle.rotation,
                             color: particle.color,
+                            axis,
+                            soft_distance,
                         },
                         Vertex {
                             position,
@@ -539,6 +756,8 @@ impl NodeTrait for ParticleSystem {
                             size: particle.size,
                             rotation: particle.rotation,
                             color: particle.color,
+                            axis,
+                            soft_distance,
                         },
                         Vertex {
                             position,
@@ -546,6 +765,8 @@ impl NodeTrait for ParticleSystem {
                             size: particle.size,
                             rotation: particle.rotation,
                             color: particle.color,
+                            axis,
+                            soft_distance,
                         },
                         Vertex {
                             position,
@@ -553,6 +774,8 @@ impl NodeTrait for ParticleSystem {
                             size: particle.size,
                             rotation: particle.rotation,
                             color: particle.color,
+                            axis,
+                            soft_distance,
                         },
                     ]
                 });
@@ -591,8 +814,11 @@ pub struct ParticleSystemBuilder {
     acceleration: Vector3<f32>,
     particles: Vec<Particle>,
     color_over_lifetime: ColorGradient,
+    color_over_lifetime_resource: Option<ColorGradientResource>,
     is_playing: bool,
     rng: ParticleSystemRng,
+    simulation_mode: ParticleSimulationMode,
+    gpu_capacity: u32,
 }
 
 impl ParticleSystemBuilder {
@@ -608,8 +834,11 @@ impl ParticleSystemBuilder {
             particles: Default::default(),
             acceleration: Vector3::new(0.0, -9.81, 0.0),
             color_over_lifetime: Default::default(),
+            color_over_lifetime_resource: None,
             is_playing: true,
             rng: ParticleSystemRng::default(),
+            simulation_mode: Default::default(),
+            gpu_capacity: 65536,
         }
     }
 
@@ -637,6 +866,12 @@ impl ParticleSystemBuilder {
         self
     }
 
+    /// Sets shared color gradient resource over lifetime for particle system.
+    pub fn with_color_over_lifetime_resource(mut self, resource: ColorGradientResource) -> Self {
+        self.color_over_lifetime_resource = Some(resource);
+        self
+    }
+
     /// Sets an initial set of particles that not belongs to any emitter. This method
     /// could be useful if you need a custom position/velocity/etc. of each particle.
     pub fn with_particles(mut self, particles: Vec<Particle>) -> Self {
@@ -656,6 +891,19 @@ impl ParticleSystemBuilder {
         self
     }
 
+    /// Sets desired simulation mode. See [`ParticleSimulationMode`] docs for more info.
+    pub fn with_simulation_mode(mut self, mode: ParticleSimulationMode) -> Self {
+        self.simulation_mode = mode;
+        self
+    }
+
+    /// Sets desired max amount of GPU-simulated particles. See [`ParticleSystem::set_gpu_capacity`]
+    /// for more info.
+    pub fn with_gpu_capacity(mut self, capacity: u32) -> Self {
+        self.gpu_capacity = capacity.clamp(1, MAX_GPU_PARTICLES);
+        self
+    }
+
     fn build_particle_system(self) -> ParticleSystem {
         ParticleSystem {
             base: self.base_builder.build_base(),
@@ -665,8 +913,12 @@ impl ParticleSystemBuilder {
             material: self.material.into(),
             acceleration: self.acceleration.into(),
             color_over_lifetime: self.color_over_lifetime.into(),
+            color_over_lifetime_resource: self.color_over_lifetime_resource.into(),
             is_playing: self.is_playing.into(),
             rng: self.rng,
+            simulation_mode: self.simulation_mode.into(),
+            gpu_capacity: self.gpu_capacity.into(),
+            gpu_particles: Default::default(),
         }
     }
 
@@ -0,0 +1,460 @@
+//! Portal is a rectangular "window" that shows the scene from another point of view. It could be used
+//! for mirrors and for non-euclidean doors (teleporters).
+//!
+//! For more info see [`Portal`]
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, Vector2, Vector3, Vector4},
+        color::Color,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{Node, NodeTrait},
+    },
+};
+use fyrox_graph::BaseSceneGraph;
+use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Max recursion depth of portals. Every level of recursion renders the entire scene once more for each
+/// visible portal, so the depth is always clamped to this value.
+pub const MAX_PORTAL_RECURSION_DEPTH: u32 = 4;
+
+/// Defines what is seen through a portal.
+#[derive(
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Debug,
+    Default,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+)]
+#[type_uuid(id = "3b0e5f3c-8d4a-4c71-9e2b-6a1f7d5c0e94")]
+pub enum PortalKind {
+    /// The portal reflects the scene in front of it.
+    #[default]
+    Mirror,
+    /// The portal shows the scene in front of its target node, as if the portal and the target were
+    /// glued together back to back.
+    Door,
+}
+
+/// Portal is a rectangle that shows the scene from a transformed point of view. The rectangle lies in
+/// local XY plane of the node, it is visible only from its front side (the side where local Z axis
+/// points to).
+///
+/// # Kinds
+///
+/// - [`PortalKind::Mirror`] - the portal reflects everything in front of it. Target node is ignored.
+/// - [`PortalKind::Door`] - the portal shows everything in front of its target node. Looking into the
+/// front side of the portal is the same as looking out of the front side of the target. Two doors
+/// targeting each other form a pair of non-euclidean passages. The target could be any node, its
+/// scale is taken into account, so a door could lead to a scaled copy of the world.
+///
+/// # Rendering
+///
+/// The renderer creates a virtual camera for each visible portal and renders the scene from it. Near
+/// clipping plane of the virtual camera is aligned with the plane of the destination, so nothing behind
+/// the destination can be seen through the portal. The result is then composited into the frame only
+/// where the portal is visible (using stencil mask).
+///
+/// Portals could see other portals (or themselves), in this case the rendering is done recursively,
+/// until [`Portal::recursion_depth`] is reached. Portals that are beyond the recursion depth are filled
+/// with [`Portal::fallback_color`].
+///
+/// # Performance
+///
+/// Every visible portal renders the entire scene once more on each level of recursion, keep amount of
+/// simultaneously visible portals and recursion depth as low as possible.
+///
+/// # Example
+///
+/// ```
+/// # use fyrox_impl::{
+/// #     core::{algebra::Vector2, pool::Handle},
+/// #     scene::{base::BaseBuilder, graph::Graph, node::Node, portal::PortalBuilder},
+/// # };
+/// fn create_mirror(graph: &mut Graph) -> Handle<Node> {
+///     PortalBuilder::new(BaseBuilder::new())
+///         .with_size(Vector2::new(2.0, 3.0))
+///         .build(graph)
+/// }
+/// ```
+#[derive(Debug, Visit, Clone, Reflect)]
+pub struct Portal {
+    base: Base,
+
+    #[reflect(setter = "set_kind")]
+    kind: InheritableVariable<PortalKind>,
+
+    #[reflect(setter = "set_target")]
+    target: InheritableVariable<Handle<Node>>,
+
+    #[reflect(setter = "set_size")]
+    size: InheritableVariable<Vector2<f32>>,
+
+    #[reflect(min_value = 0.0, max_value = 4.0)]
+    #[reflect(setter = "set_recursion_depth")]
+    recursion_depth: InheritableVariable<u32>,
+
+    #[reflect(setter = "set_fallback_color")]
+    fallback_color: InheritableVariable<Color>,
+}
+
+impl Deref for Portal {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Portal {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl Default for Portal {
+    fn default() -> Self {
+        PortalBuilder::new(BaseBuilder::new()).build_portal()
+    }
+}
+
+impl TypeUuidProvider for Portal {
+    fn type_uuid() -> Uuid {
+        uuid!("8f41c2d6-5b7e-4e09-a3c8-1d6f92b0e57a")
+    }
+}
+
+impl Portal {
+    /// Sets new kind of the portal.
+    pub fn set_kind(&mut self, kind: PortalKind) -> PortalKind {
+        self.kind.set_value_and_mark_modified(kind)
+    }
+
+    /// Returns current kind of the portal.
+    pub fn kind(&self) -> PortalKind {
+        *self.kind
+    }
+
+    /// Sets new target node of the portal. It is used only by [`PortalKind::Door`] portals.
+    pub fn set_target(&mut self, target: Handle<Node>) -> Handle<Node> {
+        self.target.set_value_and_mark_modified(target)
+    }
+
+    /// Returns current target node of the portal.
+    pub fn target(&self) -> Handle<Node> {
+        *self.target
+    }
+
+    /// Sets new size (width and height) of the portal rectangle in local coordinates.
+    pub fn set_size(&mut self, size: Vector2<f32>) -> Vector2<f32> {
+        self.size.set_value_and_mark_modified(size)
+    }
+
+    /// Returns current size of the portal rectangle.
+    pub fn size(&self) -> Vector2<f32> {
+        *self.size
+    }
+
+    /// Sets how many times portals could be seen through this portal. Zero means that the portal is
+    /// always filled with the fallback color. The value is clamped to [`MAX_PORTAL_RECURSION_DEPTH`].
+    pub fn set_recursion_depth(&mut self, depth: u32) -> u32 {
+        self.recursion_depth
+            .set_value_and_mark_modified(depth.min(MAX_PORTAL_RECURSION_DEPTH))
+    }
+
+    /// Returns current recursion depth of the portal.
+    pub fn recursion_depth(&self) -> u32 {
+        (*self.recursion_depth).min(MAX_PORTAL_RECURSION_DEPTH)
+    }
+
+    /// Sets the color that is used when the portal is beyond recursion depth (or it has no valid
+    /// target).
+    pub fn set_fallback_color(&mut self, color: Color) -> Color {
+        self.fallback_color.set_value_and_mark_modified(color)
+    }
+
+    /// Returns current fallback color.
+    pub fn fallback_color(&self) -> Color {
+        *self.fallback_color
+    }
+
+    /// Returns world-space transformation of the destination of the portal. It is the portal itself for
+    /// mirrors and the target node for doors. `None` is returned if the target of a door is invalid.
+    pub fn destination_transform(&self, graph: &Graph) -> Option<Matrix4<f32>> {
+        match *self.kind {
+            PortalKind::Mirror => Some(self.global_transform()),
+            PortalKind::Door => graph
+                .try_get(*self.target)
+                .map(|target| target.global_transform()),
+        }
+    }
+
+    /// Returns a matrix that maps the world in front of the portal to the world in front of its
+    /// destination. Transformation of a virtual camera is `portal_transform * camera_transform`.
+    pub fn portal_transform(&self, graph: &Graph) -> Option<Matrix4<f32>> {
+        let destination = self.destination_transform(graph)?;
+        let inv_portal = self.global_transform().try_inverse()?;
+        let flip = match *self.kind {
+            // Reflection over local XY plane.
+            PortalKind::Mirror => Matrix4::new_nonuniform_scaling(&Vector3::new(1.0, 1.0, -1.0)),
+            // Turn around, so the front side of the portal matches the front side of the target.
+            PortalKind::Door => Matrix4::new_nonuniform_scaling(&Vector3::new(-1.0, 1.0, -1.0)),
+        };
+        Some(destination * flip * inv_portal)
+    }
+
+    /// Returns world-space plane of the destination of the portal, normal of the plane points to the
+    /// visible side. The plane is defined as `(normal, -dot(normal, point))`.
+    pub fn clip_plane(&self, graph: &Graph) -> Option<Vector4<f32>> {
+        let destination = self.destination_transform(graph)?;
+        let normal = Vector3::new(destination[8], destination[9], destination[10])
+            .try_normalize(f32::EPSILON)?;
+        let point = Vector3::new(destination[12], destination[13], destination[14]);
+        Some(Vector4::new(
+            normal.x,
+            normal.y,
+            normal.z,
+            -normal.dot(&point),
+        ))
+    }
+
+    /// Returns `true` if the given point is in front of the portal, only such points could see
+    /// through it.
+    pub fn is_in_front(&self, point: Vector3<f32>) -> bool {
+        self.look_vector()
+            .dot(&(point - self.global_position()))
+            .is_sign_positive()
+    }
+
+    /// Returns world-space corners of the portal rectangle.
+    pub fn corners(&self) -> [Vector3<f32>; 4] {
+        let half = *self.size * 0.5;
+        let transform = self.global_transform();
+        [
+            Point3::new(-half.x, -half.y, 0.0),
+            Point3::new(half.x, -half.y, 0.0),
+            Point3::new(half.x, half.y, 0.0),
+            Point3::new(-half.x, half.y, 0.0),
+        ]
+        .map(|corner| transform.transform_point(&corner).coords)
+    }
+}
+
+/// Modifies the given projection matrix so its near clipping plane matches the given view-space plane,
+/// while the far plane stays (almost) the same. This is so-called oblique near-plane clipping, it
+/// prevents anything behind the plane from being rendered without any additional clipping planes. The
+/// plane must face away from the camera, otherwise the projection is returned unchanged.
+pub fn oblique_projection(projection: Matrix4<f32>, plane: Vector4<f32>) -> Matrix4<f32> {
+    // Camera is at the origin, it must be strictly behind the plane.
+    if plane.w > -f32::EPSILON {
+        return projection;
+    }
+
+    let Some(inv_projection) = projection.try_inverse() else {
+        return projection;
+    };
+
+    // Farthest corner of the view frustum in the direction of the plane.
+    let corner = inv_projection * Vector4::new(plane.x.signum(), plane.y.signum(), 1.0, 1.0);
+    let scaled_plane = plane * (2.0 / plane.dot(&corner));
+
+    let mut result = projection;
+    let third_row = scaled_plane.transpose() - projection.row(3);
+    result.set_row(2, &third_row);
+    result
+}
+
+impl NodeTrait for Portal {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        let half = *self.size * 0.5;
+        AxisAlignedBoundingBox::from_min_max(
+            Vector3::new(-half.x, -half.y, 0.0),
+            Vector3::new(half.x, half.y, 0.0),
+        )
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Allows you to create a portal in a declarative manner.
+pub struct PortalBuilder {
+    base_builder: BaseBuilder,
+    kind: PortalKind,
+    target: Handle<Node>,
+    size: Vector2<f32>,
+    recursion_depth: u32,
+    fallback_color: Color,
+}
+
+impl PortalBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            kind: PortalKind::Mirror,
+            target: Handle::NONE,
+            size: Vector2::new(1.0, 1.0),
+            recursion_depth: 1,
+            fallback_color: Color::opaque(20, 20, 20),
+        }
+    }
+
+    /// Sets desired kind of the portal.
+    pub fn with_kind(mut self, kind: PortalKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Sets desired target of the portal.
+    pub fn with_target(mut self, target: Handle<Node>) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Sets desired size of the portal rectangle.
+    pub fn with_size(mut self, size: Vector2<f32>) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets desired recursion depth.
+    pub fn with_recursion_depth(mut self, depth: u32) -> Self {
+        self.recursion_depth = depth.min(MAX_PORTAL_RECURSION_DEPTH);
+        self
+    }
+
+    /// Sets desired fallback color.
+    pub fn with_fallback_color(mut self, color: Color) -> Self {
+        self.fallback_color = color;
+        self
+    }
+
+    /// Creates new portal.
+    pub fn build_portal(self) -> Portal {
+        Portal {
+            base: self.base_builder.build_base(),
+            kind: self.kind.into(),
+            target: self.target.into(),
+            size: self.size.into(),
+            recursion_depth: self.recursion_depth.into(),
+            fallback_color: self.fallback_color.into(),
+        }
+    }
+
+    /// Creates new portal node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_portal())
+    }
+
+    /// Creates new portal node and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Matrix4, Point3, Vector2, Vector3, Vector4},
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            pivot::PivotBuilder,
+            portal::{oblique_projection, Portal, PortalBuilder, PortalKind},
+            transform::TransformBuilder,
+        },
+    };
+
+    #[test]
+    fn test_oblique_projection() {
+        let projection = Matrix4::new_perspective(1.0, 60.0f32.to_radians(), 0.1, 100.0);
+        // Plane z = -5 facing away from the camera, tilted a bit.
+        let normal = Vector3::new(0.2, 0.0, -1.0).normalize();
+        let point = Vector3::new(0.0, 0.0, -5.0);
+        let plane = Vector4::new(normal.x, normal.y, normal.z, -normal.dot(&point));
+
+        let oblique = oblique_projection(projection, plane);
+
+        // Points on the plane must lie on the near plane of the new projection.
+        for point in [point, point + Vector3::new(0.0, 1.0, 0.0)] {
+            let clip = oblique * point.push(1.0);
+            assert!((clip.z / clip.w + 1.0).abs() < 1.0e-4);
+        }
+
+        // Points behind the plane must be clipped.
+        let behind = oblique * Vector4::new(0.0, 0.0, -3.0, 1.0);
+        assert!(behind.z / behind.w < -1.0);
+
+        // Points in front of the plane must be visible.
+        let visible = oblique * Vector4::new(0.0, 0.0, -10.0, 1.0);
+        assert!((visible.z / visible.w).abs() <= 1.0);
+    }
+
+    #[test]
+    fn test_portal_transform() {
+        let mut graph = Graph::new();
+
+        let target = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(10.0, 0.0, 0.0))
+                    .build(),
+            ),
+        )
+        .build(&mut graph);
+
+        let door = PortalBuilder::new(BaseBuilder::new())
+            .with_kind(PortalKind::Door)
+            .with_target(target)
+            .with_size(Vector2::new(2.0, 2.0))
+            .build(&mut graph);
+        let mirror = PortalBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        graph.update_hierarchical_data();
+
+        // A camera in front of the door must be behind the target, looking through it.
+        let door = graph[door].cast::<Portal>().unwrap();
+        let portal_transform = door.portal_transform(&graph).unwrap();
+        let virtual_position = portal_transform.transform_point(&Point3::new(0.0, 0.0, 3.0));
+        assert!((virtual_position.coords - Vector3::new(10.0, 0.0, -3.0)).norm() < 1.0e-5);
+        let plane = door.clip_plane(&graph).unwrap();
+        assert!(plane.dot(&virtual_position.coords.push(1.0)) < 0.0);
+
+        // Reflection leaves points of the mirror untouched.
+        let mirror = graph[mirror].cast::<Portal>().unwrap();
+        let reflection = mirror.portal_transform(&graph).unwrap();
+        let point = Point3::new(0.5, 0.5, 0.0);
+        assert_eq!(reflection.transform_point(&point), point);
+        assert_eq!(
+            reflection.transform_point(&Point3::new(0.0, 0.0, 2.0)),
+            Point3::new(0.0, 0.0, -2.0)
+        );
+    }
+}
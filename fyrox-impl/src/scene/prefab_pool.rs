@@ -0,0 +1,287 @@
+//! Object pooling for frequently spawned prefabs. See [`PrefabPool`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        pool::Handle,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    resource::model::{ModelResource, ModelResourceExtension},
+    scene::{
+        base::{LifetimePolicy, NodeScriptMessage},
+        graph::Graph,
+        node::Node,
+        Scene,
+    },
+};
+
+/// Prefab pool keeps a set of instances of a prefab and recycles them instead of creating new
+/// instances and destroying old ones. It is useful for objects that are spawned very often and
+/// live for a short period of time - bullets, impact effects, pickups, etc. Instantiating a prefab
+/// every frame is relatively costly, since it requires to copy the whole hierarchy of nodes,
+/// allocate their scripts and so on.
+///
+/// The pool is integrated with the lifetime of scene nodes - every instance of the pool uses
+/// [`LifetimePolicy::Disable`], which means that the instance is disabled when its lifetime ends
+/// and it will be reused by the next [`Self::spawn`] call.
+///
+/// # Scripts
+///
+/// Scripts of recycled instances are not initialized again, instead the engine calls
+/// [`crate::script::ScriptTrait::on_recycle`] method, which should be used to reset the state of
+/// the scripts.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::algebra::{UnitQuaternion, Vector3},
+/// #     resource::model::ModelResource,
+/// #     scene::{prefab_pool::PrefabPool, Scene},
+/// # };
+/// fn fire(pool: &mut PrefabPool, scene: &mut Scene, position: Vector3<f32>) {
+///     pool.spawn(scene, position, UnitQuaternion::identity());
+/// }
+///
+/// fn create_bullet_pool(bullet: ModelResource, scene: &mut Scene) -> PrefabPool {
+///     // Every bullet will live for 2 seconds.
+///     let mut pool = PrefabPool::new(bullet, Some(2.0));
+///     // Pre-instantiate some bullets to avoid hitches when the game starts.
+///     pool.reserve(scene, 32);
+///     pool
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PrefabPool {
+    prefab: ModelResource,
+    lifetime: Option<f32>,
+    active: Vec<Handle<Node>>,
+    free: Vec<Handle<Node>>,
+}
+
+impl PrefabPool {
+    /// Creates a new empty pool for the given prefab. `lifetime` defines a lifetime of spawned
+    /// instances, `None` means that instances must be returned to the pool manually using
+    /// [`Self::despawn`].
+    pub fn new(prefab: ModelResource, lifetime: Option<f32>) -> Self {
+        Self {
+            prefab,
+            lifetime,
+            active: Default::default(),
+            free: Default::default(),
+        }
+    }
+
+    /// Returns a reference to the prefab of the pool.
+    pub fn prefab(&self) -> &ModelResource {
+        &self.prefab
+    }
+
+    /// Sets a new lifetime of spawned instances. It does not affect instances that are already
+    /// spawned.
+    pub fn set_lifetime(&mut self, lifetime: Option<f32>) {
+        self.lifetime = lifetime;
+    }
+
+    /// Returns current lifetime of spawned instances.
+    pub fn lifetime(&self) -> Option<f32> {
+        self.lifetime
+    }
+
+    /// Returns amount of instances that are currently in use.
+    pub fn active_count(&self) -> usize {
+        self.active.len()
+    }
+
+    /// Returns amount of instances that are ready to be reused.
+    pub fn free_count(&self) -> usize {
+        self.free.len()
+    }
+
+    fn instantiate_disabled(&self, scene: &mut Scene) -> Handle<Node> {
+        let instance = self.prefab.instantiate(scene);
+        let node = &mut scene.graph[instance];
+        node.set_lifetime_policy(LifetimePolicy::Disable);
+        node.set_enabled(false);
+        scene
+            .graph
+            .update_hierarchical_data_for_descendants(instance);
+        instance
+    }
+
+    /// Pre-instantiates the given amount of instances. Pre-instantiated instances are disabled
+    /// until they're spawned.
+    pub fn reserve(&mut self, scene: &mut Scene, count: usize) {
+        self.free.reserve(count);
+        for _ in 0..count {
+            let instance = self.instantiate_disabled(scene);
+            self.free.push(instance);
+        }
+    }
+
+    /// Returns the instances, that were disabled (because of ended lifetime or by some other
+    /// means), back to the pool. Instances that were removed from the graph are forgotten.
+    pub fn reclaim(&mut self, graph: &Graph) {
+        let free = &mut self.free;
+        self.active.retain(|handle| match graph.try_get(*handle) {
+            Some(node) if node.is_enabled() => true,
+            Some(_) => {
+                free.push(*handle);
+                false
+            }
+            None => false,
+        });
+        self.free.retain(|handle| graph.is_valid_handle(*handle));
+    }
+
+    /// Takes a free instance from the pool (or creates a new one if there are no free instances)
+    /// and places it at the given position and orientation in global coordinates.
+    pub fn spawn(
+        &mut self,
+        scene: &mut Scene,
+        position: Vector3<f32>,
+        orientation: UnitQuaternion<f32>,
+    ) -> Handle<Node> {
+        self.reclaim(&scene.graph);
+
+        let (instance, recycled) = match self.free.pop() {
+            Some(instance) => (instance, true),
+            None => (self.instantiate_disabled(scene), false),
+        };
+
+        let node = &mut scene.graph[instance];
+        node.local_transform_mut()
+            .set_position(position)
+            .set_rotation(orientation);
+        node.set_lifetime(self.lifetime);
+        node.set_enabled(true);
+        scene
+            .graph
+            .update_hierarchical_data_for_descendants(instance);
+
+        if recycled {
+            let graph = &scene.graph;
+            for handle in graph.traverse_handle_iter(instance) {
+                for script_index in 0..graph[handle].scripts.len() {
+                    graph
+                        .script_message_sender
+                        .send(NodeScriptMessage::RecycleScript {
+                            handle,
+                            script_index,
+                        })
+                        .unwrap();
+                }
+            }
+        }
+
+        self.active.push(instance);
+
+        instance
+    }
+
+    /// Returns the given instance back to the pool. The instance will be disabled. Returns `false`
+    /// if the instance does not belong to the pool or it was already returned.
+    pub fn despawn(&mut self, graph: &mut Graph, instance: Handle<Node>) -> bool {
+        let Some(position) = self.active.iter().position(|h| *h == instance) else {
+            return false;
+        };
+
+        self.active.remove(position);
+
+        if let Some(node) = graph.try_get_mut(instance) {
+            node.set_enabled(false);
+            self.free.push(instance);
+        }
+
+        true
+    }
+
+    /// Removes every instance of the pool from the graph.
+    pub fn clear(&mut self, graph: &mut Graph) {
+        for instance in self.active.drain(..).chain(self.free.drain(..)) {
+            if graph.is_valid_handle(instance) {
+                graph.remove_node(instance);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{UnitQuaternion, Vector2, Vector3},
+            pool::Handle,
+        },
+        graph::BaseSceneGraph,
+        scene::{
+            base::{BaseBuilder, LifetimePolicy},
+            graph::Graph,
+            node::Node,
+            pivot::PivotBuilder,
+            prefab_pool::PrefabPool,
+            Scene,
+        },
+    };
+
+    #[test]
+    fn test_prefab_pool() {
+        let mut graph = Graph::new();
+        let bullet = PivotBuilder::new(BaseBuilder::new().with_name("Bullet")).build(&mut graph);
+        let prefab = graph.extract_as_prefab(bullet);
+
+        let mut scene = Scene::new();
+        let mut pool = PrefabPool::new(prefab, Some(1.0));
+        pool.reserve(&mut scene, 2);
+        assert_eq!(pool.free_count(), 2);
+        // Root + two instances.
+        assert_eq!(scene.graph.node_count(), 3);
+
+        let spawn = |pool: &mut PrefabPool, scene: &mut Scene| -> Handle<Node> {
+            pool.spawn(
+                scene,
+                Vector3::new(1.0, 2.0, 3.0),
+                UnitQuaternion::identity(),
+            )
+        };
+
+        let a = spawn(&mut pool, &mut scene);
+        let node = &scene.graph[a];
+        assert!(node.is_enabled());
+        assert_eq!(node.lifetime(), Some(1.0));
+        assert_eq!(node.lifetime_policy(), LifetimePolicy::Disable);
+        assert_eq!(
+            **node.local_transform().position(),
+            Vector3::new(1.0, 2.0, 3.0)
+        );
+
+        assert!(pool.despawn(&mut scene.graph, a));
+        assert!(!pool.despawn(&mut scene.graph, a));
+        assert!(!scene.graph[a].is_enabled());
+
+        // Despawned instance must be reused.
+        let b = spawn(&mut pool, &mut scene);
+        assert_eq!(a, b);
+
+        // Instance with ended lifetime must be disabled, but not removed.
+        scene
+            .graph
+            .update(Vector2::new(800.0, 600.0), 2.0, Default::default());
+        assert!(scene.graph.is_valid_handle(b));
+        assert!(!scene.graph[b].is_enabled());
+
+        pool.reclaim(&scene.graph);
+        assert_eq!(pool.active_count(), 0);
+        assert_eq!(pool.free_count(), 2);
+
+        // No new instances must be created until all the free ones are used.
+        spawn(&mut pool, &mut scene);
+        spawn(&mut pool, &mut scene);
+        assert_eq!(scene.graph.node_count(), 3);
+        spawn(&mut pool, &mut scene);
+        assert_eq!(scene.graph.node_count(), 4);
+
+        pool.clear(&mut scene.graph);
+        assert_eq!(scene.graph.node_count(), 1);
+    }
+}
@@ -0,0 +1,381 @@
+//! Probe volume is a grid of light probes, that provides dynamic diffuse global illumination for
+//! everything inside of it.
+//!
+//! For more info see [`ProbeVolume`]
+
+use crate::{
+    core::{
+        algebra::{Point3, Vector3},
+        color::Color,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{Node, NodeTrait},
+    },
+};
+use fyrox_graph::BaseSceneGraph;
+use std::ops::{Deref, DerefMut};
+
+/// Max amount of probes along each axis of a probe volume.
+pub const MAX_PROBES_PER_AXIS: u32 = 32;
+
+/// Max amount of rays, that are traced from a probe during a single update.
+pub const MAX_RAYS_PER_PROBE: u32 = 256;
+
+/// Probe volume is a box filled with a regular grid of light probes, that capture incoming diffuse
+/// light. The probes are used to light everything inside the volume with indirect (bounced) light,
+/// which is much more realistic than constant ambient lighting. The volume is a unit cube in local
+/// coordinates of the node, use the scale of the node to define its size.
+///
+/// # How it works
+///
+/// Every frame the renderer updates a few probes (see [`ProbeVolume::set_probes_per_frame`]) by
+/// casting rays from them against a simplified (CPU-side) representation of the scene, that
+/// consists of the visible meshes and the lights. Each ray gathers direct light, emission, sky light
+/// and the light from the probes themselves at the hit point, which gives multiple bounces of light
+/// over time. The new lighting is blended with the previous state of the probe (see
+/// [`ProbeVolume::set_hysteresis`]), which hides noise and makes the lighting change smoothly. The
+/// representation of the scene is rebuilt every time all probes of the volume were updated.
+///
+/// Each probe stores irradiance and the distances to the surrounding geometry in six directions.
+/// The distances are used when the probes are sampled in the deferred lighting pass, the probes,
+/// that cannot "see" the shaded point are discarded, so the light does not leak through walls.
+///
+/// The indirect light of the probes is added on top of the ambient lighting of the scene, so it is
+/// a good idea to lower the ambient lighting color when using probe volumes.
+///
+/// # Performance
+///
+/// The total amount of traced rays per frame is `probes_per_frame * rays_per_probe`, the rays are
+/// traced on all CPU cores. The volume must cover only the area, where the indirect light is
+/// important, and probe density should be as low as possible. Probe volumes work only with the
+/// deferred renderer, forward-rendered (transparent) objects do not receive indirect light.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::Vector3, pool::Handle},
+/// #     scene::{
+/// #         base::BaseBuilder, graph::Graph, node::Node, probe_volume::ProbeVolumeBuilder,
+/// #         transform::TransformBuilder,
+/// #     },
+/// # };
+/// fn create_probe_volume(graph: &mut Graph) -> Handle<Node> {
+///     ProbeVolumeBuilder::new(
+///         BaseBuilder::new().with_local_transform(
+///             TransformBuilder::new()
+///                 .with_local_scale(Vector3::new(20.0, 5.0, 20.0))
+///                 .build(),
+///         ),
+///     )
+///     .with_probe_count(Vector3::new(10, 3, 10))
+///     .build(graph)
+/// }
+/// ```
+#[derive(Debug, Visit, Clone, Reflect)]
+pub struct ProbeVolume {
+    base: Base,
+
+    #[reflect(setter = "set_probe_count")]
+    probe_count: InheritableVariable<Vector3<u32>>,
+
+    #[reflect(min_value = 1.0, max_value = 256.0)]
+    #[reflect(setter = "set_rays_per_probe")]
+    rays_per_probe: InheritableVariable<u32>,
+
+    #[reflect(min_value = 1.0)]
+    #[reflect(setter = "set_probes_per_frame")]
+    probes_per_frame: InheritableVariable<u32>,
+
+    #[reflect(min_value = 0.0, max_value = 0.999, step = 0.01)]
+    #[reflect(setter = "set_hysteresis")]
+    hysteresis: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.05)]
+    #[reflect(setter = "set_intensity")]
+    intensity: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.01)]
+    #[reflect(setter = "set_normal_bias")]
+    normal_bias: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_sky_color")]
+    sky_color: InheritableVariable<Color>,
+
+    #[reflect(min_value = 0.0, step = 0.05)]
+    #[reflect(setter = "set_sky_intensity")]
+    sky_intensity: InheritableVariable<f32>,
+}
+
+impl Default for ProbeVolume {
+    fn default() -> Self {
+        ProbeVolumeBuilder::new(BaseBuilder::new()).build_probe_volume()
+    }
+}
+
+impl Deref for ProbeVolume {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for ProbeVolume {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for ProbeVolume {
+    fn type_uuid() -> Uuid {
+        uuid!("2a7f3c1e-94d6-4b8a-bf05-6e1d8c93a4f2")
+    }
+}
+
+fn clamp_probe_count(count: Vector3<u32>) -> Vector3<u32> {
+    count.map(|n| n.clamp(1, MAX_PROBES_PER_AXIS))
+}
+
+impl ProbeVolume {
+    /// Sets the amount of probes along each axis of the volume. Every component is clamped to
+    /// `[1; MAX_PROBES_PER_AXIS]` range. Changing the amount of probes discards the lighting
+    /// gathered so far.
+    pub fn set_probe_count(&mut self, count: Vector3<u32>) -> Vector3<u32> {
+        self.probe_count
+            .set_value_and_mark_modified(clamp_probe_count(count))
+    }
+
+    /// Returns the amount of probes along each axis of the volume.
+    pub fn probe_count(&self) -> Vector3<u32> {
+        clamp_probe_count(*self.probe_count)
+    }
+
+    /// Returns total amount of probes in the volume.
+    pub fn total_probe_count(&self) -> usize {
+        let count = self.probe_count();
+        count.x as usize * count.y as usize * count.z as usize
+    }
+
+    /// Sets the amount of rays, that are traced from a probe when it is updated. More rays give
+    /// less noisy lighting. The value is clamped to `[1; MAX_RAYS_PER_PROBE]` range.
+    pub fn set_rays_per_probe(&mut self, rays: u32) -> u32 {
+        self.rays_per_probe
+            .set_value_and_mark_modified(rays.clamp(1, MAX_RAYS_PER_PROBE))
+    }
+
+    /// Returns the amount of rays, that are traced from a probe when it is updated.
+    pub fn rays_per_probe(&self) -> u32 {
+        (*self.rays_per_probe).clamp(1, MAX_RAYS_PER_PROBE)
+    }
+
+    /// Sets the amount of probes, that are updated each frame. Larger values make the lighting
+    /// react faster to the changes in the scene, at the cost of performance.
+    pub fn set_probes_per_frame(&mut self, probes: u32) -> u32 {
+        self.probes_per_frame
+            .set_value_and_mark_modified(probes.max(1))
+    }
+
+    /// Returns the amount of probes, that are updated each frame.
+    pub fn probes_per_frame(&self) -> u32 {
+        (*self.probes_per_frame).max(1)
+    }
+
+    /// Sets how much of the previous lighting of a probe is kept when the probe is updated. Values
+    /// close to one give smooth, but slowly changing lighting. The value is clamped to
+    /// `[0.0; 0.999]` range.
+    pub fn set_hysteresis(&mut self, hysteresis: f32) -> f32 {
+        self.hysteresis
+            .set_value_and_mark_modified(hysteresis.clamp(0.0, 0.999))
+    }
+
+    /// Returns how much of the previous lighting of a probe is kept when the probe is updated.
+    pub fn hysteresis(&self) -> f32 {
+        *self.hysteresis
+    }
+
+    /// Sets the multiplier of the indirect light of the volume.
+    pub fn set_intensity(&mut self, intensity: f32) -> f32 {
+        self.intensity
+            .set_value_and_mark_modified(intensity.max(0.0))
+    }
+
+    /// Returns the multiplier of the indirect light of the volume.
+    pub fn intensity(&self) -> f32 {
+        *self.intensity
+    }
+
+    /// Sets an offset (in world units) along the normal of a shaded point, that is used when
+    /// sampling the probes. It prevents the surfaces from shadowing themselves.
+    pub fn set_normal_bias(&mut self, bias: f32) -> f32 {
+        self.normal_bias.set_value_and_mark_modified(bias.max(0.0))
+    }
+
+    /// Returns the offset along the normal of a shaded point, that is used when sampling the probes.
+    pub fn normal_bias(&self) -> f32 {
+        *self.normal_bias
+    }
+
+    /// Sets the color of the sky, that is used for every ray that does not hit anything.
+    pub fn set_sky_color(&mut self, color: Color) -> Color {
+        self.sky_color.set_value_and_mark_modified(color)
+    }
+
+    /// Returns the color of the sky.
+    pub fn sky_color(&self) -> Color {
+        *self.sky_color
+    }
+
+    /// Sets the intensity of the sky light. Set it to zero for closed interiors.
+    pub fn set_sky_intensity(&mut self, intensity: f32) -> f32 {
+        self.sky_intensity
+            .set_value_and_mark_modified(intensity.max(0.0))
+    }
+
+    /// Returns the intensity of the sky light.
+    pub fn sky_intensity(&self) -> f32 {
+        *self.sky_intensity
+    }
+
+    /// Returns local position of a probe with the given coordinates in the grid. Probes are placed
+    /// in the centers of the cells of the grid.
+    pub fn probe_local_position(&self, x: u32, y: u32, z: u32) -> Vector3<f32> {
+        let count = self.probe_count().cast::<f32>();
+        Vector3::new(
+            (x as f32 + 0.5) / count.x - 0.5,
+            (y as f32 + 0.5) / count.y - 0.5,
+            (z as f32 + 0.5) / count.z - 0.5,
+        )
+    }
+
+    /// Returns world-space position of a probe with the given coordinates in the grid.
+    pub fn probe_position(&self, x: u32, y: u32, z: u32) -> Vector3<f32> {
+        self.global_transform()
+            .transform_point(&Point3::from(self.probe_local_position(x, y, z)))
+            .coords
+    }
+}
+
+impl NodeTrait for ProbeVolume {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox::unit()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Allows you to create probe volumes in declarative manner.
+pub struct ProbeVolumeBuilder {
+    base_builder: BaseBuilder,
+    probe_count: Vector3<u32>,
+    rays_per_probe: u32,
+    probes_per_frame: u32,
+    hysteresis: f32,
+    intensity: f32,
+    normal_bias: f32,
+    sky_color: Color,
+    sky_intensity: f32,
+}
+
+impl ProbeVolumeBuilder {
+    /// Creates new probe volume builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            probe_count: Vector3::new(8, 4, 8),
+            rays_per_probe: 64,
+            probes_per_frame: 32,
+            hysteresis: 0.9,
+            intensity: 1.0,
+            normal_bias: 0.1,
+            sky_color: Color::opaque(130, 150, 170),
+            sky_intensity: 1.0,
+        }
+    }
+
+    /// Sets desired amount of probes along each axis. See [`ProbeVolume::set_probe_count`].
+    pub fn with_probe_count(mut self, count: Vector3<u32>) -> Self {
+        self.probe_count = clamp_probe_count(count);
+        self
+    }
+
+    /// Sets desired amount of rays per probe. See [`ProbeVolume::set_rays_per_probe`].
+    pub fn with_rays_per_probe(mut self, rays: u32) -> Self {
+        self.rays_per_probe = rays.clamp(1, MAX_RAYS_PER_PROBE);
+        self
+    }
+
+    /// Sets desired amount of probes, that are updated each frame.
+    pub fn with_probes_per_frame(mut self, probes: u32) -> Self {
+        self.probes_per_frame = probes.max(1);
+        self
+    }
+
+    /// Sets desired hysteresis. See [`ProbeVolume::set_hysteresis`].
+    pub fn with_hysteresis(mut self, hysteresis: f32) -> Self {
+        self.hysteresis = hysteresis.clamp(0.0, 0.999);
+        self
+    }
+
+    /// Sets desired intensity of the indirect light.
+    pub fn with_intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity.max(0.0);
+        self
+    }
+
+    /// Sets desired normal bias. See [`ProbeVolume::set_normal_bias`].
+    pub fn with_normal_bias(mut self, bias: f32) -> Self {
+        self.normal_bias = bias.max(0.0);
+        self
+    }
+
+    /// Sets desired color and intensity of the sky.
+    pub fn with_sky(mut self, color: Color, intensity: f32) -> Self {
+        self.sky_color = color;
+        self.sky_intensity = intensity.max(0.0);
+        self
+    }
+
+    /// Creates new probe volume.
+    pub fn build_probe_volume(self) -> ProbeVolume {
+        ProbeVolume {
+            base: self.base_builder.build_base(),
+            probe_count: self.probe_count.into(),
+            rays_per_probe: self.rays_per_probe.into(),
+            probes_per_frame: self.probes_per_frame.into(),
+            hysteresis: self.hysteresis.into(),
+            intensity: self.intensity.into(),
+            normal_bias: self.normal_bias.into(),
+            sky_color: self.sky_color.into(),
+            sky_intensity: self.sky_intensity.into(),
+        }
+    }
+
+    /// Creates new probe volume node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_probe_volume())
+    }
+
+    /// Creates new probe volume and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
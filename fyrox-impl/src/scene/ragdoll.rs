@@ -81,6 +81,8 @@ impl Reflect for Limb {
                 max_value: None,
                 step: None,
                 precision: None,
+                units: "",
+                editor: None,
                 doc: "",
             },
             FieldInfo {
@@ -97,6 +99,8 @@ impl Reflect for Limb {
                 max_value: None,
                 step: None,
                 precision: None,
+                units: "",
+                editor: None,
                 doc: "",
             },
             FieldInfo {
@@ -113,6 +117,8 @@ impl Reflect for Limb {
                 max_value: None,
                 step: None,
                 precision: None,
+                units: "",
+                editor: None,
                 doc: "",
             },
         ])
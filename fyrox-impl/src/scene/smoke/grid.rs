@@ -0,0 +1,269 @@
+//! CPU implementation of the smoke simulation. It is used when GPU simulation is not supported or
+//! explicitly disabled. See [`SmokeGrid`] docs for more info.
+
+use crate::{
+    core::algebra::{Vector3, Vector4},
+    scene::smoke::SmokeSimulation,
+};
+use std::ops::{Add, Mul};
+
+// Simulation becomes unstable on large time steps, so they're clamped.
+const MAX_TIME_STEP: f32 = 1.0 / 30.0;
+
+/// A cubic grid of velocities, densities and temperatures of the smoke. The grid uses the classic
+/// "stable fluids" approach: semi-Lagrangian advection and a pressure projection that is solved using
+/// Jacobi iterations. Exactly the same algorithm is used by the GPU simulation.
+///
+/// Every cell is a cube with unit size, velocities are measured in cells per second.
+#[derive(Clone, Debug)]
+pub struct SmokeGrid {
+    size: usize,
+    velocity: Vec<Vector3<f32>>,
+    density: Vec<f32>,
+    temperature: Vec<f32>,
+    pressure: Vec<f32>,
+    divergence: Vec<f32>,
+    velocity_next: Vec<Vector3<f32>>,
+    scalar_next: Vec<f32>,
+}
+
+fn sample<T>(field: &[T], size: usize, position: Vector3<f32>) -> T
+where
+    T: Copy + Add<Output = T> + Mul<f32, Output = T>,
+{
+    // Cell centers are at half-integer coordinates.
+    let max = (size - 1) as f32;
+    let p = position.add_scalar(-0.5).map(|c| c.clamp(0.0, max));
+    let i0 = p.map(|c| c.floor() as usize);
+    let i1 = i0.map(|c| (c + 1).min(size - 1));
+    let f = p - i0.map(|c| c as f32);
+
+    let at = |x: usize, y: usize, z: usize| field[x + y * size + z * size * size];
+    let lerp = |a: T, b: T, t: f32| a * (1.0 - t) + b * t;
+
+    let c00 = lerp(at(i0.x, i0.y, i0.z), at(i1.x, i0.y, i0.z), f.x);
+    let c10 = lerp(at(i0.x, i1.y, i0.z), at(i1.x, i1.y, i0.z), f.x);
+    let c01 = lerp(at(i0.x, i0.y, i1.z), at(i1.x, i0.y, i1.z), f.x);
+    let c11 = lerp(at(i0.x, i1.y, i1.z), at(i1.x, i1.y, i1.z), f.x);
+
+    lerp(lerp(c00, c10, f.y), lerp(c01, c11, f.y), f.z)
+}
+
+// Iterates over linear indices and coordinates of the cells.
+fn cells(size: usize) -> impl Iterator<Item = (usize, Vector3<usize>)> {
+    (0..size * size * size).map(move |i| {
+        (
+            i,
+            Vector3::new(i % size, (i / size) % size, i / (size * size)),
+        )
+    })
+}
+
+impl SmokeGrid {
+    /// Creates a new empty grid with the given amount of cells along each axis.
+    pub fn new(size: usize) -> Self {
+        let size = size.max(2);
+        let count = size * size * size;
+        Self {
+            size,
+            velocity: vec![Vector3::zeros(); count],
+            density: vec![0.0; count],
+            temperature: vec![0.0; count],
+            pressure: vec![0.0; count],
+            divergence: vec![0.0; count],
+            velocity_next: vec![Vector3::zeros(); count],
+            scalar_next: vec![0.0; count],
+        }
+    }
+
+    /// Returns amount of cells along each axis.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns densities of the cells. Cell `(x, y, z)` has `x + y * size + z * size * size` index.
+    pub fn density(&self) -> &[f32] {
+        &self.density
+    }
+
+    /// Returns temperatures of the cells. Uses the same layout as [`Self::density`].
+    pub fn temperature(&self) -> &[f32] {
+        &self.temperature
+    }
+
+    /// Returns velocities of the cells. Uses the same layout as [`Self::density`].
+    pub fn velocity(&self) -> &[Vector3<f32>] {
+        &self.velocity
+    }
+
+    #[inline]
+    fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        x + y * self.size + z * self.size * self.size
+    }
+
+    // Value of the field in a neighbour cell. Out-of-bounds cells are clamped, which gives zero
+    // gradient on the walls of the grid.
+    fn neighbour<T: Copy>(&self, field: &[T], cell: Vector3<usize>, axis: usize, dir: isize) -> T {
+        let mut cell = cell;
+        cell[axis] = (cell[axis] as isize + dir).clamp(0, self.size as isize - 1) as usize;
+        field[self.index(cell.x, cell.y, cell.z)]
+    }
+
+    /// Performs one simulation step.
+    pub fn step(&mut self, simulation: &SmokeSimulation, dt: f32) {
+        let dt = (dt * simulation.time_scale).min(MAX_TIME_STEP);
+        if dt <= 0.0 {
+            return;
+        }
+
+        let size = self.size;
+        let n = size as f32;
+        let source = simulation.grid_source(size);
+
+        // Advect densities and temperatures, then add new smoke from the source.
+        let density_decay = (-simulation.density_dissipation * dt).exp();
+        let temperature_decay = (-simulation.temperature_dissipation * dt).exp();
+        for (i, cell) in cells(self.size) {
+            let position = cell.map(|c| c as f32).add_scalar(0.5);
+            let back = position - self.velocity[i].scale(dt);
+            let w = source.weight(position);
+            self.scalar_next[i] =
+                sample(&self.density, size, back) * density_decay + source.density * w * dt;
+            self.divergence[i] = sample(&self.temperature, size, back) * temperature_decay
+                + source.temperature * w * dt;
+        }
+        std::mem::swap(&mut self.density, &mut self.scalar_next);
+        // Divergence is used as a scratch buffer here, it is fully overwritten by the projection.
+        std::mem::swap(&mut self.temperature, &mut self.divergence);
+
+        // Advect velocity and apply forces: hot smoke rises, dense smoke sinks.
+        let velocity_decay = (-simulation.velocity_dissipation * dt).exp();
+        for (i, cell) in cells(self.size) {
+            let position = cell.map(|c| c as f32).add_scalar(0.5);
+            let back = position - self.velocity[i].scale(dt);
+            let mut velocity = sample(&self.velocity, size, back) * velocity_decay;
+            velocity.y += (simulation.buoyancy * self.temperature[i]
+                - simulation.weight * self.density[i])
+                * n
+                * dt;
+            let w = source.weight(position);
+            self.velocity_next[i] = velocity.lerp(&source.velocity, w);
+        }
+        std::mem::swap(&mut self.velocity, &mut self.velocity_next);
+
+        self.project(simulation.pressure_iterations as usize);
+    }
+
+    // Makes velocity field divergence-free, which gives the swirling motion of the smoke.
+    fn project(&mut self, iterations: usize) {
+        for (i, cell) in cells(self.size) {
+            let mut divergence = 0.0;
+            for axis in 0..3 {
+                // Walls of the grid are solid, so there's no velocity outside.
+                let next = if cell[axis] + 1 < self.size {
+                    self.neighbour(&self.velocity, cell, axis, 1)[axis]
+                } else {
+                    0.0
+                };
+                let prev = if cell[axis] > 0 {
+                    self.neighbour(&self.velocity, cell, axis, -1)[axis]
+                } else {
+                    0.0
+                };
+                divergence += 0.5 * (next - prev);
+            }
+            self.divergence[i] = divergence;
+        }
+
+        for _ in 0..iterations {
+            for (i, cell) in cells(self.size) {
+                let mut sum = 0.0;
+                for axis in 0..3 {
+                    sum += self.neighbour(&self.pressure, cell, axis, 1)
+                        + self.neighbour(&self.pressure, cell, axis, -1);
+                }
+                self.scalar_next[i] = (sum - self.divergence[i]) / 6.0;
+            }
+            std::mem::swap(&mut self.pressure, &mut self.scalar_next);
+        }
+
+        for (i, cell) in cells(self.size) {
+            let mut gradient = Vector3::zeros();
+            for axis in 0..3 {
+                gradient[axis] = 0.5
+                    * (self.neighbour(&self.pressure, cell, axis, 1)
+                        - self.neighbour(&self.pressure, cell, axis, -1));
+            }
+            self.velocity[i] -= gradient;
+        }
+    }
+
+    /// Writes densities (`x`) and temperatures (`y`) of the cells into an "atlas" - a 2D image with
+    /// `size * size` width and `size` height, where all the slices of the grid are placed in a row.
+    /// This layout is used by the renderer.
+    pub fn write_atlas(&self, atlas: &mut Vec<Vector4<f32>>) {
+        let size = self.size;
+        atlas.clear();
+        atlas.reserve(size * size * size);
+        for y in 0..size {
+            for z in 0..size {
+                for x in 0..size {
+                    let i = self.index(x, y, z);
+                    atlas.push(Vector4::new(self.density[i], self.temperature[i], 0.0, 0.0));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        scene::smoke::{grid::SmokeGrid, SmokeSimulation},
+    };
+
+    #[test]
+    fn test_smoke_rises() {
+        let simulation = SmokeSimulation {
+            source_position: Vector3::new(0.0, -0.3, 0.0),
+            ..Default::default()
+        };
+
+        let mut grid = SmokeGrid::new(16);
+        for _ in 0..60 {
+            grid.step(&simulation, 1.0 / 60.0);
+        }
+
+        let size = grid.size();
+        let (mut total, mut weighted_height) = (0.0, 0.0);
+        for (i, density) in grid.density().iter().enumerate() {
+            assert!(density.is_finite());
+            total += density;
+            weighted_height += density * (((i / size) % size) as f32 + 0.5);
+        }
+        assert!(total > 0.0);
+
+        // Center of mass of the smoke must be above the source.
+        let source_height = (simulation.source_position.y + 0.5) * size as f32;
+        assert!(weighted_height / total > source_height);
+    }
+
+    #[test]
+    fn test_smoke_dissipates() {
+        let mut simulation = SmokeSimulation::default();
+
+        let mut grid = SmokeGrid::new(8);
+        for _ in 0..10 {
+            grid.step(&simulation, 1.0 / 30.0);
+        }
+        assert!(grid.density().iter().any(|d| *d > 0.0));
+
+        simulation.emitting = false;
+        simulation.density_dissipation = 10.0;
+        for _ in 0..300 {
+            grid.step(&simulation, 1.0 / 30.0);
+        }
+        assert!(grid.density().iter().all(|d| *d < 1.0e-3));
+    }
+}
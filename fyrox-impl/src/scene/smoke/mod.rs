@@ -0,0 +1,475 @@
+//! Smoke is a small volume with a grid-based fluid simulation, that is rendered using ray marching.
+//!
+//! For more info see [`Smoke`]
+
+use crate::{
+    core::{
+        algebra::Vector3,
+        color::Color,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{Node, NodeTrait},
+    },
+};
+use fyrox_graph::BaseSceneGraph;
+use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+pub mod grid;
+
+/// Defines where the smoke is simulated.
+#[derive(
+    Copy,
+    Clone,
+    Default,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum SmokeSimulationMode {
+    /// The simulation is performed on GPU if the current graphics API supports floating-point
+    /// render targets, otherwise it falls back to CPU.
+    #[default]
+    Gpu,
+    /// The simulation is always performed on CPU and the result is uploaded to GPU every frame.
+    /// It is much slower, use it only for low-resolution grids.
+    Cpu,
+}
+
+uuid_provider!(SmokeSimulationMode = "9a4c6e1f-2b7d-4e38-a5f0-7c3d1b8e2f64");
+
+/// Parameters of the smoke simulation. All the distances are measured in fractions of the volume
+/// size, so the simulation looks the same at any resolution of the grid.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct SmokeSimulation {
+    /// Amount of cells of the grid along each axis. Memory and computational costs grow cubically
+    /// with the resolution.
+    #[reflect(min_value = 8.0, max_value = 128.0, step = 1.0)]
+    pub resolution: u32,
+    /// Whether the source emits smoke or not.
+    pub emitting: bool,
+    /// Position of the spherical source of the smoke in local coordinates of the volume (the volume
+    /// is a cube with `[-0.5; 0.5]` bounds).
+    pub source_position: Vector3<f32>,
+    /// Radius of the source.
+    #[reflect(min_value = 0.0, max_value = 0.5, step = 0.01)]
+    pub source_radius: f32,
+    /// Amount of density emitted by the source per second.
+    #[reflect(min_value = 0.0)]
+    pub source_density: f32,
+    /// Amount of temperature emitted by the source per second. Temperature makes the smoke rise and
+    /// glow like fire.
+    #[reflect(min_value = 0.0)]
+    pub source_temperature: f32,
+    /// Velocity of the smoke inside the source.
+    pub source_velocity: Vector3<f32>,
+    /// Upward acceleration of the smoke per unit of temperature.
+    #[reflect(min_value = 0.0)]
+    pub buoyancy: f32,
+    /// Downward acceleration of the smoke per unit of density.
+    #[reflect(min_value = 0.0)]
+    pub weight: f32,
+    /// How fast the smoke dissipates (units per second).
+    #[reflect(min_value = 0.0)]
+    pub density_dissipation: f32,
+    /// How fast the smoke cools down (units per second).
+    #[reflect(min_value = 0.0)]
+    pub temperature_dissipation: f32,
+    /// How fast the motion of the smoke fades out (units per second).
+    #[reflect(min_value = 0.0)]
+    pub velocity_dissipation: f32,
+    /// Amount of iterations of the pressure solver. More iterations give more swirly motion, but
+    /// cost more.
+    #[reflect(min_value = 1.0, max_value = 100.0, step = 1.0)]
+    pub pressure_iterations: u32,
+    /// Speed of the simulation.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub time_scale: f32,
+}
+
+impl Default for SmokeSimulation {
+    fn default() -> Self {
+        Self {
+            resolution: 32,
+            emitting: true,
+            source_position: Vector3::new(0.0, -0.35, 0.0),
+            source_radius: 0.1,
+            source_density: 4.0,
+            source_temperature: 2.0,
+            source_velocity: Vector3::new(0.0, 0.25, 0.0),
+            buoyancy: 1.0,
+            weight: 0.05,
+            density_dissipation: 0.3,
+            temperature_dissipation: 1.5,
+            velocity_dissipation: 0.2,
+            pressure_iterations: 20,
+            time_scale: 1.0,
+        }
+    }
+}
+
+/// Source of the smoke in coordinates of the simulation grid.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct GridSource {
+    /// Center of the source (in cells).
+    pub position: Vector3<f32>,
+    /// Radius of the source (in cells). Zero, if the source is not emitting.
+    pub radius: f32,
+    /// Density per second.
+    pub density: f32,
+    /// Temperature per second.
+    pub temperature: f32,
+    /// Velocity (in cells per second).
+    pub velocity: Vector3<f32>,
+}
+
+impl GridSource {
+    /// Returns influence of the source at the given point. It fades out linearly from the center of
+    /// the source to its border.
+    pub fn weight(&self, position: Vector3<f32>) -> f32 {
+        if self.radius <= 0.0 {
+            0.0
+        } else {
+            (1.0 - (position - self.position).norm() / self.radius).clamp(0.0, 1.0)
+        }
+    }
+}
+
+impl SmokeSimulation {
+    /// Returns amount of cells along each axis.
+    pub fn grid_size(&self) -> usize {
+        self.resolution.clamp(8, 128) as usize
+    }
+
+    /// Converts source parameters to the coordinates of the grid of the given size.
+    pub fn grid_source(&self, size: usize) -> GridSource {
+        if !self.emitting {
+            return Default::default();
+        }
+
+        let n = size as f32;
+        GridSource {
+            position: self.source_position.add_scalar(0.5).scale(n),
+            // At least a single cell, otherwise the source will miss cell centers.
+            radius: (self.source_radius * n).max(1.0),
+            density: self.source_density,
+            temperature: self.source_temperature,
+            velocity: self.source_velocity.scale(n),
+        }
+    }
+}
+
+/// Smoke is a localized volumetric effect, like smoke of a torch, a campfire, or a fireball of an
+/// explosion. Motion of the smoke is simulated on a low-resolution 3D grid using a simple fluid solver
+/// and the result is rendered using ray marching in the forward pass.
+///
+/// # Size and transformations
+///
+/// The volume of the smoke is a cube with `[-0.5; 0.5]` bounds in local coordinates, its actual size is
+/// defined by the local scale of the node (the same as for [`crate::scene::decal::Decal`]). The smoke
+/// cannot leave the volume, so make it large enough.
+///
+/// # Simulation
+///
+/// By default, the simulation is performed on GPU (using a ping-pong of render targets), if the current
+/// graphics API does not support floating-point render targets, the simulation falls back to CPU. The
+/// simulation is performed by the renderer only for visible scenes. See [`SmokeSimulation`] docs for
+/// the list of parameters.
+///
+/// # Rendering
+///
+/// Density of the smoke defines its opacity, temperature adds emission, which makes hot smoke look like
+/// fire. The smoke is not lit by the light sources of the scene, use smoke color to match the lighting.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::Vector3, pool::Handle},
+/// #     scene::{
+/// #         base::BaseBuilder, graph::Graph, node::Node, smoke::SmokeBuilder,
+/// #         transform::TransformBuilder,
+/// #     },
+/// # };
+/// fn create_torch_smoke(graph: &mut Graph) -> Handle<Node> {
+///     SmokeBuilder::new(
+///         BaseBuilder::new().with_local_transform(
+///             TransformBuilder::new()
+///                 .with_local_scale(Vector3::new(0.5, 1.5, 0.5))
+///                 .build(),
+///         ),
+///     )
+///     .with_absorption(4.0)
+///     .build(graph)
+/// }
+/// ```
+#[derive(Debug, Visit, Clone, Reflect)]
+pub struct Smoke {
+    base: Base,
+
+    #[reflect(setter = "set_simulation")]
+    simulation: InheritableVariable<SmokeSimulation>,
+
+    #[reflect(setter = "set_simulation_mode")]
+    simulation_mode: InheritableVariable<SmokeSimulationMode>,
+
+    #[reflect(setter = "set_smoke_color")]
+    smoke_color: InheritableVariable<Color>,
+
+    #[reflect(setter = "set_fire_color")]
+    fire_color: InheritableVariable<Color>,
+
+    #[reflect(min_value = 0.0)]
+    #[reflect(setter = "set_fire_intensity")]
+    fire_intensity: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0)]
+    #[reflect(setter = "set_absorption")]
+    absorption: InheritableVariable<f32>,
+
+    #[reflect(min_value = 1.0, max_value = 256.0, step = 1.0)]
+    #[reflect(setter = "set_step_count")]
+    step_count: InheritableVariable<u32>,
+}
+
+impl Deref for Smoke {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Smoke {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl Default for Smoke {
+    fn default() -> Self {
+        SmokeBuilder::new(BaseBuilder::new()).build_smoke()
+    }
+}
+
+impl TypeUuidProvider for Smoke {
+    fn type_uuid() -> Uuid {
+        uuid!("e1d5b8a2-3f64-4c9e-8b07-5a2f9c6d4e13")
+    }
+}
+
+impl Smoke {
+    /// Sets new simulation parameters. Changing the resolution resets the simulation.
+    pub fn set_simulation(&mut self, simulation: SmokeSimulation) -> SmokeSimulation {
+        self.simulation.set_value_and_mark_modified(simulation)
+    }
+
+    /// Returns current simulation parameters.
+    pub fn simulation(&self) -> &SmokeSimulation {
+        &self.simulation
+    }
+
+    /// Starts or stops emission of the smoke. Already emitted smoke will dissipate over time.
+    pub fn set_emitting(&mut self, emitting: bool) -> bool {
+        std::mem::replace(
+            &mut self.simulation.get_value_mut_and_mark_modified().emitting,
+            emitting,
+        )
+    }
+
+    /// Returns `true` if the source emits smoke, `false` - otherwise.
+    pub fn is_emitting(&self) -> bool {
+        self.simulation.emitting
+    }
+
+    /// Sets new simulation mode. Changing the mode resets the simulation.
+    pub fn set_simulation_mode(&mut self, mode: SmokeSimulationMode) -> SmokeSimulationMode {
+        self.simulation_mode.set_value_and_mark_modified(mode)
+    }
+
+    /// Returns current simulation mode.
+    pub fn simulation_mode(&self) -> SmokeSimulationMode {
+        *self.simulation_mode
+    }
+
+    /// Sets new color of the smoke. Alpha channel is ignored, use [`Self::set_absorption`] to
+    /// change opacity.
+    pub fn set_smoke_color(&mut self, color: Color) -> Color {
+        self.smoke_color.set_value_and_mark_modified(color)
+    }
+
+    /// Returns current color of the smoke.
+    pub fn smoke_color(&self) -> Color {
+        *self.smoke_color
+    }
+
+    /// Sets new color of the fire (the glow of hot smoke).
+    pub fn set_fire_color(&mut self, color: Color) -> Color {
+        self.fire_color.set_value_and_mark_modified(color)
+    }
+
+    /// Returns current color of the fire.
+    pub fn fire_color(&self) -> Color {
+        *self.fire_color
+    }
+
+    /// Sets new intensity of the fire emission. Values larger than one will make the fire glow
+    /// (if bloom is enabled).
+    pub fn set_fire_intensity(&mut self, intensity: f32) -> f32 {
+        self.fire_intensity.set_value_and_mark_modified(intensity)
+    }
+
+    /// Returns current intensity of the fire emission.
+    pub fn fire_intensity(&self) -> f32 {
+        *self.fire_intensity
+    }
+
+    /// Sets how much light is absorbed by a unit of density of the smoke per size of the volume.
+    /// Larger values make the smoke more opaque.
+    pub fn set_absorption(&mut self, absorption: f32) -> f32 {
+        self.absorption.set_value_and_mark_modified(absorption)
+    }
+
+    /// Returns current absorption of the smoke.
+    pub fn absorption(&self) -> f32 {
+        *self.absorption
+    }
+
+    /// Sets max amount of ray marching steps per pixel. Too low values will produce visible
+    /// banding.
+    pub fn set_step_count(&mut self, count: u32) -> u32 {
+        self.step_count.set_value_and_mark_modified(count)
+    }
+
+    /// Returns max amount of ray marching steps per pixel.
+    pub fn step_count(&self) -> u32 {
+        *self.step_count
+    }
+}
+
+impl NodeTrait for Smoke {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox::unit()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Allows you to create a smoke in a declarative manner.
+pub struct SmokeBuilder {
+    base_builder: BaseBuilder,
+    simulation: SmokeSimulation,
+    simulation_mode: SmokeSimulationMode,
+    smoke_color: Color,
+    fire_color: Color,
+    fire_intensity: f32,
+    absorption: f32,
+    step_count: u32,
+}
+
+impl SmokeBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            simulation: Default::default(),
+            simulation_mode: Default::default(),
+            smoke_color: Color::opaque(70, 70, 70),
+            fire_color: Color::opaque(255, 120, 30),
+            fire_intensity: 4.0,
+            absorption: 8.0,
+            step_count: 48,
+        }
+    }
+
+    /// Sets desired simulation parameters.
+    pub fn with_simulation(mut self, simulation: SmokeSimulation) -> Self {
+        self.simulation = simulation;
+        self
+    }
+
+    /// Sets desired simulation mode.
+    pub fn with_simulation_mode(mut self, mode: SmokeSimulationMode) -> Self {
+        self.simulation_mode = mode;
+        self
+    }
+
+    /// Sets desired color of the smoke.
+    pub fn with_smoke_color(mut self, color: Color) -> Self {
+        self.smoke_color = color;
+        self
+    }
+
+    /// Sets desired color of the fire.
+    pub fn with_fire_color(mut self, color: Color) -> Self {
+        self.fire_color = color;
+        self
+    }
+
+    /// Sets desired intensity of the fire emission.
+    pub fn with_fire_intensity(mut self, intensity: f32) -> Self {
+        self.fire_intensity = intensity;
+        self
+    }
+
+    /// Sets desired absorption of the smoke.
+    pub fn with_absorption(mut self, absorption: f32) -> Self {
+        self.absorption = absorption;
+        self
+    }
+
+    /// Sets desired max amount of ray marching steps.
+    pub fn with_step_count(mut self, count: u32) -> Self {
+        self.step_count = count;
+        self
+    }
+
+    /// Creates new smoke.
+    pub fn build_smoke(self) -> Smoke {
+        Smoke {
+            base: self.base_builder.build_base(),
+            simulation: self.simulation.into(),
+            simulation_mode: self.simulation_mode.into(),
+            smoke_color: self.smoke_color.into(),
+            fire_color: self.fire_color.into(),
+            fire_intensity: self.fire_intensity.into(),
+            absorption: self.absorption.into(),
+            step_count: self.step_count.into(),
+        }
+    }
+
+    /// Creates new smoke node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_smoke())
+    }
+
+    /// Creates new instance of smoke node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
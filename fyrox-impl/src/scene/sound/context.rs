@@ -6,7 +6,10 @@ use crate::{
         pool::Handle,
         visitor::prelude::*,
     },
-    scene::{node::Node, sound::Sound},
+    scene::{
+        node::Node,
+        sound::{occlusion::OcclusionCache, Sound},
+    },
 };
 use fxhash::FxHashSet;
 use fyrox_sound::{
@@ -22,6 +25,8 @@ use std::{sync::MutexGuard, time::Duration};
 pub struct SoundContext {
     #[visit(optional)]
     pub(crate) native: fyrox_sound::context::SoundContext,
+    #[visit(skip)]
+    pub(crate) occlusion_cache: OcclusionCache,
 }
 
 /// Proxy for guarded access to the sound context.
@@ -60,6 +65,17 @@ impl<'a> SoundContextGuard<'a> {
         self.guard.distance_model()
     }
 
+    /// Sets new speed of sound (in units per second), that is used to calculate the Doppler effect.
+    /// Zero disables the Doppler effect.
+    pub fn set_speed_of_sound(&mut self, speed_of_sound: f32) {
+        self.guard.set_speed_of_sound(speed_of_sound);
+    }
+
+    /// Returns current speed of sound.
+    pub fn speed_of_sound(&self) -> f32 {
+        self.guard.speed_of_sound()
+    }
+
     /// Normalizes given frequency using context's sampling rate. Normalized frequency then can be used
     /// to create filters.
     pub fn normalize_frequency(&self, f: f32) -> f32 {
@@ -104,7 +120,10 @@ impl Default for SoundContext {
         // There's no need to serialize native sources, because they'll be re-created automatically.
         state.serialization_options.skip_sources = true;
         drop(state);
-        Self { native }
+        Self {
+            native,
+            occlusion_cache: Default::default(),
+        }
     }
 }
 
@@ -117,9 +136,22 @@ impl SoundContext {
     pub fn deep_clone(&self) -> Self {
         Self {
             native: self.native.deep_clone(),
+            occlusion_cache: self.occlusion_cache.clone(),
         }
     }
 
+    /// Returns a reference to the cache of the visibility between sound sources and the listener.
+    /// See [`OcclusionCache`] docs for more info.
+    pub fn occlusion_cache(&self) -> &OcclusionCache {
+        &self.occlusion_cache
+    }
+
+    /// Returns a reference to the cache of the visibility between sound sources and the listener.
+    /// See [`OcclusionCache`] docs for more info.
+    pub fn occlusion_cache_mut(&mut self) -> &mut OcclusionCache {
+        &mut self.occlusion_cache
+    }
+
     /// Returns locked inner state of the sound context.
     pub fn state(&self) -> SoundContextGuard {
         SoundContextGuard {
@@ -127,6 +159,15 @@ impl SoundContext {
         }
     }
 
+    /// Returns root mean square amplitude of the samples of the given sound, that were played during
+    /// the given time window. See [`SoundSource::amplitude`] for more info.
+    pub fn sound_amplitude(&self, sound: &Sound, window: Duration) -> f32 {
+        self.native
+            .state()
+            .try_get_source(sound.native.get())
+            .map_or(0.0, |source| source.amplitude(window))
+    }
+
     pub(crate) fn remove_sound(&mut self, sound: Handle<SoundSource>, name: &str) {
         let mut state = self.native.state();
         if state.is_valid_handle(sound) {
@@ -142,11 +183,19 @@ impl SoundContext {
     pub(crate) fn set_sound_position(&mut self, sound: &Sound) {
         if let Some(source) = self.native.state().try_get_source_mut(sound.native.get()) {
             source.set_position(sound.global_position());
+            source.set_direction(sound.look_vector());
         }
     }
 
-    pub(crate) fn sync_with_sound(&self, sound: &mut Sound) {
+    pub(crate) fn sync_with_sound(&self, sound: &mut Sound, dt: f32) {
         if let Some(source) = self.native.state().try_get_source_mut(sound.native.get()) {
+            let position = sound.global_position();
+            if let Some(prev_position) = sound.prev_position.replace(Some(position)) {
+                if dt > 0.0 {
+                    source.set_velocity((position - prev_position) / dt);
+                }
+            }
+
             // Sync back.
             sound.status.set_value_silent(source.status());
             sound
@@ -216,6 +265,21 @@ impl SoundContext {
             sound.audio_bus.try_sync_model(|audio_bus| {
                 source.set_bus(audio_bus);
             });
+            sound.sends.try_sync_model(|sends| {
+                source.set_sends(sends);
+            });
+            sound.distance_model.try_sync_model(|distance_model| {
+                source.set_distance_model(distance_model);
+            });
+            sound.doppler_factor.try_sync_model(|doppler_factor| {
+                source.set_doppler_factor(doppler_factor);
+            });
+            sound.cone.try_sync_model(|cone| {
+                source.set_cone(cone);
+            });
+            sound.spread.try_sync_model(|spread| {
+                source.set_spread(spread);
+            });
         } else {
             match SoundSourceBuilder::new()
                 .with_gain(sound.gain())
@@ -229,7 +293,13 @@ impl SoundContext {
                 .with_radius(sound.radius())
                 .with_max_distance(sound.max_distance())
                 .with_bus(sound.audio_bus())
+                .with_sends(sound.sends().to_vec())
                 .with_rolloff_factor(sound.rolloff_factor())
+                .with_distance_model(sound.distance_model())
+                .with_doppler_factor(sound.doppler_factor())
+                .with_direction(sound.look_vector())
+                .with_cone(sound.cone())
+                .with_spread(sound.spread())
                 .build()
             {
                 Ok(source) => {
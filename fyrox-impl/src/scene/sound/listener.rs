@@ -5,6 +5,7 @@
 
 use crate::{
     core::{
+        algebra::Vector3,
         math::aabb::AxisAlignedBoundingBox,
         pool::Handle,
         reflect::prelude::*,
@@ -15,11 +16,14 @@ use crate::{
     scene::{
         base::{Base, BaseBuilder},
         graph::Graph,
-        node::{Node, NodeTrait, SyncContext},
+        node::{Node, NodeTrait, SyncContext, UpdateContext},
     },
 };
 use fyrox_graph::BaseSceneGraph;
-use std::ops::{Deref, DerefMut};
+use std::{
+    cell::Cell,
+    ops::{Deref, DerefMut},
+};
 
 /// Listener represents directional microphone-like device. It receives sound from surroundings
 /// and plays it through output device (headphones, speakers, etc.). Orientation of the listener
@@ -35,9 +39,16 @@ use std::ops::{Deref, DerefMut};
 ///
 /// 2D sound sources (with spatial blend == 0.0) are not influenced by listener's position and
 /// orientation.
+///
+/// Velocity of the listener (that is used to calculate the Doppler effect) is calculated
+/// automatically from the movement of the node.
 #[derive(Visit, Reflect, Default, Clone, Debug)]
 pub struct Listener {
     base: Base,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    prev_position: Cell<Option<Vector3<f32>>>,
 }
 
 impl Deref for Listener {
@@ -90,6 +101,22 @@ impl NodeTrait for Listener {
         native.set_position(self.global_position());
         native.set_orientation_lh(self.look_vector(), self.up_vector());
     }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        if !self.is_globally_enabled() {
+            return;
+        }
+
+        let position = self.global_position();
+        if let Some(prev_position) = self.prev_position.replace(Some(position)) {
+            if context.dt > 0.0 {
+                let mut state = context.sound_context.native.state();
+                state
+                    .listener_mut()
+                    .set_velocity((position - prev_position) / context.dt);
+            }
+        }
+    }
 }
 
 /// Allows you to create listener in declarative manner.
@@ -107,6 +134,7 @@ impl ListenerBuilder {
     pub fn build_listener(self) -> Listener {
         Listener {
             base: self.base_builder.build_base(),
+            prev_position: Default::default(),
         }
     }
 
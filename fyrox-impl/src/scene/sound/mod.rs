@@ -2,7 +2,7 @@
 
 use crate::{
     core::{
-        algebra::Matrix4,
+        algebra::{Matrix4, Vector3},
         math::{aabb::AxisAlignedBoundingBox, m4x4_approx_eq},
         pool::Handle,
         reflect::prelude::*,
@@ -33,10 +33,10 @@ pub use fyrox_sound::{
     error::SoundError,
     hrtf::HrirSphere,
     renderer::{hrtf::*, Renderer},
-    source::Status,
+    source::{SoundCone, Status},
 };
 
-use crate::scene::Scene;
+use crate::scene::{sound::occlusion::SoundOcclusion, Scene};
 use fyrox_graph::BaseSceneGraph;
 use fyrox_resource::state::ResourceState;
 use fyrox_sound::source::SoundSource;
@@ -48,6 +48,8 @@ use std::{
 
 pub mod context;
 pub mod listener;
+pub mod occlusion;
+pub mod reverb_zone;
 
 /// Sound source.
 #[derive(Visit, Reflect, Debug)]
@@ -103,6 +105,56 @@ pub struct Sound {
     )]
     audio_bus: InheritableVariable<String>,
 
+    #[visit(optional)]
+    #[reflect(
+        setter = "set_sends",
+        description = "Additional audio buses to which the sound will send its samples."
+    )]
+    sends: InheritableVariable<Vec<AudioSend>>,
+
+    #[visit(optional)]
+    #[reflect(
+        setter = "set_reverb_zone_sends",
+        description = "Whether the sound sends its samples to the reverb zones around it or not."
+    )]
+    reverb_zone_sends: InheritableVariable<bool>,
+
+    #[visit(optional)]
+    #[reflect(
+        setter = "set_occlusion",
+        description = "Defines how the sound is muffled by obstacles between it and the listener."
+    )]
+    occlusion: InheritableVariable<SoundOcclusion>,
+
+    #[visit(optional)]
+    #[reflect(
+        setter = "set_distance_model",
+        description = "Distance model of the sound. If not set, the distance model of the sound context is used."
+    )]
+    distance_model: InheritableVariable<Option<DistanceModel>>,
+
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, step = 0.05)]
+    #[reflect(setter = "set_doppler_factor")]
+    doppler_factor: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_cone")]
+    cone: InheritableVariable<SoundCone>,
+
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    #[reflect(setter = "set_spread")]
+    spread: InheritableVariable<f32>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    prev_position: Cell<Option<Vector3<f32>>>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) occlusion_factor: Cell<f32>,
+
     #[reflect(hidden)]
     #[visit(skip)]
     pub(crate) native: Cell<Handle<SoundSource>>,
@@ -139,6 +191,15 @@ impl Default for Sound {
             playback_time: Default::default(),
             spatial_blend: InheritableVariable::new_modified(1.0),
             audio_bus: InheritableVariable::new_modified(AudioBusGraph::PRIMARY_BUS.to_string()),
+            sends: InheritableVariable::new_modified(Default::default()),
+            reverb_zone_sends: InheritableVariable::new_modified(true),
+            occlusion: InheritableVariable::new_modified(Default::default()),
+            distance_model: InheritableVariable::new_modified(None),
+            doppler_factor: InheritableVariable::new_modified(1.0),
+            cone: InheritableVariable::new_modified(Default::default()),
+            spread: InheritableVariable::new_modified(0.0),
+            prev_position: Default::default(),
+            occlusion_factor: Default::default(),
             native: Default::default(),
         }
     }
@@ -161,6 +222,15 @@ impl Clone for Sound {
             playback_time: self.playback_time.clone(),
             spatial_blend: self.spatial_blend.clone(),
             audio_bus: self.audio_bus.clone(),
+            sends: self.sends.clone(),
+            reverb_zone_sends: self.reverb_zone_sends.clone(),
+            occlusion: self.occlusion.clone(),
+            distance_model: self.distance_model.clone(),
+            doppler_factor: self.doppler_factor.clone(),
+            cone: self.cone.clone(),
+            spread: self.spread.clone(),
+            prev_position: Default::default(),
+            occlusion_factor: Default::default(),
             // Do not copy. The copy will have its own native representation.
             native: Default::default(),
         }
@@ -363,6 +433,99 @@ impl Sound {
     pub fn audio_bus(&self) -> &str {
         &self.audio_bus
     }
+
+    /// Sets new sends of the sound. Each send adds a copy of the samples of the sound to some other
+    /// audio bus, see [`AudioSend`] docs for more info.
+    pub fn set_sends(&mut self, sends: Vec<AudioSend>) -> Vec<AudioSend> {
+        self.sends.set_value_and_mark_modified(sends)
+    }
+
+    /// Returns the sends of the sound.
+    pub fn sends(&self) -> &[AudioSend] {
+        &self.sends
+    }
+
+    /// Enables or disables automatic sends to the audio buses of the reverb zones around the sound.
+    /// It works only with the zones in [`reverb_zone::ReverbZoneMode::Sources`] mode, see
+    /// [`reverb_zone::ReverbZone`] docs for more info. Manual sends (see [`Self::set_sends`]) have
+    /// priority over the automatic ones.
+    pub fn set_reverb_zone_sends(&mut self, enabled: bool) -> bool {
+        self.reverb_zone_sends.set_value_and_mark_modified(enabled)
+    }
+
+    /// Returns `true` if the sound sends its samples to the reverb zones around it.
+    pub fn is_reverb_zone_sends_enabled(&self) -> bool {
+        *self.reverb_zone_sends
+    }
+
+    /// Sets new occlusion settings of the sound. See [`SoundOcclusion`] docs for more info.
+    pub fn set_occlusion(&mut self, occlusion: SoundOcclusion) -> SoundOcclusion {
+        self.occlusion.set_value_and_mark_modified(occlusion)
+    }
+
+    /// Returns current occlusion settings of the sound.
+    pub fn occlusion(&self) -> &SoundOcclusion {
+        &self.occlusion
+    }
+
+    /// Sets distance model of the sound, that overrides the distance model of the sound context.
+    /// `None` means that the distance model of the context will be used. See [`DistanceModel`] docs
+    /// for more info.
+    pub fn set_distance_model(
+        &mut self,
+        distance_model: Option<DistanceModel>,
+    ) -> Option<DistanceModel> {
+        self.distance_model
+            .set_value_and_mark_modified(distance_model)
+    }
+
+    /// Returns distance model of the sound (if any).
+    pub fn distance_model(&self) -> Option<DistanceModel> {
+        *self.distance_model
+    }
+
+    /// Sets Doppler factor of the sound. It exaggerates (values larger than 1.0) or reduces (values
+    /// less than 1.0) the Doppler effect. Zero disables the Doppler effect. Velocity of the sound,
+    /// that is needed for the Doppler effect, is calculated automatically from the movement of the
+    /// node.
+    pub fn set_doppler_factor(&mut self, doppler_factor: f32) -> f32 {
+        self.doppler_factor
+            .set_value_and_mark_modified(doppler_factor.max(0.0))
+    }
+
+    /// Returns Doppler factor of the sound.
+    pub fn doppler_factor(&self) -> f32 {
+        *self.doppler_factor
+    }
+
+    /// Sets directivity cone of the sound. The axis of the cone is the look vector of the node. See
+    /// [`SoundCone`] docs for more info.
+    pub fn set_cone(&mut self, cone: SoundCone) -> SoundCone {
+        self.cone.set_value_and_mark_modified(cone)
+    }
+
+    /// Returns directivity cone of the sound.
+    pub fn cone(&self) -> SoundCone {
+        *self.cone
+    }
+
+    /// Sets spread of the sound in `[0; 1]` range. 0.0 - the sound is a point, 1.0 - the sound comes
+    /// from every direction. It is useful for large sources like rivers, crowds, etc.
+    pub fn set_spread(&mut self, spread: f32) -> f32 {
+        self.spread
+            .set_value_and_mark_modified(spread.clamp(0.0, 1.0))
+    }
+
+    /// Returns spread of the sound.
+    pub fn spread(&self) -> f32 {
+        *self.spread
+    }
+
+    /// Returns current occlusion factor of the sound in `[0; 1]` range, where `0.0` means that there
+    /// are no obstacles between the sound and the listener and `1.0` - the sound is fully occluded.
+    pub fn occlusion_factor(&self) -> f32 {
+        self.occlusion_factor.get()
+    }
 }
 
 impl NodeTrait for Sound {
@@ -411,7 +574,7 @@ impl NodeTrait for Sound {
     }
 
     fn update(&mut self, context: &mut UpdateContext) {
-        context.sound_context.sync_with_sound(self);
+        context.sound_context.sync_with_sound(self, context.dt);
     }
 
     fn validate(&self, _scene: &Scene) -> Result<(), String> {
@@ -452,6 +615,13 @@ pub struct SoundBuilder {
     playback_time: Duration,
     spatial_blend: f32,
     audio_bus: String,
+    sends: Vec<AudioSend>,
+    reverb_zone_sends: bool,
+    occlusion: SoundOcclusion,
+    distance_model: Option<DistanceModel>,
+    doppler_factor: f32,
+    cone: SoundCone,
+    spread: f32,
 }
 
 impl SoundBuilder {
@@ -472,6 +642,13 @@ impl SoundBuilder {
             spatial_blend: 1.0,
             playback_time: Default::default(),
             audio_bus: AudioBusGraph::PRIMARY_BUS.to_string(),
+            sends: Default::default(),
+            reverb_zone_sends: true,
+            occlusion: Default::default(),
+            distance_model: None,
+            doppler_factor: 1.0,
+            cone: Default::default(),
+            spread: 0.0,
         }
     }
 
@@ -540,6 +717,41 @@ impl SoundBuilder {
         fn with_audio_bus(audio_bus: String)
     );
 
+    define_with!(
+        /// Sets desired sends. See [`Sound::set_sends`] for more info.
+        fn with_sends(sends: Vec<AudioSend>)
+    );
+
+    define_with!(
+        /// Enables or disables reverb zone sends. See [`Sound::set_reverb_zone_sends`] for more info.
+        fn with_reverb_zone_sends(reverb_zone_sends: bool)
+    );
+
+    define_with!(
+        /// Sets desired occlusion settings. See [`Sound::set_occlusion`] for more info.
+        fn with_occlusion(occlusion: SoundOcclusion)
+    );
+
+    define_with!(
+        /// Sets desired distance model. See [`Sound::set_distance_model`] for more info.
+        fn with_distance_model(distance_model: Option<DistanceModel>)
+    );
+
+    define_with!(
+        /// Sets desired Doppler factor. See [`Sound::set_doppler_factor`] for more info.
+        fn with_doppler_factor(doppler_factor: f32)
+    );
+
+    define_with!(
+        /// Sets desired directivity cone. See [`Sound::set_cone`] for more info.
+        fn with_cone(cone: SoundCone)
+    );
+
+    define_with!(
+        /// Sets desired spread. See [`Sound::set_spread`] for more info.
+        fn with_spread(spread: f32)
+    );
+
     /// Creates a new [`Sound`] node.
     #[must_use]
     pub fn build_sound(self) -> Sound {
@@ -558,6 +770,15 @@ impl SoundBuilder {
             playback_time: self.playback_time.as_secs_f32().into(),
             spatial_blend: self.spatial_blend.into(),
             audio_bus: self.audio_bus.into(),
+            sends: self.sends.into(),
+            reverb_zone_sends: self.reverb_zone_sends.into(),
+            occlusion: self.occlusion.into(),
+            distance_model: self.distance_model.into(),
+            doppler_factor: self.doppler_factor.into(),
+            cone: self.cone.into(),
+            spread: self.spread.into(),
+            prev_position: Default::default(),
+            occlusion_factor: Default::default(),
             native: Default::default(),
         }
     }
@@ -0,0 +1,410 @@
+//! Sound occlusion - sounds behind obstacles are muffled and attenuated. See [`SoundOcclusion`] docs
+//! for more info.
+
+use crate::{
+    core::{
+        algebra::{Point3, Vector3},
+        math::lerpf,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid_provider,
+        visitor::prelude::*,
+    },
+    scene::{
+        collider::{Collider, InteractionGroups},
+        graph::{
+            physics::{Intersection, PhysicsWorld, RayCastOptions},
+            NodePool,
+        },
+        node::Node,
+        sound::{context::SoundContext, listener::Listener, Sound, Status, SAMPLE_RATE},
+    },
+};
+use fxhash::FxHashMap;
+
+/// Occlusion settings of a sound source. When enabled, the engine casts a ray from the sound
+/// source to the listener through the physics world every frame. Every collider (except sensors and
+/// colliders of rigid bodies, that the source or the listener are attached to), that blocks the ray,
+/// increases the occlusion of the source by [`Self::obstacle_occlusion`]. Occluded sources are
+/// attenuated and muffled using a low-pass filter:
+///
+/// - Fully occluded source has [`Self::occluded_gain`] gain and [`Self::occluded_cutoff`] cutoff
+/// frequency of the low-pass filter.
+/// - Partially occluded sources have interpolated parameters.
+///
+/// The occlusion changes smoothly (see [`Self::smoothing_time`]), so sounds do not "pop" when an
+/// obstacle appears between the source and the listener.
+///
+/// When the [`OcclusionCache`] of the sound context is enabled, the results of the ray casts are
+/// reused while the source and the listener stay in the same cells of the cache grid. Sources, that
+/// must react to moving obstacles immediately, could opt out of the cache using
+/// [`Self::use_cache`].
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct SoundOcclusion {
+    /// Whether the occlusion is enabled or not.
+    pub enabled: bool,
+    /// Gain of fully occluded source.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub occluded_gain: f32,
+    /// Cutoff frequency (in Hz) of the low-pass filter of fully occluded source.
+    #[reflect(min_value = 20.0, max_value = 20000.0, step = 10.0)]
+    pub occluded_cutoff: f32,
+    /// Occlusion, that is added by every obstacle between the source and the listener. `1.0` means
+    /// that a single obstacle occludes the source fully.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub obstacle_occlusion: f32,
+    /// Time (in seconds), that is needed for the occlusion to reach ~63% of its new value.
+    #[reflect(min_value = 0.0, step = 0.01)]
+    pub smoothing_time: f32,
+    /// Collision groups of the obstacles.
+    pub collision_groups: InteractionGroups,
+    /// Whether the source could use cached visibility results of the [`OcclusionCache`] or not.
+    #[visit(optional)]
+    pub use_cache: bool,
+}
+
+uuid_provider!(SoundOcclusion = "50a36fa9-2ac4-4ef3-8f49-50d6d1f2cc6d");
+
+impl Default for SoundOcclusion {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            occluded_gain: 0.4,
+            occluded_cutoff: 1000.0,
+            obstacle_occlusion: 0.6,
+            smoothing_time: 0.15,
+            collision_groups: Default::default(),
+            use_cache: true,
+        }
+    }
+}
+
+impl SoundOcclusion {
+    /// Frequency, that is used when there's no occlusion.
+    pub const MAX_CUTOFF: f32 = 20000.0;
+
+    /// Calculates gain and normalized cutoff frequency for the given occlusion in `[0; 1]` range.
+    pub fn params(&self, occlusion: f32) -> (f32, f32) {
+        let gain = lerpf(1.0, self.occluded_gain, occlusion);
+        // Interpolate in logarithmic space, because hearing is logarithmic.
+        let cutoff = lerpf(
+            Self::MAX_CUTOFF.ln(),
+            self.occluded_cutoff.clamp(1.0, Self::MAX_CUTOFF).ln(),
+            occlusion,
+        )
+        .exp();
+        let fc = if occlusion > 0.0 {
+            cutoff / SAMPLE_RATE as f32
+        } else {
+            1.0
+        };
+        (gain, fc)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CachedVisibility {
+    source_cell: Vector3<i32>,
+    listener_cell: Vector3<i32>,
+    obstacles: usize,
+    age: u32,
+}
+
+/// Grid-based cache of the visibility between the sound sources and the listener. Positions of a
+/// source and the listener are snapped to the cells of a uniform grid and the amount of obstacles
+/// between them is stored. While both stay in the same cells, the cached amount is used and no
+/// ray casts are done. Every entry expires after [`Self::max_age`] frames, so moving obstacles
+/// are still taken into account, with a small delay.
+///
+/// The cache is disabled by default. It could be enabled using
+/// [`SoundContext::occlusion_cache_mut`] and is useful for scenes with lots of occluded sources.
+#[derive(Debug, Clone)]
+pub struct OcclusionCache {
+    enabled: bool,
+    cell_size: f32,
+    max_age: u32,
+    entries: FxHashMap<Handle<Node>, CachedVisibility>,
+}
+
+impl Default for OcclusionCache {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cell_size: 1.0,
+            max_age: 15,
+            entries: Default::default(),
+        }
+    }
+}
+
+impl OcclusionCache {
+    /// Enables or disables the cache. Disabling the cache discards all cached results.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.clear();
+        }
+    }
+
+    /// Returns `true` if the cache is enabled, `false` - otherwise.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Sets new size (in meters) of the cells of the cache grid. Larger cells mean less ray casts,
+    /// but occlusion changes less precisely when a source or the listener move.
+    pub fn set_cell_size(&mut self, cell_size: f32) {
+        self.cell_size = cell_size.max(0.01);
+        self.clear();
+    }
+
+    /// Returns current size of the cells of the cache grid.
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Sets amount of frames, after which a cached result is discarded and the visibility is
+    /// checked again.
+    pub fn set_max_age(&mut self, max_age: u32) {
+        self.max_age = max_age;
+    }
+
+    /// Returns amount of frames, after which a cached result is discarded.
+    pub fn max_age(&self) -> u32 {
+        self.max_age
+    }
+
+    /// Returns amount of cached results.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there's no cached results, `false` - otherwise.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Discards all cached results.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn cell(&self, position: Vector3<f32>) -> Vector3<i32> {
+        position.map(|c| (c / self.cell_size).floor() as i32)
+    }
+
+    fn begin_frame(&mut self) {
+        let max_age = self.max_age;
+        self.entries.retain(|_, entry| {
+            entry.age += 1;
+            entry.age <= max_age
+        });
+    }
+
+    fn get(
+        &self,
+        sound: Handle<Node>,
+        source: Vector3<f32>,
+        listener: Vector3<f32>,
+    ) -> Option<usize> {
+        self.entries.get(&sound).and_then(|entry| {
+            (entry.source_cell == self.cell(source) && entry.listener_cell == self.cell(listener))
+                .then_some(entry.obstacles)
+        })
+    }
+
+    fn put(
+        &mut self,
+        sound: Handle<Node>,
+        source: Vector3<f32>,
+        listener: Vector3<f32>,
+        obstacles: usize,
+    ) {
+        let entry = CachedVisibility {
+            source_cell: self.cell(source),
+            listener_cell: self.cell(listener),
+            obstacles,
+            age: 0,
+        };
+        self.entries.insert(sound, entry);
+    }
+}
+
+fn is_ancestor(nodes: &NodePool, ancestor: Handle<Node>, mut node: Handle<Node>) -> bool {
+    while let Some(node_ref) = nodes.try_borrow(node) {
+        if node == ancestor {
+            return true;
+        }
+        node = node_ref.parent();
+    }
+    false
+}
+
+fn is_obstacle(
+    nodes: &NodePool,
+    intersection: &Intersection,
+    sound: Handle<Node>,
+    listener: Handle<Node>,
+) -> bool {
+    let Some(collider) = nodes.try_borrow(intersection.collider) else {
+        return false;
+    };
+    if collider.cast::<Collider>().map_or(true, |c| c.is_sensor()) {
+        return false;
+    }
+    // Ignore the bodies, that the source or the listener are attached to (a car with an engine
+    // sound, a player capsule with a camera, etc.).
+    let body = collider.parent();
+    !is_ancestor(nodes, body, sound) && !is_ancestor(nodes, body, listener)
+}
+
+/// Calculates occlusion of every sound source with enabled occlusion and passes the parameters to
+/// the respective native sound sources.
+pub(crate) fn update_occlusion(
+    nodes: &NodePool,
+    physics: &PhysicsWorld,
+    sound_context: &mut SoundContext,
+    dt: f32,
+) {
+    let mut listener = Handle::NONE;
+    let mut any = false;
+    for (handle, node) in nodes.pair_iter() {
+        if node.is_globally_enabled() {
+            if node.cast::<Listener>().is_some() {
+                listener = handle;
+            } else if let Some(sound) = node.cast::<Sound>() {
+                any |= sound.occlusion().enabled || sound.occlusion_factor.get() > 0.0;
+            }
+        }
+    }
+    if !any {
+        return;
+    }
+
+    let cache = &mut sound_context.occlusion_cache;
+    if cache.enabled {
+        cache.begin_frame();
+    }
+    let mut state = sound_context.native.state();
+    let listener_position = state.listener().position();
+    let mut intersections = Vec::new();
+
+    for (handle, node) in nodes.pair_iter() {
+        let Some(sound) = node.cast::<Sound>() else {
+            continue;
+        };
+        let Some(source) = state.try_get_source_mut(sound.native.get()) else {
+            continue;
+        };
+        let settings = sound.occlusion();
+        if !settings.enabled {
+            // Reset the occlusion, if it was disabled.
+            if sound.occlusion_factor.get() > 0.0 {
+                sound.occlusion_factor.set(0.0);
+                source.set_occlusion(1.0, 1.0);
+            }
+            continue;
+        }
+        if !sound.is_globally_enabled() || sound.status() != Status::Playing {
+            continue;
+        }
+
+        let position = sound.global_position();
+        let use_cache = cache.enabled && settings.use_cache;
+        let cached = if use_cache {
+            cache.get(handle, position, listener_position)
+        } else {
+            None
+        };
+        let obstacles = if let Some(obstacles) = cached {
+            obstacles
+        } else {
+            let direction = listener_position - position;
+            physics.cast_ray(
+                RayCastOptions {
+                    ray_origin: Point3::from(position),
+                    ray_direction: direction,
+                    max_len: direction.norm(),
+                    groups: settings.collision_groups,
+                    sort_results: false,
+                },
+                &mut intersections,
+            );
+            let obstacles = intersections
+                .iter()
+                .filter(|i| is_obstacle(nodes, i, handle, listener))
+                .count();
+            if use_cache {
+                cache.put(handle, position, listener_position, obstacles);
+            }
+            obstacles
+        };
+        let target = (obstacles as f32 * settings.obstacle_occlusion).min(1.0);
+
+        let k = if settings.smoothing_time > 0.0 {
+            1.0 - (-dt / settings.smoothing_time).exp()
+        } else {
+            1.0
+        };
+        let occlusion = lerpf(sound.occlusion_factor.get(), target, k);
+        sound.occlusion_factor.set(occlusion);
+
+        let (gain, fc) = settings.params(occlusion);
+        source.set_occlusion(gain, fc);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, pool::Handle},
+        scene::sound::{
+            occlusion::{OcclusionCache, SoundOcclusion},
+            SAMPLE_RATE,
+        },
+    };
+
+    #[test]
+    fn test_occlusion_params() {
+        let occlusion = SoundOcclusion::default();
+
+        assert_eq!(occlusion.params(0.0), (1.0, 1.0));
+
+        let (gain, fc) = occlusion.params(1.0);
+        assert!((gain - occlusion.occluded_gain).abs() < 1.0e-5);
+        assert!((fc * SAMPLE_RATE as f32 - occlusion.occluded_cutoff).abs() < 1.0);
+
+        let (gain, fc) = occlusion.params(0.5);
+        assert!(gain < 1.0 && gain > occlusion.occluded_gain);
+        let cutoff = fc * SAMPLE_RATE as f32;
+        assert!(cutoff < SoundOcclusion::MAX_CUTOFF && cutoff > occlusion.occluded_cutoff);
+    }
+
+    #[test]
+    fn test_occlusion_cache() {
+        let mut cache = OcclusionCache::default();
+        cache.set_enabled(true);
+        cache.set_max_age(2);
+
+        let sound = Handle::new(1, 1);
+        let listener = Vector3::new(10.0, 0.0, 0.0);
+        cache.put(sound, Vector3::new(0.2, 0.0, 0.0), listener, 2);
+
+        // Same cells - cached result is used.
+        assert_eq!(
+            cache.get(sound, Vector3::new(0.7, 0.5, 0.1), listener),
+            Some(2)
+        );
+        // Source moved to another cell.
+        assert_eq!(
+            cache.get(sound, Vector3::new(1.2, 0.0, 0.0), listener),
+            None
+        );
+
+        cache.begin_frame();
+        cache.begin_frame();
+        assert_eq!(cache.len(), 1);
+        cache.begin_frame();
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,660 @@
+//! Reverb zone is a volume in a scene, that defines reverberation of an environment (a room, a cave,
+//! a hall, etc.). See [`ReverbZone`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Point3, Vector3},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::{Graph, NodePool},
+        node::{Node, NodeTrait},
+        sound::{context::SoundContext, reverb::ReverbPreset, AudioSend, Effect, Sound},
+    },
+};
+use fxhash::FxHashMap;
+use fyrox_graph::BaseSceneGraph;
+use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Shape of a reverb zone in local coordinates of the zone.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum ReverbZoneShape {
+    /// Oriented box with the given half extents.
+    Box {
+        /// Half size of the box along each axis.
+        half_extents: Vector3<f32>,
+    },
+    /// Sphere with the given radius.
+    Sphere {
+        /// Radius of the sphere.
+        radius: f32,
+    },
+}
+
+uuid_provider!(ReverbZoneShape = "eb1c093e-cd8d-467d-bb80-ad9339f9c839");
+
+/// Defines what drives the reverb of a zone.
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, Visit, Reflect, AsRefStr, EnumString, VariantNames,
+)]
+pub enum ReverbZoneMode {
+    /// The reverb is heard, when the listener is inside (or near) the zone. Sound sources should send
+    /// their samples to the audio bus of the zone manually (see
+    /// [`crate::scene::sound::Sound::set_sends`]). Suitable for environments, where the listener is.
+    #[default]
+    Listener,
+    /// Sound sources inside (or near) the zone automatically send their samples to the audio bus of
+    /// the zone, with send levels blended by the distance to the boundaries of the zone. The reverb
+    /// is heard regardless of the position of the listener, which is suitable for environments, that
+    /// could be heard from outside (a cave, a tunnel, etc.).
+    Sources,
+}
+
+uuid_provider!(ReverbZoneMode = "cae6d0ff-1d38-4a33-b832-7998db114469");
+
+impl Default for ReverbZoneShape {
+    fn default() -> Self {
+        Self::Box {
+            half_extents: Vector3::repeat(5.0),
+        }
+    }
+}
+
+impl ReverbZoneShape {
+    fn closest_point(&self, point: Vector3<f32>) -> Vector3<f32> {
+        match self {
+            Self::Box { half_extents } => Vector3::new(
+                point.x.clamp(-half_extents.x, half_extents.x),
+                point.y.clamp(-half_extents.y, half_extents.y),
+                point.z.clamp(-half_extents.z, half_extents.z),
+            ),
+            Self::Sphere { radius } => {
+                let length = point.norm();
+                if length > *radius {
+                    point.scale(*radius / length)
+                } else {
+                    point
+                }
+            }
+        }
+    }
+}
+
+/// Reverb zone is a volume (a box or a sphere), that carries a reverb preset (see [`ReverbPreset`]).
+/// The engine picks reverb zones around the listener every frame and drives a [`crate::scene::sound::reverb::Reverb`]
+/// effect of an audio bus with their blended preset:
+///
+/// - When the listener is inside a zone, the preset of the zone is used as is.
+/// - When the listener is outside of the zone, but closer than fade distance (see
+/// [`ReverbZone::set_fade_distance`]), the level of the reverb fades out with the distance. This
+/// makes transitions between a zone and an environment without reverb smooth.
+/// - When the listener is inside (or near) multiple zones, their presets are blended with the
+/// weights of the zones. This crossfades the reverb, when the listener moves from one zone to
+/// another.
+///
+/// Zones could drive different audio buses (see [`ReverbZone::set_audio_bus`]), zones of each bus
+/// are blended separately. The audio bus should contain a reverb effect, usually with zero dry part,
+/// and sound sources should send their samples to it using sends (see
+/// [`crate::scene::sound::Sound::set_sends`]). The gain of the bus is set to the blended level of the
+/// presets, so it becomes silent when there are no zones around the listener.
+///
+/// Alternatively, a zone could work in [`ReverbZoneMode::Sources`] mode. In this mode the positions
+/// of sound sources are used instead of the position of the listener: every sound source with
+/// enabled reverb zone sends (see [`crate::scene::sound::Sound::set_reverb_zone_sends`]) gets an
+/// additional send to the audio bus of the zone with the level equal to the weight of the zone at
+/// the position of the source (see [`ReverbZone::weight`]). When a source is near multiple zones
+/// of different buses, the levels are normalized, so the reverbs crossfade.
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::algebra::Vector3,
+/// #     scene::{
+/// #         base::BaseBuilder,
+/// #         graph::Graph,
+/// #         sound::{
+/// #             reverb::{Reverb, ReverbPreset},
+/// #             reverb_zone::{ReverbZoneBuilder, ReverbZoneShape},
+/// #             AudioBus, AudioSend, Effect, SoundBuilder,
+/// #         },
+/// #     },
+/// # };
+/// fn create_cave(graph: &mut Graph) {
+///     let mut state = graph.sound_context.state();
+///     let mut reverb = Reverb::new();
+///     reverb.set_dry(0.0);
+///     let mut bus = AudioBus::new("Reverb".to_string());
+///     bus.add_effect(Effect::Reverb(reverb));
+///     let primary = state.bus_graph_ref().primary_bus_handle();
+///     state.bus_graph_mut().add_bus(bus, primary);
+///     drop(state);
+///
+///     ReverbZoneBuilder::new(BaseBuilder::new())
+///         .with_shape(ReverbZoneShape::Sphere { radius: 20.0 })
+///         .with_preset(ReverbPreset::cave())
+///         .with_audio_bus("Reverb".to_string())
+///         .build(graph);
+///
+///     SoundBuilder::new(BaseBuilder::new())
+///         .with_sends(vec![AudioSend::new("Reverb", 1.0)])
+///         .build(graph);
+/// }
+/// ```
+#[derive(Visit, Reflect, Clone, Debug)]
+pub struct ReverbZone {
+    base: Base,
+
+    #[reflect(setter = "set_shape")]
+    shape: InheritableVariable<ReverbZoneShape>,
+
+    #[reflect(setter = "set_preset")]
+    preset: InheritableVariable<ReverbPreset>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_fade_distance")]
+    fade_distance: InheritableVariable<f32>,
+
+    #[reflect(
+        setter = "set_audio_bus",
+        description = "A name of an audio bus with a reverb effect, that will be driven by the zone."
+    )]
+    audio_bus: InheritableVariable<String>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_mode")]
+    mode: InheritableVariable<ReverbZoneMode>,
+}
+
+impl Default for ReverbZone {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            shape: Default::default(),
+            preset: Default::default(),
+            fade_distance: 2.0.into(),
+            audio_bus: ReverbZone::DEFAULT_BUS.to_string().into(),
+            mode: Default::default(),
+        }
+    }
+}
+
+impl Deref for ReverbZone {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for ReverbZone {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for ReverbZone {
+    fn type_uuid() -> Uuid {
+        uuid!("b14306ac-7463-4512-a5ab-414853741d23")
+    }
+}
+
+impl ReverbZone {
+    /// Default name of the audio bus, that is driven by reverb zones.
+    pub const DEFAULT_BUS: &'static str = "Reverb";
+
+    /// Sets new shape of the zone.
+    pub fn set_shape(&mut self, shape: ReverbZoneShape) -> ReverbZoneShape {
+        self.shape.set_value_and_mark_modified(shape)
+    }
+
+    /// Returns current shape of the zone.
+    pub fn shape(&self) -> &ReverbZoneShape {
+        &self.shape
+    }
+
+    /// Sets new reverb preset of the zone.
+    pub fn set_preset(&mut self, preset: ReverbPreset) -> ReverbPreset {
+        self.preset.set_value_and_mark_modified(preset)
+    }
+
+    /// Returns current reverb preset of the zone.
+    pub fn preset(&self) -> &ReverbPreset {
+        &self.preset
+    }
+
+    /// Sets a distance from the boundaries of the zone at which the influence of the zone fades out
+    /// completely.
+    pub fn set_fade_distance(&mut self, distance: f32) -> f32 {
+        self.fade_distance
+            .set_value_and_mark_modified(distance.max(0.0))
+    }
+
+    /// Returns current fade distance of the zone.
+    pub fn fade_distance(&self) -> f32 {
+        *self.fade_distance
+    }
+
+    /// Sets a name of the audio bus, that will be driven by the zone.
+    pub fn set_audio_bus(&mut self, name: String) -> String {
+        self.audio_bus.set_value_and_mark_modified(name)
+    }
+
+    /// Returns a name of the audio bus, that is driven by the zone.
+    pub fn audio_bus(&self) -> &str {
+        &self.audio_bus
+    }
+
+    /// Sets new mode of the zone. See [`ReverbZoneMode`] docs for more info.
+    pub fn set_mode(&mut self, mode: ReverbZoneMode) -> ReverbZoneMode {
+        self.mode.set_value_and_mark_modified(mode)
+    }
+
+    /// Returns current mode of the zone.
+    pub fn mode(&self) -> ReverbZoneMode {
+        *self.mode
+    }
+
+    /// Returns a distance from the given point (in world coordinates) to the boundaries of the zone.
+    /// The distance is zero, if the point is inside the zone.
+    pub fn distance(&self, point: Vector3<f32>) -> f32 {
+        let transform = self.global_transform();
+        let Some(inv_transform) = transform.try_inverse() else {
+            return f32::MAX;
+        };
+        let local_point = inv_transform.transform_point(&Point3::from(point)).coords;
+        let closest = transform
+            .transform_point(&Point3::from(self.shape.closest_point(local_point)))
+            .coords;
+        point.metric_distance(&closest)
+    }
+
+    /// Returns influence of the zone at the given point (in world coordinates) in `[0; 1]` range.
+    /// It is `1.0` inside the zone and it fades out linearly to `0.0` at the fade distance.
+    pub fn weight(&self, point: Vector3<f32>) -> f32 {
+        let distance = self.distance(point);
+        if *self.fade_distance > 0.0 {
+            (1.0 - distance / *self.fade_distance).clamp(0.0, 1.0)
+        } else if distance <= 0.0 {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+impl NodeTrait for ReverbZone {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        match *self.shape {
+            ReverbZoneShape::Box { half_extents } => {
+                AxisAlignedBoundingBox::from_min_max(-half_extents, half_extents)
+            }
+            ReverbZoneShape::Sphere { radius } => AxisAlignedBoundingBox::from_radius(radius),
+        }
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+#[derive(Default)]
+struct BusReverb {
+    total_weight: f32,
+    preset: Option<ReverbPreset>,
+}
+
+/// Calculates send levels of a sound source at the given position for every audio bus of the zones
+/// in [`ReverbZoneMode::Sources`] mode. The levels are normalized, when the total level is more than
+/// one.
+fn zone_sends<'a>(zones: &[&'a ReverbZone], position: Vector3<f32>) -> Vec<(&'a str, f32)> {
+    let mut sends = Vec::<(&str, f32)>::new();
+    for zone in zones {
+        let weight = zone.weight(position);
+        if weight > 0.0 {
+            match sends.iter_mut().find(|(bus, _)| *bus == zone.audio_bus()) {
+                Some((_, level)) => *level = (*level + weight).min(1.0),
+                None => sends.push((zone.audio_bus(), weight)),
+            }
+        }
+    }
+    let total = sends.iter().map(|(_, level)| level).sum::<f32>();
+    if total > 1.0 {
+        for (_, level) in sends.iter_mut() {
+            *level /= total;
+        }
+    }
+    sends
+}
+
+/// Blends presets of every enabled reverb zone around the listener and applies them to the respective
+/// audio buses. Also updates sends of the sound sources near the zones in [`ReverbZoneMode::Sources`]
+/// mode.
+pub(crate) fn update_reverb_zones(nodes: &NodePool, sound_context: &mut SoundContext) {
+    let mut state = sound_context.native.state();
+    let listener_position = state.listener().position();
+
+    let mut buses = FxHashMap::<&str, BusReverb>::default();
+    let mut source_zones = Vec::new();
+    for node in nodes.iter() {
+        let Some(zone) = node.cast::<ReverbZone>() else {
+            continue;
+        };
+        if !zone.is_globally_enabled() {
+            continue;
+        }
+
+        let bus = buses.entry(zone.audio_bus()).or_default();
+        let weight = match zone.mode() {
+            ReverbZoneMode::Listener => zone.weight(listener_position),
+            ReverbZoneMode::Sources => {
+                source_zones.push(zone);
+                // The bus is always audible, the sources control their send levels.
+                1.0
+            }
+        };
+        if weight > 0.0 {
+            // Weighted running average of the presets.
+            let new_total_weight = bus.total_weight + weight;
+            bus.preset = Some(match bus.preset {
+                Some(preset) => preset.lerp(zone.preset(), weight / new_total_weight),
+                None => *zone.preset(),
+            });
+            bus.total_weight = new_total_weight;
+        }
+    }
+
+    for (name, reverb) in buses {
+        let Some(bus) = state
+            .bus_graph_mut()
+            .buses_iter_mut()
+            .find(|bus| bus.name() == name)
+        else {
+            continue;
+        };
+
+        match reverb.preset {
+            Some(preset) => {
+                // Fade out the reverb, when the listener is leaving the zones.
+                bus.set_gain(preset.level * reverb.total_weight.min(1.0));
+                if let Some(Effect::Reverb(effect)) = bus
+                    .effects_mut()
+                    .find(|effect| matches!(effect, Effect::Reverb(_)))
+                {
+                    effect.apply_preset(&preset);
+                }
+            }
+            None => bus.set_gain(0.0),
+        }
+    }
+
+    for node in nodes.iter() {
+        let Some(sound) = node.cast::<Sound>() else {
+            continue;
+        };
+        let Some(source) = state.try_get_source_mut(sound.native.get()) else {
+            continue;
+        };
+
+        let zone_sends = if *sound.reverb_zone_sends {
+            zone_sends(&source_zones, sound.global_position())
+        } else {
+            Vec::new()
+        };
+        if zone_sends.is_empty() {
+            // Restore the sends of the sound, when it leaves the zones.
+            if source.sends() != sound.sends() {
+                source.set_sends(sound.sends().to_vec());
+            }
+            continue;
+        }
+
+        // Manual sends of the sound have priority over the automatic ones.
+        let mut sends = sound.sends().to_vec();
+        for (bus, level) in zone_sends {
+            if !sends.iter().any(|send| send.bus == bus) {
+                sends.push(AudioSend::new(bus, level));
+            }
+        }
+        if source.sends() != sends.as_slice() {
+            source.set_sends(sends);
+        }
+    }
+}
+
+/// Allows you to create a reverb zone in declarative manner.
+pub struct ReverbZoneBuilder {
+    base_builder: BaseBuilder,
+    shape: ReverbZoneShape,
+    preset: ReverbPreset,
+    fade_distance: f32,
+    audio_bus: String,
+    mode: ReverbZoneMode,
+}
+
+impl ReverbZoneBuilder {
+    /// Creates a new reverb zone builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            shape: Default::default(),
+            preset: Default::default(),
+            fade_distance: 2.0,
+            audio_bus: ReverbZone::DEFAULT_BUS.to_string(),
+            mode: Default::default(),
+        }
+    }
+
+    /// Sets desired shape of the zone.
+    pub fn with_shape(mut self, shape: ReverbZoneShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Sets desired reverb preset of the zone.
+    pub fn with_preset(mut self, preset: ReverbPreset) -> Self {
+        self.preset = preset;
+        self
+    }
+
+    /// Sets desired fade distance of the zone. See [`ReverbZone::set_fade_distance`] for more info.
+    pub fn with_fade_distance(mut self, fade_distance: f32) -> Self {
+        self.fade_distance = fade_distance;
+        self
+    }
+
+    /// Sets desired audio bus of the zone. See [`ReverbZone::set_audio_bus`] for more info.
+    pub fn with_audio_bus(mut self, audio_bus: String) -> Self {
+        self.audio_bus = audio_bus;
+        self
+    }
+
+    /// Sets desired mode of the zone. See [`ReverbZoneMode`] docs for more info.
+    pub fn with_mode(mut self, mode: ReverbZoneMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Creates a new [`ReverbZone`] instance.
+    pub fn build_reverb_zone(self) -> ReverbZone {
+        ReverbZone {
+            base: self.base_builder.build_base(),
+            shape: self.shape.into(),
+            preset: self.preset.into(),
+            fade_distance: self.fade_distance.max(0.0).into(),
+            audio_bus: self.audio_bus.into(),
+            mode: self.mode.into(),
+        }
+    }
+
+    /// Creates a new [`ReverbZone`] node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_reverb_zone())
+    }
+
+    /// Creates a new [`ReverbZone`] node and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            sound::{
+                reverb::{Reverb, ReverbPreset},
+                reverb_zone::{ReverbZoneBuilder, ReverbZoneMode, ReverbZoneShape},
+                AudioBus, AudioSend, Effect, Sound, SoundBuilder,
+            },
+            transform::TransformBuilder,
+        },
+    };
+
+    fn reverb_state(graph: &Graph) -> (f32, f32) {
+        let state = graph.sound_context.state();
+        let bus = state
+            .bus_graph_ref()
+            .buses_iter()
+            .find(|bus| bus.name() == "Reverb")
+            .unwrap();
+        let Some(Effect::Reverb(reverb)) = bus.effect(0) else {
+            unreachable!()
+        };
+        (bus.gain(), reverb.decay_time())
+    }
+
+    #[test]
+    fn test_reverb_zones_crossfade() {
+        let mut graph = Graph::new();
+        {
+            let mut state = graph.sound_context.state();
+            let mut bus = AudioBus::new("Reverb".to_string());
+            bus.add_effect(Effect::Reverb(Reverb::new()));
+            let primary = state.bus_graph_ref().primary_bus_handle();
+            state.bus_graph_mut().add_bus(bus, primary);
+        }
+
+        let room = ReverbPreset {
+            decay_time: 1.0,
+            fc: 0.2,
+            level: 1.0,
+        };
+        let hall = ReverbPreset {
+            decay_time: 3.0,
+            fc: 0.2,
+            level: 1.0,
+        };
+        // Two boxes touching each other at x = 0.0.
+        for (x, preset) in [(-5.0, room), (5.0, hall)] {
+            ReverbZoneBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(x, 0.0, 0.0))
+                        .build(),
+                ),
+            )
+            .with_shape(ReverbZoneShape::Box {
+                half_extents: Vector3::repeat(5.0),
+            })
+            .with_preset(preset)
+            .with_fade_distance(2.0)
+            .build(&mut graph);
+        }
+
+        let mut listen_at = |x: f32| {
+            graph
+                .sound_context
+                .native
+                .state()
+                .listener_mut()
+                .set_position(Vector3::new(x, 0.0, 0.0));
+            graph.update(Default::default(), 0.0, Default::default());
+            reverb_state(&graph)
+        };
+
+        // Deep inside of the first zone.
+        assert_eq!(listen_at(-5.0), (1.0, 1.0));
+        // At the boundary both zones have equal weights.
+        assert_eq!(listen_at(0.0), (1.0, 2.0));
+        // Fading out outside of the second zone.
+        assert_eq!(listen_at(11.0), (0.5, 3.0));
+        // Far away from any zone.
+        assert_eq!(listen_at(100.0).0, 0.0);
+    }
+
+    #[test]
+    fn test_reverb_zone_source_sends() {
+        let mut graph = Graph::new();
+        {
+            let mut state = graph.sound_context.state();
+            let primary = state.bus_graph_ref().primary_bus_handle();
+            for name in ["Cave", "Hall"] {
+                let mut bus = AudioBus::new(name.to_string());
+                bus.add_effect(Effect::Reverb(Reverb::new()));
+                state.bus_graph_mut().add_bus(bus, primary);
+            }
+        }
+
+        for (x, bus) in [(-5.0, "Cave"), (5.0, "Hall")] {
+            ReverbZoneBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(x, 0.0, 0.0))
+                        .build(),
+                ),
+            )
+            .with_shape(ReverbZoneShape::Box {
+                half_extents: Vector3::repeat(5.0),
+            })
+            .with_fade_distance(2.0)
+            .with_audio_bus(bus.to_string())
+            .with_mode(ReverbZoneMode::Sources)
+            .build(&mut graph);
+        }
+
+        let sound = SoundBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        let mut sends_at = |x: f32| {
+            graph[sound]
+                .local_transform_mut()
+                .set_position(Vector3::new(x, 0.0, 0.0));
+            graph.update(Default::default(), 0.0, Default::default());
+            let native = graph[sound].cast::<Sound>().unwrap().native.get();
+            graph
+                .sound_context
+                .native
+                .state()
+                .source(native)
+                .sends()
+                .to_vec()
+        };
+
+        assert_eq!(sends_at(-5.0), vec![AudioSend::new("Cave", 1.0)]);
+        // Crossfade at the boundary between the zones.
+        assert_eq!(
+            sends_at(0.0),
+            vec![AudioSend::new("Cave", 0.5), AudioSend::new("Hall", 0.5)]
+        );
+        // Fading out outside of the second zone.
+        assert_eq!(sends_at(11.0), vec![AudioSend::new("Hall", 0.5)]);
+        assert!(sends_at(100.0).is_empty());
+    }
+}
@@ -31,9 +31,43 @@ use crate::{
         node::{Node, NodeTrait},
     },
 };
-use fyrox_core::value_as_u8_slice;
+use fyrox_core::{uuid_provider, value_as_u8_slice};
 use fyrox_graph::BaseSceneGraph;
 use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines how a billboard (a sprite or a particle) is oriented relative to the camera.
+#[derive(
+    Visit, Copy, Clone, PartialEq, Debug, Default, Reflect, AsRefStr, EnumString, VariantNames,
+)]
+pub enum BillboardMode {
+    /// Billboard always faces the camera.
+    #[default]
+    FaceCamera,
+    /// Billboard is stretched along its direction of movement and rotates only around it to face the
+    /// camera as much as possible. Particles use their velocity as the direction; sprites use the up
+    /// vector (Y axis) of their global transform, so a sprite should be rotated along its movement
+    /// (useful for tracers, sparks, rain drops, etc.).
+    VelocityAligned,
+    /// Billboard rotates only around the given world-space axis to face the camera as much as
+    /// possible (useful for trees, flames, light shafts, etc.).
+    AxisLocked(Vector3<f32>),
+}
+
+uuid_provider!(BillboardMode = "5b2a8c4e-1d7f-4e63-9a0b-c8f3d6e2a714");
+
+impl BillboardMode {
+    /// Returns world-space axis around which a billboard should be rotated. Zero vector means
+    /// that the billboard should face the camera. `direction` is used for
+    /// [`BillboardMode::VelocityAligned`] mode.
+    pub fn axis(&self, direction: Vector3<f32>) -> Vector3<f32> {
+        match self {
+            BillboardMode::FaceCamera => Vector3::default(),
+            BillboardMode::VelocityAligned => direction,
+            BillboardMode::AxisLocked(axis) => *axis,
+        }
+    }
+}
 
 /// A vertex for sprites.
 #[derive(Copy, Clone, Debug, Default)]
@@ -47,6 +81,12 @@ pub struct SpriteVertex {
     pub params: Vector2<f32>,
     /// Diffuse color.
     pub color: Color,
+    /// World-space axis around which the sprite rotates to face the camera. Zero vector means
+    /// that the sprite always faces the camera. See [`BillboardMode`] for more info.
+    pub axis: Vector3<f32>,
+    /// Distance (in world units) over which the sprite fades out when it approaches opaque geometry.
+    /// Zero means no fading.
+    pub depth_fade_distance: f32,
 }
 
 impl VertexTrait for SpriteVertex {
@@ -84,6 +124,22 @@ impl VertexTrait for SpriteVertex {
                 shader_location: 3,
                 normalized: true,
             },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Custom1,
+                data_type: VertexAttributeDataType::F32,
+                size: 3,
+                divisor: 0,
+                shader_location: 4,
+                normalized: false,
+            },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Custom2,
+                data_type: VertexAttributeDataType::F32,
+                size: 1,
+                divisor: 0,
+                shader_location: 5,
+                normalized: false,
+            },
         ]
     }
 }
@@ -91,6 +147,16 @@ impl VertexTrait for SpriteVertex {
 /// Sprite is a billboard which always faces towards camera. It can be used as a "model" for bullets,
 /// and so on.
 ///
+/// # Orientation
+///
+/// By default, sprites always face the camera. This could be changed by [`Sprite::set_billboard_mode`],
+/// see [`BillboardMode`] docs for more info.
+///
+/// # Soft edges
+///
+/// Sprites that intersect opaque geometry could fade out near the intersection, instead of having a
+/// sharp edge. See [`Sprite::set_depth_fade_distance`].
+///
 /// # Depth sorting
 ///
 /// Sprites are **not** depth-sorted so there could be some blending issues if multiple sprites are
@@ -155,6 +221,13 @@ pub struct Sprite {
 
     #[reflect(setter = "set_rotation")]
     rotation: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_billboard_mode")]
+    billboard_mode: InheritableVariable<BillboardMode>,
+
+    #[reflect(min_value = 0.0, step = 0.05)]
+    #[reflect(setter = "set_depth_fade_distance")]
+    depth_fade_distance: InheritableVariable<f32>,
 }
 
 impl Visit for Sprite {
@@ -180,6 +253,10 @@ impl Visit for Sprite {
 
         // Backward compatibility.
         let _ = self.uv_rect.visit("UvRect", &mut region);
+        let _ = self.billboard_mode.visit("BillboardMode", &mut region);
+        let _ = self
+            .depth_fade_distance
+            .visit("DepthFadeDistance", &mut region);
 
         Ok(())
     }
@@ -273,6 +350,33 @@ impl Sprite {
     pub fn set_uv_rect(&mut self, uv_rect: Rect<f32>) -> Rect<f32> {
         self.uv_rect.set_value_and_mark_modified(uv_rect)
     }
+
+    /// Sets new billboard mode of the sprite. See [`BillboardMode`] docs for more info. Default is
+    /// [`BillboardMode::FaceCamera`].
+    pub fn set_billboard_mode(&mut self, mode: BillboardMode) -> BillboardMode {
+        self.billboard_mode.set_value_and_mark_modified(mode)
+    }
+
+    /// Returns current billboard mode of the sprite.
+    pub fn billboard_mode(&self) -> BillboardMode {
+        *self.billboard_mode
+    }
+
+    /// Sets a distance (in world units) over which the sprite fades out when it approaches opaque
+    /// geometry behind it. It hides hard intersection lines with the geometry. Zero disables fading.
+    /// Default is 0.0.
+    ///
+    /// Keep in mind, that the fading is implemented in the standard sprite shader, custom shaders
+    /// must implement it manually.
+    pub fn set_depth_fade_distance(&mut self, distance: f32) -> f32 {
+        self.depth_fade_distance
+            .set_value_and_mark_modified(distance.max(0.0))
+    }
+
+    /// Returns current depth fade distance of the sprite.
+    pub fn depth_fade_distance(&self) -> f32 {
+        *self.depth_fade_distance
+    }
 }
 
 impl NodeTrait for Sprite {
@@ -307,6 +411,8 @@ impl NodeTrait for Sprite {
 
         let position = self.global_position();
         let params = Vector2::new(*self.size, *self.rotation);
+        let axis = self.billboard_mode.axis(self.up_vector());
+        let depth_fade_distance = *self.depth_fade_distance;
 
         type Vertex = SpriteVertex;
 
@@ -316,24 +422,32 @@ impl NodeTrait for Sprite {
                 tex_coord: self.uv_rect.right_top_corner(),
                 params,
                 color: *self.color,
+                axis,
+                depth_fade_distance,
             },
             Vertex {
                 position,
                 tex_coord: self.uv_rect.left_top_corner(),
                 params,
                 color: *self.color,
+                axis,
+                depth_fade_distance,
             },
             Vertex {
                 position,
                 tex_coord: self.uv_rect.left_bottom_corner(),
                 params,
                 color: *self.color,
+                axis,
+                depth_fade_distance,
             },
             Vertex {
                 position,
                 tex_coord: self.uv_rect.right_bottom_corner(),
                 params,
                 color: *self.color,
+                axis,
+                depth_fade_distance,
             },
         ];
 
@@ -376,6 +490,8 @@ pub struct SpriteBuilder {
     color: Color,
     size: f32,
     rotation: f32,
+    billboard_mode: BillboardMode,
+    depth_fade_distance: f32,
 }
 
 impl SpriteBuilder {
@@ -388,6 +504,8 @@ impl SpriteBuilder {
             color: Color::WHITE,
             size: 0.2,
             rotation: 0.0,
+            billboard_mode: Default::default(),
+            depth_fade_distance: 0.0,
         }
     }
 
@@ -422,6 +540,18 @@ impl SpriteBuilder {
         self
     }
 
+    /// Sets desired billboard mode. See [`BillboardMode`] docs for more info.
+    pub fn with_billboard_mode(mut self, mode: BillboardMode) -> Self {
+        self.billboard_mode = mode;
+        self
+    }
+
+    /// Sets desired depth fade distance. See [`Sprite::set_depth_fade_distance`] for more info.
+    pub fn with_depth_fade_distance(mut self, distance: f32) -> Self {
+        self.depth_fade_distance = distance.max(0.0);
+        self
+    }
+
     fn build_sprite(self) -> Sprite {
         Sprite {
             base: self.base_builder.build_base(),
@@ -430,6 +560,8 @@ impl SpriteBuilder {
             color: self.color.into(),
             size: self.size.into(),
             rotation: self.rotation.into(),
+            billboard_mode: self.billboard_mode.into(),
+            depth_fade_distance: self.depth_fade_distance.into(),
         }
     }
 
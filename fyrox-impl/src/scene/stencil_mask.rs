@@ -0,0 +1,206 @@
+//! Stencil mask restricts rendering of a set of nodes to the screen region covered by a mask mesh.
+//!
+//! For more info see [`StencilMask`]
+
+use crate::{
+    core::{
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{Node, NodeTrait},
+    },
+};
+use fyrox_graph::BaseSceneGraph;
+use std::ops::{Deref, DerefMut};
+
+/// Max amount of stencil masks, that could be active at the same time. Every mask occupies one bit
+/// of the 8-bit stencil buffer, the masks after the limit are ignored by the renderer.
+pub const MAX_STENCIL_MASKS: usize = 8;
+
+/// Stencil mask restricts rendering of a set of nodes (see [`StencilMask::set_masked_nodes`]) to
+/// the screen region covered by the surfaces of a mask mesh (see [`StencilMask::set_mask`]). It
+/// could be used to create sniper scopes, portals, minimaps, "magic reveal" areas and similar
+/// effects without writing custom draw parameters. The masked nodes are drawn only inside of the
+/// region, or only outside of it if the mask is inverted (see [`StencilMask::set_inverted`]).
+///
+/// The mask works with both deferred and forward (including order-independent transparency)
+/// rendering paths. Descendant nodes of every masked node are masked as well.
+///
+/// # Limitations
+///
+/// - The mask mesh is drawn without depth test, so the region is its projection on the screen,
+///   regardless of the objects in front of it. The mask mesh itself is rendered as usual, make it
+///   invisible if it should only define the region.
+/// - Shadows of the masked nodes are not masked.
+/// - At most [`MAX_STENCIL_MASKS`] masks could be active at the same time. A node could be masked
+///   by a single mask only.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     scene::{
+/// #         base::BaseBuilder, graph::Graph, node::Node,
+/// #         stencil_mask::StencilMaskBuilder,
+/// #     },
+/// # };
+/// fn create_scope(
+///     lens_mesh: Handle<Node>,
+///     zoomed_world: Handle<Node>,
+///     graph: &mut Graph,
+/// ) -> Handle<Node> {
+///     StencilMaskBuilder::new(BaseBuilder::new().with_name("ScopeMask"))
+///         .with_mask(lens_mesh)
+///         .with_masked_nodes(vec![zoomed_world])
+///         .build(graph)
+/// }
+/// ```
+#[derive(Debug, Visit, Clone, Reflect, Default)]
+pub struct StencilMask {
+    base: Base,
+
+    #[reflect(setter = "set_mask")]
+    mask: InheritableVariable<Handle<Node>>,
+
+    #[reflect(setter = "set_masked_nodes")]
+    masked_nodes: InheritableVariable<Vec<Handle<Node>>>,
+
+    #[reflect(setter = "set_inverted")]
+    inverted: InheritableVariable<bool>,
+}
+
+impl Deref for StencilMask {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for StencilMask {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for StencilMask {
+    fn type_uuid() -> Uuid {
+        uuid!("4f6c1b8e-93a2-4d75-b0e1-7c2d95a8f364")
+    }
+}
+
+impl StencilMask {
+    /// Sets a handle of a mesh, which surfaces define the mask region.
+    pub fn set_mask(&mut self, mask: Handle<Node>) -> Handle<Node> {
+        self.mask.set_value_and_mark_modified(mask)
+    }
+
+    /// Returns a handle of the mask mesh.
+    pub fn mask(&self) -> Handle<Node> {
+        *self.mask
+    }
+
+    /// Sets a new set of nodes, that will be drawn only inside of the mask region (or only outside
+    /// of it, if the mask is inverted).
+    pub fn set_masked_nodes(&mut self, nodes: Vec<Handle<Node>>) -> Vec<Handle<Node>> {
+        self.masked_nodes.set_value_and_mark_modified(nodes)
+    }
+
+    /// Returns a set of masked nodes.
+    pub fn masked_nodes(&self) -> &[Handle<Node>] {
+        &self.masked_nodes
+    }
+
+    /// Defines whether the masked nodes should be drawn outside of the mask region instead.
+    pub fn set_inverted(&mut self, inverted: bool) -> bool {
+        self.inverted.set_value_and_mark_modified(inverted)
+    }
+
+    /// Returns `true` if the masked nodes are drawn outside of the mask region.
+    pub fn is_inverted(&self) -> bool {
+        *self.inverted
+    }
+}
+
+impl NodeTrait for StencilMask {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Allows you to create stencil masks in declarative manner.
+pub struct StencilMaskBuilder {
+    base_builder: BaseBuilder,
+    mask: Handle<Node>,
+    masked_nodes: Vec<Handle<Node>>,
+    inverted: bool,
+}
+
+impl StencilMaskBuilder {
+    /// Creates new stencil mask builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            mask: Default::default(),
+            masked_nodes: Default::default(),
+            inverted: false,
+        }
+    }
+
+    /// Sets desired mask mesh. See [`StencilMask::set_mask`].
+    pub fn with_mask(mut self, mask: Handle<Node>) -> Self {
+        self.mask = mask;
+        self
+    }
+
+    /// Sets desired set of masked nodes. See [`StencilMask::set_masked_nodes`].
+    pub fn with_masked_nodes(mut self, nodes: Vec<Handle<Node>>) -> Self {
+        self.masked_nodes = nodes;
+        self
+    }
+
+    /// Sets whether the mask is inverted or not. See [`StencilMask::set_inverted`].
+    pub fn with_inverted(mut self, inverted: bool) -> Self {
+        self.inverted = inverted;
+        self
+    }
+
+    /// Creates new stencil mask.
+    pub fn build_stencil_mask(self) -> StencilMask {
+        StencilMask {
+            base: self.base_builder.build_base(),
+            mask: self.mask.into(),
+            masked_nodes: self.masked_nodes.into(),
+            inverted: self.inverted.into(),
+        }
+    }
+
+    /// Creates new stencil mask node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_stencil_mask())
+    }
+
+    /// Creates new stencil mask and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
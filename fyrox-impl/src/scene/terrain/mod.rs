@@ -6,10 +6,11 @@ use crate::scene::node::RdcControlFlow;
 use crate::{
     asset::Resource,
     core::{
-        algebra::{Matrix4, Point3, Vector2, Vector3, Vector4},
+        algebra::{Matrix3, Matrix4, Point3, Vector2, Vector3, Vector4},
         arrayvec::ArrayVec,
         log::Log,
-        math::{aabb::AxisAlignedBoundingBox, ray::Ray, ray_rect_intersection, Rect},
+        math::{aabb::AxisAlignedBoundingBox, ray::Ray, ray_rect_intersection, Matrix4Ext, Rect},
+        noise::Noise,
         pool::Handle,
         reflect::prelude::*,
         sstorage::ImmutableString,
@@ -30,11 +31,12 @@ use crate::{
     },
     scene::{
         base::{Base, BaseBuilder},
+        camera::Camera,
         debug::SceneDrawingContext,
         graph::Graph,
         mesh::RenderPath,
-        node::{Node, NodeTrait},
-        terrain::{geometry::TerrainGeometry, quadtree::QuadTree},
+        node::{Node, NodeTrait, UpdateContext},
+        terrain::{geometry::TerrainGeometry, quadtree::QuadTree, streaming::StreamingState},
     },
 };
 use fyrox_core::uuid_provider;
@@ -51,6 +53,9 @@ use std::{
 
 mod geometry;
 mod quadtree;
+mod streaming;
+
+pub use streaming::{ChunkResidency, TerrainStreaming};
 
 /// Current implementation version marker.
 pub const VERSION: u8 = 1;
@@ -160,6 +165,8 @@ pub struct Chunk {
     /// Layer blending masks of the chunk.
     #[reflect(hidden)]
     pub layer_masks: Vec<TextureResource>,
+    #[reflect(hidden)]
+    residency: ChunkResidency,
 }
 
 uuid_provider!(Chunk = "ae996754-69c1-49ba-9c17-a7bd4be072a9");
@@ -181,6 +188,16 @@ impl Clone for Chunk {
                 .map(|m| m.deep_clone())
                 .collect::<Vec<_>>(),
             quad_tree: make_quad_tree(&self.heightmap, self.height_map_size, self.block_size),
+            // A copy of a streamed chunk has the impostor only, its data will be loaded from the
+            // same file, so the file must be written first.
+            residency: match self.residency {
+                ChunkResidency::Resident => ChunkResidency::Resident,
+                ChunkResidency::Unloading => {
+                    streaming::wait_for_io();
+                    ChunkResidency::Unloaded
+                }
+                _ => ChunkResidency::Unloaded,
+            },
         }
     }
 }
@@ -257,6 +274,7 @@ impl Default for Chunk {
             block_size: Vector2::new(32, 32),
             grid_position: Default::default(),
             layer_masks: Default::default(),
+            residency: Default::default(),
         }
     }
 }
@@ -507,6 +525,92 @@ impl Chunk {
             .debug_draw(&transform, self.height_map_size, self.physical_size, ctx)
     }
 
+    /// Returns a rectangle, that is occupied by the chunk, in local 2D coordinates of the terrain.
+    pub fn local_rect(&self) -> Rect<f32> {
+        let position = self.local_position();
+        Rect::new(
+            position.x,
+            position.y,
+            self.physical_size.x,
+            self.physical_size.y,
+        )
+    }
+
+    /// Applies the given function to each pixel of the height map of the chunk within the given
+    /// region. Returns `false` if the region does not cover any pixel of the chunk, in this case
+    /// the height map is left untouched.
+    fn modify_height_map_region<F>(&mut self, region: &Rect<f32>, func: &mut F) -> bool
+    where
+        F: FnMut(&mut f32, Vector2<f32>),
+    {
+        if !self.is_resident() {
+            return false;
+        }
+
+        let Some((x_range, y_range)) = pixel_ranges(
+            self.local_position(),
+            self.physical_size,
+            self.height_map_size,
+            region,
+        ) else {
+            return false;
+        };
+
+        let mut texture_data = self.heightmap.as_ref().unwrap().data_ref();
+        let mut texture_modifier = texture_data.modify();
+        let height_map = texture_modifier.data_mut_of_type::<f32>().unwrap();
+
+        for iy in y_range {
+            let kz = iy as f32 / (self.height_map_size.y - 1) as f32;
+            for ix in x_range.clone() {
+                let kx = ix as f32 / (self.height_map_size.x - 1) as f32;
+
+                let pixel_position = self.local_position()
+                    + Vector2::new(kx * self.physical_size.x, kz * self.physical_size.y);
+
+                let index = (iy * self.height_map_size.x + ix) as usize;
+
+                func(&mut height_map[index], pixel_position)
+            }
+        }
+
+        drop(texture_modifier);
+        drop(texture_data);
+
+        self.quad_tree = make_quad_tree(&self.heightmap, self.height_map_size, self.block_size);
+
+        true
+    }
+
+    /// Samples the height map of the chunk at the given point (in local 2D coordinates of the
+    /// terrain) using bilinear filtering.
+    fn sample_height(&self, position: Vector2<f32>) -> Option<f32> {
+        if !self.local_rect().contains(position) {
+            return None;
+        }
+
+        let texture = self.heightmap.as_ref()?.data_ref();
+        let height_map = texture.data_of_type::<f32>()?;
+
+        let size = self.height_map_size.map(|s| s.saturating_sub(1).max(1));
+        let k = (position - self.local_position()).component_div(&self.physical_size);
+        let x = (k.x * size.x as f32).clamp(0.0, size.x as f32);
+        let y = (k.y * size.y as f32).clamp(0.0, size.y as f32);
+        let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+        let (x1, y1) = ((x0 + 1).min(size.x), (y0 + 1).min(size.y));
+        let pixel = |x: u32, y: u32| {
+            height_map
+                .get((y * self.height_map_size.x + x) as usize)
+                .cloned()
+                .unwrap_or_default()
+        };
+
+        let (tx, ty) = (x.fract(), y.fract());
+        let top = pixel(x0, y0) + (pixel(x1, y0) - pixel(x0, y0)) * tx;
+        let bottom = pixel(x0, y1) + (pixel(x1, y1) - pixel(x0, y1)) * tx;
+        Some(top + (bottom - top) * ty)
+    }
+
     fn set_block_size(&mut self, block_size: Vector2<u32>) {
         self.block_size = block_size;
         self.quad_tree = make_quad_tree(&self.heightmap, self.height_map_size, block_size);
@@ -518,6 +622,30 @@ fn map_to_local(v: Vector3<f32>) -> Vector2<f32> {
     Vector2::new(v.x, v.z)
 }
 
+/// Calculates ranges of pixels of a texture, that is stretched over a chunk, that lie within the
+/// given region.
+fn pixel_ranges(
+    chunk_position: Vector2<f32>,
+    chunk_size: Vector2<f32>,
+    texture_size: Vector2<u32>,
+    region: &Rect<f32>,
+) -> Option<(Range<u32>, Range<u32>)> {
+    let range = |origin: f32, size: f32, pixels: u32, min: f32, max: f32| {
+        let last = pixels.checked_sub(1)?;
+        let step = size / last.max(1) as f32;
+        let first_pixel = ((min - origin) / step).ceil().max(0.0);
+        let last_pixel = ((max - origin) / step).floor().min(last as f32);
+        (first_pixel <= last_pixel).then(|| first_pixel as u32..last_pixel as u32 + 1)
+    };
+
+    let min = region.left_top_corner();
+    let max = region.right_bottom_corner();
+    Some((
+        range(chunk_position.x, chunk_size.x, texture_size.x, min.x, max.x)?,
+        range(chunk_position.y, chunk_size.y, texture_size.y, min.y, max.y)?,
+    ))
+}
+
 /// Ray-terrain intersection result.
 #[derive(Debug)]
 pub struct TerrainRayCastResult {
@@ -534,6 +662,15 @@ pub struct TerrainRayCastResult {
     pub toi: f32,
 }
 
+/// A point on the surface of a terrain, see [`Terrain::surface_at`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TerrainSurfacePoint {
+    /// World-space position of the point.
+    pub position: Vector3<f32>,
+    /// World-space normal of the surface at the point.
+    pub normal: Vector3<f32>,
+}
+
 /// Terrain is a height field where each point has fixed coordinates in XZ plane, but variable Y coordinate.
 /// It can be used to create landscapes. It supports multiple layers, where each layer has its own material
 /// and mask.
@@ -649,6 +786,12 @@ pub struct Terrain {
     #[reflect(immutable_collection)]
     chunks: InheritableVariable<Vec<Chunk>>,
 
+    #[reflect(setter = "set_streaming")]
+    streaming: InheritableVariable<TerrainStreaming>,
+
+    #[reflect(hidden)]
+    streaming_state: StreamingState,
+
     #[reflect(hidden)]
     bounding_box_dirty: Cell<bool>,
 
@@ -662,6 +805,9 @@ pub struct Terrain {
 
     #[reflect(hidden)]
     version: u8,
+
+    #[reflect(hidden)]
+    height_map_version: u64,
 }
 
 impl Default for Terrain {
@@ -677,10 +823,13 @@ impl Default for Terrain {
             block_size: Vector2::new(32, 32).into(),
             mask_size: Default::default(),
             chunks: Default::default(),
+            streaming: Default::default(),
+            streaming_state: Default::default(),
             bounding_box_dirty: Cell::new(true),
             bounding_box: Cell::new(Default::default()),
             geometry: Default::default(),
             version: VERSION,
+            height_map_version: 0,
         }
     }
 }
@@ -799,7 +948,12 @@ impl Visit for Terrain {
                 self.height_map_size.visit("HeightMapSize", &mut region)?;
                 let _ = self.block_size.visit("BlockSize", &mut region);
                 self.mask_size.visit("MaskSize", &mut region)?;
+                if !region.is_reading() {
+                    // Unloaded chunks have impostors only, the full data must be saved.
+                    self.load_all_chunks()?;
+                }
                 self.chunks.visit("Chunks", &mut region)?;
+                let _ = self.streaming.visit("Streaming", &mut region);
             }
             _ => (),
         }
@@ -995,6 +1149,7 @@ impl Terrain {
                             })
                             .collect::<Vec<_>>(),
                         version: VERSION,
+                        residency: Default::default(),
                     };
 
                     new_chunk
@@ -1042,42 +1197,235 @@ impl Terrain {
         F: FnMut(&mut f32, Vector2<f32>),
     {
         for chunk in self.chunks.iter_mut() {
-            let mut texture_data = chunk.heightmap.as_ref().unwrap().data_ref();
-            let mut texture_modifier = texture_data.modify();
-            let height_map = texture_modifier.data_mut_of_type::<f32>().unwrap();
+            let region = chunk.local_rect();
+            chunk.modify_height_map_region(&region, &mut func);
+        }
 
-            for iy in 0..chunk.height_map_size.y {
-                let kz = iy as f32 / (chunk.height_map_size.y - 1) as f32;
-                for ix in 0..chunk.height_map_size.x {
-                    let kx = ix as f32 / (chunk.height_map_size.x - 1) as f32;
+        self.height_map_version += 1;
+        self.bounding_box_dirty.set(true);
+    }
 
-                    let pixel_position = chunk.local_position()
-                        + Vector2::new(kx * chunk.physical_size.x, kz * chunk.physical_size.y);
+    /// Applies the given function to each pixel of the height map within the given region (in
+    /// local 2D coordinates of the terrain, see [`Self::project`]). Unlike
+    /// [`Self::for_each_height_map_pixel`], only the chunks that intersect the region are touched,
+    /// so only their height maps are uploaded to GPU again and only their quad trees are rebuilt.
+    /// This makes it suitable for runtime deformation (craters, digging, etc.). Colliders that
+    /// use the terrain as a geometry source are refreshed automatically on next physics sync.
+    ///
+    /// Returns `true` if at least one pixel was visited.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use fyrox_impl::{
+    /// #     core::{algebra::Vector2, math::Rect},
+    /// #     scene::terrain::Terrain,
+    /// # };
+    /// // Digs a rectangular pit, that is 2 meters deep.
+    /// fn dig(terrain: &mut Terrain, position: Vector2<f32>) {
+    ///     let region = Rect::new(position.x - 1.0, position.y - 1.0, 2.0, 2.0);
+    ///     terrain.for_each_height_map_pixel_in_region(region, |height, _| *height -= 2.0);
+    /// }
+    /// ```
+    pub fn for_each_height_map_pixel_in_region<F>(&mut self, region: Rect<f32>, mut func: F) -> bool
+    where
+        F: FnMut(&mut f32, Vector2<f32>),
+    {
+        let mut modified = false;
+        for chunk in self.chunks.iter_mut() {
+            if chunk.local_rect().intersects(region) {
+                modified |= chunk.modify_height_map_region(&region, &mut func);
+            }
+        }
+
+        if modified {
+            self.height_map_version += 1;
+            self.bounding_box_dirty.set(true);
+        }
+
+        modified
+    }
 
-                    let index = (iy * chunk.height_map_size.x + ix) as usize;
+    /// Applies the given function to each pixel of the blending mask of the given layer within the
+    /// given region (in local 2D coordinates of the terrain, see [`Self::project`]). Only the masks
+    /// of the chunks that intersect the region are modified. Returns `true` if at least one pixel
+    /// was visited.
+    pub fn for_each_mask_pixel_in_region<F>(
+        &mut self,
+        layer: usize,
+        region: Rect<f32>,
+        mut func: F,
+    ) -> bool
+    where
+        F: FnMut(&mut u8, Vector2<f32>),
+    {
+        let mut modified = false;
 
-                    func(&mut height_map[index], pixel_position)
+        for chunk in self.chunks.iter_mut() {
+            if !chunk.local_rect().intersects(region) || !chunk.is_resident() {
+                continue;
+            }
+
+            let Some(mask) = chunk.layer_masks.get(layer) else {
+                continue;
+            };
+
+            let chunk_position = chunk.local_position();
+            let mut texture_data = mask.data_ref();
+
+            let TextureKind::Rectangle { width, height } = texture_data.kind() else {
+                unreachable!("Mask must be a 2D greyscale image!")
+            };
+
+            let Some((x_range, y_range)) = pixel_ranges(
+                chunk_position,
+                chunk.physical_size,
+                Vector2::new(width, height),
+                &region,
+            ) else {
+                continue;
+            };
+
+            let mut texture_data_mut = texture_data.modify();
+            // We can modify mask directly, without any problems because it has R8 pixel format.
+            let data = texture_data_mut.data_mut();
+
+            for z in y_range {
+                let kz = z as f32 / (height - 1) as f32;
+                for x in x_range.clone() {
+                    let kx = x as f32 / (width - 1) as f32;
+
+                    let pixel_position = chunk_position
+                        + Vector2::new(kx * chunk.physical_size.x, kz * chunk.physical_size.y);
+
+                    func(&mut data[(z * width + x) as usize], pixel_position);
                 }
             }
 
-            drop(texture_modifier);
-            drop(texture_data);
+            modified = true;
+        }
+
+        modified
+    }
+
+    /// Returns a number, that is incremented every time the height map is modified by one of the
+    /// methods of the terrain. It is used to refresh colliders, that use the terrain as a geometry
+    /// source.
+    pub fn height_map_version(&self) -> u64 {
+        self.height_map_version
+    }
+
+    /// Returns height of the terrain (in local coordinates of the terrain) at the given point (in
+    /// local 2D coordinates of the terrain, see [`Self::project`]). The height is interpolated
+    /// between the pixels of the height map. Returns `None` if the point is outside the terrain.
+    pub fn height_at(&self, position: Vector2<f32>) -> Option<f32> {
+        self.chunks
+            .iter()
+            .find_map(|chunk| chunk.sample_height(position))
+    }
+
+    /// Returns normal of the terrain (in local coordinates of the terrain) at the given point (in
+    /// local 2D coordinates of the terrain, see [`Self::project`]). The normal is calculated from
+    /// the interpolated heights of the neighbour pixels. Returns `None` if the point is outside the
+    /// terrain.
+    pub fn normal_at(&self, position: Vector2<f32>) -> Option<Vector3<f32>> {
+        let height = self.height_at(position)?;
+
+        let step = self.chunk_size.component_div(
+            &self
+                .height_map_size
+                .map(|s| s.saturating_sub(1).max(1) as f32),
+        );
+
+        // Use one-sided differences at the edges of the terrain.
+        let gradient = |offset: Vector2<f32>| {
+            let (h0, d0) = self
+                .height_at(position - offset)
+                .map_or((height, 0.0), |h| (h, 1.0));
+            let (h1, d1) = self
+                .height_at(position + offset)
+                .map_or((height, 0.0), |h| (h, 1.0));
+            let distance = (d0 + d1) * offset.norm();
+            if distance > 0.0 {
+                (h1 - h0) / distance
+            } else {
+                0.0
+            }
+        };
+
+        let dx = gradient(Vector2::new(step.x, 0.0));
+        let dz = gradient(Vector2::new(0.0, step.y));
+
+        Some(Vector3::new(-dx, 1.0, -dz).normalize())
+    }
+
+    /// Returns exact position and normal of the surface of the terrain under (or above) the given
+    /// point (in world coordinates). The values are interpolated between the pixels of the height
+    /// map, so this method could be used to place objects on the terrain without physics. Returns
+    /// `None` if the point is outside the terrain.
+    pub fn surface_at(&self, point: Vector3<f32>) -> Option<TerrainSurfacePoint> {
+        let position = self.project(point)?;
+        let height = self.height_at(position)?;
+        let normal = self.normal_at(position)?;
+
+        let global_transform = self.global_transform();
+        let normal_matrix = global_transform
+            .basis()
+            .try_inverse()
+            .map(|m| m.transpose())
+            .unwrap_or_else(Matrix3::identity);
+
+        Some(TerrainSurfacePoint {
+            position: global_transform
+                .transform_point(&Point3::new(position.x, height, position.y))
+                .coords,
+            normal: (normal_matrix * normal)
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::y),
+        })
+    }
+
+    /// Deforms the terrain in a circle with the given center (in world coordinates), radius and
+    /// depth, leaving a smooth crater. Negative depth raises the terrain instead. Only the affected
+    /// chunks are updated, see [`Self::for_each_height_map_pixel_in_region`] for more info.
+    pub fn make_crater(&mut self, center: Vector3<f32>, radius: f32, depth: f32) {
+        let Some(center) = self.project(center) else {
+            return;
+        };
 
-            chunk.quad_tree =
-                make_quad_tree(&chunk.heightmap, chunk.height_map_size, chunk.block_size);
+        if radius <= 0.0 {
+            return;
         }
 
-        self.bounding_box_dirty.set(true);
+        let region = BrushShape::Circle { radius }.bounds(center);
+        self.for_each_height_map_pixel_in_region(region, |height, position| {
+            let distance = (position - center).norm();
+            if distance < radius {
+                // Cosine falloff keeps both the bottom and the rim of the crater smooth.
+                let k = 0.5 * (1.0 + (std::f32::consts::PI * distance / radius).cos());
+                *height -= depth * k;
+            }
+        });
+    }
+
+    /// Adds the given noise to the height map. Each pixel of the height map is offset by the value of
+    /// the noise at the pixel's position multiplied by `frequency`, and scaled by `amplitude`. Use
+    /// [`crate::core::noise::Fbm`] to get natural-looking hills and mountains.
+    pub fn apply_noise<N: Noise + ?Sized>(&mut self, noise: &N, frequency: f32, amplitude: f32) {
+        self.for_each_height_map_pixel(|height, position| {
+            *height += noise.sample_2d(position.scale(frequency)) * amplitude;
+        });
     }
 
     /// Multi-functional drawing method. It uses given brush to modify terrain, see [`Brush`] docs for
     /// more info.
     pub fn draw(&mut self, brush: &Brush) {
         let center = project(self.global_transform(), brush.center).unwrap();
+        let region = brush.shape.bounds(center);
 
         match brush.mode {
             BrushMode::ModifyHeightMap { amount } => {
-                self.for_each_height_map_pixel(|pixel, pixel_position| {
+                self.for_each_height_map_pixel_in_region(region, |pixel, pixel_position| {
                     let k = match brush.shape {
                         BrushShape::Circle { radius } => {
                             1.0 - ((center - pixel_position).norm() / radius).powf(2.0)
@@ -1097,48 +1445,21 @@ impl Terrain {
 
                 let alpha = alpha.clamp(-1.0, 1.0);
 
-                for chunk in self.chunks.iter_mut() {
-                    let chunk_position = chunk.local_position();
-                    let mut texture_data = chunk.layer_masks[layer].data_ref();
-                    let mut texture_data_mut = texture_data.modify();
-
-                    let (texture_width, texture_height) =
-                        if let TextureKind::Rectangle { width, height } = texture_data_mut.kind() {
-                            (width as usize, height as usize)
-                        } else {
-                            unreachable!("Mask must be a 2D greyscale image!")
-                        };
-
-                    for z in 0..texture_height {
-                        let kz = z as f32 / (texture_height - 1) as f32;
-                        for x in 0..texture_width {
-                            let kx = x as f32 / (texture_width - 1) as f32;
-
-                            let pixel_position = chunk_position
-                                + Vector2::new(
-                                    kx * chunk.physical_size.x,
-                                    kz * chunk.physical_size.y,
-                                );
-
-                            let k = match brush.shape {
-                                BrushShape::Circle { radius } => {
-                                    1.0 - ((center - pixel_position).norm() / radius).powf(4.0)
-                                }
-                                BrushShape::Rectangle { .. } => 1.0,
-                            };
-
-                            if brush.shape.contains(center, pixel_position) {
-                                // We can draw on mask directly, without any problems because it has R8 pixel format.
-                                let data = texture_data_mut.data_mut();
-                                let pixel = &mut data[z * texture_width + x];
-                                *pixel = (*pixel as f32 + k * alpha * 255.0).min(255.0) as u8;
-                            }
+                self.for_each_mask_pixel_in_region(layer, region, |pixel, pixel_position| {
+                    let k = match brush.shape {
+                        BrushShape::Circle { radius } => {
+                            1.0 - ((center - pixel_position).norm() / radius).powf(4.0)
                         }
+                        BrushShape::Rectangle { .. } => 1.0,
+                    };
+
+                    if brush.shape.contains(center, pixel_position) {
+                        *pixel = (*pixel as f32 + k * alpha * 255.0).min(255.0) as u8;
                     }
-                }
+                });
             }
             BrushMode::FlattenHeightMap { height } => {
-                self.for_each_height_map_pixel(|pixel, pixel_position| {
+                self.for_each_height_map_pixel_in_region(region, |pixel, pixel_position| {
                     if brush.shape.contains(center, pixel_position) {
                         *pixel = height;
                     }
@@ -1370,6 +1691,10 @@ impl Terrain {
     fn resize_height_maps(&mut self, mut new_size: Vector2<u32>) {
         new_size = new_size.sup(&Vector2::repeat(2));
 
+        if let Err(err) = self.load_all_chunks() {
+            Log::err(err.to_string());
+        }
+
         for chunk in self.chunks.iter_mut() {
             let texture = chunk.heightmap.as_ref().unwrap().data_ref();
             let mut heightmap = texture.data_of_type::<f32>().unwrap().to_vec();
@@ -1516,7 +1841,7 @@ impl NodeTrait for Terrain {
                 let mut selection = Vec::new();
                 chunk.quad_tree.select(
                     &chunk_transform,
-                    self.height_map_size(),
+                    chunk.height_map_size,
                     self.chunk_size(),
                     ctx.frustum,
                     *ctx.observer_position,
@@ -1549,11 +1874,13 @@ impl NodeTrait for Terrain {
                 );
 
                 for node in selection {
-                    let kx = node.position.x as f32 / self.height_map_size.x as f32;
-                    let kz = node.position.y as f32 / self.height_map_size.y as f32;
+                    // Use the size of the chunk's height map, because impostors of unloaded chunks
+                    // have smaller height maps.
+                    let kx = node.position.x as f32 / chunk.height_map_size.x as f32;
+                    let kz = node.position.y as f32 / chunk.height_map_size.y as f32;
 
-                    let kw = node.size.x as f32 / self.height_map_size.x as f32;
-                    let kh = node.size.y as f32 / self.height_map_size.y as f32;
+                    let kw = node.size.x as f32 / chunk.height_map_size.x as f32;
+                    let kh = node.size.y as f32 / chunk.height_map_size.y as f32;
 
                     Log::verify_message(
                         material.set_property(
@@ -1587,6 +1914,8 @@ impl NodeTrait for Terrain {
                             SurfaceInstanceData {
                                 world_transform: node_transform,
                                 bone_matrices: Default::default(),
+                                prev_world_transform: node_transform,
+                                prev_bone_matrices: Default::default(),
                                 depth_offset: self.depth_offset_factor(),
                                 blend_shapes_weights: Default::default(),
                                 element_range: ElementRange::Full,
@@ -1610,6 +1939,8 @@ impl NodeTrait for Terrain {
                                     SurfaceInstanceData {
                                         world_transform: node_transform,
                                         bone_matrices: Default::default(),
+                                        prev_world_transform: node_transform,
+                                        prev_bone_matrices: Default::default(),
                                         depth_offset: self.depth_offset_factor(),
                                         blend_shapes_weights: Default::default(),
                                         element_range: self.geometry.quadrants[i],
@@ -1636,6 +1967,24 @@ impl NodeTrait for Terrain {
             chunk.debug_draw(&self.global_transform(), ctx)
         }
     }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        if !self.streaming.enabled {
+            // Finish pending background tasks, if the streaming was disabled.
+            self.handle_streaming_events();
+            return;
+        }
+
+        let observer_position = context
+            .nodes
+            .iter()
+            .filter(|n| n.is_globally_enabled())
+            .find_map(|n| n.cast::<Camera>().map(|c| c.global_position()));
+
+        if let Some(observer_position) = observer_position {
+            self.update_streaming(observer_position);
+        }
+    }
 }
 
 /// Shape of a brush.
@@ -1658,6 +2007,20 @@ pub enum BrushShape {
 uuid_provider!(BrushShape = "a4dbfba0-077c-4658-9972-38384a8432f9");
 
 impl BrushShape {
+    /// Returns a rectangle, that contains the brush with the given center.
+    pub fn bounds(&self, brush_center: Vector2<f32>) -> Rect<f32> {
+        let size = match *self {
+            BrushShape::Circle { radius } => Vector2::repeat(2.0 * radius),
+            BrushShape::Rectangle { width, length } => Vector2::new(width, length),
+        };
+        Rect::new(
+            brush_center.x - size.x * 0.5,
+            brush_center.y - size.y * 0.5,
+            size.x,
+            size.y,
+        )
+    }
+
     fn contains(&self, brush_center: Vector2<f32>, pixel_position: Vector2<f32>) -> bool {
         match *self {
             BrushShape::Circle { radius } => (brush_center - pixel_position).norm() < radius,
@@ -1720,6 +2083,7 @@ pub struct TerrainBuilder {
     block_size: Vector2<u32>,
     layers: Vec<Layer>,
     decal_layer_index: u8,
+    streaming: TerrainStreaming,
 }
 
 fn create_layer_mask(width: u32, height: u32, value: u8) -> TextureResource {
@@ -1752,6 +2116,7 @@ impl TerrainBuilder {
             block_size: Vector2::new(32, 32),
             layers: Default::default(),
             decal_layer_index: 0,
+            streaming: Default::default(),
         }
     }
 
@@ -1804,6 +2169,12 @@ impl TerrainBuilder {
         self
     }
 
+    /// Sets desired streaming settings. See [`TerrainStreaming`] docs for more info.
+    pub fn with_streaming(mut self, streaming: TerrainStreaming) -> Self {
+        self.streaming = streaming;
+        self
+    }
+
     /// Build terrain node.
     pub fn build_node(self) -> Node {
         let mut chunks = Vec::new();
@@ -1837,6 +2208,7 @@ impl TerrainBuilder {
                         .collect::<Vec<_>>(),
                     version: VERSION,
                     block_size: self.block_size,
+                    residency: Default::default(),
                 };
 
                 chunks.push(chunk);
@@ -1848,6 +2220,8 @@ impl TerrainBuilder {
             base: self.base_builder.build_base(),
             layers: self.layers.into(),
             chunks: chunks.into(),
+            streaming: self.streaming.into(),
+            streaming_state: Default::default(),
             bounding_box_dirty: Cell::new(true),
             bounding_box: Default::default(),
             mask_size: self.mask_size.into(),
@@ -1858,6 +2232,7 @@ impl TerrainBuilder {
             version: VERSION,
             geometry: TerrainGeometry::new(self.block_size),
             block_size: self.block_size.into(),
+            height_map_version: 0,
         };
         Node::new(terrain)
     }
@@ -1867,3 +2242,64 @@ impl TerrainBuilder {
         graph.add_node(self.build_node())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Vector2, Vector3},
+            math::Rect,
+        },
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            terrain::{Terrain, TerrainBuilder},
+        },
+    };
+
+    #[test]
+    fn test_height_map_region_modification() {
+        let mut graph = Graph::new();
+        let terrain = TerrainBuilder::new(BaseBuilder::new())
+            .with_chunk_size(Vector2::new(4.0, 4.0))
+            .with_height_map_size(Vector2::new(5, 5))
+            .with_width_chunks(0..2)
+            .with_length_chunks(0..1)
+            .build(&mut graph);
+        graph.update(Vector2::new(800.0, 600.0), 0.0, Default::default());
+        let terrain = graph[terrain].cast_mut::<Terrain>().unwrap();
+
+        let mut visited = Vec::new();
+        assert!(terrain.for_each_height_map_pixel_in_region(
+            Rect::new(0.5, 0.5, 2.0, 2.0),
+            |height, position| {
+                *height = -1.0;
+                visited.push(position);
+            }
+        ));
+        // Only 4 pixels of the first chunk are inside the region.
+        assert_eq!(visited.len(), 4);
+        assert_eq!(terrain.height_map_version(), 1);
+        assert_eq!(terrain.height_at(Vector2::new(1.5, 1.5)), Some(-1.0));
+        assert_eq!(terrain.height_at(Vector2::new(6.0, 2.0)), Some(0.0));
+        assert_eq!(terrain.height_at(Vector2::new(10.0, 2.0)), None);
+
+        assert!(!terrain.for_each_height_map_pixel_in_region(
+            Rect::new(100.0, 100.0, 1.0, 1.0),
+            |_, _| unreachable!()
+        ));
+        assert_eq!(terrain.height_map_version(), 1);
+
+        terrain.make_crater(Vector3::new(6.0, 0.0, 2.0), 1.5, 1.0);
+        assert_eq!(terrain.height_at(Vector2::new(6.0, 2.0)), Some(-1.0));
+        assert_eq!(terrain.height_map_version(), 2);
+
+        // The crater is symmetric, so the normal at its center points straight up.
+        let surface = terrain.surface_at(Vector3::new(6.0, 10.0, 2.0)).unwrap();
+        assert_eq!(surface.position, Vector3::new(6.0, -1.0, 2.0));
+        assert!((surface.normal - Vector3::y()).norm() < 1.0e-5);
+        // Slope of the crater faces its center.
+        assert!(terrain.normal_at(Vector2::new(5.0, 2.0)).unwrap().x > 0.0);
+        assert!(terrain.surface_at(Vector3::new(-1.0, 0.0, 2.0)).is_none());
+    }
+}
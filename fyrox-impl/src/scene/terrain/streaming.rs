@@ -0,0 +1,580 @@
+//! Terrain streaming - height maps and blending masks of distant chunks are unloaded to disk and loaded
+//! back in background, when the observer comes closer. See [`TerrainStreaming`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Point3, Vector2, Vector3},
+        log::Log,
+        math::Rect,
+        parking_lot::Mutex,
+        reflect::prelude::*,
+        uuid_provider,
+        visitor::prelude::*,
+    },
+    resource::texture::{TextureKind, TextureResource},
+    scene::terrain::{create_layer_mask, make_height_map_texture, make_quad_tree, Chunk, Terrain},
+};
+use lazy_static::lazy_static;
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+};
+
+/// Streaming settings of a terrain. When enabled, the terrain keeps full-resolution height maps and
+/// blending masks only for the chunks around the observer (the first enabled camera of the scene, or
+/// any other point passed to [`Terrain::update_streaming`]):
+///
+/// - Chunks farther than [`Self::unload_radius`] are unloaded - their data is written to a file in
+///   [`Self::cache_dir`] in background, and the chunk is replaced with a low-resolution impostor
+///   (its height map and masks are downsampled to the block size of the terrain), so the terrain
+///   has no holes on the horizon.
+/// - Unloaded chunks closer than [`Self::load_radius`] are loaded back from the file in background.
+///
+/// All the files are read and written by a single IO thread shared by every terrain, one file at a
+/// time in the order of the requests.
+///
+/// The unload radius should be larger than the load radius, otherwise chunks on the boundary will be
+/// loaded and unloaded all the time. Distances are measured in local coordinates of the terrain from
+/// the observer to the closest point of a chunk.
+///
+/// Impostors are fully functional chunks with lower resolution - they could be rendered, ray cast,
+/// used for physics, etc. However, editing unloaded chunks (via [`Terrain::for_each_height_map_pixel`]
+/// and similar methods) is not supported - the changes will be lost when the chunk is loaded back.
+/// Streaming is not supported on WebAssembly.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct TerrainStreaming {
+    /// Whether the streaming is enabled or not.
+    pub enabled: bool,
+    /// Chunks closer than this distance will be loaded.
+    #[reflect(min_value = 0.0, step = 1.0)]
+    pub load_radius: f32,
+    /// Chunks farther than this distance will be unloaded.
+    #[reflect(min_value = 0.0, step = 1.0)]
+    pub unload_radius: f32,
+    /// A directory, where the data of unloaded chunks is stored.
+    pub cache_dir: PathBuf,
+}
+
+uuid_provider!(TerrainStreaming = "0d3f52c1-a0cb-4a8e-9f2d-c4f5f1d2b89e");
+
+impl Default for TerrainStreaming {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            load_radius: 256.0,
+            unload_radius: 320.0,
+            cache_dir: PathBuf::from("terrain_cache"),
+        }
+    }
+}
+
+/// Residency state of a terrain chunk. See [`TerrainStreaming`] docs for more info.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ChunkResidency {
+    /// Full-resolution data of the chunk is in memory.
+    #[default]
+    Resident,
+    /// The chunk is replaced with its impostor and its data is being written to disk.
+    Unloading,
+    /// The chunk is replaced with its impostor, its data is on disk.
+    Unloaded,
+    /// The chunk is replaced with its impostor and its data is being loaded from disk.
+    Loading,
+}
+
+/// Full-resolution data of a chunk.
+pub(super) struct ChunkData {
+    heights: Vec<f32>,
+    masks: Vec<Vec<u8>>,
+}
+
+impl ChunkData {
+    const MAGIC: &'static [u8; 4] = b"FTCD";
+
+    fn write(&self, path: &Path) -> io::Result<()> {
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(Self::MAGIC)?;
+        writer.write_all(&(self.heights.len() as u32).to_le_bytes())?;
+        for height in self.heights.iter() {
+            writer.write_all(&height.to_le_bytes())?;
+        }
+        writer.write_all(&(self.masks.len() as u32).to_le_bytes())?;
+        for mask in self.masks.iter() {
+            writer.write_all(&(mask.len() as u32).to_le_bytes())?;
+            writer.write_all(mask)?;
+        }
+        writer.flush()
+    }
+
+    fn read(path: &Path) -> io::Result<Self> {
+        fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+            let mut bytes = [0; 4];
+            reader.read_exact(&mut bytes)?;
+            Ok(u32::from_le_bytes(bytes))
+        }
+
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != Self::MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Not a terrain chunk data file.",
+            ));
+        }
+
+        let height_count = read_u32(&mut reader)? as usize;
+        let mut bytes = vec![0; height_count * 4];
+        reader.read_exact(&mut bytes)?;
+        let heights = bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+
+        let mask_count = read_u32(&mut reader)? as usize;
+        let mut masks = Vec::with_capacity(mask_count);
+        for _ in 0..mask_count {
+            let mut mask = vec![0; read_u32(&mut reader)? as usize];
+            reader.read_exact(&mut mask)?;
+            masks.push(mask);
+        }
+
+        Ok(Self { heights, masks })
+    }
+}
+
+enum StreamingEvent {
+    Unloaded {
+        grid_position: Vector2<i32>,
+        // The data is sent back, if it cannot be written to disk.
+        result: Result<(), (ChunkData, io::Error)>,
+    },
+    Loaded {
+        grid_position: Vector2<i32>,
+        result: io::Result<ChunkData>,
+    },
+}
+
+type IoTask = Box<dyn FnOnce() + Send>;
+
+lazy_static! {
+    // The IO thread is started on first use and lives until the end of the process.
+    static ref IO_QUEUE: Mutex<Sender<IoTask>> = {
+        let (sender, receiver) = mpsc::channel::<IoTask>();
+        std::thread::Builder::new()
+            .name("TerrainStreamingIo".to_string())
+            .spawn(move || {
+                for task in receiver {
+                    task();
+                }
+            })
+            .expect("Unable to start terrain streaming IO thread.");
+        Mutex::new(sender)
+    };
+}
+
+/// Puts the given task at the end of the IO queue.
+fn enqueue_io(task: impl FnOnce() + Send + 'static) {
+    // The IO thread never stops, so the task cannot be lost.
+    let _ = IO_QUEUE.lock().send(Box::new(task));
+}
+
+/// Blocks the current thread until every IO task queued so far is finished.
+pub(super) fn wait_for_io() {
+    let (sender, receiver) = mpsc::channel();
+    enqueue_io(move || {
+        let _ = sender.send(());
+    });
+    let _ = receiver.recv();
+}
+
+/// Internal state of the terrain streaming, it is not serialized and not cloned.
+pub(super) struct StreamingState {
+    sender: Sender<StreamingEvent>,
+    receiver: Receiver<StreamingEvent>,
+}
+
+impl Default for StreamingState {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self { sender, receiver }
+    }
+}
+
+impl Clone for StreamingState {
+    fn clone(&self) -> Self {
+        // The copy must have its own channel, background tasks will report to the original.
+        Self::default()
+    }
+}
+
+impl std::fmt::Debug for StreamingState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "StreamingState")
+    }
+}
+
+/// Downsamples the given 2D array using nearest pixels. Corner pixels are kept as is, so the edges
+/// of neighbour chunks match each other.
+fn downsample<T: Copy>(data: &[T], size: Vector2<u32>, new_size: Vector2<u32>) -> Vec<T> {
+    let map = |i: u32, size: u32, new_size: u32| {
+        if new_size > 1 {
+            (i as f32 * (size - 1) as f32 / (new_size - 1) as f32).round() as u32
+        } else {
+            0
+        }
+    };
+
+    let mut result = Vec::with_capacity((new_size.x * new_size.y) as usize);
+    for y in 0..new_size.y {
+        let sy = map(y, size.y, new_size.y);
+        for x in 0..new_size.x {
+            let sx = map(x, size.x, new_size.x);
+            result.push(data[(sy * size.x + sx) as usize]);
+        }
+    }
+    result
+}
+
+fn texture_size(texture: &TextureResource) -> Vector2<u32> {
+    match texture.data_ref().kind() {
+        TextureKind::Rectangle { width, height } => Vector2::new(width, height),
+        _ => Vector2::new(1, 1),
+    }
+}
+
+impl Chunk {
+    /// Returns current residency state of the chunk. See [`TerrainStreaming`] docs for more info.
+    pub fn residency(&self) -> ChunkResidency {
+        self.residency
+    }
+
+    /// Returns `true` if the full-resolution data of the chunk is in memory.
+    pub fn is_resident(&self) -> bool {
+        self.residency == ChunkResidency::Resident
+    }
+
+    /// Extracts full-resolution data of the chunk and replaces it with an impostor of the given size.
+    fn unload(&mut self, impostor_size: Vector2<u32>) -> ChunkData {
+        let impostor_size = impostor_size.inf(&self.height_map_size);
+
+        let heights = self.heightmap_owned();
+        let masks = self
+            .layer_masks
+            .iter()
+            .map(|mask| mask.data_ref().mip_level_data(0).to_vec())
+            .collect::<Vec<_>>();
+
+        self.heightmap = Some(make_height_map_texture(
+            downsample(&heights, self.height_map_size, impostor_size),
+            impostor_size,
+        ));
+        for (mask, data) in self.layer_masks.iter_mut().zip(masks.iter()) {
+            let mask_size = texture_size(mask);
+            let impostor_mask_size = impostor_size.inf(&mask_size);
+            let impostor_mask = create_layer_mask(impostor_mask_size.x, impostor_mask_size.y, 0);
+            impostor_mask
+                .data_ref()
+                .modify()
+                .data_mut()
+                .copy_from_slice(&downsample(data, mask_size, impostor_mask_size));
+            *mask = impostor_mask;
+        }
+        self.height_map_size = impostor_size;
+        self.quad_tree = make_quad_tree(&self.heightmap, self.height_map_size, self.block_size);
+        self.residency = ChunkResidency::Unloading;
+
+        ChunkData { heights, masks }
+    }
+
+    /// Replaces the impostor of the chunk with the given full-resolution data. Returns `false` if
+    /// the data does not match the given sizes.
+    fn load(
+        &mut self,
+        data: ChunkData,
+        height_map_size: Vector2<u32>,
+        mask_size: Vector2<u32>,
+    ) -> bool {
+        if data.heights.len() != (height_map_size.x * height_map_size.y) as usize
+            || data.masks.len() != self.layer_masks.len()
+            || data
+                .masks
+                .iter()
+                .any(|mask| mask.len() != (mask_size.x * mask_size.y) as usize)
+        {
+            return false;
+        }
+
+        self.heightmap = Some(make_height_map_texture(data.heights, height_map_size));
+        for (mask, data) in self.layer_masks.iter_mut().zip(data.masks) {
+            let new_mask = create_layer_mask(mask_size.x, mask_size.y, 0);
+            new_mask
+                .data_ref()
+                .modify()
+                .data_mut()
+                .copy_from_slice(&data);
+            *mask = new_mask;
+        }
+        self.height_map_size = height_map_size;
+        self.quad_tree = make_quad_tree(&self.heightmap, self.height_map_size, self.block_size);
+        self.residency = ChunkResidency::Resident;
+
+        true
+    }
+}
+
+fn distance_to_rect(rect: &Rect<f32>, point: Vector2<f32>) -> f32 {
+    let closest = Vector2::new(
+        point
+            .x
+            .clamp(rect.position.x, rect.position.x + rect.size.x),
+        point
+            .y
+            .clamp(rect.position.y, rect.position.y + rect.size.y),
+    );
+    closest.metric_distance(&point)
+}
+
+impl Terrain {
+    /// Returns current streaming settings of the terrain.
+    pub fn streaming(&self) -> &TerrainStreaming {
+        &self.streaming
+    }
+
+    /// Sets new streaming settings of the terrain. See [`TerrainStreaming`] docs for more info.
+    /// Disabling the streaming does not load unloaded chunks back, use [`Self::load_all_chunks`]
+    /// for that.
+    pub fn set_streaming(&mut self, streaming: TerrainStreaming) -> TerrainStreaming {
+        self.streaming.set_value_and_mark_modified(streaming)
+    }
+
+    fn chunk_cache_path(&self, grid_position: Vector2<i32>) -> PathBuf {
+        self.streaming.cache_dir.join(format!(
+            "{}_{}_{}.chunk",
+            self.instance_id().0,
+            grid_position.x,
+            grid_position.y
+        ))
+    }
+
+    pub(super) fn handle_streaming_events(&mut self) {
+        while let Ok(event) = self.streaming_state.receiver.try_recv() {
+            self.handle_streaming_event(event);
+        }
+    }
+
+    fn handle_streaming_event(&mut self, event: StreamingEvent) {
+        let height_map_size = *self.height_map_size;
+        let mask_size = *self.mask_size;
+        match event {
+            StreamingEvent::Unloaded {
+                grid_position,
+                result,
+            } => {
+                let Some(chunk) = self
+                    .chunks
+                    .get_value_mut_silent()
+                    .iter_mut()
+                    .find(|c| c.grid_position == grid_position)
+                else {
+                    return;
+                };
+                if chunk.residency != ChunkResidency::Unloading {
+                    return;
+                }
+                match result {
+                    Ok(()) => chunk.residency = ChunkResidency::Unloaded,
+                    Err((data, err)) => {
+                        Log::err(format!(
+                            "Unable to unload terrain chunk ({}, {}). Reason: {}",
+                            grid_position.x, grid_position.y, err
+                        ));
+                        chunk.load(data, height_map_size, mask_size);
+                        self.height_map_version += 1;
+                        // Prevent endless attempts to unload chunks.
+                        self.streaming.get_value_mut_silent().enabled = false;
+                    }
+                }
+            }
+            StreamingEvent::Loaded {
+                grid_position,
+                result,
+            } => {
+                let Some(chunk) = self
+                    .chunks
+                    .get_value_mut_silent()
+                    .iter_mut()
+                    .find(|c| c.grid_position == grid_position)
+                else {
+                    return;
+                };
+                if chunk.residency != ChunkResidency::Loading {
+                    return;
+                }
+                match result.map(|data| chunk.load(data, height_map_size, mask_size)) {
+                    Ok(true) => {
+                        self.height_map_version += 1;
+                        self.bounding_box_dirty.set(true);
+                    }
+                    Ok(false) => {
+                        Log::err(format!(
+                            "Unable to load terrain chunk ({}, {}). Reason: data size mismatch.",
+                            grid_position.x, grid_position.y
+                        ));
+                        chunk.residency = ChunkResidency::Unloaded;
+                        self.streaming.get_value_mut_silent().enabled = false;
+                    }
+                    Err(err) => {
+                        Log::err(format!(
+                            "Unable to load terrain chunk ({}, {}). Reason: {}",
+                            grid_position.x, grid_position.y, err
+                        ));
+                        chunk.residency = ChunkResidency::Unloaded;
+                        self.streaming.get_value_mut_silent().enabled = false;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Loads and unloads chunks of the terrain depending on the distance to the given observer
+    /// position (in world coordinates) and applies the results of the finished background tasks.
+    /// It is called automatically every frame with the position of the first enabled camera of the
+    /// scene, so there's no need to call it manually, unless the terrain should be streamed around
+    /// some other point. See [`TerrainStreaming`] docs for more info.
+    pub fn update_streaming(&mut self, observer_position: Vector3<f32>) {
+        self.handle_streaming_events();
+
+        if !self.streaming.enabled || cfg!(target_arch = "wasm32") {
+            return;
+        }
+
+        let Some(inv_transform) = self.global_transform().try_inverse() else {
+            return;
+        };
+        let local_position = inv_transform
+            .transform_point(&Point3::from(observer_position))
+            .coords;
+        let observer = Vector2::new(local_position.x, local_position.z);
+
+        let impostor_size = *self.block_size;
+        for i in 0..self.chunks.len() {
+            let chunk = &self.chunks[i];
+            let distance = distance_to_rect(&chunk.local_rect(), observer);
+            let grid_position = chunk.grid_position;
+            let path = self.chunk_cache_path(grid_position);
+            let sender = self.streaming_state.sender.clone();
+
+            let chunk = &mut self.chunks.get_value_mut_silent()[i];
+            match chunk.residency {
+                ChunkResidency::Resident if distance > self.streaming.unload_radius => {
+                    let data = chunk.unload(impostor_size);
+                    self.height_map_version += 1;
+                    enqueue_io(move || {
+                        let result = data.write(&path).map_err(|err| (data, err));
+                        let _ = sender.send(StreamingEvent::Unloaded {
+                            grid_position,
+                            result,
+                        });
+                    });
+                }
+                ChunkResidency::Unloaded if distance < self.streaming.load_radius => {
+                    chunk.residency = ChunkResidency::Loading;
+                    enqueue_io(move || {
+                        let _ = sender.send(StreamingEvent::Loaded {
+                            grid_position,
+                            result: ChunkData::read(&path),
+                        });
+                    });
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Loads every unloaded chunk of the terrain synchronously. It is called automatically before
+    /// saving the terrain, so the saved terrain always has full-resolution data. Returns an error if
+    /// the data of some chunk cannot be loaded, such chunks keep their impostors.
+    pub fn load_all_chunks(&mut self) -> io::Result<()> {
+        let height_map_size = *self.height_map_size;
+        let mask_size = *self.mask_size;
+
+        // Wait until every chunk is written to disk.
+        while self
+            .chunks
+            .iter()
+            .any(|c| c.residency == ChunkResidency::Unloading)
+        {
+            match self.streaming_state.receiver.recv() {
+                Ok(event) => self.handle_streaming_event(event),
+                Err(_) => break,
+            }
+        }
+
+        if self.chunks.iter().all(|c| c.is_resident()) {
+            return Ok(());
+        }
+
+        // Copies of the terrain share the files, one of them could be written right now.
+        wait_for_io();
+
+        let mut loaded = false;
+        let mut result = Ok(());
+        for i in 0..self.chunks.len() {
+            if self.chunks[i].is_resident() {
+                continue;
+            }
+
+            let grid_position = self.chunks[i].grid_position;
+            let path = self.chunk_cache_path(grid_position);
+            let chunk = &mut self.chunks.get_value_mut_silent()[i];
+            let error = match ChunkData::read(&path) {
+                Ok(data) => {
+                    if chunk.load(data, height_map_size, mask_size) {
+                        loaded = true;
+                        continue;
+                    }
+                    io::Error::new(io::ErrorKind::InvalidData, "data size mismatch")
+                }
+                Err(err) => err,
+            };
+            if result.is_ok() {
+                result = Err(io::Error::new(
+                    error.kind(),
+                    format!(
+                        "Unable to load terrain chunk ({}, {}). Reason: {}",
+                        grid_position.x, grid_position.y, error
+                    ),
+                ));
+            }
+        }
+
+        if loaded {
+            self.height_map_version += 1;
+            self.bounding_box_dirty.set(true);
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::downsample;
+    use crate::core::algebra::Vector2;
+
+    #[test]
+    fn test_downsample_keeps_edges() {
+        let size = Vector2::new(5, 5);
+        let data = (0..25).collect::<Vec<u32>>();
+        let result = downsample(&data, size, Vector2::new(3, 3));
+        assert_eq!(result, vec![0, 2, 4, 10, 12, 14, 20, 22, 24]);
+    }
+}
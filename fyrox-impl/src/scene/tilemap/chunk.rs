@@ -0,0 +1,567 @@
+//! Chunked storage for tile maps. See [`Tiles`] docs for more info.
+
+use crate::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Vector2, Vector3, Vector4},
+        color::Color,
+        math::{aabb::AxisAlignedBoundingBox, TriangleDefinition},
+        parking_lot::Mutex,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    fxhash::FxHashMap,
+    material::{
+        shader::{ShaderResource, ShaderResourceExtension},
+        Material, MaterialResource,
+    },
+    scene::{
+        dim2::rectangle::RectangleVertex,
+        mesh::{
+            buffer::{
+                TriangleBuffer, VertexAttributeDataType, VertexAttributeDescriptor,
+                VertexAttributeUsage, VertexBuffer, VertexTrait,
+            },
+            surface::{SurfaceData, SurfaceResource},
+        },
+        tilemap::{projection::TileMapProjection, tileset::TileSet, Tile},
+    },
+};
+
+/// Size of a side of a tile map chunk (in tiles).
+pub const CHUNK_SIZE: i32 = 32;
+
+/// Returns a position of a chunk that contains a tile at the given position.
+pub fn chunk_position(tile_position: Vector2<i32>) -> Vector2<i32> {
+    Vector2::new(
+        tile_position.x.div_euclid(CHUNK_SIZE),
+        tile_position.y.div_euclid(CHUNK_SIZE),
+    )
+}
+
+/// Per-instance data of a tile, that is drawn using hardware instancing. See
+/// [`TileBatch::instance_count`] docs for more info.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[repr(C)] // OpenGL expects this structure packed as in C
+pub struct TileInstance {
+    /// Position (xy) and size (zw) of the tile in local coordinates of the tile map.
+    pub rect: Vector4<f32>,
+    /// Position (xy) and size (zw) of the texture coordinates rectangle of the tile.
+    pub uv_rect: Vector4<f32>,
+    /// Color of the tile.
+    pub color: Color,
+    /// Index of the layer of the texture array, it is used only if the tile set is packed into a
+    /// texture array (see [`crate::scene::tilemap::packing::TileSetPacking::TextureArray`]).
+    pub layer: f32,
+}
+
+impl VertexTrait for TileInstance {
+    fn layout() -> &'static [VertexAttributeDescriptor] {
+        &[
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Custom0,
+                data_type: VertexAttributeDataType::F32,
+                size: 4,
+                divisor: 1,
+                shader_location: 0,
+                normalized: false,
+            },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Custom1,
+                data_type: VertexAttributeDataType::F32,
+                size: 4,
+                divisor: 1,
+                shader_location: 1,
+                normalized: false,
+            },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Color,
+                data_type: VertexAttributeDataType::U8,
+                size: 4,
+                divisor: 1,
+                shader_location: 2,
+                normalized: true,
+            },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Custom2,
+                data_type: VertexAttributeDataType::F32,
+                size: 1,
+                divisor: 1,
+                shader_location: 3,
+                normalized: false,
+            },
+        ]
+    }
+}
+
+/// A set of tiles that share the same material, baked into a single surface.
+#[derive(Debug, Clone)]
+pub struct TileBatch {
+    /// Material of the tiles.
+    pub material: MaterialResource,
+    /// Vertices and triangles of the tiles (in local coordinates of the tile map).
+    pub data: SurfaceResource,
+    /// Amount of tiles in the batch, if the batch is drawn using hardware instancing. In this case
+    /// the vertex buffer of [`Self::data`] contains [`TileInstance`]s instead of vertices and the
+    /// material uses the standard tile shader (see [`ShaderResourceExtension::standard_tile`]).
+    /// Instancing is used only for tiles with materials, that use the standard 2D shader or the
+    /// standard tile array shader, tiles with any other materials are baked into vertices.
+    pub instance_count: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct ChunkRenderData {
+    pub tile_set_key: u64,
+    pub batches: Vec<TileBatch>,
+}
+
+/// Cached render data of a chunk. `None` means that the chunk must be rebuilt before rendering.
+#[derive(Debug, Default)]
+pub(crate) struct ChunkRenderCache(pub Mutex<Option<ChunkRenderData>>);
+
+impl Clone for ChunkRenderCache {
+    fn clone(&self) -> Self {
+        // Force the copy to rebuild its own render data, so the surfaces won't be shared.
+        Self::default()
+    }
+}
+
+/// A square piece of a tile map with the side of [`CHUNK_SIZE`] tiles. Every chunk caches the
+/// geometry of its tiles, the geometry is rebuilt only when the chunk is modified.
+#[derive(Clone, Default, Debug, Visit, Reflect)]
+pub struct TileMapChunk {
+    #[reflect(hidden)]
+    tiles: FxHashMap<Vector2<i32>, Tile>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) render_cache: ChunkRenderCache,
+}
+
+impl PartialEq for TileMapChunk {
+    fn eq(&self, other: &Self) -> bool {
+        self.tiles == other.tiles
+    }
+}
+
+impl TileMapChunk {
+    /// Returns a reference to the tiles of the chunk.
+    pub fn tiles(&self) -> &FxHashMap<Vector2<i32>, Tile> {
+        &self.tiles
+    }
+
+    /// Marks the chunk as modified, so its geometry will be rebuilt on the next frame.
+    pub fn invalidate(&self) {
+        *self.render_cache.0.lock() = None;
+    }
+
+    /// Returns bounding box of the chunk in local coordinates of the tile map.
+    pub fn local_bounding_box(
+        chunk_position: Vector2<i32>,
+        projection: TileMapProjection,
+    ) -> AxisAlignedBoundingBox {
+        // Every projection is linear along the grid axes (up to the shift of odd rows), so it is
+        // enough to take the corner tiles of the chunk.
+        let min = chunk_position * CHUNK_SIZE;
+        let max = min + Vector2::repeat(CHUNK_SIZE - 1);
+        let mut bounding_box = AxisAlignedBoundingBox::default();
+        for corner in [
+            min,
+            Vector2::new(max.x, min.y),
+            Vector2::new(min.x, max.y),
+            max,
+        ] {
+            let rect = projection.tile_rect(corner);
+            bounding_box.add_point(Vector3::new(rect.position.x, rect.position.y, 0.0));
+            bounding_box.add_point(Vector3::new(
+                rect.position.x + rect.size.x,
+                rect.position.y + rect.size.y,
+                0.0,
+            ));
+        }
+        bounding_box
+    }
+
+    pub(crate) fn build_render_data(
+        &self,
+        tile_set: &TileSet,
+        tile_set_key: u64,
+        projection: TileMapProjection,
+    ) -> ChunkRenderData {
+        let mut groups = FxHashMap::<u64, TileGroup>::default();
+        let packed = tile_set.packed();
+
+        for tile in self.tiles.values() {
+            let Some(tile_definition) = tile_set.tiles.get(tile.definition_index) else {
+                continue;
+            };
+
+            // Packed tile sets share the same material, so every chunk has just one batch.
+            let (material, rect, layer) = match packed {
+                Some(packed) => {
+                    let packed_tile = &packed.tiles[tile.definition_index];
+                    (&packed.material, &packed_tile.uv_rect, packed_tile.layer)
+                }
+                None => (&tile_definition.material, &tile_definition.uv_rect, 0),
+            };
+
+            let group = groups
+                .entry(material.key())
+                .or_insert_with(|| TileGroup::new(material));
+
+            let tile_rect = projection.tile_rect(tile.position);
+            let color = tile_definition.color;
+
+            if let Some(instances) = group.instances.as_mut() {
+                instances.push(TileInstance {
+                    rect: Vector4::new(
+                        tile_rect.position.x,
+                        tile_rect.position.y,
+                        tile_rect.size.x,
+                        tile_rect.size.y,
+                    ),
+                    uv_rect: Vector4::new(
+                        rect.position.x,
+                        rect.position.y,
+                        rect.size.x,
+                        rect.size.y,
+                    ),
+                    color,
+                    layer: layer as f32,
+                });
+                continue;
+            }
+
+            let corner = |dx: f32, dy: f32| {
+                Vector3::new(
+                    tile_rect.position.x + dx * tile_rect.size.x,
+                    tile_rect.position.y + dy * tile_rect.size.y,
+                    0.0,
+                )
+            };
+
+            let start = group.vertices.len() as u32;
+            group.vertices.extend_from_slice(&[
+                RectangleVertex {
+                    position: corner(0.0, 1.0),
+                    tex_coord: rect.right_top_corner(),
+                    color,
+                },
+                RectangleVertex {
+                    position: corner(1.0, 1.0),
+                    tex_coord: rect.left_top_corner(),
+                    color,
+                },
+                RectangleVertex {
+                    position: corner(1.0, 0.0),
+                    tex_coord: rect.left_bottom_corner(),
+                    color,
+                },
+                RectangleVertex {
+                    position: corner(0.0, 0.0),
+                    tex_coord: rect.right_bottom_corner(),
+                    color,
+                },
+            ]);
+            group
+                .triangles
+                .push(TriangleDefinition([start, start + 1, start + 2]));
+            group
+                .triangles
+                .push(TriangleDefinition([start + 2, start + 3, start]));
+        }
+
+        ChunkRenderData {
+            tile_set_key,
+            batches: groups
+                .into_values()
+                .filter_map(|group| group.into_batch())
+                .collect(),
+        }
+    }
+}
+
+/// Tiles of a chunk, that share the same material.
+struct TileGroup {
+    material: MaterialResource,
+    vertices: Vec<RectangleVertex>,
+    triangles: Vec<TriangleDefinition>,
+    /// `Some` if the tiles are drawn using hardware instancing.
+    instances: Option<Vec<TileInstance>>,
+}
+
+impl TileGroup {
+    fn new(material: &MaterialResource) -> Self {
+        match make_instanced_material(material) {
+            Some(instanced_material) => Self {
+                material: instanced_material,
+                vertices: Default::default(),
+                triangles: Default::default(),
+                instances: Some(Default::default()),
+            },
+            None => Self {
+                material: material.clone(),
+                vertices: Default::default(),
+                triangles: Default::default(),
+                instances: None,
+            },
+        }
+    }
+
+    fn into_batch(self) -> Option<TileBatch> {
+        let (vertex_buffer, triangles, instance_count) = match self.instances {
+            Some(instances) => {
+                let count = instances.len();
+                // A single quad, corners of which are defined by vertex indices in the shader.
+                let triangles = vec![TriangleDefinition([0, 1, 2]), TriangleDefinition([2, 3, 0])];
+                (
+                    VertexBuffer::new(count, instances).ok()?,
+                    triangles,
+                    Some(count),
+                )
+            }
+            None => (
+                VertexBuffer::new(self.vertices.len(), self.vertices).ok()?,
+                self.triangles,
+                None,
+            ),
+        };
+
+        Some(TileBatch {
+            material: self.material,
+            data: SurfaceResource::new_ok(
+                ResourceKind::Embedded,
+                SurfaceData::new(vertex_buffer, TriangleBuffer::new(triangles)),
+            ),
+            instance_count,
+        })
+    }
+}
+
+/// Creates a copy of the given material, that uses the standard tile shader. It is possible only if
+/// the material uses the standard 2D shader, because custom shaders cannot read per-instance data.
+/// Materials with the standard tile array shader are instanced already, so they're used as is.
+fn make_instanced_material(material_resource: &MaterialResource) -> Option<MaterialResource> {
+    let mut state = material_resource.state();
+    let material = state.data()?;
+    let shader_key = material.shader().key();
+    if shader_key == ShaderResource::standard_tile_array().key() {
+        return Some(material_resource.clone());
+    }
+    if shader_key != ShaderResource::standard_2d().key() {
+        return None;
+    }
+
+    let mut instanced_material = Material::from_shader(ShaderResource::standard_tile(), None);
+    for (name, value) in material.properties() {
+        // Both shaders have the same set of properties.
+        let _ = instanced_material.set_property(name, value.clone());
+    }
+
+    Some(MaterialResource::new_ok(
+        ResourceKind::Embedded,
+        instanced_material,
+    ))
+}
+
+/// Chunked tile storage. Tiles are split into square chunks (see [`CHUNK_SIZE`]), every chunk
+/// keeps its own cached geometry that is rebuilt only when some of its tiles were changed. This
+/// also allows the renderer to skip whole chunks that are outside of the view frustum.
+#[derive(Clone, Default, Debug, PartialEq, Visit, Reflect)]
+pub struct Tiles {
+    #[reflect(hidden)]
+    chunks: FxHashMap<Vector2<i32>, TileMapChunk>,
+}
+
+impl FromIterator<Tile> for Tiles {
+    fn from_iter<T: IntoIterator<Item = Tile>>(iter: T) -> Self {
+        let mut tiles = Tiles::default();
+        for tile in iter {
+            tiles.insert(tile);
+        }
+        tiles
+    }
+}
+
+impl Tiles {
+    /// Inserts a tile in the storage. If there's already a tile at the same position, it will be
+    /// replaced and returned.
+    pub fn insert(&mut self, tile: Tile) -> Option<Tile> {
+        let chunk = self
+            .chunks
+            .entry(chunk_position(tile.position))
+            .or_default();
+        chunk.invalidate();
+        chunk.tiles.insert(tile.position, tile)
+    }
+
+    /// Removes a tile at the given position. Empty chunks are removed as well.
+    pub fn remove(&mut self, position: Vector2<i32>) -> Option<Tile> {
+        let chunk_position = chunk_position(position);
+        let chunk = self.chunks.get_mut(&chunk_position)?;
+        let tile = chunk.tiles.remove(&position);
+        if tile.is_some() {
+            if chunk.tiles.is_empty() {
+                self.chunks.remove(&chunk_position);
+            } else {
+                chunk.invalidate();
+            }
+        }
+        tile
+    }
+
+    /// Returns a reference to a tile at the given position (if any).
+    pub fn get(&self, position: Vector2<i32>) -> Option<&Tile> {
+        self.chunks
+            .get(&chunk_position(position))
+            .and_then(|chunk| chunk.tiles.get(&position))
+    }
+
+    /// Removes all the tiles from the storage.
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+    }
+
+    /// Returns total amount of tiles in the storage.
+    pub fn len(&self) -> usize {
+        self.chunks.values().map(|chunk| chunk.tiles.len()).sum()
+    }
+
+    /// Returns `true` if the storage has no tiles, `false` - otherwise.
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Returns an iterator over all the tiles in the storage.
+    pub fn iter(&self) -> impl Iterator<Item = &Tile> {
+        self.chunks.values().flat_map(|chunk| chunk.tiles.values())
+    }
+
+    /// Returns a reference to the chunks of the storage.
+    pub fn chunks(&self) -> &FxHashMap<Vector2<i32>, TileMapChunk> {
+        &self.chunks
+    }
+
+    /// Marks all the chunks as modified, so their geometry will be rebuilt on the next frame.
+    pub fn invalidate(&self) {
+        for chunk in self.chunks.values() {
+            chunk.invalidate();
+        }
+    }
+
+    /// Returns bounding box of all the chunks in local coordinates of the tile map.
+    pub fn local_bounding_box(&self, projection: TileMapProjection) -> AxisAlignedBoundingBox {
+        let mut bounding_box = AxisAlignedBoundingBox::default();
+        for chunk_position in self.chunks.keys() {
+            bounding_box.add_box(TileMapChunk::local_bounding_box(
+                *chunk_position,
+                projection,
+            ));
+        }
+        bounding_box
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::untyped::ResourceKind,
+        core::{algebra::Vector2, math::Rect},
+        material::{Material, MaterialResource},
+        scene::tilemap::{
+            chunk::{chunk_position, Tiles, CHUNK_SIZE},
+            projection::TileMapProjection,
+            tileset::{TileDefinition, TileSet},
+            Tile,
+        },
+    };
+
+    #[test]
+    fn test_chunk_position() {
+        assert_eq!(chunk_position(Vector2::new(0, 0)), Vector2::new(0, 0));
+        assert_eq!(
+            chunk_position(Vector2::new(CHUNK_SIZE - 1, CHUNK_SIZE)),
+            Vector2::new(0, 1)
+        );
+        assert_eq!(
+            chunk_position(Vector2::new(-1, -CHUNK_SIZE)),
+            Vector2::new(-1, -1)
+        );
+        assert_eq!(
+            chunk_position(Vector2::new(-CHUNK_SIZE - 1, 0)),
+            Vector2::new(-2, 0)
+        );
+    }
+
+    #[test]
+    fn test_tiles() {
+        let mut tiles = [
+            Tile::new(Vector2::new(0, 0), 0),
+            Tile::new(Vector2::new(1, 0), 1),
+            Tile::new(Vector2::new(-1, 0), 2),
+        ]
+        .into_iter()
+        .collect::<Tiles>();
+
+        assert_eq!(tiles.len(), 3);
+        assert_eq!(tiles.chunks().len(), 2);
+        assert_eq!(tiles.get(Vector2::new(1, 0)).unwrap().definition_index(), 1);
+
+        let replaced = tiles.insert(Tile::new(Vector2::new(1, 0), 3)).unwrap();
+        assert_eq!(replaced.definition_index(), 1);
+        assert_eq!(tiles.len(), 3);
+
+        assert!(tiles.remove(Vector2::new(-1, 0)).is_some());
+        assert_eq!(tiles.chunks().len(), 1);
+        assert!(tiles.remove(Vector2::new(-1, 0)).is_none());
+        assert!(tiles.get(Vector2::new(-1, 0)).is_none());
+
+        let bounds = tiles.local_bounding_box(TileMapProjection::Orthogonal);
+        assert_eq!(bounds.min.x, 0.0);
+        assert_eq!(bounds.max.x, CHUNK_SIZE as f32);
+    }
+
+    #[test]
+    fn test_instanced_batches() {
+        let definition = |material: Material| TileDefinition {
+            material: MaterialResource::new_ok(ResourceKind::Embedded, material),
+            uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+            collider: Default::default(),
+            color: Default::default(),
+        };
+        let tile_set = TileSet::new(vec![
+            definition(Material::standard_2d()),
+            definition(Material::standard_sprite()),
+        ]);
+
+        let tiles = [
+            Tile::new(Vector2::new(0, 0), 0),
+            Tile::new(Vector2::new(1, 0), 0),
+            Tile::new(Vector2::new(2, 0), 0),
+            Tile::new(Vector2::new(3, 0), 1),
+        ]
+        .into_iter()
+        .collect::<Tiles>();
+
+        let chunk = tiles.chunks().values().next().unwrap();
+        let render_data = chunk.build_render_data(&tile_set, 0, TileMapProjection::Orthogonal);
+        assert_eq!(render_data.batches.len(), 2);
+
+        // Tiles with the standard 2D material are drawn using instancing.
+        let instanced = render_data
+            .batches
+            .iter()
+            .find(|b| b.instance_count.is_some())
+            .unwrap();
+        assert_eq!(instanced.instance_count, Some(3));
+        assert_eq!(instanced.data.data_ref().vertex_buffer.vertex_count(), 3);
+
+        // Custom materials are baked into vertices.
+        let baked = render_data
+            .batches
+            .iter()
+            .find(|b| b.instance_count.is_none())
+            .unwrap();
+        assert_eq!(baked.data.data_ref().vertex_buffer.vertex_count(), 4);
+    }
+}
@@ -1,29 +1,45 @@
 #![allow(missing_docs)] // TODO
 
+pub mod chunk;
+pub mod packing;
+pub mod projection;
 pub mod tileset;
 
 use crate::{
     asset::untyped::ResourceKind,
     core::{
-        algebra::{Vector2, Vector3},
-        math::{aabb::AxisAlignedBoundingBox, Rect, TriangleDefinition},
+        algebra::{Point3, Vector2, Vector3},
+        math::{aabb::AxisAlignedBoundingBox, Rect},
         pool::Handle,
         reflect::prelude::*,
         type_traits::prelude::*,
-        value_as_u8_slice,
         variable::InheritableVariable,
         visitor::prelude::*,
     },
-    graph::BaseSceneGraph,
+    fxhash::FxHashSet,
+    graph::{BaseSceneGraph, SceneGraph},
     material::{Material, MaterialResource},
-    renderer::{self, bundle::RenderContext},
+    renderer::{
+        self,
+        bundle::{PersistentIdentifier, RenderContext, SurfaceInstanceData},
+        framework::geometry_buffer::ElementRange,
+    },
     scene::{
         base::{Base, BaseBuilder},
-        dim2::rectangle::RectangleVertex,
+        dim2::{
+            collider::{ColliderBuilder, ColliderShape},
+            rigidbody::RigidBodyBuilder,
+        },
         graph::Graph,
-        mesh::{buffer::VertexTrait, RenderPath},
+        mesh::RenderPath,
         node::{Node, NodeTrait, RdcControlFlow},
-        tilemap::tileset::{TileDefinition, TileSet, TileSetResource},
+        rigidbody::RigidBodyType,
+        tilemap::{
+            chunk::{TileMapChunk, Tiles},
+            projection::TileMapProjection,
+            tileset::{TileCollider, TileDefinition, TileSet, TileSetResource},
+        },
+        transform::TransformBuilder,
     },
 };
 use std::ops::{Deref, DerefMut};
@@ -35,14 +51,38 @@ pub struct Tile {
     definition_index: usize,
 }
 
+impl Tile {
+    /// Creates a new tile at the given grid position, that uses a tile definition with the given
+    /// index in the tile set of the tile map.
+    pub fn new(position: Vector2<i32>, definition_index: usize) -> Self {
+        Self {
+            position,
+            definition_index,
+        }
+    }
+
+    /// Returns the position of the tile on the grid of the tile map.
+    pub fn position(&self) -> Vector2<i32> {
+        self.position
+    }
+
+    /// Returns an index of the tile definition in the tile set of the tile map.
+    pub fn definition_index(&self) -> usize {
+        self.definition_index
+    }
+}
+
 #[derive(Clone, Reflect, Debug, Visit, ComponentProvider, TypeUuidProvider)]
 #[type_uuid(id = "aa9a3385-a4af-4faf-a69a-8d3af1a3aa67")]
 pub struct TileMap {
     base: Base,
     tile_set: InheritableVariable<Option<TileSetResource>>,
     #[reflect(read_only)]
-    tiles: InheritableVariable<Vec<Tile>>,
+    tiles: InheritableVariable<Tiles>,
     tile_scale: InheritableVariable<Vector2<f32>>,
+    #[reflect(setter = "set_projection")]
+    #[visit(optional)]
+    projection: InheritableVariable<TileMapProjection>,
 }
 
 impl Default for TileMap {
@@ -52,6 +92,7 @@ impl Default for TileMap {
             tile_set: Default::default(),
             tiles: Default::default(),
             tile_scale: Vector2::repeat(1.0).into(),
+            projection: Default::default(),
         }
     }
 }
@@ -70,11 +111,257 @@ impl DerefMut for TileMap {
     }
 }
 
+impl TileMap {
+    /// Returns a reference to the tile set of the tile map.
+    pub fn tile_set(&self) -> Option<&TileSetResource> {
+        (*self.tile_set).as_ref()
+    }
+
+    /// Sets new tile set of the tile map.
+    pub fn set_tile_set(&mut self, tile_set: Option<TileSetResource>) {
+        self.tile_set.set_value_and_mark_modified(tile_set);
+        self.tiles.invalidate();
+    }
+
+    /// Returns a reference to the tile storage.
+    pub fn tiles(&self) -> &Tiles {
+        &self.tiles
+    }
+
+    /// Replaces all the tiles of the tile map.
+    pub fn set_tiles(&mut self, tiles: Tiles) {
+        self.tiles.set_value_and_mark_modified(tiles);
+    }
+
+    /// Inserts a tile in the tile map. If there's already a tile at the same position, it will be
+    /// replaced and returned. Only the chunk that contains the tile will be rebuilt.
+    pub fn insert_tile(&mut self, tile: Tile) -> Option<Tile> {
+        self.tiles.insert(tile)
+    }
+
+    /// Removes a tile at the given position.
+    pub fn remove_tile(&mut self, position: Vector2<i32>) -> Option<Tile> {
+        self.tiles.remove(position)
+    }
+
+    /// Returns a reference to a tile at the given position (if any).
+    pub fn tile(&self, position: Vector2<i32>) -> Option<&Tile> {
+        self.tiles.get(position)
+    }
+
+    /// Forces the tile map to rebuild the geometry of all its chunks. Could be useful if the
+    /// tile set was modified.
+    pub fn invalidate_render_cache(&self) {
+        self.tiles.invalidate();
+    }
+
+    /// Returns current projection of the tile map.
+    pub fn projection(&self) -> TileMapProjection {
+        *self.projection
+    }
+
+    /// Sets a new projection of the tile map. See [`TileMapProjection`] docs for more info.
+    pub fn set_projection(&mut self, projection: TileMapProjection) -> TileMapProjection {
+        let prev = self.projection.set_value_and_mark_modified(projection);
+        self.tiles.invalidate();
+        prev
+    }
+
+    /// Returns a grid position of a tile that contains the given point in world coordinates. The
+    /// point is projected on the plane of the tile map. Could be used to pick tiles.
+    pub fn world_to_grid(&self, point: Vector3<f32>) -> Vector2<i32> {
+        let local = self
+            .global_transform()
+            .try_inverse()
+            .unwrap_or_default()
+            .transform_point(&Point3::from(point));
+        self.projection.local_to_grid(local.xy().coords)
+    }
+
+    /// Returns the center of a tile at the given grid position in world coordinates.
+    pub fn grid_to_world(&self, position: Vector2<i32>) -> Vector3<f32> {
+        let local = self.projection.grid_to_local(position);
+        self.global_transform()
+            .transform_point(&Point3::new(local.x, local.y, 0.0))
+            .coords
+    }
+
+    /// Returns a set of rectangles (in grid coordinates) that covers all the solid tiles of the
+    /// tile map. A tile is solid, if its definition in the tile set has a collider (see
+    /// [`TileCollider`]). Adjacent solid tiles are merged into larger rectangles to reduce the
+    /// amount of colliders. Tiles of staggered and hexagonal tile maps cannot be merged, so every
+    /// tile has its own rectangle. Returns an empty set if the tile set is not loaded.
+    pub fn collision_rects(&self) -> Vec<Rect<i32>> {
+        let Some(tile_set) = self.tile_set.as_ref() else {
+            return Vec::new();
+        };
+
+        if !tile_set.is_ok() {
+            return Vec::new();
+        }
+
+        let tile_set = tile_set.data_ref();
+        let solid = self
+            .tiles
+            .iter()
+            .filter(|tile| {
+                tile_set
+                    .tiles
+                    .get(tile.definition_index)
+                    .map_or(false, |definition| {
+                        definition.collider != TileCollider::None
+                    })
+            })
+            .map(|tile| tile.position)
+            .collect::<FxHashSet<_>>();
+
+        match *self.projection {
+            TileMapProjection::Orthogonal | TileMapProjection::Isometric => merge_tiles(solid),
+            TileMapProjection::Staggered | TileMapProjection::Hexagonal => solid
+                .into_iter()
+                .map(|position| Rect::new(position.x, position.y, 1, 1))
+                .collect(),
+        }
+    }
+
+    /// Returns counter-clockwise outline of the given rectangle (see [`Self::collision_rects`]) in
+    /// local coordinates of the tile map.
+    fn rect_outline(&self, rect: Rect<i32>) -> Vec<Vector2<f32>> {
+        let projection = *self.projection;
+        match projection {
+            TileMapProjection::Orthogonal | TileMapProjection::Isometric => {
+                // Both projections are linear, so the outline of a rectangle is formed by the
+                // outer corners of its corner tiles.
+                let min = rect.position;
+                let max = rect.position + rect.size - Vector2::repeat(1);
+                vec![
+                    projection.tile_outline(min)[0],
+                    projection.tile_outline(Vector2::new(max.x, min.y))[1],
+                    projection.tile_outline(max)[2],
+                    projection.tile_outline(Vector2::new(min.x, max.y))[3],
+                ]
+            }
+            TileMapProjection::Staggered | TileMapProjection::Hexagonal => {
+                projection.tile_outline(rect.position)
+            }
+        }
+    }
+
+    /// Generates a static 2D rigid body with a set of colliders that cover all the solid tiles of
+    /// the given tile map (see [`Self::collision_rects`]). Orthogonal tile maps use rectangular
+    /// colliders, other projections use sets of triangles that form the outline of tiles. The
+    /// rigid body is
+    /// attached to the tile map, the colliders are placed in local coordinates of the tile map.
+    /// Keep in mind, that colliders cannot be scaled, so the tile map should not be scaled
+    /// either. Call this method again (and remove the previous body) if the tiles were changed.
+    ///
+    /// Returns `None` if the given handle does not point to a tile map, or it has no solid
+    /// tiles.
+    pub fn generate_colliders(graph: &mut Graph, tile_map: Handle<Node>) -> Option<Handle<Node>> {
+        let tile_map_ref = graph.try_get_of_type::<TileMap>(tile_map)?;
+        let projection = tile_map_ref.projection();
+        let outlines = tile_map_ref
+            .collision_rects()
+            .into_iter()
+            .map(|rect| tile_map_ref.rect_outline(rect))
+            .collect::<Vec<_>>();
+
+        if outlines.is_empty() {
+            return None;
+        }
+
+        let mut colliders = Vec::new();
+        for outline in outlines {
+            if projection == TileMapProjection::Orthogonal {
+                let half_size = (outline[2] - outline[0]).scale(0.5);
+                let center = outline[0] + half_size;
+                colliders.push(
+                    ColliderBuilder::new(
+                        BaseBuilder::new().with_local_transform(
+                            TransformBuilder::new()
+                                .with_local_position(Vector3::new(center.x, center.y, 0.0))
+                                .build(),
+                        ),
+                    )
+                    .with_shape(ColliderShape::cuboid(half_size.x, half_size.y))
+                    .build(graph),
+                );
+            } else {
+                // Outlines are convex, so they could be triangulated as a fan.
+                for i in 1..outline.len() - 1 {
+                    colliders.push(
+                        ColliderBuilder::new(BaseBuilder::new())
+                            .with_shape(ColliderShape::triangle(
+                                outline[0],
+                                outline[i],
+                                outline[i + 1],
+                            ))
+                            .build(graph),
+                    );
+                }
+            }
+        }
+
+        let body = RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_name("TileMapColliders")
+                .with_children(&colliders),
+        )
+        .with_body_type(RigidBodyType::Static)
+        .build(graph);
+
+        graph.link_nodes(body, tile_map);
+
+        Some(body)
+    }
+}
+
+/// Merges the given set of tile positions into a set of non-overlapping rectangles. It uses
+/// greedy approach: it takes the lowest (by Y, then by X) tile, expands it as far as possible
+/// along X axis, then expands the resulting row along Y axis.
+fn merge_tiles(mut tiles: FxHashSet<Vector2<i32>>) -> Vec<Rect<i32>> {
+    let mut positions = tiles.iter().cloned().collect::<Vec<_>>();
+    positions.sort_unstable_by_key(|p| (p.y, p.x));
+
+    let mut rects = Vec::new();
+    for start in positions {
+        if !tiles.remove(&start) {
+            // Already covered by some other rectangle.
+            continue;
+        }
+
+        let mut width = 1;
+        while tiles.remove(&Vector2::new(start.x + width, start.y)) {
+            width += 1;
+        }
+
+        let mut height = 1;
+        loop {
+            let y = start.y + height;
+            if !(0..width).all(|dx| tiles.contains(&Vector2::new(start.x + dx, y))) {
+                break;
+            }
+            for dx in 0..width {
+                tiles.remove(&Vector2::new(start.x + dx, y));
+            }
+            height += 1;
+        }
+
+        rects.push(Rect::new(start.x, start.y, width, height));
+    }
+
+    rects
+}
+
 impl NodeTrait for TileMap {
     crate::impl_query_component!();
 
     fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
-        AxisAlignedBoundingBox::unit()
+        if self.tiles.is_empty() {
+            AxisAlignedBoundingBox::unit()
+        } else {
+            self.tiles.local_bounding_box(*self.projection)
+        }
     }
 
     fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
@@ -109,75 +396,73 @@ impl NodeTrait for TileMap {
             return RdcControlFlow::Continue;
         }
 
+        let tile_set_key = tile_set_resource.key();
         let tile_set = tile_set_resource.data_ref();
-
-        for tile in self.tiles.iter() {
-            let Some(tile_definition) = tile_set.tiles.get(tile.definition_index) else {
+        let global_transform = self.global_transform();
+        let sort_index = ctx.calculate_sorting_index(self.global_position());
+
+        for (chunk_position, chunk) in self.tiles.chunks() {
+            if self.frustum_culling()
+                && !ctx.frustum.map_or(true, |f| {
+                    f.is_intersects_aabb(
+                        &TileMapChunk::local_bounding_box(*chunk_position, *self.projection)
+                            .transform(&global_transform),
+                    )
+                })
+            {
+                continue;
+            }
+
+            let mut render_cache = chunk.render_cache.0.lock();
+            if render_cache
+                .as_ref()
+                .map_or(true, |data| data.tile_set_key != tile_set_key)
+            {
+                *render_cache =
+                    Some(chunk.build_render_data(&tile_set, tile_set_key, *self.projection));
+            }
+
+            let Some(render_data) = render_cache.as_ref() else {
                 continue;
             };
 
-            let global_transform = self.global_transform();
-
-            type Vertex = RectangleVertex;
-
-            let position = tile.position.cast::<f32>().to_homogeneous();
-
-            let vertices = [
-                Vertex {
-                    position: global_transform
-                        .transform_point(&(position + Vector3::new(0.0, 1.0, 0.0)).into())
-                        .coords,
-                    tex_coord: tile_definition.uv_rect.right_top_corner(),
-                    color: tile_definition.color,
-                },
-                Vertex {
-                    position: global_transform
-                        .transform_point(&(position + Vector3::new(1.0, 1.0, 0.0)).into())
-                        .coords,
-                    tex_coord: tile_definition.uv_rect.left_top_corner(),
-                    color: tile_definition.color,
-                },
-                Vertex {
-                    position: global_transform
-                        .transform_point(&(position + Vector3::new(1.00, 0.0, 0.0)).into())
-                        .coords,
-                    tex_coord: tile_definition.uv_rect.left_bottom_corner(),
-                    color: tile_definition.color,
-                },
-                Vertex {
-                    position: global_transform
-                        .transform_point(&(position + Vector3::new(0.0, 0.0, 0.0)).into())
-                        .coords,
-                    tex_coord: tile_definition.uv_rect.right_bottom_corner(),
-                    color: tile_definition.color,
-                },
-            ];
-
-            let triangles = [TriangleDefinition([0, 1, 2]), TriangleDefinition([2, 3, 0])];
-
-            let sort_index = ctx.calculate_sorting_index(self.global_position());
-
-            ctx.storage.push_triangles(
-                RectangleVertex::layout(),
-                &tile_definition.material,
-                RenderPath::Forward,
-                0,
-                sort_index,
-                false,
-                self.self_handle,
-                &mut move |mut vertex_buffer, mut triangle_buffer| {
-                    let start_vertex_index = vertex_buffer.vertex_count();
-
-                    for vertex in vertices.iter() {
-                        vertex_buffer
-                            .push_vertex_raw(value_as_u8_slice(vertex))
-                            .unwrap();
-                    }
-
-                    triangle_buffer
-                        .push_triangles_iter_with_offset(start_vertex_index, triangles.into_iter());
-                },
-            );
+            for (index, batch) in render_data.batches.iter().enumerate() {
+                let instance_data = SurfaceInstanceData {
+                    world_transform: global_transform,
+                    bone_matrices: Default::default(),
+                    prev_world_transform: global_transform,
+                    prev_bone_matrices: Default::default(),
+                    depth_offset: 0.0,
+                    blend_shapes_weights: Default::default(),
+                    element_range: ElementRange::Full,
+                    persistent_identifier: PersistentIdentifier::new_combined(
+                        &batch.data,
+                        self.self_handle,
+                        index,
+                    ),
+                    node_handle: self.self_handle,
+                };
+
+                match batch.instance_count {
+                    // The entire batch is drawn using a single instanced draw call.
+                    Some(count) => ctx.storage.push_instanced(
+                        &batch.data,
+                        &batch.material,
+                        RenderPath::Forward,
+                        sort_index,
+                        count,
+                        instance_data,
+                    ),
+                    None => ctx.storage.push(
+                        &batch.data,
+                        &batch.material,
+                        RenderPath::Forward,
+                        0,
+                        sort_index,
+                        instance_data,
+                    ),
+                }
+            }
         }
 
         RdcControlFlow::Continue
@@ -189,19 +474,18 @@ pub struct TileMapBuilder {
     tile_set: Option<TileSetResource>,
     tiles: Vec<Tile>,
     tile_scale: Vector2<f32>,
+    projection: TileMapProjection,
 }
 
 impl TileMapBuilder {
     pub fn new(base_builder: BaseBuilder) -> Self {
         // TODO: testing
-        let tile_set = TileSet {
-            tiles: vec![TileDefinition {
-                material: MaterialResource::new_ok(ResourceKind::Embedded, Material::standard_2d()),
-                uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
-                collider: Default::default(),
-                color: Default::default(),
-            }],
-        };
+        let tile_set = TileSet::new(vec![TileDefinition {
+            material: MaterialResource::new_ok(ResourceKind::Embedded, Material::standard_2d()),
+            uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+            collider: Default::default(),
+            color: Default::default(),
+        }]);
 
         let tile_set = Some(TileSetResource::new_ok(ResourceKind::Embedded, tile_set));
 
@@ -226,6 +510,7 @@ impl TileMapBuilder {
             tile_set,
             tiles,
             tile_scale: Vector2::repeat(1.0),
+            projection: Default::default(),
         }
     }
 
@@ -244,12 +529,18 @@ impl TileMapBuilder {
         self
     }
 
+    pub fn with_projection(mut self, projection: TileMapProjection) -> Self {
+        self.projection = projection;
+        self
+    }
+
     pub fn build_node(self) -> Node {
         Node::new(TileMap {
             base: self.base_builder.build_base(),
             tile_set: self.tile_set.into(),
-            tiles: self.tiles.into(),
+            tiles: self.tiles.into_iter().collect::<Tiles>().into(),
             tile_scale: self.tile_scale.into(),
+            projection: self.projection.into(),
         })
     }
 
@@ -257,3 +548,100 @@ impl TileMapBuilder {
         graph.add_node(self.build_node())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::untyped::ResourceKind,
+        core::{algebra::Vector2, math::Rect},
+        graph::SceneGraph,
+        scene::{
+            base::BaseBuilder,
+            dim2::{collider::Collider, rigidbody::RigidBody},
+            graph::Graph,
+            tilemap::{
+                merge_tiles,
+                projection::TileMapProjection,
+                tileset::{TileCollider, TileDefinition, TileSet, TileSetResource},
+                Tile, TileMap, TileMapBuilder,
+            },
+        },
+    };
+
+    #[test]
+    fn test_merge_tiles() {
+        // ##.
+        // ###
+        let tiles = [(0, 0), (1, 0), (2, 0), (0, 1), (1, 1)]
+            .into_iter()
+            .map(|(x, y)| Vector2::new(x, y))
+            .collect();
+        let mut rects = merge_tiles(tiles);
+        rects.sort_by_key(|r| (r.position.y, r.position.x));
+        assert_eq!(rects, vec![Rect::new(0, 0, 3, 1), Rect::new(0, 1, 2, 1)]);
+
+        let tiles = (0..4)
+            .flat_map(|x| (-2..2).map(move |y| Vector2::new(x, y)))
+            .collect();
+        assert_eq!(merge_tiles(tiles), vec![Rect::new(0, -2, 4, 4)]);
+    }
+
+    #[test]
+    fn test_generate_colliders() {
+        let tile_set = TileSet::new(vec![
+            TileDefinition {
+                collider: TileCollider::Rectangle,
+                ..Default::default()
+            },
+            TileDefinition {
+                collider: TileCollider::None,
+                ..Default::default()
+            },
+        ]);
+
+        let mut graph = Graph::new();
+        let tile_map = TileMapBuilder::new(BaseBuilder::new())
+            .with_tile_set(TileSetResource::new_ok(ResourceKind::Embedded, tile_set))
+            .with_tiles(vec![
+                Tile::new(Vector2::new(0, 0), 0),
+                Tile::new(Vector2::new(1, 0), 0),
+                Tile::new(Vector2::new(2, 0), 1),
+                Tile::new(Vector2::new(5, 5), 0),
+            ])
+            .build(&mut graph);
+
+        assert_eq!(
+            graph[tile_map]
+                .cast::<TileMap>()
+                .unwrap()
+                .collision_rects()
+                .len(),
+            2
+        );
+
+        let body = TileMap::generate_colliders(&mut graph, tile_map).unwrap();
+        assert_eq!(graph[body].parent(), tile_map);
+        assert!(graph.try_get_of_type::<RigidBody>(body).is_some());
+        let colliders = graph[body].children();
+        assert_eq!(colliders.len(), 2);
+        assert!(colliders
+            .iter()
+            .all(|c| graph.try_get_of_type::<Collider>(*c).is_some()));
+    }
+
+    #[test]
+    fn test_world_to_grid() {
+        let mut graph = Graph::new();
+        let tile_map = TileMapBuilder::new(BaseBuilder::new())
+            .with_projection(TileMapProjection::Hexagonal)
+            .build(&mut graph);
+        graph.update_hierarchical_data();
+
+        let tile_map = graph[tile_map].cast::<TileMap>().unwrap();
+        let position = Vector2::new(3, -7);
+        assert_eq!(
+            tile_map.world_to_grid(tile_map.grid_to_world(position)),
+            position
+        );
+    }
+}
@@ -0,0 +1,474 @@
+//! Packing of the textures of a tile set into a single texture. See [`TileSetPacking`] docs for more
+//! info.
+
+use crate::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::Vector2, math::Rect, reflect::prelude::*, sstorage::ImmutableString,
+        type_traits::prelude::*, visitor::prelude::*,
+    },
+    material::{
+        shader::{ShaderResource, ShaderResourceExtension},
+        Material, MaterialResource,
+    },
+    resource::texture::{
+        Texture, TextureKind, TextureMagnificationFilter, TextureMinificationFilter,
+        TexturePixelKind, TextureResource, TextureWrapMode,
+    },
+    scene::tilemap::tileset::TileDefinition,
+};
+use std::fmt::{Display, Formatter};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines how the textures of a tile set are packed (see [`super::tileset::TileSet::pack`]).
+/// Packing takes the region of the diffuse texture of every tile (defined by its material and uv
+/// rectangle) and copies it into a single generated texture, so the whole tile map could be drawn
+/// with a single texture binding and no UV bleeding between adjacent tiles.
+#[derive(
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Debug,
+    Default,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+)]
+#[type_uuid(id = "c4b1e1a8-0f3d-4d57-9b0e-5b7fb7b8d2a6")]
+pub enum TileSetPacking {
+    /// Tiles are rendered using their own materials as is.
+    #[default]
+    None,
+    /// Tiles are packed into a single atlas texture. Every tile is surrounded by a gutter of the
+    /// given width (in pixels), that is filled with the edge pixels of the tile. It prevents
+    /// texture filtering from fetching pixels of neighbouring tiles, when a tile map is drawn at
+    /// non-integer zoom.
+    Atlas {
+        /// Width of the gutter around every tile in pixels.
+        padding: u32,
+    },
+    /// Every tile is copied to its own layer of a texture array (see [`TextureKind::Array`]).
+    /// Layers are sampled separately, so there is no bleeding at all. All layers have the same
+    /// size, smaller tiles are stretched to the size of the largest one.
+    TextureArray,
+}
+
+/// Location of a tile in the packed texture.
+#[derive(Copy, Clone, Default, PartialEq, Debug, Visit, Reflect)]
+pub struct PackedTile {
+    /// Texture coordinates of the tile. For texture arrays it always covers the whole layer.
+    pub uv_rect: Rect<f32>,
+    /// Index of the layer of the texture array, it is always zero for atlases.
+    pub layer: u32,
+}
+
+/// Result of tile set packing. It contains the material, that should be used to render every tile
+/// of the tile set, and the location of every tile in the packed texture.
+#[derive(Clone, Default, PartialEq, Debug, Visit, Reflect)]
+pub struct PackedTileSet {
+    /// Shared material of all the tiles. Atlases use the standard 2D shader, texture arrays use
+    /// the standard tile array shader (see [`crate::material::shader::ShaderResourceExtension::standard_tile_array`]).
+    pub material: MaterialResource,
+    /// Location of every tile, in the same order as the tiles of the tile set.
+    pub tiles: Vec<PackedTile>,
+}
+
+/// An error that may occur during tile set packing.
+#[derive(Debug, PartialEq)]
+pub enum TileSetPackingError {
+    /// The tile at the given index has no diffuse texture.
+    NoTexture(usize),
+    /// The texture of the tile at the given index is not loaded.
+    TextureNotLoaded(usize),
+    /// The texture of the tile at the given index is not a 2D texture or it has unsupported pixel
+    /// format. Only uncompressed 8-bit formats are supported.
+    UnsupportedTexture(usize),
+}
+
+impl Display for TileSetPackingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoTexture(index) => {
+                write!(f, "Tile {index} has no diffuse texture.")
+            }
+            Self::TextureNotLoaded(index) => {
+                write!(f, "Texture of tile {index} is not loaded.")
+            }
+            Self::UnsupportedTexture(index) => {
+                write!(
+                    f,
+                    "Texture of tile {index} is not a 2D texture with uncompressed 8-bit pixels."
+                )
+            }
+        }
+    }
+}
+
+/// RGBA8 pixels of a tile.
+struct TileImage {
+    size: Vector2<u32>,
+    pixels: Vec<[u8; 4]>,
+}
+
+impl TileImage {
+    /// Fetches a pixel with the coordinates clamped to the image bounds.
+    fn pixel_clamped(&self, x: i64, y: i64) -> [u8; 4] {
+        let x = x.clamp(0, self.size.x as i64 - 1) as u32;
+        let y = y.clamp(0, self.size.y as i64 - 1) as u32;
+        self.pixels[(y * self.size.x + x) as usize]
+    }
+}
+
+struct SourceInfo {
+    minification_filter: TextureMinificationFilter,
+    magnification_filter: TextureMagnificationFilter,
+}
+
+fn decode_pixel(pixel_kind: TexturePixelKind, bytes: &[u8]) -> Option<[u8; 4]> {
+    Some(match pixel_kind {
+        TexturePixelKind::RGBA8 => [bytes[0], bytes[1], bytes[2], bytes[3]],
+        TexturePixelKind::BGRA8 => [bytes[2], bytes[1], bytes[0], bytes[3]],
+        TexturePixelKind::RGB8 => [bytes[0], bytes[1], bytes[2], 255],
+        TexturePixelKind::BGR8 => [bytes[2], bytes[1], bytes[0], 255],
+        TexturePixelKind::R8 => [bytes[0], 0, 0, 255],
+        TexturePixelKind::Luminance8 => [bytes[0], bytes[0], bytes[0], 255],
+        TexturePixelKind::LuminanceAlpha8 => [bytes[0], bytes[0], bytes[0], bytes[1]],
+        TexturePixelKind::RG8 => [bytes[0], bytes[1], 0, 255],
+        _ => return None,
+    })
+}
+
+/// Copies the region of the diffuse texture of the tile, that is covered by its uv rectangle.
+fn read_tile_image(
+    index: usize,
+    definition: &TileDefinition,
+) -> Result<(TileImage, SourceInfo), TileSetPackingError> {
+    let texture = definition
+        .material
+        .state()
+        .data()
+        .and_then(|material| {
+            material
+                .property_ref(&ImmutableString::new("diffuseTexture"))
+                .and_then(|property| property.as_sampler())
+        })
+        .ok_or(TileSetPackingError::NoTexture(index))?;
+
+    let mut state = texture.state();
+    let texture = state
+        .data()
+        .ok_or(TileSetPackingError::TextureNotLoaded(index))?;
+
+    let size = texture
+        .kind()
+        .rectangle_size()
+        .ok_or(TileSetPackingError::UnsupportedTexture(index))?;
+    let pixel_kind = texture.pixel_kind();
+    let pixel_size = pixel_kind
+        .size_in_bytes()
+        .filter(|_| decode_pixel(pixel_kind, &[0; 4]).is_some())
+        .ok_or(TileSetPackingError::UnsupportedTexture(index))?;
+    let data = texture.mip_level_data(0);
+
+    // Texture coordinates are converted to whole pixels, so the region could be copied exactly.
+    let uv_rect = &definition.uv_rect;
+    let x0 = (uv_rect.position.x * size.x as f32).round() as i64;
+    let y0 = (uv_rect.position.y * size.y as f32).round() as i64;
+    let width = ((uv_rect.size.x * size.x as f32).round() as i64).max(1);
+    let height = ((uv_rect.size.y * size.y as f32).round() as i64).max(1);
+
+    let mut pixels = Vec::with_capacity((width * height) as usize);
+    for y in y0..y0 + height {
+        for x in x0..x0 + width {
+            let x = x.clamp(0, size.x as i64 - 1) as usize;
+            let y = y.clamp(0, size.y as i64 - 1) as usize;
+            let offset = (y * size.x as usize + x) * pixel_size;
+            let pixel = decode_pixel(pixel_kind, &data[offset..offset + pixel_size])
+                .ok_or(TileSetPackingError::UnsupportedTexture(index))?;
+            pixels.push(pixel);
+        }
+    }
+
+    Ok((
+        TileImage {
+            size: Vector2::new(width as u32, height as u32),
+            pixels,
+        },
+        SourceInfo {
+            minification_filter: texture.minification_filter(),
+            magnification_filter: texture.magnification_filter(),
+        },
+    ))
+}
+
+fn make_texture(kind: TextureKind, pixels: Vec<[u8; 4]>, source: &SourceInfo) -> TextureResource {
+    let bytes = pixels.into_iter().flatten().collect::<Vec<_>>();
+    let mut texture = Texture::from_bytes(kind, TexturePixelKind::RGBA8, bytes)
+        .expect("Packed texture data must match its size!");
+    // Packed texture has no mips.
+    texture.set_minification_filter(match source.minification_filter {
+        TextureMinificationFilter::Nearest
+        | TextureMinificationFilter::NearestMipMapNearest
+        | TextureMinificationFilter::NearestMipMapLinear => TextureMinificationFilter::Nearest,
+        _ => TextureMinificationFilter::Linear,
+    });
+    texture.set_magnification_filter(source.magnification_filter);
+    texture.set_s_wrap_mode(TextureWrapMode::ClampToEdge);
+    texture.set_t_wrap_mode(TextureWrapMode::ClampToEdge);
+    TextureResource::new_ok(ResourceKind::Embedded, texture)
+}
+
+fn make_material(shader: ShaderResource, texture: TextureResource) -> MaterialResource {
+    let mut material = Material::from_shader(shader, None);
+    material
+        .set_texture(&ImmutableString::new("diffuseTexture"), Some(texture))
+        .expect("Standard tile shaders must have diffuseTexture property!");
+    MaterialResource::new_ok(ResourceKind::Embedded, material)
+}
+
+/// Returns the amount of columns and rows of a grid, that could fit the given amount of cells and
+/// is as close to a square as possible. Wider grids are preferred.
+fn grid_size(count: u32, cell_size: Vector2<u32>) -> Vector2<u32> {
+    let count = count.max(1);
+    let mut best = Vector2::new(count, 1);
+    let mut best_side = u32::MAX;
+    for columns in 1..=count {
+        let rows = (count + columns - 1) / columns;
+        let side = (columns * cell_size.x).max(rows * cell_size.y);
+        if side <= best_side {
+            best_side = side;
+            best = Vector2::new(columns, rows);
+        }
+    }
+    best
+}
+
+fn pack_atlas(images: &[TileImage], padding: u32, source: &SourceInfo) -> PackedTileSet {
+    let max_size = images
+        .iter()
+        .fold(Vector2::new(1, 1), |size, image| size.sup(&image.size));
+    let cell_size = max_size + Vector2::repeat(padding * 2);
+    let grid = grid_size(images.len() as u32, cell_size);
+    let atlas_size = grid.component_mul(&cell_size);
+
+    let mut pixels = vec![[0u8; 4]; (atlas_size.x * atlas_size.y) as usize];
+    let mut tiles = Vec::with_capacity(images.len());
+    for (index, image) in images.iter().enumerate() {
+        let index = index as u32;
+        let cell_origin = Vector2::new(index % grid.x, index / grid.x).component_mul(&cell_size);
+
+        // Copy the tile with its gutter, gutter pixels repeat the nearest edge pixel of the tile.
+        let padding = padding as i64;
+        for y in -padding..image.size.y as i64 + padding {
+            for x in -padding..image.size.x as i64 + padding {
+                let atlas_x = cell_origin.x as i64 + padding + x;
+                let atlas_y = cell_origin.y as i64 + padding + y;
+                pixels[(atlas_y * atlas_size.x as i64 + atlas_x) as usize] =
+                    image.pixel_clamped(x, y);
+            }
+        }
+
+        let origin = cell_origin + Vector2::repeat(padding as u32);
+        tiles.push(PackedTile {
+            uv_rect: Rect::new(
+                origin.x as f32 / atlas_size.x as f32,
+                origin.y as f32 / atlas_size.y as f32,
+                image.size.x as f32 / atlas_size.x as f32,
+                image.size.y as f32 / atlas_size.y as f32,
+            ),
+            layer: 0,
+        });
+    }
+
+    let texture = make_texture(
+        TextureKind::Rectangle {
+            width: atlas_size.x,
+            height: atlas_size.y,
+        },
+        pixels,
+        source,
+    );
+
+    PackedTileSet {
+        material: make_material(ShaderResource::standard_2d(), texture),
+        tiles,
+    }
+}
+
+fn pack_array(images: &[TileImage], source: &SourceInfo) -> PackedTileSet {
+    let size = images
+        .iter()
+        .fold(Vector2::new(1, 1), |size, image| size.sup(&image.size));
+
+    let mut pixels = Vec::with_capacity((size.x * size.y) as usize * images.len());
+    for image in images {
+        // Nearest-neighbour resampling to the size of the layer.
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let src_x = (x * image.size.x / size.x) as i64;
+                let src_y = (y * image.size.y / size.y) as i64;
+                pixels.push(image.pixel_clamped(src_x, src_y));
+            }
+        }
+    }
+
+    let texture = make_texture(
+        TextureKind::Array {
+            width: size.x,
+            height: size.y,
+            layers: images.len() as u32,
+        },
+        pixels,
+        source,
+    );
+
+    PackedTileSet {
+        material: make_material(ShaderResource::standard_tile_array(), texture),
+        tiles: (0..images.len() as u32)
+            .map(|layer| PackedTile {
+                uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+                layer,
+            })
+            .collect(),
+    }
+}
+
+/// Packs the textures of the given tiles using the given packing mode. Returns `Ok(None)` for
+/// [`TileSetPacking::None`] or if there are no tiles. Diffuse textures of every tile must be
+/// loaded.
+pub fn pack_tiles(
+    tiles: &[TileDefinition],
+    packing: TileSetPacking,
+) -> Result<Option<PackedTileSet>, TileSetPackingError> {
+    if packing == TileSetPacking::None || tiles.is_empty() {
+        return Ok(None);
+    }
+
+    let mut images = Vec::with_capacity(tiles.len());
+    let mut source = None;
+    for (index, definition) in tiles.iter().enumerate() {
+        let (image, info) = read_tile_image(index, definition)?;
+        images.push(image);
+        source.get_or_insert(info);
+    }
+    let source = source.expect("There is at least one tile!");
+
+    Ok(Some(match packing {
+        TileSetPacking::None => unreachable!(),
+        TileSetPacking::Atlas { padding } => pack_atlas(&images, padding, &source),
+        TileSetPacking::TextureArray => pack_array(&images, &source),
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::untyped::ResourceKind,
+        core::{
+            algebra::{Vector2, Vector3},
+            math::Rect,
+            sstorage::ImmutableString,
+        },
+        material::{Material, MaterialResource},
+        resource::texture::{
+            Texture, TextureKind, TexturePixelKind, TextureResource, TextureResourceExtension,
+        },
+        scene::tilemap::{
+            packing::{grid_size, pack_tiles, TileSetPacking, TileSetPackingError},
+            tileset::TileDefinition,
+        },
+    };
+
+    /// 2x1 texture with a red and a green pixel, every tile uses one of the pixels.
+    fn make_tiles() -> Vec<TileDefinition> {
+        let texture = TextureResource::from_bytes(
+            TextureKind::Rectangle {
+                width: 2,
+                height: 1,
+            },
+            TexturePixelKind::RGBA8,
+            vec![255, 0, 0, 255, 0, 255, 0, 255],
+            ResourceKind::Embedded,
+        )
+        .unwrap();
+        let mut material = Material::standard_2d();
+        material
+            .set_texture(&ImmutableString::new("diffuseTexture"), Some(texture))
+            .unwrap();
+        let material = MaterialResource::new_ok(ResourceKind::Embedded, material);
+
+        [Rect::new(0.0, 0.0, 0.5, 1.0), Rect::new(0.5, 0.0, 0.5, 1.0)]
+            .into_iter()
+            .map(|uv_rect| TileDefinition {
+                material: material.clone(),
+                uv_rect,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn packed_texture(material: &MaterialResource) -> Texture {
+        material
+            .data_ref()
+            .property_ref(&ImmutableString::new("diffuseTexture"))
+            .unwrap()
+            .as_sampler()
+            .unwrap()
+            .data_ref()
+            .clone()
+    }
+
+    #[test]
+    fn test_grid_size() {
+        assert_eq!(grid_size(1, Vector2::new(4, 4)), Vector2::new(1, 1));
+        assert_eq!(grid_size(4, Vector2::new(4, 4)), Vector2::new(2, 2));
+        assert_eq!(grid_size(5, Vector2::new(4, 4)), Vector2::new(3, 2));
+    }
+
+    #[test]
+    fn test_pack_atlas() {
+        let packed = pack_tiles(&make_tiles(), TileSetPacking::Atlas { padding: 1 })
+            .unwrap()
+            .unwrap();
+        assert_eq!(packed.tiles.len(), 2);
+
+        // Two 3x3 cells (1x1 tile with 1 pixel gutter) side by side.
+        let texture = packed_texture(&packed.material);
+        assert_eq!(texture.kind().rectangle_size(), Some(Vector2::new(6, 3)));
+        let pixels = texture.data();
+        // Gutter repeats the edge pixels of the tile.
+        assert_eq!(&pixels[0..4], &[255, 0, 0, 255]);
+        assert_eq!(&pixels[5 * 4..6 * 4], &[0, 255, 0, 255]);
+
+        let uv = packed.tiles[1].uv_rect;
+        assert_eq!(uv.position, Vector2::new(4.0 / 6.0, 1.0 / 3.0));
+        assert_eq!(uv.size, Vector2::new(1.0 / 6.0, 1.0 / 3.0));
+    }
+
+    #[test]
+    fn test_pack_array() {
+        let packed = pack_tiles(&make_tiles(), TileSetPacking::TextureArray)
+            .unwrap()
+            .unwrap();
+        assert_eq!(packed.tiles[1].layer, 1);
+        assert_eq!(packed.tiles[1].uv_rect, Rect::new(0.0, 0.0, 1.0, 1.0));
+
+        let texture = packed_texture(&packed.material);
+        assert_eq!(texture.kind().array_size(), Some(Vector3::new(1, 1, 2)));
+        assert_eq!(texture.data(), &[255, 0, 0, 255, 0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn test_pack_without_texture() {
+        let tiles = vec![TileDefinition::default()];
+        assert!(pack_tiles(&tiles, TileSetPacking::None).unwrap().is_none());
+        assert_eq!(
+            pack_tiles(&tiles, TileSetPacking::TextureArray),
+            Err(TileSetPackingError::NoTexture(0))
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! Tile map projections define how tiles are laid out on a plane. See [`TileMapProjection`] docs
+//! for more info.
+
+use crate::core::{
+    algebra::Vector2, math::Rect, reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*,
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Height of a hexagonal tile. Hexagonal tiles are "pointy-topped" and have unit width, so their
+/// height is `2 / sqrt(3)`.
+const HEX_HEIGHT: f32 = 1.154_700_5;
+
+/// Vertical distance between rows of hexagonal tiles.
+const HEX_ROW_STEP: f32 = HEX_HEIGHT * 0.75;
+
+/// Projection of a tile map defines how grid positions of tiles are mapped to local coordinates
+/// of a tile map and vice versa. Every tile is rendered as a quad that covers the bounding
+/// rectangle of its shape (see [`TileMapProjection::tile_rect`]), so tile textures must have
+/// transparent areas outside of the tile shape for non-orthogonal projections.
+#[derive(
+    Copy,
+    Clone,
+    Hash,
+    PartialEq,
+    Eq,
+    Debug,
+    Default,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+)]
+#[type_uuid(id = "6a7e0f63-3c38-4f2b-9a1e-7c25e2b4d0c9")]
+pub enum TileMapProjection {
+    /// Square tiles of unit size, aligned with the axes.
+    #[default]
+    Orthogonal,
+    /// Diamond-shaped tiles with `1x0.5` size. Grid axes are rotated by 45 degrees, so the map
+    /// itself has diamond shape.
+    Isometric,
+    /// Diamond-shaped tiles with `1x0.5` size. Odd rows are shifted by a half of a tile, so the
+    /// map has rectangular shape.
+    Staggered,
+    /// Pointy-topped hexagonal tiles with unit width. Odd rows are shifted by a half of a tile.
+    Hexagonal,
+}
+
+fn is_odd(value: i32) -> bool {
+    value.rem_euclid(2) == 1
+}
+
+fn shift(row: i32) -> f32 {
+    if is_odd(row) {
+        0.5
+    } else {
+        0.0
+    }
+}
+
+/// Converts a point to a grid position on a grid of diamonds with `1x0.5` size, with the diamond
+/// `(0, 0)` centered at the origin.
+fn diamond_grid_position(point: Vector2<f32>) -> Vector2<i32> {
+    let a = point.x / 0.5;
+    let b = point.y / 0.25;
+    Vector2::new(
+        ((a + b) * 0.5).round() as i32,
+        ((b - a) * 0.5).round() as i32,
+    )
+}
+
+impl TileMapProjection {
+    /// Returns size of the bounding rectangle of a single tile.
+    pub fn tile_size(self) -> Vector2<f32> {
+        match self {
+            Self::Orthogonal => Vector2::new(1.0, 1.0),
+            Self::Isometric | Self::Staggered => Vector2::new(1.0, 0.5),
+            Self::Hexagonal => Vector2::new(1.0, HEX_HEIGHT),
+        }
+    }
+
+    /// Returns the center of a tile at the given grid position in local coordinates of a tile
+    /// map.
+    pub fn grid_to_local(self, position: Vector2<i32>) -> Vector2<f32> {
+        let p = position.cast::<f32>();
+        match self {
+            Self::Orthogonal => Vector2::new(p.x + 0.5, p.y + 0.5),
+            Self::Isometric => Vector2::new((p.x - p.y) * 0.5, (p.x + p.y) * 0.25),
+            Self::Staggered => Vector2::new(p.x + shift(position.y), p.y * 0.25),
+            Self::Hexagonal => Vector2::new(p.x + shift(position.y), p.y * HEX_ROW_STEP),
+        }
+    }
+
+    /// Returns a grid position of a tile that contains the given point (in local coordinates of
+    /// a tile map).
+    pub fn local_to_grid(self, point: Vector2<f32>) -> Vector2<i32> {
+        match self {
+            Self::Orthogonal => Vector2::new(point.x.floor() as i32, point.y.floor() as i32),
+            Self::Isometric => diamond_grid_position(point),
+            Self::Staggered => {
+                // Staggered map is an isometric map with different indexing.
+                let diamond = diamond_grid_position(point);
+                let y = diamond.x + diamond.y;
+                let x = (diamond.x - diamond.y - is_odd(y) as i32).div_euclid(2);
+                Vector2::new(x, y)
+            }
+            Self::Hexagonal => {
+                // Convert to axial coordinates first and then round them as cube coordinates.
+                let r = point.y / HEX_ROW_STEP;
+                let q = point.x - r * 0.5;
+                let s = -q - r;
+
+                let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+                let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+                if dq > dr && dq > ds {
+                    rq = -rr - rs;
+                } else if dr > ds {
+                    rr = -rq - rs;
+                }
+
+                let (q, r) = (rq as i32, rr as i32);
+                Vector2::new(q + (r - is_odd(r) as i32) / 2, r)
+            }
+        }
+    }
+
+    /// Returns bounding rectangle of a tile at the given grid position in local coordinates of a
+    /// tile map.
+    pub fn tile_rect(self, position: Vector2<i32>) -> Rect<f32> {
+        let size = self.tile_size();
+        let center = self.grid_to_local(position);
+        Rect::new(
+            center.x - size.x * 0.5,
+            center.y - size.y * 0.5,
+            size.x,
+            size.y,
+        )
+    }
+
+    /// Returns counter-clockwise outline of a tile at the given grid position in local
+    /// coordinates of a tile map.
+    pub fn tile_outline(self, position: Vector2<i32>) -> Vec<Vector2<f32>> {
+        let c = self.grid_to_local(position);
+        match self {
+            Self::Orthogonal => vec![
+                c + Vector2::new(-0.5, -0.5),
+                c + Vector2::new(0.5, -0.5),
+                c + Vector2::new(0.5, 0.5),
+                c + Vector2::new(-0.5, 0.5),
+            ],
+            Self::Isometric | Self::Staggered => vec![
+                c + Vector2::new(0.0, -0.25),
+                c + Vector2::new(0.5, 0.0),
+                c + Vector2::new(0.0, 0.25),
+                c + Vector2::new(-0.5, 0.0),
+            ],
+            Self::Hexagonal => {
+                let h = HEX_HEIGHT * 0.5;
+                vec![
+                    c + Vector2::new(0.0, -h),
+                    c + Vector2::new(0.5, -h * 0.5),
+                    c + Vector2::new(0.5, h * 0.5),
+                    c + Vector2::new(0.0, h),
+                    c + Vector2::new(-0.5, h * 0.5),
+                    c + Vector2::new(-0.5, -h * 0.5),
+                ]
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{core::algebra::Vector2, scene::tilemap::projection::TileMapProjection};
+
+    #[test]
+    fn test_grid_round_trip() {
+        for projection in [
+            TileMapProjection::Orthogonal,
+            TileMapProjection::Isometric,
+            TileMapProjection::Staggered,
+            TileMapProjection::Hexagonal,
+        ] {
+            for x in -5..5 {
+                for y in -5..5 {
+                    let position = Vector2::new(x, y);
+                    let center = projection.grid_to_local(position);
+                    assert_eq!(projection.local_to_grid(center), position, "{projection:?}");
+
+                    // Points slightly inside of the outline must belong to the tile as well.
+                    for vertex in projection.tile_outline(position) {
+                        let point = vertex.lerp(&center, 0.05);
+                        assert_eq!(projection.local_to_grid(point), position, "{projection:?}");
+                    }
+                }
+            }
+        }
+    }
+}
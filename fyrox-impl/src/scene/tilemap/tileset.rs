@@ -10,6 +10,7 @@ use crate::{
         visitor::prelude::*,
     },
     material::MaterialResource,
+    scene::tilemap::packing::{pack_tiles, PackedTileSet, TileSetPacking, TileSetPackingError},
 };
 use std::{
     any::Any,
@@ -58,6 +59,8 @@ impl From<VisitError> for TileSetResourceError {
     }
 }
 
+/// Collision shape of a tile. It is used to generate physics colliders for tile maps (see
+/// [`super::TileMap::generate_colliders`]).
 #[derive(
     Copy,
     Clone,
@@ -75,9 +78,13 @@ impl From<VisitError> for TileSetResourceError {
 )]
 #[type_uuid(id = "04a44fec-394f-4497-97d5-fe9e6f915831")]
 pub enum TileCollider {
+    /// The tile has no collision.
     None,
+    /// The tile is fully solid. Adjacent solid tiles are merged into larger rectangles.
     #[default]
     Rectangle,
+    /// The tile uses its geometry for collision. Tiles are rendered as quads, so it is the same
+    /// as [`Self::Rectangle`].
     Mesh,
 }
 
@@ -93,9 +100,48 @@ pub struct TileDefinition {
 #[type_uuid(id = "7b7e057b-a41e-4150-ab3b-0ae99f4024f0")]
 pub struct TileSet {
     pub tiles: Vec<TileDefinition>,
+    #[visit(optional)]
+    #[reflect(read_only)]
+    packing: TileSetPacking,
+    #[visit(optional)]
+    #[reflect(hidden)]
+    packed: Option<PackedTileSet>,
 }
 
 impl TileSet {
+    /// Creates a new tile set with the given tiles.
+    pub fn new(tiles: Vec<TileDefinition>) -> Self {
+        Self {
+            tiles,
+            ..Default::default()
+        }
+    }
+
+    /// Packs the textures of the tiles into a single texture (see [`TileSetPacking`] docs for more
+    /// info). Diffuse textures of every tile must be loaded. The packed texture is stored in the
+    /// tile set, so the packing must be done only once. Tile maps will use the packed texture
+    /// instead of the materials of the tiles, keep in mind that the tile set must be packed again
+    /// if its tiles were changed, otherwise the packed texture will be ignored. Use
+    /// [`TileSetPacking::None`] to remove the packed texture.
+    pub fn pack(&mut self, packing: TileSetPacking) -> Result<(), TileSetPackingError> {
+        self.packed = pack_tiles(&self.tiles, packing)?;
+        self.packing = packing;
+        Ok(())
+    }
+
+    /// Returns packing mode of the tile set.
+    pub fn packing(&self) -> TileSetPacking {
+        self.packing
+    }
+
+    /// Returns packed texture of the tile set (if any). It returns `None` if the tiles were
+    /// changed after packing.
+    pub fn packed(&self) -> Option<&PackedTileSet> {
+        self.packed
+            .as_ref()
+            .filter(|packed| packed.tiles.len() == self.tiles.len())
+    }
+
     /// Load a tile set resource from the specific file path.
     pub async fn from_file(path: &Path, io: &dyn ResourceIo) -> Result<Self, TileSetResourceError> {
         let bytes = io.load_file(path).await?;
@@ -0,0 +1,571 @@
+//! Timeline player is a node that plays cinematic sequences (cutscenes). See [`TimelinePlayer`]
+//! docs for more info.
+
+use crate::{
+    core::{
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    generic_animation::value::{BoundValue, ValueBinding},
+    graph::BaseSceneGraph,
+    resource::timeline::TimelineResource,
+    scene::{
+        animation::AnimationPlayer,
+        base::{Base, BaseBuilder},
+        camera::Camera,
+        graph::{Graph, NodePool},
+        node::{Node, NodeTrait, UpdateContext},
+        sound::Sound,
+        timeline::track::{is_in_window, TimelineTrack, TrackKind},
+    },
+};
+use fxhash::{FxHashMap, FxHashSet};
+use std::{
+    collections::VecDeque,
+    ops::{Deref, DerefMut},
+};
+
+pub mod track;
+
+/// Timeline is a set of tracks, that are played simultaneously. See [`TrackKind`] docs for the
+/// list of supported tracks.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct Timeline {
+    /// Duration of the timeline in seconds.
+    #[reflect(min_value = 0.0)]
+    pub duration: f32,
+    /// A list of tracks of the timeline.
+    pub tracks: Vec<TimelineTrack>,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self {
+            duration: 10.0,
+            tracks: Default::default(),
+        }
+    }
+}
+
+/// An event, that is emitted by a timeline player when the playback crosses an event marker. See
+/// [`TimelinePlayer::pop_event`] docs for more info.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimelineEvent {
+    /// Name of a track, that contains the marker.
+    pub track: String,
+    /// Name of the marker.
+    pub name: String,
+    /// Time of the marker.
+    pub time: f32,
+}
+
+/// Timeline player is a node that plays a [`Timeline`] - a set of tracks, that switches cameras,
+/// plays animations and sounds, animates properties and emits events for scripts. It is intended
+/// to be used for cutscenes and other scripted sequences.
+///
+/// Timeline player refers to scene nodes by their names, so the same timeline could be used in
+/// multiple scenes. If there are multiple nodes with the same name, the first one is used.
+///
+/// # Events
+///
+/// Event tracks are the main way of synchronizing game logic with a timeline. Every crossed event
+/// marker produces a [`TimelineEvent`], that could be fetched by [`TimelinePlayer::pop_event`]:
+///
+/// ```rust
+/// # use fyrox_impl::scene::timeline::TimelinePlayer;
+/// fn handle_events(player: &mut TimelinePlayer) {
+///     while let Some(event) = player.pop_event() {
+///         if event.name == "Explosion" {
+///             // Spawn an explosion effect here.
+///         }
+///     }
+/// }
+/// ```
+///
+/// # Limitations
+///
+/// Negative playback speed is not supported. Events that lie between the old and new position are
+/// not emitted when the time position is changed by [`TimelinePlayer::set_time_position`].
+#[derive(Visit, Reflect, Clone, Debug)]
+pub struct TimelinePlayer {
+    base: Base,
+    timeline: InheritableVariable<Option<TimelineResource>>,
+    playing: InheritableVariable<bool>,
+    looped: InheritableVariable<bool>,
+    #[reflect(min_value = 0.0)]
+    speed: InheritableVariable<f32>,
+    #[reflect(hidden)]
+    time_position: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    include_start: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    active_clips: FxHashSet<(usize, usize)>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    node_cache: FxHashMap<String, Handle<Node>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    events: VecDeque<TimelineEvent>,
+}
+
+impl Default for TimelinePlayer {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            timeline: Default::default(),
+            playing: true.into(),
+            looped: false.into(),
+            speed: 1.0.into(),
+            time_position: 0.0,
+            include_start: true,
+            active_clips: Default::default(),
+            node_cache: Default::default(),
+            events: Default::default(),
+        }
+    }
+}
+
+impl TypeUuidProvider for TimelinePlayer {
+    fn type_uuid() -> Uuid {
+        uuid!("5c2e9b71-0a4f-4d86-93e8-b1f6c7d2a053")
+    }
+}
+
+impl Deref for TimelinePlayer {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for TimelinePlayer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TimelinePlayer {
+    /// Sets a new timeline to play. Playback position is reset to the beginning.
+    pub fn set_timeline(&mut self, timeline: Option<TimelineResource>) {
+        self.timeline.set_value_and_mark_modified(timeline);
+        self.set_time_position(0.0);
+    }
+
+    /// Returns current timeline.
+    pub fn timeline(&self) -> Option<&TimelineResource> {
+        self.timeline.as_ref()
+    }
+
+    /// Starts or resumes playback.
+    pub fn play(&mut self) {
+        self.playing.set_value_and_mark_modified(true);
+    }
+
+    /// Pauses playback. Animations of the timeline keep their current state.
+    pub fn pause(&mut self) {
+        self.playing.set_value_and_mark_modified(false);
+    }
+
+    /// Stops playback and rewinds the timeline to the beginning. Animations, that were enabled by
+    /// the timeline, will be disabled on next update.
+    pub fn stop(&mut self) {
+        self.pause();
+        self.set_time_position(0.0);
+    }
+
+    /// Returns `true` if the timeline is playing, `false` - otherwise. Non-looping timelines stop
+    /// automatically when they reach the end.
+    pub fn is_playing(&self) -> bool {
+        *self.playing
+    }
+
+    /// Sets whether the timeline should start over when it reaches the end.
+    pub fn set_looped(&mut self, looped: bool) {
+        self.looped.set_value_and_mark_modified(looped);
+    }
+
+    /// Returns `true` if the timeline is looping, `false` - otherwise.
+    pub fn is_looped(&self) -> bool {
+        *self.looped
+    }
+
+    /// Sets playback speed. Negative values are clamped to zero.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed.set_value_and_mark_modified(speed.max(0.0));
+    }
+
+    /// Returns playback speed.
+    pub fn speed(&self) -> f32 {
+        *self.speed
+    }
+
+    /// Sets new time position (in seconds) of the playback. Events at the new position will be
+    /// emitted on next update.
+    pub fn set_time_position(&mut self, time: f32) {
+        self.time_position = time.max(0.0);
+        self.include_start = true;
+    }
+
+    /// Returns current time position (in seconds) of the playback.
+    pub fn time_position(&self) -> f32 {
+        self.time_position
+    }
+
+    /// Takes the oldest event from the internal queue.
+    pub fn pop_event(&mut self) -> Option<TimelineEvent> {
+        self.events.pop_front()
+    }
+
+    fn find_node(&mut self, nodes: &NodePool, name: &str) -> Handle<Node> {
+        if let Some(handle) = self.node_cache.get(name) {
+            if nodes.try_borrow(*handle).is_some_and(|n| n.name() == name) {
+                return *handle;
+            }
+        }
+
+        let handle = nodes
+            .pair_iter()
+            .find_map(|(handle, node)| (node.name() == name).then_some(handle))
+            .unwrap_or_default();
+        self.node_cache.insert(name.to_string(), handle);
+        handle
+    }
+
+    fn set_animation_enabled(
+        &mut self,
+        nodes: &mut NodePool,
+        player: &str,
+        animation: &str,
+        enabled: bool,
+        time_position: Option<f32>,
+    ) {
+        let handle = self.find_node(nodes, player);
+        let Some(animation_player) = nodes
+            .try_borrow_mut(handle)
+            .and_then(|n| n.query_component_mut::<AnimationPlayer>())
+        else {
+            return;
+        };
+        if let Some((_, animation)) = animation_player
+            .animations_mut()
+            .get_value_mut_silent()
+            .find_by_name_mut(animation)
+        {
+            animation.set_enabled(enabled);
+            if let Some(time_position) = time_position {
+                animation.set_time_position(time_position);
+            }
+        }
+    }
+
+    fn deactivate_clips(&mut self, tracks: &[TimelineTrack], nodes: &mut NodePool) {
+        for (track_index, clip_index) in std::mem::take(&mut self.active_clips) {
+            if let Some(TrackKind::Animation(track)) = tracks.get(track_index).map(|t| &t.kind) {
+                if let Some(clip) = track.clips.get(clip_index) {
+                    self.set_animation_enabled(
+                        nodes,
+                        &track.animation_player,
+                        &clip.animation,
+                        false,
+                        None,
+                    );
+                }
+            }
+        }
+    }
+
+    fn fire_events(
+        &mut self,
+        tracks: &[TimelineTrack],
+        nodes: &mut NodePool,
+        from: f32,
+        to: f32,
+        include_start: bool,
+    ) {
+        for track in tracks.iter().filter(|t| t.enabled) {
+            match track.kind {
+                TrackKind::Audio(ref audio) => {
+                    for cue in audio.cues.iter() {
+                        if is_in_window(cue.time, from, to, include_start) {
+                            let handle = self.find_node(nodes, &cue.sound);
+                            if let Some(sound) = nodes
+                                .try_borrow_mut(handle)
+                                .and_then(|n| n.cast_mut::<Sound>())
+                            {
+                                sound.play();
+                            }
+                        }
+                    }
+                }
+                TrackKind::Events(ref events) => {
+                    for marker in events.markers.iter() {
+                        if is_in_window(marker.time, from, to, include_start) {
+                            self.events.push_back(TimelineEvent {
+                                track: track.name.clone(),
+                                name: marker.name.clone(),
+                                time: marker.time,
+                            });
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn apply_tracks(&mut self, tracks: &[TimelineTrack], nodes: &mut NodePool, time: f32) {
+        for (track_index, track) in tracks.iter().enumerate() {
+            match track.kind {
+                TrackKind::CameraCuts(ref cuts) if track.enabled => {
+                    let active = cuts.active_cut(time).map(|cut| cut.camera.as_str());
+                    for cut in cuts.cuts.iter() {
+                        let handle = self.find_node(nodes, &cut.camera);
+                        if let Some(camera) = nodes
+                            .try_borrow_mut(handle)
+                            .and_then(|n| n.cast_mut::<Camera>())
+                        {
+                            let enabled = active == Some(cut.camera.as_str());
+                            if camera.is_enabled() != enabled {
+                                camera.set_enabled(enabled);
+                            }
+                        }
+                    }
+                }
+                TrackKind::Animation(ref animation) => {
+                    for (clip_index, clip) in animation.clips.iter().enumerate() {
+                        let key = (track_index, clip_index);
+                        if track.enabled && clip.contains(time) {
+                            self.active_clips.insert(key);
+                            self.set_animation_enabled(
+                                nodes,
+                                &animation.animation_player,
+                                &clip.animation,
+                                true,
+                                Some(clip.animation_time(time)),
+                            );
+                        } else if self.active_clips.remove(&key) {
+                            self.set_animation_enabled(
+                                nodes,
+                                &animation.animation_player,
+                                &clip.animation,
+                                false,
+                                None,
+                            );
+                        }
+                    }
+                }
+                TrackKind::Property(ref property) if track.enabled => {
+                    let Some(value) = property.data.fetch(time) else {
+                        continue;
+                    };
+                    let handle = self.find_node(nodes, &property.target);
+                    if let Some(node) = nodes.try_borrow_mut(handle) {
+                        let bound_value = BoundValue {
+                            binding: ValueBinding::Property {
+                                name: property.property.clone(),
+                                value_type: property.value_type,
+                            },
+                            value,
+                        };
+                        bound_value.apply_to_object(node, &property.property, property.value_type);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+impl NodeTrait for TimelinePlayer {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        let Some(resource) = (*self.timeline).clone() else {
+            return;
+        };
+        let mut state = resource.state();
+        let Some(data) = state.data() else {
+            return;
+        };
+        let tracks = data.timeline.tracks.as_slice();
+        let duration = data.timeline.duration.max(0.0);
+
+        if !*self.playing {
+            if self.include_start {
+                // The timeline was stopped or rewound while paused.
+                self.deactivate_clips(tracks, context.nodes);
+            }
+            return;
+        }
+
+        let prev = self.time_position.min(duration);
+        let include_start = std::mem::take(&mut self.include_start);
+        let mut time = prev + context.dt * *self.speed;
+
+        if time >= duration {
+            self.fire_events(tracks, context.nodes, prev, duration, include_start);
+            if *self.looped && duration > 0.0 {
+                time %= duration;
+                self.fire_events(tracks, context.nodes, 0.0, time, true);
+            } else {
+                time = duration;
+                self.playing.set_value_silent(false);
+            }
+        } else {
+            self.fire_events(tracks, context.nodes, prev, time, include_start);
+        }
+
+        self.time_position = time;
+        self.apply_tracks(tracks, context.nodes, time);
+
+        if !*self.playing {
+            self.deactivate_clips(tracks, context.nodes);
+        }
+    }
+}
+
+/// A builder for [`TimelinePlayer`] node.
+pub struct TimelinePlayerBuilder {
+    base_builder: BaseBuilder,
+    timeline: Option<TimelineResource>,
+    playing: bool,
+    looped: bool,
+    speed: f32,
+}
+
+impl TimelinePlayerBuilder {
+    /// Creates new builder instance.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            timeline: None,
+            playing: true,
+            looped: false,
+            speed: 1.0,
+        }
+    }
+
+    /// Sets a timeline to play.
+    pub fn with_timeline(mut self, timeline: TimelineResource) -> Self {
+        self.timeline = Some(timeline);
+        self
+    }
+
+    /// Sets whether the timeline should be played immediately or not.
+    pub fn with_playing(mut self, playing: bool) -> Self {
+        self.playing = playing;
+        self
+    }
+
+    /// Sets whether the timeline should start over when it reaches the end.
+    pub fn with_looped(mut self, looped: bool) -> Self {
+        self.looped = looped;
+        self
+    }
+
+    /// Sets playback speed.
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Creates an instance of [`TimelinePlayer`] node.
+    pub fn build_node(self) -> Node {
+        Node::new(TimelinePlayer {
+            base: self.base_builder.build_base(),
+            timeline: self.timeline.into(),
+            playing: self.playing.into(),
+            looped: self.looped.into(),
+            speed: self.speed.max(0.0).into(),
+            ..Default::default()
+        })
+    }
+
+    /// Creates an instance of [`TimelinePlayer`] node and adds it to the given scene graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::{untyped::ResourceKind, Resource},
+        core::algebra::Vector2,
+        resource::timeline::TimelineResourceState,
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            timeline::{
+                track::{EventMarker, EventTrack, TimelineTrack, TrackKind},
+                Timeline, TimelinePlayer, TimelinePlayerBuilder,
+            },
+        },
+    };
+
+    #[test]
+    fn test_timeline_events() {
+        let timeline = Timeline {
+            duration: 2.0,
+            tracks: vec![TimelineTrack {
+                name: "Events".to_string(),
+                enabled: true,
+                kind: TrackKind::Events(EventTrack {
+                    markers: vec![
+                        EventMarker {
+                            time: 0.0,
+                            name: "Start".to_string(),
+                        },
+                        EventMarker {
+                            time: 1.5,
+                            name: "Middle".to_string(),
+                        },
+                    ],
+                }),
+            }],
+        };
+
+        let mut graph = Graph::new();
+        let player = TimelinePlayerBuilder::new(BaseBuilder::new())
+            .with_timeline(Resource::new_ok(
+                ResourceKind::Embedded,
+                TimelineResourceState { timeline },
+            ))
+            .with_looped(true)
+            .build(&mut graph);
+
+        let names = |graph: &mut Graph, dt: f32| {
+            graph.update(Vector2::new(800.0, 600.0), dt, Default::default());
+            let player = graph[player].cast_mut::<TimelinePlayer>().unwrap();
+            std::iter::from_fn(|| player.pop_event())
+                .map(|e| e.name)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(names(&mut graph, 1.0), ["Start"]);
+        assert_eq!(names(&mut graph, 0.75), ["Middle"]);
+        // Wrap around.
+        assert_eq!(names(&mut graph, 0.5), ["Start"]);
+        assert_eq!(names(&mut graph, 0.25), Vec::<String>::new());
+    }
+}
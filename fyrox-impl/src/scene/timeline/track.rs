@@ -0,0 +1,256 @@
+//! Tracks of a timeline. See [`TrackKind`] docs for more info.
+
+use crate::{
+    core::{reflect::prelude::*, uuid_provider, visitor::prelude::*},
+    generic_animation::{
+        container::{TrackDataContainer, TrackValueKind},
+        value::ValueType,
+    },
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Camera cut makes the given camera active starting from the given time.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct CameraCut {
+    /// Time (in seconds) at which the cut happens.
+    pub time: f32,
+    /// Name of a camera node that becomes active.
+    pub camera: String,
+}
+
+uuid_provider!(CameraCut = "2a9f6e1b-7d43-4c58-a0e2-94b7c3f5d816");
+
+/// A track, that switches cameras. Only one camera of the track is enabled at a time - the camera
+/// of the last cut, that has happened before the current time. Every other camera mentioned in the
+/// track is disabled. Cameras, that are not mentioned in the track, are left untouched.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct CameraCutsTrack {
+    /// A list of camera cuts. The order of the cuts does not matter.
+    pub cuts: Vec<CameraCut>,
+}
+
+impl CameraCutsTrack {
+    /// Returns a cut, that is active at the given time (if any).
+    pub fn active_cut(&self, time: f32) -> Option<&CameraCut> {
+        self.cuts
+            .iter()
+            .filter(|cut| cut.time <= time)
+            .max_by(|a, b| a.time.total_cmp(&b.time))
+    }
+}
+
+/// Animation clip plays an animation of an animation player in the given time range.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct AnimationClip {
+    /// Time (in seconds) at which the clip starts.
+    pub start: f32,
+    /// Duration (in seconds) of the clip.
+    #[reflect(min_value = 0.0)]
+    pub duration: f32,
+    /// Name of an animation in the animation player of the track.
+    pub animation: String,
+    /// Time position of the animation at the start of the clip.
+    pub offset: f32,
+    /// Playback speed of the animation within the clip.
+    pub speed: f32,
+}
+
+uuid_provider!(AnimationClip = "c4e8b2d7-5a19-4f63-8b0d-1e7a9c3f6b52");
+
+impl Default for AnimationClip {
+    fn default() -> Self {
+        Self {
+            start: 0.0,
+            duration: 1.0,
+            animation: Default::default(),
+            offset: 0.0,
+            speed: 1.0,
+        }
+    }
+}
+
+impl AnimationClip {
+    /// Returns `true` if the clip covers the given time.
+    pub fn contains(&self, time: f32) -> bool {
+        time >= self.start && time < self.start + self.duration
+    }
+
+    /// Returns time position of the animation at the given time of the timeline.
+    pub fn animation_time(&self, time: f32) -> f32 {
+        self.offset + (time - self.start) * self.speed
+    }
+}
+
+/// A track, that controls animations of an animation player. Animations of the clips are enabled
+/// while the time of the timeline is within the clip and their time position is driven by the
+/// timeline. Animations are disabled when their clip ends.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct AnimationTrack {
+    /// Name of an animation player node.
+    pub animation_player: String,
+    /// A list of animation clips.
+    pub clips: Vec<AnimationClip>,
+}
+
+/// Sound cue starts playback of a sound node at the given time.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct SoundCue {
+    /// Time (in seconds) at which the sound starts playing.
+    pub time: f32,
+    /// Name of a sound node.
+    pub sound: String,
+}
+
+uuid_provider!(SoundCue = "7b1d5f9a-3e62-4c07-9a84-d2f6b8e1c039");
+
+/// A track, that starts playback of sounds.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct AudioTrack {
+    /// A list of sound cues.
+    pub cues: Vec<SoundCue>,
+}
+
+/// A track, that animates an arbitrary numeric property of a scene node. It works the same as
+/// property tracks of animations, see [`crate::generic_animation::value::ValueBinding::Property`]
+/// docs for more info.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct PropertyTrack {
+    /// Name of a scene node.
+    pub target: String,
+    /// Path to a property of the node (`foo.bar.baz[1].foobar@EnumVariant.stuff`).
+    pub property: String,
+    /// Actual type of the property.
+    pub value_type: ValueType,
+    /// Curves of the property.
+    pub data: TrackDataContainer,
+}
+
+impl Default for PropertyTrack {
+    fn default() -> Self {
+        Self {
+            target: Default::default(),
+            property: Default::default(),
+            value_type: ValueType::F32,
+            data: TrackDataContainer::new(TrackValueKind::Real),
+        }
+    }
+}
+
+/// Event marker is a named point on a timeline. See [`super::TimelineEvent`] docs for more info.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct EventMarker {
+    /// Time (in seconds) of the marker.
+    pub time: f32,
+    /// Name of the marker.
+    pub name: String,
+}
+
+uuid_provider!(EventMarker = "f0c3a8e5-6b27-4d91-b5e4-38a1d7c9f62e");
+
+/// A track, that emits events, which could be handled by scripts. This is the main way of
+/// synchronizing game logic with a timeline.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct EventTrack {
+    /// A list of event markers.
+    pub markers: Vec<EventMarker>,
+}
+
+/// Kind of a timeline track.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum TrackKind {
+    /// See [`CameraCutsTrack`] docs.
+    CameraCuts(CameraCutsTrack),
+    /// See [`AnimationTrack`] docs.
+    Animation(AnimationTrack),
+    /// See [`AudioTrack`] docs.
+    Audio(AudioTrack),
+    /// See [`PropertyTrack`] docs.
+    Property(PropertyTrack),
+    /// See [`EventTrack`] docs.
+    Events(EventTrack),
+}
+
+uuid_provider!(TrackKind = "0d5f7a3c-9e21-4b64-8c0f-6e3b2d8a5c17");
+
+impl Default for TrackKind {
+    fn default() -> Self {
+        Self::Events(Default::default())
+    }
+}
+
+/// Named track of a timeline.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct TimelineTrack {
+    /// Name of the track. It is used to identify the source of [`super::TimelineEvent`]s.
+    pub name: String,
+    /// Disabled tracks are ignored.
+    pub enabled: bool,
+    /// Actual content of the track.
+    pub kind: TrackKind,
+}
+
+uuid_provider!(TimelineTrack = "e8a16c4d-2f95-4a7b-b3d0-71c9f5e2a864");
+
+impl Default for TimelineTrack {
+    fn default() -> Self {
+        Self {
+            name: "Track".to_string(),
+            enabled: true,
+            kind: Default::default(),
+        }
+    }
+}
+
+/// Returns `true` if the given time is within the `(from, to]` range (or `[from, to]` range, if
+/// `include_start` is set).
+pub(super) fn is_in_window(time: f32, from: f32, to: f32, include_start: bool) -> bool {
+    (time > from || (include_start && time == from)) && time <= to
+}
+
+#[cfg(test)]
+mod test {
+    use crate::scene::timeline::track::{is_in_window, AnimationClip, CameraCut, CameraCutsTrack};
+
+    #[test]
+    fn test_active_cut() {
+        let track = CameraCutsTrack {
+            cuts: vec![
+                CameraCut {
+                    time: 2.0,
+                    camera: "B".to_string(),
+                },
+                CameraCut {
+                    time: 0.0,
+                    camera: "A".to_string(),
+                },
+            ],
+        };
+        assert_eq!(track.active_cut(-1.0), None);
+        assert_eq!(track.active_cut(1.0).unwrap().camera, "A");
+        assert_eq!(track.active_cut(2.0).unwrap().camera, "B");
+        assert_eq!(track.active_cut(5.0).unwrap().camera, "B");
+    }
+
+    #[test]
+    fn test_animation_clip() {
+        let clip = AnimationClip {
+            start: 1.0,
+            duration: 2.0,
+            offset: 0.5,
+            speed: 2.0,
+            ..Default::default()
+        };
+        assert!(!clip.contains(0.5));
+        assert!(clip.contains(1.0));
+        assert!(!clip.contains(3.0));
+        assert_eq!(clip.animation_time(1.5), 1.5);
+    }
+
+    #[test]
+    fn test_is_in_window() {
+        assert!(!is_in_window(1.0, 1.0, 2.0, false));
+        assert!(is_in_window(1.0, 1.0, 2.0, true));
+        assert!(is_in_window(2.0, 1.0, 2.0, false));
+        assert!(!is_in_window(2.5, 1.0, 2.0, true));
+    }
+}
@@ -0,0 +1,709 @@
+//! Vector shape is a flat shape (a circle, a rounded rectangle or an arbitrary path), that is rendered
+//! crisply at any scale. See [`VectorShape`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Point3, Vector2, Vector3},
+        color::Color,
+        math::{aabb::AxisAlignedBoundingBox, Rect, TriangleDefinition},
+        parking_lot::Mutex,
+        pool::Handle,
+        reflect::prelude::*,
+        sstorage::ImmutableString,
+        uuid::{uuid, Uuid},
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    material::{Material, MaterialResource},
+    renderer::{self, bundle::RenderContext},
+    resource::texture::{
+        TextureKind, TextureMinificationFilter, TexturePixelKind, TextureResource,
+        TextureResourceExtension, TextureWrapMode,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        mesh::{
+            buffer::{
+                VertexAttributeDataType, VertexAttributeDescriptor, VertexAttributeUsage,
+                VertexTrait,
+            },
+            RenderPath,
+        },
+        node::{Node, NodeTrait, RdcControlFlow},
+    },
+};
+use fyrox_core::value_as_u8_slice;
+use fyrox_graph::BaseSceneGraph;
+use fyrox_resource::untyped::ResourceKind;
+use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+pub mod path;
+pub mod svg;
+
+pub use path::VectorPath;
+
+/// Amount of texels of a distance field, that is used for anti-aliasing around the edges of a shape.
+const EDGE_TEXELS: f32 = 4.0;
+
+/// Maximum size of a side of a distance field texture.
+const MAX_RESOLUTION: u32 = 2048;
+
+/// A vertex of a vector shape.
+#[derive(Copy, Clone, Debug, Default)]
+#[repr(C)] // OpenGL expects this structure packed as in C
+pub struct VectorShapeVertex {
+    /// Position of the vertex in world coordinates.
+    pub position: Vector3<f32>,
+    /// Texture coordinates of the distance field.
+    pub tex_coord: Vector2<f32>,
+    /// Fill color.
+    pub fill_color: Color,
+    /// Outline color.
+    pub outline_color: Color,
+    /// Shape parameters: x - spread of the distance field, y - outline thickness.
+    pub params: Vector2<f32>,
+}
+
+impl VertexTrait for VectorShapeVertex {
+    fn layout() -> &'static [VertexAttributeDescriptor] {
+        &[
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Position,
+                data_type: VertexAttributeDataType::F32,
+                size: 3,
+                divisor: 0,
+                shader_location: 0,
+                normalized: false,
+            },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::TexCoord0,
+                data_type: VertexAttributeDataType::F32,
+                size: 2,
+                divisor: 0,
+                shader_location: 1,
+                normalized: false,
+            },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Color,
+                data_type: VertexAttributeDataType::U8,
+                size: 4,
+                divisor: 0,
+                shader_location: 2,
+                normalized: true,
+            },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Custom0,
+                data_type: VertexAttributeDataType::U8,
+                size: 4,
+                divisor: 0,
+                shader_location: 3,
+                normalized: true,
+            },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Custom1,
+                data_type: VertexAttributeDataType::F32,
+                size: 2,
+                divisor: 0,
+                shader_location: 4,
+                normalized: false,
+            },
+        ]
+    }
+}
+
+/// Geometry of a vector shape. Every shape is defined in local XY plane of the node, centered at
+/// its origin (except paths, that could have arbitrary position).
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum VectorShapeKind {
+    /// A circle with the given radius.
+    Circle {
+        /// Radius of the circle.
+        #[reflect(min_value = 0.0, step = 0.05)]
+        radius: f32,
+    },
+    /// A rectangle with rounded corners.
+    RoundedRectangle {
+        /// Full size of the rectangle.
+        size: Vector2<f32>,
+        /// Radius of the corners. It is clamped to the half of the smallest side of the rectangle.
+        #[reflect(min_value = 0.0, step = 0.05)]
+        corner_radius: f32,
+    },
+    /// An arbitrary path. It could be imported from SVG (see [`VectorPath::from_svg`]).
+    Path(VectorPath),
+}
+
+uuid_provider!(VectorShapeKind = "e4a7b0c2-93d5-4f18-b6e1-2c5d8f7a9b30");
+
+impl Default for VectorShapeKind {
+    fn default() -> Self {
+        Self::Circle { radius: 0.5 }
+    }
+}
+
+impl VectorShapeKind {
+    /// Calculates signed distance from the given point to the shape. The distance is negative
+    /// inside the shape.
+    pub fn signed_distance(&self, point: Vector2<f32>) -> f32 {
+        match self {
+            VectorShapeKind::Circle { radius } => point.norm() - radius,
+            VectorShapeKind::RoundedRectangle {
+                size,
+                corner_radius,
+            } => {
+                let radius = corner_radius.min(size.x.min(size.y) * 0.5).max(0.0);
+                let q = point.abs() - size.scale(0.5) + Vector2::repeat(radius);
+                q.sup(&Vector2::default()).norm() + q.x.max(q.y).min(0.0) - radius
+            }
+            VectorShapeKind::Path(path) => path.signed_distance(point),
+        }
+    }
+
+    /// Returns bounds of the shape in local coordinates. Returns `None` if the shape is empty.
+    pub fn bounds(&self) -> Option<Rect<f32>> {
+        match self {
+            VectorShapeKind::Circle { radius } => {
+                (*radius > 0.0).then(|| Rect::new(-radius, -radius, radius * 2.0, radius * 2.0))
+            }
+            VectorShapeKind::RoundedRectangle { size, .. } => (size.x > 0.0 && size.y > 0.0)
+                .then(|| Rect::new(-size.x * 0.5, -size.y * 0.5, size.x, size.y)),
+            VectorShapeKind::Path(path) => {
+                if path.is_empty() {
+                    None
+                } else {
+                    path.bounds()
+                }
+            }
+        }
+    }
+}
+
+/// A distance field of a shape.
+pub struct ShapeDistanceField {
+    /// Distance field texture. Distances are encoded as `0.5 - distance / (2.0 * spread)`, so the
+    /// edge of the shape has 0.5 value and the values above 0.5 are inside the shape.
+    pub texture: TextureResource,
+    /// Local-space rectangle, that is covered by the texture.
+    pub rect: Rect<f32>,
+    /// Maximum distance (in local units), that could be stored in the texture.
+    pub spread: f32,
+}
+
+impl ShapeDistanceField {
+    /// Generates distance field of the given shape. `resolution` defines amount of texels along the
+    /// largest side of the shape, `outline_thickness` is used to add extra space for the outline.
+    /// Returns `None` if the shape is empty.
+    pub fn new(shape: &VectorShapeKind, resolution: u32, outline_thickness: f32) -> Option<Self> {
+        let bounds = shape.bounds()?;
+
+        let resolution = resolution.clamp(1, MAX_RESOLUTION);
+        let texel_size = bounds.w().max(bounds.h()) / resolution as f32;
+        let spread = outline_thickness.max(0.0) + EDGE_TEXELS * texel_size;
+
+        let width =
+            (((bounds.w() + 2.0 * spread) / texel_size).ceil() as u32).clamp(1, MAX_RESOLUTION);
+        let height =
+            (((bounds.h() + 2.0 * spread) / texel_size).ceil() as u32).clamp(1, MAX_RESOLUTION);
+        let rect = Rect::new(
+            bounds.x() - spread,
+            bounds.y() - spread,
+            width as f32 * texel_size,
+            height as f32 * texel_size,
+        );
+
+        let mut bytes = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let point = Vector2::new(
+                    rect.x() + (x as f32 + 0.5) * texel_size,
+                    rect.y() + (y as f32 + 0.5) * texel_size,
+                );
+                let distance = shape.signed_distance(point);
+                let value = (0.5 - distance / (2.0 * spread)).clamp(0.0, 1.0);
+                bytes.push((value * 255.0).round() as u8);
+            }
+        }
+
+        let texture = TextureResource::from_bytes(
+            TextureKind::Rectangle { width, height },
+            TexturePixelKind::R8,
+            bytes,
+            ResourceKind::Embedded,
+        )?;
+
+        let mut data_ref = texture.data_ref();
+        data_ref.set_s_wrap_mode(TextureWrapMode::ClampToEdge);
+        data_ref.set_t_wrap_mode(TextureWrapMode::ClampToEdge);
+        data_ref.set_minification_filter(TextureMinificationFilter::Linear);
+        drop(data_ref);
+
+        Some(Self {
+            texture,
+            rect,
+            spread,
+        })
+    }
+}
+
+struct ShapeRenderData {
+    shape: VectorShapeKind,
+    resolution: u32,
+    outline_thickness: f32,
+    source_material: MaterialResource,
+    material: MaterialResource,
+    rect: Rect<f32>,
+    spread: f32,
+}
+
+/// Cached distance field of a shape. `None` means that the distance field must be regenerated
+/// before rendering.
+#[derive(Default)]
+struct ShapeRenderCache(Mutex<Option<ShapeRenderData>>);
+
+impl Clone for ShapeRenderCache {
+    fn clone(&self) -> Self {
+        // The copy will generate its own distance field when needed.
+        Self::default()
+    }
+}
+
+impl std::fmt::Debug for ShapeRenderCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ShapeRenderCache")
+    }
+}
+
+/// Vector shape is a flat shape (a circle, a rounded rectangle or an arbitrary path), that is
+/// rendered crisply at any scale in world space. It is useful for holographic UI, markers,
+/// highlights and stylized games.
+///
+/// # Rendering
+///
+/// The shape is converted into a signed distance field (SDF) texture, which is then rendered using
+/// a single quad in the local XY plane of the node. Since the texture stores distances instead of
+/// colors, edges of the shape stay sharp and anti-aliased regardless of the distance to the camera.
+/// The distance field is regenerated only when the shape (or its resolution, or outline thickness)
+/// changes, so changing colors is cheap. Sharp corners of paths become slightly rounded at low
+/// resolutions, increase the resolution (see [`VectorShape::set_resolution`]) if this is a
+/// problem.
+///
+/// Shapes are unlit and transparent, they're rendered in the forward pass.
+///
+/// # SVG
+///
+/// Paths could be imported from a subset of SVG, see [`svg`] module docs for more info.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::Vector2, color::Color, pool::Handle},
+/// #     scene::{
+/// #         base::BaseBuilder,
+/// #         graph::Graph,
+/// #         node::Node,
+/// #         vector_shape::{VectorShapeBuilder, VectorShapeKind},
+/// #     },
+/// # };
+/// fn create_marker(graph: &mut Graph) -> Handle<Node> {
+///     VectorShapeBuilder::new(BaseBuilder::new())
+///         .with_shape(VectorShapeKind::RoundedRectangle {
+///             size: Vector2::new(2.0, 1.0),
+///             corner_radius: 0.25,
+///         })
+///         .with_fill_color(Color::opaque(0, 200, 255))
+///         .with_outline(Color::WHITE, 0.05)
+///         .build(graph)
+/// }
+/// ```
+#[derive(Debug, Clone, Visit, Reflect)]
+pub struct VectorShape {
+    base: Base,
+
+    #[reflect(setter = "set_shape")]
+    shape: InheritableVariable<VectorShapeKind>,
+
+    #[reflect(setter = "set_fill_color")]
+    fill_color: InheritableVariable<Color>,
+
+    #[reflect(setter = "set_outline_color")]
+    outline_color: InheritableVariable<Color>,
+
+    #[reflect(min_value = 0.0, step = 0.01)]
+    #[reflect(setter = "set_outline_thickness")]
+    outline_thickness: InheritableVariable<f32>,
+
+    #[reflect(min_value = 1.0, max_value = 2048.0, step = 1.0)]
+    #[reflect(setter = "set_resolution")]
+    resolution: InheritableVariable<u32>,
+
+    #[reflect(setter = "set_material")]
+    material: InheritableVariable<MaterialResource>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    render_cache: ShapeRenderCache,
+}
+
+impl Default for VectorShape {
+    fn default() -> Self {
+        VectorShapeBuilder::new(BaseBuilder::new()).build_vector_shape()
+    }
+}
+
+impl Deref for VectorShape {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for VectorShape {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for VectorShape {
+    fn type_uuid() -> Uuid {
+        uuid!("0c3b5f1e-8a2d-4e7b-9f64-d1a2b3c4e5f6")
+    }
+}
+
+impl VectorShape {
+    /// Sets new shape. The distance field will be regenerated on next render.
+    pub fn set_shape(&mut self, shape: VectorShapeKind) -> VectorShapeKind {
+        self.shape.set_value_and_mark_modified(shape)
+    }
+
+    /// Returns current shape.
+    pub fn shape(&self) -> &VectorShapeKind {
+        &self.shape
+    }
+
+    /// Sets new fill color of the shape.
+    pub fn set_fill_color(&mut self, color: Color) -> Color {
+        self.fill_color.set_value_and_mark_modified(color)
+    }
+
+    /// Returns current fill color of the shape.
+    pub fn fill_color(&self) -> Color {
+        *self.fill_color
+    }
+
+    /// Sets new outline color of the shape.
+    pub fn set_outline_color(&mut self, color: Color) -> Color {
+        self.outline_color.set_value_and_mark_modified(color)
+    }
+
+    /// Returns current outline color of the shape.
+    pub fn outline_color(&self) -> Color {
+        *self.outline_color
+    }
+
+    /// Sets new outline thickness (in local units). The outline is drawn outside of the shape. Zero
+    /// thickness disables the outline.
+    pub fn set_outline_thickness(&mut self, thickness: f32) -> f32 {
+        self.outline_thickness
+            .set_value_and_mark_modified(thickness.max(0.0))
+    }
+
+    /// Returns current outline thickness.
+    pub fn outline_thickness(&self) -> f32 {
+        *self.outline_thickness
+    }
+
+    /// Sets new resolution of the distance field of the shape. It defines amount of texels along
+    /// the largest side of the shape. Default is 128, which is enough for most shapes.
+    pub fn set_resolution(&mut self, resolution: u32) -> u32 {
+        self.resolution
+            .set_value_and_mark_modified(resolution.clamp(1, MAX_RESOLUTION))
+    }
+
+    /// Returns current resolution of the distance field.
+    pub fn resolution(&self) -> u32 {
+        *self.resolution
+    }
+
+    /// Sets new material of the shape. The material must have `sdfTexture` sampler property, it
+    /// will be set to the distance field of the shape. See the standard vector shape shader for
+    /// the layout of vertices.
+    pub fn set_material(&mut self, material: MaterialResource) -> MaterialResource {
+        self.material.set_value_and_mark_modified(material)
+    }
+
+    /// Returns current material of the shape.
+    pub fn material(&self) -> &MaterialResource {
+        &self.material
+    }
+
+    /// Returns a material with the distance field of the shape and a rectangle, that is covered by
+    /// the distance field. The distance field is regenerated if needed.
+    fn render_data(&self) -> Option<(MaterialResource, Rect<f32>, f32)> {
+        let mut cache = self.render_cache.0.lock();
+
+        let is_valid = cache.as_ref().is_some_and(|data| {
+            data.shape == *self.shape
+                && data.resolution == *self.resolution
+                && data.outline_thickness == *self.outline_thickness
+                && data.source_material == *self.material
+        });
+
+        if !is_valid {
+            *cache = None;
+
+            let distance_field =
+                ShapeDistanceField::new(&self.shape, *self.resolution, *self.outline_thickness)?;
+
+            let mut material = self.material.state().data()?.clone();
+            material
+                .set_texture(
+                    &ImmutableString::new("sdfTexture"),
+                    Some(distance_field.texture),
+                )
+                .ok()?;
+
+            *cache = Some(ShapeRenderData {
+                shape: (*self.shape).clone(),
+                resolution: *self.resolution,
+                outline_thickness: *self.outline_thickness,
+                source_material: (*self.material).clone(),
+                material: MaterialResource::new_ok(ResourceKind::Embedded, material),
+                rect: distance_field.rect,
+                spread: distance_field.spread,
+            });
+        }
+
+        cache
+            .as_ref()
+            .map(|data| (data.material.clone(), data.rect, data.spread))
+    }
+}
+
+impl NodeTrait for VectorShape {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        match self.shape.bounds() {
+            Some(bounds) => {
+                let outline = *self.outline_thickness;
+                AxisAlignedBoundingBox::from_min_max(
+                    Vector3::new(bounds.x() - outline, bounds.y() - outline, 0.0),
+                    Vector3::new(
+                        bounds.x() + bounds.w() + outline,
+                        bounds.y() + bounds.h() + outline,
+                        0.0,
+                    ),
+                )
+            }
+            None => AxisAlignedBoundingBox::default(),
+        }
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn collect_render_data(&self, ctx: &mut RenderContext) -> RdcControlFlow {
+        if !self.global_visibility()
+            || !self.is_globally_enabled()
+            || (self.frustum_culling()
+                && !ctx
+                    .frustum
+                    .map_or(true, |f| f.is_intersects_aabb(&self.world_bounding_box())))
+        {
+            return RdcControlFlow::Continue;
+        }
+
+        if renderer::is_shadow_pass(ctx.render_pass_name) {
+            return RdcControlFlow::Continue;
+        }
+
+        let Some((material, rect, spread)) = self.render_data() else {
+            return RdcControlFlow::Continue;
+        };
+
+        let global_transform = self.global_transform();
+        let fill_color = *self.fill_color;
+        let outline_color = *self.outline_color;
+        let params = Vector2::new(spread, *self.outline_thickness);
+
+        let vertices = [
+            Vector2::new(0.0, 0.0),
+            Vector2::new(1.0, 0.0),
+            Vector2::new(1.0, 1.0),
+            Vector2::new(0.0, 1.0),
+        ]
+        .map(|tex_coord: Vector2<f32>| {
+            let local = Point3::new(
+                rect.x() + tex_coord.x * rect.w(),
+                rect.y() + tex_coord.y * rect.h(),
+                0.0,
+            );
+            VectorShapeVertex {
+                position: global_transform.transform_point(&local).coords,
+                tex_coord,
+                fill_color,
+                outline_color,
+                params,
+            }
+        });
+
+        let triangles = [TriangleDefinition([0, 1, 2]), TriangleDefinition([2, 3, 0])];
+
+        let sort_index = ctx.calculate_sorting_index(self.global_position());
+
+        ctx.storage.push_triangles(
+            VectorShapeVertex::layout(),
+            &material,
+            RenderPath::Forward,
+            0,
+            sort_index,
+            false,
+            self.self_handle,
+            &mut move |mut vertex_buffer, mut triangle_buffer| {
+                let start_vertex_index = vertex_buffer.vertex_count();
+
+                for vertex in vertices.iter() {
+                    vertex_buffer
+                        .push_vertex_raw(value_as_u8_slice(vertex))
+                        .unwrap();
+                }
+
+                triangle_buffer
+                    .push_triangles_iter_with_offset(start_vertex_index, triangles.into_iter());
+            },
+        );
+
+        RdcControlFlow::Continue
+    }
+}
+
+/// Allows you to create a vector shape in a declarative manner.
+pub struct VectorShapeBuilder {
+    base_builder: BaseBuilder,
+    shape: VectorShapeKind,
+    fill_color: Color,
+    outline_color: Color,
+    outline_thickness: f32,
+    resolution: u32,
+    material: MaterialResource,
+}
+
+impl VectorShapeBuilder {
+    /// Creates new builder with default state (white circle with 0.5 radius, no outline).
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            shape: Default::default(),
+            fill_color: Color::WHITE,
+            outline_color: Color::BLACK,
+            outline_thickness: 0.0,
+            resolution: 128,
+            material: MaterialResource::new_ok(
+                Default::default(),
+                Material::standard_vector_shape(),
+            ),
+        }
+    }
+
+    /// Sets desired shape.
+    pub fn with_shape(mut self, shape: VectorShapeKind) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Sets desired fill color.
+    pub fn with_fill_color(mut self, color: Color) -> Self {
+        self.fill_color = color;
+        self
+    }
+
+    /// Sets desired outline color and thickness.
+    pub fn with_outline(mut self, color: Color, thickness: f32) -> Self {
+        self.outline_color = color;
+        self.outline_thickness = thickness.max(0.0);
+        self
+    }
+
+    /// Sets desired resolution of the distance field. See [`VectorShape::set_resolution`] for
+    /// more info.
+    pub fn with_resolution(mut self, resolution: u32) -> Self {
+        self.resolution = resolution.clamp(1, MAX_RESOLUTION);
+        self
+    }
+
+    /// Sets desired material.
+    pub fn with_material(mut self, material: MaterialResource) -> Self {
+        self.material = material;
+        self
+    }
+
+    fn build_vector_shape(self) -> VectorShape {
+        VectorShape {
+            base: self.base_builder.build_base(),
+            shape: self.shape.into(),
+            fill_color: self.fill_color.into(),
+            outline_color: self.outline_color.into(),
+            outline_thickness: self.outline_thickness.into(),
+            resolution: self.resolution.into(),
+            material: self.material.into(),
+            render_cache: Default::default(),
+        }
+    }
+
+    /// Creates new vector shape instance.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_vector_shape())
+    }
+
+    /// Creates new vector shape instance and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_signed_distance() {
+        let circle = VectorShapeKind::Circle { radius: 1.0 };
+        assert_eq!(circle.signed_distance(Vector2::new(0.0, 0.0)), -1.0);
+        assert_eq!(circle.signed_distance(Vector2::new(2.0, 0.0)), 1.0);
+
+        let rect = VectorShapeKind::RoundedRectangle {
+            size: Vector2::new(4.0, 2.0),
+            corner_radius: 0.5,
+        };
+        assert_eq!(rect.signed_distance(Vector2::new(0.0, 0.0)), -1.0);
+        assert_eq!(rect.signed_distance(Vector2::new(3.0, 0.0)), 1.0);
+        // Corners are rounded.
+        let corner = rect.signed_distance(Vector2::new(2.0, 1.0));
+        assert!((corner - (0.5f32.sqrt() - 0.5)).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_distance_field() {
+        let field =
+            ShapeDistanceField::new(&VectorShapeKind::Circle { radius: 1.0 }, 32, 0.25).unwrap();
+        assert!(field.spread > 0.25);
+        assert!(field.rect.x() <= -1.25);
+        assert!(field.rect.w() >= 2.5);
+
+        assert!(
+            ShapeDistanceField::new(&VectorShapeKind::Circle { radius: 0.0 }, 32, 0.0).is_none()
+        );
+    }
+}
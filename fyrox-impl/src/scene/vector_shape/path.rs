@@ -0,0 +1,101 @@
+//! Vector path is a set of closed contours, that defines an arbitrary shape. See [`VectorPath`] docs
+//! for more info.
+
+use crate::{
+    core::{algebra::Vector2, math::Rect, reflect::prelude::*, visitor::prelude::*},
+    scene::vector_shape::svg::{self, SvgError},
+};
+
+/// Vector path is a set of closed contours (polygons), that defines an arbitrary shape. Curves are
+/// flattened into line segments when a path is created. Filling is done using even-odd rule, which
+/// means that a contour inside another contour makes a hole in it.
+///
+/// Paths are defined in local coordinates of a vector shape, Y axis points up.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct VectorPath {
+    /// A list of closed contours. Last point of each contour is implicitly connected with the
+    /// first one.
+    #[reflect(hidden)]
+    pub contours: Vec<Vec<Vector2<f32>>>,
+}
+
+impl VectorPath {
+    /// Creates a new path from the given set of contours.
+    pub fn new(contours: Vec<Vec<Vector2<f32>>>) -> Self {
+        Self { contours }
+    }
+
+    /// Imports a path from the given SVG document. See [`svg`] module docs for the list of supported
+    /// features.
+    pub fn from_svg(source: &str) -> Result<Self, SvgError> {
+        svg::parse(source)
+    }
+
+    /// Returns `true` if the path has no contours with at least three points.
+    pub fn is_empty(&self) -> bool {
+        self.contours.iter().all(|contour| contour.len() < 3)
+    }
+
+    /// Calculates bounds of the path. Returns `None` if the path is empty.
+    pub fn bounds(&self) -> Option<Rect<f32>> {
+        let mut points = self.contours.iter().flatten();
+        let first = *points.next()?;
+        let (min, max) = points.fold((first, first), |(min, max), point| {
+            (min.inf(point), max.sup(point))
+        });
+        Some(Rect::new(min.x, min.y, max.x - min.x, max.y - min.y))
+    }
+
+    /// Moves every point of the path so the center of its bounds will be at the origin.
+    pub fn centered(mut self) -> Self {
+        if let Some(bounds) = self.bounds() {
+            let center = bounds.position + bounds.size.scale(0.5);
+            for point in self.contours.iter_mut().flatten() {
+                *point -= center;
+            }
+        }
+        self
+    }
+
+    /// Calculates signed distance from the given point to the path. The distance is negative
+    /// inside the path.
+    pub fn signed_distance(&self, point: Vector2<f32>) -> f32 {
+        let mut min_distance = f32::MAX;
+        let mut inside = false;
+
+        for contour in self.contours.iter().filter(|c| c.len() >= 2) {
+            let mut prev = contour[contour.len() - 1];
+            for &current in contour {
+                min_distance = min_distance.min(distance_to_segment(point, prev, current));
+
+                // Even-odd rule.
+                if (current.y > point.y) != (prev.y > point.y) {
+                    let x =
+                        prev.x + (point.y - prev.y) / (current.y - prev.y) * (current.x - prev.x);
+                    if point.x < x {
+                        inside = !inside;
+                    }
+                }
+
+                prev = current;
+            }
+        }
+
+        if inside {
+            -min_distance
+        } else {
+            min_distance
+        }
+    }
+}
+
+fn distance_to_segment(point: Vector2<f32>, a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+    let ab = b - a;
+    let length_squared = ab.norm_squared();
+    let t = if length_squared > f32::EPSILON {
+        ((point - a).dot(&ab) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (a + ab.scale(t) - point).norm()
+}
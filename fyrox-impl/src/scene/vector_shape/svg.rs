@@ -0,0 +1,701 @@
+//! A tiny importer of a subset of SVG, that is enough to import simple vector shapes (icons, markers,
+//! logos, etc.). Supported elements are: `path`, `polygon`, `polyline`, `rect` (including rounded
+//! corners), `circle` and `ellipse`. Path data supports every command except elliptical arcs (`A`
+//! and `a`). Everything else (strokes, transforms, groups, gradients, etc.) is ignored. Every element
+//! is converted to a set of closed contours and filled using even-odd rule.
+//!
+//! [`parse_document`] additionally keeps every element as a separate shape with its fill color
+//! (`fill`, `fill-opacity` and `opacity`, both as attributes and in `style`), it is used to
+//! rasterize SVG images (see [`crate::resource::svg`]). Fill is not inherited from groups.
+//!
+//! Y axis of SVG points down, the importer flips it, so the imported shape is not upside down.
+
+use crate::{
+    core::{algebra::Vector2, color::Color, math::Rect},
+    scene::vector_shape::path::VectorPath,
+};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+/// Amount of line segments, that is used to flatten a single curve.
+const CURVE_SEGMENTS: usize = 16;
+
+/// Amount of line segments, that is used to flatten a circle or an ellipse.
+const ELLIPSE_SEGMENTS: usize = 64;
+
+/// An error, that may occur during SVG import.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SvgError {
+    /// An element has a malformed number.
+    InvalidNumber(String),
+    /// Path data contains unsupported or unknown command.
+    UnsupportedCommand(char),
+    /// Path data ended unexpectedly (for example, a command has not enough arguments).
+    UnexpectedEnd,
+    /// An element lacks a required attribute.
+    MissingAttribute {
+        /// Name of the element.
+        element: String,
+        /// Name of the attribute.
+        attribute: &'static str,
+    },
+    /// The document has no supported elements.
+    NoShapes,
+}
+
+impl Display for SvgError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SvgError::InvalidNumber(number) => write!(f, "Invalid number {number}"),
+            SvgError::UnsupportedCommand(command) => {
+                write!(f, "Unsupported path command {command}")
+            }
+            SvgError::UnexpectedEnd => write!(f, "Unexpected end of path data"),
+            SvgError::MissingAttribute { element, attribute } => {
+                write!(f, "Element {element} has no {attribute} attribute")
+            }
+            SvgError::NoShapes => write!(f, "SVG document has no supported shapes"),
+        }
+    }
+}
+
+impl Error for SvgError {}
+
+/// A filled shape of an SVG document.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgShape {
+    /// Closed contours of the shape in coordinates of the document (Y axis points down).
+    pub path: VectorPath,
+    /// Fill color of the shape (including `fill-opacity` and `opacity`). `None` means that the
+    /// shape has `fill="none"` and it should not be drawn.
+    pub fill: Option<Color>,
+}
+
+/// Shapes of an SVG document with its view box.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SvgDocument {
+    /// A rectangle in coordinates of the document, that is visible. It is taken from `viewBox`
+    /// attribute of the root element, or from its `width` and `height` attributes, or from the
+    /// bounds of the shapes (in this order).
+    pub view_box: Rect<f32>,
+    /// Size of the document in pixels (`width` and `height` attributes of the root element,
+    /// defaults to the size of the view box).
+    pub size: Vector2<f32>,
+    /// A list of shapes in drawing order.
+    pub shapes: Vec<SvgShape>,
+}
+
+/// Parses the given SVG document and converts all supported elements into a single path.
+pub fn parse(source: &str) -> Result<VectorPath, SvgError> {
+    let mut contours = parse_document(source)?
+        .shapes
+        .into_iter()
+        .flat_map(|shape| shape.path.contours)
+        .collect::<Vec<_>>();
+
+    // Flip Y axis.
+    for point in contours.iter_mut().flatten() {
+        point.y = -point.y;
+    }
+
+    Ok(VectorPath::new(contours))
+}
+
+/// Parses the given SVG document and keeps every supported element as a separate shape with its
+/// own fill color. Unlike [`parse`], the Y axis is not flipped.
+pub fn parse_document(source: &str) -> Result<SvgDocument, SvgError> {
+    let mut shapes = Vec::new();
+    let mut view_box = None;
+    let mut size = None;
+
+    for element in elements(source) {
+        let mut contours = Vec::new();
+        match element.name {
+            "svg" => {
+                if view_box.is_none() {
+                    let width = element.number("width")?;
+                    let height = element.number("height")?;
+                    if let Some(numbers) = element.attribute("viewBox").map(parse_numbers) {
+                        if let [x, y, w, h] = numbers?[..] {
+                            view_box = Some(Rect::new(x, y, w, h));
+                        }
+                    }
+                    if let (Some(width), Some(height)) = (width, height) {
+                        size = Some(Vector2::new(width, height));
+                        view_box = view_box.or(Some(Rect::new(0.0, 0.0, width, height)));
+                    }
+                }
+                continue;
+            }
+            "path" => parse_path_data(element.required("d")?, &mut contours)?,
+            "polygon" | "polyline" => {
+                let numbers = parse_numbers(element.required("points")?)?;
+                contours.push(
+                    numbers
+                        .chunks_exact(2)
+                        .map(|p| Vector2::new(p[0], p[1]))
+                        .collect(),
+                );
+            }
+            "rect" => {
+                let x = element.number("x")?.unwrap_or_default();
+                let y = element.number("y")?.unwrap_or_default();
+                let width = element.required_number("width")?;
+                let height = element.required_number("height")?;
+                let rx = element.number("rx")?;
+                let ry = element.number("ry")?.or(rx).unwrap_or_default();
+                let rx = rx.unwrap_or(ry);
+                contours.push(rounded_rect(
+                    Vector2::new(x, y),
+                    Vector2::new(width, height),
+                    Vector2::new(rx.min(width * 0.5), ry.min(height * 0.5)),
+                ));
+            }
+            "circle" => {
+                let r = element.required_number("r")?;
+                contours.push(ellipse(
+                    Vector2::new(
+                        element.number("cx")?.unwrap_or_default(),
+                        element.number("cy")?.unwrap_or_default(),
+                    ),
+                    Vector2::new(r, r),
+                ));
+            }
+            "ellipse" => {
+                contours.push(ellipse(
+                    Vector2::new(
+                        element.number("cx")?.unwrap_or_default(),
+                        element.number("cy")?.unwrap_or_default(),
+                    ),
+                    Vector2::new(
+                        element.required_number("rx")?,
+                        element.required_number("ry")?,
+                    ),
+                ));
+            }
+            _ => continue,
+        }
+
+        contours.retain(|contour: &Vec<Vector2<f32>>| contour.len() >= 3);
+        if !contours.is_empty() {
+            shapes.push(SvgShape {
+                path: VectorPath::new(contours),
+                fill: element.fill(),
+            });
+        }
+    }
+
+    if shapes.is_empty() {
+        return Err(SvgError::NoShapes);
+    }
+
+    let view_box = match view_box {
+        Some(view_box) => view_box,
+        None => {
+            // Every shape has at least one contour, so the bounds are always defined.
+            let mut min = Vector2::repeat(f32::MAX);
+            let mut max = Vector2::repeat(-f32::MAX);
+            for bounds in shapes.iter().filter_map(|shape| shape.path.bounds()) {
+                min = min.inf(&bounds.position);
+                max = max.sup(&(bounds.position + bounds.size));
+            }
+            Rect::new(min.x, min.y, max.x - min.x, max.y - min.y)
+        }
+    };
+
+    Ok(SvgDocument {
+        view_box,
+        size: size.unwrap_or(view_box.size),
+        shapes,
+    })
+}
+
+struct Element<'a> {
+    name: &'a str,
+    attributes: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Element<'a> {
+    fn attribute(&self, name: &str) -> Option<&'a str> {
+        self.attributes
+            .iter()
+            .find_map(|(n, v)| if *n == name { Some(*v) } else { None })
+    }
+
+    fn required(&self, name: &'static str) -> Result<&'a str, SvgError> {
+        self.attribute(name)
+            .ok_or_else(|| SvgError::MissingAttribute {
+                element: self.name.to_string(),
+                attribute: name,
+            })
+    }
+
+    fn number(&self, name: &str) -> Result<Option<f32>, SvgError> {
+        self.attribute(name).map(parse_length).transpose()
+    }
+
+    fn required_number(&self, name: &'static str) -> Result<f32, SvgError> {
+        parse_length(self.required(name)?)
+    }
+
+    /// Looks for a presentation attribute, either in `style` attribute or as a separate attribute.
+    fn style(&self, name: &str) -> Option<&'a str> {
+        self.attribute("style")
+            .and_then(|style| {
+                style.split(';').find_map(|declaration| {
+                    let (property, value) = declaration.split_once(':')?;
+                    (property.trim() == name).then_some(value.trim())
+                })
+            })
+            .or_else(|| self.attribute(name))
+    }
+
+    /// Fill color of the element, unknown colors are treated as black (the default fill color).
+    fn fill(&self) -> Option<Color> {
+        let mut color = match self.style("fill") {
+            Some(fill) => parse_color(fill)?,
+            None => Color::BLACK,
+        };
+        let opacity = self
+            .style("fill-opacity")
+            .and_then(|v| v.parse::<f32>().ok())
+            .unwrap_or(1.0)
+            * self
+                .style("opacity")
+                .and_then(|v| v.parse::<f32>().ok())
+                .unwrap_or(1.0);
+        color.a = (color.a as f32 * opacity.clamp(0.0, 1.0)) as u8;
+        Some(color)
+    }
+}
+
+/// Parses a color in `#rgb`, `#rrggbb` or `rgb(r, g, b)` form, or one of the basic color keywords.
+/// Returns `None` for `none`.
+fn parse_color(value: &str) -> Option<Color> {
+    let value = value.trim();
+    if let Some(hex) = value.strip_prefix('#') {
+        let digits = hex
+            .chars()
+            .map(|c| c.to_digit(16).map(|d| d as u8))
+            .collect::<Option<Vec<_>>>();
+        return Some(match digits.as_deref() {
+            Some([r, g, b]) => Color::opaque(r * 17, g * 17, b * 17),
+            Some([r1, r2, g1, g2, b1, b2]) => {
+                Color::opaque(r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2)
+            }
+            _ => Color::BLACK,
+        });
+    }
+    if let Some(arguments) = value
+        .strip_prefix("rgb(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        let mut components = arguments.split(',').map(|c| {
+            c.trim()
+                .parse::<f32>()
+                .unwrap_or_default()
+                .clamp(0.0, 255.0) as u8
+        });
+        return Some(Color::opaque(
+            components.next().unwrap_or_default(),
+            components.next().unwrap_or_default(),
+            components.next().unwrap_or_default(),
+        ));
+    }
+    match value {
+        "none" => None,
+        "transparent" => Some(Color::TRANSPARENT),
+        "white" => Some(Color::WHITE),
+        "red" => Some(Color::opaque(255, 0, 0)),
+        "green" => Some(Color::opaque(0, 128, 0)),
+        "lime" => Some(Color::opaque(0, 255, 0)),
+        "blue" => Some(Color::opaque(0, 0, 255)),
+        "yellow" => Some(Color::opaque(255, 255, 0)),
+        "orange" => Some(Color::opaque(255, 165, 0)),
+        "gray" | "grey" => Some(Color::opaque(128, 128, 128)),
+        _ => Some(Color::BLACK),
+    }
+}
+
+/// Parses a length attribute, unit suffixes (like `px`) are ignored.
+fn parse_length(value: &str) -> Result<f32, SvgError> {
+    let value = value.trim();
+    let number = value.trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '%');
+    number
+        .parse::<f32>()
+        .map_err(|_| SvgError::InvalidNumber(value.to_string()))
+}
+
+/// Iterates over all opening (or self-closing) tags of the document.
+fn elements(source: &str) -> impl Iterator<Item = Element<'_>> {
+    source.split('<').skip(1).filter_map(|tag| {
+        let tag = tag.split('>').next()?;
+        if tag.starts_with(['/', '!', '?']) {
+            return None;
+        }
+        let tag = tag.trim_end_matches('/');
+        let name_end = tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len());
+        let (name, mut rest) = tag.split_at(name_end);
+
+        let mut attributes = Vec::new();
+        while let Some(eq) = rest.find('=') {
+            let attribute_name = rest[..eq].trim();
+            let after = rest[eq + 1..].trim_start();
+            let quote = after.chars().next()?;
+            if quote != '"' && quote != '\'' {
+                break;
+            }
+            let value_end = after[1..].find(quote)?;
+            attributes.push((attribute_name, &after[1..value_end + 1]));
+            rest = &after[value_end + 2..];
+        }
+
+        Some(Element { name, attributes })
+    })
+}
+
+/// Parses a list of numbers separated by whitespaces and/or commas. Numbers could also be
+/// "glued" together, like `1.5.5` (which is `1.5` and `.5`) or `10-5` (`10` and `-5`).
+fn parse_numbers(data: &str) -> Result<Vec<f32>, SvgError> {
+    let mut numbers = Vec::new();
+    let mut tokenizer = Tokenizer::new(data);
+    while let Some(number) = tokenizer.number()? {
+        numbers.push(number);
+    }
+    Ok(numbers)
+}
+
+struct Tokenizer<'a> {
+    data: &'a str,
+    position: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn new(data: &'a str) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn skip_separators(&mut self) {
+        let bytes = self.data.as_bytes();
+        while self.position < bytes.len()
+            && (bytes[self.position].is_ascii_whitespace() || bytes[self.position] == b',')
+        {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_separators();
+        self.data.as_bytes().get(self.position).copied()
+    }
+
+    fn command(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        if c.is_ascii_alphabetic() && c != b'e' && c != b'E' {
+            self.position += 1;
+            Some(c as char)
+        } else {
+            None
+        }
+    }
+
+    fn number(&mut self) -> Result<Option<f32>, SvgError> {
+        let Some(c) = self.peek() else {
+            return Ok(None);
+        };
+        if !(c.is_ascii_digit() || c == b'-' || c == b'+' || c == b'.') {
+            return Ok(None);
+        }
+
+        let bytes = self.data.as_bytes();
+        let start = self.position;
+        let mut end = start;
+        if bytes[end] == b'-' || bytes[end] == b'+' {
+            end += 1;
+        }
+        let mut has_dot = false;
+        while end < bytes.len() {
+            match bytes[end] {
+                b'0'..=b'9' => end += 1,
+                b'.' if !has_dot => {
+                    has_dot = true;
+                    end += 1;
+                }
+                b'e' | b'E' => {
+                    end += 1;
+                    if end < bytes.len() && (bytes[end] == b'-' || bytes[end] == b'+') {
+                        end += 1;
+                    }
+                    while end < bytes.len() && bytes[end].is_ascii_digit() {
+                        end += 1;
+                    }
+                    break;
+                }
+                _ => break,
+            }
+        }
+        self.position = end;
+
+        let token = &self.data[start..end];
+        token
+            .parse::<f32>()
+            .map(Some)
+            .map_err(|_| SvgError::InvalidNumber(token.to_string()))
+    }
+
+    fn expect_number(&mut self) -> Result<f32, SvgError> {
+        self.number()?.ok_or(SvgError::UnexpectedEnd)
+    }
+
+    fn expect_point(&mut self) -> Result<Vector2<f32>, SvgError> {
+        Ok(Vector2::new(self.expect_number()?, self.expect_number()?))
+    }
+}
+
+fn parse_path_data(data: &str, contours: &mut Vec<Vec<Vector2<f32>>>) -> Result<(), SvgError> {
+    let mut tokenizer = Tokenizer::new(data);
+    let mut contour: Vec<Vector2<f32>> = Vec::new();
+    let mut current = Vector2::default();
+    let mut start = Vector2::default();
+    // Last control point of a curve, used by smooth curve commands (S, s, T, t).
+    let mut last_control: Option<Vector2<f32>> = None;
+    let mut command = None;
+
+    loop {
+        if let Some(new_command) = tokenizer.command() {
+            command = Some(new_command);
+        } else if tokenizer.peek().is_none() {
+            break;
+        }
+
+        let Some(cmd) = command else {
+            return Err(SvgError::UnexpectedEnd);
+        };
+        let relative = cmd.is_ascii_lowercase();
+        let offset = if relative {
+            current
+        } else {
+            Vector2::default()
+        };
+
+        match cmd.to_ascii_uppercase() {
+            'M' => {
+                if contour.len() >= 3 {
+                    contours.push(std::mem::take(&mut contour));
+                }
+                contour.clear();
+                current = offset + tokenizer.expect_point()?;
+                start = current;
+                contour.push(current);
+                last_control = None;
+                // Subsequent pairs of coordinates are implicit line-to commands.
+                command = Some(if relative { 'l' } else { 'L' });
+            }
+            'L' => {
+                current = offset + tokenizer.expect_point()?;
+                contour.push(current);
+                last_control = None;
+            }
+            'H' => {
+                let x = tokenizer.expect_number()?;
+                current.x = if relative { current.x + x } else { x };
+                contour.push(current);
+                last_control = None;
+            }
+            'V' => {
+                let y = tokenizer.expect_number()?;
+                current.y = if relative { current.y + y } else { y };
+                contour.push(current);
+                last_control = None;
+            }
+            'C' | 'S' => {
+                let control1 = if cmd.eq_ignore_ascii_case(&'C') {
+                    offset + tokenizer.expect_point()?
+                } else {
+                    last_control.map_or(current, |c| current * 2.0 - c)
+                };
+                let control2 = offset + tokenizer.expect_point()?;
+                let end = offset + tokenizer.expect_point()?;
+                for i in 1..=CURVE_SEGMENTS {
+                    let t = i as f32 / CURVE_SEGMENTS as f32;
+                    let it = 1.0 - t;
+                    contour.push(
+                        current * (it * it * it)
+                            + control1 * (3.0 * it * it * t)
+                            + control2 * (3.0 * it * t * t)
+                            + end * (t * t * t),
+                    );
+                }
+                current = end;
+                last_control = Some(control2);
+            }
+            'Q' | 'T' => {
+                let control = if cmd.eq_ignore_ascii_case(&'Q') {
+                    offset + tokenizer.expect_point()?
+                } else {
+                    last_control.map_or(current, |c| current * 2.0 - c)
+                };
+                let end = offset + tokenizer.expect_point()?;
+                for i in 1..=CURVE_SEGMENTS {
+                    let t = i as f32 / CURVE_SEGMENTS as f32;
+                    let it = 1.0 - t;
+                    contour.push(current * (it * it) + control * (2.0 * it * t) + end * (t * t));
+                }
+                current = end;
+                last_control = Some(control);
+            }
+            'Z' => {
+                if contour.len() >= 3 {
+                    contours.push(std::mem::take(&mut contour));
+                }
+                contour.clear();
+                current = start;
+                last_control = None;
+                command = None;
+            }
+            _ => return Err(SvgError::UnsupportedCommand(cmd)),
+        }
+    }
+
+    if contour.len() >= 3 {
+        contours.push(contour);
+    }
+
+    Ok(())
+}
+
+fn ellipse(center: Vector2<f32>, radius: Vector2<f32>) -> Vec<Vector2<f32>> {
+    (0..ELLIPSE_SEGMENTS)
+        .map(|i| {
+            let angle = i as f32 / ELLIPSE_SEGMENTS as f32 * std::f32::consts::TAU;
+            center + Vector2::new(angle.cos() * radius.x, angle.sin() * radius.y)
+        })
+        .collect()
+}
+
+fn rounded_rect(
+    position: Vector2<f32>,
+    size: Vector2<f32>,
+    radius: Vector2<f32>,
+) -> Vec<Vector2<f32>> {
+    if radius.x <= 0.0 || radius.y <= 0.0 {
+        return vec![
+            position,
+            position + Vector2::new(size.x, 0.0),
+            position + size,
+            position + Vector2::new(0.0, size.y),
+        ];
+    }
+
+    let corner_segments = ELLIPSE_SEGMENTS / 4;
+    let corners = [
+        (position + size - radius, 0.0),
+        (position + Vector2::new(radius.x, size.y - radius.y), 0.25),
+        (position + radius, 0.5),
+        (position + Vector2::new(size.x - radius.x, radius.y), 0.75),
+    ];
+    let mut points = Vec::with_capacity(4 * (corner_segments + 1));
+    for (center, start) in corners {
+        for i in 0..=corner_segments {
+            let angle = (start + i as f32 / corner_segments as f32 * 0.25) * std::f32::consts::TAU;
+            points.push(center + Vector2::new(angle.cos() * radius.x, angle.sin() * radius.y));
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_numbers() {
+        assert_eq!(
+            parse_numbers("1,2 3.5-4.5.5 1e2").unwrap(),
+            vec![1.0, 2.0, 3.5, -4.5, 0.5, 100.0]
+        );
+    }
+
+    #[test]
+    fn test_parse_path() {
+        let path = parse(
+            r#"<?xml version="1.0"?>
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <!-- A square with a triangular hole. -->
+                <path d="M0 0 H10 V10 h-10 Z m2 2 l2 0 l0 2 z"/>
+            </svg>"#,
+        )
+        .unwrap();
+
+        assert_eq!(path.contours.len(), 2);
+        assert_eq!(path.contours[0].len(), 4);
+        // Y axis is flipped.
+        assert_eq!(path.contours[0][2], Vector2::new(10.0, -10.0));
+        assert_eq!(path.contours[1][1], Vector2::new(4.0, -2.0));
+
+        // Even-odd filling.
+        assert!(path.signed_distance(Vector2::new(8.0, -8.0)) < 0.0);
+        assert!(path.signed_distance(Vector2::new(3.5, -2.5)) > 0.0);
+        assert!(path.signed_distance(Vector2::new(12.0, -5.0)) > 0.0);
+    }
+
+    #[test]
+    fn test_parse_basic_shapes() {
+        let path = parse(
+            r#"<svg>
+                <rect x="0" y="0" width="10px" height="5" rx="1"/>
+                <circle cx="20" cy="20" r="2"/>
+                <polygon points="0,0 1,0 1,1"/>
+                <line x1="0" y1="0" x2="1" y2="1"/>
+            </svg>"#,
+        )
+        .unwrap();
+
+        assert_eq!(path.contours.len(), 3);
+        assert!((path.signed_distance(Vector2::new(20.0, -20.0)) + 2.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_parse_document() {
+        let document = parse_document(
+            r##"<svg width="32" height="16" viewBox="0 0 64 32">
+                <rect width="64" height="32" fill="none"/>
+                <circle cx="16" cy="16" r="8" fill="#f00" opacity="0.5"/>
+                <path d="M32 0 L64 0 L64 32 Z" style="fill: rgb(0, 255, 0)"/>
+                <polygon points="0,0 1,0 1,1"/>
+            </svg>"##,
+        )
+        .unwrap();
+
+        assert_eq!(document.view_box, Rect::new(0.0, 0.0, 64.0, 32.0));
+        assert_eq!(document.size, Vector2::new(32.0, 16.0));
+        assert_eq!(document.shapes.len(), 4);
+        assert_eq!(document.shapes[0].fill, None);
+        assert_eq!(
+            document.shapes[1].fill,
+            Some(Color::from_rgba(255, 0, 0, 127))
+        );
+        assert_eq!(document.shapes[2].fill, Some(Color::opaque(0, 255, 0)));
+        assert_eq!(document.shapes[3].fill, Some(Color::BLACK));
+        // Y axis is not flipped.
+        assert_eq!(
+            document.shapes[2].path.contours[0][2],
+            Vector2::new(64.0, 32.0)
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(parse("<svg></svg>"), Err(SvgError::NoShapes));
+        assert_eq!(
+            parse(r#"<path d="M0 0 A 1 1 0 0 0 1 1"/>"#),
+            Err(SvgError::UnsupportedCommand('A'))
+        );
+        assert_eq!(
+            parse(r#"<path d="M0 0 L1"/>"#),
+            Err(SvgError::UnexpectedEnd)
+        );
+    }
+}
@@ -0,0 +1,323 @@
+//! Weather of a scene - precipitation, surface wetness, snow coverage and wind. See [`Weather`]
+//! docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Vector2, Vector3, Vector4},
+        color::Color,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid_provider,
+        visitor::prelude::*,
+    },
+    graph::SceneGraph,
+    scene::{graph::Graph, node::Node, sound::Sound},
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+// Wind speed (in m/s), at which the wind sound is played at full volume.
+const WIND_SOUND_REFERENCE_SPEED: f32 = 15.0;
+
+/// Kind of precipitation.
+#[derive(
+    Copy,
+    Clone,
+    Default,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum PrecipitationKind {
+    /// Rain makes surfaces wet. Rain drops are rendered as thin streaks, that leave small splashes
+    /// on collision with the geometry.
+    #[default]
+    Rain,
+    /// Snow covers surfaces facing up. Snow flakes are rendered as soft discs, that flutter while
+    /// falling.
+    Snow,
+}
+
+uuid_provider!(PrecipitationKind = "5f0e2c8b-4a7d-4b16-8f3e-2d9c1a6b7e45");
+
+/// Precipitation settings. Precipitation is simulated and rendered entirely on GPU in a box around
+/// the camera, particles collide with the depth buffer, so they do not fall through visible
+/// geometry.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct Precipitation {
+    /// Kind of the precipitation.
+    pub kind: PrecipitationKind,
+    /// Intensity of the precipitation in `[0; 1]` range. Zero means that there's no
+    /// precipitation at all.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub intensity: f32,
+    /// Amount of particles at full intensity.
+    #[reflect(min_value = 0.0)]
+    pub max_particle_count: u32,
+    /// Size of the box around the camera, where the particles are simulated.
+    #[reflect(min_value = 1.0)]
+    pub area_size: f32,
+    /// Falling speed of the particles in meters per second.
+    #[reflect(min_value = 0.0)]
+    pub fall_speed: f32,
+    /// Width and length of a particle.
+    pub particle_size: Vector2<f32>,
+    /// Color of the particles.
+    pub color: Color,
+    /// How far (in meters) a particle could move behind the visible geometry before it is
+    /// considered as collided. Collided particles are drawn as splashes on the surface.
+    #[reflect(min_value = 0.0)]
+    pub collision_thickness: f32,
+}
+
+impl Default for Precipitation {
+    fn default() -> Self {
+        Self {
+            kind: Default::default(),
+            intensity: 0.0,
+            max_particle_count: 20000,
+            area_size: 30.0,
+            fall_speed: 9.0,
+            particle_size: Vector2::new(0.01, 0.4),
+            color: Color::from_rgba(180, 190, 210, 90),
+            collision_thickness: 0.5,
+        }
+    }
+}
+
+impl Precipitation {
+    /// Returns amount of particles for the current intensity.
+    pub fn particle_count(&self) -> u32 {
+        (self.max_particle_count as f32 * self.intensity.clamp(0.0, 1.0)) as u32
+    }
+}
+
+/// Wind settings. Wind is shared between precipitation, vegetation (via `fyrox_wind` built-in
+/// shader property) and audio (see [`Weather::wind_sound`]).
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct Wind {
+    /// Direction of the wind. It does not need to be normalized.
+    pub direction: Vector3<f32>,
+    /// Average speed of the wind in meters per second.
+    #[reflect(min_value = 0.0)]
+    pub strength: f32,
+    /// Maximum additional speed of the wind during gusts in meters per second.
+    #[reflect(min_value = 0.0)]
+    pub gust_strength: f32,
+    /// Amount of gusts per second.
+    #[reflect(min_value = 0.0)]
+    pub gust_frequency: f32,
+}
+
+impl Default for Wind {
+    fn default() -> Self {
+        Self {
+            direction: Vector3::x(),
+            strength: 0.0,
+            gust_strength: 0.0,
+            gust_frequency: 0.2,
+        }
+    }
+}
+
+impl Wind {
+    /// Returns velocity of the wind at the given time.
+    pub fn velocity(&self, time: f32) -> Vector3<f32> {
+        let phase = time * self.gust_frequency * std::f32::consts::TAU;
+        // Sum of two incommensurable waves gives non-repeating gusts.
+        let gust = 0.5 + 0.25 * (phase.sin() + (phase * 0.37 + 1.3).sin());
+        let speed = self.strength + self.gust_strength * gust;
+        self.direction
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::zeros)
+            .scale(speed)
+    }
+}
+
+/// A set of values, that is passed to shaders using `fyrox_weather` and `fyrox_wind` built-in
+/// properties.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct WeatherShaderData {
+    /// Wetness, snow coverage, time and precipitation intensity.
+    pub weather: Vector4<f32>,
+    /// Velocity of the wind (xyz) and its speed (w).
+    pub wind: Vector4<f32>,
+}
+
+/// Weather of a scene. It coordinates precipitation particles, a global wetness and snow coverage
+/// of the surfaces (applied by the standard shaders) and wind, that is shared with vegetation
+/// and audio.
+///
+/// Wetness and snow coverage are changed gradually: rain makes surfaces wet and melts snow, snow
+/// accumulates on surfaces, and everything dries out when there's no precipitation.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::scene::{weather::PrecipitationKind, Scene};
+/// fn start_storm(scene: &mut Scene) {
+///     let weather = &mut scene.weather;
+///     weather.precipitation.kind = PrecipitationKind::Rain;
+///     weather.precipitation.intensity = 1.0;
+///     weather.wind.strength = 10.0;
+///     weather.wind.gust_strength = 8.0;
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct Weather {
+    /// Whether the weather is enabled or not. Disabled weather is not rendered and does not
+    /// affect the materials.
+    pub enabled: bool,
+    /// Precipitation settings.
+    pub precipitation: Precipitation,
+    /// Wind settings.
+    pub wind: Wind,
+    /// Current wetness of the surfaces in `[0; 1]` range.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub wetness: f32,
+    /// Current snow coverage of the surfaces in `[0; 1]` range.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub snow_coverage: f32,
+    /// How fast surfaces become wet during the rain of full intensity (units per second).
+    #[reflect(min_value = 0.0)]
+    pub wetting_speed: f32,
+    /// How fast surfaces dry out when there's no rain (units per second).
+    #[reflect(min_value = 0.0)]
+    pub drying_speed: f32,
+    /// How fast snow accumulates during the snowfall of full intensity (units per second).
+    #[reflect(min_value = 0.0)]
+    pub snow_accumulation_speed: f32,
+    /// How fast snow melts when there's no snowfall (units per second).
+    #[reflect(min_value = 0.0)]
+    pub melting_speed: f32,
+    /// A sound source, which gain is driven by the intensity of the rain.
+    pub rain_sound: Handle<Node>,
+    /// A sound source, which gain is driven by the speed of the wind.
+    pub wind_sound: Handle<Node>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    time: f32,
+}
+
+impl Default for Weather {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            precipitation: Default::default(),
+            wind: Default::default(),
+            wetness: 0.0,
+            snow_coverage: 0.0,
+            wetting_speed: 0.05,
+            drying_speed: 0.01,
+            snow_accumulation_speed: 0.01,
+            melting_speed: 0.005,
+            rain_sound: Default::default(),
+            wind_sound: Default::default(),
+            time: 0.0,
+        }
+    }
+}
+
+impl Weather {
+    /// Returns time (in seconds) of the weather simulation.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Returns current velocity of the wind.
+    pub fn wind_velocity(&self) -> Vector3<f32> {
+        self.wind.velocity(self.time)
+    }
+
+    /// Updates wetness and snow coverage of the surfaces and gain of the weather sounds.
+    pub fn update(&mut self, dt: f32, graph: &mut Graph) {
+        if !self.enabled {
+            return;
+        }
+
+        self.time += dt;
+
+        let intensity = self.precipitation.intensity.clamp(0.0, 1.0);
+        let (rain, snow) = match self.precipitation.kind {
+            PrecipitationKind::Rain => (intensity, 0.0),
+            PrecipitationKind::Snow => (0.0, intensity),
+        };
+
+        if rain > 0.0 {
+            self.wetness += self.wetting_speed * rain * dt;
+            // Rain washes away the snow.
+            self.snow_coverage -= self.melting_speed * (1.0 + rain) * dt;
+        } else {
+            self.wetness -= self.drying_speed * dt;
+            if snow > 0.0 {
+                self.snow_coverage += self.snow_accumulation_speed * snow * dt;
+            } else {
+                self.snow_coverage -= self.melting_speed * dt;
+            }
+        }
+        self.wetness = self.wetness.clamp(0.0, 1.0);
+        self.snow_coverage = self.snow_coverage.clamp(0.0, 1.0);
+
+        let wind_gain = (self.wind_velocity().norm() / WIND_SOUND_REFERENCE_SPEED).min(1.0);
+        for (handle, gain) in [(self.rain_sound, rain), (self.wind_sound, wind_gain)] {
+            if let Some(sound) = graph.try_get_mut_of_type::<Sound>(handle) {
+                sound.set_gain(gain);
+            }
+        }
+    }
+
+    /// Returns a set of values, that is passed to shaders.
+    pub fn shader_data(&self) -> WeatherShaderData {
+        if !self.enabled {
+            return Default::default();
+        }
+
+        let wind = self.wind_velocity();
+        WeatherShaderData {
+            weather: Vector4::new(
+                self.wetness,
+                self.snow_coverage,
+                self.time,
+                self.precipitation.intensity.clamp(0.0, 1.0),
+            ),
+            wind: Vector4::new(wind.x, wind.y, wind.z, wind.norm()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::scene::{
+        graph::Graph,
+        weather::{PrecipitationKind, Weather},
+    };
+
+    #[test]
+    fn test_weather_surfaces() {
+        let mut graph = Graph::new();
+        let mut weather = Weather::default();
+        weather.precipitation.intensity = 1.0;
+
+        for _ in 0..10 {
+            weather.update(1.0, &mut graph);
+        }
+        assert!(weather.wetness > 0.4);
+        assert_eq!(weather.snow_coverage, 0.0);
+
+        weather.precipitation.kind = PrecipitationKind::Snow;
+        weather.update(10.0, &mut graph);
+        assert!(weather.snow_coverage > 0.0);
+        // Surfaces dry out under snow.
+        assert!(weather.wetness < 0.5);
+
+        weather.precipitation.intensity = 0.0;
+        weather.update(1000.0, &mut graph);
+        assert_eq!(weather.wetness, 0.0);
+        assert_eq!(weather.snow_coverage, 0.0);
+    }
+}
@@ -546,6 +546,52 @@ impl<'a, 'b, 'c> UniversalScriptContext for ScriptDeinitContext<'a, 'b, 'c> {
     }
 }
 
+/// Defines how often [`ScriptTrait::on_update`] method of a script is called. Reducing update rate
+/// of scripts, that do not need to be updated every frame (for example - distant AI agents), can
+/// significantly improve performance. Skipped frames are not lost - their delta time is
+/// accumulated and passed to the next `on_update` call (see [`ScriptContext::dt`]).
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub enum ScriptUpdateRate {
+    /// The script is updated every frame.
+    #[default]
+    EveryFrame,
+
+    /// The script is updated once in the given amount of frames. Updates of scripts with the same
+    /// rate are spread evenly across frames.
+    EveryNthFrame(u32),
+
+    /// The update rate depends on the distance from the node of the script to the observer (the
+    /// first enabled camera of the scene). Every `bucket_size` units of distance add one more frame
+    /// to the update interval, but the interval cannot exceed `max_interval` frames. The script is
+    /// updated every frame if there is no observer.
+    DistanceBased {
+        /// Size of a single distance bucket.
+        bucket_size: f32,
+        /// Maximum interval (in frames) between updates.
+        max_interval: u32,
+    },
+}
+
+impl ScriptUpdateRate {
+    /// Calculates an interval (in frames) between updates using the given distance to the
+    /// observer.
+    pub fn interval(self, distance_to_observer: Option<f32>) -> u32 {
+        match self {
+            ScriptUpdateRate::EveryFrame => 1,
+            ScriptUpdateRate::EveryNthFrame(n) => n.max(1),
+            ScriptUpdateRate::DistanceBased {
+                bucket_size,
+                max_interval,
+            } => match distance_to_observer {
+                Some(distance) if bucket_size > 0.0 => {
+                    (1 + (distance / bucket_size) as u32).clamp(1, max_interval.max(1))
+                }
+                _ => 1,
+            },
+        }
+    }
+}
+
 /// Script is a set predefined methods that are called on various stages by the engine. It is used to add
 /// custom behaviour to game entities.
 pub trait ScriptTrait: BaseScript + ComponentProvider {
@@ -566,6 +612,13 @@ pub trait ScriptTrait: BaseScript + ComponentProvider {
     /// be called once.
     fn on_start(&mut self, #[allow(unused_variables)] ctx: &mut ScriptContext) {}
 
+    /// The method is called when the node of the script was taken from a
+    /// [`crate::scene::prefab_pool::PrefabPool`] for reuse. It should be used to reset the state
+    /// of the script to its initial values (health, timers, etc.), because [`ScriptTrait::on_init`]
+    /// and [`ScriptTrait::on_start`] are not called again for recycled nodes. The method is called
+    /// only for initialized scripts.
+    fn on_recycle(&mut self, #[allow(unused_variables)] ctx: &mut ScriptContext) {}
+
     /// The method is called when the script is about to be destroyed. It is guaranteed to be called last.
     fn on_deinit(&mut self, #[allow(unused_variables)] ctx: &mut ScriptDeinitContext) {}
 
@@ -584,6 +637,13 @@ pub trait ScriptTrait: BaseScript + ComponentProvider {
     /// [`crate::engine::executor::Executor::set_desired_update_rate`] method.
     fn on_update(&mut self, #[allow(unused_variables)] ctx: &mut ScriptContext) {}
 
+    /// Defines how often [`ScriptTrait::on_update`] should be called. The method is called every
+    /// frame, so the rate could be changed dynamically. See [`ScriptUpdateRate`] docs for more
+    /// info.
+    fn update_rate(&self) -> ScriptUpdateRate {
+        ScriptUpdateRate::EveryFrame
+    }
+
     /// Allows you to react to certain script messages. It could be used for communication between scripts; to
     /// bypass borrowing issues. If you need to receive messages of a particular type, you must subscribe to a type
     /// explicitly. Usually it is done in [`ScriptTrait::on_start`] method:
@@ -638,6 +698,10 @@ pub struct Script {
     instance: Box<dyn ScriptTrait>,
     pub(crate) initialized: bool,
     pub(crate) started: bool,
+    // Delta time of the frames that were skipped because of reduced update rate.
+    pub(crate) accumulated_dt: f32,
+    // Delta time for the next `on_update` call, `None` - use delta time of the current frame.
+    pub(crate) update_dt: Option<f32>,
 }
 
 impl TypeUuidProvider for Script {
@@ -778,6 +842,8 @@ impl Clone for Script {
             instance: self.instance.clone_box(),
             initialized: false,
             started: false,
+            accumulated_dt: 0.0,
+            update_dt: None,
         }
     }
 }
@@ -790,6 +856,8 @@ impl Script {
             instance: Box::new(script_object),
             initialized: false,
             started: false,
+            accumulated_dt: 0.0,
+            update_dt: None,
         }
     }
 
@@ -834,7 +902,7 @@ mod test {
             variable::InheritableVariable, visitor::prelude::*,
         },
         scene::base::Base,
-        script::{Script, ScriptTrait},
+        script::{Script, ScriptTrait, ScriptUpdateRate},
     };
     use fyrox_core::uuid_provider;
 
@@ -914,4 +982,20 @@ mod test {
             3.21
         );
     }
+
+    #[test]
+    fn test_script_update_rate_interval() {
+        assert_eq!(ScriptUpdateRate::EveryFrame.interval(Some(100.0)), 1);
+        assert_eq!(ScriptUpdateRate::EveryNthFrame(0).interval(None), 1);
+        assert_eq!(ScriptUpdateRate::EveryNthFrame(3).interval(None), 3);
+
+        let rate = ScriptUpdateRate::DistanceBased {
+            bucket_size: 10.0,
+            max_interval: 4,
+        };
+        assert_eq!(rate.interval(None), 1);
+        assert_eq!(rate.interval(Some(5.0)), 1);
+        assert_eq!(rate.interval(Some(15.0)), 2);
+        assert_eq!(rate.interval(Some(1000.0)), 4);
+    }
 }
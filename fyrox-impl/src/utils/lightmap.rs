@@ -159,7 +159,8 @@ struct Instance {
     transform: Matrix4<f32>,
 }
 
-/// Small helper that allows you stop lightmap generation in any time.
+/// Small helper that allows you stop lightmap generation (or any other engine task, see
+/// [`crate::engine::task::TaskPoolHandler::spawn_engine_task`]) in any time.
 #[derive(Clone, Default)]
 pub struct CancellationToken(pub Arc<AtomicBool>);
 
@@ -252,13 +253,21 @@ impl ProgressData {
         self.stage.store(stage as u32, atomic::Ordering::SeqCst);
     }
 
+    /// Sets progress of the current stage. It could be used by tasks, that are not split into stages.
+    pub fn set_progress(&self, progress: u32, max_iterations: u32) {
+        self.max_iterations
+            .store(max_iterations, atomic::Ordering::SeqCst);
+        self.progress.store(progress, atomic::Ordering::SeqCst);
+    }
+
     /// Advances progress.
     fn advance_progress(&self) {
         self.progress.fetch_add(1, atomic::Ordering::SeqCst);
     }
 }
 
-/// Small helper that allows you to track progress of lightmap generation.
+/// Small helper that allows you to track progress of lightmap generation (or any other engine task, see
+/// [`crate::engine::task::TaskPoolHandler::spawn_engine_task`]).
 #[derive(Clone, Default)]
 pub struct ProgressIndicator(pub Arc<ProgressData>);
 
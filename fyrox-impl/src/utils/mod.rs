@@ -6,8 +6,12 @@ pub mod astar;
 pub mod behavior;
 pub mod lightmap;
 pub mod navmesh;
+pub mod path_tracer;
+pub mod placement;
 pub mod raw_mesh;
 pub mod uvgen;
+pub mod visual_test;
+pub mod voxel_ao;
 
 use crate::{
     core::algebra::{Vector2, Vector3},
@@ -525,35 +529,58 @@ pub fn translate_event(event: &WindowEvent) -> Option<OsEvent> {
             force,
             id,
             ..
-        }) => Some(OsEvent::Touch {
-            phase: match phase {
+        }) => {
+            let phase = match phase {
                 winit::event::TouchPhase::Started => fyrox_ui::message::TouchPhase::Started,
                 winit::event::TouchPhase::Moved => fyrox_ui::message::TouchPhase::Moved,
                 winit::event::TouchPhase::Ended => fyrox_ui::message::TouchPhase::Ended,
                 winit::event::TouchPhase::Cancelled => fyrox_ui::message::TouchPhase::Cancelled,
-            },
-            location: Vector2::new(location.x as f32, location.y as f32),
-            force: match force {
-                Some(force) => match force {
-                    winit::event::Force::Calibrated {
-                        force,
-                        max_possible_force,
-                        altitude_angle,
-                    } => Some(fyrox_ui::message::Force::Calibrated {
-                        force: force.to_be_bytes(),
-                        max_possible_force: max_possible_force.to_be_bytes(),
-                        altitude_angle: altitude_angle
-                            .as_ref()
-                            .map(|altitude_angle| altitude_angle.to_be_bytes()),
-                    }),
-                    winit::event::Force::Normalized(value) => {
-                        Some(fyrox_ui::message::Force::Normalized(value.to_be_bytes()))
-                    }
+            };
+            let location = Vector2::new(location.x as f32, location.y as f32);
+
+            // Only pens report altitude angle. Winit does not report the direction of the tilt,
+            // so it is always along X axis.
+            if let Some(
+                force @ winit::event::Force::Calibrated {
+                    altitude_angle: Some(altitude_angle),
+                    ..
                 },
-                None => None,
-            },
-            id: *id,
-        }),
+            ) = force
+            {
+                return Some(OsEvent::Pen {
+                    phase,
+                    location,
+                    pressure: force.normalized().clamp(0.0, 1.0) as f32,
+                    tilt: Vector2::new((std::f64::consts::FRAC_PI_2 - altitude_angle) as f32, 0.0),
+                    id: *id,
+                });
+            }
+
+            Some(OsEvent::Touch {
+                phase,
+                location,
+                force: match force {
+                    Some(force) => match force {
+                        winit::event::Force::Calibrated {
+                            force,
+                            max_possible_force,
+                            altitude_angle,
+                        } => Some(fyrox_ui::message::Force::Calibrated {
+                            force: force.to_be_bytes(),
+                            max_possible_force: max_possible_force.to_be_bytes(),
+                            altitude_angle: altitude_angle
+                                .as_ref()
+                                .map(|altitude_angle| altitude_angle.to_be_bytes()),
+                        }),
+                        winit::event::Force::Normalized(value) => {
+                            Some(fyrox_ui::message::Force::Normalized(value.to_be_bytes()))
+                        }
+                    },
+                    None => None,
+                },
+                id: *id,
+            })
+        }
         _ => None,
     }
 }
@@ -0,0 +1,1253 @@
+//! Navmesh baking from the scene geometry. See [`NavmeshBuilder`] docs for more info.
+//!
+//! The algorithm is similar to the one, that is used in Recast:
+//!
+//! 1) Every source triangle is voxelized into a height field - a grid of columns, where each
+//! column contains a set of solid spans. Top of a span is marked as walkable if the slope of the
+//! triangle, that produced it, is less than max agent slope.
+//! 2) Walkable spans with enough free space above them (at least the height of the agent) are
+//! converted into "open" spans, that are connected with the open spans in the neighbouring columns
+//! if the agent can climb from one to another.
+//! 3) Walkable area is eroded by the radius of the agent, so the agent won't be able to walk too
+//! close to the walls.
+//! 4) Open spans are merged into rectangles, which are then triangulated. Vertices of the
+//! rectangles are shared, so the resulting navmesh is connected.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, Vector2, Vector3},
+        math::TriangleDefinition,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid_provider,
+        visitor::prelude::*,
+    },
+    scene::{
+        collider::{Collider, ColliderShape},
+        graph::Graph,
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait},
+            Mesh,
+        },
+        node::Node,
+        terrain::Terrain,
+    },
+    utils::navmesh::Navmesh,
+};
+use fxhash::{FxHashMap, FxHashSet};
+use fyrox_graph::{BaseSceneGraph, SceneGraph};
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, ops::Range};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// A set of parameters for navmesh baking.
+#[derive(Clone, Debug, PartialEq, Reflect, Visit, Serialize, Deserialize)]
+pub struct NavmeshBakeSettings {
+    /// Size of a cell of the voxel grid on XZ plane (in meters). Smaller cells give more precise
+    /// results, but baking takes more time.
+    #[reflect(min_value = 0.01, step = 0.05)]
+    pub cell_size: f32,
+
+    /// Size of a cell of the voxel grid along Y axis (in meters).
+    #[reflect(min_value = 0.01, step = 0.05)]
+    pub cell_height: f32,
+
+    /// Radius of the agent (in meters). Walkable area will be shrunk by this value from every
+    /// obstacle.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub agent_radius: f32,
+
+    /// Height of the agent (in meters). Places with less free space above the floor are not
+    /// walkable.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub agent_height: f32,
+
+    /// Max height of a step (in meters), that the agent can climb.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub agent_max_climb: f32,
+
+    /// Max slope (in degrees) of a surface, that the agent can walk on.
+    #[reflect(min_value = 0.0, max_value = 90.0, step = 1.0)]
+    pub agent_max_slope: f32,
+
+    /// Max size (in cells) of a single navmesh polygon along each axis.
+    #[reflect(min_value = 1.0)]
+    pub max_polygon_size: u32,
+
+    /// Size of a tile (in cells) along each axis. Dynamic navmeshes re-bake only the tiles, that
+    /// were affected by obstacles, so smaller tiles makes re-baking faster, but adds more vertices
+    /// on the borders of the tiles.
+    #[reflect(min_value = 1.0)]
+    pub tile_size: u32,
+
+    /// Whether to use meshes as source geometry or not.
+    pub use_meshes: bool,
+
+    /// Whether to use colliders as source geometry or not. Only cuboid, cylinder and triangle
+    /// shapes are supported, other shapes are ignored.
+    pub use_colliders: bool,
+
+    /// Whether to use terrains as source geometry or not.
+    pub use_terrains: bool,
+
+    /// A set of nodes, which descendants (including the nodes themselves) will be used as source
+    /// geometry. If empty, the entire graph is used.
+    #[serde(skip)]
+    #[reflect(hidden)]
+    pub sources: Vec<Handle<Node>>,
+}
+
+impl Default for NavmeshBakeSettings {
+    fn default() -> Self {
+        Self {
+            cell_size: 0.3,
+            cell_height: 0.2,
+            agent_radius: 0.6,
+            agent_height: 2.0,
+            agent_max_climb: 0.9,
+            agent_max_slope: 45.0,
+            max_polygon_size: 16,
+            tile_size: 32,
+            use_meshes: true,
+            use_colliders: true,
+            use_terrains: true,
+            sources: Default::default(),
+        }
+    }
+}
+
+/// Navmesh builder creates navigational meshes from the scene geometry (meshes, colliders and
+/// terrains), using the parameters of an agent. See [module docs](self) for the description of the
+/// algorithm.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     scene::Scene,
+/// #     utils::navmesh::bake::{NavmeshBakeSettings, NavmeshBuilder},
+/// # };
+/// fn bake_navmesh(scene: &mut Scene) {
+///     let navmesh = NavmeshBuilder::bake(
+///         &scene.graph,
+///         &NavmeshBakeSettings {
+///             agent_radius: 0.4,
+///             agent_height: 1.8,
+///             ..Default::default()
+///         },
+///     );
+///     println!("Baked {} triangles", navmesh.triangles().len());
+/// }
+/// ```
+pub struct NavmeshBuilder;
+
+impl NavmeshBuilder {
+    /// Bakes a navmesh from the geometry of the given graph. The result is in world coordinates.
+    pub fn bake(graph: &Graph, settings: &NavmeshBakeSettings) -> Navmesh {
+        TiledNavmeshBaker::from_graph(graph, settings).navmesh()
+    }
+
+    /// Bakes a navmesh from the given triangles (in world coordinates).
+    pub fn bake_triangles(
+        triangles: &[[Vector3<f32>; 3]],
+        settings: &NavmeshBakeSettings,
+    ) -> Navmesh {
+        TiledNavmeshBaker::new(triangles, settings).navmesh()
+    }
+}
+
+/// Collects the source geometry for baking. `get` must return a node by its handle, `nodes` must
+/// iterate over every node of the graph. Subtrees of `excluded` nodes are ignored.
+pub(crate) fn collect_triangles<'a>(
+    get: impl Fn(Handle<Node>) -> Option<&'a Node>,
+    nodes: impl Iterator<Item = (Handle<Node>, &'a Node)>,
+    excluded: &FxHashSet<Handle<Node>>,
+    settings: &NavmeshBakeSettings,
+) -> Vec<[Vector3<f32>; 3]> {
+    let mut triangles = Vec::new();
+
+    let mut visit = |node: &Node| {
+        if !node.is_globally_enabled() {
+            return;
+        }
+
+        let transform = node.global_transform();
+        let mut push = |a: Vector3<f32>, b: Vector3<f32>, c: Vector3<f32>| {
+            triangles.push([a, b, c].map(|v| transform.transform_point(&Point3::from(v)).coords));
+        };
+
+        if let Some(mesh) = node.cast::<Mesh>() {
+            if !settings.use_meshes {
+                return;
+            }
+
+            for surface in mesh.surfaces() {
+                let data = surface.data();
+                let data = data.data_ref();
+                let vertex_buffer = &data.vertex_buffer;
+                let position = |i: u32| {
+                    vertex_buffer
+                        .get(i as usize)
+                        .and_then(|v| v.read_3_f32(VertexAttributeUsage::Position).ok())
+                };
+                for triangle in data.geometry_buffer.iter() {
+                    if let (Some(a), Some(b), Some(c)) = (
+                        position(triangle[0]),
+                        position(triangle[1]),
+                        position(triangle[2]),
+                    ) {
+                        push(a, b, c);
+                    }
+                }
+            }
+        } else if let Some(collider) = node.cast::<Collider>() {
+            if settings.use_colliders {
+                collider_triangles(collider.shape(), &mut push);
+            }
+        } else if let Some(terrain) = node.cast::<Terrain>() {
+            if !settings.use_terrains {
+                return;
+            }
+
+            for chunk in terrain.chunks_ref() {
+                let origin = chunk.local_position();
+                let size = chunk.physical_size();
+                let cells = chunk.height_map_size().map(|s| s.saturating_sub(1).max(1));
+                let point = |x: u32, z: u32| {
+                    let position = origin
+                        + Vector2::new(
+                            size.x * x as f32 / cells.x as f32,
+                            size.y * z as f32 / cells.y as f32,
+                        );
+                    let height = terrain.height_at(position).unwrap_or_default();
+                    Vector3::new(position.x, height, position.y)
+                };
+                for z in 0..cells.y {
+                    for x in 0..cells.x {
+                        let (a, b, c, d) = (
+                            point(x, z),
+                            point(x + 1, z),
+                            point(x + 1, z + 1),
+                            point(x, z + 1),
+                        );
+                        push(a, d, c);
+                        push(a, c, b);
+                    }
+                }
+            }
+        }
+    };
+
+    let mut stack = if settings.sources.is_empty() {
+        nodes
+            .filter_map(|(handle, node)| node.parent().is_none().then_some(handle))
+            .collect::<Vec<_>>()
+    } else {
+        settings.sources.clone()
+    };
+    let mut visited = FxHashSet::default();
+    while let Some(handle) = stack.pop() {
+        if excluded.contains(&handle) || !visited.insert(handle) {
+            continue;
+        }
+        if let Some(node) = get(handle) {
+            visit(node);
+            stack.extend_from_slice(node.children());
+        }
+    }
+
+    triangles
+}
+
+fn collider_triangles(
+    shape: &ColliderShape,
+    push: &mut impl FnMut(Vector3<f32>, Vector3<f32>, Vector3<f32>),
+) {
+    match shape {
+        ColliderShape::Cuboid(cuboid) => {
+            let e = cuboid.half_extents;
+            let corner = |i: usize| {
+                Vector3::new(
+                    if i & 1 != 0 { e.x } else { -e.x },
+                    if i & 2 != 0 { e.y } else { -e.y },
+                    if i & 4 != 0 { e.z } else { -e.z },
+                )
+            };
+            // Faces as quads of corner indices.
+            for [a, b, c, d] in [
+                [0, 1, 5, 4],
+                [2, 6, 7, 3],
+                [0, 4, 6, 2],
+                [1, 3, 7, 5],
+                [0, 2, 3, 1],
+                [4, 5, 7, 6],
+            ] {
+                push(corner(a), corner(b), corner(c));
+                push(corner(a), corner(c), corner(d));
+            }
+        }
+        ColliderShape::Cylinder(cylinder) => {
+            const SIDES: usize = 16;
+            let point = |i: usize, y: f32| {
+                let angle = i as f32 / SIDES as f32 * std::f32::consts::TAU;
+                Vector3::new(
+                    angle.cos() * cylinder.radius,
+                    y,
+                    angle.sin() * cylinder.radius,
+                )
+            };
+            let top = Vector3::new(0.0, cylinder.half_height, 0.0);
+            let bottom = -top;
+            for i in 0..SIDES {
+                let j = (i + 1) % SIDES;
+                let (a, b) = (
+                    point(i, cylinder.half_height),
+                    point(j, cylinder.half_height),
+                );
+                let (c, d) = (
+                    point(i, -cylinder.half_height),
+                    point(j, -cylinder.half_height),
+                );
+                push(top, b, a);
+                push(bottom, c, d);
+                push(a, b, d);
+                push(a, d, c);
+            }
+        }
+        ColliderShape::Triangle(triangle) => push(triangle.a, triangle.b, triangle.c),
+        _ => (),
+    }
+}
+
+/// Splits the polygon by the plane, that is perpendicular to the given axis. Returns two polygons
+/// - the first one lies below the plane, the second one - above.
+fn split_polygon(
+    polygon: &[Vector3<f32>],
+    value: f32,
+    axis: usize,
+) -> (Vec<Vector3<f32>>, Vec<Vector3<f32>>) {
+    let mut below = Vec::new();
+    let mut above = Vec::new();
+    for (i, a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        let da = value - a[axis];
+        let db = value - b[axis];
+        if da >= 0.0 {
+            below.push(*a);
+        }
+        if da <= 0.0 {
+            above.push(*a);
+        }
+        if (da > 0.0 && db < 0.0) || (da < 0.0 && db > 0.0) {
+            let point = a.lerp(&b, da / (da - db));
+            below.push(point);
+            above.push(point);
+        }
+    }
+    (below, above)
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Span {
+    min: i32,
+    max: i32,
+    walkable: bool,
+}
+
+struct HeightField {
+    origin: Vector3<f32>,
+    width: usize,
+    depth: usize,
+    cell_size: f32,
+    cell_height: f32,
+    walkable_normal_y: f32,
+    merge_threshold: i32,
+    // Spans of every column, sorted from bottom to top.
+    columns: Vec<Vec<Span>>,
+}
+
+impl HeightField {
+    fn new(triangles: &[[Vector3<f32>; 3]], settings: &NavmeshBakeSettings) -> Self {
+        let mut min = Vector3::repeat(f32::MAX);
+        let mut max = Vector3::repeat(-f32::MAX);
+        for vertex in triangles.iter().flatten() {
+            min = min.inf(vertex);
+            max = max.sup(vertex);
+        }
+
+        let cell_size = settings.cell_size.max(0.01);
+        let cell_height = settings.cell_height.max(0.01);
+        let (width, depth) = if triangles.is_empty() {
+            min = Vector3::default();
+            (0, 0)
+        } else {
+            (
+                ((max.x - min.x) / cell_size).ceil() as usize + 1,
+                ((max.z - min.z) / cell_size).ceil() as usize + 1,
+            )
+        };
+
+        Self {
+            origin: min,
+            width,
+            depth,
+            cell_size,
+            cell_height,
+            walkable_normal_y: settings.agent_max_slope.clamp(0.0, 90.0).to_radians().cos(),
+            merge_threshold: (settings.agent_max_climb / cell_height).floor() as i32,
+            columns: vec![Vec::new(); width * depth],
+        }
+    }
+
+    fn add_span(&mut self, x: usize, z: usize, mut new: Span) {
+        let column = &mut self.columns[z * self.width + x];
+        let mut i = 0;
+        while i < column.len() {
+            let span = column[i];
+            if span.min > new.max {
+                break;
+            }
+            if span.max < new.min {
+                i += 1;
+                continue;
+            }
+
+            // Overlapping spans are merged, walkable flag is taken from the top one.
+            new.walkable = if (span.max - new.max).abs() <= self.merge_threshold {
+                span.walkable || new.walkable
+            } else if span.max > new.max {
+                span.walkable
+            } else {
+                new.walkable
+            };
+            new.min = new.min.min(span.min);
+            new.max = new.max.max(span.max);
+            column.remove(i);
+        }
+        column.insert(i, new);
+    }
+
+    fn rasterize_triangle(&mut self, triangle: &[Vector3<f32>; 3]) {
+        let normal = (triangle[1] - triangle[0]).cross(&(triangle[2] - triangle[0]));
+        let Some(normal) = normal.try_normalize(f32::EPSILON) else {
+            return;
+        };
+        // Only front faces (counter-clockwise winding) could be walkable, so the insides of closed
+        // meshes won't be walkable.
+        let walkable = normal.y >= self.walkable_normal_y;
+
+        let min = triangle[0].inf(&triangle[1]).inf(&triangle[2]);
+        let max = triangle[0].sup(&triangle[1]).sup(&triangle[2]);
+        let cell_size = self.cell_size;
+        let cell = |value: f32, origin: f32, count: usize| {
+            (((value - origin) / cell_size).floor().max(0.0) as usize).min(count - 1)
+        };
+
+        let mut rest = triangle.to_vec();
+        for z in cell(min.z, self.origin.z, self.depth)..=cell(max.z, self.origin.z, self.depth) {
+            let (row, above) =
+                split_polygon(&rest, self.origin.z + (z + 1) as f32 * self.cell_size, 2);
+            rest = above;
+            if row.len() < 3 {
+                continue;
+            }
+
+            let mut row_rest = row;
+            for x in cell(min.x, self.origin.x, self.width)..=cell(max.x, self.origin.x, self.width)
+            {
+                let (cell_polygon, right) = split_polygon(
+                    &row_rest,
+                    self.origin.x + (x + 1) as f32 * self.cell_size,
+                    0,
+                );
+                row_rest = right;
+                if cell_polygon.len() < 3 {
+                    continue;
+                }
+
+                let (y_min, y_max) = cell_polygon
+                    .iter()
+                    .fold((f32::MAX, -f32::MAX), |(min, max), v| {
+                        (min.min(v.y), max.max(v.y))
+                    });
+                let span_min = ((y_min - self.origin.y) / self.cell_height).floor() as i32;
+                // Tolerance prevents surfaces, that lie exactly on the border of a cell, from being
+                // lifted by a whole cell because of floating point errors.
+                let span_max = ((y_max - self.origin.y) / self.cell_height - 1.0e-3).ceil() as i32;
+                self.add_span(
+                    x,
+                    z,
+                    Span {
+                        min: span_min.min(span_max - 1),
+                        max: span_max,
+                        walkable,
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// Shape of a navmesh obstacle in local coordinates of its node.
+#[derive(Copy, Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum NavmeshObstacleShape {
+    /// A box with the given half extents.
+    Box {
+        /// Half extents of the box.
+        half_extents: Vector3<f32>,
+    },
+    /// A cylinder along local Y axis.
+    Cylinder {
+        /// Radius of the cylinder.
+        radius: f32,
+        /// Half height of the cylinder.
+        half_height: f32,
+    },
+}
+
+uuid_provider!(NavmeshObstacleShape = "c6a3a27c-b3e4-416a-92b1-99b23ae400d4");
+
+impl Default for NavmeshObstacleShape {
+    fn default() -> Self {
+        Self::Box {
+            half_extents: Vector3::repeat(0.5),
+        }
+    }
+}
+
+/// An obstacle in world coordinates, that carves its volume out of the navmesh. See
+/// [`TiledNavmeshBaker`] docs for more info.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NavmeshObstacleVolume {
+    /// World transform of the obstacle.
+    pub transform: Matrix4<f32>,
+    /// Shape of the obstacle in local coordinates.
+    pub shape: NavmeshObstacleShape,
+}
+
+impl NavmeshObstacleVolume {
+    fn local_bounds(&self) -> Vector3<f32> {
+        match self.shape {
+            NavmeshObstacleShape::Box { half_extents } => half_extents,
+            NavmeshObstacleShape::Cylinder {
+                radius,
+                half_height,
+            } => Vector3::new(radius, half_height, radius),
+        }
+    }
+
+    /// Returns world-space bounds of the obstacle.
+    pub fn world_bounds(&self) -> (Vector3<f32>, Vector3<f32>) {
+        let extents = self.local_bounds();
+        let mut min = Vector3::repeat(f32::MAX);
+        let mut max = Vector3::repeat(-f32::MAX);
+        for i in 0..8 {
+            let corner = Vector3::new(
+                if i & 1 != 0 { extents.x } else { -extents.x },
+                if i & 2 != 0 { extents.y } else { -extents.y },
+                if i & 4 != 0 { extents.z } else { -extents.z },
+            );
+            let corner = self.transform.transform_point(&Point3::from(corner)).coords;
+            min = min.inf(&corner);
+            max = max.sup(&corner);
+        }
+        (min, max)
+    }
+
+    /// Checks whether the given world-space segment intersects the obstacle.
+    pub fn intersects_segment(&self, begin: Vector3<f32>, end: Vector3<f32>) -> bool {
+        let Some(inverse) = self.transform.try_inverse() else {
+            return false;
+        };
+        let begin = inverse.transform_point(&Point3::from(begin)).coords;
+        let end = inverse.transform_point(&Point3::from(end)).coords;
+        let extents = self.local_bounds();
+        let Some((t0, t1)) = clip_segment(begin, end, -extents, extents) else {
+            return false;
+        };
+        match self.shape {
+            NavmeshObstacleShape::Box { .. } => true,
+            NavmeshObstacleShape::Cylinder { radius, .. } => [t0, (t0 + t1) * 0.5, t1]
+                .into_iter()
+                .map(|t| begin.lerp(&end, t))
+                .any(|p| p.x * p.x + p.z * p.z <= radius * radius),
+        }
+    }
+}
+
+/// Clips the segment by the box and returns the parameters of the clipped part.
+fn clip_segment(
+    begin: Vector3<f32>,
+    end: Vector3<f32>,
+    min: Vector3<f32>,
+    max: Vector3<f32>,
+) -> Option<(f32, f32)> {
+    let dir = end - begin;
+    let (mut t0, mut t1) = (0.0f32, 1.0f32);
+    for i in 0..3 {
+        if dir[i].abs() <= f32::EPSILON {
+            if begin[i] < min[i] || begin[i] > max[i] {
+                return None;
+            }
+        } else {
+            let a = (min[i] - begin[i]) / dir[i];
+            let b = (max[i] - begin[i]) / dir[i];
+            t0 = t0.max(a.min(b));
+            t1 = t1.min(a.max(b));
+            if t0 > t1 {
+                return None;
+            }
+        }
+    }
+    Some((t0, t1))
+}
+
+// -x, +z, +x, -z
+const DIRECTIONS: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+#[derive(Clone, Debug)]
+struct OpenSpan {
+    x: usize,
+    z: usize,
+    floor: i32,
+    neighbours: [Option<usize>; 4],
+}
+
+/// Walkable spans of the static geometry and their connections. Corners of a span: 0 - (x, z),
+/// 1 - (x + 1, z), 2 - (x + 1, z + 1), 3 - (x, z + 1). Corners of connected spans are merged, so
+/// the polygons will share vertices.
+#[derive(Clone, Debug)]
+struct OpenField {
+    origin: Vector3<f32>,
+    width: usize,
+    depth: usize,
+    cell_size: f32,
+    cell_height: f32,
+    spans: Vec<OpenSpan>,
+    columns: Vec<Range<usize>>,
+    // Root corner of every corner.
+    corner_roots: Vec<usize>,
+    // The highest floor of the spans, that share a root corner.
+    corner_heights: Vec<i32>,
+}
+
+impl OpenField {
+    fn new(height_field: &HeightField, settings: &NavmeshBakeSettings) -> Self {
+        let agent_height = (settings.agent_height / height_field.cell_height).ceil() as i32;
+        let agent_climb = (settings.agent_max_climb / height_field.cell_height).floor() as i32;
+
+        let mut spans = Vec::new();
+        let mut ceilings = Vec::new();
+        let mut columns = vec![0..0; height_field.columns.len()];
+        for z in 0..height_field.depth {
+            for x in 0..height_field.width {
+                let index = z * height_field.width + x;
+                let column = &height_field.columns[index];
+                let start = spans.len();
+                for (i, span) in column.iter().enumerate() {
+                    if !span.walkable {
+                        continue;
+                    }
+                    let ceiling = column.get(i + 1).map_or(i32::MAX, |s| s.min);
+                    if ceiling.saturating_sub(span.max) >= agent_height {
+                        spans.push(OpenSpan {
+                            x,
+                            z,
+                            floor: span.max,
+                            neighbours: [None; 4],
+                        });
+                        ceilings.push(ceiling);
+                    }
+                }
+                columns[index] = start..spans.len();
+            }
+        }
+
+        for i in 0..spans.len() {
+            for (direction, (dx, dz)) in DIRECTIONS.iter().enumerate() {
+                let (Some(x), Some(z)) = (
+                    spans[i].x.checked_add_signed(*dx),
+                    spans[i].z.checked_add_signed(*dz),
+                ) else {
+                    continue;
+                };
+                if x >= height_field.width || z >= height_field.depth {
+                    continue;
+                }
+
+                let floor = spans[i].floor;
+                let ceiling = ceilings[i];
+                let neighbour = columns[z * height_field.width + x].clone().find(|&j| {
+                    (spans[j].floor - floor).abs() <= agent_climb
+                        && ceilings[j].min(ceiling) - spans[j].floor.max(floor) >= agent_height
+                });
+                spans[i].neighbours[direction] = neighbour;
+            }
+        }
+
+        let mut corners = UnionFind::new(spans.len() * 4);
+        for (i, span) in spans.iter().enumerate() {
+            if let Some(j) = span.neighbours[2] {
+                corners.union(i * 4 + 1, j * 4);
+                corners.union(i * 4 + 2, j * 4 + 3);
+            }
+            if let Some(j) = span.neighbours[1] {
+                corners.union(i * 4 + 3, j * 4);
+                corners.union(i * 4 + 2, j * 4 + 1);
+            }
+        }
+        let corner_roots = (0..spans.len() * 4)
+            .map(|corner| corners.find(corner))
+            .collect::<Vec<_>>();
+        let mut corner_heights = vec![i32::MIN; corner_roots.len()];
+        for (corner, root) in corner_roots.iter().enumerate() {
+            let height = &mut corner_heights[*root];
+            *height = (*height).max(spans[corner / 4].floor);
+        }
+
+        Self {
+            origin: height_field.origin,
+            width: height_field.width,
+            depth: height_field.depth,
+            cell_size: height_field.cell_size,
+            cell_height: height_field.cell_height,
+            spans,
+            columns,
+            corner_roots,
+            corner_heights,
+        }
+    }
+
+    fn corner_position(&self, corner: usize) -> Vector3<f32> {
+        let span = &self.spans[corner / 4];
+        let (dx, dz) = [(0, 0), (1, 0), (1, 1), (0, 1)][corner % 4];
+        let height = self.corner_heights[self.corner_roots[corner]];
+        self.origin
+            + Vector3::new(
+                (span.x + dx) as f32 * self.cell_size,
+                height as f32 * self.cell_height,
+                (span.z + dz) as f32 * self.cell_size,
+            )
+    }
+
+    fn is_blocked(
+        &self,
+        span: usize,
+        obstacles: &[NavmeshObstacleVolume],
+        agent_height: f32,
+    ) -> bool {
+        let span = &self.spans[span];
+        let bottom = self.origin
+            + Vector3::new(
+                (span.x as f32 + 0.5) * self.cell_size,
+                span.floor as f32 * self.cell_height,
+                (span.z as f32 + 0.5) * self.cell_size,
+            );
+        let top = bottom + Vector3::new(0.0, agent_height, 0.0);
+        obstacles
+            .iter()
+            .any(|obstacle| obstacle.intersects_segment(bottom, top))
+    }
+
+    /// Returns a range of cells along an axis, that is covered by the given world-space range.
+    fn cell_range(&self, min: f32, max: f32, origin: f32, count: usize) -> Option<Range<usize>> {
+        let first = ((min - origin) / self.cell_size).floor();
+        let last = ((max - origin) / self.cell_size).floor();
+        if count == 0 || last < 0.0 || first >= count as f32 {
+            None
+        } else {
+            Some(first.max(0.0) as usize..(last as usize).min(count - 1) + 1)
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+enum TileVertex {
+    // Index of a span corner.
+    Corner(usize),
+    // Index of a vertex in the tile.
+    Center(usize),
+}
+
+#[derive(Clone, Debug, Default)]
+struct Tile {
+    triangles: Vec<[TileVertex; 3]>,
+    centers: Vec<Vector3<f32>>,
+}
+
+/// Navmesh baker, that keeps the voxelized static geometry and the navmesh split into square tiles.
+/// When obstacles change, only the tiles around the old and the new obstacle positions are
+/// re-baked. It is used by [`crate::scene::navmesh::NavigationalMesh`] for dynamic navmeshes, but
+/// could also be used directly.
+///
+/// Changes of the static geometry are not tracked, create a new baker if the geometry has
+/// changed.
+#[derive(Clone, Debug)]
+pub struct TiledNavmeshBaker {
+    field: OpenField,
+    tile_size: usize,
+    tiles_x: usize,
+    tiles: Vec<Tile>,
+    obstacles: Vec<NavmeshObstacleVolume>,
+    agent_height: f32,
+    agent_radius: usize,
+    max_polygon_size: usize,
+    last_rebaked_tiles: usize,
+}
+
+impl TiledNavmeshBaker {
+    /// Voxelizes the given triangles (in world coordinates) and bakes every tile.
+    pub fn new(triangles: &[[Vector3<f32>; 3]], settings: &NavmeshBakeSettings) -> Self {
+        let mut height_field = HeightField::new(triangles, settings);
+        for triangle in triangles {
+            height_field.rasterize_triangle(triangle);
+        }
+
+        let field = OpenField::new(&height_field, settings);
+        let tile_size = settings.tile_size.max(1) as usize;
+        let tiles_x = (field.width + tile_size - 1) / tile_size;
+        let tiles_z = (field.depth + tile_size - 1) / tile_size;
+
+        let mut baker = Self {
+            agent_radius: (settings.agent_radius / field.cell_size).ceil() as usize,
+            field,
+            tile_size,
+            tiles_x,
+            tiles: vec![Default::default(); tiles_x * tiles_z],
+            obstacles: Default::default(),
+            agent_height: settings.agent_height,
+            max_polygon_size: settings.max_polygon_size.max(1) as usize,
+            last_rebaked_tiles: 0,
+        };
+        for tile in 0..baker.tiles.len() {
+            baker.tiles[tile] = baker.bake_tile(tile);
+        }
+        baker.last_rebaked_tiles = baker.tiles.len();
+        baker
+    }
+
+    /// Voxelizes the geometry of the given graph and bakes every tile.
+    pub fn from_graph(graph: &Graph, settings: &NavmeshBakeSettings) -> Self {
+        let triangles = collect_triangles(
+            |handle| graph.try_get(handle),
+            graph.pair_iter(),
+            &Default::default(),
+            settings,
+        );
+        Self::new(&triangles, settings)
+    }
+
+    /// Returns total amount of tiles.
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Returns amount of tiles, that were baked by the last call of [`Self::set_obstacles`] (or by
+    /// the constructor).
+    pub fn last_rebaked_tiles(&self) -> usize {
+        self.last_rebaked_tiles
+    }
+
+    /// Returns current obstacles.
+    pub fn obstacles(&self) -> &[NavmeshObstacleVolume] {
+        &self.obstacles
+    }
+
+    /// Sets new obstacles and re-bakes the tiles affected by the obstacles, that were added, removed
+    /// or moved. Returns `true` if any tile was re-baked, call [`Self::navmesh`] to get the updated
+    /// navmesh in this case.
+    pub fn set_obstacles(&mut self, obstacles: Vec<NavmeshObstacleVolume>) -> bool {
+        let mut dirty = FxHashSet::default();
+        for obstacle in self
+            .obstacles
+            .iter()
+            .filter(|o| !obstacles.contains(o))
+            .chain(obstacles.iter().filter(|o| !self.obstacles.contains(o)))
+        {
+            self.affected_tiles(obstacle, &mut dirty);
+        }
+
+        self.obstacles = obstacles;
+        self.last_rebaked_tiles = dirty.len();
+        for &tile in dirty.iter() {
+            self.tiles[tile] = self.bake_tile(tile);
+        }
+
+        !dirty.is_empty()
+    }
+
+    fn affected_tiles(&self, obstacle: &NavmeshObstacleVolume, tiles: &mut FxHashSet<usize>) {
+        let (min, max) = obstacle.world_bounds();
+        // Erosion spreads the changes by the radius of the agent.
+        let margin = (self.agent_radius + 1) as f32 * self.field.cell_size;
+        let field = &self.field;
+        let (Some(x_range), Some(z_range)) = (
+            field.cell_range(min.x - margin, max.x + margin, field.origin.x, field.width),
+            field.cell_range(min.z - margin, max.z + margin, field.origin.z, field.depth),
+        ) else {
+            return;
+        };
+        for tz in z_range.start / self.tile_size..=(z_range.end - 1) / self.tile_size {
+            for tx in x_range.start / self.tile_size..=(x_range.end - 1) / self.tile_size {
+                tiles.insert(tz * self.tiles_x + tx);
+            }
+        }
+    }
+
+    fn bake_tile(&self, tile: usize) -> Tile {
+        let field = &self.field;
+        let (tx, tz) = (tile % self.tiles_x, tile / self.tiles_x);
+        let x_range = tx * self.tile_size..((tx + 1) * self.tile_size).min(field.width);
+        let z_range = tz * self.tile_size..((tz + 1) * self.tile_size).min(field.depth);
+
+        // Spans of the tile could be eroded by the obstacles and the borders around the tile, so
+        // the region around the tile must be checked too.
+        let margin = self.agent_radius + 1;
+        let region_x =
+            x_range.start.saturating_sub(margin)..(x_range.end + margin).min(field.width);
+        let region_z =
+            z_range.start.saturating_sub(margin)..(z_range.end + margin).min(field.depth);
+        let in_region = |span: &OpenSpan| region_x.contains(&span.x) && region_z.contains(&span.z);
+
+        let mut distances = FxHashMap::default();
+        for z in region_z.clone() {
+            for x in region_x.clone() {
+                for span in field.columns[z * field.width + x].clone() {
+                    if !field.is_blocked(span, &self.obstacles, self.agent_height) {
+                        distances.insert(span, u32::MAX);
+                    }
+                }
+            }
+        }
+
+        if self.agent_radius > 0 {
+            // Distances to the border of walkable area. Spans outside the region are not
+            // borders, they're just unknown.
+            let mut queue = distances
+                .keys()
+                .filter(|span| {
+                    field.spans[**span].neighbours.iter().any(|n| match n {
+                        Some(n) => in_region(&field.spans[*n]) && !distances.contains_key(n),
+                        None => true,
+                    })
+                })
+                .cloned()
+                .collect::<VecDeque<_>>();
+            for span in queue.iter() {
+                distances.insert(*span, 0);
+            }
+            while let Some(span) = queue.pop_front() {
+                let distance = distances[&span] + 1;
+                for neighbour in field.spans[span].neighbours.into_iter().flatten() {
+                    if let Some(neighbour_distance) = distances.get_mut(&neighbour) {
+                        if *neighbour_distance > distance {
+                            *neighbour_distance = distance;
+                            queue.push_back(neighbour);
+                        }
+                    }
+                }
+            }
+        }
+
+        let walkable = |span: usize| {
+            let span_ref = &field.spans[span];
+            x_range.contains(&span_ref.x)
+                && z_range.contains(&span_ref.z)
+                && distances
+                    .get(&span)
+                    .map_or(false, |d| *d >= self.agent_radius as u32)
+        };
+
+        // Merge spans into rectangles.
+        let mut assigned = FxHashSet::default();
+        let mut rectangles = Vec::new();
+        for z in z_range.clone() {
+            for x in x_range.clone() {
+                for i in field.columns[z * field.width + x].clone() {
+                    if !walkable(i) || assigned.contains(&i) {
+                        continue;
+                    }
+
+                    let floor = field.spans[i].floor;
+                    let can_merge = |j: usize, assigned: &FxHashSet<usize>| {
+                        walkable(j) && !assigned.contains(&j) && field.spans[j].floor == floor
+                    };
+
+                    let mut first_row = vec![i];
+                    assigned.insert(i);
+                    while first_row.len() < self.max_polygon_size {
+                        match field.spans[*first_row.last().unwrap()].neighbours[2] {
+                            Some(j) if can_merge(j, &assigned) => {
+                                assigned.insert(j);
+                                first_row.push(j);
+                            }
+                            _ => break,
+                        }
+                    }
+
+                    let mut rows = vec![first_row];
+                    'rows: while rows.len() < self.max_polygon_size {
+                        let mut row: Vec<usize> = Vec::with_capacity(rows[0].len());
+                        for (k, &below) in rows.last().unwrap().iter().enumerate() {
+                            match field.spans[below].neighbours[1] {
+                                Some(j)
+                                    if can_merge(j, &assigned)
+                                        && (k == 0
+                                            || field.spans[row[k - 1]].neighbours[2]
+                                                == Some(j)) =>
+                                {
+                                    row.push(j)
+                                }
+                                _ => break 'rows,
+                            }
+                        }
+                        assigned.extend(row.iter().copied());
+                        rows.push(row);
+                    }
+
+                    rectangles.push(rows);
+                }
+            }
+        }
+
+        // Corners of the rectangles must be added to the edges of the adjacent rectangles to
+        // prevent T-junctions. Every corner on the border of the tile is added too, so the edges
+        // of adjacent tiles will match regardless of their rectangles.
+        let rectangle_corners = |rows: &[Vec<usize>]| {
+            let (first, last) = (&rows[0], rows.last().unwrap());
+            [
+                first[0] * 4,
+                last[0] * 4 + 3,
+                last.last().unwrap() * 4 + 2,
+                first.last().unwrap() * 4 + 1,
+            ]
+        };
+        let used_corners = rectangles
+            .iter()
+            .flat_map(|rows| rectangle_corners(rows))
+            .map(|corner| field.corner_roots[corner])
+            .collect::<FxHashSet<_>>();
+        let is_on_border = |corner: usize| {
+            let span = &field.spans[corner / 4];
+            let (dx, dz) = [(0, 0), (1, 0), (1, 1), (0, 1)][corner % 4];
+            let (x, z) = (span.x + dx, span.z + dz);
+            x == x_range.start || x == x_range.end || z == z_range.start || z == z_range.end
+        };
+
+        let mut result = Tile::default();
+        for rows in rectangles.iter() {
+            let (first, last) = (&rows[0], rows.last().unwrap());
+            let rectangle_corners = rectangle_corners(rows);
+
+            // Counter-clockwise (looking from above) perimeter of the rectangle.
+            let perimeter = rows
+                .iter()
+                .map(|row| row[0] * 4)
+                .chain(last.iter().map(|s| s * 4 + 3))
+                .chain(rows.iter().rev().map(|row| row.last().unwrap() * 4 + 2))
+                .chain(first.iter().rev().map(|s| s * 4 + 1));
+
+            let polygon = perimeter
+                .filter(|corner| {
+                    rectangle_corners.contains(corner)
+                        || used_corners.contains(&field.corner_roots[*corner])
+                        || is_on_border(*corner)
+                })
+                .collect::<Vec<_>>();
+
+            let corner = |i: usize| TileVertex::Corner(polygon[i]);
+            if polygon.len() == 4 {
+                result.triangles.push([corner(0), corner(1), corner(2)]);
+                result.triangles.push([corner(0), corner(2), corner(3)]);
+            } else {
+                let center = polygon
+                    .iter()
+                    .map(|c| field.corner_position(*c))
+                    .sum::<Vector3<f32>>()
+                    .scale(1.0 / polygon.len() as f32);
+                let center_index = TileVertex::Center(result.centers.len());
+                result.centers.push(center);
+                for k in 0..polygon.len() {
+                    result.triangles.push([
+                        center_index,
+                        corner(k),
+                        corner((k + 1) % polygon.len()),
+                    ]);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Assembles the navmesh from the tiles.
+    pub fn navmesh(&self) -> Navmesh {
+        let mut vertices = Vec::new();
+        let mut corner_indices = FxHashMap::default();
+        let mut triangles = Vec::new();
+        for tile in self.tiles.iter() {
+            let base = vertices.len();
+            vertices.extend_from_slice(&tile.centers);
+            for triangle in tile.triangles.iter() {
+                triangles.push(TriangleDefinition(triangle.map(|vertex| {
+                    match vertex {
+                        TileVertex::Corner(corner) => *corner_indices
+                            .entry(self.field.corner_roots[corner])
+                            .or_insert_with(|| {
+                                vertices.push(self.field.corner_position(corner));
+                                vertices.len() as u32 - 1
+                            }),
+                        TileVertex::Center(index) => (base + index) as u32,
+                    }
+                })));
+            }
+        }
+        Navmesh::new(triangles, vertices)
+    }
+}
+
+struct UnionFind {
+    parents: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(count: usize) -> Self {
+        Self {
+            parents: (0..count).collect(),
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parents[b] = a;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Matrix4, Vector3},
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            graph::Graph,
+            transform::TransformBuilder,
+        },
+        utils::{
+            astar::PathKind,
+            navmesh::bake::{
+                NavmeshBakeSettings, NavmeshBuilder, NavmeshObstacleShape, NavmeshObstacleVolume,
+                TiledNavmeshBaker,
+            },
+        },
+    };
+
+    #[test]
+    fn test_navmesh_baking() {
+        let mut graph = Graph::new();
+        ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::cuboid(5.0, 0.1, 5.0))
+            .build(&mut graph);
+        // A wall, that blocks the direct path.
+        ColliderBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 1.1, -1.5))
+                    .build(),
+            ),
+        )
+        .with_shape(ColliderShape::cuboid(0.25, 1.0, 3.5))
+        .build(&mut graph);
+        graph.update(Default::default(), 0.0, Default::default());
+
+        let navmesh = NavmeshBuilder::bake(&graph, &NavmeshBakeSettings::default());
+        assert!(!navmesh.triangles().is_empty());
+        // Every vertex lies on the floor.
+        assert!(navmesh
+            .vertices()
+            .iter()
+            .all(|v| (v.y - 0.1).abs() <= 0.01 && v.x.abs() <= 5.0 && v.z.abs() <= 5.0));
+
+        let (_, from) = navmesh
+            .query_closest(Vector3::new(-3.0, 0.1, -4.0))
+            .unwrap();
+        let (_, to) = navmesh.query_closest(Vector3::new(3.0, 0.1, -4.0)).unwrap();
+        let mut path = Vec::new();
+        assert!(matches!(
+            navmesh.build_path(from, to, &mut path),
+            Ok(PathKind::Full)
+        ));
+        // The path goes around the wall.
+        assert!(path.iter().any(|p| p.z > 2.0));
+    }
+
+    #[test]
+    fn test_obstacle_rebaking() {
+        let floor = [
+            [
+                Vector3::new(-5.0, 0.0, -5.0),
+                Vector3::new(-5.0, 0.0, 5.0),
+                Vector3::new(5.0, 0.0, 5.0),
+            ],
+            [
+                Vector3::new(-5.0, 0.0, -5.0),
+                Vector3::new(5.0, 0.0, 5.0),
+                Vector3::new(5.0, 0.0, -5.0),
+            ],
+        ];
+        let mut baker = TiledNavmeshBaker::new(
+            &floor,
+            &NavmeshBakeSettings {
+                tile_size: 8,
+                ..Default::default()
+            },
+        );
+        assert_eq!(baker.tile_count(), 25);
+        let full = baker.navmesh();
+
+        let obstacle = NavmeshObstacleVolume {
+            transform: Matrix4::new_translation(&Vector3::new(0.0, 0.5, 0.0)),
+            shape: NavmeshObstacleShape::Box {
+                half_extents: Vector3::repeat(1.0),
+            },
+        };
+        assert!(baker.set_obstacles(vec![obstacle.clone()]));
+        assert!(baker.last_rebaked_tiles() > 0 && baker.last_rebaked_tiles() < 25);
+        // Nothing has changed.
+        assert!(!baker.set_obstacles(vec![obstacle]));
+
+        let navmesh = baker.navmesh();
+        for triangle in navmesh.triangles() {
+            let center = triangle
+                .0
+                .iter()
+                .map(|i| navmesh.vertices()[*i as usize])
+                .sum::<Vector3<f32>>()
+                .scale(1.0 / 3.0);
+            assert!(center.x.abs() > 1.0 || center.z.abs() > 1.0);
+        }
+
+        // The path goes around the obstacle.
+        let (_, from) = navmesh.query_closest(Vector3::new(-3.0, 0.0, 0.0)).unwrap();
+        let (_, to) = navmesh.query_closest(Vector3::new(3.0, 0.0, 0.0)).unwrap();
+        let mut path = Vec::new();
+        assert!(matches!(
+            navmesh.build_path(from, to, &mut path),
+            Ok(PathKind::Full)
+        ));
+
+        // Removed obstacle does not affect the navmesh anymore.
+        assert!(baker.set_obstacles(Vec::new()));
+        assert_eq!(baker.navmesh().triangles().len(), full.triangles().len());
+    }
+}
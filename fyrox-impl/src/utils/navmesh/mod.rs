@@ -1,7 +1,8 @@
 //! Contains all structures and methods to create and manage navigation meshes (navmesh).
 //!
 //! Navigation mesh is a set of convex polygons which is used for path finding in complex
-//! environment.
+//! environment. Navigation meshes could be created by hand, from an existing mesh (see
+//! [`Navmesh::from_mesh`]) or baked from the scene geometry (see [`bake::NavmeshBuilder`]).
 
 #![warn(missing_docs)]
 
@@ -26,6 +27,8 @@ use fxhash::{FxBuildHasher, FxHashMap};
 use fyrox_core::math::octree::{Octree, OctreeNode};
 use std::ops::{Deref, DerefMut};
 
+pub mod bake;
+
 #[derive(Clone, Debug, Default, Visit)]
 struct Vertex {
     triangle_index: usize,
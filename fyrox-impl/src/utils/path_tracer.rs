@@ -0,0 +1,822 @@
+//! Offline progressive path tracer, that is used to render ground-truth reference images of a
+//! scene. See [`PathTracer`] docs for more info.
+//!
+//! # Performance
+//!
+//! This is CPU path tracer, its performance is linear with core count of your CPU. It is not
+//! suitable for real-time rendering.
+
+#![forbid(unsafe_code)]
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, Vector2, Vector3, Vector4},
+        color::Color,
+        math::{aabb::AxisAlignedBoundingBox, octree::Octree, ray::Ray},
+        pool::Handle,
+        rng::{split_mix64, Pcg32},
+        sstorage::ImmutableString,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    material::PropertyValue,
+    resource::texture::{Texture, TextureKind, TexturePixelKind},
+    scene::{
+        camera::Camera,
+        graph::query::{SpatialIndex, SpatialRayHit},
+        light::{directional::DirectionalLight, point::PointLight, spot::SpotLight},
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexFetchError, VertexReadTrait},
+            Mesh,
+        },
+        node::Node,
+        Scene,
+    },
+    utils::lightmap::CancellationToken,
+};
+use fxhash::FxHashMap;
+use rayon::prelude::*;
+use std::fmt::{Display, Formatter};
+
+/// An offset along the surface normal, that is used to prevent self-intersection of secondary rays.
+const SURFACE_BIAS: f32 = 0.001;
+
+/// Path tracing settings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PathTracingSettings {
+    /// Width of the output image in pixels.
+    pub width: u32,
+    /// Height of the output image in pixels.
+    pub height: u32,
+    /// Maximum amount of bounces of a single path. Default is 4.
+    pub max_bounces: u32,
+    /// Color of the sky, that is used for every ray that does not hit anything. It is a very rough
+    /// approximation of image-based lighting.
+    pub sky_color: Color,
+    /// Intensity of the sky light. Default is 1.0.
+    pub sky_intensity: f32,
+    /// Seed of the random number generator. The same seed and the same scene produce the same image.
+    pub seed: u64,
+}
+
+impl Default for PathTracingSettings {
+    fn default() -> Self {
+        Self {
+            width: 640,
+            height: 480,
+            max_bounces: 4,
+            sky_color: Color::opaque(130, 150, 170),
+            sky_intensity: 1.0,
+            seed: 0,
+        }
+    }
+}
+
+/// An error that may occur during path tracing.
+#[derive(Debug)]
+pub enum PathTracingError {
+    /// A handle does not point to a camera.
+    InvalidCamera,
+    /// Rendering was cancelled by user.
+    Cancelled,
+    /// An index of a vertex in a triangle is out of bounds.
+    InvalidIndex,
+    /// Vertex buffer of a mesh lacks required data.
+    InvalidData(VertexFetchError),
+}
+
+impl Display for PathTracingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PathTracingError::InvalidCamera => {
+                write!(f, "The handle does not point to a camera.")
+            }
+            PathTracingError::Cancelled => {
+                write!(f, "Path tracing was cancelled by the user.")
+            }
+            PathTracingError::InvalidIndex => {
+                write!(f, "An index of a vertex in a triangle is out of bounds.")
+            }
+            PathTracingError::InvalidData(v) => {
+                write!(f, "Vertex buffer of a mesh lacks required data {v}.")
+            }
+        }
+    }
+}
+
+impl From<VertexFetchError> for PathTracingError {
+    fn from(e: VertexFetchError) -> Self {
+        Self::InvalidData(e)
+    }
+}
+
+/// Approximation of a material of a surface. Only diffuse color and emission of the standard
+/// shader are taken into account, textures are ignored.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct TracedMaterial {
+    pub(crate) albedo: Vector3<f32>,
+    pub(crate) emission: Vector3<f32>,
+}
+
+struct TracedMesh {
+    vertices: Vec<[Vector3<f32>; 3]>,
+    normals: Vec<[Vector3<f32>; 3]>,
+    materials: Vec<TracedMaterial>,
+    // Index of a material for each triangle.
+    triangle_materials: Vec<u32>,
+    octree: Octree,
+}
+
+enum TracedLight {
+    Point {
+        position: Vector3<f32>,
+        radius: f32,
+        color: Vector3<f32>,
+    },
+    Spot {
+        position: Vector3<f32>,
+        direction: Vector3<f32>,
+        distance: f32,
+        half_cone_angle_cos: f32,
+        half_hotspot_cone_angle_cos: f32,
+        color: Vector3<f32>,
+    },
+    Directional {
+        direction: Vector3<f32>,
+        color: Vector3<f32>,
+    },
+}
+
+pub(crate) struct Hit {
+    pub(crate) position: Vector3<f32>,
+    pub(crate) normal: Vector3<f32>,
+    pub(crate) material: TracedMaterial,
+}
+
+/// A snapshot of the geometry and the lights of a scene, that could be used to trace rays against
+/// the scene. It is shared by the path tracer and the probe volumes.
+pub(crate) struct TracingScene {
+    spatial_index: SpatialIndex,
+    meshes: FxHashMap<Handle<Node>, TracedMesh>,
+    lights: Vec<TracedLight>,
+    bounds: AxisAlignedBoundingBox,
+    max_distance: f32,
+}
+
+/// Offline progressive path tracer. It renders a scene as it is seen from a camera to an HDR
+/// image. The image is refined with each [`PathTracer::render_pass`], so it could be shown to
+/// the user while it is being rendered. It could be used to produce lighting ground truth for
+/// comparisons with the real-time renderer (to validate global illumination or image-based
+/// lighting, for example) or high quality still images.
+///
+/// The tracer takes a snapshot of the scene on creation, so the scene could be modified (or even
+/// destroyed) while the image is being rendered. Top-level acceleration structure is taken from
+/// the [spatial index](crate::scene::graph::Graph::spatial_index) of the scene graph, each mesh
+/// has its own octree of triangles.
+///
+/// # Limitations
+///
+/// Materials are approximated: every surface is treated as a perfect diffuse reflector with the
+/// color defined by `diffuseColor` property of its material. Surfaces with `emissionTexture` emit
+/// light with `emissionStrength`. Sky is represented by a constant color.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     resource::texture::Texture,
+/// #     scene::{node::Node, Scene},
+/// #     utils::path_tracer::{PathTracer, PathTracingError, PathTracingSettings},
+/// # };
+/// fn render_reference(scene: &Scene, camera: Handle<Node>) -> Result<Texture, PathTracingError> {
+///     let mut tracer = PathTracer::from_scene(scene, camera, PathTracingSettings::default())?;
+///     for _ in 0..64 {
+///         tracer.render_pass();
+///     }
+///     Ok(tracer.to_texture())
+/// }
+/// ```
+pub struct PathTracer {
+    settings: PathTracingSettings,
+    scene: TracingScene,
+    inv_view_projection: Matrix4<f32>,
+    accumulator: Vec<Vector3<f32>>,
+    sample_count: u32,
+}
+
+fn linear_color(color: Color) -> Vector3<f32> {
+    color.srgb_to_linear().as_frgb()
+}
+
+fn make_material(mesh: &Mesh, surface_index: usize) -> TracedMaterial {
+    let mut material = TracedMaterial {
+        albedo: Vector3::repeat(1.0),
+        emission: Vector3::default(),
+    };
+
+    let mut state = mesh.surfaces()[surface_index].material().state();
+    if let Some(data) = state.data() {
+        if let Some(PropertyValue::Color(color)) =
+            data.property_ref(&ImmutableString::new("diffuseColor"))
+        {
+            material.albedo = linear_color(*color);
+        }
+
+        let has_emission_texture = matches!(
+            data.property_ref(&ImmutableString::new("emissionTexture")),
+            Some(PropertyValue::Sampler { value: Some(_), .. })
+        );
+        if has_emission_texture {
+            if let Some(PropertyValue::Vector3(strength)) =
+                data.property_ref(&ImmutableString::new("emissionStrength"))
+            {
+                material.emission = *strength;
+            }
+        }
+    }
+
+    material
+}
+
+fn make_mesh(mesh: &Mesh) -> Result<TracedMesh, PathTracingError> {
+    let global_transform = mesh.global_transform();
+    let normal_matrix = global_transform
+        .try_inverse()
+        .unwrap_or_else(Matrix4::identity)
+        .transpose();
+
+    let mut vertices = Vec::new();
+    let mut normals = Vec::new();
+    let mut materials = Vec::new();
+    let mut triangle_materials = Vec::new();
+
+    for (surface_index, surface) in mesh.surfaces().iter().enumerate() {
+        let material_index = materials.len() as u32;
+        materials.push(make_material(mesh, surface_index));
+
+        let data = surface.data();
+        let data = data.data_ref();
+        for triangle in data.geometry_buffer.iter() {
+            let mut triangle_vertices = [Vector3::default(); 3];
+            let mut triangle_normals = [Vector3::default(); 3];
+            for (i, &index) in triangle.0.iter().enumerate() {
+                let vertex = data
+                    .vertex_buffer
+                    .get(index as usize)
+                    .ok_or(PathTracingError::InvalidIndex)?;
+                triangle_vertices[i] = global_transform
+                    .transform_point(&Point3::from(
+                        vertex.read_3_f32(VertexAttributeUsage::Position)?,
+                    ))
+                    .coords;
+                triangle_normals[i] = normal_matrix
+                    .transform_vector(&vertex.read_3_f32(VertexAttributeUsage::Normal)?)
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_else(Vector3::y);
+            }
+            vertices.push(triangle_vertices);
+            normals.push(triangle_normals);
+            triangle_materials.push(material_index);
+        }
+    }
+
+    Ok(TracedMesh {
+        octree: Octree::new(&vertices, 64),
+        vertices,
+        normals,
+        materials,
+        triangle_materials,
+    })
+}
+
+/// Möller–Trumbore ray-triangle intersection. Returns ray parameter and barycentric coordinates
+/// of the intersection point.
+fn intersect_triangle(ray: &Ray, vertices: &[Vector3<f32>; 3]) -> Option<(f32, f32, f32)> {
+    let edge1 = vertices[1] - vertices[0];
+    let edge2 = vertices[2] - vertices[0];
+    let p = ray.dir.cross(&edge2);
+    let det = edge1.dot(&p);
+    if det.abs() < f32::EPSILON {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+    let s = ray.origin - vertices[0];
+    let u = s.dot(&p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(&edge1);
+    let v = ray.dir.dot(&q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = edge2.dot(&q) * inv_det;
+    if (0.0..=1.0).contains(&t) {
+        Some((t, u, v))
+    } else {
+        None
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Generates cosine-weighted random direction in the hemisphere around the given normal.
+fn sample_hemisphere(normal: Vector3<f32>, rng: &mut Pcg32) -> Vector3<f32> {
+    let r1 = 2.0 * std::f32::consts::PI * rng.next_f32();
+    let r2 = rng.next_f32();
+    let r2s = r2.sqrt();
+
+    let tangent = if normal.x.abs() > 0.1 {
+        Vector3::y()
+    } else {
+        Vector3::x()
+    }
+    .cross(&normal)
+    .normalize();
+    let bitangent = normal.cross(&tangent);
+
+    (tangent * (r1.cos() * r2s) + bitangent * (r1.sin() * r2s) + normal * (1.0 - r2).sqrt())
+        .try_normalize(f32::EPSILON)
+        .unwrap_or(normal)
+}
+
+impl TracingScene {
+    /// Takes a snapshot of the visible and enabled meshes and lights of the scene.
+    pub(crate) fn from_scene(scene: &Scene) -> Result<Self, PathTracingError> {
+        let mut meshes = FxHashMap::default();
+        let mut lights = Vec::new();
+        let mut bounds = AxisAlignedBoundingBox::default();
+
+        for (handle, node) in scene.graph.pair_iter() {
+            if !node.global_visibility() || !node.is_globally_enabled() {
+                continue;
+            }
+
+            if let Some(mesh) = node.cast::<Mesh>() {
+                let mesh = make_mesh(mesh)?;
+                for triangle in mesh.vertices.iter() {
+                    for vertex in triangle {
+                        bounds.add_point(*vertex);
+                    }
+                }
+                meshes.insert(handle, mesh);
+            } else if let Some(point) = node.cast::<PointLight>() {
+                let base = point.base_light_ref();
+                lights.push(TracedLight::Point {
+                    position: node.global_position(),
+                    radius: point.radius(),
+                    color: linear_color(base.color()) * base.intensity(),
+                });
+            } else if let Some(spot) = node.cast::<SpotLight>() {
+                let base = spot.base_light_ref();
+                lights.push(TracedLight::Spot {
+                    position: node.global_position(),
+                    direction: node
+                        .up_vector()
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_else(Vector3::y),
+                    distance: spot.distance(),
+                    half_cone_angle_cos: (spot.full_cone_angle() * 0.5).cos(),
+                    half_hotspot_cone_angle_cos: (spot.hotspot_cone_angle() * 0.5).cos(),
+                    color: linear_color(base.color()) * base.intensity(),
+                });
+            } else if let Some(directional) = node.cast::<DirectionalLight>() {
+                let base = directional.base_light_ref();
+                lights.push(TracedLight::Directional {
+                    direction: node
+                        .up_vector()
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_else(Vector3::y),
+                    color: linear_color(base.color()) * base.intensity(),
+                });
+            }
+        }
+
+        let mut tracing_scene = Self {
+            spatial_index: scene.graph.spatial_index().clone(),
+            meshes,
+            lights,
+            bounds,
+            max_distance: 0.0,
+        };
+        tracing_scene.update_max_distance();
+        Ok(tracing_scene)
+    }
+
+    fn update_max_distance(&mut self) {
+        let size = if self.bounds.is_valid() {
+            (self.bounds.max - self.bounds.min).norm()
+        } else {
+            0.0
+        };
+        self.max_distance = size.max(1.0) * 2.0;
+    }
+
+    /// Extends the bounds of the scene, so rays that start at the given point are long enough to
+    /// reach any geometry of the scene.
+    pub(crate) fn include_point(&mut self, point: Vector3<f32>) {
+        self.bounds.add_point(point);
+        self.update_max_distance();
+    }
+
+    /// Returns the length of a ray, that is enough to reach any geometry of the scene.
+    pub(crate) fn max_distance(&self) -> f32 {
+        self.max_distance
+    }
+
+    /// Finds the closest intersection of a ray segment with the scene geometry. If `any_hit` is
+    /// set, the first found intersection is returned instead.
+    pub(crate) fn trace(
+        &self,
+        origin: Vector3<f32>,
+        direction: Vector3<f32>,
+        length: f32,
+        any_hit: bool,
+    ) -> Option<Hit> {
+        let ray = Ray::new(origin, direction.scale(length));
+
+        let mut candidates = Vec::<SpatialRayHit>::new();
+        self.spatial_index.query_ray(&ray, &mut candidates);
+
+        let mut triangles = Vec::new();
+        let mut closest: Option<(f32, f32, f32, &TracedMesh, usize)> = None;
+        for candidate in candidates {
+            if let Some((toi, ..)) = closest {
+                // Candidates are sorted by the distance, so there's no need to check the rest.
+                if toi < candidate.toi {
+                    break;
+                }
+            }
+
+            let Some(mesh) = self.meshes.get(&candidate.node) else {
+                continue;
+            };
+
+            mesh.octree.ray_query(&ray, &mut triangles);
+            for &index in triangles.iter() {
+                let index = index as usize;
+                if let Some((toi, u, v)) = intersect_triangle(&ray, &mesh.vertices[index]) {
+                    if closest.map_or(true, |(closest_toi, ..)| toi < closest_toi) {
+                        closest = Some((toi, u, v, mesh, index));
+                        if any_hit {
+                            break;
+                        }
+                    }
+                }
+            }
+
+            if any_hit && closest.is_some() {
+                break;
+            }
+        }
+
+        closest.map(|(toi, u, v, mesh, index)| {
+            let normals = &mesh.normals[index];
+            let mut normal =
+                (normals[0].scale(1.0 - u - v) + normals[1].scale(u) + normals[2].scale(v))
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_else(Vector3::y);
+            // Make sure the normal faces the ray, this allows tracing double-sided geometry.
+            if normal.dot(&direction) > 0.0 {
+                normal = -normal;
+            }
+
+            Hit {
+                position: ray.get_point(toi),
+                normal,
+                material: mesh.materials[mesh.triangle_materials[index] as usize],
+            }
+        })
+    }
+
+    pub(crate) fn is_occluded(
+        &self,
+        origin: Vector3<f32>,
+        direction: Vector3<f32>,
+        distance: f32,
+    ) -> bool {
+        self.trace(origin, direction, distance, true).is_some()
+    }
+
+    /// Calculates the light, that is reflected by the surface from the light sources of the scene.
+    pub(crate) fn direct_lighting(&self, hit: &Hit) -> Vector3<f32> {
+        let origin = hit.position + hit.normal.scale(SURFACE_BIAS);
+        let mut lighting = Vector3::default();
+        for light in self.lights.iter() {
+            let (to_light, distance, radiance) = match light {
+                TracedLight::Point {
+                    position,
+                    radius,
+                    color,
+                } => {
+                    let delta = position - hit.position;
+                    let distance = delta.norm();
+                    let attenuation =
+                        (1.0 - distance * distance / (radius * radius)).clamp(0.0, 1.0);
+                    (delta.scale(1.0 / distance), distance, color * attenuation)
+                }
+                TracedLight::Spot {
+                    position,
+                    direction,
+                    distance: light_distance,
+                    half_cone_angle_cos,
+                    half_hotspot_cone_angle_cos,
+                    color,
+                } => {
+                    let delta = position - hit.position;
+                    let distance = delta.norm();
+                    let to_light = delta.scale(1.0 / distance);
+                    let attenuation = (1.0
+                        - distance * distance / (light_distance * light_distance))
+                        .clamp(0.0, 1.0);
+                    let cone_factor = smoothstep(
+                        *half_cone_angle_cos,
+                        *half_hotspot_cone_angle_cos,
+                        direction.dot(&to_light),
+                    );
+                    (to_light, distance, color * attenuation * cone_factor)
+                }
+                TracedLight::Directional { direction, color } => {
+                    (*direction, self.max_distance, *color)
+                }
+            };
+
+            let cos_theta = hit.normal.dot(&to_light);
+            if cos_theta <= 0.0 || radiance == Vector3::default() {
+                continue;
+            }
+
+            if !self.is_occluded(origin, to_light, distance) {
+                lighting += radiance.scale(cos_theta);
+            }
+        }
+        // Lambertian BRDF.
+        lighting.component_mul(&hit.material.albedo) / std::f32::consts::PI
+    }
+}
+
+impl PathTracer {
+    /// Takes a snapshot of the given scene as it is seen from the given camera. Keep in mind, that
+    /// the scene must be updated at least once before calling this method, otherwise global
+    /// transforms of the nodes and the spatial index will be invalid.
+    pub fn from_scene(
+        scene: &Scene,
+        camera: Handle<Node>,
+        settings: PathTracingSettings,
+    ) -> Result<Self, PathTracingError> {
+        let camera = scene
+            .graph
+            .try_get(camera)
+            .and_then(|n| n.cast::<Camera>())
+            .ok_or(PathTracingError::InvalidCamera)?;
+
+        let frame_size = Vector2::new(settings.width as f32, settings.height as f32);
+        let view_projection = camera.projection().matrix(frame_size) * camera.view_matrix();
+        let inv_view_projection = view_projection
+            .try_inverse()
+            .unwrap_or_else(Matrix4::identity);
+
+        let mut tracing_scene = TracingScene::from_scene(scene)?;
+        tracing_scene.include_point(camera.global_position());
+
+        let pixel_count = settings.width as usize * settings.height as usize;
+
+        Ok(Self {
+            scene: tracing_scene,
+            inv_view_projection,
+            accumulator: vec![Vector3::default(); pixel_count],
+            sample_count: 0,
+            settings,
+        })
+    }
+
+    /// Returns current path tracing settings.
+    pub fn settings(&self) -> &PathTracingSettings {
+        &self.settings
+    }
+
+    /// Returns total amount of samples per pixel, that were accumulated so far.
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Discards every accumulated sample.
+    pub fn reset(&mut self) {
+        self.accumulator.fill(Vector3::default());
+        self.sample_count = 0;
+    }
+
+    fn radiance(
+        &self,
+        mut origin: Vector3<f32>,
+        mut direction: Vector3<f32>,
+        rng: &mut Pcg32,
+    ) -> Vector3<f32> {
+        let sky = linear_color(self.settings.sky_color) * self.settings.sky_intensity;
+
+        let mut radiance = Vector3::default();
+        let mut throughput = Vector3::repeat(1.0);
+        for bounce in 0..=self.settings.max_bounces {
+            let Some(hit) = self
+                .scene
+                .trace(origin, direction, self.scene.max_distance(), false)
+            else {
+                radiance += throughput.component_mul(&sky);
+                break;
+            };
+
+            radiance += throughput.component_mul(&hit.material.emission);
+            radiance += throughput.component_mul(&self.scene.direct_lighting(&hit));
+
+            // Cosine-weighted sampling cancels out the cosine term and the PDF.
+            throughput.component_mul_assign(&hit.material.albedo);
+
+            // Russian roulette.
+            if bounce >= 2 {
+                let survival = throughput.max().clamp(0.05, 1.0);
+                if rng.next_f32() > survival {
+                    break;
+                }
+                throughput /= survival;
+            }
+
+            origin = hit.position + hit.normal.scale(SURFACE_BIAS);
+            direction = sample_hemisphere(hit.normal, rng);
+        }
+        radiance
+    }
+
+    fn make_primary_ray(&self, x: f32, y: f32) -> (Vector3<f32>, Vector3<f32>) {
+        let nx = x / self.settings.width as f32 * 2.0 - 1.0;
+        // Rows of the image go from top to bottom.
+        let ny = 1.0 - y / self.settings.height as f32 * 2.0;
+        let near = self.inv_view_projection * Vector4::new(nx, ny, -1.0, 1.0);
+        let far = self.inv_view_projection * Vector4::new(nx, ny, 1.0, 1.0);
+        let begin = near.xyz().scale(1.0 / near.w);
+        let end = far.xyz().scale(1.0 / far.w);
+        (
+            begin,
+            (end - begin)
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::z),
+        )
+    }
+
+    /// Adds one more sample to every pixel of the image. Rows of the image are rendered in
+    /// parallel.
+    pub fn render_pass(&mut self) {
+        let width = self.settings.width as usize;
+        if width == 0 {
+            return;
+        }
+
+        let seed = split_mix64(self.settings.seed ^ self.sample_count as u64);
+        let mut accumulator = std::mem::take(&mut self.accumulator);
+        accumulator
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(y, row)| {
+                let mut rng = Pcg32::new(seed, y as u64);
+                for (x, pixel) in row.iter_mut().enumerate() {
+                    let (origin, direction) =
+                        self.make_primary_ray(x as f32 + rng.next_f32(), y as f32 + rng.next_f32());
+                    *pixel += self.radiance(origin, direction, &mut rng);
+                }
+            });
+        self.accumulator = accumulator;
+        self.sample_count += 1;
+    }
+
+    /// Renders the given amount of passes. See [`Self::render_pass`] for more info. Rendering
+    /// could be interrupted using the cancellation token, already accumulated samples are kept.
+    pub fn render(
+        &mut self,
+        passes: u32,
+        cancellation_token: &CancellationToken,
+    ) -> Result<(), PathTracingError> {
+        for _ in 0..passes {
+            if cancellation_token.is_cancelled() {
+                return Err(PathTracingError::Cancelled);
+            }
+            self.render_pass();
+        }
+        Ok(())
+    }
+
+    /// Returns linear HDR color of every pixel of the image, averaged over all accumulated samples.
+    /// Rows go from top to bottom.
+    pub fn pixels(&self) -> Vec<Vector3<f32>> {
+        let scale = 1.0 / self.sample_count.max(1) as f32;
+        self.accumulator.iter().map(|p| p.scale(scale)).collect()
+    }
+
+    /// Creates an HDR texture (`RGB32F`) from the current state of the image.
+    pub fn to_texture(&self) -> Texture {
+        let mut bytes = Vec::with_capacity(self.accumulator.len() * 3 * std::mem::size_of::<f32>());
+        for pixel in self.pixels() {
+            for component in pixel.iter() {
+                bytes.extend_from_slice(&component.to_ne_bytes());
+            }
+        }
+        Texture::from_bytes(
+            TextureKind::Rectangle {
+                width: self.settings.width,
+                height: self.settings.height,
+            },
+            TexturePixelKind::RGB32F,
+            bytes,
+        )
+        .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::untyped::ResourceKind,
+        core::{
+            algebra::{Matrix4, UnitQuaternion, Vector2, Vector3},
+            color::Color,
+        },
+        scene::{
+            base::BaseBuilder,
+            camera::CameraBuilder,
+            light::{point::PointLightBuilder, BaseLightBuilder},
+            mesh::{
+                surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+                MeshBuilder,
+            },
+            transform::TransformBuilder,
+            Scene,
+        },
+        utils::path_tracer::{PathTracer, PathTracingSettings},
+    };
+
+    #[test]
+    fn test_path_tracer() {
+        let mut scene = Scene::new();
+
+        // Floor, that is seen in the lower half of the image.
+        MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+                ResourceKind::Embedded,
+                SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&Vector3::new(
+                    20.0, 0.1, 20.0,
+                ))),
+            ))
+            .build()])
+            .build(&mut scene.graph);
+
+        PointLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 2.0, 3.0))
+                    .build(),
+            ),
+        ))
+        .with_radius(10.0)
+        .build(&mut scene.graph);
+
+        let camera = CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 1.0, 0.0))
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        30.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        scene
+            .graph
+            .update(Vector2::new(16.0, 16.0), 0.0, Default::default());
+
+        let settings = PathTracingSettings {
+            width: 16,
+            height: 16,
+            sky_color: Color::BLACK,
+            ..Default::default()
+        };
+        let mut tracer = PathTracer::from_scene(&scene, camera, settings).unwrap();
+        tracer.render_pass();
+        tracer.render_pass();
+        assert_eq!(tracer.sample_count(), 2);
+
+        let pixels = tracer.pixels();
+        // Top-left corner looks at the black sky.
+        assert_eq!(pixels[0], Vector3::default());
+        // Bottom row looks at the lit floor.
+        assert!(pixels[15 * 16 + 8].x > 0.0);
+
+        let texture = tracer.to_texture();
+        assert_eq!(texture.data().len(), 16 * 16 * 3 * 4);
+
+        assert!(PathTracer::from_scene(&scene, Default::default(), Default::default()).is_err());
+    }
+}
@@ -0,0 +1,386 @@
+//! Grid snapping, vertex snapping and surface placement utilities. These are the same algorithms,
+//! that are used by the editor placement tools, so in-game building mechanics could behave
+//! exactly like the editor.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, UnitQuaternion, Vector3},
+        math::{self, plane::Plane, ray::Ray, TriangleDefinition, Vector3Ext},
+        pool::Handle,
+    },
+    graph::SceneGraph,
+    scene::{
+        graph::Graph,
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait},
+            surface::SurfaceData,
+            Mesh,
+        },
+        node::Node,
+    },
+};
+
+/// Snaps every component of the given vector to a grid with the given step (per axis). Zero or
+/// negative step of an axis leaves the respective component untouched.
+pub fn snap_to_grid(v: Vector3<f32>, step: Vector3<f32>) -> Vector3<f32> {
+    v.zip_map(&step, |v, step| {
+        if step > 0.0 {
+            math::round_to_step(v, step)
+        } else {
+            v
+        }
+    })
+}
+
+/// Snaps Euler angles of the given rotation to the given step (in radians, per axis). Zero or
+/// negative step of an axis leaves the respective angle untouched.
+pub fn snap_angles(rotation: UnitQuaternion<f32>, step: Vector3<f32>) -> UnitQuaternion<f32> {
+    let (roll, pitch, yaw) = rotation.euler_angles();
+    let angles = snap_to_grid(Vector3::new(roll, pitch, yaw), step);
+    UnitQuaternion::from_euler_angles(angles.x, angles.y, angles.z)
+}
+
+fn read_vertex_position(data: &SurfaceData, i: u32) -> Option<Vector3<f32>> {
+    data.vertex_buffer
+        .get(i as usize)
+        .and_then(|v| v.read_3_f32(VertexAttributeUsage::Position).ok())
+}
+
+fn transform_vertex(vertex: Vector3<f32>, transform: &Matrix4<f32>) -> Vector3<f32> {
+    transform.transform_point(&Point3::from(vertex)).coords
+}
+
+fn read_triangle(
+    data: &SurfaceData,
+    triangle: &TriangleDefinition,
+    transform: &Matrix4<f32>,
+) -> Option<[Vector3<f32>; 3]> {
+    let a = transform_vertex(read_vertex_position(data, triangle[0])?, transform);
+    let b = transform_vertex(read_vertex_position(data, triangle[1])?, transform);
+    let c = transform_vertex(read_vertex_position(data, triangle[2])?, transform);
+    Some([a, b, c])
+}
+
+/// Result of a ray test with triangles of a mesh.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeshRayHit {
+    /// Squared distance from the origin of the ray to the intersection point.
+    pub sqr_distance: f32,
+    /// Intersection point in world coordinates.
+    pub position: Vector3<f32>,
+    /// Normal of the intersected triangle in world coordinates. It always faces the origin of
+    /// the ray.
+    pub normal: Vector3<f32>,
+}
+
+/// Performs precise ray test with every triangle of the given mesh and returns the closest
+/// intersection (if any). Back faces (triangles, that are facing in the same direction as the
+/// ray) could be ignored using `ignore_back_faces` flag.
+pub fn mesh_ray_test(mesh: &Mesh, ray: &Ray, ignore_back_faces: bool) -> Option<MeshRayHit> {
+    let transform = mesh.global_transform();
+    let mut closest: Option<MeshRayHit> = None;
+
+    for surface in mesh.surfaces().iter() {
+        let data = surface.data();
+        let data = data.data_ref();
+
+        for triangle in data
+            .geometry_buffer
+            .iter()
+            .filter_map(|t| read_triangle(&data, t, &transform))
+        {
+            let normal = (triangle[1] - triangle[0]).cross(&(triangle[2] - triangle[0]));
+            let facing_away = normal.dot(&ray.dir) >= 0.0;
+            if ignore_back_faces && facing_away {
+                continue;
+            }
+
+            if let Some(position) = ray.triangle_intersection_point(&triangle) {
+                let sqr_distance = ray.origin.sqr_distance(&position);
+
+                if closest
+                    .as_ref()
+                    .map_or(true, |c| sqr_distance < c.sqr_distance)
+                {
+                    let normal = normal.try_normalize(f32::EPSILON).unwrap_or_default();
+                    closest = Some(MeshRayHit {
+                        sqr_distance,
+                        position,
+                        normal: if facing_away { -normal } else { normal },
+                    });
+                }
+            }
+        }
+    }
+
+    closest
+}
+
+/// Performs precise ray test with every visible mesh of the given graph, that passes the given
+/// filter, and returns the closest intersection together with a handle of the intersected mesh.
+pub fn ray_cast_meshes<F>(
+    graph: &Graph,
+    ray: &Ray,
+    ignore_back_faces: bool,
+    mut filter: F,
+) -> Option<(Handle<Node>, MeshRayHit)>
+where
+    F: FnMut(Handle<Node>, &Node) -> bool,
+{
+    let mut closest: Option<(Handle<Node>, MeshRayHit)> = None;
+
+    for (handle, node) in graph.pair_iter() {
+        let Some(mesh) = node.query_component_ref::<Mesh>() else {
+            continue;
+        };
+
+        if !node.global_visibility() || !filter(handle, node) {
+            continue;
+        }
+
+        // Do coarse, but fast, intersection test with bounding box first.
+        let aabb = node
+            .local_bounding_box()
+            .transform(&node.global_transform());
+        if ray.aabb_intersection_points(&aabb).is_none() {
+            continue;
+        }
+
+        if let Some(hit) = mesh_ray_test(mesh, ray, ignore_back_faces) {
+            if closest
+                .as_ref()
+                .map_or(true, |(_, c)| hit.sqr_distance < c.sqr_distance)
+            {
+                closest = Some((handle, hit));
+            }
+        }
+    }
+
+    closest
+}
+
+/// Searches for the closest vertex of visible meshes (that pass the given filter) in the given
+/// radius around the given point. Returns position of the vertex in world coordinates.
+pub fn snap_to_vertex<F>(
+    graph: &Graph,
+    point: Vector3<f32>,
+    radius: f32,
+    mut filter: F,
+) -> Option<Vector3<f32>>
+where
+    F: FnMut(Handle<Node>, &Node) -> bool,
+{
+    let mut closest_sqr_distance = radius * radius;
+    let mut closest = None;
+
+    for (handle, node) in graph.pair_iter() {
+        let Some(mesh) = node.query_component_ref::<Mesh>() else {
+            continue;
+        };
+
+        if !node.global_visibility() || !filter(handle, node) {
+            continue;
+        }
+
+        let transform = node.global_transform();
+        let mut aabb = node.local_bounding_box().transform(&transform);
+        aabb.inflate(Vector3::repeat(2.0 * radius));
+        if !aabb.is_contains_point(point) {
+            continue;
+        }
+
+        for surface in mesh.surfaces().iter() {
+            let data = surface.data();
+            let data = data.data_ref();
+
+            for vertex in data
+                .vertex_buffer
+                .iter()
+                .filter_map(|v| v.read_3_f32(VertexAttributeUsage::Position).ok())
+            {
+                let vertex = transform_vertex(vertex, &transform);
+                let sqr_distance = vertex.sqr_distance(&point);
+                if sqr_distance <= closest_sqr_distance {
+                    closest_sqr_distance = sqr_distance;
+                    closest = Some(vertex);
+                }
+            }
+        }
+    }
+
+    closest
+}
+
+/// A set of options for [`place_on_surface`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlacementOptions {
+    /// Whether back faces of meshes should be ignored or not.
+    pub ignore_back_faces: bool,
+    /// Optional grid step (per axis), that is used to snap final position.
+    pub grid_step: Option<Vector3<f32>>,
+    /// Optional plane, that is used when the ray does not intersect any mesh. By default it is
+    /// `oXZ` plane, which is the same plane as the editor uses.
+    pub fallback_plane: Option<Plane>,
+}
+
+impl Default for PlacementOptions {
+    fn default() -> Self {
+        Self {
+            ignore_back_faces: false,
+            grid_step: None,
+            fallback_plane: Plane::from_normal_and_point(&Vector3::y(), &Default::default()),
+        }
+    }
+}
+
+/// Result of [`place_on_surface`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SurfacePlacement {
+    /// A handle of a mesh, that was intersected by the ray. It is [`Handle::NONE`] if the object
+    /// was placed on the fallback plane.
+    pub node: Handle<Node>,
+    /// Final position of the object (snapped to the grid, if needed).
+    pub position: Vector3<f32>,
+    /// Normal of the surface at the intersection point.
+    pub normal: Vector3<f32>,
+    /// Rotation of the object. It is identity, unless the alignment to the surface normal was
+    /// requested, in this case it rotates `Y` axis of the object to the normal of the surface.
+    pub rotation: UnitQuaternion<f32>,
+}
+
+/// Finds a place for an object on the closest surface along the given ray. It works the same as
+/// the placement of objects dragged from the asset browser in the editor: the ray is tested with
+/// every visible mesh (that passes the given filter) and, if there's no intersection, with the
+/// fallback plane. When `align_normal` is set, the object is rotated so its `Y` axis matches the
+/// normal of the surface.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::Vector3, math::ray::Ray, pool::Handle},
+/// #     scene::{graph::Graph, node::Node},
+/// #     utils::placement::{place_on_surface, PlacementOptions},
+/// # };
+/// fn place_building(graph: &mut Graph, building: Handle<Node>, ray: &Ray) {
+///     let options = PlacementOptions {
+///         grid_step: Some(Vector3::repeat(0.5)),
+///         ..Default::default()
+///     };
+///     // Buildings should not be placed on themselves.
+///     if let Some(placement) = place_on_surface(graph, ray, true, &options, |h, _| h != building) {
+///         graph[building]
+///             .local_transform_mut()
+///             .set_position(placement.position)
+///             .set_rotation(placement.rotation);
+///     }
+/// }
+/// ```
+pub fn place_on_surface<F>(
+    graph: &Graph,
+    ray: &Ray,
+    align_normal: bool,
+    options: &PlacementOptions,
+    filter: F,
+) -> Option<SurfacePlacement>
+where
+    F: FnMut(Handle<Node>, &Node) -> bool,
+{
+    let (node, position, normal) =
+        if let Some((node, hit)) = ray_cast_meshes(graph, ray, options.ignore_back_faces, filter) {
+            (node, hit.position, hit.normal)
+        } else {
+            let plane = options.fallback_plane.as_ref()?;
+            let position = ray.plane_intersection_point(plane)?;
+            (Handle::NONE, position, plane.normal)
+        };
+
+    let rotation = if align_normal {
+        UnitQuaternion::rotation_between(&Vector3::y(), &normal).unwrap_or_else(|| {
+            // Normal is opposite to Y axis.
+            UnitQuaternion::from_axis_angle(&Vector3::x_axis(), std::f32::consts::PI)
+        })
+    } else {
+        UnitQuaternion::identity()
+    };
+
+    Some(SurfacePlacement {
+        node,
+        position: options
+            .grid_step
+            .map_or(position, |step| snap_to_grid(position, step)),
+        normal,
+        rotation,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::{untyped::ResourceKind, Resource},
+        core::{
+            algebra::{Matrix4, Vector2, Vector3},
+            math::ray::Ray,
+        },
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            mesh::{
+                surface::{SurfaceBuilder, SurfaceData},
+                MeshBuilder,
+            },
+        },
+        utils::placement::{place_on_surface, snap_to_grid, snap_to_vertex, PlacementOptions},
+    };
+
+    #[test]
+    fn test_snap_to_grid() {
+        assert_eq!(
+            snap_to_grid(Vector3::new(0.3, 1.3, -0.8), Vector3::new(0.5, 0.0, 1.0)),
+            Vector3::new(0.5, 1.3, -1.0)
+        );
+    }
+
+    #[test]
+    fn test_place_on_surface() {
+        let mut graph = Graph::new();
+        let cube = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(Resource::new_ok(
+                ResourceKind::Embedded,
+                SurfaceData::make_cube(Matrix4::identity()),
+            ))
+            .build()])
+            .build(&mut graph);
+        graph.update(Vector2::new(800.0, 600.0), 0.0, Default::default());
+
+        let options = PlacementOptions::default();
+
+        // Top face of the cube.
+        let ray = Ray::new(Vector3::new(0.1, 5.0, 0.0), Vector3::new(0.0, -10.0, 0.0));
+        let placement = place_on_surface(&graph, &ray, true, &options, |_, _| true).unwrap();
+        assert_eq!(placement.node, cube);
+        assert!((placement.position - Vector3::new(0.1, 0.5, 0.0)).norm() < 1.0e-5);
+        assert!((placement.normal - Vector3::y()).norm() < 1.0e-5);
+
+        // Side face of the cube, the object must be rotated.
+        let ray = Ray::new(Vector3::new(5.0, 0.0, 0.0), Vector3::new(-10.0, 0.0, 0.0));
+        let placement = place_on_surface(&graph, &ray, true, &options, |_, _| true).unwrap();
+        assert!((placement.normal - Vector3::x()).norm() < 1.0e-5);
+        assert!((placement.rotation * Vector3::y() - Vector3::x()).norm() < 1.0e-5);
+
+        // Fallback plane.
+        let ray = Ray::new(Vector3::new(3.0, 5.0, 0.0), Vector3::new(0.0, -10.0, 0.0));
+        let placement = place_on_surface(&graph, &ray, false, &options, |_, _| true).unwrap();
+        assert!(placement.node.is_none());
+        assert!((placement.position - Vector3::new(3.0, 0.0, 0.0)).norm() < 1.0e-5);
+
+        assert!(
+            snap_to_vertex(&graph, Vector3::new(0.45, 0.45, 0.6), 0.2, |_, _| true)
+                .is_some_and(|v| (v - Vector3::repeat(0.5)).norm() < 1.0e-5)
+        );
+        assert_eq!(
+            snap_to_vertex(&graph, Vector3::new(2.0, 0.0, 0.0), 0.2, |_, _| true),
+            None
+        );
+    }
+}
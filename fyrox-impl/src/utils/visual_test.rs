@@ -0,0 +1,649 @@
+//! Visual regression testing harness. It renders a scene off-screen at a fixed resolution, with fixed
+//! time step and fixed random seeds, and compares the result with a "golden" image using a perceptual
+//! difference metric. See [`VisualTest`] docs for more info.
+
+use crate::{
+    core::{algebra::Vector2, pool::Handle},
+    renderer::{
+        framework::{error::FrameworkError, gpu_texture::GpuTextureKind},
+        Renderer,
+    },
+    resource::texture::{TextureResource, TextureResourceExtension},
+    scene::{
+        particle_system::{ParticleSystem, ParticleSystemRng},
+        Scene,
+    },
+};
+use std::{
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+/// Name of an environment variable, that forces the harness to overwrite golden images with the actual
+/// rendering results instead of comparing them.
+pub const UPDATE_GOLDEN_ENV_VAR: &str = "FYROX_UPDATE_GOLDEN";
+
+/// An error that may occur during visual testing.
+#[derive(Debug)]
+pub enum VisualTestError {
+    /// Rendering error.
+    Framework(FrameworkError),
+    /// Unable to load or save an image.
+    Image(image::ImageError),
+    /// Golden image has different size.
+    SizeMismatch {
+        /// Size of the golden image.
+        expected: Vector2<u32>,
+        /// Size of the rendered image.
+        actual: Vector2<u32>,
+    },
+    /// Rendered image differs from the golden image too much.
+    Mismatch {
+        /// Comparison result.
+        diff: ImageDiff,
+        /// Path to the image that contains the actual rendering result.
+        actual_path: PathBuf,
+        /// Path to the image with highlighted differences.
+        diff_path: PathBuf,
+    },
+}
+
+impl Display for VisualTestError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VisualTestError::Framework(v) => Display::fmt(v, f),
+            VisualTestError::Image(v) => Display::fmt(v, f),
+            VisualTestError::SizeMismatch { expected, actual } => write!(
+                f,
+                "Golden image size mismatch. Expected {}x{}, got {}x{}.",
+                expected.x, expected.y, actual.x, actual.y
+            ),
+            VisualTestError::Mismatch {
+                diff,
+                actual_path,
+                diff_path,
+            } => write!(
+                f,
+                "Rendered image differs from the golden image: {} of {} pixels ({:.3}%) differ, \
+                max delta is {:.4}. See {} and {}.",
+                diff.differing_pixels,
+                diff.total_pixels,
+                diff.differing_fraction() * 100.0,
+                diff.max_delta,
+                actual_path.display(),
+                diff_path.display()
+            ),
+        }
+    }
+}
+
+impl From<FrameworkError> for VisualTestError {
+    fn from(value: FrameworkError) -> Self {
+        Self::Framework(value)
+    }
+}
+
+impl From<image::ImageError> for VisualTestError {
+    fn from(value: image::ImageError) -> Self {
+        Self::Image(value)
+    }
+}
+
+/// Defines how much two images may differ to be considered equal.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DiffThreshold {
+    /// Perceptual difference (in `[0; 1]` range) of a pixel pair above which the pixels are considered
+    /// different. Small values catch subtle color shifts, large values catch only notable changes.
+    pub pixel_threshold: f32,
+    /// Maximum fraction (in `[0; 1]` range) of different pixels.
+    pub max_differing_fraction: f32,
+}
+
+impl Default for DiffThreshold {
+    fn default() -> Self {
+        Self {
+            pixel_threshold: 0.1,
+            max_differing_fraction: 0.001,
+        }
+    }
+}
+
+/// Result of comparison of two images.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImageDiff {
+    /// Total amount of pixels in each image.
+    pub total_pixels: usize,
+    /// Amount of pixels that were considered different.
+    pub differing_pixels: usize,
+    /// Maximum perceptual difference between two pixels.
+    pub max_delta: f32,
+    /// Average perceptual difference over all pixels.
+    pub mean_delta: f32,
+}
+
+impl ImageDiff {
+    /// Returns fraction (in `[0; 1]` range) of different pixels.
+    pub fn differing_fraction(&self) -> f32 {
+        if self.total_pixels == 0 {
+            0.0
+        } else {
+            self.differing_pixels as f32 / self.total_pixels as f32
+        }
+    }
+
+    /// Checks whether the difference is within the given threshold or not.
+    pub fn is_within(&self, threshold: &DiffThreshold) -> bool {
+        self.differing_fraction() <= threshold.max_differing_fraction
+    }
+}
+
+/// Captured image in RGBA8 format. Rows are stored from top to bottom.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CapturedFrame {
+    /// Width of the frame in pixels.
+    pub width: u32,
+    /// Height of the frame in pixels.
+    pub height: u32,
+    /// Pixels of the frame in RGBA8 format.
+    pub pixels: Vec<u8>,
+}
+
+impl CapturedFrame {
+    /// Loads a frame from an image file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, VisualTestError> {
+        let image = image::open(path)?.to_rgba8();
+        Ok(Self {
+            width: image.width(),
+            height: image.height(),
+            pixels: image.into_raw(),
+        })
+    }
+
+    /// Saves the frame to an image file. Format is defined by the extension of the file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), VisualTestError> {
+        if let Some(parent) = path.as_ref().parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        image::save_buffer(
+            path,
+            &self.pixels,
+            self.width,
+            self.height,
+            image::ExtendedColorType::Rgba8,
+        )?;
+        Ok(())
+    }
+
+    /// Compares the frame with another one and returns the comparison result together with an image
+    /// that highlights differences (red pixels are different, the rest is a faded copy of `self`).
+    pub fn compare(
+        &self,
+        other: &CapturedFrame,
+        threshold: &DiffThreshold,
+    ) -> Result<(ImageDiff, CapturedFrame), VisualTestError> {
+        if self.width != other.width || self.height != other.height {
+            return Err(VisualTestError::SizeMismatch {
+                expected: Vector2::new(self.width, self.height),
+                actual: Vector2::new(other.width, other.height),
+            });
+        }
+
+        let total_pixels = (self.width * self.height) as usize;
+        let mut diff = ImageDiff {
+            total_pixels,
+            ..Default::default()
+        };
+        let mut diff_pixels = Vec::with_capacity(self.pixels.len());
+        let mut delta_sum = 0.0;
+
+        for (a, b) in self
+            .pixels
+            .chunks_exact(4)
+            .zip(other.pixels.chunks_exact(4))
+        {
+            let delta = perceptual_delta([a[0], a[1], a[2], a[3]], [b[0], b[1], b[2], b[3]]);
+            delta_sum += delta;
+            diff.max_delta = diff.max_delta.max(delta);
+            if delta > threshold.pixel_threshold {
+                diff.differing_pixels += 1;
+                diff_pixels.extend_from_slice(&[255, 0, 0, 255]);
+            } else {
+                let gray = (luma(a[0], a[1], a[2]) * 0.25 + 191.0) as u8;
+                diff_pixels.extend_from_slice(&[gray, gray, gray, 255]);
+            }
+        }
+
+        if total_pixels > 0 {
+            diff.mean_delta = delta_sum / total_pixels as f32;
+        }
+
+        Ok((
+            diff,
+            CapturedFrame {
+                width: self.width,
+                height: self.height,
+                pixels: diff_pixels,
+            },
+        ))
+    }
+}
+
+fn luma(r: u8, g: u8, b: u8) -> f32 {
+    0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32
+}
+
+// Blends a color with white background using its alpha.
+fn blend(c: u8, a: f32) -> f32 {
+    255.0 + (c as f32 - 255.0) * a
+}
+
+/// Calculates perceptual difference between two RGBA8 pixels in `[0; 1]` range. It uses YIQ color space,
+/// which is a good approximation of human color perception ("Measuring perceived color difference using
+/// YIQ NTSC transmission color space in mobile applications" by Y. Kotsarenko and F. Ramos).
+pub fn perceptual_delta(a: [u8; 4], b: [u8; 4]) -> f32 {
+    if a == b {
+        return 0.0;
+    }
+
+    let alpha_a = a[3] as f32 / 255.0;
+    let alpha_b = b[3] as f32 / 255.0;
+
+    let (r1, g1, b1) = (
+        blend(a[0], alpha_a),
+        blend(a[1], alpha_a),
+        blend(a[2], alpha_a),
+    );
+    let (r2, g2, b2) = (
+        blend(b[0], alpha_b),
+        blend(b[1], alpha_b),
+        blend(b[2], alpha_b),
+    );
+
+    let y = |r: f32, g: f32, b: f32| r * 0.2988953 + g * 0.5866225 + b * 0.11448223;
+    let i = |r: f32, g: f32, b: f32| r * 0.59597796 - g * 0.2741761 - b * 0.3218019;
+    let q = |r: f32, g: f32, b: f32| r * 0.21147017 - g * 0.5226171 + b * 0.31114694;
+
+    let dy = y(r1, g1, b1) - y(r2, g2, b2);
+    let di = i(r1, g1, b1) - i(r2, g2, b2);
+    let dq = q(r1, g1, b1) - q(r2, g2, b2);
+
+    // Maximum possible value of the weighted sum below for 8-bit colors.
+    const MAX_DELTA: f32 = 35215.0;
+
+    ((0.5053 * dy * dy + 0.299 * di * di + 0.1957 * dq * dq) / MAX_DELTA).clamp(0.0, 1.0)
+}
+
+/// Visual test renders a scene off-screen and compares the result with a golden image. If there's no
+/// golden image (or [`UPDATE_GOLDEN_ENV_VAR`] environment variable is set), the rendered image becomes
+/// the new golden image. On mismatch, the actual image and the difference image are written next to the
+/// golden image with `.actual.png` and `.diff.png` suffixes.
+///
+/// ## Determinism
+///
+/// The test updates the scene with a fixed time step and re-seeds every particle system in the scene
+/// with a fixed seed before rendering, so the result does not depend on the frame rate or on previous
+/// runs.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// # use fyrox_impl::{renderer::Renderer, scene::Scene, utils::visual_test::VisualTest};
+/// fn test_scene(renderer: &mut Renderer, scene: &mut Scene) {
+///     VisualTest::new("tests/golden/my_scene.png")
+///         .with_frame_size(256, 256)
+///         .with_frames(30)
+///         .run(renderer, scene)
+///         .unwrap();
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct VisualTest {
+    golden_path: PathBuf,
+    width: u32,
+    height: u32,
+    frames: usize,
+    dt: f32,
+    seed: u64,
+    threshold: DiffThreshold,
+}
+
+impl VisualTest {
+    /// Creates new visual test, that will use the given path as a golden image.
+    pub fn new<P: AsRef<Path>>(golden_path: P) -> Self {
+        Self {
+            golden_path: golden_path.as_ref().to_path_buf(),
+            width: 256,
+            height: 256,
+            frames: 1,
+            dt: 1.0 / 60.0,
+            seed: 0xDEADBEEF,
+            threshold: Default::default(),
+        }
+    }
+
+    /// Sets the size of the off-screen frame.
+    pub fn with_frame_size(mut self, width: u32, height: u32) -> Self {
+        self.width = width.max(1);
+        self.height = height.max(1);
+        self
+    }
+
+    /// Sets the amount of frames to update and render before capturing the result.
+    pub fn with_frames(mut self, frames: usize) -> Self {
+        self.frames = frames.max(1);
+        self
+    }
+
+    /// Sets fixed time step (in seconds) of each frame.
+    pub fn with_dt(mut self, dt: f32) -> Self {
+        self.dt = dt;
+        self
+    }
+
+    /// Sets the seed, that will be used for every particle system in the scene.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets the comparison threshold.
+    pub fn with_threshold(mut self, threshold: DiffThreshold) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Renders the scene and captures the last frame, without any comparison.
+    pub fn capture(
+        &self,
+        renderer: &mut Renderer,
+        scene: &mut Scene,
+    ) -> Result<CapturedFrame, VisualTestError> {
+        for (_, node) in scene.graph.pair_iter_mut() {
+            if let Some(particle_system) = node.cast_mut::<ParticleSystem>() {
+                particle_system.set_rng(ParticleSystemRng::new(self.seed));
+                particle_system.clear_particles();
+            }
+        }
+
+        let prev_render_target = scene
+            .rendering_options
+            .render_target
+            .replace(TextureResource::new_render_target(self.width, self.height));
+
+        let frame_size = Vector2::new(self.width as f32, self.height as f32);
+
+        // Use a handle that cannot collide with any real scene, so the test won't interfere with the
+        // data of scenes that were registered in the engine.
+        let temp_handle = Handle::new(u32::MAX, u32::MAX);
+
+        let result = (|| -> Result<CapturedFrame, VisualTestError> {
+            let mut ldr_texture = None;
+            for _ in 0..self.frames {
+                scene.update(frame_size, self.dt, Default::default());
+                let data = renderer.render_scene(temp_handle, scene, self.dt)?;
+                ldr_texture = data
+                    .ldr_scene_framebuffer
+                    .color_attachments()
+                    .first()
+                    .map(|a| a.texture.clone());
+            }
+
+            let ldr_texture = ldr_texture.ok_or_else(|| {
+                FrameworkError::Custom("Scene frame buffer has no color attachment!".to_string())
+            })?;
+            let mut ldr_texture = ldr_texture.borrow_mut();
+            let (width, height) = match ldr_texture.kind() {
+                GpuTextureKind::Rectangle { width, height } => (width, height),
+                _ => unreachable!(),
+            };
+
+            let pipeline_state = renderer.pipeline_state();
+            let pixels = ldr_texture
+                .bind_mut(pipeline_state, 0)
+                .read_pixels(pipeline_state);
+
+            // OpenGL stores rows from bottom to top, flip them.
+            let row_size = width * 4;
+            let mut flipped = Vec::with_capacity(pixels.len());
+            for row in pixels.chunks_exact(row_size).rev() {
+                flipped.extend_from_slice(row);
+            }
+
+            Ok(CapturedFrame {
+                width: width as u32,
+                height: height as u32,
+                pixels: flipped,
+            })
+        })();
+
+        renderer.scene_data_map.remove(&temp_handle);
+        scene.rendering_options.render_target = prev_render_target;
+
+        result
+    }
+
+    /// Renders the scene and compares the result with the golden image.
+    pub fn run(
+        &self,
+        renderer: &mut Renderer,
+        scene: &mut Scene,
+    ) -> Result<ImageDiff, VisualTestError> {
+        let actual = self.capture(renderer, scene)?;
+        self.compare_with_golden(&actual)
+    }
+
+    /// Compares the given frame with the golden image. See [`VisualTest`] docs for more info.
+    pub fn compare_with_golden(
+        &self,
+        actual: &CapturedFrame,
+    ) -> Result<ImageDiff, VisualTestError> {
+        if std::env::var_os(UPDATE_GOLDEN_ENV_VAR).is_some() || !self.golden_path.exists() {
+            actual.save(&self.golden_path)?;
+            return Ok(ImageDiff {
+                total_pixels: (actual.width * actual.height) as usize,
+                ..Default::default()
+            });
+        }
+
+        let golden = CapturedFrame::load(&self.golden_path)?;
+        let (diff, diff_image) = golden.compare(actual, &self.threshold)?;
+        if diff.is_within(&self.threshold) {
+            Ok(diff)
+        } else {
+            let actual_path = self.golden_path.with_extension("actual.png");
+            let diff_path = self.golden_path.with_extension("diff.png");
+            actual.save(&actual_path)?;
+            diff_image.save(&diff_path)?;
+            Err(VisualTestError::Mismatch {
+                diff,
+                actual_path,
+                diff_path,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // EGL is not available on macOS, golden images are checked on the other desktop platforms.
+    #[cfg(not(any(target_os = "macos", target_os = "ios", target_arch = "wasm32")))]
+    mod golden {
+        use super::*;
+        use crate::{
+            asset::{manager::ResourceManager, untyped::ResourceKind},
+            core::{
+                algebra::{Matrix4, Vector3},
+                color::Color,
+                sstorage::ImmutableString,
+            },
+            material::{Material, MaterialResource, PropertyValue},
+            renderer::framework::state::GlKind,
+            scene::{
+                base::BaseBuilder,
+                camera::{CameraBuilder, SkyBoxKind},
+                light::{point::PointLightBuilder, BaseLightBuilder},
+                mesh::{
+                    surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+                    MeshBuilder,
+                },
+                transform::TransformBuilder,
+            },
+        };
+        use glutin::{
+            api::egl::{device::Device, display::Display},
+            config::{ConfigSurfaceTypes, ConfigTemplateBuilder},
+            context::{ContextApi, ContextAttributesBuilder, GlProfile, Version},
+            display::GlDisplay,
+        };
+        use std::{ffi::CString, sync::Arc};
+
+        // Creates a renderer with an off-screen (surfaceless) OpenGL context. Returns `None` if there's
+        // no suitable EGL device (software rasterizers, such as llvmpipe, are fine).
+        fn make_headless_renderer(
+            width: u32,
+            height: u32,
+        ) -> Option<(Renderer, glutin::api::egl::context::PossiblyCurrentContext)> {
+            let device = Device::query_devices().ok()?.next()?;
+            let display = unsafe { Display::with_device(&device, None) }.ok()?;
+            let template = ConfigTemplateBuilder::new()
+                .with_surface_type(ConfigSurfaceTypes::empty())
+                .with_depth_size(24)
+                .with_stencil_size(8)
+                .build();
+            let config = unsafe { display.find_configs(template) }.ok()?.next()?;
+            let attributes = ContextAttributesBuilder::new()
+                .with_profile(GlProfile::Core)
+                .with_context_api(ContextApi::OpenGl(Some(Version::new(3, 3))))
+                .build(None);
+            let context = unsafe { display.create_context(&config, &attributes) }
+                .ok()?
+                .make_current_surfaceless()
+                .ok()?;
+            let gl = unsafe {
+                glow::Context::from_loader_function(|s| {
+                    display.get_proc_address(&CString::new(s).unwrap())
+                })
+            };
+            let resource_manager = ResourceManager::new(Arc::new(Default::default()));
+            let renderer =
+                Renderer::new(gl, (width, height), &resource_manager, GlKind::OpenGL).ok()?;
+            Some((renderer, context))
+        }
+
+        fn make_lit_cube_scene() -> Scene {
+            let mut scene = Scene::new();
+            scene.rendering_options.ambient_lighting_color = Color::opaque(60, 60, 60);
+            scene.rendering_options.clear_color = Some(Color::opaque(20, 30, 40));
+
+            CameraBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 0.0, -3.0))
+                        .build(),
+                ),
+            )
+            .with_specific_skybox(SkyBoxKind::None)
+            .build(&mut scene.graph);
+
+            let mut material = Material::standard();
+            material
+                .set_property(
+                    &ImmutableString::new("diffuseColor"),
+                    PropertyValue::Color(Color::opaque(200, 40, 40)),
+                )
+                .unwrap();
+
+            MeshBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_rotation(
+                            crate::core::algebra::UnitQuaternion::from_euler_angles(0.4, 0.7, 0.0),
+                        )
+                        .build(),
+                ),
+            )
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+                ResourceKind::Embedded,
+                SurfaceData::make_cube(Matrix4::identity()),
+            ))
+            .with_material(MaterialResource::new_ok(ResourceKind::Embedded, material))
+            .build()])
+            .build(&mut scene.graph);
+
+            PointLightBuilder::new(BaseLightBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(1.5, 2.0, -2.0))
+                        .build(),
+                ),
+            ))
+            .with_radius(10.0)
+            .build(&mut scene.graph);
+
+            scene
+        }
+
+        #[test]
+        fn test_golden_lit_cube() {
+            let Some((mut renderer, _context)) = make_headless_renderer(128, 128) else {
+                println!("Skipping visual test: headless OpenGL context is not available.");
+                return;
+            };
+
+            let mut scene = make_lit_cube_scene();
+            let diff = VisualTest::new("test_data/golden/lit_cube.png")
+                .with_frame_size(128, 128)
+                .with_frames(2)
+                .run(&mut renderer, &mut scene)
+                .unwrap();
+            assert_eq!(diff.total_pixels, 128 * 128);
+        }
+    }
+
+    fn frame(color: [u8; 4]) -> CapturedFrame {
+        CapturedFrame {
+            width: 4,
+            height: 4,
+            pixels: color.repeat(16),
+        }
+    }
+
+    #[test]
+    fn test_perceptual_delta() {
+        assert_eq!(perceptual_delta([10, 20, 30, 255], [10, 20, 30, 255]), 0.0);
+        assert!(perceptual_delta([0, 0, 0, 255], [255, 255, 255, 255]) > 0.9);
+        assert!(perceptual_delta([100, 100, 100, 255], [101, 100, 100, 255]) < 0.001);
+    }
+
+    #[test]
+    fn test_compare() {
+        let threshold = DiffThreshold::default();
+
+        let (diff, _) = frame([255, 0, 0, 255])
+            .compare(&frame([255, 0, 0, 255]), &threshold)
+            .unwrap();
+        assert_eq!(diff.differing_pixels, 0);
+        assert!(diff.is_within(&threshold));
+
+        let mut other = frame([255, 0, 0, 255]);
+        other.pixels[0..4].copy_from_slice(&[0, 0, 255, 255]);
+        let (diff, diff_image) = frame([255, 0, 0, 255]).compare(&other, &threshold).unwrap();
+        assert_eq!(diff.differing_pixels, 1);
+        assert_eq!(&diff_image.pixels[0..4], &[255, 0, 0, 255]);
+        assert!(!diff.is_within(&threshold));
+
+        assert!(matches!(
+            frame([0, 0, 0, 255]).compare(
+                &CapturedFrame {
+                    width: 1,
+                    height: 1,
+                    pixels: vec![0, 0, 0, 255]
+                },
+                &threshold
+            ),
+            Err(VisualTestError::SizeMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,438 @@
+//! Offline voxel-based ambient occlusion and bent normals baking. See [`bake_voxel_ao`] docs for
+//! more info.
+
+use crate::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, Point3, Vector3},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+    },
+    graph::SceneGraph,
+    resource::texture::{Texture, TextureKind, TexturePixelKind, TextureResource, TextureWrapMode},
+    scene::{
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexFetchError, VertexReadTrait},
+            Mesh,
+        },
+        node::Node,
+        Scene,
+    },
+};
+use rayon::prelude::*;
+use std::fmt::{Display, Formatter};
+
+/// Max amount of voxels along each axis of a voxel grid.
+pub const MAX_VOXELS_PER_AXIS: u32 = 128;
+
+/// An error that may occur during voxel AO baking.
+#[derive(Debug)]
+pub enum VoxelAoError {
+    /// An index of a vertex in a triangle is out of bounds.
+    InvalidIndex,
+    /// Vertex buffer of a mesh lacks required data.
+    InvalidData(VertexFetchError),
+    /// Transform of the volume is degenerated (has zero scale along some axis).
+    InvalidTransform,
+}
+
+impl Display for VoxelAoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VoxelAoError::InvalidIndex => {
+                write!(f, "An index of a vertex in a triangle is out of bounds.")
+            }
+            VoxelAoError::InvalidData(v) => {
+                write!(f, "Vertex buffer of a mesh lacks required data {v}.")
+            }
+            VoxelAoError::InvalidTransform => {
+                write!(f, "Transform of the volume is degenerated.")
+            }
+        }
+    }
+}
+
+impl From<VertexFetchError> for VoxelAoError {
+    fn from(e: VertexFetchError) -> Self {
+        Self::InvalidData(e)
+    }
+}
+
+/// Parameters of voxel AO baking.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoxelAoSettings {
+    /// Amount of voxels along each axis of the volume. Every component is clamped to
+    /// `[1; MAX_VOXELS_PER_AXIS]` range.
+    pub resolution: Vector3<u32>,
+    /// Amount of rays, that are cast from every voxel.
+    pub ray_count: u32,
+    /// Max distance (in world units) at which the geometry occludes a voxel. Occluders closer to a
+    /// voxel give more occlusion.
+    pub max_distance: f32,
+}
+
+impl Default for VoxelAoSettings {
+    fn default() -> Self {
+        Self {
+            resolution: Vector3::new(32, 16, 32),
+            ray_count: 64,
+            max_distance: 4.0,
+        }
+    }
+}
+
+/// Binary occupancy grid of the geometry inside a volume. The volume is a unit cube in the space
+/// defined by its transform.
+pub struct VoxelGrid {
+    resolution: Vector3<u32>,
+    transform: Matrix4<f32>,
+    inv_transform: Matrix4<f32>,
+    occupancy: Vec<bool>,
+}
+
+impl VoxelGrid {
+    /// Creates a new empty grid with the given transform and resolution.
+    pub fn new(transform: Matrix4<f32>, resolution: Vector3<u32>) -> Result<Self, VoxelAoError> {
+        let resolution = resolution.map(|n| n.clamp(1, MAX_VOXELS_PER_AXIS));
+        let inv_transform = transform
+            .try_inverse()
+            .ok_or(VoxelAoError::InvalidTransform)?;
+        Ok(Self {
+            resolution,
+            transform,
+            inv_transform,
+            occupancy: vec![
+                false;
+                resolution.x as usize * resolution.y as usize * resolution.z as usize
+            ],
+        })
+    }
+
+    /// Returns the amount of voxels along each axis.
+    pub fn resolution(&self) -> Vector3<u32> {
+        self.resolution
+    }
+
+    fn index(&self, voxel: Vector3<i32>) -> Option<usize> {
+        if voxel
+            .iter()
+            .zip(self.resolution.iter())
+            .all(|(c, n)| *c >= 0 && *c < *n as i32)
+        {
+            Some(
+                (voxel.z as usize * self.resolution.y as usize + voxel.y as usize)
+                    * self.resolution.x as usize
+                    + voxel.x as usize,
+            )
+        } else {
+            None
+        }
+    }
+
+    fn coordinates(&self, index: usize) -> Vector3<i32> {
+        let (nx, ny) = (self.resolution.x as usize, self.resolution.y as usize);
+        Vector3::new(
+            (index % nx) as i32,
+            ((index / nx) % ny) as i32,
+            (index / (nx * ny)) as i32,
+        )
+    }
+
+    /// Converts a world-space point into the grid space, where every voxel is a unit cube.
+    fn world_to_grid(&self, point: &Vector3<f32>) -> Vector3<f32> {
+        let local = self.inv_transform.transform_point(&Point3::from(*point));
+        (local.coords + Vector3::repeat(0.5)).component_mul(&self.resolution.cast::<f32>())
+    }
+
+    /// Returns the size of a voxel along each axis in world units.
+    pub fn voxel_size(&self) -> Vector3<f32> {
+        Vector3::new(
+            self.transform.column(0).xyz().norm(),
+            self.transform.column(1).xyz().norm(),
+            self.transform.column(2).xyz().norm(),
+        )
+        .component_div(&self.resolution.cast::<f32>())
+    }
+
+    /// Returns `true` if a voxel with the given coordinates is occupied by some geometry. Voxels
+    /// outside of the grid are always empty.
+    pub fn is_occupied(&self, voxel: Vector3<i32>) -> bool {
+        self.index(voxel).is_some_and(|i| self.occupancy[i])
+    }
+
+    fn is_occupied_at(&self, grid_point: &Vector3<f32>) -> bool {
+        self.is_occupied(grid_point.map(|c| c.floor() as i32))
+    }
+
+    /// Marks every voxel, that is touched by the given world-space triangle, as occupied.
+    pub fn add_triangle(&mut self, triangle: &[Vector3<f32>; 3]) {
+        let [a, b, c] = triangle.map(|v| self.world_to_grid(&v));
+
+        // The triangle is sampled densely enough to touch every voxel it passes through.
+        let longest_edge = (b - a).norm().max((c - a).norm()).max((c - b).norm());
+        let steps = (longest_edge * 2.0).ceil().max(1.0) as usize;
+        for i in 0..=steps {
+            for j in 0..=(steps - i) {
+                let point = a
+                    + (b - a).scale(i as f32 / steps as f32)
+                    + (c - a).scale(j as f32 / steps as f32);
+                if let Some(index) = self.index(point.map(|c| c.floor() as i32)) {
+                    self.occupancy[index] = true;
+                }
+            }
+        }
+    }
+
+    /// Voxelizes every mesh of the scene, that passes the given filter.
+    pub fn add_scene<F>(&mut self, scene: &Scene, mut filter: F) -> Result<(), VoxelAoError>
+    where
+        F: FnMut(Handle<Node>, &Node) -> bool,
+    {
+        let bounds = AxisAlignedBoundingBox::unit().transform(&self.transform);
+
+        for (handle, node) in scene.graph.pair_iter() {
+            if !filter(handle, node) {
+                continue;
+            }
+            let Some(mesh) = node.cast::<Mesh>() else {
+                continue;
+            };
+            if !mesh.world_bounding_box().is_intersects_aabb(&bounds) {
+                continue;
+            }
+
+            let global_transform = mesh.global_transform();
+            for surface in mesh.surfaces() {
+                let data = surface.data();
+                let data = data.data_ref();
+                for triangle in data.geometry_buffer.iter() {
+                    let mut vertices = [Vector3::default(); 3];
+                    for (vertex, &index) in vertices.iter_mut().zip(triangle.0.iter()) {
+                        let position = data
+                            .vertex_buffer
+                            .get(index as usize)
+                            .ok_or(VoxelAoError::InvalidIndex)?
+                            .read_3_f32(VertexAttributeUsage::Position)?;
+                        *vertex = global_transform
+                            .transform_point(&Point3::from(position))
+                            .coords;
+                    }
+                    self.add_triangle(&vertices);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Ambient occlusion and bent normal of a voxel.
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+pub struct VoxelAo {
+    /// Fraction of unoccluded directions, one means no occlusion at all.
+    pub ambient_occlusion: f32,
+    /// Average unoccluded direction in world space, its length is less than one if the
+    /// unoccluded directions are spread widely.
+    pub bent_normal: Vector3<f32>,
+}
+
+/// Returns i-th of n directions, that are evenly distributed over the unit sphere.
+fn fibonacci_direction(i: usize, n: usize) -> Vector3<f32> {
+    let golden_angle = std::f32::consts::PI * (3.0 - 5.0f32.sqrt());
+    let z = 1.0 - (2.0 * i as f32 + 1.0) / n as f32;
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    let phi = golden_angle * i as f32;
+    Vector3::new(r * phi.cos(), r * phi.sin(), z)
+}
+
+/// Calculates ambient occlusion of every voxel of the grid by marching rays through the grid.
+/// Occupied voxels get the average value of their empty neighbours, so the result could be
+/// filtered without dark halos around the geometry.
+pub fn compute_voxel_ao(grid: &VoxelGrid, ray_count: u32, max_distance: f32) -> Vec<VoxelAo> {
+    let ray_count = ray_count.max(1) as usize;
+    let max_distance = max_distance.max(f32::EPSILON);
+    let directions = (0..ray_count)
+        .map(|i| fibonacci_direction(i, ray_count))
+        .collect::<Vec<_>>();
+
+    // Rays are marched in grid space with a half-voxel step.
+    let grid_scale = grid.resolution.cast::<f32>();
+    let step = grid.voxel_size().min() * 0.5;
+    let grid_directions = directions
+        .iter()
+        .map(|d| {
+            grid.inv_transform
+                .transform_vector(d)
+                .component_mul(&grid_scale)
+        })
+        .collect::<Vec<_>>();
+
+    let mut result = (0..grid.occupancy.len())
+        .into_par_iter()
+        .map(|index| {
+            if grid.occupancy[index] {
+                return VoxelAo::default();
+            }
+
+            let origin = grid.coordinates(index).cast::<f32>() + Vector3::repeat(0.5);
+            let mut visibility_sum = 0.0;
+            let mut bent_normal = Vector3::default();
+            for (direction, grid_direction) in directions.iter().zip(grid_directions.iter()) {
+                let mut visibility = 1.0;
+                let mut t = step;
+                while t < max_distance {
+                    if grid.is_occupied_at(&(origin + grid_direction.scale(t))) {
+                        // Closer occluders give more occlusion.
+                        visibility = t / max_distance;
+                        break;
+                    }
+                    t += step;
+                }
+                visibility_sum += visibility;
+                bent_normal += direction.scale(visibility);
+            }
+
+            VoxelAo {
+                ambient_occlusion: visibility_sum / ray_count as f32,
+                bent_normal: bent_normal / ray_count as f32,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // Dilate the values into the occupied voxels.
+    let neighbours = [
+        Vector3::new(1, 0, 0),
+        Vector3::new(-1, 0, 0),
+        Vector3::new(0, 1, 0),
+        Vector3::new(0, -1, 0),
+        Vector3::new(0, 0, 1),
+        Vector3::new(0, 0, -1),
+    ];
+    let dilated = (0..grid.occupancy.len())
+        .into_par_iter()
+        .filter(|index| grid.occupancy[*index])
+        .map(|index| {
+            let voxel = grid.coordinates(index);
+            let mut sum = VoxelAo::default();
+            let mut count = 0;
+            for offset in neighbours.iter() {
+                if let Some(neighbour) = grid.index(voxel + offset).filter(|i| !grid.occupancy[*i])
+                {
+                    sum.ambient_occlusion += result[neighbour].ambient_occlusion;
+                    sum.bent_normal += result[neighbour].bent_normal;
+                    count += 1;
+                }
+            }
+            if count > 0 {
+                sum.ambient_occlusion /= count as f32;
+                sum.bent_normal /= count as f32;
+            }
+            (index, sum)
+        })
+        .collect::<Vec<_>>();
+    for (index, value) in dilated {
+        result[index] = value;
+    }
+
+    result
+}
+
+/// Packs the result of [`compute_voxel_ao`] into a `RGBA8` volume texture. Bent normals are stored
+/// in `RGB` channels (remapped to `[0; 1]` range), ambient occlusion is stored in alpha channel.
+pub fn make_voxel_ao_texture(resolution: Vector3<u32>, voxels: &[VoxelAo]) -> TextureResource {
+    let to_u8 = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let bytes = voxels
+        .iter()
+        .flat_map(|voxel| {
+            let n = voxel.bent_normal.map(|c| c * 0.5 + 0.5);
+            [
+                to_u8(n.x),
+                to_u8(n.y),
+                to_u8(n.z),
+                to_u8(voxel.ambient_occlusion),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let mut texture = Texture::from_bytes(
+        TextureKind::Volume {
+            width: resolution.x,
+            height: resolution.y,
+            depth: resolution.z,
+        },
+        TexturePixelKind::RGBA8,
+        bytes,
+    )
+    .expect("Voxel AO data must match the resolution!");
+    texture.set_s_wrap_mode(TextureWrapMode::ClampToEdge);
+    texture.set_t_wrap_mode(TextureWrapMode::ClampToEdge);
+    TextureResource::new_ok(ResourceKind::Embedded, texture)
+}
+
+/// Voxelizes static geometry inside a volume (a unit cube in the space, defined by the given
+/// transform) and bakes large-scale ambient occlusion and bent normals of the empty space into a
+/// volume texture. Only the meshes, that pass the given filter, are voxelized, use it to skip
+/// dynamic objects. Unlike screen-space ambient occlusion, the result takes into account the
+/// geometry, that is not visible on screen. The baking is done on all CPU cores and could take
+/// some time for large resolutions.
+///
+/// Global transforms of the scene nodes must be up-to-date.
+pub fn bake_voxel_ao<F>(
+    scene: &Scene,
+    transform: Matrix4<f32>,
+    settings: &VoxelAoSettings,
+    filter: F,
+) -> Result<TextureResource, VoxelAoError>
+where
+    F: FnMut(Handle<Node>, &Node) -> bool,
+{
+    let mut grid = VoxelGrid::new(transform, settings.resolution)?;
+    grid.add_scene(scene, filter)?;
+    let voxels = compute_voxel_ao(&grid, settings.ray_count, settings.max_distance);
+    Ok(make_voxel_ao_texture(grid.resolution(), &voxels))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Matrix4, Vector3},
+        utils::voxel_ao::{compute_voxel_ao, VoxelGrid},
+    };
+
+    #[test]
+    fn test_voxel_ao() {
+        // 8x8x8 voxels, every voxel is one unit.
+        let mut grid = VoxelGrid::new(Matrix4::new_scaling(8.0), Vector3::repeat(8)).unwrap();
+
+        // A floor at y = -3.5 (the lowest layer of voxels).
+        let y = -3.5;
+        grid.add_triangle(&[
+            Vector3::new(-4.0, y, -4.0),
+            Vector3::new(4.0, y, -4.0),
+            Vector3::new(4.0, y, 4.0),
+        ]);
+        grid.add_triangle(&[
+            Vector3::new(-4.0, y, -4.0),
+            Vector3::new(4.0, y, 4.0),
+            Vector3::new(-4.0, y, 4.0),
+        ]);
+        assert!(grid.is_occupied(Vector3::new(0, 0, 0)));
+        assert!(grid.is_occupied(Vector3::new(7, 0, 7)));
+        assert!(!grid.is_occupied(Vector3::new(3, 1, 3)));
+
+        let voxels = compute_voxel_ao(&grid, 64, 4.0);
+        let at = |x: usize, y: usize, z: usize| voxels[(z * 8 + y) * 8 + x];
+
+        // Voxels near the floor are more occluded than the voxels above.
+        let near = at(4, 1, 4);
+        let far = at(4, 6, 4);
+        assert!(near.ambient_occlusion < far.ambient_occlusion);
+        assert!(far.ambient_occlusion > 0.95);
+
+        // Unoccluded directions point away from the floor.
+        assert!(near.bent_normal.y > 0.1);
+
+        // Floor voxels get the values of the empty voxels above them.
+        assert_eq!(at(4, 0, 4).ambient_occlusion, near.ambient_occlusion);
+    }
+}
@@ -203,6 +203,33 @@ impl AxisAlignedBoundingBox {
                 && (position.z <= self.max.z))
     }
 
+    /// Returns a point on (or in) the box that is closest to the given point.
+    #[inline]
+    pub fn closest_point(&self, point: Vector3<f32>) -> Vector3<f32> {
+        point.sup(&self.min).inf(&self.max)
+    }
+
+    /// Returns squared distance from the box to the given point. Zero if the point is inside.
+    #[inline]
+    pub fn sqr_distance_to_point(&self, point: Vector3<f32>) -> f32 {
+        (self.closest_point(point) - point).norm_squared()
+    }
+
+    /// Returns distance from the box to the given point. Zero if the point is inside.
+    #[inline]
+    pub fn distance_to_point(&self, point: Vector3<f32>) -> f32 {
+        self.sqr_distance_to_point(point).sqrt()
+    }
+
+    /// Returns distance between two boxes. Zero if the boxes intersect.
+    #[inline]
+    pub fn distance_to_aabb(&self, other: &Self) -> f32 {
+        let gap = (self.min - other.max)
+            .sup(&(other.min - self.max))
+            .sup(&Vector3::default());
+        gap.norm()
+    }
+
     #[inline]
     pub fn is_intersects_aabb(&self, other: &Self) -> bool {
         let self_center = self.center();
@@ -510,6 +537,22 @@ mod test {
         assert!(!_box.is_intersects_aabb(&_box2));
     }
 
+    #[test]
+    fn test_aabb_distances() {
+        let _box = AxisAlignedBoundingBox::unit();
+        assert_eq!(
+            _box.closest_point(Vector3::new(2.0, 0.25, -3.0)),
+            Vector3::new(0.5, 0.25, -0.5)
+        );
+        assert_eq!(_box.distance_to_point(Vector3::new(0.0, 0.0, 0.0)), 0.0);
+        assert_eq!(_box.distance_to_point(Vector3::new(0.0, 2.5, 0.0)), 2.0);
+
+        let mut _box2 = _box;
+        _box2.offset(Vector3::new(4.0, 0.0, 0.0));
+        assert_eq!(_box.distance_to_aabb(&_box2), 3.0);
+        assert_eq!(_box.distance_to_aabb(&_box), 0.0);
+    }
+
     #[test]
     fn test_aabb_split() {
         let _box = AxisAlignedBoundingBox::from_radius(1.0);
@@ -0,0 +1,272 @@
+use crate::{aabb::AxisAlignedBoundingBox, obb::OrientedBoundingBox, plane::Plane};
+use nalgebra::Vector3;
+
+/// Capsule is a line segment "inflated" by some radius. It is the usual shape of character
+/// controllers, which makes capsule tests useful for gameplay queries and culling.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Capsule {
+    /// First point of the inner segment.
+    pub begin: Vector3<f32>,
+    /// Second point of the inner segment.
+    pub end: Vector3<f32>,
+    /// Radius of the capsule.
+    pub radius: f32,
+}
+
+impl Default for Capsule {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            begin: Vector3::new(0.0, -0.5, 0.0),
+            end: Vector3::new(0.0, 0.5, 0.0),
+            radius: 0.5,
+        }
+    }
+}
+
+/// Returns a point on the segment `[begin, end]` that is closest to the given point.
+#[inline]
+pub fn closest_point_on_segment(
+    begin: Vector3<f32>,
+    end: Vector3<f32>,
+    point: Vector3<f32>,
+) -> Vector3<f32> {
+    let dir = end - begin;
+    let sqr_len = dir.norm_squared();
+    if sqr_len <= f32::EPSILON {
+        return begin;
+    }
+    let t = ((point - begin).dot(&dir) / sqr_len).clamp(0.0, 1.0);
+    begin + dir.scale(t)
+}
+
+/// Returns a pair of closest points of two segments `[p1, q1]` and `[p2, q2]`.
+///
+/// # References
+///
+/// "Real-Time Collision Detection" by Christer Ericson, 5.1.9 Closest Points of Two Line Segments
+#[inline]
+pub fn closest_points_between_segments(
+    p1: Vector3<f32>,
+    q1: Vector3<f32>,
+    p2: Vector3<f32>,
+    q2: Vector3<f32>,
+) -> (Vector3<f32>, Vector3<f32>) {
+    let d1 = q1 - p1;
+    let d2 = q2 - p2;
+    let r = p1 - p2;
+    let a = d1.norm_squared();
+    let e = d2.norm_squared();
+    let f = d2.dot(&r);
+
+    let (s, t) = if a <= f32::EPSILON && e <= f32::EPSILON {
+        (0.0, 0.0)
+    } else if a <= f32::EPSILON {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = d1.dot(&r);
+        if e <= f32::EPSILON {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = d1.dot(&d2);
+            let denom = a * e - b * b;
+            let mut s = if denom != 0.0 {
+                ((b * f - c * e) / denom).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let mut t = (b * s + f) / e;
+            if t < 0.0 {
+                t = 0.0;
+                s = (-c / a).clamp(0.0, 1.0);
+            } else if t > 1.0 {
+                t = 1.0;
+                s = ((b - c) / a).clamp(0.0, 1.0);
+            }
+            (s, t)
+        }
+    };
+
+    (p1 + d1.scale(s), p2 + d2.scale(t))
+}
+
+impl Capsule {
+    #[inline]
+    pub fn new(begin: Vector3<f32>, end: Vector3<f32>, radius: f32) -> Self {
+        Self { begin, end, radius }
+    }
+
+    /// Returns a point on the inner segment of the capsule that is closest to the given point.
+    #[inline]
+    pub fn closest_point_on_axis(&self, point: Vector3<f32>) -> Vector3<f32> {
+        closest_point_on_segment(self.begin, self.end, point)
+    }
+
+    /// Returns distance from the surface of the capsule to the given point. Zero if the point is
+    /// inside.
+    #[inline]
+    pub fn distance_to_point(&self, point: Vector3<f32>) -> f32 {
+        ((self.closest_point_on_axis(point) - point).norm() - self.radius).max(0.0)
+    }
+
+    #[inline]
+    pub fn is_contains_point(&self, point: Vector3<f32>) -> bool {
+        (self.closest_point_on_axis(point) - point).norm_squared() <= self.radius * self.radius
+    }
+
+    #[inline]
+    pub fn is_intersects_sphere(&self, position: Vector3<f32>, radius: f32) -> bool {
+        let r = self.radius + radius;
+        (self.closest_point_on_axis(position) - position).norm_squared() <= r * r
+    }
+
+    /// Returns distance between surfaces of two capsules. Zero if the capsules intersect.
+    #[inline]
+    pub fn distance_to_capsule(&self, other: &Self) -> f32 {
+        let (a, b) = closest_points_between_segments(self.begin, self.end, other.begin, other.end);
+        ((a - b).norm() - self.radius - other.radius).max(0.0)
+    }
+
+    #[inline]
+    pub fn is_intersects_capsule(&self, other: &Self) -> bool {
+        let (a, b) = closest_points_between_segments(self.begin, self.end, other.begin, other.end);
+        let r = self.radius + other.radius;
+        (a - b).norm_squared() <= r * r
+    }
+
+    /// Returns distance between the surface of the capsule and the given oriented box. Zero if
+    /// they intersect.
+    #[inline]
+    pub fn distance_to_obb(&self, obb: &OrientedBoundingBox) -> f32 {
+        // Alternating projections between two convex sets (segment and box) converge to the
+        // closest pair of points. Few iterations are enough for the precision needed by games.
+        const ITERATIONS: usize = 8;
+
+        let mut on_segment = self.closest_point_on_axis(obb.center);
+        let mut on_box = obb.closest_point(on_segment);
+        for _ in 0..ITERATIONS {
+            let new_on_segment = self.closest_point_on_axis(on_box);
+            let new_on_box = obb.closest_point(new_on_segment);
+            let converged = (new_on_segment - on_segment).norm_squared() <= f32::EPSILON;
+            on_segment = new_on_segment;
+            on_box = new_on_box;
+            if converged {
+                break;
+            }
+        }
+
+        ((on_segment - on_box).norm() - self.radius).max(0.0)
+    }
+
+    #[inline]
+    pub fn is_intersects_obb(&self, obb: &OrientedBoundingBox) -> bool {
+        self.distance_to_obb(obb) <= 0.0
+    }
+
+    #[inline]
+    pub fn is_intersects_aabb(&self, aabb: &AxisAlignedBoundingBox) -> bool {
+        self.is_intersects_obb(&OrientedBoundingBox::from_aabb(aabb))
+    }
+
+    /// Returns signed distance from the plane to the capsule: positive if the capsule is fully in
+    /// front of the plane, negative if it is fully behind, zero if the plane crosses the capsule.
+    #[inline]
+    pub fn signed_distance_to_plane(&self, plane: &Plane) -> f32 {
+        let a = plane.dot(&self.begin);
+        let b = plane.dot(&self.end);
+        let (min, max) = if a < b { (a, b) } else { (b, a) };
+        if min > self.radius {
+            min - self.radius
+        } else if max < -self.radius {
+            max + self.radius
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns bounding box of the capsule.
+    #[inline]
+    pub fn aabb(&self) -> AxisAlignedBoundingBox {
+        let r = Vector3::repeat(self.radius);
+        AxisAlignedBoundingBox::from_min_max(
+            self.begin.inf(&self.end) - r,
+            self.begin.sup(&self.end) + r,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        aabb::AxisAlignedBoundingBox,
+        capsule::{closest_points_between_segments, Capsule},
+        obb::OrientedBoundingBox,
+        plane::Plane,
+    };
+    use nalgebra::{Matrix3, Vector3};
+
+    #[test]
+    fn test_closest_points_between_segments() {
+        let (a, b) = closest_points_between_segments(
+            Vector3::new(-1.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, -1.0),
+            Vector3::new(0.0, 1.0, 1.0),
+        );
+        assert_eq!(a, Vector3::new(0.0, 0.0, 0.0));
+        assert_eq!(b, Vector3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn test_capsule_point_and_sphere() {
+        let capsule = Capsule::default();
+        assert!(capsule.is_contains_point(Vector3::new(0.0, 0.9, 0.0)));
+        assert!(!capsule.is_contains_point(Vector3::new(0.0, 1.1, 0.0)));
+        assert_eq!(capsule.distance_to_point(Vector3::new(2.0, 0.0, 0.0)), 1.5);
+        assert!(capsule.is_intersects_sphere(Vector3::new(1.0, 0.0, 0.0), 0.6));
+        assert!(!capsule.is_intersects_sphere(Vector3::new(1.0, 0.0, 0.0), 0.4));
+    }
+
+    #[test]
+    fn test_capsule_capsule() {
+        let a = Capsule::default();
+        let b = Capsule::new(
+            Vector3::new(0.9, 0.0, -1.0),
+            Vector3::new(0.9, 0.0, 1.0),
+            0.5,
+        );
+        assert!(a.is_intersects_capsule(&b));
+        let c = Capsule::new(
+            Vector3::new(2.0, 0.0, -1.0),
+            Vector3::new(2.0, 0.0, 1.0),
+            0.5,
+        );
+        assert!(!a.is_intersects_capsule(&c));
+        assert_eq!(a.distance_to_capsule(&c), 1.0);
+    }
+
+    #[test]
+    fn test_capsule_boxes() {
+        let capsule = Capsule::default();
+        let obb = OrientedBoundingBox::new(
+            Vector3::new(1.5, 0.0, 0.0),
+            Matrix3::identity(),
+            Vector3::new(0.5, 0.5, 0.5),
+        );
+        assert!(!capsule.is_intersects_obb(&obb));
+        assert!((capsule.distance_to_obb(&obb) - 0.5).abs() < 1.0e-5);
+        assert!(capsule.is_intersects_aabb(&AxisAlignedBoundingBox::unit()));
+        assert_eq!(capsule.aabb().min, Vector3::new(-0.5, -1.0, -0.5));
+        assert_eq!(capsule.aabb().max, Vector3::new(0.5, 1.0, 0.5));
+    }
+
+    #[test]
+    fn test_capsule_signed_distance_to_plane() {
+        let capsule = Capsule::default();
+        let plane =
+            Plane::from_normal_and_point(&Vector3::y(), &Vector3::new(0.0, -2.0, 0.0)).unwrap();
+        assert_eq!(capsule.signed_distance_to_plane(&plane), 1.0);
+        let plane = Plane::from_normal_and_point(&Vector3::x(), &Vector3::default()).unwrap();
+        assert_eq!(capsule.signed_distance_to_plane(&plane), 0.0);
+    }
+}
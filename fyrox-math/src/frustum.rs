@@ -1,4 +1,6 @@
-use crate::{aabb::AxisAlignedBoundingBox, plane::Plane};
+use crate::{
+    aabb::AxisAlignedBoundingBox, capsule::Capsule, obb::OrientedBoundingBox, plane::Plane,
+};
 use nalgebra::Point3;
 use nalgebra::{Matrix4, Vector3};
 
@@ -30,9 +32,13 @@ impl Frustum {
     pub const FAR: usize = 4;
     pub const NEAR: usize = 5;
 
+    /// Distance between near and far planes of a frustum built from a projection matrix with
+    /// infinite far plane. See [`Self::from_matrix`] for more info.
+    pub const INFINITE_FAR_DISTANCE: f32 = 1.0e6;
+
     #[inline]
     pub fn from_view_projection_matrix(m: Matrix4<f32>) -> Option<Self> {
-        let planes = [
+        Some(Self::from_planes([
             // Left
             Plane::from_abcd(m[3] + m[0], m[7] + m[4], m[11] + m[8], m[15] + m[12])?,
             // Right
@@ -45,8 +51,46 @@ impl Frustum {
             Plane::from_abcd(m[3] - m[2], m[7] - m[6], m[11] - m[10], m[15] - m[14])?,
             // Near
             Plane::from_abcd(m[3] + m[2], m[7] + m[6], m[11] + m[10], m[15] + m[14])?,
-        ];
+        ]))
+    }
+
+    /// Same as [`Self::from_view_projection_matrix`], but also handles projection matrices with
+    /// infinite far plane (for example, the ones that are used with reversed depth). Such matrices
+    /// have degenerate far plane equation, so the far plane is replaced with a plane that is
+    /// parallel to the near plane and placed at [`Self::INFINITE_FAR_DISTANCE`] from it. This keeps
+    /// the corners of the frustum finite, so they can be safely used for bounds calculation.
+    #[inline]
+    pub fn from_matrix(m: Matrix4<f32>) -> Option<Self> {
+        // Relative threshold, since far plane equation is a difference of two rows that are
+        // almost equal for infinite projection and thus contains some numerical noise. It must be
+        // small enough to not catch finite projections with large far/near ratio.
+        const DEGENERATE_THRESHOLD: f32 = 1.0e-6;
+
+        let near_normal = Vector3::new(m[3] + m[2], m[7] + m[6], m[11] + m[10]);
+        let near = Plane::from_abcd(near_normal.x, near_normal.y, near_normal.z, m[15] + m[14])?;
+        let far_normal = Vector3::new(m[3] - m[2], m[7] - m[6], m[11] - m[10]);
+        let far = if far_normal.norm() <= DEGENERATE_THRESHOLD * near_normal.norm() {
+            Plane {
+                normal: -near.normal,
+                d: Self::INFINITE_FAR_DISTANCE - near.d,
+            }
+        } else {
+            Plane::from_abcd(far_normal.x, far_normal.y, far_normal.z, m[15] - m[14])?
+        };
+        Some(Self::from_planes([
+            Plane::from_abcd(m[3] + m[0], m[7] + m[4], m[11] + m[8], m[15] + m[12])?,
+            Plane::from_abcd(m[3] - m[0], m[7] - m[4], m[11] - m[8], m[15] - m[12])?,
+            Plane::from_abcd(m[3] - m[1], m[7] - m[5], m[11] - m[9], m[15] - m[13])?,
+            Plane::from_abcd(m[3] + m[1], m[7] + m[5], m[11] + m[9], m[15] + m[13])?,
+            far,
+            near,
+        ]))
+    }
 
+    /// Creates frustum from a set of planes in the following order: left, right, top, bottom, far,
+    /// near. Normals of the planes must point inside the frustum.
+    #[inline]
+    pub fn from_planes(planes: [Plane; 6]) -> Self {
         let corners = [
             planes[Self::LEFT].intersection_point(&planes[Self::TOP], &planes[Self::FAR]),
             planes[Self::LEFT].intersection_point(&planes[Self::BOTTOM], &planes[Self::FAR]),
@@ -58,7 +102,7 @@ impl Frustum {
             planes[Self::RIGHT].intersection_point(&planes[Self::TOP], &planes[Self::NEAR]),
         ];
 
-        Some(Self { planes, corners })
+        Self { planes, corners }
     }
 
     #[inline]
@@ -312,6 +356,37 @@ impl Frustum {
         true
     }
 
+    /// Checks whether the frustum intersects the given oriented bounding box. The test is
+    /// conservative - it may report intersection for boxes that are near frustum corners, but never
+    /// rejects visible boxes.
+    #[inline]
+    pub fn is_intersects_obb(&self, obb: &OrientedBoundingBox) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| obb.signed_distance_to_plane(plane) >= 0.0)
+    }
+
+    /// Checks whether the frustum intersects the given capsule. The test is conservative - it may
+    /// report intersection for capsules that are near frustum corners, but never rejects visible
+    /// capsules.
+    #[inline]
+    pub fn is_intersects_capsule(&self, capsule: &Capsule) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| capsule.signed_distance_to_plane(plane) >= 0.0)
+    }
+
+    /// Returns approximate distance from the frustum to the given point: the largest distance to
+    /// the planes the point is behind of. Zero if the point is inside the frustum. The result is
+    /// exact for points that are "behind" a single plane and underestimates the distance near the
+    /// edges and corners of the frustum.
+    #[inline]
+    pub fn distance_to_point(&self, point: Vector3<f32>) -> f32 {
+        self.planes
+            .iter()
+            .fold(0.0f32, |distance, plane| distance.max(-plane.dot(&point)))
+    }
+
     #[inline]
     pub fn is_intersects_sphere(&self, p: Vector3<f32>, r: f32) -> bool {
         for plane in self.planes.iter() {
@@ -330,8 +405,9 @@ impl Frustum {
 #[cfg(test)]
 mod test {
     use crate::aabb::AxisAlignedBoundingBox;
+    use crate::{capsule::Capsule, obb::OrientedBoundingBox};
     use crate::{frustum::Frustum, plane::Plane};
-    use nalgebra::{Matrix4, Vector3};
+    use nalgebra::{Matrix3, Matrix4, Vector3};
 
     #[test]
     fn test_default_for_frustum() {
@@ -530,4 +606,79 @@ mod test {
         assert!(f.is_intersects_sphere(Vector3::new(0.0, 0.0, 0.0), 2.0));
         assert!(!f.is_intersects_sphere(Vector3::new(10.0, 10.0, 10.0), 1.0));
     }
+
+    fn infinite_perspective(aspect: f32, fov: f32, z_near: f32) -> Matrix4<f32> {
+        let f = 1.0 / (fov * 0.5).tan();
+        Matrix4::new(
+            f / aspect,
+            0.0,
+            0.0,
+            0.0, //
+            0.0,
+            f,
+            0.0,
+            0.0, //
+            0.0,
+            0.0,
+            -1.0,
+            -2.0 * z_near, //
+            0.0,
+            0.0,
+            -1.0,
+            0.0,
+        )
+    }
+
+    #[test]
+    fn test_frustum_from_matrix() {
+        let finite = Matrix4::new_perspective(1.0, std::f32::consts::FRAC_PI_2, 0.01, 1024.0);
+        assert_eq!(
+            Frustum::from_matrix(finite),
+            Frustum::from_view_projection_matrix(finite)
+        );
+
+        let infinite = infinite_perspective(1.0, std::f32::consts::FRAC_PI_2, 0.1);
+        assert!(Frustum::from_view_projection_matrix(infinite).is_none());
+
+        let f = Frustum::from_matrix(infinite).unwrap();
+        assert!(f.corners().iter().all(|c| c.iter().all(|v| v.is_finite())));
+        assert!(f.is_contains_point(Vector3::new(0.0, 0.0, -10000.0)));
+        assert!(!f.is_contains_point(Vector3::new(0.0, 0.0, 1.0)));
+        // The first four corners lie on the far plane.
+        assert!(f.corners()[..4]
+            .iter()
+            .all(|c| (c.z + Frustum::INFINITE_FAR_DISTANCE).abs() < 1.0));
+    }
+
+    #[test]
+    fn test_frustum_is_intersects_obb_and_capsule() {
+        let f = Frustum::from_view_projection_matrix(Matrix4::identity()).unwrap();
+
+        assert!(f.is_intersects_obb(&OrientedBoundingBox::default()));
+        assert!(!f.is_intersects_obb(&OrientedBoundingBox::new(
+            Vector3::new(5.0, 0.0, 0.0),
+            Matrix3::identity(),
+            Vector3::new(1.0, 1.0, 1.0)
+        )));
+
+        assert!(f.is_intersects_capsule(&Capsule::default()));
+        assert!(f.is_intersects_capsule(&Capsule::new(
+            Vector3::new(-5.0, 0.0, 0.0),
+            Vector3::new(5.0, 0.0, 0.0),
+            0.1
+        )));
+        assert!(!f.is_intersects_capsule(&Capsule::new(
+            Vector3::new(3.0, -5.0, 0.0),
+            Vector3::new(3.0, 5.0, 0.0),
+            0.5
+        )));
+    }
+
+    #[test]
+    fn test_frustum_distance_to_point() {
+        let f = Frustum::from_view_projection_matrix(Matrix4::identity()).unwrap();
+
+        assert_eq!(f.distance_to_point(Vector3::new(0.0, 0.0, 0.0)), 0.0);
+        assert_eq!(f.distance_to_point(Vector3::new(3.0, 0.0, 0.0)), 2.0);
+    }
 }
@@ -2,8 +2,10 @@
 #![allow(clippy::many_single_char_names)]
 
 pub mod aabb;
+pub mod capsule;
 pub mod curve;
 pub mod frustum;
+pub mod obb;
 pub mod octree;
 pub mod plane;
 pub mod ray;
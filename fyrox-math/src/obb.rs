@@ -0,0 +1,308 @@
+use crate::{aabb::AxisAlignedBoundingBox, plane::Plane, Matrix4Ext};
+use nalgebra::{Matrix3, Matrix4, Vector3};
+
+/// Oriented bounding box (OBB) - a box with arbitrary orientation in space. It is defined by its
+/// center, three orthonormal axes and half-extents along each axis. OBBs fit rotated objects much
+/// tighter than axis-aligned bounding boxes at the cost of slightly more expensive intersection
+/// tests.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OrientedBoundingBox {
+    /// Center of the box in world space.
+    pub center: Vector3<f32>,
+    /// Orthonormal axes of the box stored as columns of the matrix.
+    pub axes: Matrix3<f32>,
+    /// Half-sizes of the box along each of its axes.
+    pub half_extents: Vector3<f32>,
+}
+
+impl Default for OrientedBoundingBox {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            center: Vector3::default(),
+            axes: Matrix3::identity(),
+            half_extents: Vector3::new(0.5, 0.5, 0.5),
+        }
+    }
+}
+
+impl OrientedBoundingBox {
+    /// Creates new oriented bounding box. Axes must be orthonormal.
+    #[inline]
+    pub fn new(center: Vector3<f32>, axes: Matrix3<f32>, half_extents: Vector3<f32>) -> Self {
+        Self {
+            center,
+            axes,
+            half_extents,
+        }
+    }
+
+    /// Creates oriented bounding box from an axis-aligned bounding box.
+    #[inline]
+    pub fn from_aabb(aabb: &AxisAlignedBoundingBox) -> Self {
+        Self {
+            center: aabb.center(),
+            axes: Matrix3::identity(),
+            half_extents: aabb.half_extents(),
+        }
+    }
+
+    /// Transforms the given axis-aligned bounding box by an affine transformation matrix. Unlike
+    /// [`AxisAlignedBoundingBox::transform`], the result is exact: rotation is kept in the axes
+    /// of the box and scaling is moved into its half-extents.
+    #[inline]
+    pub fn from_aabb_and_transform(aabb: &AxisAlignedBoundingBox, m: &Matrix4<f32>) -> Self {
+        let basis = m.basis();
+        let center = m.transform_point(&aabb.center().into()).coords;
+        let local_half_extents = aabb.half_extents();
+
+        let mut axes = Matrix3::identity();
+        let mut half_extents = Vector3::default();
+        for i in 0..3 {
+            let column = basis.column(i).into_owned();
+            let scale = column.norm();
+            if scale > f32::EPSILON {
+                axes.set_column(i, &column.scale(1.0 / scale));
+            }
+            half_extents[i] = local_half_extents[i] * scale;
+        }
+
+        Self {
+            center,
+            axes,
+            half_extents,
+        }
+    }
+
+    /// Returns an axis of the box with the given index (0 - X, 1 - Y, 2 - Z).
+    #[inline]
+    pub fn axis(&self, i: usize) -> Vector3<f32> {
+        self.axes.column(i).into_owned()
+    }
+
+    /// Returns 8 corners of the box, order matches [`AxisAlignedBoundingBox::corners`].
+    #[inline]
+    pub fn corners(&self) -> [Vector3<f32>; 8] {
+        let x = self.axis(0).scale(self.half_extents.x);
+        let y = self.axis(1).scale(self.half_extents.y);
+        let z = self.axis(2).scale(self.half_extents.z);
+        let c = self.center;
+        [
+            c - x - y - z,
+            c - x - y + z,
+            c + x - y + z,
+            c + x - y - z,
+            c - x + y - z,
+            c - x + y + z,
+            c + x + y + z,
+            c + x + y - z,
+        ]
+    }
+
+    /// Calculates the smallest axis-aligned bounding box that encloses this box.
+    #[inline]
+    pub fn to_aabb(&self) -> AxisAlignedBoundingBox {
+        let extents = self.projected_extents();
+        AxisAlignedBoundingBox::from_min_max(self.center - extents, self.center + extents)
+    }
+
+    /// Returns half-extents of the box projected on world axes.
+    #[inline]
+    fn projected_extents(&self) -> Vector3<f32> {
+        self.axes.abs() * self.half_extents
+    }
+
+    /// Returns radius of the box projected on the given direction.
+    #[inline]
+    pub fn projected_radius(&self, direction: &Vector3<f32>) -> f32 {
+        (0..3)
+            .map(|i| self.half_extents[i] * self.axis(i).dot(direction).abs())
+            .sum()
+    }
+
+    /// Transforms a point from world space to the local space of the box.
+    #[inline]
+    fn to_local(self, point: Vector3<f32>) -> Vector3<f32> {
+        self.axes.tr_mul(&(point - self.center))
+    }
+
+    #[inline]
+    pub fn is_contains_point(&self, point: Vector3<f32>) -> bool {
+        let local = self.to_local(point);
+        local.x.abs() <= self.half_extents.x
+            && local.y.abs() <= self.half_extents.y
+            && local.z.abs() <= self.half_extents.z
+    }
+
+    /// Returns a point on (or in) the box that is closest to the given point.
+    #[inline]
+    pub fn closest_point(&self, point: Vector3<f32>) -> Vector3<f32> {
+        let local = self.to_local(point);
+        let clamped = Vector3::new(
+            local.x.clamp(-self.half_extents.x, self.half_extents.x),
+            local.y.clamp(-self.half_extents.y, self.half_extents.y),
+            local.z.clamp(-self.half_extents.z, self.half_extents.z),
+        );
+        self.center + self.axes * clamped
+    }
+
+    /// Returns squared distance from the box to the given point. Zero if the point is inside.
+    #[inline]
+    pub fn sqr_distance_to_point(&self, point: Vector3<f32>) -> f32 {
+        (self.closest_point(point) - point).norm_squared()
+    }
+
+    /// Returns distance from the box to the given point. Zero if the point is inside.
+    #[inline]
+    pub fn distance_to_point(&self, point: Vector3<f32>) -> f32 {
+        self.sqr_distance_to_point(point).sqrt()
+    }
+
+    #[inline]
+    pub fn is_intersects_sphere(&self, position: Vector3<f32>, radius: f32) -> bool {
+        self.sqr_distance_to_point(position) <= radius * radius
+    }
+
+    /// Returns signed distance from the plane to the box: positive if the box is fully in front
+    /// of the plane, negative if it is fully behind, zero if the plane crosses the box.
+    #[inline]
+    pub fn signed_distance_to_plane(&self, plane: &Plane) -> f32 {
+        let d = plane.dot(&self.center);
+        let r = self.projected_radius(&plane.normal);
+        if d > r {
+            d - r
+        } else if d < -r {
+            d + r
+        } else {
+            0.0
+        }
+    }
+
+    /// Checks whether this box intersects another one using the separating axis theorem.
+    ///
+    /// # References
+    ///
+    /// "Real-Time Collision Detection" by Christer Ericson, 4.4.1 OBB-OBB Intersection
+    #[inline]
+    pub fn is_intersects_obb(&self, other: &Self) -> bool {
+        const EPSILON: f32 = 1.0e-6;
+
+        let a = self.half_extents;
+        let b = other.half_extents;
+
+        // Rotation matrix expressing other box in this box's coordinate frame.
+        let r = self.axes.tr_mul(&other.axes);
+        let abs_r = r.abs().add_scalar(EPSILON);
+
+        // Translation in this box's coordinate frame.
+        let t = self.to_local(other.center);
+
+        // Axes of this box.
+        for i in 0..3 {
+            let ra = a[i];
+            let rb = b[0] * abs_r[(i, 0)] + b[1] * abs_r[(i, 1)] + b[2] * abs_r[(i, 2)];
+            if t[i].abs() > ra + rb {
+                return false;
+            }
+        }
+
+        // Axes of other box.
+        for j in 0..3 {
+            let ra = a[0] * abs_r[(0, j)] + a[1] * abs_r[(1, j)] + a[2] * abs_r[(2, j)];
+            let rb = b[j];
+            if (t[0] * r[(0, j)] + t[1] * r[(1, j)] + t[2] * r[(2, j)]).abs() > ra + rb {
+                return false;
+            }
+        }
+
+        // Cross products of the axes.
+        for i in 0..3 {
+            let i1 = (i + 1) % 3;
+            let i2 = (i + 2) % 3;
+            for j in 0..3 {
+                let j1 = (j + 1) % 3;
+                let j2 = (j + 2) % 3;
+                let ra = a[i1] * abs_r[(i2, j)] + a[i2] * abs_r[(i1, j)];
+                let rb = b[j1] * abs_r[(i, j2)] + b[j2] * abs_r[(i, j1)];
+                if (t[i2] * r[(i1, j)] - t[i1] * r[(i2, j)]).abs() > ra + rb {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    #[inline]
+    pub fn is_intersects_aabb(&self, aabb: &AxisAlignedBoundingBox) -> bool {
+        self.is_intersects_obb(&Self::from_aabb(aabb))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{aabb::AxisAlignedBoundingBox, obb::OrientedBoundingBox, plane::Plane};
+    use nalgebra::{Matrix4, UnitQuaternion, Vector3};
+
+    fn rotated_unit_box(position: Vector3<f32>) -> OrientedBoundingBox {
+        let transform = Matrix4::new_translation(&position)
+            * UnitQuaternion::from_axis_angle(&Vector3::y_axis(), std::f32::consts::FRAC_PI_4)
+                .to_homogeneous();
+        OrientedBoundingBox::from_aabb_and_transform(&AxisAlignedBoundingBox::unit(), &transform)
+    }
+
+    #[test]
+    fn test_obb_from_aabb_and_transform() {
+        let obb = OrientedBoundingBox::from_aabb_and_transform(
+            &AxisAlignedBoundingBox::unit(),
+            &Matrix4::new_nonuniform_scaling(&Vector3::new(2.0, 4.0, 6.0)),
+        );
+        assert_eq!(obb.center, Vector3::default());
+        assert_eq!(obb.half_extents, Vector3::new(1.0, 2.0, 3.0));
+
+        let aabb = obb.to_aabb();
+        assert_eq!(aabb.min, Vector3::new(-1.0, -2.0, -3.0));
+        assert_eq!(aabb.max, Vector3::new(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn test_obb_closest_point_and_distance() {
+        let obb = OrientedBoundingBox::default();
+        assert!(obb.is_contains_point(Vector3::new(0.25, 0.25, 0.25)));
+        assert!(!obb.is_contains_point(Vector3::new(1.0, 0.0, 0.0)));
+        assert_eq!(
+            obb.closest_point(Vector3::new(2.0, 0.0, 0.0)),
+            Vector3::new(0.5, 0.0, 0.0)
+        );
+        assert_eq!(obb.distance_to_point(Vector3::new(2.0, 0.0, 0.0)), 1.5);
+        assert_eq!(obb.distance_to_point(Vector3::new(0.1, 0.0, 0.0)), 0.0);
+
+        let rotated = rotated_unit_box(Vector3::default());
+        // Corner of the rotated box points along X axis.
+        let d = rotated.distance_to_point(Vector3::new(1.0, 0.0, 0.0));
+        assert!((d - (1.0 - 0.5 * std::f32::consts::SQRT_2)).abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_obb_is_intersects_obb() {
+        let a = OrientedBoundingBox::default();
+        let b = rotated_unit_box(Vector3::new(1.15, 0.0, 0.0));
+        assert!(a.is_intersects_obb(&b));
+        let c = rotated_unit_box(Vector3::new(1.25, 0.0, 0.0));
+        assert!(!a.is_intersects_obb(&c));
+        assert!(!a.is_intersects_aabb(&AxisAlignedBoundingBox::from_min_max(
+            Vector3::new(2.0, 2.0, 2.0),
+            Vector3::new(3.0, 3.0, 3.0)
+        )));
+    }
+
+    #[test]
+    fn test_obb_signed_distance_to_plane() {
+        let obb = OrientedBoundingBox::default();
+        let plane =
+            Plane::from_normal_and_point(&Vector3::y(), &Vector3::new(0.0, -2.0, 0.0)).unwrap();
+        assert_eq!(obb.signed_distance_to_plane(&plane), 1.5);
+        let plane = Plane::from_normal_and_point(&Vector3::y(), &Vector3::default()).unwrap();
+        assert_eq!(obb.signed_distance_to_plane(&plane), 0.0);
+    }
+}
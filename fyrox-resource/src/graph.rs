@@ -1,7 +1,23 @@
 //! Resource dependency graph. See [`ResourceDependencyGraph`] docs for more info.
 
 use crate::{collect_used_resources, state::ResourceState, untyped::UntypedResource};
-use fxhash::FxHashSet;
+use fxhash::{FxHashMap, FxHashSet};
+use std::path::{Path, PathBuf};
+
+/// Collects direct (non-recursive) dependencies of the given resource. Returns an empty set if the
+/// resource is not loaded.
+pub fn collect_direct_dependencies(resource: &UntypedResource) -> FxHashSet<UntypedResource> {
+    let mut dependent_resources = FxHashSet::default();
+
+    let header = resource.0.lock();
+    if let ResourceState::Ok(ref resource_data) = header.state {
+        (**resource_data).as_reflect(&mut |entity| {
+            collect_used_resources(entity, &mut dependent_resources);
+        });
+    }
+
+    dependent_resources
+}
 
 /// A node of [`ResourceDependencyGraph`].
 pub struct ResourceGraphNode {
@@ -15,23 +31,10 @@ impl ResourceGraphNode {
     /// Creates a new resource graph node for a given untyped resource. This method is recursive -
     /// it will initialize the entire sub-graph of dependencies automatically.
     pub fn new(resource: &UntypedResource) -> Self {
-        let mut children = Vec::new();
-
-        // Look for dependent resources.
-        let mut dependent_resources = FxHashSet::default();
-
-        let header = resource.0.lock();
-        if let ResourceState::Ok(ref resource_data) = header.state {
-            (**resource_data).as_reflect(&mut |entity| {
-                collect_used_resources(entity, &mut dependent_resources);
-            });
-        }
-
-        children.extend(
-            dependent_resources
-                .into_iter()
-                .map(|r| ResourceGraphNode::new(&r)),
-        );
+        let children = collect_direct_dependencies(resource)
+            .into_iter()
+            .map(|r| ResourceGraphNode::new(&r))
+            .collect();
 
         Self {
             resource: resource.clone(),
@@ -93,6 +96,147 @@ impl ResourceDependencyGraph {
         out
     }
 }
+
+/// Flat graph of references between resources (who references what). Unlike [`ResourceDependencyGraph`],
+/// which is a tree built for a single resource, this graph stores direct references of many resources
+/// in both directions, so it could be used to query reverse dependencies (which resources use a given
+/// resource) and to find unreferenced assets.
+///
+/// The graph operates on resource paths, not on resources themselves, so it does not prevent the
+/// resources from being unloaded. Embedded resources are ignored. The resource manager maintains an
+/// instance of this graph, see [`crate::manager::ResourceManagerState::reference_graph`].
+#[derive(Default, Clone, Debug)]
+pub struct ResourceReferenceGraph {
+    dependencies: FxHashMap<PathBuf, FxHashSet<PathBuf>>,
+    dependents: FxHashMap<PathBuf, FxHashSet<PathBuf>>,
+}
+
+impl ResourceReferenceGraph {
+    /// Scans the given resource for references and puts them in the graph, replacing any previous
+    /// references of the resource. Returns `false` if the resource is embedded or is not loaded.
+    pub fn add_resource(&mut self, resource: &UntypedResource) -> bool {
+        let Some(path) = resource.kind().into_path() else {
+            return false;
+        };
+
+        if !matches!(resource.0.lock().state, ResourceState::Ok(_)) {
+            return false;
+        }
+
+        let dependencies = collect_direct_dependencies(resource)
+            .into_iter()
+            .filter_map(|dependency| dependency.kind().into_path())
+            .collect();
+
+        self.set_dependencies(path, dependencies);
+
+        true
+    }
+
+    /// Sets direct dependencies of a resource at the given path, replacing previous ones.
+    pub fn set_dependencies(&mut self, path: PathBuf, dependencies: FxHashSet<PathBuf>) {
+        self.remove_dependencies(&path);
+
+        for dependency in dependencies.iter() {
+            self.dependents
+                .entry(dependency.clone())
+                .or_default()
+                .insert(path.clone());
+        }
+
+        self.dependencies.insert(path, dependencies);
+    }
+
+    /// Removes all outgoing references of a resource at the given path. References to the resource
+    /// from other resources are kept.
+    pub fn remove_dependencies(&mut self, path: &Path) {
+        if let Some(old_dependencies) = self.dependencies.remove(path) {
+            for dependency in old_dependencies {
+                if let Some(dependents) = self.dependents.get_mut(&dependency) {
+                    dependents.remove(path);
+                    if dependents.is_empty() {
+                        self.dependents.remove(&dependency);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if the references of a resource at the given path were scanned.
+    pub fn is_scanned(&self, path: &Path) -> bool {
+        self.dependencies.contains_key(path)
+    }
+
+    /// Returns an iterator over paths of every scanned resource.
+    pub fn scanned(&self) -> impl Iterator<Item = &Path> {
+        self.dependencies.keys().map(|p| p.as_path())
+    }
+
+    /// Returns an iterator over direct dependencies of a resource at the given path.
+    pub fn dependencies_of(&self, path: &Path) -> impl Iterator<Item = &Path> {
+        self.dependencies
+            .get(path)
+            .into_iter()
+            .flatten()
+            .map(|p| p.as_path())
+    }
+
+    /// Returns an iterator over resources that directly reference a resource at the given path
+    /// (reverse dependencies).
+    pub fn dependents_of(&self, path: &Path) -> impl Iterator<Item = &Path> {
+        self.dependents
+            .get(path)
+            .into_iter()
+            .flatten()
+            .map(|p| p.as_path())
+    }
+
+    /// Returns `true` if a resource at the given path is referenced by at least one other scanned
+    /// resource.
+    pub fn is_referenced(&self, path: &Path) -> bool {
+        self.dependents
+            .get(path)
+            .is_some_and(|dependents| !dependents.is_empty())
+    }
+
+    /// Collects paths of all resources that are reachable (directly or indirectly) from the given
+    /// roots. The roots are included in the result.
+    pub fn collect_reachable<'a, I>(&self, roots: I) -> FxHashSet<PathBuf>
+    where
+        I: IntoIterator<Item = &'a Path>,
+    {
+        let mut reachable = FxHashSet::default();
+        let mut stack = roots
+            .into_iter()
+            .map(|p| p.to_path_buf())
+            .collect::<Vec<_>>();
+        while let Some(path) = stack.pop() {
+            if reachable.contains(&path) {
+                continue;
+            }
+            stack.extend(self.dependencies_of(&path).map(|p| p.to_path_buf()));
+            reachable.insert(path);
+        }
+        reachable
+    }
+
+    /// Returns every path from `candidates` that is not reachable from the given roots. It could be
+    /// used to find unused assets: `candidates` is a list of every asset in a project and `roots` is
+    /// a list of scenes used by a game.
+    pub fn find_unreferenced<'a, C, R>(&self, candidates: C, roots: R) -> Vec<PathBuf>
+    where
+        C: IntoIterator<Item = &'a Path>,
+        R: IntoIterator<Item = &'a Path>,
+    {
+        let reachable = self.collect_reachable(roots);
+        candidates
+            .into_iter()
+            .filter(|p| !reachable.contains(*p))
+            .map(|p| p.to_path_buf())
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::path::PathBuf;
@@ -183,4 +327,53 @@ mod test {
         graph.for_each(&mut |r: &UntypedResource| uuids.push(r.type_uuid()));
         assert_eq!(uuids, [Uuid::default(), Uuid::default()]);
     }
+
+    fn set(graph: &mut ResourceReferenceGraph, path: &str, dependencies: &[&str]) {
+        graph.set_dependencies(
+            PathBuf::from(path),
+            dependencies.iter().map(PathBuf::from).collect(),
+        );
+    }
+
+    #[test]
+    fn resource_reference_graph() {
+        let mut graph = ResourceReferenceGraph::default();
+        set(&mut graph, "level.rgs", &["tree.rgs", "grass.png"]);
+        set(&mut graph, "tree.rgs", &["bark.png", "grass.png"]);
+        set(&mut graph, "unused.rgs", &["old.png"]);
+
+        let mut dependents = graph
+            .dependents_of(Path::new("grass.png"))
+            .collect::<Vec<_>>();
+        dependents.sort();
+        assert_eq!(dependents, [Path::new("level.rgs"), Path::new("tree.rgs")]);
+        assert!(graph.is_referenced(Path::new("bark.png")));
+        assert!(!graph.is_referenced(Path::new("level.rgs")));
+
+        let reachable = graph.collect_reachable([Path::new("level.rgs")]);
+        assert_eq!(reachable.len(), 4);
+
+        let mut unreferenced = graph.find_unreferenced(
+            [
+                Path::new("level.rgs"),
+                Path::new("tree.rgs"),
+                Path::new("bark.png"),
+                Path::new("old.png"),
+                Path::new("unused.rgs"),
+            ],
+            [Path::new("level.rgs")],
+        );
+        unreferenced.sort();
+        assert_eq!(
+            unreferenced,
+            [PathBuf::from("old.png"), PathBuf::from("unused.rgs")]
+        );
+
+        // Replacing dependencies must remove stale reverse references.
+        set(&mut graph, "tree.rgs", &["bark.png"]);
+        assert_eq!(graph.dependents_of(Path::new("grass.png")).count(), 1);
+        graph.remove_dependencies(Path::new("tree.rgs"));
+        assert!(!graph.is_referenced(Path::new("bark.png")));
+        assert!(!graph.is_scanned(Path::new("tree.rgs")));
+    }
 }
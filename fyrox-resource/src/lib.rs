@@ -34,6 +34,7 @@ pub use fyrox_core as core;
 use fyrox_core::combine_uuids;
 
 pub mod constructor;
+pub mod curve;
 pub mod entry;
 pub mod event;
 pub mod graph;
@@ -54,6 +55,18 @@ pub const SOUND_BUFFER_RESOURCE_UUID: Uuid = uuid!("f6a077b7-c8ff-4473-a95b-0289
 pub const SHADER_RESOURCE_UUID: Uuid = uuid!("f1346417-b726-492a-b80f-c02096c6c019");
 /// Type UUID of curve resource. It is defined here to load old versions of resources.
 pub const CURVE_RESOURCE_UUID: Uuid = uuid!("f28b949f-28a2-4b68-9089-59c234f58b6b");
+/// Type UUID of color gradient resource. It is defined here to load old versions of resources.
+pub const COLOR_GRADIENT_RESOURCE_UUID: Uuid = uuid!("6a3b0c1e-52d4-4b8e-9f27-c0e5d8a1b4f3");
+/// Type UUID of timeline resource. It is defined here to load old versions of resources.
+pub const TIMELINE_RESOURCE_UUID: Uuid = uuid!("b7d2e4a9-1c63-4f08-8e5b-3a9c6f17d240");
+/// Type UUID of dialogue resource. It is defined here to load old versions of resources.
+pub const DIALOGUE_RESOURCE_UUID: Uuid = uuid!("3c8f1a6e-d274-4b95-a0e3-7f5b2c9d1e48");
+/// Type UUID of item resource. It is defined here to load old versions of resources.
+pub const ITEM_RESOURCE_UUID: Uuid = uuid!("a61e9f3b-82c4-4d07-b5f8-4c2d7e0a9b31");
+/// Type UUID of SVG image resource. It is defined here to load old versions of resources.
+pub const SVG_IMAGE_RESOURCE_UUID: Uuid = uuid!("5e0b7c2d-94a1-4f6e-8d3b-c1a7f5e29064");
+/// Type UUID of material graph resource. It is defined here to load old versions of resources.
+pub const MATERIAL_GRAPH_RESOURCE_UUID: Uuid = uuid!("c82d5f14-6b3e-4a97-9e01-f4b7a2d863c5");
 
 /// A trait for resource data.
 pub trait ResourceData: 'static + Debug + Visit + Send + Reflect {
@@ -9,7 +9,9 @@ use crate::{
         futures::future::join_all,
         io::FileLoadError,
         log::Log,
-        make_relative_path, notify,
+        make_relative_path,
+        memory::{self, MemoryTag},
+        notify,
         parking_lot::{Mutex, MutexGuard},
         task::TaskPool,
         watcher::FileSystemWatcher,
@@ -17,6 +19,7 @@ use crate::{
     },
     entry::{TimedEntry, DEFAULT_RESOURCE_LIFETIME},
     event::{ResourceEvent, ResourceEventBroadcaster},
+    graph::ResourceReferenceGraph,
     io::{FsResourceIo, ResourceIo},
     loader::{ResourceLoader, ResourceLoadersContainer},
     options::OPTIONS_EXTENSION,
@@ -69,6 +72,7 @@ pub struct ResourceManagerState {
     resources: Vec<TimedEntry<UntypedResource>>,
     task_pool: Arc<TaskPool>,
     watcher: Option<FileSystemWatcher>,
+    reference_graph: ResourceReferenceGraph,
 }
 
 /// See module docs.
@@ -368,6 +372,7 @@ impl ResourceManagerState {
             event_broadcaster: Default::default(),
             constructors_container: Default::default(),
             watcher: None,
+            reference_graph: Default::default(),
             built_in_resources: Default::default(),
             // Use the file system resource io by default
             resource_io: Arc::new(FsResourceIo),
@@ -512,6 +517,37 @@ impl ResourceManagerState {
         self.resources.iter().map(|entry| &entry.value)
     }
 
+    /// Returns the graph of references between resources. The graph is updated lazily - every loaded
+    /// resource that was not scanned yet (or was reloaded since the last scan) is scanned on this call.
+    /// The graph keeps references of unloaded resources, but it knows nothing about resources that
+    /// were never loaded. Use [`Self::scan_references`] to add such resources to the graph.
+    pub fn reference_graph(&mut self) -> &ResourceReferenceGraph {
+        for entry in self.resources.iter() {
+            if let Some(path) = entry.value.kind().into_path() {
+                if !self.reference_graph.is_scanned(&path) {
+                    self.reference_graph.add_resource(&entry.value);
+                }
+            }
+        }
+
+        &self.reference_graph
+    }
+
+    /// Scans the given resource for references and puts them in the reference graph. The resource
+    /// must be loaded, otherwise the method has no effect and returns `false`.
+    pub fn scan_references(&mut self, resource: &UntypedResource) -> bool {
+        self.reference_graph.add_resource(resource)
+    }
+
+    /// Returns a list of paths of every resource that directly references a resource at the given
+    /// path. See [`Self::reference_graph`] for more info about limitations.
+    pub fn find_dependents(&mut self, path: &Path) -> Vec<PathBuf> {
+        self.reference_graph()
+            .dependents_of(path)
+            .map(|p| p.to_path_buf())
+            .collect()
+    }
+
     /// Immediately destroys all resources in the manager that are not used anywhere else.
     pub fn destroy_unused_resources(&mut self) {
         self.resources
@@ -589,8 +625,13 @@ impl ResourceManagerState {
         loader: &dyn ResourceLoader,
         reload: bool,
     ) {
+        fyrox_core::profile_span!("ResourceManager::spawn_loading_task");
+
         let event_broadcaster = self.event_broadcaster.clone();
-        let loader_future = loader.load(path.clone(), self.resource_io.clone());
+        let loader_future = memory::tagged(
+            MemoryTag::Resources,
+            loader.load(path.clone(), self.resource_io.clone()),
+        );
         self.task_pool.spawn_task(async move {
             match loader_future.await {
                 Ok(data) => {
@@ -603,6 +644,7 @@ impl ResourceManagerState {
 
                     // Separate scope to keep mutex locking time at minimum.
                     {
+                        fyrox_core::profile_span!("ResourceManager::commit_loaded_resource");
                         let mut mutex_guard = resource.0.lock();
                         assert_eq!(mutex_guard.type_uuid, data.type_uuid());
                         assert!(mutex_guard.kind.is_external());
@@ -630,6 +672,11 @@ impl ResourceManagerState {
 
         if !header.state.is_loading() {
             if let Some(path) = header.kind.path_owned() {
+                if self.find_loader(&path).is_some() {
+                    // References will be re-scanned when the resource is loaded again.
+                    self.reference_graph.remove_dependencies(&path);
+                }
+
                 if let Some(loader) = self.find_loader(&path) {
                     header.state.switch_to_pending_state();
                     drop(header);
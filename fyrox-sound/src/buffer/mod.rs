@@ -99,6 +99,13 @@ pub trait RawStreamingDataSource: Iterator<Item = f32> + Send + Sync + Debug {
     fn channel_duration_in_samples(&self) -> usize {
         0
     }
+
+    /// Returns amount of samples (per channel), that will be read from the source at once. Smaller
+    /// blocks reduce latency of real-time sources (voice chat, for example), larger blocks reduce
+    /// overhead. The default value is [`streaming::StreamingBuffer::STREAM_SAMPLE_COUNT`].
+    fn block_size(&self) -> usize {
+        streaming::StreamingBuffer::STREAM_SAMPLE_COUNT
+    }
 }
 
 impl DataSource {
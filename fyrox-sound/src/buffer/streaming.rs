@@ -96,6 +96,14 @@ impl StreamingSource {
         }
     }
 
+    #[inline]
+    fn block_size(&self) -> usize {
+        match self {
+            StreamingSource::Raw(raw) => raw.block_size().max(1),
+            _ => StreamingBuffer::STREAM_SAMPLE_COUNT,
+        }
+    }
+
     fn channel_duration_in_samples(&self) -> usize {
         match self {
             StreamingSource::Null => 0,
@@ -123,7 +131,7 @@ impl StreamingSource {
     #[inline]
     fn read_next_samples_block_into(&mut self, buffer: &mut Vec<f32>) -> usize {
         buffer.clear();
-        let count = StreamingBuffer::STREAM_SAMPLE_COUNT * self.channel_count();
+        let count = self.block_size() * self.channel_count();
         match self {
             StreamingSource::Decoder(decoder) => {
                 for _ in 0..count {
@@ -151,12 +159,12 @@ impl StreamingSource {
 }
 
 impl StreamingBuffer {
-    /// Defines amount of samples `per channel` which each streaming buffer will use for internal buffer.
+    /// Defines default amount of samples `per channel` which each streaming buffer will use for internal buffer.
     pub const STREAM_SAMPLE_COUNT: usize = 44100;
 
     /// Creates new streaming buffer using given data source. May fail if data source has unsupported format
     /// or it has corrupted data. Length of internal generic buffer cannot be changed but can be fetched from
-    /// `StreamingBuffer::STREAM_SAMPLE_COUNT`
+    /// [`Self::block_size`]
     ///
     /// # Notes
     ///
@@ -182,6 +190,14 @@ impl StreamingBuffer {
         })
     }
 
+    /// Returns amount of samples `per channel` in the internal buffer. It is equal to
+    /// [`Self::STREAM_SAMPLE_COUNT`] for every data source, except raw streaming sources, that
+    /// could specify their own size (see [`RawStreamingDataSource::block_size`]).
+    #[inline]
+    pub fn block_size(&self) -> usize {
+        self.streaming_source.block_size()
+    }
+
     #[inline]
     pub(crate) fn read_next_block(&mut self) {
         self.streaming_source
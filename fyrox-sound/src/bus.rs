@@ -5,6 +5,7 @@ use crate::effects::{Effect, EffectRenderTrait};
 use fyrox_core::{
     pool::{Handle, Pool, Ticket},
     reflect::prelude::*,
+    uuid_provider,
     visitor::prelude::*,
 };
 use std::fmt::{Debug, Formatter};
@@ -76,6 +77,63 @@ impl PingPongBuffer {
     }
 }
 
+/// Send is an additional output of a sound source. The source outputs its samples to its own audio bus
+/// (see [`crate::source::SoundSource::set_bus`]) and then a copy of the samples, scaled by the send level,
+/// is added to the send bus. This is a classic send/return scheme, the most common use case is a shared
+/// "Reverb" bus (return), that contains a reverb effect with no dry signal: every source could have its
+/// own amount of reverberation without having its own copy of the (quite expensive) reverb effect.
+///
+/// ```rust
+/// # use fyrox_sound::{
+/// #     bus::{AudioBus, AudioBusGraph, AudioSend},
+/// #     effects::{reverb::Reverb, Effect},
+/// #     source::SoundSourceBuilder,
+/// # };
+/// let mut graph = AudioBusGraph::new();
+///
+/// let mut reverb = Reverb::new();
+/// reverb.set_dry(0.0);
+/// let mut reverb_bus = AudioBus::new("Reverb".to_string());
+/// reverb_bus.add_effect(Effect::Reverb(reverb));
+/// let primary_bus = graph.primary_bus_handle();
+/// graph.add_bus(reverb_bus, primary_bus);
+///
+/// // The source is heard directly and a half of its signal is sent to the reverb.
+/// let source = SoundSourceBuilder::new()
+///     .with_sends(vec![AudioSend::new("Reverb", 0.5)])
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Reflect, Visit)]
+pub struct AudioSend {
+    /// A name of the audio bus to which the samples will be sent.
+    pub bus: String,
+    /// A multiplier for the samples, that are sent to the bus.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub level: f32,
+}
+
+uuid_provider!(AudioSend = "4bbf5b70-93f3-4ca6-b8f4-d9b7bc5ceedb");
+
+impl Default for AudioSend {
+    fn default() -> Self {
+        Self {
+            bus: String::new(),
+            level: 1.0,
+        }
+    }
+}
+
+impl AudioSend {
+    /// Creates a new send to the bus with the given name.
+    pub fn new<S: AsRef<str>>(bus: S, level: f32) -> Self {
+        Self {
+            bus: bus.as_ref().to_string(),
+            level,
+        }
+    }
+}
+
 /// Audio bus is a top-level audio processing unit. It takes data from multiple audio sources and passes their
 /// samples through a chain of effects. Output signal is then can be either sent to an audio playback device or
 /// to some other audio bus and be processed again, but with different sound effects (this can be done via
@@ -33,6 +33,10 @@ use strum_macros::{AsRefStr, EnumString, VariantNames};
 /// TODO: Make this configurable, for now its set to most commonly used sample rate of 44100 Hz.
 pub const SAMPLE_RATE: u32 = 44100;
 
+/// Default speed of sound (speed of sound in the air in meters per second), it is used to calculate
+/// the Doppler effect.
+pub const DEFAULT_SPEED_OF_SOUND: f32 = 343.3;
+
 /// Distance model defines how volume of sound will decay when distance to listener changes.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Reflect, Visit, AsRefStr, EnumString, VariantNames)]
 #[repr(u32)]
@@ -117,7 +121,13 @@ pub struct State {
     renderer: Renderer,
     bus_graph: AudioBusGraph,
     distance_model: DistanceModel,
+    #[reflect(min_value = 0.0, step = 1.0)]
+    speed_of_sound: f32,
     paused: bool,
+    // Temporary buffer for sources with sends, it is used to render a source once and then mix its
+    // samples to multiple audio buses.
+    #[reflect(hidden)]
+    send_buffer: Vec<(f32, f32)>,
     /// A set of flags, that can be used to define what should be skipped during the
     /// serialization of a sound context.
     #[reflect(hidden)]
@@ -168,6 +178,18 @@ impl State {
         self.distance_model
     }
 
+    /// Sets new speed of sound (in units per second), that is used to calculate the Doppler effect.
+    /// Default value is [`DEFAULT_SPEED_OF_SOUND`] (speed of sound in the air in meters per second).
+    /// Zero disables the Doppler effect.
+    pub fn set_speed_of_sound(&mut self, speed_of_sound: f32) {
+        self.speed_of_sound = speed_of_sound.max(0.0);
+    }
+
+    /// Returns current speed of sound.
+    pub fn speed_of_sound(&self) -> f32 {
+        self.speed_of_sound
+    }
+
     /// Normalizes given frequency using context's sampling rate. Normalized frequency then can be used
     /// to create filters.
     pub fn normalize_frequency(&self, f: f32) -> f32 {
@@ -219,6 +241,11 @@ impl State {
         self.sources.borrow(handle)
     }
 
+    /// Returns shared reference to sound source at given handle. Returns `None` if the handle is invalid.
+    pub fn try_get_source(&self, handle: Handle<SoundSource>) -> Option<&SoundSource> {
+        self.sources.try_borrow(handle)
+    }
+
     /// Checks whether a handle to a sound source is valid or not.
     pub fn is_valid_handle(&self, handle: Handle<SoundSource>) -> bool {
         self.sources.is_valid_handle(handle)
@@ -271,27 +298,50 @@ impl State {
                 .iter_mut()
                 .filter(|s| s.status() == Status::Playing)
             {
-                if let Some(bus_input_buffer) = self.bus_graph.try_get_bus_input_buffer(&source.bus)
-                {
+                source.doppler_pitch =
+                    source.calculate_doppler_pitch(&self.listener, self.speed_of_sound);
+
+                if source.sends.is_empty() {
+                    if let Some(bus_input_buffer) =
+                        self.bus_graph.try_get_bus_input_buffer(&source.bus)
+                    {
+                        source.render(output_device_buffer.len());
+
+                        render_source(
+                            &mut self.renderer,
+                            source,
+                            &self.listener,
+                            self.distance_model,
+                            bus_input_buffer,
+                        );
+                    }
+                } else {
+                    // Render the source once and then mix its samples to every bus it outputs to.
+                    self.send_buffer.clear();
+                    self.send_buffer
+                        .resize(output_device_buffer.len(), (0.0, 0.0));
+
                     source.render(output_device_buffer.len());
 
-                    match self.renderer {
-                        Renderer::Default => {
-                            // Simple rendering path. Much faster (4-5 times) than HRTF path.
-                            render_source_default(
-                                source,
-                                &self.listener,
-                                self.distance_model,
-                                bus_input_buffer,
-                            );
-                        }
-                        Renderer::HrtfRenderer(ref mut hrtf_renderer) => {
-                            hrtf_renderer.render_source(
-                                source,
-                                &self.listener,
-                                self.distance_model,
-                                bus_input_buffer,
-                            );
+                    render_source(
+                        &mut self.renderer,
+                        source,
+                        &self.listener,
+                        self.distance_model,
+                        &mut self.send_buffer,
+                    );
+
+                    if let Some(bus_input_buffer) =
+                        self.bus_graph.try_get_bus_input_buffer(&source.bus)
+                    {
+                        mix(&self.send_buffer, bus_input_buffer, 1.0);
+                    }
+
+                    for send in source.sends.iter() {
+                        if let Some(bus_input_buffer) =
+                            self.bus_graph.try_get_bus_input_buffer(&send.bus)
+                        {
+                            mix(&self.send_buffer, bus_input_buffer, send.level);
                         }
                     }
                 }
@@ -304,6 +354,31 @@ impl State {
     }
 }
 
+fn render_source(
+    renderer: &mut Renderer,
+    source: &mut SoundSource,
+    listener: &Listener,
+    distance_model: DistanceModel,
+    mix_buffer: &mut [(f32, f32)],
+) {
+    match *renderer {
+        Renderer::Default => {
+            // Simple rendering path. Much faster (4-5 times) than HRTF path.
+            render_source_default(source, listener, distance_model, mix_buffer);
+        }
+        Renderer::HrtfRenderer(ref mut hrtf_renderer) => {
+            hrtf_renderer.render_source(source, listener, distance_model, mix_buffer);
+        }
+    }
+}
+
+fn mix(input: &[(f32, f32)], output: &mut [(f32, f32)], gain: f32) {
+    for ((input_left, input_right), (output_left, output_right)) in input.iter().zip(output) {
+        *output_left += *input_left * gain;
+        *output_right += *input_right * gain;
+    }
+}
+
 impl SoundContext {
     /// TODO: This is magic constant that gives 1024 + 1 number when summed with
     ///       HRTF length for faster FFT calculations. Find a better way of selecting this.
@@ -326,7 +401,9 @@ impl SoundContext {
                 renderer: Renderer::Default,
                 bus_graph: AudioBusGraph::new(),
                 distance_model: DistanceModel::InverseDistance,
+                speed_of_sound: DEFAULT_SPEED_OF_SOUND,
                 paused: false,
+                send_buffer: Default::default(),
                 serialization_options: Default::default(),
             }))),
         }
@@ -385,6 +462,7 @@ impl Visit for State {
         self.renderer.visit("Renderer", &mut region)?;
         self.paused.visit("Paused", &mut region)?;
         self.distance_model.visit("DistanceModel", &mut region)?;
+        let _ = self.speed_of_sound.visit("SpeedOfSound", &mut region);
 
         Ok(())
     }
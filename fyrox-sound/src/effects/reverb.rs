@@ -29,7 +29,7 @@ use crate::{
     dsp::filters::{AllPass, LpfComb},
     effects::EffectRenderTrait,
 };
-use fyrox_core::{reflect::prelude::*, visitor::prelude::*};
+use fyrox_core::{math::lerpf, reflect::prelude::*, uuid_provider, visitor::prelude::*};
 
 #[derive(Default, Debug, Clone, PartialEq, Visit)]
 struct ChannelReverb {
@@ -130,6 +130,96 @@ impl ChannelReverb {
     }
 }
 
+/// A set of parameters, that describes reverberation of some environment (a room, a hall, a cave,
+/// etc.). Presets can be applied to a [`Reverb`] effect (see [`Reverb::apply_preset`]) and blended
+/// with each other, which is useful to smoothly change the reverberation when a listener moves from
+/// one environment to another.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, Visit)]
+pub struct ReverbPreset {
+    /// Duration of the reverberation in seconds. See [`Reverb::set_decay_time`].
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub decay_time: f32,
+    /// Normalized cutoff frequency of the reflections. See [`Reverb::set_fc`].
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub fc: f32,
+    /// Output level of the reverberation.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub level: f32,
+}
+
+uuid_provider!(ReverbPreset = "43a2e77f-dd07-4564-969b-ee7e4faa373a");
+
+impl Default for ReverbPreset {
+    fn default() -> Self {
+        Self::generic()
+    }
+}
+
+impl ReverbPreset {
+    /// Generic environment, the same as the default reverb.
+    pub fn generic() -> Self {
+        Self {
+            decay_time: 2.0,
+            fc: 0.25615,
+            level: 1.0,
+        }
+    }
+
+    /// Small room with soft reflections.
+    pub fn room() -> Self {
+        Self {
+            decay_time: 0.6,
+            fc: 0.2,
+            level: 0.6,
+        }
+    }
+
+    /// Large hall with long and bright reflections.
+    pub fn hall() -> Self {
+        Self {
+            decay_time: 3.5,
+            fc: 0.3,
+            level: 1.0,
+        }
+    }
+
+    /// Cave with very long and dark reflections.
+    pub fn cave() -> Self {
+        Self {
+            decay_time: 6.0,
+            fc: 0.15,
+            level: 1.0,
+        }
+    }
+
+    /// Narrow corridor.
+    pub fn corridor() -> Self {
+        Self {
+            decay_time: 1.2,
+            fc: 0.22,
+            level: 0.8,
+        }
+    }
+
+    /// Open space with barely audible reflections.
+    pub fn outdoors() -> Self {
+        Self {
+            decay_time: 0.8,
+            fc: 0.1,
+            level: 0.2,
+        }
+    }
+
+    /// Linearly interpolates every parameter of the presets.
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            decay_time: lerpf(self.decay_time, other.decay_time, t),
+            fc: lerpf(self.fc, other.fc, t),
+            level: lerpf(self.level, other.level, t),
+        }
+    }
+}
+
 /// See module docs.
 #[derive(Debug, Clone, Reflect, PartialEq)]
 pub struct Reverb {
@@ -265,6 +355,18 @@ impl Reverb {
     pub fn fc(&self) -> f32 {
         self.fc
     }
+
+    /// Applies decay time and cutoff frequency of the preset. The level of the preset should be
+    /// applied to an audio bus, that contains the reverb.
+    pub fn apply_preset(&mut self, preset: &ReverbPreset) {
+        // Changing the parameters recalculates the filters, so do this only if needed.
+        if self.decay_time != preset.decay_time {
+            self.set_decay_time(preset.decay_time);
+        }
+        if self.fc != preset.fc {
+            self.set_fc(preset.fc);
+        }
+    }
 }
 
 impl EffectRenderTrait for Reverb {
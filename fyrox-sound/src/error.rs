@@ -53,6 +53,9 @@ pub enum SoundError {
 
     /// A buffer is not loaded yet, consider to `await` it before use.
     BufferIsNotLoaded,
+
+    /// Voice codec specific error, exact reason stored in inner value.
+    Codec(String),
 }
 
 impl From<std::io::Error> for SoundError {
@@ -93,6 +96,7 @@ impl Display for SoundError {
             SoundError::DecoderError(de) => write!(f, "internal decoder error: {:?}", de),
             SoundError::BufferFailedToLoad => write!(f, "a buffer failed to load"),
             SoundError::BufferIsNotLoaded => write!(f, "a buffer is not loaded yet"),
+            SoundError::Codec(reason) => write!(f, "voice codec error: {}", reason),
         }
     }
 }
@@ -7,6 +7,8 @@
 //! - Streaming.
 //! - Head-related transfer function support ([HRTF](https://en.wikipedia.org/wiki/Head-related_transfer_function)).
 //! - Reverb effect.
+//! - Voice chat building blocks - microphone capture and Opus codec (`voice` feature), low-latency
+//! playback of streamed samples.
 //!
 //! ## Examples
 //!
@@ -78,6 +80,7 @@ pub mod error;
 pub mod listener;
 pub mod renderer;
 pub mod source;
+pub mod voice;
 
 // Reexport some modules because there some types of them in public API.
 pub use fyrox_core::algebra;
@@ -84,12 +84,13 @@ pub(crate) fn render_source_default(
 ) {
     let distance_gain = lerpf(
         1.0,
-        source.calculate_distance_gain(listener, distance_model),
+        source.calculate_distance_gain(listener, distance_model)
+            * source.calculate_cone_gain(listener),
         source.spatial_blend(),
     );
     let panning = lerpf(
         source.panning(),
-        source.calculate_panning(listener),
+        source.calculate_panning(listener) * (1.0 - source.spread()),
         source.spatial_blend(),
     );
     let gain = distance_gain * source.gain();
@@ -30,13 +30,15 @@
 
 use crate::{
     buffer::{streaming::StreamingBuffer, SoundBuffer, SoundBufferResource},
-    bus::AudioBusGraph,
+    bus::{AudioBusGraph, AudioSend},
     context::DistanceModel,
+    dsp::filters::OnePole,
     error::SoundError,
     listener::Listener,
 };
 use fyrox_core::{
     algebra::Vector3,
+    math::lerpf,
     reflect::prelude::*,
     uuid_provider,
     visitor::{Visit, VisitResult, Visitor},
@@ -61,6 +63,61 @@ pub enum Status {
 
 uuid_provider!(Status = "1980bded-86cd-4eff-a5db-bab729bdb3ad");
 
+/// Directivity cone of a sound source. The sound is heard at full volume inside the inner cone, with
+/// [`Self::outer_gain`] outside the outer cone and with interpolated gain between the cones. The
+/// axis of the cones is defined by the direction of the source (see [`SoundSource::set_direction`]).
+/// Default cone is omnidirectional - the sound is heard equally in every direction.
+///
+/// Angles are full angles of the cones (not half-angles) in radians.
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, Visit)]
+pub struct SoundCone {
+    /// Full angle of the inner cone in radians.
+    #[reflect(min_value = 0.0, max_value = 6.3, step = 0.05)]
+    pub inner_angle: f32,
+    /// Full angle of the outer cone in radians. It should be larger or equal to the inner angle.
+    #[reflect(min_value = 0.0, max_value = 6.3, step = 0.05)]
+    pub outer_angle: f32,
+    /// Gain of the sound outside the outer cone.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub outer_gain: f32,
+}
+
+uuid_provider!(SoundCone = "2df91e2b-8170-4e0e-9750-cea627a78922");
+
+impl Default for SoundCone {
+    fn default() -> Self {
+        Self::OMNIDIRECTIONAL
+    }
+}
+
+impl SoundCone {
+    /// A cone, that does not affect the sound.
+    pub const OMNIDIRECTIONAL: Self = Self {
+        inner_angle: std::f32::consts::TAU,
+        outer_angle: std::f32::consts::TAU,
+        outer_gain: 1.0,
+    };
+
+    /// Returns `true` if the cone does not affect the sound.
+    pub fn is_omnidirectional(&self) -> bool {
+        self.inner_angle >= std::f32::consts::TAU || self.outer_gain == 1.0
+    }
+
+    /// Calculates gain for the given angle (in radians) between the axis of the cone and the
+    /// direction to the listener.
+    pub fn gain(&self, angle: f32) -> f32 {
+        let inner = self.inner_angle * 0.5;
+        let outer = self.outer_angle.max(self.inner_angle) * 0.5;
+        if angle <= inner {
+            1.0
+        } else if angle >= outer {
+            self.outer_gain
+        } else {
+            lerpf(1.0, self.outer_gain, (angle - inner) / (outer - inner))
+        }
+    }
+}
+
 /// See module info.
 #[derive(Debug, Clone, Reflect, Visit)]
 pub struct SoundSource {
@@ -98,6 +155,8 @@ pub struct SoundSource {
     status: Status,
     #[visit(optional)]
     pub(crate) bus: String,
+    #[visit(optional)]
+    pub(crate) sends: Vec<AudioSend>,
     play_once: bool,
     // Here we use Option because when source is just created it has no info about it
     // previous left and right channel gains. We can't set it to 1.0 for example
@@ -125,6 +184,24 @@ pub struct SoundSource {
     max_distance: f32,
     #[reflect(min_value = 0.0, step = 0.05)]
     rolloff_factor: f32,
+    #[visit(optional)]
+    distance_model: Option<DistanceModel>,
+    #[visit(optional)]
+    velocity: Vector3<f32>,
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, step = 0.05)]
+    doppler_factor: f32,
+    #[visit(optional)]
+    direction: Vector3<f32>,
+    #[visit(optional)]
+    cone: SoundCone,
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    spread: f32,
+    // Pitch multiplier caused by the Doppler effect, it is re-calculated on every render.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) doppler_pitch: f64,
     // Some data that needed for iterative overlap-save convolution.
     #[reflect(hidden)]
     #[visit(skip)]
@@ -138,6 +215,19 @@ pub struct SoundSource {
     #[reflect(hidden)]
     #[visit(skip)]
     pub(crate) prev_distance_gain: Option<f32>,
+    // Occlusion parameters, they're set by the engine every frame, so there's no need to save them.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    occlusion_gain: f32,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    prev_occlusion_gain: f32,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    occlusion_fc: f32,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    occlusion_filters: [OnePole; 2],
 }
 
 impl Default for SoundSource {
@@ -155,6 +245,7 @@ impl Default for SoundSource {
             resampling_multiplier: 1.0,
             status: Status::Stopped,
             bus: "Master".to_string(),
+            sends: Default::default(),
             play_once: false,
             last_left_gain: None,
             last_right_gain: None,
@@ -164,10 +255,21 @@ impl Default for SoundSource {
             position: Vector3::new(0.0, 0.0, 0.0),
             max_distance: f32::MAX,
             rolloff_factor: 1.0,
+            distance_model: None,
+            velocity: Default::default(),
+            doppler_factor: 1.0,
+            direction: Vector3::new(0.0, 0.0, 1.0),
+            cone: Default::default(),
+            spread: 0.0,
+            doppler_pitch: 1.0,
             prev_left_samples: Default::default(),
             prev_right_samples: Default::default(),
             prev_sampling_vector: Vector3::new(0.0, 0.0, 1.0),
             prev_distance_gain: None,
+            occlusion_gain: 1.0,
+            prev_occlusion_gain: 1.0,
+            occlusion_fc: 1.0,
+            occlusion_filters: Default::default(),
         }
     }
 }
@@ -394,6 +496,80 @@ impl SoundSource {
         self.max_distance
     }
 
+    /// Sets distance model of the source, that overrides the distance model of the context. `None`
+    /// means that the distance model of the context will be used.
+    pub fn set_distance_model(&mut self, distance_model: Option<DistanceModel>) -> &mut Self {
+        self.distance_model = distance_model;
+        self
+    }
+
+    /// Returns distance model of the source (if any).
+    pub fn distance_model(&self) -> Option<DistanceModel> {
+        self.distance_model
+    }
+
+    /// Sets velocity of the source in world space (in units per second). It is used to calculate
+    /// the Doppler effect.
+    pub fn set_velocity(&mut self, velocity: Vector3<f32>) -> &mut Self {
+        self.velocity = velocity;
+        self
+    }
+
+    /// Returns velocity of the source.
+    pub fn velocity(&self) -> Vector3<f32> {
+        self.velocity
+    }
+
+    /// Sets Doppler factor of the source. It exaggerates (values larger than 1.0) or reduces (values
+    /// less than 1.0) the Doppler effect. Zero disables the Doppler effect. Default value is 1.0,
+    /// which corresponds to physically correct effect.
+    pub fn set_doppler_factor(&mut self, doppler_factor: f32) -> &mut Self {
+        self.doppler_factor = doppler_factor.max(0.0);
+        self
+    }
+
+    /// Returns Doppler factor of the source.
+    pub fn doppler_factor(&self) -> f32 {
+        self.doppler_factor
+    }
+
+    /// Sets direction of the source in world space. It defines the axis of the directivity cone,
+    /// see [`SoundCone`] docs for more info.
+    pub fn set_direction(&mut self, direction: Vector3<f32>) -> &mut Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Returns direction of the source.
+    pub fn direction(&self) -> Vector3<f32> {
+        self.direction
+    }
+
+    /// Sets new directivity cone of the source. See [`SoundCone`] docs for more info.
+    pub fn set_cone(&mut self, cone: SoundCone) -> &mut Self {
+        self.cone = cone;
+        self
+    }
+
+    /// Returns directivity cone of the source.
+    pub fn cone(&self) -> SoundCone {
+        self.cone
+    }
+
+    /// Sets spread of the source in `[0; 1]` range. Spread defines how much the sound is "wide",
+    /// 0.0 - the sound is a point (regular panning), 1.0 - the sound comes from every direction
+    /// (no panning). It is useful for large sources like rivers, crowds, etc. Spread is ignored by
+    /// HRTF renderer.
+    pub fn set_spread(&mut self, spread: f32) -> &mut Self {
+        self.spread = spread.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Returns spread of the source.
+    pub fn spread(&self) -> f32 {
+        self.spread
+    }
+
     /// Sets new name of the target audio bus. The name must be valid, otherwise the sound won't play!
     /// Default is [`AudioBusGraph::PRIMARY_BUS`].
     pub fn set_bus<S: AsRef<str>>(&mut self, bus: S) {
@@ -405,6 +581,72 @@ impl SoundSource {
         &self.bus
     }
 
+    /// Sets new sends of the source. See [`AudioSend`] docs for more info.
+    pub fn set_sends(&mut self, sends: Vec<AudioSend>) {
+        self.sends = sends;
+    }
+
+    /// Returns a slice with the sends of the source.
+    pub fn sends(&self) -> &[AudioSend] {
+        &self.sends
+    }
+
+    /// Returns a mutable reference to the sends of the source.
+    pub fn sends_mut(&mut self) -> &mut Vec<AudioSend> {
+        &mut self.sends
+    }
+
+    /// Sets occlusion parameters of the source - its samples are multiplied by the given gain (in
+    /// `[0; 1]` range) and passed through a low-pass filter with the given normalized cutoff frequency
+    /// (see [`crate::context::SoundContext::normalize_frequency`]). Use `1.0` for both to disable the
+    /// occlusion. Changes of the gain are interpolated across the rendered block to prevent clicks.
+    ///
+    /// These parameters are usually set by the engine, using raycasting from the source to the
+    /// listener, so the sound is muffled when there's an obstacle between them.
+    pub fn set_occlusion(&mut self, gain: f32, fc: f32) {
+        self.occlusion_gain = gain.clamp(0.0, 1.0);
+        let fc = fc.clamp(0.0, 1.0);
+        if fc != self.occlusion_fc {
+            self.occlusion_fc = fc;
+            for filter in self.occlusion_filters.iter_mut() {
+                filter.set_fc(fc);
+            }
+        }
+    }
+
+    /// Returns current occlusion gain of the source. See [`Self::set_occlusion`] for more info.
+    pub fn occlusion_gain(&self) -> f32 {
+        self.occlusion_gain
+    }
+
+    /// Returns current normalized cutoff frequency of the occlusion filter. See [`Self::set_occlusion`]
+    /// for more info.
+    pub fn occlusion_fc(&self) -> f32 {
+        self.occlusion_fc
+    }
+
+    fn apply_occlusion(&mut self) {
+        // Cutoff frequencies close to the Nyquist frequency makes no audible difference.
+        let filter = self.occlusion_fc < 0.45;
+        if !filter && self.occlusion_gain == 1.0 && self.prev_occlusion_gain == 1.0 {
+            return;
+        }
+
+        let step = 1.0 / self.frame_samples.len().max(1) as f32;
+        let mut t = 0.0;
+        for (left, right) in self.frame_samples.iter_mut() {
+            if filter {
+                *left = self.occlusion_filters[0].feed(*left);
+                *right = self.occlusion_filters[1].feed(*right);
+            }
+            let gain = lerpf(self.prev_occlusion_gain, self.occlusion_gain, t);
+            *left *= gain;
+            *right *= gain;
+            t += step;
+        }
+        self.prev_occlusion_gain = self.occlusion_gain;
+    }
+
     // Distance models were taken from OpenAL Specification because it looks like they're
     // standard in industry and there is no need to reinvent it.
     // https://www.openal.org/documentation/openal-1.1-specification.pdf
@@ -417,7 +659,7 @@ impl SoundSource {
             .position
             .metric_distance(&listener.position())
             .clamp(self.radius, self.max_distance);
-        match distance_model {
+        match self.distance_model.unwrap_or(distance_model) {
             DistanceModel::None => 1.0,
             DistanceModel::InverseDistance => {
                 self.radius / (self.radius + self.rolloff_factor * (distance - self.radius))
@@ -429,6 +671,38 @@ impl SoundSource {
         }
     }
 
+    pub(crate) fn calculate_cone_gain(&self, listener: &Listener) -> f32 {
+        if self.cone.is_omnidirectional() {
+            return 1.0;
+        }
+        let (Some(direction), Some(to_listener)) = (
+            self.direction.try_normalize(f32::EPSILON),
+            (listener.position() - self.position).try_normalize(f32::EPSILON),
+        ) else {
+            return 1.0;
+        };
+        self.cone
+            .gain(direction.dot(&to_listener).clamp(-1.0, 1.0).acos())
+    }
+
+    // Doppler shift is also taken from OpenAL Specification (see link above).
+    pub(crate) fn calculate_doppler_pitch(&self, listener: &Listener, speed_of_sound: f32) -> f64 {
+        if self.doppler_factor <= 0.0 || speed_of_sound <= 0.0 {
+            return 1.0;
+        }
+        let Some(to_listener) = (listener.position() - self.position).try_normalize(f32::EPSILON)
+        else {
+            return 1.0;
+        };
+        let max_speed = speed_of_sound / self.doppler_factor;
+        let listener_speed = listener.velocity().dot(&to_listener).min(max_speed);
+        let source_speed = self.velocity.dot(&to_listener).min(max_speed);
+        // Prevent division by zero, when the source moves at the speed of sound.
+        let pitch = (speed_of_sound - self.doppler_factor * listener_speed)
+            / (speed_of_sound - self.doppler_factor * source_speed).max(0.01 * speed_of_sound);
+        lerpf(1.0, pitch, self.spatial_blend) as f64
+    }
+
     pub(crate) fn calculate_panning(&self, listener: &Listener) -> f32 {
         (listener.position() - self.position)
             .try_normalize(f32::EPSILON)
@@ -459,6 +733,39 @@ impl SoundSource {
         Duration::from_secs(0)
     }
 
+    /// Returns root mean square amplitude of the samples of the buffer, that were played during the
+    /// given time window before the current playback position. The amplitude does not depend on the
+    /// gain of the source, it could be used to drive visual effects, like lip-sync. Streaming
+    /// buffers contain only a small portion of data, so the window is limited by the current block.
+    /// Returns zero if the source is not playing.
+    pub fn amplitude(&self, window: Duration) -> f32 {
+        if self.status != Status::Playing {
+            return 0.0;
+        }
+
+        let Some(buffer) = self.buffer.as_ref() else {
+            return 0.0;
+        };
+
+        let mut state = buffer.state();
+        let Some(buffer) = state.data() else {
+            return 0.0;
+        };
+
+        let channel_count = buffer.channel_count().max(1);
+        let frame_count = buffer.samples().len() / channel_count;
+        let window = ((window.as_secs_f64() * buffer.sample_rate() as f64) as usize).max(1);
+        let end = (self.buf_read_pos.max(0.0) as usize).min(frame_count);
+        let begin = end.saturating_sub(window);
+
+        let samples = &buffer.samples()[(begin * channel_count)..(end * channel_count)];
+        if samples.is_empty() {
+            0.0
+        } else {
+            (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+        }
+    }
+
     /// Sets playback duration.
     pub fn set_playback_time(&mut self, time: Duration) {
         if let Some(buffer) = self.buffer.as_ref() {
@@ -477,7 +784,7 @@ impl SoundSource {
                         streaming.read_next_block();
                         // Streaming sources has different buffer read position because
                         // buffer contains only small portion of data.
-                        self.playback_pos % (StreamingBuffer::STREAM_SAMPLE_COUNT as f64)
+                        self.playback_pos % (streaming.block_size() as f64)
                     }
                     SoundBuffer::Generic(_) => self.playback_pos,
                 };
@@ -506,6 +813,7 @@ impl SoundSource {
         }
         // Fill the remaining part of frame_samples.
         self.frame_samples.resize(amount, (0.0, 0.0));
+        self.apply_occlusion();
     }
 
     fn render_playing(&mut self, buffer: &mut SoundBuffer, amount: usize) {
@@ -521,7 +829,7 @@ impl SoundSource {
             let mut end_reached = true;
             if let SoundBuffer::Streaming(streaming) = buffer {
                 // Means that this is the last available block.
-                if len != channel_count * StreamingBuffer::STREAM_SAMPLE_COUNT {
+                if len != channel_count * streaming.block_size() {
                     let _ = streaming.rewind();
                 } else {
                     end_reached = false;
@@ -545,7 +853,7 @@ impl SoundSource {
     // Renders until the end of the block or until amount samples is written and returns
     // the number of written samples.
     fn render_until_block_end(&mut self, buffer: &mut SoundBuffer, mut amount: usize) -> usize {
-        let step = self.pitch * self.resampling_multiplier;
+        let step = self.pitch * self.resampling_multiplier * self.doppler_pitch;
         if step == 1.0 {
             if self.buf_read_pos < 0.0 {
                 // This can theoretically happen if we change pitch on the fly.
@@ -716,6 +1024,12 @@ pub struct SoundSourceBuilder {
     rolloff_factor: f32,
     spatial_blend: f32,
     bus: String,
+    sends: Vec<AudioSend>,
+    distance_model: Option<DistanceModel>,
+    doppler_factor: f32,
+    direction: Vector3<f32>,
+    cone: SoundCone,
+    spread: f32,
 }
 
 impl Default for SoundSourceBuilder {
@@ -743,6 +1057,12 @@ impl SoundSourceBuilder {
             rolloff_factor: 1.0,
             spatial_blend: 1.0,
             bus: AudioBusGraph::PRIMARY_BUS.to_string(),
+            sends: Default::default(),
+            distance_model: None,
+            doppler_factor: 1.0,
+            direction: Vector3::new(0.0, 0.0, 1.0),
+            cone: Default::default(),
+            spread: 0.0,
         }
     }
 
@@ -842,6 +1162,42 @@ impl SoundSourceBuilder {
         self
     }
 
+    /// Sets desired sends of the sound source. See [`AudioSend`] docs for more info.
+    pub fn with_sends(mut self, sends: Vec<AudioSend>) -> Self {
+        self.sends = sends;
+        self
+    }
+
+    /// See [`SoundSource::set_distance_model`]
+    pub fn with_distance_model(mut self, distance_model: Option<DistanceModel>) -> Self {
+        self.distance_model = distance_model;
+        self
+    }
+
+    /// See [`SoundSource::set_doppler_factor`]
+    pub fn with_doppler_factor(mut self, doppler_factor: f32) -> Self {
+        self.doppler_factor = doppler_factor.max(0.0);
+        self
+    }
+
+    /// See [`SoundSource::set_direction`]
+    pub fn with_direction(mut self, direction: Vector3<f32>) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// See [`SoundSource::set_cone`]
+    pub fn with_cone(mut self, cone: SoundCone) -> Self {
+        self.cone = cone;
+        self
+    }
+
+    /// See [`SoundSource::set_spread`]
+    pub fn with_spread(mut self, spread: f32) -> Self {
+        self.spread = spread.clamp(0.0, 1.0);
+        self
+    }
+
     /// Creates new instance of generic sound source. May fail if buffer is invalid.
     pub fn build(self) -> Result<SoundSource, SoundError> {
         let mut source = SoundSource {
@@ -862,6 +1218,13 @@ impl SoundSourceBuilder {
             prev_left_samples: Default::default(),
             prev_right_samples: Default::default(),
             bus: self.bus,
+            sends: self.sends,
+            distance_model: self.distance_model,
+            doppler_factor: self.doppler_factor,
+            direction: self.direction,
+            cone: self.cone,
+            spread: self.spread,
+            occlusion_filters: Default::default(),
             ..Default::default()
         };
 
@@ -871,3 +1234,95 @@ impl SoundSourceBuilder {
         Ok(source)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        buffer::{DataSource, SoundBufferResource, SoundBufferResourceExtension},
+        context::DEFAULT_SPEED_OF_SOUND,
+        listener::Listener,
+        source::{SoundCone, SoundSourceBuilder, Status},
+    };
+    use fyrox_core::algebra::Vector3;
+    use std::time::Duration;
+
+    #[test]
+    fn test_cone_gain() {
+        let cone = SoundCone {
+            inner_angle: 90.0f32.to_radians(),
+            outer_angle: 180.0f32.to_radians(),
+            outer_gain: 0.2,
+        };
+        assert_eq!(cone.gain(0.0), 1.0);
+        assert_eq!(cone.gain(45.0f32.to_radians()), 1.0);
+        assert!((cone.gain(67.5f32.to_radians()) - 0.6).abs() < 0.001);
+        assert_eq!(cone.gain(120.0f32.to_radians()), 0.2);
+
+        let source = SoundSourceBuilder::new()
+            .with_direction(Vector3::new(0.0, 0.0, 1.0))
+            .with_cone(cone)
+            .build()
+            .unwrap();
+        let mut listener = Listener::new();
+        listener.set_position(Vector3::new(0.0, 0.0, 5.0));
+        assert_eq!(source.calculate_cone_gain(&listener), 1.0);
+        listener.set_position(Vector3::new(0.0, 0.0, -5.0));
+        assert_eq!(source.calculate_cone_gain(&listener), 0.2);
+    }
+
+    #[test]
+    fn test_doppler_pitch() {
+        let mut listener = Listener::new();
+        listener.set_position(Vector3::new(0.0, 0.0, 10.0));
+
+        let mut source = SoundSourceBuilder::new().build().unwrap();
+        assert_eq!(
+            source.calculate_doppler_pitch(&listener, DEFAULT_SPEED_OF_SOUND),
+            1.0
+        );
+
+        // Approaching source has higher pitch.
+        source.set_velocity(Vector3::new(0.0, 0.0, 30.0));
+        assert!(source.calculate_doppler_pitch(&listener, DEFAULT_SPEED_OF_SOUND) > 1.0);
+
+        // Receding source has lower pitch.
+        source.set_velocity(Vector3::new(0.0, 0.0, -30.0));
+        assert!(source.calculate_doppler_pitch(&listener, DEFAULT_SPEED_OF_SOUND) < 1.0);
+
+        // Zero Doppler factor disables the effect.
+        source.set_doppler_factor(0.0);
+        assert_eq!(
+            source.calculate_doppler_pitch(&listener, DEFAULT_SPEED_OF_SOUND),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_amplitude() {
+        // Silence followed by a constant signal.
+        let mut samples = vec![0.0; 50];
+        samples.extend(std::iter::repeat(0.5).take(50));
+        let buffer = SoundBufferResource::new_generic(DataSource::Raw {
+            sample_rate: 100,
+            channel_count: 1,
+            samples,
+        })
+        .unwrap();
+
+        let mut source = SoundSourceBuilder::new()
+            .with_buffer(buffer)
+            .build()
+            .unwrap();
+        source.buf_read_pos = 100.0;
+        assert_eq!(source.amplitude(Duration::from_secs_f32(0.1)), 0.0);
+
+        source.status = Status::Playing;
+        assert!((source.amplitude(Duration::from_secs_f32(0.1)) - 0.5).abs() < 1.0e-5);
+        // Half of the window is silent.
+        let expected = (0.5f32 * 0.5 * 0.5).sqrt();
+        assert!((source.amplitude(Duration::from_secs(1)) - expected).abs() < 1.0e-5);
+
+        source.buf_read_pos = 40.0;
+        assert_eq!(source.amplitude(Duration::from_secs_f32(0.1)), 0.0);
+    }
+}
@@ -0,0 +1,187 @@
+//! Microphone capture. See [`VoiceCapture`] docs for more info.
+
+use crate::{
+    error::SoundError,
+    voice::{resample_linear, VOICE_SAMPLE_RATE},
+};
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    SampleFormat, SizedSample, Stream, StreamConfig,
+};
+use fyrox_core::log::Log;
+use std::{
+    collections::VecDeque,
+    fmt::{Debug, Formatter},
+    sync::{Arc, Mutex},
+};
+
+/// Maximum amount of captured samples (2 seconds), that are kept if nobody reads them.
+const MAX_CAPTURED: usize = VOICE_SAMPLE_RATE * 2;
+
+#[derive(Default)]
+struct CaptureState {
+    samples: VecDeque<f32>,
+    resampled: Vec<f32>,
+    mono: Vec<f32>,
+    last_sample: f32,
+    phase: f64,
+}
+
+/// Captures samples from an input device (microphone). Captured samples are mixed down to mono and
+/// resampled to [`VOICE_SAMPLE_RATE`], so they could be passed directly to
+/// [`super::VoiceEncoder`]. The capture starts immediately after creation and stops when the
+/// instance is dropped.
+///
+/// # Notes
+///
+/// The capture must be created and dropped on the same thread, because some platforms do not allow
+/// sending input streams between threads. Call [`Self::read_samples`] regularly (every frame, for
+/// example), otherwise the oldest samples will be discarded.
+pub struct VoiceCapture {
+    // Keeps the capture running.
+    _stream: Stream,
+    state: Arc<Mutex<CaptureState>>,
+    device_name: String,
+    muted: bool,
+}
+
+impl Debug for VoiceCapture {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VoiceCapture")
+            .field("device_name", &self.device_name)
+            .field("muted", &self.muted)
+            .finish()
+    }
+}
+
+fn device_error<E: std::fmt::Display>(err: E) -> SoundError {
+    SoundError::FailedToInitializeDevice(err.to_string())
+}
+
+impl VoiceCapture {
+    /// Starts capturing from the default input device of the system.
+    pub fn new() -> Result<Self, SoundError> {
+        let device = cpal::default_host()
+            .default_input_device()
+            .ok_or_else(|| device_error("no input device available"))?;
+        Self::from_device(device)
+    }
+
+    /// Returns names of every input device of the system, they could be passed to
+    /// [`Self::with_device_name`].
+    pub fn input_device_names() -> Result<Vec<String>, SoundError> {
+        Ok(cpal::default_host()
+            .input_devices()
+            .map_err(device_error)?
+            .filter_map(|device| device.name().ok())
+            .collect())
+    }
+
+    /// Starts capturing from an input device with the given name.
+    pub fn with_device_name(name: &str) -> Result<Self, SoundError> {
+        let device = cpal::default_host()
+            .input_devices()
+            .map_err(device_error)?
+            .find(|device| device.name().is_ok_and(|n| n == name))
+            .ok_or_else(|| device_error(format!("no input device with name {name}")))?;
+        Self::from_device(device)
+    }
+
+    fn from_device(device: cpal::Device) -> Result<Self, SoundError> {
+        let supported_config = device.default_input_config().map_err(device_error)?;
+        let sample_format = supported_config.sample_format();
+        let config: StreamConfig = supported_config.into();
+
+        let state = Arc::new(Mutex::new(CaptureState::default()));
+
+        let stream = match sample_format {
+            SampleFormat::F32 => Self::build_stream::<f32>(&device, &config, state.clone()),
+            SampleFormat::I16 => Self::build_stream::<i16>(&device, &config, state.clone()),
+            SampleFormat::U16 => Self::build_stream::<u16>(&device, &config, state.clone()),
+            _ => return Err(SoundError::UnsupportedFormat),
+        }?;
+        stream.play().map_err(device_error)?;
+
+        Ok(Self {
+            _stream: stream,
+            state,
+            device_name: device.name().unwrap_or_default(),
+            muted: false,
+        })
+    }
+
+    fn build_stream<T>(
+        device: &cpal::Device,
+        config: &StreamConfig,
+        state: Arc<Mutex<CaptureState>>,
+    ) -> Result<Stream, SoundError>
+    where
+        T: SizedSample,
+        f32: cpal::FromSample<T>,
+    {
+        let channel_count = config.channels.max(1) as usize;
+        let sample_rate = config.sample_rate.0 as usize;
+
+        device
+            .build_input_stream(
+                config,
+                move |data: &[T], _: &cpal::InputCallbackInfo| {
+                    let mut state = state.lock().unwrap();
+                    let state = &mut *state;
+
+                    state.mono.clear();
+                    state.mono.extend(data.chunks(channel_count).map(|frame| {
+                        frame
+                            .iter()
+                            .map(|s| <f32 as cpal::FromSample<T>>::from_sample_(*s))
+                            .sum::<f32>()
+                            / channel_count as f32
+                    }));
+
+                    state.resampled.clear();
+                    resample_linear(
+                        &state.mono,
+                        sample_rate,
+                        VOICE_SAMPLE_RATE,
+                        &mut state.last_sample,
+                        &mut state.phase,
+                        &mut state.resampled,
+                    );
+
+                    state.samples.extend(state.resampled.iter().copied());
+                    let excess = state.samples.len().saturating_sub(MAX_CAPTURED);
+                    state.samples.drain(..excess);
+                },
+                |err| Log::err(format!("Voice capture error: {err}")),
+                None,
+            )
+            .map_err(device_error)
+    }
+
+    /// Returns name of the device, that is used for capturing.
+    pub fn device_name(&self) -> &str {
+        &self.device_name
+    }
+
+    /// Mutes or unmutes the capture. Muted capture discards every captured sample.
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+    }
+
+    /// Returns `true` if the capture is muted.
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    /// Moves every captured sample to the given buffer, replacing its previous content. The samples
+    /// are mono with [`VOICE_SAMPLE_RATE`] sample rate.
+    pub fn read_samples(&self, samples: &mut Vec<f32>) {
+        samples.clear();
+        let mut state = self.state.lock().unwrap();
+        if self.muted {
+            state.samples.clear();
+        } else {
+            samples.extend(state.samples.drain(..));
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Opus encoding and decoding of voice samples. See [`VoiceEncoder`] and [`VoiceDecoder`] docs for
+//! more info.
+
+use crate::{
+    error::SoundError,
+    voice::{VOICE_FRAME_SIZE, VOICE_SAMPLE_RATE},
+};
+use std::fmt::{Debug, Formatter};
+
+/// Maximum size of a single packet in bytes, recommended by Opus documentation.
+const MAX_PACKET_SIZE: usize = 4000;
+
+impl From<opus::Error> for SoundError {
+    fn from(err: opus::Error) -> Self {
+        SoundError::Codec(err.to_string())
+    }
+}
+
+/// Compresses mono voice samples with [`VOICE_SAMPLE_RATE`] sample rate into Opus packets. Each
+/// packet contains [`VOICE_FRAME_SIZE`] samples (20 ms of audio).
+pub struct VoiceEncoder {
+    encoder: opus::Encoder,
+    pending: Vec<f32>,
+}
+
+impl Debug for VoiceEncoder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VoiceEncoder")
+            .field("pending", &self.pending.len())
+            .finish()
+    }
+}
+
+impl VoiceEncoder {
+    /// Default bit rate of the encoder, that gives good quality of speech.
+    pub const DEFAULT_BITRATE: i32 = 24000;
+
+    /// Creates a new encoder with the default bit rate.
+    pub fn new() -> Result<Self, SoundError> {
+        let mut encoder = opus::Encoder::new(
+            VOICE_SAMPLE_RATE as u32,
+            opus::Channels::Mono,
+            opus::Application::Voip,
+        )?;
+        encoder.set_bitrate(opus::Bitrate::Bits(Self::DEFAULT_BITRATE))?;
+        Ok(Self {
+            encoder,
+            pending: Vec::with_capacity(VOICE_FRAME_SIZE),
+        })
+    }
+
+    /// Sets desired bit rate (in bits per second) of the encoder. Lower values reduce the traffic,
+    /// but decrease quality.
+    pub fn set_bitrate(&mut self, bitrate: i32) -> Result<(), SoundError> {
+        Ok(self.encoder.set_bitrate(opus::Bitrate::Bits(bitrate))?)
+    }
+
+    /// Enables or disables in-band forward error correction, that allows the decoder to restore
+    /// lost packets partially, using the data from the next packet.
+    pub fn set_fec(&mut self, enabled: bool) -> Result<(), SoundError> {
+        Ok(self.encoder.set_inband_fec(enabled)?)
+    }
+
+    /// Encodes the given samples and returns the packets. Samples, that do not fill a whole frame,
+    /// are kept until the next call.
+    pub fn encode(&mut self, samples: &[f32]) -> Result<Vec<Vec<u8>>, SoundError> {
+        let mut packets = Vec::new();
+        let mut buffer = [0; MAX_PACKET_SIZE];
+        for sample in samples {
+            self.pending.push(*sample);
+            if self.pending.len() == VOICE_FRAME_SIZE {
+                let size = self.encoder.encode_float(&self.pending, &mut buffer)?;
+                packets.push(buffer[..size].to_vec());
+                self.pending.clear();
+            }
+        }
+        Ok(packets)
+    }
+
+    /// Discards the samples, that do not fill a whole frame, and resets the state of the encoder.
+    /// Should be called when the stream is interrupted (for example, when push-to-talk is released).
+    pub fn reset(&mut self) -> Result<(), SoundError> {
+        self.pending.clear();
+        Ok(self.encoder.reset_state()?)
+    }
+}
+
+/// Decompresses Opus packets made by [`VoiceEncoder`] into mono samples with [`VOICE_SAMPLE_RATE`]
+/// sample rate, that could be written to a [`super::VoiceStream`].
+pub struct VoiceDecoder {
+    decoder: opus::Decoder,
+}
+
+impl Debug for VoiceDecoder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VoiceDecoder").finish()
+    }
+}
+
+impl VoiceDecoder {
+    /// Creates a new decoder.
+    pub fn new() -> Result<Self, SoundError> {
+        Ok(Self {
+            decoder: opus::Decoder::new(VOICE_SAMPLE_RATE as u32, opus::Channels::Mono)?,
+        })
+    }
+
+    /// Decodes the given packet. Pass `None`, if a packet was lost - the decoder will produce a
+    /// frame, that conceals the loss.
+    pub fn decode(&mut self, packet: Option<&[u8]>) -> Result<Vec<f32>, SoundError> {
+        // A packet may contain up to 120 ms of audio, a lost packet is concealed with one frame.
+        let max_samples = if packet.is_some() {
+            VOICE_FRAME_SIZE * 6
+        } else {
+            VOICE_FRAME_SIZE
+        };
+        let mut samples = vec![0.0; max_samples];
+        let count = self
+            .decoder
+            .decode_float(packet.unwrap_or_default(), &mut samples, false)?;
+        samples.truncate(count);
+        Ok(samples)
+    }
+
+    /// Resets the state of the decoder. Should be called when a new stream starts.
+    pub fn reset(&mut self) -> Result<(), SoundError> {
+        Ok(self.decoder.reset_state()?)
+    }
+}
@@ -0,0 +1,321 @@
+//! Voice chat support - microphone capture, Opus encoding/decoding and low-latency playback of
+//! streamed samples.
+//!
+//! # Overview
+//!
+//! The module does not send anything over the network, it only provides the building blocks, that
+//! could be connected to any transport:
+//!
+//! - [`VoiceCapture`] - captures samples from a microphone (requires `voice` feature).
+//! - [`VoiceEncoder`] and [`VoiceDecoder`] - compress captured samples into small Opus packets and
+//!   decompress them back (requires `voice` feature).
+//! - [`VoiceStream`] - a streaming data source, that plays samples as soon as they arrive. It is
+//!   used as a buffer of a usual sound source, so voices could be spatial, could be sent to effects,
+//!   etc.
+//!
+//! # Usage
+//!
+//! ```no_run
+//! # #[cfg(feature = "voice")]
+//! # fn example() -> Result<(), fyrox_sound::error::SoundError> {
+//! use fyrox_sound::{
+//!     context::SoundContext,
+//!     source::{SoundSourceBuilder, Status},
+//!     voice::{VoiceCapture, VoiceDecoder, VoiceEncoder, VoiceStream, VOICE_SAMPLE_RATE},
+//! };
+//!
+//! // Sending side.
+//! let capture = VoiceCapture::new()?;
+//! let mut encoder = VoiceEncoder::new()?;
+//! let mut samples = Vec::new();
+//!
+//! // Receiving side.
+//! let context = SoundContext::new();
+//! let mut decoder = VoiceDecoder::new()?;
+//! let (stream, writer) = VoiceStream::new(VOICE_SAMPLE_RATE, 1);
+//! let source = SoundSourceBuilder::new()
+//!     .with_buffer(stream.into_buffer())
+//!     .with_status(Status::Playing)
+//!     .build()?;
+//! context.state().add_source(source);
+//!
+//! // Every frame:
+//! capture.read_samples(&mut samples);
+//! for packet in encoder.encode(&samples)? {
+//!     // Send the packet over the network, and on the other side:
+//!     writer.write(&decoder.decode(Some(packet.as_slice()))?);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::buffer::{
+    DataSource, RawStreamingDataSource, SoundBuffer, SoundBufferResource,
+    SoundBufferResourceExtension,
+};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+#[cfg(feature = "voice")]
+mod capture;
+#[cfg(feature = "voice")]
+mod codec;
+
+#[cfg(feature = "voice")]
+pub use capture::VoiceCapture;
+#[cfg(feature = "voice")]
+pub use codec::{VoiceDecoder, VoiceEncoder};
+
+/// Sample rate of voice samples. Captured samples are resampled to this rate, encoder and decoder
+/// use it as well.
+pub const VOICE_SAMPLE_RATE: usize = 48000;
+
+/// Amount of samples in a single voice frame (20 ms), that is encoded into a single packet.
+pub const VOICE_FRAME_SIZE: usize = VOICE_SAMPLE_RATE / 50;
+
+#[derive(Debug)]
+struct VoiceQueue {
+    samples: VecDeque<f32>,
+    max_buffered: usize,
+    // Set when the queue runs out of samples, playback resumes when there's enough samples again.
+    starving: bool,
+}
+
+/// A handle, that is used to pass samples to a [`VoiceStream`]. It could be cloned and sent to
+/// other threads (a network thread, for example).
+#[derive(Clone, Debug)]
+pub struct VoiceStreamWriter {
+    queue: Arc<Mutex<VoiceQueue>>,
+}
+
+impl VoiceStreamWriter {
+    /// Adds the given interleaved samples to the end of the stream. If the stream has more samples
+    /// than its maximum latency allows, the oldest samples are dropped.
+    pub fn write(&self, samples: &[f32]) {
+        let mut queue = self.queue.lock().unwrap();
+        queue.samples.extend(samples.iter().copied());
+        let excess = queue.samples.len().saturating_sub(queue.max_buffered);
+        queue.samples.drain(..excess);
+    }
+
+    /// Returns amount of samples, that are waiting for playback.
+    pub fn buffered(&self) -> usize {
+        self.queue.lock().unwrap().samples.len()
+    }
+
+    /// Removes every sample from the stream.
+    pub fn clear(&self) {
+        let mut queue = self.queue.lock().unwrap();
+        queue.samples.clear();
+        queue.starving = true;
+    }
+}
+
+/// A streaming data source, that plays samples written by a [`VoiceStreamWriter`]. It never ends,
+/// if there's no samples the silence is produced, so a sound source with the stream could be kept
+/// playing all the time. The stream reads the samples in small blocks (10 ms), so the latency is
+/// much lower than of usual streaming buffers.
+///
+/// Network packets arrive irregularly, so the stream accumulates some samples (see
+/// [`Self::with_min_latency`]) after it runs out of them, before resuming the playback. It also
+/// drops the oldest samples, if there's too many of them (see [`Self::with_max_latency`]), so the
+/// delay does not grow over time.
+#[derive(Debug)]
+pub struct VoiceStream {
+    queue: Arc<Mutex<VoiceQueue>>,
+    sample_rate: usize,
+    channel_count: usize,
+    min_buffered: usize,
+}
+
+impl VoiceStream {
+    /// Default amount of time (in seconds) of audio, that must be accumulated before playback.
+    pub const DEFAULT_MIN_LATENCY: f32 = 0.06;
+
+    /// Default maximum amount of time (in seconds) of audio, that could be accumulated.
+    pub const DEFAULT_MAX_LATENCY: f32 = 0.5;
+
+    /// Creates a new stream with the given sample rate and channel count and a writer for it.
+    pub fn new(sample_rate: usize, channel_count: usize) -> (Self, VoiceStreamWriter) {
+        let channel_count = channel_count.max(1);
+        let queue = Arc::new(Mutex::new(VoiceQueue {
+            samples: Default::default(),
+            max_buffered: 0,
+            starving: true,
+        }));
+        let stream = Self {
+            queue: queue.clone(),
+            sample_rate,
+            channel_count,
+            min_buffered: 0,
+        }
+        .with_min_latency(Self::DEFAULT_MIN_LATENCY)
+        .with_max_latency(Self::DEFAULT_MAX_LATENCY);
+        (stream, VoiceStreamWriter { queue })
+    }
+
+    // Converts the given time to the amount of samples, keeping frames of interleaved samples whole.
+    fn samples_for(&self, seconds: f32) -> usize {
+        let frames = (seconds.max(0.0) * self.sample_rate as f32).round() as usize;
+        frames * self.channel_count
+    }
+
+    /// Sets the amount of time (in seconds) of audio, that must be accumulated before playback is
+    /// resumed after the stream runs out of samples. Larger values make playback smoother on
+    /// unstable connections, but increase the delay.
+    pub fn with_min_latency(mut self, seconds: f32) -> Self {
+        self.min_buffered = self.samples_for(seconds);
+        self
+    }
+
+    /// Sets the maximum amount of time (in seconds) of audio, that could be accumulated. When the
+    /// writer adds more, the oldest samples are dropped.
+    pub fn with_max_latency(self, seconds: f32) -> Self {
+        self.queue.lock().unwrap().max_buffered = self.samples_for(seconds);
+        self
+    }
+
+    /// Creates a streaming sound buffer with the stream, that could be used by a sound source.
+    pub fn into_buffer(self) -> SoundBufferResource {
+        SoundBufferResource::new_streaming(DataSource::RawStreaming(Box::new(self)))
+            .expect("Raw streaming data source must be accepted by streaming buffer!")
+    }
+
+    /// Same as [`Self::into_buffer`], but returns raw sound buffer.
+    pub fn into_raw_buffer(self) -> SoundBuffer {
+        SoundBuffer::raw_streaming(DataSource::RawStreaming(Box::new(self)))
+            .expect("Raw streaming data source must be accepted by streaming buffer!")
+    }
+}
+
+impl Iterator for VoiceStream {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut queue = self.queue.lock().unwrap();
+        if queue.starving {
+            if queue.samples.len() < self.min_buffered.max(1) {
+                return Some(0.0);
+            }
+            queue.starving = false;
+        }
+        match queue.samples.pop_front() {
+            Some(sample) => Some(sample),
+            None => {
+                queue.starving = true;
+                Some(0.0)
+            }
+        }
+    }
+}
+
+impl RawStreamingDataSource for VoiceStream {
+    fn sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+
+    fn channel_count(&self) -> usize {
+        self.channel_count
+    }
+
+    fn block_size(&self) -> usize {
+        // 10 ms
+        (self.sample_rate / 100).max(1)
+    }
+}
+
+/// Resamples the given mono samples using linear interpolation. `phase` keeps the position between
+/// calls, so the stream could be resampled in chunks without clicks.
+#[cfg_attr(not(feature = "voice"), allow(dead_code))]
+pub(crate) fn resample_linear(
+    input: &[f32],
+    from_rate: usize,
+    to_rate: usize,
+    last_sample: &mut f32,
+    phase: &mut f64,
+    output: &mut Vec<f32>,
+) {
+    if from_rate == to_rate {
+        output.extend_from_slice(input);
+        if let Some(last) = input.last() {
+            *last_sample = *last;
+        }
+        return;
+    }
+
+    let step = from_rate as f64 / to_rate as f64;
+    // Position is relative to the last sample of the previous chunk, that has index -1.
+    while *phase < input.len() as f64 - 1.0 {
+        let index = phase.floor();
+        let t = (*phase - index) as f32;
+        let a = if index < 0.0 {
+            *last_sample
+        } else {
+            input[index as usize]
+        };
+        let b = input[(index + 1.0) as usize];
+        output.push(a + (b - a) * t);
+        *phase += step;
+    }
+    *phase -= input.len() as f64;
+    if let Some(last) = input.last() {
+        *last_sample = *last;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::voice::{resample_linear, VoiceStream};
+
+    #[test]
+    fn test_voice_stream_buffering() {
+        let (stream, writer) = VoiceStream::new(1000, 1);
+        let mut stream = stream.with_min_latency(0.004);
+
+        // Not enough samples - silence.
+        writer.write(&[1.0, 2.0]);
+        assert_eq!(stream.next(), Some(0.0));
+
+        writer.write(&[3.0, 4.0]);
+        assert_eq!(stream.next(), Some(1.0));
+        assert_eq!(stream.next(), Some(2.0));
+        assert_eq!(stream.next(), Some(3.0));
+        assert_eq!(stream.next(), Some(4.0));
+
+        // Out of samples - silence until enough samples are accumulated again.
+        assert_eq!(stream.next(), Some(0.0));
+        writer.write(&[5.0]);
+        assert_eq!(stream.next(), Some(0.0));
+    }
+
+    #[test]
+    fn test_voice_stream_max_latency() {
+        let (stream, writer) = VoiceStream::new(1000, 1);
+        let mut stream = stream.with_min_latency(0.0).with_max_latency(0.002);
+        writer.write(&[1.0, 2.0, 3.0]);
+        assert_eq!(writer.buffered(), 2);
+        assert_eq!(stream.next(), Some(2.0));
+        assert_eq!(stream.next(), Some(3.0));
+    }
+
+    #[test]
+    fn test_resample_linear() {
+        let mut output = Vec::new();
+        let (mut last, mut phase) = (0.0, 0.0);
+        resample_linear(
+            &[0.0, 1.0, 2.0, 3.0],
+            2,
+            4,
+            &mut last,
+            &mut phase,
+            &mut output,
+        );
+        resample_linear(&[4.0, 5.0], 2, 4, &mut last, &mut phase, &mut output);
+        assert_eq!(
+            output,
+            vec![0.0, 0.5, 1.0, 1.5, 2.0, 2.5, 3.0, 3.5, 4.0, 4.5]
+        );
+    }
+}
@@ -88,7 +88,9 @@ pub mod prelude {
     };
     pub use crate::generic_animation::machine::{
         node::AnimationEventCollectionStrategy,
-        parameter::{Parameter, ParameterContainer, ParameterDefinition, PoseWeight},
+        parameter::{
+            CurveParameter, Parameter, ParameterContainer, ParameterDefinition, PoseWeight,
+        },
     };
 }
 
@@ -0,0 +1,271 @@
+//! Accessibility support - exposes widgets to assistive technologies (screen readers, etc.) using
+//! [AccessKit](https://accesskit.dev).
+//!
+//! Every widget has a role (see [`AccessibilityRole`]) and an optional label. The standard widgets
+//! set their roles automatically, custom widgets could set it using
+//! [`crate::widget::WidgetBuilder::with_accessibility_role`]. Widgets with [`AccessibilityRole::None`]
+//! are not exposed, their children are exposed as children of the nearest exposed ancestor.
+//! Widget-specific data (values, checked state, etc.) is provided by
+//! [`crate::Control::accessibility_node`].
+//!
+//! The user interface does not talk to the OS directly, instead it builds a tree update (see
+//! [`crate::UserInterface::accessibility_tree_update`]), that should be passed to a platform
+//! adapter (for example, `accesskit_winit`). Action requests from the adapter should be passed back
+//! to [`crate::UserInterface::handle_accessibility_action`].
+//!
+//! ```rust
+//! # use fyrox_ui::{
+//! #     accessibility::AccessibilityRole, core::pool::Handle, image::ImageBuilder,
+//! #     widget::WidgetBuilder, BuildContext, UiNode,
+//! # };
+//! fn create_health_icon(ctx: &mut BuildContext) -> Handle<UiNode> {
+//!     // Images are decorative by default, so they're exposed only with an explicit role.
+//!     ImageBuilder::new(
+//!         WidgetBuilder::new()
+//!             .with_accessibility_role(AccessibilityRole::Image)
+//!             .with_accessibility_label("Health"),
+//!     )
+//!     .build(ctx)
+//! }
+//! ```
+
+use crate::{
+    core::{pool::Handle, reflect::prelude::*, uuid_provider, visitor::prelude::*},
+    text::Text,
+    UiNode, UserInterface,
+};
+use accesskit::{Action, NodeBuilder, NodeClassSet, NodeId, Rect, Role, Tree, TreeUpdate};
+use fyrox_graph::BaseSceneGraph;
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Role of a widget for assistive technologies. It is a small subset of AccessKit roles, that is
+/// enough for game user interfaces.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum AccessibilityRole {
+    /// The widget is not exposed to assistive technologies, but its children could be.
+    #[default]
+    None,
+    /// A container for other widgets.
+    Group,
+    /// A clickable button.
+    Button,
+    /// A check box.
+    CheckBox,
+    /// A non-editable text.
+    Label,
+    /// An editable text.
+    TextInput,
+    /// An image.
+    Image,
+    /// A scroll bar.
+    ScrollBar,
+    /// A slider.
+    Slider,
+    /// A progress bar.
+    ProgressBar,
+    /// A list of items.
+    List,
+    /// An item of a list.
+    ListItem,
+    /// A drop-down list.
+    ComboBox,
+    /// A menu.
+    Menu,
+    /// An item of a menu.
+    MenuItem,
+    /// A tree.
+    Tree,
+    /// An item of a tree.
+    TreeItem,
+    /// A tab header.
+    Tab,
+    /// A window.
+    Window,
+    /// A modal dialog.
+    Dialog,
+}
+
+uuid_provider!(AccessibilityRole = "c8cb928f-93fd-4c45-ace7-ddf071c68e8c");
+
+impl From<AccessibilityRole> for Role {
+    fn from(role: AccessibilityRole) -> Self {
+        match role {
+            AccessibilityRole::None => Role::Unknown,
+            AccessibilityRole::Group => Role::Group,
+            AccessibilityRole::Button => Role::Button,
+            AccessibilityRole::CheckBox => Role::CheckBox,
+            AccessibilityRole::Label => Role::StaticText,
+            AccessibilityRole::TextInput => Role::TextInput,
+            AccessibilityRole::Image => Role::Image,
+            AccessibilityRole::ScrollBar => Role::ScrollBar,
+            AccessibilityRole::Slider => Role::Slider,
+            AccessibilityRole::ProgressBar => Role::ProgressIndicator,
+            AccessibilityRole::List => Role::List,
+            AccessibilityRole::ListItem => Role::ListItem,
+            AccessibilityRole::ComboBox => Role::ComboBox,
+            AccessibilityRole::Menu => Role::Menu,
+            AccessibilityRole::MenuItem => Role::MenuItem,
+            AccessibilityRole::Tree => Role::Tree,
+            AccessibilityRole::TreeItem => Role::TreeItem,
+            AccessibilityRole::Tab => Role::Tab,
+            AccessibilityRole::Window => Role::Window,
+            AccessibilityRole::Dialog => Role::Dialog,
+        }
+    }
+}
+
+impl AccessibilityRole {
+    // Widgets of these roles take their name from the text widgets inside them, if the name was
+    // not set explicitly.
+    fn name_from_content(self) -> bool {
+        matches!(
+            self,
+            Self::Button
+                | Self::CheckBox
+                | Self::ListItem
+                | Self::MenuItem
+                | Self::TreeItem
+                | Self::Tab
+                | Self::Window
+                | Self::Dialog
+        )
+    }
+}
+
+/// Converts a widget handle into AccessKit node id.
+pub fn node_id(handle: Handle<UiNode>) -> NodeId {
+    NodeId(((handle.generation() as u64) << 32) | handle.index() as u64)
+}
+
+/// Converts AccessKit node id back into a widget handle.
+pub fn widget_handle(id: NodeId) -> Handle<UiNode> {
+    Handle::new(id.0 as u32, (id.0 >> 32) as u32)
+}
+
+fn is_exposed(ui: &UserInterface, handle: Handle<UiNode>) -> bool {
+    ui.try_get(handle).map_or(false, |w| {
+        handle == ui.root()
+            || (w.is_globally_visible() && *w.accessibility_role != AccessibilityRole::None)
+    })
+}
+
+fn collect_exposed_children(
+    ui: &UserInterface,
+    handle: Handle<UiNode>,
+    children: &mut Vec<Handle<UiNode>>,
+) {
+    for &child in ui.node(handle).children() {
+        if !ui.node(child).is_globally_visible() {
+            continue;
+        }
+        if is_exposed(ui, child) {
+            children.push(child);
+        } else {
+            collect_exposed_children(ui, child, children);
+        }
+    }
+}
+
+fn collect_text(ui: &UserInterface, handle: Handle<UiNode>, text: &mut String) {
+    let node = ui.node(handle);
+    if !node.is_globally_visible() {
+        return;
+    }
+    if let Some(text_widget) = node.query_component::<Text>() {
+        let content = text_widget.text();
+        if !content.is_empty() {
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            text.push_str(&content);
+        }
+    }
+    for &child in node.children() {
+        collect_text(ui, child, text);
+    }
+}
+
+pub(crate) fn build_tree_update(ui: &UserInterface) -> TreeUpdate {
+    let mut classes = NodeClassSet::lock_global();
+    let mut nodes = Vec::new();
+    let mut stack = vec![ui.root()];
+    while let Some(handle) = stack.pop() {
+        let widget = ui.node(handle);
+        let role = *widget.accessibility_role;
+
+        let mut builder = NodeBuilder::new(if handle == ui.root() {
+            Role::Window
+        } else {
+            role.into()
+        });
+
+        let bounds = widget.screen_bounds();
+        builder.set_bounds(Rect {
+            x0: bounds.x() as f64,
+            y0: bounds.y() as f64,
+            x1: (bounds.x() + bounds.w()) as f64,
+            y1: (bounds.y() + bounds.h()) as f64,
+        });
+
+        let mut children = Vec::new();
+        collect_exposed_children(ui, handle, &mut children);
+        builder.set_children(children.iter().map(|c| node_id(*c)).collect::<Vec<_>>());
+        stack.extend(children);
+
+        if !widget.enabled() {
+            builder.set_disabled();
+        }
+        if widget.accepts_input {
+            builder.add_action(Action::Focus);
+        }
+
+        widget.accessibility_node(ui, &mut builder);
+
+        if !widget.accessibility_label.is_empty() {
+            builder.set_name(widget.accessibility_label.as_str());
+        } else if builder.name().is_none() && role.name_from_content() {
+            let mut text = String::new();
+            for &child in widget.children() {
+                collect_text(ui, child, &mut text);
+            }
+            if !text.is_empty() {
+                builder.set_name(text);
+            }
+        }
+
+        nodes.push((node_id(handle), builder.build(&mut classes)));
+    }
+
+    // Focused widget might be not exposed (for example, an inner field of a complex widget), use
+    // its nearest exposed ancestor instead.
+    let mut focus = ui.keyboard_focus_node;
+    while focus.is_some() && !is_exposed(ui, focus) {
+        focus = ui.node(focus).parent();
+    }
+    if focus.is_none() {
+        focus = ui.root();
+    }
+
+    TreeUpdate {
+        nodes,
+        tree: Some(Tree {
+            toolkit_name: Some("Fyrox".to_string()),
+            toolkit_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            ..Tree::new(node_id(ui.root()))
+        }),
+        focus: node_id(focus),
+    }
+}
@@ -3,6 +3,7 @@
 #![warn(missing_docs)]
 
 use crate::{
+    accessibility::AccessibilityRole,
     border::BorderBuilder,
     core::{
         pool::Handle, reflect::prelude::*, type_traits::prelude::*, variable::InheritableVariable,
@@ -130,6 +131,11 @@ impl Control for Button {
         }
     }
 
+    fn accessibility_node(&self, _ui: &UserInterface, node: &mut accesskit::NodeBuilder) {
+        node.add_action(accesskit::Action::Default);
+        node.set_default_action_verb(accesskit::DefaultActionVerb::Click);
+    }
+
     fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
         self.widget.handle_routed_message(ui, message);
 
@@ -167,6 +173,15 @@ impl Control for Button {
                             message.set_handled(true);
                         }
                     }
+                    WidgetMessage::AccessibilityAction(accesskit::Action::Default)
+                        if message.destination() == self.handle =>
+                    {
+                        ui.send_message(ButtonMessage::click(
+                            self.handle,
+                            MessageDirection::FromWidget,
+                        ));
+                        message.set_handled(true);
+                    }
                     _ => (),
                 }
             }
@@ -374,6 +389,7 @@ impl ButtonBuilder {
                 .widget_builder
                 .with_accepts_input(true)
                 .with_need_update(true)
+                .with_default_accessibility_role(AccessibilityRole::Button)
                 .with_child(back)
                 .build(),
             decorator: back.into(),
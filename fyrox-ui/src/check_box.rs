@@ -5,6 +5,7 @@
 #![warn(missing_docs)]
 
 use crate::{
+    accessibility::AccessibilityRole,
     border::BorderBuilder,
     brush::Brush,
     core::{
@@ -145,6 +146,20 @@ pub struct CheckBox {
 crate::define_widget_deref!(CheckBox);
 
 impl Control for CheckBox {
+    fn accessibility_node(&self, _ui: &UserInterface, node: &mut accesskit::NodeBuilder) {
+        node.set_checked(match *self.checked {
+            Some(true) => accesskit::Checked::True,
+            Some(false) => accesskit::Checked::False,
+            None => accesskit::Checked::Mixed,
+        });
+        node.add_action(accesskit::Action::Default);
+        node.set_default_action_verb(if *self.checked == Some(true) {
+            accesskit::DefaultActionVerb::Uncheck
+        } else {
+            accesskit::DefaultActionVerb::Check
+        });
+    }
+
     fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
         self.widget.handle_routed_message(ui, message);
 
@@ -192,6 +207,16 @@ impl Control for CheckBox {
                         message.set_handled(true);
                     }
                 }
+                WidgetMessage::AccessibilityAction(accesskit::Action::Default)
+                    if message.destination() == self.handle =>
+                {
+                    ui.send_message(CheckBoxMessage::checked(
+                        self.handle,
+                        MessageDirection::ToWidget,
+                        Some(*self.checked != Some(true)),
+                    ));
+                    message.set_handled(true);
+                }
                 _ => (),
             }
         } else if let Some(&CheckBoxMessage::Check(value)) = message.data::<CheckBoxMessage>() {
@@ -421,6 +446,7 @@ impl CheckBoxBuilder {
             widget: self
                 .widget_builder
                 .with_accepts_input(true)
+                .with_default_accessibility_role(AccessibilityRole::CheckBox)
                 .with_child(grid)
                 .build(),
             checked: self.checked.into(),
@@ -320,4 +320,14 @@ pub trait Control:
         #[allow(unused_variables)] event: &OsEvent,
     ) {
     }
+
+    /// Fills a node of the accessibility tree with widget-specific data (a value, checked state,
+    /// supported actions, etc.). Role, label, bounds and children are set by the user interface.
+    /// See [`crate::accessibility`] module docs for more info.
+    fn accessibility_node(
+        &self,
+        #[allow(unused_variables)] ui: &UserInterface,
+        #[allow(unused_variables)] node: &mut accesskit::NodeBuilder,
+    ) {
+    }
 }
@@ -5,6 +5,7 @@
 #![warn(missing_docs)]
 
 use crate::{
+    accessibility::AccessibilityRole,
     border::BorderBuilder,
     core::{
         algebra::Vector2, pool::Handle, reflect::prelude::*, type_traits::prelude::*,
@@ -463,6 +464,7 @@ impl DropdownListBuilder {
             widget: self
                 .widget_builder
                 .with_accepts_input(true)
+                .with_default_accessibility_role(AccessibilityRole::ComboBox)
                 .with_preview_messages(true)
                 .with_child(
                     BorderBuilder::new(
@@ -116,6 +116,8 @@ where
         max_value: array_property_info.max_value,
         step: array_property_info.step,
         precision: array_property_info.precision,
+        units: array_property_info.units,
+        editor: None,
         description: array_property_info.description,
         type_name: array_property_info.type_name,
         doc: array_property_info.doc,
@@ -421,6 +423,7 @@ where
                                     environment: ctx.environment.clone(),
                                     name: "",
                                     owner_type_id: ctx.owner_type_id,
+                                    editor: None,
                                     message,
                                     definition_container: ctx.definition_container.clone(),
                                 })?
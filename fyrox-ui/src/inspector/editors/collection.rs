@@ -236,6 +236,8 @@ where
         max_value: collection_property_info.max_value,
         step: collection_property_info.step,
         precision: collection_property_info.precision,
+        units: collection_property_info.units,
+        editor: None,
         description: collection_property_info.description,
         type_name: collection_property_info.type_name,
         doc: collection_property_info.doc,
@@ -622,6 +624,7 @@ where
                                     environment: ctx.environment.clone(),
                                     name: "",
                                     owner_type_id: ctx.owner_type_id,
+                                    editor: None,
                                     message,
                                     definition_container: ctx.definition_container.clone(),
                                 })?
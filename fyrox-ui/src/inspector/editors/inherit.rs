@@ -211,6 +211,8 @@ where
         max_value: property_info.max_value,
         step: property_info.step,
         precision: property_info.precision,
+        units: property_info.units,
+        editor: property_info.editor,
         description: property_info.description,
         type_name: property_info.type_name,
         doc: property_info.doc,
@@ -231,8 +233,7 @@ where
     ) -> Result<PropertyEditorInstance, InspectorError> {
         if let Some(definition) = ctx
             .definition_container
-            .definitions()
-            .get(&TypeId::of::<T>())
+            .definition_for(TypeId::of::<T>(), ctx.property_info.editor)
         {
             let instance =
                 definition
@@ -284,8 +285,7 @@ where
     ) -> Result<Option<UiMessage>, InspectorError> {
         if let Some(definition) = ctx
             .definition_container
-            .definitions()
-            .get(&TypeId::of::<T>())
+            .definition_for(TypeId::of::<T>(), ctx.property_info.editor)
         {
             let instance = ctx
                 .ui
@@ -332,14 +332,14 @@ where
         // Try translate other messages using inner property editor.
         if let Some(definition) = ctx
             .definition_container
-            .definitions()
-            .get(&TypeId::of::<T>())
+            .definition_for(TypeId::of::<T>(), ctx.editor)
         {
             return definition.property_editor.translate_message(
                 PropertyEditorTranslationContext {
                     environment: ctx.environment.clone(),
                     name: ctx.name,
                     owner_type_id: ctx.owner_type_id,
+                    editor: ctx.editor,
                     message: ctx.message,
                     definition_container: ctx.definition_container.clone(),
                 },
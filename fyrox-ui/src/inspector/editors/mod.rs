@@ -4,6 +4,7 @@
 use crate::inspector::editors::path::PathPropertyEditorDefinition;
 use crate::{
     absm::{EventAction, EventKind},
+    accessibility::AccessibilityRole,
     bit::BitField,
     border::Border,
     brush::{Brush, GradientPoint},
@@ -15,7 +16,7 @@ use crate::{
         color_gradient::ColorGradient,
         math::curve::Curve,
         math::{Rect, SmoothAngle},
-        parking_lot::{Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard},
+        parking_lot::{MappedRwLockReadGuard, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard},
         pool::Handle,
         reflect::{FieldInfo, FieldValue, Reflect},
         uuid::Uuid,
@@ -44,6 +45,7 @@ use crate::{
             range::RangePropertyEditorDefinition,
             rect::RectPropertyEditorDefinition,
             refcell::RefCellPropertyEditorDefinition,
+            slider::{SliderPropertyEditorDefinition, SLIDER_EDITOR_NAME},
             string::StringPropertyEditorDefinition,
             utf32::Utf32StringPropertyEditorDefinition,
             uuid::UuidPropertyEditorDefinition,
@@ -114,6 +116,7 @@ pub mod quat;
 pub mod range;
 pub mod rect;
 pub mod refcell;
+pub mod slider;
 pub mod string;
 pub mod utf32;
 pub mod uuid;
@@ -214,6 +217,9 @@ pub struct PropertyEditorTranslationContext<'b, 'c> {
     /// The type of the object whose property is being edited.
     /// This comes from [ContextEntry::property_owner_type_id](crate::inspector::ContextEntry).
     pub owner_type_id: TypeId,
+    /// A name of a custom property editor that was used to edit the property (if any).
+    /// This comes from [ContextEntry::property_editor_name](crate::inspector::ContextEntry).
+    pub editor: Option<&'b str>,
     /// The original message that may be translated, if it represents a change in the property.
     pub message: &'c UiMessage,
     /// The list of the Inspectors property editors.
@@ -324,6 +330,8 @@ pub struct PropertyEditorDefinitionContainer {
     /// that plugin.
     pub context_type_id: Mutex<TypeId>,
     definitions: RwLock<FxHashMap<TypeId, PropertyEditorDefinitionContainerEntry>>,
+    named_definitions:
+        RwLock<FxHashMap<String, FxHashMap<TypeId, PropertyEditorDefinitionContainerEntry>>>,
 }
 
 impl Debug for PropertyEditorDefinitionContainer {
@@ -337,6 +345,7 @@ impl Default for PropertyEditorDefinitionContainer {
         Self {
             context_type_id: Mutex::new(().type_id()),
             definitions: Default::default(),
+            named_definitions: Default::default(),
         }
     }
 }
@@ -357,6 +366,14 @@ macro_rules! reg_property_editor {
     }
 }
 
+macro_rules! reg_named_property_editor {
+    ($container:ident, $name:expr, $base:ident:$init:ident, $($ty:ty),*) => {
+        $(
+             $container.insert_named($name, $base::<$ty>::$init());
+        )*
+    }
+}
+
 macro_rules! reg_inspectables {
     ($container:ident, $($ty:ty),*) => {
         $(
@@ -391,6 +408,7 @@ impl PropertyEditorDefinitionContainer {
         // NumericType + InheritableVariable<NumericType>
         reg_property_editor! { container, NumericPropertyEditorDefinition: default, f64, f32, i64, u64, i32, u32, i16, u16, i8, u8, usize, isize }
         reg_property_editor! { container, InheritablePropertyEditorDefinition: new, f64, f32, i64, u64, i32, u32, i16, u16, i8, u8, usize, isize }
+        reg_named_property_editor! { container, SLIDER_EDITOR_NAME, SliderPropertyEditorDefinition: default, f64, f32, i64, u64, i32, u32, i16, u16, i8, u8, usize, isize }
 
         // Vector4<NumericType> + InheritableVariable<Vector4>
         reg_property_editor! { container, Vec4PropertyEditorDefinition: default, f64, f32, i64, u64, i32, u32, i16, u16, i8, u8, usize, isize }
@@ -490,6 +508,7 @@ impl PropertyEditorDefinitionContainer {
         container.register_inheritable_enum::<WrapMode, _>();
         container.register_inheritable_enum::<Primitive, _>();
         container.register_inheritable_enum::<SizeMode, _>();
+        container.register_inheritable_enum::<AccessibilityRole, _>();
         container.insert(EnumPropertyEditorDefinition::<CursorIcon>::new());
         container.insert(EnumPropertyEditorDefinition::<CursorIcon>::new_optional());
         container.insert(EnumPropertyEditorDefinition::<bool>::new_optional());
@@ -670,6 +689,11 @@ impl PropertyEditorDefinitionContainer {
         for (_, definition) in other.definitions.into_inner() {
             self.insert_raw(definition.property_editor);
         }
+        for (name, definitions) in other.named_definitions.into_inner() {
+            for (_, definition) in definitions {
+                self.insert_named_raw(&name, definition.property_editor);
+            }
+        }
     }
 
     /// Move a PropertyEditorDefinition into the list, where it will automatically be boxed.
@@ -688,6 +712,68 @@ impl PropertyEditorDefinitionContainer {
         )
     }
 
+    /// Add an already boxed dynamic PropertyEditorDefinition under the given name. Named editors
+    /// are used only for the properties that explicitly request them using
+    /// `#[reflect(editor = "name")]` attribute. There could be multiple editors with the same name
+    /// for different types. If this container already had a named PropertyEditorDefinition for the
+    /// same name and type, the old property editor is removed and returned.
+    pub fn insert_named_raw(
+        &self,
+        name: &str,
+        definition: Box<dyn PropertyEditorDefinition>,
+    ) -> Option<PropertyEditorDefinitionContainerEntry> {
+        self.named_definitions
+            .write()
+            .entry(name.to_string())
+            .or_default()
+            .insert(
+                definition.value_type_id(),
+                PropertyEditorDefinitionContainerEntry {
+                    source_type_id: *self.context_type_id.lock(),
+                    property_editor: definition,
+                },
+            )
+    }
+
+    /// Move a named PropertyEditorDefinition into the list, where it will automatically be boxed.
+    /// See [`Self::insert_named_raw`] for more info.
+    pub fn insert_named<T>(
+        &self,
+        name: &str,
+        definition: T,
+    ) -> Option<PropertyEditorDefinitionContainerEntry>
+    where
+        T: PropertyEditorDefinition + 'static,
+    {
+        self.insert_named_raw(name, Box::new(definition))
+    }
+
+    /// Searches for a property editor definition for a property of the given type. If the editor
+    /// name is specified and there is a named editor for the type, then it will be returned.
+    /// Otherwise the default editor for the type will be returned.
+    pub fn definition_for(
+        &self,
+        type_id: TypeId,
+        editor: Option<&str>,
+    ) -> Option<MappedRwLockReadGuard<PropertyEditorDefinitionContainerEntry>> {
+        if let Some(editor) = editor {
+            if let Ok(definition) =
+                RwLockReadGuard::try_map(self.named_definitions.read(), |definitions| {
+                    definitions
+                        .get(editor)
+                        .and_then(|definitions| definitions.get(&type_id))
+                })
+            {
+                return Some(definition);
+            }
+        }
+
+        RwLockReadGuard::try_map(self.definitions.read(), |definitions| {
+            definitions.get(&type_id)
+        })
+        .ok()
+    }
+
     /// Inserts the default property editor for `Vec<T>` and `InheritableVariable<Vec<T>>`.
     /// Panic if these types already have editor definitions.
     pub fn register_inheritable_vec_collection<T>(&self)
@@ -767,4 +853,21 @@ impl PropertyEditorDefinitionContainer {
     ) -> RwLockWriteGuard<FxHashMap<TypeId, PropertyEditorDefinitionContainerEntry>> {
         self.definitions.write()
     }
+
+    /// Direct read-only access to all the named editor definitions.
+    pub fn named_definitions(
+        &self,
+    ) -> RwLockReadGuard<FxHashMap<String, FxHashMap<TypeId, PropertyEditorDefinitionContainerEntry>>>
+    {
+        self.named_definitions.read()
+    }
+
+    /// Direct and unrestricted access to all the named editor definitions.
+    pub fn named_definitions_mut(
+        &self,
+    ) -> RwLockWriteGuard<
+        FxHashMap<String, FxHashMap<TypeId, PropertyEditorDefinitionContainerEntry>>,
+    > {
+        self.named_definitions.write()
+    }
 }
@@ -0,0 +1,104 @@
+//! Property editor for numeric properties that have a known range of values. It uses a
+//! [`ScrollBar`](crate::scroll_bar::ScrollBar) widget as a slider. The editor is not used by
+//! default, instead it must be requested explicitly using `#[reflect(editor = "slider")]`
+//! attribute (see [`SLIDER_EDITOR_NAME`]).
+
+use crate::{
+    core::num_traits::NumCast,
+    inspector::{
+        editors::{
+            PropertyEditorBuildContext, PropertyEditorDefinition, PropertyEditorInstance,
+            PropertyEditorMessageContext, PropertyEditorTranslationContext,
+        },
+        FieldKind, InspectorError, PropertyChanged,
+    },
+    message::{MessageDirection, UiMessage},
+    numeric::NumericType,
+    scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+    widget::WidgetBuilder,
+    Thickness,
+};
+use std::{any::TypeId, marker::PhantomData};
+
+/// A name of the slider editor, that could be used in `#[reflect(editor = "slider")]` attribute.
+/// The slider uses `min_value` and `max_value` attributes as its bounds (`0..1` by default) and
+/// `step` attribute as its step.
+pub const SLIDER_EDITOR_NAME: &str = "slider";
+
+#[derive(Debug)]
+pub struct SliderPropertyEditorDefinition<T>
+where
+    T: NumericType,
+{
+    phantom: PhantomData<T>,
+}
+
+impl<T> Default for SliderPropertyEditorDefinition<T>
+where
+    T: NumericType,
+{
+    fn default() -> Self {
+        Self {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> PropertyEditorDefinition for SliderPropertyEditorDefinition<T>
+where
+    T: NumericType,
+{
+    fn value_type_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn create_instance(
+        &self,
+        ctx: PropertyEditorBuildContext,
+    ) -> Result<PropertyEditorInstance, InspectorError> {
+        let value = ctx.property_info.cast_value::<T>()?;
+        let min = ctx.property_info.min_value.unwrap_or(0.0) as f32;
+        let max = ctx.property_info.max_value.unwrap_or(1.0) as f32;
+        Ok(PropertyEditorInstance::Simple {
+            editor: ScrollBarBuilder::new(
+                WidgetBuilder::new().with_margin(Thickness::top_bottom(1.0)),
+            )
+            .with_min(min)
+            .with_max(max)
+            .with_step(
+                ctx.property_info
+                    .step
+                    .map(|step| step as f32)
+                    .unwrap_or((max - min) / 100.0),
+            )
+            .with_value(NumCast::from(*value).unwrap_or(min))
+            .with_value_precision(ctx.property_info.precision.unwrap_or(3))
+            .show_value(true)
+            .build(ctx.build_context),
+        })
+    }
+
+    fn create_message(
+        &self,
+        ctx: PropertyEditorMessageContext,
+    ) -> Result<Option<UiMessage>, InspectorError> {
+        let value = ctx.property_info.cast_value::<T>()?;
+        Ok(NumCast::from(*value)
+            .map(|value| ScrollBarMessage::value(ctx.instance, MessageDirection::ToWidget, value)))
+    }
+
+    fn translate_message(&self, ctx: PropertyEditorTranslationContext) -> Option<PropertyChanged> {
+        if ctx.message.direction() == MessageDirection::FromWidget {
+            if let Some(ScrollBarMessage::Value(value)) = ctx.message.data() {
+                let value: T = NumCast::from(*value)?;
+                return Some(PropertyChanged {
+                    name: ctx.name.to_string(),
+                    owner_type_id: ctx.owner_type_id,
+                    value: FieldKind::object(value),
+                });
+            }
+        }
+
+        None
+    }
+}
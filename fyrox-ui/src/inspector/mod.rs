@@ -508,6 +508,8 @@ pub struct ContextEntry {
     pub property_owner_type_id: TypeId,
     /// The type of the property being edited, as found in [PropertyEditorDefinition::value_type_id](editors::PropertyEditorDefinition::value_type_id).
     pub property_value_type_id: TypeId,
+    /// A name of a custom property editor of the property, as found in [FieldInfo::editor].
+    pub property_editor_name: Option<String>,
     /// The list of property editor definitions being used by the inspector.
     pub property_editor_definition_container: Arc<PropertyEditorDefinitionContainer>,
     /// The handle of the widget that is editing the property.
@@ -530,6 +532,7 @@ impl PartialEq for ContextEntry {
         self.property_editor == other.property_editor
             && self.property_name == other.property_name
             && self.property_value_type_id ==other.property_value_type_id
+            && self.property_editor_name == other.property_editor_name
             // Compare thin pointers.
             && std::ptr::eq(ptr_a, ptr_b)
     }
@@ -815,15 +818,20 @@ impl InspectorContext {
                     continue;
                 }
 
-                let description = if info.description.is_empty() {
+                let display_name = if info.units.is_empty() {
                     info.display_name.to_string()
                 } else {
-                    format!("{}\n\n{}", info.display_name, info.description)
+                    format!("{} ({})", info.display_name, info.units)
+                };
+
+                let description = if info.description.is_empty() {
+                    display_name.clone()
+                } else {
+                    format!("{}\n\n{}", display_name, info.description)
                 };
 
-                if let Some(definition) = definition_container
-                    .definitions()
-                    .get(&info.value.type_id())
+                if let Some(definition) =
+                    definition_container.definition_for(info.value.type_id(), info.editor)
                 {
                     let editor = match definition.property_editor.create_instance(
                         PropertyEditorBuildContext {
@@ -841,7 +849,7 @@ impl InspectorContext {
                             let (container, editor) = match instance {
                                 PropertyEditorInstance::Simple { editor } => (
                                     make_simple_property_container(
-                                        create_header(ctx, info.display_name, layer_index),
+                                        create_header(ctx, &display_name, layer_index),
                                         editor,
                                         &description,
                                         ctx,
@@ -856,6 +864,7 @@ impl InspectorContext {
                             entries.push(ContextEntry {
                                 property_editor: editor,
                                 property_value_type_id: definition.property_editor.value_type_id(),
+                                property_editor_name: info.editor.map(|e| e.to_string()),
                                 property_editor_definition_container: definition_container.clone(),
                                 property_name: info.name.to_string(),
                                 property_owner_type_id: info.owner_type_id,
@@ -870,7 +879,7 @@ impl InspectorContext {
                             container
                         }
                         Err(e) => make_simple_property_container(
-                            create_header(ctx, info.display_name, layer_index),
+                            create_header(ctx, &display_name, layer_index),
                             TextBuilder::new(WidgetBuilder::new().on_row(i).on_column(1))
                                 .with_wrap(WrapMode::Word)
                                 .with_vertical_text_alignment(VerticalAlignment::Center)
@@ -888,7 +897,7 @@ impl InspectorContext {
                     editors.push(editor);
                 } else {
                     editors.push(make_simple_property_container(
-                        create_header(ctx, info.display_name, layer_index),
+                        create_header(ctx, &display_name, layer_index),
                         TextBuilder::new(WidgetBuilder::new().on_row(i).on_column(1))
                             .with_wrap(WrapMode::Word)
                             .with_vertical_text_alignment(VerticalAlignment::Center)
@@ -978,8 +987,7 @@ impl InspectorContext {
 
                 if let Some(constructor) = self
                     .property_definitions
-                    .definitions()
-                    .get(&info.value.type_id())
+                    .definition_for(info.value.type_id(), info.editor)
                 {
                     if let Some(property_editor) = self.find_property_editor(info.name) {
                         let ctx = PropertyEditorMessageContext {
@@ -1102,14 +1110,17 @@ impl Control for Inspector {
                 if message.destination() == entry.property_editor {
                     if let Some(args) = entry
                         .property_editor_definition_container
-                        .definitions()
-                        .get(&entry.property_value_type_id)
+                        .definition_for(
+                            entry.property_value_type_id,
+                            entry.property_editor_name.as_deref(),
+                        )
                         .and_then(|e| {
                             e.property_editor
                                 .translate_message(PropertyEditorTranslationContext {
                                     environment: env.clone(),
                                     name: &entry.property_name,
                                     owner_type_id: entry.property_owner_type_id,
+                                    editor: entry.property_editor_name.as_deref(),
                                     message,
                                     definition_container: self.context.property_definitions.clone(),
                                 })
@@ -179,11 +179,14 @@
 #![allow(clippy::too_many_arguments)]
 #![allow(clippy::type_complexity)]
 
+pub use accesskit;
 pub use copypasta;
 pub use fyrox_core as core;
-use message::TouchPhase;
+use message::{Force, TouchPhase};
+use pointer::{Pointer, PointerKind};
 
 pub mod absm;
+pub mod accessibility;
 mod alignment;
 pub mod animation;
 pub mod bit;
@@ -219,6 +222,7 @@ pub mod nine_patch;
 mod node;
 pub mod numeric;
 pub mod path;
+pub mod pointer;
 pub mod popup;
 pub mod progress_bar;
 pub mod range;
@@ -233,6 +237,7 @@ pub mod stack_panel;
 pub mod tab_control;
 pub mod text;
 pub mod text_box;
+pub mod theme;
 mod thickness;
 pub mod tree;
 pub mod utils;
@@ -267,6 +272,7 @@ use crate::{
         UiMessage,
     },
     popup::{Placement, PopupMessage},
+    theme::Theme,
     widget::{Widget, WidgetBuilder, WidgetMessage},
 };
 use copypasta::ClipboardContext;
@@ -694,6 +700,11 @@ pub struct UserInterface {
     #[reflect(hidden)]
     double_click_entries: FxHashMap<MouseButton, DoubleClickEntry>,
     pub double_click_time_slice: f32,
+    #[reflect(hidden)]
+    pointers: FxHashMap<u64, Pointer>,
+    scale_factor: f32,
+    #[reflect(hidden)]
+    theme: Theme,
 }
 
 impl Visit for UserInterface {
@@ -775,6 +786,9 @@ impl Clone for UserInterface {
             default_font: self.default_font.clone(),
             double_click_entries: self.double_click_entries.clone(),
             double_click_time_slice: self.double_click_time_slice,
+            pointers: self.pointers.clone(),
+            scale_factor: self.scale_factor,
+            theme: self.theme.clone(),
         }
     }
 }
@@ -1073,6 +1087,9 @@ impl UserInterface {
             default_font: BUILT_IN_FONT.clone(),
             double_click_entries: Default::default(),
             double_click_time_slice: 0.5, // 500 ms is standard in most operating systems.
+            pointers: Default::default(),
+            scale_factor: 1.0,
+            theme: Default::default(),
         };
         ui.root_canvas = ui.add_node(UiNode::new(Canvas {
             widget: WidgetBuilder::new().build(),
@@ -1104,6 +1121,40 @@ impl UserInterface {
         self.captured_node = Handle::NONE;
     }
 
+    /// Captures the pointer with the given id, so every message of the pointer will be sent to the
+    /// given node. Returns `false` if there's no such pointer or it is already captured. See
+    /// [`Pointer`] docs for more info.
+    #[inline]
+    pub fn capture_pointer(&mut self, node: Handle<UiNode>, id: u64) -> bool {
+        match self.pointers.get_mut(&id) {
+            Some(pointer) if pointer.captured.is_none() => {
+                pointer.captured = node;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Releases the capture of the pointer with the given id.
+    #[inline]
+    pub fn release_pointer_capture(&mut self, id: u64) {
+        if let Some(pointer) = self.pointers.get_mut(&id) {
+            pointer.captured = Handle::NONE;
+        }
+    }
+
+    /// Returns the pointer with the given id, if it is currently touching the screen.
+    #[inline]
+    pub fn pointer(&self, id: u64) -> Option<&Pointer> {
+        self.pointers.get(&id)
+    }
+
+    /// Returns an iterator over every pointer, that is currently touching the screen.
+    #[inline]
+    pub fn pointers(&self) -> impl Iterator<Item = &Pointer> {
+        self.pointers.values()
+    }
+
     #[inline]
     pub fn get_drawing_context(&self) -> &DrawingContext {
         &self.drawing_context
@@ -1154,6 +1205,8 @@ impl UserInterface {
     fn update_visual_transform(&mut self, from: Handle<UiNode>) {
         scope_profile!();
 
+        let scale = Matrix3::new_scaling(self.scale_factor);
+
         self.stack.clear();
         self.stack.push(from);
         while let Some(node_handle) = self.stack.pop() {
@@ -1174,7 +1227,8 @@ impl UserInterface {
                 let visual_transform = if let Some(parent) = parent {
                     parent.visual_transform * widget.render_transform * layout_transform
                 } else {
-                    widget.render_transform * layout_transform
+                    // Global scale is applied to the root widgets.
+                    scale * widget.render_transform * layout_transform
                 };
 
                 widget.visual_transform = visual_transform;
@@ -1190,6 +1244,73 @@ impl UserInterface {
         self.screen_size = screen_size;
     }
 
+    /// Returns the size of the screen in logical units (the coordinate system of the root widgets),
+    /// which is the screen size divided by the scale factor. See [`Self::set_scale_factor`] for more
+    /// info.
+    pub fn logical_screen_size(&self) -> Vector2<f32> {
+        self.screen_size / self.scale_factor
+    }
+
+    /// Sets the global scale factor of the user interface. Every widget is measured and arranged
+    /// in logical units, that are multiplied by the scale factor when drawn, so `2.0` makes every
+    /// widget twice bigger. It could be used to follow DPI of the screen or to make the interface
+    /// more readable for players with low vision. Input events and screen bounds of the widgets are
+    /// still in physical pixels.
+    pub fn set_scale_factor(&mut self, scale_factor: f32) {
+        let scale_factor = scale_factor.max(f32::EPSILON);
+        if self.scale_factor != scale_factor {
+            self.scale_factor = scale_factor;
+            self.invalidate_layout();
+            self.need_update_global_transform = true;
+        }
+    }
+
+    /// Returns the global scale factor of the user interface. See [`Self::set_scale_factor`] for
+    /// more info.
+    pub fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    /// Switches the color theme of the user interface and returns the previous theme. See [`Theme`]
+    /// docs for more info.
+    pub fn set_theme(&mut self, theme: Theme) -> Theme {
+        for node in self.nodes.iter_mut() {
+            theme.apply(&self.theme, node);
+        }
+        std::mem::replace(&mut self.theme, theme)
+    }
+
+    /// Returns current color theme of the user interface.
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Builds a full update of the accessibility tree, that should be passed to an AccessKit
+    /// platform adapter. See [`accessibility`] module docs for more info.
+    pub fn accessibility_tree_update(&self) -> accesskit::TreeUpdate {
+        accessibility::build_tree_update(self)
+    }
+
+    /// Handles an action request from an AccessKit platform adapter. Focus requests are handled
+    /// directly, any other action is sent to the target widget as
+    /// [`WidgetMessage::AccessibilityAction`].
+    pub fn handle_accessibility_action(&mut self, request: accesskit::ActionRequest) {
+        let target = accessibility::widget_handle(request.target);
+        if !self.nodes.is_valid_handle(target) {
+            return;
+        }
+
+        if request.action == accesskit::Action::Focus {
+            self.send_message(WidgetMessage::focus(target, MessageDirection::ToWidget));
+        } else {
+            self.send_message(WidgetMessage::accessibility_action(
+                target,
+                MessageDirection::FromWidget,
+                request.action,
+            ));
+        }
+    }
+
     fn handle_layout_events(&mut self) {
         fn invalidate_recursive_up(
             nodes: &Pool<UiNode, WidgetContainer>,
@@ -1235,10 +1356,11 @@ impl UserInterface {
 
         self.handle_layout_events();
 
-        self.measure_node(self.root_canvas, screen_size);
+        let logical_screen_size = self.logical_screen_size();
+        self.measure_node(self.root_canvas, logical_screen_size);
         let arrangement_changed = self.arrange_node(
             self.root_canvas,
-            &Rect::new(0.0, 0.0, screen_size.x, screen_size.y),
+            &Rect::new(0.0, 0.0, logical_screen_size.x, logical_screen_size.y),
         );
 
         if self.need_update_global_transform {
@@ -1874,7 +1996,7 @@ impl UserInterface {
                                 let parent_size = if parent.is_some() {
                                     self.node(parent).actual_initial_size()
                                 } else {
-                                    self.screen_size
+                                    self.logical_screen_size()
                                 };
 
                                 self.send_message(WidgetMessage::desired_position(
@@ -1898,7 +2020,7 @@ impl UserInterface {
                                 let parent_size = if parent.is_some() {
                                     self.node(parent).actual_initial_size()
                                 } else {
-                                    self.screen_size
+                                    self.logical_screen_size()
                                 };
 
                                 if position.x < 0.0 {
@@ -2151,6 +2273,11 @@ impl UserInterface {
         self.captured_node
     }
 
+    /// Returns a handle of the widget, that is currently under the cursor.
+    pub fn picked_node(&self) -> Handle<UiNode> {
+        self.picked_node
+    }
+
     // Tries to set new picked node (a node under the cursor) and returns `true` if the node was
     // changed.
     fn try_set_picked_node(&mut self, node: Handle<UiNode>) -> bool {
@@ -2190,6 +2317,256 @@ impl UserInterface {
         }
     }
 
+    fn pointer_target(&self, pointer: &Pointer) -> Handle<UiNode> {
+        if self.nodes.is_valid_handle(pointer.captured) {
+            pointer.captured
+        } else if pointer.primary {
+            self.picked_node
+        } else {
+            self.hit_test(pointer.position)
+        }
+    }
+
+    // The primary pointer works the same as the mouse (it changes the picked node, the keyboard
+    // focus, etc.), other pointers only send touch messages to their targets.
+    fn process_pointer_event(
+        &mut self,
+        phase: TouchPhase,
+        location: Vector2<f32>,
+        id: u64,
+        kind: PointerKind,
+        pressure: f32,
+        tilt: Vector2<f32>,
+        force: Option<Force>,
+    ) -> bool {
+        let mut event_processed = false;
+
+        match phase {
+            TouchPhase::Started => {
+                let primary = self.pointers.values().all(|pointer| !pointer.primary);
+                let mut emit_double_tap = false;
+
+                if primary {
+                    self.cursor_position = location;
+                    let picked_changed =
+                        self.try_set_picked_node(self.hit_test(self.cursor_position));
+
+                    if !picked_changed {
+                        match self.double_click_entries.entry(MouseButton::Left) {
+                            Entry::Occupied(e) => {
+                                let entry = e.into_mut();
+                                if entry.timer > 0.0 {
+                                    entry.click_count += 1;
+                                    if entry.click_count >= 2 {
+                                        entry.click_count = 0;
+                                        entry.timer = self.double_click_time_slice;
+                                        emit_double_tap = true;
+                                    }
+                                } else {
+                                    entry.timer = self.double_click_time_slice;
+                                    entry.click_count = 1;
+                                }
+                            }
+                            Entry::Vacant(entry) => {
+                                // A button was clicked for the first time, no double click
+                                // in this case.
+                                entry.insert(DoubleClickEntry {
+                                    timer: self.double_click_time_slice,
+                                    click_count: 1,
+                                });
+                            }
+                        }
+                    }
+
+                    // Try to find draggable node in hierarchy starting from picked node.
+                    if self.picked_node.is_some() {
+                        self.stack.clear();
+                        self.stack.push(self.picked_node);
+                        while let Some(handle) = self.stack.pop() {
+                            let node = &self.nodes[handle];
+                            if node.is_drag_allowed() {
+                                self.drag_context.drag_node = handle;
+                                self.stack.clear();
+                                break;
+                            } else if node.parent().is_some() {
+                                self.stack.push(node.parent());
+                            }
+                        }
+                        self.drag_context.click_pos = self.cursor_position;
+                    }
+
+                    self.request_focus(self.picked_node);
+                }
+
+                let mut pointer = Pointer {
+                    id,
+                    kind,
+                    position: location,
+                    start_position: location,
+                    pressure,
+                    tilt,
+                    target: Handle::NONE,
+                    captured: Handle::NONE,
+                    primary,
+                };
+                pointer.target = self.pointer_target(&pointer);
+                let target = pointer.target;
+                self.pointers.insert(id, pointer);
+
+                if target.is_some() {
+                    self.send_message(WidgetMessage::touch_started(
+                        target,
+                        MessageDirection::FromWidget,
+                        location,
+                        force,
+                        id,
+                    ));
+                    event_processed = true;
+                }
+
+                // Make sure double click will be emitted after mouse down event.
+                if emit_double_tap {
+                    self.send_message(WidgetMessage::double_tap(
+                        self.picked_node,
+                        MessageDirection::FromWidget,
+                        location,
+                        force,
+                        id,
+                    ));
+                }
+            }
+            TouchPhase::Moved => {
+                let Some(mut pointer) = self.pointers.get(&id).cloned() else {
+                    return false;
+                };
+                pointer.position = location;
+                pointer.pressure = pressure;
+                pointer.tilt = tilt;
+
+                if pointer.primary {
+                    self.cursor_position = location;
+                    self.try_set_picked_node(self.hit_test(self.cursor_position));
+
+                    // Try to find draggable node in hierarchy starting from picked node.
+                    if self.picked_node.is_some() {
+                        self.stack.clear();
+                        self.stack.push(self.picked_node);
+                        while let Some(handle) = self.stack.pop() {
+                            let node = &self.nodes[handle];
+                            if node.is_drag_allowed() {
+                                self.drag_context.drag_node = handle;
+                                self.stack.clear();
+                                break;
+                            } else if node.parent().is_some() {
+                                self.stack.push(node.parent());
+                            }
+                        }
+                        self.drag_context.click_pos = self.cursor_position;
+                    }
+
+                    self.request_focus(self.picked_node);
+                }
+
+                pointer.target = self.pointer_target(&pointer);
+                let target = pointer.target;
+                self.pointers.insert(id, pointer);
+
+                if target.is_some() {
+                    self.send_message(WidgetMessage::touch_moved(
+                        target,
+                        MessageDirection::FromWidget,
+                        location,
+                        force,
+                        id,
+                    ));
+                    event_processed = true;
+                }
+            }
+            TouchPhase::Ended => {
+                let Some(mut pointer) = self.pointers.remove(&id) else {
+                    return false;
+                };
+                pointer.position = location;
+
+                let target = self.pointer_target(&pointer);
+                if target.is_some() {
+                    self.send_message(WidgetMessage::touch_ended(
+                        target,
+                        MessageDirection::FromWidget,
+                        location,
+                        id,
+                    ));
+                    event_processed = true;
+                }
+
+                if pointer.primary && self.picked_node.is_some() {
+                    if self.drag_context.is_dragging {
+                        self.drag_context.is_dragging = false;
+
+                        // Try to find node with drop allowed in hierarchy starting from picked node.
+                        self.stack.clear();
+                        self.stack.push(self.picked_node);
+                        while let Some(handle) = self.stack.pop() {
+                            let node = &self.nodes[handle];
+                            if node.is_drop_allowed() {
+                                self.send_message(WidgetMessage::drop(
+                                    handle,
+                                    MessageDirection::FromWidget,
+                                    self.drag_context.drag_node,
+                                ));
+                                self.stack.clear();
+                                break;
+                            } else if node.parent().is_some() {
+                                self.stack.push(node.parent());
+                            }
+                        }
+                    }
+                    self.drag_context.drag_node = Handle::NONE;
+                    if self.nodes.is_valid_handle(self.drag_context.drag_preview) {
+                        self.remove_node(self.drag_context.drag_preview);
+                        self.drag_context.drag_preview = Default::default();
+                    }
+
+                    event_processed = true;
+                }
+            }
+            TouchPhase::Cancelled => {
+                let Some(mut pointer) = self.pointers.remove(&id) else {
+                    return false;
+                };
+                pointer.position = location;
+
+                let target = self.pointer_target(&pointer);
+                if target.is_some() {
+                    self.send_message(WidgetMessage::touch_cancelled(
+                        target,
+                        MessageDirection::FromWidget,
+                        location,
+                        id,
+                    ));
+                    event_processed = true;
+                }
+
+                if pointer.primary && self.picked_node.is_some() {
+                    if self.drag_context.is_dragging {
+                        self.drag_context.is_dragging = false;
+                        self.cursor_icon = CursorIcon::Default;
+                        self.stack.clear();
+                    }
+                    self.drag_context.drag_node = Handle::NONE;
+                    if self.nodes.is_valid_handle(self.drag_context.drag_preview) {
+                        self.remove_node(self.drag_context.drag_preview);
+                        self.drag_context.drag_preview = Default::default();
+                    }
+
+                    event_processed = true;
+                }
+            }
+        }
+
+        event_processed
+    }
+
     /// Translates raw window event into some specific UI message. This is one of the
     /// most important methods of UI. You must call it each time you received a message
     /// from a window.
@@ -2457,178 +2834,38 @@ impl UserInterface {
                 location,
                 force,
                 id,
-            } => match phase {
-                TouchPhase::Started => {
-                    self.cursor_position = *location;
-                    let picked_changed =
-                        self.try_set_picked_node(self.hit_test(self.cursor_position));
-
-                    let mut emit_double_tap = false;
-                    if !picked_changed {
-                        match self.double_click_entries.entry(MouseButton::Left) {
-                            Entry::Occupied(e) => {
-                                let entry = e.into_mut();
-                                if entry.timer > 0.0 {
-                                    entry.click_count += 1;
-                                    if entry.click_count >= 2 {
-                                        entry.click_count = 0;
-                                        entry.timer = self.double_click_time_slice;
-                                        emit_double_tap = true;
-                                    }
-                                } else {
-                                    entry.timer = self.double_click_time_slice;
-                                    entry.click_count = 1;
-                                }
-                            }
-                            Entry::Vacant(entry) => {
-                                // A button was clicked for the first time, no double click
-                                // in this case.
-                                entry.insert(DoubleClickEntry {
-                                    timer: self.double_click_time_slice,
-                                    click_count: 1,
-                                });
-                            }
-                        }
-                    }
-
-                    // Try to find draggable node in hierarchy starting from picked node.
-                    if self.picked_node.is_some() {
-                        self.stack.clear();
-                        self.stack.push(self.picked_node);
-                        while let Some(handle) = self.stack.pop() {
-                            let node = &self.nodes[handle];
-                            if node.is_drag_allowed() {
-                                self.drag_context.drag_node = handle;
-                                self.stack.clear();
-                                break;
-                            } else if node.parent().is_some() {
-                                self.stack.push(node.parent());
-                            }
-                        }
-                        self.drag_context.click_pos = self.cursor_position;
-                    }
-
-                    self.request_focus(self.picked_node);
-
-                    if self.picked_node.is_some() {
-                        self.send_message(WidgetMessage::touch_started(
-                            self.picked_node,
-                            MessageDirection::FromWidget,
-                            self.cursor_position,
-                            *force,
-                            *id,
-                        ));
-                        event_processed = true;
-                    }
-
-                    // Make sure double click will be emitted after mouse down event.
-                    if emit_double_tap {
-                        self.send_message(WidgetMessage::double_tap(
-                            self.picked_node,
-                            MessageDirection::FromWidget,
-                            *location,
-                            *force,
-                            *id,
-                        ));
-                    }
-                }
-                TouchPhase::Moved => {
-                    self.cursor_position = *location;
-                    self.try_set_picked_node(self.hit_test(self.cursor_position));
-
-                    // Try to find draggable node in hierarchy starting from picked node.
-                    if self.picked_node.is_some() {
-                        self.stack.clear();
-                        self.stack.push(self.picked_node);
-                        while let Some(handle) = self.stack.pop() {
-                            let node = &self.nodes[handle];
-                            if node.is_drag_allowed() {
-                                self.drag_context.drag_node = handle;
-                                self.stack.clear();
-                                break;
-                            } else if node.parent().is_some() {
-                                self.stack.push(node.parent());
-                            }
-                        }
-                        self.drag_context.click_pos = self.cursor_position;
-                    }
-
-                    self.request_focus(self.picked_node);
-
-                    if self.picked_node.is_some() {
-                        self.send_message(WidgetMessage::touch_moved(
-                            self.picked_node,
-                            MessageDirection::FromWidget,
-                            self.cursor_position,
-                            *force,
-                            *id,
-                        ));
-                        event_processed = true;
-                    }
-                }
-                TouchPhase::Ended => {
-                    if self.picked_node.is_some() {
-                        self.send_message(WidgetMessage::touch_ended(
-                            self.picked_node,
-                            MessageDirection::FromWidget,
-                            self.cursor_position,
-                            *id,
-                        ));
-
-                        if self.drag_context.is_dragging {
-                            self.drag_context.is_dragging = false;
-
-                            // Try to find node with drop allowed in hierarchy starting from picked node.
-                            self.stack.clear();
-                            self.stack.push(self.picked_node);
-                            while let Some(handle) = self.stack.pop() {
-                                let node = &self.nodes[handle];
-                                if node.is_drop_allowed() {
-                                    self.send_message(WidgetMessage::drop(
-                                        handle,
-                                        MessageDirection::FromWidget,
-                                        self.drag_context.drag_node,
-                                    ));
-                                    self.stack.clear();
-                                    break;
-                                } else if node.parent().is_some() {
-                                    self.stack.push(node.parent());
-                                }
-                            }
-                        }
-                        self.drag_context.drag_node = Handle::NONE;
-                        if self.nodes.is_valid_handle(self.drag_context.drag_preview) {
-                            self.remove_node(self.drag_context.drag_preview);
-                            self.drag_context.drag_preview = Default::default();
-                        }
-
-                        event_processed = true;
-                    }
-                }
-                TouchPhase::Cancelled => {
-                    if self.picked_node.is_some() {
-                        self.send_message(WidgetMessage::touch_cancelled(
-                            self.picked_node,
-                            MessageDirection::FromWidget,
-                            self.cursor_position,
-                            *id,
-                        ));
-
-                        if self.drag_context.is_dragging {
-                            self.drag_context.is_dragging = false;
-                            self.cursor_icon = CursorIcon::Default;
-                            self.stack.clear();
-                        }
-                        self.drag_context.drag_node = Handle::NONE;
-                        if self.nodes.is_valid_handle(self.drag_context.drag_preview) {
-                            self.remove_node(self.drag_context.drag_preview);
-                            self.drag_context.drag_preview = Default::default();
-                        }
-
-                        event_processed = true;
-                    }
-                }
-            },
+            } => {
+                let pressure = force
+                    .as_ref()
+                    .map_or(1.0, |force| force.normalized() as f32);
+                event_processed |= self.process_pointer_event(
+                    *phase,
+                    *location,
+                    *id,
+                    PointerKind::Touch,
+                    pressure,
+                    Vector2::default(),
+                    *force,
+                );
+            }
+            OsEvent::Pen {
+                phase,
+                location,
+                pressure,
+                tilt,
+                id,
+            } => {
+                let force = Force::Normalized((*pressure as f64).to_be_bytes());
+                event_processed |= self.process_pointer_event(
+                    *phase,
+                    *location,
+                    *id,
+                    PointerKind::Pen,
+                    *pressure,
+                    *tilt,
+                    Some(force),
+                );
+            }
         }
 
         self.prev_picked_node = self.picked_node;
@@ -3092,6 +3329,10 @@ impl BaseSceneGraph for UserInterface {
         for child in children {
             self.link_nodes(child, node_handle, false)
         }
+        if self.theme != Theme::default() {
+            self.theme
+                .apply(&Theme::default(), &mut self.nodes[node_handle]);
+        }
         let node = self.nodes[node_handle].deref_mut();
         node.layout_events_sender = Some(self.layout_events_sender.clone());
         node.handle = node_handle;
@@ -3119,6 +3360,14 @@ impl BaseSceneGraph for UserInterface {
             if self.captured_node == handle {
                 self.captured_node = Handle::NONE;
             }
+            for pointer in self.pointers.values_mut() {
+                if pointer.captured == handle {
+                    pointer.captured = Handle::NONE;
+                }
+                if pointer.target == handle {
+                    pointer.target = Handle::NONE;
+                }
+            }
             if self.keyboard_focus_node == handle {
                 self.keyboard_focus_node = Handle::NONE;
             }
@@ -3412,7 +3661,7 @@ impl ResourceData for UserInterface {
 
 #[cfg(test)]
 mod test {
-    use crate::message::{ButtonState, KeyCode};
+    use crate::message::{ButtonState, KeyCode, TouchPhase};
     use crate::{
         border::BorderBuilder,
         core::algebra::{Rotation2, UnitComplex, Vector2},
@@ -3508,4 +3757,114 @@ mod test {
 
         assert!(ui.poll_message().is_none());
     }
+
+    #[test]
+    fn test_multi_touch() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::new(screen_size);
+
+        let make_border = |ui: &mut UserInterface, x: f32| {
+            BorderBuilder::new(
+                WidgetBuilder::new()
+                    .with_desired_position(Vector2::new(x, 0.0))
+                    .with_width(100.0)
+                    .with_height(100.0),
+            )
+            .build(&mut ui.build_ctx())
+        };
+        let left = make_border(&mut ui, 0.0);
+        let right = make_border(&mut ui, 200.0);
+        ui.update(screen_size, 0.0, &Default::default());
+        // Hit test uses drawing commands of the widgets.
+        ui.draw();
+
+        let touch = |ui: &mut UserInterface, phase, x: f32, id| {
+            ui.process_os_event(&OsEvent::Touch {
+                phase,
+                location: Vector2::new(x, 50.0),
+                force: None,
+                id,
+            });
+            let mut destinations = Vec::new();
+            while let Some(message) = ui.poll_message() {
+                if let Some(
+                    WidgetMessage::TouchStarted { id: msg_id, .. }
+                    | WidgetMessage::TouchMoved { id: msg_id, .. }
+                    | WidgetMessage::TouchEnded { id: msg_id, .. },
+                ) = message.data()
+                {
+                    assert_eq!(*msg_id, id);
+                    destinations.push(message.destination());
+                }
+            }
+            destinations
+        };
+
+        // Every finger touches its own widget.
+        assert_eq!(touch(&mut ui, TouchPhase::Started, 50.0, 0), vec![left]);
+        assert_eq!(touch(&mut ui, TouchPhase::Started, 250.0, 1), vec![right]);
+        assert!(ui.pointer(0).unwrap().primary);
+        assert!(!ui.pointer(1).unwrap().primary);
+        assert_eq!(ui.pointers().count(), 2);
+
+        // Captured pointer sends messages to the capturing widget only.
+        assert!(ui.capture_pointer(left, 1));
+        assert_eq!(touch(&mut ui, TouchPhase::Moved, 260.0, 1), vec![left]);
+        assert_eq!(touch(&mut ui, TouchPhase::Moved, 40.0, 0), vec![left]);
+        assert_eq!(touch(&mut ui, TouchPhase::Ended, 260.0, 1), vec![left]);
+        assert!(ui.pointer(1).is_none());
+
+        // The capture is released with the pointer.
+        assert_eq!(touch(&mut ui, TouchPhase::Started, 250.0, 2), vec![right]);
+        assert_eq!(ui.pointer(2).unwrap().captured, Default::default());
+    }
+
+    #[test]
+    fn test_accessibility_tree() {
+        use crate::{
+            accessibility::{node_id, AccessibilityRole},
+            accesskit::Role,
+            button::ButtonBuilder,
+        };
+
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::new(screen_size);
+
+        let button = ButtonBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(20.0))
+            .with_text("Play")
+            .build(&mut ui.build_ctx());
+        let decoration = BorderBuilder::new(WidgetBuilder::new()).build(&mut ui.build_ctx());
+        assert_eq!(
+            ui.node(decoration).accessibility_role(),
+            AccessibilityRole::None
+        );
+
+        ui.update(screen_size, 0.0, &Default::default());
+
+        let update = ui.accessibility_tree_update();
+        let find = |update: &crate::accesskit::TreeUpdate, handle| {
+            update
+                .nodes
+                .iter()
+                .find(|(id, _)| *id == node_id(handle))
+                .map(|(_, node)| node.clone())
+        };
+        let node = find(&update, button).unwrap();
+        assert_eq!(node.role(), Role::Button);
+        assert_eq!(node.name(), Some("Play"));
+        // Text of the button is exposed as a child.
+        assert_eq!(node.children().len(), 1);
+        // Widgets without a role are not exposed.
+        assert!(find(&update, decoration).is_none());
+        let bounds = node.bounds().unwrap();
+        assert_eq!(bounds.x1 - bounds.x0, 100.0);
+
+        // Scale factor affects screen bounds, but not the layout.
+        ui.set_scale_factor(2.0);
+        ui.update(screen_size, 0.0, &Default::default());
+        let node = find(&ui.accessibility_tree_update(), button).unwrap();
+        let bounds = node.bounds().unwrap();
+        assert_eq!(bounds.x1 - bounds.x0, 200.0);
+        assert_eq!(ui.node(button).actual_local_size().x, 100.0);
+    }
 }
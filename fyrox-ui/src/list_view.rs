@@ -4,6 +4,7 @@
 #![warn(missing_docs)]
 
 use crate::{
+    accessibility::AccessibilityRole,
     border::BorderBuilder,
     brush::Brush,
     core::{
@@ -547,6 +548,7 @@ impl ListViewBuilder {
             widget: self
                 .widget_builder
                 .with_accepts_input(true)
+                .with_default_accessibility_role(AccessibilityRole::List)
                 .with_child(back)
                 .build(),
             selected_index: None,
@@ -562,7 +564,10 @@ impl ListViewBuilder {
 
 fn generate_item_container(ctx: &mut BuildContext, item: Handle<UiNode>) -> Handle<UiNode> {
     let item = ListViewItem {
-        widget: WidgetBuilder::new().with_child(item).build(),
+        widget: WidgetBuilder::new()
+            .with_accessibility_role(AccessibilityRole::ListItem)
+            .with_child(item)
+            .build(),
     };
 
     ctx.add_node(UiNode::new(item))
@@ -4,6 +4,7 @@
 #![warn(missing_docs)]
 
 use crate::{
+    accessibility::AccessibilityRole,
     border::BorderBuilder,
     brush::Brush,
     core::{
@@ -764,6 +765,7 @@ impl MenuBuilder {
         let menu = Menu {
             widget: self
                 .widget_builder
+                .with_default_accessibility_role(AccessibilityRole::Menu)
                 .with_handle_os_events(true)
                 .with_child(back)
                 .build(),
@@ -999,6 +1001,7 @@ impl<'a, 'b> MenuItemBuilder<'a, 'b> {
         let menu = MenuItem {
             widget: self
                 .widget_builder
+                .with_default_accessibility_role(AccessibilityRole::MenuItem)
                 .with_handle_os_events(true)
                 .with_preview_messages(true)
                 .with_child(decorator)
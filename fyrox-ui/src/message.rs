@@ -569,6 +569,20 @@ pub enum OsEvent {
         /// Unique touch event identifier to distinguish between fingers, for example
         id: u64,
     },
+    /// Pen (stylus) event.
+    Pen {
+        /// Phase of the pen event
+        phase: TouchPhase,
+        /// Screen location of the tip of the pen
+        location: Vector2<f32>,
+        /// Pressure of the pen in `[0; 1]` range
+        pressure: f32,
+        /// Tilt of the pen (in radians) in XZ and YZ planes, where Z axis is perpendicular to the
+        /// screen
+        tilt: Vector2<f32>,
+        /// Unique identifier of the pen
+        id: u64,
+    },
 }
 
 /// A set of possible keyboard modifiers.
@@ -0,0 +1,60 @@
+//! Pointers are fingers and pens, that are currently touching the screen. See [`Pointer`] docs for
+//! more info.
+
+use crate::{
+    core::{algebra::Vector2, pool::Handle, reflect::prelude::*, visitor::prelude::*},
+    UiNode,
+};
+
+/// Kind of a pointer device.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Visit, Reflect)]
+pub enum PointerKind {
+    /// A finger on a touch screen.
+    #[default]
+    Touch,
+    /// A pen (stylus) on a touch screen or a graphics tablet.
+    Pen,
+}
+
+/// State of a pointer (finger or pen), that is currently touching the screen. The user interface
+/// tracks every pointer separately, so multiple widgets could be touched at the same time. Touch
+/// messages (see [`crate::widget::WidgetMessage::TouchStarted`] and others) contain the id of the
+/// pointer, use [`crate::UserInterface::pointer`] to get its full state (for example, to get the
+/// pressure and the tilt of a pen in a drawing app).
+///
+/// # Primary pointer
+///
+/// The first pointer, that touched the screen when there were no other pointers, is primary. It
+/// works the same as the mouse: it changes the picked node and the keyboard focus, and it can drag
+/// and drop widgets. Other pointers only send touch messages to the widgets under them.
+///
+/// # Capture
+///
+/// A widget could capture a pointer (see [`crate::UserInterface::capture_pointer`]), so every
+/// message of the pointer will be sent to the widget regardless of the position of the pointer.
+/// This is useful for sliders, joysticks, drawing canvases, etc. The capture is released
+/// automatically, when the pointer is lifted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pointer {
+    /// Unique id of the pointer, it is the same as in the touch messages.
+    pub id: u64,
+    /// Kind of the pointer device.
+    pub kind: PointerKind,
+    /// Current position of the pointer in screen coordinates.
+    pub position: Vector2<f32>,
+    /// Position, where the pointer has touched the screen.
+    pub start_position: Vector2<f32>,
+    /// Pressure of the pointer in `[0; 1]` range. It is `1.0` for devices, that does not support
+    /// pressure.
+    pub pressure: f32,
+    /// Tilt of the pen (in radians) in XZ and YZ planes, where Z axis is perpendicular to the
+    /// screen. Zero tilt means that the pen is perpendicular to the screen. It is always zero for
+    /// fingers.
+    pub tilt: Vector2<f32>,
+    /// A widget, that received the last message of the pointer.
+    pub target: Handle<UiNode>,
+    /// A widget, that has captured the pointer.
+    pub captured: Handle<UiNode>,
+    /// `true` if the pointer is primary. See [`Pointer`] docs for more info.
+    pub primary: bool,
+}
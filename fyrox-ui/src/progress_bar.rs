@@ -4,6 +4,7 @@
 #![warn(missing_docs)]
 
 use crate::{
+    accessibility::AccessibilityRole,
     border::BorderBuilder,
     brush::Brush,
     canvas::CanvasBuilder,
@@ -87,6 +88,12 @@ crate::define_widget_deref!(ProgressBar);
 uuid_provider!(ProgressBar = "d6ebb853-d945-46bc-86db-4c8b5d5faf8e");
 
 impl Control for ProgressBar {
+    fn accessibility_node(&self, _ui: &UserInterface, node: &mut accesskit::NodeBuilder) {
+        node.set_numeric_value(*self.progress as f64);
+        node.set_min_numeric_value(0.0);
+        node.set_max_numeric_value(1.0);
+    }
+
     fn arrange_override(&self, ui: &UserInterface, final_size: Vector2<f32>) -> Vector2<f32> {
         let size = self.widget.arrange_override(ui, final_size);
 
@@ -183,7 +190,11 @@ impl ProgressBarBuilder {
         ctx.link(canvas, body);
 
         let progress_bar = ProgressBar {
-            widget: self.widget_builder.with_child(body).build(),
+            widget: self
+                .widget_builder
+                .with_default_accessibility_role(AccessibilityRole::ProgressBar)
+                .with_child(body)
+                .build(),
             progress: self.progress.into(),
             indicator: indicator.into(),
             body: body.into(),
@@ -104,26 +104,31 @@ uuid_provider!(Screen = "3bc7649f-a1ba-49be-bc4e-e0624654e40c");
 impl Control for Screen {
     fn measure_override(&self, ui: &UserInterface, _available_size: Vector2<f32>) -> Vector2<f32> {
         for &child in self.children.iter() {
-            ui.measure_node(child, ui.screen_size());
+            ui.measure_node(child, ui.logical_screen_size());
         }
 
-        ui.screen_size()
+        ui.logical_screen_size()
     }
 
     fn arrange_override(&self, ui: &UserInterface, _final_size: Vector2<f32>) -> Vector2<f32> {
-        let final_rect = Rect::new(0.0, 0.0, ui.screen_size().x, ui.screen_size().y);
+        let final_rect = Rect::new(
+            0.0,
+            0.0,
+            ui.logical_screen_size().x,
+            ui.logical_screen_size().y,
+        );
 
         for &child in self.children.iter() {
             ui.arrange_node(child, &final_rect);
         }
 
-        ui.screen_size()
+        ui.logical_screen_size()
     }
 
     fn update(&mut self, _dt: f32, ui: &mut UserInterface) {
-        if self.last_screen_size.get() != ui.screen_size {
+        if self.last_screen_size.get() != ui.logical_screen_size() {
             self.invalidate_layout();
-            self.last_screen_size.set(ui.screen_size);
+            self.last_screen_size.set(ui.logical_screen_size());
         }
     }
 
@@ -5,6 +5,7 @@
 
 use crate::font::FontResource;
 use crate::{
+    accessibility::AccessibilityRole,
     border::BorderBuilder,
     brush::Brush,
     button::{ButtonBuilder, ButtonMessage},
@@ -164,6 +165,19 @@ crate::define_widget_deref!(ScrollBar);
 uuid_provider!(ScrollBar = "92accc96-b334-424d-97ea-332c4787acf6");
 
 impl Control for ScrollBar {
+    fn accessibility_node(&self, _ui: &UserInterface, node: &mut accesskit::NodeBuilder) {
+        node.set_numeric_value(*self.value as f64);
+        node.set_min_numeric_value(*self.min as f64);
+        node.set_max_numeric_value(*self.max as f64);
+        node.set_numeric_value_step(*self.step as f64);
+        node.set_orientation(match *self.orientation {
+            Orientation::Horizontal => accesskit::Orientation::Horizontal,
+            Orientation::Vertical => accesskit::Orientation::Vertical,
+        });
+        node.add_action(accesskit::Action::Increment);
+        node.add_action(accesskit::Action::Decrement);
+    }
+
     fn arrange_override(&self, ui: &UserInterface, final_size: Vector2<f32>) -> Vector2<f32> {
         let size = self.widget.arrange_override(ui, final_size);
 
@@ -236,7 +250,23 @@ impl Control for ScrollBar {
     fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
         self.widget.handle_routed_message(ui, message);
 
-        if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+        if let Some(WidgetMessage::AccessibilityAction(action)) = message.data::<WidgetMessage>() {
+            if message.destination() == self.handle() {
+                let delta = match action {
+                    accesskit::Action::Increment => *self.step,
+                    accesskit::Action::Decrement => -*self.step,
+                    _ => 0.0,
+                };
+                if delta != 0.0 {
+                    ui.send_message(ScrollBarMessage::value(
+                        self.handle(),
+                        MessageDirection::ToWidget,
+                        *self.value + delta,
+                    ));
+                    message.set_handled(true);
+                }
+            }
+        } else if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
             if message.destination() == *self.increase {
                 ui.send_message(ScrollBarMessage::value(
                     self.handle(),
@@ -552,13 +582,16 @@ impl ScrollBarBuilder {
         let orientation = self.orientation.unwrap_or(Orientation::Horizontal);
 
         let increase = self.increase.unwrap_or_else(|| {
-            ButtonBuilder::new(WidgetBuilder::new())
-                .with_content(match orientation {
-                    Orientation::Horizontal => make_arrow(ctx, ArrowDirection::Right, 8.0),
-                    Orientation::Vertical => make_arrow(ctx, ArrowDirection::Bottom, 8.0),
-                })
-                .with_repeat_clicks_on_hold(true)
-                .build(ctx)
+            // The scroll bar itself supports increment and decrement actions.
+            ButtonBuilder::new(
+                WidgetBuilder::new().with_accessibility_role(AccessibilityRole::None),
+            )
+            .with_content(match orientation {
+                Orientation::Horizontal => make_arrow(ctx, ArrowDirection::Right, 8.0),
+                Orientation::Vertical => make_arrow(ctx, ArrowDirection::Bottom, 8.0),
+            })
+            .with_repeat_clicks_on_hold(true)
+            .build(ctx)
         });
 
         match orientation {
@@ -571,13 +604,16 @@ impl ScrollBarBuilder {
         }
 
         let decrease = self.decrease.unwrap_or_else(|| {
-            ButtonBuilder::new(WidgetBuilder::new())
-                .with_content(match orientation {
-                    Orientation::Horizontal => make_arrow(ctx, ArrowDirection::Left, 8.0),
-                    Orientation::Vertical => make_arrow(ctx, ArrowDirection::Top, 8.0),
-                })
-                .with_repeat_clicks_on_hold(true)
-                .build(ctx)
+            // The scroll bar itself supports increment and decrement actions.
+            ButtonBuilder::new(
+                WidgetBuilder::new().with_accessibility_role(AccessibilityRole::None),
+            )
+            .with_content(match orientation {
+                Orientation::Horizontal => make_arrow(ctx, ArrowDirection::Left, 8.0),
+                Orientation::Vertical => make_arrow(ctx, ArrowDirection::Top, 8.0),
+            })
+            .with_repeat_clicks_on_hold(true)
+            .build(ctx)
         });
 
         ctx[decrease].set_row(0).set_column(0);
@@ -682,7 +718,11 @@ impl ScrollBarBuilder {
         ctx.link(grid, body);
 
         let node = UiNode::new(ScrollBar {
-            widget: self.widget_builder.with_child(body).build(),
+            widget: self
+                .widget_builder
+                .with_default_accessibility_role(AccessibilityRole::ScrollBar)
+                .with_child(body)
+                .build(),
             min: min.into(),
             max: max.into(),
             value: value.into(),
@@ -4,6 +4,7 @@
 #![warn(missing_docs)]
 
 use crate::{
+    accessibility::AccessibilityRole,
     brush::Brush,
     core::{
         algebra::Vector2, color::Color, pool::Handle, reflect::prelude::*, type_traits::prelude::*,
@@ -316,6 +317,10 @@ crate::define_widget_deref!(Text);
 uuid_provider!(Text = "22f7f502-7622-4ecb-8c5f-ba436e7ee823");
 
 impl Control for Text {
+    fn accessibility_node(&self, _ui: &UserInterface, node: &mut accesskit::NodeBuilder) {
+        node.set_name(self.text());
+    }
+
     fn measure_override(&self, _: &UserInterface, available_size: Vector2<f32>) -> Vector2<f32> {
         self.formatted_text
             .borrow_mut()
@@ -556,7 +561,10 @@ impl TextBuilder {
         }
 
         let text = Text {
-            widget: self.widget_builder.build(),
+            widget: self
+                .widget_builder
+                .with_default_accessibility_role(AccessibilityRole::Label)
+                .build(),
             formatted_text: RefCell::new(
                 FormattedTextBuilder::new(font)
                     .with_text(self.text.unwrap_or_default())
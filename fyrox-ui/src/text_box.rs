@@ -4,6 +4,7 @@
 #![warn(missing_docs)]
 
 use crate::{
+    accessibility::AccessibilityRole,
     brush::Brush,
     core::{
         algebra::{Point2, Vector2},
@@ -906,6 +907,13 @@ impl TextBox {
 uuid_provider!(TextBox = "536276f2-a175-4c05-a376-5a7d8bf0d10b");
 
 impl Control for TextBox {
+    fn accessibility_node(&self, _ui: &UserInterface, node: &mut accesskit::NodeBuilder) {
+        node.set_value(self.text());
+        if !*self.editable {
+            node.set_read_only();
+        }
+    }
+
     fn measure_override(&self, _: &UserInterface, available_size: Vector2<f32>) -> Vector2<f32> {
         self.formatted_text
             .borrow_mut()
@@ -1609,6 +1617,7 @@ impl TextBoxBuilder {
                 .widget_builder
                 .with_accepts_input(true)
                 .with_need_update(true)
+                .with_default_accessibility_role(AccessibilityRole::TextInput)
                 .build(),
             caret_position: Position::default().into(),
             caret_visible: false.into(),
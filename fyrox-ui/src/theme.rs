@@ -0,0 +1,146 @@
+//! Color themes of the user interface. See [`Theme`] docs for more info.
+
+use crate::{
+    brush::Brush,
+    core::{color::Color, reflect::prelude::*, visitor::prelude::*},
+    font::FontResource,
+    UiNode, COLOR_BRIGHT, COLOR_BRIGHTEST, COLOR_BRIGHT_BLUE, COLOR_COAL_BLACK, COLOR_DARK,
+    COLOR_DARKER, COLOR_DARKEST, COLOR_DIM_BLUE, COLOR_FOREGROUND, COLOR_LIGHT, COLOR_LIGHTER,
+    COLOR_LIGHTEST, COLOR_PRIMARY, COLOR_TEXT,
+};
+use fyrox_resource::untyped::UntypedResource;
+use std::any::TypeId;
+
+/// A set of colors, that is used by the standard widgets. The standard widgets are built with the
+/// colors of the default (dark) theme (see `BRUSH_*` constants), a theme replaces them at runtime.
+/// Use [`crate::UserInterface::set_theme`] to switch the theme, for example to
+/// [`Theme::high_contrast`] for players with low vision.
+///
+/// Every solid brush of every widget, that has a color of the current theme, is replaced with the
+/// corresponding color of the new theme. Widgets, that are added later, are converted
+/// automatically. Colors of a theme should be unique, otherwise switching back to the previous
+/// theme won't be able to restore the original colors.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct Theme {
+    /// The darkest background color.
+    pub coal_black: Color,
+    /// Darkest color.
+    pub darkest: Color,
+    /// Darker color.
+    pub darker: Color,
+    /// Dark color.
+    pub dark: Color,
+    /// Primary (default background) color.
+    pub primary: Color,
+    /// Light color.
+    pub light: Color,
+    /// Lighter color.
+    pub lighter: Color,
+    /// Lightest color.
+    pub lightest: Color,
+    /// Bright color.
+    pub bright: Color,
+    /// Brightest color.
+    pub brightest: Color,
+    /// Accent (selection) color.
+    pub bright_blue: Color,
+    /// Dimmed accent color.
+    pub dim_blue: Color,
+    /// Text color.
+    pub text: Color,
+    /// Default foreground color.
+    pub foreground: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    /// The default theme, its colors match `COLOR_*` constants.
+    pub fn dark() -> Self {
+        Self {
+            coal_black: COLOR_COAL_BLACK,
+            darkest: COLOR_DARKEST,
+            darker: COLOR_DARKER,
+            dark: COLOR_DARK,
+            primary: COLOR_PRIMARY,
+            light: COLOR_LIGHT,
+            lighter: COLOR_LIGHTER,
+            lightest: COLOR_LIGHTEST,
+            bright: COLOR_BRIGHT,
+            brightest: COLOR_BRIGHTEST,
+            bright_blue: COLOR_BRIGHT_BLUE,
+            dim_blue: COLOR_DIM_BLUE,
+            text: COLOR_TEXT,
+            foreground: COLOR_FOREGROUND,
+        }
+    }
+
+    /// High contrast theme - black backgrounds, white text and yellow accents.
+    pub fn high_contrast() -> Self {
+        Self {
+            coal_black: Color::opaque(0, 0, 0),
+            darkest: Color::opaque(1, 1, 1),
+            darker: Color::opaque(2, 2, 2),
+            dark: Color::opaque(3, 3, 3),
+            primary: Color::opaque(4, 4, 4),
+            light: Color::opaque(60, 60, 60),
+            lighter: Color::opaque(90, 90, 90),
+            lightest: Color::opaque(120, 120, 120),
+            bright: Color::opaque(245, 245, 245),
+            brightest: Color::opaque(240, 240, 240),
+            bright_blue: Color::opaque(255, 230, 0),
+            dim_blue: Color::opaque(200, 180, 0),
+            text: Color::opaque(250, 250, 250),
+            // The same as in the default theme, so the tint of images is not affected.
+            foreground: Color::WHITE,
+        }
+    }
+
+    fn colors(&self) -> [Color; 14] {
+        [
+            self.coal_black,
+            self.darkest,
+            self.darker,
+            self.dark,
+            self.primary,
+            self.light,
+            self.lighter,
+            self.lightest,
+            self.bright,
+            self.brightest,
+            self.bright_blue,
+            self.dim_blue,
+            self.text,
+            self.foreground,
+        ]
+    }
+
+    /// Replaces every solid brush of the widget, that has a color of `from` theme, with the
+    /// corresponding color of this theme.
+    pub(crate) fn apply(&self, from: &Theme, node: &mut UiNode) {
+        let from = from.colors();
+        let to = self.colors();
+        let ignored_types = [
+            TypeId::of::<UntypedResource>(),
+            TypeId::of::<FontResource>(),
+        ];
+        node.as_reflect_mut(&mut |node| {
+            node.apply_recursively_mut(
+                &mut |value| {
+                    value.downcast_mut::<Brush>(&mut |brush| {
+                        if let Some(Brush::Solid(color)) = brush {
+                            if let Some(index) = from.iter().position(|c| *c == *color) {
+                                *color = to[index];
+                            }
+                        }
+                    })
+                },
+                &ignored_types,
+            )
+        });
+    }
+}
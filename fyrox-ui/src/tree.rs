@@ -5,6 +5,7 @@
 
 use crate::message::KeyCode;
 use crate::{
+    accessibility::AccessibilityRole,
     border::BorderBuilder,
     brush::Brush,
     check_box::{CheckBoxBuilder, CheckBoxMessage},
@@ -636,6 +637,7 @@ impl TreeBuilder {
         Tree {
             widget: self
                 .widget_builder
+                .with_default_accessibility_role(AccessibilityRole::TreeItem)
                 .with_allow_drag(true)
                 .with_allow_drop(true)
                 .with_child(grid)
@@ -1001,7 +1003,11 @@ impl TreeRootBuilder {
                 .build(ctx);
 
         let tree = TreeRoot {
-            widget: self.widget_builder.with_child(panel).build(),
+            widget: self
+                .widget_builder
+                .with_default_accessibility_role(AccessibilityRole::Tree)
+                .with_child(panel)
+                .build(),
             panel,
             items: self.items,
             selected: Default::default(),
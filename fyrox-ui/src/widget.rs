@@ -4,6 +4,7 @@
 #![warn(missing_docs)]
 
 use crate::{
+    accessibility::AccessibilityRole,
     brush::Brush,
     core::{
         algebra::{Matrix3, Point2, Vector2},
@@ -374,6 +375,18 @@ pub enum WidgetMessage {
         /// unique identifier for touch event
         id: u64,
     },
+
+    /// A request to set new accessibility label for a widget. See [`crate::accessibility`] module
+    /// docs for more info.
+    ///
+    /// Direction: **From/To UI**
+    AccessibilityLabel(String),
+
+    /// An assistive technology (for example, a screen reader) has requested an action. Focus action
+    /// is handled by the user interface itself, every other action should be handled by the widget.
+    ///
+    /// Direction: **From UI**.
+    AccessibilityAction(accesskit::Action),
 }
 
 impl WidgetMessage {
@@ -660,6 +673,17 @@ impl WidgetMessage {
         /// be used anywhere else.
         WidgetMessage:DoubleTap => fn double_tap(pos: Vector2<f32>, force: Option<Force>, id: u64), layout: false
     );
+
+    define_constructor!(
+        /// Creates [`WidgetMessage::AccessibilityLabel`] message.
+        WidgetMessage:AccessibilityLabel => fn accessibility_label(String), layout: false
+    );
+
+    define_constructor!(
+        /// Creates [`WidgetMessage::AccessibilityAction`] message. This method is for internal use only, and should not
+        /// be used anywhere else.
+        WidgetMessage:AccessibilityAction => fn accessibility_action(accesskit::Action), layout: false
+    );
 }
 
 /// Widget is a base UI element, that is always used to build derived, more complex, widgets. In general, it is a container
@@ -798,6 +822,13 @@ pub struct Widget {
     /// on its own. Default value is `false`.
     #[visit(optional)]
     pub accepts_input: bool,
+    /// Role of the widget for assistive technologies. See [`crate::accessibility`] module docs for more info.
+    #[visit(optional)]
+    pub accessibility_role: InheritableVariable<AccessibilityRole>,
+    /// A text, that describes the widget for assistive technologies. If it is empty, the text of the descendant
+    /// text widgets is used for buttons, check boxes, etc.
+    #[visit(optional)]
+    pub accessibility_label: InheritableVariable<String>,
     /// Internal sender for layout events.
     #[reflect(hidden)]
     #[visit(skip)]
@@ -1385,6 +1416,10 @@ impl Widget {
                     &WidgetMessage::Cursor(icon) => {
                         self.cursor.set_value_and_mark_modified(icon);
                     }
+                    WidgetMessage::AccessibilityLabel(label) => {
+                        self.accessibility_label
+                            .set_value_and_mark_modified(label.clone());
+                    }
                     WidgetMessage::LayoutTransform(transform) => {
                         if &self.layout_transform != transform {
                             self.layout_transform = *transform;
@@ -1619,6 +1654,18 @@ impl Widget {
         *self.cursor
     }
 
+    /// Returns the role of the widget for assistive technologies.
+    #[inline]
+    pub fn accessibility_role(&self) -> AccessibilityRole {
+        *self.accessibility_role
+    }
+
+    /// Returns a text, that describes the widget for assistive technologies.
+    #[inline]
+    pub fn accessibility_label(&self) -> &str {
+        &self.accessibility_label
+    }
+
     /// Tries to fetch user-defined data of the specified type `T`.
     #[inline]
     pub fn user_data_cloned<T: Clone + 'static>(&self) -> Option<T> {
@@ -1785,6 +1832,10 @@ pub struct WidgetBuilder {
     pub tab_stop: bool,
     /// A flag, that indicates that the widget accepts user input.
     pub accepts_input: bool,
+    /// Role of the widget for assistive technologies.
+    pub accessibility_role: Option<AccessibilityRole>,
+    /// A text, that describes the widget for assistive technologies.
+    pub accessibility_label: String,
 }
 
 impl Default for WidgetBuilder {
@@ -1834,6 +1885,8 @@ impl WidgetBuilder {
             tab_index: None,
             tab_stop: false,
             accepts_input: false,
+            accessibility_role: None,
+            accessibility_label: Default::default(),
         }
     }
 
@@ -2090,6 +2143,25 @@ impl WidgetBuilder {
         self
     }
 
+    /// Sets the role of the widget for assistive technologies. See [`crate::accessibility`] module docs for more info.
+    pub fn with_accessibility_role(mut self, role: AccessibilityRole) -> Self {
+        self.accessibility_role = Some(role);
+        self
+    }
+
+    /// Sets the role of the widget for assistive technologies, if it wasn't set explicitly. It is used by the builders
+    /// of the standard widgets, so the role could still be overridden by users.
+    pub fn with_default_accessibility_role(mut self, role: AccessibilityRole) -> Self {
+        self.accessibility_role.get_or_insert(role);
+        self
+    }
+
+    /// Sets a text, that describes the widget for assistive technologies.
+    pub fn with_accessibility_label<S: AsRef<str>>(mut self, label: S) -> Self {
+        self.accessibility_label = label.as_ref().to_owned();
+        self
+    }
+
     /// Finishes building of the base widget.
     pub fn build(self) -> Widget {
         Widget {
@@ -2150,6 +2222,8 @@ impl WidgetBuilder {
             need_update: self.need_update,
             ignore_layout_rounding: false,
             accepts_input: self.accepts_input,
+            accessibility_role: self.accessibility_role.unwrap_or_default().into(),
+            accessibility_label: self.accessibility_label.into(),
             layout_events_sender: None,
             layout_transform: self.layout_transform,
             render_transform: self.render_transform,
@@ -3,6 +3,7 @@
 
 use crate::font::FontResource;
 use crate::{
+    accessibility::AccessibilityRole,
     border::BorderBuilder,
     brush::Brush,
     button::{ButtonBuilder, ButtonMessage},
@@ -471,10 +472,12 @@ impl Control for Window {
                                     GripKind::LeftBottomCorner => (-1.0, 0.0, 1.0, -1.0),
                                 };
 
+                                // Position is in screen space, but the size is in logical units.
+                                let local_delta = delta / ui.scale_factor();
                                 let new_pos = self.initial_position
                                     + Vector2::new(delta.x * dx, delta.y * dy);
-                                let new_size =
-                                    self.initial_size + Vector2::new(delta.x * dw, delta.y * dh);
+                                let new_size = self.initial_size
+                                    + Vector2::new(local_delta.x * dw, local_delta.y * dh);
 
                                 if new_size.x > self.min_width()
                                     && new_size.x < self.max_width()
@@ -754,7 +757,8 @@ impl Control for Window {
                                 current_size.y,
                             ))
                             .unwrap_or_else(|| {
-                                Rect::new(0.0, 0.0, ui.screen_size.x, ui.screen_size.y)
+                                let screen_size = ui.logical_screen_size();
+                                Rect::new(0.0, 0.0, screen_size.x, screen_size.y)
                             });
 
                         ui.send_message(WidgetMessage::desired_position(
@@ -809,11 +813,11 @@ impl Control for Window {
                             // application window, thus leaving an opportunity to drag window to some other place.
                             new_pos.x = new_pos.x.clamp(
                                 -(self.actual_local_size().x - safe_border.x).abs(),
-                                (ui.screen_size().x - safe_border.x).abs(),
+                                (ui.logical_screen_size().x - safe_border.x).abs(),
                             );
                             new_pos.y = new_pos
                                 .y
-                                .clamp(0.0, (ui.screen_size().y - safe_border.y).abs());
+                                .clamp(0.0, (ui.logical_screen_size().y - safe_border.y).abs());
                         }
 
                         if self.is_dragging && self.desired_local_position() != new_pos {
@@ -1354,6 +1358,7 @@ impl WindowBuilder {
             widget: self
                 .widget_builder
                 .with_visibility(self.open)
+                .with_default_accessibility_role(AccessibilityRole::Window)
                 .with_child(
                     BorderBuilder::new(
                         WidgetBuilder::new()